@@ -622,6 +622,7 @@ const fileBrowserCommandSelectors = [
 
 const appUpdateCommandSelectors = [
   "check_for_updates",
+  "get_last_update_result",
   "download_update",
   "close_update_window",
   "dismiss_update_notification",
@@ -647,6 +648,9 @@ const systemSupportCommandSelectors = [
   "show_notification",
   "auto_fix_configuration",
   "report_frontend_crash",
+  "list_crash_reports",
+  "export_crash_report",
+  "clear_crash_reports",
 ].map((command) => ({
   selector: `CallExpression[callee.name='safeInvoke'][arguments.0.value='${command}'], CallExpression[callee.name='invoke'][arguments.0.value='${command}']`,
   message:
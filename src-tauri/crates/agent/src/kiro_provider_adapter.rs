@@ -263,6 +263,21 @@ impl LimeKiroProvider {
             lime_providers::providers::ProviderError::RequestError(details) => {
                 ProviderError::RequestFailed(details)
             }
+            lime_providers::providers::ProviderError::ConversionError(err) => {
+                ProviderError::ExecutionError(err.to_string())
+            }
+            lime_providers::providers::ProviderError::HttpError { status, message } => {
+                let details = format!("HTTP {status} - {message}");
+                match status {
+                    401 | 403 => ProviderError::Authentication(details),
+                    429 => ProviderError::RateLimitExceeded {
+                        details,
+                        retry_delay: None,
+                    },
+                    500..=599 => ProviderError::ServerError(details),
+                    _ => ProviderError::RequestFailed(details),
+                }
+            }
             lime_providers::providers::ProviderError::ParseError(details)
             | lime_providers::providers::ProviderError::ConfigurationError(details)
             | lime_providers::providers::ProviderError::Unknown(details)
@@ -11,7 +11,10 @@ use super::observers::{
 };
 use super::subject::ConfigSubject;
 use super::traits::ConfigObserver;
-use lime_core::config::{Config, EndpointProvidersConfig, HotReloadManager, ReloadResult};
+use lime_core::config::{
+    record_config_change, save_config_with_source, Config, ConfigAuditSource,
+    EndpointProvidersConfig, HotReloadManager, ReloadResult,
+};
 use lime_core::router::{ModelMapper, Router};
 use lime_infra::Injector;
 use std::path::PathBuf;
@@ -129,6 +132,11 @@ impl GlobalConfigManager {
                     let hot_reload = self.hot_reload.read();
                     hot_reload.config()
                 };
+                record_config_change(
+                    ConfigAuditSource::HotReload,
+                    &self.subject.config(),
+                    &new_config,
+                );
 
                 self.subject
                     .update_config(new_config, ConfigChangeSource::HotReload)
@@ -148,7 +156,17 @@ impl GlobalConfigManager {
 
     /// 保存配置到文件并通知观察者
     pub async fn save_config(&self, config: &Config) -> Result<(), String> {
-        lime_core::config::save_config(config).map_err(|e| e.to_string())?;
+        self.save_config_from(config, ConfigAuditSource::Command)
+            .await
+    }
+
+    /// 保存配置到文件并通知观察者，按 `audit_source` 记录配置审计
+    pub async fn save_config_from(
+        &self,
+        config: &Config,
+        audit_source: ConfigAuditSource,
+    ) -> Result<(), String> {
+        save_config_with_source(config, audit_source).map_err(|e| e.to_string())?;
         self.update_config(config.clone(), ConfigChangeSource::ApiCall)
             .await;
         Ok(())
@@ -0,0 +1,328 @@
+//! 匿名使用统计
+//!
+//! 独立于请求日志与遥测的子系统，默认关闭，由 `anonymous_stats.enabled` 总开关控制：
+//! - 只在本地内存中累计粗粒度计数：端点类别 × 结果类别（成功、客户端错误、认证错误、限流、服务端错误），
+//!   不记录模型、Key、凭证、IP、请求内容或精确时间
+//! - 上传前必须先生成预览（[`preview`]），上传的正是预览中的内容；预览对每个计数加入
+//!   拉普拉斯噪声（差分隐私，参数 ε 越小噪声越大），统计周期只精确到日期
+//! - 关闭总开关时立即停止统计并清空已累计的数据与待上传的预览
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use chrono::{NaiveDate, Utc};
+use parking_lot::Mutex;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AnonymousStatsConfig;
+
+/// 上传内容的格式版本
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// 端点类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EndpointClass {
+    ChatCompletions,
+    Messages,
+    Responses,
+    Embeddings,
+    Images,
+    Audio,
+    Gemini,
+    Models,
+    Other,
+}
+
+impl EndpointClass {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ChatCompletions => "chat_completions",
+            Self::Messages => "messages",
+            Self::Responses => "responses",
+            Self::Embeddings => "embeddings",
+            Self::Images => "images",
+            Self::Audio => "audio",
+            Self::Gemini => "gemini",
+            Self::Models => "models",
+            Self::Other => "other",
+        }
+    }
+
+    /// 按请求路径归类（只看路径形态，不保留路径本身）
+    pub fn from_path(path: &str) -> Self {
+        if path.ends_with("/chat/completions") {
+            Self::ChatCompletions
+        } else if path.ends_with("/messages") || path.ends_with("/messages/count_tokens") {
+            Self::Messages
+        } else if path.contains("/v1/responses") {
+            Self::Responses
+        } else if path.ends_with("/embeddings") {
+            Self::Embeddings
+        } else if path.contains("/images/") {
+            Self::Images
+        } else if path.contains("/audio/") {
+            Self::Audio
+        } else if path.starts_with("/v1beta/") || path.contains(":generateContent") {
+            Self::Gemini
+        } else if path == "/v1/models" {
+            Self::Models
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// 结果类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OutcomeClass {
+    Success,
+    ClientError,
+    AuthError,
+    RateLimited,
+    ServerError,
+}
+
+impl OutcomeClass {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::ClientError => "client_error",
+            Self::AuthError => "auth_error",
+            Self::RateLimited => "rate_limited",
+            Self::ServerError => "server_error",
+        }
+    }
+
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            401 | 403 => Self::AuthError,
+            429 => Self::RateLimited,
+            400..=499 => Self::ClientError,
+            500..=599 => Self::ServerError,
+            _ => Self::Success,
+        }
+    }
+}
+
+type Counters = BTreeMap<(EndpointClass, OutcomeClass), u64>;
+
+/// 待上传的统计报告（预览内容即上传内容）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageReport {
+    pub schema_version: u32,
+    /// 统计周期（日期）
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    /// 应用版本（只保留主次版本号）
+    pub app_version: String,
+    /// 操作系统类型
+    pub platform: String,
+    /// 差分隐私参数 ε
+    pub epsilon: f64,
+    /// 端点类别 -> 结果类别 -> 加噪后的计数
+    pub counters: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+struct Pending {
+    report: UsageReport,
+    /// 生成预览时的原始计数，上传成功后从累计值中扣除
+    snapshot: Counters,
+}
+
+#[derive(Default)]
+struct State {
+    counters: Counters,
+    period_start: Option<NaiveDate>,
+    pending: Option<Pending>,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+fn state() -> &'static Mutex<State> {
+    static STATE: OnceLock<Mutex<State>> = OnceLock::new();
+    STATE.get_or_init(|| Mutex::new(State::default()))
+}
+
+/// 应用配置；关闭时清空已累计的数据与预览
+pub fn configure(config: &AnonymousStatsConfig) {
+    ENABLED.store(config.enabled, Ordering::Relaxed);
+    if !config.enabled {
+        clear();
+    }
+}
+
+/// 是否启用
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// 记录一次请求（未启用时不做任何事）
+pub fn record(path: &str, status: u16) {
+    if !is_enabled() {
+        return;
+    }
+    let key = (
+        EndpointClass::from_path(path),
+        OutcomeClass::from_status(status),
+    );
+    let mut state = state().lock();
+    state
+        .period_start
+        .get_or_insert_with(|| Utc::now().date_naive());
+    *state.counters.entry(key).or_insert(0) += 1;
+}
+
+/// 清空已累计的数据与预览
+pub fn clear() {
+    *state().lock() = State::default();
+}
+
+/// 拉普拉斯噪声（尺度 `scale`）
+fn laplace(rng: &mut impl Rng, scale: f64) -> f64 {
+    let u: f64 = rng.gen_range(-0.5..0.5);
+    -scale * u.signum() * (1.0 - 2.0 * u.abs()).max(f64::MIN_POSITIVE).ln()
+}
+
+/// 对计数加噪（每个请求只影响一个计数，敏感度为 1）
+fn noisy_counters(
+    counters: &Counters,
+    epsilon: f64,
+    rng: &mut impl Rng,
+) -> BTreeMap<String, BTreeMap<String, u64>> {
+    let scale = 1.0 / epsilon.max(0.01);
+    let mut output: BTreeMap<String, BTreeMap<String, u64>> = BTreeMap::new();
+    for ((endpoint, outcome), count) in counters {
+        let noisy = (*count as f64 + laplace(rng, scale)).round().max(0.0) as u64;
+        output
+            .entry(endpoint.as_str().to_string())
+            .or_default()
+            .insert(outcome.as_str().to_string(), noisy);
+    }
+    output
+}
+
+/// 生成（或返回已生成的）待上传报告；未启用或没有数据时为 `None`
+///
+/// 同一份预览在上传或清空之前保持不变，上传的内容与用户看到的完全一致。
+pub fn preview(epsilon: f64, app_version: &str) -> Option<UsageReport> {
+    if !is_enabled() {
+        return None;
+    }
+    let mut state = state().lock();
+    if let Some(pending) = &state.pending {
+        return Some(pending.report.clone());
+    }
+    if state.counters.is_empty() {
+        return None;
+    }
+    let today = Utc::now().date_naive();
+    let report = UsageReport {
+        schema_version: SCHEMA_VERSION,
+        period_start: state.period_start.unwrap_or(today),
+        period_end: today,
+        app_version: app_version.split('.').take(2).collect::<Vec<_>>().join("."),
+        platform: std::env::consts::OS.to_string(),
+        epsilon,
+        counters: noisy_counters(&state.counters, epsilon, &mut rand::thread_rng()),
+    };
+    state.pending = Some(Pending {
+        report: report.clone(),
+        snapshot: state.counters.clone(),
+    });
+    Some(report)
+}
+
+/// 取出已预览的报告用于上传（未启用或尚未预览时为 `None`）
+pub fn pending_report() -> Option<UsageReport> {
+    if !is_enabled() {
+        return None;
+    }
+    state()
+        .lock()
+        .pending
+        .as_ref()
+        .map(|pending| pending.report.clone())
+}
+
+/// 上传成功后调用：从累计值中扣除已上传的部分，开始新的统计周期
+pub fn commit_upload() {
+    let mut state = state().lock();
+    let Some(pending) = state.pending.take() else {
+        return;
+    };
+    for (key, count) in pending.snapshot {
+        if let Some(current) = state.counters.get_mut(&key) {
+            *current = current.saturating_sub(count);
+        }
+    }
+    state.counters.retain(|_, count| *count > 0);
+    state.period_start = (!state.counters.is_empty()).then(|| Utc::now().date_naive());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_classification() {
+        assert_eq!(
+            EndpointClass::from_path("/v1/chat/completions"),
+            EndpointClass::ChatCompletions
+        );
+        assert_eq!(
+            EndpointClass::from_path("/claude/v1/messages"),
+            EndpointClass::Messages
+        );
+        assert_eq!(
+            EndpointClass::from_path("/v1beta/models/gemini-2.5-pro:generateContent"),
+            EndpointClass::Gemini
+        );
+        assert_eq!(
+            EndpointClass::from_path("/admin/keys"),
+            EndpointClass::Other
+        );
+        assert_eq!(OutcomeClass::from_status(200), OutcomeClass::Success);
+        assert_eq!(OutcomeClass::from_status(403), OutcomeClass::AuthError);
+        assert_eq!(OutcomeClass::from_status(429), OutcomeClass::RateLimited);
+        assert_eq!(OutcomeClass::from_status(404), OutcomeClass::ClientError);
+        assert_eq!(OutcomeClass::from_status(503), OutcomeClass::ServerError);
+    }
+
+    #[test]
+    fn test_noise_is_bounded_and_preview_is_stable() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut counters = Counters::new();
+        counters.insert((EndpointClass::Messages, OutcomeClass::Success), 1000);
+        let samples: Vec<u64> = (0..200)
+            .map(|_| noisy_counters(&counters, 1.0, &mut rng)["messages"]["success"])
+            .collect();
+        let mean = samples.iter().sum::<u64>() as f64 / samples.len() as f64;
+        assert!((mean - 1000.0).abs() < 1.0);
+        assert!(samples.iter().any(|count| *count != 1000));
+
+        // 关闭总开关后不统计、不预览
+        configure(&AnonymousStatsConfig::default());
+        record("/v1/chat/completions", 200);
+        assert!(preview(1.0, "0.96.0").is_none());
+
+        configure(&AnonymousStatsConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        record("/v1/chat/completions", 200);
+        record("/v1/chat/completions", 429);
+        let report = preview(1.0, "0.96.0").unwrap();
+        assert_eq!(report.app_version, "0.96");
+        assert_eq!(preview(1.0, "0.96.0"), Some(report.clone()));
+        assert_eq!(pending_report(), Some(report));
+
+        commit_upload();
+        assert!(pending_report().is_none());
+        assert!(preview(1.0, "0.96.0").is_none());
+        configure(&AnonymousStatsConfig::default());
+    }
+}
@@ -64,5 +64,9 @@ pub fn load_and_validate_config() -> Result<Config, ConfigError> {
         return Err(ConfigError::RemoteManagementNotSupported);
     }
 
+    for warning in config::lint_config(&config) {
+        tracing::warn!("[CONFIG_LINT] {}", warning);
+    }
+
     Ok(config)
 }
@@ -0,0 +1,429 @@
+//! 类型化应用事件通道
+//!
+//! 后端向前端推送结构化事件（凭证池、配置、用量、服务器状态），
+//! 统一通过 [`APP_EVENT_CHANNEL`] 发送带版本号的信封，前端无需解析日志文本。
+//!
+//! 事件通过全局 [`app_event_bus`] 发布；未注入发射器时（如测试、CLI）静默丢弃。
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{OnceLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::event_emit::DynEmitter;
+
+/// 应用事件通道名称
+pub const APP_EVENT_CHANNEL: &str = "lime://app-event";
+
+/// 事件协议版本（事件结构出现不兼容变更时递增）
+pub const APP_EVENT_API_VERSION: u32 = 1;
+
+/// Tauri 命令 API 版本（命令签名出现不兼容变更时递增）
+pub const COMMAND_API_VERSION: u32 = 1;
+
+/// 凭证池事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PoolEvent {
+    /// 凭证健康状态更新
+    CredentialHealthChanged {
+        uuid: String,
+        healthy: bool,
+        error_count: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    /// 凭证被使用
+    CredentialUsed { uuid: String, usage_count: u64 },
+    /// 整个 Provider 被暂停/恢复
+    ProviderPaused {
+        provider_type: String,
+        paused: bool,
+        affected: usize,
+    },
+}
+
+/// 配置事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConfigEvent {
+    /// 配置热重载完成
+    Reloaded {
+        success: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    /// 已切换到指定配置方案
+    ProfileApplied { name: String },
+}
+
+/// 服务器事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerEvent {
+    Started {
+        host: String,
+        port: u16,
+    },
+    Stopped,
+    /// 配置端口被占用，已回退到其他端口
+    PortFallback {
+        requested_port: u16,
+        port: u16,
+    },
+    /// 已接管另一个 Lime 实例的端口
+    TookOver {
+        pid: u32,
+        port: u16,
+    },
+    /// 来源 IP 认证失败次数过多，已被临时锁定
+    AuthLockout {
+        ip: String,
+        failures: u32,
+        lockout_secs: u64,
+    },
+    /// 数据库不可用时进入/退出内存降级凭证池模式
+    DegradedMode {
+        active: bool,
+        /// 降级池中的凭证数
+        credentials: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    /// 受限 Key 按轮换策略生成后继 Key、进入宽限期或宽限期结束
+    KeyRotation {
+        key_id: String,
+        label: String,
+        stage: KeyRotationStage,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        successor_id: Option<String>,
+        /// 旧 Key 失效时间（RFC 3339）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expires_at: Option<String>,
+    },
+}
+
+/// Key 轮换阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyRotationStage {
+    /// 已提前生成后继 Key，旧 Key 仍正常可用
+    SuccessorPrepared,
+    /// 旧 Key 达到轮换条件，进入宽限期
+    Rotated,
+    /// 宽限期结束，旧 Key 已删除
+    Retired,
+}
+
+/// 用量快照（按时间窗口聚合的增量）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageTick {
+    /// 聚合窗口（秒）
+    pub window_secs: u64,
+    pub requests: u64,
+    pub failed: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// 模型请求占比
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelShare {
+    pub model: String,
+    pub requests: u64,
+    pub tokens: u64,
+    /// 占窗口内请求数的比例（0-1）
+    pub share: f64,
+}
+
+/// 凭证请求占比
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CredentialShare {
+    pub credential_id: String,
+    pub requests: u64,
+    /// 占窗口内请求数的比例（0-1）
+    pub share: f64,
+}
+
+/// 本地推理服务（Ollama、llama.cpp 等）的资源占用
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LocalProviderResources {
+    pub provider_id: String,
+    pub name: String,
+    /// 服务类型：`ollama`、`llama_cpp` 或 `openai_compatible`
+    pub kind: String,
+    /// 服务接口是否可访问
+    pub reachable: bool,
+    /// 服务进程的 CPU 占用（百分比，多核时可超过 100）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_percent: Option<f32>,
+    /// 服务进程的常驻内存（字节）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_bytes: Option<u64>,
+    /// 已加载模型占用的显存（字节）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vram_bytes: Option<u64>,
+    /// 当前加载在内存中的模型
+    #[serde(default)]
+    pub loaded_models: Vec<String>,
+    /// 正在处理的请求数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_processing: Option<u64>,
+    /// 排队等待的请求数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_deferred: Option<u64>,
+    /// KV 缓存使用率（0-1）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kv_cache_usage: Option<f64>,
+}
+
+/// GPU 占用
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GpuUsage {
+    pub index: u32,
+    pub name: String,
+    pub memory_used_bytes: u64,
+    pub memory_total_bytes: u64,
+    /// GPU 利用率（百分比）
+    pub utilization_percent: f32,
+}
+
+/// 预热模型状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarmModelState {
+    /// 尚未完成首次预热
+    #[default]
+    Pending,
+    /// 已加载，保活正常
+    Ready,
+    /// 预热或保活失败
+    Failed,
+    /// 找不到对应的本地 Provider（未启用或不在本机）
+    Unavailable,
+}
+
+/// 预热模型的状态
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WarmModelStatus {
+    /// 配置中的 Provider ID 或名称
+    pub provider: String,
+    pub model: String,
+    pub state: WarmModelState,
+    /// 最近一次成功预热 / 保活的时间（毫秒时间戳）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_warmed_at_ms: Option<i64>,
+    /// 最近一次请求耗时（毫秒），首次预热的耗时即冷启动时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 滚动统计快照（由后端定期预计算，前端无需拉取原始日志）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatsSnapshot {
+    /// 计算速率使用的短窗口（秒）
+    pub rate_window_secs: u64,
+    /// 短窗口内的平均每分钟请求数
+    pub requests_per_min: f64,
+    /// 统计窗口（秒），以下字段均按该窗口计算
+    pub window_secs: u64,
+    pub requests: u64,
+    pub failed: u64,
+    /// 失败率（0-1），失败与超时计为失败
+    pub error_rate: f64,
+    /// 窗口内的 Token 总数（窗口为一小时，即每小时 Token 数）
+    pub tokens_per_hour: u64,
+    /// 请求数最多的模型
+    pub top_models: Vec<ModelShare>,
+    /// 各凭证承担的请求占比
+    pub credential_share: Vec<CredentialShare>,
+    /// 已启用的本地推理服务资源占用（未配置本地服务时为空）
+    #[serde(default)]
+    pub local_providers: Vec<LocalProviderResources>,
+    /// 本机 GPU 占用（仅配置了本地服务且可读取时提供）
+    #[serde(default)]
+    pub gpus: Vec<GpuUsage>,
+    /// 本地模型预热状态（未启用预热时为空）
+    #[serde(default)]
+    pub warm_models: Vec<WarmModelStatus>,
+}
+
+/// 应用事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "category", content = "payload", rename_all = "snake_case")]
+pub enum AppEvent {
+    Pool(PoolEvent),
+    Config(ConfigEvent),
+    Usage(UsageTick),
+    Server(ServerEvent),
+    Stats(StatsSnapshot),
+}
+
+/// 事件信封
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppEventEnvelope {
+    /// 事件协议版本
+    pub version: u32,
+    /// 单调递增序号（前端可据此检测丢失）
+    pub seq: u64,
+    /// 发送时间（毫秒时间戳）
+    pub timestamp_ms: i64,
+    #[serde(flatten)]
+    pub event: AppEvent,
+}
+
+/// 事件总线
+pub struct AppEventBus {
+    emitter: RwLock<Option<DynEmitter>>,
+    seq: AtomicU64,
+    requests: AtomicU64,
+    failed: AtomicU64,
+    input_tokens: AtomicU64,
+    output_tokens: AtomicU64,
+}
+
+impl AppEventBus {
+    fn new() -> Self {
+        Self {
+            emitter: RwLock::new(None),
+            seq: AtomicU64::new(0),
+            requests: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            input_tokens: AtomicU64::new(0),
+            output_tokens: AtomicU64::new(0),
+        }
+    }
+
+    /// 注入事件发射器（应用启动时由主 crate 调用）
+    pub fn set_emitter(&self, emitter: DynEmitter) {
+        if let Ok(mut guard) = self.emitter.write() {
+            *guard = Some(emitter);
+        }
+    }
+
+    /// 构建事件信封
+    pub fn envelope(&self, event: AppEvent) -> AppEventEnvelope {
+        AppEventEnvelope {
+            version: APP_EVENT_API_VERSION,
+            seq: self.seq.fetch_add(1, Ordering::Relaxed) + 1,
+            timestamp_ms: chrono::Utc::now().timestamp_millis(),
+            event,
+        }
+    }
+
+    /// 发布事件
+    pub fn publish(&self, event: AppEvent) {
+        let emitter = match self.emitter.read() {
+            Ok(guard) => guard.clone(),
+            Err(_) => None,
+        };
+        let Some(emitter) = emitter else {
+            return;
+        };
+
+        let envelope = self.envelope(event);
+        match serde_json::to_value(&envelope) {
+            Ok(payload) => {
+                if let Err(e) = emitter.emit_event(APP_EVENT_CHANNEL, &payload) {
+                    tracing::debug!("[AppEvent] 发送事件失败: {}", e);
+                }
+            }
+            Err(e) => tracing::warn!("[AppEvent] 序列化事件失败: {}", e),
+        }
+    }
+
+    /// 累计一次请求（由用量快照定期汇总发送）
+    pub fn record_request(&self, success: bool) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if !success {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 累计 Token 用量
+    pub fn record_tokens(&self, input_tokens: u64, output_tokens: u64) {
+        self.input_tokens.fetch_add(input_tokens, Ordering::Relaxed);
+        self.output_tokens
+            .fetch_add(output_tokens, Ordering::Relaxed);
+    }
+
+    /// 取出当前窗口的累计值并重置
+    pub fn take_usage_tick(&self, window_secs: u64) -> UsageTick {
+        UsageTick {
+            window_secs,
+            requests: self.requests.swap(0, Ordering::Relaxed),
+            failed: self.failed.swap(0, Ordering::Relaxed),
+            input_tokens: self.input_tokens.swap(0, Ordering::Relaxed),
+            output_tokens: self.output_tokens.swap(0, Ordering::Relaxed),
+        }
+    }
+
+    /// 发送用量快照（窗口内无请求时跳过）
+    pub fn flush_usage_tick(&self, window_secs: u64) {
+        let tick = self.take_usage_tick(window_secs);
+        if tick.requests > 0 || tick.input_tokens > 0 || tick.output_tokens > 0 {
+            self.publish(AppEvent::Usage(tick));
+        }
+    }
+}
+
+static APP_EVENT_BUS: OnceLock<AppEventBus> = OnceLock::new();
+
+/// 获取全局事件总线
+pub fn app_event_bus() -> &'static AppEventBus {
+    APP_EVENT_BUS.get_or_init(AppEventBus::new)
+}
+
+/// 发布事件到全局事件总线
+pub fn publish_app_event(event: AppEvent) {
+    app_event_bus().publish(event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn envelope_should_serialize_with_category_and_type_tags() {
+        let bus = AppEventBus::new();
+        let envelope = bus.envelope(AppEvent::Pool(PoolEvent::CredentialHealthChanged {
+            uuid: "u1".to_string(),
+            healthy: false,
+            error_count: 3,
+            message: Some("401".to_string()),
+        }));
+
+        let value = serde_json::to_value(&envelope).expect("序列化应成功");
+        assert_eq!(value["version"], APP_EVENT_API_VERSION);
+        assert_eq!(value["seq"], 1);
+        assert_eq!(value["category"], "pool");
+        assert_eq!(value["payload"]["type"], "credential_health_changed");
+        assert_eq!(value["payload"]["error_count"], 3);
+
+        let decoded: AppEventEnvelope = serde_json::from_value(value).expect("反序列化应成功");
+        assert_eq!(decoded, envelope);
+    }
+
+    #[test]
+    fn usage_tick_should_reset_after_take() {
+        let bus = AppEventBus::new();
+        bus.record_request(true);
+        bus.record_request(false);
+        bus.record_tokens(10, 20);
+
+        let tick = bus.take_usage_tick(5);
+        assert_eq!(
+            tick,
+            UsageTick {
+                window_secs: 5,
+                requests: 2,
+                failed: 1,
+                input_tokens: 10,
+                output_tokens: 20,
+            }
+        );
+        assert_eq!(bus.take_usage_tick(5).requests, 0);
+    }
+}
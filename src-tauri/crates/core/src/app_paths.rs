@@ -70,6 +70,10 @@ pub fn resolve_request_logs_dir() -> Result<PathBuf, String> {
     resolve_runtime_subdir("request_logs")
 }
 
+pub fn resolve_media_dir() -> Result<PathBuf, String> {
+    resolve_runtime_subdir("media")
+}
+
 pub fn resolve_projects_dir() -> Result<PathBuf, String> {
     resolve_runtime_subdir("projects")
 }
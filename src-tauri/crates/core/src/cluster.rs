@@ -0,0 +1,101 @@
+//! 多实例选主
+//!
+//! 多个实例共享凭证池存储（Redis / PostgreSQL）时，通过存储中的租约选出一个主实例，
+//! 仅由它执行后台 Token 刷新、健康探测与定时任务，避免多个实例同时刷新同一个
+//! refresh token 而互相使对方失效。本地 SQLite 存储只有单个实例，始终为主。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::database::pool_storage::pool_storage;
+
+/// 后台任务租约名
+const LEADER_LEASE: &str = "background-tasks";
+
+static IS_LEADER: AtomicBool = AtomicBool::new(false);
+
+/// 当前进程的实例标识（租约持有者）
+pub fn instance_id() -> &'static str {
+    static ID: OnceLock<String> = OnceLock::new();
+    ID.get_or_init(|| {
+        let host = std::env::var("HOSTNAME")
+            .or_else(|_| std::env::var("COMPUTERNAME"))
+            .unwrap_or_else(|_| "lime".to_string());
+        format!("{host}-{}", uuid::Uuid::new_v4().simple())
+    })
+}
+
+/// 当前实例是否负责执行后台任务
+pub fn is_leader() -> bool {
+    !pool_storage().is_shared() || IS_LEADER.load(Ordering::Relaxed)
+}
+
+fn set_leader(leader: bool) {
+    if IS_LEADER.swap(leader, Ordering::Relaxed) != leader {
+        if leader {
+            tracing::info!("[集群] 实例 {} 成为主实例，接管后台任务", instance_id());
+        } else {
+            tracing::warn!("[集群] 实例 {} 失去主实例身份，暂停后台任务", instance_id());
+        }
+    }
+}
+
+/// 获取或续期一次租约，存储不可用时视为失去主实例身份
+async fn renew_once(ttl: Duration) {
+    let acquired = tokio::task::spawn_blocking(move || {
+        pool_storage().try_acquire_lease(LEADER_LEASE, instance_id(), ttl)
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result);
+    match acquired {
+        Ok(leader) => set_leader(leader),
+        Err(e) => {
+            tracing::warn!("[集群] 续期主实例租约失败: {}", e);
+            set_leader(false);
+        }
+    }
+}
+
+/// 选主循环：每隔租约有效期的三分之一续期一次
+pub async fn run_leader_election(lease_ttl: Duration) {
+    if !pool_storage().is_shared() {
+        tracing::debug!("[集群] 使用本地存储，无需选主");
+        return;
+    }
+    let lease_ttl = lease_ttl.max(Duration::from_secs(3));
+    tracing::info!(
+        "[集群] 实例 {} 参与选主，租约 {} 秒",
+        instance_id(),
+        lease_ttl.as_secs()
+    );
+    loop {
+        renew_once(lease_ttl).await;
+        tokio::time::sleep(lease_ttl / 3).await;
+    }
+}
+
+/// 退出前释放租约，让其他实例尽快接管
+pub fn release_leadership() {
+    if !IS_LEADER.swap(false, Ordering::Relaxed) {
+        return;
+    }
+    if let Err(e) = pool_storage().release_lease(LEADER_LEASE, instance_id()) {
+        tracing::warn!("[集群] 释放主实例租约失败: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_storage_is_always_leader() {
+        assert!(!pool_storage().is_shared());
+        assert!(is_leader());
+        assert!(pool_storage()
+            .try_acquire_lease(LEADER_LEASE, instance_id(), Duration::from_secs(30))
+            .unwrap());
+    }
+}
@@ -0,0 +1,296 @@
+//! 配置变更审计
+//!
+//! 每次写入配置文件（界面保存、命令修改、导入配置方案）以及文件热重载时，
+//! 对比变更前后的配置树，生成字段级变更列表交给应用注册的审计落地函数（持久化到数据库）。
+//! 密钥类字段（按字段名识别，并包含钥匙串管理的全部密钥字段）只记录“已修改”，不记录明文。
+
+use std::collections::{BTreeSet, HashSet};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::secrets::secret_field_paths;
+use super::types::Config;
+
+/// 密钥类字段的占位值
+const REDACTED: &str = "***";
+
+/// 摘要中最多列出的字段数
+const SUMMARY_MAX_PATHS: usize = 3;
+
+/// 变更来源
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigAuditSource {
+    /// 界面设置页保存
+    Ui,
+    /// 其他 Tauri 命令 / 内部调用写入
+    Command,
+    /// 导入或切换配置方案
+    Import,
+    /// 配置文件被外部修改后热重载
+    HotReload,
+    /// 外部自动化通过远程控制接口触发
+    RemoteControl,
+}
+
+impl ConfigAuditSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ui => "ui",
+            Self::Command => "command",
+            Self::Import => "import",
+            Self::HotReload => "hot_reload",
+            Self::RemoteControl => "remote_control",
+        }
+    }
+}
+
+/// 单个字段的变更（`before` / `after` 为 `None` 表示字段不存在）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConfigFieldChange {
+    /// 以 `.` 分隔的字段路径，如 `routing.rules`
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+}
+
+/// 一次配置变更
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConfigAuditRecord {
+    pub source: ConfigAuditSource,
+    /// 变更摘要，如 `server.port、routing.rules`
+    pub summary: String,
+    pub changes: Vec<ConfigFieldChange>,
+}
+
+type AuditSink = Box<dyn Fn(ConfigAuditRecord) + Send + Sync>;
+
+static AUDIT_SINK: OnceLock<AuditSink> = OnceLock::new();
+
+/// 注册审计落地函数（进程内只生效一次）
+///
+/// 落地函数可能在持有其他锁时被调用，实现中不应阻塞或获取数据库锁，宜转交后台线程写入。
+pub fn install_config_audit_sink(sink: impl Fn(ConfigAuditRecord) + Send + Sync + 'static) {
+    if AUDIT_SINK.set(Box::new(sink)).is_err() {
+        tracing::warn!("[CONFIG_AUDIT] 审计落地函数已注册，忽略重复注册");
+    }
+}
+
+/// 记录两份配置之间的变更（无变更时不记录）
+pub fn record_config_change(source: ConfigAuditSource, before: &Config, after: &Config) {
+    let (Ok(before), Ok(after)) = (serde_json::to_value(before), serde_json::to_value(after))
+    else {
+        return;
+    };
+    record_config_value_change(source, &before, &after);
+}
+
+/// 记录两棵配置树之间的变更（无变更时不记录）
+pub fn record_config_value_change(source: ConfigAuditSource, before: &Value, after: &Value) {
+    let changes = diff_config_values(before, after);
+    if changes.is_empty() {
+        return;
+    }
+    let record = ConfigAuditRecord {
+        source,
+        summary: summarize_changes(&changes),
+        changes,
+    };
+    tracing::info!(
+        "[CONFIG_AUDIT] 配置变更: source={} fields={}",
+        source.as_str(),
+        record.summary
+    );
+    if let Some(sink) = AUDIT_SINK.get() {
+        sink(record);
+    }
+}
+
+/// 对比两棵配置树，返回叶子级变更（数组整体视为一个字段）
+pub fn diff_config_values(before: &Value, after: &Value) -> Vec<ConfigFieldChange> {
+    let mut secret_paths = HashSet::new();
+    for value in [before, after] {
+        if let Ok(config) = serde_json::from_value::<Config>(value.clone()) {
+            secret_paths.extend(secret_field_paths(&config));
+        }
+    }
+    let mut changes = Vec::new();
+    diff_into("", before, after, &secret_paths, &mut changes);
+    changes
+}
+
+fn child_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+fn diff_into(
+    path: &str,
+    before: &Value,
+    after: &Value,
+    secret_paths: &HashSet<String>,
+    out: &mut Vec<ConfigFieldChange>,
+) {
+    if before == after {
+        return;
+    }
+    if let (Value::Object(before), Value::Object(after)) = (before, after) {
+        let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+        for key in keys {
+            diff_into(
+                &child_path(path, key),
+                before.get(key).unwrap_or(&Value::Null),
+                after.get(key).unwrap_or(&Value::Null),
+                secret_paths,
+                out,
+            );
+        }
+        return;
+    }
+
+    let present =
+        |value: &Value| (!value.is_null()).then(|| redact_secrets(path, value, secret_paths));
+    out.push(ConfigFieldChange {
+        path: path.to_string(),
+        before: present(before),
+        after: present(after),
+    });
+}
+
+/// 字段名是否为密钥类（按 `_` 分词匹配，避免误伤 `max_tokens` 之类的字段）
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    key.split(['_', '-']).any(|word| {
+        matches!(
+            word,
+            "key"
+                | "secret"
+                | "password"
+                | "passphrase"
+                | "token"
+                | "authtoken"
+                | "pin"
+                | "sha256"
+                | "cookie"
+        )
+    })
+}
+
+fn is_secret_path(path: &str, secret_paths: &HashSet<String>) -> bool {
+    secret_paths.contains(path) || path.rsplit('.').next().is_some_and(is_secret_key)
+}
+
+fn redact_secrets(path: &str, value: &Value, secret_paths: &HashSet<String>) -> Value {
+    if is_secret_path(path, secret_paths) {
+        return Value::String(REDACTED.to_string());
+    }
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let value = if value.is_null() {
+                        Value::Null
+                    } else {
+                        redact_secrets(&child_path(path, key), value, secret_paths)
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| redact_secrets(path, item, secret_paths))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn summarize_changes(changes: &[ConfigFieldChange]) -> String {
+    let paths: Vec<&str> = changes
+        .iter()
+        .take(SUMMARY_MAX_PATHS)
+        .map(|change| change.path.as_str())
+        .collect();
+    let mut summary = paths.join("、");
+    if changes.len() > SUMMARY_MAX_PATHS {
+        summary.push_str(&format!(" 等 {} 项", changes.len()));
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_reports_leaf_paths_and_redacts_secrets() {
+        let before = json!({
+            "server": {"port": 8999, "api_key": "old-key"},
+            "routing": {"rules": [{"pattern": "claude-*", "provider": "kiro"}]},
+            "retry": {"max_retries": 3}
+        });
+        let after = json!({
+            "server": {"port": 9000, "api_key": "new-key"},
+            "routing": {"rules": [{"pattern": "claude-*", "provider": "claude"}]},
+            "retry": {"max_retries": 3},
+            "pairing": {"enabled": true}
+        });
+
+        let changes = diff_config_values(&before, &after);
+        let paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["pairing", "routing.rules", "server.api_key", "server.port"]
+        );
+
+        assert_eq!(changes[0].before, None);
+        assert_eq!(changes[2].before, Some(json!("***")));
+        assert_eq!(changes[2].after, Some(json!("***")));
+        assert_eq!(changes[3].after, Some(json!(9000)));
+        assert_eq!(
+            summarize_changes(&changes),
+            "pairing、routing.rules、server.api_key 等 4 项"
+        );
+    }
+
+    #[test]
+    fn test_nested_secrets_redacted_in_arrays() {
+        let before = json!({"providers": {"entries": []}});
+        let after =
+            json!({"providers": {"entries": [{"name": "a", "api_key": "sk-1", "max_tokens": 10}]}});
+
+        let changes = diff_config_values(&before, &after);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(
+            changes[0].after,
+            Some(json!([{"name": "a", "api_key": "***", "max_tokens": 10}]))
+        );
+    }
+
+    #[test]
+    fn test_keychain_managed_fields_redacted() {
+        let before = serde_json::to_value(Config::default()).unwrap();
+        let mut after = before.clone();
+        after["cloud_backup"]["passphrase"] = json!("correct horse");
+        after["providers"]["upstream_auth"] =
+            json!({"openai": {"headers": {"x-tenant": "tenant-secret"}}});
+
+        let changes = diff_config_values(&before, &after);
+        let rendered = serde_json::to_string(&changes).unwrap();
+        assert!(!rendered.contains("correct horse"));
+        assert!(!rendered.contains("tenant-secret"));
+        assert!(changes
+            .iter()
+            .any(|c| c.path == "providers.upstream_auth" && c.after.is_some()));
+    }
+}
@@ -0,0 +1,213 @@
+//! 加密备份包
+//!
+//! 云备份的文件格式：`LIMEBK1` 魔数 + 盐（16 字节）+ Nonce（12 字节）+ 密文。
+//! 明文为 ZIP 包，包含 `manifest.json`、`config.yaml`（密钥已还原为明文）、可选的 `lime.db`
+//! 与 `field.key`（数据库字段加密密钥，恢复到其他设备时用于解密快照中的凭证）。
+//! 加密密钥由口令经 PBKDF2-HMAC-SHA256 派生，使用 ChaCha20-Poly1305 加密。
+
+use std::io::{Cursor, Read, Write};
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+/// 备份文件魔数
+const BACKUP_MAGIC: &[u8] = b"LIMEBK1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+/// PBKDF2 迭代次数
+const KDF_ITERATIONS: u32 = 200_000;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const CONFIG_ENTRY: &str = "config.yaml";
+const DATABASE_ENTRY: &str = "lime.db";
+const FIELD_KEY_ENTRY: &str = "field.key";
+
+/// 备份包内容
+#[derive(Debug, Clone)]
+pub struct BackupArchive {
+    pub created_at: DateTime<Utc>,
+    /// 配置 YAML（密钥为明文，依赖整体加密保护）
+    pub config_yaml: String,
+    /// 数据库快照（SQLite 文件）
+    pub database: Option<Vec<u8>>,
+    /// 快照中凭证字段的加密密钥（Base64）
+    pub field_key: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupManifest {
+    format: u32,
+    app_version: String,
+    created_at: DateTime<Utc>,
+    has_database: bool,
+}
+
+impl BackupArchive {
+    /// 打包并加密
+    pub fn seal(&self, passphrase: &str) -> Result<Vec<u8>, String> {
+        if passphrase.is_empty() {
+            return Err("未设置备份加密口令".to_string());
+        }
+        let plain = self.to_zip().map_err(|e| format!("打包备份失败: {e}"))?;
+
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let key = derive_key(passphrase, &salt);
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+        let sealed = cipher
+            .encrypt(Nonce::from_slice(&nonce), plain.as_slice())
+            .map_err(|_| "加密备份失败".to_string())?;
+
+        let mut out = Vec::with_capacity(BACKUP_MAGIC.len() + SALT_LEN + NONCE_LEN + sealed.len());
+        out.extend_from_slice(BACKUP_MAGIC);
+        out.extend_from_slice(&salt);
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&sealed);
+        Ok(out)
+    }
+
+    /// 解密并解包
+    pub fn open(bytes: &[u8], passphrase: &str) -> Result<Self, String> {
+        let header_len = BACKUP_MAGIC.len() + SALT_LEN + NONCE_LEN;
+        if bytes.len() < header_len || !bytes.starts_with(BACKUP_MAGIC) {
+            return Err("不是有效的 Lime 备份文件".to_string());
+        }
+        let salt = &bytes[BACKUP_MAGIC.len()..BACKUP_MAGIC.len() + SALT_LEN];
+        let nonce = &bytes[BACKUP_MAGIC.len() + SALT_LEN..header_len];
+        let key = derive_key(passphrase, salt);
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+        let plain = cipher
+            .decrypt(Nonce::from_slice(nonce), &bytes[header_len..])
+            .map_err(|_| "解密备份失败：口令错误或文件已损坏".to_string())?;
+        Self::from_zip(&plain).map_err(|e| format!("解包备份失败: {e}"))
+    }
+
+    fn to_zip(&self) -> zip::result::ZipResult<Vec<u8>> {
+        let manifest = BackupManifest {
+            format: 1,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: self.created_at,
+            has_database: self.database.is_some(),
+        };
+        let options =
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file(MANIFEST_ENTRY, options)?;
+        zip.write_all(&serde_json::to_vec_pretty(&manifest).unwrap_or_default())?;
+        zip.start_file(CONFIG_ENTRY, options)?;
+        zip.write_all(self.config_yaml.as_bytes())?;
+        if let Some(database) = &self.database {
+            zip.start_file(DATABASE_ENTRY, options)?;
+            zip.write_all(database)?;
+        }
+        if let Some(field_key) = &self.field_key {
+            zip.start_file(FIELD_KEY_ENTRY, options)?;
+            zip.write_all(field_key.as_bytes())?;
+        }
+        Ok(zip.finish()?.into_inner())
+    }
+
+    fn from_zip(bytes: &[u8]) -> Result<Self, String> {
+        let mut zip = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| e.to_string())?;
+        let mut read_entry = |name: &str| -> Result<Option<Vec<u8>>, String> {
+            let mut file = match zip.by_name(name) {
+                Ok(file) => file,
+                Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+                Err(e) => return Err(e.to_string()),
+            };
+            let mut buf = Vec::new();
+            file.read_to_end(&mut buf).map_err(|e| e.to_string())?;
+            Ok(Some(buf))
+        };
+
+        let manifest: BackupManifest = read_entry(MANIFEST_ENTRY)?
+            .ok_or("缺少 manifest.json")
+            .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|_| "manifest.json 无效"))?;
+        let config_yaml = read_entry(CONFIG_ENTRY)?
+            .ok_or("缺少 config.yaml")
+            .and_then(|bytes| String::from_utf8(bytes).map_err(|_| "config.yaml 不是 UTF-8"))?;
+        let database = read_entry(DATABASE_ENTRY)?;
+        let field_key = read_entry(FIELD_KEY_ENTRY)?
+            .map(|bytes| String::from_utf8(bytes).map_err(|_| "field.key 不是 UTF-8"))
+            .transpose()?;
+
+        Ok(Self {
+            created_at: manifest.created_at,
+            config_yaml,
+            database,
+            field_key,
+        })
+    }
+}
+
+/// 由口令派生加密密钥
+pub fn derive_key(passphrase: &str, salt: &[u8]) -> [u8; 32] {
+    pbkdf2_sha256(passphrase.as_bytes(), salt, KDF_ITERATIONS)
+}
+
+/// PBKDF2-HMAC-SHA256，输出 32 字节（单个分块）
+fn pbkdf2_sha256(password: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+    let prf = Hmac::<Sha256>::new_from_slice(password).expect("HMAC 接受任意长度的密钥");
+    let mut block = prf.clone();
+    block.update(salt);
+    block.update(&1u32.to_be_bytes());
+    let mut u: [u8; 32] = block.finalize().into_bytes().into();
+    let mut key = u;
+    for _ in 1..iterations {
+        let mut round = prf.clone();
+        round.update(&u);
+        u = round.finalize().into_bytes().into();
+        key.iter_mut().zip(u.iter()).for_each(|(k, u)| *k ^= u);
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open_roundtrip() {
+        let archive = BackupArchive {
+            created_at: Utc::now(),
+            config_yaml: "server:\n  port: 8999\n".to_string(),
+            database: Some(vec![1, 2, 3, 4]),
+            field_key: Some("a2V5".to_string()),
+        };
+
+        let sealed = archive.seal("correct horse").unwrap();
+        assert!(sealed.starts_with(BACKUP_MAGIC));
+        assert!(!sealed
+            .windows(archive.config_yaml.len())
+            .any(|w| w == archive.config_yaml.as_bytes()));
+
+        let opened = BackupArchive::open(&sealed, "correct horse").unwrap();
+        assert_eq!(opened.config_yaml, archive.config_yaml);
+        assert_eq!(opened.database, Some(vec![1, 2, 3, 4]));
+        assert_eq!(opened.field_key.as_deref(), Some("a2V5"));
+
+        assert!(BackupArchive::open(&sealed, "wrong").is_err());
+        assert!(archive.seal("").is_err());
+    }
+
+    #[test]
+    fn test_pbkdf2_matches_rfc7914_vector() {
+        // RFC 7914 §11：P = "passwd"，S = "salt"，c = 1（比对前 16 字节）
+        let key = pbkdf2_sha256(b"passwd", b"salt", 1);
+        assert_eq!(
+            key[..16],
+            [
+                0x55, 0xac, 0x04, 0x6e, 0x56, 0xe3, 0x08, 0x9f, 0xec, 0x16, 0x91, 0xc2, 0x25, 0x44,
+                0xb6, 0x05
+            ]
+        );
+    }
+}
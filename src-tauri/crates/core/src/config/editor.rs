@@ -0,0 +1,163 @@
+//! 结构化配置编辑
+//!
+//! 界面以 JSON 树读取当前生效的配置（已合并默认值、已解析钥匙串引用），
+//! 以 JSON Merge Patch（RFC 7386）提交修改：
+//! - 对象按字段递归合并，`null` 删除字段（恢复默认值）
+//! - 其他值（包括数组）整体替换
+//! - 值为脱敏占位符的字段保持原值，读取时脱敏的密钥可以原样提交
+//!
+//! 合并后的配置经过与热重载相同的校验才会写盘。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::audit::{diff_config_values, ConfigFieldChange};
+use super::export::{ExportService, REDACTED_PLACEHOLDER};
+use super::hot_reload::{validate_config, HotReloadError};
+use super::lint::{lint_config, ConfigLintWarning};
+use super::types::Config;
+use super::yaml::ConfigError;
+
+/// 应用补丁的结果
+#[derive(Debug, Clone)]
+pub struct ConfigPatchOutcome {
+    /// 合并并校验后的配置
+    pub config: Config,
+    /// 字段级变更（密钥只显示为 `***`）
+    pub changes: Vec<ConfigFieldChange>,
+}
+
+/// 补丁预览（校验失败时 `error` 为失败原因）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConfigPatchPreview {
+    pub valid: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub changes: Vec<ConfigFieldChange>,
+    /// 合并后配置的逻辑检查警告（不影响 `valid`）
+    #[serde(default)]
+    pub lint: Vec<ConfigLintWarning>,
+}
+
+impl ConfigPatchPreview {
+    pub fn from_result(result: &Result<ConfigPatchOutcome, ConfigError>) -> Self {
+        match result {
+            Ok(outcome) => Self {
+                valid: true,
+                error: None,
+                changes: outcome.changes.clone(),
+                lint: lint_config(&outcome.config),
+            },
+            Err(e) => Self {
+                valid: false,
+                error: Some(e.to_string()),
+                changes: Vec::new(),
+                lint: Vec::new(),
+            },
+        }
+    }
+}
+
+/// 当前生效配置的 JSON 树，`redact_secrets` 为 true 时密钥替换为占位符
+pub fn effective_config_value(config: &Config, redact_secrets: bool) -> Result<Value, ConfigError> {
+    let value = if redact_secrets {
+        serde_json::to_value(ExportService::redact_config(config))
+    } else {
+        serde_json::to_value(config)
+    };
+    value.map_err(|e| ConfigError::SerializeError(e.to_string()))
+}
+
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        if patch.as_str() != Some(REDACTED_PLACEHOLDER) {
+            *target = patch.clone();
+        }
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(target) = target else {
+        return;
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+/// 将补丁应用到配置上并校验，不写盘
+pub fn apply_config_patch(base: &Config, patch: &Value) -> Result<ConfigPatchOutcome, ConfigError> {
+    if !patch.is_object() {
+        return Err(ConfigError::ValidationError(
+            "配置补丁必须是 JSON 对象".to_string(),
+        ));
+    }
+    let before = effective_config_value(base, false)?;
+    let mut merged = before.clone();
+    merge_patch(&mut merged, patch);
+
+    let config: Config = serde_json::from_value(merged)
+        .map_err(|e| ConfigError::ValidationError(format!("字段格式错误: {e}")))?;
+    validate_config(&config).map_err(|e| match e {
+        HotReloadError::ValidationError(msg) => ConfigError::ValidationError(msg),
+        other => ConfigError::ValidationError(other.to_string()),
+    })?;
+
+    // 以反序列化后的配置对比，避免补丁中被忽略的未知字段显示为变更
+    let after = effective_config_value(&config, false)?;
+    Ok(ConfigPatchOutcome {
+        changes: diff_config_values(&before, &after),
+        config,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn base_config() -> Config {
+        let mut config = Config::default();
+        config.server.api_key = "sk-local-secret".to_string();
+        config
+    }
+
+    #[test]
+    fn test_apply_patch_merges_and_keeps_redacted_secrets() {
+        let base = base_config();
+        let patch = json!({
+            "server": {"port": 9100, "api_key": REDACTED_PLACEHOLDER},
+            "retry": {"max_retries": 5}
+        });
+        let outcome = apply_config_patch(&base, &patch).unwrap();
+
+        assert_eq!(outcome.config.server.port, 9100);
+        assert_eq!(outcome.config.server.api_key, "sk-local-secret");
+        assert_eq!(outcome.config.server.host, base.server.host);
+        assert_eq!(outcome.config.retry.max_retries, 5);
+        let paths: Vec<&str> = outcome.changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["retry.max_retries", "server.port"]);
+
+        let redacted = effective_config_value(&base, true).unwrap();
+        assert_eq!(redacted["server"]["api_key"], json!(REDACTED_PLACEHOLDER));
+    }
+
+    #[test]
+    fn test_apply_patch_rejects_invalid_config() {
+        let base = base_config();
+        let result = apply_config_patch(&base, &json!({"server": {"port": 0}}));
+        assert!(matches!(result, Err(ConfigError::ValidationError(_))));
+
+        let result = apply_config_patch(&base, &json!({"server": {"port": "abc"}}));
+        let preview = ConfigPatchPreview::from_result(&result);
+        assert!(!preview.valid);
+        assert!(preview.error.unwrap().contains("字段格式错误"));
+
+        assert!(apply_config_patch(&base, &json!([1])).is_err());
+    }
+}
@@ -7,8 +7,10 @@
 //! - 敏感信息脱敏
 
 use super::path_utils::expand_tilde;
+use super::preset::RoutingPreset;
 use super::types::{ApiKeyEntry, Config, CredentialEntry, CredentialPoolConfig};
 use super::yaml::{ConfigError, ConfigManager};
+use crate::secret_store::{is_keychain_ref, KEYCHAIN_REF_PREFIX};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -185,6 +187,32 @@ pub struct ExportService;
 
 #[allow(dead_code)]
 impl ExportService {
+    /// 导出路由预设（模型别名、规模 / 时段路由规则、参数注入规则）
+    ///
+    /// # Arguments
+    /// * `config` - 当前配置
+    /// * `name` - 预设名称
+    /// * `description` - 预设说明
+    /// * `app_version` - 应用版本
+    pub fn export_preset(
+        config: &Config,
+        name: &str,
+        description: &str,
+        app_version: &str,
+    ) -> RoutingPreset {
+        let mut preset = RoutingPreset::new(name, description, app_version);
+        preset.model_aliases = config
+            .routing
+            .model_aliases
+            .iter()
+            .map(|(alias, model)| (alias.clone(), model.clone()))
+            .collect();
+        preset.size_rules = config.routing.size_routing.rules.clone();
+        preset.time_window_rules = config.routing.time_windows.rules.clone();
+        preset.injection_rules = config.injection.rules.clone();
+        preset
+    }
+
     /// 导出配置为 YAML 字符串
     ///
     /// # Arguments
@@ -341,38 +369,36 @@ impl ExportService {
 
     /// 检查配置是否包含敏感信息
     ///
-    /// 用于验证脱敏是否完整
+    /// 用于验证脱敏是否完整（钥匙串引用不含密钥本身，不视为敏感信息）
     pub fn contains_secrets(config: &Config) -> bool {
+        let is_secret = |value: &str| {
+            !value.is_empty() && value != REDACTED_PLACEHOLDER && !is_keychain_ref(value)
+        };
+
         // 检查服务器 API 密钥
-        if !config.server.api_key.is_empty() && config.server.api_key != REDACTED_PLACEHOLDER {
+        if is_secret(&config.server.api_key) {
             return true;
         }
 
         // 检查 Provider API 密钥
-        if let Some(ref key) = config.providers.openai.api_key {
-            if !key.is_empty() && key != REDACTED_PLACEHOLDER {
-                return true;
-            }
-        }
-        if let Some(ref key) = config.providers.claude.api_key {
-            if !key.is_empty() && key != REDACTED_PLACEHOLDER {
-                return true;
-            }
+        if [
+            &config.providers.openai.api_key,
+            &config.providers.claude.api_key,
+        ]
+        .into_iter()
+        .flatten()
+        .any(|key| is_secret(key))
+        {
+            return true;
         }
 
         // 检查凭证池中的 API Key
-        for entry in &config.credential_pool.openai {
-            if !entry.api_key.is_empty() && entry.api_key != REDACTED_PLACEHOLDER {
-                return true;
-            }
-        }
-        for entry in &config.credential_pool.claude {
-            if !entry.api_key.is_empty() && entry.api_key != REDACTED_PLACEHOLDER {
-                return true;
-            }
-        }
-
-        false
+        config
+            .credential_pool
+            .openai
+            .iter()
+            .chain(config.credential_pool.claude.iter())
+            .any(|entry| is_secret(&entry.api_key))
     }
 
     /// 检查 YAML 字符串是否包含敏感信息
@@ -388,7 +414,10 @@ impl ExportService {
             if yaml.contains(pattern) && !yaml.contains(REDACTED_PLACEHOLDER) {
                 // 进一步检查是否是实际的密钥值
                 for line in yaml.lines() {
-                    if line.contains(pattern) && !line.contains(REDACTED_PLACEHOLDER) {
+                    if line.contains(pattern)
+                        && !line.contains(REDACTED_PLACEHOLDER)
+                        && !line.contains(KEYCHAIN_REF_PREFIX)
+                    {
                         // 排除注释行
                         let trimmed = line.trim();
                         if !trimmed.starts_with('#') {
@@ -677,6 +706,17 @@ mod unit_tests {
         assert!(ExportService::contains_secrets(&config));
     }
 
+    #[test]
+    fn test_keychain_references_are_not_secrets() {
+        let mut config = Config::default();
+        config.server.api_key = "keychain:config:server.api_key".to_string();
+
+        assert!(!ExportService::contains_secrets(&config));
+        assert!(!ExportService::yaml_contains_secrets(
+            "server:\n  api_key: keychain:config:server.api_key\n"
+        ));
+    }
+
     #[test]
     fn test_export_config_only() {
         let config = Config::default();
@@ -7,6 +7,7 @@
 #![allow(dead_code)]
 //! - 失败时自动回滚到之前的配置
 
+use super::lint::lint_config;
 use super::types::{is_default_api_key, Config};
 use super::yaml::ConfigManager;
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
@@ -48,6 +49,67 @@ impl std::fmt::Display for HotReloadError {
 
 impl std::error::Error for HotReloadError {}
 
+/// 验证配置是否可以应用到运行中的实例（热重载与配置编辑共用）
+pub fn validate_config(config: &Config) -> Result<(), HotReloadError> {
+    let _is_localhost = is_localhost_host(&config.server.host);
+    let is_valid_host = is_valid_bind_host(&config.server.host);
+    let _is_non_local = is_non_local_bind(&config.server.host);
+
+    // 验证端口范围
+    if config.server.port == 0 {
+        return Err(HotReloadError::ValidationError(
+            "端口号不能为 0".to_string(),
+        ));
+    }
+
+    // 验证绑定地址
+    if !is_valid_host {
+        return Err(HotReloadError::ValidationError(
+            "无效的监听地址。允许的地址：127.0.0.1、localhost、::1、0.0.0.0、::".to_string(),
+        ));
+    }
+
+    // 验证重试配置
+    if config.retry.max_retries > 100 {
+        return Err(HotReloadError::ValidationError(
+            "最大重试次数不能超过 100".to_string(),
+        ));
+    }
+
+    if config.retry.base_delay_ms == 0 {
+        return Err(HotReloadError::ValidationError(
+            "基础延迟不能为 0".to_string(),
+        ));
+    }
+
+    // 验证日志保留天数
+    if config.logging.retention_days == 0 {
+        return Err(HotReloadError::ValidationError(
+            "日志保留天数不能为 0".to_string(),
+        ));
+    }
+
+    if config.server.api_key.trim().is_empty() {
+        return Err(HotReloadError::ValidationError(
+            "API Key 不能为空".to_string(),
+        ));
+    }
+
+    if config.server.tls.enable {
+        return Err(HotReloadError::ValidationError(
+            "当前版本暂不支持 TLS，请关闭 TLS 配置".to_string(),
+        ));
+    }
+
+    if config.remote_management.allow_remote {
+        return Err(HotReloadError::ValidationError(
+            "当前版本未启用 TLS，禁止开启远程管理".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
 /// 热重载结果
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -336,6 +398,11 @@ impl HotReloadManager {
             };
         }
 
+        // 逻辑检查只记录警告，不阻止重载
+        for warning in lint_config(&new_config) {
+            tracing::warn!("[CONFIG_LINT] {}", warning);
+        }
+
         // 4. 原子性地应用新配置
         {
             let mut current = self.current_config.write();
@@ -375,63 +442,7 @@ impl HotReloadManager {
 
     /// 验证配置
     fn validate_config(&self, config: &Config) -> Result<(), HotReloadError> {
-        let _is_localhost = is_localhost_host(&config.server.host);
-        let is_valid_host = is_valid_bind_host(&config.server.host);
-        let _is_non_local = is_non_local_bind(&config.server.host);
-
-        // 验证端口范围
-        if config.server.port == 0 {
-            return Err(HotReloadError::ValidationError(
-                "端口号不能为 0".to_string(),
-            ));
-        }
-
-        // 验证绑定地址
-        if !is_valid_host {
-            return Err(HotReloadError::ValidationError(
-                "无效的监听地址。允许的地址：127.0.0.1、localhost、::1、0.0.0.0、::".to_string(),
-            ));
-        }
-
-        // 验证重试配置
-        if config.retry.max_retries > 100 {
-            return Err(HotReloadError::ValidationError(
-                "最大重试次数不能超过 100".to_string(),
-            ));
-        }
-
-        if config.retry.base_delay_ms == 0 {
-            return Err(HotReloadError::ValidationError(
-                "基础延迟不能为 0".to_string(),
-            ));
-        }
-
-        // 验证日志保留天数
-        if config.logging.retention_days == 0 {
-            return Err(HotReloadError::ValidationError(
-                "日志保留天数不能为 0".to_string(),
-            ));
-        }
-
-        if config.server.api_key.trim().is_empty() {
-            return Err(HotReloadError::ValidationError(
-                "API Key 不能为空".to_string(),
-            ));
-        }
-
-        if config.server.tls.enable {
-            return Err(HotReloadError::ValidationError(
-                "当前版本暂不支持 TLS，请关闭 TLS 配置".to_string(),
-            ));
-        }
-
-        if config.remote_management.allow_remote {
-            return Err(HotReloadError::ValidationError(
-                "当前版本未启用 TLS，禁止开启远程管理".to_string(),
-            ));
-        }
-
-        Ok(())
+        validate_config(config)
     }
 
     /// 手动回滚到备份配置
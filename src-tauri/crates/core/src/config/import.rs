@@ -8,8 +8,11 @@
 //! - 导入验证（格式、版本、脱敏状态）
 //! - 合并和替换模式
 
+use super::backup::BackupArchive;
 use super::export::{base64_decode, ExportBundle, REDACTED_PLACEHOLDER};
+use super::lint::{lint_config, ConfigLintWarning};
 use super::path_utils::expand_tilde;
+use super::preset::{PresetConflictStrategy, RoutingPreset};
 use super::types::{ApiKeyEntry, Config, CredentialEntry, CredentialPoolConfig};
 use super::yaml::{ConfigError, ConfigManager, YamlService};
 use serde::{Deserialize, Serialize};
@@ -57,6 +60,9 @@ pub struct ValidationResult {
     pub errors: Vec<String>,
     /// 警告信息列表
     pub warnings: Vec<String>,
+    /// 配置逻辑检查警告
+    #[serde(default)]
+    pub lint: Vec<ConfigLintWarning>,
 }
 
 impl ValidationResult {
@@ -70,6 +76,7 @@ impl ValidationResult {
             has_credentials: false,
             errors: Vec::new(),
             warnings: Vec::new(),
+            lint: Vec::new(),
         }
     }
 
@@ -83,6 +90,7 @@ impl ValidationResult {
             has_credentials: false,
             errors: vec![error.into()],
             warnings: Vec::new(),
+            lint: Vec::new(),
         }
     }
 
@@ -105,6 +113,9 @@ pub struct ImportResult {
     pub success: bool,
     /// 警告信息
     pub warnings: Vec<String>,
+    /// 导入后配置的逻辑检查警告
+    #[serde(default)]
+    pub lint: Vec<ConfigLintWarning>,
     /// 导入的配置
     pub config: Config,
 }
@@ -112,11 +123,7 @@ pub struct ImportResult {
 impl ImportResult {
     /// 创建成功的导入结果
     pub fn success(config: Config) -> Self {
-        Self {
-            success: true,
-            warnings: Vec::new(),
-            config,
-        }
+        Self::success_with_warnings(config, Vec::new())
     }
 
     /// 创建带警告的成功导入结果
@@ -124,11 +131,25 @@ impl ImportResult {
         Self {
             success: true,
             warnings,
+            lint: lint_config(&config),
             config,
         }
     }
 }
 
+/// 从备份包恢复出的内容
+#[derive(Debug, Clone)]
+pub struct RestoredBackup {
+    /// 备份时间
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// 备份中的配置
+    pub config: Config,
+    /// 数据库快照（SQLite 文件）
+    pub database: Option<Vec<u8>>,
+    /// 快照中凭证字段的加密密钥（Base64）
+    pub field_key: Option<String>,
+}
+
 /// 导入错误类型
 #[derive(Debug, Clone)]
 #[allow(clippy::enum_variant_names)]
@@ -197,11 +218,12 @@ impl ImportService {
         }
 
         // 尝试解析为 YAML 配置
-        if let Ok(_config) = ConfigManager::parse_yaml(content) {
+        if let Ok(config) = ConfigManager::parse_yaml(content) {
             let mut result = ValidationResult::valid();
             result.has_config = true;
             result.has_credentials = false;
             result.version = Some("yaml".to_string());
+            result.lint = lint_config(&config);
             return result;
         }
 
@@ -234,8 +256,9 @@ impl ImportService {
 
         // 验证配置内容（如果存在）
         if let Some(ref yaml) = bundle.config_yaml {
-            if let Err(e) = ConfigManager::parse_yaml(yaml) {
-                result.add_error(format!("配置 YAML 解析失败: {e}"));
+            match ConfigManager::parse_yaml(yaml) {
+                Ok(config) => result.lint = lint_config(&config),
+                Err(e) => result.add_error(format!("配置 YAML 解析失败: {e}")),
             }
         }
 
@@ -533,6 +556,28 @@ impl ImportService {
         Self::import_yaml(&content, current_config, options)
     }
 
+    /// 从加密备份包恢复
+    ///
+    /// 解密并解析备份中的配置，数据库快照原样返回，由调用方负责写回数据库。
+    ///
+    /// # Arguments
+    /// * `bytes` - 备份文件内容
+    /// * `passphrase` - 备份加密口令
+    ///
+    /// # Returns
+    /// * `Ok(RestoredBackup)` - 解析成功
+    /// * `Err(ImportError)` - 口令错误、文件损坏或配置无效
+    pub fn restore_backup(bytes: &[u8], passphrase: &str) -> Result<RestoredBackup, ImportError> {
+        let archive = BackupArchive::open(bytes, passphrase).map_err(ImportError::FormatError)?;
+        let config = ConfigManager::parse_yaml(&archive.config_yaml)?;
+        Ok(RestoredBackup {
+            created_at: archive.created_at,
+            config,
+            database: archive.database,
+            field_key: archive.field_key,
+        })
+    }
+
     /// 保存导入的配置到文件
     ///
     /// # Arguments
@@ -546,11 +591,226 @@ impl ImportService {
         YamlService::save_preserve_comments(path, config)?;
         Ok(())
     }
+
+    /// 验证路由预设，并列出与当前配置冲突的条目
+    ///
+    /// # Arguments
+    /// * `content` - 预设内容（JSON）
+    /// * `current_config` - 当前配置
+    pub fn validate_preset(content: &str, current_config: &Config) -> ValidationResult {
+        let preset = match RoutingPreset::from_json(content) {
+            Ok(preset) => preset,
+            Err(e) => return ValidationResult::invalid(format!("无法解析路由预设: {e}")),
+        };
+        let mut result = ValidationResult::valid();
+        result.version = Some(preset.version.clone());
+        result.has_config = preset.item_count() > 0;
+        if !Self::SUPPORTED_VERSIONS.contains(&preset.version.as_str()) {
+            result.add_warning(format!(
+                "预设版本 {} 可能不完全兼容，支持的版本: {:?}",
+                preset.version,
+                Self::SUPPORTED_VERSIONS
+            ));
+        }
+        if preset.item_count() == 0 {
+            result.add_warning("预设不包含任何规则");
+        }
+
+        let mut config = current_config.clone();
+        let conflicts = Self::merge_preset(&preset, &mut config, PresetConflictStrategy::Skip);
+        for item in conflicts.items {
+            result.add_warning(format!("{item} 与现有条目冲突"));
+        }
+        result.lint = lint_config(&config);
+        result
+    }
+
+    /// 导入路由预设
+    ///
+    /// 预设中的条目追加到当前配置，与现有条目冲突时按 `strategy` 处理；
+    /// 与现有条目完全相同的条目直接跳过。
+    ///
+    /// # Arguments
+    /// * `preset` - 路由预设
+    /// * `current_config` - 当前配置
+    /// * `strategy` - 冲突处理方式
+    pub fn import_preset(
+        preset: &RoutingPreset,
+        current_config: &Config,
+        strategy: PresetConflictStrategy,
+    ) -> ImportResult {
+        let mut config = current_config.clone();
+        let mut warnings = Self::merge_preset(preset, &mut config, strategy).actions;
+
+        if !preset.size_rules.is_empty() && !config.routing.size_routing.enabled {
+            warnings.push("按规模路由未启用，导入的规则需启用后才会生效".to_string());
+        }
+        if !preset.time_window_rules.is_empty() && !config.routing.time_windows.enabled {
+            warnings.push("按时段路由未启用，导入的规则需启用后才会生效".to_string());
+        }
+        if !preset.injection_rules.is_empty() && !config.injection.enabled {
+            warnings.push("参数注入未启用，导入的规则需启用后才会生效".to_string());
+        }
+
+        ImportResult::success_with_warnings(config, warnings)
+    }
+
+    /// 把预设合并到配置中，返回冲突记录
+    fn merge_preset(
+        preset: &RoutingPreset,
+        config: &mut Config,
+        strategy: PresetConflictStrategy,
+    ) -> PresetConflicts {
+        let mut conflicts = PresetConflicts::default();
+
+        let aliases = &mut config.routing.model_aliases;
+        for (alias, model) in &preset.model_aliases {
+            match aliases.get(alias) {
+                None => {
+                    aliases.insert(alias.clone(), model.clone());
+                }
+                Some(existing) if existing == model => {}
+                Some(_) => {
+                    let item = format!("模型别名 `{alias}`");
+                    match strategy {
+                        PresetConflictStrategy::Overwrite => {
+                            aliases.insert(alias.clone(), model.clone());
+                            conflicts.record(item, "已覆盖".to_string());
+                        }
+                        PresetConflictStrategy::Skip => {
+                            conflicts.record(item, "已跳过".to_string());
+                        }
+                        PresetConflictStrategy::Rename => {
+                            let renamed = unique_name(alias, |name| aliases.contains_key(name));
+                            aliases.insert(renamed.clone(), model.clone());
+                            conflicts.record(item, format!("已重命名为 `{renamed}`"));
+                        }
+                    }
+                }
+            }
+        }
+
+        // 规模规则没有名称，匹配条件相同即视为冲突，无法重命名
+        let size_rules = &mut config.routing.size_routing.rules;
+        for rule in &preset.size_rules {
+            let existing = size_rules
+                .iter()
+                .position(|e| e.size == rule.size && e.attachments == rule.attachments);
+            match existing {
+                None => size_rules.push(rule.clone()),
+                Some(index) if size_rules[index] == *rule => {}
+                Some(index) => {
+                    let item = format!("规模路由规则 rules[{index}]");
+                    if strategy == PresetConflictStrategy::Overwrite {
+                        size_rules[index] = rule.clone();
+                        conflicts.record(item, "已覆盖".to_string());
+                    } else {
+                        conflicts.record(item, "已跳过".to_string());
+                    }
+                }
+            }
+        }
+
+        merge_named(
+            &mut config.routing.time_windows.rules,
+            &preset.time_window_rules,
+            |rule| rule.name.as_str(),
+            |rule, name| rule.name = name,
+            "时段路由规则",
+            strategy,
+            &mut conflicts,
+        );
+        merge_named(
+            &mut config.injection.rules,
+            &preset.injection_rules,
+            |rule| rule.id.as_str(),
+            |rule, id| rule.id = id,
+            "注入规则",
+            strategy,
+            &mut conflicts,
+        );
+
+        conflicts
+    }
+}
+
+/// 预设合并时记录的冲突
+#[derive(Debug, Default)]
+struct PresetConflicts {
+    /// 冲突条目描述
+    items: Vec<String>,
+    /// 按冲突策略处理后的说明
+    actions: Vec<String>,
+}
+
+impl PresetConflicts {
+    fn record(&mut self, item: String, action: String) {
+        self.actions.push(format!("{item} 已存在，{action}"));
+        self.items.push(item);
+    }
+}
+
+/// 生成不与现有名称重复的新名称（`<name>-imported`、`<name>-imported-2`…）
+fn unique_name(base: &str, taken: impl Fn(&str) -> bool) -> String {
+    let mut candidate = format!("{base}-imported");
+    let mut suffix = 2;
+    while taken(&candidate) {
+        candidate = format!("{base}-imported-{suffix}");
+        suffix += 1;
+    }
+    candidate
+}
+
+/// 按名称合并规则列表，名称为空的规则只按内容去重
+fn merge_named<T: Clone + PartialEq>(
+    existing: &mut Vec<T>,
+    incoming: &[T],
+    key: impl Fn(&T) -> &str,
+    set_key: impl Fn(&mut T, String),
+    label: &str,
+    strategy: PresetConflictStrategy,
+    conflicts: &mut PresetConflicts,
+) {
+    for rule in incoming {
+        if existing.contains(rule) {
+            continue;
+        }
+        let name = key(rule);
+        let position = if name.is_empty() {
+            None
+        } else {
+            existing.iter().position(|e| key(e) == name)
+        };
+        let Some(index) = position else {
+            existing.push(rule.clone());
+            continue;
+        };
+        let item = format!("{label} `{name}`");
+        match strategy {
+            PresetConflictStrategy::Overwrite => {
+                existing[index] = rule.clone();
+                conflicts.record(item, "已覆盖".to_string());
+            }
+            PresetConflictStrategy::Skip => {
+                conflicts.record(item, "已跳过".to_string());
+            }
+            PresetConflictStrategy::Rename => {
+                let renamed = unique_name(name, |candidate| {
+                    existing.iter().any(|e| key(e) == candidate)
+                });
+                let mut rule = rule.clone();
+                set_key(&mut rule, renamed.clone());
+                existing.push(rule);
+                conflicts.record(item, format!("已重命名为 `{renamed}`"));
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod unit_tests {
     use super::*;
+    use crate::config::{ExportService, InjectionRuleConfig};
 
     #[test]
     fn test_import_options_default() {
@@ -791,4 +1051,84 @@ credential_pool:
         let err = ImportError::RedactedDataError("test".to_string());
         assert!(err.to_string().contains("脱敏数据"));
     }
+
+    fn preset_test_config() -> Config {
+        let mut config = Config::default();
+        config
+            .routing
+            .model_aliases
+            .insert("fast".to_string(), "gpt-4o-mini".to_string());
+        config.injection.rules.push(InjectionRuleConfig {
+            id: "temp".to_string(),
+            pattern: "*".to_string(),
+            parameters: serde_json::json!({ "temperature": 0.2 }),
+            mode: Default::default(),
+            priority: 100,
+            enabled: true,
+        });
+        config
+    }
+
+    #[test]
+    fn test_import_preset_conflict_strategies() {
+        let current = preset_test_config();
+        let mut preset = ExportService::export_preset(&current, "shared", "", "1.0.0");
+        preset
+            .model_aliases
+            .insert("fast".to_string(), "claude-haiku".to_string());
+        preset
+            .model_aliases
+            .insert("smart".to_string(), "claude-sonnet".to_string());
+        preset.injection_rules[0].parameters = serde_json::json!({ "temperature": 0.7 });
+
+        let result = ImportService::import_preset(&preset, &current, PresetConflictStrategy::Skip);
+        let aliases = &result.config.routing.model_aliases;
+        assert_eq!(aliases["fast"], "gpt-4o-mini");
+        assert_eq!(aliases["smart"], "claude-sonnet");
+        assert_eq!(result.config.injection.rules.len(), 1);
+        assert!(result.warnings.iter().any(|w| w.contains("已跳过")));
+
+        let result =
+            ImportService::import_preset(&preset, &current, PresetConflictStrategy::Overwrite);
+        assert_eq!(result.config.routing.model_aliases["fast"], "claude-haiku");
+        assert_eq!(
+            result.config.injection.rules[0].parameters["temperature"],
+            0.7
+        );
+
+        let result =
+            ImportService::import_preset(&preset, &current, PresetConflictStrategy::Rename);
+        let aliases = &result.config.routing.model_aliases;
+        assert_eq!(aliases["fast"], "gpt-4o-mini");
+        assert_eq!(aliases["fast-imported"], "claude-haiku");
+        let ids: Vec<&str> = result
+            .config
+            .injection
+            .rules
+            .iter()
+            .map(|rule| rule.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["temp", "temp-imported"]);
+    }
+
+    #[test]
+    fn test_validate_preset_reports_conflicts() {
+        let current = preset_test_config();
+        let mut preset = ExportService::export_preset(&current, "shared", "", "1.0.0");
+        let json = preset.to_json().unwrap();
+        let result = ImportService::validate_preset(&json, &current);
+        assert!(result.valid);
+        // 与现有条目完全相同的不算冲突
+        assert!(result.warnings.is_empty());
+
+        preset
+            .model_aliases
+            .insert("fast".to_string(), "claude-haiku".to_string());
+        let result = ImportService::validate_preset(&preset.to_json().unwrap(), &current);
+        assert!(result.warnings.iter().any(|w| w.contains("`fast`")));
+
+        // 完整导出包不是预设
+        let bundle = ExportBundle::new("1.0.0").to_json().unwrap();
+        assert!(!ImportService::validate_preset(&bundle, &current).valid);
+    }
 }
@@ -0,0 +1,434 @@
+//! 配置逻辑检查
+//!
+//! 在格式校验之外检查配置中的逻辑问题：被前面规则完全覆盖的路由规则、
+//! 引用了未定义的 Provider、凭证池中没有对应启用 Provider 的凭证，
+//! 以及不会按预期生效的回退链（别名、弃用替代、降级规则的链式或循环引用）。
+//! 检查结果只作为警告返回，不阻止加载或导入。
+
+use std::collections::HashSet;
+
+use chrono::{NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+use super::types::{Config, SizeRoutingRule, TimeWindowRule};
+use crate::models::injection_types::pattern_matches;
+use crate::ProviderType;
+
+/// 警告类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigLintCode {
+    /// 规则被前面的规则完全覆盖，永远不会命中
+    ShadowedRule,
+    /// 引用了无法识别的 Provider
+    UndefinedProvider,
+    /// 凭证所属的 Provider 未启用
+    OrphanCredential,
+    /// 回退链形成循环
+    FallbackCycle,
+    /// 回退链的后续环节不会被执行
+    UnreachableFallback,
+}
+
+/// 配置检查警告
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigLintWarning {
+    pub code: ConfigLintCode,
+    /// 出问题的配置路径，如 `routing.size_routing.rules[2]`
+    pub path: String,
+    pub message: String,
+    /// 修复建议
+    pub suggestion: String,
+}
+
+impl std::fmt::Display for ConfigLintWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}（{}）", self.path, self.message, self.suggestion)
+    }
+}
+
+struct Linter {
+    warnings: Vec<ConfigLintWarning>,
+}
+
+impl Linter {
+    fn warn(
+        &mut self,
+        code: ConfigLintCode,
+        path: impl Into<String>,
+        message: String,
+        suggestion: &str,
+    ) {
+        self.warnings.push(ConfigLintWarning {
+            code,
+            path: path.into(),
+            message,
+            suggestion: suggestion.to_string(),
+        });
+    }
+
+    fn check_provider(&mut self, path: &str, provider: &str) {
+        let provider = provider.trim();
+        if provider.is_empty() || provider.parse::<ProviderType>().is_ok() {
+            return;
+        }
+        self.warn(
+            ConfigLintCode::UndefinedProvider,
+            path,
+            format!("Provider `{provider}` 未定义"),
+            "检查拼写，或改为已配置的 Provider ID",
+        );
+    }
+}
+
+/// 检查配置中的逻辑问题
+pub fn lint_config(config: &Config) -> Vec<ConfigLintWarning> {
+    let mut linter = Linter {
+        warnings: Vec::new(),
+    };
+    lint_providers(&mut linter, config);
+    lint_credentials(&mut linter, config);
+    lint_size_rules(&mut linter, &config.routing.size_routing.rules);
+    lint_time_windows(&mut linter, &config.routing.time_windows.rules);
+    lint_fallback_chains(&mut linter, config);
+    linter.warnings
+}
+
+fn lint_providers(linter: &mut Linter, config: &Config) {
+    linter.check_provider("default_provider", &config.default_provider);
+    linter.check_provider("routing.default_provider", &config.routing.default_provider);
+    let endpoints = &config.endpoint_providers;
+    for (name, provider) in [
+        ("cursor", &endpoints.cursor),
+        ("claude_code", &endpoints.claude_code),
+        ("codex", &endpoints.codex),
+        ("windsurf", &endpoints.windsurf),
+        ("kiro", &endpoints.kiro),
+        ("other", &endpoints.other),
+    ] {
+        if let Some(provider) = provider {
+            linter.check_provider(&format!("endpoint_providers.{name}"), provider);
+        }
+    }
+    for (index, rule) in config.routing.time_windows.rules.iter().enumerate() {
+        if let Some(provider) = &rule.provider {
+            linter.check_provider(
+                &format!("routing.time_windows.rules[{index}].provider"),
+                provider,
+            );
+        }
+    }
+    let mut endpoint_names: Vec<&String> = config.providers.endpoints.keys().collect();
+    endpoint_names.sort();
+    for name in endpoint_names {
+        linter.check_provider(&format!("providers.endpoints.{name}"), name);
+    }
+}
+
+fn lint_credentials(linter: &mut Linter, config: &Config) {
+    let pool = &config.credential_pool;
+    let providers = &config.providers;
+    for (name, count, enabled) in [
+        ("kiro", pool.kiro.len(), providers.kiro.enabled),
+        ("gemini", pool.gemini.len(), providers.gemini.enabled),
+        ("qwen", pool.qwen.len(), providers.qwen.enabled),
+        ("openai", pool.openai.len(), providers.openai.enabled),
+        ("claude", pool.claude.len(), providers.claude.enabled),
+    ] {
+        if count > 0 && !enabled {
+            linter.warn(
+                ConfigLintCode::OrphanCredential,
+                format!("credential_pool.{name}"),
+                format!("有 {count} 个凭证，但 providers.{name} 未启用"),
+                "启用对应的 Provider，或移除这些凭证",
+            );
+        }
+    }
+}
+
+fn lint_size_rules(linter: &mut Linter, rules: &[SizeRoutingRule]) {
+    let covers = |earlier: &SizeRoutingRule, later: &SizeRoutingRule| {
+        earlier.size.map_or(true, |size| later.size == Some(size))
+            && earlier
+                .attachments
+                .map_or(true, |attachments| later.attachments == Some(attachments))
+    };
+    for (index, rule) in rules.iter().enumerate() {
+        if let Some(earlier) = rules[..index].iter().position(|e| covers(e, rule)) {
+            linter.warn(
+                ConfigLintCode::ShadowedRule,
+                format!("routing.size_routing.rules[{index}]"),
+                format!("被 rules[{earlier}] 完全覆盖，永远不会命中"),
+                "把更具体的规则移到前面，或删除该规则",
+            );
+        }
+    }
+}
+
+/// 解析后的时间段，无法解析时返回 `None`（运行时会忽略该规则）
+struct Window {
+    /// 为空表示每天
+    days: HashSet<Weekday>,
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl Window {
+    fn parse(rule: &TimeWindowRule) -> Option<Self> {
+        let time = |value: &str| NaiveTime::parse_from_str(value.trim(), "%H:%M").ok();
+        Some(Self {
+            days: rule
+                .days
+                .iter()
+                .map(|day| day.trim().parse::<Weekday>().ok())
+                .collect::<Option<HashSet<_>>>()?,
+            start: time(&rule.start)?,
+            end: time(&rule.end)?,
+        })
+    }
+
+    fn all_day(&self) -> bool {
+        self.start == self.end
+    }
+
+    fn overnight(&self) -> bool {
+        self.start > self.end
+    }
+
+    /// 是否完整覆盖 `other` 的时段（跨午夜与不跨午夜的组合只在前者全天时判断）
+    fn covers(&self, other: &Window) -> bool {
+        let days =
+            self.days.is_empty() || (!other.days.is_empty() && other.days.is_subset(&self.days));
+        if !days {
+            return false;
+        }
+        if self.all_day() {
+            return true;
+        }
+        if other.all_day() || self.overnight() != other.overnight() {
+            return false;
+        }
+        self.start <= other.start && other.end <= self.end
+    }
+}
+
+fn lint_time_windows(linter: &mut Linter, rules: &[TimeWindowRule]) {
+    let windows: Vec<Option<Window>> = rules.iter().map(Window::parse).collect();
+    let models_covered = |earlier: &TimeWindowRule, later: &TimeWindowRule| {
+        earlier.models.is_empty()
+            || (!later.models.is_empty()
+                && later.models.iter().all(|model| {
+                    earlier
+                        .models
+                        .iter()
+                        .any(|pattern| pattern_matches(pattern.trim(), model.trim()))
+                }))
+    };
+    for (index, rule) in rules.iter().enumerate() {
+        let Some(window) = &windows[index] else {
+            continue;
+        };
+        let shadowed_by = (0..index).find(|&earlier| {
+            windows[earlier]
+                .as_ref()
+                .is_some_and(|w| w.covers(window) && models_covered(&rules[earlier], rule))
+        });
+        if let Some(earlier) = shadowed_by {
+            linter.warn(
+                ConfigLintCode::ShadowedRule,
+                format!("routing.time_windows.rules[{index}]"),
+                format!("时段与模型均被 rules[{earlier}] 覆盖，永远不会命中"),
+                "把更具体的规则移到前面，或缩小前面规则的时段与模型范围",
+            );
+        }
+    }
+}
+
+fn lint_fallback_chains(linter: &mut Linter, config: &Config) {
+    // 模型别名只解析一次，目标本身又是别名时不会继续解析
+    let aliases = &config.routing.model_aliases;
+    let mut alias_keys: Vec<&String> = aliases.keys().collect();
+    alias_keys.sort();
+    for alias in alias_keys {
+        let target = &aliases[alias];
+        if target == alias {
+            continue;
+        }
+        if let Some(next) = aliases.get(target).filter(|next| *next != target) {
+            linter.warn(
+                ConfigLintCode::UnreachableFallback,
+                format!("routing.model_aliases.{alias}"),
+                format!("目标 `{target}` 也是别名（→ `{next}`），别名只解析一次"),
+                "直接把别名指向最终模型",
+            );
+        }
+    }
+
+    // 弃用模型的替代模型本身也已弃用时，客户端仍会被改写到弃用模型
+    let deprecation = &config.routing.model_deprecation;
+    let mut deprecated: Vec<&String> = deprecation.replacements.keys().collect();
+    deprecated.sort();
+    for model in deprecated {
+        let replacement = deprecation.replacements[model].trim();
+        if replacement.is_empty() || replacement == model.trim() {
+            continue;
+        }
+        if deprecation
+            .replacements
+            .get(replacement)
+            .is_some_and(|next| !next.trim().is_empty())
+        {
+            linter.warn(
+                ConfigLintCode::UnreachableFallback,
+                format!("routing.model_deprecation.replacements.{model}"),
+                format!("替代模型 `{replacement}` 本身也已弃用"),
+                "把替代模型改为最终的可用模型",
+            );
+        }
+    }
+
+    // 降级规则：被前面的通配规则覆盖，或降级目标形成循环
+    let downgrade = &config.server.model_downgrade;
+    let rules = &downgrade.rules;
+    for (index, rule) in rules.iter().enumerate() {
+        if let Some(earlier) = rules[..index]
+            .iter()
+            .position(|e| pattern_matches(e.from.trim(), rule.from.trim()))
+        {
+            linter.warn(
+                ConfigLintCode::ShadowedRule,
+                format!("server.model_downgrade.rules[{index}]"),
+                format!("`{}` 已被 rules[{earlier}] 匹配，该规则不会命中", rule.from),
+                "把更具体的规则移到通配规则前面",
+            );
+        }
+    }
+    let next_hop = |model: &str| {
+        rules
+            .iter()
+            .find(|rule| pattern_matches(rule.from.trim(), model))
+            .map(|rule| rule.to.trim())
+    };
+    for (index, rule) in rules.iter().enumerate() {
+        let mut seen = vec![rule.from.trim()];
+        let mut current = rule.to.trim();
+        while let Some(next) = next_hop(current) {
+            if seen.contains(&current) || pattern_matches(rule.from.trim(), current) {
+                linter.warn(
+                    ConfigLintCode::FallbackCycle,
+                    format!("server.model_downgrade.rules[{index}]"),
+                    format!("降级链 `{}` → `{current}` 回到了已尝试过的模型", rule.from),
+                    "确保降级目标逐级变小且不会指回原模型",
+                );
+                break;
+            }
+            seen.push(current);
+            current = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ModelDowngradeRule, PromptSizeClass};
+
+    fn codes(config: &Config) -> Vec<(ConfigLintCode, String)> {
+        lint_config(config)
+            .into_iter()
+            .map(|w| (w.code, w.path))
+            .collect()
+    }
+
+    #[test]
+    fn test_default_config_is_clean() {
+        assert!(lint_config(&Config::default()).is_empty());
+    }
+
+    #[test]
+    fn test_detects_logical_problems() {
+        let mut config = Config::default();
+        config.routing.default_provider = "no-such-provider".to_string();
+        config.providers.gemini.enabled = false;
+        config
+            .credential_pool
+            .gemini
+            .push(serde_yaml::from_str("id: g1\ntoken_file: g1.json").unwrap());
+        config.routing.size_routing.rules = vec![
+            SizeRoutingRule {
+                size: None,
+                attachments: Some(true),
+                model: "gpt-4o".to_string(),
+            },
+            SizeRoutingRule {
+                size: Some(PromptSizeClass::Large),
+                attachments: Some(true),
+                model: "gemini-2.5-pro".to_string(),
+            },
+        ];
+        config.routing.time_windows.rules = vec![
+            serde_yaml::from_str("{start: '09:00', end: '18:00', provider: kiro}").unwrap(),
+            serde_yaml::from_str("{days: [mon], start: '10:00', end: '12:00', provider: gemini}")
+                .unwrap(),
+        ];
+        config
+            .routing
+            .model_aliases
+            .insert("fast".to_string(), "small".to_string());
+        config
+            .routing
+            .model_aliases
+            .insert("small".to_string(), "claude-haiku-4-5".to_string());
+        config.server.model_downgrade.rules = vec![
+            ModelDowngradeRule {
+                from: "claude-opus-*".to_string(),
+                to: "claude-sonnet-4-5".to_string(),
+            },
+            ModelDowngradeRule {
+                from: "claude-opus-4-1".to_string(),
+                to: "claude-haiku-4-5".to_string(),
+            },
+            ModelDowngradeRule {
+                from: "claude-sonnet-4-5".to_string(),
+                to: "claude-opus-4".to_string(),
+            },
+        ];
+
+        let found = codes(&config);
+        for expected in [
+            (
+                ConfigLintCode::UndefinedProvider,
+                "routing.default_provider",
+            ),
+            (ConfigLintCode::OrphanCredential, "credential_pool.gemini"),
+            (
+                ConfigLintCode::ShadowedRule,
+                "routing.size_routing.rules[1]",
+            ),
+            (
+                ConfigLintCode::ShadowedRule,
+                "routing.time_windows.rules[1]",
+            ),
+            (
+                ConfigLintCode::UnreachableFallback,
+                "routing.model_aliases.fast",
+            ),
+            (
+                ConfigLintCode::ShadowedRule,
+                "server.model_downgrade.rules[1]",
+            ),
+            (
+                ConfigLintCode::FallbackCycle,
+                "server.model_downgrade.rules[0]",
+            ),
+        ] {
+            assert!(
+                found.contains(&(expected.0, expected.1.to_string())),
+                "缺少 {expected:?}，实际: {found:?}"
+            );
+        }
+        assert!(!found
+            .iter()
+            .any(|(_, path)| path == "routing.model_aliases.small"));
+    }
+}
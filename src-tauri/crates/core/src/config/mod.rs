@@ -5,46 +5,97 @@
 
 #![allow(unused_imports)]
 
+mod audit;
+mod backup;
+mod editor;
 mod export;
 mod hot_reload;
 mod import;
+mod lint;
+mod pairing;
 mod path_utils;
+mod preset;
+mod profiles;
+mod secrets;
+mod server_features;
 mod types;
 mod yaml;
 
+pub use audit::{
+    diff_config_values, install_config_audit_sink, record_config_change,
+    record_config_value_change, ConfigAuditRecord, ConfigAuditSource, ConfigFieldChange,
+};
+pub use backup::{derive_key, BackupArchive};
+pub use editor::{
+    apply_config_patch, effective_config_value, ConfigPatchOutcome, ConfigPatchPreview,
+};
 pub use export::{ExportBundle, ExportOptions, ExportService, REDACTED_PLACEHOLDER};
 pub use hot_reload::{
-    ConfigChangeEvent as FileChangeEvent, ConfigChangeKind, FileWatcher, HotReloadManager,
-    ReloadResult,
+    validate_config, ConfigChangeEvent as FileChangeEvent, ConfigChangeKind, FileWatcher,
+    HotReloadManager, ReloadResult,
 };
-pub use import::{ImportOptions, ImportService, ValidationResult};
+pub use import::{ImportOptions, ImportService, RestoredBackup, ValidationResult};
+pub use lint::{lint_config, ConfigLintCode, ConfigLintWarning};
+pub use pairing::{CredentialPairingBundle, PairedCredential, PAIRING_PREFIX};
 pub use path_utils::{collapse_tilde, contains_tilde, expand_tilde};
+pub use preset::{PresetConflictStrategy, RoutingPreset, ROUTING_PRESET_KIND};
+pub use profiles::{
+    delete_config_profile, list_config_profiles, load_config_profile, profiles_dir,
+    save_config_profile, validate_profile_name,
+};
+pub use secrets::{externalize_config_secrets, resolve_config_secrets, MAIN_CONFIG_NAMESPACE};
+pub use server_features::{
+    AdminOidcSettings, AttributionMode, AttributionSettings, AuthLockoutSettings,
+    BurstSmoothingSettings, ChatImageFormat, ChatImageSettings, CitationSettings, ClusterSettings,
+    CodeExecutionRule, CodeExecutionSettings, ContentPolicyAction, ContentPolicyMatch,
+    ContentPolicyRule, ContentPolicySettings, ContextCacheSettings, ConverterShadowSettings,
+    CorsOriginRule, CorsSettings, DbMaintenanceSettings, DevUtilsSettings,
+    DistributedRateLimitSettings, EmbeddingCacheSettings, EndpointToggleSettings,
+    FakeStreamingSettings, KeyRotationSettings, KeychainSettings, LanDiscoverySettings,
+    MaintenanceModeSettings, MaxOutputTokenRule, MaxOutputTokenSettings, ModelConcurrencyRule,
+    ModelConcurrencySettings, ModelDowngradeRule, ModelDowngradeSettings, PeerForwardingSettings,
+    PeerInstance, PoolStorageBackend, PoolStorageSettings, PortConflictSettings,
+    PortConflictStrategy, PriorityLane, PriorityLaneSettings, PromptClassifierSettings,
+    PromptFirewallAction, PromptFirewallRule, PromptFirewallSettings, RagSettings,
+    RateLimitStoreBackend, RegionalProxySettings, RemoteControlSettings, RequestJournalSettings,
+    RequestSigningSettings, RerankMode, RerankSettings, RetentionPolicy, RetentionSettings,
+    SseHeartbeatRoute, SseHeartbeatSettings, StreamOutputCapRule, StreamOutputCapSettings,
+    StreamOutputLimit, StreamTransformSettings, TokenizerFamilyRule, TokenizerKind,
+    TokenizerSettings, TranscriptCaptureSettings, UploadDedupSettings, UpstreamHeaderSettings,
+    WarmModel, WarmPoolSettings,
+};
 pub use types::{
-    generate_secure_api_key, AmpConfig, AmpModelMapping, ApiKeyEntry, AsrCredentialEntry,
-    AsrProviderType, AutomationExecutionMode, AutomationSettings, BaiduConfig, ChannelsConfig,
-    ChatAppearanceConfig, CloudflareTunnelConfig, Config, ContentCreatorConfig,
+    generate_secure_api_key, is_strong_api_key, AccessControlSettings, AmpConfig, AmpModelMapping,
+    AnonymousStatsConfig, ApiKeyEntry, AppRole, AsrCredentialEntry, AsrProviderType,
+    AutomationExecutionMode, AutomationSettings, BaiduConfig, ChannelsConfig, ChatAppearanceConfig,
+    CloudBackupSettings, CloudBackupTarget, CloudflareTunnelConfig, Config, ContentCreatorConfig,
     ConversationSettings, CrashReportingConfig, CredentialEntry, CredentialPoolConfig,
     CustomProviderConfig, DeliveryConfig, DiscordAccountConfig, DiscordActionsConfig,
     DiscordAgentComponentsConfig, DiscordAutoPresenceConfig, DiscordBotConfig,
     DiscordChannelConfig, DiscordExecApprovalsConfig, DiscordGuildConfig, DiscordIntentsConfig,
     DiscordThreadBindingsConfig, DiscordUiComponentsConfig, DiscordUiConfig,
-    DiscordVoiceAutoJoinConfig, DiscordVoiceConfig, EndpointProvidersConfig, EnvironmentConfig,
-    EnvironmentVariableOverride, ExperimentalFeatures, FeishuAccountConfig, FeishuBotConfig,
-    FeishuGroupConfig, GatewayConfig, GatewayTunnelConfig, GeminiApiKeyEntry,
-    HintRouteSettingsEntry, HintRouterSettings, ImageGenConfig, InjectionRuleConfig,
-    InjectionSettings, LoggingConfig, MemoryAutoConfig, MemoryConfig, MemoryProfileConfig,
-    MemoryResolveConfig, MemorySourcesConfig, ModelInfo, ModelsConfig, MultiSearchConfig,
-    MultiSearchEngineEntryConfig, NativeAgentConfig, NavigationConfig, OpenAIAsrConfig,
-    PairingSettings, ProviderConfig, ProviderModelsConfig, ProvidersConfig, QuotaExceededConfig,
-    RateLimitSettings, RemoteManagementConfig, ResponseCacheSettings, RetrySettings, RoutingConfig,
-    ScreenshotChatConfig, SearchEngine, ServerConfig, ShellEnvironmentImportConfig, TaskSchedule,
-    TelegramAccountConfig, TelegramBotConfig, TelegramGroupConfig, TelegramTopicConfig, TlsConfig,
+    DiscordVoiceAutoJoinConfig, DiscordVoiceConfig, EndpointOverride, EndpointProvidersConfig,
+    EndpointSelectionConfig, EnvironmentConfig, EnvironmentVariableOverride, ExperimentalFeatures,
+    FeishuAccountConfig, FeishuBotConfig, FeishuGroupConfig, GatewayConfig, GatewayTunnelConfig,
+    GeminiApiKeyEntry, HintRouteSettingsEntry, HintRouterSettings, HmacEncoding, ImageGenConfig,
+    InjectionRuleConfig, InjectionSettings, LoggingConfig, MemoryAutoConfig, MemoryConfig,
+    MemoryProfileConfig, MemoryResolveConfig, MemorySourcesConfig, ModelDeprecationConfig,
+    ModelInfo, ModelsConfig, MultiSearchConfig, MultiSearchEngineEntryConfig, NativeAgentConfig,
+    NavigationConfig, NgrokTunnelConfig, OpenAIAsrConfig, PairingSettings, PromptSizeClass,
+    ProviderConfig, ProviderModelsConfig, ProvidersConfig, QuotaExceededConfig, RateLimitSettings,
+    RemoteManagementConfig, ResponseCacheSettings, RetrySettings, RoutingConfig, S3BackupSettings,
+    ScreenshotChatConfig, SearchEngine, ServerConfig, ShellEnvironmentImportConfig,
+    SizeRoutingConfig, SizeRoutingRule, TaskSchedule, TelegramAccountConfig, TelegramBotConfig,
+    TelegramGroupConfig, TelegramTopicConfig, TimeWindowRoutingConfig, TimeWindowRule, TlsConfig,
     ToolCallingConfig, ToolExecutionOverrideConfig, ToolExecutionPolicyConfig,
     ToolExecutionRestrictionProfileConfig, ToolExecutionSandboxProfileConfig,
-    ToolExecutionWarningPolicyConfig, UpdateCheckConfig, UserProfile, VertexApiKeyEntry,
+    ToolExecutionWarningPolicyConfig, UpdateChannel, UpdateCheckConfig, UpstreamAuthConfig,
+    UpstreamHmacAuth, UpstreamOAuthClientCredentials, UserProfile, VertexApiKeyEntry,
     VertexModelAlias, VoiceConfig, VoiceInputConfig, VoiceInstruction, VoiceOutputConfig,
-    VoiceOutputMode, VoiceProcessorConfig, WebSearchConfig, WebSearchProvider, WechatAccountConfig,
-    WechatBotConfig, WechatGroupConfig, WhisperLocalConfig, WhisperModelSize,
-    WorkspaceSandboxConfig, XunfeiConfig, DEFAULT_API_KEY,
+    VoiceOutputMode, VoiceProcessorConfig, WebDavBackupSettings, WebSearchConfig,
+    WebSearchProvider, WechatAccountConfig, WechatBotConfig, WechatGroupConfig, WhisperLocalConfig,
+    WhisperModelSize, WorkspaceSandboxConfig, XunfeiConfig, DEFAULT_API_KEY,
+};
+pub use yaml::{
+    load_config, save_config, save_config_with_source, ConfigError, ConfigManager, YamlService,
 };
-pub use yaml::{load_config, save_config, ConfigError, ConfigManager, YamlService};
@@ -0,0 +1,215 @@
+//! 凭证配对串
+//!
+//! 用于在两台机器之间迁移凭证：导出端把选中的凭证打包为 JSON，
+//! 经 Deflate 压缩后用口令加密，编码为 `lime-pair1:` 前缀的 Base64URL 字符串，
+//! 既可以直接复制粘贴，也可以渲染为二维码扫描。
+//!
+//! 二进制布局：盐（16 字节）+ Nonce（12 字节）+ 密文，密钥派生与云备份相同。
+//! OAuth 凭证的文件路径在另一台机器上无效，因此凭证文件内容会一并打包。
+
+use std::io::{Read, Write};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chrono::{DateTime, Utc};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use super::backup::derive_key;
+use crate::models::provider_pool_model::{
+    CredentialData, CredentialSource, PoolProviderType, ProviderCredential,
+};
+
+/// 配对串前缀
+pub const PAIRING_PREFIX: &str = "lime-pair1:";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+/// 配对包格式版本
+const PAIRING_FORMAT: u32 = 1;
+
+/// 配对包中的单个凭证（不含使用统计与健康状态）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairedCredential {
+    pub provider_type: PoolProviderType,
+    pub credential: CredentialData,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub check_model_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub not_supported_models: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub supported_models: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    /// OAuth 凭证文件内容
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creds_file: Option<String>,
+}
+
+impl PairedCredential {
+    /// 由本机凭证构建，`creds_file` 为 OAuth 凭证文件内容
+    pub fn from_credential(cred: &ProviderCredential, creds_file: Option<String>) -> Self {
+        Self {
+            provider_type: cred.provider_type,
+            credential: cred.credential.clone(),
+            name: cred.name.clone(),
+            check_model_name: cred.check_model_name.clone(),
+            not_supported_models: cred.not_supported_models.clone(),
+            supported_models: cred.supported_models.clone(),
+            proxy_url: cred.proxy_url.clone(),
+            creds_file,
+        }
+    }
+
+    /// 转换为待插入的新凭证，`credential` 为已替换本机文件路径的凭证数据
+    pub fn into_credential(self, credential: CredentialData) -> ProviderCredential {
+        let mut cred = ProviderCredential::new(self.provider_type, credential);
+        cred.name = self.name;
+        cred.check_model_name = self.check_model_name;
+        cred.not_supported_models = self.not_supported_models;
+        cred.supported_models = self.supported_models;
+        cred.proxy_url = self.proxy_url;
+        cred.source = CredentialSource::Imported;
+        cred
+    }
+}
+
+/// 凭证配对包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialPairingBundle {
+    pub format: u32,
+    pub created_at: DateTime<Utc>,
+    pub credentials: Vec<PairedCredential>,
+}
+
+impl CredentialPairingBundle {
+    pub fn new(credentials: Vec<PairedCredential>) -> Self {
+        Self {
+            format: PAIRING_FORMAT,
+            created_at: Utc::now(),
+            credentials,
+        }
+    }
+
+    /// 压缩、加密并编码为配对串
+    pub fn seal(&self, passphrase: &str) -> Result<String, String> {
+        if passphrase.is_empty() {
+            return Err("未设置配对口令".to_string());
+        }
+        if self.credentials.is_empty() {
+            return Err("没有可导出的凭证".to_string());
+        }
+        let json = serde_json::to_vec(self).map_err(|e| format!("序列化凭证失败: {e}"))?;
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder
+            .write_all(&json)
+            .map_err(|e| format!("压缩凭证失败: {e}"))?;
+        let plain = encoder.finish().map_err(|e| format!("压缩凭证失败: {e}"))?;
+
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let key = derive_key(passphrase, &salt);
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+        let sealed = cipher
+            .encrypt(Nonce::from_slice(&nonce), plain.as_slice())
+            .map_err(|_| "加密凭证失败".to_string())?;
+
+        let mut bytes = Vec::with_capacity(SALT_LEN + NONCE_LEN + sealed.len());
+        bytes.extend_from_slice(&salt);
+        bytes.extend_from_slice(&nonce);
+        bytes.extend_from_slice(&sealed);
+        Ok(format!("{PAIRING_PREFIX}{}", URL_SAFE_NO_PAD.encode(bytes)))
+    }
+
+    /// 解码并解密配对串（允许首尾空白与换行）
+    pub fn open(pairing: &str, passphrase: &str) -> Result<Self, String> {
+        let encoded: String = pairing
+            .trim()
+            .strip_prefix(PAIRING_PREFIX)
+            .ok_or("不是有效的 Lime 配对串")?
+            .split_whitespace()
+            .collect();
+        let bytes = URL_SAFE_NO_PAD
+            .decode(encoded.trim_end_matches('='))
+            .map_err(|_| "配对串已损坏".to_string())?;
+        let header_len = SALT_LEN + NONCE_LEN;
+        if bytes.len() <= header_len {
+            return Err("配对串已损坏".to_string());
+        }
+
+        let key = derive_key(passphrase, &bytes[..SALT_LEN]);
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+        let plain = cipher
+            .decrypt(
+                Nonce::from_slice(&bytes[SALT_LEN..header_len]),
+                &bytes[header_len..],
+            )
+            .map_err(|_| "解密配对串失败：口令错误或内容已损坏".to_string())?;
+
+        let mut json = Vec::new();
+        DeflateDecoder::new(plain.as_slice())
+            .read_to_end(&mut json)
+            .map_err(|e| format!("解压凭证失败: {e}"))?;
+        let bundle: Self =
+            serde_json::from_slice(&json).map_err(|e| format!("凭证格式无效: {e}"))?;
+        if bundle.format > PAIRING_FORMAT {
+            return Err(format!(
+                "配对串版本 {} 高于当前支持的版本 {}，请升级后重试",
+                bundle.format, PAIRING_FORMAT
+            ));
+        }
+        Ok(bundle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pairing_roundtrip() {
+        let mut cred = ProviderCredential::new(
+            PoolProviderType::Kiro,
+            CredentialData::KiroOAuth {
+                creds_file_path: "/home/a/.lime/credentials/kiro_1.json".to_string(),
+            },
+        );
+        cred.name = Some("工作账号".to_string());
+        cred.usage_count = 42;
+        let token = r#"{"accessToken":"at","refreshToken":"rt"}"#.to_string();
+        let bundle = CredentialPairingBundle::new(vec![PairedCredential::from_credential(
+            &cred,
+            Some(token.clone()),
+        )]);
+
+        let pairing = bundle.seal("pass phrase").unwrap();
+        assert!(pairing.starts_with(PAIRING_PREFIX));
+        assert!(!pairing.contains("refreshToken"));
+
+        // 二维码扫描或复制时可能带上换行
+        let wrapped = format!("  {}\n{}  ", &pairing[..40], &pairing[40..]);
+        let opened = CredentialPairingBundle::open(&wrapped, "pass phrase").unwrap();
+        assert_eq!(opened.created_at, bundle.created_at);
+        assert_eq!(opened.credentials.len(), 1);
+        assert_eq!(opened.credentials[0].creds_file.as_deref(), Some(&*token));
+
+        let imported = opened.credentials[0]
+            .clone()
+            .into_credential(cred.credential.clone());
+        assert_eq!(imported.name.as_deref(), Some("工作账号"));
+        assert_eq!(imported.usage_count, 0);
+        assert_eq!(imported.source, CredentialSource::Imported);
+        assert_ne!(imported.uuid, cred.uuid);
+
+        assert!(CredentialPairingBundle::open(&pairing, "wrong").is_err());
+        assert!(CredentialPairingBundle::open("lime-pair1:abc", "pass phrase").is_err());
+        assert!(bundle.seal("").is_err());
+    }
+}
@@ -0,0 +1,110 @@
+//! 路由预设
+//!
+//! 把模型别名、按规模 / 按时段路由规则和参数注入规则打包为可分享的 JSON 文件，
+//! 与完整配置导出包相互独立：预设不含 Provider、凭证等环境相关内容，
+//! 可以直接分享给他人。导入时与现有条目的冲突按 [`PresetConflictStrategy`] 处理，
+//! 见 `ImportService::import_preset`。
+
+use super::export::ExportError;
+use super::import::ImportError;
+use super::types::{InjectionRuleConfig, SizeRoutingRule, TimeWindowRule};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 预设文件的类型标识，用于与完整导出包区分
+pub const ROUTING_PRESET_KIND: &str = "lime_routing_preset";
+
+/// 路由预设
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RoutingPreset {
+    /// 类型标识，固定为 [`ROUTING_PRESET_KIND`]
+    pub kind: String,
+    /// 预设格式版本号
+    pub version: String,
+    /// 预设名称
+    pub name: String,
+    /// 说明
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// 导出时间
+    pub exported_at: DateTime<Utc>,
+    /// 导出时的应用版本
+    #[serde(default)]
+    pub app_version: String,
+    /// 模型别名映射
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub model_aliases: BTreeMap<String, String>,
+    /// 按请求规模路由规则
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub size_rules: Vec<SizeRoutingRule>,
+    /// 按时段路由规则
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub time_window_rules: Vec<TimeWindowRule>,
+    /// 参数注入规则
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub injection_rules: Vec<InjectionRuleConfig>,
+}
+
+impl RoutingPreset {
+    /// 当前预设格式版本
+    pub const CURRENT_VERSION: &'static str = "1.0";
+
+    /// 创建空预设
+    pub fn new(name: &str, description: &str, app_version: &str) -> Self {
+        Self {
+            kind: ROUTING_PRESET_KIND.to_string(),
+            version: Self::CURRENT_VERSION.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            exported_at: Utc::now(),
+            app_version: app_version.to_string(),
+            model_aliases: BTreeMap::new(),
+            size_rules: Vec::new(),
+            time_window_rules: Vec::new(),
+            injection_rules: Vec::new(),
+        }
+    }
+
+    /// 预设中的条目总数
+    pub fn item_count(&self) -> usize {
+        self.model_aliases.len()
+            + self.size_rules.len()
+            + self.time_window_rules.len()
+            + self.injection_rules.len()
+    }
+
+    /// 序列化为 JSON 字符串
+    pub fn to_json(&self) -> Result<String, ExportError> {
+        serde_json::to_string_pretty(self).map_err(|e| ExportError::SerializeError(e.to_string()))
+    }
+
+    /// 从 JSON 字符串解析，类型标识不符时返回错误
+    pub fn from_json(json: &str) -> Result<Self, ImportError> {
+        let preset: Self =
+            serde_json::from_str(json).map_err(|e| ImportError::FormatError(e.to_string()))?;
+        if preset.kind != ROUTING_PRESET_KIND {
+            return Err(ImportError::FormatError(format!(
+                "不是路由预设文件（kind: {}）",
+                preset.kind
+            )));
+        }
+        Ok(preset)
+    }
+}
+
+/// 导入预设时与现有条目冲突的处理方式
+///
+/// 冲突指同名模型别名、同 ID 注入规则、同名时段规则，或匹配条件相同的规模规则；
+/// 与现有条目完全相同的视为重复，总是跳过。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresetConflictStrategy {
+    /// 以新名称导入（规模规则没有名称，按跳过处理）
+    Rename,
+    /// 用预设中的条目覆盖现有条目
+    Overwrite,
+    /// 保留现有条目
+    #[default]
+    Skip,
+}
@@ -0,0 +1,141 @@
+//! 配置方案
+//!
+//! 将完整配置保存为命名快照（`<配置目录>/profiles/<name>.yaml`），
+//! 可在托盘或设置页中一键切换。
+
+use std::path::{Path, PathBuf};
+
+use super::secrets::externalize_config_secrets;
+use super::types::Config;
+use super::yaml::{ConfigError, ConfigManager};
+use crate::secret_store::secret_store;
+
+/// 配置方案目录
+pub fn profiles_dir() -> PathBuf {
+    ConfigManager::default_config_path()
+        .parent()
+        .map(|dir| dir.join("profiles"))
+        .unwrap_or_else(|| PathBuf::from("profiles"))
+}
+
+/// 校验方案名称（仅允许字母、数字、`-`、`_`，避免路径穿越）
+pub fn validate_profile_name(name: &str) -> Result<(), ConfigError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(ConfigError::ValidationError(format!(
+            "无效的配置方案名称: {name}（仅允许字母、数字、- 和 _）"
+        )))
+    }
+}
+
+/// 配置方案的钥匙串命名空间
+fn profile_namespace(name: &str) -> String {
+    format!("profile:{name}")
+}
+
+fn profile_path(dir: &Path, name: &str) -> Result<PathBuf, ConfigError> {
+    validate_profile_name(name)?;
+    Ok(dir.join(format!("{name}.yaml")))
+}
+
+/// 列出目录中的配置方案（按名称排序）
+pub fn list_profiles_in(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("yaml"))
+        .filter_map(|path| path.file_stem()?.to_str().map(str::to_string))
+        .filter(|name| validate_profile_name(name).is_ok())
+        .collect();
+    names.sort();
+    names
+}
+
+/// 读取配置方案
+pub fn load_profile_from(dir: &Path, name: &str) -> Result<Config, ConfigError> {
+    let path = profile_path(dir, name)?;
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| ConfigError::ReadError(format!("{}: {e}", path.display())))?;
+    ConfigManager::parse_yaml_in(&content, &profile_namespace(name))
+}
+
+/// 保存配置方案（已存在时覆盖）
+pub fn save_profile_to(dir: &Path, name: &str, config: &Config) -> Result<(), ConfigError> {
+    let path = profile_path(dir, name)?;
+    std::fs::create_dir_all(dir).map_err(|e| ConfigError::WriteError(e.to_string()))?;
+    let yaml = ConfigManager::to_yaml(config)?;
+    std::fs::write(&path, yaml).map_err(|e| ConfigError::WriteError(e.to_string()))
+}
+
+/// 删除配置方案，返回是否存在
+pub fn delete_profile_in(dir: &Path, name: &str) -> Result<bool, ConfigError> {
+    let path = profile_path(dir, name)?;
+    if !path.exists() {
+        return Ok(false);
+    }
+    std::fs::remove_file(&path).map_err(|e| ConfigError::WriteError(e.to_string()))?;
+    Ok(true)
+}
+
+/// 列出默认目录中的配置方案
+pub fn list_config_profiles() -> Vec<String> {
+    list_profiles_in(&profiles_dir())
+}
+
+/// 读取默认目录中的配置方案
+pub fn load_config_profile(name: &str) -> Result<Config, ConfigError> {
+    load_profile_from(&profiles_dir(), name)
+}
+
+/// 保存配置方案到默认目录
+pub fn save_config_profile(name: &str, config: &Config) -> Result<(), ConfigError> {
+    validate_profile_name(name)?;
+    let stored = externalize_config_secrets(secret_store(), config, &profile_namespace(name));
+    save_profile_to(&profiles_dir(), name, &stored)
+}
+
+/// 从默认目录删除配置方案
+pub fn delete_config_profile(name: &str) -> Result<bool, ConfigError> {
+    delete_profile_in(&profiles_dir(), name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_reject_unsafe_profile_names() {
+        assert!(validate_profile_name("work-2").is_ok());
+        assert!(validate_profile_name("").is_err());
+        assert!(validate_profile_name("../config").is_err());
+        assert!(validate_profile_name("a b").is_err());
+    }
+
+    #[test]
+    fn should_round_trip_profiles() {
+        let dir = tempfile::tempdir().expect("创建临时目录失败");
+        let mut config = Config::default();
+        config.server.port = 9123;
+
+        save_profile_to(dir.path(), "work", &config).expect("保存应成功");
+        save_profile_to(dir.path(), "home", &Config::default()).expect("保存应成功");
+        std::fs::write(dir.path().join("notes.txt"), "ignored").expect("写入失败");
+
+        assert_eq!(list_profiles_in(dir.path()), vec!["home", "work"]);
+        let loaded = load_profile_from(dir.path(), "work").expect("读取应成功");
+        assert_eq!(loaded.server.port, 9123);
+
+        assert!(delete_profile_in(dir.path(), "work").expect("删除应成功"));
+        assert!(!delete_profile_in(dir.path(), "work").expect("删除应成功"));
+        assert_eq!(list_profiles_in(dir.path()), vec!["home"]);
+    }
+}
@@ -0,0 +1,317 @@
+//! 配置文件中的密钥与钥匙串之间的转换
+//!
+//! 内存中的 [`Config`] 始终保存明文；写盘前通过 [`externalize_config_secrets`]
+//! 将密钥移入钥匙串并替换为引用，读取后通过 [`resolve_config_secrets`] 还原。
+//! 旧配置中的明文密钥会在下次保存时自动迁移。
+//! 无法解析的引用替换为随机占位值（失败即关闭），写盘时还原为原引用。
+//! 字段只能引用其自身的钥匙串条目（`<命名空间>:<字段>`），指向其他条目的引用同样视为无法解析，
+//! 避免导入的配置借引用读出其他密钥。
+
+use std::collections::HashSet;
+
+use super::types::{generate_secure_api_key, Config};
+use crate::secret_store::{is_keychain_ref, SecretStore, KEYCHAIN_REF_PREFIX};
+
+/// 主配置的钥匙串命名空间
+pub const MAIN_CONFIG_NAMESPACE: &str = "config";
+
+/// 无法解析的钥匙串引用的占位前缀，格式为 `unresolved:<随机值>:<原引用>`
+const UNRESOLVED_PREFIX: &str = "unresolved:";
+
+/// 生成不可用的占位密钥，避免引用字符串本身被当作密钥使用
+fn unresolved_placeholder(reference: &str) -> String {
+    format!(
+        "{UNRESOLVED_PREFIX}{}:{reference}",
+        generate_secure_api_key()
+    )
+}
+
+/// 从占位密钥中取回原引用
+fn original_reference(value: &str) -> Option<&str> {
+    value
+        .strip_prefix(UNRESOLVED_PREFIX)?
+        .split_once(':')
+        .map(|(_, reference)| reference)
+}
+
+/// 遍历配置中的全部密钥字段（账户后缀, 字段）
+fn for_each_secret(config: &mut Config, mut visit: impl FnMut(String, &mut String)) {
+    visit("server.api_key".to_string(), &mut config.server.api_key);
+    if let Some(key) = config.providers.openai.api_key.as_mut() {
+        visit("providers.openai.api_key".to_string(), key);
+    }
+    if let Some(key) = config.providers.claude.api_key.as_mut() {
+        visit("providers.claude.api_key".to_string(), key);
+    }
+    for entry in config.credential_pool.openai.iter_mut() {
+        visit(
+            format!("credential_pool.openai.{}", entry.id),
+            &mut entry.api_key,
+        );
+    }
+    for entry in config.credential_pool.claude.iter_mut() {
+        visit(
+            format!("credential_pool.claude.{}", entry.id),
+            &mut entry.api_key,
+        );
+    }
+    for (provider, auth) in config.providers.upstream_auth.iter_mut() {
+        for (name, value) in auth.headers.iter_mut() {
+            visit(
+                format!("providers.upstream_auth.{}.headers.{}", provider, name),
+                value,
+            );
+        }
+        if let Some(hmac) = auth.hmac.as_mut() {
+            visit(
+                format!("providers.upstream_auth.{}.hmac.secret", provider),
+                &mut hmac.secret,
+            );
+        }
+        if let Some(oauth) = auth.oauth_client_credentials.as_mut() {
+            visit(
+                format!(
+                    "providers.upstream_auth.{}.oauth_client_credentials.client_secret",
+                    provider
+                ),
+                &mut oauth.client_secret,
+            );
+        }
+    }
+    for peer in config.server.peer_forwarding.peers.iter_mut() {
+        visit(
+            format!("server.peer_forwarding.peers.{}.api_key", peer.base_url),
+            &mut peer.api_key,
+        );
+    }
+    let pool_storage = &mut config.server.pool_storage;
+    visit("server.pool_storage.url".to_string(), &mut pool_storage.url);
+    visit(
+        "server.pool_storage.encryption_key".to_string(),
+        &mut pool_storage.encryption_key,
+    );
+    let backup = &mut config.cloud_backup;
+    visit(
+        "cloud_backup.passphrase".to_string(),
+        &mut backup.passphrase,
+    );
+    visit(
+        "cloud_backup.s3.secret_access_key".to_string(),
+        &mut backup.s3.secret_access_key,
+    );
+    visit(
+        "cloud_backup.webdav.password".to_string(),
+        &mut backup.webdav.password,
+    );
+}
+
+/// 配置中全部密钥字段的路径（以 `.` 分隔，供审计脱敏）
+pub(crate) fn secret_field_paths(config: &Config) -> HashSet<String> {
+    let mut paths = HashSet::new();
+    for_each_secret(&mut config.clone(), |field, _| {
+        paths.insert(field);
+    });
+    paths
+}
+
+/// 解析配置中的钥匙串引用，返回是否存在待迁移的密钥
+///
+/// 启用钥匙串时明文密钥待迁入；禁用时引用待写回明文。`namespace` 为写盘时使用的命名空间，
+/// 只接受 `keychain:<namespace>:<字段>` 形式的引用。
+/// 解析失败的字段替换为随机占位值，使其无法通过认证或被发往上游。
+pub fn resolve_config_secrets(store: &SecretStore, config: &mut Config, namespace: &str) -> bool {
+    let migrate_plaintext = store.is_active();
+    let mut pending = false;
+    for_each_secret(config, |field, value| {
+        if let Some(reference) = original_reference(value) {
+            *value = reference.to_string();
+        }
+        if value.is_empty() {
+            return;
+        }
+        if !is_keychain_ref(value) {
+            pending |= migrate_plaintext;
+            return;
+        }
+        let expected = format!("{KEYCHAIN_REF_PREFIX}{namespace}:{field}");
+        let resolved = if *value == expected {
+            store.resolve(value)
+        } else {
+            Err(format!("引用 {value} 不属于该字段"))
+        };
+        match resolved {
+            Ok(secret) => {
+                *value = secret;
+                // 已禁用钥匙串时需要写回明文
+                pending |= !store.is_active();
+            }
+            Err(e) => {
+                tracing::error!("[CONFIG] 无法解析 {}，该密钥已停用: {}", field, e);
+                *value = unresolved_placeholder(value);
+            }
+        }
+    });
+    pending
+}
+
+/// 生成写盘用的配置副本（密钥替换为钥匙串引用）
+pub fn externalize_config_secrets(store: &SecretStore, config: &Config, namespace: &str) -> Config {
+    let mut stored = config.clone();
+    let active = store.is_active();
+    for_each_secret(&mut stored, |field, value| {
+        // 保留原引用，避免覆盖钥匙串中可能恢复的条目
+        if let Some(reference) = original_reference(value) {
+            *value = reference.to_string();
+        } else if active && !value.is_empty() && !is_keychain_ref(value) {
+            *value = store.store_or_keep(&format!("{namespace}:{field}"), value);
+        }
+    });
+    stored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{
+        ApiKeyEntry, HmacEncoding, PeerInstance, UpstreamAuthConfig, UpstreamHmacAuth,
+    };
+
+    fn config_with_secrets() -> Config {
+        let mut config = Config::default();
+        config.server.api_key = "lime-inbound-key".to_string();
+        config.providers.openai.api_key = Some("sk-openai".to_string());
+        config.credential_pool.claude.push(ApiKeyEntry {
+            id: "c1".to_string(),
+            api_key: "sk-ant-1".to_string(),
+            base_url: None,
+            disabled: false,
+            proxy_url: None,
+        });
+        config.providers.upstream_auth.insert(
+            "openai".to_string(),
+            UpstreamAuthConfig {
+                headers: [("x-gateway-key".to_string(), "gw-static".to_string())].into(),
+                hmac: Some(UpstreamHmacAuth {
+                    secret: "hmac-secret".to_string(),
+                    key_id: None,
+                    key_id_header: None,
+                    signature_header: "x-signature".to_string(),
+                    timestamp_header: "x-timestamp".to_string(),
+                    encoding: HmacEncoding::Hex,
+                }),
+                ..Default::default()
+            },
+        );
+        config.server.peer_forwarding.peers.push(PeerInstance {
+            name: "peer-a".to_string(),
+            base_url: "http://10.0.0.6:8999".to_string(),
+            api_key: "peer-secret".to_string(),
+        });
+        config
+    }
+
+    #[test]
+    fn should_round_trip_secrets_through_keychain() {
+        let store = SecretStore::in_memory();
+        let config = config_with_secrets();
+
+        let stored = externalize_config_secrets(&store, &config, MAIN_CONFIG_NAMESPACE);
+        let yaml = serde_yaml::to_string(&stored).expect("序列化失败");
+        assert!(!yaml.contains("lime-inbound-key"));
+        assert!(!yaml.contains("sk-openai"));
+        assert!(!yaml.contains("sk-ant-1"));
+        assert!(!yaml.contains("gw-static"));
+        assert!(!yaml.contains("hmac-secret"));
+        assert!(!yaml.contains("peer-secret"));
+        assert_eq!(stored.server.api_key, "keychain:config:server.api_key");
+
+        let mut loaded = stored.clone();
+        assert!(!resolve_config_secrets(
+            &store,
+            &mut loaded,
+            MAIN_CONFIG_NAMESPACE
+        ));
+        assert_eq!(loaded.server.api_key, config.server.api_key);
+        assert_eq!(loaded.credential_pool.claude[0].api_key, "sk-ant-1");
+        assert_eq!(
+            loaded.server.peer_forwarding.peers,
+            config.server.peer_forwarding.peers
+        );
+        assert_eq!(
+            loaded.providers.upstream_auth,
+            config.providers.upstream_auth
+        );
+    }
+
+    #[test]
+    fn should_flag_plaintext_for_migration() {
+        let store = SecretStore::in_memory();
+        let mut config = config_with_secrets();
+        assert!(resolve_config_secrets(
+            &store,
+            &mut config,
+            MAIN_CONFIG_NAMESPACE
+        ));
+
+        let mut disabled = externalize_config_secrets(&store, &config, MAIN_CONFIG_NAMESPACE);
+        store.set_enabled(false);
+        assert!(resolve_config_secrets(
+            &store,
+            &mut disabled,
+            MAIN_CONFIG_NAMESPACE
+        ));
+        assert_eq!(disabled.server.api_key, "lime-inbound-key");
+        assert_eq!(
+            externalize_config_secrets(&store, &disabled, MAIN_CONFIG_NAMESPACE)
+                .server
+                .api_key,
+            "lime-inbound-key"
+        );
+    }
+
+    #[test]
+    fn should_disable_secret_when_reference_cannot_be_resolved() {
+        let store = SecretStore::in_memory();
+        let mut config = Config::default();
+        config.server.api_key = "keychain:config:server.api_key".to_string();
+
+        assert!(!resolve_config_secrets(
+            &store,
+            &mut config,
+            MAIN_CONFIG_NAMESPACE
+        ));
+        assert_ne!(config.server.api_key, "keychain:config:server.api_key");
+        assert!(config.server.api_key.starts_with(UNRESOLVED_PREFIX));
+
+        let stored = externalize_config_secrets(&store, &config, MAIN_CONFIG_NAMESPACE);
+        assert_eq!(stored.server.api_key, "keychain:config:server.api_key");
+        assert!(store.resolve(&stored.server.api_key).is_err());
+    }
+
+    #[test]
+    fn should_only_resolve_references_to_own_field() {
+        let store = SecretStore::in_memory();
+        let stored =
+            externalize_config_secrets(&store, &config_with_secrets(), MAIN_CONFIG_NAMESPACE);
+        store
+            .get_or_create("database:field-key", || Ok("data-key".to_string()))
+            .expect("应写入钥匙串");
+
+        let mut config = stored.clone();
+        config.server.api_key = "keychain:database:field-key".to_string();
+        config.providers.openai.api_key = Some("keychain:config:server.api_key".to_string());
+        resolve_config_secrets(&store, &mut config, MAIN_CONFIG_NAMESPACE);
+        assert!(config.server.api_key.starts_with(UNRESOLVED_PREFIX));
+        assert!(config
+            .providers
+            .openai
+            .api_key
+            .as_deref()
+            .is_some_and(|key| key.starts_with(UNRESOLVED_PREFIX)));
+
+        // 其他命名空间（如配置方案）的引用同样不能解析
+        let mut profile = stored;
+        resolve_config_secrets(&store, &mut profile, "profile:work");
+        assert!(profile.server.api_key.starts_with(UNRESOLVED_PREFIX));
+    }
+}
@@ -0,0 +1,2153 @@
+//! 服务器扩展功能配置
+//!
+//! 存放 `server.*` 下新增功能的配置结构，避免继续膨胀 `types.rs`。
+
+use crate::models::injection_types::pattern_matches;
+use serde::{Deserialize, Serialize};
+
+/// 嵌入缓存配置
+///
+/// 按 (模型, 规范化文本哈希) 持久化 `/v1/embeddings` 的结果，
+/// RAG 场景下重复分块不会再次请求上游。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EmbeddingCacheSettings {
+    /// 是否启用嵌入缓存
+    #[serde(default = "default_embedding_cache_enabled")]
+    pub enabled: bool,
+    /// 最大持久化条目数（超出后按最近命中时间淘汰）
+    #[serde(default = "default_embedding_cache_max_entries")]
+    pub max_entries: usize,
+    /// 条目保留天数（0 表示永不过期）
+    #[serde(default = "default_embedding_cache_ttl_days")]
+    pub ttl_days: u32,
+}
+
+fn default_embedding_cache_enabled() -> bool {
+    true
+}
+
+fn default_embedding_cache_max_entries() -> usize {
+    100_000
+}
+
+fn default_embedding_cache_ttl_days() -> u32 {
+    30
+}
+
+impl Default for EmbeddingCacheSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_embedding_cache_enabled(),
+            max_entries: default_embedding_cache_max_entries(),
+            ttl_days: default_embedding_cache_ttl_days(),
+        }
+    }
+}
+
+/// 本地 RAG 配置
+///
+/// 启用后提供 `/v1/rag/documents`（写入 / 删除）与 `/v1/rag/query`，
+/// 文档向量存储在本地 SQLite 中，检索走按集合构建的内存 HNSW 索引。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RagSettings {
+    /// 是否启用本地向量存储与检索端点
+    #[serde(default)]
+    pub enabled: bool,
+    /// 生成向量使用的 Provider（需为 OpenAI 兼容凭证）
+    #[serde(default = "default_rag_embedding_provider")]
+    pub embedding_provider: String,
+    /// 生成向量使用的模型
+    #[serde(default = "default_rag_embedding_model")]
+    pub embedding_model: String,
+    /// 未指定集合时使用的默认集合
+    #[serde(default = "default_rag_collection")]
+    pub default_collection: String,
+    /// 默认返回的文档数
+    #[serde(default = "default_rag_top_k")]
+    pub top_k: usize,
+    /// 最低相似度阈值（低于该值的结果会被丢弃）
+    #[serde(default)]
+    pub min_score: f32,
+    /// 是否自动为 `/v1/chat/completions` 与 `/v1/messages` 注入检索上下文
+    ///
+    /// 关闭时仍可通过请求头 `x-lime-rag: on` 按请求开启。
+    #[serde(default)]
+    pub auto_augment: bool,
+    /// 注入上下文的最大字符数
+    #[serde(default = "default_rag_max_context_chars")]
+    pub max_context_chars: usize,
+}
+
+fn default_rag_embedding_provider() -> String {
+    "openai".to_string()
+}
+
+fn default_rag_embedding_model() -> String {
+    "text-embedding-3-small".to_string()
+}
+
+fn default_rag_collection() -> String {
+    "default".to_string()
+}
+
+fn default_rag_top_k() -> usize {
+    4
+}
+
+fn default_rag_max_context_chars() -> usize {
+    4000
+}
+
+impl Default for RagSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            embedding_provider: default_rag_embedding_provider(),
+            embedding_model: default_rag_embedding_model(),
+            default_collection: default_rag_collection(),
+            top_k: default_rag_top_k(),
+            min_score: 0.0,
+            auto_augment: false,
+            max_context_chars: default_rag_max_context_chars(),
+        }
+    }
+}
+
+/// 重排序实现方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RerankMode {
+    /// 优先调用上游 `/rerank`，上游不支持时回退到 LLM 打分
+    #[default]
+    Auto,
+    /// 仅调用上游 `/rerank`
+    Native,
+    /// 始终使用 LLM 打分提示词模拟
+    Llm,
+}
+
+/// 重排序配置（`/v1/rerank`）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RerankSettings {
+    /// 未指定 X-Provider-Id 时使用的 Provider
+    #[serde(default = "default_rerank_provider")]
+    pub provider: String,
+    /// 实现方式
+    #[serde(default)]
+    pub mode: RerankMode,
+    /// LLM 模拟打分使用的聊天模型
+    #[serde(default = "default_rerank_llm_model")]
+    pub llm_model: String,
+    /// LLM 模拟模式下单次请求允许的最大文档数
+    #[serde(default = "default_rerank_max_documents")]
+    pub max_documents: usize,
+}
+
+fn default_rerank_provider() -> String {
+    "openai".to_string()
+}
+
+fn default_rerank_llm_model() -> String {
+    "gpt-4o-mini".to_string()
+}
+
+fn default_rerank_max_documents() -> usize {
+    100
+}
+
+impl Default for RerankSettings {
+    fn default() -> Self {
+        Self {
+            provider: default_rerank_provider(),
+            mode: RerankMode::default(),
+            llm_model: default_rerank_llm_model(),
+            max_documents: default_rerank_max_documents(),
+        }
+    }
+}
+
+/// 开发辅助端点配置（`/utils/summarize-diff`、`/utils/commit-message`）
+///
+/// 端点把原始文本交给提示词模板与低成本模型处理，方便 shell 脚本直接调用。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DevUtilsSettings {
+    /// 是否启用
+    #[serde(default = "default_dev_utils_enabled")]
+    pub enabled: bool,
+    /// 未指定 X-Provider-Id 时使用的 Provider
+    #[serde(default = "default_rerank_provider")]
+    pub provider: String,
+    /// 未在请求中指定模型时使用的模型
+    #[serde(default = "default_rerank_llm_model")]
+    pub model: String,
+    /// 输入的最大字符数，超出部分截断
+    #[serde(default = "default_dev_utils_max_input_chars")]
+    pub max_input_chars: usize,
+    /// 总结 diff 的系统提示词
+    #[serde(default = "default_summarize_diff_prompt")]
+    pub summarize_diff_prompt: String,
+    /// 生成提交信息的系统提示词
+    #[serde(default = "default_commit_message_prompt")]
+    pub commit_message_prompt: String,
+}
+
+fn default_dev_utils_enabled() -> bool {
+    true
+}
+
+fn default_dev_utils_max_input_chars() -> usize {
+    60_000
+}
+
+fn default_summarize_diff_prompt() -> String {
+    "You are a senior engineer reviewing a code change. Summarize the following diff in a few \
+     concise bullet points: what changed and why it matters. Do not restate the diff line by line."
+        .to_string()
+}
+
+fn default_commit_message_prompt() -> String {
+    "Write a git commit message for the following diff. Use an imperative subject line of at \
+     most 72 characters, then a blank line and a short body only if the change needs explanation. \
+     Output only the commit message, without quotes or code fences."
+        .to_string()
+}
+
+impl Default for DevUtilsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_dev_utils_enabled(),
+            provider: default_rerank_provider(),
+            model: default_rerank_llm_model(),
+            max_input_chars: default_dev_utils_max_input_chars(),
+            summarize_diff_prompt: default_summarize_diff_prompt(),
+            commit_message_prompt: default_commit_message_prompt(),
+        }
+    }
+}
+
+/// CORS 单个来源规则
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CorsOriginRule {
+    /// 来源，支持 `*` 通配（如 `http://192.168.*.*:3000`）
+    pub origin: String,
+    /// 是否允许该来源发起私有网络访问（Private Network Access 预检）
+    #[serde(default)]
+    pub allow_private_network: bool,
+    /// 该来源的预检缓存时长（秒），未设置时使用全局值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_secs: Option<u64>,
+}
+
+/// CORS 配置
+///
+/// 内置的桌面端来源（Tauri、本地开发服务器）始终允许，
+/// `origins` 用于追加局域网 Web UI 等来源。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CorsSettings {
+    /// 额外允许的来源
+    #[serde(default)]
+    pub origins: Vec<CorsOriginRule>,
+    /// 预检缓存时长（秒，`Access-Control-Max-Age`）
+    #[serde(default = "default_cors_max_age_secs")]
+    pub max_age_secs: u64,
+}
+
+fn default_cors_max_age_secs() -> u64 {
+    7200
+}
+
+impl Default for CorsSettings {
+    fn default() -> Self {
+        Self {
+            origins: Vec::new(),
+            max_age_secs: default_cors_max_age_secs(),
+        }
+    }
+}
+
+/// 端口冲突处理策略
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PortConflictStrategy {
+    /// 直接报错，不做任何处理
+    Fail,
+    /// 端口被另一个 Lime 实例占用时，通知其优雅退出后接管端口
+    Takeover,
+    /// 自动选择后续可用端口
+    #[default]
+    Fallback,
+}
+
+/// 启动时端口冲突处理配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PortConflictSettings {
+    /// 处理策略
+    #[serde(default)]
+    pub strategy: PortConflictStrategy,
+    /// 回退模式下向后尝试的端口数量
+    #[serde(default = "default_port_fallback_attempts")]
+    pub fallback_attempts: u16,
+    /// 接管模式下等待旧实例释放端口的超时（毫秒）
+    #[serde(default = "default_port_takeover_timeout_ms")]
+    pub takeover_timeout_ms: u64,
+}
+
+fn default_port_fallback_attempts() -> u16 {
+    20
+}
+
+fn default_port_takeover_timeout_ms() -> u64 {
+    5000
+}
+
+impl Default for PortConflictSettings {
+    fn default() -> Self {
+        Self {
+            strategy: PortConflictStrategy::default(),
+            fallback_attempts: default_port_fallback_attempts(),
+            takeover_timeout_ms: default_port_takeover_timeout_ms(),
+        }
+    }
+}
+
+/// 局域网发现与移动端配对配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LanDiscoverySettings {
+    /// 是否通过 mDNS 广播网关地址（仅在监听非回环地址时生效）
+    #[serde(default)]
+    pub enabled: bool,
+    /// mDNS 实例名称（为空时使用主机名）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance_name: Option<String>,
+    /// 配对生成的受限 API Key 有效期（小时，0 表示永不过期）
+    #[serde(default = "default_pairing_key_ttl_hours")]
+    pub pairing_key_ttl_hours: u64,
+}
+
+fn default_pairing_key_ttl_hours() -> u64 {
+    720
+}
+
+impl Default for LanDiscoverySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            instance_name: None,
+            pairing_key_ttl_hours: default_pairing_key_ttl_hours(),
+        }
+    }
+}
+
+/// HMAC 请求签名配置（公网暴露时替代明文 API Key 的认证方式）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RequestSigningSettings {
+    /// 是否接受签名请求
+    #[serde(default)]
+    pub enabled: bool,
+    /// 签名密钥（客户端与服务器共享，不随请求传输）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub secret: String,
+    /// 允许的时间偏差（秒），超出视为过期请求
+    #[serde(default = "default_signing_max_skew_secs")]
+    pub max_skew_secs: u64,
+    /// 经隧道/反向代理转发的请求必须签名
+    #[serde(default)]
+    pub require_for_forwarded: bool,
+}
+
+fn default_signing_max_skew_secs() -> u64 {
+    300
+}
+
+impl Default for RequestSigningSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            secret: String::new(),
+            max_skew_secs: default_signing_max_skew_secs(),
+            require_for_forwarded: false,
+        }
+    }
+}
+
+/// 远程控制接口配置（供 CI 等外部自动化触发配置重载、切换 Provider、轮换凭证）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RemoteControlSettings {
+    /// 是否启用 `/admin/remote/*` 接口
+    #[serde(default)]
+    pub enabled: bool,
+    /// HMAC 签名密钥（独立于请求签名密钥，仅用于远程控制）
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub secret: String,
+    /// 允许的时间偏差（秒），超出视为过期请求
+    #[serde(default = "default_signing_max_skew_secs")]
+    pub max_skew_secs: u64,
+}
+
+impl Default for RemoteControlSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            secret: String::new(),
+            max_skew_secs: default_signing_max_skew_secs(),
+        }
+    }
+}
+
+/// 认证失败锁定配置（按来源 IP 防暴力破解）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuthLockoutSettings {
+    /// 是否启用
+    #[serde(default = "default_auth_lockout_enabled")]
+    pub enabled: bool,
+    /// 统计窗口内允许的最大失败次数
+    #[serde(default = "default_lockout_max_failures")]
+    pub max_failures: u32,
+    /// 失败次数统计窗口（秒）
+    #[serde(default = "default_lockout_window_secs")]
+    pub window_secs: u64,
+    /// 首次锁定时长（秒），之后每次锁定翻倍
+    #[serde(default = "default_lockout_base_secs")]
+    pub base_lockout_secs: u64,
+    /// 锁定时长上限（秒）
+    #[serde(default = "default_lockout_max_secs")]
+    pub max_lockout_secs: u64,
+}
+
+fn default_auth_lockout_enabled() -> bool {
+    true
+}
+
+fn default_lockout_max_failures() -> u32 {
+    5
+}
+
+fn default_lockout_window_secs() -> u64 {
+    300
+}
+
+fn default_lockout_base_secs() -> u64 {
+    60
+}
+
+fn default_lockout_max_secs() -> u64 {
+    3600
+}
+
+impl Default for AuthLockoutSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_auth_lockout_enabled(),
+            max_failures: default_lockout_max_failures(),
+            window_secs: default_lockout_window_secs(),
+            base_lockout_secs: default_lockout_base_secs(),
+            max_lockout_secs: default_lockout_max_secs(),
+        }
+    }
+}
+
+/// 钥匙串密钥存储配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeychainSettings {
+    /// 将 API Key 与 OAuth Refresh Token 保存到系统钥匙串（关闭后下次保存时写回明文）
+    #[serde(default = "default_keychain_enabled")]
+    pub enabled: bool,
+}
+
+fn default_keychain_enabled() -> bool {
+    true
+}
+
+impl Default for KeychainSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_keychain_enabled(),
+        }
+    }
+}
+
+/// 数据库维护配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DbMaintenanceSettings {
+    /// 是否启用定时维护（完整性检查 + WAL checkpoint）
+    #[serde(default = "default_db_maintenance_enabled")]
+    pub enabled: bool,
+    /// 维护间隔（小时）
+    #[serde(default = "default_db_maintenance_interval_hours")]
+    pub interval_hours: u64,
+    /// 定时维护时是否执行 VACUUM
+    #[serde(default = "default_db_maintenance_vacuum")]
+    pub vacuum: bool,
+}
+
+fn default_db_maintenance_enabled() -> bool {
+    true
+}
+
+fn default_db_maintenance_interval_hours() -> u64 {
+    24
+}
+
+fn default_db_maintenance_vacuum() -> bool {
+    true
+}
+
+impl Default for DbMaintenanceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_db_maintenance_enabled(),
+            interval_hours: default_db_maintenance_interval_hours(),
+            vacuum: default_db_maintenance_vacuum(),
+        }
+    }
+}
+
+/// 凭证池存储后端
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PoolStorageBackend {
+    /// 本地 SQLite 数据库
+    #[default]
+    Sqlite,
+    /// Redis（多实例共享凭证池状态）
+    Redis,
+    /// PostgreSQL（多实例共享凭证池状态）
+    Postgres,
+}
+
+/// 凭证池存储配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PoolStorageSettings {
+    #[serde(default)]
+    pub backend: PoolStorageBackend,
+    /// 连接地址（`redis://...` / `postgres://...`），SQLite 时忽略
+    #[serde(default)]
+    pub url: String,
+    /// 键前缀（Redis）或表名前缀（PostgreSQL），同一后端上的多个集群用不同前缀隔离
+    #[serde(default = "default_pool_storage_prefix")]
+    pub prefix: String,
+    /// 共享存储中凭证的加密口令，所有实例必须一致；未配置时拒绝使用共享存储
+    #[serde(default)]
+    pub encryption_key: String,
+}
+
+fn default_pool_storage_prefix() -> String {
+    "lime_pool".to_string()
+}
+
+impl Default for PoolStorageSettings {
+    fn default() -> Self {
+        Self {
+            backend: PoolStorageBackend::default(),
+            url: String::new(),
+            prefix: default_pool_storage_prefix(),
+            encryption_key: String::new(),
+        }
+    }
+}
+
+/// 容量不足时的模型降级规则
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModelDowngradeRule {
+    /// 原模型，支持 `*` 通配
+    pub from: String,
+    /// 降级后的模型
+    pub to: String,
+}
+
+/// 容量错误自动降级配置
+///
+/// 上游返回模型过载（如 Anthropic 529 `overloaded_error`）时，按规则把同一请求
+/// 改用更小的模型重试，并通过 `x-lime-model-downgraded-from/to` 响应头告知客户端。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModelDowngradeSettings {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 直接视为容量错误的状态码
+    #[serde(default = "default_downgrade_status_codes")]
+    pub status_codes: Vec<u16>,
+    /// 其他错误状态码下，错误信息包含这些关键字（不区分大小写）时也视为容量错误
+    #[serde(default = "default_downgrade_error_patterns")]
+    pub error_patterns: Vec<String>,
+    /// 降级规则，按顺序匹配第一条
+    #[serde(default)]
+    pub rules: Vec<ModelDowngradeRule>,
+    /// 单个请求最多连续降级的次数
+    #[serde(default = "default_downgrade_max_hops")]
+    pub max_hops: u32,
+}
+
+fn default_downgrade_status_codes() -> Vec<u16> {
+    vec![529]
+}
+
+fn default_downgrade_error_patterns() -> Vec<String> {
+    vec![
+        "overloaded".to_string(),
+        "at capacity".to_string(),
+        "insufficient capacity".to_string(),
+    ]
+}
+
+fn default_downgrade_max_hops() -> u32 {
+    1
+}
+
+impl Default for ModelDowngradeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            status_codes: default_downgrade_status_codes(),
+            error_patterns: default_downgrade_error_patterns(),
+            rules: Vec::new(),
+            max_hops: default_downgrade_max_hops(),
+        }
+    }
+}
+
+/// 突发流量平滑配置
+///
+/// IDE 智能体常常同时发起大量并行请求。启用后按客户端（API Key）维护令牌桶，
+/// 超出突发容量的请求排队等待而不是立即转发，把突发分摊到平滑窗口内，
+/// 让后续请求在凭证池中轮转，避免同一时刻触发上游限流。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BurstSmoothingSettings {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 每个客户端每秒放行的请求数
+    #[serde(default = "default_burst_requests_per_sec")]
+    pub requests_per_sec: f64,
+    /// 无需等待即可放行的突发请求数
+    #[serde(default = "default_burst_size")]
+    pub burst: u32,
+    /// 平滑窗口（毫秒）：单个请求最长排队时间，超出时直接返回 429
+    #[serde(default = "default_burst_max_delay_ms")]
+    pub max_delay_ms: u64,
+}
+
+fn default_burst_requests_per_sec() -> f64 {
+    4.0
+}
+
+fn default_burst_size() -> u32 {
+    8
+}
+
+fn default_burst_max_delay_ms() -> u64 {
+    15_000
+}
+
+impl Default for BurstSmoothingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            requests_per_sec: default_burst_requests_per_sec(),
+            burst: default_burst_size(),
+            max_delay_ms: default_burst_max_delay_ms(),
+        }
+    }
+}
+
+/// 限流计数存储
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitStoreBackend {
+    /// 进程内计数（仅对当前实例生效）
+    #[default]
+    Local,
+    /// Redis 计数（多实例共享限额）
+    Redis,
+}
+
+/// 分布式限流配置
+///
+/// 入站按 API Key 限流沿用顶层 `rate_limit` 的阈值；出站限额按凭证计算，0 表示不限制。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DistributedRateLimitSettings {
+    #[serde(default)]
+    pub store: RateLimitStoreBackend,
+    /// Redis 连接地址（`redis://...`）
+    #[serde(default)]
+    pub redis_url: String,
+    /// Redis 键前缀，同一 Redis 上的多个集群用不同前缀隔离
+    #[serde(default = "default_rate_limit_key_prefix")]
+    pub key_prefix: String,
+    /// 每个凭证每秒最多发往上游的请求数
+    #[serde(default)]
+    pub outbound_rps: u32,
+    /// 每个凭证每分钟最多消耗的 Token 数
+    #[serde(default)]
+    pub outbound_tpm: u64,
+}
+
+fn default_rate_limit_key_prefix() -> String {
+    "lime_rl".to_string()
+}
+
+impl Default for DistributedRateLimitSettings {
+    fn default() -> Self {
+        Self {
+            store: RateLimitStoreBackend::default(),
+            redis_url: String::new(),
+            key_prefix: default_rate_limit_key_prefix(),
+            outbound_rps: 0,
+            outbound_tpm: 0,
+        }
+    }
+}
+
+/// 后台任务与多实例选主配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClusterSettings {
+    /// 主实例租约有效期（秒），仅共享凭证池存储时生效
+    #[serde(default = "default_cluster_lease_ttl_secs")]
+    pub lease_ttl_secs: u64,
+    /// 后台 Token 提前刷新的检查间隔（秒），0 表示禁用（默认）
+    #[serde(default)]
+    pub token_refresh_interval_secs: u64,
+    /// 距过期不足该分钟数的 Token 会被后台提前刷新
+    #[serde(default = "default_cluster_token_refresh_ahead_minutes")]
+    pub token_refresh_ahead_minutes: i64,
+    /// 不健康凭证的后台探测间隔（秒），0 表示禁用
+    #[serde(default)]
+    pub health_probe_interval_secs: u64,
+    /// 按流量自适应探测：空闲凭证也定时探测，近期有真实请求的凭证只按请求结果判断
+    #[serde(default)]
+    pub adaptive_health_probe: bool,
+    /// 自适应时空闲与不健康凭证的探测间隔（秒）
+    #[serde(default = "default_cluster_idle_probe_interval_secs")]
+    pub idle_probe_interval_secs: u64,
+    /// 最近一次真实请求距今不超过该秒数的凭证视为繁忙，不发送探测请求
+    #[serde(default = "default_cluster_probe_traffic_window_secs")]
+    pub probe_traffic_window_secs: u64,
+}
+
+fn default_cluster_lease_ttl_secs() -> u64 {
+    30
+}
+
+fn default_cluster_token_refresh_ahead_minutes() -> i64 {
+    15
+}
+
+fn default_cluster_idle_probe_interval_secs() -> u64 {
+    120
+}
+
+fn default_cluster_probe_traffic_window_secs() -> u64 {
+    600
+}
+
+impl Default for ClusterSettings {
+    fn default() -> Self {
+        Self {
+            lease_ttl_secs: default_cluster_lease_ttl_secs(),
+            token_refresh_interval_secs: 0,
+            token_refresh_ahead_minutes: default_cluster_token_refresh_ahead_minutes(),
+            health_probe_interval_secs: 0,
+            adaptive_health_probe: false,
+            idle_probe_interval_secs: default_cluster_idle_probe_interval_secs(),
+            probe_traffic_window_secs: default_cluster_probe_traffic_window_secs(),
+        }
+    }
+}
+
+/// 对等实例
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct PeerInstance {
+    /// 显示名称（日志用）
+    #[serde(default)]
+    pub name: String,
+    /// 对等实例地址，如 `http://10.0.0.6:8999`
+    pub base_url: String,
+    /// 对等实例的 API Key
+    #[serde(default)]
+    pub api_key: String,
+}
+
+/// 对等实例转发配置
+///
+/// 本地凭证池没有可用凭证时，将请求转发给配置的对等实例，组成简单的代理网格。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PeerForwardingSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 按顺序尝试的对等实例
+    #[serde(default)]
+    pub peers: Vec<PeerInstance>,
+    /// 单个对等实例的请求超时（秒）
+    #[serde(default = "default_peer_forwarding_timeout_secs")]
+    pub timeout_secs: u64,
+    /// 最大转发跳数，防止实例间循环转发
+    #[serde(default = "default_peer_forwarding_max_hops")]
+    pub max_hops: u32,
+}
+
+fn default_peer_forwarding_timeout_secs() -> u64 {
+    300
+}
+
+fn default_peer_forwarding_max_hops() -> u32 {
+    1
+}
+
+impl Default for PeerForwardingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            peers: Vec::new(),
+            timeout_secs: default_peer_forwarding_timeout_secs(),
+            max_hops: default_peer_forwarding_max_hops(),
+        }
+    }
+}
+
+/// 上游响应头透传配置
+///
+/// 将选定的上游响应头（限流余量、模型版本、请求 ID 等）以 `x-lime-upstream-<name>`
+/// 的形式返回给客户端，便于高级客户端根据配额状态调整请求节奏。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UpstreamHeaderSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 允许透传的上游响应头，大小写不敏感，支持以 `*` 结尾的前缀匹配
+    #[serde(default = "default_upstream_header_allow")]
+    pub allow: Vec<String>,
+}
+
+fn default_upstream_header_allow() -> Vec<String> {
+    [
+        "x-ratelimit-*",
+        "anthropic-ratelimit-*",
+        "retry-after",
+        "x-request-id",
+        "request-id",
+        "openai-model",
+        "openai-processing-ms",
+        "x-goog-request-id",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
+impl Default for UpstreamHeaderSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            allow: default_upstream_header_allow(),
+        }
+    }
+}
+
+/// 伪流式配置
+///
+/// 客户端请求 `stream: true` 而上游只返回了完整响应时，按配置节奏将完整响应拆成
+/// SSE 分块返回，使只支持流式的客户端也能正常工作。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FakeStreamingSettings {
+    #[serde(default = "default_fake_streaming_enabled")]
+    pub enabled: bool,
+    /// 每个分块包含的字符数
+    #[serde(default = "default_fake_streaming_chunk_chars")]
+    pub chunk_chars: usize,
+    /// 分块之间的间隔（毫秒），0 表示不等待
+    #[serde(default = "default_fake_streaming_interval_ms")]
+    pub interval_ms: u64,
+    /// 始终以非流式调用上游的 Provider 类型（如 `vertex`）
+    #[serde(default)]
+    pub providers: Vec<String>,
+    /// 含图片的请求是否以非流式调用上游
+    #[serde(default)]
+    pub for_images: bool,
+}
+
+fn default_fake_streaming_enabled() -> bool {
+    true
+}
+
+fn default_fake_streaming_chunk_chars() -> usize {
+    24
+}
+
+fn default_fake_streaming_interval_ms() -> u64 {
+    15
+}
+
+impl FakeStreamingSettings {
+    /// 该 Provider 是否需要以非流式调用上游
+    pub fn forces_non_streaming(&self, provider_type: &str) -> bool {
+        self.enabled
+            && self
+                .providers
+                .iter()
+                .any(|p| p.trim().eq_ignore_ascii_case(provider_type))
+    }
+}
+
+impl Default for FakeStreamingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_fake_streaming_enabled(),
+            chunk_chars: default_fake_streaming_chunk_chars(),
+            interval_ms: default_fake_streaming_interval_ms(),
+            providers: Vec::new(),
+            for_images: false,
+        }
+    }
+}
+
+/// 流式响应转换配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StreamTransformSettings {
+    /// 合并上游细碎增量，累计到该字节数再发送；0 表示不合并
+    #[serde(default)]
+    pub min_chunk_bytes: usize,
+    /// 合并时最长等待时间（毫秒），超时后即使不足 `min_chunk_bytes` 也发送
+    #[serde(default = "default_stream_transform_max_delay_ms")]
+    pub max_delay_ms: u64,
+    /// 客户端 `Accept: application/x-ndjson` 时是否将 SSE 转为 NDJSON
+    #[serde(default = "default_stream_transform_ndjson")]
+    pub ndjson: bool,
+}
+
+fn default_stream_transform_max_delay_ms() -> u64 {
+    50
+}
+
+fn default_stream_transform_ndjson() -> bool {
+    true
+}
+
+impl Default for StreamTransformSettings {
+    fn default() -> Self {
+        Self {
+            min_chunk_bytes: 0,
+            max_delay_ms: default_stream_transform_max_delay_ms(),
+            ndjson: default_stream_transform_ndjson(),
+        }
+    }
+}
+
+/// 单个路由的 SSE 心跳设置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SseHeartbeatRoute {
+    /// 请求路径，支持 `*` 通配（如 `/*/v1/messages`）
+    pub path: String,
+    /// 心跳间隔（秒），0 表示该路由不发送心跳
+    pub interval_secs: u64,
+}
+
+/// SSE 心跳配置
+///
+/// 上游长时间思考无输出时，定期发送 `: ping` 注释行，避免反向代理或客户端空闲超时断开连接。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SseHeartbeatSettings {
+    /// 是否启用
+    #[serde(default = "default_sse_heartbeat_enabled")]
+    pub enabled: bool,
+    /// 默认心跳间隔（秒）
+    #[serde(default = "default_sse_heartbeat_interval_secs")]
+    pub interval_secs: u64,
+    /// 按路由覆盖心跳间隔，按顺序匹配第一条
+    #[serde(default)]
+    pub routes: Vec<SseHeartbeatRoute>,
+}
+
+fn default_sse_heartbeat_enabled() -> bool {
+    true
+}
+
+fn default_sse_heartbeat_interval_secs() -> u64 {
+    15
+}
+
+impl Default for SseHeartbeatSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_sse_heartbeat_enabled(),
+            interval_secs: default_sse_heartbeat_interval_secs(),
+            routes: Vec::new(),
+        }
+    }
+}
+
+/// 管理接口 OIDC 认证配置
+///
+/// 启用后，管理接口（凭证、受限 Key、签名示例等）需要携带 OIDC 提供方签发的
+/// ID Token / Access Token，按所属组映射为只读（viewer）或可操作（operator）角色。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AdminOidcSettings {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// Issuer（如 `https://login.example.com/realms/team`），用于发现 JWKS 并校验 `iss`
+    #[serde(default)]
+    pub issuer: String,
+    /// 期望的 `aud`（通常为 Client ID），为空时不校验
+    #[serde(default)]
+    pub audience: String,
+    /// 组声明名称，支持 `.` 分隔的嵌套路径（如 `realm_access.roles`）
+    #[serde(default = "default_admin_oidc_groups_claim")]
+    pub groups_claim: String,
+    /// 映射为 operator 角色的组
+    #[serde(default)]
+    pub operator_groups: Vec<String>,
+    /// 映射为 viewer 角色的组
+    #[serde(default)]
+    pub viewer_groups: Vec<String>,
+    /// 受保护的路径，支持 `*` 通配
+    #[serde(default = "default_admin_oidc_paths")]
+    pub paths: Vec<String>,
+    /// 是否仍接受主 API Key（视为 operator）
+    #[serde(default = "default_admin_oidc_allow_api_key")]
+    pub allow_api_key: bool,
+    /// JWKS 缓存时长（秒）
+    #[serde(default = "default_admin_oidc_jwks_ttl_secs")]
+    pub jwks_ttl_secs: u64,
+}
+
+fn default_admin_oidc_groups_claim() -> String {
+    "groups".to_string()
+}
+
+fn default_admin_oidc_paths() -> Vec<String> {
+    [
+        "/admin/*",
+        "/api/*",
+        "/v1/credentials/*",
+        "/v1/keys*",
+        "/v1/signing/*",
+    ]
+    .iter()
+    .map(|path| path.to_string())
+    .collect()
+}
+
+fn default_admin_oidc_allow_api_key() -> bool {
+    true
+}
+
+fn default_admin_oidc_jwks_ttl_secs() -> u64 {
+    3600
+}
+
+impl Default for AdminOidcSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            issuer: String::new(),
+            audience: String::new(),
+            groups_claim: default_admin_oidc_groups_claim(),
+            operator_groups: Vec::new(),
+            viewer_groups: Vec::new(),
+            paths: default_admin_oidc_paths(),
+            allow_api_key: default_admin_oidc_allow_api_key(),
+            jwks_ttl_secs: default_admin_oidc_jwks_ttl_secs(),
+        }
+    }
+}
+
+/// 响应归属标注方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AttributionMode {
+    /// 不标注
+    #[default]
+    Off,
+    /// 在响应 JSON（流式为首个事件）中附加 `lime_attribution` 字段
+    Metadata,
+    /// 在回复文本末尾追加页脚
+    Footer,
+}
+
+/// 响应归属标注配置
+///
+/// 标注实际处理请求的 Provider 与模型，便于在别名或路由隐藏真实后端时排查；
+/// 受限 Key 可单独设置标注方式，覆盖这里的默认值。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AttributionSettings {
+    /// 默认标注方式
+    #[serde(default)]
+    pub mode: AttributionMode,
+    /// 页脚模板，支持 `{provider}`、`{model}` 占位符
+    #[serde(default = "default_attribution_footer_template")]
+    pub footer_template: String,
+}
+
+fn default_attribution_footer_template() -> String {
+    "\n\n— {model} via {provider}".to_string()
+}
+
+impl Default for AttributionSettings {
+    fn default() -> Self {
+        Self {
+            mode: AttributionMode::default(),
+            footer_template: default_attribution_footer_template(),
+        }
+    }
+}
+
+/// 搜索接地引用透传配置
+///
+/// Gemini 返回的 `groundingMetadata` 会转换为 OpenAI `annotations`（`url_citation`）
+/// 与 Anthropic 文本块 `citations`；关闭后响应中不再包含引用。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CitationSettings {
+    /// 是否保留引用
+    #[serde(default = "default_citations_keep")]
+    pub keep: bool,
+}
+
+fn default_citations_keep() -> bool {
+    true
+}
+
+impl Default for CitationSettings {
+    fn default() -> Self {
+        Self {
+            keep: default_citations_keep(),
+        }
+    }
+}
+
+/// 代码执行工具规则
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CodeExecutionRule {
+    /// 模型匹配模式（支持通配符，如 `gemini-2.5-*`）
+    pub pattern: String,
+    /// 匹配的模型是否启用代码执行工具
+    pub enabled: bool,
+}
+
+/// Gemini 代码执行（`codeExecution`）工具配置
+///
+/// 响应中执行的代码与输出总是转换为 OpenAI 兼容的内容；请求侧按规则为 Gemini 模型
+/// 附加 `codeExecution` 工具，或将其从请求中移除（包括 Gemini 原生请求）。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct CodeExecutionSettings {
+    /// 未匹配任何规则的模型是否自动启用（关闭时保持客户端请求原样）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 按模型的规则，按顺序取第一条匹配的规则
+    #[serde(default)]
+    pub rules: Vec<CodeExecutionRule>,
+}
+
+impl CodeExecutionSettings {
+    /// 指定模型的代码执行工具设置
+    ///
+    /// `Some(true)` 附加工具，`Some(false)` 移除工具，`None` 保持请求原样。
+    pub fn enabled_for(&self, model: &str) -> Option<bool> {
+        match self
+            .rules
+            .iter()
+            .find(|rule| pattern_matches(&rule.pattern, model))
+        {
+            Some(rule) => Some(rule.enabled),
+            None => self.enabled.then_some(true),
+        }
+    }
+}
+
+/// 对话响应中图片的返回格式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatImageFormat {
+    /// 以 Markdown 图片（`![image](url)`）拼接在文本中
+    #[default]
+    Markdown,
+    /// 以 OpenAI 内容数组返回（`text` 与 `image_url` 分段）；流式响应仍使用 Markdown
+    ContentParts,
+}
+
+/// 对话响应图片输出配置（Gemini 图片输出模型）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ChatImageSettings {
+    /// 图片返回格式
+    #[serde(default)]
+    pub format: ChatImageFormat,
+    /// 收到第一张图片后停止，丢弃之后的文本与图片
+    #[serde(default)]
+    pub stop_on_first_image: bool,
+    /// 将图片保存到本地媒体目录，响应中以 `/v1/media/...` URL 代替 data URL
+    #[serde(default)]
+    pub persist: bool,
+}
+
+/// 提示词防火墙命中后的动作（按严重程度递增）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptFirewallAction {
+    /// 仅记录日志
+    #[default]
+    Log,
+    /// 记录日志并在响应中附加 `x-lime-prompt-firewall` 头
+    Warn,
+    /// 拒绝请求
+    Block,
+}
+
+/// 提示词防火墙自定义规则
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromptFirewallRule {
+    /// 规则名称（出现在日志与响应头中）
+    pub name: String,
+    /// 正则表达式（不区分大小写）
+    pub pattern: String,
+    /// 命中时累加的风险分
+    #[serde(default = "default_prompt_firewall_weight")]
+    pub weight: f64,
+    /// 命中动作
+    #[serde(default)]
+    pub action: PromptFirewallAction,
+}
+
+fn default_prompt_firewall_weight() -> f64 {
+    1.0
+}
+
+/// 提示词防火墙外部分类器
+///
+/// 以 `POST {"input": "..."}` 调用，响应需包含 `score`（0~1），
+/// 或 OpenAI moderation 格式的 `results[0].category_scores`（取最大值）。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromptClassifierSettings {
+    /// 分类器地址
+    pub url: String,
+    /// 以 Bearer 方式发送的 API Key
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// 分数达到该值时视为命中
+    #[serde(default = "default_prompt_classifier_threshold")]
+    pub threshold: f64,
+    /// 命中动作
+    #[serde(default = "default_prompt_classifier_action")]
+    pub action: PromptFirewallAction,
+    /// 超时时间（毫秒），超时或失败时放行
+    #[serde(default = "default_prompt_classifier_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_prompt_classifier_threshold() -> f64 {
+    0.8
+}
+
+fn default_prompt_classifier_action() -> PromptFirewallAction {
+    PromptFirewallAction::Block
+}
+
+fn default_prompt_classifier_timeout_ms() -> u64 {
+    3000
+}
+
+/// 提示词防火墙配置（提示词注入 / 越狱检测）
+///
+/// 检查 `/v1/chat/completions` 与 `/v1/messages` 请求中除助手消息外的文本，
+/// 内置启发式规则与自定义规则按正则匹配累加风险分，取命中规则中最严重的动作；
+/// 规则未拒绝时再调用可选的外部分类器。只检查通过认证的请求。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromptFirewallSettings {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 是否启用内置启发式规则
+    #[serde(default = "default_prompt_firewall_builtin")]
+    pub builtin_rules: bool,
+    /// 内置规则命中时的动作
+    #[serde(default = "default_prompt_firewall_builtin_action")]
+    pub builtin_action: PromptFirewallAction,
+    /// 自定义规则
+    #[serde(default)]
+    pub rules: Vec<PromptFirewallRule>,
+    /// 外部分类器（可选）
+    #[serde(default)]
+    pub classifier: Option<PromptClassifierSettings>,
+}
+
+fn default_prompt_firewall_builtin() -> bool {
+    true
+}
+
+fn default_prompt_firewall_builtin_action() -> PromptFirewallAction {
+    PromptFirewallAction::Warn
+}
+
+impl Default for PromptFirewallSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            builtin_rules: default_prompt_firewall_builtin(),
+            builtin_action: default_prompt_firewall_builtin_action(),
+            rules: Vec::new(),
+            classifier: None,
+        }
+    }
+}
+
+/// 响应内容策略命中后的动作
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentPolicyAction {
+    /// 保留原文，仅标注命中的规则
+    #[default]
+    Annotate,
+    /// 将命中的内容替换为 `redaction`
+    Redact,
+    /// 拦截整个回复
+    Block,
+}
+
+/// 响应内容策略规则的匹配方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentPolicyMatch {
+    /// 正则表达式
+    #[default]
+    Regex,
+    /// 关键词（按字面匹配）
+    Keyword,
+}
+
+/// 响应内容策略规则
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContentPolicyRule {
+    /// 规则名称（出现在响应头与标注中）
+    pub name: String,
+    /// 正则表达式或关键词（不区分大小写）
+    pub pattern: String,
+    /// 匹配方式
+    #[serde(default)]
+    pub kind: ContentPolicyMatch,
+    /// 命中动作
+    #[serde(default)]
+    pub action: ContentPolicyAction,
+}
+
+/// 响应内容策略配置（出站过滤）
+///
+/// 在返回给客户端前检查模型生成的文本；流式响应保留最近 `window_chars` 个字符
+/// 暂不发送，以便匹配跨越多个增量的内容。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContentPolicySettings {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 规则列表
+    #[serde(default)]
+    pub rules: Vec<ContentPolicyRule>,
+    /// 流式扫描窗口（字符数），应不小于需要匹配的最长内容
+    #[serde(default = "default_content_policy_window_chars")]
+    pub window_chars: usize,
+    /// 脱敏替换文本
+    #[serde(default = "default_content_policy_redaction")]
+    pub redaction: String,
+    /// 拦截时返回的文本
+    #[serde(default = "default_content_policy_block_message")]
+    pub block_message: String,
+}
+
+fn default_content_policy_window_chars() -> usize {
+    200
+}
+
+fn default_content_policy_redaction() -> String {
+    "[REDACTED]".to_string()
+}
+
+fn default_content_policy_block_message() -> String {
+    "[response blocked by content policy]".to_string()
+}
+
+impl Default for ContentPolicySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rules: Vec::new(),
+            window_chars: default_content_policy_window_chars(),
+            redaction: default_content_policy_redaction(),
+            block_message: default_content_policy_block_message(),
+        }
+    }
+}
+
+/// 按模型的最大输出 Token 规则
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MaxOutputTokenRule {
+    /// 模型匹配模式（支持通配符，如 `claude-*`）
+    pub pattern: String,
+    /// 最大输出 Token 数
+    pub max_tokens: u32,
+}
+
+/// 最大输出 Token 上限配置
+///
+/// 无论客户端请求多少（或未指定），`max_tokens` 都会被收紧到上限以内；
+/// 受限 Key 可单独设置上限，两者同时存在时取较小值。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct MaxOutputTokenSettings {
+    /// 未匹配任何规则的模型使用的上限，未设置表示不限制
+    #[serde(default)]
+    pub default_limit: Option<u32>,
+    /// 按模型的规则，按顺序取第一条匹配的规则
+    #[serde(default)]
+    pub rules: Vec<MaxOutputTokenRule>,
+}
+
+impl MaxOutputTokenSettings {
+    /// 指定模型的输出上限
+    pub fn limit_for(&self, model: &str) -> Option<u32> {
+        self.rules
+            .iter()
+            .find(|rule| pattern_matches(&rule.pattern, model))
+            .map(|rule| rule.max_tokens)
+            .or(self.default_limit)
+            .filter(|limit| *limit > 0)
+    }
+}
+
+/// 按模型的流式输出上限规则
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StreamOutputCapRule {
+    /// 模型匹配模式（支持通配符，如 `claude-*`）
+    pub pattern: String,
+    /// 最大输出 Token 数（按默认分词器估算）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    /// 最大输出字节数（响应体字节）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<u64>,
+}
+
+/// 单次流式响应的输出上限
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StreamOutputLimit {
+    pub max_tokens: Option<u32>,
+    pub max_bytes: Option<u64>,
+}
+
+impl StreamOutputLimit {
+    /// 是否设置了任一上限
+    pub fn is_limited(&self) -> bool {
+        self.max_tokens.is_some() || self.max_bytes.is_some()
+    }
+
+    /// 与另一组上限合并，各项取较小值
+    pub fn tighten(self, other: StreamOutputLimit) -> Self {
+        fn min<T: Ord>(a: Option<T>, b: Option<T>) -> Option<T> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, None) => a,
+                (None, b) => b,
+            }
+        }
+        Self {
+            max_tokens: min(self.max_tokens, other.max_tokens),
+            max_bytes: min(self.max_bytes, other.max_bytes),
+        }
+    }
+}
+
+/// 流式输出上限配置
+///
+/// 上游忽略 `max_tokens` 或生成失控时，累计输出超出上限后以 `length` 结束原因
+/// 正常收尾流式响应并记录截断；受限 Key 可单独设置上限，两者同时存在时取较小值。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct StreamOutputCapSettings {
+    /// 是否启用按模型的上限（受限 Key 的单独上限始终生效）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 未匹配任何规则的模型使用的 Token 上限
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_max_tokens: Option<u32>,
+    /// 未匹配任何规则的模型使用的字节上限
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_max_bytes: Option<u64>,
+    /// 按模型的规则，按顺序取第一条匹配的规则
+    #[serde(default)]
+    pub rules: Vec<StreamOutputCapRule>,
+}
+
+impl StreamOutputCapSettings {
+    /// 指定模型的流式输出上限
+    pub fn limit_for(&self, model: &str) -> StreamOutputLimit {
+        if !self.enabled {
+            return StreamOutputLimit::default();
+        }
+        let limit = match self
+            .rules
+            .iter()
+            .find(|rule| pattern_matches(&rule.pattern, model))
+        {
+            Some(rule) => StreamOutputLimit {
+                max_tokens: rule.max_tokens,
+                max_bytes: rule.max_bytes,
+            },
+            None => StreamOutputLimit {
+                max_tokens: self.default_max_tokens,
+                max_bytes: self.default_max_bytes,
+            },
+        };
+        StreamOutputLimit {
+            max_tokens: limit.max_tokens.filter(|limit| *limit > 0),
+            max_bytes: limit.max_bytes.filter(|limit| *limit > 0),
+        }
+    }
+}
+
+/// 需要预热的本地模型
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WarmModel {
+    /// 本地 Provider 的 ID 或名称
+    pub provider: String,
+    /// 模型名称
+    pub model: String,
+}
+
+/// 本地模型预热配置
+///
+/// 服务启动时加载配置的本地模型，并定期发送保活请求使其常驻内存，
+/// 避免冷启动较慢的本地后端让首个请求等待数十秒。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WarmPoolSettings {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 保活间隔（秒）
+    #[serde(default = "default_warm_keep_alive_interval_secs")]
+    pub keep_alive_interval_secs: u64,
+    /// 请求 Ollama 保留模型的时长（`keep_alive` 参数，如 `30m`；`-1` 表示一直保留）
+    #[serde(default = "default_warm_keep_alive")]
+    pub keep_alive: String,
+    /// 需要预热的模型
+    #[serde(default)]
+    pub models: Vec<WarmModel>,
+}
+
+fn default_warm_keep_alive_interval_secs() -> u64 {
+    240
+}
+
+fn default_warm_keep_alive() -> String {
+    "30m".to_string()
+}
+
+impl Default for WarmPoolSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            keep_alive_interval_secs: default_warm_keep_alive_interval_secs(),
+            keep_alive: default_warm_keep_alive(),
+            models: Vec::new(),
+        }
+    }
+}
+
+/// 单个存储的保留策略（未设置的限制不生效）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct RetentionPolicy {
+    /// 最长保留天数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u32>,
+    /// 最多保留的条目数（数据库行或文件数）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_entries: Option<u64>,
+    /// 最大占用空间（MB，仅文件存储）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size_mb: Option<u64>,
+}
+
+/// 数据保留配置
+///
+/// 定时清理各持久化存储中的过期数据，超出条目数或空间限制时从最旧的开始删除，
+/// 避免长期运行的实例无限增长。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RetentionSettings {
+    /// 是否启用定时清理
+    #[serde(default = "default_retention_enabled")]
+    pub enabled: bool,
+    /// 清理间隔（小时）
+    #[serde(default = "default_retention_interval_hours")]
+    pub interval_hours: u64,
+    /// 配置变更审计日志
+    #[serde(default = "default_retention_audit")]
+    pub audit: RetentionPolicy,
+    /// 按日汇总的模型用量
+    #[serde(default = "default_retention_usage")]
+    pub usage: RetentionPolicy,
+    /// 对话图片等媒体文件
+    #[serde(default = "default_retention_media")]
+    pub media: RetentionPolicy,
+    /// 调试模式保存的请求 / 响应抓包
+    #[serde(default = "default_retention_debug_captures")]
+    pub debug_captures: RetentionPolicy,
+    /// 会话文件（默认不清理）
+    #[serde(default)]
+    pub transcripts: RetentionPolicy,
+    /// 凭证回收站（保留天数即可恢复的期限）
+    #[serde(default = "default_retention_deleted_credentials")]
+    pub deleted_credentials: RetentionPolicy,
+}
+
+fn default_retention_enabled() -> bool {
+    true
+}
+
+fn default_retention_interval_hours() -> u64 {
+    24
+}
+
+fn default_retention_audit() -> RetentionPolicy {
+    RetentionPolicy {
+        max_age_days: Some(180),
+        max_entries: Some(5000),
+        max_size_mb: None,
+    }
+}
+
+fn default_retention_usage() -> RetentionPolicy {
+    RetentionPolicy {
+        max_age_days: Some(400),
+        ..Default::default()
+    }
+}
+
+fn default_retention_media() -> RetentionPolicy {
+    RetentionPolicy {
+        max_age_days: Some(30),
+        max_entries: None,
+        max_size_mb: Some(1024),
+    }
+}
+
+fn default_retention_debug_captures() -> RetentionPolicy {
+    RetentionPolicy {
+        max_age_days: Some(7),
+        max_entries: None,
+        max_size_mb: Some(200),
+    }
+}
+
+fn default_retention_deleted_credentials() -> RetentionPolicy {
+    RetentionPolicy {
+        max_age_days: Some(30),
+        ..Default::default()
+    }
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_retention_enabled(),
+            interval_hours: default_retention_interval_hours(),
+            audit: default_retention_audit(),
+            usage: default_retention_usage(),
+            media: default_retention_media(),
+            debug_captures: default_retention_debug_captures(),
+            transcripts: RetentionPolicy::default(),
+            deleted_credentials: default_retention_deleted_credentials(),
+        }
+    }
+}
+
+/// 端点开关配置
+///
+/// 关闭的端点返回与不存在的路由相同的 404，用于缩小暴露面。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EndpointToggleSettings {
+    /// 图像生成与对话图片媒体（`/v1/images/*`、`/v1/media/*`）
+    #[serde(default = "default_endpoint_enabled")]
+    pub images: bool,
+    /// 音频（`/v1/audio/*`）
+    #[serde(default = "default_endpoint_enabled")]
+    pub audio: bool,
+    /// 向量、RAG 与重排序（`/v1/embeddings`、`/v1/rag/*`、`/v1/rerank`）
+    #[serde(default = "default_endpoint_enabled")]
+    pub embeddings: bool,
+    /// 管理接口（`/api/*`、`/v1/credentials/*`、`/v1/keys*`、`/v1/signing/*`）
+    #[serde(default = "default_endpoint_enabled")]
+    pub admin: bool,
+    /// Ollama 兼容接口（`/api/chat`、`/api/generate`、`/api/tags` 等）
+    #[serde(default = "default_endpoint_enabled")]
+    pub ollama: bool,
+}
+
+fn default_endpoint_enabled() -> bool {
+    true
+}
+
+impl Default for EndpointToggleSettings {
+    fn default() -> Self {
+        Self {
+            images: true,
+            audio: true,
+            embeddings: true,
+            admin: true,
+            ollama: true,
+        }
+    }
+}
+
+/// 维护模式配置
+///
+/// 开启后推理端点统一返回 503 与 `message`，管理接口与健康检查不受影响，
+/// 便于在调整凭证或迁移配置时暂停对外服务。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MaintenanceModeSettings {
+    /// 是否处于维护模式
+    #[serde(default)]
+    pub enabled: bool,
+    /// 返回给客户端的提示
+    #[serde(default = "default_maintenance_message")]
+    pub message: String,
+    /// `Retry-After` 响应头（秒），未设置时不返回
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+}
+
+fn default_maintenance_message() -> String {
+    "Service is under maintenance, please try again later".to_string()
+}
+
+impl Default for MaintenanceModeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            message: default_maintenance_message(),
+            retry_after_secs: None,
+        }
+    }
+}
+
+/// 调度通道
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum PriorityLane {
+    /// 交互请求（IDE 补全、对话）
+    #[default]
+    Interactive,
+    /// 后台 / 批处理请求
+    Background,
+}
+
+impl PriorityLane {
+    /// 解析请求头取值，无法识别时返回 `None`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "interactive" | "high" | "realtime" => Some(Self::Interactive),
+            "background" | "batch" | "low" | "bulk" => Some(Self::Background),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Interactive => "interactive",
+            Self::Background => "background",
+        }
+    }
+}
+
+/// 请求优先级通道配置
+///
+/// 推理请求分为交互与后台两个通道，通道由请求头或受限 Key 的优先级决定。
+/// 并发名额紧张时后台请求让行：只要有交互请求在排队，后台请求就不会获得新名额，
+/// 且后台请求最多占用 `background_max_concurrent` 个名额，为交互请求预留余量。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PriorityLaneSettings {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 指定通道的请求头（取值 `interactive` / `background`，也接受 `batch`、`low` 等别名）
+    #[serde(default = "default_priority_header")]
+    pub header: String,
+    /// 未指定通道时使用的通道
+    #[serde(default)]
+    pub default_lane: PriorityLane,
+    /// 推理请求的总并发上限
+    #[serde(default = "default_priority_max_concurrent")]
+    pub max_concurrent: u32,
+    /// 后台请求的并发上限（不超过总上限）
+    #[serde(default = "default_priority_background_max_concurrent")]
+    pub background_max_concurrent: u32,
+    /// 最长排队时间（毫秒），超出时返回 429
+    #[serde(default = "default_priority_max_wait_ms")]
+    pub max_wait_ms: u64,
+}
+
+fn default_priority_header() -> String {
+    "x-lime-priority".to_string()
+}
+
+fn default_priority_max_concurrent() -> u32 {
+    16
+}
+
+fn default_priority_background_max_concurrent() -> u32 {
+    4
+}
+
+fn default_priority_max_wait_ms() -> u64 {
+    120_000
+}
+
+impl Default for PriorityLaneSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            header: default_priority_header(),
+            default_lane: PriorityLane::default(),
+            max_concurrent: default_priority_max_concurrent(),
+            background_max_concurrent: default_priority_background_max_concurrent(),
+            max_wait_ms: default_priority_max_wait_ms(),
+        }
+    }
+}
+
+/// 单个模型的并发与排队规则
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelConcurrencyRule {
+    /// 模型匹配模式，支持 `*` 通配（按别名解析后的模型匹配）
+    pub pattern: String,
+    /// 每个匹配模型的并发上限
+    pub max_concurrent: u32,
+    /// 每个匹配模型最多排队的请求数，0 表示不排队
+    #[serde(default = "default_model_queue_length")]
+    pub queue_length: u32,
+    /// 最长排队时间（毫秒）
+    #[serde(default = "default_model_queue_max_wait_ms")]
+    pub max_wait_ms: u64,
+    /// 模型饱和（队列已满或排队超时）时改用的模型，留空时返回 429
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<String>,
+}
+
+fn default_model_queue_length() -> u32 {
+    16
+}
+
+fn default_model_queue_max_wait_ms() -> u64 {
+    30_000
+}
+
+/// 模型级并发与排队配置
+///
+/// 在凭证级限额之外为上游限流更严格的模型单独设置并发上限：
+/// 超出上限的请求排队等待，队列已满或排队超时后按规则改用备用模型或返回 429。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ModelConcurrencySettings {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 按模型的规则，按顺序取第一条匹配的规则
+    #[serde(default)]
+    pub rules: Vec<ModelConcurrencyRule>,
+}
+
+impl ModelConcurrencySettings {
+    /// 指定模型适用的规则
+    pub fn rule_for(&self, model: &str) -> Option<&ModelConcurrencyRule> {
+        if !self.enabled {
+            return None;
+        }
+        self.rules
+            .iter()
+            .find(|rule| rule.max_concurrent > 0 && pattern_matches(&rule.pattern, model))
+    }
+}
+
+/// 在途请求日志配置
+///
+/// 启用后把已接收但尚未完成的非流式推理请求记录到应用数据目录的日志文件，
+/// 进程崩溃或被强制结束后，下次启动时可查看哪些请求丢失；
+/// 后台通道（批处理）请求还会保存请求体，可在启动后重新执行。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RequestJournalSettings {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 启动后自动重新执行丢失的后台通道请求
+    #[serde(default)]
+    pub replay_background: bool,
+    /// 保存请求体的大小上限（字节），超出时仅记录请求元信息，无法重新执行
+    #[serde(default = "default_journal_max_body_bytes")]
+    pub max_body_bytes: usize,
+}
+
+fn default_journal_max_body_bytes() -> usize {
+    1024 * 1024
+}
+
+impl Default for RequestJournalSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            replay_background: false,
+            max_body_bytes: default_journal_max_body_bytes(),
+        }
+    }
+}
+
+/// 按地区可达性自动走代理配置
+///
+/// 启用后 Provider 请求默认直连；某个上游主机直连失败（连接被重置、TLS 握手失败）
+/// 或返回地区限制错误时，自动改为经出站代理访问该主机，其他主机保持直连。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RegionalProxySettings {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 出站代理地址，未设置时使用顶层 `proxy_url`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    /// 始终经代理访问的主机（支持 `*` 通配，如 `*.googleapis.com`）
+    #[serde(default)]
+    pub always_proxy: Vec<String>,
+    /// 始终直连的主机（优先于自动检测与 `always_proxy`）
+    #[serde(default)]
+    pub direct: Vec<String>,
+    /// 自动标记的主机在多少秒后重新尝试直连
+    #[serde(default = "default_regional_recheck_after_secs")]
+    pub recheck_after_secs: u64,
+}
+
+fn default_regional_recheck_after_secs() -> u64 {
+    6 * 60 * 60
+}
+
+impl Default for RegionalProxySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            proxy_url: None,
+            always_proxy: Vec::new(),
+            direct: Vec::new(),
+            recheck_after_secs: default_regional_recheck_after_secs(),
+        }
+    }
+}
+
+/// 转换器影子验证配置（`server.converter_shadow`）
+///
+/// 重构协议转换时，把旧版（或新版）转换器注册为影子版本，对真实流量同时运行两个版本，
+/// 比对生成的上游请求体并记录差异。影子版本的输出只用于比对，不会发往上游。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConverterShadowSettings {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 参与比对的请求比例（0.0 ~ 1.0）
+    #[serde(default = "default_converter_shadow_sample_rate")]
+    pub sample_rate: f64,
+    /// 比对时忽略的路径（如 `conversationState.conversationId`、`messages[*].id`）
+    #[serde(default)]
+    pub ignore_paths: Vec<String>,
+    /// 保留的最近差异条数
+    #[serde(default = "default_converter_shadow_max_recent")]
+    pub max_recent: usize,
+}
+
+fn default_converter_shadow_sample_rate() -> f64 {
+    1.0
+}
+
+fn default_converter_shadow_max_recent() -> usize {
+    50
+}
+
+impl Default for ConverterShadowSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sample_rate: default_converter_shadow_sample_rate(),
+            ignore_paths: Vec::new(),
+            max_recent: default_converter_shadow_max_recent(),
+        }
+    }
+}
+
+/// Token 计数使用的分词器
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenizerKind {
+    /// 按字符估算（CJK 约 1.5 Token/字，其他约 4 字节 1 Token），无需加载词表
+    #[default]
+    Heuristic,
+    /// tiktoken `cl100k_base`（GPT-4 / GPT-3.5）
+    Cl100k,
+    /// tiktoken `o200k_base`（GPT-4o / o 系列 / GPT-5）
+    O200k,
+    /// tiktoken `p50k_base`（早期 Codex / davinci）
+    P50k,
+    /// Gemini 字符估算（未内置 SentencePiece 词表；CJK 约 1 Token/字，其他约 4 字节 1 Token）
+    Gemini,
+    /// 近似 Claude 分词器（在 `cl100k_base` 基础上上浮）
+    Claude,
+}
+
+/// 按模型指定分词器
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TokenizerFamilyRule {
+    /// 模型名匹配模式，支持 `*` 通配（如 `qwen-*`、`*-preview`）
+    pub models: Vec<String>,
+    pub tokenizer: TokenizerKind,
+}
+
+/// 分词器配置（`server.tokenizer`）
+///
+/// 预算检查、规模路由、上下文截断、用量估算与 `/v1/messages/count_tokens` 统一按此选择分词器。
+/// 依次匹配 `families`、内置的模型族（GPT / o 系列 / Gemini / Claude），都未命中时使用 `default`。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct TokenizerSettings {
+    /// 未识别模型使用的分词器
+    #[serde(default)]
+    pub default: TokenizerKind,
+    /// 自定义模型族，优先于内置识别
+    #[serde(default)]
+    pub families: Vec<TokenizerFamilyRule>,
+}
+
+/// 多模态上传去重配置（`server.upload_dedup`）
+///
+/// 请求中内联的图片、音频等素材按内容哈希去重：首次出现时上传到 Provider 的文件接口
+/// （Gemini Files API、Anthropic Files API），之后相同内容直接引用已上传的文件。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UploadDedupSettings {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 小于该大小（解码后字节数）的素材保持内联
+    #[serde(default = "default_upload_dedup_min_bytes")]
+    pub min_bytes: usize,
+    /// Gemini 文件引用的保留小时数（Gemini 文件 48 小时后自动删除，需小于 48）
+    #[serde(default = "default_upload_dedup_gemini_ttl_hours")]
+    pub gemini_ttl_hours: u32,
+    /// Anthropic 文件引用的保留小时数（Anthropic 文件不会自动删除，0 表示不过期）
+    #[serde(default = "default_upload_dedup_anthropic_ttl_hours")]
+    pub anthropic_ttl_hours: u32,
+}
+
+fn default_upload_dedup_min_bytes() -> usize {
+    64 * 1024
+}
+
+fn default_upload_dedup_gemini_ttl_hours() -> u32 {
+    46
+}
+
+fn default_upload_dedup_anthropic_ttl_hours() -> u32 {
+    24 * 30
+}
+
+impl Default for UploadDedupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_bytes: default_upload_dedup_min_bytes(),
+            gemini_ttl_hours: default_upload_dedup_gemini_ttl_hours(),
+            anthropic_ttl_hours: default_upload_dedup_anthropic_ttl_hours(),
+        }
+    }
+}
+
+/// Gemini 显式上下文缓存配置（`server.context_cache`）
+///
+/// 请求中较大的静态前缀（系统指令、工具声明，以及客户端声明的前几轮内容）
+/// 按内容哈希创建 Gemini `cachedContents` 并在有效期内复用，缓存的部分按缓存价计费。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContextCacheSettings {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 自动检测时静态前缀的最小 Token 数（估算），低于该值不缓存
+    #[serde(default = "default_context_cache_min_tokens")]
+    pub min_tokens: u64,
+    /// 缓存有效期（秒）
+    #[serde(default = "default_context_cache_ttl_secs")]
+    pub ttl_secs: u64,
+}
+
+fn default_context_cache_min_tokens() -> u64 {
+    4096
+}
+
+fn default_context_cache_ttl_secs() -> u64 {
+    3600
+}
+
+impl Default for ContextCacheSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_tokens: default_context_cache_min_tokens(),
+            ttl_secs: default_context_cache_ttl_secs(),
+        }
+    }
+}
+
+/// 对话记录采集配置（`server.transcript_capture`）
+///
+/// 启用后保存非流式对话请求（`/v1/chat/completions`、`/v1/messages`）的请求与响应，
+/// 可导出为 OpenAI / Gemini 微调 JSONL。只有带同意标记的记录会被导出。同意标记只来自
+/// Key 的配置（受限 Key 签发时的 `training_consent`，主 Key 为 `primary_key_consent`），
+/// 不接受客户端请求头。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TranscriptCaptureSettings {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 未同意的请求也保存（导出时仍会被排除，便于事后补充同意）
+    #[serde(default)]
+    pub record_without_consent: bool,
+    /// 主 API Key 的请求视为同意用于训练
+    #[serde(default)]
+    pub primary_key_consent: bool,
+    /// 请求体或响应体超过该大小（字节）时不保存
+    #[serde(default = "default_transcript_max_body_bytes")]
+    pub max_body_bytes: usize,
+}
+
+fn default_transcript_max_body_bytes() -> usize {
+    1024 * 1024
+}
+
+impl Default for TranscriptCaptureSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            record_without_consent: false,
+            primary_key_consent: false,
+            max_body_bytes: default_transcript_max_body_bytes(),
+        }
+    }
+}
+
+/// 受限 Key 自动轮换配置（`server.key_rotation`）
+///
+/// 轮换策略在签发受限 Key 时单独设置，这里只控制检查周期与通知：
+/// 生成后继 Key、旧 Key 进入宽限期与宽限期结束时，除了发布应用事件，
+/// 还会向 `webhook_urls` 发送 JSON 通知（不含 Key 明文）。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeyRotationSettings {
+    /// 检查轮换策略的间隔（秒）
+    #[serde(default = "default_key_rotation_check_interval_secs")]
+    pub check_interval_secs: u64,
+    /// 通知地址
+    #[serde(default)]
+    pub webhook_urls: Vec<String>,
+}
+
+fn default_key_rotation_check_interval_secs() -> u64 {
+    300
+}
+
+impl Default for KeyRotationSettings {
+    fn default() -> Self {
+        Self {
+            check_interval_secs: default_key_rotation_check_interval_secs(),
+            webhook_urls: Vec::new(),
+        }
+    }
+}
@@ -38,8 +38,7 @@ fn arb_server_config() -> impl Strategy<Value = ServerConfig> {
         host,
         port,
         api_key,
-        tls: crate::config::TlsConfig::default(),
-        response_cache: crate::config::ResponseCacheSettings::default(),
+        ..ServerConfig::default()
     })
 }
 
@@ -61,6 +60,7 @@ fn arb_provider_config() -> impl Strategy<Value = ProviderConfig> {
                 credentials_path,
                 region,
                 project_id,
+                ..Default::default()
             },
         )
 }
@@ -80,6 +80,7 @@ fn arb_custom_provider_config() -> impl Strategy<Value = CustomProviderConfig> {
             enabled,
             api_key,
             base_url,
+            ..Default::default()
         })
 }
 
@@ -98,6 +99,9 @@ fn arb_providers_config() -> impl Strategy<Value = ProvidersConfig> {
             qwen,
             openai,
             claude,
+            endpoints: Default::default(),
+            endpoint_selection: Default::default(),
+            upstream_auth: Default::default(),
         })
 }
 
@@ -118,6 +122,9 @@ fn arb_routing_config() -> impl Strategy<Value = RoutingConfig> {
         .prop_map(|(default_provider, model_aliases)| RoutingConfig {
             default_provider,
             model_aliases,
+            model_deprecation: Default::default(),
+            size_routing: Default::default(),
+            time_windows: Default::default(),
         })
 }
 
@@ -358,8 +365,7 @@ fn arb_valid_server_config() -> impl Strategy<Value = ServerConfig> {
         host,
         port,
         api_key,
-        tls: crate::config::TlsConfig::default(),
-        response_cache: crate::config::ResponseCacheSettings::default(),
+        ..ServerConfig::default()
     })
 }
 
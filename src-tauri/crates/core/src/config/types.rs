@@ -3,6 +3,20 @@
 //! 定义 Lime 的配置结构，支持 YAML 和 JSON 序列化/反序列化
 //! 保持与旧版 JSON 配置的向后兼容性
 
+use super::server_features::{
+    AdminOidcSettings, AttributionSettings, AuthLockoutSettings, BurstSmoothingSettings,
+    ChatImageSettings, CitationSettings, ClusterSettings, CodeExecutionSettings,
+    ContentPolicySettings, ContextCacheSettings, ConverterShadowSettings, CorsSettings,
+    DbMaintenanceSettings, DevUtilsSettings, DistributedRateLimitSettings, EmbeddingCacheSettings,
+    EndpointToggleSettings, FakeStreamingSettings, KeyRotationSettings, KeychainSettings,
+    LanDiscoverySettings, MaintenanceModeSettings, MaxOutputTokenSettings,
+    ModelConcurrencySettings, ModelDowngradeSettings, PeerForwardingSettings, PoolStorageSettings,
+    PortConflictSettings, PriorityLaneSettings, PromptFirewallSettings, RagSettings,
+    RegionalProxySettings, RemoteControlSettings, RequestJournalSettings, RequestSigningSettings,
+    RerankSettings, RetentionSettings, SseHeartbeatSettings, StreamOutputCapSettings,
+    StreamTransformSettings, TokenizerSettings, TranscriptCaptureSettings, UploadDedupSettings,
+    UpstreamHeaderSettings, WarmPoolSettings,
+};
 use crate::models::injection_types::{InjectionMode, InjectionRule};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -156,6 +170,15 @@ pub struct OpenAIAsrConfig {
     /// 代理 URL（可选）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub proxy_url: Option<String>,
+    /// 识别模型（可选，默认 whisper-1；说话人分离需使用支持 diarization 的模型）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// 是否返回词级时间戳（verbose_json）
+    #[serde(default)]
+    pub word_timestamps: bool,
+    /// 是否进行说话人分离
+    #[serde(default)]
+    pub diarization: bool,
 }
 
 /// Gemini API Key 凭证条目
@@ -377,7 +400,7 @@ pub struct Config {
     /// 关闭时最小化到托盘（而不是退出应用）
     #[serde(default = "default_minimize_to_tray")]
     pub minimize_to_tray: bool,
-    /// 用户界面语言 ("zh" 或 "en")
+    /// 用户界面语言 ("zh" 或 "en")，未设置 `server.locale` 时也用于后端文案
     #[serde(default = "default_language")]
     pub language: String,
     /// 模型配置（动态加载 Provider 和模型列表）
@@ -425,6 +448,9 @@ pub struct Config {
     /// 崩溃上报配置（Sentry 协议兼容）
     #[serde(default)]
     pub crash_reporting: CrashReportingConfig,
+    /// 匿名使用统计（默认关闭）
+    #[serde(default)]
+    pub anonymous_stats: AnonymousStatsConfig,
     /// 对话管理配置
     #[serde(default)]
     pub conversation: ConversationSettings,
@@ -434,6 +460,12 @@ pub struct Config {
     /// 配对认证配置
     #[serde(default)]
     pub pairing: PairingSettings,
+    /// 应用内角色访问控制
+    #[serde(default)]
+    pub access_control: AccessControlSettings,
+    /// 云备份配置
+    #[serde(default)]
+    pub cloud_backup: CloudBackupSettings,
     /// 自动化调度配置
     #[serde(default)]
     pub automation: AutomationSettings,
@@ -879,6 +911,26 @@ pub struct UpdateCheckConfig {
     /// 关闭提醒次数（ESC/关闭按钮）
     #[serde(default)]
     pub action_dismiss_count: u64,
+    /// 更新通道（stable / beta）
+    #[serde(default)]
+    pub channel: UpdateChannel,
+    /// 检测到新版本后在后台预下载安装包（安装仍需用户确认）
+    #[serde(default)]
+    pub auto_download: bool,
+    /// 本机安装标识，用于分阶段推送的稳定分桶；首次检查时自动生成
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub install_id: Option<String>,
+}
+
+/// 更新通道
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    /// 正式版
+    #[default]
+    Stable,
+    /// 测试版（包含预发布版本）
+    Beta,
 }
 
 fn default_update_check_enabled() -> bool {
@@ -911,6 +963,9 @@ impl Default for UpdateCheckConfig {
             action_remind_later_count: 0,
             action_skip_version_count: 0,
             action_dismiss_count: 0,
+            channel: UpdateChannel::default(),
+            auto_download: false,
+            install_id: None,
         }
     }
 }
@@ -1244,6 +1299,145 @@ pub struct ServerConfig {
     /// 响应缓存配置（仅影响非流式请求）
     #[serde(default)]
     pub response_cache: ResponseCacheSettings,
+    /// 嵌入缓存配置（`/v1/embeddings`）
+    #[serde(default)]
+    pub embedding_cache: EmbeddingCacheSettings,
+    /// 本地 RAG 配置（`/v1/rag/*`）
+    #[serde(default)]
+    pub rag: RagSettings,
+    /// 重排序配置（`/v1/rerank`）
+    #[serde(default)]
+    pub rerank: RerankSettings,
+    /// 开发辅助端点配置（`/utils/*`）
+    #[serde(default)]
+    pub dev_utils: DevUtilsSettings,
+    /// CORS 配置（局域网 Web UI 来源、预检缓存、私有网络访问）
+    #[serde(default)]
+    pub cors: CorsSettings,
+    /// 启动时端口冲突处理（接管旧实例或回退到可用端口）
+    #[serde(default)]
+    pub port_conflict: PortConflictSettings,
+    /// 局域网 mDNS 发现与移动端扫码配对
+    #[serde(default)]
+    pub lan_discovery: LanDiscoverySettings,
+    /// HMAC 请求签名（时间戳 + 请求体哈希）
+    #[serde(default)]
+    pub request_signing: RequestSigningSettings,
+    /// 远程控制接口（HMAC 签名的配置重载 / Provider 开关 / 凭证轮换）
+    #[serde(default)]
+    pub remote_control: RemoteControlSettings,
+    /// 认证失败锁定（按来源 IP 防暴力破解）
+    #[serde(default)]
+    pub auth_lockout: AuthLockoutSettings,
+    /// 系统钥匙串密钥存储
+    #[serde(default)]
+    pub keychain: KeychainSettings,
+    /// 数据库维护（VACUUM / 完整性检查 / WAL checkpoint）
+    #[serde(default)]
+    pub db_maintenance: DbMaintenanceSettings,
+    /// 凭证池存储后端（默认本地 SQLite，可切换为 Redis / PostgreSQL 供多实例共享）
+    #[serde(default)]
+    pub pool_storage: PoolStorageSettings,
+    /// 分布式限流（共享计数与出站限额）
+    #[serde(default)]
+    pub distributed_rate_limit: DistributedRateLimitSettings,
+    /// 突发流量平滑
+    #[serde(default)]
+    pub burst_smoothing: BurstSmoothingSettings,
+    /// 容量错误自动降级模型
+    #[serde(default)]
+    pub model_downgrade: ModelDowngradeSettings,
+    /// 后台任务与多实例选主
+    #[serde(default)]
+    pub cluster: ClusterSettings,
+    /// 对等实例转发（本地无可用凭证时）
+    #[serde(default)]
+    pub peer_forwarding: PeerForwardingSettings,
+    /// 上游响应头透传
+    #[serde(default)]
+    pub upstream_headers: UpstreamHeaderSettings,
+    /// 伪流式（上游非流式响应转 SSE）
+    #[serde(default)]
+    pub fake_streaming: FakeStreamingSettings,
+    /// 流式响应转换（增量合并、NDJSON）
+    #[serde(default)]
+    pub stream_transform: StreamTransformSettings,
+    /// SSE 心跳配置
+    #[serde(default)]
+    pub sse_heartbeat: SseHeartbeatSettings,
+    /// 管理接口 OIDC 认证配置
+    #[serde(default)]
+    pub admin_oidc: AdminOidcSettings,
+    /// 响应归属标注（实际处理请求的 Provider / 模型）
+    #[serde(default)]
+    pub attribution: AttributionSettings,
+    /// 搜索接地引用透传
+    #[serde(default)]
+    pub citations: CitationSettings,
+    /// Gemini 代码执行工具
+    #[serde(default)]
+    pub code_execution: CodeExecutionSettings,
+    /// 对话响应图片输出
+    #[serde(default)]
+    pub chat_images: ChatImageSettings,
+    /// 提示词防火墙
+    #[serde(default)]
+    pub prompt_firewall: PromptFirewallSettings,
+    /// 响应内容策略
+    #[serde(default)]
+    pub content_policy: ContentPolicySettings,
+    /// 最大输出 Token 上限
+    #[serde(default)]
+    pub max_output_tokens: MaxOutputTokenSettings,
+    /// 流式输出上限（累计 Token / 字节超限时截断）
+    #[serde(default)]
+    pub stream_output_caps: StreamOutputCapSettings,
+    /// 本地模型预热与保活
+    #[serde(default)]
+    pub warm_pool: WarmPoolSettings,
+    /// 模型级并发与排队
+    #[serde(default)]
+    pub model_concurrency: ModelConcurrencySettings,
+    /// 数据保留与定时清理
+    #[serde(default)]
+    pub retention: RetentionSettings,
+    /// 端点开关
+    #[serde(default)]
+    pub endpoints: EndpointToggleSettings,
+    /// 维护模式
+    #[serde(default)]
+    pub maintenance_mode: MaintenanceModeSettings,
+    /// 请求优先级通道（交互 / 后台）
+    #[serde(default)]
+    pub priority_lanes: PriorityLaneSettings,
+    /// 在途请求日志（崩溃恢复）
+    #[serde(default)]
+    pub request_journal: RequestJournalSettings,
+    /// 按地区可达性自动走代理
+    #[serde(default)]
+    pub regional_proxy: RegionalProxySettings,
+    /// 转换器影子验证
+    #[serde(default)]
+    pub converter_shadow: ConverterShadowSettings,
+    /// 按模型族选择分词器
+    #[serde(default)]
+    pub tokenizer: TokenizerSettings,
+    /// 多模态上传去重
+    #[serde(default)]
+    pub upload_dedup: UploadDedupSettings,
+    /// Gemini 显式上下文缓存
+    #[serde(default)]
+    pub context_cache: ContextCacheSettings,
+    /// 对话记录采集（用于导出微调数据集）
+    #[serde(default)]
+    pub transcript_capture: TranscriptCaptureSettings,
+    /// 受限 Key 自动轮换
+    #[serde(default)]
+    pub key_rotation: KeyRotationSettings,
+    /// API 错误体等后端文案的语言（"zh" 或 "en"），未设置时跟随界面语言 `language`；
+    /// 请求携带 `Accept-Language` 时按请求协商
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
 }
 
 /// 响应缓存配置
@@ -1425,6 +1619,18 @@ pub fn is_default_api_key(api_key: &str) -> bool {
     api_key == DEFAULT_API_KEY
 }
 
+/// 强 API Key 最小长度
+const STRONG_API_KEY_MIN_LEN: usize = 24;
+
+/// 是否为强 API Key（非默认值、足够长且字符不过于单一）
+pub fn is_strong_api_key(api_key: &str) -> bool {
+    let distinct = api_key
+        .chars()
+        .collect::<std::collections::HashSet<_>>()
+        .len();
+    !is_default_api_key(api_key) && api_key.len() >= STRONG_API_KEY_MIN_LEN && distinct >= 10
+}
+
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
@@ -1433,6 +1639,52 @@ impl Default for ServerConfig {
             api_key: default_api_key(),
             tls: TlsConfig::default(),
             response_cache: ResponseCacheSettings::default(),
+            embedding_cache: EmbeddingCacheSettings::default(),
+            rag: RagSettings::default(),
+            rerank: RerankSettings::default(),
+            dev_utils: DevUtilsSettings::default(),
+            cors: CorsSettings::default(),
+            port_conflict: PortConflictSettings::default(),
+            lan_discovery: LanDiscoverySettings::default(),
+            request_signing: RequestSigningSettings::default(),
+            remote_control: RemoteControlSettings::default(),
+            auth_lockout: AuthLockoutSettings::default(),
+            keychain: KeychainSettings::default(),
+            db_maintenance: DbMaintenanceSettings::default(),
+            pool_storage: PoolStorageSettings::default(),
+            distributed_rate_limit: DistributedRateLimitSettings::default(),
+            burst_smoothing: BurstSmoothingSettings::default(),
+            model_downgrade: ModelDowngradeSettings::default(),
+            cluster: ClusterSettings::default(),
+            peer_forwarding: PeerForwardingSettings::default(),
+            upstream_headers: UpstreamHeaderSettings::default(),
+            fake_streaming: FakeStreamingSettings::default(),
+            stream_transform: StreamTransformSettings::default(),
+            sse_heartbeat: SseHeartbeatSettings::default(),
+            admin_oidc: AdminOidcSettings::default(),
+            attribution: AttributionSettings::default(),
+            citations: CitationSettings::default(),
+            code_execution: CodeExecutionSettings::default(),
+            chat_images: ChatImageSettings::default(),
+            prompt_firewall: PromptFirewallSettings::default(),
+            content_policy: ContentPolicySettings::default(),
+            max_output_tokens: MaxOutputTokenSettings::default(),
+            stream_output_caps: StreamOutputCapSettings::default(),
+            warm_pool: WarmPoolSettings::default(),
+            model_concurrency: ModelConcurrencySettings::default(),
+            retention: RetentionSettings::default(),
+            endpoints: EndpointToggleSettings::default(),
+            maintenance_mode: MaintenanceModeSettings::default(),
+            priority_lanes: PriorityLaneSettings::default(),
+            request_journal: RequestJournalSettings::default(),
+            regional_proxy: RegionalProxySettings::default(),
+            converter_shadow: ConverterShadowSettings::default(),
+            tokenizer: TokenizerSettings::default(),
+            upload_dedup: UploadDedupSettings::default(),
+            context_cache: ContextCacheSettings::default(),
+            transcript_capture: TranscriptCaptureSettings::default(),
+            key_rotation: KeyRotationSettings::default(),
+            locale: None,
         }
     }
 }
@@ -1455,6 +1707,15 @@ pub struct ProvidersConfig {
     /// Claude 自定义 Provider 配置
     #[serde(default)]
     pub claude: CustomProviderConfig,
+    /// 其他 Provider 的上游端点覆盖（键为 Provider 名称，如 `antigravity`、`codex`、`vertex`）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub endpoints: HashMap<String, EndpointOverride>,
+    /// 多区域端点的延迟探测与选择
+    #[serde(default)]
+    pub endpoint_selection: EndpointSelectionConfig,
+    /// 上游企业网关认证（键为 Provider 名称，如 `openai`、`gemini_api_key`）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub upstream_auth: HashMap<String, UpstreamAuthConfig>,
 }
 
 impl Default for ProvidersConfig {
@@ -1465,29 +1726,37 @@ impl Default for ProvidersConfig {
                 credentials_path: Some("~/.aws/sso/cache/kiro-auth-token.json".to_string()),
                 region: Some("us-east-1".to_string()),
                 project_id: None,
+                ..Default::default()
             },
             gemini: ProviderConfig {
                 enabled: false,
                 credentials_path: Some("~/.gemini/oauth_creds.json".to_string()),
                 region: None,
                 project_id: None,
+                ..Default::default()
             },
             qwen: ProviderConfig {
                 enabled: false,
                 credentials_path: Some("~/.qwen/oauth_creds.json".to_string()),
                 region: None,
                 project_id: None,
+                ..Default::default()
             },
             openai: CustomProviderConfig {
                 enabled: false,
                 api_key: None,
                 base_url: Some("https://api.openai.com/v1".to_string()),
+                ..Default::default()
             },
             claude: CustomProviderConfig {
                 enabled: false,
                 api_key: None,
                 base_url: Some("https://api.anthropic.com".to_string()),
+                ..Default::default()
             },
+            endpoints: HashMap::new(),
+            endpoint_selection: EndpointSelectionConfig::default(),
+            upstream_auth: HashMap::new(),
         }
     }
 }
@@ -1507,6 +1776,12 @@ pub struct ProviderConfig {
     /// 项目 ID
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub project_id: Option<String>,
+    /// 上游基础 URL 覆盖（区域端点、预发环境、自建网关）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// 上游 API 版本覆盖
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_version: Option<String>,
 }
 
 /// 自定义 Provider 配置（API Key 方式）
@@ -1521,6 +1796,144 @@ pub struct CustomProviderConfig {
     /// 基础 URL
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub base_url: Option<String>,
+    /// 上游 API 版本覆盖（Claude 为 `anthropic-version` 请求头）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_version: Option<String>,
+}
+
+/// 上游端点覆盖
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct EndpointOverride {
+    /// 上游基础 URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// 上游 API 版本
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_version: Option<String>,
+}
+
+/// 上游企业网关认证
+///
+/// 自建网关前置 Gemini / OpenAI 等上游时，按配置为发往网关的请求附加认证信息：
+/// 静态请求头、HMAC 请求签名、OAuth 客户端凭证令牌，可组合使用。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct UpstreamAuthConfig {
+    /// 生效的主机（支持 `*` 通配），为空时使用该 Provider 端点覆盖的主机
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hosts: Vec<String>,
+    /// 静态请求头（覆盖同名请求头）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    /// HMAC 请求签名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hmac: Option<UpstreamHmacAuth>,
+    /// OAuth 客户端凭证（client_credentials）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oauth_client_credentials: Option<UpstreamOAuthClientCredentials>,
+}
+
+fn default_hmac_signature_header() -> String {
+    "x-signature".to_string()
+}
+
+fn default_hmac_timestamp_header() -> String {
+    "x-timestamp".to_string()
+}
+
+/// HMAC 签名编码
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HmacEncoding {
+    #[default]
+    Hex,
+    Base64,
+}
+
+/// HMAC-SHA256 请求签名
+///
+/// 签名串为 `{时间戳}\n{方法}\n{路径与查询}\n{请求体 SHA-256 十六进制}`，时间戳为 Unix 秒。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UpstreamHmacAuth {
+    /// 签名密钥
+    pub secret: String,
+    /// 密钥 ID（设置后写入 `key_id_header`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+    /// 密钥 ID 请求头
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id_header: Option<String>,
+    /// 签名请求头
+    #[serde(default = "default_hmac_signature_header")]
+    pub signature_header: String,
+    /// 时间戳请求头
+    #[serde(default = "default_hmac_timestamp_header")]
+    pub timestamp_header: String,
+    /// 签名编码
+    #[serde(default)]
+    pub encoding: HmacEncoding,
+}
+
+fn default_oauth_token_header() -> String {
+    "authorization".to_string()
+}
+
+/// OAuth 客户端凭证
+///
+/// 向 `token_url` 换取访问令牌并缓存至过期前，以 `Bearer <令牌>` 写入 `header`。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UpstreamOAuthClientCredentials {
+    /// 令牌端点
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// 申请的 scope（空格分隔）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// 申请的 audience
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
+    /// 写入令牌的请求头
+    #[serde(default = "default_oauth_token_header")]
+    pub header: String,
+}
+
+fn default_endpoint_selection_enabled() -> bool {
+    true
+}
+
+fn default_endpoint_probe_interval_secs() -> u64 {
+    300
+}
+
+/// 多区域端点选择配置
+///
+/// 定期探测各区域端点的延迟，请求时优先使用最快的端点；
+/// `pinned` 中的固定端点优先于探测结果。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EndpointSelectionConfig {
+    /// 是否启用延迟探测
+    #[serde(default = "default_endpoint_selection_enabled")]
+    pub enabled: bool,
+    /// 探测间隔（秒）
+    #[serde(default = "default_endpoint_probe_interval_secs")]
+    pub probe_interval_secs: u64,
+    /// 额外的候选端点（键为 Provider 名称，追加在内置区域端点之后）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub regions: HashMap<String, Vec<String>>,
+    /// 固定端点（键为凭证 UUID 或 Provider 名称，凭证 UUID 优先）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub pinned: HashMap<String, String>,
+}
+
+impl Default for EndpointSelectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_endpoint_selection_enabled(),
+            probe_interval_secs: default_endpoint_probe_interval_secs(),
+            regions: HashMap::new(),
+            pinned: HashMap::new(),
+        }
+    }
 }
 
 /// 路由配置
@@ -1532,6 +1945,15 @@ pub struct RoutingConfig {
     /// 模型别名映射
     #[serde(default)]
     pub model_aliases: HashMap<String, String>,
+    /// 弃用模型处理
+    #[serde(default)]
+    pub model_deprecation: ModelDeprecationConfig,
+    /// 按请求规模路由
+    #[serde(default)]
+    pub size_routing: SizeRoutingConfig,
+    /// 按时间段调度 Provider
+    #[serde(default)]
+    pub time_windows: TimeWindowRoutingConfig,
 }
 
 fn default_provider() -> String {
@@ -1543,10 +1965,155 @@ impl Default for RoutingConfig {
         Self {
             default_provider: default_provider(),
             model_aliases: HashMap::new(),
+            model_deprecation: ModelDeprecationConfig::default(),
+            size_routing: SizeRoutingConfig::default(),
+            time_windows: TimeWindowRoutingConfig::default(),
+        }
+    }
+}
+
+/// 弃用模型处理配置
+///
+/// 客户端请求已下线或改名的上游模型时记录警告并在响应头中提示，
+/// 开启 `rewrite` 时透明改写为替代模型。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModelDeprecationConfig {
+    /// 是否检查弃用模型
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 是否改写为替代模型（关闭后只记录警告与响应头）
+    #[serde(default = "default_true")]
+    pub rewrite: bool,
+    /// 自定义映射（弃用模型 -> 替代模型），覆盖或追加内置条目；替代模型留空表示不视为弃用
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub replacements: HashMap<String, String>,
+}
+
+impl Default for ModelDeprecationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rewrite: true,
+            replacements: HashMap::new(),
         }
     }
 }
 
+/// 提示词规模等级
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptSizeClass {
+    Small,
+    Medium,
+    Large,
+}
+
+impl std::fmt::Display for PromptSizeClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Small => write!(f, "small"),
+            Self::Medium => write!(f, "medium"),
+            Self::Large => write!(f, "large"),
+        }
+    }
+}
+
+/// 规模路由规则
+///
+/// `size` 与 `attachments` 至少填写一项，填写的条件需全部满足；
+/// 命中后无论客户端请求哪个模型都改写为 `model`。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SizeRoutingRule {
+    /// 匹配的规模等级，留空表示不限
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<PromptSizeClass>,
+    /// 是否携带附件（图片、文档），留空表示不限
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<bool>,
+    /// 目标模型
+    pub model: String,
+}
+
+/// 按请求规模路由配置
+///
+/// 按估算的提示词 Token 数把请求分为 small / medium / large 三档，
+/// 规则按顺序匹配第一条。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SizeRoutingConfig {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 估算 Token 数达到该值视为 medium
+    #[serde(default = "default_size_medium_tokens")]
+    pub medium_tokens: u32,
+    /// 估算 Token 数达到该值视为 large
+    #[serde(default = "default_size_large_tokens")]
+    pub large_tokens: u32,
+    /// 路由规则
+    #[serde(default)]
+    pub rules: Vec<SizeRoutingRule>,
+}
+
+fn default_size_medium_tokens() -> u32 {
+    8_000
+}
+
+fn default_size_large_tokens() -> u32 {
+    32_000
+}
+
+impl Default for SizeRoutingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            medium_tokens: default_size_medium_tokens(),
+            large_tokens: default_size_large_tokens(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// 时间段路由规则
+///
+/// 时间按本机时区计算，`start` 晚于 `end` 表示跨午夜（如 `22:00`–`07:00`）。
+/// `provider` 与 `model` 至少填写一项。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimeWindowRule {
+    /// 规则名称（用于日志）
+    #[serde(default)]
+    pub name: String,
+    /// 生效的星期（`mon`…`sun`），留空表示每天；跨午夜时按开始当天计算
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub days: Vec<String>,
+    /// 开始时间（`HH:MM`）
+    pub start: String,
+    /// 结束时间（`HH:MM`，不含）
+    pub end: String,
+    /// 仅对这些模型生效（支持 `*` 通配），留空表示全部模型
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+    /// 时间段内使用的 Provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// 时间段内改写的模型
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+/// 按时间段调度 Provider 配置
+///
+/// 规则按顺序匹配第一条，例如只在下班时间使用个人额度，或夜间切换到低价 Provider。
+/// 请求头 `X-Provider-Id` 指定的 Provider 不受影响。
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimeWindowRoutingConfig {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 路由规则
+    #[serde(default)]
+    pub rules: Vec<TimeWindowRule>,
+}
+
 /// 重试配置
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct RetrySettings {
@@ -1626,6 +2193,15 @@ pub struct CrashReportingConfig {
     /// 是否发送可能包含 PII 的默认字段
     #[serde(default)]
     pub send_pii: bool,
+    /// 用户是否同意自动上传到远端（未同意时即使配置了 DSN 也只保存在本地）
+    #[serde(default)]
+    pub upload_consent: bool,
+    /// 是否在本地保存崩溃报告（panic 与前端异常，脱敏后写入 `crash_reports` 目录）
+    #[serde(default = "default_crash_reporting_local_capture")]
+    pub local_capture: bool,
+    /// 本地最多保留的崩溃报告数，超出时删除最旧的
+    #[serde(default = "default_crash_reporting_max_local_reports")]
+    pub max_local_reports: usize,
 }
 
 fn default_crash_reporting_enabled() -> bool {
@@ -1640,6 +2216,14 @@ fn default_crash_reporting_sample_rate() -> f64 {
     1.0
 }
 
+fn default_crash_reporting_local_capture() -> bool {
+    true
+}
+
+fn default_crash_reporting_max_local_reports() -> usize {
+    20
+}
+
 impl Default for CrashReportingConfig {
     fn default() -> Self {
         Self {
@@ -1648,6 +2232,39 @@ impl Default for CrashReportingConfig {
             environment: default_crash_reporting_environment(),
             sample_rate: default_crash_reporting_sample_rate(),
             send_pii: false,
+            upload_consent: false,
+            local_capture: default_crash_reporting_local_capture(),
+            max_local_reports: default_crash_reporting_max_local_reports(),
+        }
+    }
+}
+
+/// 匿名使用统计配置
+///
+/// 只统计端点类别与结果类别的粗粒度计数，上传前需在应用内预览，上传内容加入差分隐私噪声
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AnonymousStatsConfig {
+    /// 总开关：关闭时不统计、不上传，并清空已统计的数据
+    #[serde(default)]
+    pub enabled: bool,
+    /// 上传地址，未设置时只能在本地查看
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_url: Option<String>,
+    /// 差分隐私参数 ε，越小噪声越大
+    #[serde(default = "default_anonymous_stats_epsilon")]
+    pub epsilon: f64,
+}
+
+fn default_anonymous_stats_epsilon() -> f64 {
+    1.0
+}
+
+impl Default for AnonymousStatsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            upload_url: None,
+            epsilon: default_anonymous_stats_epsilon(),
         }
     }
 }
@@ -2157,9 +2774,12 @@ impl Default for Config {
             user_profile: UserProfile::default(),
             rate_limit: RateLimitSettings::default(),
             crash_reporting: CrashReportingConfig::default(),
+            anonymous_stats: AnonymousStatsConfig::default(),
             conversation: ConversationSettings::default(),
             hint_router: HintRouterSettings::default(),
             pairing: PairingSettings::default(),
+            access_control: AccessControlSettings::default(),
+            cloud_backup: CloudBackupSettings::default(),
             automation: AutomationSettings::default(),
             gateway: GatewayConfig::default(),
             channels: ChannelsConfig::default(),
@@ -2909,6 +3529,14 @@ mod unit_tests {
         assert_eq!(parsed, entry);
     }
 
+    #[test]
+    fn test_is_strong_api_key() {
+        assert!(is_strong_api_key(&generate_secure_api_key()));
+        assert!(!is_strong_api_key(DEFAULT_API_KEY));
+        assert!(!is_strong_api_key("short-key"));
+        assert!(!is_strong_api_key(&"a".repeat(40)));
+    }
+
     #[test]
     fn test_api_key_entry_serialization() {
         let entry = ApiKeyEntry {
@@ -3511,6 +4139,154 @@ pub struct PairingSettings {
     pub enabled: bool,
 }
 
+/// 应用内角色（由低到高）
+///
+/// - `viewer`：只读，可查看仪表盘、日志与统计
+/// - `operator`：可执行运行时操作（启停服务、刷新 Token、健康检查等）
+/// - `admin`：可修改配置、管理与删除凭证
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Default,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum AppRole {
+    Viewer,
+    Operator,
+    #[default]
+    Admin,
+}
+
+impl AppRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Operator => "operator",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+/// 应用访问控制配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct AccessControlSettings {
+    /// 启动时的角色（默认 admin，即不做限制）
+    #[serde(default)]
+    pub default_role: AppRole,
+    /// 切换到更高角色时校验的 PIN 的加盐摘要（PBKDF2-HMAC-SHA256，为空时不允许升级角色）
+    #[serde(default, alias = "pin_sha256", skip_serializing_if = "Option::is_none")]
+    pub pin_hash: Option<String>,
+}
+
+/// 云备份目标类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CloudBackupTarget {
+    /// S3 兼容存储（AWS S3、MinIO、Cloudflare R2 等）
+    #[default]
+    S3,
+    /// WebDAV（坚果云、Nextcloud 等）
+    Webdav,
+}
+
+/// 定时云备份配置（配置文件 + 数据库，口令加密后上传）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CloudBackupSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 备份间隔（小时）
+    #[serde(default = "default_cloud_backup_interval_hours")]
+    pub interval_hours: u64,
+    /// 远端保留的备份份数（按时间保留最新的 N 份）
+    #[serde(default = "default_cloud_backup_retention_count")]
+    pub retention_count: usize,
+    /// 是否包含数据库（凭证池、统计等）
+    #[serde(default = "default_true")]
+    pub include_database: bool,
+    /// 加密口令（恢复时需要，遗失后备份无法解密）
+    #[serde(default)]
+    pub passphrase: String,
+    #[serde(default)]
+    pub target: CloudBackupTarget,
+    #[serde(default)]
+    pub s3: S3BackupSettings,
+    #[serde(default)]
+    pub webdav: WebDavBackupSettings,
+}
+
+fn default_cloud_backup_interval_hours() -> u64 {
+    24
+}
+
+fn default_cloud_backup_retention_count() -> usize {
+    7
+}
+
+impl Default for CloudBackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_hours: default_cloud_backup_interval_hours(),
+            retention_count: default_cloud_backup_retention_count(),
+            include_database: true,
+            passphrase: String::new(),
+            target: CloudBackupTarget::default(),
+            s3: S3BackupSettings::default(),
+            webdav: WebDavBackupSettings::default(),
+        }
+    }
+}
+
+/// S3 兼容存储
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct S3BackupSettings {
+    /// 服务地址，如 `https://s3.us-east-1.amazonaws.com` 或 `https://<account>.r2.cloudflarestorage.com`
+    #[serde(default)]
+    pub endpoint: String,
+    #[serde(default = "default_s3_region")]
+    pub region: String,
+    #[serde(default)]
+    pub bucket: String,
+    /// 对象前缀（目录），如 `lime/`
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub access_key_id: String,
+    #[serde(default)]
+    pub secret_access_key: String,
+    /// 使用路径风格 URL（`endpoint/bucket/key`），MinIO 等自建服务通常需要
+    #[serde(default = "default_true")]
+    pub path_style: bool,
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+impl Default for S3BackupSettings {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            region: default_s3_region(),
+            bucket: String::new(),
+            prefix: String::new(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            path_style: true,
+        }
+    }
+}
+
+/// WebDAV 存储
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct WebDavBackupSettings {
+    /// 备份目录地址，如 `https://dav.jianguoyun.com/dav/lime/`
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+}
+
 // ============ Gateway 配置类型 ============
 
 /// Gateway 全局配置
@@ -3546,6 +4322,11 @@ pub struct GatewayTunnelConfig {
     pub public_base_url: Option<String>,
     #[serde(default)]
     pub cloudflare: CloudflareTunnelConfig,
+    #[serde(default)]
+    pub ngrok: NgrokTunnelConfig,
+    /// 隧道暴露 API 服务器时要求强 API Key（经隧道转发的请求拒绝弱 Key）
+    #[serde(default = "default_true")]
+    pub require_strong_api_key: bool,
 }
 
 impl Default for GatewayTunnelConfig {
@@ -3559,6 +4340,8 @@ impl Default for GatewayTunnelConfig {
             local_port: default_gateway_tunnel_local_port(),
             public_base_url: None,
             cloudflare: CloudflareTunnelConfig::default(),
+            ngrok: NgrokTunnelConfig::default(),
+            require_strong_api_key: true,
         }
     }
 }
@@ -3586,6 +4369,17 @@ pub struct CloudflareTunnelConfig {
     pub dns_name: Option<String>,
 }
 
+/// ngrok 隧道配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct NgrokTunnelConfig {
+    /// ngrok authtoken（用户自行提供）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authtoken: Option<String>,
+    /// 固定域名（例如 lime.ngrok.app，为空时使用随机域名）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+}
+
 fn default_gateway_tunnel_provider() -> String {
     "cloudflare".to_string()
 }
@@ -5,7 +5,10 @@
 
 #![allow(dead_code)]
 
+use super::audit::{record_config_value_change, ConfigAuditSource};
+use super::secrets::{externalize_config_secrets, resolve_config_secrets, MAIN_CONFIG_NAMESPACE};
 use super::types::Config;
+use crate::secret_store::secret_store;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
@@ -84,9 +87,17 @@ impl ConfigManager {
         })
     }
 
-    /// 从 YAML 字符串解析配置
+    /// 从 YAML 字符串解析配置（主配置的钥匙串引用会被解析为明文）
     pub fn parse_yaml(yaml: &str) -> Result<Config, ConfigError> {
-        serde_yaml::from_str(yaml).map_err(|e| ConfigError::ParseError(e.to_string()))
+        Self::parse_yaml_in(yaml, MAIN_CONFIG_NAMESPACE)
+    }
+
+    /// 从 YAML 字符串解析配置，只解析 `namespace` 下的钥匙串引用
+    pub fn parse_yaml_in(yaml: &str, namespace: &str) -> Result<Config, ConfigError> {
+        let mut config: Config =
+            serde_yaml::from_str(yaml).map_err(|e| ConfigError::ParseError(e.to_string()))?;
+        resolve_config_secrets(secret_store(), &mut config, namespace);
+        Ok(config)
     }
 
     /// 将配置序列化为 YAML 字符串
@@ -110,7 +121,7 @@ impl ConfigManager {
             let backup_path = path.with_extension("yaml.backup");
             let _ = std::fs::copy(path, backup_path);
         }
-        let yaml = Self::to_yaml(&self.config)?;
+        let yaml = Self::to_yaml(&config_for_disk(&self.config))?;
         std::fs::write(path, yaml).map_err(|e| ConfigError::WriteError(e.to_string()))
     }
 
@@ -664,7 +675,11 @@ pub fn load_config() -> Result<Config, Box<dyn std::error::Error>> {
     if yaml_path.exists() {
         let content = std::fs::read_to_string(&yaml_path)?;
         let mut config: Config = serde_yaml::from_str(&content)?;
-        let mut should_save = config.normalize_workspace_preferences();
+        secret_store().set_enabled(config.server.keychain.enabled);
+        // 明文密钥迁入钥匙串（或禁用钥匙串后写回明文）
+        let mut should_save =
+            resolve_config_secrets(secret_store(), &mut config, MAIN_CONFIG_NAMESPACE);
+        should_save |= config.normalize_workspace_preferences();
         // 如果配置中使用默认 API Key，生成强随机 Key 并保存
         if is_default_api_key(&config.server.api_key) {
             let new_key = generate_secure_api_key();
@@ -684,7 +699,10 @@ pub fn load_config() -> Result<Config, Box<dyn std::error::Error>> {
     if json_path.exists() {
         let content = std::fs::read_to_string(&json_path)?;
         let mut config: Config = serde_json::from_str(&content)?;
-        let mut should_save = config.normalize_workspace_preferences();
+        secret_store().set_enabled(config.server.keychain.enabled);
+        let mut should_save =
+            resolve_config_secrets(secret_store(), &mut config, MAIN_CONFIG_NAMESPACE);
+        should_save |= config.normalize_workspace_preferences();
         // 如果配置中使用默认 API Key，生成强随机 Key 并保存
         if is_default_api_key(&config.server.api_key) {
             let new_key = generate_secure_api_key();
@@ -712,21 +730,48 @@ pub fn load_config() -> Result<Config, Box<dyn std::error::Error>> {
     Ok(config)
 }
 
+/// 生成主配置的写盘副本（密钥移入钥匙串）
+fn config_for_disk(config: &Config) -> Config {
+    secret_store().set_enabled(config.server.keychain.enabled);
+    externalize_config_secrets(secret_store(), config, MAIN_CONFIG_NAMESPACE)
+}
+
 /// 保存配置（同时写入 YAML 与 JSON，兼容旧版）
 pub fn save_config(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    save_config_with_source(config, ConfigAuditSource::Command)
+}
+
+/// 保存配置并按来源记录审计
+pub fn save_config_with_source(
+    config: &Config,
+    source: ConfigAuditSource,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let yaml_path = ConfigManager::default_config_path();
+    let before = read_yaml_tree(&yaml_path);
+
     // 主配置优先写入 YAML
     save_config_yaml(config)?;
 
+    // 以落盘内容对比，密钥外置后的占位值不会被误判为变更
+    if let (Some(before), Some(after)) = (before, read_yaml_tree(&yaml_path)) {
+        record_config_value_change(source, &before, &after);
+    }
+
     // 兼容旧版 JSON 配置
     let path = json_config_path();
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
-    let content = serde_json::to_string_pretty(config)?;
+    let content = serde_json::to_string_pretty(&config_for_disk(config))?;
     std::fs::write(&path, content)?;
     Ok(())
 }
 
+fn read_yaml_tree(path: &Path) -> Option<serde_json::Value> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_yaml::from_str(&content).ok()
+}
+
 /// 保存配置为 YAML 格式
 pub fn save_config_yaml(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
     let path = ConfigManager::default_config_path();
@@ -737,8 +782,19 @@ pub fn save_config_yaml(config: &Config) -> Result<(), Box<dyn std::error::Error
         let backup_path = path.with_extension("yaml.backup");
         let _ = std::fs::copy(&path, &backup_path);
     }
-    let content = serde_yaml::to_string(config)?;
-    std::fs::write(&path, content)?;
+    let content = serde_yaml::to_string(&config_for_disk(config))?;
+    write_atomic(&path, content.as_bytes())?;
+    Ok(())
+}
+
+/// 先写临时文件再重命名，避免写入中断或热重载读到半个文件
+fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let tmp_path = path.with_extension("yaml.tmp");
+    std::fs::write(&tmp_path, content)?;
+    if let Err(e) = std::fs::rename(&tmp_path, path) {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e);
+    }
     Ok(())
 }
 
@@ -24,6 +24,30 @@ pub enum HealthStatus {
     Unknown,
 }
 
+/// 健康分低于该值时展示为不健康
+///
+/// 健康分只影响展示与负载均衡权重，凭证的可用状态仍由连续失败阈值决定。
+pub const UNHEALTHY_SCORE_THRESHOLD: f64 = 0.3;
+
+impl HealthStatus {
+    /// 按健康分映射为展示用的健康状态
+    pub fn from_score(score: f64, total_requests: u64, consecutive_failures: u32) -> Self {
+        if total_requests == 0 {
+            HealthStatus::Unknown
+        } else if score < UNHEALTHY_SCORE_THRESHOLD {
+            HealthStatus::Unhealthy {
+                reason: format!(
+                    "健康分 {:.2} 低于阈值 {:.2}",
+                    score, UNHEALTHY_SCORE_THRESHOLD
+                ),
+                consecutive_failures,
+            }
+        } else {
+            HealthStatus::Healthy
+        }
+    }
+}
+
 /// 健康检查结果
 #[derive(Debug, Clone)]
 pub struct HealthCheckResult {
@@ -31,21 +55,101 @@ pub struct HealthCheckResult {
     pub credential_id: String,
     /// 健康状态
     pub status: HealthStatus,
+    /// 健康分（0.0 ~ 1.0）
+    pub score: f64,
     /// 检查时间
     pub checked_at: DateTime<Utc>,
     /// 检查延迟（毫秒）
     pub latency_ms: Option<u64>,
 }
 
+/// 错误响应摘录的最大字符数
+const PROBE_ERROR_EXCERPT_CHARS: usize = 500;
+
+/// 按需探测（试运行）的原始结果
+///
+/// 与定时巡检不同，试运行只返回探测结果，不更新凭证的健康状态与错误计数。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthProbeOutcome {
+    /// 凭证 ID
+    pub credential_id: String,
+    /// 探测使用的模型
+    pub model: String,
+    /// 是否成功
+    pub success: bool,
+    /// 探测耗时（毫秒）
+    pub latency_ms: u64,
+    /// 上游 HTTP 状态码（能从错误中解析时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    /// 错误信息摘录（最多 500 字符）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_excerpt: Option<String>,
+    /// 探测时间
+    pub checked_at: DateTime<Utc>,
+}
+
+impl HealthProbeOutcome {
+    /// 由探测结果构造，错误形如 `HTTP 429 Too Many Requests - {body}` 时解析出状态码
+    pub fn from_result(
+        credential_id: &str,
+        model: &str,
+        result: &Result<(), String>,
+        latency_ms: u64,
+    ) -> Self {
+        let (status_code, error_excerpt) = match result {
+            Ok(()) => (None, None),
+            Err(error) => (
+                parse_http_status(error),
+                Some(error.chars().take(PROBE_ERROR_EXCERPT_CHARS).collect()),
+            ),
+        };
+        Self {
+            credential_id: credential_id.to_string(),
+            model: model.to_string(),
+            success: result.is_ok(),
+            latency_ms,
+            status_code,
+            error_excerpt,
+            checked_at: Utc::now(),
+        }
+    }
+}
+
+/// 从 `HTTP 401 ...` 形式的错误信息中解析状态码
+fn parse_http_status(error: &str) -> Option<u16> {
+    let rest = &error[error.find("HTTP ")? + 5..];
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok().filter(|code| (100..600).contains(code))
+}
+
 /// 健康检查配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthCheckConfig {
-    /// 检查间隔
+    /// 检查间隔（未启用自适应时所有凭证统一使用）
     pub check_interval: Duration,
     /// 连续失败阈值（达到此值标记为不健康）
     pub failure_threshold: u32,
     /// 恢复阈值（连续成功此次数后恢复为健康）
     pub recovery_threshold: u32,
+    /// 按流量自适应探测：近期有真实请求的凭证不再发送探测请求，
+    /// 由请求结果被动判断健康状态；空闲和不健康的凭证按 `idle_check_interval` 探测
+    #[serde(default)]
+    pub adaptive: bool,
+    /// 自适应时空闲凭证的探测间隔
+    #[serde(default = "default_idle_check_interval")]
+    pub idle_check_interval: Duration,
+    /// 最近一次真实请求距今不超过该时长的凭证视为繁忙
+    #[serde(default = "default_traffic_window")]
+    pub traffic_window: Duration,
+}
+
+fn default_idle_check_interval() -> Duration {
+    Duration::from_secs(30)
+}
+
+fn default_traffic_window() -> Duration {
+    Duration::from_secs(300)
 }
 
 impl Default for HealthCheckConfig {
@@ -54,6 +158,9 @@ impl Default for HealthCheckConfig {
             check_interval: Duration::from_secs(60),
             failure_threshold: 3,
             recovery_threshold: 1,
+            adaptive: false,
+            idle_check_interval: default_idle_check_interval(),
+            traffic_window: default_traffic_window(),
         }
     }
 }
@@ -90,6 +197,59 @@ impl HealthChecker {
         self.config.recovery_threshold
     }
 
+    /// 凭证在统计窗口内是否有真实请求
+    pub fn is_busy(&self, last_used: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+        let window = chrono::Duration::from_std(self.config.traffic_window)
+            .unwrap_or_else(|_| chrono::Duration::zero());
+        last_used.is_some_and(|used| now - used <= window)
+    }
+
+    /// 凭证的探测间隔，`None` 表示无需探测（由真实请求结果被动判断）
+    ///
+    /// 不健康的凭证不会被选中、没有真实流量，自适应时总是按空闲间隔探测以便尽快恢复。
+    pub fn probe_interval(
+        &self,
+        healthy: bool,
+        last_used: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Option<Duration> {
+        if !self.config.adaptive {
+            return Some(self.config.check_interval);
+        }
+        if healthy && self.is_busy(last_used, now) {
+            return None;
+        }
+        Some(self.config.idle_check_interval)
+    }
+
+    /// 凭证是否到了该发送探测请求的时间
+    pub fn probe_due(
+        &self,
+        healthy: bool,
+        last_used: Option<DateTime<Utc>>,
+        last_probe: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let Some(interval) = self.probe_interval(healthy, last_used, now) else {
+            return false;
+        };
+        match (last_probe, chrono::Duration::from_std(interval)) {
+            (Some(probed), Ok(interval)) => now - probed >= interval,
+            _ => true,
+        }
+    }
+
+    /// 内存凭证池中的凭证是否需要探测
+    pub fn needs_probe(
+        &self,
+        credential: &Credential,
+        last_probe: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let healthy = !matches!(credential.status, CredentialStatus::Unhealthy { .. });
+        self.probe_due(healthy, credential.last_used, last_probe, now)
+    }
+
     /// 检查单个凭证的健康状态
     ///
     /// 根据凭证的统计信息判断健康状态
@@ -99,6 +259,7 @@ impl HealthChecker {
         HealthCheckResult {
             credential_id: credential.id.clone(),
             status,
+            score: credential.stats.health_score,
             checked_at: Utc::now(),
             latency_ms: if credential.stats.successful_requests > 0 {
                 Some(credential.stats.avg_latency_ms as u64)
@@ -129,12 +290,11 @@ impl HealthChecker {
             };
         }
 
-        // 如果没有请求记录，状态未知
-        if credential.stats.total_requests == 0 {
-            return HealthStatus::Unknown;
-        }
-
-        HealthStatus::Healthy
+        HealthStatus::from_score(
+            credential.stats.health_score,
+            credential.stats.total_requests,
+            credential.stats.consecutive_failures,
+        )
     }
 
     /// 记录凭证使用失败并更新健康状态
@@ -259,6 +419,26 @@ mod health_tests {
         )
     }
 
+    #[test]
+    fn test_probe_outcome_parses_status_and_excerpt() {
+        let error = format!("HTTP 429 Too Many Requests - {}", "x".repeat(800));
+        let outcome = HealthProbeOutcome::from_result("c1", "gpt-4o-mini", &Err(error), 320);
+        assert!(!outcome.success);
+        assert_eq!(outcome.status_code, Some(429));
+        assert_eq!(
+            outcome.error_excerpt.unwrap().chars().count(),
+            PROBE_ERROR_EXCERPT_CHARS
+        );
+
+        let outcome =
+            HealthProbeOutcome::from_result("c1", "m", &Err("请求失败: timed out".into()), 5);
+        assert_eq!(outcome.status_code, None);
+
+        let outcome = HealthProbeOutcome::from_result("c1", "m", &Ok(()), 12);
+        assert!(outcome.success);
+        assert_eq!(outcome.error_excerpt, None);
+    }
+
     #[test]
     fn test_health_checker_new() {
         let checker = HealthChecker::with_defaults();
@@ -272,12 +452,60 @@ mod health_tests {
             check_interval: Duration::from_secs(30),
             failure_threshold: 5,
             recovery_threshold: 2,
+            ..Default::default()
         };
         let checker = HealthChecker::new(config);
         assert_eq!(checker.failure_threshold(), 5);
         assert_eq!(checker.recovery_threshold(), 2);
     }
 
+    #[test]
+    fn test_adaptive_probe_skips_busy_credentials() {
+        let checker = HealthChecker::new(HealthCheckConfig {
+            adaptive: true,
+            ..Default::default()
+        });
+        let now = Utc::now();
+        let recent = Some(now - chrono::Duration::seconds(60));
+        let stale = Some(now - chrono::Duration::minutes(30));
+
+        // 繁忙的健康凭证只看真实请求结果
+        assert_eq!(checker.probe_interval(true, recent, now), None);
+        assert!(!checker.probe_due(true, recent, None, now));
+        // 空闲或不健康的凭证按空闲间隔探测
+        assert_eq!(
+            checker.probe_interval(true, stale, now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            checker.probe_interval(false, recent, now),
+            Some(Duration::from_secs(30))
+        );
+        assert!(checker.probe_due(true, None, None, now));
+        assert!(!checker.probe_due(true, stale, Some(now - chrono::Duration::seconds(10)), now));
+        assert!(checker.probe_due(true, stale, Some(now - chrono::Duration::seconds(45)), now));
+
+        let mut cred = create_test_credential("test-1");
+        cred.mark_used();
+        assert!(!checker.needs_probe(&cred, None, Utc::now()));
+    }
+
+    #[test]
+    fn test_non_adaptive_probe_uses_check_interval() {
+        let checker = HealthChecker::with_defaults();
+        let now = Utc::now();
+        assert_eq!(
+            checker.probe_interval(true, Some(now), now),
+            Some(Duration::from_secs(60))
+        );
+        assert!(!checker.probe_due(
+            true,
+            Some(now),
+            Some(now - chrono::Duration::seconds(45)),
+            now
+        ));
+    }
+
     #[test]
     fn test_check_healthy_credential() {
         let checker = HealthChecker::with_defaults();
@@ -361,6 +589,27 @@ mod health_tests {
         assert!(matches!(cred.status, CredentialStatus::Active));
     }
 
+    #[test]
+    fn test_low_health_score_maps_to_unhealthy() {
+        let checker = HealthChecker::with_defaults();
+        let mut cred = create_test_credential("test-1");
+
+        // 失败与成功交替：连续失败不超过阈值，但健康分持续走低
+        for _ in 0..6 {
+            cred.stats.record_failure();
+            cred.stats.record_failure();
+            cred.stats.record_success(100);
+        }
+        cred.stats.record_failure();
+        cred.stats.record_failure();
+
+        let result = checker.check(&cred);
+        assert!(result.score < UNHEALTHY_SCORE_THRESHOLD);
+        assert!(matches!(result.status, HealthStatus::Unhealthy { .. }));
+        assert_eq!(HealthStatus::from_score(0.8, 10, 0), HealthStatus::Healthy);
+        assert_eq!(HealthStatus::from_score(1.0, 0, 0), HealthStatus::Unknown);
+    }
+
     #[test]
     fn test_check_all() {
         let checker = HealthChecker::with_defaults();
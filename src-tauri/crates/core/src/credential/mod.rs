@@ -9,7 +9,9 @@ pub mod pool;
 pub mod risk;
 pub mod types;
 
-pub use health::{HealthCheckConfig, HealthCheckResult, HealthChecker, HealthStatus};
+pub use health::{
+    HealthCheckConfig, HealthCheckResult, HealthChecker, HealthProbeOutcome, HealthStatus,
+};
 pub use pool::{CredentialPool, PoolError, PoolStatus};
 pub use risk::{CooldownConfig, RateLimitEvent, RateLimitStats, RiskController, RiskLevel};
 pub use types::{Credential, CredentialData, CredentialStats, CredentialStatus};
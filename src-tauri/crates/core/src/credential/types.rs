@@ -107,8 +107,14 @@ pub enum CredentialStatus {
     Disabled,
 }
 
+/// 健康分 EWMA 平滑系数（越大越偏向最近的请求）
+pub const HEALTH_SCORE_ALPHA: f64 = 0.3;
+
+/// 延迟超过该值（毫秒）的成功请求按比例扣分
+pub const HEALTH_SLOW_LATENCY_MS: f64 = 5_000.0;
+
 /// 凭证统计信息
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CredentialStats {
     /// 总请求数
     pub total_requests: u64,
@@ -118,6 +124,29 @@ pub struct CredentialStats {
     pub consecutive_failures: u32,
     /// 平均延迟（毫秒）
     pub avg_latency_ms: f64,
+    /// 健康分（0.0 ~ 1.0），成功/失败与延迟的指数加权移动平均
+    #[serde(default = "default_health_score")]
+    pub health_score: f64,
+    /// 延迟的指数加权移动平均（毫秒）
+    #[serde(default)]
+    pub latency_ewma_ms: f64,
+}
+
+fn default_health_score() -> f64 {
+    1.0
+}
+
+impl Default for CredentialStats {
+    fn default() -> Self {
+        Self {
+            total_requests: 0,
+            successful_requests: 0,
+            consecutive_failures: 0,
+            avg_latency_ms: 0.0,
+            health_score: default_health_score(),
+            latency_ewma_ms: 0.0,
+        }
+    }
 }
 
 impl CredentialStats {
@@ -130,12 +159,33 @@ impl CredentialStats {
         // 更新平均延迟（移动平均）
         let n = self.successful_requests as f64;
         self.avg_latency_ms = self.avg_latency_ms * (n - 1.0) / n + latency_ms as f64 / n;
+
+        let latency = latency_ms as f64;
+        self.latency_ewma_ms = if self.successful_requests == 1 {
+            latency
+        } else {
+            HEALTH_SCORE_ALPHA * latency + (1.0 - HEALTH_SCORE_ALPHA) * self.latency_ewma_ms
+        };
+        // 慢请求最多扣一半
+        let sample = if latency <= HEALTH_SLOW_LATENCY_MS {
+            1.0
+        } else {
+            (HEALTH_SLOW_LATENCY_MS / latency).max(0.5)
+        };
+        self.update_health_score(sample);
     }
 
     /// 记录失败请求
     pub fn record_failure(&mut self) {
         self.total_requests += 1;
         self.consecutive_failures += 1;
+        self.update_health_score(0.0);
+    }
+
+    fn update_health_score(&mut self, sample: f64) {
+        self.health_score = (HEALTH_SCORE_ALPHA * sample
+            + (1.0 - HEALTH_SCORE_ALPHA) * self.health_score)
+            .clamp(0.0, 1.0);
     }
 
     /// 获取成功率
@@ -227,6 +277,26 @@ mod type_tests {
         assert_eq!(stats.consecutive_failures, 0);
     }
 
+    #[test]
+    fn test_credential_stats_health_score() {
+        let mut stats = CredentialStats::default();
+        assert!((stats.health_score - 1.0).abs() < 0.001);
+
+        stats.record_failure();
+        stats.record_failure();
+        assert!((stats.health_score - 0.49).abs() < 0.001);
+
+        stats.record_success(100);
+        assert!((stats.health_score - 0.643).abs() < 0.001);
+        assert!((stats.latency_ewma_ms - 100.0).abs() < 0.001);
+
+        // 慢请求只部分恢复健康分
+        let before = stats.health_score;
+        stats.record_success(20_000);
+        assert!(stats.health_score > before);
+        assert!(stats.health_score < 0.3 * 1.0 + 0.7 * before);
+    }
+
     #[test]
     fn test_credential_stats_success_rate() {
         let mut stats = CredentialStats::default();
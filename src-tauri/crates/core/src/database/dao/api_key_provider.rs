@@ -906,6 +906,18 @@ impl ApiKeyProviderDao {
         Ok(affected > 0)
     }
 
+    /// 统计存储值相同的 API Key 数量
+    pub fn count_api_keys_by_encrypted(
+        conn: &Connection,
+        api_key_encrypted: &str,
+    ) -> Result<i64, rusqlite::Error> {
+        conn.query_row(
+            "SELECT COUNT(*) FROM api_keys WHERE api_key_encrypted = ?1",
+            [api_key_encrypted],
+            |row| row.get(0),
+        )
+    }
+
     /// 更新 API Key 使用统计
     pub fn update_api_key_usage(
         conn: &Connection,
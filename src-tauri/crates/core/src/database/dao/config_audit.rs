@@ -0,0 +1,152 @@
+//! 配置变更审计 DAO
+//!
+//! 每条记录对应一次配置写入或热重载，`changes` 以 JSON 存储字段级变更。
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ConfigAuditRecord, ConfigFieldChange};
+
+/// 审计记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigAuditEntry {
+    pub id: i64,
+    pub created_at: String,
+    /// 变更来源：ui / command / import / hot_reload / remote_control
+    pub source: String,
+    /// 操作者（系统用户名与应用角色）
+    pub actor: String,
+    pub summary: String,
+    pub changes: Vec<ConfigFieldChange>,
+}
+
+pub struct ConfigAuditDao;
+
+impl ConfigAuditDao {
+    pub fn insert(
+        conn: &Connection,
+        record: &ConfigAuditRecord,
+        actor: &str,
+    ) -> Result<i64, rusqlite::Error> {
+        let changes = serde_json::to_string(&record.changes)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        conn.execute(
+            "INSERT INTO config_audit_log (created_at, source, actor, summary, changes)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                Utc::now().to_rfc3339(),
+                record.source.as_str(),
+                actor,
+                record.summary,
+                changes,
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// 按时间倒序列出审计记录，`path` 不为空时只返回涉及该字段（或其子字段）的记录
+    pub fn list(
+        conn: &Connection,
+        limit: usize,
+        path: Option<&str>,
+    ) -> Result<Vec<ConfigAuditEntry>, rusqlite::Error> {
+        let mut stmt = conn.prepare(
+            "SELECT id, created_at, source, actor, summary, changes
+             FROM config_audit_log ORDER BY id DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let changes: String = row.get(5)?;
+            Ok(ConfigAuditEntry {
+                id: row.get(0)?,
+                created_at: row.get(1)?,
+                source: row.get(2)?,
+                actor: row.get(3)?,
+                summary: row.get(4)?,
+                changes: serde_json::from_str(&changes).unwrap_or_default(),
+            })
+        })?;
+
+        let path = path.map(str::trim).filter(|path| !path.is_empty());
+        let mut entries = Vec::new();
+        for entry in rows {
+            let entry = entry?;
+            let matched = match path {
+                None => true,
+                Some(path) => entry.changes.iter().any(|change| {
+                    change.path == path
+                        || change.path.starts_with(&format!("{path}."))
+                        || path.starts_with(&format!("{}.", change.path))
+                }),
+            };
+            if matched {
+                entries.push(entry);
+                if entries.len() >= limit {
+                    break;
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// 只保留最近 `max_entries` 条记录，返回删除的条数
+    pub fn prune(conn: &Connection, max_entries: usize) -> Result<usize, rusqlite::Error> {
+        conn.execute(
+            "DELETE FROM config_audit_log WHERE id NOT IN (
+                SELECT id FROM config_audit_log ORDER BY id DESC LIMIT ?1
+            )",
+            params![max_entries as i64],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigAuditSource;
+    use crate::database::schema::create_tables;
+    use serde_json::json;
+
+    fn record(path: &str) -> ConfigAuditRecord {
+        ConfigAuditRecord {
+            source: ConfigAuditSource::Ui,
+            summary: path.to_string(),
+            changes: vec![ConfigFieldChange {
+                path: path.to_string(),
+                before: Some(json!(1)),
+                after: Some(json!(2)),
+            }],
+        }
+    }
+
+    #[test]
+    fn should_list_newest_first_and_filter_by_path() {
+        let conn = Connection::open_in_memory().expect("创建内存数据库失败");
+        create_tables(&conn).expect("创建表结构失败");
+
+        ConfigAuditDao::insert(&conn, &record("server.port"), "alice (admin)").expect("写入应成功");
+        ConfigAuditDao::insert(&conn, &record("routing.rules"), "bob (admin)").expect("写入应成功");
+        ConfigAuditDao::insert(&conn, &record("routing.default_provider"), "bob (admin)")
+            .expect("写入应成功");
+
+        let all = ConfigAuditDao::list(&conn, 10, None).expect("查询应成功");
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].summary, "routing.default_provider");
+        assert_eq!(all[2].actor, "alice (admin)");
+        assert_eq!(all[2].source, "ui");
+
+        let routing = ConfigAuditDao::list(&conn, 10, Some("routing")).expect("查询应成功");
+        assert_eq!(routing.len(), 2);
+        let rules = ConfigAuditDao::list(&conn, 10, Some("routing.rules")).expect("查询应成功");
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].changes[0].after, Some(json!(2)));
+
+        assert_eq!(ConfigAuditDao::prune(&conn, 1).expect("裁剪应成功"), 2);
+        assert_eq!(
+            ConfigAuditDao::list(&conn, 10, None)
+                .expect("查询应成功")
+                .len(),
+            1
+        );
+    }
+}
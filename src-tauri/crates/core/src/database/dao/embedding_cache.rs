@@ -0,0 +1,159 @@
+//! 嵌入缓存 DAO
+//!
+//! 向量以小端 f32 字节序存储为 BLOB，主键为 (model, text_hash)。
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+
+pub struct EmbeddingCacheDao;
+
+impl EmbeddingCacheDao {
+    /// 查询缓存向量，命中时同时更新命中计数与最近命中时间
+    pub fn get(
+        conn: &Connection,
+        model: &str,
+        text_hash: &str,
+    ) -> Result<Option<Vec<f32>>, rusqlite::Error> {
+        let blob: Option<Vec<u8>> = conn
+            .query_row(
+                "SELECT embedding FROM embedding_cache WHERE model = ?1 AND text_hash = ?2",
+                params![model, text_hash],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        let Some(blob) = blob else {
+            return Ok(None);
+        };
+
+        conn.execute(
+            "UPDATE embedding_cache SET hit_count = hit_count + 1, last_hit_at = ?3
+             WHERE model = ?1 AND text_hash = ?2",
+            params![model, text_hash, Utc::now().to_rfc3339()],
+        )?;
+
+        Ok(Some(decode_embedding(&blob)))
+    }
+
+    /// 写入或覆盖缓存向量
+    pub fn upsert(
+        conn: &Connection,
+        model: &str,
+        text_hash: &str,
+        embedding: &[f32],
+    ) -> Result<(), rusqlite::Error> {
+        let now = Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT INTO embedding_cache (
+                model, text_hash, dimensions, embedding, hit_count, created_at, last_hit_at
+            ) VALUES (?1, ?2, ?3, ?4, 0, ?5, ?5)
+            ON CONFLICT(model, text_hash) DO UPDATE SET
+                dimensions = excluded.dimensions,
+                embedding = excluded.embedding,
+                last_hit_at = excluded.last_hit_at",
+            params![
+                model,
+                text_hash,
+                embedding.len() as i64,
+                encode_embedding(embedding),
+                now,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn count(conn: &Connection) -> Result<usize, rusqlite::Error> {
+        let count: i64 =
+            conn.query_row("SELECT COUNT(*) FROM embedding_cache", [], |row| row.get(0))?;
+        Ok(count.max(0) as usize)
+    }
+
+    /// 清理过期条目并按最近命中时间裁剪到 `max_entries`
+    ///
+    /// `ttl_days` 为 0 时不按时间清理。返回删除的条目数。
+    pub fn prune(
+        conn: &Connection,
+        max_entries: usize,
+        ttl_days: u32,
+    ) -> Result<usize, rusqlite::Error> {
+        let mut removed = 0;
+
+        if ttl_days > 0 {
+            let cutoff = (Utc::now() - chrono::Duration::days(ttl_days as i64)).to_rfc3339();
+            removed += conn.execute(
+                "DELETE FROM embedding_cache WHERE last_hit_at < ?1",
+                params![cutoff],
+            )?;
+        }
+
+        removed += conn.execute(
+            "DELETE FROM embedding_cache WHERE rowid NOT IN (
+                SELECT rowid FROM embedding_cache ORDER BY last_hit_at DESC LIMIT ?1
+            )",
+            params![max_entries as i64],
+        )?;
+
+        Ok(removed)
+    }
+
+    pub fn clear(conn: &Connection) -> Result<usize, rusqlite::Error> {
+        conn.execute("DELETE FROM embedding_cache", [])
+    }
+}
+
+pub(crate) fn encode_embedding(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+pub(crate) fn decode_embedding(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn should_roundtrip_embedding_and_count_hits() {
+        let conn = Connection::open_in_memory().expect("创建内存数据库失败");
+        create_tables(&conn).expect("创建表结构失败");
+
+        EmbeddingCacheDao::upsert(&conn, "text-embedding-3-small", "h1", &[0.5, -1.25, 3.0])
+            .expect("写入缓存应成功");
+
+        let hit = EmbeddingCacheDao::get(&conn, "text-embedding-3-small", "h1")
+            .expect("查询缓存应成功")
+            .expect("应命中缓存");
+        assert_eq!(hit, vec![0.5, -1.25, 3.0]);
+        assert!(EmbeddingCacheDao::get(&conn, "other-model", "h1")
+            .expect("查询缓存应成功")
+            .is_none());
+
+        let hit_count: i64 = conn
+            .query_row(
+                "SELECT hit_count FROM embedding_cache WHERE text_hash = 'h1'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("查询命中计数应成功");
+        assert_eq!(hit_count, 1);
+    }
+
+    #[test]
+    fn should_prune_to_max_entries() {
+        let conn = Connection::open_in_memory().expect("创建内存数据库失败");
+        create_tables(&conn).expect("创建表结构失败");
+
+        for i in 0..5 {
+            EmbeddingCacheDao::upsert(&conn, "m", &format!("h{i}"), &[i as f32])
+                .expect("写入缓存应成功");
+        }
+
+        let removed = EmbeddingCacheDao::prune(&conn, 2, 0).expect("裁剪应成功");
+        assert_eq!(removed, 3);
+        assert_eq!(EmbeddingCacheDao::count(&conn).expect("计数应成功"), 2);
+    }
+}
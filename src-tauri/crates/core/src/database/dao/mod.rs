@@ -10,6 +10,8 @@ pub mod brand_persona_dao;
 pub mod browser_environment_preset;
 pub mod browser_profile;
 pub mod chat;
+pub mod config_audit;
+pub mod embedding_cache;
 pub mod installed_plugins;
 pub mod material_dao;
 pub mod mcp;
@@ -19,7 +21,10 @@ pub mod poster_material_dao;
 pub mod prompts;
 pub mod provider_pool;
 pub mod providers;
+pub mod proxy_transcript;
 pub mod publish_config_dao;
+pub mod rag_document;
 pub mod skills;
 pub mod template_dao;
+pub mod upload_file_ref;
 pub mod video_generation_task_dao;
@@ -2,13 +2,20 @@
 //!
 //! 提供凭证池的 CRUD 操作。
 
+use crate::database::field_crypto::{decrypt_field, encrypt_field};
 use crate::models::provider_pool_model::{
     CachedTokenInfo, CredentialData, CredentialSource, PoolProviderType, ProviderCredential,
     ProviderPools,
 };
+use crate::secret_store::{is_keychain_ref, secret_store, KEYCHAIN_REF_PREFIX};
 use chrono::{DateTime, TimeZone, Utc};
 use rusqlite::{params, Connection};
 
+/// OAuth Refresh Token 在钥匙串中的账户名
+fn refresh_token_account(uuid: &str) -> String {
+    format!("oauth-refresh:{uuid}")
+}
+
 pub struct ProviderPoolDao;
 
 impl ProviderPoolDao {
@@ -18,8 +25,9 @@ impl ProviderPoolDao {
             "SELECT uuid, provider_type, credential_data, name, is_healthy, is_disabled,
                     check_health, check_model_name, not_supported_models, supported_models, usage_count, error_count,
                     last_used, last_error_time, last_error_message, last_health_check_time,
-                    last_health_check_model, created_at, updated_at, source, proxy_url
+                    last_health_check_model, created_at, updated_at, source, proxy_url, deleted_at
              FROM provider_pool_credentials
+             WHERE deleted_at IS NULL
              ORDER BY provider_type, created_at ASC",
         )?;
 
@@ -41,9 +49,9 @@ impl ProviderPoolDao {
             "SELECT uuid, provider_type, credential_data, name, is_healthy, is_disabled,
                     check_health, check_model_name, not_supported_models, supported_models, usage_count, error_count,
                     last_used, last_error_time, last_error_message, last_health_check_time,
-                    last_health_check_model, created_at, updated_at, source, proxy_url
+                    last_health_check_model, created_at, updated_at, source, proxy_url, deleted_at
              FROM provider_pool_credentials
-             WHERE provider_type = ?1
+             WHERE provider_type = ?1 AND deleted_at IS NULL
              ORDER BY created_at ASC",
         )?;
 
@@ -67,9 +75,9 @@ impl ProviderPoolDao {
             "SELECT uuid, provider_type, credential_data, name, is_healthy, is_disabled,
                     check_health, check_model_name, not_supported_models, supported_models, usage_count, error_count,
                     last_used, last_error_time, last_error_message, last_health_check_time,
-                    last_health_check_model, created_at, updated_at, source, proxy_url
+                    last_health_check_model, created_at, updated_at, source, proxy_url, deleted_at
              FROM provider_pool_credentials
-             WHERE uuid = ?1",
+             WHERE uuid = ?1 AND deleted_at IS NULL",
         )?;
 
         let mut rows = stmt.query([uuid])?;
@@ -89,9 +97,9 @@ impl ProviderPoolDao {
             "SELECT uuid, provider_type, credential_data, name, is_healthy, is_disabled,
                     check_health, check_model_name, not_supported_models, supported_models, usage_count, error_count,
                     last_used, last_error_time, last_error_message, last_health_check_time,
-                    last_health_check_model, created_at, updated_at, source, proxy_url
+                    last_health_check_model, created_at, updated_at, source, proxy_url, deleted_at
              FROM provider_pool_credentials
-             WHERE name = ?1",
+             WHERE name = ?1 AND deleted_at IS NULL",
         )?;
 
         let mut rows = stmt.query([name])?;
@@ -116,8 +124,10 @@ impl ProviderPoolDao {
 
     /// 插入新凭证
     pub fn insert(conn: &Connection, cred: &ProviderCredential) -> Result<(), rusqlite::Error> {
-        let credential_json =
-            serde_json::to_string(&cred.credential).unwrap_or_else(|_| "{}".to_string());
+        let credential_json = encrypt_field(
+            "credential_data",
+            &serde_json::to_string(&cred.credential).unwrap_or_else(|_| "{}".to_string()),
+        );
         let not_supported_models_json =
             serde_json::to_string(&cred.not_supported_models).unwrap_or_else(|_| "[]".to_string());
         let supported_models_json =
@@ -164,8 +174,10 @@ impl ProviderPoolDao {
 
     /// 更新凭证
     pub fn update(conn: &Connection, cred: &ProviderCredential) -> Result<(), rusqlite::Error> {
-        let credential_json =
-            serde_json::to_string(&cred.credential).unwrap_or_else(|_| "{}".to_string());
+        let credential_json = encrypt_field(
+            "credential_data",
+            &serde_json::to_string(&cred.credential).unwrap_or_else(|_| "{}".to_string()),
+        );
         let not_supported_models_json =
             serde_json::to_string(&cred.not_supported_models).unwrap_or_else(|_| "[]".to_string());
         let supported_models_json =
@@ -204,12 +216,58 @@ impl ProviderPoolDao {
         Ok(())
     }
 
-    /// 删除凭证
+    /// 获取回收站中的凭证（最近删除的在前）
+    pub fn get_deleted(conn: &Connection) -> Result<Vec<ProviderCredential>, rusqlite::Error> {
+        let mut stmt = conn.prepare(
+            "SELECT uuid, provider_type, credential_data, name, is_healthy, is_disabled,
+                    check_health, check_model_name, not_supported_models, supported_models, usage_count, error_count,
+                    last_used, last_error_time, last_error_message, last_health_check_time,
+                    last_health_check_model, created_at, updated_at, source, proxy_url, deleted_at
+             FROM provider_pool_credentials
+             WHERE deleted_at IS NOT NULL
+             ORDER BY deleted_at DESC",
+        )?;
+
+        let rows = stmt.query_map([], Self::row_to_credential)?;
+        Ok(rows.flatten().collect())
+    }
+
+    /// 移入回收站（软删除），凭证不存在或已在回收站时返回 false
+    pub fn soft_delete(
+        conn: &Connection,
+        uuid: &str,
+        deleted_at: DateTime<Utc>,
+    ) -> Result<bool, rusqlite::Error> {
+        let affected = conn.execute(
+            "UPDATE provider_pool_credentials SET deleted_at = ?2
+             WHERE uuid = ?1 AND deleted_at IS NULL",
+            params![uuid, deleted_at.timestamp()],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// 从回收站恢复，凭证不在回收站时返回 false
+    pub fn restore(conn: &Connection, uuid: &str) -> Result<bool, rusqlite::Error> {
+        let affected = conn.execute(
+            "UPDATE provider_pool_credentials SET deleted_at = NULL, updated_at = ?2
+             WHERE uuid = ?1 AND deleted_at IS NOT NULL",
+            params![uuid, Utc::now().timestamp()],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// 永久删除凭证
     pub fn delete(conn: &Connection, uuid: &str) -> Result<bool, rusqlite::Error> {
         let affected = conn.execute(
             "DELETE FROM provider_pool_credentials WHERE uuid = ?1",
             [uuid],
         )?;
+        if affected > 0 {
+            secret_store().remove(&format!(
+                "{KEYCHAIN_REF_PREFIX}{}",
+                refresh_token_account(uuid)
+            ));
+        }
         Ok(affected > 0)
     }
 
@@ -297,7 +355,14 @@ impl ProviderPoolDao {
     fn row_to_credential(row: &rusqlite::Row) -> Result<ProviderCredential, rusqlite::Error> {
         let uuid: String = row.get(0)?;
         let provider_type_str: String = row.get(1)?;
-        let credential_json: String = row.get(2)?;
+        let credential_json =
+            decrypt_field("credential_data", &row.get::<_, String>(2)?).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    2,
+                    rusqlite::types::Type::Text,
+                    Box::<dyn std::error::Error + Send + Sync>::from(e),
+                )
+            })?;
         let name: Option<String> = row.get(3)?;
         let is_healthy: bool = row.get(4)?;
         let is_disabled: bool = row.get(5)?;
@@ -316,6 +381,7 @@ impl ProviderPoolDao {
         let updated_at_ts: i64 = row.get(18)?;
         let source_str: Option<String> = row.get(19).ok();
         let proxy_url: Option<String> = row.get(20).ok();
+        let deleted_at_ts: Option<i64> = row.get(21).ok().flatten();
 
         let provider_type: PoolProviderType =
             provider_type_str.parse().unwrap_or(PoolProviderType::Kiro);
@@ -368,6 +434,7 @@ impl ProviderPoolDao {
             cached_token: None, // 从 get_token_cache 单独获取
             source,
             proxy_url,
+            deleted_at: deleted_at_ts.and_then(|ts| Utc.timestamp_opt(ts, 0).single()),
         })
     }
 
@@ -387,8 +454,17 @@ impl ProviderPoolDao {
 
         let mut rows = stmt.query([uuid])?;
         if let Some(row) = rows.next()? {
-            let access_token: Option<String> = row.get(0)?;
-            let refresh_token: Option<String> = row.get(1)?;
+            let access_token =
+                row.get::<_, Option<String>>(0)?.and_then(|value| {
+                    match decrypt_field("cached_access_token", &value) {
+                        Ok(token) => Some(token),
+                        Err(e) => {
+                            tracing::warn!("[DAO] 读取 Access Token 失败 ({}): {}", uuid, e);
+                            None
+                        }
+                    }
+                });
+            let stored_refresh_token: Option<String> = row.get(1)?;
             let expiry_time_str: Option<String> = row.get(2)?;
             let last_refresh_str: Option<String> = row.get(3)?;
             let refresh_error_count: i32 = row.get::<_, Option<i32>>(4)?.unwrap_or(0);
@@ -407,6 +483,36 @@ impl ProviderPoolDao {
                 .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
                 .map(|dt| dt.with_timezone(&Utc));
 
+            let refresh_token = match stored_refresh_token {
+                Some(value) if is_keychain_ref(&value) => match secret_store().resolve(&value) {
+                    Ok(token) => Some(token),
+                    Err(e) => {
+                        tracing::warn!("[DAO] 读取 Refresh Token 失败 ({}): {}", uuid, e);
+                        None
+                    }
+                },
+                Some(value) => {
+                    let token = match decrypt_field("cached_refresh_token", &value) {
+                        Ok(token) => token,
+                        Err(e) => {
+                            tracing::warn!("[DAO] 读取 Refresh Token 失败 ({}): {}", uuid, e);
+                            return Ok(None);
+                        }
+                    };
+                    // 旧版明文 Refresh Token 迁入钥匙串
+                    if let Some(reference) =
+                        secret_store().store(&refresh_token_account(uuid), &token)
+                    {
+                        conn.execute(
+                            "UPDATE provider_pool_credentials SET cached_refresh_token = ?2 WHERE uuid = ?1",
+                            params![uuid, reference],
+                        )?;
+                    }
+                    Some(token)
+                }
+                None => None,
+            };
+
             Ok(Some(CachedTokenInfo {
                 access_token,
                 refresh_token,
@@ -426,6 +532,15 @@ impl ProviderPoolDao {
         uuid: &str,
         token_info: &CachedTokenInfo,
     ) -> Result<(), rusqlite::Error> {
+        let refresh_token = token_info.refresh_token.as_deref().map(|token| {
+            secret_store()
+                .store(&refresh_token_account(uuid), token)
+                .unwrap_or_else(|| encrypt_field("cached_refresh_token", token))
+        });
+        let access_token = token_info
+            .access_token
+            .as_deref()
+            .map(|token| encrypt_field("cached_access_token", token));
         conn.execute(
             "UPDATE provider_pool_credentials SET
              cached_access_token = ?2,
@@ -438,8 +553,8 @@ impl ProviderPoolDao {
              WHERE uuid = ?1",
             params![
                 uuid,
-                token_info.access_token,
-                token_info.refresh_token,
+                access_token,
+                refresh_token,
                 token_info.expiry_time.map(|t| t.to_rfc3339()),
                 token_info.last_refresh.map(|t| t.to_rfc3339()),
                 token_info.refresh_error_count as i32,
@@ -452,6 +567,10 @@ impl ProviderPoolDao {
 
     /// 清除凭证的 Token 缓存
     pub fn clear_token_cache(conn: &Connection, uuid: &str) -> Result<(), rusqlite::Error> {
+        secret_store().remove(&format!(
+            "{KEYCHAIN_REF_PREFIX}{}",
+            refresh_token_account(uuid)
+        ));
         conn.execute(
             "UPDATE provider_pool_credentials SET
              cached_access_token = NULL,
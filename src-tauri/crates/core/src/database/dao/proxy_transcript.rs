@@ -0,0 +1,246 @@
+//! 对话记录 DAO
+//!
+//! 保存经代理的非流式对话请求与响应（原始 JSON），供导出微调数据集。
+//! `key_id` 为受限 Key 的 ID，主 API Key 记为 [`PRIMARY_KEY_ID`]。
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{params, Connection, ToSql};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 主 API Key 的记录标识
+pub const PRIMARY_KEY_ID: &str = "primary";
+
+/// 对话记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProxyTranscript {
+    pub id: i64,
+    pub created_at: DateTime<Utc>,
+    /// 请求路径（决定请求与响应的协议格式）
+    pub path: String,
+    pub model: String,
+    pub key_id: String,
+    /// 是否同意用于训练
+    pub consent: bool,
+    pub request: Value,
+    pub response: Value,
+}
+
+/// 查询条件，未设置的条件不限制
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptFilter {
+    /// 模型名（精确匹配）
+    #[serde(default)]
+    pub model: Option<String>,
+    /// 起始时间（含）
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    /// 截止时间（不含）
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    /// Key 标识
+    #[serde(default)]
+    pub key_id: Option<String>,
+    /// 只返回同意用于训练的记录
+    #[serde(default)]
+    pub consented_only: bool,
+}
+
+impl TranscriptFilter {
+    fn where_clause(&self) -> (String, Vec<Box<dyn ToSql>>) {
+        let mut conditions = Vec::new();
+        let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+        if let Some(model) = &self.model {
+            values.push(Box::new(model.clone()));
+            conditions.push(format!("model = ?{}", values.len()));
+        }
+        if let Some(since) = self.since {
+            values.push(Box::new(format_time(since)));
+            conditions.push(format!("created_at >= ?{}", values.len()));
+        }
+        if let Some(until) = self.until {
+            values.push(Box::new(format_time(until)));
+            conditions.push(format!("created_at < ?{}", values.len()));
+        }
+        if let Some(key_id) = &self.key_id {
+            values.push(Box::new(key_id.clone()));
+            conditions.push(format!("key_id = ?{}", values.len()));
+        }
+        if self.consented_only {
+            conditions.push("consent = 1".to_string());
+        }
+        let clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
+        };
+        (clause, values)
+    }
+}
+
+/// 固定格式的 UTC 时间，保证按字符串比较有序
+fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+fn parse_time(value: String) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(&value)
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+pub struct ProxyTranscriptDao;
+
+impl ProxyTranscriptDao {
+    pub fn insert(conn: &Connection, transcript: &ProxyTranscript) -> Result<i64, rusqlite::Error> {
+        conn.execute(
+            "INSERT INTO proxy_transcripts (
+                created_at, path, model, key_id, consent, request_json, response_json
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                format_time(transcript.created_at),
+                transcript.path,
+                transcript.model,
+                transcript.key_id,
+                transcript.consent,
+                transcript.request.to_string(),
+                transcript.response.to_string(),
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// 按条件查询，按时间升序，最多返回 `limit` 条
+    pub fn list(
+        conn: &Connection,
+        filter: &TranscriptFilter,
+        limit: usize,
+    ) -> Result<Vec<ProxyTranscript>, rusqlite::Error> {
+        let (clause, values) = filter.where_clause();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, created_at, path, model, key_id, consent, request_json, response_json
+             FROM proxy_transcripts{clause} ORDER BY created_at ASC, id ASC LIMIT {limit}"
+        ))?;
+        let params: Vec<&dyn ToSql> = values.iter().map(|v| v.as_ref()).collect();
+        let rows = stmt.query_map(params.as_slice(), |row| {
+            Ok(ProxyTranscript {
+                id: row.get(0)?,
+                created_at: parse_time(row.get(1)?),
+                path: row.get(2)?,
+                model: row.get(3)?,
+                key_id: row.get(4)?,
+                consent: row.get(5)?,
+                request: serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or(Value::Null),
+                response: serde_json::from_str(&row.get::<_, String>(7)?).unwrap_or(Value::Null),
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// 按条件计数
+    pub fn count(conn: &Connection, filter: &TranscriptFilter) -> Result<usize, rusqlite::Error> {
+        let (clause, values) = filter.where_clause();
+        let params: Vec<&dyn ToSql> = values.iter().map(|v| v.as_ref()).collect();
+        let count: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM proxy_transcripts{clause}"),
+            params.as_slice(),
+            |row| row.get(0),
+        )?;
+        Ok(count.max(0) as usize)
+    }
+
+    /// 更新某个 Key 全部记录的同意标记，返回更新的条数
+    pub fn set_consent(
+        conn: &Connection,
+        key_id: &str,
+        consent: bool,
+    ) -> Result<usize, rusqlite::Error> {
+        conn.execute(
+            "UPDATE proxy_transcripts SET consent = ?1 WHERE key_id = ?2",
+            params![consent, key_id],
+        )
+    }
+
+    /// 删除符合条件的记录，返回删除的条数
+    pub fn delete(conn: &Connection, filter: &TranscriptFilter) -> Result<usize, rusqlite::Error> {
+        let (clause, values) = filter.where_clause();
+        let params: Vec<&dyn ToSql> = values.iter().map(|v| v.as_ref()).collect();
+        conn.execute(
+            &format!("DELETE FROM proxy_transcripts{clause}"),
+            params.as_slice(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+    use serde_json::json;
+
+    fn transcript(
+        model: &str,
+        key_id: &str,
+        consent: bool,
+        created_at: DateTime<Utc>,
+    ) -> ProxyTranscript {
+        ProxyTranscript {
+            id: 0,
+            created_at,
+            path: "/v1/chat/completions".to_string(),
+            model: model.to_string(),
+            key_id: key_id.to_string(),
+            consent,
+            request: json!({ "model": model, "messages": [] }),
+            response: json!({ "choices": [] }),
+        }
+    }
+
+    #[test]
+    fn should_filter_by_model_date_key_and_consent() {
+        let conn = Connection::open_in_memory().expect("创建内存数据库失败");
+        create_tables(&conn).expect("创建表结构失败");
+
+        let now = Utc::now();
+        let yesterday = now - chrono::Duration::days(1);
+        for t in [
+            transcript("gpt-4o", PRIMARY_KEY_ID, true, yesterday),
+            transcript("gpt-4o", "key-a", false, now),
+            transcript("claude-sonnet-4", "key-a", true, now),
+        ] {
+            ProxyTranscriptDao::insert(&conn, &t).expect("写入应成功");
+        }
+
+        let consented = TranscriptFilter {
+            consented_only: true,
+            ..Default::default()
+        };
+        assert_eq!(ProxyTranscriptDao::count(&conn, &consented).unwrap(), 2);
+
+        let filter = TranscriptFilter {
+            model: Some("gpt-4o".to_string()),
+            since: Some(now - chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        let found = ProxyTranscriptDao::list(&conn, &filter, 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].key_id, "key-a");
+        assert_eq!(found[0].request["model"], "gpt-4o");
+
+        assert_eq!(
+            ProxyTranscriptDao::set_consent(&conn, "key-a", true).unwrap(),
+            2
+        );
+        assert_eq!(ProxyTranscriptDao::count(&conn, &consented).unwrap(), 3);
+
+        let by_key = TranscriptFilter {
+            key_id: Some("key-a".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(ProxyTranscriptDao::delete(&conn, &by_key).unwrap(), 2);
+        assert_eq!(
+            ProxyTranscriptDao::count(&conn, &TranscriptFilter::default()).unwrap(),
+            1
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! 本地 RAG 文档 DAO
+//!
+//! 主键为 (collection, id)，向量编码与嵌入缓存一致（小端 f32 BLOB）。
+
+use chrono::Utc;
+use rusqlite::{params, Connection};
+
+use super::embedding_cache::{decode_embedding, encode_embedding};
+
+/// RAG 文档记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct RagDocumentRecord {
+    pub collection: String,
+    pub id: String,
+    pub content: String,
+    pub metadata: Option<serde_json::Value>,
+    pub embedding_model: String,
+    pub embedding: Vec<f32>,
+}
+
+pub struct RagDocumentDao;
+
+impl RagDocumentDao {
+    /// 写入或覆盖文档
+    pub fn upsert(conn: &Connection, doc: &RagDocumentRecord) -> Result<(), rusqlite::Error> {
+        let now = Utc::now().to_rfc3339();
+        let metadata = doc.metadata.as_ref().map(|value| value.to_string());
+        conn.execute(
+            "INSERT INTO rag_documents (
+                collection, id, content, metadata, embedding_model, embedding, created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)
+            ON CONFLICT(collection, id) DO UPDATE SET
+                content = excluded.content,
+                metadata = excluded.metadata,
+                embedding_model = excluded.embedding_model,
+                embedding = excluded.embedding,
+                updated_at = excluded.updated_at",
+            params![
+                doc.collection,
+                doc.id,
+                doc.content,
+                metadata,
+                doc.embedding_model,
+                encode_embedding(&doc.embedding),
+                now,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 读取集合内使用指定嵌入模型的全部文档
+    pub fn list_by_collection(
+        conn: &Connection,
+        collection: &str,
+        embedding_model: &str,
+    ) -> Result<Vec<RagDocumentRecord>, rusqlite::Error> {
+        let mut stmt = conn.prepare(
+            "SELECT collection, id, content, metadata, embedding_model, embedding
+             FROM rag_documents WHERE collection = ?1 AND embedding_model = ?2",
+        )?;
+        let rows = stmt.query_map(params![collection, embedding_model], |row| {
+            let metadata: Option<String> = row.get(3)?;
+            let blob: Vec<u8> = row.get(5)?;
+            Ok(RagDocumentRecord {
+                collection: row.get(0)?,
+                id: row.get(1)?,
+                content: row.get(2)?,
+                metadata: metadata.and_then(|raw| serde_json::from_str(&raw).ok()),
+                embedding_model: row.get(4)?,
+                embedding: decode_embedding(&blob),
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn delete(conn: &Connection, collection: &str, id: &str) -> Result<bool, rusqlite::Error> {
+        let affected = conn.execute(
+            "DELETE FROM rag_documents WHERE collection = ?1 AND id = ?2",
+            params![collection, id],
+        )?;
+        Ok(affected > 0)
+    }
+
+    pub fn count(conn: &Connection, collection: &str) -> Result<usize, rusqlite::Error> {
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM rag_documents WHERE collection = ?1",
+            params![collection],
+            |row| row.get(0),
+        )?;
+        Ok(count.max(0) as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    fn record(id: &str, content: &str) -> RagDocumentRecord {
+        RagDocumentRecord {
+            collection: "docs".to_string(),
+            id: id.to_string(),
+            content: content.to_string(),
+            metadata: Some(serde_json::json!({ "source": "readme" })),
+            embedding_model: "m".to_string(),
+            embedding: vec![1.0, 0.0],
+        }
+    }
+
+    #[test]
+    fn should_upsert_and_list_documents() {
+        let conn = Connection::open_in_memory().expect("创建内存数据库失败");
+        create_tables(&conn).expect("创建表结构失败");
+
+        RagDocumentDao::upsert(&conn, &record("a", "first")).expect("写入文档应成功");
+        RagDocumentDao::upsert(&conn, &record("a", "updated")).expect("覆盖文档应成功");
+        RagDocumentDao::upsert(&conn, &record("b", "second")).expect("写入文档应成功");
+
+        let docs = RagDocumentDao::list_by_collection(&conn, "docs", "m").expect("查询应成功");
+        assert_eq!(docs.len(), 2);
+        assert!(docs.iter().any(|doc| doc.content == "updated"));
+        assert_eq!(
+            docs[0].metadata,
+            Some(serde_json::json!({ "source": "readme" }))
+        );
+        assert!(RagDocumentDao::list_by_collection(&conn, "docs", "other")
+            .expect("查询应成功")
+            .is_empty());
+
+        assert!(RagDocumentDao::delete(&conn, "docs", "a").expect("删除应成功"));
+        assert_eq!(RagDocumentDao::count(&conn, "docs").expect("计数应成功"), 1);
+    }
+}
@@ -0,0 +1,156 @@
+//! 多模态上传去重 DAO
+//!
+//! 记录已上传到 Provider 文件接口的素材：主键为 (scope, content_hash)，
+//! `expires_at` 为空表示 Provider 不会自动删除该文件。
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// 已上传的文件引用
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadFileRef {
+    pub scope: String,
+    pub content_hash: String,
+    /// Provider 返回的文件引用（Gemini 为 `fileUri`，Anthropic 为 `file_id`）
+    pub file_ref: String,
+    pub mime_type: String,
+    pub size_bytes: u64,
+    pub hit_count: u64,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+fn parse_time(value: String) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(&value)
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+pub struct UploadFileRefDao;
+
+impl UploadFileRefDao {
+    /// 查询未过期的文件引用，命中时递增命中计数
+    pub fn get(
+        conn: &Connection,
+        scope: &str,
+        content_hash: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Option<UploadFileRef>, rusqlite::Error> {
+        let found = conn
+            .query_row(
+                "SELECT file_ref, mime_type, size_bytes, hit_count, created_at, expires_at
+                 FROM upload_file_refs
+                 WHERE scope = ?1 AND content_hash = ?2
+                   AND (expires_at IS NULL OR expires_at > ?3)",
+                params![scope, content_hash, now.to_rfc3339()],
+                |row| {
+                    Ok(UploadFileRef {
+                        scope: scope.to_string(),
+                        content_hash: content_hash.to_string(),
+                        file_ref: row.get(0)?,
+                        mime_type: row.get(1)?,
+                        size_bytes: row.get::<_, i64>(2)?.max(0) as u64,
+                        hit_count: row.get::<_, i64>(3)?.max(0) as u64 + 1,
+                        created_at: parse_time(row.get(4)?),
+                        expires_at: row.get::<_, Option<String>>(5)?.map(parse_time),
+                    })
+                },
+            )
+            .optional()?;
+        if found.is_some() {
+            conn.execute(
+                "UPDATE upload_file_refs SET hit_count = hit_count + 1
+                 WHERE scope = ?1 AND content_hash = ?2",
+                params![scope, content_hash],
+            )?;
+        }
+        Ok(found)
+    }
+
+    /// 写入或覆盖文件引用
+    pub fn upsert(conn: &Connection, entry: &UploadFileRef) -> Result<(), rusqlite::Error> {
+        conn.execute(
+            "INSERT INTO upload_file_refs (
+                scope, content_hash, file_ref, mime_type, size_bytes, hit_count, created_at, expires_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT(scope, content_hash) DO UPDATE SET
+                file_ref = excluded.file_ref,
+                mime_type = excluded.mime_type,
+                size_bytes = excluded.size_bytes,
+                created_at = excluded.created_at,
+                expires_at = excluded.expires_at",
+            params![
+                entry.scope,
+                entry.content_hash,
+                entry.file_ref,
+                entry.mime_type,
+                entry.size_bytes as i64,
+                entry.hit_count as i64,
+                entry.created_at.to_rfc3339(),
+                entry.expires_at.map(|t| t.to_rfc3339()),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 清理过期条目，返回删除的条目数
+    pub fn prune_expired(conn: &Connection, now: DateTime<Utc>) -> Result<usize, rusqlite::Error> {
+        conn.execute(
+            "DELETE FROM upload_file_refs WHERE expires_at IS NOT NULL AND expires_at <= ?1",
+            params![now.to_rfc3339()],
+        )
+    }
+
+    pub fn count(conn: &Connection) -> Result<usize, rusqlite::Error> {
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM upload_file_refs", [], |row| {
+            row.get(0)
+        })?;
+        Ok(count.max(0) as usize)
+    }
+
+    pub fn clear(conn: &Connection) -> Result<usize, rusqlite::Error> {
+        conn.execute("DELETE FROM upload_file_refs", [])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema::create_tables;
+
+    #[test]
+    fn should_skip_expired_refs_and_count_hits() {
+        let conn = Connection::open_in_memory().expect("创建内存数据库失败");
+        create_tables(&conn).expect("创建表结构失败");
+
+        let now = Utc::now();
+        let entry = UploadFileRef {
+            scope: "gemini:https://example.com:abcd".to_string(),
+            content_hash: "h1".to_string(),
+            file_ref: "https://example.com/v1beta/files/abc".to_string(),
+            mime_type: "image/png".to_string(),
+            size_bytes: 1024,
+            hit_count: 0,
+            created_at: now,
+            expires_at: Some(now + chrono::Duration::hours(1)),
+        };
+        UploadFileRefDao::upsert(&conn, &entry).expect("写入应成功");
+
+        let hit = UploadFileRefDao::get(&conn, &entry.scope, "h1", now)
+            .expect("查询应成功")
+            .expect("应命中");
+        assert_eq!(hit.file_ref, entry.file_ref);
+        assert_eq!(hit.hit_count, 1);
+        assert!(UploadFileRefDao::get(&conn, "other", "h1", now)
+            .expect("查询应成功")
+            .is_none());
+
+        let later = now + chrono::Duration::hours(2);
+        assert!(UploadFileRefDao::get(&conn, &entry.scope, "h1", later)
+            .expect("查询应成功")
+            .is_none());
+        assert_eq!(UploadFileRefDao::prune_expired(&conn, later).unwrap(), 1);
+        assert_eq!(UploadFileRefDao::count(&conn).unwrap(), 0);
+    }
+}
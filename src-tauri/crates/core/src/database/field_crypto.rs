@@ -0,0 +1,333 @@
+//! 凭证字段加密
+//!
+//! 凭证池中的敏感列（`credential_data`、缓存的 Access/Refresh Token）以
+//! ChaCha20-Poly1305 加密后写入 SQLite，存储格式为 `enc:v1:<base64(nonce || 密文)>`。
+//! 数据密钥随机生成并保存在系统钥匙串中，列名作为附加认证数据，防止密文被挪到其他列。
+//!
+//! - 未安装加密器（钥匙串不可用、测试环境）时写入明文
+//! - 读取时明文原样返回，兼容加密前的旧数据
+//! - 仅在数据库中尚无密文时生成新密钥；已有密文但密钥缺失或无法读取时拒绝启动
+
+use std::sync::OnceLock;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rusqlite::Connection;
+
+use crate::secret_store::SecretStore;
+
+/// 加密字段前缀
+pub const ENCRYPTED_FIELD_PREFIX: &str = "enc:v1:";
+
+/// 数据密钥在钥匙串中的账户名
+const FIELD_KEY_ACCOUNT: &str = "database:field-key";
+
+const NONCE_LEN: usize = 12;
+
+static FIELD_CIPHER: OnceLock<FieldCipher> = OnceLock::new();
+
+/// 字段加密器
+pub struct FieldCipher {
+    cipher: ChaCha20Poly1305,
+    key: [u8; 32],
+}
+
+impl FieldCipher {
+    pub fn new(key: [u8; 32]) -> Self {
+        Self {
+            cipher: ChaCha20Poly1305::new(Key::from_slice(&key)),
+            key,
+        }
+    }
+
+    /// 从 Base64 编码的数据密钥创建
+    pub fn from_base64(encoded: &str) -> Result<Self, String> {
+        BASE64
+            .decode(encoded.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(Self::new)
+            .ok_or_else(|| "数据库加密密钥格式无效".to_string())
+    }
+
+    /// Base64 编码的数据密钥（写入加密备份）
+    pub fn to_base64(&self) -> String {
+        BASE64.encode(self.key)
+    }
+
+    /// 从钥匙串加载数据密钥；不存在且 `allow_create` 时生成
+    pub fn from_store(store: &SecretStore, allow_create: bool) -> Result<Self, String> {
+        let encoded = store.get_or_create(FIELD_KEY_ACCOUNT, || {
+            if allow_create {
+                Ok(BASE64.encode(rand::random::<[u8; 32]>()))
+            } else {
+                Err("钥匙串中缺少数据库加密密钥，但数据库中已有加密数据".to_string())
+            }
+        })?;
+        Self::from_base64(&encoded)
+    }
+
+    /// 加密字段（`column` 作为附加认证数据）
+    pub fn encrypt(&self, column: &str, plaintext: &str) -> Result<String, String> {
+        let nonce_bytes = rand::random::<[u8; NONCE_LEN]>();
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                Nonce::from_slice(&nonce_bytes),
+                Payload {
+                    msg: plaintext.as_bytes(),
+                    aad: column.as_bytes(),
+                },
+            )
+            .map_err(|_| format!("加密字段 {column} 失败"))?;
+        let mut packed = nonce_bytes.to_vec();
+        packed.extend_from_slice(&ciphertext);
+        Ok(format!("{ENCRYPTED_FIELD_PREFIX}{}", BASE64.encode(packed)))
+    }
+
+    /// 解密字段；明文值原样返回
+    pub fn decrypt(&self, column: &str, stored: &str) -> Result<String, String> {
+        let Some(encoded) = stored.strip_prefix(ENCRYPTED_FIELD_PREFIX) else {
+            return Ok(stored.to_string());
+        };
+        let packed = BASE64
+            .decode(encoded)
+            .map_err(|e| format!("字段 {column} 密文格式无效: {e}"))?;
+        if packed.len() <= NONCE_LEN {
+            return Err(format!("字段 {column} 密文长度无效"));
+        }
+        let (nonce, ciphertext) = packed.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: column.as_bytes(),
+                },
+            )
+            .map_err(|_| format!("解密字段 {column} 失败（密钥不匹配或数据被篡改）"))?;
+        String::from_utf8(plaintext).map_err(|e| format!("字段 {column} 解密结果无效: {e}"))
+    }
+}
+
+/// 数据库中是否已有加密字段（凭证表不存在时视为没有）
+///
+/// 字段加密迁移会同时加密 `credential_data`，检查该列即可。
+pub fn has_encrypted_fields(conn: &Connection) -> Result<bool, String> {
+    let table_exists: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master
+             WHERE type = 'table' AND name = 'provider_pool_credentials')",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("检查凭证表失败: {e}"))?;
+    if !table_exists {
+        return Ok(false);
+    }
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM provider_pool_credentials
+         WHERE credential_data LIKE ?1)",
+        [format!("{ENCRYPTED_FIELD_PREFIX}%")],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("检查加密字段失败: {e}"))
+}
+
+/// 安装进程级加密器（数据库初始化时调用）
+///
+/// 数据库中尚无密文时，钥匙串不可用则保持明文存储并返回 `Ok(false)`；
+/// 已有密文时密钥缺失或无法读取返回错误，避免生成新密钥导致旧数据无法解密。
+pub fn install_field_cipher(store: &SecretStore, conn: &Connection) -> Result<bool, String> {
+    if FIELD_CIPHER.get().is_some() {
+        return Ok(true);
+    }
+    let has_ciphertext = has_encrypted_fields(conn)?;
+    match FieldCipher::from_store(store, !has_ciphertext) {
+        Ok(cipher) => {
+            let _ = FIELD_CIPHER.set(cipher);
+            tracing::info!("[数据库] 已启用凭证字段加密");
+            Ok(true)
+        }
+        Err(e) if has_ciphertext => Err(format!("无法加载数据库加密密钥: {e}")),
+        Err(e) => {
+            tracing::warn!("[数据库] 无法加载加密密钥，凭证字段将以明文保存: {}", e);
+            Ok(false)
+        }
+    }
+}
+
+/// 是否已启用字段加密
+pub fn is_field_encryption_active() -> bool {
+    FIELD_CIPHER.get().is_some()
+}
+
+/// 当前数据密钥（Base64），未启用加密时为 `None`
+pub fn active_field_key() -> Option<String> {
+    FIELD_CIPHER.get().map(FieldCipher::to_base64)
+}
+
+/// 将以 `source` 加密的凭证字段改用当前数据密钥重新加密，返回处理的行数
+///
+/// 恢复其他设备或旧密钥下的数据库快照后调用；未启用加密时解密为明文保存。
+pub fn reencrypt_fields(conn: &Connection, source: &FieldCipher) -> Result<usize, String> {
+    let rows: Vec<(String, String, Option<String>, Option<String>)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT uuid, credential_data, cached_access_token, cached_refresh_token
+                 FROM provider_pool_credentials",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+    };
+
+    let rekey = |column: &str, stored: &str| -> Result<String, String> {
+        if !is_encrypted_field(stored) {
+            return Ok(stored.to_string());
+        }
+        Ok(encrypt_field(column, &source.decrypt(column, stored)?))
+    };
+    let mut updated = 0;
+    for (uuid, credential_data, access_token, refresh_token) in &rows {
+        let fields = [
+            credential_data.as_str(),
+            access_token.as_deref().unwrap_or_default(),
+            refresh_token.as_deref().unwrap_or_default(),
+        ];
+        if !fields.iter().any(|value| is_encrypted_field(value)) {
+            continue;
+        }
+        conn.execute(
+            "UPDATE provider_pool_credentials SET
+             credential_data = ?2, cached_access_token = ?3, cached_refresh_token = ?4
+             WHERE uuid = ?1",
+            rusqlite::params![
+                uuid,
+                rekey("credential_data", credential_data)?,
+                access_token
+                    .as_deref()
+                    .map(|token| rekey("cached_access_token", token))
+                    .transpose()?,
+                refresh_token
+                    .as_deref()
+                    .map(|token| rekey("cached_refresh_token", token))
+                    .transpose()?,
+            ],
+        )
+        .map_err(|e| format!("重新加密凭证 {uuid} 失败: {e}"))?;
+        updated += 1;
+    }
+    Ok(updated)
+}
+
+/// 是否为加密字段
+pub fn is_encrypted_field(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_FIELD_PREFIX)
+}
+
+/// 加密字段（未启用或失败时返回明文）
+pub fn encrypt_field(column: &str, plaintext: &str) -> String {
+    let Some(cipher) = FIELD_CIPHER.get() else {
+        return plaintext.to_string();
+    };
+    if plaintext.is_empty() || is_encrypted_field(plaintext) {
+        return plaintext.to_string();
+    }
+    cipher.encrypt(column, plaintext).unwrap_or_else(|e| {
+        tracing::warn!("[数据库] {}，改为明文保存", e);
+        plaintext.to_string()
+    })
+}
+
+/// 解密字段；明文原样返回
+pub fn decrypt_field(column: &str, stored: &str) -> Result<String, String> {
+    if !is_encrypted_field(stored) {
+        return Ok(stored.to_string());
+    }
+    FIELD_CIPHER
+        .get()
+        .ok_or_else(|| format!("字段 {column} 已加密，但数据库加密密钥不可用"))?
+        .decrypt(column, stored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_round_trip_and_pass_through_plaintext() {
+        let cipher = FieldCipher::new([7u8; 32]);
+        let encrypted = cipher
+            .encrypt("credential_data", r#"{"api_key":"sk-1"}"#)
+            .expect("加密失败");
+
+        assert!(is_encrypted_field(&encrypted));
+        assert!(!encrypted.contains("sk-1"));
+        assert_eq!(
+            cipher.decrypt("credential_data", &encrypted).as_deref(),
+            Ok(r#"{"api_key":"sk-1"}"#)
+        );
+        assert_eq!(
+            cipher.decrypt("credential_data", "legacy").as_deref(),
+            Ok("legacy")
+        );
+    }
+
+    #[test]
+    fn should_reject_wrong_key_or_column() {
+        let cipher = FieldCipher::new([7u8; 32]);
+        let encrypted = cipher
+            .encrypt("cached_access_token", "token")
+            .expect("加密失败");
+
+        assert!(cipher.decrypt("credential_data", &encrypted).is_err());
+        assert!(FieldCipher::new([8u8; 32])
+            .decrypt("cached_access_token", &encrypted)
+            .is_err());
+
+        let store = SecretStore::in_memory();
+        assert!(FieldCipher::from_store(&store, false).is_err());
+        let first = FieldCipher::from_store(&store, true).expect("生成密钥失败");
+        let second = FieldCipher::from_store(&store, false).expect("读取密钥失败");
+        let sealed = first.encrypt("c", "v").expect("加密失败");
+        assert_eq!(second.decrypt("c", &sealed).as_deref(), Ok("v"));
+    }
+
+    #[test]
+    fn should_detect_existing_ciphertext() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(has_encrypted_fields(&conn), Ok(false));
+
+        conn.execute_batch(
+            "CREATE TABLE provider_pool_credentials (uuid TEXT, credential_data TEXT);
+             INSERT INTO provider_pool_credentials VALUES ('a', '{}');",
+        )
+        .unwrap();
+        assert_eq!(has_encrypted_fields(&conn), Ok(false));
+
+        let sealed = FieldCipher::new([7u8; 32])
+            .encrypt("credential_data", "{}")
+            .unwrap();
+        conn.execute(
+            "INSERT INTO provider_pool_credentials VALUES ('b', ?1)",
+            [sealed],
+        )
+        .unwrap();
+        assert_eq!(has_encrypted_fields(&conn), Ok(true));
+
+        conn.execute_batch(
+            "ALTER TABLE provider_pool_credentials ADD COLUMN cached_access_token TEXT;
+             ALTER TABLE provider_pool_credentials ADD COLUMN cached_refresh_token TEXT;",
+        )
+        .unwrap();
+        let source = FieldCipher::from_base64(&FieldCipher::new([7u8; 32]).to_base64()).unwrap();
+        assert_eq!(reencrypt_fields(&conn, &source), Ok(1));
+    }
+}
@@ -0,0 +1,174 @@
+//! 数据库维护
+//!
+//! 提供完整性检查、WAL checkpoint 与 VACUUM，供定时任务与手动触发使用。
+//! 最近一次维护结果写入 `settings` 表，前端可随时读取。
+
+use std::path::Path;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use super::migration_support::{read_setting_value, upsert_setting};
+
+/// 最近一次维护结果在 settings 表中的键
+const LAST_REPORT_KEY: &str = "db_maintenance_last_report";
+
+/// 完整性检查最多返回的问题条数
+const MAX_INTEGRITY_ISSUES: usize = 20;
+
+/// 维护结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DbMaintenanceReport {
+    pub integrity_ok: bool,
+    /// 完整性检查发现的问题
+    pub issues: Vec<String>,
+    pub checkpointed: bool,
+    pub vacuumed: bool,
+    /// 数据库文件（含 WAL）维护前后的大小
+    pub size_before_bytes: u64,
+    pub size_after_bytes: u64,
+    pub duration_ms: u64,
+    pub ran_at: DateTime<Utc>,
+}
+
+/// 是否为表示数据库损坏的错误
+pub fn is_corruption_error(error: &rusqlite::Error) -> bool {
+    matches!(
+        error.sqlite_error_code(),
+        Some(rusqlite::ErrorCode::DatabaseCorrupt) | Some(rusqlite::ErrorCode::NotADatabase)
+    )
+}
+
+fn collect_check_rows(conn: &Connection, pragma: &str) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!("PRAGMA {pragma}({MAX_INTEGRITY_ISSUES})"))?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    let issues = rows
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|line| line != "ok")
+        .collect();
+    Ok(issues)
+}
+
+/// 快速完整性检查（启动时使用），返回发现的问题
+pub fn quick_check(conn: &Connection) -> Result<Vec<String>, rusqlite::Error> {
+    collect_check_rows(conn, "quick_check")
+}
+
+/// 完整性检查，返回发现的问题
+pub fn integrity_check(conn: &Connection) -> Result<Vec<String>, rusqlite::Error> {
+    collect_check_rows(conn, "integrity_check")
+}
+
+/// 将 WAL 内容写回主库并截断 WAL 文件
+pub fn checkpoint_wal(conn: &Connection) -> Result<(), String> {
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+        .map_err(|e| format!("WAL checkpoint 失败: {e}"))
+}
+
+/// 数据库文件与 WAL 文件总大小
+fn database_size(db_path: Option<&Path>) -> u64 {
+    let Some(path) = db_path else {
+        return 0;
+    };
+    let wal = path.with_file_name(format!(
+        "{}-wal",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    [path, wal.as_path()]
+        .iter()
+        .filter_map(|p| std::fs::metadata(p).ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// 执行一次维护：完整性检查 → checkpoint →（可选）VACUUM
+///
+/// 完整性检查失败时跳过 VACUUM，避免在损坏的数据库上重写文件。
+pub fn run_maintenance(
+    conn: &Connection,
+    db_path: Option<&Path>,
+    vacuum: bool,
+) -> Result<DbMaintenanceReport, String> {
+    let started = Instant::now();
+    let size_before_bytes = database_size(db_path);
+
+    let issues = integrity_check(conn).map_err(|e| format!("完整性检查失败: {e}"))?;
+    let integrity_ok = issues.is_empty();
+    if !integrity_ok {
+        tracing::error!(
+            "[数据库] 完整性检查发现 {} 个问题: {:?}",
+            issues.len(),
+            issues
+        );
+    }
+
+    let checkpointed = match checkpoint_wal(conn) {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!("[数据库] {}", e);
+            false
+        }
+    };
+
+    let vacuumed = if vacuum && integrity_ok {
+        conn.execute_batch("VACUUM")
+            .map_err(|e| format!("VACUUM 失败: {e}"))?;
+        let _ = checkpoint_wal(conn);
+        true
+    } else {
+        false
+    };
+
+    let report = DbMaintenanceReport {
+        integrity_ok,
+        issues,
+        checkpointed,
+        vacuumed,
+        size_before_bytes,
+        size_after_bytes: database_size(db_path),
+        duration_ms: started.elapsed().as_millis() as u64,
+        ran_at: Utc::now(),
+    };
+    if let Ok(json) = serde_json::to_string(&report) {
+        if let Err(e) = upsert_setting(conn, LAST_REPORT_KEY, &json) {
+            tracing::warn!("[数据库] 保存维护结果失败: {}", e);
+        }
+    }
+    tracing::info!(
+        "[数据库] 维护完成: integrity_ok={}, vacuumed={}, {} → {} 字节, 耗时 {}ms",
+        report.integrity_ok,
+        report.vacuumed,
+        report.size_before_bytes,
+        report.size_after_bytes,
+        report.duration_ms
+    );
+    Ok(report)
+}
+
+/// 读取最近一次维护结果
+pub fn last_maintenance_report(conn: &Connection) -> Option<DbMaintenanceReport> {
+    read_setting_value(conn, LAST_REPORT_KEY).and_then(|json| serde_json::from_str(&json).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_run_maintenance_and_persist_report() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL)",
+            [],
+        )
+        .unwrap();
+
+        let report = run_maintenance(&conn, None, true).expect("维护失败");
+        assert!(report.integrity_ok);
+        assert!(report.vacuumed);
+        assert_eq!(last_maintenance_report(&conn), Some(report));
+    }
+}
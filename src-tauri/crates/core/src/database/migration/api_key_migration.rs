@@ -1,6 +1,7 @@
 use rusqlite::{params, Connection};
 
 use super::{is_true_setting, mark_true_setting};
+use crate::database::field_crypto::{decrypt_field, encrypt_field};
 
 const API_KEYS_TO_POOL_MIGRATED_KEY: &str = "migrated_api_keys_to_pool";
 const PROVIDER_IDS_MIGRATED_KEY: &str = "migrated_provider_ids_v1";
@@ -53,20 +54,18 @@ pub fn migrate_api_keys_to_pool(conn: &Connection) -> Result<usize, String> {
 
     let mut migrated_count = 0;
     let now = chrono::Utc::now().timestamp();
+    let mut existing: Vec<String> = load_pool_credentials(conn)?
+        .into_iter()
+        .map(|credential| credential.credential_data)
+        .collect();
 
     for row_result in rows {
         let row = row_result.map_err(|e| format!("读取行数据失败: {e}"))?;
 
-        let exists: bool = conn
-            .query_row(
-                "SELECT COUNT(*) > 0 FROM provider_pool_credentials
-                 WHERE credential_data LIKE ?1",
-                params![format!("%{}%", row.api_key_encrypted)],
-                |result_row| result_row.get(0),
-            )
-            .unwrap_or(false);
-
-        if exists {
+        if existing
+            .iter()
+            .any(|data| data.contains(&row.api_key_encrypted))
+        {
             tracing::debug!(
                 "[迁移] 跳过已存在的 API Key: {} (provider: {})",
                 row.alias.as_deref().unwrap_or(&row.id),
@@ -107,7 +106,7 @@ pub fn migrate_api_keys_to_pool(conn: &Connection) -> Result<usize, String> {
             params![
                 uuid,
                 pool_provider_type,
-                credential_json,
+                encrypt_field("credential_data", &credential_json),
                 name,
                 true,
                 !row.enabled,
@@ -128,6 +127,7 @@ pub fn migrate_api_keys_to_pool(conn: &Connection) -> Result<usize, String> {
             ],
         )
         .map_err(|e| format!("插入凭证失败: {e}"))?;
+        existing.push(credential_json);
 
         tracing::info!(
             "[迁移] 已迁移 API Key: {} -> {} (provider_type: {})",
@@ -251,64 +251,104 @@ pub fn cleanup_legacy_api_key_credentials(conn: &Connection) -> Result<usize, St
 
     tracing::info!("[清理] 开始清理旧的 API Key 凭证（openai_key, claude_key 类型）");
 
-    let count: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM provider_pool_credentials
-             WHERE credential_data LIKE '%\"type\":\"openai_key\"%'
-                OR credential_data LIKE '%\"type\":\"claude_key\"%'",
-            [],
-            |row| row.get(0),
-        )
-        .unwrap_or(0);
+    let legacy: Vec<PoolCredentialRow> = load_pool_credentials(conn)?
+        .into_iter()
+        .filter(|credential| is_legacy_api_key_credential(&credential.credential_data))
+        .collect();
 
-    if count == 0 {
+    if legacy.is_empty() {
         tracing::info!("[清理] 没有需要清理的旧 API Key 凭证");
         mark_true_setting(conn, LEGACY_API_KEY_CREDENTIALS_CLEANED_KEY)?;
         return Ok(0);
     }
 
+    let mut deleted = 0;
+    for credential in &legacy {
+        tracing::info!(
+            "[清理] 将删除旧凭证: {} (name: {}, type: {})",
+            credential.uuid,
+            credential.name.as_deref().unwrap_or("未命名"),
+            credential.provider_type
+        );
+        deleted += conn
+            .execute(
+                "DELETE FROM provider_pool_credentials WHERE uuid = ?1",
+                params![credential.uuid],
+            )
+            .map_err(|e| format!("删除旧凭证失败: {e}"))?;
+    }
+
+    mark_true_setting(conn, LEGACY_API_KEY_CREDENTIALS_CLEANED_KEY)?;
+
+    tracing::info!("[清理] 旧 API Key 凭证清理完成，共删除 {} 条记录", deleted);
+
+    Ok(deleted)
+}
+
+/// 凭证池中的一行（`credential_data` 已解密）
+struct PoolCredentialRow {
+    uuid: String,
+    name: Option<String>,
+    provider_type: String,
+    credential_data: String,
+}
+
+/// 读取凭证池中未删除的凭证并解密 `credential_data`
+///
+/// 加密后的行无法用 `LIKE` 匹配内容，需解密后再比较；解密失败时中止，避免误判或误删。
+/// 启动迁移先于版本迁移执行，旧库此时可能还没有 `deleted_at` 列。
+fn load_pool_credentials(conn: &Connection) -> Result<Vec<PoolCredentialRow>, String> {
+    let sql = if has_deleted_at_column(conn)? {
+        "SELECT uuid, name, provider_type, credential_data FROM provider_pool_credentials
+         WHERE deleted_at IS NULL"
+    } else {
+        "SELECT uuid, name, provider_type, credential_data FROM provider_pool_credentials"
+    };
     let mut stmt = conn
-        .prepare(
-            "SELECT uuid, name, provider_type
-             FROM provider_pool_credentials
-             WHERE credential_data LIKE '%\"type\":\"openai_key\"%'
-                OR credential_data LIKE '%\"type\":\"claude_key\"%'",
-        )
+        .prepare(sql)
         .map_err(|e| format!("准备查询语句失败: {e}"))?;
-
     let rows = stmt
         .query_map([], |row| {
             Ok((
                 row.get::<_, String>(0)?,
                 row.get::<_, Option<String>>(1)?,
                 row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
             ))
         })
-        .map_err(|e| format!("查询旧凭证失败: {e}"))?;
+        .map_err(|e| format!("查询凭证失败: {e}"))?;
 
-    for (uuid, name, provider_type) in rows.into_iter().filter_map(Result::ok) {
-        tracing::info!(
-            "[清理] 将删除旧凭证: {} (name: {}, type: {})",
+    let mut credentials = Vec::new();
+    for row in rows {
+        let (uuid, name, provider_type, stored) = row.map_err(|e| format!("读取凭证失败: {e}"))?;
+        credentials.push(PoolCredentialRow {
+            credential_data: decrypt_field("credential_data", &stored)?,
             uuid,
-            name.as_deref().unwrap_or("未命名"),
-            provider_type
-        );
+            name,
+            provider_type,
+        });
     }
+    Ok(credentials)
+}
 
-    let deleted = conn
-        .execute(
-            "DELETE FROM provider_pool_credentials
-             WHERE credential_data LIKE '%\"type\":\"openai_key\"%'
-                OR credential_data LIKE '%\"type\":\"claude_key\"%'",
-            [],
-        )
-        .map_err(|e| format!("删除旧凭证失败: {e}"))?;
-
-    mark_true_setting(conn, LEGACY_API_KEY_CREDENTIALS_CLEANED_KEY)?;
-
-    tracing::info!("[清理] 旧 API Key 凭证清理完成，共删除 {} 条记录", deleted);
+/// 是否为旧 UI 添加的 OpenAIKey / ClaudeKey 凭证
+fn has_deleted_at_column(conn: &Connection) -> Result<bool, String> {
+    let mut stmt = conn
+        .prepare("PRAGMA table_info(provider_pool_credentials)")
+        .map_err(|e| format!("读取 provider_pool_credentials 表结构失败: {e}"))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(|e| format!("扫描 provider_pool_credentials 列失败: {e}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("收集 provider_pool_credentials 列失败: {e}"))?;
+    Ok(columns.iter().any(|column| column == "deleted_at"))
+}
 
-    Ok(deleted)
+fn is_legacy_api_key_credential(credential_data: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(credential_data)
+        .ok()
+        .and_then(|data| data.get("type")?.as_str().map(str::to_string))
+        .is_some_and(|kind| kind == "openai_key" || kind == "claude_key")
 }
 
 fn map_api_key_credential(row: &ApiKeyMigrationRow) -> (&'static str, serde_json::Value) {
@@ -440,7 +480,8 @@ mod tests {
                 created_at INTEGER NOT NULL,
                 updated_at INTEGER NOT NULL,
                 source TEXT,
-                proxy_url TEXT
+                proxy_url TEXT,
+                deleted_at INTEGER
             );
             ",
         )
@@ -508,7 +549,7 @@ mod tests {
         let stored: (String, String, Option<String>, bool) = conn
             .query_row(
                 "SELECT provider_type, credential_data, name, is_disabled
-                 FROM provider_pool_credentials",
+                 FROM provider_pool_credentials WHERE deleted_at IS NULL",
                 [],
                 |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
             )
@@ -566,13 +607,19 @@ mod tests {
         insert_pool_credential(&conn, "legacy-openai", "openai_key");
         insert_pool_credential(&conn, "legacy-claude", "claude_key");
         insert_pool_credential(&conn, "new-gemini", "gemini_api_key");
+        insert_pool_credential(&conn, "deleted-openai", "openai_key");
+        conn.execute(
+            "UPDATE provider_pool_credentials SET deleted_at = 1 WHERE uuid = 'deleted-openai'",
+            [],
+        )
+        .unwrap();
 
         let deleted = cleanup_legacy_api_key_credentials(&conn).unwrap();
         assert_eq!(deleted, 2);
 
         let remaining: i64 = conn
             .query_row(
-                "SELECT COUNT(*) FROM provider_pool_credentials",
+                "SELECT COUNT(*) FROM provider_pool_credentials WHERE deleted_at IS NULL",
                 [],
                 |row| row.get(0),
             )
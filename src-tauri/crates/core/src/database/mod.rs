@@ -1,18 +1,23 @@
 pub mod agent_runtime_queue_repository;
 pub mod agent_session_repository;
 pub mod dao;
+pub mod field_crypto;
+pub mod maintenance;
 pub mod migration;
 mod migration_support;
 pub mod migration_v2;
 pub mod migration_v3;
 pub mod migration_v4;
+pub mod pool_storage;
+pub mod repair;
 pub mod schema;
+pub mod schema_migrations;
 mod startup_migrations;
 pub mod system_providers;
 
 use crate::app_paths;
 use rusqlite::Connection;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 /// 进程内共享的 SQLite 连接。
@@ -51,7 +56,7 @@ pub fn get_db_path() -> Result<PathBuf, String> {
 /// 初始化数据库连接
 pub fn init_database() -> Result<DbConnection, String> {
     let db_path = get_db_path()?;
-    let conn = Connection::open(&db_path).map_err(|e| e.to_string())?;
+    let (conn, corrupt_backup) = open_with_recovery(&db_path)?;
 
     // 设置 busy_timeout 为 5 秒，避免 "database is locked" 错误
     conn.busy_timeout(std::time::Duration::from_secs(5))
@@ -68,10 +73,73 @@ pub fn init_database() -> Result<DbConnection, String> {
 
     tracing::info!("[数据库] 已启用 WAL 模式和性能优化参数");
 
+    // 降级保护：数据库由更新版本的应用写入时拒绝打开
+    schema_migrations::check_schema_version(&conn)?;
+    field_crypto::install_field_cipher(crate::secret_store::secret_store(), &conn)?;
+
     // 创建表结构
     schema::create_tables(&conn).map_err(|e| e.to_string())?;
+    if let Some((reason, backup_path)) = corrupt_backup {
+        let salvaged = repair::salvage_tables(&conn, &backup_path);
+        tracing::warn!(
+            "[数据库] 已重建损坏的数据库，备份: {}，抢救: {:?}",
+            backup_path.display(),
+            salvaged
+        );
+        repair::record_repair(repair::DbRepairReport {
+            reason,
+            backup_path,
+            salvaged,
+        });
+    }
     migration::migrate_from_json(&conn)?;
     startup_migrations::run_startup_migrations(&conn);
 
+    let version = schema_migrations::run_pending_migrations(&conn)?;
+    tracing::info!("[数据库] 当前数据库版本: v{}", version);
+
     Ok(Arc::new(Mutex::new(conn)))
 }
+
+/// 校验待恢复的数据库快照（拒绝更新版本应用写入的快照）
+pub fn check_restore_snapshot(path: &Path, has_field_key: bool) -> Result<(), String> {
+    let snapshot = Connection::open(path).map_err(|e| format!("打开数据库快照失败: {e}"))?;
+    schema_migrations::check_schema_version(&snapshot)?;
+    if !has_field_key && field_crypto::has_encrypted_fields(&snapshot)? {
+        tracing::warn!("[数据库] 备份未包含数据密钥，快照中的加密凭证仅在密钥与当前一致时可读");
+    }
+    Ok(())
+}
+
+/// 恢复数据库快照后的初始化
+///
+/// 补齐表结构，用备份中的数据密钥把凭证字段改为当前密钥加密，再执行启动迁移与待运行的版本迁移。
+pub fn prepare_restored_database(conn: &Connection, field_key: Option<&str>) -> Result<(), String> {
+    schema::create_tables(conn).map_err(|e| e.to_string())?;
+    if let Some(field_key) = field_key {
+        let source = field_crypto::FieldCipher::from_base64(field_key)?;
+        let updated = field_crypto::reencrypt_fields(conn, &source)?;
+        tracing::info!("[数据库] 已按当前数据密钥重新加密 {} 条凭证", updated);
+    }
+    startup_migrations::run_startup_migrations(conn);
+    let version = schema_migrations::run_pending_migrations(conn)?;
+    tracing::info!("[数据库] 已恢复数据库快照，当前版本: v{}", version);
+    Ok(())
+}
+
+/// 打开数据库；检测到损坏时备份原文件并创建新库，返回（连接, 损坏原因与备份路径）
+fn open_with_recovery(db_path: &Path) -> Result<(Connection, Option<(String, PathBuf)>), String> {
+    let reason = match Connection::open(db_path) {
+        Ok(conn) => match repair::detect_corruption(&conn) {
+            None => return Ok((conn, None)),
+            Some(reason) => reason,
+        },
+        Err(e) if maintenance::is_corruption_error(&e) => e.to_string(),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    tracing::error!("[数据库] 检测到数据库损坏，将备份并重建: {}", reason);
+    let backup_path = repair::quarantine_database(db_path)?;
+    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    Ok((conn, Some((reason, backup_path))))
+}
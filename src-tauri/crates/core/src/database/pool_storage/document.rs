@@ -0,0 +1,570 @@
+//! 文档型存储（Redis / PostgreSQL 共用）
+//!
+//! 每个凭证（含 Token 缓存）序列化为一份 JSON 文档，按 UUID 存取。凭证数据与缓存的
+//! Token 使用共享存储的数据密钥加密（与 SQLite 列加密格式相同），后端只保存密文。
+//! 局部更新（健康状态、使用统计）为比较并交换：写入前文档已被其他实例修改时重新读取再应用，
+//! 多实例并发更新同一凭证不会丢失写入。
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use super::PoolStorage;
+use crate::database::field_crypto::{FieldCipher, ENCRYPTED_FIELD_PREFIX};
+use crate::models::provider_pool_model::{CachedTokenInfo, PoolProviderType, ProviderCredential};
+
+/// 比较并交换的最大重试次数
+const MAX_CAS_ATTEMPTS: usize = 16;
+
+/// Token 缓存中需要加密的字段（文档字段, 加密列名）
+const TOKEN_FIELDS: &[(&str, &str)] = &[
+    ("access_token", "cached_access_token"),
+    ("refresh_token", "cached_refresh_token"),
+];
+
+/// 文档存储的最小接口（文档为已加密的 JSON 字符串）
+pub trait DocumentBackend: Send + Sync {
+    fn load_all(&self) -> Result<Vec<String>, String>;
+    fn load(&self, uuid: &str) -> Result<Option<String>, String>;
+    /// 仅在文档不存在时写入，返回是否写入
+    fn insert_new(&self, uuid: &str, provider_type: &str, document: &str) -> Result<bool, String>;
+    /// 仅在当前文档仍为 `expected` 时替换，返回是否替换
+    fn compare_and_swap(
+        &self,
+        uuid: &str,
+        provider_type: &str,
+        expected: &str,
+        document: &str,
+    ) -> Result<bool, String>;
+    fn remove(&self, uuid: &str) -> Result<bool, String>;
+    /// 原子地获取或续期租约：无人持有、已过期或本就由 `holder` 持有时成功
+    fn try_lease(&self, name: &str, holder: &str, ttl: Duration) -> Result<bool, String>;
+    /// 释放租约（仅当持有者匹配时）
+    fn release_lease(&self, name: &str, holder: &str) -> Result<(), String>;
+}
+
+/// 执行阻塞的远程调用；位于多线程 tokio 运行时中时先让出工作线程，避免阻塞其他任务
+pub(super) fn blocking<T>(call: impl FnOnce() -> T) -> T {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(call)
+        }
+        _ => call(),
+    }
+}
+
+/// 基于文档后端的凭证池存储
+pub struct DocumentPoolStorage<B> {
+    kind: &'static str,
+    backend: B,
+    cipher: FieldCipher,
+}
+
+impl<B: DocumentBackend> DocumentPoolStorage<B> {
+    pub fn new(kind: &'static str, backend: B, cipher: FieldCipher) -> Self {
+        Self {
+            kind,
+            backend,
+            cipher,
+        }
+    }
+
+    /// 序列化并加密凭证数据与 Token
+    fn encode(&self, cred: &ProviderCredential) -> Result<String, String> {
+        let mut document = serde_json::to_value(cred).map_err(|e| e.to_string())?;
+        let credential = serde_json::to_string(&cred.credential).map_err(|e| e.to_string())?;
+        document["credential"] =
+            Value::String(self.cipher.encrypt("credential_data", &credential)?);
+        if let Some(token) = document
+            .get_mut("cached_token")
+            .and_then(Value::as_object_mut)
+        {
+            for (field, column) in TOKEN_FIELDS {
+                if let Some(Value::String(value)) = token.get_mut(*field) {
+                    *value = self.cipher.encrypt(column, value)?;
+                }
+            }
+        }
+        serde_json::to_string(&document).map_err(|e| e.to_string())
+    }
+
+    /// 解密并反序列化；兼容加密前写入的明文文档
+    fn decode(&self, json: &str) -> Result<ProviderCredential, String> {
+        let mut document: Value =
+            serde_json::from_str(json).map_err(|e| format!("解析凭证文档失败: {e}"))?;
+        if let Some(Value::String(sealed)) = document.get("credential") {
+            let credential = self.cipher.decrypt("credential_data", sealed)?;
+            document["credential"] =
+                serde_json::from_str(&credential).map_err(|e| format!("解析凭证数据失败: {e}"))?;
+        }
+        if let Some(token) = document
+            .get_mut("cached_token")
+            .and_then(Value::as_object_mut)
+        {
+            for (field, column) in TOKEN_FIELDS {
+                if let Some(Value::String(value)) = token.get_mut(*field) {
+                    if value.starts_with(ENCRYPTED_FIELD_PREFIX) {
+                        *value = self.cipher.decrypt(column, value)?;
+                    }
+                }
+            }
+        }
+        serde_json::from_value(document).map_err(|e| format!("解析凭证文档失败: {e}"))
+    }
+
+    fn load(&self, uuid: &str) -> Result<Option<ProviderCredential>, String> {
+        blocking(|| self.backend.load(uuid))?
+            .map(|json| self.decode(&json))
+            .transpose()
+    }
+
+    fn load_all(&self) -> Result<Vec<ProviderCredential>, String> {
+        blocking(|| self.backend.load_all())?
+            .iter()
+            .map(|json| self.decode(json))
+            .collect()
+    }
+
+    /// 原子地读-改-写单个凭证，`apply` 返回 `false` 时不写入
+    ///
+    /// 凭证不存在时返回 `false`（与 SQLite UPDATE 语义一致）；写入期间文档被其他实例修改时
+    /// 重新读取并再次应用 `apply`。
+    fn modify_if(
+        &self,
+        uuid: &str,
+        mut apply: impl FnMut(&mut ProviderCredential) -> bool,
+    ) -> Result<bool, String> {
+        for _ in 0..MAX_CAS_ATTEMPTS {
+            let Some(current) = blocking(|| self.backend.load(uuid))? else {
+                return Ok(false);
+            };
+            let mut cred = self.decode(&current)?;
+            if !apply(&mut cred) {
+                return Ok(false);
+            }
+            let document = self.encode(&cred)?;
+            let provider_type = cred.provider_type.to_string();
+            if blocking(|| {
+                self.backend
+                    .compare_and_swap(uuid, &provider_type, &current, &document)
+            })? {
+                return Ok(true);
+            }
+        }
+        Err(format!("凭证 {uuid} 并发更新冲突，请稍后重试"))
+    }
+
+    /// 原子地更新凭证并刷新 `updated_at`
+    fn modify(
+        &self,
+        uuid: &str,
+        mut apply: impl FnMut(&mut ProviderCredential),
+    ) -> Result<(), String> {
+        self.modify_if(uuid, |cred| {
+            apply(cred);
+            cred.updated_at = Utc::now();
+            true
+        })?;
+        Ok(())
+    }
+}
+
+impl<B: DocumentBackend> PoolStorage for DocumentPoolStorage<B> {
+    fn kind(&self) -> &'static str {
+        self.kind
+    }
+
+    fn try_acquire_lease(&self, name: &str, holder: &str, ttl: Duration) -> Result<bool, String> {
+        blocking(|| self.backend.try_lease(name, holder, ttl))
+    }
+
+    fn release_lease(&self, name: &str, holder: &str) -> Result<(), String> {
+        blocking(|| self.backend.release_lease(name, holder))
+    }
+
+    fn get_all(&self) -> Result<Vec<ProviderCredential>, String> {
+        let mut all = self.load_all()?;
+        all.retain(|cred| cred.deleted_at.is_none());
+        all.sort_by(|a, b| {
+            (a.provider_type.to_string(), a.created_at)
+                .cmp(&(b.provider_type.to_string(), b.created_at))
+        });
+        Ok(all)
+    }
+
+    fn get_by_uuid(&self, uuid: &str) -> Result<Option<ProviderCredential>, String> {
+        Ok(self.load(uuid)?.filter(|cred| cred.deleted_at.is_none()))
+    }
+
+    fn insert(&self, cred: &ProviderCredential) -> Result<(), String> {
+        let mut stored = cred.clone();
+        stored.cached_token = None;
+        let document = self.encode(&stored)?;
+        let provider_type = stored.provider_type.to_string();
+        if blocking(|| {
+            self.backend
+                .insert_new(&stored.uuid, &provider_type, &document)
+        })? {
+            Ok(())
+        } else {
+            Err(format!("凭证已存在: {}", cred.uuid))
+        }
+    }
+
+    fn update(&self, cred: &ProviderCredential) -> Result<(), String> {
+        // Token 缓存与来源由专用接口维护，与 SQLite 的 UPDATE 列保持一致
+        self.modify_if(&cred.uuid, |existing| {
+            let mut stored = cred.clone();
+            stored.cached_token = existing.cached_token.take();
+            stored.source = existing.source.clone();
+            stored.created_at = existing.created_at;
+            stored.deleted_at = existing.deleted_at;
+            *existing = stored;
+            true
+        })?;
+        Ok(())
+    }
+
+    fn delete(&self, uuid: &str) -> Result<bool, String> {
+        blocking(|| self.backend.remove(uuid))
+    }
+
+    fn get_deleted(&self) -> Result<Vec<ProviderCredential>, String> {
+        let mut deleted: Vec<_> = self
+            .load_all()?
+            .into_iter()
+            .filter(|cred| cred.deleted_at.is_some())
+            .collect();
+        deleted.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+        Ok(deleted)
+    }
+
+    fn soft_delete(&self, uuid: &str, deleted_at: DateTime<Utc>) -> Result<bool, String> {
+        self.modify_if(uuid, |cred| {
+            if cred.deleted_at.is_some() {
+                return false;
+            }
+            cred.deleted_at = Some(deleted_at);
+            true
+        })
+    }
+
+    fn restore(&self, uuid: &str) -> Result<bool, String> {
+        self.modify_if(uuid, |cred| {
+            if cred.deleted_at.is_none() {
+                return false;
+            }
+            cred.deleted_at = None;
+            cred.updated_at = Utc::now();
+            true
+        })
+    }
+
+    fn update_health_status(
+        &self,
+        uuid: &str,
+        is_healthy: bool,
+        error_count: u32,
+        last_error_time: Option<DateTime<Utc>>,
+        last_error_message: Option<&str>,
+        last_health_check_time: Option<DateTime<Utc>>,
+        last_health_check_model: Option<&str>,
+    ) -> Result<(), String> {
+        self.modify(uuid, |cred| {
+            cred.is_healthy = is_healthy;
+            cred.error_count = error_count;
+            cred.last_error_time = last_error_time;
+            cred.last_error_message = last_error_message.map(str::to_string);
+            cred.last_health_check_time = last_health_check_time;
+            cred.last_health_check_model = last_health_check_model.map(str::to_string);
+        })
+    }
+
+    fn update_usage(
+        &self,
+        uuid: &str,
+        usage_count: u64,
+        last_used: DateTime<Utc>,
+    ) -> Result<(), String> {
+        self.modify(uuid, |cred| {
+            cred.usage_count = usage_count;
+            cred.last_used = Some(last_used);
+        })
+    }
+
+    fn reset_counters(&self, uuid: &str) -> Result<(), String> {
+        self.modify(uuid, |cred| {
+            cred.usage_count = 0;
+            cred.error_count = 0;
+            cred.is_healthy = true;
+            cred.last_error_time = None;
+            cred.last_error_message = None;
+        })
+    }
+
+    fn reset_health_by_type(&self, provider_type: &PoolProviderType) -> Result<usize, String> {
+        let credentials = self.get_by_type(provider_type)?;
+        for cred in &credentials {
+            self.modify(&cred.uuid, |cred| {
+                cred.is_healthy = true;
+                cred.error_count = 0;
+                cred.last_error_time = None;
+                cred.last_error_message = None;
+            })?;
+        }
+        Ok(credentials.len())
+    }
+
+    fn get_token_cache(&self, uuid: &str) -> Result<Option<CachedTokenInfo>, String> {
+        Ok(self
+            .load(uuid)?
+            .and_then(|cred| cred.cached_token)
+            .filter(|token| token.access_token.is_some()))
+    }
+
+    fn update_token_cache(&self, uuid: &str, token_info: &CachedTokenInfo) -> Result<(), String> {
+        self.modify(uuid, |cred| cred.cached_token = Some(token_info.clone()))
+    }
+
+    fn clear_token_cache(&self, uuid: &str) -> Result<(), String> {
+        self.modify(uuid, |cred| cred.cached_token = None)
+    }
+
+    fn record_token_refresh_error(&self, uuid: &str, error_message: &str) -> Result<(), String> {
+        self.modify(uuid, |cred| {
+            let token = cred
+                .cached_token
+                .get_or_insert_with(CachedTokenInfo::default);
+            token.refresh_error_count += 1;
+            token.last_refresh_error = Some(error_message.to_string());
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::provider_pool_model::CredentialData;
+    use parking_lot::Mutex;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct MemoryDocuments(Mutex<HashMap<String, String>>);
+
+    impl DocumentBackend for MemoryDocuments {
+        fn load_all(&self) -> Result<Vec<String>, String> {
+            Ok(self.0.lock().values().cloned().collect())
+        }
+
+        fn load(&self, uuid: &str) -> Result<Option<String>, String> {
+            Ok(self.0.lock().get(uuid).cloned())
+        }
+
+        fn insert_new(
+            &self,
+            uuid: &str,
+            _provider_type: &str,
+            document: &str,
+        ) -> Result<bool, String> {
+            let mut documents = self.0.lock();
+            if documents.contains_key(uuid) {
+                return Ok(false);
+            }
+            documents.insert(uuid.to_string(), document.to_string());
+            Ok(true)
+        }
+
+        fn compare_and_swap(
+            &self,
+            uuid: &str,
+            _provider_type: &str,
+            expected: &str,
+            document: &str,
+        ) -> Result<bool, String> {
+            let mut documents = self.0.lock();
+            match documents.get_mut(uuid) {
+                Some(current) if current == expected => {
+                    *current = document.to_string();
+                    Ok(true)
+                }
+                _ => Ok(false),
+            }
+        }
+
+        fn remove(&self, uuid: &str) -> Result<bool, String> {
+            Ok(self.0.lock().remove(uuid).is_some())
+        }
+
+        fn try_lease(&self, _name: &str, _holder: &str, _ttl: Duration) -> Result<bool, String> {
+            Ok(true)
+        }
+
+        fn release_lease(&self, _name: &str, _holder: &str) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    fn storage() -> DocumentPoolStorage<MemoryDocuments> {
+        DocumentPoolStorage::new(
+            "memory",
+            MemoryDocuments::default(),
+            FieldCipher::new([7u8; 32]),
+        )
+    }
+
+    fn credential() -> ProviderCredential {
+        ProviderCredential::new(
+            PoolProviderType::OpenAI,
+            CredentialData::OpenAIKey {
+                api_key: "sk-test".to_string(),
+                base_url: None,
+            },
+        )
+    }
+
+    #[test]
+    fn should_mirror_sqlite_update_semantics() {
+        let storage = storage();
+        let cred = credential();
+
+        storage.insert(&cred).unwrap();
+        assert!(storage.insert(&cred).is_err());
+
+        storage
+            .update_token_cache(
+                &cred.uuid,
+                &CachedTokenInfo {
+                    access_token: Some("at".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let mut edited = cred.clone();
+        edited.name = Some("renamed".to_string());
+        storage.update(&edited).unwrap();
+
+        let loaded = storage.get_by_uuid(&cred.uuid).unwrap().unwrap();
+        assert_eq!(loaded.name.as_deref(), Some("renamed"));
+        assert_eq!(
+            storage
+                .get_token_cache(&cred.uuid)
+                .unwrap()
+                .and_then(|t| t.access_token)
+                .as_deref(),
+            Some("at")
+        );
+
+        storage
+            .update_health_status(&cred.uuid, false, 3, None, Some("boom"), None, None)
+            .unwrap();
+        assert_eq!(
+            storage
+                .reset_health_by_type(&PoolProviderType::OpenAI)
+                .unwrap(),
+            1
+        );
+        assert!(storage.get_by_uuid(&cred.uuid).unwrap().unwrap().is_healthy);
+        assert!(storage.delete(&cred.uuid).unwrap());
+    }
+
+    #[test]
+    fn should_soft_delete_restore_and_purge() {
+        let storage = storage();
+        let cred = credential();
+        storage.insert(&cred).unwrap();
+
+        let deleted_at = Utc::now() - chrono::Duration::days(40);
+        assert!(storage.soft_delete(&cred.uuid, deleted_at).unwrap());
+        assert!(!storage.soft_delete(&cred.uuid, deleted_at).unwrap());
+        assert!(storage.get_all().unwrap().is_empty());
+        assert!(storage.get_by_uuid(&cred.uuid).unwrap().is_none());
+        assert_eq!(storage.get_deleted().unwrap().len(), 1);
+
+        assert!(storage.restore(&cred.uuid).unwrap());
+        assert!(!storage.restore(&cred.uuid).unwrap());
+        assert_eq!(storage.get_all().unwrap().len(), 1);
+
+        storage.soft_delete(&cred.uuid, deleted_at).unwrap();
+        let cutoff = Utc::now() - chrono::Duration::days(30);
+        assert_eq!(storage.purge_deleted_before(cutoff).unwrap(), 1);
+        assert!(storage.get_deleted().unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_hide_token_cache_without_access_token() {
+        let storage = storage();
+        let cred = credential();
+        storage.insert(&cred).unwrap();
+
+        storage
+            .record_token_refresh_error(&cred.uuid, "invalid_grant")
+            .unwrap();
+        assert!(storage.get_token_cache(&cred.uuid).unwrap().is_none());
+        // 不存在的凭证不报错
+        storage.update_usage("missing", 1, Utc::now()).unwrap();
+    }
+
+    #[test]
+    fn should_store_credentials_encrypted() {
+        let storage = storage();
+        let cred = credential();
+        storage.insert(&cred).unwrap();
+        storage
+            .update_token_cache(
+                &cred.uuid,
+                &CachedTokenInfo {
+                    access_token: Some("at-secret".to_string()),
+                    refresh_token: Some("rt-secret".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        let raw = storage.backend.load(&cred.uuid).unwrap().unwrap();
+        assert!(!raw.contains("sk-test"));
+        assert!(!raw.contains("at-secret"));
+        assert!(!raw.contains("rt-secret"));
+
+        let loaded = storage.get_by_uuid(&cred.uuid).unwrap().unwrap();
+        assert!(matches!(
+            loaded.credential,
+            CredentialData::OpenAIKey { ref api_key, .. } if api_key == "sk-test"
+        ));
+        let token = storage.get_token_cache(&cred.uuid).unwrap().unwrap();
+        assert_eq!(token.refresh_token.as_deref(), Some("rt-secret"));
+
+        // 其他实例使用不同密钥时无法读取
+        let other = DocumentPoolStorage::new(
+            "memory",
+            MemoryDocuments::default(),
+            FieldCipher::new([8u8; 32]),
+        );
+        other.backend.0.lock().insert(cred.uuid.clone(), raw);
+        assert!(other.get_by_uuid(&cred.uuid).is_err());
+    }
+
+    #[test]
+    fn should_reapply_update_after_concurrent_write() {
+        let storage = storage();
+        let cred = credential();
+        storage.insert(&cred).unwrap();
+
+        let mut attempts = 0;
+        storage
+            .modify(&cred.uuid, |stored| {
+                attempts += 1;
+                if attempts == 1 {
+                    // 模拟另一实例在本次读取之后写入
+                    storage.update_usage(&cred.uuid, 5, Utc::now()).unwrap();
+                }
+                stored.error_count += 1;
+            })
+            .unwrap();
+
+        assert_eq!(attempts, 2);
+        let loaded = storage.get_by_uuid(&cred.uuid).unwrap().unwrap();
+        assert_eq!(loaded.usage_count, 5);
+        assert_eq!(loaded.error_count, 1);
+    }
+}
@@ -0,0 +1,552 @@
+//! 凭证池存储后端
+//!
+//! 凭证池的读写统一经过 [`PoolStore`]：默认使用本地 SQLite，直接委托给 [`ProviderPoolDao`]；
+//! 多实例部署可切换为 Redis / PostgreSQL（需启用 `pool-redis` / `pool-postgres` 特性），
+//! 各实例共享同一份凭证、健康状态与 Token 缓存。共享存储实现 [`PoolStorage`]，不访问本地数据库。
+//!
+//! 调用方通过 [`lock_pool`] 获取 [`PoolConnection`]：本地存储时持有数据库锁，SQLite 沿用该连接，
+//! 避免重入 `DbConnection` 的互斥锁；共享存储时不获取数据库锁，远程请求期间其他线程仍可使用 SQLite。
+//!
+//! 配置了共享存储但连接失败时启动直接报错，不回退到本地 SQLite，避免各实例各自维护一份凭证。
+
+mod document;
+#[cfg(feature = "pool-postgres")]
+mod postgres_backend;
+#[cfg(feature = "pool-redis")]
+mod redis_backend;
+
+use std::sync::{Arc, MutexGuard, OnceLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use rusqlite::Connection;
+
+use crate::config::{PoolStorageBackend, PoolStorageSettings};
+use crate::database::dao::provider_pool::ProviderPoolDao;
+#[cfg(any(feature = "pool-redis", feature = "pool-postgres"))]
+use crate::database::field_crypto::FieldCipher;
+use crate::database::{lock_db, DbConnection};
+use crate::models::provider_pool_model::{
+    CachedTokenInfo, PoolProviderType, ProviderCredential, ProviderPools,
+};
+
+pub use document::{DocumentBackend, DocumentPoolStorage};
+
+/// 多实例共享的凭证池存储
+pub trait PoolStorage: Send + Sync {
+    /// 后端名称（日志与诊断用）
+    fn kind(&self) -> &'static str;
+
+    fn get_all(&self) -> Result<Vec<ProviderCredential>, String>;
+
+    fn get_by_uuid(&self, uuid: &str) -> Result<Option<ProviderCredential>, String>;
+
+    fn get_by_type(
+        &self,
+        provider_type: &PoolProviderType,
+    ) -> Result<Vec<ProviderCredential>, String> {
+        Ok(self
+            .get_all()?
+            .into_iter()
+            .filter(|cred| cred.provider_type == *provider_type)
+            .collect())
+    }
+
+    fn get_by_name(&self, name: &str) -> Result<Option<ProviderCredential>, String> {
+        Ok(self
+            .get_all()?
+            .into_iter()
+            .find(|cred| cred.name.as_deref() == Some(name)))
+    }
+
+    fn get_grouped(&self) -> Result<ProviderPools, String> {
+        let mut grouped: ProviderPools = std::collections::HashMap::new();
+        for cred in self.get_all()? {
+            grouped.entry(cred.provider_type).or_default().push(cred);
+        }
+        Ok(grouped)
+    }
+
+    fn insert(&self, cred: &ProviderCredential) -> Result<(), String>;
+
+    fn update(&self, cred: &ProviderCredential) -> Result<(), String>;
+
+    /// 永久删除凭证
+    fn delete(&self, uuid: &str) -> Result<bool, String>;
+
+    /// 回收站中的凭证（最近删除的在前），其他读取接口均不返回这些凭证
+    fn get_deleted(&self) -> Result<Vec<ProviderCredential>, String>;
+
+    /// 移入回收站，凭证不存在或已在回收站时返回 `false`
+    fn soft_delete(&self, uuid: &str, deleted_at: DateTime<Utc>) -> Result<bool, String>;
+
+    /// 从回收站恢复，凭证不在回收站时返回 `false`
+    fn restore(&self, uuid: &str) -> Result<bool, String>;
+
+    /// 永久删除在 `cutoff` 之前移入回收站的凭证，返回删除数量
+    fn purge_deleted_before(&self, cutoff: DateTime<Utc>) -> Result<u64, String> {
+        let mut purged = 0;
+        for cred in self.get_deleted()? {
+            if cred.deleted_at.is_some_and(|t| t < cutoff) && self.delete(&cred.uuid)? {
+                purged += 1;
+            }
+        }
+        Ok(purged)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn update_health_status(
+        &self,
+        uuid: &str,
+        is_healthy: bool,
+        error_count: u32,
+        last_error_time: Option<DateTime<Utc>>,
+        last_error_message: Option<&str>,
+        last_health_check_time: Option<DateTime<Utc>>,
+        last_health_check_model: Option<&str>,
+    ) -> Result<(), String>;
+
+    fn update_usage(
+        &self,
+        uuid: &str,
+        usage_count: u64,
+        last_used: DateTime<Utc>,
+    ) -> Result<(), String>;
+
+    fn reset_counters(&self, uuid: &str) -> Result<(), String>;
+
+    fn reset_health_by_type(&self, provider_type: &PoolProviderType) -> Result<usize, String>;
+
+    fn get_token_cache(&self, uuid: &str) -> Result<Option<CachedTokenInfo>, String>;
+
+    fn update_token_cache(&self, uuid: &str, token_info: &CachedTokenInfo) -> Result<(), String>;
+
+    fn clear_token_cache(&self, uuid: &str) -> Result<(), String>;
+
+    fn record_token_refresh_error(&self, uuid: &str, error_message: &str) -> Result<(), String>;
+
+    /// 获取或续期租约（多实例选主用），成功返回 `true`
+    fn try_acquire_lease(&self, name: &str, holder: &str, ttl: Duration) -> Result<bool, String>;
+
+    /// 主动释放租约（仅当持有者匹配时）
+    fn release_lease(&self, name: &str, holder: &str) -> Result<(), String>;
+}
+
+/// 凭证池操作使用的连接
+pub enum PoolConnection<'a> {
+    /// 本地 SQLite：持有数据库锁
+    Local(MutexGuard<'a, Connection>),
+    /// 共享存储：不占用数据库锁
+    Shared,
+}
+
+impl PoolConnection<'_> {
+    /// 本地 SQLite 连接（存储在获取连接后被切换时返回错误）
+    fn local(&self) -> Result<&Connection, String> {
+        match self {
+            Self::Local(guard) => Ok(guard),
+            Self::Shared => Err("凭证池存储已切换，请重试".to_string()),
+        }
+    }
+}
+
+/// 当前使用的凭证池存储
+#[derive(Clone)]
+pub enum PoolStore {
+    /// 本地 SQLite（默认）
+    Local,
+    /// 多实例共享存储
+    Shared(Arc<dyn PoolStorage>),
+}
+
+impl PoolStore {
+    /// 本地存储走 DAO，共享存储忽略连接
+    fn dispatch<T>(
+        &self,
+        conn: &PoolConnection<'_>,
+        local: impl FnOnce(&Connection) -> Result<T, rusqlite::Error>,
+        shared: impl FnOnce(&dyn PoolStorage) -> Result<T, String>,
+    ) -> Result<T, String> {
+        match self {
+            Self::Local => local(conn.local()?).map_err(|e| e.to_string()),
+            Self::Shared(storage) => shared(storage.as_ref()),
+        }
+    }
+
+    /// 后端名称（日志与诊断用）
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Local => "sqlite",
+            Self::Shared(storage) => storage.kind(),
+        }
+    }
+
+    /// 是否为多实例共享的存储
+    pub fn is_shared(&self) -> bool {
+        matches!(self, Self::Shared(_))
+    }
+
+    pub fn get_all(&self, conn: &PoolConnection<'_>) -> Result<Vec<ProviderCredential>, String> {
+        self.dispatch(conn, ProviderPoolDao::get_all, |s| s.get_all())
+    }
+
+    pub fn get_by_uuid(
+        &self,
+        conn: &PoolConnection<'_>,
+        uuid: &str,
+    ) -> Result<Option<ProviderCredential>, String> {
+        self.dispatch(
+            conn,
+            |c| ProviderPoolDao::get_by_uuid(c, uuid),
+            |s| s.get_by_uuid(uuid),
+        )
+    }
+
+    pub fn get_by_type(
+        &self,
+        conn: &PoolConnection<'_>,
+        provider_type: &PoolProviderType,
+    ) -> Result<Vec<ProviderCredential>, String> {
+        self.dispatch(
+            conn,
+            |c| ProviderPoolDao::get_by_type(c, provider_type),
+            |s| s.get_by_type(provider_type),
+        )
+    }
+
+    pub fn get_by_name(
+        &self,
+        conn: &PoolConnection<'_>,
+        name: &str,
+    ) -> Result<Option<ProviderCredential>, String> {
+        self.dispatch(
+            conn,
+            |c| ProviderPoolDao::get_by_name(c, name),
+            |s| s.get_by_name(name),
+        )
+    }
+
+    pub fn get_grouped(&self, conn: &PoolConnection<'_>) -> Result<ProviderPools, String> {
+        self.dispatch(conn, ProviderPoolDao::get_grouped, |s| s.get_grouped())
+    }
+
+    pub fn insert(
+        &self,
+        conn: &PoolConnection<'_>,
+        cred: &ProviderCredential,
+    ) -> Result<(), String> {
+        self.dispatch(
+            conn,
+            |c| ProviderPoolDao::insert(c, cred),
+            |s| s.insert(cred),
+        )
+    }
+
+    pub fn update(
+        &self,
+        conn: &PoolConnection<'_>,
+        cred: &ProviderCredential,
+    ) -> Result<(), String> {
+        self.dispatch(
+            conn,
+            |c| ProviderPoolDao::update(c, cred),
+            |s| s.update(cred),
+        )
+    }
+
+    /// 永久删除凭证
+    pub fn delete(&self, conn: &PoolConnection<'_>, uuid: &str) -> Result<bool, String> {
+        self.dispatch(
+            conn,
+            |c| ProviderPoolDao::delete(c, uuid),
+            |s| s.delete(uuid),
+        )
+    }
+
+    /// 回收站中的凭证（最近删除的在前），其他读取接口均不返回这些凭证
+    pub fn get_deleted(
+        &self,
+        conn: &PoolConnection<'_>,
+    ) -> Result<Vec<ProviderCredential>, String> {
+        self.dispatch(conn, ProviderPoolDao::get_deleted, |s| s.get_deleted())
+    }
+
+    /// 移入回收站，凭证不存在或已在回收站时返回 `false`
+    pub fn soft_delete(
+        &self,
+        conn: &PoolConnection<'_>,
+        uuid: &str,
+        deleted_at: DateTime<Utc>,
+    ) -> Result<bool, String> {
+        self.dispatch(
+            conn,
+            |c| ProviderPoolDao::soft_delete(c, uuid, deleted_at),
+            |s| s.soft_delete(uuid, deleted_at),
+        )
+    }
+
+    /// 从回收站恢复，凭证不在回收站时返回 `false`
+    pub fn restore(&self, conn: &PoolConnection<'_>, uuid: &str) -> Result<bool, String> {
+        self.dispatch(
+            conn,
+            |c| ProviderPoolDao::restore(c, uuid),
+            |s| s.restore(uuid),
+        )
+    }
+
+    /// 永久删除在 `cutoff` 之前移入回收站的凭证，返回删除数量
+    pub fn purge_deleted_before(
+        &self,
+        conn: &PoolConnection<'_>,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64, String> {
+        self.dispatch(
+            conn,
+            |c| {
+                let mut purged = 0;
+                for cred in ProviderPoolDao::get_deleted(c)? {
+                    if cred.deleted_at.is_some_and(|t| t < cutoff)
+                        && ProviderPoolDao::delete(c, &cred.uuid)?
+                    {
+                        purged += 1;
+                    }
+                }
+                Ok(purged)
+            },
+            |s| s.purge_deleted_before(cutoff),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_health_status(
+        &self,
+        conn: &PoolConnection<'_>,
+        uuid: &str,
+        is_healthy: bool,
+        error_count: u32,
+        last_error_time: Option<DateTime<Utc>>,
+        last_error_message: Option<&str>,
+        last_health_check_time: Option<DateTime<Utc>>,
+        last_health_check_model: Option<&str>,
+    ) -> Result<(), String> {
+        self.dispatch(
+            conn,
+            |c| {
+                ProviderPoolDao::update_health_status(
+                    c,
+                    uuid,
+                    is_healthy,
+                    error_count,
+                    last_error_time,
+                    last_error_message,
+                    last_health_check_time,
+                    last_health_check_model,
+                )
+            },
+            |s| {
+                s.update_health_status(
+                    uuid,
+                    is_healthy,
+                    error_count,
+                    last_error_time,
+                    last_error_message,
+                    last_health_check_time,
+                    last_health_check_model,
+                )
+            },
+        )
+    }
+
+    pub fn update_usage(
+        &self,
+        conn: &PoolConnection<'_>,
+        uuid: &str,
+        usage_count: u64,
+        last_used: DateTime<Utc>,
+    ) -> Result<(), String> {
+        self.dispatch(
+            conn,
+            |c| ProviderPoolDao::update_usage(c, uuid, usage_count, last_used),
+            |s| s.update_usage(uuid, usage_count, last_used),
+        )
+    }
+
+    pub fn reset_counters(&self, conn: &PoolConnection<'_>, uuid: &str) -> Result<(), String> {
+        self.dispatch(
+            conn,
+            |c| ProviderPoolDao::reset_counters(c, uuid),
+            |s| s.reset_counters(uuid),
+        )
+    }
+
+    pub fn reset_health_by_type(
+        &self,
+        conn: &PoolConnection<'_>,
+        provider_type: &PoolProviderType,
+    ) -> Result<usize, String> {
+        self.dispatch(
+            conn,
+            |c| ProviderPoolDao::reset_health_by_type(c, provider_type),
+            |s| s.reset_health_by_type(provider_type),
+        )
+    }
+
+    pub fn get_token_cache(
+        &self,
+        conn: &PoolConnection<'_>,
+        uuid: &str,
+    ) -> Result<Option<CachedTokenInfo>, String> {
+        self.dispatch(
+            conn,
+            |c| ProviderPoolDao::get_token_cache(c, uuid),
+            |s| s.get_token_cache(uuid),
+        )
+    }
+
+    pub fn update_token_cache(
+        &self,
+        conn: &PoolConnection<'_>,
+        uuid: &str,
+        token_info: &CachedTokenInfo,
+    ) -> Result<(), String> {
+        self.dispatch(
+            conn,
+            |c| ProviderPoolDao::update_token_cache(c, uuid, token_info),
+            |s| s.update_token_cache(uuid, token_info),
+        )
+    }
+
+    pub fn clear_token_cache(&self, conn: &PoolConnection<'_>, uuid: &str) -> Result<(), String> {
+        self.dispatch(
+            conn,
+            |c| ProviderPoolDao::clear_token_cache(c, uuid),
+            |s| s.clear_token_cache(uuid),
+        )
+    }
+
+    pub fn record_token_refresh_error(
+        &self,
+        conn: &PoolConnection<'_>,
+        uuid: &str,
+        error_message: &str,
+    ) -> Result<(), String> {
+        self.dispatch(
+            conn,
+            |c| ProviderPoolDao::record_token_refresh_error(c, uuid, error_message),
+            |s| s.record_token_refresh_error(uuid, error_message),
+        )
+    }
+
+    /// 获取或续期租约（多实例选主用），成功返回 `true`
+    ///
+    /// 本地存储只服务单个实例，总是成功。
+    pub fn try_acquire_lease(
+        &self,
+        name: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<bool, String> {
+        match self {
+            Self::Local => Ok(true),
+            Self::Shared(storage) => storage.try_acquire_lease(name, holder, ttl),
+        }
+    }
+
+    /// 主动释放租约（仅当持有者匹配时）
+    pub fn release_lease(&self, name: &str, holder: &str) -> Result<(), String> {
+        match self {
+            Self::Local => Ok(()),
+            Self::Shared(storage) => storage.release_lease(name, holder),
+        }
+    }
+}
+
+fn active_slot() -> &'static RwLock<PoolStore> {
+    static ACTIVE: OnceLock<RwLock<PoolStore>> = OnceLock::new();
+    ACTIVE.get_or_init(|| RwLock::new(PoolStore::Local))
+}
+
+/// 当前使用的凭证池存储
+pub fn pool_storage() -> PoolStore {
+    active_slot().read().clone()
+}
+
+/// 获取凭证池操作的连接：本地存储时锁定数据库，共享存储时不获取数据库锁
+///
+/// 返回的连接只能传给 [`PoolStore`] 的方法，其他 DAO 仍需使用 [`lock_db`]。
+pub fn lock_pool(db: &DbConnection) -> Result<PoolConnection<'_>, String> {
+    if pool_storage().is_shared() {
+        Ok(PoolConnection::Shared)
+    } else {
+        lock_db(db).map(PoolConnection::Local)
+    }
+}
+
+/// 切换凭证池存储（启动时调用）
+pub fn install_pool_storage(store: PoolStore) {
+    tracing::info!("[凭证池] 使用存储后端: {}", store.kind());
+    *active_slot().write() = store;
+}
+
+/// 按配置创建存储后端
+pub fn build_pool_storage(settings: &PoolStorageSettings) -> Result<PoolStore, String> {
+    let require_url = || {
+        if settings.url.trim().is_empty() {
+            Err("未配置凭证池存储连接地址 (server.pool_storage.url)".to_string())
+        } else {
+            Ok(settings.url.trim())
+        }
+    };
+    // 所有实例由同一口令派生数据密钥，共享存储中只保存密文
+    #[cfg(any(feature = "pool-redis", feature = "pool-postgres"))]
+    let require_cipher = || {
+        if settings.encryption_key.is_empty() {
+            return Err("未配置共享存储加密口令 (server.pool_storage.encryption_key)".to_string());
+        }
+        let salt = format!("lime-pool-storage:{}", settings.prefix);
+        Ok(FieldCipher::new(crate::config::derive_key(
+            &settings.encryption_key,
+            salt.as_bytes(),
+        )))
+    };
+    match settings.backend {
+        PoolStorageBackend::Sqlite => Ok(PoolStore::Local),
+        #[cfg(feature = "pool-redis")]
+        PoolStorageBackend::Redis => {
+            let cipher = require_cipher()?;
+            let backend = redis_backend::RedisBackend::connect(require_url()?, &settings.prefix)?;
+            Ok(PoolStore::Shared(Arc::new(DocumentPoolStorage::new(
+                "redis", backend, cipher,
+            ))))
+        }
+        #[cfg(feature = "pool-postgres")]
+        PoolStorageBackend::Postgres => {
+            let cipher = require_cipher()?;
+            let backend =
+                postgres_backend::PostgresBackend::connect(require_url()?, &settings.prefix)?;
+            Ok(PoolStore::Shared(Arc::new(DocumentPoolStorage::new(
+                "postgres", backend, cipher,
+            ))))
+        }
+        #[allow(unreachable_patterns)]
+        other => {
+            require_url()?;
+            Err(format!(
+                "当前构建未包含 {other:?} 凭证池存储支持，请启用对应的编译特性"
+            ))
+        }
+    }
+}
+
+/// 按配置切换存储后端
+///
+/// 共享存储不可用时返回错误而不回退到本地 SQLite：回退后该实例会与其他实例各自维护凭证与 Token，
+/// 互相使对方的 refresh token 失效。
+pub fn configure_pool_storage(settings: &PoolStorageSettings) -> Result<(), String> {
+    if settings.backend == PoolStorageBackend::Sqlite {
+        return Ok(());
+    }
+    install_pool_storage(build_pool_storage(settings)?);
+    Ok(())
+}
@@ -0,0 +1,222 @@
+//! PostgreSQL 凭证池存储
+//!
+//! 凭证存放在表 `{prefix}_credentials(uuid, provider_type, data, updated_at)` 中，
+//! `data` 为加密后的 JSON 文档；选主租约存放在 `{prefix}_leases(name, holder, expires_at)`。
+//! 连接池基于 sqlx（异步），查询在专用运行时中执行，同步接口阻塞等待结果。
+//! 比较并交换为带 `data = $expected` 条件的单条 UPDATE，由数据库保证原子性。
+
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use sqlx::postgres::{PgPool, PgPoolOptions};
+
+use super::document::{blocking, DocumentBackend};
+
+pub(super) struct PostgresBackend {
+    pool: PgPool,
+    table: String,
+    lease_table: String,
+}
+
+/// PostgreSQL 连接专用的运行时
+///
+/// sqlx 连接绑定在创建它的运行时上，因此所有查询都提交到同一个运行时执行，
+/// 调用方（可能位于任意 tokio 运行时或普通线程中）阻塞等待结果。
+fn runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("lime-pool-postgres")
+            .enable_all()
+            .build()
+            .expect("创建 PostgreSQL 运行时失败")
+    })
+}
+
+/// 在专用运行时中执行查询并等待结果，任务异常退出（panic）时返回错误
+fn block_on<T: Send + 'static>(
+    future: impl Future<Output = T> + Send + 'static,
+) -> Result<T, String> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    runtime().spawn(async move {
+        let _ = tx.send(future.await);
+    });
+    blocking(move || rx.recv()).map_err(|_| "PostgreSQL 任务异常退出".to_string())
+}
+
+impl PostgresBackend {
+    pub(super) fn connect(url: &str, prefix: &str) -> Result<Self, String> {
+        if !prefix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(format!(
+                "PostgreSQL 表名前缀只能包含字母、数字和下划线: {prefix}"
+            ));
+        }
+        let table = format!("{prefix}_credentials");
+        let url = url.to_string();
+        let pool =
+            block_on(async move { PgPoolOptions::new().max_connections(4).connect(&url).await })?
+                .map_err(|e| format!("连接 PostgreSQL 失败: {e}"))?;
+        let create_table = format!(
+            "CREATE TABLE IF NOT EXISTS {table} (
+                uuid TEXT PRIMARY KEY,
+                provider_type TEXT NOT NULL,
+                data TEXT NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+            )"
+        );
+        let lease_table = format!("{prefix}_leases");
+        let create_lease_table = format!(
+            "CREATE TABLE IF NOT EXISTS {lease_table} (
+                name TEXT PRIMARY KEY,
+                holder TEXT NOT NULL,
+                expires_at TIMESTAMPTZ NOT NULL
+            )"
+        );
+        let setup_pool = pool.clone();
+        block_on(async move {
+            sqlx::query(&create_table).execute(&setup_pool).await?;
+            sqlx::query(&create_lease_table).execute(&setup_pool).await
+        })?
+        .map_err(|e| format!("创建凭证表失败: {e}"))?;
+        Ok(Self {
+            pool,
+            table,
+            lease_table,
+        })
+    }
+}
+
+impl DocumentBackend for PostgresBackend {
+    fn load_all(&self) -> Result<Vec<String>, String> {
+        let (pool, sql) = (
+            self.pool.clone(),
+            format!("SELECT data FROM {}", self.table),
+        );
+        let rows: Vec<(String,)> =
+            block_on(async move { sqlx::query_as(&sql).fetch_all(&pool).await })?
+                .map_err(|e| format!("读取凭证失败: {e}"))?;
+        Ok(rows.into_iter().map(|(json,)| json).collect())
+    }
+
+    fn load(&self, uuid: &str) -> Result<Option<String>, String> {
+        let pool = self.pool.clone();
+        let sql = format!("SELECT data FROM {} WHERE uuid = $1", self.table);
+        let uuid = uuid.to_string();
+        let row: Option<(String,)> =
+            block_on(async move { sqlx::query_as(&sql).bind(uuid).fetch_optional(&pool).await })?
+                .map_err(|e| format!("读取凭证失败: {e}"))?;
+        Ok(row.map(|(json,)| json))
+    }
+
+    fn insert_new(&self, uuid: &str, provider_type: &str, document: &str) -> Result<bool, String> {
+        let pool = self.pool.clone();
+        let sql = format!(
+            "INSERT INTO {} (uuid, provider_type, data, updated_at) VALUES ($1, $2, $3, now())
+             ON CONFLICT (uuid) DO NOTHING",
+            self.table
+        );
+        let (uuid, provider_type, document) = (
+            uuid.to_string(),
+            provider_type.to_string(),
+            document.to_string(),
+        );
+        let result = block_on(async move {
+            sqlx::query(&sql)
+                .bind(uuid)
+                .bind(provider_type)
+                .bind(document)
+                .execute(&pool)
+                .await
+        })?
+        .map_err(|e| format!("保存凭证失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn compare_and_swap(
+        &self,
+        uuid: &str,
+        provider_type: &str,
+        expected: &str,
+        document: &str,
+    ) -> Result<bool, String> {
+        let pool = self.pool.clone();
+        let sql = format!(
+            "UPDATE {} SET provider_type = $2, data = $4, updated_at = now()
+             WHERE uuid = $1 AND data = $3",
+            self.table
+        );
+        let (uuid, provider_type, expected, document) = (
+            uuid.to_string(),
+            provider_type.to_string(),
+            expected.to_string(),
+            document.to_string(),
+        );
+        let result = block_on(async move {
+            sqlx::query(&sql)
+                .bind(uuid)
+                .bind(provider_type)
+                .bind(expected)
+                .bind(document)
+                .execute(&pool)
+                .await
+        })?
+        .map_err(|e| format!("保存凭证失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn remove(&self, uuid: &str) -> Result<bool, String> {
+        let pool = self.pool.clone();
+        let sql = format!("DELETE FROM {} WHERE uuid = $1", self.table);
+        let uuid = uuid.to_string();
+        let result = block_on(async move { sqlx::query(&sql).bind(uuid).execute(&pool).await })?
+            .map_err(|e| format!("删除凭证失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn try_lease(&self, name: &str, holder: &str, ttl: Duration) -> Result<bool, String> {
+        let pool = self.pool.clone();
+        let sql = format!(
+            "INSERT INTO {lease} (name, holder, expires_at)
+             VALUES ($1, $2, now() + $3::bigint * interval '1 millisecond')
+             ON CONFLICT (name) DO UPDATE SET
+             holder = EXCLUDED.holder, expires_at = EXCLUDED.expires_at
+             WHERE {lease}.holder = EXCLUDED.holder OR {lease}.expires_at < now()",
+            lease = self.lease_table
+        );
+        let (name, holder) = (name.to_string(), holder.to_string());
+        let ttl_ms = ttl.as_millis().max(1) as i64;
+        let result = block_on(async move {
+            sqlx::query(&sql)
+                .bind(name)
+                .bind(holder)
+                .bind(ttl_ms)
+                .execute(&pool)
+                .await
+        })?
+        .map_err(|e| format!("获取租约失败: {e}"))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn release_lease(&self, name: &str, holder: &str) -> Result<(), String> {
+        let pool = self.pool.clone();
+        let sql = format!(
+            "DELETE FROM {} WHERE name = $1 AND holder = $2",
+            self.lease_table
+        );
+        let (name, holder) = (name.to_string(), holder.to_string());
+        block_on(async move {
+            sqlx::query(&sql)
+                .bind(name)
+                .bind(holder)
+                .execute(&pool)
+                .await
+        })?
+        .map_err(|e| format!("释放租约失败: {e}"))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+//! Redis 凭证池存储
+//!
+//! 全部凭证存放在哈希 `{prefix}:credentials` 中（field 为 UUID，value 为加密后的 JSON 文档）；
+//! 选主租约存放在 `{prefix}:lease:{name}`（带过期时间的字符串键）。
+//! 比较并交换通过 Lua 脚本在服务端原子执行。
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use redis::Commands;
+
+use super::document::DocumentBackend;
+
+/// 持有者匹配时续期，否则仅在无人持有时获取
+const ACQUIRE_LEASE_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    redis.call('PEXPIRE', KEYS[1], ARGV[2])
+    return 1
+end
+if redis.call('SET', KEYS[1], ARGV[1], 'NX', 'PX', ARGV[2]) then
+    return 1
+end
+return 0
+"#;
+
+/// 文档仍为期望值时替换
+const COMPARE_AND_SWAP_SCRIPT: &str = r#"
+if redis.call('HGET', KEYS[1], ARGV[1]) == ARGV[2] then
+    redis.call('HSET', KEYS[1], ARGV[1], ARGV[3])
+    return 1
+end
+return 0
+"#;
+
+const RELEASE_LEASE_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+pub(super) struct RedisBackend {
+    client: redis::Client,
+    connection: Mutex<redis::Connection>,
+    key: String,
+    prefix: String,
+}
+
+impl RedisBackend {
+    pub(super) fn connect(url: &str, prefix: &str) -> Result<Self, String> {
+        let client = redis::Client::open(url).map_err(|e| format!("Redis 地址无效: {e}"))?;
+        let connection = client
+            .get_connection()
+            .map_err(|e| format!("连接 Redis 失败: {e}"))?;
+        Ok(Self {
+            client,
+            connection: Mutex::new(connection),
+            key: format!("{prefix}:credentials"),
+            prefix: prefix.to_string(),
+        })
+    }
+
+    /// 执行命令，连接断开时重连并重试一次
+    fn with_connection<T>(
+        &self,
+        operation: impl Fn(&mut redis::Connection, &str) -> redis::RedisResult<T>,
+    ) -> Result<T, String> {
+        let mut connection = self.connection.lock();
+        match operation(&mut connection, &self.key) {
+            Err(e) if e.is_connection_dropped() || e.is_io_error() => {
+                tracing::warn!("[凭证池] Redis 连接中断，正在重连: {}", e);
+                *connection = self
+                    .client
+                    .get_connection()
+                    .map_err(|e| format!("重连 Redis 失败: {e}"))?;
+                operation(&mut connection, &self.key).map_err(|e| format!("Redis 操作失败: {e}"))
+            }
+            result => result.map_err(|e| format!("Redis 操作失败: {e}")),
+        }
+    }
+}
+
+impl DocumentBackend for RedisBackend {
+    fn load_all(&self) -> Result<Vec<String>, String> {
+        let documents: HashMap<String, String> =
+            self.with_connection(|conn, key| conn.hgetall(key))?;
+        Ok(documents.into_values().collect())
+    }
+
+    fn load(&self, uuid: &str) -> Result<Option<String>, String> {
+        self.with_connection(|conn, key| conn.hget(key, uuid))
+    }
+
+    fn insert_new(&self, uuid: &str, _provider_type: &str, document: &str) -> Result<bool, String> {
+        self.with_connection(|conn, key| conn.hset_nx(key, uuid, document))
+    }
+
+    fn compare_and_swap(
+        &self,
+        uuid: &str,
+        _provider_type: &str,
+        expected: &str,
+        document: &str,
+    ) -> Result<bool, String> {
+        let swapped: i64 = self.with_connection(|conn, key| {
+            redis::Script::new(COMPARE_AND_SWAP_SCRIPT)
+                .key(key)
+                .arg(uuid)
+                .arg(expected)
+                .arg(document)
+                .invoke(conn)
+        })?;
+        Ok(swapped == 1)
+    }
+
+    fn remove(&self, uuid: &str) -> Result<bool, String> {
+        let removed: i64 = self.with_connection(|conn, key| conn.hdel(key, uuid))?;
+        Ok(removed > 0)
+    }
+
+    fn try_lease(&self, name: &str, holder: &str, ttl: Duration) -> Result<bool, String> {
+        let lease_key = format!("{}:lease:{name}", self.prefix);
+        let ttl_ms = ttl.as_millis().max(1) as u64;
+        let acquired: i64 = self.with_connection(|conn, _| {
+            redis::Script::new(ACQUIRE_LEASE_SCRIPT)
+                .key(&lease_key)
+                .arg(holder)
+                .arg(ttl_ms)
+                .invoke(conn)
+        })?;
+        Ok(acquired == 1)
+    }
+
+    fn release_lease(&self, name: &str, holder: &str) -> Result<(), String> {
+        let lease_key = format!("{}:lease:{name}", self.prefix);
+        self.with_connection(|conn, _| {
+            redis::Script::new(RELEASE_LEASE_SCRIPT)
+                .key(&lease_key)
+                .arg(holder)
+                .invoke::<i64>(conn)
+        })?;
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+//! 损坏数据库的自动修复
+//!
+//! 启动时对数据库做快速完整性检查，损坏时：
+//! 1. 将数据库文件（含 `-wal` / `-shm`）改名备份为 `*.corrupt-<时间戳>`
+//! 2. 创建新的数据库并建表
+//! 3. 从备份中尽力抢救可读的关键表（逐表、按共有列复制，失败则跳过）
+//!
+//! 应用层随后从配置文件重建凭证池（见 `take_last_repair`），保证服务可用而不是
+//! 一直返回 "Database not available"。
+
+use std::path::{Path, PathBuf};
+
+use parking_lot::Mutex;
+use rusqlite::Connection;
+use serde::Serialize;
+
+use super::maintenance::{is_corruption_error, quick_check};
+
+/// 尝试从损坏库中抢救的表（按依赖顺序）
+const SALVAGE_TABLES: &[&str] = &[
+    "settings",
+    "api_key_providers",
+    "api_keys",
+    "provider_pool_credentials",
+    "providers",
+    "mcp_servers",
+    "prompts",
+    "skills",
+    "skill_repos",
+];
+
+static LAST_REPAIR: Mutex<Option<DbRepairReport>> = Mutex::new(None);
+
+/// 修复结果
+#[derive(Debug, Clone, Serialize)]
+pub struct DbRepairReport {
+    /// 检测到损坏的原因
+    pub reason: String,
+    /// 损坏数据库的备份路径
+    pub backup_path: PathBuf,
+    /// 成功抢救的表及行数
+    pub salvaged: Vec<(String, usize)>,
+}
+
+/// 取出本次启动的修复结果（仅返回一次）
+pub fn take_last_repair() -> Option<DbRepairReport> {
+    LAST_REPAIR.lock().take()
+}
+
+pub(crate) fn record_repair(report: DbRepairReport) {
+    *LAST_REPAIR.lock() = Some(report);
+}
+
+/// 检测数据库是否损坏，返回损坏原因
+pub(crate) fn detect_corruption(conn: &Connection) -> Option<String> {
+    match quick_check(conn) {
+        Ok(issues) if issues.is_empty() => None,
+        Ok(issues) => Some(issues.join("; ")),
+        Err(e) if is_corruption_error(&e) => Some(e.to_string()),
+        Err(e) => {
+            tracing::warn!("[数据库] 完整性快速检查失败（忽略）: {}", e);
+            None
+        }
+    }
+}
+
+fn sidecar_path(path: &Path, suffix: &str) -> PathBuf {
+    path.with_file_name(format!(
+        "{}{suffix}",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    ))
+}
+
+/// 将损坏的数据库文件改名备份，返回备份路径
+pub(crate) fn quarantine_database(path: &Path) -> Result<PathBuf, String> {
+    let stamp = chrono::Utc::now().format("%Y%m%d%H%M%S");
+    let backup = sidecar_path(path, &format!(".corrupt-{stamp}"));
+    std::fs::rename(path, &backup).map_err(|e| format!("备份损坏的数据库失败: {e}"))?;
+    for suffix in ["-wal", "-shm"] {
+        let sidecar = sidecar_path(path, suffix);
+        if sidecar.exists() {
+            let target = sidecar_path(&backup, suffix);
+            if let Err(e) = std::fs::rename(&sidecar, &target) {
+                tracing::warn!("[数据库] 备份 {} 失败: {}", sidecar.display(), e);
+            }
+        }
+    }
+    Ok(backup)
+}
+
+fn table_columns(conn: &Connection, schema: &str, table: &str) -> Vec<String> {
+    let Ok(mut stmt) = conn.prepare(&format!("PRAGMA {schema}.table_info({table})")) else {
+        return Vec::new();
+    };
+    stmt.query_map([], |row| row.get::<_, String>(1))
+        .map(|rows| rows.filter_map(Result::ok).collect())
+        .unwrap_or_default()
+}
+
+/// 从损坏库中抢救关键表到新库（新库需已建表）
+pub(crate) fn salvage_tables(conn: &Connection, backup: &Path) -> Vec<(String, usize)> {
+    if let Err(e) = conn.execute(
+        "ATTACH DATABASE ?1 AS corrupt",
+        [backup.to_string_lossy().as_ref()],
+    ) {
+        tracing::warn!("[数据库] 无法打开损坏的数据库进行抢救: {}", e);
+        return Vec::new();
+    }
+
+    let mut salvaged = Vec::new();
+    for table in SALVAGE_TABLES {
+        let old_columns = table_columns(conn, "corrupt", table);
+        let columns: Vec<String> = table_columns(conn, "main", table)
+            .into_iter()
+            .filter(|column| old_columns.contains(column))
+            .map(|column| format!("\"{column}\""))
+            .collect();
+        if columns.is_empty() {
+            continue;
+        }
+        let column_list = columns.join(", ");
+        let sql = format!(
+            "INSERT OR IGNORE INTO main.{table} ({column_list}) SELECT {column_list} FROM corrupt.{table}"
+        );
+        match conn.execute(&sql, []) {
+            Ok(count) => salvaged.push((table.to_string(), count)),
+            Err(e) => tracing::warn!("[数据库] 抢救表 {} 失败: {}", table, e),
+        }
+    }
+
+    let _ = conn.execute("DETACH DATABASE corrupt", []);
+    salvaged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_quarantine_and_salvage_readable_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lime.db");
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL, legacy TEXT);
+                 INSERT INTO settings (key, value) VALUES ('theme', 'dark');",
+            )
+            .unwrap();
+        }
+
+        let backup = quarantine_database(&path).expect("备份失败");
+        assert!(!path.exists());
+        assert!(backup.exists());
+
+        let conn = Connection::open(&path).unwrap();
+        assert_eq!(detect_corruption(&conn), None);
+        conn.execute(
+            "CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT NOT NULL)",
+            [],
+        )
+        .unwrap();
+
+        let salvaged = salvage_tables(&conn, &backup);
+        assert_eq!(salvaged, vec![("settings".to_string(), 1)]);
+        let value: String = conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = 'theme'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(value, "dark");
+    }
+}
@@ -1504,6 +1504,93 @@ pub fn create_tables(conn: &Connection) -> Result<(), rusqlite::Error> {
         [],
     )?;
 
+    // 嵌入缓存表（/v1/embeddings 按模型 + 规范化文本哈希去重）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS embedding_cache (
+            model TEXT NOT NULL,
+            text_hash TEXT NOT NULL,
+            dimensions INTEGER NOT NULL,
+            embedding BLOB NOT NULL,
+            hit_count INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            last_hit_at TEXT NOT NULL,
+            PRIMARY KEY (model, text_hash)
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_embedding_cache_last_hit_at ON embedding_cache(last_hit_at)",
+        [],
+    )?;
+
+    // 本地 RAG 文档表（/v1/rag/*，向量以小端 f32 BLOB 存储）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS rag_documents (
+            collection TEXT NOT NULL,
+            id TEXT NOT NULL,
+            content TEXT NOT NULL,
+            metadata TEXT,
+            embedding_model TEXT NOT NULL,
+            embedding BLOB NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (collection, id)
+        )",
+        [],
+    )?;
+
+    // 配置变更审计表（changes 为字段级变更 JSON，密钥已脱敏）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS config_audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            created_at TEXT NOT NULL,
+            source TEXT NOT NULL,
+            actor TEXT NOT NULL,
+            summary TEXT NOT NULL,
+            changes TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_config_audit_log_created_at ON config_audit_log(created_at DESC)",
+        [],
+    )?;
+
+    // 多模态上传去重表（scope 为 Provider + 端点 + Key 指纹，content_hash 为素材 SHA-256）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS upload_file_refs (
+            scope TEXT NOT NULL,
+            content_hash TEXT NOT NULL,
+            file_ref TEXT NOT NULL,
+            mime_type TEXT NOT NULL,
+            size_bytes INTEGER NOT NULL,
+            hit_count INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            expires_at TEXT,
+            PRIMARY KEY (scope, content_hash)
+        )",
+        [],
+    )?;
+
+    // 对话记录表（用于导出微调数据集；key_id 为受限 Key 的 ID，主 Key 为 primary）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS proxy_transcripts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            created_at TEXT NOT NULL,
+            path TEXT NOT NULL,
+            model TEXT NOT NULL,
+            key_id TEXT NOT NULL,
+            consent INTEGER NOT NULL DEFAULT 0,
+            request_json TEXT NOT NULL,
+            response_json TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_proxy_transcripts_created_at ON proxy_transcripts(created_at)",
+        [],
+    )?;
+
     Ok(())
 }
 
@@ -0,0 +1,255 @@
+//! 版本化表结构迁移
+//!
+//! 数据库版本记录在 `PRAGMA user_version` 中，每个迁移在独立事务内执行，
+//! 成功后更新版本号并写入 `schema_migrations` 历史表。
+//!
+//! - 新的表结构变更请追加到 [`MIGRATIONS`] 末尾，不要再在 `schema::create_tables` 中追加 `ALTER TABLE`
+//! - 数据库版本高于当前应用支持的版本时拒绝打开（降级保护），避免旧版本应用破坏新格式数据
+
+use rusqlite::{params, Connection};
+
+use super::field_crypto::{encrypt_field, is_field_encryption_active};
+use super::migration_support::run_in_transaction;
+use super::schema;
+
+/// 单个版本迁移
+pub struct SchemaMigration {
+    pub version: i64,
+    pub description: &'static str,
+    pub up: fn(&Connection) -> Result<(), String>,
+}
+
+/// 全部迁移（按版本递增）
+const MIGRATIONS: &[SchemaMigration] = &[
+    SchemaMigration {
+        version: 1,
+        description: "基线表结构",
+        up: baseline,
+    },
+    SchemaMigration {
+        version: 2,
+        description: "凭证池敏感字段加密",
+        up: encrypt_credential_fields,
+    },
+    SchemaMigration {
+        version: 3,
+        description: "凭证回收站（软删除）",
+        up: add_credential_deleted_at,
+    },
+];
+
+/// 当前应用支持的数据库版本
+pub fn latest_schema_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// 读取数据库版本
+pub fn read_schema_version(conn: &Connection) -> Result<i64, String> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| format!("读取数据库版本失败: {e}"))
+}
+
+/// 降级保护：数据库版本高于应用支持的版本时返回错误
+pub fn ensure_not_downgraded(conn: &Connection, latest: i64) -> Result<i64, String> {
+    let version = read_schema_version(conn)?;
+    if version > latest {
+        return Err(format!(
+            "数据库版本 ({version}) 高于当前应用支持的版本 ({latest})，请升级 Lime 后再打开，或从备份恢复旧版本数据库"
+        ));
+    }
+    Ok(version)
+}
+
+/// 启动时的降级保护检查（需在建表与其他迁移之前调用）
+pub fn check_schema_version(conn: &Connection) -> Result<i64, String> {
+    ensure_not_downgraded(conn, latest_schema_version())
+}
+
+/// 执行全部待执行的版本迁移，返回迁移后的版本
+pub fn run_pending_migrations(conn: &Connection) -> Result<i64, String> {
+    apply_migrations(conn, MIGRATIONS)
+}
+
+fn ensure_history_table(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            app_version TEXT NOT NULL,
+            applied_at INTEGER NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("创建 schema_migrations 表失败: {e}"))?;
+    Ok(())
+}
+
+/// 按顺序执行版本号大于当前版本的迁移
+pub(crate) fn apply_migrations(
+    conn: &Connection,
+    migrations: &[SchemaMigration],
+) -> Result<i64, String> {
+    let latest = migrations.last().map(|m| m.version).unwrap_or(0);
+    let mut version = ensure_not_downgraded(conn, latest)?;
+    ensure_history_table(conn)?;
+
+    for migration in migrations.iter().filter(|m| m.version > version) {
+        tracing::info!(
+            "[数据库] 执行迁移 v{}: {}",
+            migration.version,
+            migration.description
+        );
+        run_in_transaction(conn, |tx| {
+            (migration.up)(tx)?;
+            tx.execute(
+                "INSERT OR REPLACE INTO schema_migrations (version, description, app_version, applied_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    migration.version,
+                    migration.description,
+                    env!("CARGO_PKG_VERSION"),
+                    chrono::Utc::now().timestamp(),
+                ],
+            )
+            .map_err(|e| format!("写入迁移历史失败: {e}"))?;
+            tx.execute_batch(&format!("PRAGMA user_version = {}", migration.version))
+                .map_err(|e| format!("更新数据库版本失败: {e}"))
+        })
+        .map_err(|e| format!("迁移 v{} 失败: {e}", migration.version))?;
+        version = migration.version;
+    }
+    Ok(version)
+}
+
+fn baseline(conn: &Connection) -> Result<(), String> {
+    // 基线表结构由 schema::create_tables 幂等创建（每次启动执行，兼容引入版本号之前的旧库）
+    schema::create_tables(conn).map_err(|e| e.to_string())
+}
+
+/// 加密凭证池中的明文敏感字段
+fn encrypt_credential_fields(conn: &Connection) -> Result<(), String> {
+    if !is_field_encryption_active() {
+        tracing::warn!("[数据库] 未启用字段加密，已有凭证将在下次更新时加密");
+        return Ok(());
+    }
+
+    let rows: Vec<(String, String, Option<String>, Option<String>)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT uuid, credential_data, cached_access_token, cached_refresh_token
+                 FROM provider_pool_credentials",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+    };
+
+    for (uuid, credential_data, access_token, refresh_token) in &rows {
+        let encrypt_token = |column: &str, value: &Option<String>| {
+            value.as_deref().map(|token| {
+                if crate::secret_store::is_keychain_ref(token) {
+                    token.to_string()
+                } else {
+                    encrypt_field(column, token)
+                }
+            })
+        };
+        conn.execute(
+            "UPDATE provider_pool_credentials SET
+             credential_data = ?2, cached_access_token = ?3, cached_refresh_token = ?4
+             WHERE uuid = ?1",
+            params![
+                uuid,
+                encrypt_field("credential_data", credential_data),
+                encrypt_token("cached_access_token", access_token),
+                encrypt_token("cached_refresh_token", refresh_token),
+            ],
+        )
+        .map_err(|e| format!("加密凭证 {uuid} 失败: {e}"))?;
+    }
+    tracing::info!("[数据库] 已加密 {} 条凭证", rows.len());
+    Ok(())
+}
+
+/// 凭证软删除时间，非空表示在回收站中
+fn add_credential_deleted_at(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "ALTER TABLE provider_pool_credentials ADD COLUMN deleted_at INTEGER;
+         CREATE INDEX IF NOT EXISTS idx_provider_pool_deleted_at
+             ON provider_pool_credentials(deleted_at);",
+    )
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_demo(conn: &Connection) -> Result<(), String> {
+        conn.execute("CREATE TABLE demo (value TEXT)", [])
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    fn failing(conn: &Connection) -> Result<(), String> {
+        conn.execute("INSERT INTO demo (value) VALUES ('partial')", [])
+            .map_err(|e| e.to_string())?;
+        Err("boom".to_string())
+    }
+
+    const DEMO: &[SchemaMigration] = &[SchemaMigration {
+        version: 1,
+        description: "demo",
+        up: create_demo,
+    }];
+
+    #[test]
+    fn should_apply_pending_migrations_once() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        assert_eq!(apply_migrations(&conn, DEMO), Ok(1));
+        assert_eq!(read_schema_version(&conn), Ok(1));
+        // 再次执行不会重复建表
+        assert_eq!(apply_migrations(&conn, DEMO), Ok(1));
+
+        let history: i64 = conn
+            .query_row("SELECT COUNT(*) FROM schema_migrations", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(history, 1);
+    }
+
+    #[test]
+    fn should_roll_back_failed_migration_and_refuse_downgrade() {
+        let conn = Connection::open_in_memory().unwrap();
+        apply_migrations(&conn, DEMO).unwrap();
+
+        let with_failure = [
+            SchemaMigration {
+                version: 1,
+                description: "demo",
+                up: create_demo,
+            },
+            SchemaMigration {
+                version: 2,
+                description: "failing",
+                up: failing,
+            },
+        ];
+        assert!(apply_migrations(&conn, &with_failure).is_err());
+        assert_eq!(read_schema_version(&conn), Ok(1));
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM demo", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, 0);
+
+        conn.execute_batch("PRAGMA user_version = 9").unwrap();
+        assert!(apply_migrations(&conn, DEMO).is_err());
+        assert!(check_schema_version(&conn).is_err());
+    }
+}
@@ -4,6 +4,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::i18n::{self, MessageCode};
+
 /// 网关错误码
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -54,18 +56,23 @@ impl GatewayErrorCode {
         }
     }
 
-    /// 默认错误文案
+    /// 默认错误文案（按当前语言）
     pub fn default_message(self) -> &'static str {
+        i18n::t(self.message_code())
+    }
+
+    /// 默认错误文案的消息码
+    pub fn message_code(self) -> MessageCode {
         match self {
-            Self::InvalidRequest => "请求参数无效",
-            Self::AuthenticationFailed => "认证失败",
-            Self::RequestConflict => "请求冲突",
-            Self::RateLimited => "请求过于频繁，请稍后重试",
-            Self::NoCredentials => "当前没有可用凭证",
-            Self::UpstreamTimeout => "上游请求超时",
-            Self::UpstreamUnavailable => "上游服务暂不可用",
-            Self::UpstreamError => "上游服务返回错误",
-            Self::InternalError => "服务内部错误",
+            Self::InvalidRequest => MessageCode::GatewayInvalidRequest,
+            Self::AuthenticationFailed => MessageCode::GatewayAuthenticationFailed,
+            Self::RequestConflict => MessageCode::GatewayRequestConflict,
+            Self::RateLimited => MessageCode::GatewayRateLimited,
+            Self::NoCredentials => MessageCode::GatewayNoCredentials,
+            Self::UpstreamTimeout => MessageCode::GatewayUpstreamTimeout,
+            Self::UpstreamUnavailable => MessageCode::GatewayUpstreamUnavailable,
+            Self::UpstreamError => MessageCode::GatewayUpstreamError,
+            Self::InternalError => MessageCode::GatewayInternalError,
         }
     }
 
@@ -0,0 +1,281 @@
+//! 后端文案本地化
+//!
+//! API 错误体与面向用户的后端文案统一从消息目录取出，按稳定的消息码（如 `auth.invalid_key`）
+//! 索引，至少提供中文与英文两种文案。
+//!
+//! 语言选择顺序：
+//! 1. 当前请求作用域内的语言（服务器按 `Accept-Language` 协商，见 [`scope`]）
+//! 2. 全局语言：`server.locale`，未设置时使用界面语言 `language`（见 [`set_locale`]）
+//!
+//! 文案中的 `{name}` 占位符由 [`t_with`] 按名称替换。
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+/// 支持的语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    Zh,
+    En,
+}
+
+impl Locale {
+    /// 语言标识
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Zh => "zh",
+            Self::En => "en",
+        }
+    }
+
+    /// 解析语言标识，接受 `zh`、`zh-CN`、`en_US` 等写法（只看主语言，大小写不敏感）
+    pub fn parse(value: &str) -> Option<Self> {
+        let primary = value
+            .trim()
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match primary.as_str() {
+            "zh" => Some(Self::Zh),
+            "en" => Some(Self::En),
+            _ => None,
+        }
+    }
+
+    /// 按 `Accept-Language` 请求头选择语言：取权重最高的受支持语言，`q=0` 表示不接受
+    pub fn from_accept_language(header: &str) -> Option<Self> {
+        let mut best: Option<(Self, f32)> = None;
+        for item in header.split(',') {
+            let mut parts = item.split(';');
+            let Some(locale) = parts.next().and_then(Self::parse) else {
+                continue;
+            };
+            let weight = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if weight <= 0.0 {
+                continue;
+            }
+            match best {
+                Some((_, best_weight)) if best_weight >= weight => {}
+                _ => best = Some((locale, weight)),
+            }
+        }
+        best.map(|(locale, _)| locale)
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::Zh => 0,
+            Self::En => 1,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::En,
+            _ => Self::Zh,
+        }
+    }
+}
+
+static GLOBAL_LOCALE: AtomicU8 = AtomicU8::new(0);
+
+tokio::task_local! {
+    static REQUEST_LOCALE: Locale;
+}
+
+/// 设置全局语言
+pub fn set_locale(locale: Locale) {
+    GLOBAL_LOCALE.store(locale.to_u8(), Ordering::Relaxed);
+}
+
+/// 按配置设置全局语言：优先 `server.locale`，其次界面语言，都无法识别时使用中文
+pub fn configure(server_locale: Option<&str>, ui_language: &str) {
+    let locale = server_locale
+        .and_then(Locale::parse)
+        .or_else(|| Locale::parse(ui_language))
+        .unwrap_or_default();
+    set_locale(locale);
+}
+
+/// 当前生效的语言
+pub fn current_locale() -> Locale {
+    REQUEST_LOCALE
+        .try_with(|locale| *locale)
+        .unwrap_or_else(|_| Locale::from_u8(GLOBAL_LOCALE.load(Ordering::Relaxed)))
+}
+
+/// 在指定语言的作用域内执行（用于按请求协商语言）
+pub async fn scope<F: Future>(locale: Locale, future: F) -> F::Output {
+    REQUEST_LOCALE.scope(locale, future).await
+}
+
+/// 消息码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageCode {
+    GatewayInvalidRequest,
+    GatewayAuthenticationFailed,
+    GatewayRequestConflict,
+    GatewayRateLimited,
+    GatewayNoCredentials,
+    GatewayUpstreamTimeout,
+    GatewayUpstreamUnavailable,
+    GatewayUpstreamError,
+    GatewayInternalError,
+    AuthMissingKey,
+    AuthMissingKeyAnthropic,
+    AuthInvalidKey,
+    AuthLockedOut,
+    DatabaseUnavailable,
+    ErrorSerializationFailed,
+}
+
+impl MessageCode {
+    /// 全部消息码
+    pub const ALL: &'static [MessageCode] = &[
+        Self::GatewayInvalidRequest,
+        Self::GatewayAuthenticationFailed,
+        Self::GatewayRequestConflict,
+        Self::GatewayRateLimited,
+        Self::GatewayNoCredentials,
+        Self::GatewayUpstreamTimeout,
+        Self::GatewayUpstreamUnavailable,
+        Self::GatewayUpstreamError,
+        Self::GatewayInternalError,
+        Self::AuthMissingKey,
+        Self::AuthMissingKeyAnthropic,
+        Self::AuthInvalidKey,
+        Self::AuthLockedOut,
+        Self::DatabaseUnavailable,
+        Self::ErrorSerializationFailed,
+    ];
+
+    /// 稳定的消息码字符串
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::GatewayInvalidRequest => "gateway.invalid_request",
+            Self::GatewayAuthenticationFailed => "gateway.authentication_failed",
+            Self::GatewayRequestConflict => "gateway.request_conflict",
+            Self::GatewayRateLimited => "gateway.rate_limited",
+            Self::GatewayNoCredentials => "gateway.no_credentials",
+            Self::GatewayUpstreamTimeout => "gateway.upstream_timeout",
+            Self::GatewayUpstreamUnavailable => "gateway.upstream_unavailable",
+            Self::GatewayUpstreamError => "gateway.upstream_error",
+            Self::GatewayInternalError => "gateway.internal_error",
+            Self::AuthMissingKey => "auth.missing_key",
+            Self::AuthMissingKeyAnthropic => "auth.missing_key_anthropic",
+            Self::AuthInvalidKey => "auth.invalid_key",
+            Self::AuthLockedOut => "auth.locked_out",
+            Self::DatabaseUnavailable => "database.unavailable",
+            Self::ErrorSerializationFailed => "error.serialization_failed",
+        }
+    }
+
+    /// 按消息码字符串查找
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|c| c.code() == code)
+    }
+
+    /// 指定语言的文案
+    pub fn text(self, locale: Locale) -> &'static str {
+        let (zh, en) = match self {
+            Self::GatewayInvalidRequest => ("请求参数无效", "Invalid request"),
+            Self::GatewayAuthenticationFailed => ("认证失败", "Authentication failed"),
+            Self::GatewayRequestConflict => ("请求冲突", "Request conflict"),
+            Self::GatewayRateLimited => (
+                "请求过于频繁，请稍后重试",
+                "Too many requests, please retry later",
+            ),
+            Self::GatewayNoCredentials => ("当前没有可用凭证", "No available credentials"),
+            Self::GatewayUpstreamTimeout => ("上游请求超时", "Upstream request timed out"),
+            Self::GatewayUpstreamUnavailable => {
+                ("上游服务暂不可用", "Upstream service unavailable")
+            }
+            Self::GatewayUpstreamError => {
+                ("上游服务返回错误", "Upstream service returned an error")
+            }
+            Self::GatewayInternalError => ("服务内部错误", "Internal server error"),
+            Self::AuthMissingKey => ("未提供 API Key", "No API key provided"),
+            Self::AuthMissingKeyAnthropic => (
+                "未提供 API Key，请设置 x-api-key 请求头",
+                "No API key provided. Please set the x-api-key header.",
+            ),
+            Self::AuthInvalidKey => ("API Key 无效", "Invalid API key"),
+            Self::AuthLockedOut => (
+                "认证失败次数过多，请在 {seconds} 秒后重试",
+                "Too many failed authentication attempts. Try again in {seconds} seconds.",
+            ),
+            Self::DatabaseUnavailable => ("数据库不可用", "Database not available"),
+            Self::ErrorSerializationFailed => {
+                ("序列化错误响应失败", "Failed to serialize error response")
+            }
+        };
+        match locale {
+            Locale::Zh => zh,
+            Locale::En => en,
+        }
+    }
+}
+
+/// 当前语言的文案
+pub fn t(code: MessageCode) -> &'static str {
+    code.text(current_locale())
+}
+
+/// 当前语言的文案，并替换 `{name}` 占位符
+pub fn t_with(code: MessageCode, args: &[(&str, &str)]) -> String {
+    args.iter()
+        .fold(t(code).to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_accept_language() {
+        assert_eq!(Locale::parse("zh-CN"), Some(Locale::Zh));
+        assert_eq!(Locale::parse("EN_us"), Some(Locale::En));
+        assert_eq!(Locale::parse("fr"), None);
+        assert_eq!(
+            Locale::from_accept_language("fr-FR, en-US;q=0.8, zh;q=0.5"),
+            Some(Locale::En)
+        );
+        assert_eq!(
+            Locale::from_accept_language("en;q=0, zh-TW;q=0.3"),
+            Some(Locale::Zh)
+        );
+        assert_eq!(Locale::from_accept_language("de, fr"), None);
+    }
+
+    #[tokio::test]
+    async fn test_catalog_and_request_scope() {
+        for code in MessageCode::ALL {
+            assert_eq!(MessageCode::from_code(code.code()), Some(*code));
+            assert!(!code.text(Locale::Zh).is_empty());
+            assert!(!code.text(Locale::En).is_empty());
+        }
+
+        let text = scope(Locale::En, async {
+            t_with(MessageCode::AuthLockedOut, &[("seconds", "30")])
+        })
+        .await;
+        assert_eq!(
+            text,
+            "Too many failed authentication attempts. Try again in 30 seconds."
+        );
+        assert_eq!(
+            scope(Locale::Zh, async { t(MessageCode::AuthInvalidKey) }).await,
+            "API Key 无效"
+        );
+    }
+}
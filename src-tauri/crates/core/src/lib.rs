@@ -8,6 +8,8 @@
 //! - `data`: 静态数据
 //! - `logger`: 日志配置
 //! - `errors`: 错误类型定义
+//! - `i18n`: 后端文案本地化（消息目录与语言选择）
+//! - `anonymous_stats`: 匿名使用统计（默认关闭）
 //! - `backends`: 后端调用层 Trait
 //! - `config`: 配置管理（类型、YAML、热重载、导入导出）
 //! - `connect`: Deep Link 协议和中转商注册表
@@ -29,10 +31,12 @@ pub mod tray_menu_meta;
 pub mod tray_state;
 
 // 独立业务模块（无主 crate 依赖）
+pub mod anonymous_stats;
 pub mod backends;
 pub mod config;
 pub mod connect;
 pub mod errors;
+pub mod i18n;
 pub mod middleware;
 pub mod orchestrator;
 pub mod plugin;
@@ -59,6 +63,9 @@ pub mod websocket;
 // 事件发射抽象（供独立 crate 解耦 Tauri 依赖）
 pub mod event_emit;
 
+// 类型化应用事件通道（版本化的前后端事件协议）
+pub mod app_events;
+
 // 网络工具
 pub mod network;
 pub mod openclaw_install;
@@ -66,10 +73,17 @@ pub mod openclaw_install;
 // 凭证清理（敏感信息过滤）
 pub mod sanitizer;
 
+// 系统钥匙串密钥存储
+pub mod secret_store;
+
+// 多实例选主
+pub mod cluster;
+
 // 数据层
 pub mod content;
 pub mod database;
 pub mod memory;
+pub mod retention;
 pub mod workspace;
 
 // 重新导出常用类型
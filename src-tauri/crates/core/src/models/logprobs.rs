@@ -0,0 +1,103 @@
+//! OpenAI `logprobs` / `top_logprobs` 请求参数
+//!
+//! 类型化的 `ChatCompletionRequest` 不包含这两个字段，这里从原始请求体中单独解析，
+//! 转发给支持的 Provider（OpenAI 兼容接口），其他 Provider 明确返回不支持而不是静默丢弃。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// `top_logprobs` 上限（与 OpenAI 一致）
+pub const MAX_TOP_LOGPROBS: u64 = 20;
+
+/// 请求的 logprobs 选项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogprobsOptions {
+    /// 每个位置返回的候选 Token 数
+    pub top_logprobs: Option<u8>,
+}
+
+impl LogprobsOptions {
+    /// 从请求体解析，未请求 logprobs 时返回 `Ok(None)`，参数无效时返回错误说明
+    pub fn from_body(body: &Value) -> Result<Option<Self>, String> {
+        let logprobs = match &body["logprobs"] {
+            Value::Null => false,
+            Value::Bool(enabled) => *enabled,
+            _ => return Err("`logprobs` must be a boolean".to_string()),
+        };
+        let top_logprobs = match &body["top_logprobs"] {
+            Value::Null => None,
+            value => match value.as_u64().filter(|n| *n <= MAX_TOP_LOGPROBS) {
+                Some(n) => Some(n as u8),
+                None => {
+                    return Err(format!(
+                        "`top_logprobs` must be an integer between 0 and {MAX_TOP_LOGPROBS}"
+                    ))
+                }
+            },
+        };
+        if !logprobs {
+            if top_logprobs.is_some() {
+                return Err("`top_logprobs` requires `logprobs` to be true".to_string());
+            }
+            return Ok(None);
+        }
+        Ok(Some(Self { top_logprobs }))
+    }
+
+    /// OpenAI 请求体中的参数
+    pub fn to_params(&self) -> Map<String, Value> {
+        let mut params = Map::new();
+        params.insert("logprobs".to_string(), Value::Bool(true));
+        if let Some(top_logprobs) = self.top_logprobs {
+            params.insert("top_logprobs".to_string(), Value::from(top_logprobs));
+        }
+        params
+    }
+
+    /// 写入 OpenAI 格式的请求体
+    pub fn apply(&self, payload: &mut Value) {
+        if let Some(object) = payload.as_object_mut() {
+            object.extend(self.to_params());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_body() {
+        assert_eq!(
+            LogprobsOptions::from_body(&json!({"model": "gpt-4o"})),
+            Ok(None)
+        );
+        assert_eq!(
+            LogprobsOptions::from_body(&json!({"logprobs": false})),
+            Ok(None)
+        );
+        assert_eq!(
+            LogprobsOptions::from_body(&json!({"logprobs": true, "top_logprobs": 5})),
+            Ok(Some(LogprobsOptions {
+                top_logprobs: Some(5)
+            }))
+        );
+        assert!(LogprobsOptions::from_body(&json!({"top_logprobs": 5})).is_err());
+        assert!(
+            LogprobsOptions::from_body(&json!({"logprobs": true, "top_logprobs": 21})).is_err()
+        );
+        assert!(LogprobsOptions::from_body(&json!({"logprobs": 5})).is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut payload = json!({"model": "gpt-4o"});
+        LogprobsOptions {
+            top_logprobs: Some(3),
+        }
+        .apply(&mut payload);
+        assert_eq!(payload["logprobs"], true);
+        assert_eq!(payload["top_logprobs"], 3);
+    }
+}
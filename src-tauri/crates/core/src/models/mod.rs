@@ -8,6 +8,7 @@ pub mod client_type;
 pub mod codewhisperer;
 pub mod injection_types;
 pub mod kiro_fingerprint;
+pub mod logprobs;
 pub mod machine_id;
 pub mod mcp_model;
 pub mod model_registry;
@@ -18,7 +19,9 @@ pub mod provider_model;
 pub mod provider_pool_model;
 pub mod provider_type;
 pub mod route_model;
+pub mod seed;
 pub mod skill_model;
+pub mod usage;
 pub mod vertex_model;
 
 #[allow(unused_imports)]
@@ -28,6 +31,7 @@ pub use client_type::{select_provider, ClientType};
 #[allow(unused_imports)]
 pub use codewhisperer::*;
 pub use injection_types::{InjectionMode, InjectionRule};
+pub use logprobs::LogprobsOptions;
 pub use mcp_model::McpServer;
 #[allow(unused_imports)]
 pub use openai::*;
@@ -37,6 +41,7 @@ pub use provider_model::Provider;
 #[allow(unused_imports)]
 pub use provider_pool_model::*;
 pub use provider_type::ProviderType;
+pub use seed::SeedOptions;
 pub use skill_model::{
     parse_skill_manifest_from_content, resolve_skill_source_kind, split_skill_frontmatter,
     summarize_skill_resources_dir, ParsedSkillManifest, Skill, SkillCatalogSource, SkillMetadata,
@@ -47,4 +52,5 @@ pub use skill_model::{
     SOCIAL_POST_WITH_COVER_SKILL_DIRECTORY, TYPESETTING_SKILL_DIRECTORY, URL_PARSE_SKILL_DIRECTORY,
     VIDEO_GENERATE_SKILL_DIRECTORY,
 };
+pub use usage::NormalizedUsage;
 pub use vertex_model::{VertexApiKeyEntry, VertexModelAlias};
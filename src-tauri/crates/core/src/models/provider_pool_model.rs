@@ -151,6 +151,55 @@ impl CredentialData {
             CredentialData::AnthropicKey { .. } => PoolProviderType::Anthropic,
         }
     }
+
+    /// 以相同配置替换账号信息（OAuth 凭证文件或 API Key），其余设置保持不变
+    ///
+    /// `project_id` 仅对 Gemini / Antigravity OAuth 生效，为 None 时沿用原值。
+    pub fn with_account(
+        &self,
+        creds_file_path: Option<String>,
+        api_key: Option<String>,
+        project_id: Option<String>,
+    ) -> Result<Self, String> {
+        let mut cloned = self.clone();
+        let (path_slot, key_slot, project_slot) = match &mut cloned {
+            CredentialData::KiroOAuth { creds_file_path }
+            | CredentialData::CodexOAuth {
+                creds_file_path, ..
+            }
+            | CredentialData::ClaudeOAuth { creds_file_path } => {
+                (Some(creds_file_path), None, None)
+            }
+            CredentialData::GeminiOAuth {
+                creds_file_path,
+                project_id,
+            }
+            | CredentialData::AntigravityOAuth {
+                creds_file_path,
+                project_id,
+            } => (Some(creds_file_path), None, Some(project_id)),
+            CredentialData::OpenAIKey { api_key, .. }
+            | CredentialData::ClaudeKey { api_key, .. }
+            | CredentialData::VertexKey { api_key, .. }
+            | CredentialData::GeminiApiKey { api_key, .. }
+            | CredentialData::AnthropicKey { api_key, .. } => (None, Some(api_key), None),
+        };
+
+        if let Some(slot) = path_slot {
+            *slot = creds_file_path
+                .filter(|path| !path.trim().is_empty())
+                .ok_or_else(|| "复制 OAuth 凭证需要提供新的凭证文件".to_string())?;
+        }
+        if let Some(slot) = key_slot {
+            *slot = api_key
+                .filter(|key| !key.trim().is_empty())
+                .ok_or_else(|| "复制 API Key 凭证需要提供新的 API Key".to_string())?;
+        }
+        if let (Some(slot), Some(project_id)) = (project_slot, project_id) {
+            *slot = (!project_id.is_empty()).then_some(project_id);
+        }
+        Ok(cloned)
+    }
 }
 
 /// 通配符模式匹配
@@ -232,6 +281,9 @@ pub struct ProviderCredential {
     pub source: CredentialSource,
     /// 代理 URL（可覆盖全局代理设置）
     pub proxy_url: Option<String>,
+    /// 移入回收站的时间（软删除），回收站中的凭证不参与选择
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 fn default_true() -> bool {
@@ -265,6 +317,7 @@ impl ProviderCredential {
             cached_token: None,
             source: CredentialSource::Manual,
             proxy_url: None,
+            deleted_at: None,
         }
     }
 
@@ -279,6 +332,21 @@ impl ProviderCredential {
         cred
     }
 
+    /// 以本凭证为模板创建新凭证
+    ///
+    /// 沿用健康检查、模型列表、代理与启用状态等设置；使用统计、健康状态与 Token 缓存重新开始。
+    pub fn clone_with(&self, credential: CredentialData) -> Self {
+        let mut cloned = Self::new(self.provider_type, credential);
+        cloned.name = self.name.clone();
+        cloned.is_disabled = self.is_disabled;
+        cloned.check_health = self.check_health;
+        cloned.check_model_name = self.check_model_name.clone();
+        cloned.not_supported_models = self.not_supported_models.clone();
+        cloned.supported_models = self.supported_models.clone();
+        cloned.proxy_url = self.proxy_url.clone();
+        cloned
+    }
+
     /// 是否可用（健康且未禁用）
     pub fn is_available(&self) -> bool {
         self.is_healthy && !self.is_disabled
@@ -546,6 +614,9 @@ pub struct CredentialDisplay {
     pub api_key: Option<String>,
     /// 凭证级代理 URL（可覆盖全局代理设置）
     pub proxy_url: Option<String>,
+    /// 移入回收站的时间（仅回收站列表有值）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<String>,
 }
 
 /// 获取凭证类型字符串
@@ -642,6 +713,7 @@ impl From<&ProviderCredential> for CredentialDisplay {
             base_url: get_base_url(&cred.credential),
             api_key: get_api_key(&cred.credential),
             proxy_url: cred.proxy_url.clone(),
+            deleted_at: cred.deleted_at.map(|t| t.to_rfc3339()),
         }
     }
 }
@@ -703,12 +775,87 @@ pub struct UpdateCredentialRequest {
     pub new_proxy_url: Option<String>,
 }
 
+/// 复制凭证请求
+///
+/// 新凭证沿用原凭证的配置，只替换账号信息。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CloneCredentialRequest {
+    /// 新凭证名称，为空时使用「原名称 (副本)」
+    pub name: Option<String>,
+    /// 新的凭证文件路径（OAuth 凭证必填）
+    pub creds_file_path: Option<String>,
+    /// 新的 API Key（API Key 凭证必填）
+    pub api_key: Option<String>,
+    /// 新的 project_id（仅 Gemini / Antigravity OAuth，为空时沿用原值）
+    pub project_id: Option<String>,
+}
+
 pub type ProviderPools = HashMap<PoolProviderType, Vec<ProviderCredential>>;
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_clone_with_replaces_account_and_keeps_settings() {
+        let mut source = ProviderCredential::new(
+            PoolProviderType::GeminiApiKey,
+            CredentialData::GeminiApiKey {
+                api_key: "gm-old".to_string(),
+                base_url: Some("https://proxy.example.com".to_string()),
+                excluded_models: vec!["*-preview".to_string()],
+            },
+        );
+        source.name = Some("team".to_string());
+        source.check_model_name = Some("gemini-2.5-flash".to_string());
+        source.proxy_url = Some("socks5://127.0.0.1:1080".to_string());
+        source.usage_count = 42;
+
+        let credential = source
+            .credential
+            .with_account(None, Some("gm-new".to_string()), None)
+            .unwrap();
+        let cloned = source.clone_with(credential);
+        assert_ne!(cloned.uuid, source.uuid);
+        assert_eq!(cloned.usage_count, 0);
+        assert_eq!(cloned.proxy_url, source.proxy_url);
+        assert_eq!(cloned.check_model_name, source.check_model_name);
+        match cloned.credential {
+            CredentialData::GeminiApiKey {
+                api_key,
+                base_url,
+                excluded_models,
+            } => {
+                assert_eq!(api_key, "gm-new");
+                assert_eq!(base_url.as_deref(), Some("https://proxy.example.com"));
+                assert_eq!(excluded_models, vec!["*-preview".to_string()]);
+            }
+            other => panic!("unexpected credential: {other:?}"),
+        }
+
+        // OAuth 凭证必须提供新的凭证文件
+        let oauth = CredentialData::GeminiOAuth {
+            creds_file_path: "/tmp/a.json".to_string(),
+            project_id: Some("p1".to_string()),
+        };
+        assert!(oauth
+            .with_account(None, Some("key".to_string()), None)
+            .is_err());
+        match oauth
+            .with_account(Some("/tmp/b.json".to_string()), None, None)
+            .unwrap()
+        {
+            CredentialData::GeminiOAuth {
+                creds_file_path,
+                project_id,
+            } => {
+                assert_eq!(creds_file_path, "/tmp/b.json");
+                assert_eq!(project_id.as_deref(), Some("p1"));
+            }
+            other => panic!("unexpected credential: {other:?}"),
+        }
+    }
+
     #[test]
     fn test_pattern_matches_exact() {
         assert!(pattern_matches("gemini-2.5-pro", "gemini-2.5-pro"));
@@ -770,6 +917,7 @@ mod tests {
             cached_token: None,
             source: CredentialSource::Manual,
             proxy_url: None,
+            deleted_at: None,
         };
 
         assert!(!cred.supports_model("claude-opus"));
@@ -805,6 +953,7 @@ mod tests {
             cached_token: None,
             source: CredentialSource::Manual,
             proxy_url: None,
+            deleted_at: None,
         };
 
         // Exact match exclusion
@@ -842,6 +991,7 @@ mod tests {
             cached_token: None,
             source: CredentialSource::Manual,
             proxy_url: None,
+            deleted_at: None,
         };
 
         // Prefix wildcard exclusion
@@ -883,6 +1033,7 @@ mod tests {
             cached_token: None,
             source: CredentialSource::Manual,
             proxy_url: None,
+            deleted_at: None,
         };
 
         // Contains wildcard exclusion
@@ -921,6 +1072,7 @@ mod tests {
             cached_token: None,
             source: CredentialSource::Manual,
             proxy_url: None,
+            deleted_at: None,
         };
 
         // Excluded by not_supported_models (exact match)
@@ -960,6 +1112,7 @@ mod tests {
             cached_token: None,
             source: CredentialSource::Manual,
             proxy_url: None,
+            deleted_at: None,
         };
 
         // All models should be supported since not_supported_models is empty
@@ -0,0 +1,67 @@
+//! OpenAI `seed` 请求参数与 `system_fingerprint`
+//!
+//! 类型化的 `ChatCompletionRequest` 不包含 `seed`，这里从原始请求体中单独解析。
+//! 评测流水线依赖 `seed` + `system_fingerprint` 判断结果是否可复现：
+//! 上游未返回 `system_fingerprint` 时，按 Provider 与模型生成稳定的指纹。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+/// 请求的 seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeedOptions {
+    pub seed: i64,
+}
+
+impl SeedOptions {
+    /// 从请求体解析，未设置 seed 时返回 `Ok(None)`
+    pub fn from_body(body: &Value) -> Result<Option<Self>, String> {
+        match &body["seed"] {
+            Value::Null => Ok(None),
+            value => value
+                .as_i64()
+                .map(|seed| Some(Self { seed }))
+                .ok_or_else(|| "`seed` must be an integer".to_string()),
+        }
+    }
+
+    /// OpenAI 请求体中的参数
+    pub fn to_params(&self) -> Map<String, Value> {
+        let mut params = Map::new();
+        params.insert("seed".to_string(), Value::from(self.seed));
+        params
+    }
+}
+
+/// 按 Provider 与模型生成的 `system_fingerprint`（同一后端配置保持不变）
+pub fn system_fingerprint(provider: &str, model: &str) -> String {
+    let digest = Sha256::digest(format!("{provider}\n{model}").as_bytes());
+    let hex: String = digest[..5].iter().map(|b| format!("{b:02x}")).collect();
+    format!("fp_lime_{hex}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_body_and_fingerprint() {
+        assert_eq!(
+            SeedOptions::from_body(&json!({"model": "gpt-4o"})),
+            Ok(None)
+        );
+        assert_eq!(
+            SeedOptions::from_body(&json!({"seed": -7})),
+            Ok(Some(SeedOptions { seed: -7 }))
+        );
+        assert!(SeedOptions::from_body(&json!({"seed": "42"})).is_err());
+        assert_eq!(SeedOptions { seed: 42 }.to_params()["seed"], 42);
+
+        let fingerprint = system_fingerprint("openai", "gpt-4o");
+        assert_eq!(fingerprint, system_fingerprint("openai", "gpt-4o"));
+        assert_ne!(fingerprint, system_fingerprint("deepseek", "gpt-4o"));
+        assert_eq!(fingerprint.len(), "fp_lime_".len() + 10);
+    }
+}
@@ -0,0 +1,220 @@
+//! Token 用量规范化
+//!
+//! 各 Provider 报告用量的字段各不相同：
+//! - OpenAI Chat：`prompt_tokens` / `completion_tokens`，明细在 `*_tokens_details`
+//! - OpenAI Responses：`input_tokens` / `output_tokens`，明细在 `*_tokens_details`
+//! - Anthropic：`input_tokens` 不含缓存，缓存另计 `cache_read_input_tokens` / `cache_creation_input_tokens`
+//! - Gemini：`usageMetadata` 中的 `promptTokenCount` / `candidatesTokenCount` / `thoughtsTokenCount`
+//!
+//! 这里统一为同一结构，再按客户端协议输出。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// 规范化后的 Token 用量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NormalizedUsage {
+    /// 输入 Token（含缓存命中与缓存写入）
+    pub prompt_tokens: u32,
+    /// 输出 Token（含推理 Token）
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+    /// 命中缓存的输入 Token
+    pub cached_tokens: u32,
+    /// 写入缓存的输入 Token
+    pub cache_creation_tokens: u32,
+    /// 推理 Token
+    pub reasoning_tokens: u32,
+    /// 是否为估算值（Provider 未返回用量）
+    pub estimated: bool,
+}
+
+fn count(value: &Value, key: &str) -> Option<u32> {
+    value
+        .get(key)
+        .and_then(Value::as_u64)
+        .map(|n| n.min(u64::from(u32::MAX)) as u32)
+}
+
+fn detail(value: &Value, object: &str, key: &str) -> u32 {
+    value.get(object).and_then(|v| count(v, key)).unwrap_or(0)
+}
+
+impl NormalizedUsage {
+    /// Provider 未返回用量时的估算值
+    pub fn estimated(prompt_tokens: u32, completion_tokens: u32) -> Self {
+        Self {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens.saturating_add(completion_tokens),
+            estimated: true,
+            ..Default::default()
+        }
+    }
+
+    /// 解析 `usage` / `usageMetadata` 对象，无法识别时返回 `None`
+    pub fn from_usage(usage: &Value) -> Option<Self> {
+        if !usage.is_object() {
+            return None;
+        }
+
+        // Gemini
+        if usage.get("promptTokenCount").is_some() || usage.get("candidatesTokenCount").is_some() {
+            let prompt_tokens = count(usage, "promptTokenCount").unwrap_or(0);
+            let reasoning_tokens = count(usage, "thoughtsTokenCount").unwrap_or(0);
+            let completion_tokens = count(usage, "candidatesTokenCount")
+                .unwrap_or(0)
+                .saturating_add(reasoning_tokens);
+            return Some(Self {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: count(usage, "totalTokenCount")
+                    .unwrap_or_else(|| prompt_tokens.saturating_add(completion_tokens)),
+                cached_tokens: count(usage, "cachedContentTokenCount").unwrap_or(0),
+                cache_creation_tokens: 0,
+                reasoning_tokens,
+                estimated: false,
+            });
+        }
+
+        // OpenAI Chat
+        if usage.get("prompt_tokens").is_some() || usage.get("completion_tokens").is_some() {
+            let prompt_tokens = count(usage, "prompt_tokens").unwrap_or(0);
+            let completion_tokens = count(usage, "completion_tokens").unwrap_or(0);
+            return Some(Self {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: count(usage, "total_tokens")
+                    .unwrap_or_else(|| prompt_tokens.saturating_add(completion_tokens)),
+                cached_tokens: detail(usage, "prompt_tokens_details", "cached_tokens"),
+                cache_creation_tokens: 0,
+                reasoning_tokens: detail(usage, "completion_tokens_details", "reasoning_tokens"),
+                estimated: false,
+            });
+        }
+
+        // Anthropic / OpenAI Responses
+        if usage.get("input_tokens").is_some() || usage.get("output_tokens").is_some() {
+            let cache_read = count(usage, "cache_read_input_tokens").unwrap_or(0);
+            let cache_creation = count(usage, "cache_creation_input_tokens").unwrap_or(0);
+            // Anthropic 的 input_tokens 不含缓存部分，Responses 不返回这两个字段
+            let prompt_tokens = count(usage, "input_tokens")
+                .unwrap_or(0)
+                .saturating_add(cache_read)
+                .saturating_add(cache_creation);
+            let completion_tokens = count(usage, "output_tokens").unwrap_or(0);
+            let cached_tokens = match detail(usage, "input_tokens_details", "cached_tokens") {
+                0 => cache_read,
+                cached => cached,
+            };
+            return Some(Self {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: count(usage, "total_tokens")
+                    .unwrap_or_else(|| prompt_tokens.saturating_add(completion_tokens)),
+                cached_tokens,
+                cache_creation_tokens: cache_creation,
+                reasoning_tokens: detail(usage, "output_tokens_details", "reasoning_tokens"),
+                estimated: false,
+            });
+        }
+
+        None
+    }
+
+    /// 在完整响应中查找用量字段
+    pub fn from_response(body: &Value) -> Option<Self> {
+        [
+            &body["usage"],
+            &body["usageMetadata"],
+            &body["response"]["usage"],
+            &body["response"]["usageMetadata"],
+        ]
+        .into_iter()
+        .find_map(Self::from_usage)
+    }
+
+    /// OpenAI 格式的 `usage`
+    pub fn to_openai(&self) -> Value {
+        json!({
+            "prompt_tokens": self.prompt_tokens,
+            "completion_tokens": self.completion_tokens,
+            "total_tokens": self.total_tokens,
+            "prompt_tokens_details": {
+                "cached_tokens": self.cached_tokens
+            },
+            "completion_tokens_details": {
+                "reasoning_tokens": self.reasoning_tokens
+            }
+        })
+    }
+
+    /// Anthropic 格式的 `usage`
+    pub fn to_anthropic(&self) -> Value {
+        let input_tokens = self
+            .prompt_tokens
+            .saturating_sub(self.cached_tokens)
+            .saturating_sub(self.cache_creation_tokens);
+        json!({
+            "input_tokens": input_tokens,
+            "output_tokens": self.completion_tokens,
+            "cache_read_input_tokens": self.cached_tokens,
+            "cache_creation_input_tokens": self.cache_creation_tokens
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_usage_shapes() {
+        let openai = NormalizedUsage::from_usage(&json!({
+            "prompt_tokens": 100,
+            "completion_tokens": 40,
+            "total_tokens": 140,
+            "prompt_tokens_details": {"cached_tokens": 60},
+            "completion_tokens_details": {"reasoning_tokens": 10}
+        }))
+        .unwrap();
+        assert_eq!(
+            (
+                openai.prompt_tokens,
+                openai.cached_tokens,
+                openai.reasoning_tokens
+            ),
+            (100, 60, 10)
+        );
+
+        let anthropic = NormalizedUsage::from_usage(&json!({
+            "input_tokens": 40,
+            "output_tokens": 20,
+            "cache_read_input_tokens": 50,
+            "cache_creation_input_tokens": 10
+        }))
+        .unwrap();
+        assert_eq!(anthropic.prompt_tokens, 100);
+        assert_eq!(anthropic.total_tokens, 120);
+        assert_eq!(anthropic.cached_tokens, 50);
+        assert_eq!(anthropic.to_anthropic()["input_tokens"], 40);
+
+        let gemini = NormalizedUsage::from_response(&json!({
+            "response": {"usageMetadata": {
+                "promptTokenCount": 30,
+                "candidatesTokenCount": 12,
+                "thoughtsTokenCount": 8,
+                "cachedContentTokenCount": 5
+            }}
+        }))
+        .unwrap();
+        assert_eq!(gemini.completion_tokens, 20);
+        assert_eq!(gemini.total_tokens, 50);
+        assert_eq!(
+            gemini.to_openai()["completion_tokens_details"]["reasoning_tokens"],
+            8
+        );
+
+        assert!(NormalizedUsage::from_response(&json!({"choices": []})).is_none());
+    }
+}
@@ -0,0 +1,434 @@
+//! 数据保留与定时清理
+//!
+//! 按 `server.retention` 为各持久化存储执行统一的保留策略：
+//! - `audit`：配置变更审计日志（`config_audit_log`）
+//! - `usage`：按日汇总的模型用量（`model_usage_stats`）
+//! - `media`：对话图片等媒体文件（`media/`）
+//! - `debug_captures`：调试模式保存的请求 / 响应抓包（`logs/` 下的抓包文件）
+//! - `transcripts`：会话文件（`sessions/` 下每个会话一个目录）
+//! - `deleted_credentials`：凭证回收站中的凭证（永久删除）
+//!
+//! 先删除超过保留天数的条目，再按条目数与空间限制从最旧的开始删除。
+//! 单个存储清理失败只记录在报告中，不影响其他存储。
+
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime};
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{RetentionPolicy, RetentionSettings};
+use crate::database::pool_storage::{lock_pool, pool_storage};
+use crate::database::{lock_db, DbConnection};
+
+/// 调试抓包文件名前缀（位于日志目录，与应用日志共存）
+const DEBUG_CAPTURE_PREFIXES: &[&str] = &["cw_request_", "antigravity_"];
+
+/// 单个存储的清理结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoreRetentionReport {
+    pub store: String,
+    /// 删除的条目数（数据库行、文件或会话目录）
+    pub removed: u64,
+    /// 释放的空间（仅文件存储）
+    pub freed_bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl StoreRetentionReport {
+    fn new(store: &str) -> Self {
+        Self {
+            store: store.to_string(),
+            removed: 0,
+            freed_bytes: 0,
+            error: None,
+        }
+    }
+}
+
+/// 一次清理的结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub stores: Vec<StoreRetentionReport>,
+    pub duration_ms: u64,
+    pub ran_at: DateTime<Utc>,
+}
+
+/// 文件存储所在目录（目录不存在的存储跳过）
+#[derive(Debug, Clone, Default)]
+pub struct RetentionDirs {
+    pub media: Option<PathBuf>,
+    pub debug_captures: Option<PathBuf>,
+    pub transcripts: Option<PathBuf>,
+}
+
+impl RetentionDirs {
+    /// 默认的应用数据目录
+    pub fn resolve() -> Self {
+        Self {
+            media: crate::app_paths::resolve_media_dir().ok(),
+            debug_captures: crate::app_paths::resolve_logs_dir().ok(),
+            transcripts: crate::app_paths::resolve_sessions_dir().ok(),
+        }
+    }
+}
+
+fn age_cutoff(policy: &RetentionPolicy, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    policy
+        .max_age_days
+        .map(|days| now - Duration::days(i64::from(days)))
+}
+
+/// 清理数据表：删除 `column` 早于截止值的行，再只保留最新的 `max_entries` 行
+///
+/// `cutoff_format` 将截止时间格式化为与列相同的文本格式，保证按字符串比较有序。
+fn prune_table(
+    conn: &Connection,
+    store: &str,
+    table: &str,
+    column: &str,
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+    cutoff_format: fn(DateTime<Utc>) -> String,
+) -> StoreRetentionReport {
+    let mut report = StoreRetentionReport::new(store);
+    let mut prune = || -> Result<(), rusqlite::Error> {
+        if let Some(cutoff) = age_cutoff(policy, now) {
+            report.removed += conn.execute(
+                &format!("DELETE FROM {table} WHERE {column} < ?1"),
+                [cutoff_format(cutoff)],
+            )? as u64;
+        }
+        if let Some(max_entries) = policy.max_entries {
+            report.removed += conn.execute(
+                &format!(
+                    "DELETE FROM {table} WHERE rowid NOT IN \
+                     (SELECT rowid FROM {table} ORDER BY {column} DESC LIMIT ?1)"
+                ),
+                [max_entries as i64],
+            )? as u64;
+        }
+        Ok(())
+    };
+    if let Err(e) = prune() {
+        report.error = Some(e.to_string());
+    }
+    report
+}
+
+/// 清理凭证回收站：永久删除超过保留天数的凭证，再只保留最近删除的 `max_entries` 个
+fn prune_deleted_credentials(
+    db: &DbConnection,
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+) -> StoreRetentionReport {
+    let mut report = StoreRetentionReport::new("deleted_credentials");
+    let storage = pool_storage();
+    let mut prune = || -> Result<(), String> {
+        // 共享存储时不占用本地数据库锁
+        let conn = lock_pool(db)?;
+        if let Some(cutoff) = age_cutoff(policy, now) {
+            report.removed += storage.purge_deleted_before(&conn, cutoff)?;
+        }
+        if let Some(max_entries) = policy.max_entries {
+            // get_deleted 按删除时间从新到旧排序
+            for cred in storage
+                .get_deleted(&conn)?
+                .iter()
+                .skip(max_entries as usize)
+            {
+                if storage.delete(&conn, &cred.uuid)? {
+                    report.removed += 1;
+                }
+            }
+        }
+        Ok(())
+    };
+    if let Err(e) = prune() {
+        report.error = Some(e);
+    }
+    report
+}
+
+struct FileEntry {
+    path: PathBuf,
+    modified: SystemTime,
+    size: u64,
+    is_dir: bool,
+}
+
+fn dir_size(path: &Path) -> u64 {
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| match entry.metadata() {
+                    Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+                    Ok(meta) => meta.len(),
+                    Err(_) => 0,
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// 清理目录：`dirs` 为 true 时以子目录为条目，否则以文件为条目
+fn prune_dir(
+    store: &str,
+    dir: &Path,
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+    dirs: bool,
+    matches: impl Fn(&str) -> bool,
+) -> StoreRetentionReport {
+    let mut report = StoreRetentionReport::new(store);
+    if *policy == RetentionPolicy::default() {
+        return report;
+    }
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return report;
+    };
+    let mut entries: Vec<FileEntry> = read_dir
+        .flatten()
+        .filter(|entry| matches(&entry.file_name().to_string_lossy()))
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            if meta.is_dir() != dirs {
+                return None;
+            }
+            let path = entry.path();
+            Some(FileEntry {
+                size: if dirs { dir_size(&path) } else { meta.len() },
+                modified: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                path,
+                is_dir: dirs,
+            })
+        })
+        .collect();
+    // 从新到旧排序，超出限制的都在末尾
+    entries.sort_by(|a, b| b.modified.cmp(&a.modified));
+
+    let cutoff: Option<SystemTime> = age_cutoff(policy, now).map(SystemTime::from);
+    let max_bytes = policy.max_size_mb.map(|mb| mb.saturating_mul(1024 * 1024));
+    let mut kept_count = 0u64;
+    let mut kept_bytes = 0u64;
+    for entry in entries {
+        let expired = cutoff.is_some_and(|cutoff| entry.modified < cutoff);
+        let over_count = policy.max_entries.is_some_and(|max| kept_count >= max);
+        let over_size = max_bytes.is_some_and(|max| kept_bytes.saturating_add(entry.size) > max);
+        if !(expired || over_count || over_size) {
+            kept_count += 1;
+            kept_bytes += entry.size;
+            continue;
+        }
+        let removed = if entry.is_dir {
+            std::fs::remove_dir_all(&entry.path)
+        } else {
+            std::fs::remove_file(&entry.path)
+        };
+        match removed {
+            Ok(()) => {
+                report.removed += 1;
+                report.freed_bytes += entry.size;
+            }
+            Err(e) => {
+                report.error = Some(format!("删除 {} 失败: {e}", entry.path.display()));
+            }
+        }
+    }
+    report
+}
+
+fn is_debug_capture(name: &str) -> bool {
+    DEBUG_CAPTURE_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+/// 执行一次清理
+///
+/// `db` 为 None 时跳过数据库存储。
+pub fn run_retention(
+    db: Option<&DbConnection>,
+    settings: &RetentionSettings,
+    dirs: &RetentionDirs,
+) -> RetentionReport {
+    let started = Instant::now();
+    let now = Utc::now();
+    let mut stores = Vec::new();
+
+    if let Some(db) = db {
+        match lock_db(db) {
+            Ok(conn) => {
+                stores.push(prune_table(
+                    &conn,
+                    "audit",
+                    "config_audit_log",
+                    "created_at",
+                    &settings.audit,
+                    now,
+                    |cutoff| cutoff.to_rfc3339(),
+                ));
+                stores.push(prune_table(
+                    &conn,
+                    "usage",
+                    "model_usage_stats",
+                    "date",
+                    &settings.usage,
+                    now,
+                    |cutoff| cutoff.format("%Y-%m-%d").to_string(),
+                ));
+            }
+            Err(e) => tracing::warn!("[数据保留] {}", e),
+        }
+        stores.push(prune_deleted_credentials(
+            db,
+            &settings.deleted_credentials,
+            now,
+        ));
+    }
+    if let Some(dir) = &dirs.media {
+        stores.push(prune_dir("media", dir, &settings.media, now, false, |_| {
+            true
+        }));
+    }
+    if let Some(dir) = &dirs.debug_captures {
+        stores.push(prune_dir(
+            "debug_captures",
+            dir,
+            &settings.debug_captures,
+            now,
+            false,
+            is_debug_capture,
+        ));
+    }
+    if let Some(dir) = &dirs.transcripts {
+        stores.push(prune_dir(
+            "transcripts",
+            dir,
+            &settings.transcripts,
+            now,
+            true,
+            |_| true,
+        ));
+    }
+
+    let report = RetentionReport {
+        stores,
+        duration_ms: started.elapsed().as_millis() as u64,
+        ran_at: now,
+    };
+    for store in &report.stores {
+        match &store.error {
+            Some(error) => tracing::warn!("[数据保留] {} 清理失败: {}", store.store, error),
+            None if store.removed > 0 => tracing::info!(
+                "[数据保留] {} 已删除 {} 条，释放 {} 字节",
+                store.store,
+                store.removed,
+                store.freed_bytes
+            ),
+            None => {}
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_modified(path: &Path, days_ago: u64) {
+        let time = SystemTime::now() - std::time::Duration::from_secs(days_ago * 86_400);
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    #[test]
+    fn should_prune_tables_by_age_and_count() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE config_audit_log (id INTEGER PRIMARY KEY, created_at TEXT NOT NULL);
+             CREATE TABLE model_usage_stats (model_id TEXT, date TEXT NOT NULL);",
+        )
+        .unwrap();
+        let now = Utc::now();
+        for days in [1, 2, 3, 500] {
+            conn.execute(
+                "INSERT INTO config_audit_log (created_at) VALUES (?1)",
+                [(now - Duration::days(days)).to_rfc3339()],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO model_usage_stats (model_id, date) VALUES ('m', ?1)",
+                [(now - Duration::days(days)).format("%Y-%m-%d").to_string()],
+            )
+            .unwrap();
+        }
+
+        let settings = RetentionSettings {
+            audit: RetentionPolicy {
+                max_age_days: Some(180),
+                max_entries: Some(2),
+                max_size_mb: None,
+            },
+            ..Default::default()
+        };
+        let db: DbConnection = std::sync::Arc::new(std::sync::Mutex::new(conn));
+        let report = run_retention(Some(&db), &settings, &RetentionDirs::default());
+        assert_eq!(report.stores[0].removed, 2);
+        assert_eq!(report.stores[1].removed, 1);
+
+        let oldest: String = lock_db(&db)
+            .unwrap()
+            .query_row("SELECT MIN(created_at) FROM config_audit_log", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert!(oldest.starts_with(&(now - Duration::days(2)).format("%Y-%m-%d").to_string()));
+    }
+
+    #[test]
+    fn should_prune_files_by_age_and_size() {
+        let root = tempfile::tempdir().unwrap();
+        let media = root.path().join("media");
+        let logs = root.path().join("logs");
+        std::fs::create_dir_all(&media).unwrap();
+        std::fs::create_dir_all(&logs).unwrap();
+        for (name, days) in [("a.png", 0), ("b.png", 1), ("c.png", 40)] {
+            std::fs::write(media.join(name), vec![0u8; 600 * 1024]).unwrap();
+            set_modified(&media.join(name), days);
+        }
+        for name in ["cw_request_1.json", "app.log"] {
+            std::fs::write(logs.join(name), "{}").unwrap();
+            set_modified(&logs.join(name), 30);
+        }
+
+        let settings = RetentionSettings {
+            media: RetentionPolicy {
+                max_age_days: Some(30),
+                max_entries: None,
+                max_size_mb: Some(1),
+            },
+            ..Default::default()
+        };
+        let dirs = RetentionDirs {
+            media: Some(media.clone()),
+            debug_captures: Some(logs.clone()),
+            transcripts: None,
+        };
+        let report = run_retention(None, &settings, &dirs);
+
+        // c.png 过期，b.png 超出空间限制
+        assert_eq!(report.stores[0].removed, 2);
+        assert!(media.join("a.png").exists());
+        // 只清理抓包文件，应用日志保留
+        assert_eq!(report.stores[1].removed, 1);
+        assert!(logs.join("app.log").exists());
+    }
+}
@@ -0,0 +1,147 @@
+//! 弃用模型映射
+//!
+//! 维护已下线或改名的上游模型与替代模型的对应关系，配置中的条目覆盖内置条目。
+
+use std::collections::HashMap;
+
+use crate::config::ModelDeprecationConfig;
+
+/// 内置的弃用模型（弃用模型 -> 替代模型）
+pub const BUILTIN_DEPRECATIONS: &[(&str, &str)] = &[
+    ("claude-instant-1.2", "claude-3-5-haiku-20241022"),
+    ("claude-2.0", "claude-sonnet-4-20250514"),
+    ("claude-2.1", "claude-sonnet-4-20250514"),
+    ("claude-3-sonnet-20240229", "claude-sonnet-4-20250514"),
+    ("claude-3-opus-20240229", "claude-opus-4-5-20251101"),
+    ("claude-3-5-sonnet-20240620", "claude-sonnet-4-5-20250929"),
+    ("claude-3-5-sonnet-20241022", "claude-sonnet-4-5-20250929"),
+    ("gpt-4-32k", "gpt-4o"),
+    ("gpt-4-vision-preview", "gpt-4o"),
+    ("gpt-4-1106-vision-preview", "gpt-4o"),
+    ("gpt-3.5-turbo-0301", "gpt-4o-mini"),
+    ("gpt-3.5-turbo-0613", "gpt-4o-mini"),
+    ("text-davinci-003", "gpt-4o-mini"),
+    ("gemini-pro", "gemini-2.5-flash"),
+    ("gemini-pro-vision", "gemini-2.5-flash"),
+    ("gemini-1.0-pro", "gemini-2.5-flash"),
+];
+
+/// 命中的弃用模型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelDeprecation {
+    /// 客户端请求的模型
+    pub model: String,
+    /// 替代模型
+    pub replacement: String,
+    /// 是否已改写为替代模型
+    pub rewritten: bool,
+}
+
+/// 弃用模型表
+#[derive(Debug, Clone)]
+pub struct ModelDeprecations {
+    enabled: bool,
+    rewrite: bool,
+    table: HashMap<String, String>,
+}
+
+impl Default for ModelDeprecations {
+    fn default() -> Self {
+        Self::from_config(&ModelDeprecationConfig::default())
+    }
+}
+
+impl ModelDeprecations {
+    /// 按配置构建：内置条目 + 配置条目（替代模型留空的条目移除）
+    pub fn from_config(config: &ModelDeprecationConfig) -> Self {
+        let mut table: HashMap<String, String> = BUILTIN_DEPRECATIONS
+            .iter()
+            .map(|(model, replacement)| (model.to_string(), replacement.to_string()))
+            .collect();
+        for (model, replacement) in &config.replacements {
+            let model = model.trim();
+            let replacement = replacement.trim();
+            if replacement.is_empty() {
+                table.remove(model);
+            } else if model != replacement {
+                table.insert(model.to_string(), replacement.to_string());
+            }
+        }
+        Self {
+            enabled: config.enabled,
+            rewrite: config.rewrite,
+            table,
+        }
+    }
+
+    /// 检查模型是否已弃用
+    pub fn check(&self, model: &str) -> Option<ModelDeprecation> {
+        if !self.enabled {
+            return None;
+        }
+        self.table.get(model).map(|replacement| ModelDeprecation {
+            model: model.to_string(),
+            replacement: replacement.clone(),
+            rewritten: self.rewrite,
+        })
+    }
+
+    /// 所有弃用条目
+    pub fn entries(&self) -> &HashMap<String, String> {
+        &self.table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_and_overrides() {
+        let mut config = ModelDeprecationConfig::default();
+        config
+            .replacements
+            .insert("gpt-4-32k".to_string(), "gpt-4.1".to_string());
+        config
+            .replacements
+            .insert("gemini-pro".to_string(), " ".to_string());
+        config
+            .replacements
+            .insert("my-old-model".to_string(), "my-new-model".to_string());
+        let deprecations = ModelDeprecations::from_config(&config);
+
+        assert_eq!(
+            deprecations.check("claude-2.1"),
+            Some(ModelDeprecation {
+                model: "claude-2.1".to_string(),
+                replacement: "claude-sonnet-4-20250514".to_string(),
+                rewritten: true,
+            })
+        );
+        assert_eq!(
+            deprecations.check("gpt-4-32k").map(|d| d.replacement),
+            Some("gpt-4.1".to_string())
+        );
+        assert!(deprecations.check("gemini-pro").is_none());
+        assert!(deprecations.check("my-old-model").is_some());
+        assert!(deprecations.check("gpt-4o").is_none());
+    }
+
+    #[test]
+    fn test_disabled_and_warn_only() {
+        let deprecations = ModelDeprecations::from_config(&ModelDeprecationConfig {
+            enabled: false,
+            ..Default::default()
+        });
+        assert!(deprecations.check("claude-2.1").is_none());
+
+        let deprecations = ModelDeprecations::from_config(&ModelDeprecationConfig {
+            rewrite: false,
+            ..Default::default()
+        });
+        assert_eq!(
+            deprecations.check("claude-2.1").map(|d| d.rewritten),
+            Some(false)
+        );
+    }
+}
@@ -11,17 +11,32 @@
 //! 模型映射：
 //! - 支持模型别名映射（如 `gpt-4` -> `claude-sonnet-4-5-20250514`）
 //!
+//! 弃用模型：
+//! - 已下线或改名的上游模型改写为替代模型（如 `claude-2.1` -> `claude-sonnet-4-20250514`）
+//!
 //! 提示路由：
 //! - 支持消息前缀提示路由（如 `[reasoning] 请分析...`）
+//!
+//! 规模路由：
+//! - 按估算的提示词规模或是否携带附件改写目标模型
+//!
+//! 时间段路由：
+//! - 按本机时区的星期与时间段切换 Provider 或改写模型
 
 mod amp_router;
+mod deprecation;
 mod hint_router;
 mod mapper;
 mod provider_router;
 mod route_registry;
 mod rules;
+mod size_router;
+mod time_window_router;
 
 pub use amp_router::AmpRouter;
+pub use deprecation::{ModelDeprecation, ModelDeprecations, BUILTIN_DEPRECATIONS};
 pub use hint_router::{HintMatch, HintRoute, HintRouteEntry, HintRouter, HintRouterConfig};
 pub use mapper::ModelMapper;
 pub use rules::Router;
+pub use size_router::{SizeRouteMatch, SizeRouter};
+pub use time_window_router::{TimeWindowMatch, TimeWindowRouter};
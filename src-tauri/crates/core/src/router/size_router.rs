@@ -0,0 +1,150 @@
+//! 请求规模路由
+//!
+//! 按估算的提示词规模（small / medium / large）或是否携带附件改写目标模型，
+//! 让长上下文请求无论客户端请求哪个模型都能自动发往长上下文模型。
+
+use crate::config::{PromptSizeClass, SizeRoutingConfig, SizeRoutingRule};
+
+/// 规模路由命中结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeRouteMatch {
+    /// 请求的规模等级
+    pub size_class: PromptSizeClass,
+    /// 估算的提示词 Token 数
+    pub estimated_tokens: u32,
+    /// 是否携带附件
+    pub has_attachments: bool,
+    /// 目标模型
+    pub model: String,
+}
+
+/// 规模路由器
+#[derive(Debug, Clone)]
+pub struct SizeRouter {
+    enabled: bool,
+    medium_tokens: u32,
+    large_tokens: u32,
+    rules: Vec<SizeRoutingRule>,
+}
+
+impl Default for SizeRouter {
+    fn default() -> Self {
+        Self::from_config(&SizeRoutingConfig::default())
+    }
+}
+
+impl SizeRouter {
+    /// 按配置构建，忽略没有任何匹配条件或目标模型为空的规则
+    pub fn from_config(config: &SizeRoutingConfig) -> Self {
+        let rules = config
+            .rules
+            .iter()
+            .filter(|rule| {
+                (rule.size.is_some() || rule.attachments.is_some()) && !rule.model.trim().is_empty()
+            })
+            .cloned()
+            .collect();
+        Self {
+            enabled: config.enabled,
+            medium_tokens: config.medium_tokens,
+            large_tokens: config.large_tokens.max(config.medium_tokens),
+            rules,
+        }
+    }
+
+    /// 是否启用（启用且至少有一条有效规则）
+    pub fn is_enabled(&self) -> bool {
+        self.enabled && !self.rules.is_empty()
+    }
+
+    /// 按估算 Token 数划分规模等级
+    pub fn classify(&self, estimated_tokens: u32) -> PromptSizeClass {
+        if estimated_tokens >= self.large_tokens {
+            PromptSizeClass::Large
+        } else if estimated_tokens >= self.medium_tokens {
+            PromptSizeClass::Medium
+        } else {
+            PromptSizeClass::Small
+        }
+    }
+
+    /// 按顺序匹配第一条规则
+    pub fn route(&self, estimated_tokens: u32, has_attachments: bool) -> Option<SizeRouteMatch> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let size_class = self.classify(estimated_tokens);
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.size.map_or(true, |size| size == size_class)
+                    && rule
+                        .attachments
+                        .map_or(true, |attachments| attachments == has_attachments)
+            })
+            .map(|rule| SizeRouteMatch {
+                size_class,
+                estimated_tokens,
+                has_attachments,
+                model: rule.model.trim().to_string(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> SizeRoutingConfig {
+        SizeRoutingConfig {
+            enabled: true,
+            rules: vec![
+                SizeRoutingRule {
+                    size: None,
+                    attachments: None,
+                    model: "ignored".to_string(),
+                },
+                SizeRoutingRule {
+                    size: Some(PromptSizeClass::Large),
+                    attachments: None,
+                    model: "gemini-2.5-pro".to_string(),
+                },
+                SizeRoutingRule {
+                    size: Some(PromptSizeClass::Small),
+                    attachments: Some(true),
+                    model: "gpt-4o".to_string(),
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        let router = SizeRouter::from_config(&config());
+        assert_eq!(router.classify(100), PromptSizeClass::Small);
+        assert_eq!(router.classify(8_000), PromptSizeClass::Medium);
+        assert_eq!(router.classify(40_000), PromptSizeClass::Large);
+    }
+
+    #[test]
+    fn test_route_first_matching_rule() {
+        let router = SizeRouter::from_config(&config());
+        assert_eq!(
+            router.route(50_000, true).map(|m| m.model),
+            Some("gemini-2.5-pro".to_string())
+        );
+        assert_eq!(
+            router.route(500, true).map(|m| m.model),
+            Some("gpt-4o".to_string())
+        );
+        assert!(router.route(500, false).is_none());
+        assert!(router.route(10_000, true).is_none());
+
+        let disabled = SizeRouter::from_config(&SizeRoutingConfig {
+            enabled: false,
+            ..config()
+        });
+        assert!(disabled.route(50_000, false).is_none());
+    }
+}
@@ -0,0 +1,243 @@
+//! 时间段路由
+//!
+//! 按本机时区的星期与时间段切换 Provider 或改写模型，例如只在下班时间使用个人额度，
+//! 或夜间切换到低价 Provider。配置随热重载生效，每次请求时按当前时间匹配。
+
+use chrono::{Datelike, Local, NaiveDateTime, NaiveTime, Weekday};
+
+use crate::config::{TimeWindowRoutingConfig, TimeWindowRule};
+use crate::models::injection_types::pattern_matches;
+
+/// 时间段路由命中结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeWindowMatch {
+    /// 规则名称
+    pub rule: String,
+    /// 时间段内使用的 Provider
+    pub provider: Option<String>,
+    /// 时间段内改写的模型
+    pub model: Option<String>,
+}
+
+/// 解析后的规则
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    name: String,
+    /// 为空表示每天
+    days: Vec<Weekday>,
+    start: NaiveTime,
+    end: NaiveTime,
+    models: Vec<String>,
+    provider: Option<String>,
+    model: Option<String>,
+}
+
+impl CompiledRule {
+    fn compile(index: usize, rule: &TimeWindowRule) -> Result<Self, String> {
+        let name = match rule.name.trim() {
+            "" => format!("#{}", index + 1),
+            name => name.to_string(),
+        };
+        let parse_time = |value: &str| {
+            NaiveTime::parse_from_str(value.trim(), "%H:%M")
+                .map_err(|_| format!("规则 {name} 的时间 `{value}` 无效，应为 HH:MM"))
+        };
+        let start = parse_time(&rule.start)?;
+        let end = parse_time(&rule.end)?;
+        let days = rule
+            .days
+            .iter()
+            .map(|day| {
+                day.trim()
+                    .parse::<Weekday>()
+                    .map_err(|_| format!("规则 {name} 的星期 `{day}` 无效"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let provider = rule
+            .provider
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(str::to_lowercase);
+        let model = rule
+            .model
+            .as_deref()
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .map(str::to_string);
+        if provider.is_none() && model.is_none() {
+            return Err(format!("规则 {name} 未设置 provider 或 model"));
+        }
+        Ok(Self {
+            name,
+            days,
+            start,
+            end,
+            models: rule
+                .models
+                .iter()
+                .map(|m| m.trim().to_string())
+                .filter(|m| !m.is_empty())
+                .collect(),
+            provider,
+            model,
+        })
+    }
+
+    fn day_matches(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+
+    /// 时间是否落在时间段内（跨午夜时段的后半段按前一天的星期计算）
+    fn contains(&self, now: NaiveDateTime) -> bool {
+        let time = now.time();
+        let today = now.weekday();
+        if self.start == self.end {
+            return self.day_matches(today);
+        }
+        if self.start < self.end {
+            return self.day_matches(today) && time >= self.start && time < self.end;
+        }
+        (time >= self.start && self.day_matches(today))
+            || (time < self.end && self.day_matches(today.pred()))
+    }
+
+    fn applies_to(&self, model: &str) -> bool {
+        self.models.is_empty()
+            || self
+                .models
+                .iter()
+                .any(|pattern| pattern_matches(pattern, model))
+    }
+}
+
+/// 时间段路由器
+#[derive(Debug, Clone, Default)]
+pub struct TimeWindowRouter {
+    enabled: bool,
+    rules: Vec<CompiledRule>,
+}
+
+impl TimeWindowRouter {
+    /// 按配置构建，跳过无效规则并记录警告
+    pub fn from_config(config: &TimeWindowRoutingConfig) -> Self {
+        let rules = config
+            .rules
+            .iter()
+            .enumerate()
+            .filter_map(|(index, rule)| match CompiledRule::compile(index, rule) {
+                Ok(rule) => Some(rule),
+                Err(e) => {
+                    tracing::warn!("[TIME_ROUTE] 忽略无效规则: {}", e);
+                    None
+                }
+            })
+            .collect();
+        Self {
+            enabled: config.enabled,
+            rules,
+        }
+    }
+
+    /// 是否启用（启用且至少有一条有效规则）
+    pub fn is_enabled(&self) -> bool {
+        self.enabled && !self.rules.is_empty()
+    }
+
+    /// 按指定的本地时间匹配第一条规则
+    pub fn route_at(&self, now: NaiveDateTime, model: &str) -> Option<TimeWindowMatch> {
+        if !self.is_enabled() {
+            return None;
+        }
+        self.rules
+            .iter()
+            .find(|rule| rule.applies_to(model) && rule.contains(now))
+            .map(|rule| TimeWindowMatch {
+                rule: rule.name.clone(),
+                provider: rule.provider.clone(),
+                model: rule.model.clone(),
+            })
+    }
+
+    /// 按本机当前时间匹配
+    pub fn route(&self, model: &str) -> Option<TimeWindowMatch> {
+        self.route_at(Local::now().naive_local(), model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn rule(days: &[&str], start: &str, end: &str, provider: &str) -> TimeWindowRule {
+        TimeWindowRule {
+            name: String::new(),
+            days: days.iter().map(|d| d.to_string()).collect(),
+            start: start.to_string(),
+            end: end.to_string(),
+            models: Vec::new(),
+            provider: Some(provider.to_string()),
+            model: None,
+        }
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // 2025-06-02 是星期一
+        NaiveDate::from_ymd_opt(2025, 6, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_overnight_and_weekday_windows() {
+        let router = TimeWindowRouter::from_config(&TimeWindowRoutingConfig {
+            enabled: true,
+            rules: vec![
+                rule(
+                    &["mon", "tue", "wed", "thu", "fri"],
+                    "09:00",
+                    "18:00",
+                    "kiro",
+                ),
+                rule(&["fri"], "22:00", "07:00", "openrouter"),
+                rule(&[], "00:00", "00:00", "antigravity"),
+            ],
+        });
+        let provider = |now| router.route_at(now, "claude-sonnet-4-5")?.provider;
+
+        assert_eq!(provider(at(2, 10, 0)).as_deref(), Some("kiro"));
+        assert_eq!(provider(at(2, 18, 0)).as_deref(), Some("antigravity"));
+        // 周五 22:00 开始的时段延续到周六 07:00
+        assert_eq!(provider(at(6, 23, 30)).as_deref(), Some("openrouter"));
+        assert_eq!(provider(at(7, 6, 59)).as_deref(), Some("openrouter"));
+        assert_eq!(provider(at(7, 7, 0)).as_deref(), Some("antigravity"));
+        // 周日 06:00 不属于周五的时段
+        assert_eq!(provider(at(8, 6, 0)).as_deref(), Some("antigravity"));
+    }
+
+    #[test]
+    fn test_invalid_rules_and_model_filter() {
+        let mut scoped = rule(&[], "00:00", "00:00", "gemini");
+        scoped.models = vec!["gemini-*".to_string()];
+        let router = TimeWindowRouter::from_config(&TimeWindowRoutingConfig {
+            enabled: true,
+            rules: vec![
+                rule(&["someday"], "09:00", "18:00", "kiro"),
+                rule(&[], "9am", "18:00", "kiro"),
+                scoped,
+            ],
+        });
+        assert!(router.route_at(at(2, 10, 0), "claude-sonnet-4-5").is_none());
+        assert_eq!(
+            router
+                .route_at(at(2, 10, 0), "gemini-2.5-pro")
+                .and_then(|m| m.provider),
+            Some("gemini".to_string())
+        );
+
+        let disabled = TimeWindowRouter::from_config(&TimeWindowRoutingConfig::default());
+        assert!(!disabled.is_enabled());
+    }
+}
@@ -0,0 +1,243 @@
+//! 系统钥匙串密钥存储
+//!
+//! 入站 API Key、Provider API Key 与 OAuth Refresh Token 保存在系统钥匙串
+//! （macOS Keychain / Windows Credential Manager / Linux Secret Service）中，
+//! 配置文件与数据库只保存 `keychain:<account>` 形式的引用。
+//!
+//! - 读取时引用会被解析为明文；明文值原样返回（兼容旧数据）
+//! - 写入时若钥匙串不可用，回退为原有存储方式并记录警告
+//! - 禁用钥匙串后仍可解析已有引用，下次保存时写回明文
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+
+/// 钥匙串服务名
+const KEYCHAIN_SERVICE: &str = "lime";
+
+/// 引用前缀
+pub const KEYCHAIN_REF_PREFIX: &str = "keychain:";
+
+/// 内部账户前缀（数据库加密密钥等），只能通过 [`SecretStore::get_or_create`] 读取，
+/// 不能通过引用解析
+const INTERNAL_ACCOUNT_PREFIXES: &[&str] = &["database:"];
+
+/// 密钥存储后端
+pub trait SecretBackend: Send + Sync {
+    fn get(&self, account: &str) -> Result<Option<String>, String>;
+    fn set(&self, account: &str, secret: &str) -> Result<(), String>;
+    fn delete(&self, account: &str) -> Result<(), String>;
+}
+
+/// 系统钥匙串后端
+struct KeyringBackend;
+
+impl KeyringBackend {
+    fn entry(account: &str) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(KEYCHAIN_SERVICE, account).map_err(|e| format!("打开钥匙串失败: {e}"))
+    }
+}
+
+impl SecretBackend for KeyringBackend {
+    fn get(&self, account: &str) -> Result<Option<String>, String> {
+        match Self::entry(account)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(format!("读取钥匙串失败: {e}")),
+        }
+    }
+
+    fn set(&self, account: &str, secret: &str) -> Result<(), String> {
+        Self::entry(account)?
+            .set_password(secret)
+            .map_err(|e| format!("写入钥匙串失败: {e}"))
+    }
+
+    fn delete(&self, account: &str) -> Result<(), String> {
+        match Self::entry(account)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("删除钥匙串条目失败: {e}")),
+        }
+    }
+}
+
+/// 内存后端（测试或无钥匙串环境使用）
+#[derive(Default)]
+pub struct MemoryBackend {
+    entries: Mutex<HashMap<String, String>>,
+}
+
+impl SecretBackend for MemoryBackend {
+    fn get(&self, account: &str) -> Result<Option<String>, String> {
+        Ok(self.entries.lock().get(account).cloned())
+    }
+
+    fn set(&self, account: &str, secret: &str) -> Result<(), String> {
+        self.entries
+            .lock()
+            .insert(account.to_string(), secret.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, account: &str) -> Result<(), String> {
+        self.entries.lock().remove(account);
+        Ok(())
+    }
+}
+
+/// 密钥存储
+pub struct SecretStore {
+    backend: Box<dyn SecretBackend>,
+    /// 是否将新写入的密钥保存到钥匙串
+    enabled: AtomicBool,
+    /// 钥匙串写入失败后置为 false，避免重复报错
+    available: AtomicBool,
+}
+
+/// 全局密钥存储（系统钥匙串）
+pub fn secret_store() -> &'static SecretStore {
+    static STORE: OnceLock<SecretStore> = OnceLock::new();
+    STORE.get_or_init(|| SecretStore::with_backend(Box::new(KeyringBackend)))
+}
+
+/// 是否为钥匙串引用
+pub fn is_keychain_ref(value: &str) -> bool {
+    value.starts_with(KEYCHAIN_REF_PREFIX)
+}
+
+/// 按内容生成账户名（相同密钥得到相同引用，便于去重）
+pub fn content_account(kind: &str, secret: &str) -> String {
+    let digest = format!("{:x}", Sha256::digest(secret.as_bytes()));
+    format!("{kind}:{}", &digest[..24])
+}
+
+impl SecretStore {
+    pub fn with_backend(backend: Box<dyn SecretBackend>) -> Self {
+        Self {
+            backend,
+            enabled: AtomicBool::new(true),
+            available: AtomicBool::new(true),
+        }
+    }
+
+    /// 内存存储（测试用）
+    pub fn in_memory() -> Self {
+        Self::with_backend(Box::<MemoryBackend>::default())
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// 新写入的密钥是否保存到钥匙串
+    pub fn is_active(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed) && self.available.load(Ordering::Relaxed)
+    }
+
+    /// 保存密钥并返回引用；未启用或写入失败时返回 `None`
+    pub fn store(&self, account: &str, secret: &str) -> Option<String> {
+        if !self.is_active() || secret.is_empty() || is_keychain_ref(secret) {
+            return None;
+        }
+        match self.backend.set(account, secret) {
+            Ok(()) => Some(format!("{KEYCHAIN_REF_PREFIX}{account}")),
+            Err(e) => {
+                tracing::warn!("[KEYCHAIN] {}，密钥将继续保存在本地存储中", e);
+                self.available.store(false, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// 保存密钥，失败时返回原值
+    pub fn store_or_keep(&self, account: &str, secret: &str) -> String {
+        self.store(account, secret)
+            .unwrap_or_else(|| secret.to_string())
+    }
+
+    /// 解析引用；明文值原样返回，引用内部账户时返回错误
+    pub fn resolve(&self, value: &str) -> Result<String, String> {
+        let Some(account) = value.strip_prefix(KEYCHAIN_REF_PREFIX) else {
+            return Ok(value.to_string());
+        };
+        if INTERNAL_ACCOUNT_PREFIXES
+            .iter()
+            .any(|prefix| account.starts_with(prefix))
+        {
+            return Err(format!("不允许解析内部钥匙串条目: {account}"));
+        }
+        self.backend
+            .get(account)?
+            .ok_or_else(|| format!("钥匙串中不存在条目: {account}"))
+    }
+
+    /// 读取内部密钥，不存在时生成并写入（不受启用开关影响，供数据库加密等内部用途）
+    ///
+    /// 读取失败时直接返回错误，不会生成新密钥；`generate` 返回错误时同样不写入。
+    pub fn get_or_create(
+        &self,
+        account: &str,
+        generate: impl FnOnce() -> Result<String, String>,
+    ) -> Result<String, String> {
+        if let Some(secret) = self.backend.get(account)? {
+            return Ok(secret);
+        }
+        let secret = generate()?;
+        self.backend.set(account, &secret)?;
+        Ok(secret)
+    }
+
+    /// 删除引用对应的钥匙串条目（明文值忽略）
+    pub fn remove(&self, value: &str) {
+        if let Some(account) = value.strip_prefix(KEYCHAIN_REF_PREFIX) {
+            if let Err(e) = self.backend.delete(account) {
+                tracing::warn!("[KEYCHAIN] {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_store_and_resolve_references() {
+        let store = SecretStore::in_memory();
+        let reference = store
+            .store("config:server.api_key", "sk-secret")
+            .expect("应写入钥匙串");
+
+        assert_eq!(reference, "keychain:config:server.api_key");
+        assert_eq!(store.resolve(&reference).as_deref(), Ok("sk-secret"));
+        assert_eq!(store.resolve("plain-value").as_deref(), Ok("plain-value"));
+
+        store.remove(&reference);
+        assert!(store.resolve(&reference).is_err());
+
+        let field_key = store
+            .get_or_create("database:field-key", || Ok("data-key".to_string()))
+            .expect("应写入钥匙串");
+        assert_eq!(field_key, "data-key");
+        assert!(store.resolve("keychain:database:field-key").is_err());
+    }
+
+    #[test]
+    fn should_keep_plaintext_when_disabled() {
+        let store = SecretStore::in_memory();
+        store.set_enabled(false);
+
+        assert_eq!(store.store_or_keep("a", "sk-secret"), "sk-secret");
+        assert_eq!(
+            content_account("api-key", "sk-1"),
+            content_account("api-key", "sk-1")
+        );
+        assert_ne!(
+            content_account("api-key", "sk-1"),
+            content_account("api-key", "sk-2")
+        );
+    }
+}
@@ -65,6 +65,14 @@ pub fn format_api_address(host: &str, port: u16) -> String {
     format!("http://{host}:{port}")
 }
 
+/// 格式化网关地址和 API Key（可直接作为环境变量粘贴）
+///
+/// # 示例输出
+/// - "OPENAI_BASE_URL=http://127.0.0.1:8080/v1\nOPENAI_API_KEY=sk-xxx"
+pub fn format_api_credentials(api_address: &str, api_key: &str) -> String {
+    format!("OPENAI_BASE_URL={api_address}/v1\nOPENAI_API_KEY={api_key}")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -157,4 +165,13 @@ mod tests {
         let address = format_api_address("127.0.0.1", 8080);
         assert_eq!(address, "http://127.0.0.1:8080");
     }
+
+    #[test]
+    fn test_format_api_credentials() {
+        let text = format_api_credentials("http://127.0.0.1:8080", "sk-test");
+        assert_eq!(
+            text,
+            "OPENAI_BASE_URL=http://127.0.0.1:8080/v1\nOPENAI_API_KEY=sk-test"
+        );
+    }
 }
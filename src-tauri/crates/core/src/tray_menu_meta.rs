@@ -30,6 +30,12 @@ pub mod menu_ids {
     pub const OPEN_WINDOW: &str = "open_window";
     /// 复制 API 地址
     pub const COPY_API_ADDRESS: &str = "copy_api_address";
+    /// 复制网关地址和 API Key
+    pub const COPY_API_CREDENTIALS: &str = "copy_api_credentials";
+    /// 暂停 Provider 子菜单
+    pub const PROVIDER_TOGGLE_ROOT: &str = "provider_toggle_root";
+    /// 配置方案子菜单
+    pub const CONFIG_PROFILE_ROOT: &str = "config_profile_root";
     /// 打开日志目录
     pub const OPEN_LOG_DIR: &str = "open_log_dir";
     /// 分隔符 3
@@ -106,6 +112,37 @@ pub fn parse_quick_model_item_id(id: &str) -> Option<(String, String)> {
     Some((provider_type.to_string(), model.to_string()))
 }
 
+const PROVIDER_TOGGLE_ID_PREFIX: &str = "provider_pause";
+const CONFIG_PROFILE_ID_PREFIX: &str = "config_profile";
+
+/// 生成 Provider 暂停开关菜单项 ID
+pub fn build_provider_toggle_item_id(provider_type: &str) -> String {
+    format!("{PROVIDER_TOGGLE_ID_PREFIX}::{provider_type}")
+}
+
+/// 解析 Provider 暂停开关菜单项 ID
+pub fn parse_provider_toggle_item_id(id: &str) -> Option<String> {
+    parse_single_value_item_id(id, PROVIDER_TOGGLE_ID_PREFIX)
+}
+
+/// 生成配置方案菜单项 ID
+pub fn build_config_profile_item_id(profile: &str) -> String {
+    format!("{CONFIG_PROFILE_ID_PREFIX}::{profile}")
+}
+
+/// 解析配置方案菜单项 ID
+pub fn parse_config_profile_item_id(id: &str) -> Option<String> {
+    parse_single_value_item_id(id, CONFIG_PROFILE_ID_PREFIX)
+}
+
+fn parse_single_value_item_id(id: &str, expected_prefix: &str) -> Option<String> {
+    let (prefix, value) = id.split_once("::")?;
+    if prefix != expected_prefix || value.is_empty() {
+        return None;
+    }
+    Some(value.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -205,6 +242,20 @@ mod tests {
         assert_eq!(parse_quick_model_item_id("other::claude::model"), None);
     }
 
+    #[test]
+    fn test_build_and_parse_single_value_item_ids() {
+        let id = build_provider_toggle_item_id("openai");
+        assert_eq!(
+            parse_provider_toggle_item_id(&id),
+            Some("openai".to_string())
+        );
+        assert_eq!(parse_config_profile_item_id(&id), None);
+
+        let id = build_config_profile_item_id("work");
+        assert_eq!(parse_config_profile_item_id(&id), Some("work".to_string()));
+        assert_eq!(parse_provider_toggle_item_id("provider_pause::"), None);
+    }
+
     proptest! {
         #[test]
         fn prop_menu_ids_completeness(
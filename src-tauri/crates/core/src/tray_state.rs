@@ -77,6 +77,17 @@ pub struct TrayQuickModelGroup {
     pub models: Vec<TrayQuickModelItem>,
 }
 
+/// 托盘 Provider 暂停开关
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct TrayProviderToggle {
+    /// Provider 类型
+    pub provider_type: String,
+    /// 凭证总数
+    pub total_credentials: usize,
+    /// 是否已通过暂停开关暂停（单独禁用的凭证不计入）
+    pub paused: bool,
+}
+
 /// 托盘状态快照
 #[derive(Debug, Clone, Serialize)]
 pub struct TrayStateSnapshot {
@@ -104,6 +115,12 @@ pub struct TrayStateSnapshot {
     pub current_theme_label: String,
     /// 托盘中的快速模型切换候选
     pub quick_model_groups: Vec<TrayQuickModelGroup>,
+    /// 凭证池中各 Provider 的暂停开关
+    pub provider_toggles: Vec<TrayProviderToggle>,
+    /// 可切换的配置方案名称
+    pub config_profiles: Vec<String>,
+    /// 最近一次应用的配置方案
+    pub active_config_profile: String,
 }
 
 impl Default for TrayStateSnapshot {
@@ -121,6 +138,9 @@ impl Default for TrayStateSnapshot {
             current_model: String::new(),
             current_theme_label: String::new(),
             quick_model_groups: Vec::new(),
+            provider_toggles: Vec::new(),
+            config_profiles: Vec::new(),
+            active_config_profile: String::new(),
         }
     }
 }
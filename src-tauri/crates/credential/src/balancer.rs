@@ -1,6 +1,6 @@
 //! 负载均衡器实现
 //!
-//! 提供轮询负载均衡策略，支持凭证冷却和自动恢复
+//! 提供按健康分加权、轮询等负载均衡策略，支持凭证冷却和自动恢复
 
 use chrono::{DateTime, Duration, Utc};
 use dashmap::DashMap;
@@ -18,8 +18,10 @@ use std::sync::Arc;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum BalanceStrategy {
-    /// 轮询策略（默认）
+    /// 按健康分加权随机（默认）
     #[default]
+    HealthWeighted,
+    /// 轮询策略
     RoundRobin,
     /// 最少使用策略
     LeastUsed,
@@ -27,6 +29,9 @@ pub enum BalanceStrategy {
     Random,
 }
 
+/// 加权选择时的最小权重，保证健康分很低的凭证仍有机会被选中以恢复
+const MIN_SELECTION_WEIGHT: f64 = 0.05;
+
 /// 冷却信息
 #[derive(Debug, Clone)]
 pub struct CooldownInfo {
@@ -147,6 +152,7 @@ impl LoadBalancer {
         let pool = self.pools.get(&provider).ok_or(PoolError::EmptyPool)?;
         pool.refresh_cooldowns();
         match self.strategy {
+            BalanceStrategy::HealthWeighted => self.select_health_weighted(&pool),
             BalanceStrategy::RoundRobin => self.select_round_robin(&pool, provider),
             BalanceStrategy::LeastUsed => self.select_least_used(&pool),
             BalanceStrategy::Random => self.select_random(&pool),
@@ -270,6 +276,16 @@ impl LoadBalancer {
         Ok(active_creds[index].clone())
     }
 
+    /// 按健康分加权随机选择凭证
+    fn select_health_weighted(&self, pool: &CredentialPool) -> Result<Credential, PoolError> {
+        let active_creds: Vec<Credential> = pool
+            .all()
+            .into_iter()
+            .filter(|c| c.is_available())
+            .collect();
+        pick_weighted(active_creds, rand::random::<f64>()).ok_or(PoolError::NoAvailableCredential)
+    }
+
     /// 最少使用选择凭证
     fn select_least_used(&self, pool: &CredentialPool) -> Result<Credential, PoolError> {
         pool.all()
@@ -367,8 +383,28 @@ impl LoadBalancer {
 
 impl Default for LoadBalancer {
     fn default() -> Self {
-        Self::round_robin()
+        Self::new(BalanceStrategy::default())
+    }
+}
+
+/// 选择时使用的权重
+fn selection_weight(credential: &Credential) -> f64 {
+    credential.stats.health_score.max(MIN_SELECTION_WEIGHT)
+}
+
+/// 按权重从候选中挑选，`roll` 取值 [0, 1)
+fn pick_weighted(candidates: Vec<Credential>, roll: f64) -> Option<Credential> {
+    let total: f64 = candidates.iter().map(selection_weight).sum();
+    let mut remaining = roll.clamp(0.0, 1.0) * total;
+    let last = candidates.len().checked_sub(1)?;
+    for (index, credential) in candidates.into_iter().enumerate() {
+        let weight = selection_weight(&credential);
+        if remaining < weight || index == last {
+            return Some(credential);
+        }
+        remaining -= weight;
     }
+    None
 }
 
 #[cfg(test)]
@@ -394,6 +430,28 @@ mod balancer_tests {
         assert!(lb.providers().is_empty());
     }
 
+    #[test]
+    fn test_pick_weighted_prefers_healthy_credentials() {
+        let healthy = create_test_credential("healthy", ProviderType::Kiro);
+        let mut degraded = create_test_credential("degraded", ProviderType::Kiro);
+        for _ in 0..5 {
+            degraded.stats.record_failure();
+        }
+        let weight = selection_weight(&degraded);
+        assert!(weight < 0.2);
+
+        let candidates = vec![degraded, healthy];
+        let total = 1.0 + weight;
+        let pick = |roll: f64| pick_weighted(candidates.clone(), roll).unwrap().id;
+        assert_eq!(pick(0.0), "degraded");
+        assert_eq!(pick(weight / total + 0.01), "healthy");
+        assert_eq!(pick(0.99), "healthy");
+        assert!(pick_weighted(Vec::new(), 0.5).is_none());
+
+        let lb = LoadBalancer::default();
+        assert_eq!(lb.strategy(), BalanceStrategy::HealthWeighted);
+    }
+
     #[test]
     fn test_load_balancer_register_pool() {
         let lb = LoadBalancer::round_robin();
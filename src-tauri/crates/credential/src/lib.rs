@@ -4,13 +4,15 @@
 //!
 //! ## 模块结构
 //!
-//! - `balancer` - 负载均衡策略（轮询、最少使用、随机）
+//! - `balancer` - 负载均衡策略（健康分加权、轮询、最少使用、随机）
 //! - `quota` - 配额超限检测、自动切换和冷却恢复
+//! - `simulator` - 凭证池模拟：用录制的流量预估不同池配置的延迟与配额消耗
 //! - `sync` - 凭证与 YAML 配置文件的同步
 
 mod balancer;
 pub mod encryption;
 mod quota;
+mod simulator;
 mod sync;
 
 // 重新导出
@@ -19,4 +21,8 @@ pub use quota::{
     create_shared_quota_manager, start_quota_cleanup_task, AllCredentialsExhaustedError,
     QuotaAutoSwitchResult, QuotaExceededRecord, QuotaManager,
 };
+pub use simulator::{
+    simulate, CredentialProjection, PoolScenario, ScenarioCredential, SimulationReport,
+    TrafficProfile, TrafficSample,
+};
 pub use sync::{CredentialSyncService, SyncError};
@@ -0,0 +1,569 @@
+//! 凭证池模拟（What-if 分析）
+//!
+//! 用录制的请求日志回放流量，在假设的凭证池配置（不同策略、权重、新增凭证、配额）上
+//! 离线运行真实的负载均衡器，预估各凭证分到的请求量、延迟与配额消耗，
+//! 便于在修改 `credential_pool` 配置前比较方案。模拟不会发送任何上游请求。
+//!
+//! 模拟中的简化：
+//! - 延迟与失败按录制样本（或场景中的覆盖值）抽样，失败的请求不重试
+//! - 配额在整个回放期间累计，用尽后该凭证不再被选中
+//! - 被标记为不健康的凭证在模拟中不会恢复（没有后台探测）
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use lime_core::config::CredentialPoolConfig;
+use lime_core::credential::pool::CredentialPool;
+use lime_core::credential::types::{Credential, CredentialData, CredentialStatus};
+use lime_core::ProviderType;
+use lime_infra::telemetry::{RequestLog, RequestStatus};
+use serde::{Deserialize, Serialize};
+
+use crate::balancer::{BalanceStrategy, LoadBalancer};
+
+/// 没有任何延迟样本时使用的默认延迟（毫秒）
+const DEFAULT_LATENCY_MS: u64 = 1_000;
+
+/// 配额用尽后的冷却时长，覆盖整个模拟过程
+const EXHAUSTED_COOLDOWN_DAYS: i64 = 365;
+
+/// 一条录制的请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrafficSample {
+    /// 相对于录制开始的秒数
+    pub offset_secs: i64,
+    /// 总 Token 数（日志中没有时为 0）
+    pub tokens: u64,
+    /// 请求耗时（毫秒）
+    pub duration_ms: u64,
+    /// 是否成功
+    pub success: bool,
+    /// 录制时使用的凭证
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential_id: Option<String>,
+}
+
+/// 录制的流量分布
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrafficProfile {
+    pub provider: ProviderType,
+    /// 录制开始时间
+    pub started_at: Option<DateTime<Utc>>,
+    /// 按时间排序的请求样本
+    pub samples: Vec<TrafficSample>,
+}
+
+impl TrafficProfile {
+    /// 从请求日志中提取指定 Provider 的流量（忽略重试中与已取消的请求）
+    pub fn from_logs(provider: ProviderType, logs: &[RequestLog]) -> Self {
+        let mut logs: Vec<&RequestLog> = logs
+            .iter()
+            .filter(|log| log.provider == provider)
+            .filter(|log| {
+                !matches!(
+                    log.status,
+                    RequestStatus::Retrying | RequestStatus::Cancelled
+                )
+            })
+            .collect();
+        logs.sort_by_key(|log| log.timestamp);
+        let started_at = logs.first().map(|log| log.timestamp);
+        let samples = logs
+            .into_iter()
+            .map(|log| TrafficSample {
+                offset_secs: started_at
+                    .map(|start| (log.timestamp - start).num_seconds())
+                    .unwrap_or(0),
+                tokens: log.total_tokens.map(u64::from).unwrap_or(0),
+                duration_ms: log.duration_ms,
+                success: log.status == RequestStatus::Success,
+                credential_id: log.credential_id.clone(),
+            })
+            .collect();
+        Self {
+            provider,
+            started_at,
+            samples,
+        }
+    }
+
+    /// 录制覆盖的时长（秒）
+    pub fn span_secs(&self) -> i64 {
+        self.samples.last().map(|s| s.offset_secs).unwrap_or(0)
+    }
+
+    /// 某凭证（或全部请求）成功请求的延迟样本
+    fn latency_samples(&self, credential_id: Option<&str>) -> Vec<u64> {
+        self.samples
+            .iter()
+            .filter(|s| s.success)
+            .filter(|s| credential_id.is_none() || s.credential_id.as_deref() == credential_id)
+            .map(|s| s.duration_ms)
+            .collect()
+    }
+
+    /// 某凭证（或全部请求）的失败率，没有样本时返回 `None`
+    fn failure_rate(&self, credential_id: Option<&str>) -> Option<f64> {
+        let (total, failed) = self
+            .samples
+            .iter()
+            .filter(|s| credential_id.is_none() || s.credential_id.as_deref() == credential_id)
+            .fold((0u64, 0u64), |(total, failed), s| {
+                (total + 1, failed + u64::from(!s.success))
+            });
+        (total > 0).then(|| failed as f64 / total as f64)
+    }
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn default_traffic_multiplier() -> f64 {
+    1.0
+}
+
+/// 场景中的一个凭证
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioCredential {
+    /// 凭证 ID，与录制中的凭证相同时沿用其延迟与失败率
+    pub id: String,
+    /// 初始健康分（0.0 ~ 1.0），即健康分加权策略下的初始选择权重
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    /// 覆盖延迟（毫秒），用于新增的凭证或预估更快 / 更慢的端点
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// 覆盖失败率（0.0 ~ 1.0）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_rate: Option<f64>,
+    /// 回放期间可用的 Token 配额
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_quota: Option<u64>,
+    /// 回放期间可用的请求次数配额
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_quota: Option<u64>,
+}
+
+impl ScenarioCredential {
+    pub fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            weight: default_weight(),
+            latency_ms: None,
+            failure_rate: None,
+            token_quota: None,
+            request_quota: None,
+        }
+    }
+}
+
+/// 假设的凭证池配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolScenario {
+    /// 场景名称
+    pub name: String,
+    /// 负载均衡策略
+    #[serde(default)]
+    pub strategy: BalanceStrategy,
+    pub credentials: Vec<ScenarioCredential>,
+    /// 流量倍数，用于预估流量增长后的表现
+    #[serde(default = "default_traffic_multiplier")]
+    pub traffic_multiplier: f64,
+    /// 延迟与失败抽样的随机种子，相同种子的结果可复现
+    #[serde(default)]
+    pub seed: u64,
+}
+
+impl PoolScenario {
+    /// 由当前 `credential_pool` 配置中该 Provider 的已启用凭证生成基准场景
+    pub fn from_pool_config(
+        name: &str,
+        config: &CredentialPoolConfig,
+        provider: ProviderType,
+        strategy: BalanceStrategy,
+    ) -> Self {
+        let ids: Vec<&str> = match provider {
+            ProviderType::Kiro => enabled_ids(&config.kiro, |e| (&e.id, e.disabled)),
+            ProviderType::Gemini => enabled_ids(&config.gemini, |e| (&e.id, e.disabled)),
+            ProviderType::Codex => enabled_ids(&config.codex, |e| (&e.id, e.disabled)),
+            ProviderType::OpenAI => enabled_ids(&config.openai, |e| (&e.id, e.disabled)),
+            ProviderType::Claude => enabled_ids(&config.claude, |e| (&e.id, e.disabled)),
+            ProviderType::GeminiApiKey => {
+                enabled_ids(&config.gemini_api_keys, |e| (&e.id, e.disabled))
+            }
+            ProviderType::Vertex => enabled_ids(&config.vertex_api_keys, |e| (&e.id, e.disabled)),
+            _ => Vec::new(),
+        };
+        Self {
+            name: name.to_string(),
+            strategy,
+            credentials: ids.into_iter().map(ScenarioCredential::new).collect(),
+            traffic_multiplier: default_traffic_multiplier(),
+            seed: 0,
+        }
+    }
+}
+
+fn enabled_ids<T>(entries: &[T], key: impl Fn(&T) -> (&String, bool)) -> Vec<&str> {
+    entries
+        .iter()
+        .map(key)
+        .filter(|(_, disabled)| !disabled)
+        .map(|(id, _)| id.as_str())
+        .collect()
+}
+
+/// 单个凭证的预估结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CredentialProjection {
+    pub id: String,
+    /// 分到的请求数
+    pub requests: u64,
+    /// 占全部已处理请求的比例
+    pub share: f64,
+    pub failures: u64,
+    pub tokens: u64,
+    pub mean_latency_ms: f64,
+    /// 配额使用比例（Token 与请求次数配额中较高者），未设置配额时为 `None`
+    pub quota_used: Option<f64>,
+    /// 配额在回放第几秒用尽
+    pub exhausted_after_secs: Option<i64>,
+    /// 模拟结束时是否因连续失败被标记为不健康
+    pub unhealthy: bool,
+}
+
+/// 场景的预估结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulationReport {
+    pub scenario: String,
+    pub strategy: BalanceStrategy,
+    /// 回放的请求数（已乘以流量倍数）
+    pub total_requests: u64,
+    /// 分配到凭证的请求数
+    pub served: u64,
+    /// 分配后失败的请求数
+    pub failed: u64,
+    /// 没有可用凭证（全部配额用尽或不健康）而被拒绝的请求数
+    pub rejected: u64,
+    /// 第一次拒绝发生在回放第几秒
+    pub first_rejection_after_secs: Option<i64>,
+    pub total_tokens: u64,
+    pub mean_latency_ms: f64,
+    pub p50_latency_ms: u64,
+    pub p95_latency_ms: u64,
+    pub credentials: Vec<CredentialProjection>,
+}
+
+/// 可复现的伪随机数（xorshift64*）
+struct SimRng(u64);
+
+impl SimRng {
+    fn new(seed: u64) -> Self {
+        Self((seed ^ 0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    /// 取值 [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn pick(&mut self, samples: &[u64]) -> Option<u64> {
+        let index = (self.next_f64() * samples.len() as f64) as usize;
+        samples
+            .get(index.min(samples.len().saturating_sub(1)))
+            .copied()
+    }
+}
+
+/// 场景中单个凭证的模拟状态
+struct SimCredential {
+    spec: ScenarioCredential,
+    latency_samples: Vec<u64>,
+    failure_rate: f64,
+    requests: u64,
+    failures: u64,
+    tokens: u64,
+    latency_total: u64,
+    exhausted_after_secs: Option<i64>,
+}
+
+impl SimCredential {
+    fn quota_used(&self) -> Option<f64> {
+        let tokens = self
+            .spec
+            .token_quota
+            .map(|quota| self.tokens as f64 / quota.max(1) as f64);
+        let requests = self
+            .spec
+            .request_quota
+            .map(|quota| self.requests as f64 / quota.max(1) as f64);
+        match (tokens, requests) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn exhausted(&self) -> bool {
+        self.spec
+            .token_quota
+            .is_some_and(|quota| self.tokens >= quota)
+            || self
+                .spec
+                .request_quota
+                .is_some_and(|quota| self.requests >= quota)
+    }
+}
+
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let index = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[index.min(sorted.len() - 1)]
+}
+
+/// 在场景上回放录制的流量
+pub fn simulate(profile: &TrafficProfile, scenario: &PoolScenario) -> SimulationReport {
+    let provider = profile.provider;
+    let pool_latency = profile.latency_samples(None);
+    let pool_failure_rate = profile.failure_rate(None).unwrap_or(0.0);
+
+    let pool = Arc::new(CredentialPool::new(provider));
+    let mut sims: HashMap<String, SimCredential> = HashMap::new();
+    for spec in &scenario.credentials {
+        let mut credential = Credential::new(
+            spec.id.clone(),
+            provider,
+            CredentialData::ApiKey {
+                key: String::new(),
+                base_url: None,
+            },
+        );
+        credential.stats.health_score = spec.weight.clamp(0.0, 1.0);
+        if pool.add(credential).is_err() {
+            continue;
+        }
+        let recorded = profile.latency_samples(Some(&spec.id));
+        let latency_samples = match spec.latency_ms {
+            Some(latency) => vec![latency],
+            None if !recorded.is_empty() => recorded,
+            None if !pool_latency.is_empty() => pool_latency.clone(),
+            None => vec![DEFAULT_LATENCY_MS],
+        };
+        let failure_rate = spec
+            .failure_rate
+            .or_else(|| profile.failure_rate(Some(&spec.id)))
+            .unwrap_or(pool_failure_rate)
+            .clamp(0.0, 1.0);
+        sims.insert(
+            spec.id.clone(),
+            SimCredential {
+                spec: spec.clone(),
+                latency_samples,
+                failure_rate,
+                requests: 0,
+                failures: 0,
+                tokens: 0,
+                latency_total: 0,
+                exhausted_after_secs: None,
+            },
+        );
+    }
+
+    let balancer = LoadBalancer::new(scenario.strategy);
+    balancer.register_pool(pool.clone());
+    let mut rng = SimRng::new(scenario.seed);
+
+    let recorded = profile.samples.len();
+    let total = (recorded as f64 * scenario.traffic_multiplier.max(0.0)).round() as usize;
+    let mut latencies = Vec::with_capacity(total);
+    let (mut served, mut failed, mut rejected, mut total_tokens) = (0u64, 0u64, 0u64, 0u64);
+    let mut first_rejection_after_secs = None;
+
+    for index in 0..total {
+        let sample = &profile.samples[index * recorded / total.max(1)];
+        let Ok(credential) = balancer.select(provider) else {
+            rejected += 1;
+            first_rejection_after_secs.get_or_insert(sample.offset_secs);
+            continue;
+        };
+        let Some(sim) = sims.get_mut(&credential.id) else {
+            continue;
+        };
+        let latency = rng.pick(&sim.latency_samples).unwrap_or(DEFAULT_LATENCY_MS);
+        let success = rng.next_f64() >= sim.failure_rate;
+        let _ = balancer.report(provider, &credential.id, success, latency);
+
+        served += 1;
+        sim.requests += 1;
+        sim.tokens += sample.tokens;
+        total_tokens += sample.tokens;
+        if success {
+            sim.latency_total += latency;
+            latencies.push(latency);
+        } else {
+            sim.failures += 1;
+            failed += 1;
+        }
+        if sim.exhausted_after_secs.is_none() && sim.exhausted() {
+            sim.exhausted_after_secs = Some(sample.offset_secs);
+            let _ = pool.mark_cooldown(
+                &credential.id,
+                chrono::Duration::days(EXHAUSTED_COOLDOWN_DAYS),
+            );
+        }
+    }
+
+    latencies.sort_unstable();
+    let mean_latency_ms = if latencies.is_empty() {
+        0.0
+    } else {
+        latencies.iter().sum::<u64>() as f64 / latencies.len() as f64
+    };
+    let credentials = scenario
+        .credentials
+        .iter()
+        .filter_map(|spec| sims.get(&spec.id))
+        .map(|sim| {
+            let successes = sim.requests - sim.failures;
+            CredentialProjection {
+                id: sim.spec.id.clone(),
+                requests: sim.requests,
+                share: if served == 0 {
+                    0.0
+                } else {
+                    sim.requests as f64 / served as f64
+                },
+                failures: sim.failures,
+                tokens: sim.tokens,
+                mean_latency_ms: if successes == 0 {
+                    0.0
+                } else {
+                    sim.latency_total as f64 / successes as f64
+                },
+                quota_used: sim.quota_used(),
+                exhausted_after_secs: sim.exhausted_after_secs,
+                unhealthy: pool
+                    .get(&sim.spec.id)
+                    .is_some_and(|c| matches!(c.status, CredentialStatus::Unhealthy { .. })),
+            }
+        })
+        .collect();
+
+    SimulationReport {
+        scenario: scenario.name.clone(),
+        strategy: scenario.strategy,
+        total_requests: total as u64,
+        served,
+        failed,
+        rejected,
+        first_rejection_after_secs,
+        total_tokens,
+        mean_latency_ms,
+        p50_latency_ms: percentile(&latencies, 0.5),
+        p95_latency_ms: percentile(&latencies, 0.95),
+        credentials,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(offset_secs: i64, credential: &str, duration_ms: u64, success: bool) -> RequestLog {
+        let mut log = RequestLog::new(
+            format!("req-{offset_secs}"),
+            ProviderType::OpenAI,
+            "gpt-4o".to_string(),
+            false,
+        );
+        log.timestamp = DateTime::<Utc>::from_timestamp(1_700_000_000 + offset_secs, 0).unwrap();
+        log.credential_id = Some(credential.to_string());
+        if success {
+            log.mark_success(duration_ms, 200);
+        } else {
+            log.mark_failed(duration_ms, Some(500), "upstream error".to_string());
+        }
+        log.set_tokens(Some(80), Some(20));
+        log
+    }
+
+    fn profile() -> TrafficProfile {
+        let logs: Vec<RequestLog> = (0..100)
+            .map(|i| log(i, "key-a", 400 + (i as u64 % 5) * 100, true))
+            .collect();
+        TrafficProfile::from_logs(ProviderType::OpenAI, &logs)
+    }
+
+    #[test]
+    fn test_profile_from_logs() {
+        let mut logs = vec![
+            log(30, "key-a", 500, false),
+            log(0, "key-a", 300, true),
+            log(10, "key-b", 900, true),
+        ];
+        let mut other = log(5, "key-a", 100, true);
+        other.provider = ProviderType::Claude;
+        logs.push(other);
+
+        let profile = TrafficProfile::from_logs(ProviderType::OpenAI, &logs);
+        assert_eq!(profile.samples.len(), 3);
+        assert_eq!(profile.span_secs(), 30);
+        assert_eq!(profile.samples[0].tokens, 100);
+        assert_eq!(profile.latency_samples(Some("key-a")), vec![300]);
+        assert_eq!(profile.failure_rate(Some("key-a")), Some(0.5));
+        assert_eq!(profile.failure_rate(Some("missing")), None);
+    }
+
+    #[test]
+    fn test_round_robin_with_added_credential_and_quota() {
+        let profile = profile();
+        let mut added = ScenarioCredential::new("key-b");
+        added.latency_ms = Some(100);
+        added.token_quota = Some(2_000);
+        let scenario = PoolScenario {
+            name: "add key-b".to_string(),
+            strategy: BalanceStrategy::RoundRobin,
+            credentials: vec![ScenarioCredential::new("key-a"), added],
+            traffic_multiplier: 1.0,
+            seed: 7,
+        };
+
+        let report = simulate(&profile, &scenario);
+        assert_eq!(report.total_requests, 100);
+        assert_eq!(report.served, 100);
+        assert_eq!(report.rejected, 0);
+        assert_eq!(report.total_tokens, 10_000);
+
+        let key_b = &report.credentials[1];
+        // 每次 100 Token，20 次后配额用尽，之后全部由 key-a 承担
+        assert_eq!(key_b.requests, 20);
+        assert_eq!(key_b.quota_used, Some(1.0));
+        assert!(key_b.exhausted_after_secs.is_some());
+        assert_eq!(key_b.mean_latency_ms, 100.0);
+        assert_eq!(report.credentials[0].requests, 80);
+        assert!(report.credentials[0].mean_latency_ms >= 400.0);
+        assert!(report.p95_latency_ms <= 800);
+
+        // 流量翻倍且唯一凭证有请求次数配额时，超出部分被拒绝
+        let mut limited = ScenarioCredential::new("key-a");
+        limited.request_quota = Some(150);
+        let scenario = PoolScenario {
+            name: "double traffic".to_string(),
+            strategy: BalanceStrategy::LeastUsed,
+            credentials: vec![limited],
+            traffic_multiplier: 2.0,
+            seed: 0,
+        };
+        let report = simulate(&profile, &scenario);
+        assert_eq!(report.total_requests, 200);
+        assert_eq!(report.served, 150);
+        assert_eq!(report.rejected, 50);
+        assert!(report.first_rejection_after_secs.is_some());
+    }
+}
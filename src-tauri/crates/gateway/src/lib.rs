@@ -8,4 +8,5 @@ pub mod discord;
 pub mod feishu;
 pub mod telegram;
 pub mod tunnel;
+mod tunnel_ngrok;
 pub mod wechat;
@@ -1,9 +1,11 @@
 //! Gateway 全局隧道运行时
 //!
-//! 目标：为 webhook 渠道提供公网入口（优先 Cloudflare Tunnel）。
+//! 目标：为 webhook 渠道提供公网入口（优先 Cloudflare Tunnel，也支持 ngrok）。
+//! 隧道暴露 API 服务器端口时，要求服务器使用强 API Key。
 
+use crate::tunnel_ngrok;
 use chrono::Utc;
-use lime_core::config::{Config, GatewayTunnelConfig};
+use lime_core::config::{is_strong_api_key, Config, GatewayTunnelConfig};
 use lime_core::logger::LogStore;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -85,7 +87,7 @@ pub async fn probe_tunnel(config: &Config) -> GatewayTunnelProbeResult {
     let mode = normalize_mode(&tunnel.mode);
     let binary = resolve_binary(tunnel).to_string();
 
-    if provider != "cloudflare" {
+    if provider == "none" {
         return GatewayTunnelProbeResult {
             ok: false,
             provider,
@@ -93,7 +95,7 @@ pub async fn probe_tunnel(config: &Config) -> GatewayTunnelProbeResult {
             binary,
             version: None,
             config_ready: false,
-            message: "当前仅支持 cloudflare provider".to_string(),
+            message: "未选择隧道 provider".to_string(),
         };
     }
 
@@ -108,6 +110,23 @@ pub async fn probe_tunnel(config: &Config) -> GatewayTunnelProbeResult {
         .map(|text| text.trim().to_string())
         .filter(|text| !text.is_empty());
 
+    if provider == "ngrok" {
+        let has_authtoken = tunnel_ngrok::has_authtoken(tunnel);
+        return GatewayTunnelProbeResult {
+            ok: version.is_some(),
+            provider,
+            mode,
+            binary,
+            version,
+            config_ready: has_authtoken,
+            message: if has_authtoken {
+                "探测成功，可尝试启动隧道".to_string()
+            } else {
+                "探测成功，但缺少 ngrok.authtoken".to_string()
+            },
+        };
+    }
+
     let has_runtime_auth = tunnel
         .cloudflare
         .run_token
@@ -227,9 +246,10 @@ pub async fn start_tunnel(
     }
 
     let provider = normalize_provider(&tunnel.provider);
-    if provider != "cloudflare" {
+    if provider == "none" {
         return Err(format!("暂不支持的 tunnel provider: {}", tunnel.provider));
     }
+    ensure_tunnel_api_key(&config)?;
 
     let mode = normalize_mode(&tunnel.mode);
     if mode == "external" {
@@ -241,11 +261,18 @@ pub async fn start_tunnel(
     let binary = resolve_binary(tunnel).to_string();
     let local_url = build_local_url(tunnel);
     let public_base_url = resolve_public_base_url(tunnel);
-    let (args, preview) = build_cloudflare_run_args(tunnel, &local_url)?;
+    let (args, envs, preview) = if provider == "ngrok" {
+        let launch = tunnel_ngrok::build_ngrok_run_args(tunnel, &local_url)?;
+        (launch.args, launch.envs, launch.preview)
+    } else {
+        let (args, preview) = build_cloudflare_run_args(tunnel, &local_url)?;
+        (args, Vec::new(), preview)
+    };
 
     let mut command = Command::new(&binary);
     command
         .args(args.iter().map(String::as_str))
+        .envs(envs)
         .kill_on_drop(true)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
@@ -255,11 +282,25 @@ pub async fn start_tunnel(
         .map_err(|e| format!("启动 tunnel 进程失败: {e}"))?;
 
     let pid = child.id();
+    // 先持有运行时锁，避免输出任务解析到的公网地址被随后写入的初始状态覆盖
+    let mut runtime = state.inner.write().await;
+    let detect_public_url = provider == "ngrok";
     let stdout_task = child.stdout.take().map(|stdout| {
         let logs = logs.clone();
+        let runtime_state = state.inner.clone();
         tokio::spawn(async move {
             let mut lines = BufReader::new(stdout).lines();
             while let Ok(Some(line)) = lines.next_line().await {
+                // ngrok 随机域名只能从运行日志中获取
+                if let Some(url) = detect_public_url
+                    .then(|| tunnel_ngrok::parse_public_url(&line))
+                    .flatten()
+                {
+                    logs.write()
+                        .await
+                        .add("info", &format!("[GatewayTunnel] 公网地址: {}", url));
+                    runtime_state.write().await.status.public_base_url = Some(url);
+                }
                 logs.write()
                     .await
                     .add("info", &format!("[GatewayTunnel][stdout] {}", line));
@@ -286,7 +327,6 @@ pub async fn start_tunnel(
         ),
     );
 
-    let mut runtime = state.inner.write().await;
     runtime.process = Some(RunningProcess {
         child,
         stdout_task,
@@ -395,12 +435,33 @@ fn poll_runtime_process(runtime: &mut GatewayTunnelRuntime) -> Result<(), String
 }
 
 fn resolve_binary(tunnel: &GatewayTunnelConfig) -> &str {
+    let default_binary = if normalize_provider(&tunnel.provider) == "ngrok" {
+        "ngrok"
+    } else {
+        "cloudflared"
+    };
     tunnel
         .binary_path
         .as_deref()
         .map(str::trim)
         .filter(|v| !v.is_empty())
-        .unwrap_or("cloudflared")
+        .unwrap_or(default_binary)
+}
+
+/// 隧道暴露 API 服务器端口时要求强 API Key
+pub fn ensure_tunnel_api_key(config: &Config) -> Result<(), String> {
+    let tunnel = &config.gateway.tunnel;
+    if !tunnel.require_strong_api_key
+        || tunnel.local_port != config.server.port
+        || is_strong_api_key(&config.server.api_key)
+    {
+        return Ok(());
+    }
+    Err(format!(
+        "隧道将公开 API 服务器（端口 {}），当前 API Key 强度不足。\
+         请先在设置中重新生成 API Key，或关闭 gateway.tunnel.require_strong_api_key",
+        config.server.port
+    ))
 }
 
 fn build_local_url(tunnel: &GatewayTunnelConfig) -> String {
@@ -419,6 +480,9 @@ fn resolve_public_base_url(tunnel: &GatewayTunnelConfig) -> Option<String> {
         .filter(|v| !v.is_empty())
         .map(strip_trailing_slash)
         .or_else(|| {
+            if normalize_provider(&tunnel.provider) == "ngrok" {
+                return tunnel_ngrok::configured_public_url(tunnel);
+            }
             tunnel
                 .cloudflare
                 .dns_name
@@ -663,7 +727,8 @@ fn as_str_lossy<T: AsRef<OsStr>>(value: T) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::is_manual_stop_error;
+    use super::{ensure_tunnel_api_key, is_manual_stop_error};
+    use lime_core::config::{generate_secure_api_key, Config};
 
     #[test]
     fn manual_stop_error_marker_detected() {
@@ -671,6 +736,21 @@ mod tests {
         assert!(is_manual_stop_error(Some("xxx tunnel 已手动停止 yyy")));
     }
 
+    #[test]
+    fn weak_api_key_rejected_when_tunnel_exposes_server() {
+        let mut config = Config::default();
+        config.server.api_key = "weak".to_string();
+        config.gateway.tunnel.local_port = config.server.port;
+        assert!(ensure_tunnel_api_key(&config).is_err());
+
+        config.server.api_key = generate_secure_api_key();
+        assert!(ensure_tunnel_api_key(&config).is_ok());
+
+        config.server.api_key = "weak".to_string();
+        config.gateway.tunnel.local_port = config.server.port.wrapping_add(1);
+        assert!(ensure_tunnel_api_key(&config).is_ok());
+    }
+
     #[test]
     fn manual_stop_error_marker_not_detected() {
         assert!(!is_manual_stop_error(None));
@@ -0,0 +1,124 @@
+//! ngrok 隧道支持
+//!
+//! 使用用户提供的 authtoken 启动 `ngrok http`，authtoken 通过环境变量传入，
+//! 不出现在进程参数中；公网地址从 JSON 日志的 `started tunnel` 事件中解析。
+
+use lime_core::config::GatewayTunnelConfig;
+
+/// ngrok 启动参数
+pub(crate) struct NgrokLaunch {
+    pub args: Vec<String>,
+    pub envs: Vec<(String, String)>,
+    pub preview: String,
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// 配置的固定域名对应的公网地址
+pub(crate) fn configured_public_url(tunnel: &GatewayTunnelConfig) -> Option<String> {
+    non_empty(tunnel.ngrok.domain.as_deref()).map(|domain| {
+        let domain = domain.trim_start_matches("https://").trim_end_matches('/');
+        format!("https://{domain}")
+    })
+}
+
+/// 是否已配置 authtoken
+pub(crate) fn has_authtoken(tunnel: &GatewayTunnelConfig) -> bool {
+    non_empty(tunnel.ngrok.authtoken.as_deref()).is_some()
+}
+
+pub(crate) fn build_ngrok_run_args(
+    tunnel: &GatewayTunnelConfig,
+    local_url: &str,
+) -> Result<NgrokLaunch, String> {
+    let authtoken = non_empty(tunnel.ngrok.authtoken.as_deref())
+        .ok_or_else(|| "缺少 ngrok.authtoken".to_string())?;
+
+    let mut args = vec![
+        "http".to_string(),
+        local_url.to_string(),
+        "--log".to_string(),
+        "stdout".to_string(),
+        "--log-format".to_string(),
+        "json".to_string(),
+    ];
+    let mut preview = format!("ngrok http {local_url} --log stdout --log-format json");
+    if let Some(domain) = non_empty(tunnel.ngrok.domain.as_deref()) {
+        let domain = domain.trim_start_matches("https://").trim_end_matches('/');
+        args.push("--domain".to_string());
+        args.push(domain.to_string());
+        preview.push_str(&format!(" --domain {domain}"));
+    }
+
+    Ok(NgrokLaunch {
+        args,
+        envs: vec![("NGROK_AUTHTOKEN".to_string(), authtoken.to_string())],
+        preview: format!("NGROK_AUTHTOKEN=**** {preview}"),
+    })
+}
+
+/// 从 ngrok JSON 日志行中解析公网地址
+pub(crate) fn parse_public_url(line: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(line.trim()).ok()?;
+    if value.get("msg").and_then(|v| v.as_str()) != Some("started tunnel") {
+        return None;
+    }
+    value
+        .get("url")
+        .and_then(|v| v.as_str())
+        .filter(|url| url.starts_with("https://") || url.starts_with("http://"))
+        .map(|url| url.trim_end_matches('/').to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tunnel(domain: Option<&str>) -> GatewayTunnelConfig {
+        let mut tunnel = GatewayTunnelConfig::default();
+        tunnel.provider = "ngrok".to_string();
+        tunnel.ngrok.authtoken = Some("secret-token".to_string());
+        tunnel.ngrok.domain = domain.map(str::to_string);
+        tunnel
+    }
+
+    #[test]
+    fn should_pass_authtoken_via_env_only() {
+        let launch = build_ngrok_run_args(&tunnel(Some("lime.ngrok.app")), "http://127.0.0.1:8999")
+            .expect("应生成启动参数");
+
+        assert!(!launch.args.iter().any(|arg| arg.contains("secret-token")));
+        assert!(!launch.preview.contains("secret-token"));
+        assert_eq!(launch.envs[0].1, "secret-token");
+        assert!(launch
+            .args
+            .ends_with(&["--domain".to_string(), "lime.ngrok.app".to_string()]));
+        assert_eq!(
+            configured_public_url(&tunnel(Some("https://lime.ngrok.app/"))).as_deref(),
+            Some("https://lime.ngrok.app")
+        );
+    }
+
+    #[test]
+    fn should_require_authtoken() {
+        let mut config = tunnel(None);
+        config.ngrok.authtoken = Some("  ".to_string());
+        assert!(build_ngrok_run_args(&config, "http://127.0.0.1:8999").is_err());
+    }
+
+    #[test]
+    fn should_parse_started_tunnel_url() {
+        let line = r#"{"lvl":"info","msg":"started tunnel","name":"command_line","url":"https://abcd.ngrok-free.app"}"#;
+        assert_eq!(
+            parse_public_url(line).as_deref(),
+            Some("https://abcd.ngrok-free.app")
+        );
+        assert_eq!(
+            parse_public_url(r#"{"msg":"client session established"}"#),
+            None
+        );
+        assert_eq!(parse_public_url("not json"), None);
+    }
+}
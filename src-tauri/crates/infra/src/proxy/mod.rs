@@ -1,9 +1,12 @@
 //! 代理模块
 //!
 //! 提供 Per-Key 代理支持，允许为每个凭证配置独立的代理设置
+//! （HTTP 客户端与 WebSocket 等长连接的 TCP 隧道）
 
 mod client_factory;
 #[cfg(test)]
 mod tests;
+mod tunnel;
 
 pub use client_factory::{ProxyClientFactory, ProxyError, ProxyProtocol};
+pub use tunnel::connect_via_proxy;
@@ -0,0 +1,296 @@
+//! 经代理建立 TCP 隧道
+//!
+//! reqwest 的代理设置只作用于 HTTP 请求，WebSocket 等长连接上游需要自行建立隧道，
+//! 才能与 HTTP 请求一样遵循 Per-Key / 全局代理设置：
+//! - `socks5://[user:pass@]host:port`：SOCKS5 CONNECT，目标主机名交由代理解析
+//! - `http://[user:pass@]host:port`：HTTP CONNECT
+//!
+//! 隧道建立后返回的 `TcpStream` 可直接交给 WebSocket 客户端完成 TLS 与握手。
+//! `https://` 代理（到代理本身的 TLS）与 SOCKS5 UDP ASSOCIATE 暂不支持。
+
+use base64::Engine;
+use reqwest::Url;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use super::{ProxyClientFactory, ProxyError, ProxyProtocol};
+
+/// HTTP CONNECT 响应头的最大长度
+const MAX_CONNECT_RESPONSE_BYTES: usize = 8 * 1024;
+
+const SOCKS_VERSION: u8 = 0x05;
+const SOCKS_NO_AUTH: u8 = 0x00;
+const SOCKS_USER_PASS: u8 = 0x02;
+
+fn io_error(e: std::io::Error) -> ProxyError {
+    ProxyError::ConfigError(format!("代理连接失败: {e}"))
+}
+
+fn credentials(url: &Url) -> Option<(String, String)> {
+    if url.username().is_empty() {
+        return None;
+    }
+    let decode = |value: &str| {
+        urlencoding::decode(value)
+            .map(|v| v.into_owned())
+            .unwrap_or_else(|_| value.to_string())
+    };
+    Some((
+        decode(url.username()),
+        decode(url.password().unwrap_or_default()),
+    ))
+}
+
+async fn socks5_handshake(
+    stream: &mut TcpStream,
+    auth: Option<(String, String)>,
+    host: &str,
+    port: u16,
+) -> Result<(), ProxyError> {
+    let methods: &[u8] = if auth.is_some() {
+        &[SOCKS_NO_AUTH, SOCKS_USER_PASS]
+    } else {
+        &[SOCKS_NO_AUTH]
+    };
+    let mut greeting = vec![SOCKS_VERSION, methods.len() as u8];
+    greeting.extend_from_slice(methods);
+    stream.write_all(&greeting).await.map_err(io_error)?;
+
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await.map_err(io_error)?;
+    match (reply[1], &auth) {
+        (SOCKS_NO_AUTH, _) => {}
+        (SOCKS_USER_PASS, Some((user, pass))) => {
+            if user.len() > 255 || pass.len() > 255 {
+                return Err(ProxyError::ConfigError(
+                    "SOCKS5 用户名或密码过长".to_string(),
+                ));
+            }
+            let mut request = vec![0x01, user.len() as u8];
+            request.extend_from_slice(user.as_bytes());
+            request.push(pass.len() as u8);
+            request.extend_from_slice(pass.as_bytes());
+            stream.write_all(&request).await.map_err(io_error)?;
+            let mut status = [0u8; 2];
+            stream.read_exact(&mut status).await.map_err(io_error)?;
+            if status[1] != 0x00 {
+                return Err(ProxyError::ConfigError("SOCKS5 代理认证失败".to_string()));
+            }
+        }
+        _ => {
+            return Err(ProxyError::ConfigError(
+                "SOCKS5 代理不接受可用的认证方式".to_string(),
+            ));
+        }
+    }
+
+    if host.len() > 255 {
+        return Err(ProxyError::InvalidUrl(format!("目标主机名过长: {host}")));
+    }
+    let mut request = vec![SOCKS_VERSION, 0x01, 0x00, 0x03, host.len() as u8];
+    request.extend_from_slice(host.as_bytes());
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await.map_err(io_error)?;
+
+    let mut head = [0u8; 4];
+    stream.read_exact(&mut head).await.map_err(io_error)?;
+    if head[1] != 0x00 {
+        return Err(ProxyError::ConfigError(format!(
+            "SOCKS5 代理拒绝连接 {host}:{port}（错误码 {}）",
+            head[1]
+        )));
+    }
+    let address_len = match head[3] {
+        0x01 => 4,
+        0x04 => 16,
+        0x03 => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len).await.map_err(io_error)?;
+            len[0] as usize
+        }
+        other => {
+            return Err(ProxyError::ConfigError(format!(
+                "SOCKS5 代理返回未知的地址类型: {other}"
+            )))
+        }
+    };
+    let mut bound = vec![0u8; address_len + 2];
+    stream.read_exact(&mut bound).await.map_err(io_error)?;
+    Ok(())
+}
+
+async fn http_connect(
+    stream: &mut TcpStream,
+    auth: Option<(String, String)>,
+    host: &str,
+    port: u16,
+) -> Result<(), ProxyError> {
+    let target = format!("{host}:{port}");
+    let mut request = format!("CONNECT {target} HTTP/1.1\r\nHost: {target}\r\n");
+    if let Some((user, pass)) = auth {
+        let token = base64::engine::general_purpose::STANDARD.encode(format!("{user}:{pass}"));
+        request.push_str(&format!("Proxy-Authorization: Basic {token}\r\n"));
+    }
+    request.push_str("\r\n");
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(io_error)?;
+
+    // 逐字节读取，避免读入隧道中属于目标服务器的数据
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_CONNECT_RESPONSE_BYTES {
+            return Err(ProxyError::ConfigError("HTTP 代理响应头过长".to_string()));
+        }
+        let byte = stream.read_u8().await.map_err(io_error)?;
+        response.push(byte);
+    }
+    let status_line = String::from_utf8_lossy(&response);
+    let status_line = status_line.lines().next().unwrap_or_default();
+    let status = status_line.split_whitespace().nth(1).unwrap_or_default();
+    if status != "200" {
+        return Err(ProxyError::ConfigError(format!(
+            "HTTP 代理拒绝连接 {target}: {status_line}"
+        )));
+    }
+    Ok(())
+}
+
+/// 经指定代理建立到 `host:port` 的 TCP 隧道
+pub async fn connect_via_proxy(
+    proxy_url: &str,
+    host: &str,
+    port: u16,
+) -> Result<TcpStream, ProxyError> {
+    let protocol = ProxyClientFactory::parse_proxy_url(proxy_url)?;
+    let url = Url::parse(proxy_url).map_err(|e| ProxyError::InvalidUrl(e.to_string()))?;
+    let proxy_host = url
+        .host_str()
+        .ok_or_else(|| ProxyError::InvalidUrl(proxy_url.to_string()))?;
+    let default_port = match protocol {
+        ProxyProtocol::Socks5 => 1080,
+        ProxyProtocol::Http => 80,
+        ProxyProtocol::Https => {
+            return Err(ProxyError::UnsupportedProtocol(format!(
+                "长连接隧道暂不支持 HTTPS 代理: {proxy_url}"
+            )))
+        }
+    };
+    let proxy_port = url.port().unwrap_or(default_port);
+    let mut stream = TcpStream::connect((proxy_host, proxy_port))
+        .await
+        .map_err(io_error)?;
+    let _ = stream.set_nodelay(true);
+
+    let auth = credentials(&url);
+    match protocol {
+        ProxyProtocol::Socks5 => socks5_handshake(&mut stream, auth, host, port).await?,
+        _ => http_connect(&mut stream, auth, host, port).await?,
+    }
+    Ok(stream)
+}
+
+impl ProxyClientFactory {
+    /// 按与 HTTP 客户端相同的代理选择逻辑建立 TCP 连接（Per-Key 代理优先，其次全局代理，否则直连）
+    pub async fn connect_tcp(
+        &self,
+        per_key_proxy: Option<&str>,
+        host: &str,
+        port: u16,
+    ) -> Result<TcpStream, ProxyError> {
+        match self.select_proxy(per_key_proxy) {
+            Some(proxy_url) => connect_via_proxy(proxy_url, host, port).await,
+            None => TcpStream::connect((host, port)).await.map_err(io_error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// 只接受用户名密码认证的最小 SOCKS5 代理，隧道建立后回显数据
+    async fn fake_socks5(listener: TcpListener) -> (String, u16) {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut head = [0u8; 2];
+        socket.read_exact(&mut head).await.unwrap();
+        let mut methods = vec![0u8; head[1] as usize];
+        socket.read_exact(&mut methods).await.unwrap();
+        assert!(methods.contains(&SOCKS_USER_PASS));
+        socket.write_all(&[0x05, SOCKS_USER_PASS]).await.unwrap();
+
+        let mut version = [0u8; 2];
+        socket.read_exact(&mut version).await.unwrap();
+        let mut user = vec![0u8; version[1] as usize];
+        socket.read_exact(&mut user).await.unwrap();
+        let pass_len = socket.read_u8().await.unwrap();
+        let mut pass = vec![0u8; pass_len as usize];
+        socket.read_exact(&mut pass).await.unwrap();
+        assert_eq!(
+            (user.as_slice(), pass.as_slice()),
+            (&b"lime"[..], &b"p@ss"[..])
+        );
+        socket.write_all(&[0x01, 0x00]).await.unwrap();
+
+        let mut request = [0u8; 5];
+        socket.read_exact(&mut request).await.unwrap();
+        assert_eq!(request[3], 0x03);
+        let mut host = vec![0u8; request[4] as usize];
+        socket.read_exact(&mut host).await.unwrap();
+        let port = socket.read_u16().await.unwrap();
+        socket
+            .write_all(&[0x05, 0x00, 0x00, 0x01, 127, 0, 0, 1, 0, 0])
+            .await
+            .unwrap();
+
+        let mut buf = [0u8; 4];
+        socket.read_exact(&mut buf).await.unwrap();
+        socket.write_all(&buf).await.unwrap();
+        (String::from_utf8(host).unwrap(), port)
+    }
+
+    #[tokio::test]
+    async fn test_socks5_tunnel_with_auth() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = format!("socks5://lime:p%40ss@{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(fake_socks5(listener));
+
+        let mut stream = connect_via_proxy(&proxy, "generativelanguage.googleapis.com", 443)
+            .await
+            .unwrap();
+        stream.write_all(b"ping").await.unwrap();
+        let mut echo = [0u8; 4];
+        stream.read_exact(&mut echo).await.unwrap();
+        assert_eq!(&echo, b"ping");
+        assert_eq!(
+            server.await.unwrap(),
+            ("generativelanguage.googleapis.com".to_string(), 443)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_connect_rejected() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await.unwrap();
+            socket
+                .write_all(b"HTTP/1.1 407 Proxy Authentication Required\r\n\r\n")
+                .await
+                .unwrap();
+        });
+
+        let error = connect_via_proxy(&proxy, "api.openai.com", 443)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("407"));
+        assert!(matches!(
+            connect_via_proxy("https://proxy.example.com", "api.openai.com", 443).await,
+            Err(ProxyError::UnsupportedProtocol(_))
+        ));
+    }
+}
@@ -3,17 +3,21 @@
 //! 提供请求日志记录、统计聚合和 Token 追踪功能
 
 mod logger;
+mod rolling;
 mod stats;
 mod tokens;
 mod types;
 
 pub use logger::{LogRotationConfig, LoggerError, RequestLogger};
+pub use rolling::{rolling_snapshot, RATE_WINDOW_SECS, ROLLING_WINDOW_SECS};
 pub use stats::StatsAggregator;
 pub use tokens::{
     ModelTokenStats, PeriodTokenStats, ProviderTokenStats, TokenSource, TokenStatsSummary,
     TokenTracker, TokenUsageRecord,
 };
-pub use types::{ModelStats, ProviderStats, RequestLog, RequestStatus, StatsSummary, TimeRange};
+pub use types::{
+    MaxTokensClamp, ModelStats, ProviderStats, RequestLog, RequestStatus, StatsSummary, TimeRange,
+};
 
 #[cfg(test)]
 mod tests;
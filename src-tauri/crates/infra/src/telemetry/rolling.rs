@@ -0,0 +1,177 @@
+//! 滚动统计
+//!
+//! 按最近一小时的请求日志与 Token 记录预计算前端仪表盘所需的聚合值
+//! （每分钟请求数、每小时 Token 数、失败率、热门模型、凭证占比），
+//! 由服务器定时通过类型化事件通道推送。
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use lime_core::app_events::{CredentialShare, ModelShare, StatsSnapshot};
+
+use super::tokens::TokenUsageRecord;
+use super::types::{RequestLog, RequestStatus};
+
+/// 统计窗口（秒）
+pub const ROLLING_WINDOW_SECS: i64 = 3600;
+
+/// 计算每分钟请求数使用的短窗口（秒）
+pub const RATE_WINDOW_SECS: i64 = 300;
+
+/// 快照中最多保留的模型数
+const TOP_MODELS: usize = 5;
+
+fn share(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+/// 计算截至 `now` 的滚动统计快照
+///
+/// 重试中的请求不计数；窗口外的日志与 Token 记录会被忽略，调用方可直接传入全部记录。
+pub fn rolling_snapshot(
+    logs: &[RequestLog],
+    tokens: &[TokenUsageRecord],
+    now: DateTime<Utc>,
+) -> StatsSnapshot {
+    let window_start = now - Duration::seconds(ROLLING_WINDOW_SECS);
+    let rate_start = now - Duration::seconds(RATE_WINDOW_SECS);
+
+    let mut requests = 0u64;
+    let mut failed = 0u64;
+    let mut recent = 0u64;
+    let mut models: HashMap<&str, (u64, u64)> = HashMap::new();
+    let mut credentials: HashMap<&str, u64> = HashMap::new();
+    for log in logs
+        .iter()
+        .filter(|l| l.timestamp >= window_start && l.timestamp <= now)
+        .filter(|l| l.status != RequestStatus::Retrying)
+    {
+        requests += 1;
+        if matches!(log.status, RequestStatus::Failed | RequestStatus::Timeout) {
+            failed += 1;
+        }
+        if log.timestamp >= rate_start {
+            recent += 1;
+        }
+        models.entry(log.model.as_str()).or_default().0 += 1;
+        if let Some(credential_id) = &log.credential_id {
+            *credentials.entry(credential_id.as_str()).or_default() += 1;
+        }
+    }
+
+    let mut tokens_per_hour = 0u64;
+    for record in tokens
+        .iter()
+        .filter(|r| r.timestamp >= window_start && r.timestamp <= now)
+    {
+        tokens_per_hour += u64::from(record.total_tokens);
+        if let Some(entry) = models.get_mut(record.model.as_str()) {
+            entry.1 += u64::from(record.total_tokens);
+        }
+    }
+
+    let mut top_models: Vec<ModelShare> = models
+        .into_iter()
+        .map(|(model, (count, tokens))| ModelShare {
+            model: model.to_string(),
+            requests: count,
+            tokens,
+            share: share(count, requests),
+        })
+        .collect();
+    top_models.sort_by(|a, b| b.requests.cmp(&a.requests).then(a.model.cmp(&b.model)));
+    top_models.truncate(TOP_MODELS);
+
+    let mut credential_share: Vec<CredentialShare> = credentials
+        .into_iter()
+        .map(|(credential_id, count)| CredentialShare {
+            credential_id: credential_id.to_string(),
+            requests: count,
+            share: share(count, requests),
+        })
+        .collect();
+    credential_share.sort_by(|a, b| {
+        b.requests
+            .cmp(&a.requests)
+            .then(a.credential_id.cmp(&b.credential_id))
+    });
+
+    StatsSnapshot {
+        rate_window_secs: RATE_WINDOW_SECS as u64,
+        requests_per_min: recent as f64 * 60.0 / RATE_WINDOW_SECS as f64,
+        window_secs: ROLLING_WINDOW_SECS as u64,
+        requests,
+        failed,
+        error_rate: share(failed, requests),
+        tokens_per_hour,
+        top_models,
+        credential_share,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::TokenSource;
+    use lime_core::ProviderType;
+
+    fn log(
+        minutes_ago: i64,
+        model: &str,
+        status: RequestStatus,
+        credential: &str,
+        now: DateTime<Utc>,
+    ) -> RequestLog {
+        let mut log = RequestLog::new(
+            uuid::Uuid::new_v4().to_string(),
+            ProviderType::Kiro,
+            model.to_string(),
+            false,
+        );
+        log.timestamp = now - Duration::minutes(minutes_ago);
+        log.status = status;
+        log.set_credential_id(credential.to_string());
+        log
+    }
+
+    #[test]
+    fn test_rolling_snapshot_aggregates_window() {
+        let now = Utc::now();
+        let logs = vec![
+            log(1, "claude-sonnet-4-5", RequestStatus::Success, "a", now),
+            log(2, "claude-sonnet-4-5", RequestStatus::Failed, "a", now),
+            log(30, "gpt-4o", RequestStatus::Success, "b", now),
+            log(40, "gpt-4o", RequestStatus::Retrying, "b", now),
+            // 窗口外
+            log(90, "gpt-4o", RequestStatus::Success, "b", now),
+        ];
+        let mut record = TokenUsageRecord::new(
+            "t1".to_string(),
+            ProviderType::Kiro,
+            "claude-sonnet-4-5".to_string(),
+            100,
+            50,
+            TokenSource::Actual,
+        );
+        record.timestamp = now - Duration::minutes(1);
+
+        let snapshot = rolling_snapshot(&logs, &[record], now);
+        assert_eq!(snapshot.requests, 3);
+        assert_eq!(snapshot.failed, 1);
+        assert!((snapshot.requests_per_min - 0.4).abs() < f64::EPSILON);
+        assert_eq!(snapshot.tokens_per_hour, 150);
+        assert_eq!(snapshot.top_models[0].model, "claude-sonnet-4-5");
+        assert_eq!(snapshot.top_models[0].tokens, 150);
+        assert_eq!(snapshot.credential_share[0].credential_id, "a");
+        assert!((snapshot.credential_share[1].share - 1.0 / 3.0).abs() < 1e-9);
+
+        let empty = rolling_snapshot(&[], &[], now);
+        assert_eq!(empty.error_rate, 0.0);
+        assert!(empty.top_models.is_empty());
+    }
+}
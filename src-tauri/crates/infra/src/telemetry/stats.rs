@@ -3,7 +3,7 @@
 //! 提供请求统计的聚合、分组和查询功能
 
 use super::types::{ModelStats, ProviderStats, RequestLog, RequestStatus, StatsSummary, TimeRange};
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use lime_core::ProviderType;
 use parking_lot::RwLock;
 use std::collections::{HashMap, VecDeque};
@@ -84,6 +84,16 @@ impl StatsAggregator {
         }
     }
 
+    /// 获取指定时间之后的日志
+    pub fn get_since(&self, start: DateTime<Utc>) -> Vec<RequestLog> {
+        self.logs
+            .read()
+            .iter()
+            .filter(|l| l.timestamp >= start)
+            .cloned()
+            .collect()
+    }
+
     /// 获取所有日志
     pub fn get_all(&self) -> Vec<RequestLog> {
         self.logs.read().iter().cloned().collect()
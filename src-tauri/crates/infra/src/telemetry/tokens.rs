@@ -5,6 +5,7 @@
 #![allow(dead_code)]
 
 use chrono::{DateTime, Duration, Utc};
+use lime_core::models::NormalizedUsage;
 use lime_core::ProviderType;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
@@ -31,6 +32,12 @@ pub struct TokenUsageRecord {
     pub source: TokenSource,
     /// 关联的请求 ID
     pub request_id: Option<String>,
+    /// 命中缓存的输入 Token 数
+    #[serde(default)]
+    pub cached_tokens: u32,
+    /// 推理 Token 数
+    #[serde(default)]
+    pub reasoning_tokens: u32,
 }
 
 impl TokenUsageRecord {
@@ -53,6 +60,35 @@ impl TokenUsageRecord {
             total_tokens: input_tokens + output_tokens,
             source,
             request_id: None,
+            cached_tokens: 0,
+            reasoning_tokens: 0,
+        }
+    }
+
+    /// 从规范化用量创建记录
+    pub fn from_usage(
+        id: String,
+        provider: ProviderType,
+        model: String,
+        usage: &NormalizedUsage,
+    ) -> Self {
+        let source = if usage.estimated {
+            TokenSource::Estimated
+        } else {
+            TokenSource::Actual
+        };
+        Self {
+            total_tokens: usage.total_tokens,
+            cached_tokens: usage.cached_tokens,
+            reasoning_tokens: usage.reasoning_tokens,
+            ..Self::new(
+                id,
+                provider,
+                model,
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                source,
+            )
         }
     }
 
@@ -91,6 +127,12 @@ pub struct TokenStatsSummary {
     pub total_output_tokens: u64,
     /// 总 Token 数
     pub total_tokens: u64,
+    /// 总缓存命中 Token 数
+    #[serde(default)]
+    pub total_cached_tokens: u64,
+    /// 总推理 Token 数
+    #[serde(default)]
+    pub total_reasoning_tokens: u64,
     /// 记录数量
     pub record_count: u64,
     /// 实际值记录数
@@ -114,6 +156,8 @@ impl TokenStatsSummary {
         let total_input_tokens: u64 = records.iter().map(|r| r.input_tokens as u64).sum();
         let total_output_tokens: u64 = records.iter().map(|r| r.output_tokens as u64).sum();
         let total_tokens = total_input_tokens + total_output_tokens;
+        let total_cached_tokens: u64 = records.iter().map(|r| r.cached_tokens as u64).sum();
+        let total_reasoning_tokens: u64 = records.iter().map(|r| r.reasoning_tokens as u64).sum();
         let actual_count = records
             .iter()
             .filter(|r| r.source == TokenSource::Actual)
@@ -127,6 +171,8 @@ impl TokenStatsSummary {
             total_input_tokens,
             total_output_tokens,
             total_tokens,
+            total_cached_tokens,
+            total_reasoning_tokens,
             record_count,
             actual_count,
             estimated_count,
@@ -67,6 +67,18 @@ pub struct RequestLog {
     pub credential_id: Option<String>,
     /// 重试次数
     pub retry_count: u32,
+    /// `max_tokens` 被输出上限收紧的记录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens_clamp: Option<MaxTokensClamp>,
+}
+
+/// `max_tokens` 收紧记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaxTokensClamp {
+    /// 客户端请求的值（未指定时为 None）
+    pub requested: Option<u32>,
+    /// 实际使用的上限
+    pub limit: u32,
 }
 
 impl RequestLog {
@@ -87,6 +99,7 @@ impl RequestLog {
             is_streaming,
             credential_id: None,
             retry_count: 0,
+            max_tokens_clamp: None,
         }
     }
 
@@ -0,0 +1,272 @@
+//! 按模型族选择的分词器
+//!
+//! 内置 tiktoken 的 `cl100k_base` / `o200k_base` / `p50k_base`、基于 `cl100k_base` 的 Claude
+//! 近似计数，以及无需词表的字符估算。未内置 Gemini 的 SentencePiece 词表，Gemini 仍按字符估算
+//! （CJK 约 1 Token/字，其他约 4 字节 1 Token）。分词器按 `server.tokenizer` 配置与模型名选择，
+//! 预算检查、规模路由、上下文截断、用量估算与计数接口都经由本模块计数，保证口径一致。
+//!
+//! tiktoken 词表在首次使用时加载；加载失败时退回字符估算。
+
+use std::sync::{OnceLock, RwLock};
+
+use lime_core::config::{TokenizerKind, TokenizerSettings};
+use lime_core::models::injection_types::pattern_matches;
+use serde_json::Value;
+use tiktoken_rs::CoreBPE;
+
+/// Claude 分词器相对 `cl100k_base` 的上浮比例
+const CLAUDE_FACTOR: f64 = 1.1;
+
+/// JSON 中每个对象（消息、内容块、工具定义）的格式开销
+const TOKENS_PER_OBJECT: usize = 3;
+
+fn settings() -> &'static RwLock<TokenizerSettings> {
+    static SETTINGS: OnceLock<RwLock<TokenizerSettings>> = OnceLock::new();
+    SETTINGS.get_or_init(Default::default)
+}
+
+/// 应用配置（启动与热重载时调用）
+pub fn configure(config: &TokenizerSettings) {
+    *settings().write().unwrap_or_else(|e| e.into_inner()) = config.clone();
+}
+
+/// 内置的模型族识别
+fn builtin_family(model: &str) -> Option<TokenizerKind> {
+    // 去掉 `openai/`、`models/` 等前缀
+    let name = model
+        .rsplit('/')
+        .next()
+        .unwrap_or(model)
+        .to_ascii_lowercase();
+    let name = name.as_str();
+    if name.starts_with("gpt-4o")
+        || name.starts_with("gpt-4.1")
+        || name.starts_with("gpt-4.5")
+        || name.starts_with("gpt-5")
+        || name.starts_with("chatgpt-")
+        || ["o1", "o3", "o4"]
+            .iter()
+            .any(|prefix| name == *prefix || name.starts_with(&format!("{prefix}-")))
+    {
+        Some(TokenizerKind::O200k)
+    } else if name.starts_with("gpt-4")
+        || name.starts_with("gpt-3.5")
+        || name.starts_with("text-embedding-")
+    {
+        Some(TokenizerKind::Cl100k)
+    } else if name.starts_with("text-davinci") || name.starts_with("code-") {
+        Some(TokenizerKind::P50k)
+    } else if name.starts_with("gemini") || name.starts_with("gemma") {
+        Some(TokenizerKind::Gemini)
+    } else if name.starts_with("claude") {
+        Some(TokenizerKind::Claude)
+    } else {
+        None
+    }
+}
+
+/// 选择模型使用的分词器
+pub fn resolve(model: Option<&str>) -> TokenizerKind {
+    resolve_with(&settings().read().unwrap_or_else(|e| e.into_inner()), model)
+}
+
+/// 按指定配置选择模型使用的分词器
+pub fn resolve_with(settings: &TokenizerSettings, model: Option<&str>) -> TokenizerKind {
+    let Some(model) = model.map(str::trim).filter(|m| !m.is_empty()) else {
+        return settings.default;
+    };
+    settings
+        .families
+        .iter()
+        .find(|rule| rule.models.iter().any(|p| pattern_matches(p, model)))
+        .map(|rule| rule.tokenizer)
+        .or_else(|| builtin_family(model))
+        .unwrap_or(settings.default)
+}
+
+fn load<E: std::fmt::Display>(kind: TokenizerKind, result: Result<CoreBPE, E>) -> Option<CoreBPE> {
+    match result {
+        Ok(bpe) => Some(bpe),
+        Err(e) => {
+            tracing::warn!("[TOKENIZER] 加载 {:?} 词表失败，改用字符估算: {}", kind, e);
+            None
+        }
+    }
+}
+
+fn bpe(kind: TokenizerKind) -> Option<&'static CoreBPE> {
+    static CL100K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+    static O200K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+    static P50K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+    match kind {
+        TokenizerKind::Cl100k | TokenizerKind::Claude => CL100K
+            .get_or_init(|| load(kind, tiktoken_rs::cl100k_base()))
+            .as_ref(),
+        TokenizerKind::O200k => O200K
+            .get_or_init(|| load(kind, tiktoken_rs::o200k_base()))
+            .as_ref(),
+        TokenizerKind::P50k => P50K
+            .get_or_init(|| load(kind, tiktoken_rs::p50k_base()))
+            .as_ref(),
+        TokenizerKind::Heuristic | TokenizerKind::Gemini => None,
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{4E00}'..='\u{9FFF}' |
+        '\u{3400}'..='\u{4DBF}' |
+        '\u{F900}'..='\u{FAFF}' |
+        '\u{3000}'..='\u{303F}' |
+        '\u{FF00}'..='\u{FFEF}' |
+        '\u{3040}'..='\u{30FF}' |
+        '\u{AC00}'..='\u{D7AF}'
+    )
+}
+
+/// 按字符估算，`cjk` 为每个 CJK 字符的 Token 数，其余按 4 字节 1 Token
+fn estimate_chars(text: &str, cjk: f64) -> usize {
+    let (cjk_chars, cjk_bytes) = text
+        .chars()
+        .filter(|c| is_cjk(*c))
+        .fold((0usize, 0usize), |(n, bytes), c| {
+            (n + 1, bytes + c.len_utf8())
+        });
+    let other_bytes = text.len() - cjk_bytes;
+    (cjk_chars as f64 * cjk) as usize + (other_bytes as f64 * 0.25) as usize
+}
+
+/// 使用指定分词器计数
+pub fn count_with(kind: TokenizerKind, text: &str) -> usize {
+    if text.is_empty() {
+        return 0;
+    }
+    match (kind, bpe(kind)) {
+        (TokenizerKind::Claude, Some(bpe)) => {
+            (bpe.encode_with_special_tokens(text).len() as f64 * CLAUDE_FACTOR).ceil() as usize
+        }
+        (_, Some(bpe)) => bpe.encode_with_special_tokens(text).len(),
+        (TokenizerKind::Gemini, None) => estimate_chars(text, 1.0),
+        (_, None) => estimate_chars(text, 1.5),
+    }
+}
+
+/// 按模型选择分词器计数文本
+pub fn count_text(text: &str, model: Option<&str>) -> usize {
+    count_with(resolve(model), text)
+}
+
+fn count_value(kind: TokenizerKind, value: &Value) -> usize {
+    match value {
+        Value::String(s) => count_with(kind, s),
+        Value::Number(_) | Value::Bool(_) => 1,
+        Value::Null => 0,
+        Value::Array(items) => items.iter().map(|v| count_value(kind, v)).sum(),
+        Value::Object(map) => {
+            TOKENS_PER_OBJECT
+                + map
+                    .iter()
+                    .map(|(key, v)| match v {
+                        // 内联图片等 base64 数据不按文本计数
+                        Value::String(s) if key == "data" && s.len() > 1024 => 0,
+                        _ => count_value(kind, v),
+                    })
+                    .sum::<usize>()
+        }
+    }
+}
+
+/// 计数 JSON（消息列表、工具定义等）中的文本，每个对象附加少量格式开销
+pub fn count_json<T: serde::Serialize + ?Sized>(value: &T, model: Option<&str>) -> usize {
+    match serde_json::to_value(value) {
+        Ok(value) => count_value(resolve(model), &value),
+        Err(_) => 0,
+    }
+}
+
+/// 按模型选择分词器，把文本截断到不超过 `max_tokens` 个 Token（在字符边界上截断）
+pub fn truncate(text: &str, max_tokens: usize, model: Option<&str>) -> String {
+    let kind = resolve(model);
+    if count_with(kind, text) <= max_tokens {
+        return text.to_string();
+    }
+    let boundaries: Vec<usize> = text
+        .char_indices()
+        .map(|(idx, _)| idx)
+        .skip(1)
+        .chain(std::iter::once(text.len()))
+        .collect();
+    // 二分查找最长的、计数不超过上限的前缀
+    let (mut low, mut high) = (0usize, boundaries.len());
+    while low < high {
+        let mid = (low + high + 1) / 2;
+        if count_with(kind, &text[..boundaries[mid - 1]]) <= max_tokens {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    match low {
+        0 => String::new(),
+        n => text[..boundaries[n - 1]].to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lime_core::config::TokenizerFamilyRule;
+
+    #[test]
+    fn test_resolve_prefers_custom_families() {
+        assert_eq!(resolve(Some("gpt-4o-mini")), TokenizerKind::O200k);
+        assert_eq!(resolve(Some("openai/gpt-4-turbo")), TokenizerKind::Cl100k);
+        assert_eq!(resolve(Some("o3-mini")), TokenizerKind::O200k);
+        assert_eq!(resolve(Some("gemini-2.5-pro")), TokenizerKind::Gemini);
+        assert_eq!(resolve(Some("claude-sonnet-4-5")), TokenizerKind::Claude);
+        assert_eq!(builtin_family("omni-moderation"), None);
+
+        let settings = TokenizerSettings {
+            default: TokenizerKind::Heuristic,
+            families: vec![TokenizerFamilyRule {
+                models: vec!["claude-*-kiro".to_string()],
+                tokenizer: TokenizerKind::Cl100k,
+            }],
+        };
+        assert_eq!(
+            resolve_with(&settings, Some("claude-sonnet-4-kiro")),
+            TokenizerKind::Cl100k
+        );
+        assert_eq!(
+            resolve_with(&settings, Some("claude-sonnet-4")),
+            TokenizerKind::Claude
+        );
+        assert_eq!(resolve_with(&settings, None), TokenizerKind::Heuristic);
+    }
+
+    #[test]
+    fn test_count_and_truncate() {
+        assert_eq!(count_with(TokenizerKind::Heuristic, ""), 0);
+        assert_eq!(count_with(TokenizerKind::Gemini, "你好世界"), 4);
+        assert_eq!(count_with(TokenizerKind::Heuristic, "你好世界"), 6);
+        assert!(count_with(TokenizerKind::Cl100k, "Hello world") <= 3);
+        assert!(
+            count_with(
+                TokenizerKind::Claude,
+                "Hello world, this is a longer sentence."
+            ) >= count_with(
+                TokenizerKind::Cl100k,
+                "Hello world, this is a longer sentence."
+            )
+        );
+
+        let messages = serde_json::json!([{ "role": "user", "content": "hi" }]);
+        assert!(count_json(&messages, Some("unknown-model")) > TOKENS_PER_OBJECT);
+
+        let text = "你好世界".repeat(10);
+        let truncated = truncate(&text, 6, None);
+        assert!(count_text(&truncated, None) <= 6);
+        assert!(text.starts_with(&truncated) && !truncated.is_empty());
+        assert_eq!(truncate("short", 100, None), "short");
+    }
+}
@@ -5,22 +5,9 @@
 
 use serde::{Deserialize, Serialize};
 
-/// 判断字符是否为 CJK（中日韩）字符
-fn is_cjk(c: char) -> bool {
-    matches!(c,
-        '\u{4E00}'..='\u{9FFF}' |   // CJK Unified Ideographs
-        '\u{3400}'..='\u{4DBF}' |   // CJK Unified Ideographs Extension A
-        '\u{F900}'..='\u{FAFF}' |   // CJK Compatibility Ideographs
-        '\u{3000}'..='\u{303F}' |   // CJK Symbols and Punctuation
-        '\u{FF00}'..='\u{FFEF}'     // Halfwidth and Fullwidth Forms
-    )
-}
-
-/// 简单的 token 估算（中文约 1.5 token/字，英文约 0.75 token/word）
+/// 估算 token 数，按 `server.tokenizer` 的默认分词器计数
 pub fn estimate_tokens(text: &str) -> usize {
-    let cjk_chars = text.chars().filter(|c| is_cjk(*c)).count();
-    let non_cjk_len = text.len().saturating_sub(cjk_chars);
-    (cjk_chars as f64 * 1.5) as usize + (non_cjk_len as f64 * 0.25) as usize
+    lime_infra::tokenizer::count_text(text, None)
 }
 
 /// 摘要配置
@@ -363,20 +350,9 @@ pub fn microcompact(messages: &mut [serde_json::Value], max_tool_output_tokens:
     }
 }
 
-/// 将文本截断到大约指定的 token 数
+/// 将文本截断到不超过指定的 token 数
 fn truncate_to_tokens(text: &str, max_tokens: usize) -> String {
-    let mut current_tokens = 0.0f64;
-    let max = max_tokens as f64;
-    let mut last_valid_idx = 0;
-    for (idx, ch) in text.char_indices() {
-        let char_tokens = if is_cjk(ch) { 1.5 } else { 0.25 };
-        current_tokens += char_tokens;
-        if current_tokens >= max {
-            break;
-        }
-        last_valid_idx = idx + ch.len_utf8();
-    }
-    text[..last_valid_idx].to_string()
+    lime_infra::tokenizer::truncate(text, max_tokens, None)
 }
 
 /// 检查消息是否为工具结果
@@ -54,6 +54,12 @@ pub struct RequestProcessor {
     pub hint_router: Arc<RwLock<lime_core::router::HintRouter>>,
     /// 对话修剪器
     pub conversation_trimmer: Arc<crate::conversation_manager::ConversationTrimmer>,
+    /// 弃用模型表
+    pub deprecations: Arc<RwLock<lime_core::router::ModelDeprecations>>,
+    /// 规模路由器
+    pub size_router: Arc<RwLock<lime_core::router::SizeRouter>>,
+    /// 时间段路由器
+    pub time_window_router: Arc<RwLock<lime_core::router::TimeWindowRouter>>,
 }
 
 impl RequestProcessor {
@@ -86,6 +92,11 @@ impl RequestProcessor {
             conversation_trimmer: Arc::new(crate::conversation_manager::ConversationTrimmer::new(
                 crate::conversation_manager::TrimConfig::default(),
             )),
+            deprecations: Arc::new(RwLock::new(lime_core::router::ModelDeprecations::default())),
+            size_router: Arc::new(RwLock::new(lime_core::router::SizeRouter::default())),
+            time_window_router: Arc::new(RwLock::new(
+                lime_core::router::TimeWindowRouter::default(),
+            )),
         }
     }
 
@@ -107,6 +118,11 @@ impl RequestProcessor {
             conversation_trimmer: Arc::new(crate::conversation_manager::ConversationTrimmer::new(
                 crate::conversation_manager::TrimConfig::default(),
             )),
+            deprecations: Arc::new(RwLock::new(lime_core::router::ModelDeprecations::default())),
+            size_router: Arc::new(RwLock::new(lime_core::router::SizeRouter::default())),
+            time_window_router: Arc::new(RwLock::new(
+                lime_core::router::TimeWindowRouter::default(),
+            )),
         }
     }
 
@@ -141,6 +157,11 @@ impl RequestProcessor {
             conversation_trimmer: Arc::new(crate::conversation_manager::ConversationTrimmer::new(
                 crate::conversation_manager::TrimConfig::default(),
             )),
+            deprecations: Arc::new(RwLock::new(lime_core::router::ModelDeprecations::default())),
+            size_router: Arc::new(RwLock::new(lime_core::router::SizeRouter::default())),
+            time_window_router: Arc::new(RwLock::new(
+                lime_core::router::TimeWindowRouter::default(),
+            )),
         }
     }
 
@@ -150,6 +171,22 @@ impl RequestProcessor {
         mapper.resolve(model)
     }
 
+    /// 检查弃用模型：请求的模型或其别名目标已弃用时返回替代信息
+    pub async fn check_deprecation(
+        &self,
+        model: &str,
+    ) -> Option<lime_core::router::ModelDeprecation> {
+        let deprecations = self.deprecations.read().await;
+        if let Some(deprecation) = deprecations.check(model) {
+            return Some(deprecation);
+        }
+        let resolved = self.resolve_model(model).await;
+        if resolved == model {
+            return None;
+        }
+        deprecations.check(&resolved)
+    }
+
     /// 解析模型别名并更新请求上下文
     pub async fn resolve_model_for_context(&self, ctx: &mut RequestContext) -> String {
         let resolved = self.resolve_model(&ctx.original_model).await;
@@ -0,0 +1,478 @@
+//! Gemini 显式上下文缓存
+//!
+//! 请求中较大的静态前缀按内容哈希创建 Gemini `cachedContents`，之后相同前缀的请求改为
+//! 引用缓存（`cachedContent` 字段），缓存部分按缓存价计费，上游在 `usageMetadata.cachedContentTokenCount`
+//! 中返回命中的 Token 数，并由用量统计计入 `cached_tokens`。
+//!
+//! 静态前缀包括系统指令、工具声明与工具配置；客户端可以用请求头 [`HINT_HEADER`]
+//! 声明前几轮内容也属于静态前缀（如放在首条消息中的长文档），或关闭本次请求的缓存。
+//!
+//! 缓存按端点与 API Key 指纹隔离（缓存只对创建它的账号可见），在本地登记有效期，
+//! 到期前一分钟起视为失效并重新创建；上游报告缓存不存在时撤销登记。
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{OnceLock, RwLock};
+
+use chrono::{DateTime, Duration, Utc};
+use lime_core::config::ContextCacheSettings;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::har_capture::CaptureSend;
+use crate::upload_dedup::{content_hash, scope};
+
+/// 客户端提示请求头：`auto`（默认）、`off`，或数字 N 表示前 N 条内容也属于静态前缀
+pub const HINT_HEADER: &str = "x-lime-context-cache";
+
+/// 缓存到期前预留的余量（秒）
+const EXPIRY_MARGIN_SECS: i64 = 60;
+
+/// 估算 Token 数时每个 Token 对应的字符数
+const CHARS_PER_TOKEN: u64 = 4;
+
+/// 前缀中可缓存的顶层字段（兼容 snake_case）
+const PREFIX_FIELDS: &[(&str, &str)] = &[
+    ("systemInstruction", "system_instruction"),
+    ("tools", "tools"),
+    ("toolConfig", "tool_config"),
+];
+
+/// 客户端对本次请求的缓存提示
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheHint {
+    /// 自动检测：只缓存系统指令与工具声明，且估算 Token 数不低于 `min_tokens`
+    #[default]
+    Auto,
+    /// 本次请求不使用缓存
+    Off,
+    /// 前 N 条内容也属于静态前缀，不检查 `min_tokens`
+    Prefix(usize),
+}
+
+impl CacheHint {
+    /// 解析 [`HINT_HEADER`] 的值，无法识别时按自动处理
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("off") || value.eq_ignore_ascii_case("false") {
+            return Self::Off;
+        }
+        value.parse().map(Self::Prefix).unwrap_or_default()
+    }
+}
+
+/// 缓存统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextCacheStats {
+    /// 创建的缓存数
+    pub created: u64,
+    /// 复用已有缓存的请求数
+    pub reused: u64,
+    /// 创建失败、按原请求发送的次数
+    pub failed: u64,
+    /// 引用缓存的请求累计命中的 Token 数（按缓存价计费的部分）
+    pub cached_tokens: u64,
+}
+
+/// 已登记的缓存
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextCacheEntry {
+    /// 缓存资源名，如 `cachedContents/abc123`
+    pub name: String,
+    pub model: String,
+    /// 缓存内容的 Token 数
+    pub token_count: u64,
+    /// 复用次数
+    pub hits: u64,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// 当前状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextCacheStatus {
+    pub enabled: bool,
+    pub entries: Vec<ContextCacheEntry>,
+    pub stats: ContextCacheStats,
+}
+
+#[derive(Default)]
+struct CacheState {
+    settings: ContextCacheSettings,
+    /// 键为 `scope:哈希(模型 + 前缀)`
+    entries: HashMap<String, ContextCacheEntry>,
+}
+
+#[derive(Default)]
+struct Counters {
+    created: AtomicU64,
+    reused: AtomicU64,
+    failed: AtomicU64,
+    cached_tokens: AtomicU64,
+}
+
+fn state() -> &'static RwLock<CacheState> {
+    static STATE: OnceLock<RwLock<CacheState>> = OnceLock::new();
+    STATE.get_or_init(Default::default)
+}
+
+fn counters() -> &'static Counters {
+    static COUNTERS: OnceLock<Counters> = OnceLock::new();
+    COUNTERS.get_or_init(Default::default)
+}
+
+/// 应用配置（启动与热重载时调用）
+pub fn configure(settings: &ContextCacheSettings) {
+    state().write().unwrap_or_else(|e| e.into_inner()).settings = settings.clone();
+}
+
+pub fn is_enabled() -> bool {
+    state()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .settings
+        .enabled
+}
+
+pub fn status() -> ContextCacheStatus {
+    let now = Utc::now();
+    let guard = state().read().unwrap_or_else(|e| e.into_inner());
+    let mut entries: Vec<ContextCacheEntry> = guard
+        .entries
+        .values()
+        .filter(|entry| entry.expires_at > now)
+        .cloned()
+        .collect();
+    entries.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    let counters = counters();
+    ContextCacheStatus {
+        enabled: guard.settings.enabled,
+        entries,
+        stats: ContextCacheStats {
+            created: counters.created.load(Ordering::Relaxed),
+            reused: counters.reused.load(Ordering::Relaxed),
+            failed: counters.failed.load(Ordering::Relaxed),
+            cached_tokens: counters.cached_tokens.load(Ordering::Relaxed),
+        },
+    }
+}
+
+/// 清空本地登记（之后的请求会重新创建缓存，上游缓存到期后自动删除），返回清除的条目数
+pub fn clear() -> usize {
+    let mut guard = state().write().unwrap_or_else(|e| e.into_inner());
+    let cleared = guard.entries.len();
+    guard.entries.clear();
+    cleared
+}
+
+/// 撤销缓存登记（上游报告缓存不存在或已过期时调用）
+pub fn invalidate(name: &str) {
+    state()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .entries
+        .retain(|_, entry| entry.name != name);
+}
+
+/// 上游错误是否表示引用的缓存不可用
+pub fn is_cache_error(status: u16, body: &str) -> bool {
+    matches!(status, 400 | 403 | 404) && body.to_ascii_lowercase().contains("cachedcontent")
+}
+
+/// 估算 JSON 内容的 Token 数
+pub fn estimate_tokens(value: &Value) -> u64 {
+    value.to_string().chars().count() as u64 / CHARS_PER_TOKEN
+}
+
+/// 请求中的静态前缀
+#[derive(Debug, Clone, PartialEq)]
+struct StaticPrefix {
+    /// 创建缓存时使用的字段（系统指令、工具、前 N 条内容）
+    fields: Map<String, Value>,
+    /// 前缀包含的内容条数
+    contents: usize,
+}
+
+/// 按提示拆出静态前缀；没有可缓存内容或不满足最小 Token 数时返回 `None`
+fn split_prefix(body: &Value, hint: CacheHint, min_tokens: u64) -> Option<StaticPrefix> {
+    let object = body.as_object()?;
+    // 客户端自行管理缓存时不做处理
+    if object.contains_key("cachedContent") || object.contains_key("cached_content") {
+        return None;
+    }
+    let contents = object.get("contents").and_then(Value::as_array)?;
+    let prefix_contents = match hint {
+        CacheHint::Off => return None,
+        CacheHint::Auto => 0,
+        // 至少保留一条内容随请求发送
+        CacheHint::Prefix(n) => n.min(contents.len().saturating_sub(1)),
+    };
+
+    let mut fields = Map::new();
+    for (camel, snake) in PREFIX_FIELDS {
+        if let Some(value) = object.get(*camel).or_else(|| object.get(*snake)) {
+            fields.insert((*camel).to_string(), value.clone());
+        }
+    }
+    if prefix_contents > 0 {
+        fields.insert(
+            "contents".to_string(),
+            Value::Array(contents[..prefix_contents].to_vec()),
+        );
+    }
+    if fields.is_empty() {
+        return None;
+    }
+    if hint == CacheHint::Auto && estimate_tokens(&Value::Object(fields.clone())) < min_tokens {
+        return None;
+    }
+    Some(StaticPrefix {
+        fields,
+        contents: prefix_contents,
+    })
+}
+
+/// 把请求体中的静态前缀替换为缓存引用
+fn apply_cache_ref(body: &mut Value, name: &str, prefix_contents: usize) {
+    let Some(object) = body.as_object_mut() else {
+        return;
+    };
+    for (camel, snake) in PREFIX_FIELDS {
+        object.remove(*camel);
+        object.remove(*snake);
+    }
+    if let Some(Value::Array(contents)) = object.get_mut("contents") {
+        contents.drain(..prefix_contents.min(contents.len()));
+    }
+    object.insert("cachedContent".to_string(), Value::String(name.to_string()));
+}
+
+fn model_resource(model: &str) -> String {
+    if model.starts_with("models/") {
+        model.to_string()
+    } else {
+        format!("models/{model}")
+    }
+}
+
+/// 解析创建缓存的响应，返回登记条目
+fn parse_created(
+    body: &Value,
+    model: &str,
+    estimated_tokens: u64,
+    ttl_secs: u64,
+    now: DateTime<Utc>,
+) -> Result<ContextCacheEntry, String> {
+    let name = body["name"].as_str().ok_or("响应缺少 name")?.to_string();
+    let configured = now + Duration::seconds(ttl_secs as i64);
+    let expires_at = body["expireTime"]
+        .as_str()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc).min(configured))
+        .unwrap_or(configured);
+    Ok(ContextCacheEntry {
+        name,
+        model: model.to_string(),
+        token_count: body["usageMetadata"]["totalTokenCount"]
+            .as_u64()
+            .unwrap_or(estimated_tokens),
+        hits: 0,
+        created_at: now,
+        expires_at,
+    })
+}
+
+/// 调用 `cachedContents` 接口创建缓存
+async fn create_gemini(
+    client: &Client,
+    base_url: &str,
+    api_key: &str,
+    model: &str,
+    prefix: &StaticPrefix,
+    hash: &str,
+    ttl_secs: u64,
+) -> Result<ContextCacheEntry, String> {
+    let mut request = prefix.fields.clone();
+    request.insert("model".to_string(), json!(model_resource(model)));
+    request.insert("ttl".to_string(), json!(format!("{ttl_secs}s")));
+    request.insert(
+        "displayName".to_string(),
+        json!(format!("lime-{}", &hash[..16])),
+    );
+
+    let response = client
+        .post(format!(
+            "{}/v1beta/cachedContents",
+            base_url.trim_end_matches('/')
+        ))
+        .header("x-goog-api-key", api_key)
+        .json(&Value::Object(request))
+        .send_captured()
+        .await
+        .map_err(|e| e.to_string())?;
+    let status = response.status();
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        let text: String = text.chars().take(200).collect();
+        return Err(format!("HTTP {status}: {text}"));
+    }
+    let body: Value = response.json().await.map_err(|e| e.to_string())?;
+    let estimated = estimate_tokens(&Value::Object(prefix.fields.clone()));
+    parse_created(&body, model, estimated, ttl_secs, Utc::now())
+}
+
+/// 为 Gemini 请求创建或复用上下文缓存，并改写请求体
+///
+/// 返回引用的缓存资源名；未启用、没有可缓存的前缀或创建失败时不改写请求体并返回 `None`。
+pub async fn apply_gemini(
+    client: &Client,
+    base_url: &str,
+    api_key: &str,
+    model: &str,
+    body: &mut Value,
+    hint: CacheHint,
+) -> Option<String> {
+    let settings = {
+        let guard = state().read().unwrap_or_else(|e| e.into_inner());
+        if !guard.settings.enabled {
+            return None;
+        }
+        guard.settings.clone()
+    };
+    let prefix = split_prefix(body, hint, settings.min_tokens)?;
+    let hash =
+        content_hash(format!("{}\n{}", model, Value::Object(prefix.fields.clone())).as_bytes());
+    let key = format!("{}:{}", scope("gemini", base_url, api_key), hash);
+    let counters = counters();
+
+    let now = Utc::now();
+    let reused = {
+        let mut guard = state().write().unwrap_or_else(|e| e.into_inner());
+        guard
+            .entries
+            .retain(|_, entry| entry.expires_at - Duration::seconds(EXPIRY_MARGIN_SECS) > now);
+        guard.entries.get_mut(&key).map(|entry| {
+            entry.hits += 1;
+            (entry.name.clone(), entry.token_count)
+        })
+    };
+
+    let (name, token_count) = match reused {
+        Some(reused) => {
+            counters.reused.fetch_add(1, Ordering::Relaxed);
+            reused
+        }
+        None => {
+            let ttl_secs = settings.ttl_secs.max(EXPIRY_MARGIN_SECS as u64 * 2);
+            match create_gemini(client, base_url, api_key, model, &prefix, &hash, ttl_secs).await {
+                Ok(entry) => {
+                    counters.created.fetch_add(1, Ordering::Relaxed);
+                    tracing::debug!(
+                        "[CONTEXT_CACHE] 为 {} 创建缓存 {}（{} Token，{} 到期）",
+                        model,
+                        entry.name,
+                        entry.token_count,
+                        entry.expires_at
+                    );
+                    let created = (entry.name.clone(), entry.token_count);
+                    state()
+                        .write()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .entries
+                        .insert(key, entry);
+                    created
+                }
+                Err(e) => {
+                    counters.failed.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!(
+                        "[CONTEXT_CACHE] 为 {} 创建缓存失败，按原请求发送: {}",
+                        model,
+                        e
+                    );
+                    return None;
+                }
+            }
+        }
+    };
+
+    counters
+        .cached_tokens
+        .fetch_add(token_count, Ordering::Relaxed);
+    apply_cache_ref(body, &name, prefix.contents);
+    Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> Value {
+        json!({
+            "systemInstruction": { "parts": [{ "text": "You are a contract reviewer. ".repeat(200) }] },
+            "tools": [{ "functionDeclarations": [{ "name": "lookup" }] }],
+            "contents": [
+                { "role": "user", "parts": [{ "text": "<document>" }] },
+                { "role": "model", "parts": [{ "text": "ok" }] },
+                { "role": "user", "parts": [{ "text": "summarize" }] },
+            ],
+            "generationConfig": { "temperature": 0.2 },
+        })
+    }
+
+    #[test]
+    fn test_split_prefix_by_hint() {
+        assert_eq!(CacheHint::parse("off"), CacheHint::Off);
+        assert_eq!(CacheHint::parse(" 2 "), CacheHint::Prefix(2));
+        assert_eq!(CacheHint::parse("whatever"), CacheHint::Auto);
+
+        let body = request();
+        let auto = split_prefix(&body, CacheHint::Auto, 1000).unwrap();
+        assert_eq!(auto.contents, 0);
+        assert!(auto.fields.contains_key("systemInstruction"));
+        assert!(auto.fields.contains_key("tools"));
+        // 前缀不足最小 Token 数时不缓存，客户端声明的前缀不受限制
+        assert!(split_prefix(&body, CacheHint::Auto, 100_000).is_none());
+        assert!(split_prefix(&body, CacheHint::Off, 0).is_none());
+        // 至少保留最后一条内容
+        let hinted = split_prefix(&body, CacheHint::Prefix(10), 100_000).unwrap();
+        assert_eq!(hinted.contents, 2);
+
+        let mut managed = request();
+        managed["cachedContent"] = json!("cachedContents/x");
+        assert!(split_prefix(&managed, CacheHint::Prefix(1), 0).is_none());
+    }
+
+    #[test]
+    fn test_apply_cache_ref_and_parse_created() {
+        let mut body = request();
+        apply_cache_ref(&mut body, "cachedContents/abc", 2);
+        assert_eq!(body["cachedContent"], "cachedContents/abc");
+        assert!(body.get("systemInstruction").is_none());
+        assert!(body.get("tools").is_none());
+        assert_eq!(body["contents"].as_array().unwrap().len(), 1);
+        assert_eq!(body["contents"][0]["parts"][0]["text"], "summarize");
+        assert_eq!(body["generationConfig"]["temperature"], 0.2);
+
+        let now = Utc::now();
+        let entry = parse_created(
+            &json!({
+                "name": "cachedContents/abc",
+                "expireTime": (now + Duration::hours(2)).to_rfc3339(),
+                "usageMetadata": { "totalTokenCount": 5120 },
+            }),
+            "gemini-2.5-pro",
+            100,
+            3600,
+            now,
+        )
+        .unwrap();
+        assert_eq!(entry.token_count, 5120);
+        // 以较早的到期时间为准
+        assert_eq!(entry.expires_at, now + Duration::seconds(3600));
+        assert!(parse_created(&json!({}), "m", 1, 60, now).is_err());
+
+        assert!(is_cache_error(404, "CachedContent not found"));
+        assert!(!is_cache_error(429, "cachedContent quota"));
+    }
+}
@@ -3,6 +3,9 @@ use lime_core::models::anthropic::*;
 use lime_core::models::openai::*;
 use uuid::Uuid;
 
+use super::golden::GoldenMapping;
+use super::shadow;
+
 /// 将 Anthropic MessagesRequest 转换为 OpenAI ChatCompletionRequest
 pub fn convert_anthropic_to_openai(request: &AnthropicMessagesRequest) -> ChatCompletionRequest {
     let mut openai_messages: Vec<ChatMessage> = Vec::new();
@@ -41,7 +44,7 @@ pub fn convert_anthropic_to_openai(request: &AnthropicMessagesRequest) -> ChatCo
             .collect()
     });
 
-    ChatCompletionRequest {
+    let converted = ChatCompletionRequest {
         model: request.model.clone(),
         messages: openai_messages,
         temperature: request.temperature,
@@ -51,7 +54,18 @@ pub fn convert_anthropic_to_openai(request: &AnthropicMessagesRequest) -> ChatCo
         tools,
         tool_choice: request.tool_choice.clone(),
         reasoning_effort: None,
-    }
+    };
+    shadow::observe(
+        GoldenMapping::AnthropicToOpenaiRequest,
+        || {
+            (
+                serde_json::to_value(request).unwrap_or_default(),
+                serde_json::Value::Null,
+            )
+        },
+        || serde_json::to_value(&converted).unwrap_or_default(),
+    );
+    converted
 }
 
 fn extract_system_text(system: &serde_json::Value) -> String {
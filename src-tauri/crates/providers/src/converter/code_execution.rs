@@ -0,0 +1,173 @@
+//! Gemini 代码执行工具桥接
+//!
+//! 请求侧按配置附加或移除 `codeExecution` 工具；响应侧将 `executableCode` 与
+//! `codeExecutionResult` 部分转换为 Markdown 代码块，转换为 OpenAI 格式时不再丢失
+//! 模型执行的代码与输出。
+
+use serde_json::{json, Value};
+
+const CODE_EXECUTION_KEYS: [&str; 2] = ["codeExecution", "code_execution"];
+
+/// 附加或移除请求中的 `codeExecution` 工具
+///
+/// 同时支持 Antigravity 包装格式（`request.tools`）与 Gemini 原生格式（`tools`）；
+/// Claude 模型不支持该工具，只会移除不会附加。
+pub fn apply_code_execution_tool(payload: &mut Value, enabled: bool) {
+    let is_claude = payload
+        .get("model")
+        .and_then(|m| m.as_str())
+        .is_some_and(|model| model.contains("claude"));
+    let target = if payload.get("request").is_some_and(Value::is_object) {
+        &mut payload["request"]
+    } else {
+        payload
+    };
+    let Some(request) = target.as_object_mut() else {
+        return;
+    };
+
+    let mut tools = match request.remove("tools") {
+        Some(Value::Array(tools)) => tools,
+        Some(other) => {
+            // 非数组的工具定义无法识别，保持原样
+            request.insert("tools".to_string(), other);
+            return;
+        }
+        None => Vec::new(),
+    };
+    let had_tools = !tools.is_empty();
+    tools.retain_mut(|tool| match tool.as_object_mut() {
+        Some(tool) => {
+            let before = tool.len();
+            for key in CODE_EXECUTION_KEYS {
+                tool.remove(key);
+            }
+            // 只包含代码执行的工具项整体移除
+            !(tool.is_empty() && before > 0)
+        }
+        None => true,
+    });
+    if enabled && !is_claude {
+        tools.push(json!({ "codeExecution": {} }));
+    }
+
+    if !tools.is_empty() {
+        request.insert("tools".to_string(), Value::Array(tools));
+    } else if had_tools {
+        // 没有任何工具时 toolConfig 无意义，上游会拒绝
+        request.remove("toolConfig");
+    }
+}
+
+/// 是否为代码执行相关的部分
+pub fn is_code_execution_part(part: &Value) -> bool {
+    part.get("executableCode").is_some() || part.get("codeExecutionResult").is_some()
+}
+
+/// 将 `executableCode` / `codeExecutionResult` 部分转换为 Markdown 代码块
+///
+/// 执行失败或超时时在输出代码块前注明结果。
+pub fn code_execution_part_text(part: &Value) -> Option<String> {
+    if let Some(code) = part.get("executableCode") {
+        let language = code
+            .get("language")
+            .and_then(|l| l.as_str())
+            .filter(|l| !l.eq_ignore_ascii_case("LANGUAGE_UNSPECIFIED"))
+            .unwrap_or("")
+            .to_lowercase();
+        let source = code.get("code").and_then(|c| c.as_str()).unwrap_or("");
+        return Some(format!(
+            "\n```{language}\n{}\n```\n",
+            source.trim_end_matches('\n')
+        ));
+    }
+
+    let result = part.get("codeExecutionResult")?;
+    let outcome = result
+        .get("outcome")
+        .and_then(|o| o.as_str())
+        .unwrap_or("OUTCOME_OK");
+    let output = result.get("output").and_then(|o| o.as_str()).unwrap_or("");
+    let mut text = String::new();
+    if outcome != "OUTCOME_OK" {
+        text.push_str(&format!("\n执行结果: {outcome}\n"));
+    }
+    if !output.is_empty() {
+        text.push_str(&format!(
+            "\n```output\n{}\n```\n",
+            output.trim_end_matches('\n')
+        ));
+    }
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_code_execution_tool() {
+        let mut payload = json!({
+            "model": "gemini-2.5-pro",
+            "request": {
+                "tools": [{"functionDeclarations": [{"name": "f"}]}],
+                "toolConfig": {"functionCallingConfig": {"mode": "AUTO"}}
+            }
+        });
+        apply_code_execution_tool(&mut payload, true);
+        assert_eq!(
+            payload["request"]["tools"],
+            json!([{"functionDeclarations": [{"name": "f"}]}, {"codeExecution": {}}])
+        );
+
+        // 重复附加不产生重复项
+        apply_code_execution_tool(&mut payload, true);
+        assert_eq!(payload["request"]["tools"].as_array().unwrap().len(), 2);
+
+        apply_code_execution_tool(&mut payload, false);
+        assert_eq!(
+            payload["request"]["tools"],
+            json!([{"functionDeclarations": [{"name": "f"}]}])
+        );
+        assert!(payload["request"].get("toolConfig").is_some());
+    }
+
+    #[test]
+    fn test_apply_code_execution_tool_native_and_claude() {
+        let mut native = json!({
+            "contents": [],
+            "tools": [{"codeExecution": {}}],
+            "toolConfig": {"functionCallingConfig": {"mode": "AUTO"}}
+        });
+        apply_code_execution_tool(&mut native, false);
+        assert!(native.get("tools").is_none());
+        assert!(native.get("toolConfig").is_none());
+
+        let mut claude = json!({"model": "claude-sonnet-4-5", "request": {}});
+        apply_code_execution_tool(&mut claude, true);
+        assert!(claude["request"].get("tools").is_none());
+    }
+
+    #[test]
+    fn test_code_execution_part_text() {
+        let code = json!({"executableCode": {"language": "PYTHON", "code": "print(1 + 1)\n"}});
+        assert!(is_code_execution_part(&code));
+        assert_eq!(
+            code_execution_part_text(&code).as_deref(),
+            Some("\n```python\nprint(1 + 1)\n```\n")
+        );
+
+        let ok = json!({"codeExecutionResult": {"outcome": "OUTCOME_OK", "output": "2\n"}});
+        assert_eq!(
+            code_execution_part_text(&ok).as_deref(),
+            Some("\n```output\n2\n```\n")
+        );
+
+        let failed = json!({"codeExecutionResult": {"outcome": "OUTCOME_DEADLINE_EXCEEDED"}});
+        assert_eq!(
+            code_execution_part_text(&failed).as_deref(),
+            Some("\n执行结果: OUTCOME_DEADLINE_EXCEEDED\n")
+        );
+        assert!(code_execution_part_text(&json!({"text": "hi"})).is_none());
+    }
+}
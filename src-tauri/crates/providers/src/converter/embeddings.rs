@@ -0,0 +1,73 @@
+//! OpenAI 嵌入响应转换
+//!
+//! 上游 OpenAI 兼容服务返回的向量按 index 重排后，统一构建为 OpenAI 格式的嵌入响应，
+//! 按请求的 `encoding_format` 输出 float 数组或 base64（小端 f32）。
+
+use base64::Engine;
+
+/// 从上游响应中按 index 顺序提取向量（要求 float 编码）
+pub fn parse_upstream_vectors(body: &serde_json::Value) -> Result<Vec<Vec<f32>>, String> {
+    let data = body
+        .get("data")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| "上游响应缺少 data 字段".to_string())?;
+
+    let mut indexed: Vec<(usize, Vec<f32>)> = Vec::with_capacity(data.len());
+    for (pos, item) in data.iter().enumerate() {
+        let index = item
+            .get("index")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize)
+            .unwrap_or(pos);
+        let vector = item
+            .get("embedding")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| format!("上游响应第 {index} 条缺少 embedding 数组"))?
+            .iter()
+            .map(|v| v.as_f64().map(|f| f as f32))
+            .collect::<Option<Vec<f32>>>()
+            .ok_or_else(|| format!("上游响应第 {index} 条包含非数值元素"))?;
+        indexed.push((index, vector));
+    }
+    indexed.sort_by_key(|(index, _)| *index);
+    Ok(indexed.into_iter().map(|(_, vector)| vector).collect())
+}
+
+fn encode_vector(vector: &[f32], base64_format: bool) -> serde_json::Value {
+    if base64_format {
+        let bytes: Vec<u8> = vector.iter().flat_map(|v| v.to_le_bytes()).collect();
+        serde_json::Value::String(base64::engine::general_purpose::STANDARD.encode(bytes))
+    } else {
+        serde_json::json!(vector)
+    }
+}
+
+/// 构建 OpenAI 格式的嵌入响应
+pub fn build_embeddings_response(
+    model: &str,
+    vectors: &[Vec<f32>],
+    base64_format: bool,
+    usage: Option<serde_json::Value>,
+) -> serde_json::Value {
+    let data: Vec<serde_json::Value> = vectors
+        .iter()
+        .enumerate()
+        .map(|(index, vector)| {
+            serde_json::json!({
+                "object": "embedding",
+                "index": index,
+                "embedding": encode_vector(vector, base64_format),
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "object": "list",
+        "data": data,
+        "model": model,
+        "usage": usage.unwrap_or_else(|| serde_json::json!({
+            "prompt_tokens": 0,
+            "total_tokens": 0
+        })),
+    })
+}
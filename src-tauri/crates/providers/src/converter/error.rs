@@ -0,0 +1,181 @@
+//! 响应转换错误
+//!
+//! 上游响应无法转换为客户端协议时返回 [`ConversionError`]：带稳定的错误码，
+//! 以及截断、脱敏后的上游响应片段，写入客户端错误与日志，便于用户反馈问题。
+
+use lime_core::sanitizer::CredentialSanitizer;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+/// 上游响应片段的最大字符数
+pub const MAX_EXCERPT_CHARS: usize = 1024;
+
+/// 片段中单个字符串值的最大字符数（超出部分多为 base64 图片等数据）
+const MAX_STRING_CHARS: usize = 64;
+
+/// 键名包含这些词的字段值一律替换为 `***`
+const SENSITIVE_KEYS: &[&str] = &[
+    "token",
+    "key",
+    "secret",
+    "password",
+    "authorization",
+    "cookie",
+    "credential",
+];
+
+/// 转换错误码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConversionErrorCode {
+    /// 上游响应不是合法 JSON
+    InvalidJson,
+    /// 响应中没有候选结果
+    MissingCandidates,
+    /// 上游因安全策略拦截了生成内容
+    ContentBlocked,
+    /// 候选结果中没有图片数据
+    NoImageData,
+}
+
+impl ConversionErrorCode {
+    /// 客户端可见的稳定错误码
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InvalidJson => "converter_invalid_json",
+            Self::MissingCandidates => "converter_missing_candidates",
+            Self::ContentBlocked => "converter_content_blocked",
+            Self::NoImageData => "converter_no_image_data",
+        }
+    }
+}
+
+/// 上游响应转换失败
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionError {
+    /// 错误码
+    pub code: ConversionErrorCode,
+    /// 错误描述
+    pub message: String,
+    /// 截断、脱敏后的上游响应片段
+    pub excerpt: Option<String>,
+}
+
+impl ConversionError {
+    /// 创建不带上游响应片段的错误
+    pub fn new(code: ConversionErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            excerpt: None,
+        }
+    }
+
+    /// 附带上游 JSON 响应片段
+    pub fn with_payload(mut self, payload: &Value) -> Self {
+        self.excerpt = Some(payload_excerpt(payload));
+        self
+    }
+
+    /// 附带上游原始响应文本片段
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.excerpt = Some(text_excerpt(text));
+        self
+    }
+
+    /// 客户端错误响应中的 `error` 对象
+    pub fn to_error_json(&self) -> Value {
+        serde_json::json!({
+            "message": self.message,
+            "type": "upstream_conversion_error",
+            "code": self.code.as_str(),
+            "upstream_excerpt": self.excerpt,
+        })
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]", self.message, self.code.as_str())?;
+        if let Some(excerpt) = &self.excerpt {
+            write!(f, " 上游响应片段: {excerpt}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// 生成 JSON 响应片段：敏感字段打码、长字符串截断，整体再截断并脱敏
+pub fn payload_excerpt(payload: &Value) -> String {
+    let compact = redact_value(payload, false).to_string();
+    text_excerpt(&compact)
+}
+
+/// 生成文本响应片段
+pub fn text_excerpt(text: &str) -> String {
+    let sanitized = CredentialSanitizer::with_defaults().sanitize(text.trim());
+    let mut excerpt: String = sanitized.chars().take(MAX_EXCERPT_CHARS).collect();
+    if sanitized.chars().count() > MAX_EXCERPT_CHARS {
+        excerpt.push('…');
+    }
+    excerpt
+}
+
+fn redact_value(value: &Value, sensitive: bool) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let lower = key.to_ascii_lowercase();
+                    let sensitive = sensitive || SENSITIVE_KEYS.iter().any(|k| lower.contains(k));
+                    (key.clone(), redact_value(value, sensitive))
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| redact_value(item, sensitive))
+                .collect(),
+        ),
+        Value::String(_) if sensitive => Value::String("***".to_string()),
+        Value::String(text) if text.chars().count() > MAX_STRING_CHARS => {
+            let head: String = text.chars().take(MAX_STRING_CHARS).collect();
+            Value::String(format!("{head}…（共 {} 字符）", text.chars().count()))
+        }
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_excerpt_redacts_and_truncates() {
+        let payload = serde_json::json!({
+            "access_token": "ya29.secret-value",
+            "candidates": [{ "inlineData": { "data": "A".repeat(5000) } }],
+        });
+        let excerpt = payload_excerpt(&payload);
+        assert!(!excerpt.contains("ya29"));
+        assert!(excerpt.contains("\"access_token\":\"***\""));
+        assert!(excerpt.contains("（共 5000 字符）"));
+        assert!(excerpt.chars().count() <= MAX_EXCERPT_CHARS + 1);
+
+        let long = "x".repeat(MAX_EXCERPT_CHARS * 2);
+        assert!(text_excerpt(&long).ends_with('…'));
+    }
+
+    #[test]
+    fn test_conversion_error_json_and_display() {
+        let err = ConversionError::new(ConversionErrorCode::NoImageData, "No image generated")
+            .with_payload(&serde_json::json!({ "candidates": [] }));
+        let json = err.to_error_json();
+        assert_eq!(json["code"], "converter_no_image_data");
+        assert_eq!(json["upstream_excerpt"], "{\"candidates\":[]}");
+        assert!(err.to_string().contains("[converter_no_image_data]"));
+    }
+}
@@ -0,0 +1,462 @@
+//! 协议转换黄金用例
+//!
+//! 从 fixture 加载「输入 → 期望输出」用例，逐条运行对应的转换函数并比对结果，
+//! 用于在上游 API 变化或转换逻辑调整后快速发现回归。
+//!
+//! 内置用例随程序打包（`fixtures/converter/*.json`），也可以从目录加载额外用例。
+//! 每个文件包含单个用例或用例数组：
+//!
+//! ```json
+//! {
+//!   "name": "plain text",
+//!   "category": "chat",
+//!   "mapping": "openai_to_antigravity_request",
+//!   "input": { ... },
+//!   "expected": { ... },
+//!   "ignore_paths": ["requestId"]
+//! }
+//! ```
+//!
+//! 默认按子集比对：只检查 `expected` 中出现的字段，数组长度必须一致；
+//! `strict: true` 时实际输出中多出的字段也视为差异。
+
+use std::path::Path;
+
+use lime_core::config::ChatImageSettings;
+use lime_core::models::anthropic::AnthropicMessagesRequest;
+use lime_core::models::openai::{ChatCompletionRequest, ImageGenerationRequest};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::anthropic_to_openai::convert_anthropic_to_openai;
+use super::embeddings::{build_embeddings_response, parse_upstream_vectors};
+use super::openai_to_antigravity::{
+    convert_antigravity_image_response, convert_antigravity_to_openai_response_with_images,
+    convert_image_request_to_antigravity, convert_openai_to_antigravity_with_context,
+};
+use super::openai_to_cw::convert_openai_to_codewhisperer;
+
+/// 内置用例（文件名, 内容）
+const BUILTIN_FIXTURES: &[(&str, &str)] = &[
+    (
+        "chat.json",
+        include_str!("../../fixtures/converter/chat.json"),
+    ),
+    (
+        "tools.json",
+        include_str!("../../fixtures/converter/tools.json"),
+    ),
+    (
+        "vision.json",
+        include_str!("../../fixtures/converter/vision.json"),
+    ),
+    (
+        "images.json",
+        include_str!("../../fixtures/converter/images.json"),
+    ),
+    (
+        "embeddings.json",
+        include_str!("../../fixtures/converter/embeddings.json"),
+    ),
+];
+
+static ARRAY_INDEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[\d+\]").expect("valid regex"));
+
+/// 被测的转换
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GoldenMapping {
+    /// Anthropic Messages 请求 → OpenAI Chat 请求
+    AnthropicToOpenaiRequest,
+    /// OpenAI Chat 请求 → Antigravity 请求（`options.project_id`）
+    OpenaiToAntigravityRequest,
+    /// OpenAI Chat 请求 → CodeWhisperer 请求（`options.profile_arn`）
+    OpenaiToCodewhispererRequest,
+    /// Antigravity 响应 → OpenAI Chat 响应（`options.model`）
+    AntigravityToOpenaiResponse,
+    /// OpenAI 图像请求 → Antigravity 请求（`options.project_id`）
+    OpenaiImageToAntigravityRequest,
+    /// Antigravity 图像响应 → OpenAI 图像响应（`options.response_format`）
+    AntigravityToOpenaiImageResponse,
+    /// 上游嵌入响应 → OpenAI 嵌入响应（`options.model` / `options.encoding_format`）
+    OpenaiEmbeddingsResponse,
+}
+
+/// 单个黄金用例
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoldenCase {
+    pub name: String,
+    /// 分类（chat / tools / vision / images / embeddings），仅用于展示
+    #[serde(default)]
+    pub category: String,
+    pub mapping: GoldenMapping,
+    /// 转换的附加参数
+    #[serde(default)]
+    pub options: Value,
+    pub input: Value,
+    pub expected: Value,
+    /// 忽略的路径（如 `requestId`、`choices[*].message.tool_calls[*].id`）
+    #[serde(default)]
+    pub ignore_paths: Vec<String>,
+    #[serde(default)]
+    pub strict: bool,
+    /// 用例来源文件，加载时填充
+    #[serde(default, skip_deserializing)]
+    pub source: String,
+}
+
+/// 字段差异
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GoldenDiff {
+    pub path: String,
+    pub expected: Value,
+    pub actual: Value,
+}
+
+/// 单个用例的运行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoldenCaseResult {
+    pub name: String,
+    pub category: String,
+    pub mapping: GoldenMapping,
+    pub source: String,
+    pub passed: bool,
+    pub diffs: Vec<GoldenDiff>,
+    /// 输入无法解析或转换失败时的错误
+    pub error: Option<String>,
+}
+
+/// 用例运行报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoldenReport {
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub results: Vec<GoldenCaseResult>,
+}
+
+fn parse_fixture(source: &str, content: &str) -> Result<Vec<GoldenCase>, String> {
+    let value: Value =
+        serde_json::from_str(content).map_err(|e| format!("解析用例文件 {source} 失败: {e}"))?;
+    let cases: Vec<GoldenCase> = match value {
+        Value::Array(_) => serde_json::from_value(value),
+        _ => serde_json::from_value(value).map(|case| vec![case]),
+    }
+    .map_err(|e| format!("用例文件 {source} 格式无效: {e}"))?;
+    Ok(cases
+        .into_iter()
+        .map(|mut case| {
+            case.source = source.to_string();
+            case
+        })
+        .collect())
+}
+
+/// 内置用例
+pub fn builtin_cases() -> Result<Vec<GoldenCase>, String> {
+    let mut cases = Vec::new();
+    for (name, content) in BUILTIN_FIXTURES {
+        cases.extend(parse_fixture(&format!("builtin:{name}"), content)?);
+    }
+    Ok(cases)
+}
+
+/// 从目录加载所有 `*.json` 用例（按文件名排序）
+pub fn load_cases_from_dir(dir: &Path) -> Result<Vec<GoldenCase>, String> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .map_err(|e| format!("读取用例目录 {} 失败: {e}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    let mut cases = Vec::new();
+    for path in paths {
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("读取用例文件 {} 失败: {e}", path.display()))?;
+        cases.extend(parse_fixture(&path.display().to_string(), &content)?);
+    }
+    Ok(cases)
+}
+
+fn option_str<'a>(options: &'a Value, key: &str) -> Option<&'a str> {
+    options.get(key).and_then(Value::as_str)
+}
+
+fn parse_input<T: serde::de::DeserializeOwned>(input: &Value) -> Result<T, String> {
+    serde_json::from_value(input.clone()).map_err(|e| format!("输入无法解析: {e}"))
+}
+
+fn to_value<T: Serialize>(output: &T) -> Result<Value, String> {
+    serde_json::to_value(output).map_err(|e| format!("输出无法序列化: {e}"))
+}
+
+/// 运行转换，返回 JSON 形式的输出
+fn convert(mapping: GoldenMapping, input: &Value, options: &Value) -> Result<Value, String> {
+    match mapping {
+        GoldenMapping::AnthropicToOpenaiRequest => {
+            let request: AnthropicMessagesRequest = parse_input(input)?;
+            to_value(&convert_anthropic_to_openai(&request))
+        }
+        GoldenMapping::OpenaiToAntigravityRequest => {
+            let request: ChatCompletionRequest = parse_input(input)?;
+            let project_id = option_str(options, "project_id").unwrap_or_default();
+            Ok(convert_openai_to_antigravity_with_context(
+                &request, project_id,
+            ))
+        }
+        GoldenMapping::OpenaiToCodewhispererRequest => {
+            let request: ChatCompletionRequest = parse_input(input)?;
+            let profile_arn = option_str(options, "profile_arn").map(str::to_string);
+            to_value(&convert_openai_to_codewhisperer(&request, profile_arn))
+        }
+        GoldenMapping::AntigravityToOpenaiResponse => {
+            let model = option_str(options, "model").unwrap_or_default();
+            Ok(convert_antigravity_to_openai_response_with_images(
+                input,
+                model,
+                &ChatImageSettings::default(),
+            ))
+        }
+        GoldenMapping::OpenaiImageToAntigravityRequest => {
+            let request: ImageGenerationRequest = parse_input(input)?;
+            let project_id = option_str(options, "project_id").unwrap_or_default();
+            Ok(convert_image_request_to_antigravity(&request, project_id))
+        }
+        GoldenMapping::AntigravityToOpenaiImageResponse => {
+            let format = option_str(options, "response_format").unwrap_or("url");
+            to_value(&convert_antigravity_image_response(input, format).map_err(|e| e.to_string())?)
+        }
+        GoldenMapping::OpenaiEmbeddingsResponse => {
+            let vectors = parse_upstream_vectors(input)?;
+            let model = option_str(options, "model").unwrap_or_default();
+            let base64 = option_str(options, "encoding_format") == Some("base64");
+            Ok(build_embeddings_response(
+                model,
+                &vectors,
+                base64,
+                input.get("usage").cloned(),
+            ))
+        }
+    }
+}
+
+fn is_ignored(path: &str, ignore_paths: &[String]) -> bool {
+    if ignore_paths.is_empty() {
+        return false;
+    }
+    let wildcard = ARRAY_INDEX.replace_all(path, "[*]");
+    ignore_paths
+        .iter()
+        .any(|pattern| pattern == path || *pattern == wildcard)
+}
+
+fn child_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{parent}.{key}")
+    }
+}
+
+fn values_equal(expected: &Value, actual: &Value) -> bool {
+    match (expected, actual) {
+        // 1 与 1.0 视为相等（f32 字段序列化后可能带小数）
+        (Value::Number(a), Value::Number(b)) => match (a.as_f64(), b.as_f64()) {
+            (Some(a), Some(b)) => (a - b).abs() <= 1e-9 * a.abs().max(1.0),
+            _ => a == b,
+        },
+        _ => expected == actual,
+    }
+}
+
+fn compare(
+    expected: &Value,
+    actual: &Value,
+    path: &str,
+    ignore_paths: &[String],
+    strict: bool,
+    diffs: &mut Vec<GoldenDiff>,
+) {
+    if is_ignored(path, ignore_paths) {
+        return;
+    }
+    let mismatch = GoldenDiff {
+        path: path.to_string(),
+        expected: expected.clone(),
+        actual: actual.clone(),
+    };
+
+    match (expected, actual) {
+        (Value::Object(expected_map), Value::Object(actual_map)) => {
+            for (key, expected_value) in expected_map {
+                let path = child_path(path, key);
+                match actual_map.get(key) {
+                    Some(actual_value) => compare(
+                        expected_value,
+                        actual_value,
+                        &path,
+                        ignore_paths,
+                        strict,
+                        diffs,
+                    ),
+                    // 期望为 null 的字段允许缺省（skip_serializing_if）
+                    None if expected_value.is_null() => {}
+                    None if is_ignored(&path, ignore_paths) => {}
+                    None => diffs.push(GoldenDiff {
+                        path,
+                        expected: expected_value.clone(),
+                        actual: Value::Null,
+                    }),
+                }
+            }
+            if strict {
+                for (key, actual_value) in actual_map {
+                    let path = child_path(path, key);
+                    if !expected_map.contains_key(key)
+                        && !actual_value.is_null()
+                        && !is_ignored(&path, ignore_paths)
+                    {
+                        diffs.push(GoldenDiff {
+                            path,
+                            expected: Value::Null,
+                            actual: actual_value.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        (Value::Array(expected_items), Value::Array(actual_items)) => {
+            if expected_items.len() != actual_items.len() {
+                diffs.push(mismatch);
+                return;
+            }
+            for (index, (expected_item, actual_item)) in
+                expected_items.iter().zip(actual_items).enumerate()
+            {
+                compare(
+                    expected_item,
+                    actual_item,
+                    &format!("{path}[{index}]"),
+                    ignore_paths,
+                    strict,
+                    diffs,
+                );
+            }
+        }
+        _ => {
+            if !values_equal(expected, actual) {
+                diffs.push(mismatch);
+            }
+        }
+    }
+}
+
+/// 严格比较两份转换输出（双向检查字段），供影子验证复用
+pub(crate) fn diff_outputs(
+    expected: &Value,
+    actual: &Value,
+    ignore_paths: &[String],
+) -> Vec<GoldenDiff> {
+    let mut diffs = Vec::new();
+    compare(expected, actual, "", ignore_paths, true, &mut diffs);
+    diffs
+}
+
+/// 运行单个用例
+pub fn run_case(case: &GoldenCase) -> GoldenCaseResult {
+    let mut diffs = Vec::new();
+    let error = match convert(case.mapping, &case.input, &case.options) {
+        Ok(actual) => {
+            compare(
+                &case.expected,
+                &actual,
+                "",
+                &case.ignore_paths,
+                case.strict,
+                &mut diffs,
+            );
+            None
+        }
+        Err(e) => Some(e),
+    };
+    GoldenCaseResult {
+        name: case.name.clone(),
+        category: case.category.clone(),
+        mapping: case.mapping,
+        source: case.source.clone(),
+        passed: error.is_none() && diffs.is_empty(),
+        diffs,
+        error,
+    }
+}
+
+/// 运行一组用例
+pub fn run_cases(cases: &[GoldenCase]) -> GoldenReport {
+    let results: Vec<GoldenCaseResult> = cases.iter().map(run_case).collect();
+    let passed = results.iter().filter(|result| result.passed).count();
+    GoldenReport {
+        total: results.len(),
+        passed,
+        failed: results.len() - passed,
+        results,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_golden_cases_pass() {
+        let cases = builtin_cases().unwrap();
+        let categories: std::collections::BTreeSet<&str> =
+            cases.iter().map(|case| case.category.as_str()).collect();
+        assert_eq!(
+            categories.into_iter().collect::<Vec<_>>(),
+            vec!["chat", "embeddings", "images", "tools", "vision"]
+        );
+
+        let report = run_cases(&cases);
+        let failures: Vec<_> = report.results.iter().filter(|r| !r.passed).collect();
+        assert!(failures.is_empty(), "黄金用例失败: {failures:#?}");
+    }
+
+    #[test]
+    fn test_compare_reports_diffs_and_ignores_paths() {
+        let mut case: GoldenCase = serde_json::from_value(serde_json::json!({
+            "name": "diff",
+            "mapping": "openai_embeddings_response",
+            "options": { "model": "m" },
+            "input": { "data": [{ "index": 0, "embedding": [1.0] }] },
+            "expected": {
+                "model": "other",
+                "data": [{ "embedding": [1] }],
+                "usage": { "prompt_tokens": 0 }
+            }
+        }))
+        .unwrap();
+        let result = run_case(&case);
+        assert!(!result.passed);
+        assert_eq!(result.diffs.len(), 1);
+        assert_eq!(result.diffs[0].path, "model");
+
+        case.ignore_paths = vec!["model".to_string()];
+        assert!(run_case(&case).passed);
+
+        case.strict = true;
+        let mut strict: Vec<String> = run_case(&case).diffs.into_iter().map(|d| d.path).collect();
+        strict.sort();
+        assert_eq!(
+            strict,
+            vec![
+                "data[0].index",
+                "data[0].object",
+                "object",
+                "usage.total_tokens"
+            ]
+        );
+    }
+}
@@ -0,0 +1,262 @@
+//! Gemini 搜索接地（grounding）引用转换
+//!
+//! 将候选结果中的 `groundingMetadata` 转换为 OpenAI `url_citation` 标注，
+//! 再由标注转换为 Anthropic 文本块的 `citations`。
+//!
+//! Gemini 的 `segment.startIndex/endIndex` 是 UTF-8 字节偏移，OpenAI 标注使用字符偏移；
+//! 优先按 `segment.text` 在最终文本中定位，这样文本前被拼接了其他内容时仍能对齐。
+
+use serde_json::{json, Value};
+
+/// 引用来源
+struct GroundingSource {
+    url: String,
+    title: String,
+}
+
+fn grounding_sources(metadata: &Value) -> Vec<Option<GroundingSource>> {
+    metadata
+        .get("groundingChunks")
+        .and_then(|c| c.as_array())
+        .map(|chunks| {
+            chunks
+                .iter()
+                .map(|chunk| {
+                    let source = chunk.get("web").or_else(|| chunk.get("retrievedContext"))?;
+                    let url = source.get("uri").and_then(|u| u.as_str())?;
+                    let title = source.get("title").and_then(|t| t.as_str()).unwrap_or(url);
+                    Some(GroundingSource {
+                        url: url.to_string(),
+                        title: title.to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 字节偏移转换为字符偏移（不在字符边界上时返回 None）
+fn byte_to_char_index(text: &str, byte_index: usize) -> Option<usize> {
+    text.is_char_boundary(byte_index)
+        .then(|| text[..byte_index].chars().count())
+}
+
+/// 定位被引用片段在文本中的字符区间
+fn locate_segment(segment: &Value, text: &str) -> Option<(usize, usize)> {
+    let start = segment
+        .get("startIndex")
+        .and_then(|i| i.as_u64())
+        .unwrap_or(0) as usize;
+    if let Some(cited) = segment
+        .get("text")
+        .and_then(|t| t.as_str())
+        .filter(|t| !t.is_empty())
+    {
+        let found = text
+            .get(start..)
+            .and_then(|rest| rest.find(cited).map(|pos| start + pos))
+            .or_else(|| text.find(cited))?;
+        let char_start = byte_to_char_index(text, found)?;
+        return Some((char_start, char_start + cited.chars().count()));
+    }
+    let end = segment.get("endIndex").and_then(|i| i.as_u64())? as usize;
+    if end > text.len() || start > end {
+        return None;
+    }
+    Some((
+        byte_to_char_index(text, start)?,
+        byte_to_char_index(text, end)?,
+    ))
+}
+
+fn url_citation(source: &GroundingSource, start: usize, end: usize) -> Value {
+    json!({
+        "type": "url_citation",
+        "url_citation": {
+            "url": source.url,
+            "title": source.title,
+            "start_index": start,
+            "end_index": end
+        }
+    })
+}
+
+/// 将 `groundingMetadata` 转换为 OpenAI `url_citation` 标注
+///
+/// 每个引用片段与其来源生成一条标注；未被任何片段引用的来源以空区间
+/// （`start_index == end_index == 0`）附在最后，避免丢失。
+pub fn grounding_to_annotations(metadata: &Value, text: &str) -> Vec<Value> {
+    let sources = grounding_sources(metadata);
+    if sources.iter().all(Option::is_none) {
+        return Vec::new();
+    }
+
+    let mut annotations = Vec::new();
+    let mut cited = vec![false; sources.len()];
+    let supports = metadata
+        .get("groundingSupports")
+        .and_then(|s| s.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for support in supports {
+        let Some((start, end)) = support
+            .get("segment")
+            .and_then(|segment| locate_segment(segment, text))
+        else {
+            continue;
+        };
+        let indices = support
+            .get("groundingChunkIndices")
+            .and_then(|i| i.as_array())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for index in indices.iter().filter_map(|i| i.as_u64()) {
+            let index = index as usize;
+            if let Some(Some(source)) = sources.get(index) {
+                cited[index] = true;
+                annotations.push(url_citation(source, start, end));
+            }
+        }
+    }
+
+    for (source, cited) in sources.iter().zip(cited) {
+        if let (Some(source), false) = (source, cited) {
+            annotations.push(url_citation(source, 0, 0));
+        }
+    }
+    annotations
+}
+
+/// 从 Gemini 候选结果中提取引用标注
+pub fn candidate_annotations(candidate: &Value, text: &str) -> Vec<Value> {
+    candidate
+        .get("groundingMetadata")
+        .map(|metadata| grounding_to_annotations(metadata, text))
+        .unwrap_or_default()
+}
+
+/// 将 OpenAI `url_citation` 标注转换为 Anthropic 文本块的 `citations`
+pub fn annotations_to_anthropic_citations(annotations: &[Value], text: &str) -> Vec<Value> {
+    annotations
+        .iter()
+        .filter(|a| a.get("type").and_then(|t| t.as_str()) == Some("url_citation"))
+        .filter_map(|a| {
+            let citation = a.get("url_citation")?;
+            let url = citation.get("url").and_then(|u| u.as_str())?;
+            let start = citation
+                .get("start_index")
+                .and_then(|i| i.as_u64())
+                .unwrap_or(0) as usize;
+            let end = citation
+                .get("end_index")
+                .and_then(|i| i.as_u64())
+                .unwrap_or(0) as usize;
+            let cited_text: String = text
+                .chars()
+                .skip(start)
+                .take(end.saturating_sub(start))
+                .collect();
+            // encrypted_index 是 Anthropic 服务端搜索结果的索引，转换来源没有该值
+            Some(json!({
+                "type": "web_search_result_location",
+                "url": url,
+                "title": citation.get("title").cloned().unwrap_or(Value::Null),
+                "cited_text": cited_text,
+                "encrypted_index": ""
+            }))
+        })
+        .collect()
+}
+
+/// 移除 OpenAI 响应中的引用标注（`choices[].message.annotations`）
+pub fn strip_annotations(response: &mut Value) {
+    if let Some(choices) = response.get_mut("choices").and_then(|c| c.as_array_mut()) {
+        for choice in choices {
+            for key in ["message", "delta"] {
+                if let Some(message) = choice.get_mut(key).and_then(|m| m.as_object_mut()) {
+                    message.remove("annotations");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> Value {
+        json!({
+            "groundingChunks": [
+                {"web": {"uri": "https://a.example.com", "title": "A"}},
+                {"web": {"uri": "https://b.example.com", "title": "B"}},
+                {"web": {"uri": "https://c.example.com"}}
+            ],
+            "groundingSupports": [
+                {
+                    "segment": {"startIndex": 0, "endIndex": 18, "text": "东京是首都。"},
+                    "groundingChunkIndices": [0, 1]
+                },
+                {
+                    "segment": {"startIndex": 18, "endIndex": 23},
+                    "groundingChunkIndices": [1]
+                }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_grounding_to_annotations() {
+        let text = "前言 东京是首都。Hello";
+        let annotations = grounding_to_annotations(&metadata(), text);
+        // 第二个片段只有字节偏移，前缀使其错位，跳过
+        assert_eq!(annotations.len(), 3);
+
+        // 按 segment.text 定位，兼容前缀偏移，使用字符偏移
+        assert_eq!(
+            annotations[0]["url_citation"]["url"],
+            "https://a.example.com"
+        );
+        assert_eq!(annotations[0]["url_citation"]["start_index"], 3);
+        assert_eq!(annotations[0]["url_citation"]["end_index"], 9);
+        assert_eq!(annotations[1]["url_citation"]["title"], "B");
+
+        // 仅有字节偏移时转换为字符偏移
+        let plain = grounding_to_annotations(&metadata(), "东京是首都。Hello");
+        assert_eq!(plain[2]["url_citation"]["start_index"], 6);
+        assert_eq!(plain[2]["url_citation"]["end_index"], 11);
+
+        // 未被引用的来源保留为空区间，标题缺省为 URL
+        assert_eq!(
+            annotations[2]["url_citation"]["title"],
+            "https://c.example.com"
+        );
+        assert_eq!(annotations[2]["url_citation"]["end_index"], 0);
+    }
+
+    #[test]
+    fn test_annotations_to_anthropic_citations_and_strip() {
+        let text = "东京是首都。Hello";
+        let annotations = grounding_to_annotations(&metadata(), text);
+        let citations = annotations_to_anthropic_citations(&annotations, text);
+        assert_eq!(citations.len(), 4);
+        assert_eq!(citations[0]["type"], "web_search_result_location");
+        assert_eq!(citations[0]["cited_text"], "东京是首都。");
+        assert_eq!(citations[2]["cited_text"], "Hello");
+
+        let mut response = json!({
+            "choices": [{"message": {"content": text, "annotations": annotations}}]
+        });
+        strip_annotations(&mut response);
+        assert!(response["choices"][0]["message"]
+            .get("annotations")
+            .is_none());
+        assert_eq!(response["choices"][0]["message"]["content"], text);
+    }
+
+    #[test]
+    fn test_no_grounding() {
+        assert!(candidate_annotations(&json!({"content": {}}), "text").is_empty());
+        assert!(grounding_to_annotations(&json!({"groundingChunks": [{}]}), "text").is_empty());
+    }
+}
@@ -1,9 +1,15 @@
 pub mod anthropic_to_openai;
+pub mod code_execution;
 pub mod cw_to_openai;
+pub mod embeddings;
+pub mod error;
+pub mod golden;
+pub mod grounding;
 pub mod openai_to_antigravity;
 pub mod openai_to_cw;
 pub mod protocol_selector;
 pub mod reasoning_handler;
+pub mod shadow;
 
 #[allow(unused_imports)]
 pub use anthropic_to_openai::*;
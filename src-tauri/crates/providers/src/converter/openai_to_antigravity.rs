@@ -14,7 +14,11 @@
 //! ## 更新日志
 //! - 2025-12-28: 修复请求格式，对齐 CLIProxyAPI 实现
 
+use super::error::{ConversionError, ConversionErrorCode};
+use super::golden::GoldenMapping;
+use super::shadow;
 use crate::session::{get_thought_signature, SessionManager};
+use lime_core::config::{ChatImageFormat, ChatImageSettings};
 use lime_core::models::openai::*;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -722,6 +726,16 @@ pub fn convert_openai_to_antigravity_with_context(
     );
     eprintln!("========== [CONVERT] OpenAI -> Antigravity 转换完成 ==========");
 
+    shadow::observe(
+        GoldenMapping::OpenaiToAntigravityRequest,
+        || {
+            (
+                serde_json::to_value(request).unwrap_or_default(),
+                serde_json::json!({ "project_id": project_id }),
+            )
+        },
+        || result.clone(),
+    );
     result
 }
 
@@ -851,6 +865,34 @@ fn parse_data_url(url: &str) -> Option<(String, String)> {
 pub fn convert_antigravity_to_openai_response(
     antigravity_resp: &serde_json::Value,
     model: &str,
+) -> serde_json::Value {
+    convert_antigravity_to_openai_response_with_images(
+        antigravity_resp,
+        model,
+        &ChatImageSettings::default(),
+    )
+}
+
+/// 追加文本到内容分段（与上一个文本分段合并）
+fn push_text_part(parts: &mut Vec<serde_json::Value>, text: &str) {
+    if let Some(last) = parts.last_mut() {
+        if last["type"] == "text" {
+            let merged = format!("{}{text}", last["text"].as_str().unwrap_or_default());
+            last["text"] = serde_json::Value::String(merged);
+            return;
+        }
+    }
+    parts.push(serde_json::json!({"type": "text", "text": text}));
+}
+
+/// 将 Antigravity 响应转换为 OpenAI 格式，按配置处理图片输出
+///
+/// - `format` 为 `content_parts` 且包含图片时，`message.content` 为 `text` / `image_url` 分段数组
+/// - `stop_on_first_image` 时，第一张图片之后的部分全部丢弃
+pub fn convert_antigravity_to_openai_response_with_images(
+    antigravity_resp: &serde_json::Value,
+    model: &str,
+    images: &ChatImageSettings,
 ) -> serde_json::Value {
     // Antigravity 响应可能在 response 字段下，也可能直接是 Gemini 格式
     let resp = antigravity_resp.get("response").unwrap_or(antigravity_resp);
@@ -861,6 +903,8 @@ pub fn convert_antigravity_to_openai_response(
     if let Some(candidates) = resp.get("candidates").and_then(|c| c.as_array()) {
         for (i, candidate) in candidates.iter().enumerate() {
             let mut content = String::new();
+            let mut content_parts: Vec<serde_json::Value> = Vec::new();
+            let mut has_image = false;
             let mut tool_calls: Vec<serde_json::Value> = Vec::new();
 
             if let Some(parts) = candidate
@@ -885,12 +929,19 @@ pub fn convert_antigravity_to_openai_response(
 
                     let has_content = part.get("text").is_some()
                         || part.get("functionCall").is_some()
-                        || part.get("inlineData").is_some();
+                        || part.get("inlineData").is_some()
+                        || super::code_execution::is_code_execution_part(part);
 
                     if has_thought_signature && !has_content {
                         continue;
                     }
 
+                    // 代码执行工具：执行的代码与输出以代码块形式并入内容
+                    if let Some(text) = super::code_execution::code_execution_part_text(part) {
+                        content.push_str(&text);
+                        push_text_part(&mut content_parts, &text);
+                    }
+
                     if let Some(text) = part.get("text").and_then(|t| t.as_str()) {
                         if is_thought {
                             // 思维内容
@@ -901,6 +952,7 @@ pub fn convert_antigravity_to_openai_response(
                             }
                         } else {
                             content.push_str(text);
+                            push_text_part(&mut content_parts, text);
                         }
                     }
 
@@ -949,6 +1001,14 @@ pub fn convert_antigravity_to_openai_response(
                                 content.push_str("\n\n");
                             }
                             content.push_str(&format!("![image]({image_url})"));
+                            content_parts.push(serde_json::json!({
+                                "type": "image_url",
+                                "image_url": {"url": image_url}
+                            }));
+                            has_image = true;
+                            if images.stop_on_first_image {
+                                break;
+                            }
                         }
                     }
                 }
@@ -970,11 +1030,25 @@ pub fn convert_antigravity_to_openai_response(
                     "stop"
                 });
 
+            // 搜索接地引用转换为 url_citation 标注
+            let annotations = super::grounding::candidate_annotations(candidate, &content);
+
+            let content = if has_image && images.format == ChatImageFormat::ContentParts {
+                serde_json::Value::Array(content_parts)
+            } else if content.is_empty() {
+                serde_json::Value::Null
+            } else {
+                serde_json::Value::String(content)
+            };
             let mut message = serde_json::json!({
                 "role": "assistant",
-                "content": if content.is_empty() { serde_json::Value::Null } else { serde_json::Value::String(content) }
+                "content": content
             });
 
+            if !annotations.is_empty() {
+                message["annotations"] = serde_json::Value::Array(annotations);
+            }
+
             if let Some(ref rc) = reasoning_content {
                 message["reasoning_content"] = serde_json::Value::String(rc.clone());
             }
@@ -1131,11 +1205,11 @@ pub fn convert_image_request_to_antigravity(
 /// - `response_format`: 响应格式 ("url" 或 "b64_json")
 ///
 /// # 返回
-/// OpenAI 格式的图像生成响应，或错误信息
+/// OpenAI 格式的图像生成响应；失败时返回带错误码和上游响应片段的 [`ConversionError`]
 pub fn convert_antigravity_image_response(
     antigravity_resp: &serde_json::Value,
     response_format: &str,
-) -> Result<ImageGenerationResponse, String> {
+) -> Result<ImageGenerationResponse, ConversionError> {
     let resp = antigravity_resp.get("response").unwrap_or(antigravity_resp);
 
     let mut images = Vec::new();
@@ -1191,7 +1265,7 @@ pub fn convert_antigravity_image_response(
     }
 
     if images.is_empty() {
-        return Err("No image generated".to_string());
+        return Err(image_conversion_error(resp).with_payload(antigravity_resp));
     }
 
     Ok(ImageGenerationResponse {
@@ -1200,6 +1274,50 @@ pub fn convert_antigravity_image_response(
     })
 }
 
+/// 判断图像响应中没有图片的原因
+fn image_conversion_error(resp: &serde_json::Value) -> ConversionError {
+    if let Some(reason) = resp
+        .get("promptFeedback")
+        .and_then(|f| f.get("blockReason"))
+        .and_then(|r| r.as_str())
+    {
+        return ConversionError::new(
+            ConversionErrorCode::ContentBlocked,
+            format!("Image generation blocked by upstream: {reason}"),
+        );
+    }
+
+    let candidates = resp
+        .get("candidates")
+        .and_then(|c| c.as_array())
+        .filter(|c| !c.is_empty());
+    let Some(candidates) = candidates else {
+        return ConversionError::new(
+            ConversionErrorCode::MissingCandidates,
+            "Upstream response contains no candidates",
+        );
+    };
+
+    let blocked = candidates.iter().find_map(|candidate| {
+        candidate
+            .get("finishReason")
+            .and_then(|r| r.as_str())
+            .filter(|reason| {
+                matches!(
+                    *reason,
+                    "SAFETY" | "IMAGE_SAFETY" | "PROHIBITED_CONTENT" | "BLOCKLIST" | "SPII"
+                )
+            })
+    });
+    match blocked {
+        Some(reason) => ConversionError::new(
+            ConversionErrorCode::ContentBlocked,
+            format!("Image generation blocked by upstream: {reason}"),
+        ),
+        None => ConversionError::new(ConversionErrorCode::NoImageData, "No image generated"),
+    }
+}
+
 // ============================================================================
 // 图像生成 API 测试
 // ============================================================================
@@ -1357,7 +1475,93 @@ mod image_tests {
 
         let result = convert_antigravity_image_response(&antigravity_resp, "url");
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "No image generated");
+        let err = result.unwrap_err();
+        assert_eq!(err.message, "No image generated");
+        assert_eq!(err.code, ConversionErrorCode::NoImageData);
+        assert!(err
+            .excerpt
+            .as_deref()
+            .is_some_and(|excerpt| excerpt.contains("Sorry, I cannot generate that image")));
+    }
+
+    #[test]
+    fn test_convert_antigravity_image_response_blocked() {
+        let antigravity_resp = serde_json::json!({
+            "response": {
+                "candidates": [{ "finishReason": "IMAGE_SAFETY" }]
+            }
+        });
+        let err = convert_antigravity_image_response(&antigravity_resp, "url").unwrap_err();
+        assert_eq!(err.code, ConversionErrorCode::ContentBlocked);
+        assert!(err.message.contains("IMAGE_SAFETY"));
+
+        let err = convert_antigravity_image_response(&serde_json::json!({}), "url").unwrap_err();
+        assert_eq!(err.code, ConversionErrorCode::MissingCandidates);
+    }
+
+    fn chat_image_response() -> serde_json::Value {
+        serde_json::json!({
+            "response": {
+                "candidates": [{
+                    "content": {
+                        "parts": [
+                            {"text": "Here you go"},
+                            {"inlineData": {"mimeType": "image/png", "data": "AAAA"}},
+                            {"text": "And another"},
+                            {"inlineData": {"mimeType": "image/jpeg", "data": "BBBB"}}
+                        ]
+                    },
+                    "finishReason": "STOP"
+                }]
+            }
+        })
+    }
+
+    #[test]
+    fn test_chat_image_markdown_default() {
+        let result = convert_antigravity_to_openai_response(&chat_image_response(), "gemini");
+        assert_eq!(
+            result["choices"][0]["message"]["content"],
+            "Here you go\n\n![image](data:image/png;base64,AAAA)And another\n\n![image](data:image/jpeg;base64,BBBB)"
+        );
+    }
+
+    #[test]
+    fn test_chat_image_content_parts_and_stop_on_first() {
+        let settings = ChatImageSettings {
+            format: ChatImageFormat::ContentParts,
+            ..Default::default()
+        };
+        let result = convert_antigravity_to_openai_response_with_images(
+            &chat_image_response(),
+            "gemini",
+            &settings,
+        );
+        let parts = result["choices"][0]["message"]["content"]
+            .as_array()
+            .unwrap();
+        assert_eq!(parts.len(), 4);
+        assert_eq!(
+            parts[0],
+            serde_json::json!({"type": "text", "text": "Here you go"})
+        );
+        assert_eq!(parts[1]["image_url"]["url"], "data:image/png;base64,AAAA");
+        assert_eq!(parts[3]["type"], "image_url");
+
+        let settings = ChatImageSettings {
+            stop_on_first_image: true,
+            ..settings
+        };
+        let result = convert_antigravity_to_openai_response_with_images(
+            &chat_image_response(),
+            "gemini",
+            &settings,
+        );
+        let parts = result["choices"][0]["message"]["content"]
+            .as_array()
+            .unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[1]["type"], "image_url");
     }
 
     #[test]
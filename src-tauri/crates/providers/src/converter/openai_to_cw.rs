@@ -13,6 +13,9 @@ use lime_core::models::openai::*;
 use std::collections::HashMap;
 use uuid::Uuid;
 
+use super::golden::GoldenMapping;
+use super::shadow;
+
 /// 模型映射表
 ///
 /// 参考 AIClient-2-API 的 provider-models.js 和 claude-kiro.js
@@ -382,7 +385,7 @@ pub fn convert_openai_to_codewhisperer(
         None
     };
 
-    CodeWhispererRequest {
+    let converted = CodeWhispererRequest {
         conversation_state: ConversationState {
             chat_trigger_type: "MANUAL".to_string(),
             conversation_id,
@@ -402,7 +405,18 @@ pub fn convert_openai_to_codewhisperer(
             },
         },
         profile_arn,
-    }
+    };
+    shadow::observe(
+        GoldenMapping::OpenaiToCodewhispererRequest,
+        || {
+            (
+                serde_json::to_value(request).unwrap_or_default(),
+                shadow_options,
+            )
+        },
+        || serde_json::to_value(&converted).unwrap_or_default(),
+    );
+    converted
 }
 
 /// 修复历史记录，确保 user/assistant 严格交替
@@ -0,0 +1,308 @@
+//! 转换器影子验证
+//!
+//! 升级协议转换逻辑时，把另一版本的转换器（通常是旧实现的副本）通过 [`register_candidate`]
+//! 注册为影子版本。启用 `server.converter_shadow` 后，转换函数在生成上游请求体的同时，
+//! 按采样比例用影子版本转换同一输入，严格比对两份输出，差异写入日志并保留最近的记录供
+//! `/admin/converter-shadow` 查看。影子版本的输出只用于比对，不会发往上游。
+//!
+//! 未启用或未注册影子版本时只有一次原子读取的开销，输入与输出不会被序列化。
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{OnceLock, RwLock};
+
+use chrono::{DateTime, Utc};
+use lime_core::config::ConverterShadowSettings;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::golden::{diff_outputs, GoldenDiff, GoldenMapping};
+
+/// 影子转换函数：`(input, options) -> output`，参数与黄金用例一致
+pub type ShadowConvertFn = fn(&Value, &Value) -> Result<Value, String>;
+
+/// 单条差异记录中最多保留的字段差异数
+const MAX_DIFFS_PER_RECORD: usize = 20;
+
+/// 已注册的影子版本
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShadowCandidate {
+    pub mapping: GoldenMapping,
+    pub version: String,
+}
+
+/// 一次比对发现的差异
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShadowDiscrepancy {
+    pub at: DateTime<Utc>,
+    pub mapping: GoldenMapping,
+    pub version: String,
+    /// 字段差异，`expected` 为线上版本的值，`actual` 为影子版本的值
+    pub diffs: Vec<GoldenDiff>,
+    /// 差异总数（`diffs` 可能被截断）
+    pub total_diffs: usize,
+    /// 影子版本转换失败时的错误
+    pub error: Option<String>,
+}
+
+/// 当前状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConverterShadowStatus {
+    pub enabled: bool,
+    pub sample_rate: f64,
+    pub candidates: Vec<ShadowCandidate>,
+    /// 已比对次数（每个影子版本计一次）
+    pub compared: u64,
+    /// 输出不一致的次数
+    pub mismatched: u64,
+    /// 影子版本转换失败的次数
+    pub failed: u64,
+    /// 最近的差异，按时间倒序
+    pub recent: Vec<ShadowDiscrepancy>,
+}
+
+struct Candidate {
+    mapping: GoldenMapping,
+    version: &'static str,
+    convert: ShadowConvertFn,
+}
+
+#[derive(Default)]
+struct ShadowState {
+    settings: ConverterShadowSettings,
+    candidates: Vec<Candidate>,
+    compared: u64,
+    mismatched: u64,
+    failed: u64,
+    recent: VecDeque<ShadowDiscrepancy>,
+}
+
+/// 启用且至少注册了一个影子版本
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// 采样计数
+static SAMPLED: AtomicU64 = AtomicU64::new(0);
+
+fn state() -> &'static RwLock<ShadowState> {
+    static STATE: OnceLock<RwLock<ShadowState>> = OnceLock::new();
+    STATE.get_or_init(Default::default)
+}
+
+fn refresh_active(state: &ShadowState) {
+    ACTIVE.store(
+        state.settings.enabled && !state.candidates.is_empty(),
+        Ordering::Relaxed,
+    );
+}
+
+/// 应用配置（启动与热重载时调用），已有的统计与差异记录保留
+pub fn configure(settings: &ConverterShadowSettings) {
+    let mut guard = state().write().unwrap_or_else(|e| e.into_inner());
+    if settings.enabled && guard.candidates.is_empty() {
+        tracing::warn!("[CONVERTER_SHADOW] 已启用影子验证，但没有注册影子版本的转换器");
+    }
+    guard.settings = settings.clone();
+    let max_recent = settings.max_recent;
+    guard.recent.truncate(max_recent);
+    refresh_active(&guard);
+}
+
+/// 注册影子版本，同一转换的同名版本会被替换
+pub fn register_candidate(mapping: GoldenMapping, version: &'static str, convert: ShadowConvertFn) {
+    let mut guard = state().write().unwrap_or_else(|e| e.into_inner());
+    guard
+        .candidates
+        .retain(|c| !(c.mapping == mapping && c.version == version));
+    guard.candidates.push(Candidate {
+        mapping,
+        version,
+        convert,
+    });
+    refresh_active(&guard);
+}
+
+/// 注销影子版本，返回是否存在
+pub fn unregister_candidate(mapping: GoldenMapping, version: &str) -> bool {
+    let mut guard = state().write().unwrap_or_else(|e| e.into_inner());
+    let before = guard.candidates.len();
+    guard
+        .candidates
+        .retain(|c| !(c.mapping == mapping && c.version == version));
+    refresh_active(&guard);
+    guard.candidates.len() != before
+}
+
+/// 按比例采样：累计 `n * rate` 的整数部分每增加一次即采样一次，比例稳定且无需随机数
+fn sampled(rate: f64) -> bool {
+    if rate >= 1.0 {
+        return true;
+    }
+    if rate <= 0.0 {
+        return false;
+    }
+    let n = SAMPLED.fetch_add(1, Ordering::Relaxed);
+    ((n + 1) as f64 * rate).floor() > (n as f64 * rate).floor()
+}
+
+/// 用已注册的影子版本转换同一输入并与线上输出比对
+///
+/// `input` 返回 `(input, options)`，`primary` 返回线上版本的输出，二者只在需要比对时才会调用。
+pub fn observe(
+    mapping: GoldenMapping,
+    input: impl FnOnce() -> (Value, Value),
+    primary: impl FnOnce() -> Value,
+) {
+    if !ACTIVE.load(Ordering::Relaxed) {
+        return;
+    }
+    let (converters, ignore_paths) = {
+        let guard = state().read().unwrap_or_else(|e| e.into_inner());
+        let converters: Vec<(&'static str, ShadowConvertFn)> = guard
+            .candidates
+            .iter()
+            .filter(|c| c.mapping == mapping)
+            .map(|c| (c.version, c.convert))
+            .collect();
+        if converters.is_empty() || !sampled(guard.settings.sample_rate) {
+            return;
+        }
+        (converters, guard.settings.ignore_paths.clone())
+    };
+
+    let (input, options) = input();
+    let primary = primary();
+    for (version, convert) in converters {
+        let (diffs, error) = match convert(&input, &options) {
+            Ok(shadow) => (diff_outputs(&primary, &shadow, &ignore_paths), None),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+        record(mapping, version, diffs, error);
+    }
+}
+
+fn record(
+    mapping: GoldenMapping,
+    version: &str,
+    mut diffs: Vec<GoldenDiff>,
+    error: Option<String>,
+) {
+    let mut guard = state().write().unwrap_or_else(|e| e.into_inner());
+    guard.compared += 1;
+    if let Some(error) = &error {
+        guard.failed += 1;
+        tracing::warn!(
+            "[CONVERTER_SHADOW] {:?} 影子版本 {} 转换失败: {}",
+            mapping,
+            version,
+            error
+        );
+    } else if diffs.is_empty() {
+        return;
+    } else {
+        guard.mismatched += 1;
+        let paths: Vec<&str> = diffs.iter().take(5).map(|d| d.path.as_str()).collect();
+        tracing::warn!(
+            "[CONVERTER_SHADOW] {:?} 影子版本 {} 输出不一致: {} 处差异（{}）",
+            mapping,
+            version,
+            diffs.len(),
+            paths.join(", ")
+        );
+    }
+
+    let max_recent = guard.settings.max_recent;
+    if max_recent == 0 {
+        return;
+    }
+    let total_diffs = diffs.len();
+    diffs.truncate(MAX_DIFFS_PER_RECORD);
+    guard.recent.push_front(ShadowDiscrepancy {
+        at: Utc::now(),
+        mapping,
+        version: version.to_string(),
+        diffs,
+        total_diffs,
+        error,
+    });
+    guard.recent.truncate(max_recent);
+}
+
+pub fn status() -> ConverterShadowStatus {
+    let guard = state().read().unwrap_or_else(|e| e.into_inner());
+    ConverterShadowStatus {
+        enabled: guard.settings.enabled,
+        sample_rate: guard.settings.sample_rate,
+        candidates: guard
+            .candidates
+            .iter()
+            .map(|c| ShadowCandidate {
+                mapping: c.mapping,
+                version: c.version.to_string(),
+            })
+            .collect(),
+        compared: guard.compared,
+        mismatched: guard.mismatched,
+        failed: guard.failed,
+        recent: guard.recent.iter().cloned().collect(),
+    }
+}
+
+/// 清空统计与差异记录，返回清除的差异条数
+pub fn clear() -> usize {
+    let mut guard = state().write().unwrap_or_else(|e| e.into_inner());
+    let cleared = guard.recent.len();
+    guard.recent.clear();
+    guard.compared = 0;
+    guard.mismatched = 0;
+    guard.failed = 0;
+    cleared
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn legacy_embeddings(input: &Value, _options: &Value) -> Result<Value, String> {
+        match input.get("fail") {
+            Some(_) => Err("boom".to_string()),
+            None => Ok(json!({ "object": "list", "model": "legacy", "id": "x" })),
+        }
+    }
+
+    #[test]
+    fn test_observe_records_discrepancies() {
+        // 线上不会对嵌入响应调用 observe，避免与其他测试的转换调用互相影响
+        let mapping = GoldenMapping::OpenaiEmbeddingsResponse;
+        observe(mapping, || unreachable!(), || unreachable!());
+
+        register_candidate(mapping, "v1", legacy_embeddings);
+        configure(&ConverterShadowSettings {
+            enabled: true,
+            ignore_paths: vec!["id".to_string()],
+            ..Default::default()
+        });
+
+        let primary = || json!({ "object": "list", "model": "current" });
+        observe(mapping, || (json!({}), Value::Null), primary);
+        observe(mapping, || (json!({ "fail": true }), Value::Null), primary);
+        observe(
+            mapping,
+            || (json!({}), Value::Null),
+            || json!({ "object": "list", "model": "legacy" }),
+        );
+
+        let status = status();
+        assert_eq!(status.candidates.len(), 1);
+        assert_eq!(status.compared, 3);
+        assert_eq!(status.mismatched, 1);
+        assert_eq!(status.failed, 1);
+        assert_eq!(status.recent[0].error.as_deref(), Some("boom"));
+        assert_eq!(status.recent[1].diffs[0].path, "model");
+        assert_eq!(status.recent[1].diffs[0].actual, json!("legacy"));
+
+        assert_eq!(clear(), 2);
+        assert!(unregister_candidate(mapping, "v1"));
+        assert!(!ACTIVE.load(Ordering::Relaxed));
+    }
+}
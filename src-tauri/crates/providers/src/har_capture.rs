@@ -0,0 +1,645 @@
+//! 上游 HTTP 抓包（HAR 导出）
+//!
+//! 用于排查 Provider 请求头、请求体的细微差异。抓包默认关闭，通过 [`start`] 开启一个
+//! 时间窗口，窗口内 Provider 经 [`CaptureSend::send_captured`] 发出的请求会被记录，
+//! 之后可通过 [`export`] 导出为 HAR 1.2 文件。
+//!
+//! 记录前会脱敏：认证相关的请求头、查询参数以及 JSON / 表单请求体中的密钥字段
+//! 一律替换为 `[REDACTED]`。SSE 流式响应的正文不会被读取，以免打断流式转发。
+
+use std::collections::VecDeque;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use axum::http::Response as HttpResponse;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use reqwest::{Client, Request, RequestBuilder, Response, ResponseBuilderExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::outbound_limit;
+use crate::regional_proxy;
+use crate::upstream_auth;
+
+/// 单次抓包最多保留的记录数（超出后丢弃最早的记录）
+const MAX_ENTRIES: usize = 500;
+/// 单个请求 / 响应正文最多保留的字节数
+const MAX_BODY_BYTES: usize = 256 * 1024;
+/// 抓包窗口上限
+const MAX_WINDOW: Duration = Duration::from_secs(60 * 60);
+/// 脱敏占位符
+const REDACTED: &str = "[REDACTED]";
+
+/// 需要脱敏的请求头 / 响应头
+const SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "x-api-key",
+    "api-key",
+    "x-goog-api-key",
+    "cookie",
+    "set-cookie",
+    "x-amz-security-token",
+];
+
+/// 需要脱敏的查询参数与请求体字段（忽略大小写、下划线与连字符）
+const SENSITIVE_FIELDS: &[&str] = &[
+    "key",
+    "apikey",
+    "accesstoken",
+    "refreshtoken",
+    "idtoken",
+    "clientsecret",
+    "password",
+    "secret",
+    "codeverifier",
+];
+
+/// HAR 中的名值对
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HarNameValue {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarPostData {
+    pub mime_type: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarRequest {
+    pub method: String,
+    pub url: String,
+    pub http_version: String,
+    pub headers: Vec<HarNameValue>,
+    pub query_string: Vec<HarNameValue>,
+    pub cookies: Vec<HarNameValue>,
+    pub headers_size: i64,
+    pub body_size: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_data: Option<HarPostData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarContent {
+    pub size: i64,
+    pub mime_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// 正文未记录或被截断的原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarResponse {
+    /// 请求未得到响应时为 0
+    pub status: u16,
+    pub status_text: String,
+    pub http_version: String,
+    pub headers: Vec<HarNameValue>,
+    pub cookies: Vec<HarNameValue>,
+    pub content: HarContent,
+    #[serde(rename = "redirectURL")]
+    pub redirect_url: String,
+    pub headers_size: i64,
+    pub body_size: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarTimings {
+    pub send: f64,
+    pub wait: f64,
+    pub receive: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarEntry {
+    pub started_date_time: DateTime<Utc>,
+    /// 总耗时（毫秒）
+    pub time: f64,
+    pub request: HarRequest,
+    pub response: HarResponse,
+    pub cache: Value,
+    pub timings: HarTimings,
+    /// 请求失败时的错误信息（HAR 自定义字段）
+    #[serde(rename = "_error", skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarCreator {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarLogBody {
+    pub version: String,
+    pub creator: HarCreator,
+    pub entries: Vec<HarEntry>,
+}
+
+/// HAR 文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarLog {
+    pub log: HarLogBody,
+}
+
+/// 抓包状态
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CaptureStatus {
+    /// 当前是否在抓包窗口内
+    pub active: bool,
+    pub started_at: Option<DateTime<Utc>>,
+    /// 抓包窗口结束时间
+    pub ends_at: Option<DateTime<Utc>>,
+    pub entries: usize,
+    pub max_entries: usize,
+}
+
+#[derive(Default)]
+struct CaptureState {
+    deadline: Option<Instant>,
+    started_at: Option<DateTime<Utc>>,
+    ends_at: Option<DateTime<Utc>>,
+    entries: VecDeque<HarEntry>,
+}
+
+impl CaptureState {
+    fn is_active(&self, now: Instant) -> bool {
+        self.deadline.is_some_and(|deadline| now < deadline)
+    }
+}
+
+fn state() -> &'static RwLock<CaptureState> {
+    static STATE: OnceLock<RwLock<CaptureState>> = OnceLock::new();
+    STATE.get_or_init(Default::default)
+}
+
+/// 开启抓包窗口（会清空上一次的记录）
+pub fn start(duration: Duration) -> CaptureStatus {
+    let duration = duration.clamp(Duration::from_secs(1), MAX_WINDOW);
+    let now = Utc::now();
+    {
+        let mut guard = state().write().unwrap_or_else(|e| e.into_inner());
+        guard.deadline = Some(Instant::now() + duration);
+        guard.started_at = Some(now);
+        guard.ends_at = chrono::Duration::from_std(duration)
+            .ok()
+            .map(|duration| now + duration);
+        guard.entries.clear();
+    }
+    tracing::info!("[HAR] 开始抓包，窗口 {} 秒", duration.as_secs());
+    status()
+}
+
+/// 提前结束抓包窗口（保留已记录的内容）
+pub fn stop() -> CaptureStatus {
+    {
+        let mut guard = state().write().unwrap_or_else(|e| e.into_inner());
+        if guard.is_active(Instant::now()) {
+            guard.ends_at = Some(Utc::now());
+        }
+        guard.deadline = None;
+    }
+    status()
+}
+
+/// 清空已记录的内容
+pub fn clear() {
+    let mut guard = state().write().unwrap_or_else(|e| e.into_inner());
+    guard.entries.clear();
+}
+
+pub fn status() -> CaptureStatus {
+    let guard = state().read().unwrap_or_else(|e| e.into_inner());
+    CaptureStatus {
+        active: guard.is_active(Instant::now()),
+        started_at: guard.started_at,
+        ends_at: guard.ends_at,
+        entries: guard.entries.len(),
+        max_entries: MAX_ENTRIES,
+    }
+}
+
+/// 导出已记录的内容
+pub fn export() -> HarLog {
+    let guard = state().read().unwrap_or_else(|e| e.into_inner());
+    HarLog {
+        log: HarLogBody {
+            version: "1.2".to_string(),
+            creator: HarCreator {
+                name: "Lime".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+            entries: guard.entries.iter().cloned().collect(),
+        },
+    }
+}
+
+fn is_active() -> bool {
+    state()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_active(Instant::now())
+}
+
+fn push_entry(entry: HarEntry) {
+    let mut guard = state().write().unwrap_or_else(|e| e.into_inner());
+    // 请求发出后窗口可能已结束，仍然保留该记录
+    if guard.entries.len() >= MAX_ENTRIES {
+        guard.entries.pop_front();
+    }
+    guard.entries.push_back(entry);
+}
+
+fn is_sensitive_field(name: &str) -> bool {
+    let normalized: String = name
+        .chars()
+        .filter(|c| !matches!(c, '_' | '-'))
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    SENSITIVE_FIELDS.contains(&normalized.as_str())
+}
+
+fn sanitize_headers(headers: &HeaderMap) -> Vec<HarNameValue> {
+    let configured = upstream_auth::credential_header_names();
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if SENSITIVE_HEADERS.contains(&name.as_str())
+                || configured.iter().any(|header| header == name.as_str())
+            {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            HarNameValue {
+                name: name.as_str().to_string(),
+                value,
+            }
+        })
+        .collect()
+}
+
+/// 脱敏 URL 查询参数，返回脱敏后的 URL 与参数列表
+fn sanitize_url(url: &url::Url) -> (String, Vec<HarNameValue>) {
+    let query: Vec<HarNameValue> = url
+        .query_pairs()
+        .map(|(name, value)| HarNameValue {
+            value: if is_sensitive_field(&name) {
+                REDACTED.to_string()
+            } else {
+                value.into_owned()
+            },
+            name: name.into_owned(),
+        })
+        .collect();
+    if query.is_empty() {
+        return (url.to_string(), query);
+    }
+    let mut sanitized = url.clone();
+    sanitized
+        .query_pairs_mut()
+        .clear()
+        .extend_pairs(query.iter().map(|pair| (&pair.name, &pair.value)));
+    (sanitized.to_string(), query)
+}
+
+fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_sensitive_field(key) && !value.is_null() {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// 脱敏并截断正文，返回（正文, 备注）
+fn sanitize_body(mime_type: &str, body: &[u8]) -> (String, Option<String>) {
+    if mime_type.contains("json") {
+        if let Ok(mut json) = serde_json::from_slice::<Value>(body) {
+            redact_json(&mut json);
+            return truncate_body(json.to_string());
+        }
+    }
+    if mime_type.contains("x-www-form-urlencoded") {
+        let pairs: Vec<(String, String)> = url::form_urlencoded::parse(body)
+            .map(|(name, value)| {
+                let value = if is_sensitive_field(&name) {
+                    REDACTED.to_string()
+                } else {
+                    value.into_owned()
+                };
+                (name.into_owned(), value)
+            })
+            .collect();
+        let text = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(pairs)
+            .finish();
+        return truncate_body(text);
+    }
+    match std::str::from_utf8(body) {
+        Ok(text) => truncate_body(text.to_string()),
+        Err(_) => (
+            String::new(),
+            Some(format!("二进制正文（{} 字节）未记录", body.len())),
+        ),
+    }
+}
+
+fn truncate_body(mut text: String) -> (String, Option<String>) {
+    if text.len() <= MAX_BODY_BYTES {
+        return (text, None);
+    }
+    let original = text.len();
+    let mut end = MAX_BODY_BYTES;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    (text, Some(format!("正文共 {original} 字节，已截断")))
+}
+
+fn mime_type_of(headers: &HeaderMap) -> String {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_string()
+}
+
+fn format_version(version: reqwest::Version) -> String {
+    format!("{version:?}")
+}
+
+fn build_request(request: &reqwest::Request) -> HarRequest {
+    let (url, query_string) = sanitize_url(request.url());
+    let mime_type = mime_type_of(request.headers());
+    let body = request.body().and_then(|body| body.as_bytes());
+    HarRequest {
+        method: request.method().to_string(),
+        url,
+        http_version: format_version(request.version()),
+        headers: sanitize_headers(request.headers()),
+        query_string,
+        cookies: Vec::new(),
+        headers_size: -1,
+        body_size: body.map_or(0, |body| body.len() as i64),
+        post_data: body.map(|body| HarPostData {
+            text: sanitize_body(&mime_type, body).0,
+            mime_type,
+        }),
+    }
+}
+
+fn build_response(
+    status: reqwest::StatusCode,
+    version: reqwest::Version,
+    headers: &HeaderMap,
+    body: Option<&Bytes>,
+    skipped: Option<String>,
+) -> HarResponse {
+    let mime_type = mime_type_of(headers);
+    let (text, comment) = match body {
+        Some(body) => {
+            let (text, comment) = sanitize_body(&mime_type, body);
+            (Some(text), comment)
+        }
+        None => (None, skipped),
+    };
+    let size = body.map_or(-1, |body| body.len() as i64);
+    HarResponse {
+        status: status.as_u16(),
+        status_text: status.canonical_reason().unwrap_or("").to_string(),
+        http_version: format_version(version),
+        headers: sanitize_headers(headers),
+        cookies: Vec::new(),
+        content: HarContent {
+            size,
+            mime_type,
+            text,
+            comment,
+        },
+        redirect_url: String::new(),
+        headers_size: -1,
+        body_size: size,
+    }
+}
+
+fn failed_response(error: &reqwest::Error) -> HarResponse {
+    HarResponse {
+        status: error.status().map_or(0, |status| status.as_u16()),
+        status_text: String::new(),
+        http_version: String::new(),
+        headers: Vec::new(),
+        cookies: Vec::new(),
+        content: HarContent {
+            size: 0,
+            mime_type: String::new(),
+            text: None,
+            comment: None,
+        },
+        redirect_url: String::new(),
+        headers_size: -1,
+        body_size: -1,
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// 读取响应正文用于记录，并重建一个等价的响应交还调用方
+///
+/// SSE 流式响应与超大正文不读取，原样返回。
+async fn capture_response(response: Response) -> reqwest::Result<(Response, HarResponse)> {
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+    let mime_type = mime_type_of(&headers);
+    // SSE 与 AWS Event Stream（Kiro）均为流式响应
+    let skipped = if mime_type.contains("event-stream") || mime_type.contains("eventstream") {
+        Some("流式响应正文未记录".to_string())
+    } else if response
+        .content_length()
+        .is_some_and(|len| len > MAX_BODY_BYTES as u64 * 4)
+    {
+        Some("响应正文过大，未记录".to_string())
+    } else {
+        None
+    };
+    if skipped.is_some() {
+        let har = build_response(status, version, &headers, None, skipped);
+        return Ok((response, har));
+    }
+
+    let url = response.url().clone();
+    let body = response.bytes().await?;
+    let har = build_response(status, version, &headers, Some(&body), None);
+    let mut builder = HttpResponse::builder()
+        .status(status)
+        .version(version)
+        .url(url);
+    if let Some(target) = builder.headers_mut() {
+        *target = headers;
+    }
+    let rebuilt = builder
+        .body(body)
+        .map(Response::from)
+        .expect("response parts come from a valid response");
+    Ok((rebuilt, har))
+}
+
+async fn send_and_record(builder: RequestBuilder) -> reqwest::Result<Response> {
+    let (client, request) = builder.build_split();
+    record(&client, request?).await
+}
+
+/// 发送已构建的请求，抓包窗口内记录
+pub(crate) async fn execute(client: &Client, request: Request) -> reqwest::Result<Response> {
+    if is_active() {
+        record(client, request).await
+    } else {
+        client.execute(request).await
+    }
+}
+
+async fn record(client: &Client, request: Request) -> reqwest::Result<Response> {
+    let har_request = build_request(&request);
+    let started_date_time = Utc::now();
+    let started = Instant::now();
+
+    let result = client.execute(request).await;
+    let wait = started.elapsed();
+    let (result, har_response, error) = match result {
+        Ok(response) => match capture_response(response).await {
+            Ok((response, har)) => (Ok(response), har, None),
+            Err(e) => {
+                let har = failed_response(&e);
+                let message = e.to_string();
+                (Err(e), har, Some(message))
+            }
+        },
+        Err(e) => {
+            let har = failed_response(&e);
+            let message = e.to_string();
+            (Err(e), har, Some(message))
+        }
+    };
+    let total = started.elapsed();
+
+    push_entry(HarEntry {
+        started_date_time,
+        time: millis(total),
+        request: har_request,
+        response: har_response,
+        cache: Value::Object(Default::default()),
+        timings: HarTimings {
+            send: 0.0,
+            wait: millis(wait),
+            receive: millis(total.saturating_sub(wait)),
+        },
+        error,
+    });
+    result
+}
+
+/// 按地区代理与抓包设置发送（已附加上游网关认证）
+pub(crate) fn dispatch(builder: RequestBuilder) -> BoxFuture<'static, reqwest::Result<Response>> {
+    if regional_proxy::is_enabled() {
+        Box::pin(regional_proxy::send(builder))
+    } else if is_active() {
+        Box::pin(send_and_record(builder))
+    } else {
+        Box::pin(builder.send())
+    }
+}
+
+/// 带抓包的请求发送
+///
+/// 抓包窗口之外、未启用按地区代理（见 [`regional_proxy`]）、未配置上游网关认证
+/// （见 [`upstream_auth`]）且不受出站限额（见 [`outbound_limit`]）约束时与
+/// `RequestBuilder::send` 完全相同。
+pub trait CaptureSend {
+    fn send_captured(self) -> BoxFuture<'static, reqwest::Result<Response>>;
+}
+
+impl CaptureSend for RequestBuilder {
+    fn send_captured(self) -> BoxFuture<'static, reqwest::Result<Response>> {
+        Box::pin(async move {
+            if let Some(limited) = outbound_limit::acquire().await {
+                return Ok(limited);
+            }
+            if upstream_auth::is_enabled() {
+                upstream_auth::send(self).await
+            } else {
+                dispatch(self).await
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_redacts_secrets() {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer sk-secret".parse().unwrap());
+        headers.insert("x-goog-api-key", "AIza-secret".parse().unwrap());
+        headers.insert("anthropic-version", "2023-06-01".parse().unwrap());
+        let headers = sanitize_headers(&headers);
+        assert!(headers
+            .iter()
+            .filter(|h| h.name != "anthropic-version")
+            .all(|h| h.value == REDACTED));
+        assert!(headers.iter().any(|h| h.value == "2023-06-01"));
+
+        let url = url::Url::parse("https://example.com/v1/models?key=AIza&alt=sse").unwrap();
+        let (url, query) = sanitize_url(&url);
+        assert!(!url.contains("AIza"));
+        assert_eq!(query[1].value, "sse");
+
+        let (json, _) = sanitize_body(
+            "application/json",
+            br#"{"model":"m","max_tokens":8,"auth":{"refreshToken":"rt","api_key":null}}"#,
+        );
+        let json: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["auth"]["refreshToken"], REDACTED);
+        assert_eq!(json["auth"]["api_key"], Value::Null);
+        assert_eq!(json["max_tokens"], 8);
+
+        let (form, _) = sanitize_body(
+            "application/x-www-form-urlencoded",
+            b"grant_type=refresh_token&refresh_token=rt&client_secret=cs",
+        );
+        assert_eq!(
+            form,
+            "grant_type=refresh_token&refresh_token=%5BREDACTED%5D&client_secret=%5BREDACTED%5D"
+        );
+
+        let (text, comment) = truncate_body("中".repeat(MAX_BODY_BYTES));
+        assert!(text.len() <= MAX_BODY_BYTES);
+        assert!(comment.is_some());
+    }
+}
@@ -5,14 +5,27 @@
 //! ## 模块结构
 //! - `providers`: Provider 实现（Kiro、Gemini、Claude、OpenAI、Vertex 等）
 //! - `converter`: 协议转换（OpenAI ↔ CW、OpenAI ↔ Antigravity 等）
+//! - `context_cache`: Gemini 显式上下文缓存
 //! - `streaming`: 流式传输管理
 //! - `translator`: 请求/响应翻译层
 //! - `stream`: 流事件解析和生成
 //! - `session`: 会话管理（签名存储、会话 ID 生成）
+//! - `response_headers`: 上游响应头记录
+//! - `har_capture`: 上游 HTTP 抓包（HAR 导出）
+//! - `upload_dedup`: 多模态素材上传去重
+//! - `upstream_auth`: 上游企业网关认证（静态请求头、HMAC、OAuth 客户端凭证）
+//! - `outbound_limit`: 按凭证的出站限额钩子
 
+pub mod context_cache;
 pub mod converter;
+pub mod har_capture;
+pub mod outbound_limit;
 pub mod providers;
+pub mod regional_proxy;
+pub mod response_headers;
 pub mod session;
 pub mod stream;
 pub mod streaming;
 pub mod translator;
+pub mod upload_dedup;
+pub mod upstream_auth;
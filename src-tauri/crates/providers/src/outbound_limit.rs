@@ -0,0 +1,111 @@
+//! 按凭证的出站限额钩子
+//!
+//! 服务器通过 [`install`] 注册限额检查，调用凭证时以 [`scope`] 标记当前凭证；作用域内经
+//! [`CaptureSend::send_captured`](crate::har_capture::CaptureSend) 发出的每个请求都会先占用
+//! 一次额度（含重试与降级），超出时不再发往上游，直接返回带 `Retry-After` 的 429 响应。
+//! 未注册检查或不在作用域内时为空操作。
+
+use std::future::Future;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+
+use axum::http::Response as HttpResponse;
+use futures::future::BoxFuture;
+use reqwest::header::{CONTENT_TYPE, RETRY_AFTER};
+use reqwest::{Response, StatusCode};
+
+/// 出站限额被触发
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutboundLimited {
+    /// 触发的限额（`rps` / `tpm`）
+    pub limit: &'static str,
+    pub retry_after: Duration,
+}
+
+/// 按凭证 ID 检查并占用一次出站额度
+pub type OutboundCheck =
+    Arc<dyn Fn(String) -> BoxFuture<'static, Result<(), OutboundLimited>> + Send + Sync>;
+
+tokio::task_local! {
+    static CREDENTIAL_ID: String;
+}
+
+fn check() -> &'static RwLock<Option<OutboundCheck>> {
+    static CHECK: OnceLock<RwLock<Option<OutboundCheck>>> = OnceLock::new();
+    CHECK.get_or_init(|| RwLock::new(None))
+}
+
+/// 注册（或以 `None` 清除）出站限额检查（服务器启动时调用）
+pub fn install(limiter: Option<OutboundCheck>) {
+    *check().write().unwrap_or_else(|e| e.into_inner()) = limiter;
+}
+
+/// 在凭证作用域内执行 `future`，其中发出的上游请求计入该凭证的出站额度
+pub async fn scope<F: Future>(credential_id: &str, future: F) -> F::Output {
+    CREDENTIAL_ID.scope(credential_id.to_string(), future).await
+}
+
+/// 发送前占用额度，超出限额时返回代替上游响应的 429 响应
+pub(crate) async fn acquire() -> Option<Response> {
+    let limiter = check().read().unwrap_or_else(|e| e.into_inner()).clone()?;
+    let credential_id = CREDENTIAL_ID.try_with(Clone::clone).ok()?;
+    let limited = limiter(credential_id.clone()).await.err()?;
+    tracing::warn!(
+        "[RATE_LIMIT] 凭证 {} 出站 {} 限额已满，{} 秒后重试",
+        credential_id,
+        limited.limit,
+        limited.retry_after.as_secs().max(1)
+    );
+    Some(limited_response(&limited))
+}
+
+fn limited_response(limited: &OutboundLimited) -> Response {
+    let retry_after_secs = limited.retry_after.as_secs().max(1);
+    let body = serde_json::json!({
+        "error": {
+            "message": format!(
+                "Outbound {} limit reached for credential. Retry after {} seconds",
+                limited.limit, retry_after_secs
+            ),
+            "type": "rate_limit_error",
+        }
+    });
+    HttpResponse::builder()
+        .status(StatusCode::TOO_MANY_REQUESTS)
+        .header(CONTENT_TYPE, "application/json")
+        .header(RETRY_AFTER, retry_after_secs.to_string())
+        .body(body.to_string())
+        .map(Response::from)
+        .expect("static response parts are valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_acquire_only_limits_inside_scope() {
+        install(Some(Arc::new(
+            |credential_id: String| -> BoxFuture<'static, Result<(), OutboundLimited>> {
+                Box::pin(async move {
+                    if credential_id == "cred-busy" {
+                        Err(OutboundLimited {
+                            limit: "rps",
+                            retry_after: Duration::from_millis(300),
+                        })
+                    } else {
+                        Ok(())
+                    }
+                })
+            },
+        )));
+
+        assert!(acquire().await.is_none());
+        assert!(scope("cred-idle", acquire()).await.is_none());
+        let response = scope("cred-busy", acquire()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RETRY_AFTER], "1");
+
+        install(None);
+    }
+}
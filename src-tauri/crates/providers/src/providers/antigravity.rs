@@ -4,8 +4,14 @@
 
 #![allow(dead_code)]
 
-use super::traits::{CredentialProvider, ProviderResult};
+use super::endpoint_latency;
+use super::endpoints;
+use super::registry::ProviderSetupError;
+use super::traits::{CredentialProvider, Provider, ProviderResult};
+use crate::converter::code_execution;
+use crate::har_capture::CaptureSend;
 use async_trait::async_trait;
+use lime_core::config::ChatImageSettings;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
@@ -95,6 +101,26 @@ const ANTIGRAVITY_BASE_URL_PROD: &str = "https://cloudcode-pa.googleapis.com";
 const ANTIGRAVITY_BASE_URL_DAILY: &str = "https://daily-cloudcode-pa.sandbox.googleapis.com";
 const ANTIGRAVITY_BASE_URL_AUTOPUSH: &str = "https://autopush-cloudcode-pa.sandbox.googleapis.com";
 const ANTIGRAVITY_API_VERSION: &str = "v1internal";
+
+/// 上游地址列表：配置了 `providers.endpoints.antigravity.base_url` 时只使用该地址
+pub(crate) fn default_base_urls() -> Vec<String> {
+    if endpoints::has_base_url_override(endpoints::ANTIGRAVITY) {
+        return vec![endpoints::base_url(
+            endpoints::ANTIGRAVITY,
+            ANTIGRAVITY_BASE_URL_PROD,
+        )];
+    }
+    // 只使用生产环境和 daily 环境（参考 Antigravity-Manager）
+    // 沙盒环境（autopush）需要特殊许可证，不适合普通用户
+    vec![
+        ANTIGRAVITY_BASE_URL_PROD.to_string(),
+        ANTIGRAVITY_BASE_URL_DAILY.to_string(),
+    ]
+}
+
+fn api_version() -> String {
+    endpoints::api_version(endpoints::ANTIGRAVITY, ANTIGRAVITY_API_VERSION)
+}
 const CREDENTIALS_DIR: &str = ".antigravity";
 const CREDENTIALS_FILE: &str = "oauth_creds.json";
 
@@ -374,6 +400,10 @@ pub struct AntigravityProvider {
     pub client: Client,
     pub base_urls: Vec<String>,
     pub available_models: Vec<String>,
+    /// 代码执行工具设置：`Some(true)` 附加，`Some(false)` 移除，`None` 保持请求原样
+    pub code_execution: Option<bool>,
+    /// 对话响应中的图片输出格式（[`Provider::chat`] 使用）
+    pub chat_images: ChatImageSettings,
 }
 
 impl Default for AntigravityProvider {
@@ -385,16 +415,13 @@ impl Default for AntigravityProvider {
                 .timeout(std::time::Duration::from_secs(120))
                 .build()
                 .unwrap_or_else(|_| Client::new()),
-            // 只使用生产环境和 daily 环境（参考 Antigravity-Manager）
-            // 沙盒环境（autopush）需要特殊许可证，不适合普通用户
-            base_urls: vec![
-                ANTIGRAVITY_BASE_URL_PROD.to_string(),
-                ANTIGRAVITY_BASE_URL_DAILY.to_string(),
-            ],
+            base_urls: endpoint_latency::ordered_base_urls(endpoints::ANTIGRAVITY, None),
             available_models: ANTIGRAVITY_MODELS_FALLBACK
                 .iter()
                 .map(|s| s.to_string())
                 .collect(),
+            code_execution: None,
+            chat_images: ChatImageSettings::default(),
         }
     }
 }
@@ -404,6 +431,58 @@ impl AntigravityProvider {
         Self::default()
     }
 
+    /// 按凭证创建：端点顺序考虑该凭证的固定端点
+    pub fn for_credential(credential_uuid: &str) -> Self {
+        Self {
+            base_urls: endpoint_latency::ordered_base_urls(
+                endpoints::ANTIGRAVITY,
+                Some(credential_uuid),
+            ),
+            ..Self::default()
+        }
+    }
+
+    /// 按号池凭证准备可直接调用的实例：加载凭证、按需刷新 Token、确定项目 ID
+    ///
+    /// 返回实例与是否刷新过 Token。项目 ID 获取失败时仅记录警告，由调用方决定后备值。
+    pub async fn prepare(
+        credential_uuid: &str,
+        creds_file_path: &str,
+        project_id: Option<&str>,
+    ) -> Result<(Self, bool), ProviderSetupError> {
+        let mut provider = Self::for_credential(credential_uuid);
+        provider
+            .load_credentials_from_path(creds_file_path)
+            .await
+            .map_err(|e| ProviderSetupError::LoadCredentials {
+                provider: "Antigravity",
+                message: e.to_string(),
+            })?;
+
+        let validation_result = provider.validate_token();
+        tracing::info!("[Antigravity] Token 验证结果: {:?}", validation_result);
+        let mut token_refreshed = false;
+        if validation_result.needs_refresh() {
+            tracing::info!("[Antigravity] Token 需要刷新，开始刷新...");
+            let new_token = provider.refresh_token_with_retry(3).await.map_err(|e| {
+                tracing::error!("[Antigravity] Token 刷新失败: {:?}", e);
+                ProviderSetupError::TokenRefresh(e)
+            })?;
+            tracing::info!(
+                "[Antigravity] Token 刷新成功，新 token 长度: {}",
+                new_token.len()
+            );
+            token_refreshed = true;
+        }
+
+        if let Some(pid) = project_id {
+            provider.project_id = Some(pid.to_string());
+        } else if let Err(e) = provider.discover_project().await {
+            tracing::warn!("[Antigravity] Failed to discover project: {}", e);
+        }
+        Ok((provider, token_refreshed))
+    }
+
     pub fn default_creds_path() -> PathBuf {
         dirs::home_dir()
             .unwrap_or_else(|| PathBuf::from("."))
@@ -693,7 +772,7 @@ impl AntigravityProvider {
                 .client
                 .post("https://oauth2.googleapis.com/token")
                 .form(&params)
-                .send()
+                .send_captured()
                 .await;
 
             match result {
@@ -803,7 +882,7 @@ impl AntigravityProvider {
             .client
             .post("https://oauth2.googleapis.com/token")
             .form(&params)
-            .send()
+            .send_captured()
             .await?;
 
         if !resp.status().is_success() {
@@ -863,7 +942,7 @@ impl AntigravityProvider {
             .as_ref()
             .ok_or_else(|| AntigravityApiError::new(401, "No access token"))?;
 
-        let url = format!("{base_url}/{ANTIGRAVITY_API_VERSION}:{method}");
+        let url = format!("{base_url}/{}:{method}", api_version());
 
         // 打印详细的请求信息
         eprintln!("========== [ANTIGRAVITY_API] 请求详情 ==========");
@@ -885,7 +964,7 @@ impl AntigravityProvider {
             .header("Content-Type", "application/json")
             .header("User-Agent", "antigravity/1.11.9 windows/amd64")
             .json(body)
-            .send()
+            .send_captured()
             .await
             .map_err(|e| {
                 eprintln!("[ANTIGRAVITY_API] 网络错误: {e}");
@@ -932,6 +1011,17 @@ impl AntigravityProvider {
     ) -> Result<serde_json::Value, AntigravityApiError> {
         let mut last_error: Option<AntigravityApiError> = None;
 
+        let bridged;
+        let body = match self.code_execution {
+            Some(enabled) if method.ends_with("enerateContent") => {
+                let mut payload = body.clone();
+                code_execution::apply_code_execution_tool(&mut payload, enabled);
+                bridged = payload;
+                &bridged
+            }
+            _ => body,
+        };
+
         for (idx, base_url) in self.base_urls.iter().enumerate() {
             match self.call_api_internal(base_url, method, body).await {
                 Ok(data) => return Ok(data),
@@ -1230,7 +1320,7 @@ pub async fn exchange_code_for_token(
     let resp = client
         .post("https://oauth2.googleapis.com/token")
         .form(&params)
-        .send()
+        .send_captured()
         .await?;
 
     if !resp.status().is_success() {
@@ -1251,7 +1341,7 @@ pub async fn fetch_user_email(
     let resp = client
         .get("https://www.googleapis.com/oauth2/v2/userinfo")
         .header("Authorization", format!("Bearer {access_token}"))
-        .send()
+        .send_captured()
         .await?;
 
     if resp.status().is_success() {
@@ -1288,7 +1378,7 @@ pub async fn fetch_project_id_for_oauth(
         .header("User-Agent", "antigravity/1.11.9 windows/amd64")
         .header("Content-Type", "application/json")
         .json(&serde_json::json!({ "metadata": { "ideType": "ANTIGRAVITY" } }))
-        .send()
+        .send_captured()
         .await?;
 
     let status = resp.status();
@@ -2165,7 +2255,10 @@ impl StreamingProvider for AntigravityProvider {
         );
 
         // 使用统一的转换函数构建请求体
-        let payload = convert_openai_to_antigravity_with_context(request, &project_id);
+        let mut payload = convert_openai_to_antigravity_with_context(request, &project_id);
+        if let Some(enabled) = self.code_execution {
+            code_execution::apply_code_execution_tool(&mut payload, enabled);
+        }
 
         tracing::info!(
             "[ANTIGRAVITY_STREAM] 请求体 (完整): {}",
@@ -2176,7 +2269,7 @@ impl StreamingProvider for AntigravityProvider {
         let mut last_error: Option<ProviderError> = None;
 
         for base_url in &self.base_urls {
-            let url = format!("{base_url}/{ANTIGRAVITY_API_VERSION}:streamGenerateContent");
+            let url = format!("{base_url}/{}:streamGenerateContent", api_version());
 
             eprintln!("[ANTIGRAVITY_STREAM] ========== 发起 HTTP 请求 ==========");
             eprintln!("[ANTIGRAVITY_STREAM] URL: {url}");
@@ -2199,7 +2292,7 @@ impl StreamingProvider for AntigravityProvider {
                 .header("Accept", "text/event-stream")
                 .header("User-Agent", "antigravity/1.11.9 windows/amd64")
                 .json(&payload)
-                .send()
+                .send_captured()
                 .await;
 
             match result {
@@ -2262,6 +2355,73 @@ impl StreamingProvider for AntigravityProvider {
     }
 }
 
+// ============================================================================
+// Provider Trait 实现
+// ============================================================================
+
+use crate::converter::openai_to_antigravity::{
+    convert_antigravity_image_response, convert_antigravity_to_openai_response_with_images,
+    convert_image_request_to_antigravity,
+};
+use lime_core::models::openai::{ImageGenerationRequest, ImageGenerationResponse};
+
+impl From<AntigravityApiError> for ProviderError {
+    fn from(e: AntigravityApiError) -> Self {
+        ProviderError::HttpError {
+            status: e.status_code,
+            message: e.message,
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for AntigravityProvider {
+    fn name(&self) -> &'static str {
+        "antigravity"
+    }
+
+    async fn chat(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> Result<serde_json::Value, ProviderError> {
+        let project_id = self.project_id.clone().unwrap_or_default();
+        let payload = convert_openai_to_antigravity_with_context(request, &project_id);
+        let resp = self.call_api("generateContent", &payload).await?;
+        Ok(convert_antigravity_to_openai_response_with_images(
+            &resp,
+            &request.model,
+            &self.chat_images,
+        ))
+    }
+
+    async fn chat_stream(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> Result<(StreamFormat, StreamResponse), ProviderError> {
+        let stream = StreamingProvider::call_api_stream(self, request).await?;
+        Ok((StreamFormat::GeminiStream, stream))
+    }
+
+    async fn generate_image(
+        &self,
+        request: &ImageGenerationRequest,
+    ) -> Result<ImageGenerationResponse, ProviderError> {
+        let project_id = self.project_id.clone().unwrap_or_default();
+        let payload = convert_image_request_to_antigravity(request, &project_id);
+        // 直接使用 call_api：generate_content 的响应整理会丢失嵌套在 response 字段下的图片
+        let resp = self.call_api("generateContent", &payload).await?;
+        convert_antigravity_image_response(&resp, &request.response_format)
+            .map_err(ProviderError::ConversionError)
+    }
+
+    async fn health_probe(&self) -> Result<(), ProviderError> {
+        self.call_api("fetchAvailableModels", &serde_json::json!({}))
+            .await
+            .map(|_| ())
+            .map_err(ProviderError::from)
+    }
+}
+
 // ==================== 测试模块 ====================
 
 #[cfg(test)]
@@ -1,7 +1,10 @@
 //! Claude Custom Provider (自定义 Claude API)
+use super::endpoints;
+use crate::har_capture::CaptureSend;
+use crate::upload_dedup;
 use lime_core::models::anthropic::AnthropicMessagesRequest;
 use lime_core::models::openai::{ChatCompletionRequest, ContentPart, MessageContent};
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder};
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::time::Duration;
@@ -13,6 +16,28 @@ pub struct ClaudeCustomConfig {
     pub enabled: bool,
 }
 
+/// 默认的 `anthropic-version` 请求头
+pub const DEFAULT_ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// 上游 `anthropic-version`（支持 `providers.claude.api_version` 覆盖）
+pub fn anthropic_version() -> String {
+    endpoints::api_version(endpoints::CLAUDE, DEFAULT_ANTHROPIC_VERSION)
+}
+
+/// 附加请求体：上传去重改写过的请求体优先，并携带 Files API 的 beta 头
+fn attach_body<T: Serialize + ?Sized>(
+    builder: RequestBuilder,
+    deduped: Option<&serde_json::Value>,
+    request: &T,
+) -> RequestBuilder {
+    match deduped {
+        Some(body) => builder
+            .header("anthropic-beta", upload_dedup::ANTHROPIC_FILES_BETA)
+            .json(body),
+        None => builder.json(request),
+    }
+}
+
 pub struct ClaudeCustomProvider {
     pub config: ClaudeCustomConfig,
     pub client: Client,
@@ -66,7 +91,7 @@ impl ClaudeCustomProvider {
         self.config
             .base_url
             .clone()
-            .unwrap_or_else(|| "https://api.anthropic.com".to_string())
+            .unwrap_or_else(|| endpoints::base_url(endpoints::CLAUDE, "https://api.anthropic.com"))
     }
 
     pub fn is_configured(&self) -> bool {
@@ -88,6 +113,29 @@ impl ClaudeCustomProvider {
         }
     }
 
+    /// 启用上传去重时把请求中的内联素材替换为 Files API 引用
+    ///
+    /// 有素材被替换时返回改写后的请求体。
+    async fn dedup_uploads<T: Serialize + ?Sized>(
+        &self,
+        api_key: &str,
+        request: &T,
+    ) -> Option<serde_json::Value> {
+        if !upload_dedup::is_enabled() {
+            return None;
+        }
+        let mut body = serde_json::to_value(request).ok()?;
+        let replaced = upload_dedup::dedup_anthropic(
+            &self.client,
+            &self.build_url("files"),
+            &anthropic_version(),
+            api_key,
+            &mut body,
+        )
+        .await;
+        (replaced > 0).then_some(body)
+    }
+
     /// 将 OpenAI 图片 URL 格式转换为 Claude 图片格式
     ///
     /// 支持两种格式：
@@ -234,14 +282,15 @@ impl ClaudeCustomProvider {
             request.stream
         );
 
-        let resp = self
+        let deduped = self.dedup_uploads(api_key, request).await;
+        let builder = self
             .client
             .post(&url)
             .header("x-api-key", api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("Content-Type", "application/json")
-            .json(request)
-            .send()
+            .header("anthropic-version", anthropic_version())
+            .header("Content-Type", "application/json");
+        let resp = attach_body(builder, deduped.as_ref(), request)
+            .send_captured()
             .await?;
 
         // 打印响应状态
@@ -356,14 +405,15 @@ impl ClaudeCustomProvider {
             request.stream
         );
 
-        let resp = self
+        let deduped = self.dedup_uploads(api_key, &anthropic_body).await;
+        let builder = self
             .client
             .post(&url)
             .header("x-api-key", api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("Content-Type", "application/json")
-            .json(&anthropic_body)
-            .send()
+            .header("anthropic-version", anthropic_version())
+            .header("Content-Type", "application/json");
+        let resp = attach_body(builder, deduped.as_ref(), &anthropic_body)
+            .send_captured()
             .await?;
 
         // 打印响应状态
@@ -451,14 +501,15 @@ impl ClaudeCustomProvider {
             stream
         );
 
-        let resp = self
+        let deduped = self.dedup_uploads(api_key, request).await;
+        let builder = self
             .client
             .post(&url)
             .header("x-api-key", api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("Content-Type", "application/json")
-            .json(request)
-            .send()
+            .header("anthropic-version", anthropic_version())
+            .header("Content-Type", "application/json");
+        let resp = attach_body(builder, deduped.as_ref(), request)
+            .send_captured()
             .await?;
 
         // 打印响应状态
@@ -487,10 +538,10 @@ impl ClaudeCustomProvider {
             .client
             .post(&url)
             .header("x-api-key", api_key)
-            .header("anthropic-version", "2023-06-01")
+            .header("anthropic-version", anthropic_version())
             .header("Content-Type", "application/json")
             .json(request)
-            .send()
+            .send_captured()
             .await?;
 
         if !resp.status().is_success() {
@@ -682,15 +733,16 @@ impl StreamingProvider for ClaudeCustomProvider {
             request.model
         );
 
-        let resp = self
+        let deduped = self.dedup_uploads(api_key, &anthropic_body).await;
+        let builder = self
             .client
             .post(&url)
             .header("x-api-key", api_key)
-            .header("anthropic-version", "2023-06-01")
+            .header("anthropic-version", anthropic_version())
             .header("Content-Type", "application/json")
-            .header("Accept", "text/event-stream")
-            .json(&anthropic_body)
-            .send()
+            .header("Accept", "text/event-stream");
+        let resp = attach_body(builder, deduped.as_ref(), &anthropic_body)
+            .send_captured()
             .await
             .map_err(|e| ProviderError::from_reqwest_error(&e))?;
 
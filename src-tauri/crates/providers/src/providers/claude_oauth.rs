@@ -19,6 +19,7 @@
 use super::error::{
     create_auth_error, create_config_error, create_token_refresh_error, ProviderError,
 };
+use crate::har_capture::CaptureSend;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
@@ -245,7 +246,7 @@ impl ClaudeOAuthProvider {
             .header("Content-Type", "application/json")
             .header("Accept", "application/json")
             .json(&body)
-            .send()
+            .send_captured()
             .await
             .map_err(|e| Box::new(ProviderError::from(e)) as Box<dyn Error + Send + Sync>)?;
 
@@ -461,7 +462,7 @@ pub async fn exchange_claude_code_for_token(
         .header("Accept", "application/json")
         .header("User-Agent", "claude-cli/1.0.56 (external, cli)")
         .json(&body)
-        .send()
+        .send_captured()
         .await?;
 
     if !resp.status().is_success() {
@@ -714,7 +715,7 @@ async fn get_organization_info(
     let resp = client
         .get(CLAUDE_ORGANIZATIONS_URL)
         .headers(headers)
-        .send()
+        .send_captured()
         .await?;
 
     if !resp.status().is_success() {
@@ -805,7 +806,7 @@ async fn authorize_with_cookie(
         .post(&authorize_url)
         .headers(headers)
         .json(&payload)
-        .send()
+        .send_captured()
         .await?;
 
     if !resp.status().is_success() {
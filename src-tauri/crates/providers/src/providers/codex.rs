@@ -5,9 +5,11 @@
 
 #![allow(dead_code)]
 
+use super::endpoints;
 use super::error::{
     create_auth_error, create_config_error, create_token_refresh_error, ProviderError,
 };
+use crate::har_capture::CaptureSend;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
@@ -444,8 +446,8 @@ impl CodexProvider {
     }
 
     /// Get the API base URL
-    pub fn get_api_base_url(&self) -> &'static str {
-        CODEX_API_BASE_URL
+    pub fn get_api_base_url(&self) -> String {
+        endpoints::base_url(endpoints::CODEX, CODEX_API_BASE_URL)
     }
 
     /// 获取已配置的 API Key（trim 后的非空值）
@@ -642,7 +644,7 @@ impl CodexProvider {
             .header("Content-Type", "application/x-www-form-urlencoded")
             .header("Accept", "application/json")
             .form(&params)
-            .send()
+            .send_captured()
             .await?;
 
         if !resp.status().is_success() {
@@ -765,7 +767,7 @@ impl CodexProvider {
             .header("Content-Type", "application/x-www-form-urlencoded")
             .header("Accept", "application/json")
             .form(&params)
-            .send()
+            .send_captured()
             .await
             .map_err(|e| Box::new(ProviderError::from(e)) as Box<dyn Error + Send + Sync>)?;
 
@@ -1066,21 +1068,18 @@ impl CodexProvider {
         // Build the Codex API URL
         let url = match mode {
             AuthMode::ApiKey => {
-                let has_custom_base_url = self
+                let credential_base_url = self
                     .credentials
                     .api_base_url
                     .as_deref()
                     .map(|s| s.trim())
-                    .filter(|s| !s.is_empty())
-                    .is_some();
+                    .filter(|s| !s.is_empty());
+                let has_custom_base_url = credential_base_url.is_some()
+                    || endpoints::has_base_url_override(endpoints::OPENAI);
 
-                let base_url = self
-                    .credentials
-                    .api_base_url
-                    .as_deref()
-                    .map(|s| s.trim())
-                    .filter(|s| !s.is_empty())
-                    .unwrap_or(DEFAULT_API_BASE_URL);
+                let base_url = credential_base_url.map(str::to_string).unwrap_or_else(|| {
+                    endpoints::base_url(endpoints::OPENAI, DEFAULT_API_BASE_URL)
+                });
 
                 // Warn if API key doesn't look like OpenAI format but no custom base URL is set
                 if !has_custom_base_url && !token.starts_with("sk-") {
@@ -1092,9 +1091,9 @@ impl CodexProvider {
                     );
                 }
 
-                Self::build_responses_url(base_url)
+                Self::build_responses_url(&base_url)
             }
-            AuthMode::OAuth => format!("{CODEX_API_BASE_URL}/responses"),
+            AuthMode::OAuth => format!("{}/responses", self.get_api_base_url()),
         };
 
         // Transform OpenAI chat completion request to Codex format
@@ -1141,7 +1140,7 @@ impl CodexProvider {
             }
         }
 
-        let resp = req.send().await?;
+        let resp = req.send_captured().await?;
 
         Ok(resp)
     }
@@ -2037,7 +2036,7 @@ pub async fn exchange_codex_code_for_token(
         .header("Content-Type", "application/x-www-form-urlencoded")
         .header("Accept", "application/json")
         .form(&params)
-        .send()
+        .send_captured()
         .await?;
 
     if !resp.status().is_success() {
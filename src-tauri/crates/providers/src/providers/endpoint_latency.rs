@@ -0,0 +1,312 @@
+//! 多区域端点延迟选择
+//!
+//! 后台任务定期探测各 Provider 的候选区域端点，请求时按延迟从低到高依次尝试；
+//! `providers.endpoint_selection.pinned` 中的固定端点（按凭证 UUID 或 Provider 名称）
+//! 优先于探测结果，且只使用该端点。目前使用该选择的 Provider 为 Antigravity。
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use lime_core::config::EndpointSelectionConfig;
+use serde::{Deserialize, Serialize};
+
+use super::endpoints;
+
+/// 单次探测超时
+const PROBE_TIMEOUT_SECS: u64 = 5;
+/// 每个端点的采样次数（取最小值；首个样本包含建连与 TLS 握手）
+const PROBE_SAMPLES: usize = 2;
+
+/// 单个端点的探测结果
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EndpointProbe {
+    pub url: String,
+    /// 往返延迟（毫秒），探测失败时为空
+    pub latency_ms: Option<u64>,
+    /// 探测失败原因
+    pub error: Option<String>,
+    /// 探测时间，尚未探测时为空
+    pub probed_at: Option<DateTime<Utc>>,
+}
+
+/// 单个 Provider 的端点选择状态
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProviderEndpointReport {
+    pub provider: String,
+    /// Provider 级固定端点
+    pub pinned: Option<String>,
+    /// 当前优先使用的端点
+    pub selected: Option<String>,
+    /// 按优先级排列的候选端点（未探测的端点没有结果）
+    pub endpoints: Vec<EndpointProbe>,
+}
+
+#[derive(Default)]
+struct SelectionState {
+    config: EndpointSelectionConfig,
+    probes: HashMap<String, Vec<EndpointProbe>>,
+}
+
+fn state() -> &'static RwLock<SelectionState> {
+    static STATE: OnceLock<RwLock<SelectionState>> = OnceLock::new();
+    STATE.get_or_init(Default::default)
+}
+
+fn normalize_url(url: &str) -> Option<String> {
+    let url = url.trim().trim_end_matches('/');
+    (!url.is_empty()).then(|| url.to_string())
+}
+
+/// 内置的区域端点
+fn builtin_candidates(provider: &str) -> Vec<String> {
+    match provider {
+        endpoints::ANTIGRAVITY => super::antigravity::default_base_urls(),
+        _ => Vec::new(),
+    }
+}
+
+/// 支持端点选择的 Provider
+const SUPPORTED_PROVIDERS: &[&str] = &[endpoints::ANTIGRAVITY];
+
+/// 按配置更新端点选择（随端点覆盖一起在启动与配置重载时调用）
+pub fn configure(config: &EndpointSelectionConfig) {
+    let mut guard = state().write().unwrap_or_else(|e| e.into_inner());
+    if guard.config.regions != config.regions {
+        // 候选端点变化后旧结果不再可比
+        guard.probes.clear();
+    }
+    guard.config = config.clone();
+}
+
+fn candidates_with(config: &EndpointSelectionConfig, provider: &str) -> Vec<String> {
+    let mut candidates: Vec<String> = Vec::new();
+    let extra = config.regions.get(provider).into_iter().flatten();
+    for url in builtin_candidates(provider).iter().chain(extra) {
+        if let Some(url) = normalize_url(url) {
+            if !candidates.contains(&url) {
+                candidates.push(url);
+            }
+        }
+    }
+    candidates
+}
+
+fn pinned_with(
+    config: &EndpointSelectionConfig,
+    provider: &str,
+    credential_uuid: Option<&str>,
+) -> Option<String> {
+    credential_uuid
+        .and_then(|uuid| config.pinned.get(uuid))
+        .or_else(|| config.pinned.get(provider))
+        .and_then(|url| normalize_url(url))
+}
+
+/// 候选端点：内置区域端点 + `regions` 中追加的端点（去重，保持顺序）
+pub fn candidates(provider: &str) -> Vec<String> {
+    let guard = state().read().unwrap_or_else(|e| e.into_inner());
+    candidates_with(&guard.config, provider)
+}
+
+/// 按延迟从低到高排序；已测得延迟的端点在前，未探测的其次，探测失败的最后，
+/// 同级保持原顺序
+pub fn rank_by_latency(candidates: Vec<String>, probes: &[EndpointProbe]) -> Vec<String> {
+    let mut ranked: Vec<(u8, u64, String)> = candidates
+        .into_iter()
+        .map(|url| match probes.iter().find(|probe| probe.url == url) {
+            Some(EndpointProbe {
+                latency_ms: Some(ms),
+                ..
+            }) => (0, *ms, url),
+            Some(_) => (2, 0, url),
+            None => (1, 0, url),
+        })
+        .collect();
+    ranked.sort_by_key(|(tier, ms, _)| (*tier, *ms));
+    ranked.into_iter().map(|(_, _, url)| url).collect()
+}
+
+/// 凭证请求时依次尝试的端点
+///
+/// 命中固定端点时只返回该端点；未启用探测时按候选端点的原顺序返回。
+pub fn ordered_base_urls(provider: &str, credential_uuid: Option<&str>) -> Vec<String> {
+    let guard = state().read().unwrap_or_else(|e| e.into_inner());
+    if let Some(pinned) = pinned_with(&guard.config, provider, credential_uuid) {
+        return vec![pinned];
+    }
+    let candidates = candidates_with(&guard.config, provider);
+    if !guard.config.enabled {
+        return candidates;
+    }
+    match guard.probes.get(provider) {
+        Some(probes) => rank_by_latency(candidates, probes),
+        None => candidates,
+    }
+}
+
+/// 探测单个端点：任何 HTTP 响应都视为可达，取多次采样的最小耗时
+pub async fn probe_url(client: &reqwest::Client, url: &str) -> EndpointProbe {
+    let mut best: Option<u64> = None;
+    let mut error = None;
+    for _ in 0..PROBE_SAMPLES {
+        let started = Instant::now();
+        match client
+            .get(url)
+            .timeout(Duration::from_secs(PROBE_TIMEOUT_SECS))
+            .send()
+            .await
+        {
+            Ok(_) => {
+                let ms = started.elapsed().as_millis() as u64;
+                best = Some(best.map_or(ms, |current| current.min(ms)));
+            }
+            Err(e) => error = Some(e.to_string()),
+        }
+    }
+    EndpointProbe {
+        url: url.to_string(),
+        latency_ms: best,
+        error: if best.is_some() { None } else { error },
+        probed_at: Some(Utc::now()),
+    }
+}
+
+/// 探测所有有多个候选端点的 Provider 并更新排序
+pub async fn probe_all(client: &reqwest::Client) -> Vec<ProviderEndpointReport> {
+    for provider in SUPPORTED_PROVIDERS {
+        let candidates = candidates(provider);
+        if candidates.len() < 2 {
+            continue;
+        }
+        let probes =
+            futures::future::join_all(candidates.iter().map(|url| probe_url(client, url))).await;
+        for probe in &probes {
+            match probe.latency_ms {
+                Some(ms) => tracing::debug!("[ENDPOINT] {} {} 延迟 {}ms", provider, probe.url, ms),
+                None => tracing::debug!(
+                    "[ENDPOINT] {} {} 探测失败: {}",
+                    provider,
+                    probe.url,
+                    probe.error.as_deref().unwrap_or_default()
+                ),
+            }
+        }
+        state()
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .probes
+            .insert(provider.to_string(), probes);
+    }
+    snapshot()
+}
+
+/// 当前端点选择状态
+pub fn snapshot() -> Vec<ProviderEndpointReport> {
+    let guard = state().read().unwrap_or_else(|e| e.into_inner());
+    SUPPORTED_PROVIDERS
+        .iter()
+        .map(|provider| {
+            let probes = guard.probes.get(*provider).cloned().unwrap_or_default();
+            let candidates = candidates_with(&guard.config, provider);
+            let ranked = if guard.config.enabled {
+                rank_by_latency(candidates, &probes)
+            } else {
+                candidates
+            };
+            let pinned = pinned_with(&guard.config, provider, None);
+            let selected = pinned.clone().or_else(|| ranked.first().cloned());
+            let endpoints = ranked
+                .into_iter()
+                .map(|url| {
+                    probes
+                        .iter()
+                        .find(|probe| probe.url == url)
+                        .cloned()
+                        .unwrap_or(EndpointProbe {
+                            url,
+                            latency_ms: None,
+                            error: None,
+                            probed_at: None,
+                        })
+                })
+                .collect();
+            ProviderEndpointReport {
+                provider: provider.to_string(),
+                pinned,
+                selected,
+                endpoints,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(url: &str, latency_ms: Option<u64>) -> EndpointProbe {
+        EndpointProbe {
+            url: url.to_string(),
+            latency_ms,
+            error: latency_ms.is_none().then(|| "timeout".to_string()),
+            probed_at: Some(Utc::now()),
+        }
+    }
+
+    #[test]
+    fn test_rank_by_latency_orders_measured_unknown_failed() {
+        let candidates = vec![
+            "https://a".to_string(),
+            "https://b".to_string(),
+            "https://c".to_string(),
+            "https://d".to_string(),
+        ];
+        let probes = vec![
+            probe("https://a", None),
+            probe("https://b", Some(180)),
+            probe("https://d", Some(40)),
+        ];
+
+        assert_eq!(
+            rank_by_latency(candidates, &probes),
+            vec!["https://d", "https://b", "https://c", "https://a"]
+        );
+    }
+
+    #[test]
+    fn test_candidates_and_pins() {
+        let mut config = EndpointSelectionConfig::default();
+        config.regions.insert(
+            endpoints::ANTIGRAVITY.to_string(),
+            vec![
+                "https://asia-cloudcode.example.com/".to_string(),
+                " ".to_string(),
+            ],
+        );
+        config.pinned.insert(
+            endpoints::ANTIGRAVITY.to_string(),
+            "https://pinned.example.com/".to_string(),
+        );
+        config
+            .pinned
+            .insert("cred-1".to_string(), "https://cred.example.com".to_string());
+
+        let candidates = candidates_with(&config, endpoints::ANTIGRAVITY);
+        assert_eq!(
+            candidates.last().map(String::as_str),
+            Some("https://asia-cloudcode.example.com")
+        );
+        assert!(candidates_with(&config, endpoints::CLAUDE).is_empty());
+
+        assert_eq!(
+            pinned_with(&config, endpoints::ANTIGRAVITY, Some("cred-1")).as_deref(),
+            Some("https://cred.example.com")
+        );
+        assert_eq!(
+            pinned_with(&config, endpoints::ANTIGRAVITY, Some("cred-2")).as_deref(),
+            Some("https://pinned.example.com")
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! 上游端点覆盖
+//!
+//! 按 Provider 覆盖上游基础 URL 与 API 版本（区域端点、预发环境、自建网关）。
+//! 各 Provider 客户端通过 [`base_url`] / [`api_version`] 取值，未配置时使用默认常量；
+//! 凭证自带的 base_url（API Key 类凭证）优先于这里的覆盖。
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock, RwLockReadGuard};
+
+use lime_core::config::{EndpointOverride, ProvidersConfig};
+
+pub const KIRO: &str = "kiro";
+pub const GEMINI: &str = "gemini";
+pub const GEMINI_API_KEY: &str = "gemini_api_key";
+pub const ANTIGRAVITY: &str = "antigravity";
+pub const CODEX: &str = "codex";
+pub const VERTEX: &str = "vertex";
+pub const OPENAI: &str = "openai";
+pub const CLAUDE: &str = "claude";
+
+fn overrides() -> &'static RwLock<HashMap<String, EndpointOverride>> {
+    static OVERRIDES: OnceLock<RwLock<HashMap<String, EndpointOverride>>> = OnceLock::new();
+    OVERRIDES.get_or_init(Default::default)
+}
+
+fn read_overrides() -> RwLockReadGuard<'static, HashMap<String, EndpointOverride>> {
+    overrides().read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+/// 从 `providers` 配置收集端点覆盖
+///
+/// `providers.endpoints` 中的同名条目优先于 `providers.<name>` 上的字段。
+pub fn collect_overrides(config: &ProvidersConfig) -> HashMap<String, EndpointOverride> {
+    let mut collected = HashMap::new();
+    for (name, base_url, api_version) in [
+        (KIRO, &config.kiro.base_url, &config.kiro.api_version),
+        (GEMINI, &config.gemini.base_url, &config.gemini.api_version),
+        ("qwen", &config.qwen.base_url, &config.qwen.api_version),
+        (OPENAI, &None, &config.openai.api_version),
+        (CLAUDE, &None, &config.claude.api_version),
+    ] {
+        let entry = EndpointOverride {
+            base_url: non_empty(base_url),
+            api_version: non_empty(api_version),
+        };
+        if entry != EndpointOverride::default() {
+            collected.insert(name.to_string(), entry);
+        }
+    }
+    for (name, entry) in &config.endpoints {
+        let slot: &mut EndpointOverride = collected.entry(name.trim().to_lowercase()).or_default();
+        if let Some(base_url) = non_empty(&entry.base_url) {
+            slot.base_url = Some(base_url);
+        }
+        if let Some(api_version) = non_empty(&entry.api_version) {
+            slot.api_version = Some(api_version);
+        }
+    }
+    collected
+}
+
+/// 按配置更新端点覆盖（启动与配置重载时调用）
+pub fn configure(config: &ProvidersConfig) {
+    let collected = collect_overrides(config);
+    if !collected.is_empty() {
+        tracing::info!(
+            "[ENDPOINT] 已配置上游端点覆盖: {:?}",
+            collected.keys().collect::<Vec<_>>()
+        );
+    }
+    *overrides().write().unwrap_or_else(|e| e.into_inner()) = collected;
+    super::endpoint_latency::configure(&config.endpoint_selection);
+    crate::upstream_auth::configure(config);
+}
+
+/// Provider 的上游基础 URL，未覆盖时返回 `default`（去掉末尾 `/`）
+pub fn base_url(provider: &str, default: &str) -> String {
+    read_overrides()
+        .get(provider)
+        .and_then(|entry| entry.base_url.clone())
+        .unwrap_or_else(|| default.to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// 是否配置了基础 URL 覆盖
+pub fn has_base_url_override(provider: &str) -> bool {
+    read_overrides()
+        .get(provider)
+        .is_some_and(|entry| entry.base_url.is_some())
+}
+
+/// Provider 的上游 API 版本，未覆盖时返回 `default`
+pub fn api_version(provider: &str, default: &str) -> String {
+    read_overrides()
+        .get(provider)
+        .and_then(|entry| entry.api_version.clone())
+        .unwrap_or_else(|| default.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_overrides_prefers_endpoints_map() {
+        let mut config = ProvidersConfig::default();
+        config.kiro.base_url = Some("https://kiro-gw.internal/".to_string());
+        config.claude.api_version = Some(" 2024-10-22 ".to_string());
+        config.endpoints.insert(
+            "Kiro".to_string(),
+            EndpointOverride {
+                base_url: Some("https://staging.kiro.internal".to_string()),
+                api_version: None,
+            },
+        );
+        config.endpoints.insert(
+            ANTIGRAVITY.to_string(),
+            EndpointOverride {
+                base_url: None,
+                api_version: Some("v1beta".to_string()),
+            },
+        );
+
+        let collected = collect_overrides(&config);
+
+        assert_eq!(
+            collected[KIRO].base_url.as_deref(),
+            Some("https://staging.kiro.internal")
+        );
+        assert_eq!(collected[CLAUDE].api_version.as_deref(), Some("2024-10-22"));
+        assert_eq!(
+            collected[ANTIGRAVITY].api_version.as_deref(),
+            Some("v1beta")
+        );
+        assert!(!collected.contains_key(GEMINI));
+    }
+}
@@ -3,6 +3,7 @@
 //! 提供统一的错误处理机制，区分可重试和不可重试错误，
 //! 并提供用户友好的中文错误信息。
 
+use crate::converter::error::ConversionError;
 use std::error::Error;
 use std::fmt;
 
@@ -43,6 +44,14 @@ pub enum ProviderError {
     /// JSON 解析失败、响应格式不符合预期
     ParseError(String),
 
+    /// 上游响应转换失败（不可重试）
+    /// 带稳定错误码与脱敏后的上游响应片段
+    ConversionError(ConversionError),
+
+    /// 上游返回的 HTTP 错误（保留原始状态码，构建响应时直接使用）
+    /// 429 与 5xx 可重试
+    HttpError { status: u16, message: String },
+
     /// 未知错误
     Unknown(String),
 }
@@ -52,12 +61,21 @@ impl ProviderError {
     ///
     /// 根据 Requirements 8.4，区分临时错误和永久错误
     pub fn is_retryable(&self) -> bool {
-        matches!(
-            self,
+        match self {
             ProviderError::NetworkError(_)
-                | ProviderError::ServerError(_)
-                | ProviderError::RateLimitError(_)
-        )
+            | ProviderError::ServerError(_)
+            | ProviderError::RateLimitError(_) => true,
+            ProviderError::HttpError { status, .. } => *status == 429 || *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// 上游返回的 HTTP 状态码（仅 [`ProviderError::HttpError`] 携带）
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            ProviderError::HttpError { status, .. } => Some(*status),
+            _ => None,
+        }
     }
 
     /// 获取用户友好的中文错误信息
@@ -89,6 +107,12 @@ impl ProviderError {
             ProviderError::ParseError(msg) => {
                 format!("数据解析失败。详情：{msg}")
             }
+            ProviderError::ConversionError(err) => {
+                format!("响应转换失败。详情：{err}")
+            }
+            ProviderError::HttpError { status, message } => {
+                format!("{}。详情：HTTP {status} - {message}", self.short_message())
+            }
             ProviderError::Unknown(msg) => {
                 format!("发生未知错误。详情：{msg}")
             }
@@ -106,6 +130,13 @@ impl ProviderError {
             ProviderError::ServerError(_) => "服务器错误",
             ProviderError::RequestError(_) => "请求失败",
             ProviderError::ParseError(_) => "数据解析失败",
+            ProviderError::ConversionError(_) => "响应转换失败",
+            ProviderError::HttpError { status, .. } => match status {
+                401 | 403 => "认证失败",
+                429 => "请求过于频繁",
+                500..=599 => "服务器错误",
+                _ => "请求失败",
+            },
             ProviderError::Unknown(_) => "未知错误",
         }
     }
@@ -121,6 +152,8 @@ impl ProviderError {
             ProviderError::ServerError(_) => "ServerError",
             ProviderError::RequestError(_) => "RequestError",
             ProviderError::ParseError(_) => "ParseError",
+            ProviderError::ConversionError(_) => "ConversionError",
+            ProviderError::HttpError { .. } => "HttpError",
             ProviderError::Unknown(_) => "Unknown",
         }
     }
@@ -212,6 +245,12 @@ impl From<reqwest::Error> for ProviderError {
     }
 }
 
+impl From<ConversionError> for ProviderError {
+    fn from(err: ConversionError) -> Self {
+        ProviderError::ConversionError(err)
+    }
+}
+
 impl From<serde_json::Error> for ProviderError {
     fn from(err: serde_json::Error) -> Self {
         ProviderError::ParseError(err.to_string())
@@ -332,6 +371,27 @@ mod tests {
         assert!(matches!(err, ProviderError::RequestError(_)));
     }
 
+    #[test]
+    fn test_http_error_keeps_status() {
+        let err = ProviderError::HttpError {
+            status: 429,
+            message: "quota exhausted".to_string(),
+        };
+        assert_eq!(err.status_code(), Some(429));
+        assert!(err.is_retryable());
+        assert!(err.user_friendly_message().contains("HTTP 429"));
+
+        let err = ProviderError::HttpError {
+            status: 404,
+            message: "model not found".to_string(),
+        };
+        assert!(!err.is_retryable());
+        assert_eq!(
+            ProviderError::ServerError(String::new()).status_code(),
+            None
+        );
+    }
+
     #[test]
     fn test_user_friendly_message() {
         let err = ProviderError::NetworkError("connection refused".to_string());
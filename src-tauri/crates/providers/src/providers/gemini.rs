@@ -5,10 +5,14 @@
 
 #![allow(dead_code)]
 
+use super::endpoints;
 use super::error::{
     create_auth_error, create_config_error, create_token_refresh_error, ProviderError,
 };
 use super::traits::{CredentialProvider, ProviderResult};
+use crate::context_cache::{self, CacheHint};
+use crate::har_capture::CaptureSend;
+use crate::upload_dedup;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -21,6 +25,15 @@ const CODE_ASSIST_API_VERSION: &str = "v1internal";
 const CREDENTIALS_DIR: &str = ".gemini";
 const CREDENTIALS_FILE: &str = "oauth_creds.json";
 
+/// Code Assist API 地址（支持 `providers.gemini` 的 base_url / api_version 覆盖）
+fn code_assist_url(action: &str) -> String {
+    format!(
+        "{}/{}:{action}",
+        endpoints::base_url(endpoints::GEMINI, CODE_ASSIST_ENDPOINT),
+        endpoints::api_version(endpoints::GEMINI, CODE_ASSIST_API_VERSION)
+    )
+}
+
 // OAuth 端点
 const GEMINI_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
 
@@ -290,7 +303,7 @@ impl GeminiProvider {
             .header("Content-Type", "application/x-www-form-urlencoded")
             .header("Accept", "application/json")
             .form(&params)
-            .send()
+            .send_captured()
             .await
             .map_err(|e| Box::new(ProviderError::from(e)) as Box<dyn Error + Send + Sync>)?;
 
@@ -373,7 +386,7 @@ impl GeminiProvider {
     }
 
     pub fn get_api_url(&self, action: &str) -> String {
-        format!("{CODE_ASSIST_ENDPOINT}/{CODE_ASSIST_API_VERSION}:{action}")
+        code_assist_url(action)
     }
 
     pub async fn call_api(
@@ -395,7 +408,7 @@ impl GeminiProvider {
             .header("Authorization", format!("Bearer {token}"))
             .header("Content-Type", "application/json")
             .json(body)
-            .send()
+            .send_captured()
             .await?;
 
         if !resp.status().is_success() {
@@ -534,8 +547,11 @@ impl GeminiApiKeyCredential {
     }
 
     /// Get the effective base URL (custom or default)
-    pub fn get_base_url(&self) -> &str {
-        self.base_url.as_deref().unwrap_or(GEMINI_API_BASE_URL)
+    pub fn get_base_url(&self) -> String {
+        match self.base_url.as_deref() {
+            Some(base_url) => base_url.to_string(),
+            None => endpoints::base_url(endpoints::GEMINI_API_KEY, GEMINI_API_BASE_URL),
+        }
     }
 
     /// Check if this credential is available (not disabled)
@@ -561,7 +577,13 @@ impl GeminiApiKeyCredential {
 
     /// Build the API URL for a given model and action
     pub fn build_api_url(&self, model: &str, action: &str) -> String {
-        format!("{}/v1beta/models/{}:{}", self.get_base_url(), model, action)
+        format!(
+            "{}/{}/models/{}:{}",
+            self.get_base_url(),
+            endpoints::api_version(endpoints::GEMINI_API_KEY, "v1beta"),
+            model,
+            action
+        )
     }
 }
 
@@ -593,6 +615,56 @@ impl GeminiApiKeyProvider {
         Self { client }
     }
 
+    /// Replace inline media with Files API references when upload dedup is enabled
+    async fn dedup_uploads(
+        &self,
+        credential: &GeminiApiKeyCredential,
+        body: &serde_json::Value,
+    ) -> Option<serde_json::Value> {
+        if !upload_dedup::is_enabled() {
+            return None;
+        }
+        let mut body = body.clone();
+        let replaced = upload_dedup::dedup_gemini(
+            &self.client,
+            &credential.get_base_url(),
+            &credential.api_key,
+            &mut body,
+        )
+        .await;
+        (replaced > 0).then_some(body)
+    }
+
+    /// Rewrite the body for upload dedup and context caching
+    ///
+    /// Returns the rewritten body (if any) and the referenced cached content name.
+    async fn prepare_body(
+        &self,
+        credential: &GeminiApiKeyCredential,
+        model: &str,
+        body: &serde_json::Value,
+        hint: CacheHint,
+    ) -> (Option<serde_json::Value>, Option<String>) {
+        let deduped = self.dedup_uploads(credential, body).await;
+        if !context_cache::is_enabled() {
+            return (deduped, None);
+        }
+        let mut prepared = deduped.clone().unwrap_or_else(|| body.clone());
+        match context_cache::apply_gemini(
+            &self.client,
+            &credential.get_base_url(),
+            &credential.api_key,
+            model,
+            &mut prepared,
+            hint,
+        )
+        .await
+        {
+            Some(name) => (Some(prepared), Some(name)),
+            None => (deduped, None),
+        }
+    }
+
     /// Make a generateContent request using the given credential
     pub async fn generate_content(
         &self,
@@ -600,22 +672,23 @@ impl GeminiApiKeyProvider {
         model: &str,
         body: &serde_json::Value,
     ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
-        let url = credential.build_api_url(model, "generateContent");
+        self.generate_content_with_hint(credential, model, body, CacheHint::Auto)
+            .await
+    }
 
+    /// Make a generateContent request with a client context cache hint
+    pub async fn generate_content_with_hint(
+        &self,
+        credential: &GeminiApiKeyCredential,
+        model: &str,
+        body: &serde_json::Value,
+        hint: CacheHint,
+    ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
+        let url = credential.build_api_url(model, "generateContent");
         let resp = self
-            .client
-            .post(&url)
-            .header("x-goog-api-key", &credential.api_key)
-            .header("Content-Type", "application/json")
-            .json(body)
-            .send()
-            .await?;
-
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let body = resp.text().await.unwrap_or_default();
-            return Err(format!("Gemini API call failed: {status} - {body}").into());
-        }
+            .post_content(credential, model, &url, body, hint)
+            .await
+            .map_err(|e| format!("Gemini API call failed: {e}"))?;
 
         let data: serde_json::Value = resp.json().await?;
         Ok(data)
@@ -627,28 +700,68 @@ impl GeminiApiKeyProvider {
         credential: &GeminiApiKeyCredential,
         model: &str,
         body: &serde_json::Value,
+    ) -> Result<reqwest::Response, Box<dyn Error + Send + Sync>> {
+        self.stream_generate_content_with_hint(credential, model, body, CacheHint::Auto)
+            .await
+    }
+
+    /// Make a streamGenerateContent request with a client context cache hint
+    pub async fn stream_generate_content_with_hint(
+        &self,
+        credential: &GeminiApiKeyCredential,
+        model: &str,
+        body: &serde_json::Value,
+        hint: CacheHint,
     ) -> Result<reqwest::Response, Box<dyn Error + Send + Sync>> {
         let url = format!(
             "{}?alt=sse",
             credential.build_api_url(model, "streamGenerateContent")
         );
-
         let resp = self
-            .client
-            .post(&url)
-            .header("x-goog-api-key", &credential.api_key)
-            .header("Content-Type", "application/json")
-            .json(body)
-            .send()
-            .await?;
+            .post_content(credential, model, &url, body, hint)
+            .await
+            .map_err(|e| format!("Gemini API stream call failed: {e}"))?;
+        Ok(resp)
+    }
 
-        if !resp.status().is_success() {
+    /// POST a content generation request, returning `status - body` on failure
+    ///
+    /// If the referenced cached content is gone upstream, the cache entry is
+    /// dropped and the original request is retried once without caching.
+    async fn post_content(
+        &self,
+        credential: &GeminiApiKeyCredential,
+        model: &str,
+        url: &str,
+        body: &serde_json::Value,
+        mut hint: CacheHint,
+    ) -> Result<reqwest::Response, String> {
+        loop {
+            let (prepared, cache) = self.prepare_body(credential, model, body, hint).await;
+            let resp = self
+                .client
+                .post(url)
+                .header("x-goog-api-key", &credential.api_key)
+                .header("Content-Type", "application/json")
+                .json(prepared.as_ref().unwrap_or(body))
+                .send_captured()
+                .await
+                .map_err(|e| e.to_string())?;
+
+            if resp.status().is_success() {
+                return Ok(resp);
+            }
             let status = resp.status();
-            let body = resp.text().await.unwrap_or_default();
-            return Err(format!("Gemini API stream call failed: {status} - {body}").into());
+            let text = resp.text().await.unwrap_or_default();
+            match cache {
+                Some(name) if context_cache::is_cache_error(status.as_u16(), &text) => {
+                    tracing::warn!("[CONTEXT_CACHE] 缓存 {} 已失效，按原请求重试", name);
+                    context_cache::invalidate(&name);
+                    hint = CacheHint::Off;
+                }
+                _ => return Err(format!("{status} - {text}")),
+            }
         }
-
-        Ok(resp)
     }
 
     /// List available models using the given credential
@@ -656,13 +769,17 @@ impl GeminiApiKeyProvider {
         &self,
         credential: &GeminiApiKeyCredential,
     ) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
-        let url = format!("{}/v1beta/models", credential.get_base_url());
+        let url = format!(
+            "{}/{}/models",
+            credential.get_base_url(),
+            endpoints::api_version(endpoints::GEMINI_API_KEY, "v1beta")
+        );
 
         let resp = self
             .client
             .get(&url)
             .header("x-goog-api-key", &credential.api_key)
-            .send()
+            .send_captured()
             .await?;
 
         if !resp.status().is_success() {
@@ -943,7 +1060,11 @@ pub async fn exchange_gemini_code_for_token(
         ("grant_type", "authorization_code"),
     ];
 
-    let resp = client.post(GEMINI_TOKEN_URL).form(&params).send().await?;
+    let resp = client
+        .post(GEMINI_TOKEN_URL)
+        .form(&params)
+        .send_captured()
+        .await?;
 
     if !resp.status().is_success() {
         let status = resp.status();
@@ -963,7 +1084,7 @@ pub async fn fetch_gemini_user_email(
     let resp = client
         .get("https://www.googleapis.com/oauth2/v2/userinfo")
         .header("Authorization", format!("Bearer {access_token}"))
-        .send()
+        .send_captured()
         .await?;
 
     if resp.status().is_success() {
@@ -982,9 +1103,7 @@ pub async fn fetch_gemini_project_id(
     tracing::info!("[Gemini OAuth] 正在获取 projectId...");
 
     let resp = client
-        .post(format!(
-            "{CODE_ASSIST_ENDPOINT}/{CODE_ASSIST_API_VERSION}:loadCodeAssist"
-        ))
+        .post(code_assist_url("loadCodeAssist"))
         .header("Authorization", format!("Bearer {access_token}"))
         .header("Content-Type", "application/json")
         .json(&serde_json::json!({
@@ -996,7 +1115,7 @@ pub async fn fetch_gemini_project_id(
                 "duetProject": ""
             }
         }))
-        .send()
+        .send_captured()
         .await?;
 
     let status = resp.status();
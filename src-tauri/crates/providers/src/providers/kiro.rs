@@ -3,6 +3,7 @@
 #![allow(dead_code)]
 
 // 使用新的 translator 模块替代旧的 converter
+use crate::har_capture::CaptureSend;
 use crate::providers::traits::{CredentialProvider, ProviderResult};
 use crate::translator::kiro::anthropic::request::convert_anthropic_to_codewhisperer;
 use crate::translator::kiro::openai::request::convert_openai_to_codewhisperer_with_conversation_id;
@@ -586,7 +587,7 @@ impl KiroProvider {
 
     pub fn get_base_url(&self) -> String {
         let region = self.credentials.region.as_deref().unwrap_or("us-east-1");
-        format!("https://codewhisperer.{region}.amazonaws.com/generateAssistantResponse")
+        Self::build_health_check_url(region)
     }
 
     pub fn get_refresh_url(&self) -> String {
@@ -622,8 +623,17 @@ impl KiroProvider {
     }
 
     /// 构建健康检查端点的静态方法，供外部服务使用
+    ///
+    /// 可通过 `providers.kiro.base_url` 覆盖上游地址，其中的 `{region}` 会替换为凭证区域。
     pub fn build_health_check_url(region: &str) -> String {
-        format!("https://codewhisperer.{region}.amazonaws.com/generateAssistantResponse")
+        let base = super::endpoints::base_url(
+            super::endpoints::KIRO,
+            "https://codewhisperer.{region}.amazonaws.com",
+        );
+        format!(
+            "{}/generateAssistantResponse",
+            base.replace("{region}", region)
+        )
     }
 
     /// 检查 Token 是否已过期
@@ -814,7 +824,7 @@ impl KiroProvider {
                 .header("Accept", "*/*")
                 .header("Connection", "close")
                 .json(&body)
-                .send()
+                .send_captured()
                 .await?
         } else {
             // Social 认证使用简单的 JSON 格式（参考 Kir-Manager）
@@ -831,7 +841,7 @@ impl KiroProvider {
                 .header("Sec-Fetch-Mode", "cors")
                 .header("Connection", "close")
                 .json(&body)
-                .send()
+                .send_captured()
                 .await?
         };
 
@@ -1093,7 +1103,7 @@ impl KiroProvider {
             // 添加 Connection: close 避免连接复用被检测
             .header("Connection", "close")
             .json(&cw_request)
-            .send()
+            .send_captured()
             .await?;
 
         Ok(resp)
@@ -1158,7 +1168,7 @@ impl KiroProvider {
             )
             // 注意：不要设置 Connection: close，否则会导致流式响应无法工作
             .json(&cw_request)
-            .send()
+            .send_captured()
             .await
             .map_err(|e| {
                 tracing::error!("[KIRO_STREAM] 请求发送失败: {}", e);
@@ -1357,7 +1367,7 @@ impl KiroProvider {
                 ),
             )
             .json(&cw_request)
-            .send()
+            .send_captured()
             .await
             .map_err(|e| {
                 tracing::error!("[KIRO_STREAM_ANTHROPIC] 请求发送失败: {}", e);
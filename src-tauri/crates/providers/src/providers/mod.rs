@@ -2,11 +2,14 @@ pub mod antigravity;
 pub mod claude_custom;
 pub mod claude_oauth;
 pub mod codex;
+pub mod endpoint_latency;
+pub mod endpoints;
 pub mod error;
 pub mod gemini;
 pub mod kiro;
 pub mod novita;
 pub mod openai_custom;
+pub mod registry;
 pub mod traits;
 pub mod vertex;
 
@@ -15,7 +18,7 @@ mod tests;
 
 // Trait exports
 #[allow(unused_imports)]
-pub use traits::{CredentialProvider, ProviderResult, TokenManager};
+pub use traits::{CredentialProvider, Provider, ProviderResult, TokenManager};
 
 #[allow(unused_imports)]
 pub use antigravity::AntigravityApiError;
@@ -43,4 +46,6 @@ pub use novita::{
 #[allow(unused_imports)]
 pub use openai_custom::OpenAICustomProvider;
 #[allow(unused_imports)]
+pub use registry::{ProviderContext, ProviderRegistry, ProviderSetupError};
+#[allow(unused_imports)]
 pub use vertex::VertexProvider;
@@ -3,6 +3,7 @@
 //! Novita AI 提供 OpenAI 兼容的 HTTP 端点，通过 Bearer Token 认证。
 //! 环境变量 `NOVITA_API_KEY` 可用作 API Key 的回退来源。
 
+use crate::har_capture::CaptureSend;
 use lime_core::models::openai::ChatCompletionRequest;
 use reqwest::Client;
 use reqwest::StatusCode;
@@ -153,7 +154,7 @@ impl NovitaProvider {
             .header("Authorization", format!("Bearer {api_key}"))
             .header("Content-Type", "application/json")
             .json(&payload)
-            .send()
+            .send_captured()
             .await?;
 
         if resp.status() == StatusCode::NOT_FOUND {
@@ -165,7 +166,7 @@ impl NovitaProvider {
                         .header("Authorization", format!("Bearer {api_key}"))
                         .header("Content-Type", "application/json")
                         .json(&payload)
-                        .send()
+                        .send_captured()
                         .await?);
                 }
             }
@@ -194,7 +195,7 @@ impl NovitaProvider {
             .header("Authorization", format!("Bearer {api_key}"))
             .header("Content-Type", "application/json")
             .json(request)
-            .send()
+            .send_captured()
             .await?;
 
         if resp.status() == StatusCode::NOT_FOUND {
@@ -206,7 +207,7 @@ impl NovitaProvider {
                         .header("Authorization", format!("Bearer {api_key}"))
                         .header("Content-Type", "application/json")
                         .json(request)
-                        .send()
+                        .send_captured()
                         .await?);
                 }
             }
@@ -230,7 +231,7 @@ impl NovitaProvider {
             .client
             .get(&url)
             .header("Authorization", format!("Bearer {api_key}"))
-            .send()
+            .send_captured()
             .await?;
 
         if !resp.status().is_success() {
@@ -285,7 +286,7 @@ impl StreamingProvider for NovitaProvider {
             .header("Content-Type", "application/json")
             .header("Accept", "text/event-stream")
             .json(&payload)
-            .send()
+            .send_captured()
             .await
             .map_err(|e| ProviderError::from_reqwest_error(&e))?;
 
@@ -298,7 +299,7 @@ impl StreamingProvider for NovitaProvider {
                         .header("Content-Type", "application/json")
                         .header("Accept", "text/event-stream")
                         .json(&payload)
-                        .send()
+                        .send_captured()
                         .await
                         .map_err(|e| ProviderError::from_reqwest_error(&e))?
                 } else {
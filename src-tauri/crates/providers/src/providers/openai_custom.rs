@@ -1,5 +1,7 @@
 //! OpenAI Custom Provider (自定义 OpenAI 兼容 API)
+use super::endpoints;
 use crate::converter::ReasoningHandler;
+use crate::har_capture::CaptureSend;
 use lime_core::models::openai::{ChatCompletionRequest, ChatMessage};
 use reqwest::Client;
 use reqwest::StatusCode;
@@ -18,6 +20,8 @@ pub struct OpenAICustomConfig {
 pub struct OpenAICustomProvider {
     pub config: OpenAICustomConfig,
     pub client: Client,
+    /// 类型化请求之外需要透传的参数（如 `logprobs`）
+    pub extra_params: serde_json::Map<String, serde_json::Value>,
 }
 
 /// 创建配置好的 HTTP 客户端
@@ -38,6 +42,7 @@ impl Default for OpenAICustomProvider {
         Self {
             config: OpenAICustomConfig::default(),
             client: create_http_client(),
+            extra_params: serde_json::Map::new(),
         }
     }
 }
@@ -52,6 +57,12 @@ impl OpenAICustomProvider {
     }
 
     fn normalize_openai_request_payload(&self, payload: &mut serde_json::Value) {
+        if let Some(object) = payload.as_object_mut() {
+            for (key, value) in &self.extra_params {
+                object.insert(key.clone(), value.clone());
+            }
+        }
+
         let model_name = payload
             .get("model")
             .and_then(|value| value.as_str())
@@ -168,14 +179,21 @@ impl OpenAICustomProvider {
                 enabled: true,
             },
             client: create_http_client(),
+            extra_params: serde_json::Map::new(),
         }
     }
 
+    /// 设置透传参数，发送时合并进请求体
+    pub fn with_extra_params(mut self, params: serde_json::Map<String, serde_json::Value>) -> Self {
+        self.extra_params = params;
+        self
+    }
+
     pub fn get_base_url(&self) -> String {
         self.config
             .base_url
             .clone()
-            .unwrap_or_else(|| "https://api.openai.com".to_string())
+            .unwrap_or_else(|| endpoints::base_url(endpoints::OPENAI, "https://api.openai.com"))
     }
 
     pub fn is_configured(&self) -> bool {
@@ -350,7 +368,7 @@ impl OpenAICustomProvider {
                 .header("Authorization", format!("Bearer {api_key}"))
                 .header("Content-Type", "application/json")
                 .json(&payload)
-                .send()
+                .send_captured()
                 .await?;
 
             Self::maybe_log_protocol_mismatch_hint(url, resp.status());
@@ -391,7 +409,7 @@ impl OpenAICustomProvider {
             .header("Authorization", format!("Bearer {api_key}"))
             .header("Content-Type", "application/json")
             .json(&payload)
-            .send()
+            .send_captured()
             .await?;
 
         Self::maybe_log_protocol_mismatch_hint(&url, resp.status());
@@ -405,7 +423,7 @@ impl OpenAICustomProvider {
                         .header("Authorization", format!("Bearer {api_key}"))
                         .header("Content-Type", "application/json")
                         .json(&payload)
-                        .send()
+                        .send_captured()
                         .await?;
                     Self::maybe_log_protocol_mismatch_hint(&fallback_url, resp2.status());
                     return Ok(resp2);
@@ -416,6 +434,59 @@ impl OpenAICustomProvider {
         Ok(resp)
     }
 
+    /// 调用 OpenAI 兼容的 `/embeddings` 端点
+    pub async fn embeddings(
+        &self,
+        request: &serde_json::Value,
+    ) -> Result<reqwest::Response, Box<dyn Error + Send + Sync>> {
+        self.post_json_with_fallbacks("embeddings", request).await
+    }
+
+    /// 调用 Cohere/Jina 兼容的 `/rerank` 端点
+    pub async fn rerank(
+        &self,
+        request: &serde_json::Value,
+    ) -> Result<reqwest::Response, Box<dyn Error + Send + Sync>> {
+        self.post_json_with_fallbacks("rerank", request).await
+    }
+
+    /// 依次尝试候选 URL 发送 JSON 请求，返回第一个非 404 响应
+    async fn post_json_with_fallbacks(
+        &self,
+        endpoint: &str,
+        request: &serde_json::Value,
+    ) -> Result<reqwest::Response, Box<dyn Error + Send + Sync>> {
+        let api_key = self
+            .config
+            .api_key
+            .as_ref()
+            .ok_or("OpenAI API key not configured")?;
+
+        let urls = self.build_urls_with_fallbacks(endpoint);
+        let mut last_resp: Option<reqwest::Response> = None;
+
+        for url in &urls {
+            eprintln!("[OPENAI_CUSTOM] {endpoint} trying URL: {url}");
+            let resp = self
+                .client
+                .post(url)
+                .header("Authorization", format!("Bearer {api_key}"))
+                .header("Content-Type", "application/json")
+                .json(request)
+                .send_captured()
+                .await?;
+
+            Self::maybe_log_protocol_mismatch_hint(url, resp.status());
+
+            if resp.status() != StatusCode::NOT_FOUND {
+                return Ok(resp);
+            }
+            last_resp = Some(resp);
+        }
+
+        Ok(last_resp.ok_or("Request failed")?)
+    }
+
     pub async fn list_models(&self) -> Result<serde_json::Value, Box<dyn Error + Send + Sync>> {
         let api_key = self
             .config
@@ -434,7 +505,7 @@ impl OpenAICustomProvider {
                 .client
                 .get(&url)
                 .header("Authorization", format!("Bearer {api_key}"))
-                .send()
+                .send_captured()
                 .await?;
             Self::maybe_log_protocol_mismatch_hint(&url, r.status());
             if r.status() != StatusCode::NOT_FOUND {
@@ -511,7 +582,7 @@ impl StreamingProvider for OpenAICustomProvider {
             .header("Content-Type", "application/json")
             .header("Accept", "text/event-stream")
             .json(&payload)
-            .send()
+            .send_captured()
             .await
             .map_err(|e| ProviderError::from_reqwest_error(&e))?;
 
@@ -524,7 +595,7 @@ impl StreamingProvider for OpenAICustomProvider {
                         .header("Content-Type", "application/json")
                         .header("Accept", "text/event-stream")
                         .json(&payload)
-                        .send()
+                        .send_captured()
                         .await
                         .map_err(|e| ProviderError::from_reqwest_error(&e))?
                 } else {
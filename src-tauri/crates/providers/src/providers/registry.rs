@@ -0,0 +1,242 @@
+//! Provider 注册表
+//!
+//! 按凭证类型注册 [`ProviderFactory`]。处理器通过 [`ProviderRegistry::resolve`]
+//! 获得已完成凭证加载、Token 刷新等准备工作的 [`Provider`]，再按统一接口分发，
+//! 不再在每个处理器中内联构造具体的 Provider。
+//!
+//! 目前只有 Antigravity 完成迁移；其余凭证类型仍由处理器直接调用各自的 Provider，
+//! 迁移时为其实现 [`Provider`] 并在 [`ProviderRegistry::with_builtin`] 中注册。
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use lime_core::config::ChatImageSettings;
+use lime_core::models::provider_pool_model::{CredentialData, ProviderCredential};
+
+use super::antigravity::{AntigravityProvider, TokenRefreshError};
+use super::traits::Provider;
+
+/// 创建 Provider 时的请求上下文
+#[derive(Debug, Clone, Default)]
+pub struct ProviderContext {
+    /// 请求的模型
+    pub model: String,
+    /// 代码执行工具设置（仅 Antigravity 使用）
+    pub code_execution: Option<bool>,
+    /// 对话图片输出设置
+    pub chat_images: ChatImageSettings,
+}
+
+/// Provider 准备失败
+#[derive(Debug, Clone)]
+pub enum ProviderSetupError {
+    /// 凭证文件加载失败
+    LoadCredentials {
+        provider: &'static str,
+        message: String,
+    },
+    /// Token 刷新失败
+    TokenRefresh(TokenRefreshError),
+    /// 该凭证类型未注册
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for ProviderSetupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LoadCredentials { provider, message } => {
+                write!(f, "Failed to load {provider} credentials: {message}")
+            }
+            Self::TokenRefresh(error) => write!(f, "{}", error.user_message()),
+            Self::Unsupported(kind) => write!(f, "Credential type {kind} is not supported"),
+        }
+    }
+}
+
+impl std::error::Error for ProviderSetupError {}
+
+/// 准备好的 Provider
+pub struct ResolvedProvider {
+    pub provider: Arc<dyn Provider>,
+    /// 准备过程中是否刷新了 Token（调用方据此标记凭证健康）
+    pub token_refreshed: bool,
+}
+
+/// 按凭证创建 Provider
+#[async_trait]
+pub trait ProviderFactory: Send + Sync {
+    async fn create(
+        &self,
+        credential: &ProviderCredential,
+        context: &ProviderContext,
+    ) -> Result<ResolvedProvider, ProviderSetupError>;
+}
+
+/// 凭证类型标识，作为注册表的键
+pub fn credential_kind(data: &CredentialData) -> &'static str {
+    match data {
+        CredentialData::KiroOAuth { .. } => "kiro_oauth",
+        CredentialData::GeminiOAuth { .. } => "gemini_oauth",
+        CredentialData::AntigravityOAuth { .. } => "antigravity_oauth",
+        CredentialData::OpenAIKey { .. } => "openai_key",
+        CredentialData::ClaudeKey { .. } => "claude_key",
+        CredentialData::VertexKey { .. } => "vertex_key",
+        CredentialData::GeminiApiKey { .. } => "gemini_api_key",
+        CredentialData::CodexOAuth { .. } => "codex_oauth",
+        CredentialData::ClaudeOAuth { .. } => "claude_oauth",
+        CredentialData::AnthropicKey { .. } => "anthropic_key",
+    }
+}
+
+struct AntigravityFactory;
+
+#[async_trait]
+impl ProviderFactory for AntigravityFactory {
+    async fn create(
+        &self,
+        credential: &ProviderCredential,
+        context: &ProviderContext,
+    ) -> Result<ResolvedProvider, ProviderSetupError> {
+        let CredentialData::AntigravityOAuth {
+            creds_file_path,
+            project_id,
+        } = &credential.credential
+        else {
+            return Err(ProviderSetupError::Unsupported(credential_kind(
+                &credential.credential,
+            )));
+        };
+        let (mut provider, token_refreshed) =
+            AntigravityProvider::prepare(&credential.uuid, creds_file_path, project_id.as_deref())
+                .await?;
+        provider.code_execution = context.code_execution;
+        provider.chat_images = context.chat_images.clone();
+        Ok(ResolvedProvider {
+            provider: Arc::new(provider),
+            token_refreshed,
+        })
+    }
+}
+
+/// Provider 注册表
+#[derive(Default)]
+pub struct ProviderRegistry {
+    factories: HashMap<&'static str, Arc<dyn ProviderFactory>>,
+}
+
+impl ProviderRegistry {
+    /// 注册内置的 Provider
+    pub fn with_builtin() -> Self {
+        let mut registry = Self::default();
+        registry.register("antigravity_oauth", Arc::new(AntigravityFactory));
+        registry
+    }
+
+    /// 注册（或替换）某类凭证的工厂
+    pub fn register(&mut self, kind: &'static str, factory: Arc<dyn ProviderFactory>) {
+        self.factories.insert(kind, factory);
+    }
+
+    pub fn supports(&self, credential: &ProviderCredential) -> bool {
+        self.factories
+            .contains_key(credential_kind(&credential.credential))
+    }
+
+    /// 为凭证创建已准备好的 Provider
+    pub async fn resolve(
+        &self,
+        credential: &ProviderCredential,
+        context: &ProviderContext,
+    ) -> Result<ResolvedProvider, ProviderSetupError> {
+        let kind = credential_kind(&credential.credential);
+        let factory = self
+            .factories
+            .get(kind)
+            .ok_or(ProviderSetupError::Unsupported(kind))?;
+        factory.create(credential, context).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::error::ProviderError;
+    use lime_core::models::openai::ChatCompletionRequest;
+    use lime_core::models::provider_pool_model::PoolProviderType;
+
+    struct EchoProvider;
+
+    #[async_trait]
+    impl Provider for EchoProvider {
+        fn name(&self) -> &'static str {
+            "echo"
+        }
+
+        async fn chat(
+            &self,
+            request: &ChatCompletionRequest,
+        ) -> Result<serde_json::Value, ProviderError> {
+            Ok(serde_json::json!({ "model": request.model }))
+        }
+
+        async fn health_probe(&self) -> Result<(), ProviderError> {
+            Ok(())
+        }
+    }
+
+    struct EchoFactory;
+
+    #[async_trait]
+    impl ProviderFactory for EchoFactory {
+        async fn create(
+            &self,
+            _credential: &ProviderCredential,
+            _context: &ProviderContext,
+        ) -> Result<ResolvedProvider, ProviderSetupError> {
+            Ok(ResolvedProvider {
+                provider: Arc::new(EchoProvider),
+                token_refreshed: false,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_dispatches_by_credential_kind() {
+        let claude = ProviderCredential::new(
+            PoolProviderType::Claude,
+            CredentialData::ClaudeKey {
+                api_key: "sk-test".to_string(),
+                base_url: None,
+            },
+        );
+
+        let mut registry = ProviderRegistry::with_builtin();
+        assert!(!registry.supports(&claude));
+        assert!(matches!(
+            registry
+                .resolve(&claude, &ProviderContext::default())
+                .await
+                .err(),
+            Some(ProviderSetupError::Unsupported("claude_key"))
+        ));
+
+        registry.register("claude_key", Arc::new(EchoFactory));
+        let resolved = registry
+            .resolve(&claude, &ProviderContext::default())
+            .await
+            .unwrap();
+        let request: ChatCompletionRequest =
+            serde_json::from_value(serde_json::json!({ "model": "m", "messages": [] })).unwrap();
+        assert_eq!(
+            resolved.provider.chat(&request).await.unwrap()["model"],
+            "m"
+        );
+        // 未实现的能力返回请求错误
+        assert!(matches!(
+            resolved.provider.count_tokens(&request).await,
+            Err(ProviderError::RequestError(_))
+        ));
+    }
+}
@@ -1,12 +1,18 @@
 //! Provider Trait 定义
 //!
-//! 统一的 Provider 接口，用于凭证管理和 Token 生命周期管理。
+//! 统一的 Provider 接口，用于凭证管理、Token 生命周期管理以及模型调用。
 
 #![allow(dead_code)]
 
 use async_trait::async_trait;
+use lime_core::models::openai::{
+    ChatCompletionRequest, ImageGenerationRequest, ImageGenerationResponse,
+};
 use std::error::Error;
 
+use super::error::ProviderError;
+use crate::streaming::traits::{StreamFormat, StreamResponse};
+
 /// Provider 结果类型别名（与现有方法签名兼容）
 pub type ProviderResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
 
@@ -93,6 +99,60 @@ pub trait TokenManager: CredentialProvider {
 // 为所有实现了 CredentialProvider 的类型自动实现 TokenManager
 impl<T: CredentialProvider> TokenManager for T {}
 
+/// 不支持的能力
+fn unsupported(provider: &str, capability: &str) -> ProviderError {
+    ProviderError::RequestError(format!("{provider} 不支持 {capability}"))
+}
+
+/// 模型调用 Trait
+///
+/// 请求与响应统一使用 OpenAI 格式，协议转换在各 Provider 内部完成。
+/// 处理器通过 [`super::registry::ProviderRegistry`] 获取已准备好的实例后按此接口分发，
+/// 未实现的能力默认返回 [`ProviderError::RequestError`]。
+#[async_trait]
+pub trait Provider: Send + Sync {
+    /// Provider 名称，用于日志与错误消息
+    fn name(&self) -> &'static str;
+
+    /// 非流式对话，返回 OpenAI `chat.completion` 响应
+    async fn chat(
+        &self,
+        request: &ChatCompletionRequest,
+    ) -> Result<serde_json::Value, ProviderError>;
+
+    /// 流式对话，返回上游原生格式的字节流及其格式
+    async fn chat_stream(
+        &self,
+        _request: &ChatCompletionRequest,
+    ) -> Result<(StreamFormat, StreamResponse), ProviderError> {
+        Err(unsupported(self.name(), "chat_stream"))
+    }
+
+    /// 文本嵌入，请求与响应为 OpenAI `/v1/embeddings` 格式
+    async fn embed(
+        &self,
+        _request: &serde_json::Value,
+    ) -> Result<serde_json::Value, ProviderError> {
+        Err(unsupported(self.name(), "embed"))
+    }
+
+    /// 图像生成
+    async fn generate_image(
+        &self,
+        _request: &ImageGenerationRequest,
+    ) -> Result<ImageGenerationResponse, ProviderError> {
+        Err(unsupported(self.name(), "generate_image"))
+    }
+
+    /// 统计请求的输入 Token 数
+    async fn count_tokens(&self, _request: &ChatCompletionRequest) -> Result<u64, ProviderError> {
+        Err(unsupported(self.name(), "count_tokens"))
+    }
+
+    /// 轻量探活：确认凭证可用且上游可达
+    async fn health_probe(&self) -> Result<(), ProviderError>;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
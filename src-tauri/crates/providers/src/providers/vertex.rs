@@ -5,6 +5,7 @@
 
 #![allow(dead_code)]
 
+use crate::har_capture::CaptureSend;
 use lime_core::models::vertex_model::VertexApiKeyEntry;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -119,10 +120,9 @@ impl VertexProvider {
 
     /// Get the base URL for API requests
     pub fn get_base_url(&self) -> String {
-        self.config
-            .base_url
-            .clone()
-            .unwrap_or_else(|| DEFAULT_VERTEX_BASE_URL.to_string())
+        self.config.base_url.clone().unwrap_or_else(|| {
+            super::endpoints::base_url(super::endpoints::VERTEX, DEFAULT_VERTEX_BASE_URL)
+        })
     }
 
     /// Get the API key
@@ -192,7 +192,7 @@ impl VertexProvider {
             .header("x-goog-api-key", api_key)
             .header("Content-Type", "application/json")
             .json(&request)
-            .send()
+            .send_captured()
             .await?;
 
         Ok(resp)
@@ -231,7 +231,7 @@ impl VertexProvider {
             .header("x-goog-api-key", api_key)
             .header("Content-Type", "application/json")
             .json(&request)
-            .send()
+            .send_captured()
             .await?;
 
         Ok(resp)
@@ -252,7 +252,7 @@ impl VertexProvider {
             .client
             .get(&url)
             .header("x-goog-api-key", api_key)
-            .send()
+            .send_captured()
             .await?;
 
         if !resp.status().is_success() {
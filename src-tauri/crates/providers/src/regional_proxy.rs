@@ -0,0 +1,438 @@
+//! 按地区可达性自动走代理
+//!
+//! 部分 Provider 在某些国家 / 地区不可用：连接被重置、TLS 握手失败，或返回
+//! `unsupported_country_region_territory`、`User location is not supported` 等地区限制错误。
+//! 启用后，经 [`CaptureSend::send_captured`](crate::har_capture::CaptureSend) 发出的请求先直连；
+//! 直连失败且经出站代理重试成功时，该主机被标记为需要代理，之后的请求直接走代理，
+//! 其他主机保持直连。标记在 `recheck_after_secs` 后过期，届时重新尝试直连。
+//!
+//! 经代理发送时使用独立的 Client，请求级的请求头与超时保持不变。
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use axum::http::Response as HttpResponse;
+use chrono::{DateTime, Utc};
+use lime_core::config::RegionalProxySettings;
+use lime_core::models::injection_types::pattern_matches;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, RequestBuilder, Response, ResponseBuilderExt, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use crate::har_capture::execute;
+
+/// 检查地区限制时最多读取的错误响应正文
+const MAX_ERROR_BODY_BYTES: u64 = 64 * 1024;
+
+/// 地区限制错误的特征文本（小写）
+const REGION_BLOCK_MARKERS: &[&str] = &[
+    "unsupported_country_region_territory",
+    "country, region, or territory not supported",
+    "user location is not supported",
+    "not available in your country",
+    "not available in your region",
+    "unsupported region",
+    "region is not supported",
+];
+
+/// 自动标记为经代理访问的主机
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockedHost {
+    pub host: String,
+    /// 直连失败原因
+    pub reason: String,
+    pub since: DateTime<Utc>,
+    /// 距重新尝试直连的秒数
+    pub recheck_in_secs: u64,
+}
+
+/// 当前状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegionalProxyStatus {
+    pub enabled: bool,
+    pub always_proxy: Vec<String>,
+    pub direct: Vec<String>,
+    pub blocked: Vec<BlockedHost>,
+}
+
+struct Routing {
+    client: Client,
+    always_proxy: Vec<String>,
+    direct: Vec<String>,
+    recheck_after: Duration,
+}
+
+struct BlockedEntry {
+    reason: String,
+    since: DateTime<Utc>,
+    until: Instant,
+}
+
+#[derive(Default)]
+struct RegionalState {
+    routing: Option<Routing>,
+    blocked: HashMap<String, BlockedEntry>,
+}
+
+fn state() -> &'static RwLock<RegionalState> {
+    static STATE: OnceLock<RwLock<RegionalState>> = OnceLock::new();
+    STATE.get_or_init(Default::default)
+}
+
+fn normalize_patterns(patterns: &[String]) -> Vec<String> {
+    patterns
+        .iter()
+        .map(|p| p.trim().to_ascii_lowercase())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+fn matches_any(patterns: &[String], host: &str) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| pattern_matches(pattern, host))
+}
+
+fn is_local_host(host: &str) -> bool {
+    host == "localhost" || host == "127.0.0.1" || host == "[::1]" || host == "::1"
+}
+
+fn build_routing(
+    settings: &RegionalProxySettings,
+    fallback_proxy_url: Option<&str>,
+) -> Option<Routing> {
+    let proxy_url = settings
+        .proxy_url
+        .as_deref()
+        .or(fallback_proxy_url)
+        .map(str::trim)
+        .filter(|url| !url.is_empty());
+    let Some(proxy_url) = proxy_url else {
+        tracing::warn!("[REGION] 已启用按地区代理，但未配置代理地址，保持直连");
+        return None;
+    };
+    let client = match reqwest::Proxy::all(proxy_url).and_then(|proxy| {
+        Client::builder()
+            .proxy(proxy)
+            .connect_timeout(Duration::from_secs(30))
+            .build()
+    }) {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::warn!("[REGION] 代理地址无效，保持直连: {}", e);
+            return None;
+        }
+    };
+    Some(Routing {
+        client,
+        always_proxy: normalize_patterns(&settings.always_proxy),
+        direct: normalize_patterns(&settings.direct),
+        recheck_after: Duration::from_secs(settings.recheck_after_secs.max(60)),
+    })
+}
+
+/// 应用配置（启动与热重载时调用）
+///
+/// `fallback_proxy_url` 为顶层 `proxy_url`，在 `regional_proxy.proxy_url` 未设置时使用。
+/// 已自动标记的主机保留。
+pub fn configure(settings: &RegionalProxySettings, fallback_proxy_url: Option<&str>) {
+    let routing = if settings.enabled {
+        build_routing(settings, fallback_proxy_url)
+    } else {
+        None
+    };
+    let mut guard = state().write().unwrap_or_else(|e| e.into_inner());
+    guard.routing = routing;
+}
+
+pub(crate) fn is_enabled() -> bool {
+    state()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .routing
+        .is_some()
+}
+
+pub fn status() -> RegionalProxyStatus {
+    let guard = state().read().unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+    let mut blocked: Vec<BlockedHost> = guard
+        .blocked
+        .iter()
+        .filter(|(_, entry)| now < entry.until)
+        .map(|(host, entry)| BlockedHost {
+            host: host.clone(),
+            reason: entry.reason.clone(),
+            since: entry.since,
+            recheck_in_secs: entry.until.saturating_duration_since(now).as_secs(),
+        })
+        .collect();
+    blocked.sort_by(|a, b| a.host.cmp(&b.host));
+    let (always_proxy, direct) = guard
+        .routing
+        .as_ref()
+        .map(|r| (r.always_proxy.clone(), r.direct.clone()))
+        .unwrap_or_default();
+    RegionalProxyStatus {
+        enabled: guard.routing.is_some(),
+        always_proxy,
+        direct,
+        blocked,
+    }
+}
+
+/// 清除自动标记，所有主机重新尝试直连，返回清除的数量
+pub fn clear() -> usize {
+    let mut guard = state().write().unwrap_or_else(|e| e.into_inner());
+    let cleared = guard.blocked.len();
+    guard.blocked.clear();
+    cleared
+}
+
+fn mark_blocked(host: &str, reason: String) {
+    let mut guard = state().write().unwrap_or_else(|e| e.into_inner());
+    let Some(recheck_after) = guard.routing.as_ref().map(|r| r.recheck_after) else {
+        return;
+    };
+    tracing::warn!("[REGION] {} 直连不可用，改为经代理访问: {}", host, reason);
+    guard.blocked.insert(
+        host.to_string(),
+        BlockedEntry {
+            reason,
+            since: Utc::now(),
+            until: Instant::now() + recheck_after,
+        },
+    );
+}
+
+fn unmark(host: &str) {
+    let mut guard = state().write().unwrap_or_else(|e| e.into_inner());
+    if guard.blocked.remove(host).is_some() {
+        tracing::info!("[REGION] {} 经代理同样不可达，恢复直连", host);
+    }
+}
+
+enum Route {
+    /// 未启用、本地地址或配置为直连
+    Passthrough,
+    /// 先直连，失败时经代理重试
+    Direct { proxy: Client },
+    /// 经代理发送，`detected` 表示由自动检测标记
+    Proxy { client: Client, detected: bool },
+}
+
+fn route_for(host: &str) -> Route {
+    let guard = state().read().unwrap_or_else(|e| e.into_inner());
+    let Some(routing) = guard.routing.as_ref() else {
+        return Route::Passthrough;
+    };
+    if host.is_empty() || is_local_host(host) || matches_any(&routing.direct, host) {
+        return Route::Passthrough;
+    }
+    if matches_any(&routing.always_proxy, host) {
+        return Route::Proxy {
+            client: routing.client.clone(),
+            detected: false,
+        };
+    }
+    if guard
+        .blocked
+        .get(host)
+        .is_some_and(|entry| Instant::now() < entry.until)
+    {
+        return Route::Proxy {
+            client: routing.client.clone(),
+            detected: true,
+        };
+    }
+    Route::Direct {
+        proxy: routing.client.clone(),
+    }
+}
+
+/// 错误响应正文是否为地区限制
+pub fn is_region_block(status: u16, body: &[u8]) -> bool {
+    match status {
+        451 => true,
+        400 | 403 => {
+            let body = String::from_utf8_lossy(body).to_lowercase();
+            REGION_BLOCK_MARKERS
+                .iter()
+                .any(|marker| body.contains(marker))
+        }
+        _ => false,
+    }
+}
+
+fn may_be_region_block(response: &Response) -> bool {
+    let status = response.status();
+    let is_stream = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("event-stream"));
+    matches!(
+        status,
+        StatusCode::BAD_REQUEST | StatusCode::FORBIDDEN | StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS
+    ) && !is_stream
+        && response
+            .content_length()
+            .map_or(true, |len| len <= MAX_ERROR_BODY_BYTES)
+}
+
+/// 检查响应是否为地区限制，返回（可能重建的）结果与限制原因
+async fn detect_block(
+    result: reqwest::Result<Response>,
+) -> (reqwest::Result<Response>, Option<String>) {
+    let response = match result {
+        Err(e) if e.is_connect() => {
+            let reason = format!("连接失败: {e}");
+            return (Err(e), Some(reason));
+        }
+        Ok(response) if may_be_region_block(&response) => response,
+        other => return (other, None),
+    };
+
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+    let url = response.url().clone();
+    let body = match response.bytes().await {
+        Ok(body) => body,
+        Err(e) => return (Err(e), None),
+    };
+    let reason: Option<String> = is_region_block(status.as_u16(), &body)
+        .then(|| {
+            format!(
+                "HTTP {}: {}",
+                status.as_u16(),
+                String::from_utf8_lossy(&body)
+            )
+        })
+        .map(|reason| reason.chars().take(200).collect());
+    let mut builder = HttpResponse::builder()
+        .status(status)
+        .version(version)
+        .url(url);
+    if let Some(target) = builder.headers_mut() {
+        *target = headers;
+    }
+    let rebuilt = builder
+        .body(body)
+        .map(Response::from)
+        .expect("response parts come from a valid response");
+    (Ok(rebuilt), reason)
+}
+
+/// 按地区可达性选择直连或代理发送
+pub(crate) async fn send(builder: RequestBuilder) -> reqwest::Result<Response> {
+    let (client, request) = builder.build_split();
+    let request = request?;
+    let host = request
+        .url()
+        .host_str()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let proxy = match route_for(&host) {
+        Route::Passthrough => return execute(&client, request).await,
+        Route::Proxy {
+            client: proxy,
+            detected,
+        } => {
+            let result = execute(&proxy, request).await;
+            if detected && result.as_ref().is_err_and(reqwest::Error::is_connect) {
+                unmark(&host);
+            }
+            return result;
+        }
+        Route::Direct { proxy } => proxy,
+    };
+
+    // 流式请求体无法复制，此时仅标记，下次请求再走代理
+    let retry = request.try_clone();
+    let (direct, reason) = detect_block(execute(&client, request).await).await;
+    let Some(reason) = reason else {
+        return direct;
+    };
+    let Some(retry) = retry else {
+        mark_blocked(&host, reason);
+        return direct;
+    };
+
+    tracing::info!("[REGION] {} 直连受限，经代理重试", host);
+    match detect_block(execute(&proxy, retry).await).await {
+        (proxied, None) => {
+            mark_blocked(&host, reason);
+            proxied
+        }
+        (_, Some(proxy_reason)) => {
+            tracing::warn!(
+                "[REGION] {} 经代理同样不可用，保持直连: {}",
+                host,
+                proxy_reason
+            );
+            direct
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_region_block() {
+        assert!(is_region_block(
+            403,
+            br#"{"error":{"code":"unsupported_country_region_territory","message":"Country, region, or territory not supported"}}"#
+        ));
+        assert!(is_region_block(
+            400,
+            br#"{"error":{"code":400,"message":"User location is not supported for the API use.","status":"FAILED_PRECONDITION"}}"#
+        ));
+        assert!(is_region_block(451, b""));
+        assert!(!is_region_block(
+            403,
+            br#"{"error":{"message":"Invalid API key"}}"#
+        ));
+        assert!(!is_region_block(500, b"not available in your region"));
+    }
+
+    #[test]
+    fn test_route_for_hosts() {
+        let kind = |host: &str| match route_for(host) {
+            Route::Passthrough => "passthrough",
+            Route::Direct { .. } => "direct",
+            Route::Proxy {
+                detected: false, ..
+            } => "always",
+            Route::Proxy { detected: true, .. } => "detected",
+        };
+
+        configure(
+            &RegionalProxySettings {
+                enabled: true,
+                always_proxy: vec!["*.googleapis.com".to_string()],
+                direct: vec!["api.deepseek.com".to_string()],
+                ..Default::default()
+            },
+            Some("http://127.0.0.1:7890"),
+        );
+        assert_eq!(kind("generativelanguage.googleapis.com"), "always");
+        assert_eq!(kind("api.deepseek.com"), "passthrough");
+        assert_eq!(kind("127.0.0.1"), "passthrough");
+        assert_eq!(kind("api.anthropic.com"), "direct");
+
+        mark_blocked("api.anthropic.com", "HTTP 403".to_string());
+        assert_eq!(kind("api.anthropic.com"), "detected");
+        assert_eq!(status().blocked[0].host, "api.anthropic.com");
+        unmark("api.anthropic.com");
+        assert_eq!(kind("api.anthropic.com"), "direct");
+
+        configure(&RegionalProxySettings::default(), None);
+        assert!(!is_enabled());
+        assert_eq!(kind("generativelanguage.googleapis.com"), "passthrough");
+    }
+}
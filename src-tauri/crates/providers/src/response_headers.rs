@@ -0,0 +1,54 @@
+//! 上游响应头记录
+//!
+//! 调用方通过 [`scope`] 建立记录作用域，Provider 收到上游响应时调用 [`record`]
+//! 保存其响应头；同一作用域内多次调用上游（重试、降级）时保留最后一次。
+//! 不在作用域内时 [`record`] 为空操作。
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use reqwest::header::HeaderMap;
+
+tokio::task_local! {
+    static LAST_HEADERS: Arc<Mutex<Option<HeaderMap>>>;
+}
+
+/// 记录上游响应头
+pub fn record(headers: &HeaderMap) {
+    let _ = LAST_HEADERS.try_with(|slot| {
+        if let Ok(mut slot) = slot.lock() {
+            *slot = Some(headers.clone());
+        }
+    });
+}
+
+/// 在记录作用域内执行 `future`，返回其结果与最后记录的上游响应头
+pub async fn scope<F: Future>(future: F) -> (F::Output, Option<HeaderMap>) {
+    let slot = Arc::new(Mutex::new(None));
+    let output = LAST_HEADERS.scope(slot.clone(), future).await;
+    let headers = slot.lock().ok().and_then(|mut slot| slot.take());
+    (output, headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_scope_keeps_last_recorded_headers() {
+        let mut first = HeaderMap::new();
+        first.insert("x-request-id", "first".parse().unwrap());
+        let mut second = HeaderMap::new();
+        second.insert("x-request-id", "second".parse().unwrap());
+
+        // 作用域外记录不生效
+        record(&first);
+
+        let ((), headers) = scope(async {
+            record(&first);
+            record(&second);
+        })
+        .await;
+        assert_eq!(headers.unwrap()["x-request-id"], "second");
+    }
+}
@@ -0,0 +1,520 @@
+//! Gemini 流式响应到 Anthropic SSE 的转换
+//!
+//! 逐 chunk 将 Gemini / Antigravity 的 `streamGenerateContent` 响应转换为完整的
+//! Anthropic Messages 事件序列，事件顺序与官方 API 一致：
+//!
+//! ```text
+//! message_start → ping
+//! → (content_block_start → content_block_delta* → content_block_stop)*
+//! → message_delta（stop_reason + usage）→ message_stop
+//! ```
+//!
+//! - `thought: true` 的文本转为 `thinking` 块，`thoughtSignature` 转为 `signature_delta`
+//! - `functionCall` 转为 `tool_use` 块，参数以单个 `input_json_delta` 发送
+//! - `usageMetadata` 转为 `message_start` / `message_delta` 中的用量
+//! - 搜索接地引用在文本块结束前以 `citations_delta` 发送
+//!
+//! 上游数据可能是 SSE（`data: {...}`）、逐行 JSON 或 JSON 数组，
+//! 转换器只按顶层 JSON 对象切分，不依赖具体的分帧方式。
+
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::converter::grounding;
+
+/// 当前打开的内容块类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OpenBlock {
+    Text,
+    Thinking,
+}
+
+/// Gemini 用量统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct GeminiUsage {
+    prompt_tokens: u64,
+    candidates_tokens: u64,
+    thoughts_tokens: u64,
+    cached_tokens: u64,
+}
+
+impl GeminiUsage {
+    fn update(&mut self, metadata: &Value) {
+        let read = |key: &str| metadata.get(key).and_then(Value::as_u64);
+        if let Some(v) = read("promptTokenCount") {
+            self.prompt_tokens = v;
+        }
+        if let Some(v) = read("candidatesTokenCount") {
+            self.candidates_tokens = v;
+        }
+        if let Some(v) = read("thoughtsTokenCount") {
+            self.thoughts_tokens = v;
+        }
+        if let Some(v) = read("cachedContentTokenCount") {
+            self.cached_tokens = v;
+        }
+    }
+
+    /// 未命中缓存的输入 Token（Anthropic 的 input_tokens 不含缓存读取部分）
+    fn input_tokens(&self) -> u64 {
+        self.prompt_tokens.saturating_sub(self.cached_tokens)
+    }
+
+    fn output_tokens(&self) -> u64 {
+        self.candidates_tokens + self.thoughts_tokens
+    }
+}
+
+/// Gemini finishReason 映射为 Anthropic stop_reason
+fn map_finish_reason(reason: Option<&str>, saw_tool_use: bool) -> &'static str {
+    if saw_tool_use {
+        return "tool_use";
+    }
+    match reason {
+        Some("MAX_TOKENS") => "max_tokens",
+        Some(
+            "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII" | "IMAGE_SAFETY",
+        ) => "refusal",
+        _ => "end_turn",
+    }
+}
+
+fn sse_event(event_type: &str, data: Value) -> String {
+    format!("event: {event_type}\ndata: {data}\n\n")
+}
+
+/// Gemini 流到 Anthropic SSE 的转换器
+#[derive(Debug)]
+pub struct GeminiAnthropicSseConverter {
+    message_id: String,
+    model: String,
+    keep_citations: bool,
+    /// 尚未组成完整 JSON 对象的原始数据
+    buffer: Vec<u8>,
+    block_index: usize,
+    open_block: Option<OpenBlock>,
+    /// 当前文本块累积的内容（用于计算引用位置）
+    block_text: String,
+    /// 最近一次携带搜索接地信息的 candidate
+    grounding_candidate: Option<Value>,
+    has_sent_message_start: bool,
+    saw_tool_use: bool,
+    finish_reason: Option<String>,
+    usage: GeminiUsage,
+    finished: bool,
+}
+
+impl GeminiAnthropicSseConverter {
+    pub fn new(model: &str, keep_citations: bool) -> Self {
+        Self {
+            message_id: format!("msg_{}", Uuid::new_v4().simple()),
+            model: model.to_string(),
+            keep_citations,
+            buffer: Vec::new(),
+            block_index: 0,
+            open_block: None,
+            block_text: String::new(),
+            grounding_candidate: None,
+            has_sent_message_start: false,
+            saw_tool_use: false,
+            finish_reason: None,
+            usage: GeminiUsage::default(),
+            finished: false,
+        }
+    }
+
+    /// 处理上游原始字节，返回可以发送的 SSE 事件
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        for chunk in self.take_complete_objects() {
+            events.extend(self.process_chunk(&chunk));
+        }
+        events
+    }
+
+    /// 从缓冲区中取出所有完整的顶层 JSON 对象，丢弃对象之间的分隔符
+    fn take_complete_objects(&mut self) -> Vec<Value> {
+        let mut objects = Vec::new();
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut escape_next = false;
+        let mut start = None;
+        let mut consumed = 0;
+
+        for (i, &byte) in self.buffer.iter().enumerate() {
+            if start.is_none() {
+                if byte == b'{' {
+                    start = Some(i);
+                    depth = 1;
+                } else {
+                    consumed = i + 1;
+                }
+                continue;
+            }
+            if in_string {
+                match byte {
+                    _ if escape_next => escape_next = false,
+                    b'\\' => escape_next = true,
+                    b'"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match byte {
+                b'"' => in_string = true,
+                b'{' => depth += 1,
+                b'}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        let begin = start.take().unwrap_or(i);
+                        match serde_json::from_slice::<Value>(&self.buffer[begin..=i]) {
+                            Ok(value) => objects.push(value),
+                            Err(e) => {
+                                tracing::warn!("[GEMINI_ANTHROPIC] 跳过无法解析的 chunk: {}", e)
+                            }
+                        }
+                        consumed = i + 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        self.buffer.drain(..consumed);
+        objects
+    }
+
+    /// 处理单个 Gemini 响应 chunk
+    pub fn process_chunk(&mut self, chunk: &Value) -> Vec<String> {
+        let mut events = Vec::new();
+        if self.finished {
+            return events;
+        }
+
+        // Antigravity 在外层包装了 `response`
+        let chunk = chunk.get("response").unwrap_or(chunk);
+        if let Some(error) = chunk.get("error") {
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("Upstream stream error");
+            return self.fail(message);
+        }
+
+        if let Some(metadata) = chunk.get("usageMetadata") {
+            self.usage.update(metadata);
+        }
+        self.ensure_message_start(&mut events);
+
+        let Some(candidate) = chunk.get("candidates").and_then(|c| c.get(0)) else {
+            return events;
+        };
+        if candidate.get("groundingMetadata").is_some() {
+            self.grounding_candidate = Some(candidate.clone());
+        }
+
+        let parts = candidate
+            .pointer("/content/parts")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for part in parts {
+            self.process_part(part, &mut events);
+        }
+
+        if let Some(reason) = candidate.get("finishReason").and_then(Value::as_str) {
+            self.finish_reason = Some(reason.to_string());
+        }
+        events
+    }
+
+    fn process_part(&mut self, part: &Value, events: &mut Vec<String>) {
+        if let Some(call) = part.get("functionCall") {
+            self.close_block(events);
+            let id = call
+                .get("id")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("toolu_{}", Uuid::new_v4().simple()));
+            let name = call.get("name").and_then(Value::as_str).unwrap_or_default();
+            let args = call.get("args").cloned().unwrap_or_else(|| json!({}));
+            let index = self.block_index;
+            events.push(sse_event(
+                "content_block_start",
+                json!({
+                    "type": "content_block_start", "index": index,
+                    "content_block": {"type": "tool_use", "id": id, "name": name, "input": {}}
+                }),
+            ));
+            events.push(sse_event(
+                "content_block_delta",
+                json!({
+                    "type": "content_block_delta", "index": index,
+                    "delta": {"type": "input_json_delta", "partial_json": args.to_string()}
+                }),
+            ));
+            events.push(sse_event(
+                "content_block_stop",
+                json!({"type": "content_block_stop", "index": index}),
+            ));
+            self.block_index += 1;
+            self.saw_tool_use = true;
+            return;
+        }
+
+        let text = part.get("text").and_then(Value::as_str);
+        let is_thought = part.get("thought").and_then(Value::as_bool) == Some(true);
+        if is_thought {
+            self.open(OpenBlock::Thinking, events);
+            if let Some(text) = text.filter(|t| !t.is_empty()) {
+                events.push(self.delta(json!({"type": "thinking_delta", "thinking": text})));
+            }
+        } else if let Some(text) = text.filter(|t| !t.is_empty()) {
+            self.open(OpenBlock::Text, events);
+            self.block_text.push_str(text);
+            events.push(self.delta(json!({"type": "text_delta", "text": text})));
+        }
+
+        // 签名属于思考块，出现在思考块之外时无法关联，直接忽略
+        if let Some(signature) = part.get("thoughtSignature").and_then(Value::as_str) {
+            if self.open_block == Some(OpenBlock::Thinking) {
+                events.push(self.delta(json!({"type": "signature_delta", "signature": signature})));
+            }
+        }
+    }
+
+    fn delta(&self, delta: Value) -> String {
+        sse_event(
+            "content_block_delta",
+            json!({"type": "content_block_delta", "index": self.block_index, "delta": delta}),
+        )
+    }
+
+    fn ensure_message_start(&mut self, events: &mut Vec<String>) {
+        if self.has_sent_message_start {
+            return;
+        }
+        self.has_sent_message_start = true;
+        events.push(sse_event(
+            "message_start",
+            json!({
+                "type": "message_start",
+                "message": {
+                    "id": self.message_id, "type": "message", "role": "assistant",
+                    "model": self.model, "content": [],
+                    "stop_reason": null, "stop_sequence": null,
+                    "usage": {
+                        "input_tokens": self.usage.input_tokens(),
+                        "cache_read_input_tokens": self.usage.cached_tokens,
+                        "output_tokens": 0
+                    }
+                }
+            }),
+        ));
+        events.push(sse_event("ping", json!({"type": "ping"})));
+    }
+
+    /// 打开指定类型的内容块（类型不同时先关闭当前块）
+    fn open(&mut self, block: OpenBlock, events: &mut Vec<String>) {
+        if self.open_block == Some(block) {
+            return;
+        }
+        self.close_block(events);
+        let content_block = match block {
+            OpenBlock::Text => json!({"type": "text", "text": ""}),
+            OpenBlock::Thinking => json!({"type": "thinking", "thinking": ""}),
+        };
+        events.push(sse_event(
+            "content_block_start",
+            json!({
+                "type": "content_block_start", "index": self.block_index,
+                "content_block": content_block
+            }),
+        ));
+        self.open_block = Some(block);
+    }
+
+    fn close_block(&mut self, events: &mut Vec<String>) {
+        let Some(block) = self.open_block.take() else {
+            return;
+        };
+        if block == OpenBlock::Text && self.keep_citations {
+            if let Some(candidate) = &self.grounding_candidate {
+                let annotations = grounding::candidate_annotations(candidate, &self.block_text);
+                for citation in
+                    grounding::annotations_to_anthropic_citations(&annotations, &self.block_text)
+                {
+                    events
+                        .push(self.delta(json!({"type": "citations_delta", "citation": citation})));
+                }
+            }
+        }
+        self.block_text.clear();
+        events.push(sse_event(
+            "content_block_stop",
+            json!({"type": "content_block_stop", "index": self.block_index}),
+        ));
+        self.block_index += 1;
+    }
+
+    /// 上游出错时结束流，发送 Anthropic `error` 事件
+    pub fn fail(&mut self, message: &str) -> Vec<String> {
+        if self.finished {
+            return Vec::new();
+        }
+        self.finished = true;
+        vec![sse_event(
+            "error",
+            json!({"type": "error", "error": {"type": "api_error", "message": message}}),
+        )]
+    }
+
+    /// 上游结束后补齐收尾事件
+    pub fn finish(&mut self) -> Vec<String> {
+        let mut events = Vec::new();
+        if self.finished {
+            return events;
+        }
+        self.finished = true;
+        self.ensure_message_start(&mut events);
+        if self.block_index == 0 && self.open_block.is_none() {
+            // 空响应也输出一个空文本块，部分客户端依赖至少一个内容块
+            self.open(OpenBlock::Text, &mut events);
+        }
+        self.close_block(&mut events);
+
+        events.push(sse_event(
+            "message_delta",
+            json!({
+                "type": "message_delta",
+                "delta": {
+                    "stop_reason": map_finish_reason(self.finish_reason.as_deref(), self.saw_tool_use),
+                    "stop_sequence": null
+                },
+                "usage": {
+                    "input_tokens": self.usage.input_tokens(),
+                    "cache_read_input_tokens": self.usage.cached_tokens,
+                    "output_tokens": self.usage.output_tokens()
+                }
+            }),
+        ));
+        events.push(sse_event("message_stop", json!({"type": "message_stop"})));
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event_types(events: &[String]) -> Vec<String> {
+        events
+            .iter()
+            .map(|e| {
+                e.lines()
+                    .next()
+                    .and_then(|l| l.strip_prefix("event: "))
+                    .unwrap_or_default()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    fn event_data(event: &str) -> Value {
+        let data = event
+            .lines()
+            .find_map(|l| l.strip_prefix("data: "))
+            .unwrap_or_default();
+        serde_json::from_str(data).unwrap()
+    }
+
+    #[test]
+    fn test_converts_thinking_text_and_tool_use_in_order() {
+        let mut converter = GeminiAnthropicSseConverter::new("gemini-2.5-pro", true);
+        let upstream = concat!(
+            "data: {\"response\":{\"candidates\":[{\"content\":{\"parts\":[",
+            "{\"text\":\"plan {\\\"x\\\"}\",\"thought\":true}]}}],",
+            "\"usageMetadata\":{\"promptTokenCount\":120,\"cachedContentTokenCount\":20}}}\n\n",
+            "data: {\"response\":{\"candidates\":[{\"content\":{\"parts\":[",
+            "{\"text\":\"\",\"thought\":true,\"thoughtSignature\":\"sig\"},",
+            "{\"text\":\"Hel\"}]}}]}}\n\n",
+            "data: {\"response\":{\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"lo\"},",
+            "{\"functionCall\":{\"name\":\"read\",\"args\":{\"path\":\"a.rs\"}}}]},",
+            "\"finishReason\":\"STOP\"}],",
+            "\"usageMetadata\":{\"promptTokenCount\":120,\"cachedContentTokenCount\":20,",
+            "\"candidatesTokenCount\":7,\"thoughtsTokenCount\":5}}}\n\n"
+        );
+
+        // 按任意位置切分，模拟网络分包（包括 UTF-8 与转义字符中间）
+        let mut events = Vec::new();
+        for piece in upstream.as_bytes().chunks(7) {
+            events.extend(converter.push_bytes(piece));
+        }
+        events.extend(converter.finish());
+        assert!(converter.finish().is_empty());
+
+        assert_eq!(
+            event_types(&events),
+            vec![
+                "message_start",
+                "ping",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_delta",
+                "content_block_stop",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_delta",
+                "content_block_stop",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_stop",
+                "message_delta",
+                "message_stop",
+            ]
+        );
+
+        let start = event_data(&events[0]);
+        assert_eq!(start["message"]["usage"]["input_tokens"], 100);
+        assert_eq!(event_data(&events[3])["delta"]["thinking"], "plan {\"x\"}");
+        assert_eq!(event_data(&events[4])["delta"]["signature"], "sig");
+        assert_eq!(event_data(&events[7])["index"], 1);
+        let tool_start = event_data(&events[10]);
+        assert_eq!(tool_start["index"], 2);
+        assert_eq!(tool_start["content_block"]["name"], "read");
+        assert_eq!(
+            event_data(&events[11])["delta"]["partial_json"],
+            "{\"path\":\"a.rs\"}"
+        );
+
+        let message_delta = event_data(&events[13]);
+        assert_eq!(message_delta["delta"]["stop_reason"], "tool_use");
+        assert_eq!(message_delta["usage"]["output_tokens"], 12);
+        assert_eq!(message_delta["usage"]["cache_read_input_tokens"], 20);
+    }
+
+    #[test]
+    fn test_empty_stream_and_errors() {
+        let mut converter = GeminiAnthropicSseConverter::new("gemini-2.5-flash", false);
+        let events = converter.push_bytes(b"[{\"candidates\":[{\"finishReason\":\"MAX_TOKENS\"}]}");
+        assert_eq!(event_types(&events), vec!["message_start", "ping"]);
+        let events = converter.finish();
+        assert_eq!(
+            event_types(&events),
+            vec![
+                "content_block_start",
+                "content_block_stop",
+                "message_delta",
+                "message_stop"
+            ]
+        );
+        assert_eq!(event_data(&events[2])["delta"]["stop_reason"], "max_tokens");
+
+        let mut converter = GeminiAnthropicSseConverter::new("gemini-2.5-flash", false);
+        let events = converter.push_bytes(b"{\"error\":{\"code\":429,\"message\":\"quota\"}}");
+        assert_eq!(event_types(&events), vec!["error"]);
+        assert_eq!(event_data(&events[0])["error"]["message"], "quota");
+        assert!(converter.finish().is_empty());
+    }
+}
@@ -10,6 +10,7 @@
 //! - `aws_parser`: AWS Event Stream 解析器（用于 Kiro/CodeWhisperer）
 //! - `anthropic_sse`: Anthropic SSE 事件生成器（将 AWS 事件转换为 Anthropic SSE 格式）
 //! - `converter`: 流式格式转换器
+//! - `gemini_anthropic_sse`: Gemini 流式响应到 Anthropic SSE 的转换
 //! - `traits`: StreamingProvider trait 定义
 //! - `manager`: 流式管理器
 
@@ -17,6 +18,7 @@ pub mod anthropic_sse;
 pub mod aws_parser;
 pub mod converter;
 pub mod error;
+pub mod gemini_anthropic_sse;
 pub mod manager;
 pub mod metrics;
 pub mod traits;
@@ -24,6 +26,7 @@ pub mod traits;
 // 重新导出核心类型
 pub use converter::StreamFormat;
 pub use error::StreamError;
+pub use gemini_anthropic_sse::GeminiAnthropicSseConverter;
 pub use manager::{with_timeout, StreamConfig, StreamContext, StreamManager};
 pub use metrics::StreamMetrics;
 pub use traits::{reqwest_stream_to_stream_response, StreamResponse};
@@ -128,6 +128,7 @@ impl StreamFormat {
 pub fn reqwest_stream_to_stream_response(response: reqwest::Response) -> StreamResponse {
     use futures::StreamExt;
 
+    crate::response_headers::record(response.headers());
     let stream = response
         .bytes_stream()
         .map(|result| result.map_err(StreamError::from));
@@ -0,0 +1,610 @@
+//! 多模态上传去重
+//!
+//! 请求中内联的图片、音频、PDF 等素材按内容（解码后字节的 SHA-256）寻址：首次出现时上传到
+//! Provider 的文件接口（Gemini Files API、Anthropic Files API），把请求中的内联数据替换为文件
+//! 引用，并在本地 `upload_file_refs` 表中记录；之后相同内容的素材直接引用已上传的文件。
+//!
+//! 文件按 Provider、端点与 API Key 指纹隔离（文件只对上传它的账号可见）。引用的保留时间
+//! 短于 Provider 的文件保留期，过期后重新上传。上传失败时保持内联，不影响请求。
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{OnceLock, RwLock};
+
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use lime_core::config::UploadDedupSettings;
+use lime_core::database::dao::upload_file_ref::{UploadFileRef, UploadFileRefDao};
+use lime_core::database::{lock_db, DbConnection};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::har_capture::CaptureSend;
+
+/// 引用 Anthropic 文件时需要携带的 `anthropic-beta` 值
+pub const ANTHROPIC_FILES_BETA: &str = "files-api-2025-04-14";
+
+/// Gemini 文件在过期前预留的余量
+const GEMINI_EXPIRY_MARGIN_MINUTES: i64 = 60;
+
+/// 去重统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadDedupStats {
+    /// 上传到 Provider 的素材数
+    pub uploaded: u64,
+    /// 复用已上传文件的次数
+    pub reused: u64,
+    /// 上传失败、保持内联的次数
+    pub failed: u64,
+    /// 复用文件省去的上传字节数
+    pub bytes_saved: u64,
+}
+
+/// 当前状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadDedupStatus {
+    pub enabled: bool,
+    /// 映射表所在的数据库是否可用（不可用时不去重）
+    pub database: bool,
+    pub entries: usize,
+    pub stats: UploadDedupStats,
+}
+
+#[derive(Default)]
+struct DedupState {
+    settings: UploadDedupSettings,
+    db: Option<DbConnection>,
+}
+
+#[derive(Default)]
+struct Counters {
+    uploaded: AtomicU64,
+    reused: AtomicU64,
+    failed: AtomicU64,
+    bytes_saved: AtomicU64,
+}
+
+fn state() -> &'static RwLock<DedupState> {
+    static STATE: OnceLock<RwLock<DedupState>> = OnceLock::new();
+    STATE.get_or_init(Default::default)
+}
+
+fn counters() -> &'static Counters {
+    static COUNTERS: OnceLock<Counters> = OnceLock::new();
+    COUNTERS.get_or_init(Default::default)
+}
+
+/// 应用配置（启动与热重载时调用）
+pub fn configure(settings: &UploadDedupSettings) {
+    state().write().unwrap_or_else(|e| e.into_inner()).settings = settings.clone();
+}
+
+/// 设置映射表所在的数据库（服务器启动时调用），同时清理已过期的条目
+pub fn set_database(db: Option<DbConnection>) {
+    if let Some(conn) = db.as_ref().and_then(|db| lock_db(db).ok()) {
+        if let Ok(removed) = UploadFileRefDao::prune_expired(&conn, Utc::now()) {
+            if removed > 0 {
+                tracing::debug!("[UPLOAD_DEDUP] 清理 {} 条过期的文件映射", removed);
+            }
+        }
+    }
+    state().write().unwrap_or_else(|e| e.into_inner()).db = db;
+}
+
+fn active_db() -> Option<(DbConnection, UploadDedupSettings)> {
+    let guard = state().read().unwrap_or_else(|e| e.into_inner());
+    if !guard.settings.enabled {
+        return None;
+    }
+    guard.db.clone().map(|db| (db, guard.settings.clone()))
+}
+
+pub fn is_enabled() -> bool {
+    active_db().is_some()
+}
+
+pub fn status() -> UploadDedupStatus {
+    let (enabled, db) = {
+        let guard = state().read().unwrap_or_else(|e| e.into_inner());
+        (guard.settings.enabled, guard.db.clone())
+    };
+    let entries = db
+        .as_ref()
+        .and_then(|db| lock_db(db).ok())
+        .and_then(|conn| UploadFileRefDao::count(&conn).ok())
+        .unwrap_or(0);
+    let counters = counters();
+    UploadDedupStatus {
+        enabled,
+        database: db.is_some(),
+        entries,
+        stats: UploadDedupStats {
+            uploaded: counters.uploaded.load(Ordering::Relaxed),
+            reused: counters.reused.load(Ordering::Relaxed),
+            failed: counters.failed.load(Ordering::Relaxed),
+            bytes_saved: counters.bytes_saved.load(Ordering::Relaxed),
+        },
+    }
+}
+
+/// 清空映射表（之后的素材会重新上传），返回清除的条目数
+pub fn clear() -> Result<usize, String> {
+    let db = state()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .db
+        .clone()
+        .ok_or("Database not available")?;
+    let conn = lock_db(&db)?;
+    UploadFileRefDao::clear(&conn).map_err(|e| e.to_string())
+}
+
+pub fn content_hash(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// 文件的可见范围：Provider + 端点 + API Key 指纹
+pub fn scope(provider: &str, endpoint: &str, api_key: &str) -> String {
+    let fingerprint = content_hash(api_key.as_bytes());
+    format!(
+        "{provider}:{}:{}",
+        endpoint.trim_end_matches('/'),
+        &fingerprint[..16]
+    )
+}
+
+/// 请求体中一处可去重的内联素材
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct InlineAsset {
+    /// 素材所在对象的 JSON Pointer（Gemini 为 part，Anthropic 为内容块）
+    pointer: String,
+    mime_type: String,
+    data: String,
+}
+
+fn gemini_dedup_mime(mime: &str) -> bool {
+    mime.starts_with("image/")
+        || mime.starts_with("audio/")
+        || mime.starts_with("video/")
+        || mime == "application/pdf"
+}
+
+fn anthropic_dedup_mime(mime: &str) -> bool {
+    mime.starts_with("image/") || mime == "application/pdf"
+}
+
+/// Gemini `contents[*].parts[*].inlineData`（兼容 snake_case）
+fn gemini_assets(body: &Value) -> Vec<InlineAsset> {
+    let mut assets = Vec::new();
+    let Some(contents) = body.get("contents").and_then(Value::as_array) else {
+        return assets;
+    };
+    for (i, content) in contents.iter().enumerate() {
+        let Some(parts) = content.get("parts").and_then(Value::as_array) else {
+            continue;
+        };
+        for (j, part) in parts.iter().enumerate() {
+            let inline = part.get("inlineData").or_else(|| part.get("inline_data"));
+            let Some(inline) = inline else {
+                continue;
+            };
+            let mime = inline
+                .get("mimeType")
+                .or_else(|| inline.get("mime_type"))
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            let Some(data) = inline.get("data").and_then(Value::as_str) else {
+                continue;
+            };
+            if gemini_dedup_mime(mime) {
+                assets.push(InlineAsset {
+                    pointer: format!("/contents/{i}/parts/{j}"),
+                    mime_type: mime.to_string(),
+                    data: data.to_string(),
+                });
+            }
+        }
+    }
+    assets
+}
+
+/// Anthropic `messages[*].content[*]` 中 `source.type = base64` 的图片与文档
+fn anthropic_assets(body: &Value) -> Vec<InlineAsset> {
+    let mut assets = Vec::new();
+    let Some(messages) = body.get("messages").and_then(Value::as_array) else {
+        return assets;
+    };
+    for (i, message) in messages.iter().enumerate() {
+        let Some(blocks) = message.get("content").and_then(Value::as_array) else {
+            continue;
+        };
+        for (j, block) in blocks.iter().enumerate() {
+            let source = &block["source"];
+            if source["type"] != "base64" {
+                continue;
+            }
+            let mime = source["media_type"].as_str().unwrap_or_default();
+            let Some(data) = source["data"].as_str() else {
+                continue;
+            };
+            if anthropic_dedup_mime(mime) {
+                assets.push(InlineAsset {
+                    pointer: format!("/messages/{i}/content/{j}"),
+                    mime_type: mime.to_string(),
+                    data: data.to_string(),
+                });
+            }
+        }
+    }
+    assets
+}
+
+fn replace_gemini(part: &mut Value, mime_type: &str, file_ref: &str) {
+    let Some(part) = part.as_object_mut() else {
+        return;
+    };
+    if part.remove("inline_data").is_some() {
+        part.insert(
+            "file_data".to_string(),
+            json!({ "mime_type": mime_type, "file_uri": file_ref }),
+        );
+    } else {
+        part.remove("inlineData");
+        part.insert(
+            "fileData".to_string(),
+            json!({ "mimeType": mime_type, "fileUri": file_ref }),
+        );
+    }
+}
+
+fn replace_anthropic(block: &mut Value, file_ref: &str) {
+    block["source"] = json!({ "type": "file", "file_id": file_ref });
+}
+
+/// 上传目标
+enum Upstream<'a> {
+    Gemini {
+        base_url: &'a str,
+    },
+    Anthropic {
+        files_url: &'a str,
+        version: &'a str,
+    },
+}
+
+impl Upstream<'_> {
+    fn provider(&self) -> &'static str {
+        match self {
+            Upstream::Gemini { .. } => "gemini",
+            Upstream::Anthropic { .. } => "anthropic",
+        }
+    }
+
+    fn endpoint(&self) -> &str {
+        match self {
+            Upstream::Gemini { base_url } => base_url,
+            Upstream::Anthropic { files_url, .. } => files_url,
+        }
+    }
+}
+
+fn file_extension(mime_type: &str) -> &str {
+    match mime_type.split_once('/').map(|(_, subtype)| subtype) {
+        Some("jpeg") => "jpg",
+        Some("mpeg") => "mp3",
+        Some(subtype) if !subtype.is_empty() => subtype,
+        _ => "bin",
+    }
+}
+
+/// 上传到 Gemini Files API（可续传协议），返回 `(fileUri, expirationTime)`
+async fn upload_gemini(
+    client: &Client,
+    base_url: &str,
+    api_key: &str,
+    hash: &str,
+    mime_type: &str,
+    bytes: Vec<u8>,
+) -> Result<(String, Option<DateTime<Utc>>), String> {
+    let start = client
+        .post(format!(
+            "{}/upload/v1beta/files",
+            base_url.trim_end_matches('/')
+        ))
+        .header("x-goog-api-key", api_key)
+        .header("X-Goog-Upload-Protocol", "resumable")
+        .header("X-Goog-Upload-Command", "start")
+        .header("X-Goog-Upload-Header-Content-Length", bytes.len())
+        .header("X-Goog-Upload-Header-Content-Type", mime_type)
+        .json(&json!({ "file": { "display_name": format!("lime-{}", &hash[..16]) } }))
+        .send_captured()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !start.status().is_success() {
+        return Err(format!("创建上传会话失败: {}", start.status()));
+    }
+    let upload_url = start
+        .headers()
+        .get("x-goog-upload-url")
+        .and_then(|v| v.to_str().ok())
+        .ok_or("响应缺少 x-goog-upload-url")?
+        .to_string();
+
+    let response = client
+        .post(upload_url)
+        .header("X-Goog-Upload-Command", "upload, finalize")
+        .header("X-Goog-Upload-Offset", "0")
+        .body(bytes)
+        .send_captured()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("上传失败: {}", response.status()));
+    }
+    let body: Value = response.json().await.map_err(|e| e.to_string())?;
+    let uri = body["file"]["uri"]
+        .as_str()
+        .ok_or("响应缺少 file.uri")?
+        .to_string();
+    let expiration = body["file"]["expirationTime"]
+        .as_str()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc));
+    Ok((uri, expiration))
+}
+
+/// 上传到 Anthropic Files API，返回 `file_id`
+async fn upload_anthropic(
+    client: &Client,
+    files_url: &str,
+    version: &str,
+    api_key: &str,
+    hash: &str,
+    mime_type: &str,
+    bytes: Vec<u8>,
+) -> Result<String, String> {
+    let boundary = format!("lime-{}", &hash[..24]);
+    let filename = format!("lime-{}.{}", &hash[..16], file_extension(mime_type));
+    let mut body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{filename}\"\r\n\
+         Content-Type: {mime_type}\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(&bytes);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+    let response = client
+        .post(files_url)
+        .header("x-api-key", api_key)
+        .header("anthropic-version", version)
+        .header("anthropic-beta", ANTHROPIC_FILES_BETA)
+        .header(
+            "Content-Type",
+            format!("multipart/form-data; boundary={boundary}"),
+        )
+        .body(body)
+        .send_captured()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("上传失败: {}", response.status()));
+    }
+    let body: Value = response.json().await.map_err(|e| e.to_string())?;
+    body["id"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "响应缺少 id".to_string())
+}
+
+fn expires_at(
+    upstream: &Upstream<'_>,
+    settings: &UploadDedupSettings,
+    now: DateTime<Utc>,
+    provider_expiry: Option<DateTime<Utc>>,
+) -> Option<DateTime<Utc>> {
+    match upstream {
+        Upstream::Gemini { .. } => {
+            let configured = now + Duration::hours(i64::from(settings.gemini_ttl_hours.min(47)));
+            let limit = provider_expiry
+                .map(|t| t - Duration::minutes(GEMINI_EXPIRY_MARGIN_MINUTES))
+                .unwrap_or(configured);
+            Some(configured.min(limit))
+        }
+        Upstream::Anthropic { .. } => (settings.anthropic_ttl_hours > 0)
+            .then(|| now + Duration::hours(i64::from(settings.anthropic_ttl_hours))),
+    }
+}
+
+async fn dedup(client: &Client, upstream: Upstream<'_>, api_key: &str, body: &mut Value) -> usize {
+    let Some((db, settings)) = active_db() else {
+        return 0;
+    };
+    let assets = match upstream {
+        Upstream::Gemini { .. } => gemini_assets(body),
+        Upstream::Anthropic { .. } => anthropic_assets(body),
+    };
+    if assets.is_empty() {
+        return 0;
+    }
+    let scope = scope(upstream.provider(), upstream.endpoint(), api_key);
+    let counters = counters();
+
+    let mut replaced = 0;
+    for asset in assets {
+        let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(asset.data.as_bytes())
+        else {
+            continue;
+        };
+        if bytes.len() < settings.min_bytes {
+            continue;
+        }
+        let hash = content_hash(&bytes);
+        let now = Utc::now();
+        let cached = lock_db(&db)
+            .ok()
+            .and_then(|conn| UploadFileRefDao::get(&conn, &scope, &hash, now).ok())
+            .flatten();
+
+        let file_ref = match cached {
+            Some(entry) => {
+                counters.reused.fetch_add(1, Ordering::Relaxed);
+                counters
+                    .bytes_saved
+                    .fetch_add(bytes.len() as u64, Ordering::Relaxed);
+                entry.file_ref
+            }
+            None => {
+                let size_bytes = bytes.len() as u64;
+                let uploaded = match upstream {
+                    Upstream::Gemini { base_url } => {
+                        upload_gemini(client, base_url, api_key, &hash, &asset.mime_type, bytes)
+                            .await
+                    }
+                    Upstream::Anthropic { files_url, version } => upload_anthropic(
+                        client,
+                        files_url,
+                        version,
+                        api_key,
+                        &hash,
+                        &asset.mime_type,
+                        bytes,
+                    )
+                    .await
+                    .map(|id| (id, None)),
+                };
+                let (file_ref, provider_expiry) = match uploaded {
+                    Ok(uploaded) => uploaded,
+                    Err(e) => {
+                        counters.failed.fetch_add(1, Ordering::Relaxed);
+                        tracing::warn!(
+                            "[UPLOAD_DEDUP] {} 上传素材失败，保持内联: {}",
+                            upstream.provider(),
+                            e
+                        );
+                        continue;
+                    }
+                };
+                counters.uploaded.fetch_add(1, Ordering::Relaxed);
+                let entry = UploadFileRef {
+                    scope: scope.clone(),
+                    content_hash: hash,
+                    file_ref: file_ref.clone(),
+                    mime_type: asset.mime_type.clone(),
+                    size_bytes,
+                    hit_count: 0,
+                    created_at: now,
+                    expires_at: expires_at(&upstream, &settings, now, provider_expiry),
+                };
+                if let Ok(conn) = lock_db(&db) {
+                    if let Err(e) = UploadFileRefDao::upsert(&conn, &entry) {
+                        tracing::warn!("[UPLOAD_DEDUP] 写入文件映射失败: {}", e);
+                    }
+                }
+                file_ref
+            }
+        };
+
+        let Some(target) = body.pointer_mut(&asset.pointer) else {
+            continue;
+        };
+        match upstream {
+            Upstream::Gemini { .. } => replace_gemini(target, &asset.mime_type, &file_ref),
+            Upstream::Anthropic { .. } => replace_anthropic(target, &file_ref),
+        }
+        replaced += 1;
+    }
+
+    if replaced > 0 {
+        tracing::debug!(
+            "[UPLOAD_DEDUP] {} 请求中 {} 个素材改为文件引用",
+            upstream.provider(),
+            replaced
+        );
+    }
+    replaced
+}
+
+/// 把 Gemini 请求体中的内联素材替换为 Files API 引用，返回替换的数量
+pub async fn dedup_gemini(
+    client: &Client,
+    base_url: &str,
+    api_key: &str,
+    body: &mut Value,
+) -> usize {
+    dedup(client, Upstream::Gemini { base_url }, api_key, body).await
+}
+
+/// 把 Anthropic Messages 请求体中的内联素材替换为 Files API 引用，返回替换的数量
+///
+/// 返回值大于 0 时，发送请求需携带 `anthropic-beta: files-api-2025-04-14`。
+pub async fn dedup_anthropic(
+    client: &Client,
+    files_url: &str,
+    version: &str,
+    api_key: &str,
+    body: &mut Value,
+) -> usize {
+    dedup(
+        client,
+        Upstream::Anthropic { files_url, version },
+        api_key,
+        body,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_and_replace_inline_assets() {
+        let mut gemini = json!({
+            "contents": [{
+                "role": "user",
+                "parts": [
+                    { "text": "describe" },
+                    { "inlineData": { "mimeType": "image/png", "data": "aGVsbG8=" } },
+                    { "inline_data": { "mime_type": "text/plain", "data": "aGVsbG8=" } }
+                ]
+            }]
+        });
+        let assets = gemini_assets(&gemini);
+        assert_eq!(assets.len(), 1);
+        assert_eq!(assets[0].pointer, "/contents/0/parts/1");
+        replace_gemini(
+            gemini.pointer_mut(&assets[0].pointer).unwrap(),
+            "image/png",
+            "https://files/abc",
+        );
+        assert_eq!(
+            gemini["contents"][0]["parts"][1],
+            json!({ "fileData": { "mimeType": "image/png", "fileUri": "https://files/abc" } })
+        );
+
+        let mut anthropic = json!({
+            "messages": [{
+                "role": "user",
+                "content": [
+                    { "type": "text", "text": "hi" },
+                    { "type": "image", "source": { "type": "base64", "media_type": "image/jpeg", "data": "aGk=" } }
+                ]
+            }]
+        });
+        let assets = anthropic_assets(&anthropic);
+        assert_eq!(assets[0].pointer, "/messages/0/content/1");
+        replace_anthropic(anthropic.pointer_mut(&assets[0].pointer).unwrap(), "file_1");
+        assert_eq!(
+            anthropic["messages"][0]["content"][1]["source"],
+            json!({ "type": "file", "file_id": "file_1" })
+        );
+        assert!(anthropic_assets(&anthropic).is_empty());
+
+        assert_ne!(
+            scope("gemini", "https://a/", "key-1"),
+            scope("gemini", "https://a", "key-2")
+        );
+        assert_eq!(file_extension("image/jpeg"), "jpg");
+    }
+}
@@ -0,0 +1,399 @@
+//! 上游企业网关认证
+//!
+//! 企业自建网关前置 Gemini / OpenAI 等上游时，往往要求额外的认证方式。按 `providers.upstream_auth`
+//! 为发往匹配主机的请求附加认证信息，依次应用：
+//! - 静态请求头：覆盖同名请求头
+//! - OAuth 客户端凭证：向令牌端点换取访问令牌（缓存至过期前 60 秒），以 `Bearer <令牌>` 写入请求头；
+//!   上游返回 401 时丢弃缓存的令牌，下次请求重新获取
+//! - HMAC 签名：签名串为 `{timestamp}\n{METHOD}\n{path?query}\n{hex(sha256(body))}`，
+//!   写入时间戳与签名请求头（与本地 `server.request_signing` 的签名串格式一致）
+//!
+//! 只作用于经 [`CaptureSend::send_captured`](crate::har_capture::CaptureSend) 发出的请求。
+//! 未配置 `hosts` 时使用该 Provider 端点覆盖（`providers.endpoints` 等）中的主机。
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use lime_core::config::{
+    HmacEncoding, ProvidersConfig, UpstreamAuthConfig, UpstreamHmacAuth,
+    UpstreamOAuthClientCredentials,
+};
+use lime_core::models::injection_types::pattern_matches;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use reqwest::{Client, Request, RequestBuilder, Response, StatusCode};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::providers::endpoints;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 令牌提前刷新的余量
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// 令牌响应未给出有效期时的默认值
+const DEFAULT_TOKEN_LIFETIME_SECS: u64 = 3600;
+
+/// 令牌请求超时
+const TOKEN_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+struct Rule {
+    /// Provider 名称（也是令牌缓存的键）
+    name: String,
+    hosts: Vec<String>,
+    auth: UpstreamAuthConfig,
+}
+
+struct CachedToken {
+    value: String,
+    refresh_at: Instant,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+fn rules() -> &'static RwLock<Vec<Rule>> {
+    static RULES: OnceLock<RwLock<Vec<Rule>>> = OnceLock::new();
+    RULES.get_or_init(Default::default)
+}
+
+fn tokens() -> &'static RwLock<HashMap<String, CachedToken>> {
+    static TOKENS: OnceLock<RwLock<HashMap<String, CachedToken>>> = OnceLock::new();
+    TOKENS.get_or_init(Default::default)
+}
+
+fn token_client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(Client::new)
+}
+
+/// Provider 端点覆盖中的主机
+fn override_host(provider: &str) -> Option<String> {
+    if !endpoints::has_base_url_override(provider) {
+        return None;
+    }
+    url::Url::parse(&endpoints::base_url(provider, ""))
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+}
+
+fn collect_rules(config: &ProvidersConfig) -> Vec<Rule> {
+    let mut collected = Vec::new();
+    for (name, auth) in &config.upstream_auth {
+        let name = name.trim().to_lowercase();
+        let mut hosts: Vec<String> = auth
+            .hosts
+            .iter()
+            .map(|host| host.trim().to_ascii_lowercase())
+            .filter(|host| !host.is_empty())
+            .collect();
+        if hosts.is_empty() {
+            hosts.extend(override_host(&name));
+        }
+        if hosts.is_empty() {
+            tracing::warn!(
+                "[UPSTREAM_AUTH] {} 未配置 hosts，且没有上游端点覆盖，认证配置不生效",
+                name
+            );
+            continue;
+        }
+        collected.push(Rule {
+            name,
+            hosts,
+            auth: auth.clone(),
+        });
+    }
+    collected.sort_by(|a, b| a.name.cmp(&b.name));
+    collected
+}
+
+/// 按配置更新网关认证（启动与配置重载时调用，需在端点覆盖更新之后）
+pub fn configure(config: &ProvidersConfig) {
+    let collected = collect_rules(config);
+    if !collected.is_empty() {
+        tracing::info!(
+            "[UPSTREAM_AUTH] 已配置上游网关认证: {:?}",
+            collected
+                .iter()
+                .map(|rule| rule.name.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+    *rules().write().unwrap_or_else(|e| e.into_inner()) = collected;
+    tokens().write().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// 是否配置了任何网关认证
+pub fn is_enabled() -> bool {
+    !rules().read().unwrap_or_else(|e| e.into_inner()).is_empty()
+}
+
+/// 网关认证写入的请求头名称（小写，抓包时脱敏；时间戳请求头除外）
+pub fn credential_header_names() -> Vec<String> {
+    let rules = rules().read().unwrap_or_else(|e| e.into_inner());
+    let mut names: Vec<String> = Vec::new();
+    for rule in rules.iter() {
+        let auth = &rule.auth;
+        names.extend(auth.headers.keys().cloned());
+        if let Some(oauth) = &auth.oauth_client_credentials {
+            names.push(oauth.header.clone());
+        }
+        if let Some(hmac) = &auth.hmac {
+            names.push(hmac.signature_header.clone());
+            names.extend(hmac.key_id_header.clone());
+        }
+    }
+    for name in names.iter_mut() {
+        *name = name.trim().to_ascii_lowercase();
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn rule_for(host: &str) -> Option<Rule> {
+    rules()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find(|rule| rule.hosts.iter().any(|p| pattern_matches(p, host)))
+        .cloned()
+}
+
+/// 构建签名串
+pub fn canonical_string(timestamp: i64, method: &str, path: &str, body: &[u8]) -> String {
+    let digest: String = Sha256::digest(body)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!(
+        "{timestamp}\n{}\n{path}\n{digest}",
+        method.to_ascii_uppercase()
+    )
+}
+
+/// 计算签名
+pub fn sign(secret: &str, canonical: &str, encoding: HmacEncoding) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC 支持任意长度的密钥");
+    mac.update(canonical.as_bytes());
+    let bytes = mac.finalize().into_bytes();
+    match encoding {
+        HmacEncoding::Hex => bytes.iter().map(|b| format!("{b:02x}")).collect(),
+        HmacEncoding::Base64 => base64::engine::general_purpose::STANDARD.encode(bytes),
+    }
+}
+
+fn set_header(headers: &mut HeaderMap, name: &str, value: &str) {
+    match (
+        HeaderName::from_bytes(name.trim().as_bytes()),
+        HeaderValue::from_str(value),
+    ) {
+        (Ok(name), Ok(value)) => {
+            headers.insert(name, value);
+        }
+        _ => tracing::warn!("[UPSTREAM_AUTH] 忽略无效的请求头: {}", name),
+    }
+}
+
+fn apply_hmac(request: &mut Request, hmac: &UpstreamHmacAuth, timestamp: i64) {
+    let url = request.url();
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+    let body = match request.body() {
+        Some(body) => body.as_bytes().unwrap_or_else(|| {
+            tracing::warn!("[UPSTREAM_AUTH] 流式请求体无法签名，按空请求体计算");
+            &[]
+        }),
+        None => &[],
+    };
+    let canonical = canonical_string(timestamp, request.method().as_str(), &path, body);
+    let signature = sign(&hmac.secret, &canonical, hmac.encoding);
+    let headers = request.headers_mut();
+    set_header(headers, &hmac.timestamp_header, &timestamp.to_string());
+    set_header(headers, &hmac.signature_header, &signature);
+    if let (Some(header), Some(key_id)) = (&hmac.key_id_header, &hmac.key_id) {
+        set_header(headers, header, key_id);
+    }
+}
+
+fn cached_token(name: &str) -> Option<String> {
+    tokens()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(name)
+        .filter(|token| token.refresh_at > Instant::now())
+        .map(|token| token.value.clone())
+}
+
+fn invalidate_token(name: &str) {
+    tokens()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(name);
+}
+
+async fn access_token(
+    name: &str,
+    oauth: &UpstreamOAuthClientCredentials,
+) -> reqwest::Result<String> {
+    if let Some(token) = cached_token(name) {
+        return Ok(token);
+    }
+    let mut form = vec![
+        ("grant_type", "client_credentials"),
+        ("client_id", oauth.client_id.as_str()),
+        ("client_secret", oauth.client_secret.as_str()),
+    ];
+    if let Some(scope) = &oauth.scope {
+        form.push(("scope", scope.as_str()));
+    }
+    if let Some(audience) = &oauth.audience {
+        form.push(("audience", audience.as_str()));
+    }
+    let body = serde_urlencoded::to_string(&form).unwrap_or_default();
+    let response: TokenResponse = token_client()
+        .post(&oauth.token_url)
+        .timeout(TOKEN_REQUEST_TIMEOUT)
+        .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(body)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let lifetime = Duration::from_secs(response.expires_in.unwrap_or(DEFAULT_TOKEN_LIFETIME_SECS));
+    tokens().write().unwrap_or_else(|e| e.into_inner()).insert(
+        name.to_string(),
+        CachedToken {
+            value: response.access_token.clone(),
+            refresh_at: Instant::now() + lifetime.saturating_sub(TOKEN_REFRESH_MARGIN),
+        },
+    );
+    tracing::debug!("[UPSTREAM_AUTH] {} 已获取访问令牌", name);
+    Ok(response.access_token)
+}
+
+/// 为请求附加认证信息
+async fn apply(rule: &Rule, mut request: Request) -> reqwest::Result<Request> {
+    for (name, value) in &rule.auth.headers {
+        set_header(request.headers_mut(), name, value);
+    }
+    if let Some(oauth) = &rule.auth.oauth_client_credentials {
+        let token = access_token(&rule.name, oauth).await.map_err(|e| {
+            tracing::warn!("[UPSTREAM_AUTH] {} 获取访问令牌失败: {}", rule.name, e);
+            e
+        })?;
+        set_header(
+            request.headers_mut(),
+            &oauth.header,
+            &format!("Bearer {token}"),
+        );
+    }
+    if let Some(hmac) = &rule.auth.hmac {
+        apply_hmac(&mut request, hmac, chrono::Utc::now().timestamp());
+    }
+    Ok(request)
+}
+
+/// 按主机匹配网关认证后发送
+pub(crate) async fn send(builder: RequestBuilder) -> reqwest::Result<Response> {
+    let (client, request) = builder.build_split();
+    let request = request?;
+    let host = request
+        .url()
+        .host_str()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let Some(rule) = rule_for(&host) else {
+        return crate::har_capture::dispatch(RequestBuilder::from_parts(client, request)).await;
+    };
+
+    let request = apply(&rule, request).await?;
+    let response =
+        crate::har_capture::dispatch(RequestBuilder::from_parts(client, request)).await?;
+    if response.status() == StatusCode::UNAUTHORIZED && rule.auth.oauth_client_credentials.is_some()
+    {
+        invalidate_token(&rule.name);
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_apply_hmac() {
+        // RFC 4231 测试用例 2
+        let mut mac = HmacSha256::new_from_slice(b"Jefe").unwrap();
+        mac.update(b"what do ya want for nothing?");
+        let expected: String = mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        assert_eq!(
+            expected,
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        let canonical = canonical_string(1_700_000_000, "post", "/v1/chat?x=1", b"{}");
+        assert!(canonical.starts_with("1700000000\nPOST\n/v1/chat?x=1\n44136fa3"));
+
+        let client = Client::new();
+        let mut request = client
+            .post("https://llm-gw.corp.example/v1/chat?x=1")
+            .body("{}")
+            .build()
+            .unwrap();
+        let hmac = UpstreamHmacAuth {
+            secret: "s3cret".to_string(),
+            key_id: Some("lime".to_string()),
+            key_id_header: Some("x-key-id".to_string()),
+            signature_header: "x-signature".to_string(),
+            timestamp_header: "x-timestamp".to_string(),
+            encoding: HmacEncoding::Base64,
+        };
+        apply_hmac(&mut request, &hmac, 1_700_000_000);
+        let headers = request.headers();
+        assert_eq!(headers["x-timestamp"], "1700000000");
+        assert_eq!(headers["x-key-id"], "lime");
+        assert_eq!(
+            headers["x-signature"].to_str().unwrap(),
+            sign("s3cret", &canonical, HmacEncoding::Base64)
+        );
+    }
+
+    #[test]
+    fn test_collect_rules_uses_override_host() {
+        let mut config = ProvidersConfig::default();
+        config.upstream_auth.insert(
+            "OpenAI".to_string(),
+            UpstreamAuthConfig {
+                hosts: vec!["*.corp.example".to_string()],
+                headers: HashMap::from([("x-tenant".to_string(), "team-a".to_string())]),
+                ..Default::default()
+            },
+        );
+        config
+            .upstream_auth
+            .insert("codex".to_string(), UpstreamAuthConfig::default());
+
+        let rules = collect_rules(&config);
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].name, "openai");
+        assert!(pattern_matches(&rules[0].hosts[0], "llm-gw.corp.example"));
+    }
+}
@@ -10,6 +10,7 @@ use axum::{
 };
 use futures::stream;
 use lime_core::errors::{GatewayError, GatewayErrorCode, GatewayErrorResponse};
+use lime_core::i18n::{self, MessageCode};
 use lime_core::models::openai::{ContentPart, FunctionCall, MessageContent, ToolCall};
 use std::collections::HashMap;
 
@@ -80,7 +81,7 @@ pub fn build_gateway_error_json(
         serde_json::json!({
             "error": {
                 "code": "INTERNAL_ERROR",
-                "message": "序列化错误响应失败",
+                "message": i18n::t(MessageCode::ErrorSerializationFailed),
                 "retryable": false
             }
         })
@@ -354,14 +355,27 @@ pub fn parse_bracket_tool_calls(result: &mut CWParsedResponse) {
 
 /// 构建 Anthropic 非流式响应
 pub fn build_anthropic_response(model: &str, parsed: &CWParsedResponse) -> Response {
+    build_anthropic_response_with_citations(model, parsed, &[])
+}
+
+/// 构建 Anthropic 非流式响应，文本块附带 `citations`
+pub fn build_anthropic_response_with_citations(
+    model: &str,
+    parsed: &CWParsedResponse,
+    citations: &[serde_json::Value],
+) -> Response {
     let has_tool_calls = !parsed.tool_calls.is_empty();
     let mut content_array: Vec<serde_json::Value> = Vec::new();
 
     if !parsed.content.is_empty() {
-        content_array.push(serde_json::json!({
+        let mut text_block = serde_json::json!({
             "type": "text",
             "text": parsed.content
-        }));
+        });
+        if !citations.is_empty() {
+            text_block["citations"] = serde_json::Value::Array(citations.to_vec());
+        }
+        content_array.push(text_block);
     }
 
     for tc in &parsed.tool_calls {
@@ -403,6 +417,15 @@ pub fn build_anthropic_response(model: &str, parsed: &CWParsedResponse) -> Respo
 
 /// 构建 Anthropic 流式响应 (SSE)
 pub fn build_anthropic_stream_response(model: &str, parsed: &CWParsedResponse) -> Response {
+    build_anthropic_stream_response_with_citations(model, parsed, &[])
+}
+
+/// 构建 Anthropic 流式响应 (SSE)，引用以 `citations_delta` 事件发送
+pub fn build_anthropic_stream_response_with_citations(
+    model: &str,
+    parsed: &CWParsedResponse,
+    citations: &[serde_json::Value],
+) -> Response {
     let has_tool_calls = !parsed.tool_calls.is_empty();
     let message_id = format!("msg_{}", uuid::Uuid::new_v4());
     let model = model.to_string();
@@ -448,6 +471,16 @@ pub fn build_anthropic_stream_response(model: &str, parsed: &CWParsedResponse) -
         events.push(format!(
             "event: content_block_delta\ndata: {block_delta}\n\n"
         ));
+
+        for citation in citations {
+            let citation_delta = serde_json::json!({
+                "type": "content_block_delta", "index": block_index,
+                "delta": {"type": "citations_delta", "citation": citation}
+            });
+            events.push(format!(
+                "event: content_block_delta\ndata: {citation_delta}\n\n"
+            ));
+        }
     }
 
     let block_stop = serde_json::json!({"type": "content_block_stop", "index": block_index});
@@ -0,0 +1,144 @@
+//! 推理请求的调用方身份
+//!
+//! 在签名验证之后识别请求使用的 Key（主 Key、有效的受限 Key 或已验证的签名请求），
+//! 把 [`Caller`] 写入请求扩展。本中间件只识别不拦截，认证失败仍由处理器返回 401 并计入锁定；
+//! 在途请求日志、对话记录采集、模型并发与优先级通道等内层中间件只处理带 [`Caller`] 的请求，
+//! 未认证的请求直接交给处理器拒绝，不占用名额也不落盘。
+
+use axum::{
+    extract::{Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use chrono::Utc;
+use lime_core::database::dao::proxy_transcript::PRIMARY_KEY_ID;
+use subtle::ConstantTimeEq;
+
+use crate::auth::scoped_keys::{ScopedKeyRecord, ScopedKeyStore};
+use crate::middleware::request_signing::{is_forwarded_request, is_signature_verified};
+use crate::AppState;
+
+/// 认证通过的调用方
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caller {
+    /// 受限 Key 记录，主 Key 与签名请求为 `None`
+    pub scoped: Option<ScopedKeyRecord>,
+}
+
+impl Caller {
+    /// 主 Key（或签名请求）
+    pub fn primary() -> Self {
+        Self { scoped: None }
+    }
+
+    pub fn is_primary(&self) -> bool {
+        self.scoped.is_none()
+    }
+
+    /// Key 标识：受限 Key 的 ID，主 Key 为 [`PRIMARY_KEY_ID`]
+    pub fn key_id(&self) -> &str {
+        self.scoped
+            .as_ref()
+            .map_or(PRIMARY_KEY_ID, |record| record.id.as_str())
+    }
+}
+
+/// 按请求头识别调用方（`weak_key_blocked` 为 true 时不接受主 Key）
+pub fn identify(
+    headers: &HeaderMap,
+    api_key: &str,
+    scoped_keys: &ScopedKeyStore,
+    weak_key_blocked: bool,
+) -> Option<Caller> {
+    if is_signature_verified(headers) {
+        return Some(Caller::primary());
+    }
+    let keys: Vec<&str> = ["authorization", "x-api-key"]
+        .iter()
+        .filter_map(|name| headers.get(*name).and_then(|v| v.to_str().ok()))
+        .map(|value| value.strip_prefix("Bearer ").unwrap_or(value))
+        .collect();
+
+    let now = Utc::now();
+    if let Some(record) = keys.iter().find_map(|key| {
+        scoped_keys
+            .record_for(key)
+            .filter(|record| !record.is_expired(now) && !record.is_exhausted())
+    }) {
+        return Some(Caller {
+            scoped: Some(record),
+        });
+    }
+
+    let primary = !weak_key_blocked
+        && !api_key.is_empty()
+        && keys
+            .iter()
+            .any(|key| bool::from(key.as_bytes().ct_eq(api_key.as_bytes())));
+    primary.then(Caller::primary)
+}
+
+/// 调用方识别中间件
+pub async fn caller_identity_middleware(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let weak_key_blocked = state.tunnel_requires_strong_key
+        && is_forwarded_request(request.headers())
+        && !lime_core::config::is_strong_api_key(&state.api_key);
+    if let Some(caller) = identify(
+        request.headers(),
+        &state.api_key,
+        &state.scoped_keys,
+        weak_key_blocked,
+    ) {
+        request.extensions_mut().insert(caller);
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::scoped_keys::ScopedKeyOptions;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_identify_primary_and_scoped_keys() {
+        let store = ScopedKeyStore::in_memory();
+        let issued = store
+            .issue_with(&ScopedKeyOptions {
+                label: "mobile".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(identify(&headers, "sk-master", &store, false), None);
+
+        headers.insert("authorization", HeaderValue::from_static("Bearer sk-wrong"));
+        assert_eq!(identify(&headers, "sk-master", &store, false), None);
+
+        headers.insert("x-api-key", HeaderValue::from_static("sk-master"));
+        assert_eq!(
+            identify(&headers, "sk-master", &store, false),
+            Some(Caller::primary())
+        );
+        assert_eq!(identify(&headers, "sk-master", &store, true), None);
+        assert_eq!(identify(&headers, "", &store, false), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "authorization",
+            HeaderValue::from_str(&format!("Bearer {}", issued.api_key)).unwrap(),
+        );
+        let caller = identify(&headers, "sk-master", &store, true).unwrap();
+        assert_eq!(caller.key_id(), issued.record.id);
+        assert!(!caller.is_primary());
+
+        store.revoke(&issued.record.id).unwrap();
+        assert_eq!(identify(&headers, "sk-master", &store, false), None);
+    }
+}
@@ -0,0 +1,72 @@
+//! 受限 Key 轮换的定时检查与通知
+//!
+//! 按 `server.key_rotation.check_interval_secs` 推进各 Key 的轮换状态，
+//! 把轮换通知发布为 `ServerEvent::KeyRotation` 应用事件，并以 JSON 发送到配置的 Webhook。
+//! 通知只包含 Key ID、标签与时间，不含明文。同一任务也定期写入受限 Key 的请求计数。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use lime_core::app_events::{publish_app_event, AppEvent, ServerEvent};
+use lime_core::config::KeyRotationSettings;
+use serde_json::json;
+
+use super::scoped_keys::{RotationNotice, ScopedKeyStore};
+
+/// Webhook 请求超时
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 请求计数写盘间隔
+const COUNTER_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+fn publish(notice: &RotationNotice) {
+    publish_app_event(AppEvent::Server(ServerEvent::KeyRotation {
+        key_id: notice.key_id.clone(),
+        label: notice.label.clone(),
+        stage: notice.stage,
+        successor_id: notice.successor_id.clone(),
+        expires_at: notice.expires_at.map(|t| t.to_rfc3339()),
+    }));
+}
+
+async fn send_webhooks(client: &reqwest::Client, urls: &[String], notice: &RotationNotice) {
+    let payload = json!({ "event": "key_rotation", "data": notice });
+    for url in urls {
+        let result = client
+            .post(url)
+            .timeout(WEBHOOK_TIMEOUT)
+            .json(&payload)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = result {
+            tracing::warn!("[KEY_ROTATION] 发送 Webhook 通知到 {} 失败: {}", url, e);
+        }
+    }
+}
+
+/// 启动定时检查任务
+pub fn spawn(
+    store: Arc<ScopedKeyStore>,
+    settings: KeyRotationSettings,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = reqwest::Client::new();
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(settings.check_interval_secs.max(1)));
+        let mut flush_interval = tokio::time::interval(COUNTER_FLUSH_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = flush_interval.tick() => {
+                    store.flush();
+                    continue;
+                }
+            }
+            for notice in store.rotate_due(chrono::Utc::now()) {
+                publish(&notice);
+                send_webhooks(&client, &settings.webhook_urls, &notice).await;
+            }
+        }
+    })
+}
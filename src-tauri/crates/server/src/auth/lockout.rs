@@ -0,0 +1,363 @@
+//! 认证失败锁定
+//!
+//! 按来源 IP 统计 API Key 校验失败次数，窗口内超过阈值后临时锁定，
+//! 再次触发时锁定时长翻倍（不超过上限）。锁定期间请求直接返回 429。
+//!
+//! 失败由 `verify_api_key*` 通过任务局部标记上报，上游 Provider 返回的 401 不计入。
+
+use std::collections::{HashMap, VecDeque};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use lime_core::app_events::{publish_app_event, AppEvent, ServerEvent};
+use lime_core::config::AuthLockoutSettings;
+use lime_core::errors::GatewayErrorCode;
+use lime_core::i18n::{self, MessageCode};
+use lime_server_utils::build_gateway_error_json;
+use parking_lot::RwLock;
+use serde::Serialize;
+
+use crate::AppState;
+
+const OUTCOME_NONE: u8 = 0;
+const OUTCOME_SUCCESS: u8 = 1;
+const OUTCOME_FAILURE: u8 = 2;
+
+tokio::task_local! {
+    static AUTH_OUTCOME: Arc<AtomicU8>;
+}
+
+/// 上报本次请求的认证失败（不在中间件作用域内时忽略）
+pub fn note_auth_failure() {
+    let _ = AUTH_OUTCOME.try_with(|outcome| outcome.store(OUTCOME_FAILURE, Ordering::Relaxed));
+}
+
+/// 上报本次请求的认证成功
+pub fn note_auth_success() {
+    let _ = AUTH_OUTCOME.try_with(|outcome| {
+        let _ = outcome.compare_exchange(
+            OUTCOME_NONE,
+            OUTCOME_SUCCESS,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        );
+    });
+}
+
+/// 被锁定的 IP（供前端展示）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BannedIp {
+    pub ip: String,
+    /// 窗口内失败次数
+    pub failures: u32,
+    /// 累计锁定次数
+    pub strikes: u32,
+    pub locked_until: DateTime<Utc>,
+    pub last_failure_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+struct IpRecord {
+    failures: VecDeque<DateTime<Utc>>,
+    strikes: u32,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+/// 认证失败锁定表
+pub struct AuthLockout {
+    settings: RwLock<AuthLockoutSettings>,
+    records: RwLock<HashMap<IpAddr, IpRecord>>,
+}
+
+impl AuthLockout {
+    pub fn new(settings: AuthLockoutSettings) -> Self {
+        Self {
+            settings: RwLock::new(settings),
+            records: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 更新配置（重启服务器时调用，保留已有锁定）
+    pub fn apply_settings(&self, settings: AuthLockoutSettings) {
+        *self.settings.write() = settings;
+    }
+
+    /// 返回剩余锁定秒数
+    pub fn locked_for(&self, ip: IpAddr, now: DateTime<Utc>) -> Option<u64> {
+        if !self.settings.read().enabled {
+            return None;
+        }
+        let records = self.records.read();
+        let locked_until = records.get(&ip)?.locked_until?;
+        (locked_until > now).then(|| (locked_until - now).num_seconds().max(1) as u64)
+    }
+
+    /// 记录一次失败，触发锁定时返回锁定时长（秒）
+    pub fn record_failure(&self, ip: IpAddr, now: DateTime<Utc>) -> Option<u64> {
+        let settings = self.settings.read().clone();
+        if !settings.enabled {
+            return None;
+        }
+        let window_start = now - Duration::seconds(settings.window_secs as i64);
+        let mut records = self.records.write();
+        let record = records.entry(ip).or_default();
+        while record.failures.front().is_some_and(|t| *t < window_start) {
+            record.failures.pop_front();
+        }
+        record.failures.push_back(now);
+        if (record.failures.len() as u32) < settings.max_failures.max(1) {
+            return None;
+        }
+
+        let exponent = record.strikes.min(16);
+        let lockout_secs = settings
+            .base_lockout_secs
+            .saturating_mul(1u64 << exponent)
+            .min(settings.max_lockout_secs.max(settings.base_lockout_secs));
+        record.strikes += 1;
+        record.locked_until = Some(now + Duration::seconds(lockout_secs as i64));
+        record.failures.clear();
+        Some(lockout_secs)
+    }
+
+    /// 认证成功后清除失败计数（保留累计锁定次数）
+    pub fn record_success(&self, ip: IpAddr) {
+        let mut records = self.records.write();
+        if let Some(record) = records.get_mut(&ip) {
+            record.failures.clear();
+            if record.strikes == 0 && record.locked_until.is_none() {
+                records.remove(&ip);
+            }
+        }
+    }
+
+    /// 当前被锁定的 IP
+    pub fn list_banned(&self, now: DateTime<Utc>) -> Vec<BannedIp> {
+        let mut banned: Vec<BannedIp> = self
+            .records
+            .read()
+            .iter()
+            .filter_map(|(ip, record)| {
+                let locked_until = record.locked_until.filter(|until| *until > now)?;
+                Some(BannedIp {
+                    ip: ip.to_string(),
+                    failures: record.failures.len() as u32,
+                    strikes: record.strikes,
+                    locked_until,
+                    last_failure_at: record.failures.back().copied().unwrap_or(locked_until),
+                })
+            })
+            .collect();
+        banned.sort_by(|a, b| b.locked_until.cmp(&a.locked_until));
+        banned
+    }
+
+    /// 解除锁定（`ip` 为空时清除全部），返回清除的记录数
+    pub fn clear(&self, ip: Option<IpAddr>) -> usize {
+        let mut records = self.records.write();
+        match ip {
+            Some(ip) => records.remove(&ip).map_or(0, |_| 1),
+            None => {
+                let count = records.len();
+                records.clear();
+                count
+            }
+        }
+    }
+}
+
+/// 解析来源 IP：直连时使用对端地址；本机转发（隧道）时使用转发头中的客户端地址。
+/// 本机直连请求（桌面端自身）不参与锁定。
+///
+/// `X-Forwarded-For` 的前几项由客户端自行填写，只取最右一项（由本机隧道追加）；
+/// `cf-connecting-ip` 只在通过 Cloudflare 隧道暴露时可信。
+fn client_ip(
+    peer: Option<SocketAddr>,
+    headers: &HeaderMap,
+    behind_cloudflare: bool,
+) -> Option<IpAddr> {
+    let peer_ip = peer?.ip();
+    if !peer_ip.is_loopback() {
+        return Some(peer_ip);
+    }
+    headers
+        .get("cf-connecting-ip")
+        .filter(|_| behind_cloudflare)
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| {
+            headers
+                .get("x-forwarded-for")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.rsplit(',').next())
+        })
+        .and_then(|v| v.trim().parse().ok())
+}
+
+fn locked_response(retry_after: u64) -> Response {
+    let message = i18n::t_with(
+        MessageCode::AuthLockedOut,
+        &[("seconds", &retry_after.to_string())],
+    );
+    let body = build_gateway_error_json(
+        StatusCode::TOO_MANY_REQUESTS.as_u16(),
+        &message,
+        None,
+        None,
+        Some(GatewayErrorCode::AuthenticationFailed),
+    );
+    let mut response = (StatusCode::TOO_MANY_REQUESTS, Json(body)).into_response();
+    if let Ok(value) = HeaderValue::from_str(&retry_after.to_string()) {
+        response.headers_mut().insert(header::RETRY_AFTER, value);
+    }
+    response
+}
+
+/// 认证失败锁定中间件
+pub async fn auth_lockout_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| info.0);
+    let Some(ip) = client_ip(peer, request.headers(), state.behind_cloudflare) else {
+        return next.run(request).await;
+    };
+
+    let lockout = &state.auth_lockout;
+    if let Some(retry_after) = lockout.locked_for(ip, Utc::now()) {
+        return locked_response(retry_after);
+    }
+
+    let outcome = Arc::new(AtomicU8::new(OUTCOME_NONE));
+    let response = AUTH_OUTCOME.scope(outcome.clone(), next.run(request)).await;
+
+    match outcome.load(Ordering::Relaxed) {
+        OUTCOME_FAILURE => {
+            if let Some(lockout_secs) = lockout.record_failure(ip, Utc::now()) {
+                let failures = lockout.settings.read().max_failures;
+                tracing::warn!(
+                    "[AUTH] {} 认证失败 {} 次，锁定 {} 秒",
+                    ip,
+                    failures,
+                    lockout_secs
+                );
+                publish_app_event(AppEvent::Server(ServerEvent::AuthLockout {
+                    ip: ip.to_string(),
+                    failures,
+                    lockout_secs,
+                }));
+            } else {
+                tracing::info!("[AUTH] 来自 {} 的 API Key 校验失败", ip);
+            }
+        }
+        OUTCOME_SUCCESS => lockout.record_success(ip),
+        _ => {}
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lockout() -> AuthLockout {
+        AuthLockout::new(AuthLockoutSettings {
+            enabled: true,
+            max_failures: 3,
+            window_secs: 60,
+            base_lockout_secs: 10,
+            max_lockout_secs: 25,
+        })
+    }
+
+    #[test]
+    fn should_lock_with_exponential_backoff() {
+        let lockout = lockout();
+        let ip: IpAddr = "203.0.113.7".parse().expect("合法 IP");
+        let now = Utc::now();
+
+        assert_eq!(lockout.record_failure(ip, now), None);
+        assert_eq!(lockout.record_failure(ip, now), None);
+        assert_eq!(lockout.record_failure(ip, now), Some(10));
+        assert!(lockout.locked_for(ip, now).is_some());
+        assert_eq!(lockout.list_banned(now)[0].strikes, 1);
+
+        let later = now + Duration::seconds(11);
+        assert_eq!(lockout.locked_for(ip, later), None);
+        for _ in 0..2 {
+            lockout.record_failure(ip, later);
+        }
+        assert_eq!(lockout.record_failure(ip, later), Some(20));
+        for _ in 0..2 {
+            lockout.record_failure(ip, later + Duration::seconds(30));
+        }
+        assert_eq!(
+            lockout.record_failure(ip, later + Duration::seconds(30)),
+            Some(25)
+        );
+    }
+
+    #[test]
+    fn should_forget_failures_outside_window_and_on_success() {
+        let lockout = lockout();
+        let ip: IpAddr = "203.0.113.8".parse().expect("合法 IP");
+        let now = Utc::now();
+
+        lockout.record_failure(ip, now);
+        lockout.record_failure(ip, now);
+        assert_eq!(
+            lockout.record_failure(ip, now + Duration::seconds(61)),
+            None
+        );
+        lockout.record_success(ip);
+        assert_eq!(
+            lockout.record_failure(ip, now + Duration::seconds(62)),
+            None
+        );
+        assert_eq!(lockout.clear(None), 1);
+    }
+
+    #[test]
+    fn should_use_rightmost_forwarded_entry_for_tunnel_requests() {
+        let loopback: SocketAddr = "127.0.0.1:50000".parse().expect("合法地址");
+        let remote: SocketAddr = "198.51.100.4:50000".parse().expect("合法地址");
+        let mut headers = HeaderMap::new();
+
+        assert_eq!(client_ip(Some(loopback), &headers, false), None);
+        assert_eq!(client_ip(None, &headers, false), None);
+
+        // 客户端伪造的前置项被忽略，只取隧道追加的最右一项
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("10.9.9.9, 203.0.113.9"),
+        );
+        assert_eq!(
+            client_ip(Some(loopback), &headers, false),
+            Some("203.0.113.9".parse().expect("合法 IP"))
+        );
+        assert_eq!(client_ip(Some(remote), &headers, false), Some(remote.ip()));
+
+        // 未通过 Cloudflare 暴露时不信任 cf-connecting-ip
+        headers.insert("cf-connecting-ip", HeaderValue::from_static("192.0.2.1"));
+        assert_eq!(
+            client_ip(Some(loopback), &headers, false),
+            Some("203.0.113.9".parse().expect("合法 IP"))
+        );
+        assert_eq!(
+            client_ip(Some(loopback), &headers, true),
+            Some("192.0.2.1".parse().expect("合法 IP"))
+        );
+    }
+}
@@ -1,3 +1,8 @@
 //! 认证模块
 
+pub mod caller;
+pub mod key_rotation;
+pub mod lockout;
+pub mod oidc;
 pub mod pairing;
+pub mod scoped_keys;
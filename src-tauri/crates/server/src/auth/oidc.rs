@@ -0,0 +1,518 @@
+//! 管理接口 OIDC 认证
+//!
+//! 团队部署时用 OIDC 提供方（Keycloak、Authentik、Azure AD 等）保护管理接口，
+//! 取代共享的主 API Key：
+//! - 通过 `{issuer}/.well-known/openid-configuration` 发现 JWKS，按 `kid` 选择公钥验签，
+//!   允许的算法由公钥的 `alg` / `kty` 决定而非 Token 头；找不到 `kid` 时强制刷新
+//!   （密钥轮换，至多每 30 秒一次）
+//! - 校验 `iss` / `aud` / `exp`，按组声明映射角色：viewer 只能发起只读请求，operator 不限
+//! - 验证通过后把 [`AdminIdentity`] 写入请求扩展，处理器以 `Option<Extension<AdminIdentity>>`
+//!   提取并交给 `verify_admin_key` 放行；扩展只能由服务端中间件写入，客户端无法伪造
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use lime_core::config::AdminOidcSettings;
+use lime_core::errors::GatewayErrorCode;
+use lime_server_utils::build_gateway_error_json;
+use serde_json::Value;
+use subtle::ConstantTimeEq;
+use tokio::sync::RwLock;
+
+use crate::auth::lockout::note_auth_failure;
+use crate::handlers::remote_control::REMOTE_CONTROL_PATH_PREFIX;
+use crate::middleware::cors::wildcard_match;
+use crate::AppState;
+
+/// 遇到未知 `kid` 时强制刷新 JWKS 的最小间隔
+const FORCED_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 管理角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AdminRole {
+    /// 只读
+    Viewer,
+    /// 可修改
+    Operator,
+}
+
+impl AdminRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdminRole::Viewer => "viewer",
+            AdminRole::Operator => "operator",
+        }
+    }
+
+    /// 角色是否允许该请求方法
+    pub fn permits(&self, method: &Method) -> bool {
+        match self {
+            AdminRole::Operator => true,
+            AdminRole::Viewer => matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS),
+        }
+    }
+}
+
+/// OIDC 认证错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OidcError {
+    Missing,
+    InvalidToken(String),
+    Discovery(String),
+    NoRole,
+    Forbidden,
+}
+
+impl OidcError {
+    fn status(&self) -> StatusCode {
+        match self {
+            OidcError::Missing | OidcError::InvalidToken(_) => StatusCode::UNAUTHORIZED,
+            OidcError::Discovery(_) => StatusCode::SERVICE_UNAVAILABLE,
+            OidcError::NoRole | OidcError::Forbidden => StatusCode::FORBIDDEN,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            OidcError::Missing => "Admin API requires an OIDC bearer token".to_string(),
+            OidcError::InvalidToken(e) => format!("Invalid OIDC token: {e}"),
+            OidcError::Discovery(e) => format!("OIDC provider unavailable: {e}"),
+            OidcError::NoRole => "Token groups are not mapped to an admin role".to_string(),
+            OidcError::Forbidden => "Viewer role cannot modify admin resources".to_string(),
+        }
+    }
+}
+
+/// 验证通过的管理身份
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdminIdentity {
+    pub subject: String,
+    pub role: AdminRole,
+}
+
+/// 按路径读取声明（支持 `a.b.c`），字符串或字符串数组均视为组列表
+pub fn claim_groups(claims: &Value, path: &str) -> Vec<String> {
+    let value = path
+        .split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(claims, |value, segment| value.get(segment));
+    match value {
+        Some(Value::String(group)) => vec![group.clone()],
+        Some(Value::Array(groups)) => groups
+            .iter()
+            .filter_map(|group| group.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// 按组映射角色，同时命中时取 operator
+pub fn role_for_groups(settings: &AdminOidcSettings, groups: &[String]) -> Option<AdminRole> {
+    let in_any = |configured: &[String]| {
+        configured
+            .iter()
+            .any(|group| groups.iter().any(|g| g == group.trim()))
+    };
+    if in_any(&settings.operator_groups) {
+        Some(AdminRole::Operator)
+    } else if in_any(&settings.viewer_groups) {
+        Some(AdminRole::Viewer)
+    } else {
+        None
+    }
+}
+
+/// 公钥允许的签名算法：优先使用 JWK 的 `alg`，否则按 `kty`（及曲线）推断
+fn allowed_algorithms(jwk: &Jwk) -> Vec<Algorithm> {
+    if let Some(alg) = &jwk.common.key_algorithm {
+        // 加密用途的算法（如 RSA-OAEP）无法解析为签名算法，视为不可用
+        return serde_json::to_value(alg)
+            .ok()
+            .and_then(|value| value.as_str()?.parse::<Algorithm>().ok())
+            .into_iter()
+            .collect();
+    }
+    match &jwk.algorithm {
+        AlgorithmParameters::RSA(_) => vec![
+            Algorithm::RS256,
+            Algorithm::RS384,
+            Algorithm::RS512,
+            Algorithm::PS256,
+            Algorithm::PS384,
+            Algorithm::PS512,
+        ],
+        AlgorithmParameters::EllipticCurve(params) => match params.curve {
+            EllipticCurve::P256 => vec![Algorithm::ES256],
+            EllipticCurve::P384 => vec![Algorithm::ES384],
+            _ => Vec::new(),
+        },
+        AlgorithmParameters::OctetKeyPair(_) => vec![Algorithm::EdDSA],
+        AlgorithmParameters::OctetKey(_) => {
+            vec![Algorithm::HS256, Algorithm::HS384, Algorithm::HS512]
+        }
+    }
+}
+
+struct CachedJwks {
+    keys: JwkSet,
+    fetched_at: Instant,
+}
+
+/// OIDC Token 验证器
+pub struct OidcVerifier {
+    settings: AdminOidcSettings,
+    client: reqwest::Client,
+    jwks: RwLock<Option<CachedJwks>>,
+    /// 最近一次因未知 `kid` 强制刷新的时间
+    last_forced_refresh: Mutex<Option<Instant>>,
+}
+
+impl OidcVerifier {
+    /// 未启用或缺少 issuer 时返回 `None`
+    pub fn from_settings(settings: &AdminOidcSettings) -> Option<Self> {
+        if !settings.enabled {
+            return None;
+        }
+        if settings.issuer.trim().is_empty() {
+            tracing::warn!("[ADMIN_OIDC] 已启用 OIDC 但未配置 issuer，管理接口 OIDC 认证不会生效");
+            return None;
+        }
+        Some(Self {
+            settings: settings.clone(),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            jwks: RwLock::new(None),
+            last_forced_refresh: Mutex::new(None),
+        })
+    }
+
+    pub fn settings(&self) -> &AdminOidcSettings {
+        &self.settings
+    }
+
+    /// 路径是否受保护
+    pub fn protects(&self, path: &str) -> bool {
+        // 远程控制接口由处理器用独立密钥验签
+        !path.starts_with(REMOTE_CONTROL_PATH_PREFIX)
+            && self
+                .settings
+                .paths
+                .iter()
+                .any(|pattern| wildcard_match(pattern.trim(), path))
+    }
+
+    fn issuer(&self) -> &str {
+        self.settings.issuer.trim().trim_end_matches('/')
+    }
+
+    async fn fetch_jwks(&self) -> Result<JwkSet, OidcError> {
+        let discovery_url = format!("{}/.well-known/openid-configuration", self.issuer());
+        let discovery: Value = self
+            .client
+            .get(&discovery_url)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .map_err(|e| OidcError::Discovery(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| OidcError::Discovery(e.to_string()))?;
+        let jwks_uri = discovery["jwks_uri"]
+            .as_str()
+            .ok_or_else(|| OidcError::Discovery("discovery document has no jwks_uri".into()))?;
+        self.client
+            .get(jwks_uri)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .map_err(|e| OidcError::Discovery(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| OidcError::Discovery(e.to_string()))
+    }
+
+    /// 获取 JWKS（缓存过期或 `force` 时重新拉取）
+    async fn jwks(&self, force: bool) -> Result<JwkSet, OidcError> {
+        let ttl = Duration::from_secs(self.settings.jwks_ttl_secs.max(60));
+        if !force {
+            if let Some(cached) = self.jwks.read().await.as_ref() {
+                if cached.fetched_at.elapsed() < ttl {
+                    return Ok(cached.keys.clone());
+                }
+            }
+        }
+        let keys = self.fetch_jwks().await?;
+        tracing::info!("[ADMIN_OIDC] 已刷新 JWKS: {} 个公钥", keys.keys.len());
+        *self.jwks.write().await = Some(CachedJwks {
+            keys: keys.clone(),
+            fetched_at: Instant::now(),
+        });
+        Ok(keys)
+    }
+
+    /// 是否允许因未知 `kid` 强制刷新 JWKS（限制频率，避免伪造 `kid` 放大请求）
+    fn try_begin_forced_refresh(&self) -> bool {
+        let mut last = self
+            .last_forced_refresh
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if last.is_some_and(|at| at.elapsed() < FORCED_REFRESH_INTERVAL) {
+            return false;
+        }
+        *last = Some(Instant::now());
+        true
+    }
+
+    /// 用指定 JWKS 验证 Token；`kid` 不在 JWKS 中时返回 `Ok(None)`
+    pub fn verify_with_jwks(
+        &self,
+        token: &str,
+        jwks: &JwkSet,
+    ) -> Result<Option<AdminIdentity>, OidcError> {
+        let header = decode_header(token).map_err(|e| OidcError::InvalidToken(e.to_string()))?;
+        let kid = header
+            .kid
+            .as_deref()
+            .ok_or_else(|| OidcError::InvalidToken("token header has no kid".into()))?;
+        let Some(jwk) = jwks.find(kid) else {
+            return Ok(None);
+        };
+        let algorithms = allowed_algorithms(jwk);
+        if !algorithms.contains(&header.alg) {
+            return Err(OidcError::InvalidToken(format!(
+                "algorithm {:?} is not allowed for key {kid}",
+                header.alg
+            )));
+        }
+        let key = DecodingKey::from_jwk(jwk).map_err(|e| OidcError::InvalidToken(e.to_string()))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.algorithms = algorithms;
+        validation.set_issuer(&[self.issuer()]);
+        let audience = self.settings.audience.trim();
+        if audience.is_empty() {
+            validation.validate_aud = false;
+        } else {
+            validation.set_audience(&[audience]);
+        }
+        let claims = decode::<Value>(token, &key, &validation)
+            .map_err(|e| OidcError::InvalidToken(e.to_string()))?
+            .claims;
+
+        let groups = claim_groups(&claims, &self.settings.groups_claim);
+        let role = role_for_groups(&self.settings, &groups).ok_or(OidcError::NoRole)?;
+        Ok(Some(AdminIdentity {
+            subject: claims["sub"].as_str().unwrap_or_default().to_string(),
+            role,
+        }))
+    }
+
+    /// 验证 Token
+    pub async fn verify(&self, token: &str) -> Result<AdminIdentity, OidcError> {
+        if let Some(identity) = self.verify_with_jwks(token, &self.jwks(false).await?)? {
+            return Ok(identity);
+        }
+        let not_found = || OidcError::InvalidToken("signing key not found in JWKS".into());
+        if !self.try_begin_forced_refresh() {
+            return Err(not_found());
+        }
+        self.verify_with_jwks(token, &self.jwks(true).await?)?
+            .ok_or_else(not_found)
+    }
+}
+
+fn reject(error: OidcError) -> Response {
+    if error.status() == StatusCode::UNAUTHORIZED {
+        note_auth_failure();
+    }
+    let status = error.status();
+    let body = build_gateway_error_json(
+        status.as_u16(),
+        &error.message(),
+        None,
+        None,
+        Some(GatewayErrorCode::AuthenticationFailed),
+    );
+    (status, Json(body)).into_response()
+}
+
+/// 管理接口 OIDC 认证中间件
+pub async fn admin_oidc_middleware(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(verifier) = state.admin_oidc.clone() else {
+        return next.run(request).await;
+    };
+    if !verifier.protects(request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let token = request
+        .headers()
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .map(str::to_string);
+    let Some(token) = token else {
+        return reject(OidcError::Missing);
+    };
+
+    let identity = if verifier.settings().allow_api_key
+        && bool::from(token.as_bytes().ct_eq(state.api_key.as_bytes()))
+    {
+        AdminIdentity {
+            subject: "api-key".to_string(),
+            role: AdminRole::Operator,
+        }
+    } else {
+        match verifier.verify(&token).await {
+            Ok(identity) => identity,
+            Err(e) => {
+                tracing::warn!(
+                    "[ADMIN_OIDC] 认证失败: {} {}",
+                    request.uri().path(),
+                    e.message()
+                );
+                return reject(e);
+            }
+        }
+    };
+    if !identity.role.permits(request.method()) {
+        return reject(OidcError::Forbidden);
+    }
+
+    tracing::debug!(
+        "[ADMIN_OIDC] {} {} sub={} role={}",
+        request.method(),
+        request.uri().path(),
+        identity.subject,
+        identity.role.as_str()
+    );
+    request.extensions_mut().insert(identity);
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    fn settings() -> AdminOidcSettings {
+        AdminOidcSettings {
+            enabled: true,
+            issuer: "https://sso.example.com/realms/team/".to_string(),
+            audience: "lime".to_string(),
+            groups_claim: "realm_access.roles".to_string(),
+            operator_groups: vec!["lime-operators".to_string()],
+            viewer_groups: vec!["lime-viewers".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_role_mapping_from_nested_claim() {
+        let claims = serde_json::json!({"realm_access": {"roles": ["lime-viewers", "offline"]}});
+        let groups = claim_groups(&claims, "realm_access.roles");
+
+        assert_eq!(
+            role_for_groups(&settings(), &groups),
+            Some(AdminRole::Viewer)
+        );
+        assert_eq!(role_for_groups(&settings(), &["other".to_string()]), None);
+        assert!(!AdminRole::Viewer.permits(&Method::POST));
+        assert!(AdminRole::Operator.permits(&Method::DELETE));
+
+        let verifier = OidcVerifier::from_settings(&settings()).expect("应启用");
+        assert!(verifier.protects("/admin/config"));
+        assert!(!verifier.protects("/admin/remote/reload-config"));
+    }
+
+    #[test]
+    fn test_verify_with_jwks_checks_issuer_and_audience() {
+        let verifier = OidcVerifier::from_settings(&settings()).expect("应启用");
+        let jwks: JwkSet = serde_json::from_value(serde_json::json!({
+            "keys": [{"kty": "oct", "kid": "k1", "alg": "HS256", "k": "c2VjcmV0LWtleS1mb3ItdGVzdHM"}]
+        }))
+        .unwrap();
+        let mut header = Header::new(jsonwebtoken::Algorithm::HS256);
+        header.kid = Some("k1".to_string());
+        let sign = |claims: Value| {
+            encode(
+                &header,
+                &claims,
+                &EncodingKey::from_secret(b"secret-key-for-tests"),
+            )
+            .unwrap()
+        };
+        let exp = chrono::Utc::now().timestamp() + 600;
+
+        let token = sign(serde_json::json!({
+            "iss": "https://sso.example.com/realms/team",
+            "aud": "lime",
+            "sub": "alice",
+            "exp": exp,
+            "realm_access": {"roles": ["lime-operators"]},
+        }));
+        let identity = verifier.verify_with_jwks(&token, &jwks).unwrap().unwrap();
+        assert_eq!(identity.subject, "alice");
+        assert_eq!(identity.role, AdminRole::Operator);
+
+        let wrong_audience = sign(serde_json::json!({
+            "iss": "https://sso.example.com/realms/team",
+            "aud": "other-app",
+            "exp": exp,
+            "realm_access": {"roles": ["lime-operators"]},
+        }));
+        assert!(matches!(
+            verifier.verify_with_jwks(&wrong_audience, &jwks),
+            Err(OidcError::InvalidToken(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_unpinned_algorithm_and_missing_kid() {
+        let verifier = OidcVerifier::from_settings(&settings()).expect("应启用");
+        let jwks: JwkSet = serde_json::from_value(serde_json::json!({
+            "keys": [{"kty": "oct", "kid": "k1", "alg": "HS256", "k": "c2VjcmV0LWtleS1mb3ItdGVzdHM"}]
+        }))
+        .unwrap();
+        let claims = serde_json::json!({
+            "iss": "https://sso.example.com/realms/team",
+            "aud": "lime",
+            "exp": chrono::Utc::now().timestamp() + 600,
+            "realm_access": {"roles": ["lime-operators"]},
+        });
+        let key = EncodingKey::from_secret(b"secret-key-for-tests");
+
+        let mut hs512 = Header::new(jsonwebtoken::Algorithm::HS512);
+        hs512.kid = Some("k1".to_string());
+        let token = encode(&hs512, &claims, &key).unwrap();
+        assert!(matches!(
+            verifier.verify_with_jwks(&token, &jwks),
+            Err(OidcError::InvalidToken(_))
+        ));
+
+        let no_kid = encode(&Header::new(jsonwebtoken::Algorithm::HS256), &claims, &key).unwrap();
+        assert!(matches!(
+            verifier.verify_with_jwks(&no_kid, &jwks),
+            Err(OidcError::InvalidToken(_))
+        ));
+
+        assert!(verifier.try_begin_forced_refresh());
+        assert!(!verifier.try_begin_forced_refresh());
+    }
+}
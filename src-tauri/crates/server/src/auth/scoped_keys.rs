@@ -0,0 +1,936 @@
+//! 受限 API Key
+//!
+//! 为移动端、协作者或演示应用签发的独立 API Key：
+//! - 仅允许调用推理端点（`/v1/*`），不能访问管理与凭证接口
+//! - 可设置过期时间、允许的模型、最大请求次数与最大输出 Token，可单独吊销
+//! - 只持久化 SHA-256 哈希，明文仅在签发时返回一次
+//! - 可设置轮换策略（最长使用时间、最多请求次数）：接近上限时生成后继 Key，达到上限后
+//!   旧 Key 进入宽限期，两个 Key 同时有效，宽限期结束后旧 Key 删除。后继 Key 的明文随机生成，
+//!   生成时通过轮换通知告知 Key 的所有者，由管理员通过 `POST /v1/keys/:id/successor` 领取
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::{DateTime, Duration, Utc};
+use lime_core::app_events::KeyRotationStage;
+use lime_core::config::{AttributionMode, PriorityLane, StreamOutputLimit};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::middleware::cors::wildcard_match;
+
+/// 受限 Key 持久化文件名（位于应用数据目录）
+const SCOPED_KEYS_FILE: &str = "scoped-api-keys.json";
+
+/// 受限 Key 前缀（便于在日志和客户端中识别）
+const SCOPED_KEY_PREFIX: &str = "pc_m_";
+
+/// 累计多少次未落盘的请求计数后立即写盘（其余由定时任务与停止服务时写盘）
+const COUNTER_FLUSH_BATCH: u64 = 50;
+
+/// 受限 Key 记录（不含明文）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScopedKeyRecord {
+    pub id: String,
+    pub label: String,
+    pub key_hash: String,
+    /// 明文前缀（用于界面展示）
+    pub key_prefix: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// 允许调用的模型（支持 `*` 通配），为空表示不限制
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+    /// 最大请求次数，未设置表示不限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_requests: Option<u64>,
+    /// 已使用的请求次数
+    #[serde(default)]
+    pub request_count: u64,
+    /// 响应归属标注方式，未设置时使用 `server.attribution.mode`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<AttributionMode>,
+    /// 最大输出 Token 数，未设置时只受 `server.max_output_tokens` 限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+    /// 单次流式响应的最大输出 Token 数，未设置时只受 `server.stream_output_caps` 限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_stream_tokens: Option<u32>,
+    /// 单次流式响应的最大字节数，未设置时只受 `server.stream_output_caps` 限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_stream_bytes: Option<u64>,
+    /// 调度通道，未设置时按请求头或 `server.priority_lanes.default_lane`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<PriorityLane>,
+    /// Key 持有者同意其对话记录用于训练（导出微调数据集）
+    #[serde(default)]
+    pub training_consent: bool,
+    /// 轮换策略，未设置表示不自动轮换
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<KeyRotationPolicy>,
+    /// 已生成的后继 Key ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub successor_id: Option<String>,
+    /// 由哪个 Key 轮换而来
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub predecessor_id: Option<String>,
+    /// 达到轮换条件、进入宽限期的时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotated_at: Option<DateTime<Utc>>,
+}
+
+/// Key 轮换策略（最长使用时间与最多请求次数至少设置一项，先达到者触发轮换）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyRotationPolicy {
+    /// 最长使用时间（小时）
+    #[serde(default)]
+    pub max_age_hours: Option<u64>,
+    /// 最多请求次数（与 `max_requests` 硬上限不同，达到后进入宽限期而不是立即失效）
+    #[serde(default)]
+    pub max_requests: Option<u64>,
+    /// 达到上限的该百分比时提前生成后继 Key
+    #[serde(default = "default_prepare_percent")]
+    pub prepare_percent: u8,
+    /// 宽限期（小时），期间新旧 Key 同时有效
+    #[serde(default = "default_grace_hours")]
+    pub grace_hours: u64,
+}
+
+fn default_prepare_percent() -> u8 {
+    80
+}
+
+fn default_grace_hours() -> u64 {
+    24
+}
+
+impl KeyRotationPolicy {
+    /// 使用进度（达到上限为 1.0），取时间与请求次数中较大的一项
+    fn progress(&self, record: &ScopedKeyRecord, now: DateTime<Utc>) -> f64 {
+        let age = self
+            .max_age_hours
+            .filter(|hours| *hours > 0)
+            .map(|hours| (now - record.created_at).num_seconds() as f64 / (hours as f64 * 3600.0));
+        let usage = self
+            .max_requests
+            .filter(|max| *max > 0)
+            .map(|max| record.request_count as f64 / max as f64);
+        age.into_iter().chain(usage).fold(0.0, f64::max)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.max_age_hours.unwrap_or(0) == 0 && self.max_requests.unwrap_or(0) == 0 {
+            return Err("轮换策略需要设置最长使用时间或最多请求次数".to_string());
+        }
+        if self.prepare_percent == 0 || self.prepare_percent > 100 {
+            return Err("提前生成后继 Key 的百分比必须在 1-100 之间".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 轮换通知（发布为应用事件并发送到 Webhook）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RotationNotice {
+    pub key_id: String,
+    pub label: String,
+    pub stage: KeyRotationStage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub successor_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl ScopedKeyRecord {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// 请求次数是否已用完
+    pub fn is_exhausted(&self) -> bool {
+        self.max_requests
+            .is_some_and(|max_requests| self.request_count >= max_requests)
+    }
+
+    /// 是否允许调用该模型
+    pub fn allows_model(&self, model: &str) -> bool {
+        self.models.is_empty()
+            || self
+                .models
+                .iter()
+                .any(|pattern| wildcard_match(pattern.trim(), model))
+    }
+}
+
+/// 签发选项
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScopedKeyOptions {
+    pub label: String,
+    /// 有效期（分钟），0 表示永不过期
+    #[serde(default)]
+    pub ttl_minutes: u64,
+    /// 允许调用的模型（支持 `*` 通配），为空表示不限制
+    #[serde(default)]
+    pub models: Vec<String>,
+    /// 最大请求次数
+    #[serde(default)]
+    pub max_requests: Option<u64>,
+    /// 响应归属标注方式，未设置时使用全局配置
+    #[serde(default)]
+    pub attribution: Option<AttributionMode>,
+    /// 最大输出 Token 数
+    #[serde(default)]
+    pub max_output_tokens: Option<u32>,
+    /// 单次流式响应的最大输出 Token 数
+    #[serde(default)]
+    pub max_stream_tokens: Option<u32>,
+    /// 单次流式响应的最大字节数
+    #[serde(default)]
+    pub max_stream_bytes: Option<u64>,
+    /// 调度通道
+    #[serde(default)]
+    pub priority: Option<PriorityLane>,
+    /// 同意对话记录用于训练
+    #[serde(default)]
+    pub training_consent: bool,
+    /// 轮换策略
+    #[serde(default)]
+    pub rotation: Option<KeyRotationPolicy>,
+}
+
+/// 新签发的受限 Key（含明文，仅返回一次）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuedScopedKey {
+    pub record: ScopedKeyRecord,
+    pub api_key: String,
+}
+
+/// 受限 Key 存储
+pub struct ScopedKeyStore {
+    path: Option<PathBuf>,
+    records: RwLock<Vec<ScopedKeyRecord>>,
+    /// 待发送的轮换通知
+    notices: Mutex<Vec<RotationNotice>>,
+    /// 尚未落盘的请求计数次数
+    pending_counts: AtomicU64,
+}
+
+fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+fn generate_key() -> String {
+    use rand::distributions::Alphanumeric;
+    use rand::Rng;
+
+    let token: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect();
+    format!("{SCOPED_KEY_PREFIX}{token}")
+}
+
+impl ScopedKeyStore {
+    /// 从默认位置加载
+    pub fn load_default() -> Self {
+        Self::load(lime_core::app_paths::best_effort_app_data_file(
+            SCOPED_KEYS_FILE,
+        ))
+    }
+
+    /// 从指定文件加载（文件不存在或损坏时为空）
+    pub fn load(path: PathBuf) -> Self {
+        let records = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            records: RwLock::new(records),
+            notices: Mutex::new(Vec::new()),
+            pending_counts: AtomicU64::new(0),
+        }
+    }
+
+    /// 仅内存存储（测试用）
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            records: RwLock::new(Vec::new()),
+            notices: Mutex::new(Vec::new()),
+            pending_counts: AtomicU64::new(0),
+        }
+    }
+
+    fn persist(&self, records: &[ScopedKeyRecord]) -> Result<(), String> {
+        self.pending_counts.store(0, Ordering::Relaxed);
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {e}"))?;
+        }
+        let content =
+            serde_json::to_string_pretty(records).map_err(|e| format!("序列化失败: {e}"))?;
+        std::fs::write(path, content).map_err(|e| format!("写入受限 Key 失败: {e}"))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600));
+        }
+        Ok(())
+    }
+
+    /// 签发新 Key（`ttl_hours` 为 0 表示永不过期）
+    pub fn issue(&self, label: &str, ttl_hours: u64) -> Result<IssuedScopedKey, String> {
+        self.issue_with(&ScopedKeyOptions {
+            label: label.to_string(),
+            ttl_minutes: ttl_hours.saturating_mul(60),
+            ..Default::default()
+        })
+    }
+
+    /// 按选项签发新 Key
+    pub fn issue_with(&self, options: &ScopedKeyOptions) -> Result<IssuedScopedKey, String> {
+        if options.max_requests == Some(0) {
+            return Err("最大请求次数必须大于 0".to_string());
+        }
+        if options.max_output_tokens == Some(0) {
+            return Err("最大输出 Token 数必须大于 0".to_string());
+        }
+        if options.max_stream_tokens == Some(0) || options.max_stream_bytes == Some(0) {
+            return Err("流式输出上限必须大于 0".to_string());
+        }
+        if let Some(policy) = &options.rotation {
+            policy.validate()?;
+        }
+        let api_key = generate_key();
+        let now = Utc::now();
+        let record = ScopedKeyRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            label: options.label.trim().to_string(),
+            key_hash: hash_key(&api_key),
+            key_prefix: api_key.chars().take(SCOPED_KEY_PREFIX.len() + 4).collect(),
+            created_at: now,
+            expires_at: (options.ttl_minutes > 0)
+                .then(|| now + Duration::minutes(options.ttl_minutes as i64)),
+            last_used_at: None,
+            models: options
+                .models
+                .iter()
+                .map(|model| model.trim().to_string())
+                .filter(|model| !model.is_empty())
+                .collect(),
+            max_requests: options.max_requests,
+            request_count: 0,
+            attribution: options.attribution,
+            max_output_tokens: options.max_output_tokens,
+            max_stream_tokens: options.max_stream_tokens,
+            max_stream_bytes: options.max_stream_bytes,
+            priority: options.priority,
+            training_consent: options.training_consent,
+            rotation: options.rotation.clone(),
+            successor_id: None,
+            predecessor_id: None,
+            rotated_at: None,
+        };
+
+        let mut records = self.records.write();
+        records.retain(|existing| !existing.is_expired(now));
+        records.push(record.clone());
+        self.persist(&records)?;
+        Ok(IssuedScopedKey { record, api_key })
+    }
+
+    /// 校验 Key 是否为有效的受限 Key，有效时计一次请求
+    ///
+    /// 应在全部授权检查（含模型范围）通过后调用；认证阶段只用 [`Self::is_valid`]。
+    /// 限次 Key 的计数需要跨重启保留，按批写盘，其余由 [`Self::flush`] 写盘。
+    pub fn verify(&self, key: &str) -> bool {
+        if !key.starts_with(SCOPED_KEY_PREFIX) {
+            return false;
+        }
+        let hash = hash_key(key);
+        let now = Utc::now();
+        let mut records = self.records.write();
+        let Some(record) = records.iter_mut().find(|record| {
+            record.key_hash == hash && !record.is_expired(now) && !record.is_exhausted()
+        }) else {
+            return false;
+        };
+        // 仅内存更新最近使用时间，避免每次请求写盘
+        record.last_used_at = Some(now);
+        record.request_count += 1;
+        let counted = record.max_requests.is_some()
+            || record
+                .rotation
+                .as_ref()
+                .is_some_and(|policy| policy.max_requests.is_some());
+        let mut dirty = counted
+            && self.pending_counts.fetch_add(1, Ordering::Relaxed) + 1 >= COUNTER_FLUSH_BATCH;
+        if let Some(successor) = Self::prepare_successor(record, now) {
+            self.notices.lock().push(RotationNotice {
+                key_id: record.id.clone(),
+                label: record.label.clone(),
+                stage: KeyRotationStage::SuccessorPrepared,
+                successor_id: Some(successor.id.clone()),
+                expires_at: record.expires_at,
+            });
+            records.push(successor);
+            dirty = true;
+        }
+        if dirty {
+            if let Err(e) = self.persist(&records) {
+                tracing::warn!("[SCOPED_KEY] 保存请求计数失败: {}", e);
+            }
+        }
+        true
+    }
+
+    /// 写入尚未落盘的请求计数（定时任务与停止服务时调用）
+    pub fn flush(&self) {
+        if self.pending_counts.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let records = self.records.read();
+        if let Err(e) = self.persist(&records) {
+            tracing::warn!("[SCOPED_KEY] 保存请求计数失败: {}", e);
+        }
+    }
+
+    /// 使用进度达到提前量且尚未生成后继 Key 时生成后继 Key。明文随机生成且不保存，
+    /// 由管理员通过 [`Self::claim_successor`] 领取
+    fn prepare_successor(
+        record: &mut ScopedKeyRecord,
+        now: DateTime<Utc>,
+    ) -> Option<ScopedKeyRecord> {
+        let policy = record.rotation.as_ref()?;
+        if record.successor_id.is_some()
+            || policy.progress(record, now) < f64::from(policy.prepare_percent) / 100.0
+        {
+            return None;
+        }
+        let id = uuid::Uuid::new_v4().to_string();
+        let successor_key = generate_key();
+        let successor = ScopedKeyRecord {
+            id: id.clone(),
+            label: record.label.clone(),
+            key_hash: hash_key(&successor_key),
+            key_prefix: successor_key
+                .chars()
+                .take(SCOPED_KEY_PREFIX.len() + 4)
+                .collect(),
+            created_at: now,
+            // 有固定有效期的 Key 按相同时长续期
+            expires_at: record
+                .expires_at
+                .filter(|_| record.rotated_at.is_none())
+                .map(|expires_at| now + (expires_at - record.created_at)),
+            last_used_at: None,
+            models: record.models.clone(),
+            max_requests: record.max_requests,
+            request_count: 0,
+            attribution: record.attribution,
+            max_output_tokens: record.max_output_tokens,
+            max_stream_tokens: record.max_stream_tokens,
+            max_stream_bytes: record.max_stream_bytes,
+            priority: record.priority,
+            training_consent: record.training_consent,
+            rotation: record.rotation.clone(),
+            successor_id: None,
+            predecessor_id: Some(record.id.clone()),
+            rotated_at: None,
+        };
+        record.successor_id = Some(id);
+        tracing::info!(
+            "[SCOPED_KEY] 已为 Key {}（{}）生成后继 Key {}",
+            record.key_prefix,
+            record.label,
+            successor.key_prefix
+        );
+        Some(successor)
+    }
+
+    /// 领取 Key 的后继 Key：重新生成后继 Key 的随机明文并返回（之前领取的明文随之失效）。
+    /// Key 不存在或尚未生成后继 Key 时为 `None`
+    pub fn claim_successor(&self, id: &str) -> Result<Option<IssuedScopedKey>, String> {
+        let mut records = self.records.write();
+        let Some(successor_id) = records
+            .iter()
+            .find(|record| record.id == id)
+            .and_then(|record| record.successor_id.clone())
+        else {
+            return Ok(None);
+        };
+        let Some(successor) = records.iter_mut().find(|record| record.id == successor_id) else {
+            return Ok(None);
+        };
+        let api_key = generate_key();
+        successor.key_hash = hash_key(&api_key);
+        successor.key_prefix = api_key.chars().take(SCOPED_KEY_PREFIX.len() + 4).collect();
+        let record = successor.clone();
+        self.persist(&records)?;
+        Ok(Some(IssuedScopedKey { record, api_key }))
+    }
+
+    /// 立即轮换全部有效的受限 Key：为每个 Key 签发设置相同的随机替换 Key，旧 Key 进入宽限期，
+    /// 宽限期结束后由 [`Self::rotate_due`] 删除。已在宽限期内、已过期或已用尽的 Key 不处理。
+    /// 替换 Key 的明文仅在返回值中出现一次，`predecessor_id` 指向被替换的 Key
+    pub fn rotate_all(
+        &self,
+        grace_hours: u64,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<IssuedScopedKey>, String> {
+        let grace_end = now + Duration::hours(grace_hours as i64);
+        let mut records = self.records.write();
+        let mut issued = Vec::new();
+        for record in records.iter_mut() {
+            if record.rotated_at.is_some() || record.is_expired(now) || record.is_exhausted() {
+                continue;
+            }
+            let api_key = generate_key();
+            let replacement = ScopedKeyRecord {
+                id: uuid::Uuid::new_v4().to_string(),
+                key_hash: hash_key(&api_key),
+                key_prefix: api_key.chars().take(SCOPED_KEY_PREFIX.len() + 4).collect(),
+                created_at: now,
+                expires_at: record
+                    .expires_at
+                    .map(|expires_at| now + (expires_at - record.created_at)),
+                last_used_at: None,
+                request_count: 0,
+                successor_id: None,
+                predecessor_id: Some(record.id.clone()),
+                rotated_at: None,
+                ..record.clone()
+            };
+            record.successor_id = Some(replacement.id.clone());
+            record.rotated_at = Some(now);
+            record.expires_at = Some(record.expires_at.map_or(grace_end, |e| e.min(grace_end)));
+            self.notices.lock().push(RotationNotice {
+                key_id: record.id.clone(),
+                label: record.label.clone(),
+                stage: KeyRotationStage::Rotated,
+                successor_id: record.successor_id.clone(),
+                expires_at: record.expires_at,
+            });
+            issued.push(IssuedScopedKey {
+                record: replacement,
+                api_key,
+            });
+        }
+        records.extend(issued.iter().map(|item| item.record.clone()));
+        self.persist(&records)?;
+        Ok(issued)
+    }
+
+    /// 按轮换策略推进状态：达到上限的 Key 进入宽限期，宽限期结束的 Key 被删除。
+    /// 返回本次及请求处理中产生的全部通知
+    pub fn rotate_due(&self, now: DateTime<Utc>) -> Vec<RotationNotice> {
+        let mut notices = std::mem::take(&mut *self.notices.lock());
+        let mut records = self.records.write();
+        let mut dirty = false;
+        for record in records.iter_mut() {
+            let Some(policy) = record.rotation.as_ref() else {
+                continue;
+            };
+            if record.rotated_at.is_some() || policy.progress(record, now) < 1.0 {
+                continue;
+            }
+            let grace_end = now + Duration::hours(policy.grace_hours as i64);
+            record.rotated_at = Some(now);
+            record.expires_at = Some(record.expires_at.map_or(grace_end, |e| e.min(grace_end)));
+            dirty = true;
+            tracing::info!(
+                "[SCOPED_KEY] Key {}（{}）已达到轮换条件，宽限期至 {}",
+                record.key_prefix,
+                record.label,
+                grace_end
+            );
+            notices.push(RotationNotice {
+                key_id: record.id.clone(),
+                label: record.label.clone(),
+                stage: KeyRotationStage::Rotated,
+                successor_id: record.successor_id.clone(),
+                expires_at: record.expires_at,
+            });
+        }
+        records.retain(|record| {
+            let retired = record.rotated_at.is_some() && record.is_expired(now);
+            if retired {
+                dirty = true;
+                notices.push(RotationNotice {
+                    key_id: record.id.clone(),
+                    label: record.label.clone(),
+                    stage: KeyRotationStage::Retired,
+                    successor_id: record.successor_id.clone(),
+                    expires_at: record.expires_at,
+                });
+            }
+            !retired
+        });
+        if dirty || self.pending_counts.load(Ordering::Relaxed) > 0 {
+            if let Err(e) = self.persist(&records) {
+                tracing::warn!("[SCOPED_KEY] 保存轮换状态失败: {}", e);
+            }
+        }
+        notices
+    }
+
+    /// 只读检查 Key 是否为有效的受限 Key，不计请求次数（用于路由测试等预演场景）
+    pub fn is_valid(&self, key: &str) -> bool {
+        if !key.starts_with(SCOPED_KEY_PREFIX) {
+            return false;
+        }
+        let hash = hash_key(key);
+        let now = Utc::now();
+        self.records.read().iter().any(|record| {
+            record.key_hash == hash && !record.is_expired(now) && !record.is_exhausted()
+        })
+    }
+
+    /// 受限 Key 是否允许调用该模型（非受限 Key 不受限制）
+    pub fn allows_model(&self, key: &str, model: &str) -> bool {
+        if !key.starts_with(SCOPED_KEY_PREFIX) {
+            return true;
+        }
+        let hash = hash_key(key);
+        !self
+            .records
+            .read()
+            .iter()
+            .any(|record| record.key_hash == hash && !record.allows_model(model))
+    }
+
+    /// 受限 Key 单独设置的归属标注方式（非受限 Key 或未设置时为 `None`）
+    pub fn attribution_for(&self, key: &str) -> Option<AttributionMode> {
+        if !key.starts_with(SCOPED_KEY_PREFIX) {
+            return None;
+        }
+        let hash = hash_key(key);
+        self.records
+            .read()
+            .iter()
+            .find(|record| record.key_hash == hash)
+            .and_then(|record| record.attribution)
+    }
+
+    /// 受限 Key 单独设置的最大输出 Token 数（非受限 Key 或未设置时为 `None`）
+    pub fn max_output_tokens_for(&self, key: &str) -> Option<u32> {
+        if !key.starts_with(SCOPED_KEY_PREFIX) {
+            return None;
+        }
+        let hash = hash_key(key);
+        self.records
+            .read()
+            .iter()
+            .find(|record| record.key_hash == hash)
+            .and_then(|record| record.max_output_tokens)
+    }
+
+    /// 受限 Key 单独设置的流式输出上限（非受限 Key 或未设置时各项为 `None`）
+    pub fn stream_limit_for(&self, key: &str) -> StreamOutputLimit {
+        if !key.starts_with(SCOPED_KEY_PREFIX) {
+            return StreamOutputLimit::default();
+        }
+        let hash = hash_key(key);
+        self.records
+            .read()
+            .iter()
+            .find(|record| record.key_hash == hash)
+            .map(|record| StreamOutputLimit {
+                max_tokens: record.max_stream_tokens,
+                max_bytes: record.max_stream_bytes,
+            })
+            .unwrap_or_default()
+    }
+
+    /// 查询受限 Key 的记录（非受限 Key 或不存在时为 `None`）
+    pub fn record_for(&self, key: &str) -> Option<ScopedKeyRecord> {
+        if !key.starts_with(SCOPED_KEY_PREFIX) {
+            return None;
+        }
+        let hash = hash_key(key);
+        self.records
+            .read()
+            .iter()
+            .find(|record| record.key_hash == hash)
+            .cloned()
+    }
+
+    /// 列出全部记录
+    pub fn list(&self) -> Vec<ScopedKeyRecord> {
+        self.records.read().clone()
+    }
+
+    /// 吊销 Key，返回是否存在
+    pub fn revoke(&self, id: &str) -> Result<bool, String> {
+        let mut records = self.records.write();
+        let before = records.len();
+        records.retain(|record| record.id != id);
+        if records.len() == before {
+            return Ok(false);
+        }
+        self.persist(&records)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_verify_issued_keys_until_revoked() {
+        let store = ScopedKeyStore::in_memory();
+        let issued = store.issue("iPhone", 24).expect("签发应成功");
+
+        assert!(issued.api_key.starts_with(SCOPED_KEY_PREFIX));
+        assert_ne!(issued.record.key_hash, issued.api_key);
+        assert!(store.is_valid(&issued.api_key));
+        assert!(store.list()[0].last_used_at.is_none());
+        assert!(store.verify(&issued.api_key));
+        assert!(!store.verify("pc_m_unknown"));
+        assert!(store.list()[0].last_used_at.is_some());
+
+        assert!(store.revoke(&issued.record.id).expect("吊销应成功"));
+        assert!(!store.verify(&issued.api_key));
+    }
+
+    #[test]
+    fn should_reject_expired_keys() {
+        let store = ScopedKeyStore::in_memory();
+        let issued = store.issue("old", 1).expect("签发应成功");
+        store.records.write()[0].expires_at = Some(Utc::now() - Duration::minutes(1));
+
+        assert!(!store.verify(&issued.api_key));
+    }
+
+    #[test]
+    fn should_enforce_models_and_request_cap() {
+        let store = ScopedKeyStore::in_memory();
+        let issued = store
+            .issue_with(&ScopedKeyOptions {
+                label: "demo".to_string(),
+                ttl_minutes: 30,
+                models: vec!["claude-*".to_string(), " gpt-4o ".to_string()],
+                max_requests: Some(2),
+                attribution: None,
+                max_output_tokens: None,
+                max_stream_tokens: None,
+                max_stream_bytes: None,
+                priority: None,
+                training_consent: false,
+                rotation: None,
+            })
+            .expect("签发应成功");
+
+        assert!(store.allows_model(&issued.api_key, "claude-sonnet-4-5"));
+        assert!(store.allows_model(&issued.api_key, "gpt-4o"));
+        assert!(!store.allows_model(&issued.api_key, "gemini-2.5-pro"));
+        assert!(store.allows_model("sk-master", "gemini-2.5-pro"));
+
+        assert!(store.verify(&issued.api_key));
+        assert!(store.verify(&issued.api_key));
+        assert!(!store.verify(&issued.api_key));
+        assert_eq!(store.list()[0].request_count, 2);
+    }
+
+    #[test]
+    fn should_return_per_key_attribution() {
+        let store = ScopedKeyStore::in_memory();
+        let issued = store
+            .issue_with(&ScopedKeyOptions {
+                label: "demo".to_string(),
+                attribution: Some(AttributionMode::Footer),
+                ..Default::default()
+            })
+            .expect("签发应成功");
+        let plain = store.issue("plain", 0).expect("签发应成功");
+
+        assert_eq!(
+            store.attribution_for(&issued.api_key),
+            Some(AttributionMode::Footer)
+        );
+        assert_eq!(store.attribution_for(&plain.api_key), None);
+        assert_eq!(store.attribution_for("sk-master"), None);
+    }
+
+    #[test]
+    fn should_return_per_key_max_output_tokens() {
+        let store = ScopedKeyStore::in_memory();
+        let issued = store
+            .issue_with(&ScopedKeyOptions {
+                label: "capped".to_string(),
+                max_output_tokens: Some(1024),
+                ..Default::default()
+            })
+            .expect("签发应成功");
+
+        assert_eq!(store.max_output_tokens_for(&issued.api_key), Some(1024));
+        assert_eq!(store.max_output_tokens_for("sk-master"), None);
+        assert!(store
+            .issue_with(&ScopedKeyOptions {
+                label: "zero".to_string(),
+                max_output_tokens: Some(0),
+                ..Default::default()
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn should_return_per_key_stream_limit() {
+        let store = ScopedKeyStore::in_memory();
+        let issued = store
+            .issue_with(&ScopedKeyOptions {
+                label: "stream".to_string(),
+                max_stream_bytes: Some(4096),
+                ..Default::default()
+            })
+            .expect("签发应成功");
+
+        let limit = store.stream_limit_for(&issued.api_key);
+        assert_eq!((limit.max_tokens, limit.max_bytes), (None, Some(4096)));
+        assert!(!store.stream_limit_for("sk-master").is_limited());
+        assert!(store
+            .issue_with(&ScopedKeyOptions {
+                label: "zero".to_string(),
+                max_stream_tokens: Some(0),
+                ..Default::default()
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn should_prepare_successor_and_retire_after_grace() {
+        let store = ScopedKeyStore::in_memory();
+        let issued = store
+            .issue_with(&ScopedKeyOptions {
+                label: "ci".to_string(),
+                rotation: Some(KeyRotationPolicy {
+                    max_age_hours: None,
+                    max_requests: Some(4),
+                    prepare_percent: 50,
+                    grace_hours: 1,
+                }),
+                ..Default::default()
+            })
+            .expect("签发应成功");
+        let key = &issued.api_key;
+
+        assert!(store.verify(key));
+        assert!(store.claim_successor(&issued.record.id).unwrap().is_none());
+        assert!(store.verify(key));
+        let first = store
+            .claim_successor(&issued.record.id)
+            .unwrap()
+            .expect("应已生成后继 Key");
+        assert_eq!(
+            first.record.predecessor_id.as_deref(),
+            Some(issued.record.id.as_str())
+        );
+        // 再次领取会重新生成明文，之前的明文失效
+        let successor = store
+            .claim_successor(&issued.record.id)
+            .unwrap()
+            .expect("应已生成后继 Key");
+        assert_eq!(successor.record.id, first.record.id);
+        assert!(!store.verify(&first.api_key));
+        assert!(store.verify(&successor.api_key));
+        assert!(store.claim_successor("unknown").unwrap().is_none());
+
+        // 未达到上限前只有生成通知
+        let now = Utc::now();
+        let notices = store.rotate_due(now);
+        assert_eq!(notices.len(), 1);
+        assert_eq!(notices[0].stage, KeyRotationStage::SuccessorPrepared);
+
+        assert!(store.verify(key));
+        assert!(store.verify(key));
+        let notices = store.rotate_due(now);
+        assert_eq!(notices[0].stage, KeyRotationStage::Rotated);
+        // 宽限期内新旧 Key 都有效
+        assert!(store.verify(key));
+        assert!(store.rotate_due(now).is_empty());
+
+        let notices = store.rotate_due(now + Duration::hours(2));
+        assert_eq!(notices.len(), 1);
+        assert_eq!(notices[0].stage, KeyRotationStage::Retired);
+        assert!(!store.verify(key));
+        assert!(store.verify(&successor.api_key));
+        assert_eq!(store.list().len(), 1);
+    }
+
+    #[test]
+    fn should_rotate_all_keys_with_grace_period() {
+        let store = ScopedKeyStore::in_memory();
+        let first = store.issue("ci", 0).expect("签发应成功");
+        let second = store.issue("demo", 48).expect("签发应成功");
+        let now = Utc::now();
+
+        let issued = store.rotate_all(1, now).expect("轮换应成功");
+        assert_eq!(issued.len(), 2);
+        assert_eq!(
+            issued[0].record.predecessor_id.as_deref(),
+            Some(first.record.id.as_str())
+        );
+        assert_eq!(issued[1].record.label, "demo");
+        assert!(issued[1].record.expires_at.is_some());
+        // 宽限期内新旧 Key 都有效
+        assert!(store.verify(&first.api_key));
+        assert!(store.verify(&issued[0].api_key));
+        assert!(store.verify(&second.api_key));
+        assert_eq!(store.list().len(), 4);
+
+        let notices = store.rotate_due(now + Duration::hours(2));
+        assert!(notices
+            .iter()
+            .any(|notice| notice.stage == KeyRotationStage::Retired));
+        assert!(!store.verify(&first.api_key));
+        assert!(store.verify(&issued[1].api_key));
+    }
+
+    #[test]
+    fn should_persist_only_hashes() {
+        let dir = tempfile::tempdir().expect("创建临时目录失败");
+        let path = dir.path().join(SCOPED_KEYS_FILE);
+        let issued = ScopedKeyStore::load(path.clone())
+            .issue("tablet", 0)
+            .expect("签发应成功");
+
+        let content = std::fs::read_to_string(&path).expect("读取失败");
+        assert!(!content.contains(&issued.api_key));
+        assert!(ScopedKeyStore::load(path).verify(&issued.api_key));
+    }
+
+    #[test]
+    fn should_batch_request_count_writes() {
+        let dir = tempfile::tempdir().expect("创建临时目录失败");
+        let path = dir.path().join(SCOPED_KEYS_FILE);
+        let store = ScopedKeyStore::load(path.clone());
+        let issued = store
+            .issue_with(&ScopedKeyOptions {
+                label: "capped".to_string(),
+                max_requests: Some(100),
+                ..Default::default()
+            })
+            .expect("签发应成功");
+        let stored_count = || ScopedKeyStore::load(path.clone()).list()[0].request_count;
+
+        assert!(store.verify(&issued.api_key));
+        assert_eq!(stored_count(), 0);
+        store.flush();
+        assert_eq!(stored_count(), 1);
+
+        for _ in 0..COUNTER_FLUSH_BATCH {
+            assert!(store.verify(&issued.api_key));
+        }
+        assert_eq!(stored_count(), COUNTER_FLUSH_BATCH + 1);
+    }
+}
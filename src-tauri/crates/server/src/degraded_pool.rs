@@ -0,0 +1,270 @@
+//! 数据库不可用时的内存降级凭证池
+//!
+//! 数据库未初始化或读写失败时，不再直接返回 "Database not available"，而是改用启动时
+//! 从 YAML `credential_pool` 加载的凭证继续提供服务。健康状态与用量只记录在内存中，
+//! 数据库恢复后自动退出降级模式。进入与退出时发布 `ServerEvent::DegradedMode` 提示前端。
+//!
+//! Kiro OAuth 凭证依赖数据库中的 Token 缓存，降级模式下不参与选择。
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use chrono::{DateTime, Utc};
+use lime_core::app_events::{publish_app_event, AppEvent, ServerEvent};
+use lime_core::config::{Config, ConfigManager};
+use lime_core::models::client_type::ClientType;
+use lime_core::models::provider_pool_model::{
+    CredentialData, PoolProviderType, ProviderCredential,
+};
+use lime_credential::CredentialSyncService;
+use lime_services::provider_pool_service::ProviderCredentialClientCompat;
+use parking_lot::RwLock;
+use serde::Serialize;
+
+/// 降级模式状态
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct DegradedStatus {
+    pub active: bool,
+    /// 进入降级模式的原因（数据库错误）
+    pub reason: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    /// 内存池中可用于降级的凭证数
+    pub credentials: usize,
+}
+
+/// 内存降级凭证池
+#[derive(Default)]
+pub struct DegradedPool {
+    credentials: RwLock<Vec<ProviderCredential>>,
+    active: AtomicBool,
+    status: RwLock<DegradedStatus>,
+    cursor: AtomicUsize,
+}
+
+/// 从 YAML 配置加载可在无数据库时使用的凭证
+pub fn credentials_from_config(config: &Config) -> Vec<ProviderCredential> {
+    let manager = ConfigManager::with_config(config.clone(), ConfigManager::default_config_path());
+    let sync_service =
+        CredentialSyncService::new(std::sync::Arc::new(std::sync::RwLock::new(manager)));
+    match sync_service.load_from_config() {
+        Ok(credentials) => credentials
+            .into_iter()
+            .filter(|c| !matches!(c.credential, CredentialData::KiroOAuth { .. }))
+            .collect(),
+        Err(e) => {
+            tracing::warn!("[DEGRADED] 从配置加载降级凭证失败: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// 凭证是否属于请求的 Provider（AI Provider 与 Assistant 共享凭证，与数据库池一致）
+fn provider_matches(credential: &PoolProviderType, requested: &PoolProviderType) -> bool {
+    credential == requested
+        || matches!(
+            (credential, requested),
+            (PoolProviderType::Anthropic, PoolProviderType::Claude)
+                | (PoolProviderType::Claude, PoolProviderType::Anthropic)
+        )
+}
+
+impl DegradedPool {
+    pub fn new(credentials: Vec<ProviderCredential>) -> Self {
+        Self {
+            credentials: RwLock::new(credentials),
+            ..Default::default()
+        }
+    }
+
+    /// 配置热重载后替换凭证，保留同一凭证在内存中的健康状态与用量
+    pub fn reload(&self, credentials: Vec<ProviderCredential>) {
+        let mut current = self.credentials.write();
+        let merged = credentials
+            .into_iter()
+            .map(|mut credential| {
+                if let Some(previous) = current.iter().find(|c| c.uuid == credential.uuid) {
+                    credential.is_healthy = previous.is_healthy;
+                    credential.error_count = previous.error_count;
+                    credential.usage_count = previous.usage_count;
+                    credential.last_used = previous.last_used;
+                    credential.last_error_message = previous.last_error_message.clone();
+                }
+                credential
+            })
+            .collect();
+        *current = merged;
+        self.status.write().credentials = current.len();
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    pub fn status(&self) -> DegradedStatus {
+        let mut status = self.status.read().clone();
+        status.active = self.is_active();
+        status.credentials = self.credentials.read().len();
+        status
+    }
+
+    /// 数据库出错时进入降级模式（已处于降级模式时只更新原因）
+    pub fn enter(&self, reason: &str) {
+        let credentials = self.credentials.read().len();
+        {
+            let mut status = self.status.write();
+            status.reason = Some(reason.to_string());
+            status.credentials = credentials;
+        }
+        if self.active.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        self.status.write().since = Some(Utc::now());
+        tracing::warn!(
+            "[DEGRADED] 数据库不可用（{}），使用配置中的 {} 个凭证继续服务",
+            reason,
+            credentials
+        );
+        publish_app_event(AppEvent::Server(ServerEvent::DegradedMode {
+            active: true,
+            credentials,
+            reason: Some(reason.to_string()),
+        }));
+    }
+
+    /// 数据库访问恢复后退出降级模式
+    pub fn leave(&self) {
+        if !self.active.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        let credentials = {
+            let mut status = self.status.write();
+            status.reason = None;
+            status.since = None;
+            status.credentials
+        };
+        tracing::info!("[DEGRADED] 数据库已恢复，退出降级模式");
+        publish_app_event(AppEvent::Server(ServerEvent::DegradedMode {
+            active: false,
+            credentials,
+            reason: None,
+        }));
+    }
+
+    /// 按 Provider、模型与客户端类型轮询选择可用凭证
+    pub fn select(
+        &self,
+        provider_type: &str,
+        model: Option<&str>,
+        client_type: Option<&ClientType>,
+    ) -> Option<ProviderCredential> {
+        let requested: PoolProviderType = provider_type.parse().ok()?;
+        let credentials = self.credentials.read();
+        let available: Vec<&ProviderCredential> = credentials
+            .iter()
+            .filter(|c| provider_matches(&c.provider_type, &requested))
+            .filter(|c| c.is_available())
+            .filter(|c| model.map_or(true, |m| c.supports_model(m)))
+            .filter(|c| c.is_compatible_with_client(client_type))
+            .collect();
+        if available.is_empty() {
+            return None;
+        }
+        let index = self.cursor.fetch_add(1, Ordering::Relaxed) % available.len();
+        Some(available[index].clone())
+    }
+
+    /// 凭证是否由内存池提供
+    pub fn contains(&self, uuid: &str) -> bool {
+        self.credentials.read().iter().any(|c| c.uuid == uuid)
+    }
+
+    fn update(&self, uuid: &str, apply: impl FnOnce(&mut ProviderCredential)) -> bool {
+        let mut credentials = self.credentials.write();
+        match credentials.iter_mut().find(|c| c.uuid == uuid) {
+            Some(credential) => {
+                apply(credential);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn mark_healthy(&self, uuid: &str, check_model: Option<&str>) -> bool {
+        self.update(uuid, |c| c.mark_healthy(check_model.map(str::to_string)))
+    }
+
+    pub fn mark_unhealthy(&self, uuid: &str, error_message: Option<&str>) -> bool {
+        self.update(uuid, |c| {
+            c.mark_unhealthy(error_message.map(str::to_string))
+        })
+    }
+
+    pub fn record_usage(&self, uuid: &str) -> bool {
+        self.update(uuid, ProviderCredential::record_usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(provider_type: PoolProviderType, uuid: &str) -> ProviderCredential {
+        let mut credential = ProviderCredential::new(
+            provider_type,
+            CredentialData::OpenAIKey {
+                api_key: "sk-test".to_string(),
+                base_url: None,
+            },
+        );
+        credential.uuid = uuid.to_string();
+        credential
+    }
+
+    #[test]
+    fn test_select_rotates_and_skips_unhealthy() {
+        let pool = DegradedPool::new(vec![
+            key(PoolProviderType::OpenAI, "a"),
+            key(PoolProviderType::OpenAI, "b"),
+            key(PoolProviderType::Claude, "c"),
+        ]);
+        let first = pool.select("openai", None, None).unwrap().uuid;
+        let second = pool.select("openai", None, None).unwrap().uuid;
+        assert_ne!(first, second);
+        assert_eq!(
+            pool.select("anthropic", None, None).unwrap().uuid,
+            "c".to_string()
+        );
+        assert!(pool.select("not-a-provider", None, None).is_none());
+
+        for _ in 0..3 {
+            assert!(pool.mark_unhealthy("a", Some("boom")));
+        }
+        for _ in 0..4 {
+            assert_eq!(pool.select("openai", None, None).unwrap().uuid, "b");
+        }
+        assert!(!pool.record_usage("missing"));
+    }
+
+    #[test]
+    fn test_enter_and_leave_track_status() {
+        let pool = DegradedPool::new(vec![key(PoolProviderType::OpenAI, "a")]);
+        assert!(!pool.is_active());
+
+        pool.enter("database is locked");
+        pool.enter("disk I/O error");
+        let status = pool.status();
+        assert!(status.active);
+        assert_eq!(status.reason.as_deref(), Some("disk I/O error"));
+        assert_eq!(status.credentials, 1);
+        assert!(status.since.is_some());
+
+        pool.leave();
+        assert!(!pool.is_active());
+        assert_eq!(
+            pool.status(),
+            DegradedStatus {
+                credentials: 1,
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! 处理器依赖注入
+//!
+//! 处理器通过 trait 对象访问凭证池、日志、时钟和 Provider 注册表。
+//! 生产环境由 `AppState` 提供基于数据库与 `LogStore` 的实现，
+//! 单元测试可以换成内存实现，无需启动完整服务即可覆盖处理器逻辑。
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use lime_core::database::DbConnection;
+use lime_core::logger::LogStore;
+use lime_core::models::provider_pool_model::ProviderCredential;
+use lime_providers::providers::antigravity::TokenRefreshError;
+use lime_providers::providers::ProviderRegistry;
+use lime_services::provider_pool_service::ProviderPoolService;
+use tokio::sync::RwLock;
+
+use crate::degraded_pool::DegradedPool;
+
+/// 凭证池操作
+pub trait CredentialPool: Send + Sync {
+    fn select_credential(
+        &self,
+        provider_type: &str,
+        model: Option<&str>,
+    ) -> Result<Option<ProviderCredential>, String>;
+
+    fn mark_healthy(&self, uuid: &str, check_model: Option<&str>) -> Result<(), String>;
+
+    fn mark_unhealthy(&self, uuid: &str, error_message: Option<&str>) -> Result<(), String>;
+
+    fn mark_unhealthy_with_details(
+        &self,
+        uuid: &str,
+        error: &TokenRefreshError,
+    ) -> Result<(), String>;
+
+    fn record_usage(&self, uuid: &str) -> Result<(), String>;
+}
+
+/// 基于数据库的凭证池
+///
+/// 数据库不可用或读写失败时改用内存降级池（见 [`DegradedPool`]）。
+pub struct DbCredentialPool {
+    service: Arc<ProviderPoolService>,
+    db: Option<DbConnection>,
+    fallback: Arc<DegradedPool>,
+}
+
+impl DbCredentialPool {
+    pub fn new(
+        service: Arc<ProviderPoolService>,
+        db: Option<DbConnection>,
+        fallback: Arc<DegradedPool>,
+    ) -> Self {
+        Self {
+            service,
+            db,
+            fallback,
+        }
+    }
+
+    fn db(&self) -> Result<&DbConnection, String> {
+        self.db
+            .as_ref()
+            .ok_or_else(|| "Database not available".to_string())
+    }
+
+    /// 降级模式下由内存池提供的凭证只更新内存状态
+    fn is_fallback_credential(&self, uuid: &str) -> bool {
+        self.fallback.is_active() && self.fallback.contains(uuid)
+    }
+}
+
+impl CredentialPool for DbCredentialPool {
+    fn select_credential(
+        &self,
+        provider_type: &str,
+        model: Option<&str>,
+    ) -> Result<Option<ProviderCredential>, String> {
+        let selected = self
+            .db()
+            .and_then(|db| self.service.select_credential(db, provider_type, model));
+        match selected {
+            Ok(credential) => {
+                self.fallback.leave();
+                Ok(credential)
+            }
+            Err(e) => {
+                self.fallback.enter(&e);
+                Ok(self.fallback.select(provider_type, model, None))
+            }
+        }
+    }
+
+    fn mark_healthy(&self, uuid: &str, check_model: Option<&str>) -> Result<(), String> {
+        if self.is_fallback_credential(uuid) {
+            self.fallback.mark_healthy(uuid, check_model);
+            return Ok(());
+        }
+        self.service.mark_healthy(self.db()?, uuid, check_model)
+    }
+
+    fn mark_unhealthy(&self, uuid: &str, error_message: Option<&str>) -> Result<(), String> {
+        if self.is_fallback_credential(uuid) {
+            self.fallback.mark_unhealthy(uuid, error_message);
+            return Ok(());
+        }
+        self.service.mark_unhealthy(self.db()?, uuid, error_message)
+    }
+
+    fn mark_unhealthy_with_details(
+        &self,
+        uuid: &str,
+        error: &TokenRefreshError,
+    ) -> Result<(), String> {
+        if self.is_fallback_credential(uuid) {
+            self.fallback.mark_unhealthy(uuid, Some(&error.to_string()));
+            return Ok(());
+        }
+        self.service
+            .mark_unhealthy_with_details(self.db()?, uuid, error)
+    }
+
+    fn record_usage(&self, uuid: &str) -> Result<(), String> {
+        if self.is_fallback_credential(uuid) {
+            self.fallback.record_usage(uuid);
+            return Ok(());
+        }
+        self.service.record_usage(self.db()?, uuid)
+    }
+}
+
+/// 日志输出
+#[async_trait]
+pub trait LogSink: Send + Sync {
+    async fn add(&self, level: &str, message: &str);
+}
+
+#[async_trait]
+impl LogSink for RwLock<LogStore> {
+    async fn add(&self, level: &str, message: &str) {
+        self.write().await.add(level, message);
+    }
+}
+
+/// 时间来源
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// 系统时钟
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// 处理器依赖集合
+#[derive(Clone)]
+pub struct HandlerDeps {
+    pub pool: Arc<dyn CredentialPool>,
+    pub logs: Arc<dyn LogSink>,
+    pub clock: Arc<dyn Clock>,
+    pub providers: Arc<ProviderRegistry>,
+}
@@ -0,0 +1,267 @@
+//! 对话记录导出为微调数据集
+//!
+//! 把 `proxy_transcripts` 中保存的请求与响应还原为一段对话（系统提示词、用户与助手轮次），
+//! 再按目标格式输出 JSONL，每行一段对话：
+//! - `openai`：`{"messages": [{"role": "system" | "user" | "assistant", "content": "..."}]}`
+//! - `gemini`：`{"systemInstruction": {...}, "contents": [{"role": "user" | "model", "parts": [{"text": "..."}]}]}`
+//!
+//! 只保留文本内容；图片、工具调用与工具结果不导出。没有用户轮次或助手回复为空的记录会被跳过。
+
+use lime_core::database::dao::proxy_transcript::ProxyTranscript;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FineTuneFormat {
+    #[default]
+    Openai,
+    Gemini,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    User,
+    Assistant,
+}
+
+/// 还原出的对话
+#[derive(Debug, Clone, PartialEq)]
+struct Conversation {
+    system: Option<String>,
+    turns: Vec<(Role, String)>,
+}
+
+/// 导出结果统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ExportSummary {
+    pub exported: usize,
+    pub skipped: usize,
+}
+
+/// 提取内容中的文本：字符串，或 `[{type: "text", text}]` 块数组
+fn content_text(content: &Value) -> Option<String> {
+    let text = match content {
+        Value::String(s) => s.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter(|block| block["type"].as_str().map_or(true, |t| t == "text"))
+            .filter_map(|block| block["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => return None,
+    };
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// OpenAI Chat Completions 请求与响应
+fn openai_conversation(request: &Value, response: &Value) -> Option<Conversation> {
+    let mut system = Vec::new();
+    let mut turns = Vec::new();
+    for message in request["messages"].as_array()? {
+        let Some(text) = content_text(&message["content"]) else {
+            continue;
+        };
+        match message["role"].as_str() {
+            Some("system") | Some("developer") => system.push(text),
+            Some("user") => turns.push((Role::User, text)),
+            Some("assistant") => turns.push((Role::Assistant, text)),
+            _ => {}
+        }
+    }
+    let reply = content_text(&response["choices"][0]["message"]["content"])?;
+    turns.push((Role::Assistant, reply));
+    Some(Conversation {
+        system: (!system.is_empty()).then(|| system.join("\n\n")),
+        turns,
+    })
+}
+
+/// Anthropic Messages 请求与响应
+fn anthropic_conversation(request: &Value, response: &Value) -> Option<Conversation> {
+    let mut turns = Vec::new();
+    for message in request["messages"].as_array()? {
+        let Some(text) = content_text(&message["content"]) else {
+            continue;
+        };
+        match message["role"].as_str() {
+            Some("user") => turns.push((Role::User, text)),
+            Some("assistant") => turns.push((Role::Assistant, text)),
+            _ => {}
+        }
+    }
+    let reply = content_text(&response["content"])?;
+    turns.push((Role::Assistant, reply));
+    Some(Conversation {
+        system: content_text(&request["system"]),
+        turns,
+    })
+}
+
+fn conversation(transcript: &ProxyTranscript) -> Option<Conversation> {
+    let conversation = if transcript.path.ends_with("/messages") {
+        anthropic_conversation(&transcript.request, &transcript.response)
+    } else {
+        openai_conversation(&transcript.request, &transcript.response)
+    }?;
+    conversation
+        .turns
+        .iter()
+        .any(|(role, _)| *role == Role::User)
+        .then_some(conversation)
+}
+
+/// 合并相邻的同角色轮次（Gemini 要求用户与模型轮次交替）
+fn merge_adjacent(turns: &[(Role, String)]) -> Vec<(Role, String)> {
+    let mut merged: Vec<(Role, String)> = Vec::new();
+    for (role, text) in turns {
+        match merged.last_mut() {
+            Some((last, previous)) if last == role => {
+                previous.push_str("\n\n");
+                previous.push_str(text);
+            }
+            _ => merged.push((*role, text.clone())),
+        }
+    }
+    merged
+}
+
+fn to_line(conversation: &Conversation, format: FineTuneFormat) -> Value {
+    match format {
+        FineTuneFormat::Openai => {
+            let mut messages = Vec::new();
+            if let Some(system) = &conversation.system {
+                messages.push(json!({ "role": "system", "content": system }));
+            }
+            for (role, text) in &conversation.turns {
+                let role = match role {
+                    Role::User => "user",
+                    Role::Assistant => "assistant",
+                };
+                messages.push(json!({ "role": role, "content": text }));
+            }
+            json!({ "messages": messages })
+        }
+        FineTuneFormat::Gemini => {
+            let turns = merge_adjacent(&conversation.turns);
+            // Gemini 的对话必须以用户轮次开始
+            let start = turns
+                .iter()
+                .position(|(role, _)| *role == Role::User)
+                .unwrap_or(0);
+            let contents: Vec<Value> = turns[start..]
+                .iter()
+                .map(|(role, text)| {
+                    let role = match role {
+                        Role::User => "user",
+                        Role::Assistant => "model",
+                    };
+                    json!({ "role": role, "parts": [{ "text": text }] })
+                })
+                .collect();
+            let mut line = json!({ "contents": contents });
+            if let Some(system) = &conversation.system {
+                line["systemInstruction"] =
+                    json!({ "role": "system", "parts": [{ "text": system }] });
+            }
+            line
+        }
+    }
+}
+
+/// 导出为 JSONL（每行末尾带换行）
+pub fn export_jsonl(
+    transcripts: &[ProxyTranscript],
+    format: FineTuneFormat,
+) -> (String, ExportSummary) {
+    let mut output = String::new();
+    let mut summary = ExportSummary::default();
+    for transcript in transcripts {
+        match conversation(transcript) {
+            Some(conversation) => {
+                output.push_str(&to_line(&conversation, format).to_string());
+                output.push('\n');
+                summary.exported += 1;
+            }
+            None => summary.skipped += 1,
+        }
+    }
+    (output, summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn transcript(path: &str, request: Value, response: Value) -> ProxyTranscript {
+        ProxyTranscript {
+            id: 1,
+            created_at: Utc::now(),
+            path: path.to_string(),
+            model: "test".to_string(),
+            key_id: "primary".to_string(),
+            consent: true,
+            request,
+            response,
+        }
+    }
+
+    #[test]
+    fn test_export_openai_and_gemini_formats() {
+        let chat = transcript(
+            "/v1/chat/completions",
+            json!({
+                "messages": [
+                    { "role": "system", "content": "Be brief." },
+                    { "role": "user", "content": [
+                        { "type": "text", "text": "Hi" },
+                        { "type": "image_url", "image_url": { "url": "data:..." } }
+                    ] },
+                    { "role": "user", "content": "there" }
+                ]
+            }),
+            json!({ "choices": [{ "message": { "role": "assistant", "content": "Hello!" } }] }),
+        );
+        let messages = transcript(
+            "/v1/messages",
+            json!({
+                "system": [{ "type": "text", "text": "Sys" }],
+                "messages": [{ "role": "user", "content": "Q" }]
+            }),
+            json!({ "content": [{ "type": "text", "text": "A" }] }),
+        );
+        let empty_reply = transcript(
+            "/v1/chat/completions",
+            json!({ "messages": [{ "role": "user", "content": "Q" }] }),
+            json!({ "choices": [{ "message": { "tool_calls": [] } }] }),
+        );
+        let all = [chat, messages, empty_reply];
+
+        let (jsonl, summary) = export_jsonl(&all, FineTuneFormat::Openai);
+        assert_eq!(
+            summary,
+            ExportSummary {
+                exported: 2,
+                skipped: 1
+            }
+        );
+        let lines: Vec<Value> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines[0]["messages"][0]["role"], "system");
+        assert_eq!(lines[0]["messages"][1]["content"], "Hi");
+        assert_eq!(lines[0]["messages"][3]["content"], "Hello!");
+        assert_eq!(lines[1]["messages"][0]["content"], "Sys");
+
+        let (jsonl, _) = export_jsonl(&all[..1], FineTuneFormat::Gemini);
+        let line: Value = serde_json::from_str(jsonl.trim()).unwrap();
+        assert_eq!(line["systemInstruction"]["parts"][0]["text"], "Be brief.");
+        assert_eq!(line["contents"][0]["parts"][0]["text"], "Hi\n\nthere");
+        assert_eq!(line["contents"][1]["role"], "model");
+        assert_eq!(line["contents"].as_array().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,240 @@
+//! 客户端中断处理
+//!
+//! 流式响应的 Body 被 hyper 丢弃（客户端断开）时，上游字节流随之被丢弃，连接立即关闭，
+//! 不再继续消耗上游 Token。这里负责中断后的收尾：
+//! - 按已转发的增量估算输出 Token 并计入用量
+//! - 注销在途请求
+//! - 累计中断请求数（`ServerStatus.aborted_requests`）
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use axum::{body::Body, http::header, response::Response};
+use futures::StreamExt;
+use lime_processor::RequestContext;
+use serde_json::Value;
+
+use crate::{record_token_usage, AppState};
+
+/// 在途请求统计
+#[derive(Debug, Default)]
+pub struct InflightTracker {
+    active: AtomicU64,
+    aborted: AtomicU64,
+}
+
+impl InflightTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记一个在途请求，返回的 [`InflightSlot`] 释放时注销
+    pub fn acquire(self: &Arc<Self>) -> InflightSlot {
+        self.active.fetch_add(1, Ordering::Relaxed);
+        InflightSlot {
+            tracker: self.clone(),
+        }
+    }
+
+    fn release(&self) {
+        let _ = self
+            .active
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| {
+                Some(v.saturating_sub(1))
+            });
+    }
+
+    /// 全部在途请求数
+    pub fn active(&self) -> u64 {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// 累计被客户端中断的请求数
+    pub fn aborted(&self) -> u64 {
+        self.aborted.load(Ordering::Relaxed)
+    }
+}
+
+/// 在途请求登记，drop 时注销
+#[derive(Debug)]
+pub struct InflightSlot {
+    tracker: Arc<InflightTracker>,
+}
+
+impl Drop for InflightSlot {
+    fn drop(&mut self) {
+        self.tracker.release();
+    }
+}
+
+/// 后台任务守卫，drop 时取消任务（用于先在后台收集上游数据、再返回给客户端的流）
+pub struct AbortOnDrop(pub tokio::task::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// 从 SSE 事件中统计已输出的字符数（OpenAI / Anthropic 两种格式）
+#[derive(Debug, Default)]
+struct OutputCounter {
+    pending: Vec<u8>,
+    chars: usize,
+    reported_tokens: Option<u32>,
+}
+
+impl OutputCounter {
+    fn feed(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+        while let Some(pos) = self.pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let Some(data) = line.trim_end().strip_prefix("data:") else {
+                continue;
+            };
+            if let Ok(event) = serde_json::from_str::<Value>(data.trim_start()) {
+                self.observe(&event);
+            }
+        }
+    }
+
+    fn observe(&mut self, event: &Value) {
+        let text_len = |value: &Value| value.as_str().map_or(0, |s| s.chars().count());
+        if let Some(choices) = event["choices"].as_array() {
+            for choice in choices {
+                let delta = &choice["delta"];
+                self.chars += text_len(&delta["content"]) + text_len(&delta["reasoning_content"]);
+                if let Some(calls) = delta["tool_calls"].as_array() {
+                    self.chars += calls
+                        .iter()
+                        .map(|call| text_len(&call["function"]["arguments"]))
+                        .sum::<usize>();
+                }
+            }
+        }
+        let delta = &event["delta"];
+        self.chars += text_len(&delta["text"])
+            + text_len(&delta["thinking"])
+            + text_len(&delta["partial_json"]);
+
+        let usage = event.get("usage").and_then(|u| {
+            u["completion_tokens"]
+                .as_u64()
+                .or(u["output_tokens"].as_u64())
+        });
+        if let Some(tokens) = usage {
+            self.reported_tokens = Some(tokens as u32);
+        }
+    }
+
+    /// 已输出的 Token：优先使用上游报告的值，否则按 4 字符 ≈ 1 Token 估算
+    fn output_tokens(&self) -> u32 {
+        self.reported_tokens
+            .unwrap_or_else(|| self.chars.div_ceil(4) as u32)
+    }
+}
+
+/// 流式响应的中断观察器，未读到流结束就被 drop 即视为客户端中断
+struct AbortWatch {
+    state: AppState,
+    ctx: RequestContext,
+    counter: OutputCounter,
+    finished: bool,
+    _slot: InflightSlot,
+}
+
+impl Drop for AbortWatch {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.state.inflight.aborted.fetch_add(1, Ordering::Relaxed);
+        let output_tokens = self.counter.output_tokens();
+        tracing::info!(
+            "[ABORT] 客户端断开，已取消上游请求: request_id={} credential={:?} partial_output_tokens={}",
+            self.ctx.request_id,
+            self.ctx.credential_id,
+            output_tokens
+        );
+        record_token_usage(&self.state, &self.ctx, None, Some(output_tokens));
+    }
+}
+
+/// 为流式响应挂上中断处理，并在响应结束（或中断）时注销在途请求
+///
+/// 非 SSE 响应直接返回，在途登记随 `slot` 一起注销。
+pub fn track_stream(
+    state: &AppState,
+    ctx: &RequestContext,
+    response: Response,
+    slot: InflightSlot,
+) -> Response {
+    let is_event_stream = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    if !is_event_stream {
+        return response;
+    }
+
+    let mut watch = AbortWatch {
+        state: state.clone(),
+        ctx: ctx.clone(),
+        counter: OutputCounter::default(),
+        finished: false,
+        _slot: slot,
+    };
+    let (parts, body) = response.into_parts();
+    let mut upstream = body.into_data_stream();
+    let stream = async_stream::stream! {
+        while let Some(chunk) = upstream.next().await {
+            match chunk {
+                Ok(bytes) => {
+                    watch.counter.feed(&bytes);
+                    yield Ok(bytes);
+                }
+                Err(e) => {
+                    watch.finished = true;
+                    yield Err(e);
+                    return;
+                }
+            }
+        }
+        watch.finished = true;
+    };
+    Response::from_parts(parts, Body::from_stream(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slot_released_on_drop() {
+        let tracker = Arc::new(InflightTracker::new());
+        let first = tracker.acquire();
+        let second = tracker.acquire();
+        assert_eq!(tracker.active(), 2);
+
+        drop(first);
+        assert_eq!(tracker.active(), 1);
+        drop(second);
+        assert_eq!(tracker.active(), 0);
+    }
+
+    #[test]
+    fn test_output_counter_handles_split_events() {
+        let mut counter = OutputCounter::default();
+        counter.feed(b"data: {\"choices\":[{\"delta\":{\"content\":\"hello \"}}]}\n\ndata: {\"cho");
+        counter.feed(b"ices\":[{\"delta\":{\"content\":\"world!!\"}}]}\n\n");
+        counter.feed(b"event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"abc\"}}\n\n");
+
+        assert_eq!(counter.chars, 16);
+        assert_eq!(counter.output_tokens(), 4);
+
+        counter.feed(b"data: {\"choices\":[],\"usage\":{\"completion_tokens\":9}}\n\n");
+        assert_eq!(counter.output_tokens(), 9);
+    }
+}
@@ -28,28 +28,51 @@ use std::{
     sync::Arc,
 };
 
+use crate::auth::lockout::{note_auth_failure, note_auth_success};
+use crate::auth::oidc::AdminIdentity;
 use crate::client_detector::ClientType;
+use crate::middleware::logprobs;
+use crate::middleware::prompt_firewall;
 use crate::middleware::request_dedup::{
     build_request_fingerprint, RequestDedupCheck, RequestDedupStore,
 };
+use crate::middleware::request_signing::{is_forwarded_request, is_signature_verified};
 use crate::middleware::response_cache::{CachedHttpResponse, ResponseCacheStore};
-use crate::{record_request_telemetry, record_token_usage, AppState};
+use crate::middleware::seed;
+use crate::{
+    record_request_telemetry, record_token_usage, record_usage, AppState, MAX_TOKENS_CLAMP_METADATA,
+};
 use aster::context::MODEL_CONTEXT_WINDOWS;
+use lime_core::config::AttributionMode;
 use lime_core::errors::GatewayErrorCode;
+use lime_core::i18n::{self, MessageCode};
+use lime_core::logger::LogStore;
 use lime_core::models::anthropic::AnthropicMessagesRequest;
 use lime_core::models::openai::{ChatCompletionRequest, ContentPart, MessageContent};
+use lime_core::models::seed::system_fingerprint;
+use lime_core::models::{LogprobsOptions, SeedOptions};
+use lime_core::router::ModelDeprecation;
 use lime_core::ProviderType;
+use lime_infra::telemetry::MaxTokensClamp;
 use lime_processor::RequestContext;
 use lime_providers::converter::anthropic_to_openai::convert_anthropic_to_openai;
 use lime_providers::streaming::StreamFormat as StreamingFormat;
 use lime_server_utils::{
     build_anthropic_response, build_anthropic_stream_response, build_error_response_with_meta,
-    build_gateway_error_json, message_content_len, parse_cw_response, safe_truncate,
+    build_gateway_error_json, parse_cw_response, safe_truncate,
 };
 
+use super::abort;
+use super::attribution;
+use super::content_policy;
+use super::fake_stream::{self, SseFlavor};
+use super::model_downgrade;
+use super::routing_pin;
+use super::stream_transform;
+use super::usage;
 use super::{call_provider_anthropic, call_provider_openai};
 
-async fn select_credential_for_request(
+pub(super) async fn select_credential_for_request(
     state: &AppState,
     request_id: Option<&str>,
     selected_provider: &str,
@@ -62,23 +85,33 @@ async fn select_credential_for_request(
     let db = match &state.db {
         Some(db) => db,
         None => {
-            eprintln!("[{log_prefix}] 数据库未初始化!");
-            return Ok(None);
+            eprintln!("[{log_prefix}] 数据库未初始化，使用降级凭证池");
+            return Ok(select_degraded_credential(
+                state,
+                "数据库未初始化",
+                explicit_provider_id.unwrap_or(selected_provider),
+                model,
+                client_type,
+            ));
         }
     };
 
     if let Some(explicit_provider_id) = explicit_provider_id {
         eprintln!("[{log_prefix}] 使用 X-Provider-Id 指定的 provider: {explicit_provider_id}");
-        let cred = state
-            .pool_service
-            .select_credential_with_client_check(
-                db,
-                explicit_provider_id,
-                Some(model),
-                Some(client_type),
-            )
-            .ok()
-            .flatten();
+        let cred = match state.pool_service.select_credential_with_client_check(
+            db,
+            explicit_provider_id,
+            Some(model),
+            Some(client_type),
+        ) {
+            Ok(cred) => {
+                state.degraded_pool.leave();
+                cred
+            }
+            Err(e) => {
+                select_degraded_credential(state, &e, explicit_provider_id, model, client_type)
+            }
+        };
 
         if cred.is_none() {
             eprintln!(
@@ -117,6 +150,7 @@ async fn select_credential_for_request(
             Some(client_type),
         ) {
             Ok(cred) => {
+                state.degraded_pool.leave();
                 if cred.is_some() {
                     eprintln!("[{log_prefix}] 找到凭证: provider={selected_provider}");
                 } else {
@@ -128,7 +162,13 @@ async fn select_credential_for_request(
             }
             Err(e) => {
                 eprintln!("[{log_prefix}] 选择凭证失败: {e}");
-                Ok(None)
+                Ok(select_degraded_credential(
+                    state,
+                    &e,
+                    selected_provider,
+                    model,
+                    client_type,
+                ))
             }
         };
     }
@@ -147,6 +187,7 @@ async fn select_credential_for_request(
         .await
     {
         Ok(cred) => {
+            state.degraded_pool.leave();
             if cred.is_some() {
                 eprintln!("[{log_prefix}] 找到凭证: provider={selected_provider}");
             } else {
@@ -156,11 +197,88 @@ async fn select_credential_for_request(
         }
         Err(e) => {
             eprintln!("[{log_prefix}] 选择凭证失败: {e}");
-            Ok(None)
+            Ok(select_degraded_credential(
+                state,
+                &e,
+                selected_provider,
+                model,
+                client_type,
+            ))
         }
     }
 }
 
+/// 数据库不可用或读写失败时，从配置加载的内存降级池选择凭证
+fn select_degraded_credential(
+    state: &AppState,
+    reason: &str,
+    provider: &str,
+    model: &str,
+    client_type: &ClientType,
+) -> Option<lime_core::models::provider_pool_model::ProviderCredential> {
+    state.degraded_pool.enter(reason);
+    state
+        .degraded_pool
+        .select(provider, Some(model), Some(client_type))
+}
+
+/// 突发流量平滑：令牌耗尽时排队等待，排队超出平滑窗口时返回 429 响应
+async fn smooth_burst(state: &AppState, client_key: &str) -> Option<Response> {
+    let smoother = state.burst_smoother.as_ref()?;
+    match smoother.acquire(client_key).await {
+        Ok(waited) => {
+            if !waited.is_zero() {
+                tracing::debug!("[BURST] 请求排队 {}ms 后放行", waited.as_millis());
+            }
+            None
+        }
+        Err(rejected) => {
+            let retry_after_secs = rejected.retry_after.as_secs().max(1);
+            let response = build_error_response_with_meta(
+                StatusCode::TOO_MANY_REQUESTS.as_u16(),
+                &format!(
+                    "Too many concurrent requests from this client. Retry after {} seconds",
+                    retry_after_secs
+                ),
+                None,
+                None,
+                Some(GatewayErrorCode::RateLimited),
+            );
+            let (mut parts, body) = response.into_parts();
+            parts.headers.insert(
+                header::RETRY_AFTER,
+                header::HeaderValue::from_str(&retry_after_secs.to_string())
+                    .unwrap_or_else(|_| header::HeaderValue::from_static("1")),
+            );
+            Some(Response::from_parts(parts, body))
+        }
+    }
+}
+
+/// 本地无可用凭证（503）时尝试转发给对等实例，无法转发时返回原响应
+async fn forward_to_peers_or<T: serde::Serialize>(
+    state: &AppState,
+    headers: &HeaderMap,
+    path: &str,
+    request: &T,
+    request_id: &str,
+    fallback: Response,
+) -> Response {
+    if fallback.status() != StatusCode::SERVICE_UNAVAILABLE || !state.peer_forwarding.enabled {
+        return fallback;
+    }
+    let Ok(body) = serde_json::to_value(request) else {
+        return fallback;
+    };
+    match super::peer_forward::forward_to_peers(state, headers, path, &body, request_id).await {
+        Some(mut response) => {
+            set_request_id_header(&mut response, request_id);
+            response
+        }
+        None => fallback,
+    }
+}
+
 async fn call_with_single_provider_resilience<F, Fut>(
     state: &AppState,
     request_id: &str,
@@ -477,6 +595,50 @@ fn set_static_diag_header(response: &mut Response, name: &'static str, value: &'
     );
 }
 
+/// 弃用模型检查：开启改写时把请求模型替换为替代模型
+async fn apply_model_deprecation(state: &AppState, model: &mut String) -> Option<ModelDeprecation> {
+    let deprecation = state.processor.check_deprecation(model).await?;
+    if deprecation.rewritten {
+        *model = deprecation.replacement.clone();
+    }
+    Some(deprecation)
+}
+
+/// 记录弃用警告并通过响应头提示客户端（认证失败的请求不提示）
+async fn report_model_deprecation(
+    logs: &tokio::sync::RwLock<LogStore>,
+    deprecation: &ModelDeprecation,
+    mut response: Response,
+) -> Response {
+    if response.status() == StatusCode::UNAUTHORIZED {
+        return response;
+    }
+    let message = if deprecation.rewritten {
+        format!(
+            "[DEPRECATION] 模型 {} 已弃用，已改写为 {}",
+            deprecation.model, deprecation.replacement
+        )
+    } else {
+        format!(
+            "[DEPRECATION] 模型 {} 已弃用，建议改用 {}",
+            deprecation.model, deprecation.replacement
+        )
+    };
+    tracing::warn!("{}", message);
+    logs.write().await.add("warn", &message);
+    for (name, value) in [
+        ("x-lime-model-deprecated", &deprecation.model),
+        ("x-lime-model-replacement", &deprecation.replacement),
+    ] {
+        if let Ok(value) = header::HeaderValue::from_str(value) {
+            response
+                .headers_mut()
+                .insert(header::HeaderName::from_static(name), value);
+        }
+    }
+    response
+}
+
 fn attach_route_debug_headers(
     mut response: Response,
     requested_provider: &str,
@@ -1094,7 +1256,10 @@ mod tests {
 // ============================================================================
 
 /// 根据客户端类型和端点配置选择 Provider
-async fn select_provider_for_client(headers: &HeaderMap, state: &AppState) -> (String, ClientType) {
+pub(super) async fn select_provider_for_client(
+    headers: &HeaderMap,
+    state: &AppState,
+) -> (String, ClientType) {
     let user_agent = headers
         .get("user-agent")
         .and_then(|v| v.to_str().ok())
@@ -1164,10 +1329,9 @@ impl CapabilityMismatchReason {
     }
 }
 
-fn estimate_token_count_from_json<T: serde::Serialize>(value: &T) -> u32 {
-    serde_json::to_vec(value)
-        .map(|bytes| (bytes.len() / 4) as u32)
-        .unwrap_or(0)
+/// 按模型对应的分词器估算 JSON 中的 Token 数
+fn estimate_token_count_from_json<T: serde::Serialize>(value: &T, model: &str) -> u32 {
+    lime_infra::tokenizer::count_json(value, Some(model)) as u32
 }
 
 fn openai_requires_vision(request: &ChatCompletionRequest) -> bool {
@@ -1198,8 +1362,97 @@ fn anthropic_requires_vision(request: &AnthropicMessagesRequest) -> bool {
     })
 }
 
+/// Anthropic 请求是否携带附件（图片或文档）
+fn anthropic_has_attachments(request: &AnthropicMessagesRequest) -> bool {
+    request.messages.iter().any(|msg| {
+        msg.content
+            .as_array()
+            .map(|blocks| {
+                blocks.iter().any(|block| {
+                    matches!(
+                        block.get("type").and_then(|v| v.as_str()),
+                        Some("image") | Some("image_url") | Some("document")
+                    )
+                })
+            })
+            .unwrap_or(false)
+    })
+}
+
+/// 规模路由：命中规则时改写目标模型
+async fn apply_size_routing(
+    state: &AppState,
+    ctx: &mut RequestContext,
+    model: &mut String,
+    estimated_tokens: u32,
+    has_attachments: bool,
+) {
+    let size_match = {
+        let size_router = state.processor.size_router.read().await;
+        size_router.route(estimated_tokens, has_attachments)
+    };
+    let Some(size_match) = size_match else {
+        return;
+    };
+    if size_match.model == *model {
+        return;
+    }
+    state.logs.write().await.add(
+        "info",
+        &format!(
+            "[SIZE_ROUTE] request_id={} size={} estimated_tokens={} attachments={} {} -> model={}",
+            ctx.request_id,
+            size_match.size_class,
+            size_match.estimated_tokens,
+            size_match.has_attachments,
+            model,
+            size_match.model
+        ),
+    );
+    *model = size_match.model.clone();
+    ctx.set_resolved_model(size_match.model);
+}
+
+/// 时间段路由：命中规则时切换 Provider 或改写模型，返回最终选择的 Provider
+async fn apply_time_window_routing(
+    state: &AppState,
+    ctx: &mut RequestContext,
+    model: &mut String,
+    selected_provider: String,
+) -> String {
+    let window_match = {
+        let router = state.processor.time_window_router.read().await;
+        router.route(model)
+    };
+    let Some(window_match) = window_match else {
+        return selected_provider;
+    };
+    let provider = window_match
+        .provider
+        .unwrap_or_else(|| selected_provider.clone());
+    state.logs.write().await.add(
+        "info",
+        &format!(
+            "[TIME_ROUTE] request_id={} rule={} provider={} -> {} model={} -> {}",
+            ctx.request_id,
+            window_match.rule,
+            selected_provider,
+            provider,
+            model,
+            window_match.model.as_deref().unwrap_or(model.as_str())
+        ),
+    );
+    if let Some(target) = window_match.model {
+        if target != *model {
+            *model = target.clone();
+            ctx.set_resolved_model(target);
+        }
+    }
+    provider
+}
+
 fn build_openai_capability_requirements(request: &ChatCompletionRequest) -> CapabilityRequirements {
-    let estimated_input_tokens = estimate_token_count_from_json(&request.messages);
+    let estimated_input_tokens = estimate_token_count_from_json(&request.messages, &request.model);
     let estimated_output_tokens = request.max_tokens.unwrap_or(4096);
     CapabilityRequirements {
         requires_tools: request
@@ -1217,7 +1470,7 @@ fn build_openai_capability_requirements(request: &ChatCompletionRequest) -> Capa
 fn build_anthropic_capability_requirements(
     request: &AnthropicMessagesRequest,
 ) -> CapabilityRequirements {
-    let estimated_input_tokens = estimate_token_count_from_json(&request.messages);
+    let estimated_input_tokens = estimate_token_count_from_json(&request.messages, &request.model);
     let estimated_output_tokens = request.max_tokens.unwrap_or(4096);
     CapabilityRequirements {
         requires_tools: request
@@ -1355,7 +1608,10 @@ fn collect_provider_model_candidates(
     candidates
 }
 
-async fn collect_provider_fallback_chain(state: &AppState, selected_provider: &str) -> Vec<String> {
+pub(super) async fn collect_provider_fallback_chain(
+    state: &AppState,
+    selected_provider: &str,
+) -> Vec<String> {
     let mut chain = Vec::new();
     let mut seen = HashSet::new();
 
@@ -1704,11 +1960,12 @@ pub async fn verify_api_key(
         None => {
             let body = build_gateway_error_json(
                 StatusCode::UNAUTHORIZED.as_u16(),
-                "No API key provided",
+                i18n::t(MessageCode::AuthMissingKey),
                 None,
                 None,
                 Some(GatewayErrorCode::AuthenticationFailed),
             );
+            note_auth_failure();
             return Err((StatusCode::UNAUTHORIZED, Json(body)));
         }
     };
@@ -1716,14 +1973,16 @@ pub async fn verify_api_key(
     if key != expected_key {
         let body = build_gateway_error_json(
             StatusCode::UNAUTHORIZED.as_u16(),
-            "Invalid API key",
+            i18n::t(MessageCode::AuthInvalidKey),
             None,
             None,
             Some(GatewayErrorCode::AuthenticationFailed),
         );
+        note_auth_failure();
         return Err((StatusCode::UNAUTHORIZED, Json(body)));
     }
 
+    note_auth_success();
     Ok(())
 }
 
@@ -1743,11 +2002,12 @@ pub async fn verify_api_key_anthropic(
         None => {
             let body = build_gateway_error_json(
                 StatusCode::UNAUTHORIZED.as_u16(),
-                "No API key provided. Please set the x-api-key header.",
+                i18n::t(MessageCode::AuthMissingKeyAnthropic),
                 None,
                 None,
                 Some(GatewayErrorCode::AuthenticationFailed),
             );
+            note_auth_failure();
             return Err((
                 StatusCode::UNAUTHORIZED,
                 Json(serde_json::json!({ "type": "error", "error": body["error"].clone() })),
@@ -1758,24 +2018,263 @@ pub async fn verify_api_key_anthropic(
     if key != expected_key {
         let body = build_gateway_error_json(
             StatusCode::UNAUTHORIZED.as_u16(),
-            "Invalid API key",
+            i18n::t(MessageCode::AuthInvalidKey),
             None,
             None,
             Some(GatewayErrorCode::AuthenticationFailed),
         );
+        note_auth_failure();
         return Err((
             StatusCode::UNAUTHORIZED,
             Json(serde_json::json!({ "type": "error", "error": body["error"].clone() })),
         ));
     }
 
+    note_auth_success();
     Ok(())
 }
 
+/// 管理接口的认证（接受主 Key 或已通过 OIDC 认证的请求）
+pub async fn verify_admin_key(
+    headers: &HeaderMap,
+    admin: Option<&AdminIdentity>,
+    state: &AppState,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if admin.is_some() {
+        return Ok(());
+    }
+    verify_api_key(headers, &state.api_key).await
+}
+
+/// 推理端点的 API key 验证（接受主 Key、有效的受限 Key 或已验证的签名请求）
+pub async fn verify_inbound_api_key(
+    headers: &HeaderMap,
+    state: &AppState,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if is_signature_verified(headers) {
+        return Ok(());
+    }
+    if extract_bearer_key(headers, &["authorization", "x-api-key"])
+        .is_some_and(|key| state.scoped_keys.is_valid(key))
+    {
+        note_auth_success();
+        return Ok(());
+    }
+    reject_weak_key_via_tunnel(headers, state)?;
+    verify_api_key(headers, &state.api_key).await
+}
+
+/// 推理端点的 Anthropic 格式 API key 验证（接受主 Key、有效的受限 Key 或已验证的签名请求）
+pub async fn verify_inbound_api_key_anthropic(
+    headers: &HeaderMap,
+    state: &AppState,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if is_signature_verified(headers) {
+        return Ok(());
+    }
+    if extract_bearer_key(headers, &["x-api-key", "authorization"])
+        .is_some_and(|key| state.scoped_keys.is_valid(key))
+    {
+        note_auth_success();
+        return Ok(());
+    }
+    reject_weak_key_via_tunnel(headers, state)?;
+    verify_api_key_anthropic(headers, &state.api_key).await
+}
+
+/// 受限 Key 的模型范围检查（主 Key 与签名请求不受限制），不计请求次数
+///
+/// 用于同一请求中的附带模型（如 RAG 的嵌入模型、LLM 打分模型）。
+pub fn check_scoped_key_model(
+    headers: &HeaderMap,
+    state: &AppState,
+    model: &str,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if is_signature_verified(headers) {
+        return Ok(());
+    }
+    let allowed = ["authorization", "x-api-key"]
+        .iter()
+        .filter_map(|name| extract_bearer_key(headers, &[name]))
+        .all(|key| state.scoped_keys.allows_model(key, model));
+    if allowed {
+        return Ok(());
+    }
+
+    let body = build_gateway_error_json(
+        StatusCode::FORBIDDEN.as_u16(),
+        &format!("Model '{model}' is not allowed for this API key"),
+        None,
+        None,
+        Some(GatewayErrorCode::AuthenticationFailed),
+    );
+    Err((StatusCode::FORBIDDEN, Json(body)))
+}
+
+/// 受限 Key 的模型范围检查，通过后计一次受限 Key 的请求（每个请求只调用一次）
+///
+/// 认证阶段不计数，未通过授权的请求不会消耗受限 Key 的请求次数。
+pub fn verify_scoped_key_model(
+    headers: &HeaderMap,
+    state: &AppState,
+    model: &str,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    check_scoped_key_model(headers, state, model)?;
+    if is_signature_verified(headers) {
+        return Ok(());
+    }
+    let scoped_key = ["authorization", "x-api-key"]
+        .iter()
+        .filter_map(|name| extract_bearer_key(headers, &[name]))
+        .find(|key| state.scoped_keys.is_valid(key));
+    // 并发请求可能在认证之后用尽请求次数
+    if scoped_key.is_some_and(|key| !state.scoped_keys.verify(key)) {
+        let body = build_gateway_error_json(
+            StatusCode::UNAUTHORIZED.as_u16(),
+            i18n::t(MessageCode::AuthInvalidKey),
+            None,
+            None,
+            Some(GatewayErrorCode::AuthenticationFailed),
+        );
+        return Err((StatusCode::UNAUTHORIZED, Json(body)));
+    }
+    Ok(())
+}
+
+/// 按受限 Key 与模型的输出上限收紧 `max_tokens`（取较小值），未指定时直接使用上限
+///
+/// 发生收紧时返回收紧记录，写入请求日志。
+fn clamp_max_tokens(
+    headers: &HeaderMap,
+    state: &AppState,
+    model: &str,
+    max_tokens: &mut Option<u32>,
+) -> Option<MaxTokensClamp> {
+    let key_limit = ["authorization", "x-api-key"]
+        .iter()
+        .filter_map(|name| extract_bearer_key(headers, &[name]))
+        .filter_map(|key| state.scoped_keys.max_output_tokens_for(key))
+        .min();
+    let model_limit = state.max_output_tokens.limit_for(model);
+    let limit = match (key_limit, model_limit) {
+        (Some(a), Some(b)) => a.min(b),
+        (limit, None) | (None, limit) => limit?,
+    };
+    if max_tokens.is_some_and(|requested| requested <= limit) {
+        return None;
+    }
+    let clamp = MaxTokensClamp {
+        requested: *max_tokens,
+        limit,
+    };
+    *max_tokens = Some(limit);
+    tracing::info!(
+        "[MAX_TOKENS] 模型 {} 的 max_tokens 由 {:?} 收紧为 {}",
+        model,
+        clamp.requested,
+        limit
+    );
+    Some(clamp)
+}
+
+/// 经隧道/反向代理转发的请求（带转发头）不接受弱主 Key
+fn reject_weak_key_via_tunnel(
+    headers: &HeaderMap,
+    state: &AppState,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if !state.tunnel_requires_strong_key
+        || !is_forwarded_request(headers)
+        || lime_core::config::is_strong_api_key(&state.api_key)
+    {
+        return Ok(());
+    }
+
+    let body = build_gateway_error_json(
+        StatusCode::FORBIDDEN.as_u16(),
+        "Tunneled requests require a strong API key. Regenerate the API key in Lime settings.",
+        None,
+        None,
+        Some(GatewayErrorCode::AuthenticationFailed),
+    );
+    Err((StatusCode::FORBIDDEN, Json(body)))
+}
+
+fn extract_bearer_key<'a>(headers: &'a HeaderMap, names: &[&str]) -> Option<&'a str> {
+    let value = names
+        .iter()
+        .find_map(|name| headers.get(*name))
+        .and_then(|v| v.to_str().ok())?;
+    Some(value.strip_prefix("Bearer ").unwrap_or(value))
+}
+
+/// 当前请求的归属标注方式（受限 Key 的单独设置优先）
+fn attribution_mode(headers: &HeaderMap, state: &AppState) -> AttributionMode {
+    ["authorization", "x-api-key"]
+        .iter()
+        .filter_map(|name| extract_bearer_key(headers, &[name]))
+        .find_map(|key| state.scoped_keys.attribution_for(key))
+        .unwrap_or(state.attribution.mode)
+}
+
+/// `/v1/chat/completions`
+///
+/// 客户端请求流式而实际拿到完整响应时，按 `server.fake_streaming` 转换为伪流式 SSE；
+/// 流式响应再按 `server.stream_transform` 合并增量或转为 NDJSON；
+/// 成功响应按 `server.attribution`（受限 Key 可单独设置）标注实际处理的 Provider 与模型。
 pub async fn chat_completions(
     State(state): State<AppState>,
     headers: HeaderMap,
+    admin: Option<AdminIdentity>,
     Json(mut request): Json<ChatCompletionRequest>,
+    logprobs: Option<LogprobsOptions>,
+    seed: Option<SeedOptions>,
+) -> Response {
+    let stream_requested = request.stream;
+    if stream_requested && state.fake_streaming.for_images && openai_requires_vision(&request) {
+        request.stream = false;
+    }
+    let deprecation = apply_model_deprecation(&state, &mut request.model).await;
+    let settings = state.fake_streaming.clone();
+    let transform = state.stream_transform.clone();
+    let ndjson = stream_transform::wants_ndjson(&headers);
+    let logs = state.logs.clone();
+    let attribution = state.attribution.clone();
+    let attribution_mode = attribution_mode(&headers, &state);
+    let content_policy = state.content_policy.clone();
+    let firewall = if state.prompt_firewall.is_enabled()
+        && verify_inbound_api_key(&headers, &state).await.is_ok()
+    {
+        let payload = serde_json::to_value(&request).unwrap_or_default();
+        match prompt_firewall::enforce(&state, &payload).await {
+            Ok(verdict) => verdict,
+            Err(response) => return response,
+        }
+    } else {
+        None
+    };
+    let response =
+        handle_chat_completions(State(state), headers, admin, Json(request), logprobs, seed).await;
+    let response =
+        fake_stream::ensure_streaming(&settings, response, stream_requested, SseFlavor::OpenAi)
+            .await;
+    let response = content_policy::apply(&content_policy, SseFlavor::OpenAi, response).await;
+    let response =
+        attribution::apply(&attribution, attribution_mode, SseFlavor::OpenAi, response).await;
+    let response = stream_transform::apply(&transform, response, ndjson);
+    let response = prompt_firewall::annotate(firewall.as_ref(), response);
+    match deprecation {
+        Some(deprecation) => report_model_deprecation(&logs, &deprecation, response).await,
+        None => response,
+    }
+}
+
+async fn handle_chat_completions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<AdminIdentity>,
+    Json(mut request): Json<ChatCompletionRequest>,
+    logprobs: Option<LogprobsOptions>,
+    seed: Option<SeedOptions>,
 ) -> Response {
     // ========== 详细日志：请求入口 ==========
     eprintln!("\n========== [CHAT_COMPLETIONS] 收到请求 ==========");
@@ -1784,7 +2283,7 @@ pub async fn chat_completions(
     eprintln!("[CHAT_COMPLETIONS] 流式: {}", request.stream);
     eprintln!("[CHAT_COMPLETIONS] 消息数量: {}", request.messages.len());
 
-    if let Err(e) = verify_api_key(&headers, &state.api_key).await {
+    if let Err(e) = verify_inbound_api_key(&headers, &state).await {
         eprintln!("[CHAT_COMPLETIONS] 认证失败!");
         state
             .logs
@@ -1794,6 +2293,11 @@ pub async fn chat_completions(
         return e.into_response();
     }
     eprintln!("[CHAT_COMPLETIONS] 认证成功");
+    if let Err(e) = verify_scoped_key_model(&headers, &state, &request.model) {
+        return e.into_response();
+    }
+    let max_tokens_clamp =
+        clamp_max_tokens(&headers, &state, &request.model, &mut request.max_tokens);
 
     // 速率限制检查
     if let Some(ref limiter) = state.rate_limiter {
@@ -1803,7 +2307,7 @@ pub async fn chat_completions(
             .and_then(|v| v.to_str().ok())
             .unwrap_or("anonymous");
         if let crate::middleware::rate_limit::RateLimitResult::Limited { retry_after } =
-            limiter.check_rate_limit(client_key)
+            limiter.check(client_key).await
         {
             let response = build_error_response_with_meta(
                 StatusCode::TOO_MANY_REQUESTS.as_u16(),
@@ -1825,8 +2329,27 @@ pub async fn chat_completions(
         }
     }
 
+    // 突发流量平滑（IDE 智能体并行请求）
+    let client_key = headers
+        .get("x-api-key")
+        .or_else(|| headers.get("authorization"))
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("anonymous");
+    if let Some(response) = smooth_burst(&state, client_key).await {
+        return response;
+    }
+
+    // 本地 RAG：按配置/请求头注入检索上下文
+    super::rag::augment_chat_request(&state, &headers, &mut request).await;
+
     // 创建请求上下文
     let mut ctx = RequestContext::new(request.model.clone()).with_stream(request.stream);
+    if let Some(clamp) = max_tokens_clamp {
+        ctx.set_metadata(
+            MAX_TOKENS_CLAMP_METADATA,
+            serde_json::to_value(clamp).unwrap_or_default(),
+        );
+    }
     eprintln!("[CHAT_COMPLETIONS] 请求ID: {}", ctx.request_id);
 
     // 幂等性检查（仅非流式）
@@ -1898,6 +2421,18 @@ pub async fn chat_completions(
         );
     }
 
+    // 规模路由：按提示词规模或附件改写模型，显式的提示路由优先
+    let estimated_tokens = estimate_token_count_from_json(&request.messages, &request.model);
+    let has_attachments = openai_requires_vision(&request);
+    apply_size_routing(
+        &state,
+        &mut ctx,
+        &mut request.model,
+        estimated_tokens,
+        has_attachments,
+    )
+    .await;
+
     // 提示路由：从最后一条 user 消息提取 [hint]
     {
         let hint_router = state.processor.hint_router.read().await;
@@ -1969,6 +2504,9 @@ pub async fn chat_completions(
     // 根据客户端类型选择 Provider
     // **Validates: Requirements 3.1, 3.3, 3.4**
     let (selected_provider, client_type) = select_provider_for_client(&headers, &state).await;
+    // 时间段路由：按本机当前时间切换 Provider 或模型
+    let selected_provider =
+        apply_time_window_routing(&state, &mut ctx, &mut request.model, selected_provider).await;
     eprintln!("[CHAT_COMPLETIONS] 客户端类型: {client_type}, 选择的Provider: {selected_provider}");
 
     // 记录客户端检测和 Provider 选择结果
@@ -1986,22 +2524,53 @@ pub async fn chat_completions(
         .and_then(|v| v.to_str().ok())
         .map(|s| s.to_lowercase());
 
+    // 调试用固定路由头（仅管理 Key），固定凭证时跳过负载均衡
+    let routing_pin =
+        match routing_pin::authorize(&headers, admin.as_ref(), &state, &ctx.request_id) {
+            Ok(pin) => pin,
+            Err(resp) => return resp,
+        };
+    let pinned_credential =
+        match routing_pin::pinned_credential(&state, routing_pin.as_ref(), &ctx.request_id).await {
+            Ok(credential) => credential,
+            Err(resp) => return resp,
+        };
+    let provider_id_header = routing_pin
+        .as_ref()
+        .and_then(|pin| pin.provider.clone())
+        .or(provider_id_header);
+
     // 尝试选择凭证（含能力感知 + 跨 Provider 回退）：
     // 1) X-Provider-Id 指定时仅走精确匹配（不降级）
     // 2) 否则先按 provider 链路做能力过滤，再选择可用凭证
     eprintln!("[CHAT_COMPLETIONS] 开始选择凭证...");
-    let (effective_provider, credential) = match resolve_openai_credential_with_capability_fallback(
-        &state,
-        &ctx.request_id,
-        &selected_provider,
-        &client_type,
-        provider_id_header.as_deref(),
-        &mut request,
-    )
-    .await
-    {
+    let resolved = match pinned_credential {
+        Some(cred) => Ok((cred.provider_type.to_string(), Some(cred))),
+        None => {
+            resolve_openai_credential_with_capability_fallback(
+                &state,
+                &ctx.request_id,
+                &selected_provider,
+                &client_type,
+                provider_id_header.as_deref(),
+                &mut request,
+            )
+            .await
+        }
+    };
+    let (effective_provider, credential) = match resolved {
         Ok(result) => result,
-        Err(resp) => return resp,
+        Err(resp) => {
+            return forward_to_peers_or(
+                &state,
+                &headers,
+                "/v1/chat/completions",
+                &request,
+                &ctx.request_id,
+                resp,
+            )
+            .await;
+        }
     };
     if ctx.resolved_model != request.model {
         ctx.set_resolved_model(request.model.clone());
@@ -2017,7 +2586,14 @@ pub async fn chat_completions(
     );
 
     if !request.stream {
-        let request_payload = serde_json::to_value(&request).unwrap_or_default();
+        let mut request_payload = serde_json::to_value(&request).unwrap_or_default();
+        // logprobs 与 seed 不在类型化请求中，需计入缓存与去重的键
+        if let Some(options) = &logprobs {
+            options.apply(&mut request_payload);
+        }
+        if let (Some(seed), Some(object)) = (&seed, request_payload.as_object_mut()) {
+            object.extend(seed.to_params());
+        }
         match begin_response_cache(
             &ctx.request_id,
             "chat_completions",
@@ -2102,20 +2678,104 @@ pub async fn chat_completions(
         // **Validates: Requirements 2.1, 2.3, 2.5**
 
         eprintln!("[CHAT_COMPLETIONS] 调用 Provider: {}", cred.provider_type);
+        if let Some(response) =
+            logprobs::reject_unsupported(logprobs.as_ref(), &cred, Some(&ctx.request_id))
+        {
+            return response;
+        }
         let provider_label = cred.provider_type.to_string();
-        let response = call_with_single_provider_resilience(
-            &state,
-            &ctx.request_id,
-            &provider_label,
-            request.stream,
-            || async { call_provider_openai(&state, &cred, &request, None).await },
+        ctx.credential_id = Some(cred.uuid.clone());
+        let mut extra_params = logprobs
+            .map(|options| options.to_params())
+            .unwrap_or_default();
+        // 与 logprobs 相同，只有 OpenAI 兼容接口能指定 seed
+        let seed_forwarded = seed.map(|seed| {
+            let forwarded = logprobs::credential_supports(&cred.credential);
+            if forwarded {
+                extra_params.extend(seed.to_params());
+            } else if request.temperature.is_none() {
+                request.temperature = Some(0.0);
+            }
+            (seed.seed, forwarded)
+        });
+        if let Some((seed, forwarded)) = seed_forwarded {
+            state.logs.write().await.add(
+                "info",
+                &format!(
+                    "[SEED] request_id={} seed={} provider={} forwarded={}",
+                    ctx.request_id, seed, provider_label, forwarded
+                ),
+            );
+        }
+        let forced_request = (request.stream
+            && state.fake_streaming.forces_non_streaming(&provider_label))
+        .then(|| {
+            let mut upstream = request.clone();
+            upstream.stream = false;
+            upstream
+        });
+        let upstream_request = forced_request.as_ref().unwrap_or(&request);
+        let inflight_slot = state.inflight.acquire();
+        let request_id = ctx.request_id.as_str();
+        let upstream_model = upstream_request.model.as_str();
+        let response = model_downgrade::call_with_downgrade(
+            &state.model_downgrade,
+            &state.logs,
+            upstream_request.clone(),
+            upstream_model,
+            |request, model| request.model = model,
+            |request| {
+                let (state, headers, cred, extra_params) = (&state, &headers, &cred, &extra_params);
+                let provider_label = provider_label.as_str();
+                async move {
+                    // 降级目标同样受受限 Key 的模型范围约束（只检查，不重复计数）
+                    if request.model != upstream_model {
+                        if let Err(e) = check_scoped_key_model(headers, state, &request.model) {
+                            return e.into_response();
+                        }
+                    }
+                    call_with_single_provider_resilience(
+                        state,
+                        request_id,
+                        provider_label,
+                        request.stream,
+                        || async {
+                            call_provider_openai(state, cred, &request, extra_params, None).await
+                        },
+                    )
+                    .await
+                }
+            },
         )
         .await;
+        if let Some(model) = model_downgrade::downgraded_to(&response) {
+            ctx.set_resolved_model(model);
+        }
+        let response = abort::track_stream(&state, &ctx, response, inflight_slot);
         eprintln!(
             "[CHAT_COMPLETIONS] Provider 响应状态: {}",
             response.status()
         );
 
+        // 规范化并记录 Token 用量（流式响应由上游自行上报）
+        let (response, normalized_usage) = usage::normalize(
+            SseFlavor::OpenAi,
+            estimate_token_count_from_json(&request.messages, &request.model),
+            response,
+        )
+        .await;
+        if let Some(normalized_usage) = &normalized_usage {
+            record_usage(&state, &ctx, normalized_usage);
+        }
+        let mut response = seed::attach_system_fingerprint(
+            response,
+            &system_fingerprint(&provider_label, &ctx.resolved_model),
+        )
+        .await;
+        if let Some((_, forwarded)) = seed_forwarded {
+            seed::mark_seed(&mut response, forwarded);
+        }
+
         // 记录请求统计
         let is_success = response.status().is_success();
         let _status_code = response.status().as_u16();
@@ -2169,13 +2829,22 @@ pub async fn chat_completions(
                 effective_provider
             )
         };
-        return build_error_response_with_meta(
+        let response = build_error_response_with_meta(
             StatusCode::SERVICE_UNAVAILABLE.as_u16(),
             &message,
             Some(&ctx.request_id),
             Some(&effective_provider),
             Some(GatewayErrorCode::NoCredentials),
         );
+        return forward_to_peers_or(
+            &state,
+            &headers,
+            "/v1/chat/completions",
+            &request,
+            &ctx.request_id,
+            response,
+        )
+        .await;
     }
 
     state.logs.write().await.add(
@@ -2258,21 +2927,14 @@ pub async fn chat_completions(
                             })
                         };
 
-                        // 估算 Token 数量（基于字符数，约 4 字符 = 1 token）
-                        let estimated_output_tokens = (parsed.content.len() / 4) as u32;
+                        // 按模型对应的分词器估算 Token 数量
+                        let estimated_output_tokens = lime_infra::tokenizer::count_text(
+                            &parsed.content,
+                            Some(&request.model),
+                        ) as u32;
                         // 估算输入 Token（基于请求消息）
-                        let estimated_input_tokens = request
-                            .messages
-                            .iter()
-                            .map(|m| {
-                                let content_len = match &m.content {
-                                    Some(c) => message_content_len(c),
-                                    None => 0,
-                                };
-                                content_len / 4
-                            })
-                            .sum::<usize>()
-                            as u32;
+                        let estimated_input_tokens =
+                            estimate_token_count_from_json(&request.messages, &request.model);
 
                         let response = serde_json::json!({
                             "id": format!("chatcmpl-{}", uuid::Uuid::new_v4()),
@@ -2506,13 +3168,70 @@ pub async fn chat_completions(
     }
 }
 
+/// `/v1/messages`
+///
+/// 客户端请求流式而实际拿到完整响应时，按 `server.fake_streaming` 转换为伪流式 SSE；
+/// 流式响应再按 `server.stream_transform` 合并增量或转为 NDJSON；
+/// 成功响应按 `server.attribution`（受限 Key 可单独设置）标注实际处理的 Provider 与模型。
 pub async fn anthropic_messages(
     State(state): State<AppState>,
     headers: HeaderMap,
+    admin: Option<AdminIdentity>,
+    Json(mut request): Json<AnthropicMessagesRequest>,
+) -> Response {
+    let stream_requested = request.stream;
+    if stream_requested && state.fake_streaming.for_images && anthropic_requires_vision(&request) {
+        request.stream = false;
+    }
+    let deprecation = apply_model_deprecation(&state, &mut request.model).await;
+    let settings = state.fake_streaming.clone();
+    let transform = state.stream_transform.clone();
+    let ndjson = stream_transform::wants_ndjson(&headers);
+    let logs = state.logs.clone();
+    let attribution = state.attribution.clone();
+    let attribution_mode = attribution_mode(&headers, &state);
+    let content_policy = state.content_policy.clone();
+    let firewall = if state.prompt_firewall.is_enabled()
+        && verify_inbound_api_key_anthropic(&headers, &state)
+            .await
+            .is_ok()
+    {
+        let payload = serde_json::to_value(&request).unwrap_or_default();
+        match prompt_firewall::enforce(&state, &payload).await {
+            Ok(verdict) => verdict,
+            Err(response) => return response,
+        }
+    } else {
+        None
+    };
+    let response = handle_anthropic_messages(State(state), headers, admin, Json(request)).await;
+    let response =
+        fake_stream::ensure_streaming(&settings, response, stream_requested, SseFlavor::Anthropic)
+            .await;
+    let response = content_policy::apply(&content_policy, SseFlavor::Anthropic, response).await;
+    let response = attribution::apply(
+        &attribution,
+        attribution_mode,
+        SseFlavor::Anthropic,
+        response,
+    )
+    .await;
+    let response = stream_transform::apply(&transform, response, ndjson);
+    let response = prompt_firewall::annotate(firewall.as_ref(), response);
+    match deprecation {
+        Some(deprecation) => report_model_deprecation(&logs, &deprecation, response).await,
+        None => response,
+    }
+}
+
+async fn handle_anthropic_messages(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<AdminIdentity>,
     Json(mut request): Json<AnthropicMessagesRequest>,
 ) -> Response {
     // 使用 Anthropic 格式的认证验证（优先检查 x-api-key）
-    if let Err(e) = verify_api_key_anthropic(&headers, &state.api_key).await {
+    if let Err(e) = verify_inbound_api_key_anthropic(&headers, &state).await {
         state
             .logs
             .write()
@@ -2520,6 +3239,11 @@ pub async fn anthropic_messages(
             .add("warn", "Unauthorized request to /v1/messages");
         return e.into_response();
     }
+    if let Err(e) = verify_scoped_key_model(&headers, &state, &request.model) {
+        return e.into_response();
+    }
+    let max_tokens_clamp =
+        clamp_max_tokens(&headers, &state, &request.model, &mut request.max_tokens);
 
     // 速率限制检查
     if let Some(ref limiter) = state.rate_limiter {
@@ -2529,7 +3253,7 @@ pub async fn anthropic_messages(
             .and_then(|v| v.to_str().ok())
             .unwrap_or("anonymous");
         if let crate::middleware::rate_limit::RateLimitResult::Limited { retry_after } =
-            limiter.check_rate_limit(client_key)
+            limiter.check(client_key).await
         {
             let response = build_error_response_with_meta(
                 StatusCode::TOO_MANY_REQUESTS.as_u16(),
@@ -2551,8 +3275,27 @@ pub async fn anthropic_messages(
         }
     }
 
+    // 突发流量平滑（IDE 智能体并行请求）
+    let client_key = headers
+        .get("x-api-key")
+        .or_else(|| headers.get("authorization"))
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("anonymous");
+    if let Some(response) = smooth_burst(&state, client_key).await {
+        return response;
+    }
+
+    // 本地 RAG：按配置/请求头注入检索上下文
+    super::rag::augment_anthropic_request(&state, &headers, &mut request).await;
+
     // 创建请求上下文
     let mut ctx = RequestContext::new(request.model.clone()).with_stream(request.stream);
+    if let Some(clamp) = max_tokens_clamp {
+        ctx.set_metadata(
+            MAX_TOKENS_CLAMP_METADATA,
+            serde_json::to_value(clamp).unwrap_or_default(),
+        );
+    }
 
     // 幂等性检查（仅非流式）
     let idempotency_key = headers
@@ -2648,6 +3391,18 @@ pub async fn anthropic_messages(
         );
     }
 
+    // 规模路由：按提示词规模或附件改写模型，显式的提示路由优先
+    let estimated_tokens = estimate_token_count_from_json(&request.messages, &request.model);
+    let has_attachments = anthropic_has_attachments(&request);
+    apply_size_routing(
+        &state,
+        &mut ctx,
+        &mut request.model,
+        estimated_tokens,
+        has_attachments,
+    )
+    .await;
+
     // 提示路由：从最后一条 user 消息提取 [hint]
     {
         let hint_router = state.processor.hint_router.read().await;
@@ -2729,6 +3484,9 @@ pub async fn anthropic_messages(
     // 根据客户端类型选择 Provider
     // **Validates: Requirements 3.1, 3.3, 3.4**
     let (selected_provider, client_type) = select_provider_for_client(&headers, &state).await;
+    // 时间段路由：按本机当前时间切换 Provider 或模型
+    let selected_provider =
+        apply_time_window_routing(&state, &mut ctx, &mut request.model, selected_provider).await;
 
     // 记录客户端检测和 Provider 选择结果
     state.logs.write().await.add(
@@ -2745,21 +3503,51 @@ pub async fn anthropic_messages(
         .and_then(|v| v.to_str().ok())
         .map(|s| s.to_lowercase());
 
-    // 尝试选择凭证（含能力感知 + 跨 Provider 回退）
-    let (effective_provider, credential) =
-        match resolve_anthropic_credential_with_capability_fallback(
-            &state,
-            &ctx.request_id,
-            &selected_provider,
-            &client_type,
-            provider_id_header.as_deref(),
-            &mut request,
-        )
-        .await
-        {
-            Ok(result) => result,
+    // 调试用固定路由头（仅管理 Key），固定凭证时跳过负载均衡
+    let routing_pin =
+        match routing_pin::authorize(&headers, admin.as_ref(), &state, &ctx.request_id) {
+            Ok(pin) => pin,
             Err(resp) => return resp,
         };
+    let pinned_credential =
+        match routing_pin::pinned_credential(&state, routing_pin.as_ref(), &ctx.request_id).await {
+            Ok(credential) => credential,
+            Err(resp) => return resp,
+        };
+    let provider_id_header = routing_pin
+        .as_ref()
+        .and_then(|pin| pin.provider.clone())
+        .or(provider_id_header);
+
+    // 尝试选择凭证（含能力感知 + 跨 Provider 回退）
+    let resolved = match pinned_credential {
+        Some(cred) => Ok((cred.provider_type.to_string(), Some(cred))),
+        None => {
+            resolve_anthropic_credential_with_capability_fallback(
+                &state,
+                &ctx.request_id,
+                &selected_provider,
+                &client_type,
+                provider_id_header.as_deref(),
+                &mut request,
+            )
+            .await
+        }
+    };
+    let (effective_provider, credential) = match resolved {
+        Ok(result) => result,
+        Err(resp) => {
+            return forward_to_peers_or(
+                &state,
+                &headers,
+                "/v1/messages",
+                &request,
+                &ctx.request_id,
+                resp,
+            )
+            .await;
+        }
+    };
     if ctx.resolved_model != request.model {
         ctx.set_resolved_model(request.model.clone());
     }
@@ -2851,14 +3639,50 @@ pub async fn anthropic_messages(
         // **Validates: Requirements 2.1, 2.3, 2.5**
 
         let provider_label = cred.provider_type.to_string();
-        let response = call_with_single_provider_resilience(
-            &state,
-            &ctx.request_id,
-            &provider_label,
-            request.stream,
-            || async { call_provider_anthropic(&state, &cred, &request, None).await },
+        ctx.credential_id = Some(cred.uuid.clone());
+        let forced_request = (request.stream
+            && state.fake_streaming.forces_non_streaming(&provider_label))
+        .then(|| {
+            let mut upstream = request.clone();
+            upstream.stream = false;
+            upstream
+        });
+        let upstream_request = forced_request.as_ref().unwrap_or(&request);
+        let inflight_slot = state.inflight.acquire();
+        let request_id = ctx.request_id.as_str();
+        let upstream_model = upstream_request.model.as_str();
+        let response = model_downgrade::call_with_downgrade(
+            &state.model_downgrade,
+            &state.logs,
+            upstream_request.clone(),
+            upstream_model,
+            |request, model| request.model = model,
+            |request| {
+                let (state, headers, cred) = (&state, &headers, &cred);
+                let provider_label = provider_label.as_str();
+                async move {
+                    // 降级目标同样受受限 Key 的模型范围约束（只检查，不重复计数）
+                    if request.model != upstream_model {
+                        if let Err(e) = check_scoped_key_model(headers, state, &request.model) {
+                            return e.into_response();
+                        }
+                    }
+                    call_with_single_provider_resilience(
+                        state,
+                        request_id,
+                        provider_label,
+                        request.stream,
+                        || async { call_provider_anthropic(state, cred, &request, None).await },
+                    )
+                    .await
+                }
+            },
         )
         .await;
+        if let Some(model) = model_downgrade::downgraded_to(&response) {
+            ctx.set_resolved_model(model);
+        }
+        let response = abort::track_stream(&state, &ctx, response, inflight_slot);
 
         // 记录请求统计
         let is_success = response.status().is_success();
@@ -2888,13 +3712,18 @@ pub async fn anthropic_messages(
             .sum::<usize>() as u32;
         let estimated_output_tokens = if is_success { 100u32 } else { 0u32 };
 
-        if is_success {
-            record_token_usage(
+        // 非流式响应按上游用量规范化，流式响应沿用估算值
+        let (response, normalized_usage) =
+            usage::normalize(SseFlavor::Anthropic, estimated_input_tokens, response).await;
+        match normalized_usage {
+            Some(normalized_usage) => record_usage(&state, &ctx, &normalized_usage),
+            None if is_success => record_token_usage(
                 &state,
                 &ctx,
                 Some(estimated_input_tokens),
                 Some(estimated_output_tokens),
-            );
+            ),
+            None => {}
         }
 
         // 完成 Flow 捕获并检查响应拦截
@@ -2948,11 +3777,20 @@ pub async fn anthropic_messages(
             Some(&effective_provider),
             Some(GatewayErrorCode::NoCredentials),
         );
-        return (
+        let response = (
             StatusCode::SERVICE_UNAVAILABLE,
             Json(serde_json::json!({ "type": "error", "error": body["error"].clone() })),
         )
             .into_response();
+        return forward_to_peers_or(
+            &state,
+            &headers,
+            "/v1/messages",
+            &request,
+            &ctx.request_id,
+            response,
+        )
+        .await;
     }
 
     state.logs.write().await.add(
@@ -0,0 +1,495 @@
+//! 响应归属标注
+//!
+//! 按 `server.attribution`（受限 Key 可单独覆盖）标注实际处理请求的 Provider 与模型：
+//! - `metadata`：在响应 JSON 中附加 `lime_attribution` 字段，流式响应附加在首个事件上
+//! - `footer`：在回复文本末尾追加页脚，流式响应在结束事件前补发一段文本增量
+//!
+//! Provider 与模型取自路由阶段写入的 `x-lime-effective-provider` / `x-lime-model` 响应头，
+//! 缺失时模型回退到响应体中的 `model` 字段。
+
+use axum::{
+    body::{to_bytes, Body},
+    http::header,
+    response::Response,
+};
+use bytes::Bytes;
+use futures::StreamExt;
+use lime_core::config::{AttributionMode, AttributionSettings};
+use serde_json::{json, Value};
+
+use super::fake_stream::SseFlavor;
+use crate::sse::SseEventSplitter;
+
+/// 转换时读取的最大响应体
+const MAX_BODY_BYTES: usize = 32 * 1024 * 1024;
+/// 附加到响应中的字段名
+const ATTRIBUTION_FIELD: &str = "lime_attribution";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Attribution {
+    provider: Option<String>,
+    model: Option<String>,
+}
+
+impl Attribution {
+    fn from_response(response: &Response) -> Self {
+        let header_value = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+                .filter(|v| !v.is_empty())
+        };
+        Self {
+            provider: header_value("x-lime-effective-provider"),
+            model: header_value("x-lime-model"),
+        }
+    }
+
+    /// 模型未知时从响应体补全（OpenAI 顶层 `model`，Anthropic `message_start.message.model`）
+    fn fill_model(&mut self, value: &Value) {
+        if self.model.is_some() {
+            return;
+        }
+        self.model = value
+            .get("model")
+            .or_else(|| value.get("message").and_then(|m| m.get("model")))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+    }
+
+    fn metadata(&self) -> Value {
+        json!({ "provider": self.provider, "model": self.model })
+    }
+
+    fn footer(&self, template: &str) -> String {
+        template
+            .replace("{provider}", self.provider.as_deref().unwrap_or("unknown"))
+            .replace("{model}", self.model.as_deref().unwrap_or("unknown"))
+    }
+}
+
+fn content_type_starts_with(response: &Response, prefix: &str) -> bool {
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with(prefix))
+}
+
+fn is_text_block(block: &Value) -> bool {
+    block.get("type").and_then(Value::as_str) == Some("text")
+}
+
+/// 标注完整 JSON 响应
+fn annotate_json(
+    value: &mut Value,
+    flavor: SseFlavor,
+    mode: AttributionMode,
+    attribution: &Attribution,
+    template: &str,
+) {
+    match mode {
+        AttributionMode::Off => {}
+        AttributionMode::Metadata => {
+            if let Some(object) = value.as_object_mut() {
+                object.insert(ATTRIBUTION_FIELD.to_string(), attribution.metadata());
+            }
+        }
+        AttributionMode::Footer => {
+            let footer = attribution.footer(template);
+            let text = match flavor {
+                SseFlavor::OpenAi => value
+                    .pointer_mut("/choices/0/message/content")
+                    .filter(|content| content.is_string()),
+                SseFlavor::Anthropic => value
+                    .get_mut("content")
+                    .and_then(Value::as_array_mut)
+                    .and_then(|blocks| blocks.iter_mut().rev().find(|block| is_text_block(block)))
+                    .and_then(|block| block.get_mut("text")),
+            };
+            if let Some(Value::String(text)) = text {
+                text.push_str(&footer);
+            }
+        }
+    }
+}
+
+/// SSE 事件的 `data` 内容
+pub(crate) fn event_data(event: &str) -> Option<String> {
+    let data: Vec<&str> = event
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(str::trim_start)
+        .collect();
+    (!data.is_empty()).then(|| data.join("\n"))
+}
+
+/// 用新的 `data` 替换事件中的数据行，保留 `event:` / `id:` 等其他行
+pub(crate) fn replace_event_data(event: &str, data: &Value) -> String {
+    let mut out = String::new();
+    let mut data_written = false;
+    for line in event.lines() {
+        if line.starts_with("data:") {
+            if !data_written {
+                out.push_str(&format!("data: {data}\n"));
+                data_written = true;
+            }
+        } else {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out.push('\n');
+    out
+}
+
+/// 流式响应标注状态
+struct StreamAnnotator {
+    flavor: SseFlavor,
+    attribution: Attribution,
+    template: String,
+    metadata_done: bool,
+    footer_done: bool,
+    /// 最近一个 OpenAI chunk（补发页脚时沿用 id / model / created）
+    last_chunk: Option<Value>,
+    /// 已出现的最大 Anthropic 内容块序号
+    max_block_index: Option<u64>,
+}
+
+impl StreamAnnotator {
+    fn new(
+        flavor: SseFlavor,
+        mode: AttributionMode,
+        attribution: Attribution,
+        template: &str,
+    ) -> Self {
+        Self {
+            flavor,
+            attribution,
+            template: template.to_string(),
+            metadata_done: mode != AttributionMode::Metadata,
+            footer_done: mode != AttributionMode::Footer,
+            last_chunk: None,
+            max_block_index: None,
+        }
+    }
+
+    fn openai_footer_chunk(&mut self) -> String {
+        self.footer_done = true;
+        let last = self.last_chunk.as_ref();
+        let field = |name: &str| last.and_then(|chunk| chunk.get(name)).cloned();
+        let chunk = json!({
+            "id": field("id"),
+            "object": "chat.completion.chunk",
+            "created": field("created"),
+            "model": field("model"),
+            "choices": [{
+                "index": 0,
+                "delta": {"content": self.attribution.footer(&self.template)},
+                "finish_reason": null
+            }]
+        });
+        format!("data: {chunk}\n\n")
+    }
+
+    fn anthropic_footer_events(&mut self) -> String {
+        self.footer_done = true;
+        let index = self.max_block_index.map_or(0, |i| i + 1);
+        let footer = self.attribution.footer(&self.template);
+        let start = json!({
+            "type": "content_block_start",
+            "index": index,
+            "content_block": {"type": "text", "text": ""}
+        });
+        let delta = json!({
+            "type": "content_block_delta",
+            "index": index,
+            "delta": {"type": "text_delta", "text": footer}
+        });
+        let stop = json!({"type": "content_block_stop", "index": index});
+        [
+            ("content_block_start", start),
+            ("content_block_delta", delta),
+            ("content_block_stop", stop),
+        ]
+        .into_iter()
+        .map(|(name, data)| format!("event: {name}\ndata: {data}\n\n"))
+        .collect()
+    }
+
+    /// 处理一个完整事件（不含结尾空行），返回要发送的内容
+    fn process(&mut self, event: &str) -> String {
+        let Some(data) = event_data(event) else {
+            return format!("{event}\n\n");
+        };
+        if data.trim() == "[DONE]" {
+            let mut out = String::new();
+            if !self.footer_done && self.flavor == SseFlavor::OpenAi {
+                out.push_str(&self.openai_footer_chunk());
+            }
+            out.push_str(&format!("{event}\n\n"));
+            return out;
+        }
+        let Ok(mut value) = serde_json::from_str::<Value>(&data) else {
+            return format!("{event}\n\n");
+        };
+        self.attribution.fill_model(&value);
+
+        let mut out = String::new();
+        if !self.footer_done {
+            match self.flavor {
+                SseFlavor::OpenAi => {
+                    let finishing = value
+                        .pointer("/choices/0/finish_reason")
+                        .is_some_and(|reason| !reason.is_null());
+                    self.last_chunk = Some(value.clone());
+                    if finishing {
+                        out.push_str(&self.openai_footer_chunk());
+                    }
+                }
+                SseFlavor::Anthropic => match value.get("type").and_then(Value::as_str) {
+                    Some("content_block_start") => {
+                        if let Some(index) = value.get("index").and_then(Value::as_u64) {
+                            self.max_block_index =
+                                Some(self.max_block_index.map_or(index, |max| max.max(index)));
+                        }
+                    }
+                    Some("message_delta") | Some("message_stop") => {
+                        out.push_str(&self.anthropic_footer_events());
+                    }
+                    _ => {}
+                },
+            }
+        }
+
+        if !self.metadata_done {
+            if let Some(object) = value.as_object_mut() {
+                object.insert(ATTRIBUTION_FIELD.to_string(), self.attribution.metadata());
+                self.metadata_done = true;
+                out.push_str(&replace_event_data(event, &value));
+                return out;
+            }
+        }
+        out.push_str(&format!("{event}\n\n"));
+        out
+    }
+
+    /// 上游未发送结束事件时补发页脚（仅 OpenAI 格式）
+    fn finish(&mut self) -> Option<String> {
+        (!self.footer_done && self.flavor == SseFlavor::OpenAi).then(|| self.openai_footer_chunk())
+    }
+}
+
+fn annotate_stream<S, E>(
+    upstream: S,
+    mut annotator: StreamAnnotator,
+) -> impl futures::Stream<Item = Result<Bytes, E>> + Send
+where
+    S: futures::Stream<Item = Result<Bytes, E>> + Send + 'static,
+    E: Send + 'static,
+{
+    async_stream::stream! {
+        let mut upstream = Box::pin(upstream);
+        let mut events = SseEventSplitter::default();
+        loop {
+            match upstream.next().await {
+                Some(Ok(bytes)) => {
+                    events.push(&bytes);
+                    let mut out = String::new();
+                    while let Some(event) = events.next_text() {
+                        if !event.is_empty() {
+                            out.push_str(&annotator.process(&event));
+                        }
+                    }
+                    if !out.is_empty() {
+                        yield Ok(Bytes::from(out));
+                    }
+                }
+                Some(Err(e)) => {
+                    if !events.is_empty() {
+                        yield Ok(events.take_rest());
+                    }
+                    yield Err(e);
+                    break;
+                }
+                None => {
+                    let rest = events.take_rest_text();
+                    let mut out = if rest.is_empty() {
+                        String::new()
+                    } else {
+                        annotator.process(&rest)
+                    };
+                    if let Some(footer) = annotator.finish() {
+                        out.push_str(&footer);
+                    }
+                    if !out.is_empty() {
+                        yield Ok(Bytes::from(out));
+                    }
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// 按标注方式处理成功响应，其他响应原样返回
+pub async fn apply(
+    settings: &AttributionSettings,
+    mode: AttributionMode,
+    flavor: SseFlavor,
+    response: Response,
+) -> Response {
+    if mode == AttributionMode::Off || !response.status().is_success() {
+        return response;
+    }
+    let mut attribution = Attribution::from_response(&response);
+
+    if content_type_starts_with(&response, "text/event-stream") {
+        let (mut parts, body) = response.into_parts();
+        let annotator = StreamAnnotator::new(flavor, mode, attribution, &settings.footer_template);
+        let stream = annotate_stream(body.into_data_stream(), annotator);
+        parts.headers.remove(header::CONTENT_LENGTH);
+        return Response::from_parts(parts, Body::from_stream(stream));
+    }
+    if !content_type_starts_with(&response, "application/json") {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("[ATTRIBUTION] 读取响应失败: {}", e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+    let Ok(mut value) = serde_json::from_slice::<Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    attribution.fill_model(&value);
+    annotate_json(
+        &mut value,
+        flavor,
+        mode,
+        &attribution,
+        &settings.footer_template,
+    );
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attribution() -> Attribution {
+        Attribution {
+            provider: Some("kiro".to_string()),
+            model: Some("claude-sonnet-4-5".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_annotate_json_metadata_and_footer() {
+        let mut value = json!({"model": "alias", "choices": [{"message": {"content": "hi"}}]});
+        annotate_json(
+            &mut value,
+            SseFlavor::OpenAi,
+            AttributionMode::Metadata,
+            &attribution(),
+            "",
+        );
+        assert_eq!(value[ATTRIBUTION_FIELD]["provider"], "kiro");
+
+        annotate_json(
+            &mut value,
+            SseFlavor::OpenAi,
+            AttributionMode::Footer,
+            &attribution(),
+            " [{model}@{provider}]",
+        );
+        assert_eq!(
+            value["choices"][0]["message"]["content"],
+            "hi [claude-sonnet-4-5@kiro]"
+        );
+
+        let mut value = json!({"content": [
+            {"type": "text", "text": "a"},
+            {"type": "tool_use", "id": "t"}
+        ]});
+        annotate_json(
+            &mut value,
+            SseFlavor::Anthropic,
+            AttributionMode::Footer,
+            &attribution(),
+            "!",
+        );
+        assert_eq!(value["content"][0]["text"], "a!");
+    }
+
+    #[test]
+    fn test_stream_openai_footer_before_finish_chunk() {
+        let mut annotator = StreamAnnotator::new(
+            SseFlavor::OpenAi,
+            AttributionMode::Footer,
+            attribution(),
+            "|{provider}",
+        );
+        let first = annotator.process(
+            r#"data: {"id":"c1","model":"m","choices":[{"index":0,"delta":{"content":"x"},"finish_reason":null}]}"#,
+        );
+        assert!(!first.contains("|kiro"));
+        let finishing = annotator.process(
+            r#"data: {"id":"c1","model":"m","choices":[{"index":0,"delta":{},"finish_reason":"stop"}]}"#,
+        );
+        let footer_pos = finishing.find("|kiro").expect("应补发页脚");
+        assert!(footer_pos < finishing.find("\"stop\"").unwrap());
+        assert!(annotator
+            .process("data: [DONE]")
+            .starts_with("data: [DONE]"));
+        assert!(annotator.finish().is_none());
+    }
+
+    #[test]
+    fn test_stream_anthropic_footer_and_metadata() {
+        let mut annotator = StreamAnnotator::new(
+            SseFlavor::Anthropic,
+            AttributionMode::Footer,
+            attribution(),
+            "|f",
+        );
+        annotator.process(
+            "event: content_block_start\ndata: {\"type\":\"content_block_start\",\"index\":1,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}",
+        );
+        let out = annotator.process(
+            "event: message_delta\ndata: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"}}",
+        );
+        assert!(out.starts_with("event: content_block_start\ndata: {"));
+        assert!(out.contains("\"index\":2"));
+        assert!(out.contains("|f"));
+        assert!(out.ends_with("\"end_turn\"}}\n\n"));
+
+        let mut annotator = StreamAnnotator::new(
+            SseFlavor::Anthropic,
+            AttributionMode::Metadata,
+            Attribution {
+                provider: None,
+                model: None,
+            },
+            "",
+        );
+        let out = annotator.process(
+            "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"model\":\"claude-x\"}}",
+        );
+        assert!(out.starts_with("event: message_start\ndata: "));
+        let data: Value = serde_json::from_str(&event_data(out.trim_end()).unwrap()).unwrap();
+        assert_eq!(
+            data[ATTRIBUTION_FIELD],
+            json!({"provider": null, "model": "claude-x"})
+        );
+        let next = annotator.process("event: ping\ndata: {\"type\":\"ping\"}");
+        assert!(!next.contains(ATTRIBUTION_FIELD));
+    }
+}
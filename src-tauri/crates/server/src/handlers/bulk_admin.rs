@@ -0,0 +1,502 @@
+//! 批量管理操作接口
+//!
+//! 仅主 API Key（或通过管理接口 OIDC 认证的请求）可调用，替代界面上逐个凭证/Key 的重复操作：
+//! - `POST /admin/bulk/disable-failing`：禁用错误率达到阈值的凭证
+//! - `POST /admin/bulk/clear-cooldowns`：清除凭证的不健康状态与连续错误计数
+//! - `POST /admin/bulk/health-check`：对选中的凭证重新执行健康检查
+//! - `POST /admin/bulk/rotate-keys`：立即轮换全部受限 Key，旧 Key 保留宽限期
+//!
+//! 凭证按 `provider_type`、`name`（支持 `*` 通配，可用命名约定充当标签，如 `team-a-*`）
+//! 与 `uuids` 筛选，条件均可选。`dry_run` 为 `true` 时只返回将被处理的条目。
+//! 响应包含每个条目的处理结果，单个条目失败不影响其它条目。
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use futures::StreamExt;
+use lime_core::database::DbConnection;
+use lime_core::i18n::{self, MessageCode};
+use lime_core::models::injection_types::pattern_matches;
+use lime_core::models::provider_pool_model::CredentialDisplay;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::auth::oidc::AdminIdentity;
+use crate::handlers::verify_admin_key;
+use crate::AppState;
+
+/// 同时进行的健康检查数
+const HEALTH_CHECK_CONCURRENCY: usize = 4;
+
+/// 批量轮换受限 Key 的默认宽限期（小时）
+const DEFAULT_ROTATION_GRACE_HOURS: u64 = 24;
+
+/// 凭证筛选条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CredentialSelector {
+    #[serde(default)]
+    pub provider_type: Option<String>,
+    /// 凭证名称，支持 `*` 通配
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub uuids: Vec<String>,
+}
+
+impl CredentialSelector {
+    fn matches(&self, provider_type: &str, name: Option<&str>, uuid: &str) -> bool {
+        let type_matches = match &self.provider_type {
+            Some(expected) => expected.eq_ignore_ascii_case(provider_type),
+            None => true,
+        };
+        let name_matches = match (&self.name, name) {
+            (Some(pattern), Some(name)) => pattern_matches(pattern, name),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        let uuid_matches = self.uuids.is_empty() || self.uuids.iter().any(|id| id == uuid);
+        type_matches && name_matches && uuid_matches
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DisableFailingRequest {
+    /// 错误率阈值（0-1），错误率 = 错误次数 / 使用次数
+    pub min_error_rate: f64,
+    /// 使用次数少于该值的凭证不参与判断，避免样本过少时误禁用
+    #[serde(default = "default_min_requests")]
+    pub min_requests: u64,
+    #[serde(flatten)]
+    pub selector: CredentialSelector,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+fn default_min_requests() -> u64 {
+    10
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CredentialBulkRequest {
+    #[serde(flatten)]
+    pub selector: CredentialSelector,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RotateKeysRequest {
+    /// 旧 Key 的宽限期（小时），期间新旧 Key 同时有效
+    #[serde(default)]
+    pub grace_hours: Option<u64>,
+}
+
+/// 单个条目的处理状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkItemStatus {
+    Ok,
+    Skipped,
+    Failed,
+}
+
+/// 单个条目的处理结果
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkItemResult {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub status: BulkItemStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<Value>,
+}
+
+impl BulkItemResult {
+    pub(crate) fn new(id: &str, name: Option<&str>, status: BulkItemStatus) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.map(str::to_string),
+            status,
+            message: None,
+            detail: None,
+        }
+    }
+
+    pub(crate) fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    pub(crate) fn with_detail(mut self, detail: Value) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+
+    pub(crate) fn from_result(id: &str, name: Option<&str>, result: Result<(), String>) -> Self {
+        match result {
+            Ok(()) => Self::new(id, name, BulkItemStatus::Ok),
+            Err(e) => Self::new(id, name, BulkItemStatus::Failed).with_message(e),
+        }
+    }
+}
+
+/// 批量操作报告
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkReport {
+    pub operation: &'static str,
+    pub dry_run: bool,
+    pub succeeded: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub results: Vec<BulkItemResult>,
+}
+
+impl BulkReport {
+    pub(crate) fn new(
+        operation: &'static str,
+        dry_run: bool,
+        results: Vec<BulkItemResult>,
+    ) -> Self {
+        let count = |status| results.iter().filter(|r| r.status == status).count();
+        Self {
+            operation,
+            dry_run,
+            succeeded: count(BulkItemStatus::Ok),
+            skipped: count(BulkItemStatus::Skipped),
+            failed: count(BulkItemStatus::Failed),
+            results,
+        }
+    }
+}
+
+/// 错误率：错误次数 / 使用次数（上限为 1），未使用过的凭证为 0
+fn error_rate(usage_count: u64, error_count: u32) -> f64 {
+    if usage_count == 0 {
+        return 0.0;
+    }
+    (f64::from(error_count) / usage_count as f64).min(1.0)
+}
+
+fn error_response(status: StatusCode, message: String, error_type: &str) -> Response {
+    (
+        status,
+        Json(json!({
+            "error": {"message": message, "type": error_type}
+        })),
+    )
+        .into_response()
+}
+
+fn database(state: &AppState) -> Result<&DbConnection, Response> {
+    state.db.as_ref().ok_or_else(|| {
+        error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            i18n::t(MessageCode::DatabaseUnavailable).to_string(),
+            "service_unavailable",
+        )
+    })
+}
+
+/// 列出符合筛选条件的凭证
+pub(crate) fn select_credentials(
+    state: &AppState,
+    db: &DbConnection,
+    selector: &CredentialSelector,
+) -> Result<Vec<CredentialDisplay>, Response> {
+    let overview = state
+        .pool_service
+        .get_overview(db)
+        .map_err(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e, "server_error"))?;
+    Ok(overview
+        .into_iter()
+        .flat_map(|group| group.credentials)
+        .filter(|cred| selector.matches(&cred.provider_type, cred.name.as_deref(), &cred.uuid))
+        .collect())
+}
+
+/// `POST /admin/bulk/disable-failing`
+pub async fn disable_failing_credentials(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+    Json(request): Json<DisableFailingRequest>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    if !(0.0..=1.0).contains(&request.min_error_rate) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "min_error_rate 必须在 0-1 之间".to_string(),
+            "invalid_request_error",
+        );
+    }
+    let db = match database(&state) {
+        Ok(db) => db,
+        Err(response) => return response,
+    };
+    let credentials = match select_credentials(&state, db, &request.selector) {
+        Ok(credentials) => credentials,
+        Err(response) => return response,
+    };
+
+    let mut results = Vec::new();
+    for cred in credentials {
+        if cred.usage_count < request.min_requests {
+            continue;
+        }
+        let rate = error_rate(cred.usage_count, cred.error_count);
+        if rate < request.min_error_rate {
+            continue;
+        }
+        let name = cred.name.as_deref();
+        let detail = json!({ "error_rate": rate, "usage_count": cred.usage_count });
+        let result = if cred.is_disabled {
+            BulkItemResult::new(&cred.uuid, name, BulkItemStatus::Skipped).with_message("已禁用")
+        } else if request.dry_run {
+            BulkItemResult::new(&cred.uuid, name, BulkItemStatus::Ok)
+        } else {
+            let outcome = state
+                .pool_service
+                .update_credential(db, &cred.uuid, None, Some(true), None, None, None, None)
+                .map(|_| ());
+            BulkItemResult::from_result(&cred.uuid, name, outcome)
+        };
+        results.push(result.with_detail(detail));
+    }
+
+    let report = BulkReport::new("disable_failing", request.dry_run, results);
+    tracing::info!(
+        "[BULK_ADMIN] 禁用高错误率凭证: 阈值={} 成功={} 失败={} dry_run={}",
+        request.min_error_rate,
+        report.succeeded,
+        report.failed,
+        report.dry_run
+    );
+    Json(report).into_response()
+}
+
+/// `POST /admin/bulk/clear-cooldowns`
+pub async fn clear_credential_cooldowns(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+    Json(request): Json<CredentialBulkRequest>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    let db = match database(&state) {
+        Ok(db) => db,
+        Err(response) => return response,
+    };
+    let credentials = match select_credentials(&state, db, &request.selector) {
+        Ok(credentials) => credentials,
+        Err(response) => return response,
+    };
+
+    let results = credentials
+        .iter()
+        .filter(|cred| !cred.is_healthy || cred.error_count > 0)
+        .map(|cred| {
+            let name = cred.name.as_deref();
+            if request.dry_run {
+                return BulkItemResult::new(&cred.uuid, name, BulkItemStatus::Ok);
+            }
+            let outcome = state.credential_pool.mark_healthy(&cred.uuid, None);
+            BulkItemResult::from_result(&cred.uuid, name, outcome)
+        })
+        .collect();
+
+    let report = BulkReport::new("clear_cooldowns", request.dry_run, results);
+    tracing::info!(
+        "[BULK_ADMIN] 清除凭证冷却: 成功={} 失败={} dry_run={}",
+        report.succeeded,
+        report.failed,
+        report.dry_run
+    );
+    Json(report).into_response()
+}
+
+/// `POST /admin/bulk/health-check`
+pub async fn bulk_health_check(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+    Json(request): Json<CredentialBulkRequest>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    let db = match database(&state) {
+        Ok(db) => db,
+        Err(response) => return response,
+    };
+    let credentials = match select_credentials(&state, db, &request.selector) {
+        Ok(credentials) => credentials,
+        Err(response) => return response,
+    };
+
+    let dry_run = request.dry_run;
+    let results: Vec<BulkItemResult> = futures::stream::iter(credentials)
+        .map(|cred| {
+            let state = state.clone();
+            async move {
+                let name = cred.name.as_deref();
+                if cred.is_disabled {
+                    return BulkItemResult::new(&cred.uuid, name, BulkItemStatus::Skipped)
+                        .with_message("已禁用");
+                }
+                if dry_run {
+                    return BulkItemResult::new(&cred.uuid, name, BulkItemStatus::Ok);
+                }
+                let Some(db) = state.db.as_ref() else {
+                    return BulkItemResult::new(&cred.uuid, name, BulkItemStatus::Failed)
+                        .with_message(i18n::t(MessageCode::DatabaseUnavailable));
+                };
+                match state
+                    .pool_service
+                    .check_credential_health(db, &cred.uuid)
+                    .await
+                {
+                    Ok(check) => {
+                        let status = if check.success {
+                            BulkItemStatus::Ok
+                        } else {
+                            BulkItemStatus::Failed
+                        };
+                        let mut result =
+                            BulkItemResult::new(&cred.uuid, name, status).with_detail(json!({
+                                "model": check.model,
+                                "duration_ms": check.duration_ms,
+                            }));
+                        result.message = check.message;
+                        result
+                    }
+                    Err(e) => BulkItemResult::new(&cred.uuid, name, BulkItemStatus::Failed)
+                        .with_message(e),
+                }
+            }
+        })
+        .buffered(HEALTH_CHECK_CONCURRENCY)
+        .collect()
+        .await;
+
+    let report = BulkReport::new("health_check", dry_run, results);
+    tracing::info!(
+        "[BULK_ADMIN] 批量健康检查: 通过={} 未通过={} 跳过={} dry_run={}",
+        report.succeeded,
+        report.failed,
+        report.skipped,
+        report.dry_run
+    );
+    Json(report).into_response()
+}
+
+/// `POST /admin/bulk/rotate-keys`
+///
+/// 替换 Key 的明文只在本次响应中返回，需要分发给对应的持有者
+pub async fn rotate_scoped_keys(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+    Json(request): Json<RotateKeysRequest>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    let now = chrono::Utc::now();
+    let before = state.scoped_keys.list();
+    let grace_hours = request.grace_hours.unwrap_or(DEFAULT_ROTATION_GRACE_HOURS);
+    let issued = match state.scoped_keys.rotate_all(grace_hours, now) {
+        Ok(issued) => issued,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, e, "server_error"),
+    };
+
+    let results = before
+        .iter()
+        .map(|record| {
+            let label = Some(record.label.as_str());
+            let replacement = issued
+                .iter()
+                .find(|item| item.record.predecessor_id.as_deref() == Some(record.id.as_str()));
+            match replacement {
+                Some(item) => BulkItemResult::new(&record.id, label, BulkItemStatus::Ok)
+                    .with_detail(json!({
+                        "successor_id": item.record.id,
+                        "api_key": item.api_key,
+                        "key_prefix": item.record.key_prefix,
+                        "expires_at": item.record.expires_at,
+                    })),
+                None if record.rotated_at.is_some() => {
+                    BulkItemResult::new(&record.id, label, BulkItemStatus::Skipped)
+                        .with_message("已在宽限期内")
+                }
+                None => BulkItemResult::new(&record.id, label, BulkItemStatus::Skipped)
+                    .with_message("已过期或已用尽"),
+            }
+        })
+        .collect();
+
+    let report = BulkReport::new("rotate_keys", false, results);
+    tracing::info!(
+        "[BULK_ADMIN] 批量轮换受限 Key: 轮换={} 跳过={} 宽限期={}h",
+        report.succeeded,
+        report.skipped,
+        grace_hours
+    );
+    Json(report).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selector_matches_type_name_pattern_and_uuids() {
+        let selector: CredentialSelector = serde_json::from_value(json!({
+            "provider_type": "Gemini",
+            "name": "team-a-*"
+        }))
+        .unwrap();
+        assert!(selector.matches("gemini", Some("team-a-01"), "u1"));
+        assert!(!selector.matches("gemini", Some("team-b-01"), "u1"));
+        assert!(!selector.matches("gemini", None, "u1"));
+        assert!(!selector.matches("kiro", Some("team-a-01"), "u1"));
+
+        let by_uuid = CredentialSelector {
+            uuids: vec!["u2".to_string()],
+            ..Default::default()
+        };
+        assert!(by_uuid.matches("kiro", None, "u2"));
+        assert!(!by_uuid.matches("kiro", None, "u1"));
+        assert!(CredentialSelector::default().matches("kiro", None, "u1"));
+    }
+
+    #[test]
+    fn test_error_rate_and_report_counts() {
+        assert_eq!(error_rate(0, 3), 0.0);
+        assert_eq!(error_rate(10, 5), 0.5);
+        assert_eq!(error_rate(2, 5), 1.0);
+
+        let report = BulkReport::new(
+            "clear_cooldowns",
+            false,
+            vec![
+                BulkItemResult::from_result("a", None, Ok(())),
+                BulkItemResult::from_result("b", None, Err("boom".to_string())),
+                BulkItemResult::new("c", None, BulkItemStatus::Skipped),
+            ],
+        );
+        assert_eq!((report.succeeded, report.failed, report.skipped), (1, 1, 1));
+        let value = serde_json::to_value(&report).unwrap();
+        assert_eq!(value["results"][1]["status"], "failed");
+        assert_eq!(value["results"][1]["message"], "boom");
+        assert!(value["results"][0].get("message").is_none());
+    }
+}
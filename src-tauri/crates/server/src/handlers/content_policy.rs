@@ -0,0 +1,710 @@
+//! 响应内容策略（出站过滤）
+//!
+//! 与提示词防火墙对应，在返回给客户端前按 `server.content_policy` 检查生成的文本：
+//! - `annotate`：保留原文，响应中标注命中的规则
+//! - `redact`：将命中的内容替换为脱敏文本
+//! - `block`：以拦截提示代替回复（OpenAI `finish_reason: content_filter`，
+//!   Anthropic `stop_reason: refusal`）
+//!
+//! 流式响应按文本块暂存最近 `window_chars` 个字符，完整扫描暂存内容后只发送窗口之前
+//! 的部分，匹配跨越多个增量时仍能脱敏；拦截时已发送的内容无法撤回。
+//!
+//! 命中的规则在非流式响应中以 `x-lime-content-policy` 头与 `lime_content_policy` 字段
+//! 返回，流式响应附加在结束事件上。
+
+use std::collections::BTreeMap;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, HeaderName, HeaderValue},
+    response::Response,
+};
+use bytes::Bytes;
+use futures::StreamExt;
+use lime_core::config::{
+    ContentPolicyAction, ContentPolicyMatch, ContentPolicyRule, ContentPolicySettings,
+};
+use regex::{Regex, RegexBuilder};
+use serde_json::{json, Value};
+
+use super::attribution::{event_data, replace_event_data};
+use super::fake_stream::SseFlavor;
+use crate::sse::SseEventSplitter;
+
+/// 转换时读取的最大响应体
+const MAX_BODY_BYTES: usize = 32 * 1024 * 1024;
+/// 命中规则响应头
+pub const CONTENT_POLICY_HEADER: &str = "x-lime-content-policy";
+/// 附加到响应中的字段名
+const CONTENT_POLICY_FIELD: &str = "lime_content_policy";
+
+#[derive(Debug, Clone)]
+struct PolicyRule {
+    name: String,
+    regex: Regex,
+    action: ContentPolicyAction,
+}
+
+/// 单段文本的检查结果
+#[derive(Debug, Clone, PartialEq)]
+struct Filtered {
+    text: String,
+    /// 命中拦截规则的名称
+    blocked: Option<String>,
+    /// 命中的规则名称
+    hits: Vec<String>,
+}
+
+/// 编译后的响应内容策略
+#[derive(Debug, Clone, Default)]
+pub struct ContentPolicy {
+    enabled: bool,
+    rules: Vec<PolicyRule>,
+    window_chars: usize,
+    redaction: String,
+    block_message: String,
+}
+
+fn compile(rule: &ContentPolicyRule) -> Result<Regex, regex::Error> {
+    let pattern = match rule.kind {
+        ContentPolicyMatch::Regex => rule.pattern.clone(),
+        ContentPolicyMatch::Keyword => regex::escape(&rule.pattern),
+    };
+    RegexBuilder::new(&pattern).case_insensitive(true).build()
+}
+
+impl ContentPolicy {
+    pub fn new(settings: &ContentPolicySettings) -> Self {
+        let rules: Vec<PolicyRule> = settings
+            .rules
+            .iter()
+            .filter(|rule| !rule.pattern.is_empty())
+            .filter_map(|rule| match compile(rule) {
+                Ok(regex) => Some(PolicyRule {
+                    name: rule.name.clone(),
+                    regex,
+                    action: rule.action,
+                }),
+                Err(e) => {
+                    tracing::warn!(
+                        "[CONTENT_POLICY] 规则 {} 正则无效，已忽略: {}",
+                        rule.name,
+                        e
+                    );
+                    None
+                }
+            })
+            .collect();
+        Self {
+            enabled: settings.enabled && !rules.is_empty(),
+            rules,
+            window_chars: settings.window_chars,
+            redaction: settings.redaction.clone(),
+            block_message: settings.block_message.clone(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn filter(&self, text: &str) -> Filtered {
+        let mut filtered = Filtered {
+            text: text.to_string(),
+            blocked: None,
+            hits: Vec::new(),
+        };
+        for rule in &self.rules {
+            if !rule.regex.is_match(&filtered.text) {
+                continue;
+            }
+            filtered.hits.push(rule.name.clone());
+            match rule.action {
+                ContentPolicyAction::Annotate => {}
+                ContentPolicyAction::Redact => {
+                    filtered.text = rule
+                        .regex
+                        .replace_all(&filtered.text, regex::NoExpand(&self.redaction))
+                        .into_owned();
+                }
+                ContentPolicyAction::Block => {
+                    filtered.blocked = Some(rule.name.clone());
+                    return filtered;
+                }
+            }
+        }
+        filtered
+    }
+}
+
+fn push_hits(hits: &mut Vec<String>, new_hits: Vec<String>) {
+    for hit in new_hits {
+        if !hits.contains(&hit) {
+            hits.push(hit);
+        }
+    }
+}
+
+fn policy_field(hits: &[String], blocked: bool) -> Value {
+    json!({ "rules": hits, "blocked": blocked })
+}
+
+fn content_type_starts_with(response: &Response, prefix: &str) -> bool {
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with(prefix))
+}
+
+/// 检查完整 JSON 响应，返回命中的规则与是否拦截
+fn filter_json(
+    policy: &ContentPolicy,
+    value: &mut Value,
+    flavor: SseFlavor,
+) -> (Vec<String>, bool) {
+    let mut hits = Vec::new();
+    let mut blocked = false;
+    let mut texts: Vec<&mut String> = Vec::new();
+    match flavor {
+        SseFlavor::OpenAi => {
+            if let Some(choices) = value.get_mut("choices").and_then(Value::as_array_mut) {
+                for choice in choices {
+                    match choice.pointer_mut("/message/content") {
+                        Some(Value::String(text)) => texts.push(text),
+                        Some(Value::Array(parts)) => {
+                            for part in parts {
+                                if let Some(Value::String(text)) = part.get_mut("text") {
+                                    texts.push(text);
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        SseFlavor::Anthropic => {
+            if let Some(blocks) = value.get_mut("content").and_then(Value::as_array_mut) {
+                for block in blocks {
+                    if block.get("type").and_then(Value::as_str) != Some("text") {
+                        continue;
+                    }
+                    if let Some(Value::String(text)) = block.get_mut("text") {
+                        texts.push(text);
+                    }
+                }
+            }
+        }
+    }
+    for text in texts {
+        let filtered = policy.filter(text);
+        push_hits(&mut hits, filtered.hits);
+        if filtered.blocked.is_some() {
+            blocked = true;
+            break;
+        }
+        *text = filtered.text;
+    }
+    if !blocked {
+        return (hits, false);
+    }
+
+    match flavor {
+        SseFlavor::OpenAi => {
+            if let Some(choices) = value.get_mut("choices").and_then(Value::as_array_mut) {
+                for choice in choices {
+                    choice["message"] = json!({
+                        "role": "assistant",
+                        "content": policy.block_message
+                    });
+                    choice["finish_reason"] = json!("content_filter");
+                }
+            }
+        }
+        SseFlavor::Anthropic => {
+            value["content"] = json!([{ "type": "text", "text": policy.block_message }]);
+            value["stop_reason"] = json!("refusal");
+        }
+    }
+    (hits, true)
+}
+
+/// 按字符数切分：返回（可发送部分，保留的最后 `keep` 个字符）
+fn split_release(text: &str, keep: usize) -> (String, String) {
+    let total = text.chars().count();
+    if total <= keep {
+        return (String::new(), text.to_string());
+    }
+    let at = text
+        .char_indices()
+        .nth(total - keep)
+        .map_or(text.len(), |(i, _)| i);
+    (text[..at].to_string(), text[at..].to_string())
+}
+
+/// 流式响应过滤状态
+struct StreamFilter {
+    policy: ContentPolicy,
+    flavor: SseFlavor,
+    /// 尚未发送的文本（OpenAI 按 choice 序号，Anthropic 按内容块序号）
+    pending: BTreeMap<u64, String>,
+    hits: Vec<String>,
+    /// 已拦截，之后的上游事件全部丢弃
+    done: bool,
+    /// 最近一个 OpenAI chunk（补发事件时沿用 id / model / created）
+    last_chunk: Option<Value>,
+}
+
+impl StreamFilter {
+    fn new(policy: ContentPolicy, flavor: SseFlavor) -> Self {
+        Self {
+            policy,
+            flavor,
+            pending: BTreeMap::new(),
+            hits: Vec::new(),
+            done: false,
+            last_chunk: None,
+        }
+    }
+
+    /// 追加增量并检查暂存文本，返回可发送的部分；命中拦截规则时返回 None
+    fn feed(&mut self, index: u64, delta: &str, flush: bool) -> Option<String> {
+        let pending = self.pending.entry(index).or_default();
+        pending.push_str(delta);
+        if pending.is_empty() {
+            return Some(String::new());
+        }
+        let filtered = self.policy.filter(pending);
+        let blocked = filtered.blocked.is_some();
+        let text = filtered.text;
+        push_hits(&mut self.hits, filtered.hits);
+        if blocked {
+            return None;
+        }
+        let keep = if flush { 0 } else { self.policy.window_chars };
+        let (release, rest) = split_release(&text, keep);
+        if rest.is_empty() {
+            self.pending.remove(&index);
+        } else {
+            self.pending.insert(index, rest);
+        }
+        Some(release)
+    }
+
+    fn openai_chunk(&self, choice: Value) -> Value {
+        let last = self.last_chunk.as_ref();
+        let field = |name: &str| last.and_then(|chunk| chunk.get(name)).cloned();
+        json!({
+            "id": field("id"),
+            "object": "chat.completion.chunk",
+            "created": field("created"),
+            "model": field("model"),
+            "choices": [choice]
+        })
+    }
+
+    fn openai_blocked(&mut self) -> String {
+        self.done = true;
+        let mut chunk = self.openai_chunk(json!({
+            "index": 0,
+            "delta": {"content": self.policy.block_message},
+            "finish_reason": "content_filter"
+        }));
+        chunk[CONTENT_POLICY_FIELD] = policy_field(&self.hits, true);
+        format!("data: {chunk}\n\ndata: [DONE]\n\n")
+    }
+
+    fn anthropic_blocked(&mut self, index: u64) -> String {
+        self.done = true;
+        let mut message_delta = json!({
+            "type": "message_delta",
+            "delta": {"stop_reason": "refusal", "stop_sequence": null}
+        });
+        message_delta[CONTENT_POLICY_FIELD] = policy_field(&self.hits, true);
+        let events = [
+            (
+                "content_block_delta",
+                json!({
+                    "type": "content_block_delta",
+                    "index": index,
+                    "delta": {"type": "text_delta", "text": self.policy.block_message}
+                }),
+            ),
+            (
+                "content_block_stop",
+                json!({"type": "content_block_stop", "index": index}),
+            ),
+            ("message_delta", message_delta),
+            ("message_stop", json!({"type": "message_stop"})),
+        ];
+        events
+            .into_iter()
+            .map(|(name, data)| format!("event: {name}\ndata: {data}\n\n"))
+            .collect()
+    }
+
+    /// 发送所有暂存文本（OpenAI 上游未发送结束 chunk 时）
+    fn openai_flush(&mut self) -> String {
+        let pending = std::mem::take(&mut self.pending);
+        let mut out = String::new();
+        for (index, text) in pending {
+            let filtered = self.policy.filter(&text);
+            push_hits(&mut self.hits, filtered.hits);
+            if filtered.blocked.is_some() {
+                return self.openai_blocked();
+            }
+            let chunk = self.openai_chunk(json!({
+                "index": index,
+                "delta": {"content": filtered.text},
+                "finish_reason": null
+            }));
+            out.push_str(&format!("data: {chunk}\n\n"));
+        }
+        out
+    }
+
+    fn process_openai(&mut self, event: &str, mut value: Value) -> String {
+        let mut finishing = false;
+        if let Some(choices) = value.get_mut("choices").and_then(Value::as_array_mut) {
+            for choice in choices {
+                let index = choice.get("index").and_then(Value::as_u64).unwrap_or(0);
+                let flush = choice
+                    .get("finish_reason")
+                    .is_some_and(|reason| !reason.is_null());
+                finishing |= flush;
+                let delta = choice
+                    .pointer("/delta/content")
+                    .and_then(Value::as_str)
+                    .unwrap_or("")
+                    .to_string();
+                if delta.is_empty() && !(flush && self.pending.contains_key(&index)) {
+                    continue;
+                }
+                let Some(release) = self.feed(index, &delta, flush) else {
+                    return self.openai_blocked();
+                };
+                if let Some(delta) = choice.get_mut("delta").and_then(Value::as_object_mut) {
+                    delta.insert("content".to_string(), Value::String(release));
+                } else {
+                    choice["delta"] = json!({ "content": release });
+                }
+            }
+        }
+        self.last_chunk = Some(value.clone());
+        if finishing && !self.hits.is_empty() {
+            value[CONTENT_POLICY_FIELD] = policy_field(&self.hits, false);
+        }
+        replace_event_data(event, &value)
+    }
+
+    fn process_anthropic(&mut self, event: &str, mut value: Value) -> String {
+        let event_type = value
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .to_string();
+        match event_type.as_str() {
+            "content_block_delta"
+                if value.pointer("/delta/type").and_then(Value::as_str) == Some("text_delta") =>
+            {
+                let index = value.get("index").and_then(Value::as_u64).unwrap_or(0);
+                let delta = value
+                    .pointer("/delta/text")
+                    .and_then(Value::as_str)
+                    .unwrap_or("")
+                    .to_string();
+                let Some(release) = self.feed(index, &delta, false) else {
+                    return self.anthropic_blocked(index);
+                };
+                value["delta"]["text"] = Value::String(release);
+                replace_event_data(event, &value)
+            }
+            "content_block_stop" => {
+                let index = value.get("index").and_then(Value::as_u64).unwrap_or(0);
+                if !self.pending.contains_key(&index) {
+                    return format!("{event}\n\n");
+                }
+                let Some(release) = self.feed(index, "", true) else {
+                    return self.anthropic_blocked(index);
+                };
+                let delta = json!({
+                    "type": "content_block_delta",
+                    "index": index,
+                    "delta": {"type": "text_delta", "text": release}
+                });
+                format!("event: content_block_delta\ndata: {delta}\n\n{event}\n\n")
+            }
+            "message_delta" if !self.hits.is_empty() => {
+                value[CONTENT_POLICY_FIELD] = policy_field(&self.hits, false);
+                replace_event_data(event, &value)
+            }
+            _ => format!("{event}\n\n"),
+        }
+    }
+
+    /// 处理一个完整事件（不含结尾空行），返回要发送的内容
+    fn process(&mut self, event: &str) -> String {
+        if self.done {
+            return String::new();
+        }
+        let Some(data) = event_data(event) else {
+            return format!("{event}\n\n");
+        };
+        if data.trim() == "[DONE]" {
+            let mut out = String::new();
+            if self.flavor == SseFlavor::OpenAi && !self.pending.is_empty() {
+                out.push_str(&self.openai_flush());
+                if self.done {
+                    return out;
+                }
+            }
+            out.push_str(&format!("{event}\n\n"));
+            return out;
+        }
+        let Ok(value) = serde_json::from_str::<Value>(&data) else {
+            return format!("{event}\n\n");
+        };
+        match self.flavor {
+            SseFlavor::OpenAi => self.process_openai(event, value),
+            SseFlavor::Anthropic => self.process_anthropic(event, value),
+        }
+    }
+
+    /// 上游结束时发送剩余的暂存文本
+    fn finish(&mut self) -> String {
+        if self.done || self.flavor != SseFlavor::OpenAi || self.pending.is_empty() {
+            return String::new();
+        }
+        self.openai_flush()
+    }
+}
+
+fn filter_stream<S, E>(
+    upstream: S,
+    mut filter: StreamFilter,
+) -> impl futures::Stream<Item = Result<Bytes, E>> + Send
+where
+    S: futures::Stream<Item = Result<Bytes, E>> + Send + 'static,
+    E: Send + 'static,
+{
+    async_stream::stream! {
+        let mut upstream = Box::pin(upstream);
+        let mut events = SseEventSplitter::default();
+        loop {
+            match upstream.next().await {
+                Some(Ok(bytes)) => {
+                    events.push(&bytes);
+                    let mut out = String::new();
+                    while let Some(event) = events.next_text() {
+                        if !event.is_empty() {
+                            out.push_str(&filter.process(&event));
+                        }
+                    }
+                    if !out.is_empty() {
+                        yield Ok(Bytes::from(out));
+                    }
+                    if filter.done {
+                        // 已拦截，不再读取上游
+                        break;
+                    }
+                }
+                Some(Err(e)) => {
+                    yield Err(e);
+                    break;
+                }
+                None => {
+                    let rest = events.take_rest_text();
+                    let mut out = if rest.is_empty() {
+                        String::new()
+                    } else {
+                        filter.process(&rest)
+                    };
+                    out.push_str(&filter.finish());
+                    if !out.is_empty() {
+                        yield Ok(Bytes::from(out));
+                    }
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// 按内容策略处理成功响应，其他响应原样返回
+pub async fn apply(policy: &ContentPolicy, flavor: SseFlavor, response: Response) -> Response {
+    if !policy.is_enabled() || !response.status().is_success() {
+        return response;
+    }
+
+    if content_type_starts_with(&response, "text/event-stream") {
+        let (mut parts, body) = response.into_parts();
+        let filter = StreamFilter::new(policy.clone(), flavor);
+        let stream = filter_stream(body.into_data_stream(), filter);
+        parts.headers.remove(header::CONTENT_LENGTH);
+        return Response::from_parts(parts, Body::from_stream(stream));
+    }
+    if !content_type_starts_with(&response, "application/json") {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("[CONTENT_POLICY] 读取响应失败: {}", e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+    let Ok(mut value) = serde_json::from_slice::<Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    let (hits, blocked) = filter_json(policy, &mut value, flavor);
+    if hits.is_empty() {
+        return Response::from_parts(parts, Body::from(bytes));
+    }
+    tracing::warn!(
+        "[CONTENT_POLICY] 响应命中规则: {}{}",
+        hits.join(","),
+        if blocked { "（已拦截）" } else { "" }
+    );
+    if let Some(object) = value.as_object_mut() {
+        object.insert(
+            CONTENT_POLICY_FIELD.to_string(),
+            policy_field(&hits, blocked),
+        );
+    }
+    if let Ok(header_value) = HeaderValue::from_str(&hits.join(",")) {
+        parts
+            .headers
+            .insert(HeaderName::from_static(CONTENT_POLICY_HEADER), header_value);
+    }
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(window_chars: usize) -> ContentPolicy {
+        let rule = |name: &str, pattern: &str, kind, action| ContentPolicyRule {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            kind,
+            action,
+        };
+        ContentPolicy::new(&ContentPolicySettings {
+            enabled: true,
+            rules: vec![
+                rule(
+                    "secret",
+                    r"sk-[a-z0-9]{8}",
+                    ContentPolicyMatch::Regex,
+                    ContentPolicyAction::Redact,
+                ),
+                rule(
+                    "mention",
+                    "acme",
+                    ContentPolicyMatch::Keyword,
+                    ContentPolicyAction::Annotate,
+                ),
+                rule(
+                    "forbidden",
+                    "launch codes",
+                    ContentPolicyMatch::Keyword,
+                    ContentPolicyAction::Block,
+                ),
+            ],
+            window_chars,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_filter_json() {
+        let policy = policy(16);
+        let mut value = json!({
+            "choices": [{"message": {"content": "ACME key: sk-abcd1234"}, "finish_reason": "stop"}]
+        });
+        let (hits, blocked) = filter_json(&policy, &mut value, SseFlavor::OpenAi);
+        assert_eq!(hits, vec!["secret", "mention"]);
+        assert!(!blocked);
+        assert_eq!(
+            value["choices"][0]["message"]["content"],
+            "ACME key: [REDACTED]"
+        );
+
+        let mut value = json!({
+            "content": [{"type": "text", "text": "the launch codes are 0000"}],
+            "stop_reason": "end_turn"
+        });
+        let (_, blocked) = filter_json(&policy, &mut value, SseFlavor::Anthropic);
+        assert!(blocked);
+        assert_eq!(value["stop_reason"], "refusal");
+        assert_eq!(
+            value["content"][0]["text"],
+            "[response blocked by content policy]"
+        );
+    }
+
+    fn openai_event(content: &str, finish: Option<&str>) -> String {
+        let chunk = json!({
+            "id": "c1",
+            "object": "chat.completion.chunk",
+            "model": "m",
+            "choices": [{"index": 0, "delta": {"content": content}, "finish_reason": finish}]
+        });
+        format!("data: {chunk}")
+    }
+
+    fn streamed_text(out: &str) -> String {
+        out.split("\n\n")
+            .filter_map(event_data)
+            .filter_map(|data| serde_json::from_str::<Value>(&data).ok())
+            .filter_map(|v| {
+                v.pointer("/choices/0/delta/content")
+                    .or_else(|| v.pointer("/delta/text"))
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_stream_redacts_across_deltas() {
+        let mut filter = StreamFilter::new(policy(16), SseFlavor::OpenAi);
+        let mut out = String::new();
+        for piece in ["Your key is sk-ab", "cd1234 and ", "more text follows here"] {
+            out.push_str(&filter.process(&openai_event(piece, None)));
+        }
+        out.push_str(&filter.process(&openai_event("", Some("stop"))));
+        out.push_str(&filter.process("data: [DONE]"));
+        assert_eq!(
+            streamed_text(&out),
+            "Your key is [REDACTED] and more text follows here"
+        );
+        assert!(out.contains(CONTENT_POLICY_FIELD));
+        assert!(out.ends_with("data: [DONE]\n\n"));
+    }
+
+    #[test]
+    fn test_stream_block_anthropic() {
+        let mut filter = StreamFilter::new(policy(32), SseFlavor::Anthropic);
+        let delta = |text: &str| {
+            let data = json!({
+                "type": "content_block_delta",
+                "index": 0,
+                "delta": {"type": "text_delta", "text": text}
+            });
+            format!("event: content_block_delta\ndata: {data}")
+        };
+        let first = filter.process(&delta("Sure, the launch "));
+        assert_eq!(streamed_text(&first), "");
+        let blocked = filter.process(&delta("codes are 1234"));
+        assert!(blocked.contains("\"stop_reason\":\"refusal\""));
+        assert!(blocked.ends_with("event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n"));
+        assert!(filter.done);
+        assert!(filter.process("event: message_stop\ndata: {}").is_empty());
+    }
+}
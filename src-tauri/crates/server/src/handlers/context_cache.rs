@@ -0,0 +1,41 @@
+//! Gemini 上下文缓存接口
+//!
+//! 仅主 API Key（或通过管理接口 OIDC 认证的请求）可调用：
+//! - `GET /admin/context-cache`：有效缓存列表与创建、复用、命中 Token 统计
+//! - `DELETE /admin/context-cache`：清空本地登记，之后的请求重新创建缓存
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use lime_providers::context_cache;
+
+use crate::auth::oidc::AdminIdentity;
+use crate::handlers::verify_admin_key;
+use crate::AppState;
+
+/// `GET /admin/context-cache`
+pub async fn get_context_cache(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    Json(context_cache::status()).into_response()
+}
+
+/// `DELETE /admin/context-cache`
+pub async fn clear_context_cache(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    Json(serde_json::json!({ "cleared": context_cache::clear() })).into_response()
+}
@@ -0,0 +1,41 @@
+//! 转换器影子验证接口
+//!
+//! 仅主 API Key（或通过管理接口 OIDC 认证的请求）可调用：
+//! - `GET /admin/converter-shadow`：已注册的影子版本、比对统计与最近的差异
+//! - `DELETE /admin/converter-shadow`：清空统计与差异记录
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use lime_providers::converter::shadow;
+
+use crate::auth::oidc::AdminIdentity;
+use crate::handlers::verify_admin_key;
+use crate::AppState;
+
+/// `GET /admin/converter-shadow`
+pub async fn get_converter_shadow(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    Json(shadow::status()).into_response()
+}
+
+/// `DELETE /admin/converter-shadow`
+pub async fn clear_converter_shadow(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    Json(serde_json::json!({ "cleared": shadow::clear() })).into_response()
+}
@@ -18,7 +18,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::AppState;
 use lime_core::database::dao::api_key_provider::ApiKeyProviderDao;
-use lime_core::database::dao::provider_pool::ProviderPoolDao;
+use lime_core::database::pool_storage::{lock_pool, pool_storage};
 use lime_core::models::provider_pool_model::PoolProviderType;
 
 use super::api_key_provider_utils::{build_api_key_headers, collect_api_key_provider_ids};
@@ -334,13 +334,13 @@ async fn try_get_oauth_token(
 ) -> Result<Option<CredentialResponse>, CredentialApiError> {
     // 查询凭证
     let credential = {
-        let conn = db.lock().map_err(|e| CredentialApiError {
+        let conn = lock_pool(db).map_err(|e| CredentialApiError {
             error: "database_lock_error".to_string(),
             message: format!("数据库锁定失败: {e}"),
             status_code: 500,
         })?;
 
-        match ProviderPoolDao::get_by_uuid(&conn, uuid) {
+        match pool_storage().get_by_uuid(&conn, uuid) {
             Ok(Some(cred)) => cred,
             Ok(None) => return Ok(None),
             Err(_) => return Ok(None),
@@ -395,13 +395,13 @@ async fn try_get_oauth_token(
 
     // 重新查询凭证以获取更新后的 expires_at
     let updated_credential = {
-        let conn = db.lock().map_err(|e| CredentialApiError {
+        let conn = lock_pool(db).map_err(|e| CredentialApiError {
             error: "database_lock_error".to_string(),
             message: format!("数据库锁定失败: {e}"),
             status_code: 500,
         })?;
 
-        match ProviderPoolDao::get_by_uuid(&conn, uuid) {
+        match pool_storage().get_by_uuid(&conn, uuid) {
             Ok(Some(cred)) => cred,
             Ok(None) => return Ok(None),
             Err(_) => return Ok(None),
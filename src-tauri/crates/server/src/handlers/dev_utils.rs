@@ -0,0 +1,266 @@
+//! 开发辅助端点
+//!
+//! 面向 Shell 脚本的便捷接口，直接提交原始文本或 diff，无需构造聊天请求：
+//! - `POST /utils/summarize-diff`：总结代码变更
+//! - `POST /utils/commit-message`：生成 Git 提交信息
+//!
+//! 请求体可以是纯文本，也可以是 JSON `{ "input": "...", "instructions": "...", "model": "..." }`。
+//! 默认返回纯文本，`Accept: application/json` 时返回结构化结果。
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+
+use crate::handlers::embeddings::{
+    invalid_request, openai_provider_for, read_upstream_json, select_openai_credential,
+};
+use crate::handlers::{verify_inbound_api_key, verify_scoped_key_model};
+use crate::AppState;
+use lime_core::errors::GatewayErrorCode;
+use lime_server_utils::build_error_response_with_meta;
+
+/// JSON 形式的请求体
+#[derive(Debug, Default, Deserialize)]
+struct DevUtilsRequest {
+    #[serde(default, alias = "diff", alias = "text")]
+    input: String,
+    /// 追加到系统提示词后的额外要求
+    #[serde(default)]
+    instructions: Option<String>,
+    #[serde(default)]
+    model: Option<String>,
+}
+
+/// 解析请求体：JSON 对象按字段读取，其余按纯文本处理
+fn parse_request(headers: &HeaderMap, body: &[u8]) -> Result<DevUtilsRequest, String> {
+    let text = String::from_utf8_lossy(body);
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.contains("application/json"));
+    if is_json || text.trim_start().starts_with('{') {
+        match serde_json::from_str::<DevUtilsRequest>(&text) {
+            Ok(request) => return Ok(request),
+            Err(e) if is_json => return Err(format!("Invalid JSON body: {e}")),
+            // diff 内容恰好以 `{` 开头时按纯文本处理
+            Err(_) => {}
+        }
+    }
+    Ok(DevUtilsRequest {
+        input: text.into_owned(),
+        ..Default::default()
+    })
+}
+
+/// 按字符数截断输入，返回是否发生截断
+fn truncate_input(input: &str, max_chars: usize) -> (&str, bool) {
+    match input.char_indices().nth(max_chars) {
+        Some((index, _)) => (&input[..index], true),
+        None => (input, false),
+    }
+}
+
+/// 去掉模型回复外层的 Markdown 代码块
+fn strip_code_fence(output: &str) -> &str {
+    let trimmed = output.trim();
+    let Some(rest) = trimmed.strip_prefix("```") else {
+        return trimmed;
+    };
+    let Some(body) = rest.strip_suffix("```") else {
+        return trimmed;
+    };
+    // 跳过语言标记所在的首行
+    match body.find('\n') {
+        Some(index) => body[index + 1..].trim(),
+        None => body.trim(),
+    }
+}
+
+fn wants_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("application/json"))
+}
+
+async fn run_template(
+    state: AppState,
+    headers: HeaderMap,
+    body: Bytes,
+    prompt: String,
+    capability: &str,
+) -> Response {
+    if let Err(e) = verify_inbound_api_key(&headers, &state).await {
+        return e.into_response();
+    }
+    let settings = &state.dev_utils;
+    if !settings.enabled {
+        return build_error_response_with_meta(
+            StatusCode::NOT_FOUND.as_u16(),
+            "Dev utility endpoints are disabled",
+            None,
+            None,
+            Some(GatewayErrorCode::InvalidRequest),
+        );
+    }
+
+    let request = match parse_request(&headers, &body) {
+        Ok(request) => request,
+        Err(e) => return invalid_request(&e),
+    };
+    if request.input.trim().is_empty() {
+        return invalid_request("input is required");
+    }
+    let (input, truncated) = truncate_input(&request.input, settings.max_input_chars);
+    let model = request
+        .model
+        .clone()
+        .unwrap_or_else(|| settings.model.clone());
+    if let Err(e) = verify_scoped_key_model(&headers, &state, &model) {
+        return e.into_response();
+    }
+
+    let provider = headers
+        .get("x-provider-id")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_lowercase())
+        .unwrap_or_else(|| settings.provider.clone());
+    let credential = match select_openai_credential(&state, &provider, &model).await {
+        Ok(cred) => cred,
+        Err(resp) => return resp,
+    };
+    let upstream = match openai_provider_for(&credential, capability) {
+        Ok(upstream) => upstream,
+        Err(resp) => return resp,
+    };
+
+    let system = match request.instructions.as_deref().map(str::trim) {
+        Some(extra) if !extra.is_empty() => format!("{prompt}\n\nAdditional instructions: {extra}"),
+        _ => prompt,
+    };
+    let payload = serde_json::json!({
+        "model": model,
+        "temperature": 0,
+        "messages": [
+            { "role": "system", "content": system },
+            { "role": "user", "content": input },
+        ],
+    });
+    let (status, json) = match read_upstream_json(
+        &credential,
+        upstream.chat_completions(&payload),
+        capability,
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(resp) => return resp,
+    };
+    if !status.is_success() {
+        return (status, Json(json)).into_response();
+    }
+
+    let Some(content) = json["choices"][0]["message"]["content"].as_str() else {
+        return build_error_response_with_meta(
+            StatusCode::BAD_GATEWAY.as_u16(),
+            "Upstream response has no message content",
+            None,
+            Some(&credential.provider_type.to_string()),
+            Some(GatewayErrorCode::UpstreamError),
+        );
+    };
+    let output = strip_code_fence(content).to_string();
+    if let Some(db) = &state.db {
+        let _ = state.pool_service.record_usage(db, &credential.uuid);
+    }
+
+    if wants_json(&headers) {
+        return Json(serde_json::json!({
+            "model": model,
+            "output": output,
+            "truncated": truncated,
+            "usage": json.get("usage").cloned().unwrap_or(serde_json::Value::Null),
+        }))
+        .into_response();
+    }
+    (
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        format!("{output}\n"),
+    )
+        .into_response()
+}
+
+/// 总结 diff
+///
+/// # 端点
+/// `POST /utils/summarize-diff`
+pub async fn handle_summarize_diff(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let prompt = state.dev_utils.summarize_diff_prompt.clone();
+    run_template(state, headers, body, prompt, "summarize-diff").await
+}
+
+/// 根据 diff 生成提交信息
+///
+/// # 端点
+/// `POST /utils/commit-message`
+pub async fn handle_commit_message(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let prompt = state.dev_utils.commit_message_prompt.clone();
+    run_template(state, headers, body, prompt, "commit-message").await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request_accepts_text_and_json() {
+        let headers = HeaderMap::new();
+        let diff = "diff --git a/x b/x\n+hello\n";
+        let plain = parse_request(&headers, diff.as_bytes()).unwrap();
+        assert_eq!(plain.input, diff);
+        assert!(plain.model.is_none());
+
+        let json = parse_request(
+            &headers,
+            r#"{"diff":"+a","model":"m","instructions":"中文"}"#.as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(json.input, "+a");
+        assert_eq!(json.model.as_deref(), Some("m"));
+        assert_eq!(json.instructions.as_deref(), Some("中文"));
+
+        // 以 `{` 开头但不是 JSON 的文本按原样处理
+        let braces = parse_request(&headers, b"{ not json").unwrap();
+        assert_eq!(braces.input, "{ not json");
+
+        let mut json_headers = HeaderMap::new();
+        json_headers.insert(header::CONTENT_TYPE, "application/json".parse().unwrap());
+        assert!(parse_request(&json_headers, b"{ not json").is_err());
+    }
+
+    #[test]
+    fn test_truncate_input_and_strip_fence() {
+        assert_eq!(truncate_input("提交信息", 2), ("提交", true));
+        assert_eq!(truncate_input("abc", 3), ("abc", false));
+
+        assert_eq!(
+            strip_code_fence("```text\nfeat: add x\n\nbody\n```\n"),
+            "feat: add x\n\nbody"
+        );
+        assert_eq!(strip_code_fence("  fix: y  "), "fix: y");
+        assert_eq!(strip_code_fence("```fix: z```"), "fix: z");
+    }
+}
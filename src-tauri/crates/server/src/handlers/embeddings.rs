@@ -0,0 +1,368 @@
+//! 文本嵌入 API 处理器
+//!
+//! 实现 OpenAI 兼容的 `/v1/embeddings` 端点，并接入持久化嵌入缓存：
+//! - 输入为字符串或字符串数组时，按 (Provider + 模型, 规范化文本哈希) 查缓存
+//! - 只把未命中且去重后的文本发送到上游，结果按原始顺序回填
+//! - token 数组等其它输入形式直接透传，不参与缓存
+
+use std::future::Future;
+
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::handlers::{verify_inbound_api_key, verify_scoped_key_model};
+use crate::AppState;
+use lime_core::errors::GatewayErrorCode;
+use lime_core::i18n::{self, MessageCode};
+use lime_core::models::provider_pool_model::{CredentialData, ProviderCredential};
+use lime_providers::converter::embeddings::{build_embeddings_response, parse_upstream_vectors};
+use lime_providers::outbound_limit;
+use lime_providers::providers::openai_custom::OpenAICustomProvider;
+use lime_server_utils::{build_error_response_with_meta, safe_truncate};
+use lime_services::embedding_cache_service::{embedding_model_key, EmbeddingCacheLookup};
+
+/// 未指定 X-Provider-Id 时使用的嵌入 Provider
+const DEFAULT_EMBEDDING_PROVIDER: &str = "openai";
+
+/// 从请求中提取可缓存的文本输入
+///
+/// 仅支持字符串或字符串数组，其它形式返回 None。
+fn extract_text_inputs(input: &serde_json::Value) -> Option<Vec<String>> {
+    match input {
+        serde_json::Value::String(text) => Some(vec![text.clone()]),
+        serde_json::Value::Array(items) if !items.is_empty() => items
+            .iter()
+            .map(|item| item.as_str().map(str::to_string))
+            .collect(),
+        _ => None,
+    }
+}
+
+pub(crate) fn invalid_request(message: &str) -> Response {
+    build_error_response_with_meta(
+        StatusCode::BAD_REQUEST.as_u16(),
+        message,
+        None,
+        None,
+        Some(GatewayErrorCode::InvalidRequest),
+    )
+}
+
+/// 为 OpenAI 兼容的辅助端点（嵌入、重排序等）选择凭证
+pub(crate) async fn select_openai_credential(
+    state: &AppState,
+    provider: &str,
+    model: &str,
+) -> Result<ProviderCredential, Response> {
+    let Some(db) = &state.db else {
+        return Err(build_error_response_with_meta(
+            StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+            i18n::t(MessageCode::DatabaseUnavailable),
+            None,
+            None,
+            Some(GatewayErrorCode::InternalError),
+        ));
+    };
+
+    match state
+        .pool_service
+        .select_credential_with_fallback(
+            db,
+            &state.api_key_service,
+            provider,
+            Some(model),
+            Some(provider),
+            None,
+        )
+        .await
+    {
+        Ok(Some(cred)) => Ok(cred),
+        Ok(None) => Err(build_error_response_with_meta(
+            StatusCode::SERVICE_UNAVAILABLE.as_u16(),
+            &format!("No available credentials for provider '{provider}'"),
+            None,
+            Some(provider),
+            Some(GatewayErrorCode::NoCredentials),
+        )),
+        Err(e) => Err(build_error_response_with_meta(
+            StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+            &format!("Failed to select credential: {e}"),
+            None,
+            Some(provider),
+            Some(GatewayErrorCode::InternalError),
+        )),
+    }
+}
+
+/// 由凭证构建 OpenAI 兼容 Provider（仅支持 OpenAI Key 凭证）
+pub(crate) fn openai_provider_for(
+    credential: &ProviderCredential,
+    capability: &str,
+) -> Result<OpenAICustomProvider, Response> {
+    let CredentialData::OpenAIKey { api_key, base_url } = &credential.credential else {
+        return Err(invalid_request(&format!(
+            "Provider '{}' does not support {capability}",
+            credential.provider_type
+        )));
+    };
+    Ok(OpenAICustomProvider::with_config(
+        api_key.clone(),
+        base_url.clone(),
+    ))
+}
+
+/// 在凭证的出站限额作用域内调用上游，返回 (状态码, 响应 JSON)
+pub(crate) async fn read_upstream_json(
+    credential: &ProviderCredential,
+    upstream: impl Future<Output = Result<reqwest::Response, Box<dyn std::error::Error + Send + Sync>>>,
+    capability: &str,
+) -> Result<(StatusCode, serde_json::Value), Response> {
+    let result = outbound_limit::scope(&credential.uuid, upstream).await;
+    let resp = result.map_err(|e| {
+        build_error_response_with_meta(
+            StatusCode::BAD_GATEWAY.as_u16(),
+            &format!("{capability} request failed: {e}"),
+            None,
+            Some(&credential.provider_type.to_string()),
+            Some(GatewayErrorCode::UpstreamUnavailable),
+        )
+    })?;
+
+    let status = StatusCode::from_u16(resp.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+    let text = resp.text().await.unwrap_or_default();
+    match serde_json::from_str::<serde_json::Value>(&text) {
+        Ok(json) => Ok((status, json)),
+        Err(_) => Err(build_error_response_with_meta(
+            StatusCode::BAD_GATEWAY.as_u16(),
+            &format!(
+                "Invalid {capability} response: {}",
+                safe_truncate(&text, 200)
+            ),
+            None,
+            Some(&credential.provider_type.to_string()),
+            Some(GatewayErrorCode::UpstreamError),
+        )),
+    }
+}
+
+/// 调用上游嵌入接口，返回 (状态码, 响应 JSON)
+async fn call_upstream_embeddings(
+    credential: &ProviderCredential,
+    payload: &serde_json::Value,
+) -> Result<(StatusCode, serde_json::Value), Response> {
+    let provider = openai_provider_for(credential, "embeddings")?;
+    read_upstream_json(credential, provider.embeddings(payload), "embeddings").await
+}
+
+/// 处理文本嵌入请求
+///
+/// # 端点
+/// `POST /v1/embeddings`
+///
+/// # 诊断响应头
+/// `x-lime-embedding-cache`: `hit`（全部命中）/ `partial` / `miss` / `bypass`
+pub async fn handle_embeddings(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<serde_json::Value>,
+) -> Response {
+    if let Err(e) = verify_inbound_api_key(&headers, &state).await {
+        return e.into_response();
+    }
+
+    let Some(model) = request
+        .get("model")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+    else {
+        return invalid_request("model is required");
+    };
+    if let Err(e) = verify_scoped_key_model(&headers, &state, &model) {
+        return e.into_response();
+    }
+    let Some(input) = request.get("input") else {
+        return invalid_request("input is required");
+    };
+    let dimensions = request
+        .get("dimensions")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32);
+    let base64_format = request.get("encoding_format").and_then(|v| v.as_str()) == Some("base64");
+    let provider = headers
+        .get("x-provider-id")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_lowercase())
+        .unwrap_or_else(|| DEFAULT_EMBEDDING_PROVIDER.to_string());
+
+    // 非文本输入或缓存关闭：直接透传
+    let cache_enabled = state.embedding_cache_store.is_enabled();
+    let Some(texts) = extract_text_inputs(input).filter(|_| cache_enabled) else {
+        let credential = match select_openai_credential(&state, &provider, &model).await {
+            Ok(cred) => cred,
+            Err(resp) => return resp,
+        };
+        return match call_upstream_embeddings(&credential, &request).await {
+            Ok((status, json)) => {
+                let mut response = (status, Json(json)).into_response();
+                set_cache_header(&mut response, "bypass");
+                response
+            }
+            Err(resp) => resp,
+        };
+    };
+
+    let batch = match embed_texts(&state, &provider, &request, &model, dimensions, &texts).await {
+        Ok(batch) => batch,
+        Err(resp) => return resp,
+    };
+
+    let body = build_embeddings_response(&model, &batch.vectors, base64_format, batch.usage);
+    let mut response = (StatusCode::OK, Json(body)).into_response();
+    set_cache_header(&mut response, batch.cache_state);
+    response
+}
+
+/// 一批文本的嵌入结果
+pub(crate) struct EmbeddingBatch {
+    /// 与输入顺序一致的向量
+    pub vectors: Vec<Vec<f32>>,
+    /// 上游返回的 usage（全部命中缓存时为 None）
+    pub usage: Option<serde_json::Value>,
+    /// 缓存命中情况：`hit` / `partial` / `miss`
+    pub cache_state: &'static str,
+}
+
+/// 经由嵌入缓存批量获取文本向量
+///
+/// `template` 为发往上游的请求体模板（保留 `dimensions`、`user` 等字段），
+/// `input` 会被替换为未命中缓存的去重文本。供 `/v1/embeddings` 与 RAG 检索复用。
+pub(crate) async fn embed_texts(
+    state: &AppState,
+    provider: &str,
+    template: &serde_json::Value,
+    model: &str,
+    dimensions: Option<u32>,
+    texts: &[String],
+) -> Result<EmbeddingBatch, Response> {
+    let cache = state.embedding_cache_store.clone();
+    let model_key = embedding_model_key(provider, model, dimensions);
+    let mut lookup: EmbeddingCacheLookup = cache.lookup(&model_key, texts);
+    let cache_state = if lookup.pending_texts.is_empty() {
+        "hit"
+    } else if lookup.pending_texts.len() < texts.len() {
+        "partial"
+    } else {
+        "miss"
+    };
+
+    state.logs.write().await.add(
+        "info",
+        &format!(
+            "[EMBEDDINGS] model={} inputs={} upstream={} cache={}",
+            model,
+            texts.len(),
+            lookup.pending_texts.len(),
+            cache_state
+        ),
+    );
+
+    let mut usage = None;
+    if !lookup.pending_texts.is_empty() {
+        let credential = select_openai_credential(state, provider, model).await?;
+
+        let mut payload = template.clone();
+        if let Some(obj) = payload.as_object_mut() {
+            obj.insert("model".to_string(), serde_json::json!(model));
+            obj.insert("input".to_string(), serde_json::json!(lookup.pending_texts));
+            // 统一以 float 向上游请求，便于缓存；返回时再按客户端要求编码
+            obj.remove("encoding_format");
+        }
+
+        let (status, json) = call_upstream_embeddings(&credential, &payload).await?;
+        if !status.is_success() {
+            if let Some(db) = &state.db {
+                let _ = state.pool_service.mark_unhealthy(
+                    db,
+                    &credential.uuid,
+                    Some(&format!("embeddings upstream status {status}")),
+                );
+            }
+            return Err((status, Json(json)).into_response());
+        }
+        if let Some(db) = &state.db {
+            let _ = state.pool_service.record_usage(db, &credential.uuid);
+        }
+
+        let upstream_error = |message: &str| {
+            build_error_response_with_meta(
+                StatusCode::BAD_GATEWAY.as_u16(),
+                message,
+                None,
+                Some(&credential.provider_type.to_string()),
+                Some(GatewayErrorCode::UpstreamError),
+            )
+        };
+        let vectors = parse_upstream_vectors(&json).map_err(|e| upstream_error(&e))?;
+        cache
+            .fill(&model_key, &mut lookup, vectors)
+            .map_err(|e| upstream_error(&e))?;
+        usage = json.get("usage").cloned();
+    }
+
+    Ok(EmbeddingBatch {
+        vectors: lookup.vectors.into_iter().flatten().collect(),
+        usage,
+        cache_state,
+    })
+}
+
+fn set_cache_header(response: &mut Response, value: &'static str) {
+    response.headers_mut().insert(
+        header::HeaderName::from_static("x-lime-embedding-cache"),
+        HeaderValue::from_static(value),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_text_inputs_should_reject_token_arrays() {
+        assert_eq!(
+            extract_text_inputs(&serde_json::json!("hello")),
+            Some(vec!["hello".to_string()])
+        );
+        assert_eq!(
+            extract_text_inputs(&serde_json::json!(["a", "b"])),
+            Some(vec!["a".to_string(), "b".to_string()])
+        );
+        assert_eq!(extract_text_inputs(&serde_json::json!([1, 2, 3])), None);
+        assert_eq!(extract_text_inputs(&serde_json::json!([])), None);
+    }
+
+    #[test]
+    fn parse_upstream_vectors_should_order_by_index() {
+        let body = serde_json::json!({
+            "data": [
+                { "index": 1, "embedding": [2.0] },
+                { "index": 0, "embedding": [1.0] }
+            ]
+        });
+        assert_eq!(
+            parse_upstream_vectors(&body).expect("解析应成功"),
+            vec![vec![1.0], vec![2.0]]
+        );
+    }
+
+    #[test]
+    fn build_embeddings_response_should_encode_base64() {
+        let body = build_embeddings_response("m", &[vec![1.0]], true, None);
+        assert_eq!(body["data"][0]["embedding"], "AACAPw==");
+        assert_eq!(body["object"], "list");
+    }
+}
@@ -0,0 +1,404 @@
+//! 伪流式
+//!
+//! 客户端请求 `stream: true`，但上游（或当前调用路径）只返回了完整的 JSON 响应时，
+//! 将完整响应拆成 OpenAI `chat.completion.chunk` / Anthropic SSE 事件，按配置节奏返回。
+
+use std::time::Duration;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, HeaderValue, StatusCode},
+    response::Response,
+};
+use futures::StreamExt;
+use lime_core::config::FakeStreamingSettings;
+use lime_core::errors::GatewayErrorCode;
+use lime_server_utils::build_error_response_with_meta;
+use serde_json::{json, Value};
+
+/// 转换时读取的最大响应体
+const MAX_BODY_BYTES: usize = 32 * 1024 * 1024;
+
+/// 目标 SSE 格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SseFlavor {
+    OpenAi,
+    Anthropic,
+}
+
+fn split_chars(text: &str, chunk_chars: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .chunks(chunk_chars.max(1))
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+/// 逐 Token 的 logprobs，仅在 Token 拼接后与正文一致时使用
+fn token_logprobs<'a>(choice: &'a Value, content: &str) -> Option<&'a Vec<Value>> {
+    let tokens = choice["logprobs"]["content"]
+        .as_array()
+        .filter(|tokens| !tokens.is_empty())?;
+    let joined: String = tokens
+        .iter()
+        .map(|token| token["token"].as_str())
+        .collect::<Option<String>>()?;
+    (joined == content).then_some(tokens)
+}
+
+/// 将 `chat.completion` 响应拆成 `chat.completion.chunk` 事件
+pub fn openai_events(completion: &Value, chunk_chars: usize) -> Vec<String> {
+    let id = completion["id"].as_str().unwrap_or("chatcmpl-fake");
+    let created = completion["created"].as_u64().unwrap_or(0);
+    let model = completion["model"].as_str().unwrap_or_default();
+    let fingerprint = completion
+        .get("system_fingerprint")
+        .filter(|v| !v.is_null());
+    let chunk_with = |choice: Value| {
+        let mut data = json!({
+            "id": id,
+            "object": "chat.completion.chunk",
+            "created": created,
+            "model": model,
+            "choices": [choice],
+        });
+        if let Some(fingerprint) = fingerprint {
+            data["system_fingerprint"] = fingerprint.clone();
+        }
+        format!("data: {data}\n\n")
+    };
+    let chunk = |choice_index: u64, delta: Value, finish_reason: Value| {
+        chunk_with(json!({"index": choice_index, "delta": delta, "finish_reason": finish_reason}))
+    };
+
+    let mut events = Vec::new();
+    let choices = completion["choices"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    for (position, choice) in choices.iter().enumerate() {
+        let index = choice["index"].as_u64().unwrap_or(position as u64);
+        let message = &choice["message"];
+        events.push(chunk(
+            index,
+            json!({"role": message["role"].as_str().unwrap_or("assistant"), "content": ""}),
+            Value::Null,
+        ));
+        if let Some(reasoning) = message["reasoning_content"].as_str() {
+            for piece in split_chars(reasoning, chunk_chars) {
+                events.push(chunk(
+                    index,
+                    json!({"reasoning_content": piece}),
+                    Value::Null,
+                ));
+            }
+        }
+        if let Some(content) = message["content"].as_str() {
+            match token_logprobs(choice, content) {
+                // 有逐 Token logprobs 时按 Token 切分，每段携带对应的 logprobs
+                Some(tokens) => {
+                    for token in tokens {
+                        events.push(chunk_with(json!({
+                            "index": index,
+                            "delta": {"content": token["token"]},
+                            "logprobs": {"content": [token]},
+                            "finish_reason": null,
+                        })));
+                    }
+                }
+                None => {
+                    for piece in split_chars(content, chunk_chars) {
+                        events.push(chunk(index, json!({"content": piece}), Value::Null));
+                    }
+                }
+            }
+        }
+        if let Some(tool_calls) = message["tool_calls"].as_array() {
+            let deltas: Vec<Value> = tool_calls
+                .iter()
+                .enumerate()
+                .map(|(i, call)| {
+                    let mut call = call.clone();
+                    call["index"] = json!(i);
+                    call
+                })
+                .collect();
+            if !deltas.is_empty() {
+                events.push(chunk(index, json!({"tool_calls": deltas}), Value::Null));
+            }
+        }
+        let finish_reason = choice
+            .get("finish_reason")
+            .cloned()
+            .filter(|v| !v.is_null())
+            .unwrap_or_else(|| json!("stop"));
+        events.push(chunk(index, json!({}), finish_reason));
+    }
+    if let Some(usage) = completion.get("usage").filter(|u| !u.is_null()) {
+        let data = json!({
+            "id": id,
+            "object": "chat.completion.chunk",
+            "created": created,
+            "model": model,
+            "choices": [],
+            "usage": usage,
+        });
+        events.push(format!("data: {data}\n\n"));
+    }
+    events.push("data: [DONE]\n\n".to_string());
+    events
+}
+
+/// 将 Anthropic `message` 响应拆成 SSE 事件
+pub fn anthropic_events(message: &Value, chunk_chars: usize) -> Vec<String> {
+    let event = |name: &str, data: Value| format!("event: {name}\ndata: {data}\n\n");
+    let usage = &message["usage"];
+    let mut start_message = message.clone();
+    start_message["content"] = json!([]);
+    start_message["stop_reason"] = Value::Null;
+    start_message["stop_sequence"] = Value::Null;
+    start_message["usage"] = json!({
+        "input_tokens": usage["input_tokens"].as_u64().unwrap_or(0),
+        "output_tokens": 0,
+    });
+
+    let mut events = vec![event(
+        "message_start",
+        json!({"type": "message_start", "message": start_message}),
+    )];
+    let blocks = message["content"].as_array().cloned().unwrap_or_default();
+    for (index, block) in blocks.iter().enumerate() {
+        let block_type = block["type"].as_str().unwrap_or("text");
+        let (empty_block, deltas) = match block_type {
+            "text" => (
+                json!({"type": "text", "text": ""}),
+                split_chars(block["text"].as_str().unwrap_or_default(), chunk_chars)
+                    .into_iter()
+                    .map(|text| json!({"type": "text_delta", "text": text}))
+                    .collect(),
+            ),
+            "thinking" => {
+                let mut deltas: Vec<Value> =
+                    split_chars(block["thinking"].as_str().unwrap_or_default(), chunk_chars)
+                        .into_iter()
+                        .map(|thinking| json!({"type": "thinking_delta", "thinking": thinking}))
+                        .collect();
+                if let Some(signature) = block["signature"].as_str() {
+                    deltas.push(json!({"type": "signature_delta", "signature": signature}));
+                }
+                (json!({"type": "thinking", "thinking": ""}), deltas)
+            }
+            "tool_use" | "server_tool_use" => {
+                let mut empty = block.clone();
+                empty["input"] = json!({});
+                let input = serde_json::to_string(&block["input"]).unwrap_or_else(|_| "{}".into());
+                (
+                    empty,
+                    vec![json!({"type": "input_json_delta", "partial_json": input})],
+                )
+            }
+            // 其他块类型无增量格式，整块放在 content_block_start 中
+            _ => (block.clone(), Vec::new()),
+        };
+        events.push(event(
+            "content_block_start",
+            json!({"type": "content_block_start", "index": index, "content_block": empty_block}),
+        ));
+        for delta in deltas {
+            events.push(event(
+                "content_block_delta",
+                json!({"type": "content_block_delta", "index": index, "delta": delta}),
+            ));
+        }
+        events.push(event(
+            "content_block_stop",
+            json!({"type": "content_block_stop", "index": index}),
+        ));
+    }
+    events.push(event(
+        "message_delta",
+        json!({
+            "type": "message_delta",
+            "delta": {
+                "stop_reason": message.get("stop_reason").cloned().unwrap_or_else(|| json!("end_turn")),
+                "stop_sequence": message.get("stop_sequence").cloned().unwrap_or(Value::Null),
+            },
+            "usage": {"output_tokens": usage["output_tokens"].as_u64().unwrap_or(0)},
+        }),
+    ));
+    events.push(event("message_stop", json!({"type": "message_stop"})));
+    events
+}
+
+fn is_json_response(response: &Response) -> bool {
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"))
+}
+
+/// 客户端请求流式但拿到完整 JSON 响应时，转换为伪流式 SSE 响应；其他情况原样返回
+pub async fn ensure_streaming(
+    settings: &FakeStreamingSettings,
+    response: Response,
+    stream_requested: bool,
+    flavor: SseFlavor,
+) -> Response {
+    if !settings.enabled
+        || !stream_requested
+        || !response.status().is_success()
+        || !is_json_response(&response)
+    {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("[FAKE_STREAM] 读取上游响应失败: {}", e);
+            return build_error_response_with_meta(
+                StatusCode::BAD_GATEWAY.as_u16(),
+                &format!("Failed to read upstream response: {e}"),
+                None,
+                None,
+                Some(GatewayErrorCode::UpstreamError),
+            );
+        }
+    };
+    let Ok(value) = serde_json::from_slice::<Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    let events = match flavor {
+        SseFlavor::OpenAi => openai_events(&value, settings.chunk_chars),
+        SseFlavor::Anthropic => anthropic_events(&value, settings.chunk_chars),
+    };
+    tracing::debug!("[FAKE_STREAM] 完整响应拆分为 {} 个 SSE 事件", events.len());
+
+    let interval = Duration::from_millis(settings.interval_ms);
+    let body_stream =
+        futures::stream::iter(events.into_iter().enumerate()).then(move |(i, event)| async move {
+            if i > 0 && !interval.is_zero() {
+                tokio::time::sleep(interval).await;
+            }
+            Ok::<_, std::convert::Infallible>(event)
+        });
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/event-stream"),
+    );
+    parts
+        .headers
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    parts
+        .headers
+        .insert("x-lime-fake-stream", HeaderValue::from_static("1"));
+    Response::from_parts(parts, Body::from_stream(body_stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openai_events_split_content_and_finish() {
+        let completion = json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "gpt-4o",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "hello world"},
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 3, "completion_tokens": 2, "total_tokens": 5}
+        });
+
+        let events = openai_events(&completion, 5);
+
+        // role + 3 段内容 + finish + usage + [DONE]
+        assert_eq!(events.len(), 7);
+        assert!(events[1].contains(r#""content":"hello""#));
+        assert!(events[4].contains(r#""finish_reason":"stop""#));
+        assert!(events[5].contains(r#""total_tokens":5"#));
+        assert_eq!(events.last().unwrap(), "data: [DONE]\n\n");
+    }
+
+    #[test]
+    fn test_openai_events_carry_token_logprobs() {
+        let completion = json!({
+            "id": "chatcmpl-1",
+            "model": "gpt-4o",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "Hi there"},
+                "logprobs": {"content": [
+                    {"token": "Hi", "logprob": -0.1, "top_logprobs": []},
+                    {"token": " there", "logprob": -0.5, "top_logprobs": []}
+                ]},
+                "finish_reason": "stop"
+            }]
+        });
+
+        let events = openai_events(&completion, 1);
+
+        // role + 2 个 Token + finish + [DONE]
+        assert_eq!(events.len(), 5);
+        assert!(events[1].contains(r#""content":"Hi""#));
+        assert!(events[2].contains(r#""logprob":-0.5"#));
+    }
+
+    #[test]
+    fn test_anthropic_events_cover_text_and_tool_use() {
+        let message = json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-sonnet-4-5",
+            "content": [
+                {"type": "text", "text": "abc"},
+                {"type": "tool_use", "id": "toolu_1", "name": "search", "input": {"q": "x"}}
+            ],
+            "stop_reason": "tool_use",
+            "usage": {"input_tokens": 10, "output_tokens": 4}
+        });
+
+        let events = anthropic_events(&message, 16);
+
+        assert!(events[0].starts_with("event: message_start"));
+        assert!(events[0].contains(r#""content":[]"#));
+        assert!(events
+            .iter()
+            .any(|e| e.contains(r#""partial_json":"{\"q\":\"x\"}""#)));
+        let delta = events
+            .iter()
+            .find(|e| e.starts_with("event: message_delta"))
+            .unwrap();
+        assert!(delta.contains(r#""stop_reason":"tool_use""#));
+        assert!(delta.contains(r#""output_tokens":4"#));
+        assert!(events.last().unwrap().starts_with("event: message_stop"));
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_upstream_body_becomes_bad_gateway() {
+        let settings = FakeStreamingSettings {
+            enabled: true,
+            ..Default::default()
+        };
+        let body = Body::from_stream(futures::stream::iter([Err::<Vec<u8>, _>(
+            std::io::Error::other("connection reset"),
+        )]));
+        let response = Response::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .unwrap();
+
+        let response = ensure_streaming(&settings, response, true, SseFlavor::OpenAi).await;
+
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+    }
+}
@@ -23,14 +23,12 @@ use axum::{
     Json,
 };
 
-use crate::handlers::verify_api_key;
+use crate::deps::HandlerDeps;
+use crate::handlers::provider_dispatch::resolve_with;
+use crate::handlers::{verify_inbound_api_key, verify_scoped_key_model};
 use crate::AppState;
 use lime_core::models::openai::ImageGenerationRequest;
-use lime_core::models::provider_pool_model::CredentialData;
-use lime_providers::converter::openai_to_antigravity::{
-    convert_antigravity_image_response, convert_image_request_to_antigravity,
-};
-use lime_providers::providers::AntigravityProvider;
+use lime_providers::providers::{ProviderContext, ProviderError};
 
 /// 处理图像生成请求
 ///
@@ -66,10 +64,20 @@ pub async fn handle_image_generation(
     Json(request): Json<ImageGenerationRequest>,
 ) -> Response {
     // 验证 API Key
-    if let Err(e) = verify_api_key(&headers, &state.api_key).await {
+    if let Err(e) = verify_inbound_api_key(&headers, &state).await {
+        return e.into_response();
+    }
+    if let Err(e) = verify_scoped_key_model(&headers, &state, &request.model) {
         return e.into_response();
     }
 
+    run_image_generation(&state.handler_deps(), request).await
+}
+
+/// 图像生成的核心流程
+///
+/// 凭证池、日志、时钟与 Provider 均通过 [`HandlerDeps`] 注入，便于单元测试。
+pub async fn run_image_generation(deps: &HandlerDeps, request: ImageGenerationRequest) -> Response {
     // 验证请求参数
     if request.prompt.trim().is_empty() {
         return (
@@ -93,43 +101,23 @@ pub async fn handle_image_generation(
     } else {
         request.prompt.clone()
     };
-    state.logs.write().await.add(
-        "info",
-        &format!(
-            "[IMAGE] 收到图像生成请求: model={}, prompt={}, n={}, response_format={}",
-            request.model, prompt_display, request.n, request.response_format
-        ),
-    );
-
-    // 获取 Antigravity 凭证
-    let db = match &state.db {
-        Some(db) => db,
-        None => {
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({
-                    "error": {
-                        "message": "Database not available",
-                        "type": "server_error"
-                    }
-                })),
-            )
-                .into_response();
-        }
-    };
+    deps.logs
+        .add(
+            "info",
+            &format!(
+                "[IMAGE] 收到图像生成请求: model={}, prompt={}, n={}, response_format={}",
+                request.model, prompt_display, request.n, request.response_format
+            ),
+        )
+        .await;
 
     // 从凭证池获取 Antigravity 凭证
-    let credential = match state
-        .pool_service
-        .select_credential(db, "antigravity", None)
-    {
+    let credential = match deps.pool.select_credential("antigravity", None) {
         Ok(Some(cred)) => cred,
         Ok(None) => {
-            state
-                .logs
-                .write()
-                .await
-                .add("error", "[IMAGE] 没有可用的 Antigravity 凭证");
+            deps.logs
+                .add("error", "[IMAGE] 没有可用的 Antigravity 凭证")
+                .await;
             return (
                 StatusCode::SERVICE_UNAVAILABLE,
                 Json(serde_json::json!({
@@ -143,11 +131,9 @@ pub async fn handle_image_generation(
                 .into_response();
         }
         Err(e) => {
-            state
-                .logs
-                .write()
-                .await
-                .add("error", &format!("[IMAGE] 获取凭证失败: {e}"));
+            deps.logs
+                .add("error", &format!("[IMAGE] 获取凭证失败: {e}"))
+                .await;
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(serde_json::json!({
@@ -161,181 +147,73 @@ pub async fn handle_image_generation(
         }
     };
 
-    // 提取 Antigravity 凭证信息
-    let (creds_file_path, project_id) = match &credential.credential {
-        CredentialData::AntigravityOAuth {
-            creds_file_path,
-            project_id,
-        } => (creds_file_path.clone(), project_id.clone()),
-        _ => {
-            state
-                .logs
-                .write()
-                .await
-                .add("error", "[IMAGE] 选中的凭证不是 Antigravity 类型");
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({
-                    "error": {
-                        "message": "Selected credential is not Antigravity type",
-                        "type": "server_error"
-                    }
-                })),
-            )
-                .into_response();
+    // 通过 Provider 注册表准备凭证（加载凭证、刷新 Token、发现项目）
+    let context = ProviderContext {
+        model: request.model.clone(),
+        ..Default::default()
+    };
+    let provider = match resolve_with(deps, &credential, &context).await {
+        Ok(provider) => provider,
+        Err(response) => {
+            deps.logs
+                .add("error", "[IMAGE] Antigravity 凭证准备失败")
+                .await;
+            return response;
         }
     };
 
-    // 创建 Antigravity Provider
-    let mut antigravity = AntigravityProvider::new();
-    if let Err(e) = antigravity
-        .load_credentials_from_path(&creds_file_path)
-        .await
-    {
-        let _ = state.pool_service.mark_unhealthy(
-            db,
-            &credential.uuid,
-            Some(&format!("Failed to load credentials: {e}")),
-        );
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({
-                "error": {
-                    "message": format!("Failed to load Antigravity credentials: {}", e),
-                    "type": "server_error"
-                }
-            })),
-        )
-            .into_response();
-    }
+    match provider.generate_image(&request).await {
+        Ok(mut image_response) => {
+            image_response.created = deps.clock.now().timestamp();
+            // 记录成功
+            let _ = deps
+                .pool
+                .mark_healthy(&credential.uuid, Some(&request.model));
+            let _ = deps.pool.record_usage(&credential.uuid);
 
-    // 验证并刷新 Token
-    let validation_result = antigravity.validate_token();
-    if validation_result.needs_refresh() {
-        tracing::info!("[IMAGE] Token 需要刷新，开始刷新...");
-        if let Err(refresh_error) = antigravity.refresh_token_with_retry(3).await {
-            tracing::error!("[IMAGE] Token 刷新失败: {:?}", refresh_error);
-            let _ = state.pool_service.mark_unhealthy_with_details(
-                db,
-                &credential.uuid,
-                &refresh_error,
-            );
-            let (status, message) = if refresh_error.requires_reauth() {
-                (StatusCode::UNAUTHORIZED, refresh_error.user_message())
-            } else {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    refresh_error.user_message(),
+            deps.logs
+                .add(
+                    "info",
+                    &format!("[IMAGE] 图像生成成功: {} 张图片", image_response.data.len()),
                 )
-            };
-            return (
-                status,
+                .await;
+
+            (StatusCode::OK, Json(image_response)).into_response()
+        }
+        Err(ProviderError::ConversionError(e)) => {
+            // 上游已正常响应，只是内容无法转换，不影响凭证健康状态
+            deps.logs
+                .add("error", &format!("[IMAGE] 响应转换失败: {e}"))
+                .await;
+            (
+                StatusCode::BAD_GATEWAY,
+                Json(serde_json::json!({ "error": e.to_error_json() })),
+            )
+                .into_response()
+        }
+        Err(ProviderError::ParseError(e)) => {
+            deps.logs
+                .add("error", &format!("[IMAGE] 响应转换失败: {e}"))
+                .await;
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
                 Json(serde_json::json!({
                     "error": {
-                        "message": message,
-                        "type": "authentication_error"
+                        "message": e,
+                        "type": "server_error",
+                        "code": "image_generation_failed"
                     }
                 })),
             )
-                .into_response();
-        }
-    }
-
-    // 设置项目 ID
-    if let Some(pid) = project_id {
-        antigravity.project_id = Some(pid);
-    } else if let Err(e) = antigravity.discover_project().await {
-        tracing::warn!("[IMAGE] Failed to discover project: {}", e);
-    }
-
-    let proj_id = antigravity.project_id.clone().unwrap_or_default();
-
-    // 转换请求为 Antigravity 格式
-    let antigravity_request = convert_image_request_to_antigravity(&request, &proj_id);
-
-    state.logs.write().await.add(
-        "debug",
-        &format!(
-            "[IMAGE] Antigravity 请求: model={}",
-            antigravity_request["model"].as_str().unwrap_or("unknown")
-        ),
-    );
-
-    // 调用 Antigravity API - 直接使用 call_api 而不是 generate_content
-    // 因为 generate_content 内部的 to_gemini_response 会丢失嵌套在 response 字段下的数据
-    let model = antigravity_request["model"]
-        .as_str()
-        .unwrap_or("gemini-3-pro-image-preview");
-
-    eprintln!("[IMAGE] 调用 Antigravity API: model={model}");
-    eprintln!(
-        "[IMAGE] 请求内容: {}",
-        serde_json::to_string_pretty(&antigravity_request).unwrap_or_default()
-    );
-
-    match antigravity
-        .call_api("generateContent", &antigravity_request)
-        .await
-    {
-        Ok(resp) => {
-            // 调试：打印原始响应
-            eprintln!(
-                "[IMAGE] Antigravity 原始响应: {}",
-                serde_json::to_string_pretty(&resp).unwrap_or_default()
-            );
-            state.logs.write().await.add(
-                "debug",
-                &format!(
-                    "[IMAGE] Antigravity 原始响应: {}",
-                    serde_json::to_string(&resp).unwrap_or_default()
-                ),
-            );
-
-            // 转换响应为 OpenAI 格式
-            match convert_antigravity_image_response(&resp, &request.response_format) {
-                Ok(image_response) => {
-                    // 记录成功
-                    let _ = state
-                        .pool_service
-                        .mark_healthy(db, &credential.uuid, Some(model));
-                    let _ = state.pool_service.record_usage(db, &credential.uuid);
-
-                    state.logs.write().await.add(
-                        "info",
-                        &format!("[IMAGE] 图像生成成功: {} 张图片", image_response.data.len()),
-                    );
-
-                    (StatusCode::OK, Json(image_response)).into_response()
-                }
-                Err(e) => {
-                    state
-                        .logs
-                        .write()
-                        .await
-                        .add("error", &format!("[IMAGE] 响应转换失败: {e}"));
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(serde_json::json!({
-                            "error": {
-                                "message": e,
-                                "type": "server_error",
-                                "code": "image_generation_failed"
-                            }
-                        })),
-                    )
-                        .into_response()
-                }
-            }
+                .into_response()
         }
         Err(e) => {
-            let _ = state
-                .pool_service
-                .mark_unhealthy(db, &credential.uuid, Some(&e.to_string()));
-            state
-                .logs
-                .write()
-                .await
-                .add("error", &format!("[IMAGE] Antigravity API 调用失败: {e}"));
+            let _ = deps
+                .pool
+                .mark_unhealthy(&credential.uuid, Some(&e.to_string()));
+            deps.logs
+                .add("error", &format!("[IMAGE] Antigravity API 调用失败: {e}"))
+                .await;
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(serde_json::json!({
@@ -350,3 +228,268 @@ pub async fn handle_image_generation(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deps::{Clock, CredentialPool, LogSink};
+    use async_trait::async_trait;
+    use axum::body::to_bytes;
+    use chrono::{DateTime, TimeZone, Utc};
+    use lime_core::models::openai::{ChatCompletionRequest, ImageData, ImageGenerationResponse};
+    use lime_core::models::provider_pool_model::{
+        CredentialData, PoolProviderType, ProviderCredential,
+    };
+    use lime_providers::converter::error::{ConversionError, ConversionErrorCode};
+    use lime_providers::providers::antigravity::TokenRefreshError;
+    use lime_providers::providers::registry::{
+        ProviderFactory, ProviderSetupError, ResolvedProvider,
+    };
+    use lime_providers::providers::{Provider, ProviderRegistry};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct MockPool {
+        credential: Option<ProviderCredential>,
+        events: Mutex<Vec<String>>,
+    }
+
+    impl CredentialPool for MockPool {
+        fn select_credential(
+            &self,
+            _provider_type: &str,
+            _model: Option<&str>,
+        ) -> Result<Option<ProviderCredential>, String> {
+            Ok(self.credential.clone())
+        }
+
+        fn mark_healthy(&self, uuid: &str, _check_model: Option<&str>) -> Result<(), String> {
+            self.events.lock().unwrap().push(format!("healthy:{uuid}"));
+            Ok(())
+        }
+
+        fn mark_unhealthy(&self, uuid: &str, _error_message: Option<&str>) -> Result<(), String> {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("unhealthy:{uuid}"));
+            Ok(())
+        }
+
+        fn mark_unhealthy_with_details(
+            &self,
+            uuid: &str,
+            _error: &TokenRefreshError,
+        ) -> Result<(), String> {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("unhealthy:{uuid}"));
+            Ok(())
+        }
+
+        fn record_usage(&self, uuid: &str) -> Result<(), String> {
+            self.events.lock().unwrap().push(format!("usage:{uuid}"));
+            Ok(())
+        }
+    }
+
+    struct NullLogs;
+
+    #[async_trait]
+    impl LogSink for NullLogs {
+        async fn add(&self, _level: &str, _message: &str) {}
+    }
+
+    struct FixedClock;
+
+    impl Clock for FixedClock {
+        fn now(&self) -> DateTime<Utc> {
+            Utc.timestamp_opt(1_700_000_000, 0).unwrap()
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    enum MockOutcome {
+        Success,
+        ServerError,
+        ConversionError,
+    }
+
+    struct MockImageProvider {
+        outcome: MockOutcome,
+    }
+
+    #[async_trait]
+    impl Provider for MockImageProvider {
+        fn name(&self) -> &'static str {
+            "mock"
+        }
+
+        async fn chat(
+            &self,
+            _request: &ChatCompletionRequest,
+        ) -> Result<serde_json::Value, ProviderError> {
+            Ok(serde_json::json!({}))
+        }
+
+        async fn generate_image(
+            &self,
+            _request: &ImageGenerationRequest,
+        ) -> Result<ImageGenerationResponse, ProviderError> {
+            match self.outcome {
+                MockOutcome::Success => {}
+                MockOutcome::ServerError => {
+                    return Err(ProviderError::ServerError(
+                        "HTTP 503 - overloaded".to_string(),
+                    ));
+                }
+                MockOutcome::ConversionError => {
+                    return Err(ProviderError::ConversionError(
+                        ConversionError::new(
+                            ConversionErrorCode::NoImageData,
+                            "No image generated",
+                        )
+                        .with_payload(&serde_json::json!({ "candidates": [] })),
+                    ));
+                }
+            }
+            Ok(ImageGenerationResponse {
+                created: 0,
+                data: vec![ImageData {
+                    b64_json: None,
+                    url: Some("data:image/png;base64,AAAA".to_string()),
+                    revised_prompt: None,
+                }],
+            })
+        }
+
+        async fn health_probe(&self) -> Result<(), ProviderError> {
+            Ok(())
+        }
+    }
+
+    struct MockFactory {
+        outcome: MockOutcome,
+    }
+
+    #[async_trait]
+    impl ProviderFactory for MockFactory {
+        async fn create(
+            &self,
+            _credential: &ProviderCredential,
+            _context: &ProviderContext,
+        ) -> Result<ResolvedProvider, ProviderSetupError> {
+            Ok(ResolvedProvider {
+                provider: Arc::new(MockImageProvider {
+                    outcome: self.outcome,
+                }),
+                token_refreshed: false,
+            })
+        }
+    }
+
+    fn deps(pool: Arc<MockPool>, outcome: MockOutcome) -> HandlerDeps {
+        let mut providers = ProviderRegistry::default();
+        providers.register("antigravity_oauth", Arc::new(MockFactory { outcome }));
+        HandlerDeps {
+            pool,
+            logs: Arc::new(NullLogs),
+            clock: Arc::new(FixedClock),
+            providers: Arc::new(providers),
+        }
+    }
+
+    fn antigravity_credential() -> ProviderCredential {
+        ProviderCredential::new(
+            PoolProviderType::Antigravity,
+            CredentialData::AntigravityOAuth {
+                creds_file_path: "/tmp/creds.json".to_string(),
+                project_id: None,
+            },
+        )
+    }
+
+    fn request(prompt: &str) -> ImageGenerationRequest {
+        serde_json::from_value(serde_json::json!({ "prompt": prompt })).unwrap()
+    }
+
+    async fn json_body(response: Response) -> serde_json::Value {
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_image_generation_success_records_usage() {
+        let credential = antigravity_credential();
+        let uuid = credential.uuid.clone();
+        let pool = Arc::new(MockPool {
+            credential: Some(credential),
+            ..Default::default()
+        });
+
+        let response =
+            run_image_generation(&deps(pool.clone(), MockOutcome::Success), request("a cat")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["created"], 1_700_000_000);
+        assert_eq!(body["data"][0]["url"], "data:image/png;base64,AAAA");
+        assert_eq!(
+            *pool.events.lock().unwrap(),
+            vec![format!("healthy:{uuid}"), format!("usage:{uuid}")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_image_generation_upstream_error_marks_unhealthy() {
+        let credential = antigravity_credential();
+        let uuid = credential.uuid.clone();
+        let pool = Arc::new(MockPool {
+            credential: Some(credential),
+            ..Default::default()
+        });
+
+        let response = run_image_generation(
+            &deps(pool.clone(), MockOutcome::ServerError),
+            request("a cat"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(json_body(response).await["error"]["code"], "api_error");
+        assert_eq!(
+            *pool.events.lock().unwrap(),
+            vec![format!("unhealthy:{uuid}")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_image_generation_conversion_error_includes_excerpt() {
+        let pool = Arc::new(MockPool {
+            credential: Some(antigravity_credential()),
+            ..Default::default()
+        });
+
+        let response = run_image_generation(
+            &deps(pool.clone(), MockOutcome::ConversionError),
+            request("a cat"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        let body = json_body(response).await;
+        assert_eq!(body["error"]["code"], "converter_no_image_data");
+        assert_eq!(body["error"]["upstream_excerpt"], "{\"candidates\":[]}");
+        assert!(pool.events.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_image_generation_without_credentials() {
+        let pool = Arc::new(MockPool::default());
+        let response =
+            run_image_generation(&deps(pool, MockOutcome::Success), request("a cat")).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let pool = Arc::new(MockPool::default());
+        let response = run_image_generation(&deps(pool, MockOutcome::Success), request("  ")).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -16,7 +16,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::AppState;
-use lime_core::database::dao::provider_pool::ProviderPoolDao;
+use lime_core::database::pool_storage::{lock_pool, pool_storage};
 use lime_core::models::provider_pool_model::{
     CachedTokenInfo, PoolProviderType, ProviderCredential,
 };
@@ -128,13 +128,14 @@ pub async fn get_available_credentials(
 
     // 获取所有kiro凭证
     let credentials = {
-        let conn = db.lock().map_err(|e| ApiError {
+        let conn = lock_pool(db).map_err(|e| ApiError {
             error: "database_lock_error".to_string(),
             message: format!("数据库锁定失败: {e}"),
             status_code: 500,
         })?;
 
-        ProviderPoolDao::get_all(&conn)
+        pool_storage()
+            .get_all(&conn)
             .map_err(|e| ApiError {
                 error: "database_query_error".to_string(),
                 message: format!("查询凭证失败: {e}"),
@@ -232,13 +233,14 @@ pub async fn select_credential(
 
     let selected_credential = if let Some(ref force_uuid) = request.force_uuid {
         // 强制选择指定UUID
-        let conn = db.lock().map_err(|e| ApiError {
+        let conn = lock_pool(db).map_err(|e| ApiError {
             error: "database_lock_error".to_string(),
             message: format!("数据库锁定失败: {e}"),
             status_code: 500,
         })?;
 
-        ProviderPoolDao::get_by_uuid(&conn, force_uuid)
+        pool_storage()
+            .get_by_uuid(&conn, force_uuid)
             .map_err(|e| ApiError {
                 error: "database_query_error".to_string(),
                 message: format!("查询凭证失败: {e}"),
@@ -330,13 +332,14 @@ pub async fn refresh_credential(
 
     // 验证凭证存在且为kiro类型
     let credential = {
-        let conn = db.lock().map_err(|e| ApiError {
+        let conn = lock_pool(db).map_err(|e| ApiError {
             error: "database_lock_error".to_string(),
             message: format!("数据库锁定失败: {e}"),
             status_code: 500,
         })?;
 
-        let cred = ProviderPoolDao::get_by_uuid(&conn, &uuid)
+        let cred = pool_storage()
+            .get_by_uuid(&conn, &uuid)
             .map_err(|e| ApiError {
                 error: "database_query_error".to_string(),
                 message: format!("查询凭证失败: {e}"),
@@ -445,13 +448,14 @@ pub async fn get_credential_status(
 
     // 验证凭证存在
     let credential = {
-        let conn = db.lock().map_err(|e| ApiError {
+        let conn = lock_pool(db).map_err(|e| ApiError {
             error: "database_lock_error".to_string(),
             message: format!("数据库锁定失败: {e}"),
             status_code: 500,
         })?;
 
-        ProviderPoolDao::get_by_uuid(&conn, &uuid)
+        pool_storage()
+            .get_by_uuid(&conn, &uuid)
             .map_err(|e| ApiError {
                 error: "database_query_error".to_string(),
                 message: format!("查询凭证失败: {e}"),
@@ -0,0 +1,56 @@
+//! 维护模式管理接口
+//!
+//! 仅主 API Key（或通过管理接口 OIDC 认证的请求）可调用：
+//! - `GET /admin/maintenance`：查询维护模式状态
+//! - `PUT /admin/maintenance`：开启或关闭维护模式，仅影响运行时状态，不写回配置文件
+
+use axum::{
+    extract::State, http::HeaderMap, response::IntoResponse, response::Response, Extension, Json,
+};
+use serde::Deserialize;
+
+use crate::auth::oidc::AdminIdentity;
+use crate::handlers::verify_admin_key;
+use crate::AppState;
+
+/// `PUT /admin/maintenance` 请求体
+#[derive(Debug, Deserialize)]
+pub struct MaintenanceUpdate {
+    pub enabled: bool,
+    /// 返回给客户端的提示，缺省时沿用当前提示
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// `GET /admin/maintenance`
+pub async fn get_maintenance(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    Json(state.maintenance.status()).into_response()
+}
+
+/// `PUT /admin/maintenance`
+pub async fn set_maintenance(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+    Json(update): Json<MaintenanceUpdate>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    let status = state
+        .maintenance
+        .set(update.enabled, update.message.as_deref());
+    tracing::info!(
+        "[MAINTENANCE] 维护模式已{}: {}",
+        if status.enabled { "开启" } else { "关闭" },
+        status.message
+    );
+    Json(status).into_response()
+}
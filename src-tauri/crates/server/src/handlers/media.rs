@@ -0,0 +1,234 @@
+//! 对话响应图片的本地媒体存储
+//!
+//! `server.chat_images.persist` 开启时，模型在对话中输出的图片（data URL）保存到
+//! 应用数据目录下的 `media/`，响应中改为 `/v1/media/{文件名}` URL，避免巨大的
+//! base64 内容反复出现在对话历史里。文件名为内容哈希，相同图片只保存一份。
+
+use std::path::PathBuf;
+
+use axum::{
+    extract::Path,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use base64::Engine;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// 媒体 URL 路径前缀
+pub const MEDIA_ROUTE_PREFIX: &str = "/v1/media";
+
+fn media_dir() -> Result<PathBuf, String> {
+    lime_core::app_paths::resolve_media_dir()
+}
+
+fn extension_for_mime(mime: &str) -> &'static str {
+    match mime {
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/webp" => "webp",
+        "image/gif" => "gif",
+        _ => "png",
+    }
+}
+
+fn mime_for_file(name: &str) -> &'static str {
+    match name.rsplit('.').next() {
+        Some("jpg") => "image/jpeg",
+        Some("webp") => "image/webp",
+        Some("gif") => "image/gif",
+        _ => "image/png",
+    }
+}
+
+/// 文件名只允许哈希加扩展名，防止路径穿越
+fn is_valid_media_name(name: &str) -> bool {
+    match name.split_once('.') {
+        Some((stem, ext)) => {
+            !stem.is_empty()
+                && stem.chars().all(|c| c.is_ascii_hexdigit())
+                && matches!(ext, "png" | "jpg" | "webp" | "gif")
+        }
+        None => false,
+    }
+}
+
+/// 服务监听地址对应的媒体 URL 前缀（监听全部地址时使用本机回环地址）
+pub fn media_base_url(server_base_url: &str) -> String {
+    let base = server_base_url
+        .replace("://0.0.0.0", "://127.0.0.1")
+        .replace("://[::]", "://127.0.0.1");
+    format!("{}{MEDIA_ROUTE_PREFIX}", base.trim_end_matches('/'))
+}
+
+/// 按内容保存图片，返回文件名
+fn store_image(dir: &std::path::Path, mime: &str, bytes: &[u8]) -> Result<String, String> {
+    let hash = hex::encode(Sha256::digest(bytes));
+    let name = format!("{}.{}", &hash[..32], extension_for_mime(mime));
+    let path = dir.join(&name);
+    if !path.exists() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("创建媒体目录失败: {e}"))?;
+        std::fs::write(&path, bytes).map_err(|e| format!("保存图片失败: {e}"))?;
+    }
+    Ok(name)
+}
+
+/// 保存 data URL 图片，返回媒体 URL；不是 base64 图片或保存失败时返回 None
+fn persist_data_url(dir: &std::path::Path, data_url: &str, base_url: &str) -> Option<String> {
+    let (meta, data) = data_url.strip_prefix("data:")?.split_once(',')?;
+    let mime = meta.strip_suffix(";base64")?;
+    if !mime.starts_with("image/") {
+        return None;
+    }
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(data.trim())
+        .ok()?;
+    match store_image(dir, mime, &bytes) {
+        Ok(name) => Some(format!("{base_url}/{name}")),
+        Err(e) => {
+            tracing::warn!("[MEDIA] {}", e);
+            None
+        }
+    }
+}
+
+/// 替换 Markdown 文本中 `![...](data:...)` 形式的图片
+fn persist_markdown(dir: &std::path::Path, text: &str, base_url: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find("](data:") {
+        let (head, tail) = rest.split_at(pos + 2);
+        out.push_str(head);
+        let end = tail.find(')').unwrap_or(tail.len());
+        let data_url = &tail[..end];
+        match persist_data_url(dir, data_url, base_url) {
+            Some(url) => out.push_str(&url),
+            None => out.push_str(data_url),
+        }
+        rest = &tail[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 将 OpenAI 响应中的图片保存到媒体目录并替换为 URL
+///
+/// 处理 `choices[].message.content`（Markdown 文本或 `image_url` 分段）与流式的
+/// `choices[].delta.content`。
+pub fn persist_response_images(response: &mut Value, base_url: &str) {
+    let Ok(dir) = media_dir() else {
+        return;
+    };
+    persist_response_images_in(&dir, response, base_url);
+}
+
+fn persist_response_images_in(dir: &std::path::Path, response: &mut Value, base_url: &str) {
+    let Some(choices) = response.get_mut("choices").and_then(Value::as_array_mut) else {
+        return;
+    };
+    for choice in choices {
+        for key in ["message", "delta"] {
+            let Some(content) = choice.get_mut(key).and_then(|m| m.get_mut("content")) else {
+                continue;
+            };
+            match content {
+                Value::String(text) if text.contains("](data:") => {
+                    *text = persist_markdown(dir, text, base_url);
+                }
+                Value::Array(parts) => {
+                    for part in parts {
+                        if let Some(Value::String(url)) = part.pointer_mut("/image_url/url") {
+                            if let Some(stored) = persist_data_url(dir, url, base_url) {
+                                *url = stored;
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// 保存单张图片（流式响应拼接 Markdown 时使用），失败时返回原 data URL
+pub fn persist_image(mime: &str, data: &str, base_url: &str) -> String {
+    let data_url = format!("data:{mime};base64,{data}");
+    media_dir()
+        .ok()
+        .and_then(|dir| persist_data_url(&dir, &data_url, base_url))
+        .unwrap_or(data_url)
+}
+
+/// GET /v1/media/{name}
+///
+/// 文件名为内容哈希，不需要认证，便于客户端直接以 `<img>` 引用。
+pub async fn serve_media(Path(name): Path<String>) -> Response {
+    if !is_valid_media_name(&name) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let Ok(dir) = media_dir() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    match tokio::fs::read(dir.join(&name)).await {
+        Ok(bytes) => (
+            [
+                (header::CONTENT_TYPE, mime_for_file(&name)),
+                (header::CACHE_CONTROL, "public, max-age=31536000, immutable"),
+            ],
+            bytes,
+        )
+            .into_response(),
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_media_base_url_and_names() {
+        assert_eq!(
+            media_base_url("http://0.0.0.0:8999"),
+            "http://127.0.0.1:8999/v1/media"
+        );
+        assert_eq!(
+            media_base_url("http://192.168.1.5:8999/"),
+            "http://192.168.1.5:8999/v1/media"
+        );
+        assert!(is_valid_media_name("0123abcd.png"));
+        assert!(!is_valid_media_name("../secret.png"));
+        assert!(!is_valid_media_name("0123abcd.exe"));
+    }
+
+    #[test]
+    fn test_persist_response_images() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = "http://127.0.0.1:8999/v1/media";
+        let mut response = json!({
+            "choices": [
+                {"message": {"content": "看图 ![image](data:image/png;base64,AAAA) 完"}},
+                {"message": {"content": [
+                    {"type": "text", "text": "hi"},
+                    {"type": "image_url", "image_url": {"url": "data:image/jpeg;base64,BBBB"}}
+                ]}}
+            ]
+        });
+        persist_response_images_in(dir.path(), &mut response, base);
+
+        let text = response["choices"][0]["message"]["content"]
+            .as_str()
+            .unwrap();
+        assert!(text.starts_with("看图 ![image](http://127.0.0.1:8999/v1/media/"));
+        assert!(text.ends_with(".png) 完"));
+        let url = response["choices"][1]["message"]["content"][1]["image_url"]["url"]
+            .as_str()
+            .unwrap();
+        assert!(url.ends_with(".jpg"));
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+
+        // 非 base64 的 data URL 保持原样
+        let untouched = persist_markdown(dir.path(), "![x](data:text/plain,hi)", base);
+        assert_eq!(untouched, "![x](data:text/plain,hi)");
+    }
+}
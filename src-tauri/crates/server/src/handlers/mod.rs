@@ -2,18 +2,46 @@
 //!
 //! 将 server 中的各类处理器拆分到独立文件
 
+pub mod abort;
 pub mod api;
 pub mod api_key_provider_utils;
+pub mod attribution;
+pub mod bulk_admin;
 pub mod chrome_bridge_ws;
+pub mod content_policy;
+pub mod context_cache;
+pub mod converter_shadow;
 pub mod credentials_api;
+pub mod dev_utils;
+pub mod embeddings;
+pub mod fake_stream;
 pub mod image_handler;
 pub mod kiro_credential;
+pub mod maintenance;
+pub mod media;
+pub mod model_downgrade;
+pub mod peer_forward;
 pub mod provider_calls;
+pub mod provider_dispatch;
+pub mod rag;
+pub mod regional_proxy;
+pub mod remote_control;
+pub mod request_journal;
+pub mod rerank;
+pub mod route_test;
+pub mod routing_pin;
+pub mod scoped_keys;
+pub mod signing;
+pub mod stream_transform;
+pub mod transcripts;
+pub mod upload_dedup;
+pub mod usage;
 pub mod websocket;
 
 pub use api::*;
 pub use chrome_bridge_ws::*;
 pub use credentials_api::*;
+pub use embeddings::*;
 pub use image_handler::*;
 // 避免 SelectCredentialRequest 歧义 glob re-export（credentials_api 和 kiro_credential 都定义了同名类型）
 pub use kiro_credential::{
@@ -22,4 +50,7 @@ pub use kiro_credential::{
     SelectCredentialResponse,
 };
 pub use provider_calls::*;
+pub use rag::{handle_rag_delete, handle_rag_query, handle_rag_upsert};
+pub use rerank::*;
+pub use signing::signing_snippet;
 pub use websocket::*;
@@ -0,0 +1,236 @@
+//! 容量错误自动降级
+//!
+//! 上游返回模型过载时，按 `server.model_downgrade.rules` 把同一请求改用更小的模型重试。
+//! 状态码命中 `status_codes` 直接视为容量错误；其他错误状态码读取错误体，
+//! 包含 `error_patterns` 中的关键字时同样视为容量错误。
+//!
+//! 降级在 Provider 调用层进行：沿用已选定的凭证重新调用上游，不会重复认证与受限 Key 计数。
+//! 发生降级的响应带有 `x-lime-model-downgraded-from` 与 `x-lime-model-downgraded-to` 头。
+//! 降级目标不在受限 Key 的模型范围内（调用方返回 401/403）时，返回原始的容量错误。
+
+use std::future::Future;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{HeaderName, HeaderValue, StatusCode},
+    response::Response,
+};
+use lime_core::config::ModelDowngradeSettings;
+use lime_core::logger::LogStore;
+
+use crate::middleware::cors::wildcard_match;
+
+/// 读取错误体的上限
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+const DOWNGRADED_FROM_HEADER: &str = "x-lime-model-downgraded-from";
+const DOWNGRADED_TO_HEADER: &str = "x-lime-model-downgraded-to";
+
+/// 查找模型的降级目标
+fn downgrade_target<'a>(settings: &'a ModelDowngradeSettings, model: &str) -> Option<&'a str> {
+    settings
+        .rules
+        .iter()
+        .find(|rule| wildcard_match(&rule.from, model))
+        .map(|rule| rule.to.as_str())
+        .filter(|target| !target.is_empty() && !target.eq_ignore_ascii_case(model))
+}
+
+/// 判断响应是否为容量错误，需要读取错误体时会重建响应
+async fn detect_capacity_error(
+    settings: &ModelDowngradeSettings,
+    response: Response,
+) -> (Response, bool) {
+    let status = response.status();
+    if settings.status_codes.contains(&status.as_u16()) {
+        return (response, true);
+    }
+    if !(status.is_client_error() || status.is_server_error()) || settings.error_patterns.is_empty()
+    {
+        return (response, false);
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return (Response::from_parts(parts, Body::empty()), false),
+    };
+    let text = String::from_utf8_lossy(&bytes).to_lowercase();
+    let matched = settings
+        .error_patterns
+        .iter()
+        .any(|pattern| !pattern.is_empty() && text.contains(&pattern.to_lowercase()));
+    (Response::from_parts(parts, Body::from(bytes)), matched)
+}
+
+fn annotate(mut response: Response, from: &str, to: &str) -> Response {
+    for (name, value) in [(DOWNGRADED_FROM_HEADER, from), (DOWNGRADED_TO_HEADER, to)] {
+        if let Ok(value) = HeaderValue::from_str(value) {
+            response
+                .headers_mut()
+                .insert(HeaderName::from_static(name), value);
+        }
+    }
+    response
+}
+
+/// 响应实际使用的降级模型（未降级时为 `None`）
+pub(crate) fn downgraded_to(response: &Response) -> Option<String> {
+    response
+        .headers()
+        .get(DOWNGRADED_TO_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// 调用上游，遇到容量错误时按规则降级模型重试
+///
+/// `set_model` 用于在请求副本上替换模型。
+pub(crate) async fn call_with_downgrade<R, F, Fut>(
+    settings: &ModelDowngradeSettings,
+    logs: &tokio::sync::RwLock<LogStore>,
+    request: R,
+    model: &str,
+    set_model: impl Fn(&mut R, String),
+    call: F,
+) -> Response
+where
+    R: Clone,
+    F: Fn(R) -> Fut,
+    Fut: Future<Output = Response>,
+{
+    if !settings.enabled || settings.rules.is_empty() {
+        return call(request).await;
+    }
+
+    let mut current = model.to_string();
+    let mut tried = vec![current.to_lowercase()];
+    let mut response = call(request.clone()).await;
+    for _ in 0..settings.max_hops {
+        let Some(target) = downgrade_target(settings, &current) else {
+            break;
+        };
+        if tried.contains(&target.to_lowercase()) {
+            break;
+        }
+        let (checked, capacity_error) = detect_capacity_error(settings, response).await;
+        response = checked;
+        if !capacity_error {
+            break;
+        }
+
+        let message = format!(
+            "[DOWNGRADE] 模型 {} 容量不足（HTTP {}），降级为 {} 重试",
+            current,
+            response.status().as_u16(),
+            target
+        );
+        tracing::warn!("{}", message);
+        logs.write().await.add("warn", &message);
+
+        let mut retry = request.clone();
+        set_model(&mut retry, target.to_string());
+        let retried = call(retry).await;
+        if matches!(
+            retried.status(),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+        ) {
+            break;
+        }
+        response = retried;
+        current = target.to_string();
+        tried.push(current.to_lowercase());
+    }
+
+    if current.eq_ignore_ascii_case(model) {
+        response
+    } else {
+        annotate(response, model, &current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lime_core::config::ModelDowngradeRule;
+    use std::sync::Mutex;
+
+    fn settings() -> ModelDowngradeSettings {
+        ModelDowngradeSettings {
+            enabled: true,
+            rules: vec![
+                ModelDowngradeRule {
+                    from: "claude-opus-*".to_string(),
+                    to: "claude-sonnet-4-5".to_string(),
+                },
+                ModelDowngradeRule {
+                    from: "claude-sonnet-4-5".to_string(),
+                    to: "claude-haiku-4-5".to_string(),
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn respond(status: u16, body: &str) -> Response {
+        let mut response = Response::new(Body::from(body.to_string()));
+        *response.status_mut() = StatusCode::from_u16(status).unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_downgrades_on_capacity_error() {
+        let logs = tokio::sync::RwLock::new(LogStore::with_custom_config(1, false));
+        let calls = Mutex::new(Vec::new());
+        let response = call_with_downgrade(
+            &settings(),
+            &logs,
+            "claude-opus-4-1".to_string(),
+            "claude-opus-4-1",
+            |request, model| *request = model,
+            |model: String| {
+                calls.lock().unwrap().push(model.clone());
+                async move {
+                    if model.starts_with("claude-opus") {
+                        respond(503, r#"{"error":{"type":"overloaded_error"}}"#)
+                    } else {
+                        respond(200, "{}")
+                    }
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec!["claude-opus-4-1", "claude-sonnet-4-5"]
+        );
+        assert_eq!(
+            downgraded_to(&response).as_deref(),
+            Some("claude-sonnet-4-5")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keeps_non_capacity_errors() {
+        let logs = tokio::sync::RwLock::new(LogStore::with_custom_config(1, false));
+        let response = call_with_downgrade(
+            &settings(),
+            &logs,
+            "claude-opus-4-1".to_string(),
+            "claude-opus-4-1",
+            |request, model| *request = model,
+            |_model: String| async { respond(400, r#"{"error":"bad request"}"#) },
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(response
+            .headers()
+            .get("x-lime-model-downgraded-to")
+            .is_none());
+        let body = to_bytes(response.into_body(), 1024).await.unwrap();
+        assert_eq!(&body[..], br#"{"error":"bad request"}"#);
+    }
+}
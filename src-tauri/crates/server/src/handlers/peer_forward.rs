@@ -0,0 +1,206 @@
+//! 对等实例转发
+//!
+//! 本地凭证池没有可用凭证时，按配置顺序把请求转发给对等实例：
+//! - 使用对等实例自己的 API Key 认证，保留 `X-Provider-Id` 等路由头
+//! - 通过 `x-lime-forward-hops` 计数，超过 `max_hops` 的请求不再转发，避免循环
+//! - 对等实例同样无可用凭证（503）或连接失败时尝试下一个
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, StatusCode},
+    response::Response,
+};
+use lime_core::config::PeerInstance;
+
+use crate::AppState;
+
+/// 转发跳数请求头
+pub const FORWARD_HOPS_HEADER: &str = "x-lime-forward-hops";
+/// 响应头：实际处理请求的对等实例
+pub const FORWARDED_TO_HEADER: &str = "x-lime-forwarded-to";
+
+/// 随请求转发的路由相关头
+const PASSTHROUGH_HEADERS: &[&str] = &[
+    "x-provider-id",
+    "anthropic-version",
+    "anthropic-beta",
+    "user-agent",
+    "idempotency-key",
+];
+
+/// 转发共用的 HTTP 客户端（复用连接池），超时按请求设置
+fn peer_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(reqwest::Client::new)
+}
+
+fn incoming_hops(headers: &HeaderMap) -> u32 {
+    headers
+        .get(FORWARD_HOPS_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+fn peer_label(peer: &PeerInstance) -> &str {
+    if peer.name.trim().is_empty() {
+        &peer.base_url
+    } else {
+        &peer.name
+    }
+}
+
+/// 构建发往对等实例的请求
+fn build_peer_request(
+    client: &reqwest::Client,
+    peer: &PeerInstance,
+    path: &str,
+    headers: &HeaderMap,
+    body: &serde_json::Value,
+    hops: u32,
+) -> reqwest::RequestBuilder {
+    let url = format!("{}{}", peer.base_url.trim_end_matches('/'), path);
+    let mut request = client
+        .post(url)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(FORWARD_HOPS_HEADER, (hops + 1).to_string())
+        .json(body);
+    if !peer.api_key.is_empty() {
+        request = request
+            .bearer_auth(&peer.api_key)
+            .header("x-api-key", &peer.api_key);
+    }
+    for name in PASSTHROUGH_HEADERS {
+        if let Some(value) = headers.get(*name).and_then(|v| v.to_str().ok()) {
+            request = request.header(*name, value);
+        }
+    }
+    request
+}
+
+/// 把对等实例的响应原样返回给客户端（流式响应逐块透传）
+fn relay_response(resp: reqwest::Response, peer: &PeerInstance) -> Response {
+    let status = StatusCode::from_u16(resp.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+    let mut builder = Response::builder()
+        .status(status)
+        .header(FORWARDED_TO_HEADER, peer_label(peer));
+    for name in [
+        header::CONTENT_TYPE,
+        header::CACHE_CONTROL,
+        header::RETRY_AFTER,
+    ] {
+        if let Some(value) = resp.headers().get(name.as_str()) {
+            builder = builder.header(name, value.as_bytes());
+        }
+    }
+    builder
+        .body(Body::from_stream(resp.bytes_stream()))
+        .unwrap_or_else(|_| Response::new(Body::empty()))
+}
+
+/// 尝试把请求转发给对等实例，没有对等实例可以处理时返回 `None`
+pub async fn forward_to_peers(
+    state: &AppState,
+    headers: &HeaderMap,
+    path: &str,
+    body: &serde_json::Value,
+    request_id: &str,
+) -> Option<Response> {
+    let settings = &state.peer_forwarding;
+    if !settings.enabled || settings.peers.is_empty() {
+        return None;
+    }
+    let hops = incoming_hops(headers);
+    if hops >= settings.max_hops {
+        tracing::debug!(
+            "[PEER] request_id={} 已转发 {} 跳，不再继续转发",
+            request_id,
+            hops
+        );
+        return None;
+    }
+
+    let timeout = Duration::from_secs(settings.timeout_secs.max(1));
+    for peer in &settings.peers {
+        let label = peer_label(peer);
+        let result = build_peer_request(peer_client(), peer, path, headers, body, hops)
+            .timeout(timeout)
+            .send()
+            .await;
+        match result {
+            Ok(resp) if resp.status() == reqwest::StatusCode::SERVICE_UNAVAILABLE => {
+                tracing::info!(
+                    "[PEER] request_id={} 对等实例 {} 同样无可用凭证",
+                    request_id,
+                    label
+                );
+            }
+            Ok(resp) => {
+                state.logs.write().await.add(
+                    "info",
+                    &format!(
+                        "[PEER] request_id={} forwarded to peer {} status={}",
+                        request_id,
+                        label,
+                        resp.status().as_u16()
+                    ),
+                );
+                lime_providers::response_headers::record(resp.headers());
+                return Some(relay_response(resp, peer));
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "[PEER] request_id={} 转发到 {} 失败: {}",
+                    request_id,
+                    label,
+                    e
+                );
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_peer_request_increments_hops_and_passes_routing_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(FORWARD_HOPS_HEADER, HeaderValue::from_static("1"));
+        headers.insert("x-provider-id", HeaderValue::from_static("deepseek"));
+        headers.insert(
+            "authorization",
+            HeaderValue::from_static("Bearer local-key"),
+        );
+        let peer = PeerInstance {
+            name: "peer-a".to_string(),
+            base_url: "http://10.0.0.6:8999/".to_string(),
+            api_key: "peer-key".to_string(),
+        };
+
+        let request = build_peer_request(
+            &reqwest::Client::new(),
+            &peer,
+            "/v1/chat/completions",
+            &headers,
+            &serde_json::json!({"model": "m"}),
+            incoming_hops(&headers),
+        )
+        .build()
+        .unwrap();
+
+        assert_eq!(
+            request.url().as_str(),
+            "http://10.0.0.6:8999/v1/chat/completions"
+        );
+        assert_eq!(request.headers()[FORWARD_HOPS_HEADER], "2");
+        assert_eq!(request.headers()["x-provider-id"], "deepseek");
+        assert_eq!(request.headers()["authorization"], "Bearer peer-key");
+    }
+}
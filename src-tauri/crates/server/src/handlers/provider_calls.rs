@@ -49,32 +49,106 @@ use axum::{
 };
 use futures::StreamExt;
 
+use super::provider_dispatch::{provider_error_response, resolve_provider};
 use crate::AppState;
+use lime_core::i18n::{self, MessageCode};
 use lime_core::models::anthropic::AnthropicMessagesRequest;
 use lime_core::models::openai::ChatCompletionRequest;
 use lime_core::models::provider_pool_model::{CredentialData, ProviderCredential};
 use lime_providers::converter::anthropic_to_openai::convert_anthropic_to_openai;
-use lime_providers::converter::openai_to_antigravity::{
-    convert_antigravity_to_openai_response, convert_openai_to_antigravity_with_context,
-};
+use lime_providers::converter::{code_execution, grounding};
+use lime_providers::outbound_limit;
 use lime_providers::providers::{
-    AntigravityProvider, ClaudeCustomProvider, CodexProvider, KiroProvider, OpenAICustomProvider,
+    ClaudeCustomProvider, CodexProvider, KiroProvider, OpenAICustomProvider, Provider,
     VertexProvider,
 };
+use lime_providers::response_headers;
 use lime_providers::session::store_thought_signature;
 use lime_providers::stream::{PipelineConfig, StreamPipeline};
 use lime_providers::streaming::traits::StreamingProvider;
 use lime_providers::streaming::{
-    StreamConfig, StreamContext, StreamError, StreamFormat as StreamingFormat, StreamManager,
-    StreamResponse,
+    GeminiAnthropicSseConverter, StreamConfig, StreamContext, StreamError,
+    StreamFormat as StreamingFormat, StreamManager, StreamResponse,
 };
 use lime_server_utils::{
-    build_anthropic_response, build_anthropic_stream_response, build_error_response,
-    build_error_response_with_status, parse_cw_response, safe_truncate, CWParsedResponse,
+    build_anthropic_response, build_anthropic_response_with_citations,
+    build_anthropic_stream_response, parse_cw_response, safe_truncate, CWParsedResponse,
 };
 
+/// 以 Anthropic SSE 格式转发 Antigravity 流式响应
+///
+/// 上游 Gemini chunk 由 `GeminiAnthropicSseConverter` 逐个转换，
+/// 事件分帧（message_start / content_block_* / message_delta / message_stop）与官方 API 一致。
+async fn stream_antigravity_as_anthropic(
+    state: &AppState,
+    credential: &ProviderCredential,
+    provider: &dyn Provider,
+    openai_request: &ChatCompletionRequest,
+) -> Response {
+    let stream_response = match provider.chat_stream(openai_request).await {
+        Ok((_, stream_response)) => stream_response,
+        Err(provider_err) => {
+            if let Some(db) = &state.db {
+                let _ = state.pool_service.mark_unhealthy(
+                    db,
+                    &credential.uuid,
+                    Some(&provider_err.to_string()),
+                );
+            }
+            return provider_error_response(&provider_err);
+        }
+    };
+
+    if let Some(db) = &state.db {
+        let _ = state
+            .pool_service
+            .mark_healthy(db, &credential.uuid, Some(&openai_request.model));
+        let _ = state.pool_service.record_usage(db, &credential.uuid);
+    }
+
+    let mut converter =
+        GeminiAnthropicSseConverter::new(&openai_request.model, state.citations.keep);
+    let sse_stream = async_stream::stream! {
+        let mut upstream = stream_response;
+        while let Some(result) = upstream.next().await {
+            let events = match result {
+                Ok(bytes) => converter.push_bytes(&bytes),
+                Err(e) => {
+                    tracing::error!("[ANTIGRAVITY_STREAM] 流式响应中断: {}", e);
+                    converter.fail(&e.to_string())
+                }
+            };
+            for event in events {
+                yield Ok::<_, std::io::Error>(axum::body::Bytes::from(event));
+            }
+        }
+        for event in converter.finish() {
+            yield Ok::<_, std::io::Error>(axum::body::Bytes::from(event));
+        }
+    };
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/event-stream")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .header(header::CONNECTION, "keep-alive")
+        .header("X-Accel-Buffering", "no")
+        .body(Body::from_stream(sse_stream))
+        .unwrap_or_else(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(
+                    serde_json::json!({"error": {"message": "Failed to build streaming response"}}),
+                ),
+            )
+                .into_response()
+        })
+}
+
 /// 根据凭证调用 Provider (Anthropic 格式)
 ///
+/// 发出的上游请求计入该凭证的出站限额（见 [`lime_providers::outbound_limit`]）。
+///
 /// # 参数
 /// - `state`: 应用状态
 /// - `credential`: 凭证信息
@@ -85,6 +159,19 @@ pub async fn call_provider_anthropic(
     credential: &ProviderCredential,
     request: &AnthropicMessagesRequest,
     flow_id: Option<&str>,
+) -> Response {
+    outbound_limit::scope(
+        &credential.uuid,
+        call_provider_anthropic_inner(state, credential, request, flow_id),
+    )
+    .await
+}
+
+async fn call_provider_anthropic_inner(
+    state: &AppState,
+    credential: &ProviderCredential,
+    request: &AnthropicMessagesRequest,
+    flow_id: Option<&str>,
 ) -> Response {
     match &credential.credential {
         CredentialData::KiroOAuth { creds_file_path } => {
@@ -100,7 +187,7 @@ pub async fn call_provider_anthropic(
                 None => {
                     return (
                         StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(serde_json::json!({"error": {"message": "Database not available"}})),
+                        Json(serde_json::json!({"error": {"message": i18n::t(MessageCode::DatabaseUnavailable)}})),
                     )
                         .into_response();
                 }
@@ -169,6 +256,7 @@ pub async fn call_provider_anthropic(
                         .into_response();
                 }
             };
+            response_headers::record(resp.headers());
             let status = resp.status();
             if status.is_success() {
                 match resp.bytes().await {
@@ -229,6 +317,7 @@ pub async fn call_provider_anthropic(
                 kiro.credentials.access_token = Some(new_token);
                 match kiro.call_api(&openai_request).await {
                     Ok(retry_resp) => {
+                        response_headers::record(retry_resp.headers());
                         if retry_resp.status().is_success() {
                             match retry_resp.bytes().await {
                                 Ok(bytes) => {
@@ -310,96 +399,34 @@ pub async fn call_provider_anthropic(
             )
                 .into_response()
         }
-        CredentialData::AntigravityOAuth {
-            creds_file_path,
-            project_id,
-        } => {
-            let mut antigravity = AntigravityProvider::new();
-            if let Err(e) = antigravity
-                .load_credentials_from_path(creds_file_path)
-                .await
-            {
-                // 记录凭证加载失败
-                if let Some(db) = &state.db {
-                    let _ = state.pool_service.mark_unhealthy(
-                        db,
-                        &credential.uuid,
-                        Some(&format!("Failed to load credentials: {e}")),
-                    );
-                }
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({"error": {"message": format!("Failed to load Antigravity credentials: {}", e)}})),
+        CredentialData::AntigravityOAuth { .. } => {
+            let provider = match resolve_provider(state, credential, &request.model).await {
+                Ok(provider) => provider,
+                Err(response) => return response,
+            };
+            let openai_request = convert_anthropic_to_openai(request);
+            // 流式请求逐 chunk 转换为 Anthropic 事件序列
+            if request.stream {
+                return stream_antigravity_as_anthropic(
+                    state,
+                    credential,
+                    provider.as_ref(),
+                    &openai_request,
                 )
-                    .into_response();
-            }
-
-            // 使用新的 validate_token() 方法检查 Token 状态
-            let validation_result = antigravity.validate_token();
-            tracing::info!("[Antigravity] Token 验证结果: {:?}", validation_result);
-
-            // 根据验证结果决定是否刷新
-            if validation_result.needs_refresh() {
-                tracing::info!("[Antigravity] Token 需要刷新，开始刷新...");
-                match antigravity.refresh_token_with_retry(3).await {
-                    Ok(new_token) => {
-                        tracing::info!("[Antigravity] Token 刷新成功，新 token 长度: {}", new_token.len());
-                        // 刷新成功，标记为健康
-                        if let Some(db) = &state.db {
-                            let _ = state.pool_service.mark_healthy(
-                                db,
-                                &credential.uuid,
-                                None,
-                            );
-                        }
-                    }
-                    Err(refresh_error) => {
-                        tracing::error!("[Antigravity] Token 刷新失败: {:?}", refresh_error);
-                        // 使用新的 mark_unhealthy_with_details 方法
-                        if let Some(db) = &state.db {
-                            let _ = state.pool_service.mark_unhealthy_with_details(
-                                db,
-                                &credential.uuid,
-                                &refresh_error,
-                            );
-                        }
-
-                        // 根据错误类型返回不同的状态码和消息
-                        let (status, message) = if refresh_error.requires_reauth() {
-                            (StatusCode::UNAUTHORIZED, refresh_error.user_message())
-                        } else {
-                            (StatusCode::INTERNAL_SERVER_ERROR, refresh_error.user_message())
-                        };
-
-                        return (
-                            status,
-                            Json(serde_json::json!({"error": {"message": message}})),
-                        )
-                            .into_response();
-                    }
-                }
-            }
-
-            // 设置项目 ID
-            if let Some(pid) = project_id {
-                antigravity.project_id = Some(pid.clone());
-            } else if let Err(e) = antigravity.discover_project().await {
-                tracing::warn!("[Antigravity] Failed to discover project: {}", e);
+                .await;
             }
-            // 获取 project_id 用于请求
-            let proj_id = antigravity.project_id.clone().unwrap_or_default();
-            // 先转换为 OpenAI 格式，再转换为 Antigravity 格式
-            let openai_request = convert_anthropic_to_openai(request);
-            let antigravity_request = convert_openai_to_antigravity_with_context(&openai_request, &proj_id);
-            match antigravity
-                .generate_content(&request.model, &antigravity_request)
-                .await
-            {
+            match provider.chat(&openai_request).await {
                 Ok(resp) => {
-                    // 转换为 OpenAI 格式，再构建 Anthropic 响应
-                    let content = resp["candidates"][0]["content"]["parts"][0]["text"]
-                        .as_str()
-                        .unwrap_or("");
+                    // 从 OpenAI 格式响应构建 Anthropic 响应
+                    let message = &resp["choices"][0]["message"];
+                    let content = message["content"].as_str().unwrap_or("");
+                    // 搜索接地引用转换为 Anthropic citations
+                    let citations = match message["annotations"].as_array() {
+                        Some(annotations) if state.citations.keep => {
+                            grounding::annotations_to_anthropic_citations(annotations, content)
+                        }
+                        _ => Vec::new(),
+                    };
                     let parsed = CWParsedResponse {
                         content: content.to_string(),
                         tool_calls: Vec::new(),
@@ -415,24 +442,18 @@ pub async fn call_provider_anthropic(
                         );
                         let _ = state.pool_service.record_usage(db, &credential.uuid);
                     }
-                    if request.stream {
-                        build_anthropic_stream_response(&request.model, &parsed)
-                    } else {
-                        build_anthropic_response(&request.model, &parsed)
-                    }
+                    build_anthropic_response_with_citations(&request.model, &parsed, &citations)
                 }
-                Err(api_err) => {
+                Err(provider_err) => {
                     // 记录 API 调用失败
                     if let Some(db) = &state.db {
                         let _ = state.pool_service.mark_unhealthy(
                             db,
                             &credential.uuid,
-                            Some(&api_err.message),
+                            Some(&provider_err.to_string()),
                         );
                     }
-
-                    // 直接使用 AntigravityApiError 的状态码构建响应
-                    build_error_response_with_status(api_err.status_code, &api_err.to_string())
+                    provider_error_response(&provider_err)
                 }
             }
         }
@@ -441,6 +462,7 @@ pub async fn call_provider_anthropic(
             let openai_request = convert_anthropic_to_openai(request);
             match openai.call_api(&openai_request).await {
                 Ok(resp) => {
+                    response_headers::record(resp.headers());
                     let status = resp.status();
                     if status.is_success() {
                         match resp.text().await {
@@ -571,6 +593,7 @@ pub async fn call_provider_anthropic(
             );
             match claude.call_api(request).await {
                 Ok(resp) => {
+                    response_headers::record(resp.headers());
                     let status = resp.status();
                     // 打印响应状态
                     state.logs.write().await.add(
@@ -715,6 +738,7 @@ pub async fn call_provider_anthropic(
             let vertex = VertexProvider::with_config(api_key.clone(), base_url.clone());
             match vertex.chat_completions(&serde_json::to_value(&openai_request).unwrap_or_default()).await {
                 Ok(resp) => {
+                    response_headers::record(resp.headers());
                     let status = resp.status();
                     match resp.text().await {
                         Ok(body) => {
@@ -786,6 +810,7 @@ pub async fn call_provider_anthropic(
             );
             match claude.call_api(request).await {
                 Ok(resp) => {
+                    response_headers::record(resp.headers());
                     let status = resp.status();
                     state.logs.write().await.add(
                         "info",
@@ -904,15 +929,33 @@ pub async fn call_provider_anthropic(
 
 /// 根据凭证调用 Provider (OpenAI 格式)
 ///
+/// 发出的上游请求计入该凭证的出站限额（见 [`lime_providers::outbound_limit`]）。
+///
 /// # 参数
 /// - `state`: 应用状态
 /// - `credential`: 凭证信息
 /// - `request`: OpenAI 格式请求
+/// - `extra_params`: 类型化请求之外的透传参数（如 `logprobs`、`seed`，仅 OpenAI 兼容接口转发）
 /// - `flow_id`: Flow ID（可选，用于流式响应处理）
 pub async fn call_provider_openai(
     state: &AppState,
     credential: &ProviderCredential,
     request: &ChatCompletionRequest,
+    extra_params: &serde_json::Map<String, serde_json::Value>,
+    flow_id: Option<&str>,
+) -> Response {
+    outbound_limit::scope(
+        &credential.uuid,
+        call_provider_openai_inner(state, credential, request, extra_params, flow_id),
+    )
+    .await
+}
+
+async fn call_provider_openai_inner(
+    state: &AppState,
+    credential: &ProviderCredential,
+    request: &ChatCompletionRequest,
+    extra_params: &serde_json::Map<String, serde_json::Value>,
     _flow_id: Option<&str>,
 ) -> Response {
     let _start_time = std::time::Instant::now();
@@ -944,7 +987,7 @@ pub async fn call_provider_openai(
                 None => {
                     return (
                         StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(serde_json::json!({"error": {"message": "Database not available"}})),
+                        Json(serde_json::json!({"error": {"message": i18n::t(MessageCode::DatabaseUnavailable)}})),
                     )
                         .into_response();
                 }
@@ -1120,6 +1163,7 @@ pub async fn call_provider_openai(
             // 非流式请求处理
             match kiro.call_api(request).await {
                 Ok(resp) => {
+                    response_headers::record(resp.headers());
                     let status = resp.status();
                     if status.is_success() {
                         // 记录成功
@@ -1219,93 +1263,22 @@ pub async fn call_provider_openai(
             eprintln!("[ANTIGRAVITY] 模型: {}", request.model);
             eprintln!("[ANTIGRAVITY] 流式: {}", request.stream);
 
-            let mut antigravity = AntigravityProvider::new();
-            if let Err(e) = antigravity.load_credentials_from_path(creds_file_path).await {
-                eprintln!("[ANTIGRAVITY] 加载凭证失败: {e}");
-                // 记录凭证加载失败
-                if let Some(db) = &state.db {
-                    let _ = state.pool_service.mark_unhealthy(
-                        db,
-                        &credential.uuid,
-                        Some(&format!("Failed to load credentials: {e}")),
-                    );
-                }
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({"error": {"message": format!("Failed to load Antigravity credentials: {}", e)}})),
-                )
-                    .into_response();
-            }
-            eprintln!("[ANTIGRAVITY] 凭证加载成功");
-
-            // 使用新的 validate_token() 方法检查 Token 状态
-            let validation_result = antigravity.validate_token();
-            eprintln!("[ANTIGRAVITY] Token 验证结果: {validation_result:?}");
-            eprintln!("[ANTIGRAVITY] needs_refresh() = {}", validation_result.needs_refresh());
-            tracing::info!("[Antigravity] Token 验证结果: {:?}", validation_result);
-
-            // 根据验证结果决定是否刷新
-            if validation_result.needs_refresh() {
-                eprintln!("[ANTIGRAVITY] Token 需要刷新，开始刷新...");
-                tracing::info!("[Antigravity] Token 需要刷新，开始刷新...");
-                match antigravity.refresh_token_with_retry(3).await {
-                    Ok(new_token) => {
-                        eprintln!("[ANTIGRAVITY] Token 刷新成功，新 token 长度: {}", new_token.len());
-                        tracing::info!("[Antigravity] Token 刷新成功，新 token 长度: {}", new_token.len());
-                        // 刷新成功，标记为健康
-                        if let Some(db) = &state.db {
-                            let _ = state.pool_service.mark_healthy(
-                                db,
-                                &credential.uuid,
-                                None,
-                            );
-                        }
-                    }
-                    Err(refresh_error) => {
-                        eprintln!("[ANTIGRAVITY] Token 刷新失败: {refresh_error:?}");
-                        tracing::error!("[Antigravity] Token 刷新失败: {:?}", refresh_error);
-                        // 使用新的 mark_unhealthy_with_details 方法
-                        if let Some(db) = &state.db {
-                            let _ = state.pool_service.mark_unhealthy_with_details(
-                                db,
-                                &credential.uuid,
-                                &refresh_error,
-                            );
-                        }
-
-                        // 根据错误类型返回不同的状态码和消息
-                        let (status, message) = if refresh_error.requires_reauth() {
-                            (StatusCode::UNAUTHORIZED, refresh_error.user_message())
-                        } else {
-                            (StatusCode::INTERNAL_SERVER_ERROR, refresh_error.user_message())
-                        };
-
-                        return (
-                            status,
-                            Json(serde_json::json!({"error": {"message": message}})),
-                        )
-                            .into_response();
-                    }
+            let provider = match resolve_provider(state, credential, &request.model).await {
+                Ok(provider) => provider,
+                Err(response) => {
+                    eprintln!("[ANTIGRAVITY] 凭证准备失败");
+                    return response;
                 }
-            } else {
-                eprintln!("[ANTIGRAVITY] Token 不需要刷新，继续使用现有 Token");
-            }
-
-            // 设置项目 ID
-            if let Some(pid) = project_id {
-                antigravity.project_id = Some(pid.clone());
-            } else if let Err(e) = antigravity.discover_project().await {
-                tracing::warn!("[Antigravity] Failed to discover project: {}", e);
-            }
+            };
+            eprintln!("[ANTIGRAVITY] 凭证准备完成");
 
-            tracing::info!("[ANTIGRAVITY] request.stream = {}, model = {}, project_id = {:?}",
-                request.stream, request.model, antigravity.project_id);
+            tracing::info!("[ANTIGRAVITY] request.stream = {}, model = {}",
+                request.stream, request.model);
 
             // 检查是否为流式请求
             if request.stream {
                 tracing::info!("[ANTIGRAVITY_STREAM] ========== 开始处理流式请求 ==========");
-                tracing::info!("[ANTIGRAVITY_STREAM] model={}, has_token={}",
-                    request.model, antigravity.credentials.access_token.is_some());
+                tracing::info!("[ANTIGRAVITY_STREAM] model={}", request.model);
 
                 // 检查是否是图片生成模型
                 // 注意：gemini-3-pro-image-preview 是支持图片理解的模型，不是图片生成模型
@@ -1319,38 +1292,10 @@ pub async fn call_provider_openai(
                 if is_image_generation_model {
                     tracing::info!("[ANTIGRAVITY_STREAM] 图片生成模型，使用非流式请求");
 
-                    // 获取 project_id 用于请求
-                    let proj_id = antigravity.project_id.clone().unwrap_or_default();
-                    // 转换请求格式 - 这已经是完整的 Antigravity 请求格式
-                    let antigravity_request = convert_openai_to_antigravity_with_context(request, &proj_id);
-
-                    // 直接调用 call_api，因为 antigravity_request 已经是完整格式
-                    match antigravity.call_api("generateContent", &antigravity_request).await {
-                        Ok(resp) => {
-                            let resp_str = serde_json::to_string_pretty(&resp).unwrap_or_default();
-                            if is_lime_debug_enabled() {
-                                let debug_dir = lime_core::app_paths::resolve_logs_dir()
-                                    .unwrap_or_else(|_| std::env::temp_dir().join("lime").join("logs"));
-                                let _ = std::fs::create_dir_all(&debug_dir);
-                                let debug_file = debug_dir.join("antigravity_image_response.json");
-                                let _ = std::fs::write(&debug_file, &resp_str);
-                                tracing::info!(
-                                    "[ANTIGRAVITY_STREAM] 原始响应已保存到: {:?}, 大小: {} bytes",
-                                    debug_file,
-                                    resp_str.len()
-                                );
-                                eprintln!(
-                                    "[ANTIGRAVITY_STREAM] 原始响应已保存到: {:?}, 大小: {} bytes",
-                                    debug_file,
-                                    resp_str.len()
-                                );
-                            }
-
+                    match provider.chat(request).await {
+                        Ok(openai_response) => {
                             tracing::info!("[ANTIGRAVITY_STREAM] 图片生成完成，转换为流式响应");
 
-                            // 将非流式响应转换为 OpenAI 格式
-                            let openai_response = convert_antigravity_to_openai_response(&resp, &request.model);
-
                             let openai_str = serde_json::to_string_pretty(&openai_response).unwrap_or_default();
                             if is_lime_debug_enabled() {
                                 let debug_dir = lime_core::app_paths::resolve_logs_dir()
@@ -1439,16 +1384,15 @@ pub async fn call_provider_openai(
                                         .into_response()
                                 });
                         }
-                        Err(api_err) => {
-                            tracing::error!("[ANTIGRAVITY_STREAM] 图片生成失败 (HTTP {}): {}", api_err.status_code, api_err.message);
-                            // 直接使用 AntigravityApiError 的状态码构建响应
-                            return build_error_response_with_status(api_err.status_code, &api_err.to_string());
+                        Err(provider_err) => {
+                            tracing::error!("[ANTIGRAVITY_STREAM] 图片生成失败: {}", provider_err);
+                            return provider_error_response(&provider_err);
                         }
                     }
                 }
 
-                match antigravity.call_api_stream(request).await {
-                    Ok(stream_response) => {
+                match provider.chat_stream(request).await {
+                    Ok((_, stream_response)) => {
                         eprintln!("[ANTIGRAVITY_STREAM] ✓ 流式响应已建立");
                         tracing::info!("[ANTIGRAVITY_STREAM] ✓ 流式响应已建立");
 
@@ -1460,7 +1404,15 @@ pub async fn call_provider_openai(
 
                         // 在后台任务中收集所有数据
                         let model_clone = model.clone();
-                        tokio::spawn(async move {
+                        let output = AccumulatedOutput {
+                            keep_citations: state.citations.keep,
+                            stop_on_first_image: state.chat_images.stop_on_first_image,
+                            media_base_url: state
+                                .chat_images
+                                .persist
+                                .then(|| super::media::media_base_url(&state.base_url)),
+                        };
+                        let collector = tokio::spawn(async move {
                             use futures::StreamExt;
                             let mut stream = stream_response;
                             let mut all_data = String::new();
@@ -1491,12 +1443,14 @@ pub async fn call_provider_openai(
 
                             // 尝试解析累积的 JSON 数据
                             // Antigravity 返回格式: { "response": { "candidates": [...] } }
-                            let result = parse_antigravity_accumulated_response(&all_data, &model_clone);
+                            let result = parse_antigravity_accumulated_response(&all_data, &model_clone, &output);
                             let _ = tx.send(result);
                         });
 
-                        // 等待数据收集完成，然后构建 SSE 响应
+                        // 等待数据收集完成，然后构建 SSE 响应；客户端断开时取消后台收集
+                        let collector_guard = super::abort::AbortOnDrop(collector.abort_handle());
                         let sse_stream = async_stream::stream! {
+                            let _collector_guard = collector_guard;
                             match rx.await {
                                 Ok(Ok(sse_content)) => {
                                     // 返回累积的 SSE 事件
@@ -1536,8 +1490,7 @@ pub async fn call_provider_openai(
                             });
                     }
                     Err(provider_err) => {
-                        // call_api_stream 返回 ProviderError，使用字符串解析状态码
-                        return build_error_response(&provider_err.to_string());
+                        return provider_error_response(&provider_err);
                     }
                 }
             }
@@ -1546,34 +1499,32 @@ pub async fn call_provider_openai(
             eprintln!("[ANTIGRAVITY_OPENAI] ========== 开始处理非流式请求 ==========");
             eprintln!("[ANTIGRAVITY_OPENAI] 模型: {}", request.model);
 
-            // 获取 project_id 用于请求
-            let proj_id = antigravity.project_id.clone().unwrap_or_default();
-            eprintln!("[ANTIGRAVITY_OPENAI] 项目ID: {proj_id}");
-
-            // 转换请求格式
-            eprintln!("[ANTIGRAVITY_OPENAI] 开始转换请求格式...");
-            let antigravity_request = convert_openai_to_antigravity_with_context(request, &proj_id);
-            eprintln!("[ANTIGRAVITY_OPENAI] 请求格式转换完成");
-
-            eprintln!("[ANTIGRAVITY_OPENAI] 调用 generate_content...");
-            match antigravity.generate_content(&request.model, &antigravity_request).await {
-                Ok(resp) => {
-                    eprintln!("[ANTIGRAVITY_OPENAI] generate_content 返回成功");
-                    let openai_response = convert_antigravity_to_openai_response(&resp, &request.model);
+            eprintln!("[ANTIGRAVITY_OPENAI] 调用 chat...");
+            match provider.chat(request).await {
+                Ok(mut openai_response) => {
+                    eprintln!("[ANTIGRAVITY_OPENAI] chat 返回成功");
+                    if !state.citations.keep {
+                        grounding::strip_annotations(&mut openai_response);
+                    }
+                    if state.chat_images.persist {
+                        super::media::persist_response_images(
+                            &mut openai_response,
+                            &super::media::media_base_url(&state.base_url),
+                        );
+                    }
                     eprintln!("[ANTIGRAVITY_OPENAI] ========== 非流式请求处理完成 ==========");
                     Json(openai_response).into_response()
                 }
-                Err(api_err) => {
-                    eprintln!("[ANTIGRAVITY_OPENAI] generate_content 失败 (HTTP {}): {}", api_err.status_code, api_err.message);
+                Err(provider_err) => {
+                    eprintln!("[ANTIGRAVITY_OPENAI] chat 失败: {provider_err}");
                     eprintln!("[ANTIGRAVITY_OPENAI] ========== 非流式请求处理失败 ==========");
-
-                    // 直接使用 AntigravityApiError 的状态码构建响应
-                    build_error_response_with_status(api_err.status_code, &api_err.to_string())
+                    provider_error_response(&provider_err)
                 }
             }
         }
         CredentialData::OpenAIKey { api_key, base_url } => {
-            let openai = OpenAICustomProvider::with_config(api_key.clone(), base_url.clone());
+            let openai = OpenAICustomProvider::with_config(api_key.clone(), base_url.clone())
+                .with_extra_params(extra_params.clone());
 
             tracing::info!("[OPENAI_KEY] request.stream = {}, model = {}", request.stream, request.model);
 
@@ -1623,6 +1574,7 @@ pub async fn call_provider_openai(
             // 非流式请求处理
             match openai.call_api(request).await {
                 Ok(resp) => {
+                    response_headers::record(resp.headers());
                     if resp.status().is_success() {
                         match resp.text().await {
                             Ok(body) => {
@@ -1775,8 +1727,13 @@ pub async fn call_provider_openai(
             let mut modified_request = request.clone();
             modified_request.model = resolved_model;
             let vertex = VertexProvider::with_config(api_key.clone(), base_url.clone());
-            match vertex.chat_completions(&serde_json::to_value(&modified_request).unwrap_or_default()).await {
+            let mut payload = serde_json::to_value(&modified_request).unwrap_or_default();
+            if let Some(object) = payload.as_object_mut() {
+                object.extend(extra_params.clone());
+            }
+            match vertex.chat_completions(&payload).await {
                 Ok(resp) => {
+                    response_headers::record(resp.headers());
                     if resp.status().is_success() {
                         match resp.text().await {
                             Ok(body) => {
@@ -1808,7 +1765,8 @@ pub async fn call_provider_openai(
         CredentialData::AnthropicKey { api_key, base_url } => {
             // 如果有自定义 base_url，假设是 OpenAI 兼容的代理服务器
             if let Some(custom_url) = base_url {
-                let openai = OpenAICustomProvider::with_config(api_key.clone(), Some(custom_url.clone()));
+                let openai = OpenAICustomProvider::with_config(api_key.clone(), Some(custom_url.clone()))
+                    .with_extra_params(extra_params.clone());
                 state.logs.write().await.add(
                     "info",
                     &format!(
@@ -1879,6 +1837,7 @@ pub async fn call_provider_openai(
 
                 match openai.call_api(request).await {
                     Ok(resp) => {
+                        response_headers::record(resp.headers());
                         let status = resp.status();
                         state.logs.write().await.add(
                             "info",
@@ -1997,6 +1956,7 @@ pub async fn call_provider_openai(
             // 调用 Codex API
             match codex.call_api(&request_json).await {
                 Ok(response) => {
+                    response_headers::record(response.headers());
                     let status = response.status();
                     let headers = response.headers().clone();
 
@@ -2604,7 +2564,7 @@ pub async fn handle_kiro_stream(
             tracing::error!("[KIRO_STREAM] 数据库不可用");
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({"error": {"message": "Database not available"}})),
+                Json(serde_json::json!({"error": {"message": i18n::t(MessageCode::DatabaseUnavailable)}})),
             )
                 .into_response();
         }
@@ -2871,7 +2831,11 @@ fn is_lime_debug_enabled() -> bool {
 ///   }
 /// }
 /// ```
-fn parse_antigravity_accumulated_response(data: &str, model: &str) -> Result<String, String> {
+fn parse_antigravity_accumulated_response(
+    data: &str,
+    model: &str,
+    output: &AccumulatedOutput,
+) -> Result<String, String> {
     eprintln!(
         "[ANTIGRAVITY_PARSE] 开始解析累积数据，大小: {} bytes",
         data.len()
@@ -2904,7 +2868,7 @@ fn parse_antigravity_accumulated_response(data: &str, model: &str) -> Result<Str
     // 首先尝试直接解析为单个 JSON
     if let Ok(json) = serde_json::from_str::<serde_json::Value>(data) {
         eprintln!("[ANTIGRAVITY_PARSE] 单个 JSON 解析成功");
-        return parse_antigravity_json(&json, model);
+        return parse_antigravity_json(&json, model, output);
     }
 
     // 如果失败，尝试按行解析，找到包含 candidates 的 JSON
@@ -2912,6 +2876,7 @@ fn parse_antigravity_accumulated_response(data: &str, model: &str) -> Result<Str
 
     let mut all_text = String::new();
     let mut all_images: Vec<(String, String)> = Vec::new(); // (mime_type, data)
+    let mut grounding_metadata: Option<serde_json::Value> = None;
     let mut found_any = false;
 
     for line in data.lines() {
@@ -2927,6 +2892,9 @@ fn parse_antigravity_accumulated_response(data: &str, model: &str) -> Result<Str
                 all_images.extend(images);
                 found_any = true;
             }
+            if let Some(metadata) = find_grounding_metadata(&json) {
+                grounding_metadata = Some(metadata.clone());
+            }
         }
     }
 
@@ -2936,7 +2904,12 @@ fn parse_antigravity_accumulated_response(data: &str, model: &str) -> Result<Str
             all_text.len(),
             all_images.len()
         );
-        return build_sse_response(&all_text, &all_images, model);
+        let annotations = grounding_annotations(
+            grounding_metadata.as_ref(),
+            &all_text,
+            output.keep_citations,
+        );
+        return build_sse_response(&all_text, &all_images, &annotations, output, model);
     }
 
     // 如果还是失败，尝试找到 JSON 对象的边界
@@ -2949,7 +2922,7 @@ fn parse_antigravity_accumulated_response(data: &str, model: &str) -> Result<Str
         // 尝试从这个位置解析 JSON
         if let Ok(json) = serde_json::from_str::<serde_json::Value>(&data[json_start..]) {
             eprintln!("[ANTIGRAVITY_PARSE] 在位置 {json_start} 找到有效 JSON");
-            return parse_antigravity_json(&json, model);
+            return parse_antigravity_json(&json, model, output);
         }
         start = json_start + 1;
         if start >= data.len() {
@@ -3022,12 +2995,17 @@ fn extract_content_from_json(json: &serde_json::Value) -> Option<(String, Vec<(S
 
                 let has_content = part.get("text").is_some()
                     || part.get("inlineData").is_some()
-                    || part.get("inline_data").is_some();
+                    || part.get("inline_data").is_some()
+                    || code_execution::is_code_execution_part(part);
 
                 if has_thought_signature && !has_content {
                     continue;
                 }
 
+                if let Some(t) = code_execution::code_execution_part_text(part) {
+                    text.push_str(&t);
+                }
+
                 if let Some(t) = part.get("text").and_then(|t| t.as_str()) {
                     if is_thought {
                         // 思维内容
@@ -3068,8 +3046,35 @@ fn extract_content_from_json(json: &serde_json::Value) -> Option<(String, Vec<(S
     }
 }
 
+/// 查找搜索接地元数据（流式分片中通常只有最后一片携带完整的元数据，后出现的覆盖先出现的）
+fn find_grounding_metadata(json: &serde_json::Value) -> Option<&serde_json::Value> {
+    json.get("response")
+        .and_then(|r| r.get("candidates"))
+        .or_else(|| json.get("candidates"))
+        .and_then(|c| c.as_array())?
+        .iter()
+        .filter_map(|candidate| candidate.get("groundingMetadata"))
+        .last()
+}
+
+/// 将搜索接地元数据转换为 OpenAI 引用标注（配置为不保留时返回空）
+fn grounding_annotations(
+    metadata: Option<&serde_json::Value>,
+    text: &str,
+    keep_citations: bool,
+) -> Vec<serde_json::Value> {
+    match metadata {
+        Some(metadata) if keep_citations => grounding::grounding_to_annotations(metadata, text),
+        _ => Vec::new(),
+    }
+}
+
 /// 解析 Antigravity JSON 响应
-fn parse_antigravity_json(json: &serde_json::Value, model: &str) -> Result<String, String> {
+fn parse_antigravity_json(
+    json: &serde_json::Value,
+    model: &str,
+    output: &AccumulatedOutput,
+) -> Result<String, String> {
     eprintln!(
         "[ANTIGRAVITY_PARSE] 解析 JSON，顶层类型: {}",
         if json.is_object() {
@@ -3089,7 +3094,9 @@ fn parse_antigravity_json(json: &serde_json::Value, model: &str) -> Result<Strin
     }
 
     if let Some((text, images)) = extract_content_from_json(json) {
-        return build_sse_response(&text, &images, model);
+        let annotations =
+            grounding_annotations(find_grounding_metadata(json), &text, output.keep_citations);
+        return build_sse_response(&text, &images, &annotations, output, model);
     }
 
     // 如果是数组，尝试处理每个元素
@@ -3097,33 +3104,60 @@ fn parse_antigravity_json(json: &serde_json::Value, model: &str) -> Result<Strin
         eprintln!("[ANTIGRAVITY_PARSE] 顶层是数组，长度: {}", arr.len());
         let mut all_text = String::new();
         let mut all_images = Vec::new();
+        let mut grounding_metadata = None;
 
         for item in arr {
             if let Some((text, images)) = extract_content_from_json(item) {
                 all_text.push_str(&text);
                 all_images.extend(images);
             }
+            if let Some(metadata) = find_grounding_metadata(item) {
+                grounding_metadata = Some(metadata);
+            }
         }
 
         if !all_text.is_empty() || !all_images.is_empty() {
-            return build_sse_response(&all_text, &all_images, model);
+            let annotations =
+                grounding_annotations(grounding_metadata, &all_text, output.keep_citations);
+            return build_sse_response(&all_text, &all_images, &annotations, output, model);
         }
     }
 
     Err("响应中没有 candidates".to_string())
 }
 
+/// 累积流式响应转换为 SSE 时的输出选项
+#[derive(Debug, Clone, Default)]
+struct AccumulatedOutput {
+    /// 保留搜索接地引用
+    keep_citations: bool,
+    /// 只保留第一张图片
+    stop_on_first_image: bool,
+    /// 图片保存到媒体目录时的 URL 前缀（未开启保存时为空）
+    media_base_url: Option<String>,
+}
+
 /// 构建 SSE 响应
 fn build_sse_response(
     text: &str,
     images: &[(String, String)],
+    annotations: &[serde_json::Value],
+    output: &AccumulatedOutput,
     model: &str,
 ) -> Result<String, String> {
     let mut content = text.to_string();
 
     // 添加图片
-    for (mime, data) in images {
-        let image_url = format!("data:{mime};base64,{data}");
+    let image_limit = if output.stop_on_first_image {
+        1
+    } else {
+        images.len()
+    };
+    for (mime, data) in images.iter().take(image_limit) {
+        let image_url = match &output.media_base_url {
+            Some(base_url) => super::media::persist_image(mime, data, base_url),
+            None => format!("data:{mime};base64,{data}"),
+        };
         content.push_str(&format!("\n\n![Generated Image]({image_url})"));
     }
 
@@ -3135,6 +3169,10 @@ fn build_sse_response(
     let mut sse_output = String::new();
 
     if !content.is_empty() {
+        let mut delta = serde_json::json!({ "content": content });
+        if !annotations.is_empty() {
+            delta["annotations"] = serde_json::Value::Array(annotations.to_vec());
+        }
         let content_chunk = serde_json::json!({
             "id": &chunk_id,
             "object": "chat.completion.chunk",
@@ -3142,7 +3180,7 @@ fn build_sse_response(
             "model": model,
             "choices": [{
                 "index": 0,
-                "delta": { "content": content },
+                "delta": delta,
                 "finish_reason": serde_json::Value::Null
             }]
         });
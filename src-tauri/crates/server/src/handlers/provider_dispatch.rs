@@ -0,0 +1,130 @@
+//! 通用 Provider 分发
+//!
+//! 通过 Provider 注册表为选中的凭证准备 Provider，
+//! 并统一处理准备阶段的失败：凭证加载失败或 Token 刷新失败时标记凭证不健康，
+//! 返回与原先各处理器一致的错误响应。
+
+use std::sync::Arc;
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use lime_core::models::provider_pool_model::ProviderCredential;
+use lime_providers::providers::{
+    Provider, ProviderContext, ProviderError, ProviderRegistry, ProviderSetupError,
+};
+use lime_server_utils::{build_error_response, build_error_response_with_status};
+
+use crate::deps::{CredentialPool, HandlerDeps};
+use crate::AppState;
+
+/// 为凭证准备 Provider
+///
+/// 失败时已记录凭证健康状态，调用方只需把错误转换为自己的响应格式。
+pub async fn prepare_provider(
+    state: &AppState,
+    credential: &ProviderCredential,
+    model: &str,
+) -> Result<Arc<dyn Provider>, ProviderSetupError> {
+    let context = ProviderContext {
+        model: model.to_string(),
+        code_execution: state.code_execution.enabled_for(model),
+        chat_images: state.chat_images.clone(),
+    };
+    prepare_with(
+        state.credential_pool.as_ref(),
+        &state.provider_registry,
+        credential,
+        &context,
+    )
+    .await
+}
+
+/// 为凭证准备 Provider，失败时返回 HTTP 错误响应
+pub async fn resolve_provider(
+    state: &AppState,
+    credential: &ProviderCredential,
+    model: &str,
+) -> Result<Arc<dyn Provider>, Response> {
+    prepare_provider(state, credential, model)
+        .await
+        .map_err(|error| setup_error_response(&error))
+}
+
+/// 使用注入的依赖准备 Provider，失败时返回 HTTP 错误响应
+pub async fn resolve_with(
+    deps: &HandlerDeps,
+    credential: &ProviderCredential,
+    context: &ProviderContext,
+) -> Result<Arc<dyn Provider>, Response> {
+    prepare_with(deps.pool.as_ref(), &deps.providers, credential, context)
+        .await
+        .map_err(|error| setup_error_response(&error))
+}
+
+async fn prepare_with(
+    pool: &dyn CredentialPool,
+    registry: &ProviderRegistry,
+    credential: &ProviderCredential,
+    context: &ProviderContext,
+) -> Result<Arc<dyn Provider>, ProviderSetupError> {
+    match registry.resolve(credential, context).await {
+        Ok(resolved) => {
+            if resolved.token_refreshed {
+                let _ = pool.mark_healthy(&credential.uuid, None);
+            }
+            Ok(resolved.provider)
+        }
+        Err(error) => {
+            record_setup_failure(pool, credential, &error);
+            Err(error)
+        }
+    }
+}
+
+fn record_setup_failure(
+    pool: &dyn CredentialPool,
+    credential: &ProviderCredential,
+    error: &ProviderSetupError,
+) {
+    match error {
+        ProviderSetupError::LoadCredentials { message, .. } => {
+            let _ = pool.mark_unhealthy(
+                &credential.uuid,
+                Some(&format!("Failed to load credentials: {message}")),
+            );
+        }
+        ProviderSetupError::TokenRefresh(refresh_error) => {
+            let _ = pool.mark_unhealthy_with_details(&credential.uuid, refresh_error);
+        }
+        ProviderSetupError::Unsupported(_) => {}
+    }
+}
+
+fn setup_error_response(error: &ProviderSetupError) -> Response {
+    let status = match error {
+        ProviderSetupError::TokenRefresh(refresh_error) if refresh_error.requires_reauth() => {
+            StatusCode::UNAUTHORIZED
+        }
+        ProviderSetupError::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+
+    (
+        status,
+        Json(serde_json::json!({"error": {"message": error.to_string()}})),
+    )
+        .into_response()
+}
+
+/// Provider 调用失败时的错误响应
+///
+/// 携带上游状态码的错误按原状态码返回，其余错误按错误信息推断。
+pub fn provider_error_response(error: &ProviderError) -> Response {
+    match error.status_code() {
+        Some(status) => build_error_response_with_status(status, &error.to_string()),
+        None => build_error_response(&error.to_string()),
+    }
+}
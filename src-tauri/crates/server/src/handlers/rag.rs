@@ -0,0 +1,497 @@
+//! 本地 RAG API 处理器
+//!
+//! - `POST /v1/rag/documents`：向量化并写入文档
+//! - `DELETE /v1/rag/documents/:id`：删除文档
+//! - `POST /v1/rag/query`：检索与查询最相近的文档
+//! - `/v1/chat/completions` 与 `/v1/messages` 自动注入检索上下文（见 [`augment_chat_request`]、
+//!   [`augment_anthropic_request`]）
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+
+use crate::handlers::embeddings::embed_texts;
+use crate::handlers::{check_scoped_key_model, verify_inbound_api_key, verify_scoped_key_model};
+use crate::rag::{build_context_prompt, RagHit};
+use crate::AppState;
+use lime_core::errors::GatewayErrorCode;
+use lime_core::models::anthropic::AnthropicMessagesRequest;
+use lime_core::models::openai::{ChatCompletionRequest, ChatMessage, ContentPart, MessageContent};
+use lime_server_utils::build_error_response_with_meta;
+
+/// 单条待写入文档
+#[derive(Debug, Deserialize)]
+pub struct RagDocumentInput {
+    /// 文档 ID（缺省时自动生成）
+    #[serde(default)]
+    pub id: Option<String>,
+    pub content: String,
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// 文档写入请求
+#[derive(Debug, Deserialize)]
+pub struct RagUpsertRequest {
+    #[serde(default)]
+    pub collection: Option<String>,
+    pub documents: Vec<RagDocumentInput>,
+}
+
+/// 检索请求
+#[derive(Debug, Deserialize)]
+pub struct RagQueryRequest {
+    pub query: String,
+    #[serde(default)]
+    pub collection: Option<String>,
+    #[serde(default)]
+    pub top_k: Option<usize>,
+    #[serde(default)]
+    pub min_score: Option<f32>,
+}
+
+/// 删除请求的查询参数
+#[derive(Debug, Deserialize)]
+pub struct RagDeleteQuery {
+    #[serde(default)]
+    pub collection: Option<String>,
+}
+
+fn rag_error(status: StatusCode, message: &str, code: GatewayErrorCode) -> Response {
+    build_error_response_with_meta(status.as_u16(), message, None, None, Some(code))
+}
+
+fn ensure_rag_enabled(state: &AppState) -> Result<(), Response> {
+    if state.rag_store.is_enabled() {
+        Ok(())
+    } else {
+        Err(rag_error(
+            StatusCode::NOT_FOUND,
+            "RAG is disabled (set server.rag.enabled = true)",
+            GatewayErrorCode::InvalidRequest,
+        ))
+    }
+}
+
+/// 使用 RAG 配置的嵌入模型向量化文本（经由嵌入缓存）
+async fn embed_for_rag(state: &AppState, texts: &[String]) -> Result<Vec<Vec<f32>>, Response> {
+    let settings = state.rag_store.settings();
+    let template = serde_json::json!({ "model": settings.embedding_model });
+    embed_texts(
+        state,
+        &settings.embedding_provider,
+        &template,
+        &settings.embedding_model,
+        None,
+        texts,
+    )
+    .await
+    .map(|batch| batch.vectors)
+}
+
+/// 检索指定集合
+async fn retrieve(
+    state: &AppState,
+    collection: &str,
+    query: &str,
+    top_k: usize,
+    min_score: f32,
+) -> Result<Vec<RagHit>, Response> {
+    let vectors = embed_for_rag(state, &[query.to_string()]).await?;
+    let Some(query_vector) = vectors.into_iter().next() else {
+        return Ok(Vec::new());
+    };
+    state
+        .rag_store
+        .search(collection, &query_vector, top_k, min_score)
+        .map_err(|e| {
+            rag_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &e,
+                GatewayErrorCode::InternalError,
+            )
+        })
+}
+
+/// 写入文档
+///
+/// # 端点
+/// `POST /v1/rag/documents`
+pub async fn handle_rag_upsert(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<RagUpsertRequest>,
+) -> Response {
+    if let Err(e) = verify_inbound_api_key(&headers, &state).await {
+        return e.into_response();
+    }
+    if let Err(resp) = ensure_rag_enabled(&state) {
+        return resp;
+    }
+    let embedding_model = &state.rag_store.settings().embedding_model;
+    if let Err(e) = verify_scoped_key_model(&headers, &state, embedding_model) {
+        return e.into_response();
+    }
+    if request.documents.is_empty()
+        || request
+            .documents
+            .iter()
+            .any(|d| d.content.trim().is_empty())
+    {
+        return rag_error(
+            StatusCode::BAD_REQUEST,
+            "documents must be a non-empty list with non-empty content",
+            GatewayErrorCode::InvalidRequest,
+        );
+    }
+
+    let collection = state
+        .rag_store
+        .collection_or_default(request.collection.as_deref());
+    let texts: Vec<String> = request
+        .documents
+        .iter()
+        .map(|d| d.content.clone())
+        .collect();
+    let vectors = match embed_for_rag(&state, &texts).await {
+        Ok(vectors) => vectors,
+        Err(resp) => return resp,
+    };
+
+    let documents: Vec<_> = request
+        .documents
+        .into_iter()
+        .zip(vectors)
+        .map(|(doc, vector)| {
+            let id = doc
+                .id
+                .filter(|id| !id.trim().is_empty())
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+            (id, doc.content, doc.metadata, vector)
+        })
+        .collect();
+    let ids: Vec<String> = documents.iter().map(|(id, ..)| id.clone()).collect();
+
+    match state.rag_store.upsert(&collection, documents) {
+        Ok(written) => {
+            state.logs.write().await.add(
+                "info",
+                &format!("[RAG] collection={collection} upserted={written}"),
+            );
+            Json(serde_json::json!({
+                "object": "rag.upsert",
+                "collection": collection,
+                "ids": ids,
+            }))
+            .into_response()
+        }
+        Err(e) => rag_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &e,
+            GatewayErrorCode::InternalError,
+        ),
+    }
+}
+
+/// 检索文档
+///
+/// # 端点
+/// `POST /v1/rag/query`
+pub async fn handle_rag_query(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<RagQueryRequest>,
+) -> Response {
+    if let Err(e) = verify_inbound_api_key(&headers, &state).await {
+        return e.into_response();
+    }
+    if let Err(resp) = ensure_rag_enabled(&state) {
+        return resp;
+    }
+    let embedding_model = &state.rag_store.settings().embedding_model;
+    if let Err(e) = verify_scoped_key_model(&headers, &state, embedding_model) {
+        return e.into_response();
+    }
+    if request.query.trim().is_empty() {
+        return rag_error(
+            StatusCode::BAD_REQUEST,
+            "query is required",
+            GatewayErrorCode::InvalidRequest,
+        );
+    }
+
+    let settings = state.rag_store.settings();
+    let collection = state
+        .rag_store
+        .collection_or_default(request.collection.as_deref());
+    let top_k = request.top_k.unwrap_or(settings.top_k).max(1);
+    let min_score = request.min_score.unwrap_or(settings.min_score);
+
+    match retrieve(&state, &collection, &request.query, top_k, min_score).await {
+        Ok(hits) => Json(serde_json::json!({
+            "object": "list",
+            "collection": collection,
+            "data": hits,
+        }))
+        .into_response(),
+        Err(resp) => resp,
+    }
+}
+
+/// 删除文档
+///
+/// # 端点
+/// `DELETE /v1/rag/documents/:id?collection=...`
+pub async fn handle_rag_delete(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Query(query): Query<RagDeleteQuery>,
+) -> Response {
+    if let Err(e) = verify_inbound_api_key(&headers, &state).await {
+        return e.into_response();
+    }
+    if let Err(resp) = ensure_rag_enabled(&state) {
+        return resp;
+    }
+
+    let collection = state
+        .rag_store
+        .collection_or_default(query.collection.as_deref());
+    match state.rag_store.delete(&collection, &id) {
+        Ok(true) => {
+            state.logs.write().await.add(
+                "info",
+                &format!("[RAG] collection={collection} deleted={id}"),
+            );
+            Json(serde_json::json!({
+                "object": "rag.document.deleted",
+                "collection": collection,
+                "id": id,
+                "deleted": true,
+            }))
+            .into_response()
+        }
+        Ok(false) => rag_error(
+            StatusCode::NOT_FOUND,
+            &format!("Document '{id}' not found in collection '{collection}'"),
+            GatewayErrorCode::InvalidRequest,
+        ),
+        Err(e) => rag_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            &e,
+            GatewayErrorCode::InternalError,
+        ),
+    }
+}
+
+/// 提取最后一条 user 消息的文本
+fn last_user_text(request: &ChatCompletionRequest) -> Option<String> {
+    let message = request.messages.iter().rev().find(|m| m.role == "user")?;
+    let text = match message.content.as_ref()? {
+        MessageContent::Text(text) => text.clone(),
+        MessageContent::Parts(parts) => parts
+            .iter()
+            .filter_map(|part| match part {
+                ContentPart::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    };
+    let text = text.trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// 提取 Anthropic 请求最后一条 user 消息的文本
+fn last_user_text_anthropic(request: &AnthropicMessagesRequest) -> Option<String> {
+    let message = request.messages.iter().rev().find(|m| m.role == "user")?;
+    let text = match &message.content {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(blocks) => blocks
+            .iter()
+            .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|block| block.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => return None,
+    };
+    let text = text.trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// 按配置/请求头检索注入上下文，返回上下文提示词与命中文档数
+///
+/// - `server.rag.auto_augment = true` 时默认开启，`x-lime-rag: off` 可按请求关闭
+/// - 未开启自动注入时，`x-lime-rag: on` 可按请求开启
+/// - `x-lime-rag-collection` 指定检索集合
+///
+/// 检索失败不会中断请求，仅记录警告。
+async fn retrieve_context(
+    state: &AppState,
+    headers: &HeaderMap,
+    query: Option<String>,
+) -> Option<(String, usize)> {
+    if !state.rag_store.is_enabled() {
+        return None;
+    }
+    let settings = state.rag_store.settings();
+    let header_switch = headers
+        .get("x-lime-rag")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_ascii_lowercase());
+    let enabled = match header_switch.as_deref() {
+        Some("on") | Some("true") | Some("1") => true,
+        Some("off") | Some("false") | Some("0") => false,
+        _ => settings.auto_augment,
+    };
+    if !enabled {
+        return None;
+    }
+    // 受限 Key 不允许嵌入模型时不注入
+    if check_scoped_key_model(headers, state, &settings.embedding_model).is_err() {
+        return None;
+    }
+    let query = query?;
+
+    let collection = state.rag_store.collection_or_default(
+        headers
+            .get("x-lime-rag-collection")
+            .and_then(|v| v.to_str().ok()),
+    );
+    let hits = match retrieve(
+        state,
+        &collection,
+        &query,
+        settings.top_k,
+        settings.min_score,
+    )
+    .await
+    {
+        Ok(hits) => hits,
+        Err(_) => {
+            tracing::warn!("[RAG] 检索失败，跳过上下文注入 collection={}", collection);
+            return None;
+        }
+    };
+    let context = build_context_prompt(&hits, settings.max_context_chars)?;
+
+    state.logs.write().await.add(
+        "info",
+        &format!(
+            "[RAG] 注入检索上下文 collection={} hits={}",
+            collection,
+            hits.len()
+        ),
+    );
+    Some((context, hits.len()))
+}
+
+/// 为 Chat 请求注入检索上下文（开关见 [`retrieve_context`]），返回注入的文档数
+pub async fn augment_chat_request(
+    state: &AppState,
+    headers: &HeaderMap,
+    request: &mut ChatCompletionRequest,
+) -> usize {
+    let Some((context, hits)) = retrieve_context(state, headers, last_user_text(request)).await
+    else {
+        return 0;
+    };
+
+    // 插入到开头的 system 消息之后，保留客户端自带的系统提示词优先级
+    let insert_at = request
+        .messages
+        .iter()
+        .take_while(|m| m.role == "system")
+        .count();
+    request.messages.insert(
+        insert_at,
+        ChatMessage {
+            role: "system".to_string(),
+            content: Some(MessageContent::Text(context)),
+            tool_calls: None,
+            tool_call_id: None,
+            reasoning_content: None,
+        },
+    );
+    hits
+}
+
+/// 把上下文追加到 Anthropic `system` 末尾（字符串形式转为文本块数组）
+fn append_system_text(system: Option<serde_json::Value>, text: String) -> serde_json::Value {
+    let block = serde_json::json!({ "type": "text", "text": text });
+    match system {
+        Some(serde_json::Value::Array(mut blocks)) => {
+            blocks.push(block);
+            serde_json::Value::Array(blocks)
+        }
+        Some(serde_json::Value::String(existing)) if !existing.trim().is_empty() => {
+            serde_json::json!([{ "type": "text", "text": existing }, block])
+        }
+        _ => serde_json::Value::String(text),
+    }
+}
+
+/// 为 Anthropic Messages 请求注入检索上下文（开关见 [`retrieve_context`]），返回注入的文档数
+///
+/// 上下文追加在客户端 `system` 之后，不影响已有系统提示词的缓存前缀。
+pub async fn augment_anthropic_request(
+    state: &AppState,
+    headers: &HeaderMap,
+    request: &mut AnthropicMessagesRequest,
+) -> usize {
+    let query = last_user_text_anthropic(request);
+    let Some((context, hits)) = retrieve_context(state, headers, query).await else {
+        return 0;
+    };
+    request.system = Some(append_system_text(request.system.take(), context));
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_user_text_should_use_latest_user_message() {
+        let request: ChatCompletionRequest = serde_json::from_value(serde_json::json!({
+            "model": "gpt-4o",
+            "messages": [
+                { "role": "system", "content": "sys" },
+                { "role": "user", "content": "first" },
+                { "role": "assistant", "content": "ok" },
+                { "role": "user", "content": [
+                    { "type": "text", "text": "  second  " }
+                ]}
+            ]
+        }))
+        .expect("请求应可解析");
+
+        assert_eq!(last_user_text(&request), Some("second".to_string()));
+    }
+
+    #[test]
+    fn append_system_text_should_keep_client_system_first() {
+        assert_eq!(
+            append_system_text(None, "ctx".to_string()),
+            serde_json::json!("ctx")
+        );
+        assert_eq!(
+            append_system_text(Some(serde_json::json!("sys")), "ctx".to_string()),
+            serde_json::json!([
+                { "type": "text", "text": "sys" },
+                { "type": "text", "text": "ctx" }
+            ])
+        );
+        let cached = serde_json::json!([
+            { "type": "text", "text": "sys", "cache_control": { "type": "ephemeral" } }
+        ]);
+        let merged = append_system_text(Some(cached), "ctx".to_string());
+        assert_eq!(merged[0]["cache_control"]["type"], "ephemeral");
+        assert_eq!(merged[1]["text"], "ctx");
+    }
+}
@@ -0,0 +1,41 @@
+//! 按地区代理状态接口
+//!
+//! 仅主 API Key（或通过管理接口 OIDC 认证的请求）可调用：
+//! - `GET /admin/regional-proxy`：当前配置与自动标记为经代理访问的主机
+//! - `DELETE /admin/regional-proxy`：清除自动标记，所有主机重新尝试直连
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use lime_providers::regional_proxy;
+
+use crate::auth::oidc::AdminIdentity;
+use crate::handlers::verify_admin_key;
+use crate::AppState;
+
+/// `GET /admin/regional-proxy`
+pub async fn get_regional_proxy(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    Json(regional_proxy::status()).into_response()
+}
+
+/// `DELETE /admin/regional-proxy`
+pub async fn clear_regional_proxy(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    Json(serde_json::json!({ "cleared": regional_proxy::clear() })).into_response()
+}
@@ -0,0 +1,452 @@
+//! 远程控制接口
+//!
+//! 供 CI 流水线等外部自动化调用（例如更新 Git 同步的配置文件后通知服务重载），
+//! 使用 `server.remote_control.secret` 做 HMAC 签名认证，签名方式与请求签名相同：
+//! - 签名串：`{timestamp}\n{METHOD}\n{path?query}\n{hex(sha256(body))}`
+//! - 请求头：`x-lime-timestamp`、`x-lime-signature`
+//!
+//! 接口：
+//! - `POST /admin/remote/reload-config`：从磁盘重新加载配置并应用到运行中的服务
+//! - `POST /admin/remote/providers/toggle`：暂停 / 恢复某类 Provider（与托盘开关共用暂停记录，
+//!   恢复时单独禁用的凭证保持禁用）
+//! - `POST /admin/remote/credentials/rotate`：刷新选中凭证的 Token，可同时轮换受限 Key
+//!
+//! 每次成功的操作都会以 `remote_control` 来源写入配置审计记录。未启用时接口返回 404。
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Json,
+};
+use lime_core::app_events::{publish_app_event, AppEvent, ConfigEvent};
+use lime_core::config::{
+    record_config_change, record_config_value_change, ConfigAuditSource, ReloadResult,
+    RemoteControlSettings, RequestSigningSettings,
+};
+use lime_core::i18n::{self, MessageCode};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::auth::lockout::note_auth_failure;
+use crate::handlers::bulk_admin::{
+    select_credentials, BulkItemResult, BulkItemStatus, BulkReport, CredentialSelector,
+};
+use crate::middleware::request_signing::{
+    RequestSigner, SigningError, SIGNATURE_HEADER, TIMESTAMP_HEADER,
+};
+use crate::{degraded_pool, AppState};
+
+/// 远程控制接口路径前缀（请求签名与管理接口 OIDC 中间件跳过该前缀）
+pub const REMOTE_CONTROL_PATH_PREFIX: &str = "/admin/remote/";
+
+/// 远程控制签名验证器
+pub struct RemoteControl {
+    signer: RequestSigner,
+}
+
+impl RemoteControl {
+    /// 未启用或未配置密钥时返回 `None`
+    pub fn from_settings(settings: &RemoteControlSettings) -> Option<Self> {
+        if !settings.enabled {
+            return None;
+        }
+        if settings.secret.is_empty() {
+            tracing::warn!("[REMOTE_CONTROL] 已启用远程控制但未配置 secret，接口不会生效");
+            return None;
+        }
+        Some(Self {
+            signer: RequestSigner::new(RequestSigningSettings {
+                enabled: true,
+                secret: settings.secret.clone(),
+                max_skew_secs: settings.max_skew_secs,
+                require_for_forwarded: false,
+            }),
+        })
+    }
+
+    /// 验证签名（含时间偏差与重放检查）
+    pub fn verify(
+        &self,
+        headers: &HeaderMap,
+        method: &str,
+        path: &str,
+        body: &[u8],
+        now: i64,
+    ) -> Result<(), SigningError> {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        self.signer.verify(
+            header(TIMESTAMP_HEADER),
+            header(SIGNATURE_HEADER),
+            method,
+            path,
+            body,
+            now,
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ToggleProviderRequest {
+    pub provider_type: String,
+    pub disabled: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RotateCredentialsRequest {
+    #[serde(flatten)]
+    pub selector: CredentialSelector,
+    /// 同时轮换全部受限 Key
+    #[serde(default)]
+    pub rotate_scoped_keys: bool,
+    /// 旧受限 Key 的宽限期（小时）
+    #[serde(default = "default_grace_hours")]
+    pub grace_hours: u64,
+}
+
+fn default_grace_hours() -> u64 {
+    24
+}
+
+fn error_response(status: StatusCode, message: String, error_type: &str) -> Response {
+    (
+        status,
+        Json(json!({
+            "error": {"message": message, "type": error_type}
+        })),
+    )
+        .into_response()
+}
+
+/// 验证签名并解析请求体（空请求体按 `{}` 解析）
+fn authorize<T: DeserializeOwned>(
+    state: &AppState,
+    method: &Method,
+    uri: &Uri,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<T, Response> {
+    let Some(remote) = state.remote_control.as_ref() else {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    };
+    let path = uri.path_and_query().map(|pq| pq.as_str()).unwrap_or("/");
+    if let Err(e) = remote.verify(
+        headers,
+        method.as_str(),
+        path,
+        body,
+        chrono::Utc::now().timestamp(),
+    ) {
+        note_auth_failure();
+        tracing::warn!("[REMOTE_CONTROL] 签名验证失败: {:?} {}", e, path);
+        return Err(error_response(
+            StatusCode::UNAUTHORIZED,
+            e.message().to_string(),
+            "authentication_error",
+        ));
+    }
+
+    let body = if body.iter().all(u8::is_ascii_whitespace) {
+        b"{}".as_slice()
+    } else {
+        body
+    };
+    serde_json::from_slice(body).map_err(|e| {
+        error_response(
+            StatusCode::BAD_REQUEST,
+            format!("请求体格式错误: {e}"),
+            "invalid_request_error",
+        )
+    })
+}
+
+/// 构建 `{section: {key: value}}` 形式的审计值
+fn audit_value(section: &str, entries: Map<String, Value>) -> Value {
+    let mut root = Map::new();
+    root.insert(section.to_string(), Value::Object(entries));
+    Value::Object(root)
+}
+
+/// `POST /admin/remote/reload-config`
+pub async fn reload_config(
+    State(state): State<AppState>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if let Err(response) = authorize::<Value>(&state, &method, &uri, &headers, &body) {
+        return response;
+    }
+    let Some(manager) = state.hot_reload_manager.clone() else {
+        return error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "配置热重载不可用".to_string(),
+            "service_unavailable",
+        );
+    };
+
+    let before = manager.config();
+    match manager.reload() {
+        ReloadResult::Success { .. } => {
+            let after = manager.config();
+            crate::update_processor_config(&state.processor, &after).await;
+            state
+                .degraded_pool
+                .reload(degraded_pool::credentials_from_config(&after));
+            lime_providers::providers::endpoints::configure(&after.providers);
+            record_config_change(ConfigAuditSource::RemoteControl, &before, &after);
+            publish_app_event(AppEvent::Config(ConfigEvent::Reloaded {
+                success: true,
+                message: None,
+            }));
+            state
+                .logs
+                .write()
+                .await
+                .add("info", "[REMOTE_CONTROL] 已按远程请求重载配置");
+            Json(json!({ "success": true })).into_response()
+        }
+        ReloadResult::RolledBack { error, .. } => {
+            tracing::warn!("[REMOTE_CONTROL] 配置重载失败，已回滚: {}", error);
+            publish_app_event(AppEvent::Config(ConfigEvent::Reloaded {
+                success: false,
+                message: Some(format!("已回滚: {error}")),
+            }));
+            error_response(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("配置重载失败，已回滚: {error}"),
+                "invalid_request_error",
+            )
+        }
+        ReloadResult::Failed { error, .. } => {
+            tracing::error!("[REMOTE_CONTROL] 配置重载失败: {}", error);
+            publish_app_event(AppEvent::Config(ConfigEvent::Reloaded {
+                success: false,
+                message: Some(error.clone()),
+            }));
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("配置重载失败: {error}"),
+                "server_error",
+            )
+        }
+    }
+}
+
+/// `POST /admin/remote/providers/toggle`
+pub async fn toggle_provider(
+    State(state): State<AppState>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let request: ToggleProviderRequest = match authorize(&state, &method, &uri, &headers, &body) {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+    let Some(db) = state.db.as_ref() else {
+        return error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            i18n::t(MessageCode::DatabaseUnavailable).to_string(),
+            "service_unavailable",
+        );
+    };
+
+    let updated =
+        match state
+            .pool_service
+            .set_provider_paused(db, &request.provider_type, request.disabled)
+        {
+            Ok(updated) => updated,
+            Err(e) => return error_response(StatusCode::BAD_REQUEST, e, "invalid_request_error"),
+        };
+    if updated > 0 {
+        let entry = |disabled: bool| {
+            let mut entries = Map::new();
+            entries.insert(
+                request.provider_type.clone(),
+                json!({ "disabled": disabled }),
+            );
+            audit_value("provider_pool", entries)
+        };
+        record_config_value_change(
+            ConfigAuditSource::RemoteControl,
+            &entry(!request.disabled),
+            &entry(request.disabled),
+        );
+    }
+    tracing::info!(
+        "[REMOTE_CONTROL] Provider 开关: {} disabled={} 更新={}",
+        request.provider_type,
+        request.disabled,
+        updated
+    );
+    Json(json!({
+        "provider_type": request.provider_type,
+        "disabled": request.disabled,
+        "updated": updated,
+    }))
+    .into_response()
+}
+
+/// `POST /admin/remote/credentials/rotate`
+///
+/// 替换受限 Key 的明文只在本次响应中返回
+pub async fn rotate_credentials(
+    State(state): State<AppState>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let request: RotateCredentialsRequest = match authorize(&state, &method, &uri, &headers, &body)
+    {
+        Ok(request) => request,
+        Err(response) => return response,
+    };
+    let Some(db) = state.db.as_ref() else {
+        return error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            i18n::t(MessageCode::DatabaseUnavailable).to_string(),
+            "service_unavailable",
+        );
+    };
+    let credentials = match select_credentials(&state, db, &request.selector) {
+        Ok(credentials) => credentials,
+        Err(response) => return response,
+    };
+
+    let now = chrono::Utc::now();
+    let mut results = Vec::new();
+    let mut rotated = Map::new();
+    for cred in credentials {
+        let name = cred.name.as_deref();
+        if cred.is_disabled {
+            results.push(
+                BulkItemResult::new(&cred.uuid, name, BulkItemStatus::Skipped)
+                    .with_message("已禁用"),
+            );
+            continue;
+        }
+        let outcome = state
+            .pool_service
+            .refresh_credential_token(db, &cred.uuid)
+            .await
+            .map(|_| ());
+        if outcome.is_ok() {
+            rotated.insert(cred.uuid.clone(), json!(now.to_rfc3339()));
+        }
+        results.push(BulkItemResult::from_result(&cred.uuid, name, outcome));
+    }
+
+    let mut scoped_keys = Map::new();
+    if request.rotate_scoped_keys {
+        match state.scoped_keys.rotate_all(request.grace_hours, now) {
+            Ok(issued) => {
+                for item in issued {
+                    let predecessor = item.record.predecessor_id.clone().unwrap_or_default();
+                    scoped_keys.insert(predecessor.clone(), json!(item.record.id));
+                    results.push(
+                        BulkItemResult::new(
+                            &predecessor,
+                            Some(item.record.label.as_str()),
+                            BulkItemStatus::Ok,
+                        )
+                        .with_detail(json!({
+                            "successor_id": item.record.id,
+                            "api_key": item.api_key,
+                            "key_prefix": item.record.key_prefix,
+                            "expires_at": item.record.expires_at,
+                        })),
+                    );
+                }
+            }
+            Err(e) => results.push(
+                BulkItemResult::new("scoped_keys", None, BulkItemStatus::Failed).with_message(e),
+            ),
+        }
+    }
+
+    if !rotated.is_empty() || !scoped_keys.is_empty() {
+        let empty = json!({ "credential_rotation": {}, "scoped_key_rotation": {} });
+        let after = json!({
+            "credential_rotation": rotated,
+            "scoped_key_rotation": scoped_keys,
+        });
+        record_config_value_change(ConfigAuditSource::RemoteControl, &empty, &after);
+    }
+
+    let report = BulkReport::new("remote_rotate", false, results);
+    tracing::info!(
+        "[REMOTE_CONTROL] 凭证轮换: 成功={} 失败={} 跳过={}",
+        report.succeeded,
+        report.failed,
+        report.skipped
+    );
+    Json(report).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::request_signing::{canonical_string, sign};
+
+    fn settings() -> RemoteControlSettings {
+        RemoteControlSettings {
+            enabled: true,
+            secret: "ci-secret".to_string(),
+            max_skew_secs: 300,
+        }
+    }
+
+    fn signed_headers(secret: &str, timestamp: i64, path: &str, body: &[u8]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let signature = sign(secret, &canonical_string(timestamp, "POST", path, body));
+        headers.insert(TIMESTAMP_HEADER, timestamp.to_string().parse().unwrap());
+        headers.insert(SIGNATURE_HEADER, signature.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_remote_control_requires_enabled_secret() {
+        assert!(RemoteControl::from_settings(&RemoteControlSettings::default()).is_none());
+        let mut no_secret = settings();
+        no_secret.secret.clear();
+        assert!(RemoteControl::from_settings(&no_secret).is_none());
+        assert!(RemoteControl::from_settings(&settings()).is_some());
+    }
+
+    #[test]
+    fn test_verify_signature_skew_and_replay() {
+        let remote = RemoteControl::from_settings(&settings()).unwrap();
+        let path = "/admin/remote/providers/toggle";
+        let body = br#"{"provider_type":"gemini","disabled":true}"#;
+        let now = 1_700_000_000;
+
+        let headers = signed_headers("ci-secret", now, path, body);
+        assert_eq!(remote.verify(&headers, "POST", path, body, now), Ok(()));
+        assert_eq!(
+            remote.verify(&headers, "POST", path, body, now),
+            Err(SigningError::Replayed)
+        );
+
+        let wrong_key = signed_headers("other", now + 1, path, body);
+        assert_eq!(
+            remote.verify(&wrong_key, "POST", path, body, now),
+            Err(SigningError::BadSignature)
+        );
+        let stale = signed_headers("ci-secret", now - 600, path, body);
+        assert_eq!(
+            remote.verify(&stale, "POST", path, body, now),
+            Err(SigningError::Expired)
+        );
+        assert_eq!(
+            remote.verify(&HeaderMap::new(), "POST", path, body, now),
+            Err(SigningError::Missing)
+        );
+    }
+}
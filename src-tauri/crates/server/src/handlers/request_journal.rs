@@ -0,0 +1,200 @@
+//! 在途请求日志管理接口
+//!
+//! 仅主 API Key（或通过管理接口 OIDC 认证的请求）可调用：
+//! - `GET /admin/journal`：在途请求数与上次运行中丢失的请求
+//! - `DELETE /admin/journal`：清除丢失请求列表
+//! - `GET /admin/journal/:id`：丢失请求详情（含请求体与重新执行结果）
+//! - `POST /admin/journal/replay`：以主 Key 重新执行主 Key 发起的、保存了请求体的后台请求
+//! - `POST /admin/journal/:id/replay`：重新执行单个请求；受限 Key 发起的请求需在请求体
+//!   `{"api_key": "..."}` 中提供该 Key，重放时不会改用主 Key
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+
+use lime_core::database::dao::proxy_transcript::PRIMARY_KEY_ID;
+use serde::Deserialize;
+
+use crate::auth::oidc::AdminIdentity;
+use crate::handlers::verify_admin_key;
+use crate::middleware::request_journal::loopback_base_url;
+use crate::AppState;
+
+/// `POST /admin/journal/:id/replay` 请求体
+#[derive(Debug, Default, Deserialize)]
+pub struct ReplayLostRequest {
+    /// 发起该请求的受限 Key 明文（主 Key 发起的请求无需提供）
+    #[serde(default)]
+    pub api_key: Option<String>,
+}
+
+fn journal_disabled() -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({
+            "error": {
+                "message": "Request journal is disabled",
+                "type": "not_found"
+            }
+        })),
+    )
+        .into_response()
+}
+
+fn replay_error(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(serde_json::json!({
+            "error": {
+                "message": message,
+                "type": "invalid_request_error"
+            }
+        })),
+    )
+        .into_response()
+}
+
+/// `GET /admin/journal`
+pub async fn get_journal(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    let Some(journal) = state.request_journal.as_ref() else {
+        return journal_disabled();
+    };
+    Json(serde_json::json!({
+        "in_flight": journal.in_flight(),
+        "lost": journal.lost(),
+    }))
+    .into_response()
+}
+
+/// `DELETE /admin/journal`
+pub async fn clear_journal(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    let Some(journal) = state.request_journal.as_ref() else {
+        return journal_disabled();
+    };
+    Json(serde_json::json!({ "cleared": journal.clear_lost() })).into_response()
+}
+
+/// `GET /admin/journal/:id`
+pub async fn get_lost_request(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+    Path(id): Path<String>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    let Some(journal) = state.request_journal.as_ref() else {
+        return journal_disabled();
+    };
+    match journal.lost_request(&id) {
+        Some(lost) => Json(lost).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// `POST /admin/journal/replay`
+pub async fn replay_journal(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    let Some(journal) = state.request_journal.clone() else {
+        return journal_disabled();
+    };
+    let replayed = journal
+        .replay(
+            &loopback_base_url(&state.base_url),
+            PRIMARY_KEY_ID,
+            &state.api_key,
+            None,
+        )
+        .await;
+    Json(serde_json::json!({ "replayed": replayed })).into_response()
+}
+
+/// `POST /admin/journal/:id/replay`
+pub async fn replay_lost_request(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+    Path(id): Path<String>,
+    body: Option<Json<ReplayLostRequest>>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    let Some(journal) = state.request_journal.clone() else {
+        return journal_disabled();
+    };
+    let Some(lost) = journal.lost_request(&id) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if !lost.is_replayable() {
+        return replay_error(StatusCode::CONFLICT, "Request is not replayable");
+    }
+
+    // 只用发起请求的同一个 Key 重放
+    let api_key = match lost.entry.key_id.as_deref() {
+        Some(PRIMARY_KEY_ID) => state.api_key.clone(),
+        Some(key_id) => {
+            let Some(api_key) = body.and_then(|Json(body)| body.api_key) else {
+                return replay_error(
+                    StatusCode::BAD_REQUEST,
+                    "api_key of the originating scoped key is required",
+                );
+            };
+            if !state
+                .scoped_keys
+                .record_for(&api_key)
+                .is_some_and(|record| record.id == key_id)
+            {
+                return replay_error(
+                    StatusCode::FORBIDDEN,
+                    "api_key does not match the key that sent this request",
+                );
+            }
+            api_key
+        }
+        None => {
+            return replay_error(
+                StatusCode::CONFLICT,
+                "Request was journaled without a key identity and cannot be replayed",
+            )
+        }
+    };
+
+    let key_id = lost.entry.key_id.clone().unwrap_or_default();
+    journal
+        .replay(
+            &loopback_base_url(&state.base_url),
+            &key_id,
+            &api_key,
+            Some(&id),
+        )
+        .await;
+    match journal.lost_request(&id) {
+        Some(lost) => Json(lost).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
@@ -0,0 +1,302 @@
+//! 重排序 API 处理器
+//!
+//! 实现 Cohere/Jina 兼容的 `/v1/rerank` 端点：
+//! - native：转发到上游 OpenAI 兼容服务的 `/rerank`（如 Jina、SiliconFlow）
+//! - llm：上游不支持时，用聊天模型按打分提示词为每个文档评分
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+
+use crate::handlers::embeddings::{
+    invalid_request, openai_provider_for, read_upstream_json, select_openai_credential,
+};
+use crate::handlers::{check_scoped_key_model, verify_inbound_api_key, verify_scoped_key_model};
+use crate::AppState;
+use lime_core::config::RerankMode;
+use lime_core::errors::GatewayErrorCode;
+use lime_core::models::provider_pool_model::ProviderCredential;
+use lime_server_utils::build_error_response_with_meta;
+
+/// 重排序请求（Cohere/Jina 兼容）
+#[derive(Debug, Clone, Deserialize)]
+pub struct RerankRequest {
+    pub model: String,
+    pub query: String,
+    /// 文档列表：字符串或 `{ "text": "..." }`
+    pub documents: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub top_n: Option<usize>,
+    #[serde(default)]
+    pub return_documents: bool,
+}
+
+/// 提取文档文本
+fn document_texts(documents: &[serde_json::Value]) -> Option<Vec<String>> {
+    documents
+        .iter()
+        .map(|doc| match doc {
+            serde_json::Value::String(text) => Some(text.clone()),
+            serde_json::Value::Object(obj) => {
+                obj.get("text").and_then(|v| v.as_str()).map(str::to_string)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// 构建 LLM 打分提示词
+fn build_scoring_messages(query: &str, documents: &[String]) -> serde_json::Value {
+    let listing = documents
+        .iter()
+        .enumerate()
+        .map(|(index, text)| format!("[{index}] {text}"))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    serde_json::json!([
+        {
+            "role": "system",
+            "content": format!(
+                "You are a relevance scoring engine. For each document, rate how relevant it is to the query \
+                 on a scale from 0 to 1. Respond with ONLY a JSON array of {} numbers in document order, \
+                 without any explanation.",
+                documents.len()
+            )
+        },
+        {
+            "role": "user",
+            "content": format!("Query: {query}\n\nDocuments:\n{listing}")
+        }
+    ])
+}
+
+/// 从 LLM 回复中解析分数数组
+fn parse_llm_scores(content: &str, expected: usize) -> Result<Vec<f64>, String> {
+    let start = content.find('[').ok_or("LLM 回复中未找到分数数组")?;
+    let end = content.rfind(']').ok_or("LLM 回复中未找到分数数组")?;
+    if end < start {
+        return Err("LLM 回复中的分数数组格式错误".to_string());
+    }
+    let scores: Vec<f64> = serde_json::from_str(&content[start..=end])
+        .map_err(|e| format!("解析 LLM 分数失败: {e}"))?;
+    if scores.len() != expected {
+        return Err(format!(
+            "LLM 返回 {} 个分数，期望 {} 个",
+            scores.len(),
+            expected
+        ));
+    }
+    Ok(scores.into_iter().map(|s| s.clamp(0.0, 1.0)).collect())
+}
+
+/// 按分数排序并构建 Cohere 格式结果
+fn build_rerank_results(
+    scores: &[f64],
+    documents: &[String],
+    top_n: Option<usize>,
+    return_documents: bool,
+) -> Vec<serde_json::Value> {
+    let mut ranked: Vec<(usize, f64)> = scores.iter().copied().enumerate().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked.truncate(top_n.unwrap_or(ranked.len()));
+    ranked
+        .into_iter()
+        .map(|(index, score)| {
+            let mut result = serde_json::json!({
+                "index": index,
+                "relevance_score": score,
+            });
+            if return_documents {
+                result["document"] = serde_json::json!({ "text": documents[index] });
+            }
+            result
+        })
+        .collect()
+}
+
+async fn rerank_with_llm(
+    state: &AppState,
+    credential: &ProviderCredential,
+    request: &RerankRequest,
+    texts: &[String],
+) -> Result<serde_json::Value, Response> {
+    let settings = &state.rerank_settings;
+    if texts.len() > settings.max_documents {
+        return Err(invalid_request(&format!(
+            "Too many documents for LLM rerank: {} > {}",
+            texts.len(),
+            settings.max_documents
+        )));
+    }
+
+    let provider = openai_provider_for(credential, "rerank")?;
+    let payload = serde_json::json!({
+        "model": settings.llm_model,
+        "temperature": 0,
+        "messages": build_scoring_messages(&request.query, texts),
+    });
+    let (status, json) =
+        read_upstream_json(credential, provider.chat_completions(&payload), "rerank").await?;
+    if !status.is_success() {
+        return Err((status, Json(json)).into_response());
+    }
+
+    let content = json["choices"][0]["message"]["content"]
+        .as_str()
+        .unwrap_or_default();
+    let scores = parse_llm_scores(content, texts.len()).map_err(|e| {
+        build_error_response_with_meta(
+            StatusCode::BAD_GATEWAY.as_u16(),
+            &e,
+            None,
+            Some(&credential.provider_type.to_string()),
+            Some(GatewayErrorCode::UpstreamError),
+        )
+    })?;
+
+    Ok(serde_json::json!({
+        "id": uuid::Uuid::new_v4().to_string(),
+        "model": request.model,
+        "results": build_rerank_results(&scores, texts, request.top_n, request.return_documents),
+        "meta": { "billed_units": { "search_units": 1 } },
+        "usage": json.get("usage").cloned().unwrap_or(serde_json::Value::Null),
+    }))
+}
+
+/// 处理重排序请求
+///
+/// # 端点
+/// `POST /v1/rerank`
+///
+/// # 诊断响应头
+/// `x-lime-rerank-mode`: `native` / `llm`
+pub async fn handle_rerank(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<RerankRequest>,
+) -> Response {
+    if let Err(e) = verify_inbound_api_key(&headers, &state).await {
+        return e.into_response();
+    }
+    if let Err(e) = verify_scoped_key_model(&headers, &state, &request.model) {
+        return e.into_response();
+    }
+    if request.query.trim().is_empty() || request.documents.is_empty() {
+        return invalid_request("query and documents are required");
+    }
+    let Some(texts) = document_texts(&request.documents) else {
+        return invalid_request("documents must be strings or objects with a text field");
+    };
+
+    let provider = headers
+        .get("x-provider-id")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_lowercase())
+        .unwrap_or_else(|| state.rerank_settings.provider.clone());
+    let credential = match select_openai_credential(&state, &provider, &request.model).await {
+        Ok(cred) => cred,
+        Err(resp) => return resp,
+    };
+
+    let mode = state.rerank_settings.mode;
+    if mode != RerankMode::Llm {
+        let upstream = match openai_provider_for(&credential, "rerank") {
+            Ok(upstream) => upstream,
+            Err(resp) => return resp,
+        };
+        let payload = serde_json::json!({
+            "model": request.model,
+            "query": request.query,
+            "documents": request.documents,
+            "top_n": request.top_n,
+            "return_documents": request.return_documents,
+        });
+        match read_upstream_json(&credential, upstream.rerank(&payload), "rerank").await {
+            Ok((status, json)) => {
+                // 上游无 /rerank 端点时，auto 模式回退到 LLM 打分
+                let unsupported = matches!(
+                    status,
+                    StatusCode::NOT_FOUND
+                        | StatusCode::METHOD_NOT_ALLOWED
+                        | StatusCode::NOT_IMPLEMENTED
+                );
+                if !(unsupported && mode == RerankMode::Auto) {
+                    if status.is_success() {
+                        if let Some(db) = &state.db {
+                            let _ = state.pool_service.record_usage(db, &credential.uuid);
+                        }
+                    }
+                    return with_mode_header((status, Json(json)).into_response(), "native");
+                }
+            }
+            Err(resp) if mode == RerankMode::Native => return resp,
+            Err(_) => {}
+        }
+        tracing::info!(
+            "[RERANK] provider={} 不支持原生 /rerank，回退到 LLM 打分",
+            provider
+        );
+    }
+
+    // LLM 打分实际调用的是配置的打分模型
+    if let Err(e) = check_scoped_key_model(&headers, &state, &state.rerank_settings.llm_model) {
+        return e.into_response();
+    }
+    match rerank_with_llm(&state, &credential, &request, &texts).await {
+        Ok(body) => {
+            if let Some(db) = &state.db {
+                let _ = state.pool_service.record_usage(db, &credential.uuid);
+            }
+            with_mode_header(Json(body).into_response(), "llm")
+        }
+        Err(resp) => resp,
+    }
+}
+
+fn with_mode_header(mut response: Response, mode: &'static str) -> Response {
+    response
+        .headers_mut()
+        .insert("x-lime-rerank-mode", HeaderValue::from_static(mode));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document_texts_should_accept_strings_and_objects() {
+        let docs = vec![
+            serde_json::json!("plain"),
+            serde_json::json!({ "text": "object" }),
+        ];
+        assert_eq!(
+            document_texts(&docs),
+            Some(vec!["plain".to_string(), "object".to_string()])
+        );
+        assert_eq!(document_texts(&[serde_json::json!(1)]), None);
+    }
+
+    #[test]
+    fn parse_llm_scores_should_extract_array_from_reply() {
+        let scores = parse_llm_scores("Scores: [0.9, 1.4, -0.2]", 3).expect("应解析成功");
+        assert_eq!(scores, vec![0.9, 1.0, 0.0]);
+        assert!(parse_llm_scores("[0.5]", 2).is_err());
+        assert!(parse_llm_scores("no json", 1).is_err());
+    }
+
+    #[test]
+    fn build_rerank_results_should_sort_and_truncate() {
+        let docs = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let results = build_rerank_results(&[0.1, 0.8, 0.5], &docs, Some(2), true);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["index"], 1);
+        assert_eq!(results[0]["document"]["text"], "b");
+        assert_eq!(results[1]["index"], 2);
+    }
+}
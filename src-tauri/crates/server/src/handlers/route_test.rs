@@ -0,0 +1,486 @@
+//! 路由规则测试接口
+//!
+//! `POST /admin/route-test`（仅主 API Key 或通过管理接口 OIDC 认证的请求可调用）按
+//! `/v1/chat/completions` 的路由顺序预演一个假设请求：弃用改写、Key 校验、别名、规模路由、
+//! 提示路由、客户端 Provider、时间段路由、固定路由头与凭证选择，逐步返回命中的规则及原因。
+//!
+//! 预演不向上游发送请求，也不计受限 Key 的请求次数、不写请求日志与凭证用量。
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, HeaderName, HeaderValue},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use super::api::{
+    collect_provider_fallback_chain, select_credential_for_request, select_provider_for_client,
+};
+use super::routing_pin;
+use crate::auth::oidc::AdminIdentity;
+use crate::handlers::verify_admin_key;
+use crate::AppState;
+
+/// `POST /admin/route-test` 请求体
+#[derive(Debug, Default, Deserialize)]
+pub struct RouteTestRequest {
+    /// 客户端请求的模型
+    pub model: String,
+    /// 假设请求携带的请求头（如 `user-agent`、`x-provider-id`、`x-lime-credential`）
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// 假设请求使用的 API Key，缺省视为主 Key
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// 估算的提示词 Token 数
+    #[serde(default)]
+    pub estimated_tokens: u32,
+    /// 是否携带图片或文档
+    #[serde(default)]
+    pub has_attachments: bool,
+    /// 最后一条 user 消息，用于匹配 `[hint]` 提示路由
+    #[serde(default)]
+    pub message: Option<String>,
+    /// 按该本地时间匹配时间段规则（如 `2025-06-06T23:30:00`），缺省为当前时间
+    #[serde(default)]
+    pub at: Option<NaiveDateTime>,
+}
+
+/// 路由预演的一个步骤
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct RouteTestStep {
+    /// 阶段：deprecation / api_key / alias / size / hint / client / time_window / pin / credential
+    pub stage: &'static str,
+    /// 该阶段是否命中规则或改变了路由结果
+    pub matched: bool,
+    /// 命中的规则名称或关键字
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    pub detail: String,
+}
+
+impl RouteTestStep {
+    fn new(stage: &'static str, matched: bool, detail: impl Into<String>) -> Self {
+        Self {
+            stage,
+            matched,
+            rule: None,
+            detail: detail.into(),
+        }
+    }
+
+    fn with_rule(mut self, rule: impl Into<String>) -> Self {
+        self.rule = Some(rule.into());
+        self
+    }
+}
+
+/// 预演选中的凭证
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteTestCredential {
+    pub uuid: String,
+    pub name: Option<String>,
+    pub provider_type: String,
+}
+
+/// `POST /admin/route-test` 响应
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteTestResult {
+    pub requested_model: String,
+    /// 最终发往上游的模型
+    pub model: String,
+    pub client_type: String,
+    /// 最终使用的 Provider（请求被拒绝时为空）
+    pub provider: Option<String>,
+    pub credential: Option<RouteTestCredential>,
+    /// 请求会被拒绝时的原因
+    pub rejected: Option<String>,
+    pub steps: Vec<RouteTestStep>,
+}
+
+/// 把假设请求转换为请求头，`api_key` 以 `Authorization: Bearer` 携带；无效的头会被忽略
+fn build_headers(request: &RouteTestRequest, default_key: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in &request.headers {
+        let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.trim().to_ascii_lowercase().as_bytes()),
+            HeaderValue::from_str(value.trim()),
+        ) else {
+            continue;
+        };
+        headers.insert(name, value);
+    }
+    let key = request.api_key.as_deref().unwrap_or(default_key);
+    if let Ok(value) = HeaderValue::from_str(&format!("Bearer {key}")) {
+        headers.insert(header::AUTHORIZATION, value);
+        headers.remove("x-api-key");
+    }
+    headers
+}
+
+/// 按 Chat Completions 的路由顺序预演，不产生任何副作用
+pub async fn explain_route(state: &AppState, request: &RouteTestRequest) -> RouteTestResult {
+    let headers = build_headers(request, &state.api_key);
+    let key = request.api_key.as_deref().unwrap_or(&state.api_key);
+    let mut model = request.model.trim().to_string();
+    let mut steps = Vec::new();
+    let mut result = RouteTestResult {
+        requested_model: model.clone(),
+        model: String::new(),
+        client_type: String::new(),
+        provider: None,
+        credential: None,
+        rejected: None,
+        steps: Vec::new(),
+    };
+
+    // 弃用模型
+    match state.processor.check_deprecation(&model).await {
+        Some(deprecation) if deprecation.rewritten => {
+            steps.push(
+                RouteTestStep::new(
+                    "deprecation",
+                    true,
+                    format!("模型已弃用，改写为 {}", deprecation.replacement),
+                )
+                .with_rule(deprecation.model),
+            );
+            model = deprecation.replacement;
+        }
+        Some(deprecation) => steps.push(
+            RouteTestStep::new(
+                "deprecation",
+                false,
+                format!(
+                    "模型已弃用（建议改用 {}），未开启改写",
+                    deprecation.replacement
+                ),
+            )
+            .with_rule(deprecation.model),
+        ),
+        None => steps.push(RouteTestStep::new("deprecation", false, "未弃用")),
+    }
+
+    // API Key
+    let is_admin = !state.api_key.is_empty() && key == state.api_key;
+    let key_step = if is_admin {
+        RouteTestStep::new("api_key", true, "主 API Key，不限模型")
+    } else if state.scoped_keys.is_valid(key) {
+        if state.scoped_keys.allows_model(key, &model) {
+            RouteTestStep::new("api_key", true, format!("受限 Key 允许调用 {model}"))
+        } else {
+            result.rejected = Some(format!("Model '{model}' is not allowed for this API key"));
+            RouteTestStep::new(
+                "api_key",
+                false,
+                format!("受限 Key 不允许调用 {model}（403）"),
+            )
+        }
+    } else {
+        result.rejected = Some("Invalid API key".to_string());
+        RouteTestStep::new("api_key", false, "不是主 Key 或有效的受限 Key（401）")
+    };
+    steps.push(key_step);
+    if result.rejected.is_some() {
+        result.model = model;
+        result.steps = steps;
+        return result;
+    }
+
+    // 模型别名
+    let resolved = state.processor.resolve_model(&model).await;
+    if resolved != model {
+        steps.push(
+            RouteTestStep::new("alias", true, format!("别名解析为 {resolved}")).with_rule(&model),
+        );
+        model = resolved;
+    } else {
+        steps.push(RouteTestStep::new("alias", false, "没有匹配的别名"));
+    }
+
+    // 规模路由
+    let size_match = state
+        .processor
+        .size_router
+        .read()
+        .await
+        .route(request.estimated_tokens, request.has_attachments);
+    match size_match {
+        Some(size_match) if size_match.model != model => {
+            steps.push(
+                RouteTestStep::new(
+                    "size",
+                    true,
+                    format!(
+                        "估算 {} tokens、附件={}，改写为 {}",
+                        size_match.estimated_tokens, size_match.has_attachments, size_match.model
+                    ),
+                )
+                .with_rule(size_match.size_class.to_string()),
+            );
+            model = size_match.model;
+        }
+        Some(size_match) => steps.push(
+            RouteTestStep::new("size", false, "命中规则但目标模型与当前模型相同")
+                .with_rule(size_match.size_class.to_string()),
+        ),
+        None => steps.push(RouteTestStep::new("size", false, "没有匹配的规模规则")),
+    }
+
+    // 提示路由
+    let hint_match = {
+        let hint_router = state.processor.hint_router.read().await;
+        match request.message.as_deref() {
+            Some(message) if hint_router.is_enabled() => hint_router.match_message(message),
+            _ => None,
+        }
+    };
+    match hint_match {
+        Some(hint_match) => {
+            steps.push(
+                RouteTestStep::new(
+                    "hint",
+                    true,
+                    format!("提示路由改写为 {}", hint_match.route.model),
+                )
+                .with_rule(hint_match.route.hint),
+            );
+            model = hint_match.route.model;
+        }
+        None => steps.push(RouteTestStep::new("hint", false, "未携带或未匹配 [hint]")),
+    }
+
+    // 客户端类型 -> Provider
+    let (selected_provider, client_type) = select_provider_for_client(&headers, state).await;
+    result.client_type = client_type.to_string();
+    steps.push(
+        RouteTestStep::new(
+            "client",
+            true,
+            format!("客户端 {client_type} 使用 Provider {selected_provider}"),
+        )
+        .with_rule(client_type.config_key()),
+    );
+
+    // 时间段路由
+    let at = request.at.unwrap_or_else(|| Local::now().naive_local());
+    let window_match = state
+        .processor
+        .time_window_router
+        .read()
+        .await
+        .route_at(at, &model);
+    let selected_provider = match window_match {
+        Some(window_match) => {
+            let provider = window_match
+                .provider
+                .clone()
+                .unwrap_or_else(|| selected_provider.clone());
+            let target = window_match.model.unwrap_or_else(|| model.clone());
+            steps.push(
+                RouteTestStep::new(
+                    "time_window",
+                    true,
+                    format!("{at} 命中：Provider {selected_provider} -> {provider}，模型 {model} -> {target}"),
+                )
+                .with_rule(window_match.rule),
+            );
+            model = target;
+            provider
+        }
+        None => {
+            steps.push(RouteTestStep::new(
+                "time_window",
+                false,
+                format!("{at} 没有匹配的时间段规则"),
+            ));
+            selected_provider
+        }
+    };
+
+    // 固定路由头与 X-Provider-Id
+    let mut explicit_provider = headers
+        .get("x-provider-id")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_lowercase());
+    let pin = routing_pin::pin_from_headers(&headers);
+    if pin.is_some() && !is_admin {
+        result.rejected = Some("Routing pin headers require the admin API key".to_string());
+        steps.push(RouteTestStep::new(
+            "pin",
+            false,
+            "固定路由头仅接受主 Key（403）",
+        ));
+        result.model = model;
+        result.steps = steps;
+        return result;
+    }
+    let pinned = match routing_pin::pinned_credential(state, pin.as_ref(), "route-test").await {
+        Ok(credential) => credential,
+        Err(_) => {
+            let credential = pin.as_ref().and_then(|p| p.credential.clone());
+            result.rejected = Some(format!(
+                "Pinned credential '{}' not found",
+                credential.as_deref().unwrap_or_default()
+            ));
+            steps.push(RouteTestStep::new("pin", false, "固定的凭证不存在"));
+            result.model = model;
+            result.steps = steps;
+            return result;
+        }
+    };
+    if let Some(provider) = pin.as_ref().and_then(|p| p.provider.clone()) {
+        steps.push(
+            RouteTestStep::new("pin", true, "固定 Provider，跳过自动降级").with_rule(&provider),
+        );
+        explicit_provider = Some(provider);
+    } else if let Some(provider) = &explicit_provider {
+        steps.push(
+            RouteTestStep::new("pin", true, "X-Provider-Id 指定 Provider，不进行降级")
+                .with_rule(provider),
+        );
+    } else if pinned.is_none() {
+        steps.push(RouteTestStep::new("pin", false, "未指定 Provider 或凭证"));
+    }
+
+    // 凭证选择
+    let (provider, credential, note) = if let Some(credential) = pinned {
+        let provider = credential.provider_type.to_string();
+        (
+            provider,
+            Some(credential),
+            "固定凭证，跳过负载均衡".to_string(),
+        )
+    } else if let Some(provider) = explicit_provider {
+        let credential = state.db.as_ref().and_then(|db| {
+            state
+                .pool_service
+                .select_credential_with_client_check(
+                    db,
+                    &provider,
+                    Some(&model),
+                    Some(&client_type),
+                )
+                .ok()
+                .flatten()
+        });
+        if credential.is_none() {
+            result.rejected = Some(format!(
+                "No available credentials for provider '{provider}'"
+            ));
+        }
+        (provider, credential, "指定 Provider".to_string())
+    } else {
+        let mut found = None;
+        for provider in collect_provider_fallback_chain(state, &selected_provider).await {
+            let credential = select_credential_for_request(
+                state,
+                None,
+                &provider,
+                &model,
+                &client_type,
+                None,
+                "ROUTE_TEST",
+                true,
+            )
+            .await
+            .ok()
+            .flatten();
+            if let Some(credential) = credential {
+                found = Some((provider, credential));
+                break;
+            }
+            steps.push(RouteTestStep::new(
+                "credential",
+                false,
+                format!("Provider {provider} 没有可用凭证，尝试下一个"),
+            ));
+        }
+        match found {
+            Some((provider, credential)) => {
+                let note = if provider != selected_provider.to_lowercase() {
+                    format!("由 {selected_provider} 降级")
+                } else {
+                    "负载均衡".to_string()
+                };
+                (provider, Some(credential), note)
+            }
+            None => (selected_provider, None, String::new()),
+        }
+    };
+
+    match credential {
+        Some(credential) => {
+            steps.push(
+                RouteTestStep::new(
+                    "credential",
+                    true,
+                    format!(
+                        "Provider {provider} 选中凭证 {}（{note}）",
+                        credential.name.as_deref().unwrap_or(&credential.uuid)
+                    ),
+                )
+                .with_rule(&credential.uuid),
+            );
+            result.credential = Some(RouteTestCredential {
+                uuid: credential.uuid,
+                name: credential.name,
+                provider_type: credential.provider_type.to_string(),
+            });
+        }
+        None => steps.push(RouteTestStep::new(
+            "credential",
+            false,
+            format!("Provider {provider} 没有可用凭证"),
+        )),
+    }
+    result.provider = Some(provider);
+
+    result.model = model;
+    result.steps = steps;
+    result
+}
+
+/// `POST /admin/route-test`
+pub async fn test_route(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+    Json(request): Json<RouteTestRequest>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    Json(explain_route(&state, &request).await).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_headers_uses_tested_key() {
+        let request = RouteTestRequest {
+            model: "claude-sonnet-4-5".to_string(),
+            headers: HashMap::from([
+                ("User-Agent".to_string(), "claude-cli/1.0".to_string()),
+                ("x-api-key".to_string(), "ignored".to_string()),
+                ("bad header".to_string(), "x".to_string()),
+            ]),
+            api_key: Some("pc_m_test".to_string()),
+            ..Default::default()
+        };
+        let headers = build_headers(&request, "admin");
+        assert_eq!(headers.get("user-agent").unwrap(), "claude-cli/1.0");
+        assert_eq!(headers.get("authorization").unwrap(), "Bearer pc_m_test");
+        assert!(headers.get("x-api-key").is_none());
+        assert_eq!(headers.len(), 2);
+
+        let default_key = build_headers(&RouteTestRequest::default(), "admin");
+        assert_eq!(default_key.get("authorization").unwrap(), "Bearer admin");
+    }
+}
@@ -0,0 +1,188 @@
+//! 按请求固定 Provider / 凭证（调试用）
+//!
+//! 请求携带 `x-lime-provider` 或 `x-lime-credential` 时跳过负载均衡与自动降级：
+//! - `x-lime-provider`：只从指定 Provider 选择凭证，等同于 `X-Provider-Id`
+//! - `x-lime-credential`：直接使用指定凭证（UUID 或名称），即使它被标记为不健康
+//!
+//! 仅接受主 Key 或已通过 OIDC 认证的管理请求，受限 Key 携带这些头会被拒绝。
+//! 固定后的请求照常记录用量、健康状态与遥测。
+
+use axum::{
+    http::{HeaderMap, StatusCode},
+    response::Response,
+};
+use lime_core::errors::GatewayErrorCode;
+use lime_core::i18n::{self, MessageCode};
+use lime_core::models::provider_pool_model::ProviderCredential;
+use lime_server_utils::build_error_response_with_meta;
+use subtle::ConstantTimeEq;
+
+use crate::auth::oidc::AdminIdentity;
+use crate::AppState;
+
+/// 固定 Provider 的请求头
+pub const PIN_PROVIDER_HEADER: &str = "x-lime-provider";
+/// 固定凭证的请求头
+pub const PIN_CREDENTIAL_HEADER: &str = "x-lime-credential";
+
+/// 请求指定的路由目标
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoutingPin {
+    pub provider: Option<String>,
+    pub credential: Option<String>,
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+/// 读取固定路由头，两者都未携带时返回 None
+pub fn pin_from_headers(headers: &HeaderMap) -> Option<RoutingPin> {
+    let pin = RoutingPin {
+        provider: header_value(headers, PIN_PROVIDER_HEADER).map(|p| p.to_lowercase()),
+        credential: header_value(headers, PIN_CREDENTIAL_HEADER),
+    };
+    (pin != RoutingPin::default()).then_some(pin)
+}
+
+/// 请求是否使用主 Key（或 OIDC 管理员）认证
+fn is_admin_request(headers: &HeaderMap, admin: Option<&AdminIdentity>, state: &AppState) -> bool {
+    if admin.is_some() {
+        return true;
+    }
+    ["authorization", "x-api-key"]
+        .iter()
+        .filter_map(|name| headers.get(*name).and_then(|v| v.to_str().ok()))
+        .map(|value| value.strip_prefix("Bearer ").unwrap_or(value))
+        .any(|key| {
+            !state.api_key.is_empty() && bool::from(key.as_bytes().ct_eq(state.api_key.as_bytes()))
+        })
+}
+
+fn error_response(status: StatusCode, message: &str, request_id: &str) -> Response {
+    let code = if status == StatusCode::FORBIDDEN {
+        GatewayErrorCode::AuthenticationFailed
+    } else {
+        GatewayErrorCode::InvalidRequest
+    };
+    build_error_response_with_meta(status.as_u16(), message, Some(request_id), None, Some(code))
+}
+
+/// 读取并校验固定路由头（非管理请求携带时返回 403）
+pub fn authorize(
+    headers: &HeaderMap,
+    admin: Option<&AdminIdentity>,
+    state: &AppState,
+    request_id: &str,
+) -> Result<Option<RoutingPin>, Response> {
+    let Some(pin) = pin_from_headers(headers) else {
+        return Ok(None);
+    };
+    if !is_admin_request(headers, admin, state) {
+        return Err(error_response(
+            StatusCode::FORBIDDEN,
+            "Routing pin headers require the admin API key",
+            request_id,
+        ));
+    }
+    Ok(Some(pin))
+}
+
+/// 解析固定的凭证；未固定凭证时返回 None
+pub async fn pinned_credential(
+    state: &AppState,
+    pin: Option<&RoutingPin>,
+    request_id: &str,
+) -> Result<Option<ProviderCredential>, Response> {
+    let Some(pin) = pin else {
+        return Ok(None);
+    };
+    let Some(credential_ref) = pin.credential.as_deref() else {
+        return Ok(None);
+    };
+    let Some(db) = state.db.as_ref() else {
+        return Err(error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            i18n::t(MessageCode::DatabaseUnavailable),
+            request_id,
+        ));
+    };
+
+    let found = match state.pool_service.get_by_uuid(db, credential_ref) {
+        Ok(Some(credential)) => Some(credential),
+        _ => state
+            .pool_service
+            .get_by_name(db, credential_ref)
+            .ok()
+            .flatten(),
+    };
+    let Some(credential) = found else {
+        return Err(error_response(
+            StatusCode::NOT_FOUND,
+            &format!("Pinned credential '{credential_ref}' not found"),
+            request_id,
+        ));
+    };
+
+    if let Some(provider) = pin.provider.as_deref() {
+        if !credential
+            .provider_type
+            .to_string()
+            .eq_ignore_ascii_case(provider)
+        {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                &format!(
+                    "Pinned credential '{credential_ref}' belongs to provider '{}', not '{provider}'",
+                    credential.provider_type
+                ),
+                request_id,
+            ));
+        }
+    }
+
+    let message = format!(
+        "[ROUTE] request_id={} pinned credential={} provider={} healthy={} disabled={}",
+        request_id,
+        &credential.uuid[..8.min(credential.uuid.len())],
+        credential.provider_type,
+        credential.is_healthy,
+        credential.is_disabled
+    );
+    tracing::info!("{}", message);
+    state.logs.write().await.add("info", &message);
+    Ok(Some(credential))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_pin_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(pin_from_headers(&headers), None);
+
+        headers.insert(PIN_PROVIDER_HEADER, HeaderValue::from_static(" Kiro "));
+        headers.insert(PIN_CREDENTIAL_HEADER, HeaderValue::from_static(""));
+        assert_eq!(
+            pin_from_headers(&headers),
+            Some(RoutingPin {
+                provider: Some("kiro".to_string()),
+                credential: None,
+            })
+        );
+
+        headers.insert(PIN_CREDENTIAL_HEADER, HeaderValue::from_static("0b7c-uuid"));
+        assert_eq!(
+            pin_from_headers(&headers).unwrap().credential.as_deref(),
+            Some("0b7c-uuid")
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! 临时受限 Key 管理接口
+//!
+//! 仅主 API Key（或通过管理接口 OIDC 认证的请求）可调用，用于为协作者或演示应用签发短期 Key（限定模型、有效期与请求次数）：
+//! - `POST /v1/keys`：签发，明文 Key 仅在响应中返回一次
+//! - `GET /v1/keys`：列出已签发的 Key（不含明文）
+//! - `DELETE /v1/keys/:id`：吊销
+//! - `POST /v1/keys/:id/successor`：领取按轮换策略生成的后继 Key，每次领取都会重新生成明文
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+
+use crate::auth::oidc::AdminIdentity;
+use crate::auth::scoped_keys::ScopedKeyOptions;
+use crate::handlers::verify_admin_key;
+use crate::AppState;
+
+fn error_response(status: StatusCode, message: String, error_type: &str) -> Response {
+    (
+        status,
+        Json(serde_json::json!({
+            "error": {"message": message, "type": error_type}
+        })),
+    )
+        .into_response()
+}
+
+/// `POST /v1/keys`
+pub async fn create_scoped_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+    Json(options): Json<ScopedKeyOptions>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    match state.scoped_keys.issue_with(&options) {
+        Ok(issued) => {
+            tracing::info!(
+                "[SCOPED_KEY] 已签发临时 Key: label={} prefix={} models={:?} max_requests={:?}",
+                issued.record.label,
+                issued.record.key_prefix,
+                issued.record.models,
+                issued.record.max_requests
+            );
+            (StatusCode::CREATED, Json(issued)).into_response()
+        }
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e, "invalid_request_error"),
+    }
+}
+
+/// `GET /v1/keys`
+pub async fn list_scoped_keys(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    Json(serde_json::json!({ "data": state.scoped_keys.list() })).into_response()
+}
+
+/// `DELETE /v1/keys/:id`
+pub async fn revoke_scoped_key(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+    Path(id): Path<String>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    match state.scoped_keys.revoke(&id) {
+        Ok(true) => Json(serde_json::json!({ "id": id, "revoked": true })).into_response(),
+        Ok(false) => error_response(
+            StatusCode::NOT_FOUND,
+            format!("Key '{id}' not found"),
+            "not_found",
+        ),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e, "server_error"),
+    }
+}
+
+/// `POST /v1/keys/:id/successor`
+pub async fn claim_scoped_key_successor(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+    Path(id): Path<String>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    match state.scoped_keys.claim_successor(&id) {
+        Ok(Some(successor)) => {
+            tracing::info!(
+                "[SCOPED_KEY] 已领取 Key {} 的后继 Key: prefix={}",
+                id,
+                successor.record.key_prefix
+            );
+            Json(successor).into_response()
+        }
+        Ok(None) => error_response(
+            StatusCode::NOT_FOUND,
+            format!("No successor key has been prepared for key '{id}'"),
+            "not_found",
+        ),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e, "server_error"),
+    }
+}
@@ -0,0 +1,167 @@
+//! 请求签名辅助端点
+//!
+//! `GET /v1/signing/snippet` 返回签名算法说明与 curl / Python / Node 客户端示例。
+//! 示例从 `LIME_SIGNING_SECRET` 环境变量读取密钥，响应中不包含密钥本身。
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::Deserialize;
+
+use crate::auth::oidc::AdminIdentity;
+use crate::handlers::verify_admin_key;
+use crate::middleware::request_signing::{SIGNATURE_HEADER, TIMESTAMP_HEADER};
+use crate::AppState;
+
+/// 默认示例路径
+const DEFAULT_SNIPPET_PATH: &str = "/v1/chat/completions";
+
+/// 示例参数
+#[derive(Debug, Default, Deserialize)]
+pub struct SigningSnippetQuery {
+    /// 服务地址（默认取请求的 Host）
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// 请求路径
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+fn curl_snippet(base_url: &str, path: &str) -> String {
+    format!(
+        r#"BODY='{{"model":"gpt-4o-mini","messages":[{{"role":"user","content":"hi"}}]}}'
+TS=$(date +%s)
+BODY_HASH=$(printf '%s' "$BODY" | openssl dgst -sha256 -hex | sed 's/^.* //')
+SIG=$(printf '%s\nPOST\n{path}\n%s' "$TS" "$BODY_HASH" | openssl dgst -sha256 -hmac "$LIME_SIGNING_SECRET" -hex | sed 's/^.* //')
+curl {base_url}{path} \
+  -H 'Content-Type: application/json' \
+  -H "{TIMESTAMP_HEADER}: $TS" \
+  -H "{SIGNATURE_HEADER}: $SIG" \
+  -d "$BODY""#
+    )
+}
+
+fn python_snippet(base_url: &str, path: &str) -> String {
+    format!(
+        r#"import hashlib, hmac, json, os, time, urllib.request
+
+body = json.dumps({{"model": "gpt-4o-mini", "messages": [{{"role": "user", "content": "hi"}}]}}).encode()
+ts = str(int(time.time()))
+canonical = f"{{ts}}\nPOST\n{path}\n{{hashlib.sha256(body).hexdigest()}}"
+sig = hmac.new(os.environ["LIME_SIGNING_SECRET"].encode(), canonical.encode(), hashlib.sha256).hexdigest()
+req = urllib.request.Request("{base_url}{path}", data=body, method="POST", headers={{
+    "Content-Type": "application/json",
+    "{TIMESTAMP_HEADER}": ts,
+    "{SIGNATURE_HEADER}": sig,
+}})
+print(urllib.request.urlopen(req).read().decode())"#
+    )
+}
+
+fn node_snippet(base_url: &str, path: &str) -> String {
+    format!(
+        r#"import {{ createHash, createHmac }} from "node:crypto";
+
+const body = JSON.stringify({{ model: "gpt-4o-mini", messages: [{{ role: "user", content: "hi" }}] }});
+const ts = Math.floor(Date.now() / 1000).toString();
+const bodyHash = createHash("sha256").update(body).digest("hex");
+const canonical = `${{ts}}\nPOST\n{path}\n${{bodyHash}}`;
+const sig = createHmac("sha256", process.env.LIME_SIGNING_SECRET).update(canonical).digest("hex");
+const res = await fetch("{base_url}{path}", {{
+  method: "POST",
+  headers: {{ "Content-Type": "application/json", "{TIMESTAMP_HEADER}": ts, "{SIGNATURE_HEADER}": sig }},
+  body,
+}});
+console.log(await res.text());"#
+    )
+}
+
+/// 构建签名说明与客户端示例
+pub fn build_signing_snippet(base_url: &str, path: &str, max_skew_secs: u64) -> serde_json::Value {
+    let base_url = base_url.trim_end_matches('/');
+    serde_json::json!({
+        "algorithm": "HMAC-SHA256",
+        "headers": {
+            "timestamp": TIMESTAMP_HEADER,
+            "signature": SIGNATURE_HEADER,
+        },
+        "canonical_string": "{timestamp}\\n{METHOD}\\n{path?query}\\n{hex(sha256(body))}",
+        "max_skew_secs": max_skew_secs,
+        "secret_env": "LIME_SIGNING_SECRET",
+        "snippets": {
+            "curl": curl_snippet(base_url, path),
+            "python": python_snippet(base_url, path),
+            "node": node_snippet(base_url, path),
+        },
+    })
+}
+
+/// `GET /v1/signing/snippet`：生成签名客户端示例（仅主 API Key 可调用）
+pub async fn signing_snippet(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+    Query(query): Query<SigningSnippetQuery>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+
+    let signer = &state.request_signer;
+    if !signer.is_active() {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": {
+                    "message": "Request signing is not enabled (server.request_signing)",
+                    "type": "not_found",
+                }
+            })),
+        )
+            .into_response();
+    }
+
+    let base_url = query
+        .base_url
+        .filter(|url| !url.trim().is_empty())
+        .or_else(|| {
+            headers
+                .get("host")
+                .and_then(|v| v.to_str().ok())
+                .map(|host| format!("http://{host}"))
+        })
+        .unwrap_or_else(|| "http://127.0.0.1:8999".to_string());
+    let path = query
+        .path
+        .filter(|path| path.starts_with('/'))
+        .unwrap_or_else(|| DEFAULT_SNIPPET_PATH.to_string());
+
+    Json(build_signing_snippet(
+        &base_url,
+        &path,
+        signer.settings().max_skew_secs,
+    ))
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_reference_secret_env_only() {
+        let snippet = build_signing_snippet("https://lime.example.com/", "/v1/messages", 300);
+        let curl = snippet["snippets"]["curl"].as_str().expect("curl 示例");
+
+        assert!(curl.contains("https://lime.example.com/v1/messages"));
+        assert!(curl.contains("$LIME_SIGNING_SECRET"));
+        assert!(snippet["snippets"]["python"]
+            .as_str()
+            .expect("python 示例")
+            .contains("\\nPOST\\n/v1/messages\\n"));
+        assert_eq!(snippet["headers"]["signature"], SIGNATURE_HEADER);
+    }
+}
@@ -0,0 +1,252 @@
+//! 流式响应转换
+//!
+//! - 增量合并：把上游细碎的 SSE 事件累计到 `min_chunk_bytes` 或等待 `max_delay_ms`
+//!   后再发送，降低慢速终端上的逐包开销；只在事件边界处切分，不会拆开单个事件
+//! - NDJSON：客户端 `Accept: application/x-ndjson` 时，将每个 SSE 事件的 `data`
+//!   转为一行 JSON（丢弃 `[DONE]`）
+
+use std::time::Duration;
+
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, HeaderValue},
+    response::Response,
+};
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use lime_core::config::StreamTransformSettings;
+
+use crate::sse::SseEventSplitter;
+
+const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// 客户端是否要求 NDJSON
+pub fn wants_ndjson(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains(NDJSON_CONTENT_TYPE))
+}
+
+fn is_event_stream(response: &Response) -> bool {
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"))
+}
+
+/// 单个 SSE 事件转为一行 NDJSON，无数据或 `[DONE]` 时返回 `None`
+fn sse_event_to_ndjson(event: &str) -> Option<String> {
+    let data: Vec<&str> = event
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(str::trim_start)
+        .collect();
+    if data.is_empty() {
+        return None;
+    }
+    let data = data.join("\n");
+    if data.trim() == "[DONE]" {
+        return None;
+    }
+    match serde_json::from_str::<serde_json::Value>(&data) {
+        // 重新序列化，确保一行一个 JSON
+        Ok(value) => Some(format!("{value}\n")),
+        Err(_) => Some(format!("{}\n", serde_json::Value::String(data))),
+    }
+}
+
+/// 合并细碎的 SSE 事件
+fn coalesce<S, E>(
+    upstream: S,
+    min_bytes: usize,
+    max_delay: Duration,
+) -> impl Stream<Item = Result<Bytes, E>> + Send
+where
+    S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+    E: Send + 'static,
+{
+    async_stream::stream! {
+        let mut upstream = Box::pin(upstream);
+        let mut buf = SseEventSplitter::default();
+        let mut deadline: Option<tokio::time::Instant> = None;
+        loop {
+            let next = match deadline {
+                Some(at) => match tokio::time::timeout_at(at, upstream.next()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        // 等待超时：发送已完整的事件
+                        if let Some(events) = buf.take_complete() {
+                            yield Ok(events);
+                        }
+                        deadline = (!buf.is_empty())
+                            .then(|| tokio::time::Instant::now() + max_delay);
+                        continue;
+                    }
+                },
+                None => upstream.next().await,
+            };
+            match next {
+                Some(Ok(bytes)) => {
+                    buf.push(&bytes);
+                    if buf.len() >= min_bytes {
+                        if let Some(events) = buf.take_complete() {
+                            yield Ok(events);
+                            deadline = None;
+                        }
+                    }
+                    if !buf.is_empty() && deadline.is_none() {
+                        deadline = Some(tokio::time::Instant::now() + max_delay);
+                    }
+                }
+                Some(Err(e)) => {
+                    if !buf.is_empty() {
+                        yield Ok(buf.take_rest());
+                    }
+                    yield Err(e);
+                    break;
+                }
+                None => {
+                    if !buf.is_empty() {
+                        yield Ok(buf.take_rest());
+                    }
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// SSE 转 NDJSON
+fn to_ndjson<S, E>(upstream: S) -> impl Stream<Item = Result<Bytes, E>> + Send
+where
+    S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+    E: Send + 'static,
+{
+    async_stream::stream! {
+        let mut upstream = Box::pin(upstream);
+        let mut events = SseEventSplitter::default();
+        loop {
+            match upstream.next().await {
+                Some(Ok(bytes)) => {
+                    events.push(&bytes);
+                    let mut lines = String::new();
+                    while let Some(event) = events.next_text() {
+                        if let Some(line) = sse_event_to_ndjson(&event) {
+                            lines.push_str(&line);
+                        }
+                    }
+                    if !lines.is_empty() {
+                        yield Ok(Bytes::from(lines));
+                    }
+                }
+                Some(Err(e)) => {
+                    yield Err(e);
+                    break;
+                }
+                None => {
+                    let rest = events.take_rest_text();
+                    if let Some(line) = sse_event_to_ndjson(&rest) {
+                        yield Ok(Bytes::from(line));
+                    }
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// 按配置转换流式响应，非 SSE 响应原样返回
+pub fn apply(settings: &StreamTransformSettings, response: Response, ndjson: bool) -> Response {
+    let ndjson = ndjson && settings.ndjson;
+    if (settings.min_chunk_bytes == 0 && !ndjson) || !is_event_stream(&response) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let mut stream = body.into_data_stream().boxed();
+    if settings.min_chunk_bytes > 0 {
+        stream = coalesce(
+            stream,
+            settings.min_chunk_bytes,
+            Duration::from_millis(settings.max_delay_ms.max(1)),
+        )
+        .boxed();
+    }
+    if ndjson {
+        stream = to_ndjson(stream).boxed();
+        parts.headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(NDJSON_CONTENT_TYPE),
+        );
+        parts
+            .headers
+            .insert("x-lime-stream-format", HeaderValue::from_static("ndjson"));
+    }
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from_stream(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(parts: &[&'static str]) -> impl Stream<Item = Result<Bytes, std::io::Error>> {
+        futures::stream::iter(
+            parts
+                .iter()
+                .map(|p| Ok(Bytes::from_static(p.as_bytes())))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_coalesce_only_splits_on_event_boundaries() {
+        let upstream = events(&[
+            "data: {\"a\":1}\n\n",
+            "data: {\"a\":2}\n",
+            "\ndata: {\"a\":3}\n\n",
+            "data: [DONE]\n\n",
+        ]);
+        let chunks: Vec<Bytes> = coalesce(upstream, 24, Duration::from_secs(5))
+            .map(|r| r.unwrap())
+            .collect()
+            .await;
+
+        assert!(chunks.len() < 4);
+        for chunk in &chunks {
+            assert!(chunk.ends_with(b"\n\n"));
+        }
+        let joined: Vec<u8> = chunks.concat();
+        assert_eq!(
+            joined,
+            b"data: {\"a\":1}\n\ndata: {\"a\":2}\n\ndata: {\"a\":3}\n\ndata: [DONE]\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ndjson_drops_done_and_keeps_event_payloads() {
+        let upstream = events(&[
+            "event: message_start\ndata: {\"type\":\"message_start\"}\n\n",
+            "data: {\"choices\":[]}\r\n\r\ndata: [DONE]\n\n",
+        ]);
+        let lines: Vec<Bytes> = to_ndjson(upstream).map(|r| r.unwrap()).collect().await;
+        let text = String::from_utf8(lines.concat()).unwrap();
+
+        assert_eq!(text, "{\"type\":\"message_start\"}\n{\"choices\":[]}\n");
+
+        // 多字节字符被拆在两个网络包之间
+        let event = "data: {\"text\":\"你好\"}\n\n".as_bytes();
+        let cut = event.len() - 6;
+        let upstream = futures::stream::iter(vec![
+            Ok::<_, std::io::Error>(Bytes::copy_from_slice(&event[..cut])),
+            Ok(Bytes::copy_from_slice(&event[cut..])),
+        ]);
+        let lines: Vec<Bytes> = to_ndjson(upstream).map(|r| r.unwrap()).collect().await;
+        assert_eq!(
+            String::from_utf8(lines.concat()).unwrap(),
+            "{\"text\":\"你好\"}\n"
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! 对话记录与微调数据集导出接口
+//!
+//! 仅主 API Key（或通过管理接口 OIDC 认证的请求）可调用，筛选参数 `model`、`since`、`until`
+//! （RFC 3339 时间）与 `key`（受限 Key 的 ID，主 Key 为 `primary`）均可选：
+//! - `GET /admin/transcripts`：符合条件的记录数与其中已同意的记录数
+//! - `GET /admin/transcripts/export?format=openai|gemini`：导出已同意的记录为微调 JSONL
+//! - `POST /admin/transcripts/consent`：更新某个 Key 全部记录的同意标记
+//! - `DELETE /admin/transcripts`：删除符合条件的记录
+
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use lime_core::database::dao::proxy_transcript::{ProxyTranscriptDao, TranscriptFilter};
+use lime_core::database::{lock_db, DbConnection};
+use lime_core::i18n::{self, MessageCode};
+use serde::Deserialize;
+
+use crate::auth::oidc::AdminIdentity;
+use crate::fine_tune_export::{export_jsonl, FineTuneFormat};
+use crate::handlers::verify_admin_key;
+use crate::AppState;
+
+/// 单次导出的默认与最大记录数
+const DEFAULT_EXPORT_LIMIT: usize = 10_000;
+const MAX_EXPORT_LIMIT: usize = 100_000;
+
+/// 导出结果中被跳过的记录数
+const SKIPPED_HEADER: &str = "x-lime-export-skipped";
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TranscriptQuery {
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub format: FineTuneFormat,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl TranscriptQuery {
+    fn filter(&self, consented_only: bool) -> TranscriptFilter {
+        TranscriptFilter {
+            model: self.model.clone(),
+            since: self.since,
+            until: self.until,
+            key_id: self.key.clone(),
+            consented_only,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConsentRequest {
+    pub key: String,
+    pub consent: bool,
+}
+
+fn error_response(status: StatusCode, message: String, error_type: &str) -> Response {
+    (
+        status,
+        Json(serde_json::json!({
+            "error": {"message": message, "type": error_type}
+        })),
+    )
+        .into_response()
+}
+
+fn database(state: &AppState) -> Result<&DbConnection, Response> {
+    state.db.as_ref().ok_or_else(|| {
+        error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            i18n::t(MessageCode::DatabaseUnavailable).to_string(),
+            "service_unavailable",
+        )
+    })
+}
+
+/// `GET /admin/transcripts`
+pub async fn get_transcripts(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+    Query(query): Query<TranscriptQuery>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    let db = match database(&state) {
+        Ok(db) => db,
+        Err(response) => return response,
+    };
+    let result = lock_db(db).and_then(|conn| {
+        let count = |consented_only| {
+            ProxyTranscriptDao::count(&conn, &query.filter(consented_only))
+                .map_err(|e| e.to_string())
+        };
+        Ok((count(false)?, count(true)?))
+    });
+    match result {
+        Ok((total, consented)) => Json(serde_json::json!({
+            "enabled": state.transcript_capture.is_some(),
+            "total": total,
+            "consented": consented,
+        }))
+        .into_response(),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e, "server_error"),
+    }
+}
+
+/// `GET /admin/transcripts/export`
+pub async fn export_transcripts(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+    Query(query): Query<TranscriptQuery>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    let db = match database(&state) {
+        Ok(db) => db,
+        Err(response) => return response,
+    };
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_EXPORT_LIMIT)
+        .clamp(1, MAX_EXPORT_LIMIT);
+    let transcripts = match lock_db(db).and_then(|conn| {
+        ProxyTranscriptDao::list(&conn, &query.filter(true), limit).map_err(|e| e.to_string())
+    }) {
+        Ok(transcripts) => transcripts,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, e, "server_error"),
+    };
+
+    let (jsonl, summary) = export_jsonl(&transcripts, query.format);
+    tracing::info!(
+        "[TRANSCRIPT] 导出微调数据集: format={:?} exported={} skipped={}",
+        query.format,
+        summary.exported,
+        summary.skipped
+    );
+    let mut response = jsonl.into_response();
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/x-ndjson"),
+    );
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        HeaderValue::from_static("attachment; filename=\"fine-tune.jsonl\""),
+    );
+    headers.insert(SKIPPED_HEADER, HeaderValue::from(summary.skipped));
+    response
+}
+
+/// `POST /admin/transcripts/consent`
+pub async fn set_transcript_consent(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+    Json(request): Json<ConsentRequest>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    let db = match database(&state) {
+        Ok(db) => db,
+        Err(response) => return response,
+    };
+    match lock_db(db).and_then(|conn| {
+        ProxyTranscriptDao::set_consent(&conn, &request.key, request.consent)
+            .map_err(|e| e.to_string())
+    }) {
+        Ok(updated) => Json(serde_json::json!({
+            "key": request.key,
+            "consent": request.consent,
+            "updated": updated,
+        }))
+        .into_response(),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e, "server_error"),
+    }
+}
+
+/// `DELETE /admin/transcripts`
+pub async fn delete_transcripts(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+    Query(query): Query<TranscriptQuery>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    let db = match database(&state) {
+        Ok(db) => db,
+        Err(response) => return response,
+    };
+    match lock_db(db).and_then(|conn| {
+        ProxyTranscriptDao::delete(&conn, &query.filter(false)).map_err(|e| e.to_string())
+    }) {
+        Ok(deleted) => Json(serde_json::json!({ "deleted": deleted })).into_response(),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e, "server_error"),
+    }
+}
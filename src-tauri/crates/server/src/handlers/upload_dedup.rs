@@ -0,0 +1,53 @@
+//! 多模态上传去重接口
+//!
+//! 仅主 API Key（或通过管理接口 OIDC 认证的请求）可调用：
+//! - `GET /admin/upload-dedup`：映射表条目数与上传、复用统计
+//! - `DELETE /admin/upload-dedup`：清空映射表，之后的素材重新上传
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use lime_providers::upload_dedup;
+
+use crate::auth::oidc::AdminIdentity;
+use crate::handlers::verify_admin_key;
+use crate::AppState;
+
+/// `GET /admin/upload-dedup`
+pub async fn get_upload_dedup(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    Json(upload_dedup::status()).into_response()
+}
+
+/// `DELETE /admin/upload-dedup`
+pub async fn clear_upload_dedup(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    admin: Option<Extension<AdminIdentity>>,
+) -> Response {
+    if let Err(e) = verify_admin_key(&headers, admin.as_deref(), &state).await {
+        return e.into_response();
+    }
+    match upload_dedup::clear() {
+        Ok(cleared) => Json(serde_json::json!({ "cleared": cleared })).into_response(),
+        Err(message) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({
+                "error": {
+                    "message": message,
+                    "type": "service_unavailable"
+                }
+            })),
+        )
+            .into_response(),
+    }
+}
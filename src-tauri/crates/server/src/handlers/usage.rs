@@ -0,0 +1,148 @@
+//! 响应 Token 用量规范化
+//!
+//! 非流式 JSON 响应中的用量统一改写为客户端协议的格式：OpenAI 端点输出
+//! `prompt_tokens` / `completion_tokens` / `total_tokens` 及缓存、推理明细，
+//! Anthropic 端点输出 `input_tokens` / `output_tokens` 及缓存字段。
+//! 上游未返回用量时按请求与回复长度估算。流式响应原样返回。
+
+use axum::{
+    body::{to_bytes, Body},
+    http::header,
+    response::Response,
+};
+use lime_core::models::NormalizedUsage;
+use serde_json::Value;
+
+use super::fake_stream::SseFlavor;
+
+/// 规范化时读取的最大响应体
+const MAX_BODY_BYTES: usize = 32 * 1024 * 1024;
+
+fn is_json(response: &Response) -> bool {
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"))
+}
+
+/// 按回复模型对应的分词器估算输出 Token 数
+fn estimate_completion_tokens(flavor: SseFlavor, body: &Value) -> u32 {
+    let output = match flavor {
+        SseFlavor::OpenAi => &body["choices"],
+        SseFlavor::Anthropic => &body["content"],
+    };
+    if output.is_null() {
+        return 0;
+    }
+    lime_infra::tokenizer::count_json(output, body["model"].as_str()) as u32
+}
+
+/// 把 `patch` 中的字段合并进 `target`，对象字段逐层合并，保留上游的其他字段
+fn merge_into(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match target.get_mut(&key) {
+                    Some(existing) if existing.is_object() && value.is_object() => {
+                        merge_into(existing, value)
+                    }
+                    _ => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+/// 规范化响应体中的用量，返回规范化结果
+pub fn normalize_body(
+    flavor: SseFlavor,
+    estimated_prompt_tokens: u32,
+    body: &mut Value,
+) -> NormalizedUsage {
+    let usage = NormalizedUsage::from_response(body).unwrap_or_else(|| {
+        NormalizedUsage::estimated(
+            estimated_prompt_tokens,
+            estimate_completion_tokens(flavor, body),
+        )
+    });
+    let patch = match flavor {
+        SseFlavor::OpenAi => usage.to_openai(),
+        SseFlavor::Anthropic => usage.to_anthropic(),
+    };
+    if let Some(object) = body.as_object_mut() {
+        let target = object
+            .entry("usage")
+            .or_insert_with(|| Value::Object(Default::default()));
+        merge_into(target, patch);
+    }
+    usage
+}
+
+/// 规范化成功的非流式响应
+///
+/// 返回改写后的响应与规范化用量；流式、非 JSON 或失败的响应原样返回且用量为 `None`。
+pub async fn normalize(
+    flavor: SseFlavor,
+    estimated_prompt_tokens: u32,
+    response: Response,
+) -> (Response, Option<NormalizedUsage>) {
+    if !response.status().is_success() || !is_json(&response) {
+        return (response, None);
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("[USAGE] 读取响应失败: {}", e);
+            return (Response::from_parts(parts, Body::empty()), None);
+        }
+    };
+    let Ok(mut value) = serde_json::from_slice::<Value>(&bytes) else {
+        return (Response::from_parts(parts, Body::from(bytes)), None);
+    };
+    let usage = normalize_body(flavor, estimated_prompt_tokens, &mut value);
+    parts.headers.remove(header::CONTENT_LENGTH);
+    (
+        Response::from_parts(parts, Body::from(value.to_string())),
+        Some(usage),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_normalize_body_to_openai_shape() {
+        let mut body = json!({
+            "choices": [{"message": {"content": "hi"}}],
+            "usage": {
+                "input_tokens": 10,
+                "output_tokens": 5,
+                "cache_read_input_tokens": 20,
+                "service_tier": "standard"
+            }
+        });
+        let usage = normalize_body(SseFlavor::OpenAi, 0, &mut body);
+        assert!(!usage.estimated);
+        assert_eq!(body["usage"]["prompt_tokens"], 30);
+        assert_eq!(body["usage"]["total_tokens"], 35);
+        assert_eq!(body["usage"]["prompt_tokens_details"]["cached_tokens"], 20);
+        assert_eq!(body["usage"]["service_tier"], "standard");
+    }
+
+    #[test]
+    fn test_normalize_body_estimates_missing_usage() {
+        let mut body = json!({"content": [{"type": "text", "text": "hello world"}]});
+        let usage = normalize_body(SseFlavor::Anthropic, 42, &mut body);
+        assert!(usage.estimated);
+        assert_eq!(body["usage"]["input_tokens"], 42);
+        assert!(body["usage"]["output_tokens"].as_u64().unwrap() > 0);
+    }
+}
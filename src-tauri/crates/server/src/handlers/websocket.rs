@@ -16,20 +16,18 @@ use serde::Deserialize;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+use crate::handlers::provider_dispatch::prepare_provider;
 use crate::AppState;
 use lime_core::errors::GatewayErrorCode;
+use lime_core::i18n::{self, MessageCode};
 use lime_core::models::anthropic::AnthropicMessagesRequest;
 use lime_core::models::openai::ChatCompletionRequest;
 use lime_core::models::provider_pool_model::ProviderCredential;
 use lime_core::websocket::WsErrorCode;
 use lime_processor::RequestContext;
 use lime_providers::converter::anthropic_to_openai::convert_anthropic_to_openai;
-use lime_providers::converter::openai_to_antigravity::{
-    convert_antigravity_to_openai_response, convert_openai_to_antigravity_with_context,
-};
-use lime_providers::providers::{
-    AntigravityProvider, ClaudeCustomProvider, KiroProvider, OpenAICustomProvider,
-};
+use lime_providers::converter::grounding;
+use lime_providers::providers::{ClaudeCustomProvider, KiroProvider, OpenAICustomProvider};
 use lime_server_utils::parse_cw_response;
 use lime_websocket::{
     WsApiRequest, WsApiResponse, WsEndpoint, WsError, WsMessage as WsProtoMessage,
@@ -73,7 +71,7 @@ pub async fn ws_upgrade_handler(
         Some(_) => {
             return axum::http::Response::builder()
                 .status(401)
-                .body(Body::from("Invalid API key"))
+                .body(Body::from(i18n::t(MessageCode::AuthInvalidKey)))
                 .unwrap()
                 .into_response();
         }
@@ -683,70 +681,12 @@ pub async fn call_provider_openai_for_ws(
                 }
             }
         }
-        CredentialData::AntigravityOAuth {
-            creds_file_path,
-            project_id,
-        } => {
-            let mut antigravity = AntigravityProvider::new();
-            if let Err(e) = antigravity
-                .load_credentials_from_path(creds_file_path)
-                .await
-            {
-                if let Some(db) = &state.db {
-                    let _ = state.pool_service.mark_unhealthy(
-                        db,
-                        &credential.uuid,
-                        Some(&format!("Failed to load credentials: {e}")),
-                    );
-                }
-                return Err(e.to_string());
-            }
-
-            // 使用新的 validate_token() 方法检查 Token 状态
-            let validation_result = antigravity.validate_token();
-            tracing::info!("[Antigravity WS] Token 验证结果: {:?}", validation_result);
-
-            // 根据验证结果决定是否刷新
-            if validation_result.needs_refresh() {
-                tracing::info!("[Antigravity WS] Token 需要刷新，开始刷新...");
-                match antigravity.refresh_token_with_retry(3).await {
-                    Ok(new_token) => {
-                        tracing::info!(
-                            "[Antigravity WS] Token 刷新成功，新 token 长度: {}",
-                            new_token.len()
-                        );
-                        // 刷新成功，标记为健康
-                        if let Some(db) = &state.db {
-                            let _ = state.pool_service.mark_healthy(db, &credential.uuid, None);
-                        }
-                    }
-                    Err(refresh_error) => {
-                        tracing::error!("[Antigravity WS] Token 刷新失败: {:?}", refresh_error);
-                        // 使用新的 mark_unhealthy_with_details 方法
-                        if let Some(db) = &state.db {
-                            let _ = state.pool_service.mark_unhealthy_with_details(
-                                db,
-                                &credential.uuid,
-                                &refresh_error,
-                            );
-                        }
-                        return Err(refresh_error.user_message());
-                    }
-                }
-            }
-
-            // 设置项目 ID
-            if let Some(pid) = project_id {
-                antigravity.project_id = Some(pid.clone());
-            }
-            let proj_id = antigravity.project_id.clone().unwrap_or_default();
-
-            let antigravity_request = convert_openai_to_antigravity_with_context(request, &proj_id);
-            match antigravity
-                .call_api("generateContent", &antigravity_request)
+        CredentialData::AntigravityOAuth { .. } => {
+            let provider = prepare_provider(state, credential, &request.model)
                 .await
-            {
-                Ok(resp) => {
+                .map_err(|e| e.to_string())?;
+            match provider.chat(request).await {
+                Ok(mut response) => {
                     // 记录成功
                     if let Some(db) = &state.db {
                         let _ = state.pool_service.mark_healthy(
@@ -756,10 +696,16 @@ pub async fn call_provider_openai_for_ws(
                         );
                         let _ = state.pool_service.record_usage(db, &credential.uuid);
                     }
-                    Ok(convert_antigravity_to_openai_response(
-                        &resp,
-                        &request.model,
-                    ))
+                    if !state.citations.keep {
+                        grounding::strip_annotations(&mut response);
+                    }
+                    if state.chat_images.persist {
+                        crate::handlers::media::persist_response_images(
+                            &mut response,
+                            &crate::handlers::media::media_base_url(&state.base_url),
+                        );
+                    }
+                    Ok(response)
                 }
                 Err(e) => {
                     if let Some(db) = &state.db {
@@ -0,0 +1,334 @@
+//! 服务器实例锁与端口冲突处理
+//!
+//! 启动时检测端口是否已被占用：
+//! - 若占用者是另一个 Lime 实例（实例锁文件记录了其 PID、端口和控制令牌），
+//!   可通过本地控制接口通知其优雅退出后接管端口
+//! - 否则按配置自动回退到后续可用端口，最终地址通过服务器事件通知前端
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use lime_core::config::{PortConflictSettings, PortConflictStrategy};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+/// 本地控制接口：请求当前实例优雅退出
+pub const CONTROL_SHUTDOWN_PATH: &str = "/__lime/control/shutdown";
+
+/// 控制接口令牌请求头
+pub const CONTROL_TOKEN_HEADER: &str = "x-lime-control-token";
+
+/// 实例锁文件名（位于应用数据目录）
+const INSTANCE_LOCK_FILE: &str = "server-instance.json";
+
+/// 接管时轮询端口释放的间隔
+const TAKEOVER_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 实例锁（记录当前占用端口的 Lime 实例）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstanceLock {
+    pub pid: u32,
+    pub host: String,
+    pub port: u16,
+    /// 控制接口令牌（仅本机可读）
+    pub control_token: String,
+    pub started_at: String,
+}
+
+/// 默认实例锁路径
+pub fn instance_lock_path() -> PathBuf {
+    lime_core::app_paths::best_effort_app_data_file(INSTANCE_LOCK_FILE)
+}
+
+/// 读取实例锁（不存在或格式错误时返回 None）
+pub fn read_instance_lock(path: &Path) -> Option<InstanceLock> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 写入实例锁
+pub fn write_instance_lock(path: &Path, lock: &InstanceLock) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let content = serde_json::to_string_pretty(lock)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    std::fs::write(path, content)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+/// 删除实例锁（仅当锁属于指定进程时删除，避免误删接管者的锁）
+pub fn remove_instance_lock(path: &Path, pid: u32) {
+    if read_instance_lock(path).is_some_and(|lock| lock.pid == pid) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// 检测端口是否可绑定
+pub fn is_port_available(host: &str, port: u16) -> bool {
+    let Ok(ip) = host.parse::<IpAddr>() else {
+        return false;
+    };
+    TcpListener::bind(SocketAddr::new(ip, port)).is_ok()
+}
+
+/// 从 `port + 1` 开始查找可用端口
+pub fn find_fallback_port(
+    port: u16,
+    attempts: u16,
+    is_available: impl Fn(u16) -> bool,
+) -> Option<u16> {
+    (1..=attempts)
+        .filter_map(|offset| port.checked_add(offset))
+        .find(|candidate| is_available(*candidate))
+}
+
+/// 端口解析结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortResolution {
+    /// 端口空闲
+    Available,
+    /// 已接管旧实例的端口
+    TookOver { pid: u32 },
+    /// 回退到其他端口
+    Fallback { port: u16 },
+}
+
+impl PortResolution {
+    /// 最终使用的端口
+    pub fn port(&self, requested: u16) -> u16 {
+        match self {
+            PortResolution::Fallback { port } => *port,
+            _ => requested,
+        }
+    }
+}
+
+/// 控制接口应访问的地址（监听全部网卡时使用回环地址）
+fn control_host(host: &str) -> String {
+    match host.parse::<IpAddr>() {
+        Ok(ip) if ip.is_unspecified() => Ipv4Addr::LOCALHOST.to_string(),
+        Ok(IpAddr::V6(ip)) => format!("[{ip}]"),
+        _ => host.to_string(),
+    }
+}
+
+/// 通知旧实例退出并等待端口释放
+async fn takeover(
+    host: &str,
+    port: u16,
+    lock: &InstanceLock,
+    timeout: Duration,
+) -> Result<(), String> {
+    // 同一进程内重启时旧监听器正在关闭，只需等待端口释放
+    if lock.pid != std::process::id() {
+        let url = format!(
+            "http://{}:{port}{CONTROL_SHUTDOWN_PATH}",
+            control_host(host)
+        );
+        let response = reqwest::Client::new()
+            .post(&url)
+            .header(CONTROL_TOKEN_HEADER, &lock.control_token)
+            .timeout(timeout)
+            .send()
+            .await
+            .map_err(|e| format!("通知旧实例退出失败: {e}"))?;
+        if !response.status().is_success() {
+            return Err(format!("旧实例拒绝退出请求: {}", response.status()));
+        }
+    }
+
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if is_port_available(host, port) {
+            return Ok(());
+        }
+        tokio::time::sleep(TAKEOVER_POLL_INTERVAL).await;
+    }
+    Err(format!("等待旧实例释放端口 {port} 超时"))
+}
+
+/// 解析启动端口
+///
+/// `Takeover` 策略在接管失败（占用者不是 Lime 实例或未按时退出）时继续尝试回退端口。
+pub async fn resolve_port(
+    host: &str,
+    port: u16,
+    settings: &PortConflictSettings,
+    lock_path: &Path,
+) -> Result<PortResolution, String> {
+    if is_port_available(host, port) {
+        return Ok(PortResolution::Available);
+    }
+
+    match settings.strategy {
+        PortConflictStrategy::Fail => {
+            return Err(format!("端口 {port} 已被占用"));
+        }
+        PortConflictStrategy::Takeover => {
+            match read_instance_lock(lock_path).filter(|lock| lock.port == port) {
+                Some(lock) => {
+                    let timeout = Duration::from_millis(settings.takeover_timeout_ms);
+                    match takeover(host, port, &lock, timeout).await {
+                        Ok(()) => {
+                            tracing::info!(
+                                "[SERVER] 已接管 Lime 实例 (PID {}) 的端口 {}",
+                                lock.pid,
+                                port
+                            );
+                            return Ok(PortResolution::TookOver { pid: lock.pid });
+                        }
+                        Err(e) => tracing::warn!("[SERVER] 接管端口 {} 失败: {}", port, e),
+                    }
+                }
+                None => tracing::warn!(
+                    "[SERVER] 端口 {} 被非 Lime 进程占用，无法接管，尝试回退端口",
+                    port
+                ),
+            }
+        }
+        PortConflictStrategy::Fallback => {}
+    }
+
+    find_fallback_port(port, settings.fallback_attempts, |candidate| {
+        is_port_available(host, candidate)
+    })
+    .map(|fallback| {
+        tracing::warn!("[SERVER] 端口 {} 已被占用，回退到 {}", port, fallback);
+        PortResolution::Fallback { port: fallback }
+    })
+    .ok_or_else(|| {
+        format!(
+            "端口 {port} 已被占用，且后续 {} 个端口均不可用",
+            settings.fallback_attempts
+        )
+    })
+}
+
+/// 当前实例的控制状态（供控制接口使用）
+#[derive(Debug)]
+pub struct InstanceControl {
+    token: String,
+    shutdown: Notify,
+    taken_over: AtomicBool,
+}
+
+impl InstanceControl {
+    pub fn new() -> Self {
+        Self {
+            token: uuid::Uuid::new_v4().simple().to_string(),
+            shutdown: Notify::new(),
+            taken_over: AtomicBool::new(false),
+        }
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// 校验令牌并请求退出，返回令牌是否有效
+    pub fn request_shutdown(&self, token: &str) -> bool {
+        use subtle::ConstantTimeEq;
+
+        if !bool::from(self.token.as_bytes().ct_eq(token.as_bytes())) {
+            return false;
+        }
+        self.taken_over.store(true, Ordering::SeqCst);
+        self.shutdown.notify_one();
+        true
+    }
+
+    /// 等待退出请求
+    pub async fn wait_for_shutdown(&self) {
+        self.shutdown.notified().await;
+    }
+
+    /// 是否已被其他实例接管
+    pub fn is_taken_over(&self) -> bool {
+        self.taken_over.load(Ordering::SeqCst)
+    }
+}
+
+impl Default for InstanceControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_find_first_available_fallback_port() {
+        assert_eq!(find_fallback_port(8999, 5, |p| p == 9002), Some(9002));
+        assert_eq!(find_fallback_port(8999, 2, |p| p == 9002), None);
+        assert_eq!(find_fallback_port(u16::MAX, 5, |_| true), None);
+    }
+
+    #[test]
+    fn should_only_remove_own_instance_lock() {
+        let dir = tempfile::tempdir().expect("创建临时目录失败");
+        let path = dir.path().join(INSTANCE_LOCK_FILE);
+        let lock = InstanceLock {
+            pid: 42,
+            host: "127.0.0.1".to_string(),
+            port: 8999,
+            control_token: "token".to_string(),
+            started_at: "2026-01-01T00:00:00Z".to_string(),
+        };
+
+        write_instance_lock(&path, &lock).expect("写入应成功");
+        assert_eq!(read_instance_lock(&path), Some(lock));
+
+        remove_instance_lock(&path, 7);
+        assert!(path.exists());
+        remove_instance_lock(&path, 42);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn should_reject_invalid_control_token() {
+        let control = InstanceControl::new();
+        assert!(!control.request_shutdown("wrong"));
+        assert!(!control.is_taken_over());
+
+        let token = control.token().to_string();
+        assert!(control.request_shutdown(&token));
+        assert!(control.is_taken_over());
+    }
+
+    #[tokio::test]
+    async fn should_fall_back_when_port_is_occupied() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("绑定失败");
+        let port = listener.local_addr().expect("读取地址失败").port();
+        let dir = tempfile::tempdir().expect("创建临时目录失败");
+        let lock_path = dir.path().join(INSTANCE_LOCK_FILE);
+
+        let fail = PortConflictSettings {
+            strategy: PortConflictStrategy::Fail,
+            ..PortConflictSettings::default()
+        };
+        assert!(resolve_port("127.0.0.1", port, &fail, &lock_path)
+            .await
+            .is_err());
+
+        let resolution = resolve_port(
+            "127.0.0.1",
+            port,
+            &PortConflictSettings::default(),
+            &lock_path,
+        )
+        .await
+        .expect("应回退到可用端口");
+        assert!(matches!(resolution, PortResolution::Fallback { port: p } if p > port));
+    }
+}
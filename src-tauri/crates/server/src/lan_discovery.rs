@@ -0,0 +1,112 @@
+//! 局域网 mDNS 发现
+//!
+//! 服务器监听非回环地址时，通过 mDNS 广播 `_lime._tcp.local.` 服务，
+//! 移动端可直接发现网关地址。TXT 记录只包含协议信息，不包含任何密钥。
+
+use std::net::IpAddr;
+
+use mdns_sd::{ServiceDaemon, ServiceInfo};
+
+/// mDNS 服务类型
+pub const LAN_SERVICE_TYPE: &str = "_lime._tcp.local.";
+
+/// 默认实例名称
+const DEFAULT_INSTANCE_NAME: &str = "Lime";
+
+/// 广播的 IP 地址（监听全部网卡时使用所有局域网地址）
+fn announce_ips(host: &str) -> Vec<String> {
+    match host.parse::<IpAddr>() {
+        Ok(ip) if ip.is_loopback() => Vec::new(),
+        Ok(ip) if ip.is_unspecified() => lime_core::network::get_network_info()
+            .map(|info| info.all_ips)
+            .unwrap_or_default(),
+        Ok(ip) => vec![ip.to_string()],
+        Err(_) => Vec::new(),
+    }
+}
+
+/// mDNS 主机名（`lime-192-168-1-2.local.`）
+fn mdns_host_name(ip: &str) -> String {
+    format!("lime-{}.local.", ip.replace(['.', ':'], "-"))
+}
+
+/// mDNS 广播句柄（drop 时注销服务）
+pub struct LanAnnouncer {
+    daemon: ServiceDaemon,
+    fullname: String,
+}
+
+impl LanAnnouncer {
+    /// 开始广播；监听回环地址时返回 `Ok(None)`
+    pub fn start(
+        host: &str,
+        port: u16,
+        instance_name: Option<&str>,
+    ) -> Result<Option<Self>, String> {
+        let ips = announce_ips(host);
+        let Some(primary_ip) = ips.first() else {
+            tracing::info!("[LAN] 监听地址 {} 不可被局域网访问，跳过 mDNS 广播", host);
+            return Ok(None);
+        };
+
+        let instance_name = instance_name
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .unwrap_or(DEFAULT_INSTANCE_NAME);
+        let version = env!("CARGO_PKG_VERSION");
+        let properties = [
+            ("version", version),
+            ("path", "/v1"),
+            ("api", "openai,anthropic"),
+            ("pairing", "qr"),
+        ];
+
+        let daemon = ServiceDaemon::new().map_err(|e| format!("启动 mDNS 失败: {e}"))?;
+        let info = ServiceInfo::new(
+            LAN_SERVICE_TYPE,
+            instance_name,
+            &mdns_host_name(primary_ip),
+            ips.join(",").as_str(),
+            port,
+            &properties[..],
+        )
+        .map_err(|e| format!("构建 mDNS 服务信息失败: {e}"))?;
+        let fullname = info.get_fullname().to_string();
+        daemon
+            .register(info)
+            .map_err(|e| format!("注册 mDNS 服务失败: {e}"))?;
+
+        tracing::info!(
+            "[LAN] 已通过 mDNS 广播 {} ({}:{})",
+            fullname,
+            primary_ip,
+            port
+        );
+        Ok(Some(Self { daemon, fullname }))
+    }
+}
+
+impl Drop for LanAnnouncer {
+    fn drop(&mut self) {
+        let _ = self.daemon.unregister(&self.fullname);
+        let _ = self.daemon.shutdown();
+        tracing::info!("[LAN] 已停止 mDNS 广播 {}", self.fullname);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_skip_loopback_hosts() {
+        assert!(announce_ips("127.0.0.1").is_empty());
+        assert!(announce_ips("localhost").is_empty());
+        assert_eq!(announce_ips("192.168.1.20"), vec!["192.168.1.20"]);
+    }
+
+    #[test]
+    fn should_build_valid_mdns_host_name() {
+        assert_eq!(mdns_host_name("192.168.1.20"), "lime-192-168-1-20.local.");
+    }
+}
@@ -5,20 +5,29 @@
 pub mod auth;
 pub mod chrome_bridge;
 pub mod client_detector;
+pub mod degraded_pool;
+pub mod deps;
+pub mod fine_tune_export;
+pub mod instance_guard;
+pub mod lan_discovery;
 pub mod middleware;
+pub mod rag;
+pub mod sse;
 
 use axum::{
     extract::{DefaultBodyLimit, Path, Query, State},
-    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
+use instance_guard::{InstanceControl, InstanceLock, PortResolution};
+use lime_core::app_events::{app_event_bus, publish_app_event, AppEvent, ConfigEvent, ServerEvent};
 use lime_core::config::{
     Config, ConfigChangeKind, ConfigManager, EndpointProvidersConfig, FileChangeEvent, FileWatcher,
     HotReloadManager, ReloadResult,
 };
-use lime_core::database::dao::provider_pool::ProviderPoolDao;
+use lime_core::database::pool_storage::{lock_pool, pool_storage};
 use lime_core::database::DbConnection;
 use lime_core::logger::LogStore;
 use lime_core::models::anthropic::*;
@@ -34,11 +43,13 @@ use lime_providers::providers::claude_custom::ClaudeCustomProvider;
 use lime_providers::providers::gemini::GeminiProvider;
 use lime_providers::providers::kiro::KiroProvider;
 use lime_providers::providers::openai_custom::OpenAICustomProvider;
+use lime_providers::providers::ProviderSetupError;
 use lime_server_utils::{
     build_anthropic_response, build_anthropic_stream_response, build_error_response,
     build_error_response_with_status, build_gemini_cli_request, build_gemini_native_request,
     models, parse_cw_response,
 };
+use lime_services::embedding_cache_service::EmbeddingCacheStore;
 use lime_services::kiro_event_service::KiroEventService;
 use lime_services::provider_pool_service::ProviderPoolService;
 use lime_services::token_cache_service::TokenCacheService;
@@ -47,9 +58,17 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::{oneshot, RwLock};
-use tower_http::cors::CorsLayer;
 use tower_http::timeout::TimeoutLayer;
 
+/// 用量快照推送间隔（秒）
+const USAGE_TICK_INTERVAL_SECS: u64 = 5;
+
+/// 滚动统计快照推送间隔（秒）
+const STATS_SNAPSHOT_INTERVAL_SECS: u64 = 15;
+
+/// 请求上下文中 `max_tokens` 收紧记录的元数据键
+pub const MAX_TOKENS_CLAMP_METADATA: &str = "max_tokens_clamp";
+
 /// 记录请求统计到遥测系统
 pub fn record_request_telemetry(
     state: &AppState,
@@ -94,6 +113,11 @@ pub fn record_request_telemetry(
     // 设置重试次数
     log.retry_count = ctx.retry_count;
 
+    // 输出上限收紧记录
+    log.max_tokens_clamp = ctx
+        .get_metadata(MAX_TOKENS_CLAMP_METADATA)
+        .and_then(|value| serde_json::from_value(value.clone()).ok());
+
     // 记录到统计聚合器
     {
         let stats = state.processor.stats.write();
@@ -105,6 +129,11 @@ pub fn record_request_telemetry(
         let _ = logger.record(log.clone());
     }
 
+    // 累计到事件总线的用量快照（重试中的请求不计数）
+    if status != lime_infra::telemetry::RequestStatus::Retrying {
+        app_event_bus().record_request(status == lime_infra::telemetry::RequestStatus::Success);
+    }
+
     tracing::info!(
         "[TELEMETRY] request_id={} provider={:?} model={} status={:?} duration_ms={}",
         ctx.request_id,
@@ -122,21 +151,39 @@ pub fn record_token_usage(
     input_tokens: Option<u32>,
     output_tokens: Option<u32>,
 ) {
-    use lime_infra::telemetry::{TokenSource, TokenUsageRecord};
-
     // 只有当至少有一个 Token 值时才记录
     if input_tokens.is_none() && output_tokens.is_none() {
         return;
     }
 
+    let input_tokens = input_tokens.unwrap_or(0);
+    let output_tokens = output_tokens.unwrap_or(0);
+    record_usage(
+        state,
+        ctx,
+        &lime_core::models::NormalizedUsage {
+            prompt_tokens: input_tokens,
+            completion_tokens: output_tokens,
+            total_tokens: input_tokens.saturating_add(output_tokens),
+            ..Default::default()
+        },
+    );
+}
+
+/// 记录规范化后的 Token 使用量（含缓存与推理 Token 明细）
+pub fn record_usage(
+    state: &AppState,
+    ctx: &RequestContext,
+    usage: &lime_core::models::NormalizedUsage,
+) {
+    use lime_infra::telemetry::TokenUsageRecord;
+
     let provider = ctx.provider.unwrap_or(lime_core::ProviderType::Kiro);
-    let record = TokenUsageRecord::new(
+    let record = TokenUsageRecord::from_usage(
         uuid::Uuid::new_v4().to_string(),
         provider,
         ctx.resolved_model.clone(),
-        input_tokens.unwrap_or(0),
-        output_tokens.unwrap_or(0),
-        TokenSource::Actual,
+        usage,
     )
     .with_request_id(ctx.request_id.clone());
 
@@ -145,12 +192,26 @@ pub fn record_token_usage(
         let tokens = state.processor.tokens.write();
         tokens.record(record);
     }
+    // 计入凭证的出站 TPM 限额
+    if let (Some(limiter), Some(credential_id)) =
+        (state.outbound_limiter.clone(), ctx.credential_id.clone())
+    {
+        let total = u64::from(usage.prompt_tokens) + u64::from(usage.completion_tokens);
+        tokio::spawn(async move { limiter.record_tokens(&credential_id, total).await });
+    }
+    app_event_bus().record_tokens(
+        u64::from(usage.prompt_tokens),
+        u64::from(usage.completion_tokens),
+    );
 
     tracing::debug!(
-        "[TOKEN] request_id={} input={} output={}",
+        "[TOKEN] request_id={} input={} output={} cached={} reasoning={} estimated={}",
         ctx.request_id,
-        input_tokens.unwrap_or(0),
-        output_tokens.unwrap_or(0)
+        usage.prompt_tokens,
+        usage.completion_tokens,
+        usage.cached_tokens,
+        usage.reasoning_tokens,
+        usage.estimated
     );
 }
 
@@ -159,6 +220,8 @@ pub struct ServerStatus {
     pub running: bool,
     pub host: String,
     pub port: u16,
+    /// 配置的端口（发生端口回退时与 `port` 不同）
+    pub requested_port: u16,
     pub requests: u64,
     pub uptime_secs: u64,
     /// 最近 1 分钟错误率（0.0 - 1.0）
@@ -167,8 +230,10 @@ pub struct ServerStatus {
     pub p95_latency_ms_1m: Option<u64>,
     /// 当前熔断的上游数量（当前版本暂未接入熔断器）
     pub open_circuit_count: u32,
-    /// 当前活跃请求数（近似值，当前版本默认 0）
+    /// 当前在途的上游请求数（含未结束的流式响应）
     pub active_requests: u64,
+    /// 累计被客户端中断的请求数
+    pub aborted_requests: u64,
     /// 能力过滤与跨 Provider 回退指标
     pub capability_routing:
         middleware::capability_routing_metrics::CapabilityRoutingMetricsSnapshot,
@@ -286,6 +351,18 @@ pub struct ServerState {
     pub running_api_key: Option<String>,
     /// 服务器实际监听的 host（可能与配置不同，因为会自动切换到有效的 IP）
     pub running_host: Option<String>,
+    /// 服务器实际监听的端口（端口冲突时可能回退到其他端口）
+    pub running_port: Option<u16>,
+    /// 当前实例的控制状态（被其他实例接管时标记）
+    instance_control: Option<Arc<InstanceControl>>,
+    /// 局域网 mDNS 广播（drop 时注销）
+    lan_announcer: Option<lan_discovery::LanAnnouncer>,
+    /// 受限 API Key（移动端扫码配对签发）
+    pub scoped_keys: Arc<auth::scoped_keys::ScopedKeyStore>,
+    /// 认证失败锁定表（跨重启保留）
+    pub auth_lockout: Arc<auth::lockout::AuthLockout>,
+    /// 维护模式（运行时可切换）
+    pub maintenance: Arc<middleware::maintenance::MaintenanceMode>,
     /// 能力路由指标（能力过滤/模型回退/Provider 回退）
     pub capability_routing_metrics_store:
         Arc<middleware::capability_routing_metrics::CapabilityRoutingMetricsStore>,
@@ -295,6 +372,8 @@ pub struct ServerState {
     pub request_dedup_store: Arc<middleware::request_dedup::RequestDedupStore>,
     /// 幂等性存储（用于状态统计与运行时共享）
     pub idempotency_store: Arc<middleware::idempotency::IdempotencyStore>,
+    /// 在途请求统计（凭证并发占用与客户端中断）
+    pub inflight_tracker: Arc<handlers::abort::InflightTracker>,
 }
 
 impl ServerState {
@@ -319,6 +398,12 @@ impl ServerState {
                 cacheable_status_codes: config.server.response_cache.cacheable_status_codes.clone(),
             },
         ));
+        let auth_lockout = Arc::new(auth::lockout::AuthLockout::new(
+            config.server.auth_lockout.clone(),
+        ));
+        let maintenance = Arc::new(middleware::maintenance::MaintenanceMode::new(
+            config.server.maintenance_mode.clone(),
+        ));
 
         Self {
             config,
@@ -334,30 +419,44 @@ impl ServerState {
             shutdown_tx: None,
             running_api_key: None,
             running_host: None,
+            running_port: None,
+            instance_control: None,
+            lan_announcer: None,
+            scoped_keys: Arc::new(auth::scoped_keys::ScopedKeyStore::load_default()),
+            auth_lockout,
+            maintenance,
             capability_routing_metrics_store: Arc::new(
                 middleware::capability_routing_metrics::CapabilityRoutingMetricsStore::new(),
             ),
             response_cache_store,
             request_dedup_store,
             idempotency_store,
+            inflight_tracker: Arc::new(handlers::abort::InflightTracker::new()),
         }
     }
 
     pub fn status(&self) -> ServerStatus {
         ServerStatus {
-            running: self.running,
+            // 被其他实例接管后本实例已退出
+            running: self.running
+                && !self
+                    .instance_control
+                    .as_ref()
+                    .is_some_and(|control| control.is_taken_over()),
             // 使用实际运行的 host，如果没有则使用配置的 host
             host: self
                 .running_host
                 .clone()
                 .unwrap_or_else(|| self.config.server.host.clone()),
-            port: self.config.server.port,
+            port: self.running_port.unwrap_or(self.config.server.port),
+            requested_port: self.config.server.port,
             requests: self.requests,
             uptime_secs: self.start_time.map(|t| t.elapsed().as_secs()).unwrap_or(0),
             error_rate_1m: 0.0,
             p95_latency_ms_1m: None,
             open_circuit_count: 0,
-            active_requests: 0,
+            active_requests: self.inflight_tracker.active(),
+            aborted_requests: self.inflight_tracker.aborted(),
             capability_routing: self.capability_routing_metrics_store.snapshot(),
             response_cache: self.response_cache_store.stats(),
             request_dedup: self.request_dedup_store.stats(),
@@ -434,9 +533,6 @@ impl ServerState {
             return Ok(());
         }
 
-        let (tx, rx) = oneshot::channel();
-        self.shutdown_tx = Some(tx);
-
         // 智能选择监听地址
         // - 127.0.0.1, localhost, 0.0.0.0, :: 直接使用
         // - 局域网 IP：检查是否在当前网卡列表中，如果不在则自动切换到当前局域网 IP
@@ -452,7 +548,34 @@ impl ServerState {
             );
         }
 
-        let port = self.config.server.port;
+        // 端口冲突处理：接管旧 Lime 实例或回退到可用端口
+        let requested_port = self.config.server.port;
+        let lock_path = instance_guard::instance_lock_path();
+        let resolution = instance_guard::resolve_port(
+            &host,
+            requested_port,
+            &self.config.server.port_conflict,
+            &lock_path,
+        )
+        .await?;
+        let port = resolution.port(requested_port);
+        match resolution {
+            PortResolution::TookOver { pid } => {
+                publish_app_event(AppEvent::Server(ServerEvent::TookOver { pid, port }));
+            }
+            PortResolution::Fallback { port } => {
+                publish_app_event(AppEvent::Server(ServerEvent::PortFallback {
+                    requested_port,
+                    port,
+                }));
+            }
+            PortResolution::Available => {}
+        }
+
+        let (tx, rx) = oneshot::channel();
+        self.shutdown_tx = Some(tx);
+        let instance_control = Arc::new(InstanceControl::new());
+
         let api_key = self.config.server.api_key.clone();
         let api_key_for_state = api_key.clone(); // 用于保存到 running_api_key
         let default_provider_ref = self.default_provider_ref.clone();
@@ -543,6 +666,23 @@ impl ServerState {
         ));
         self.response_cache_store = response_cache_store.clone();
 
+        let instance_lock = InstanceLock {
+            pid: std::process::id(),
+            host: host.clone(),
+            port,
+            control_token: instance_control.token().to_string(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+        };
+        let server_instance_control = instance_control.clone();
+        let scoped_keys = self.scoped_keys.clone();
+        self.auth_lockout
+            .apply_settings(config.server.auth_lockout.clone());
+        let auth_lockout = self.auth_lockout.clone();
+        self.maintenance
+            .apply_settings(config.server.maintenance_mode.clone());
+        let maintenance = self.maintenance.clone();
+        let inflight_tracker = self.inflight_tracker.clone();
+
         tokio::spawn(async move {
             if let Err(e) = run_server(
                 &host,
@@ -567,6 +707,11 @@ impl ServerState {
                 response_cache_store,
                 request_dedup_store,
                 idempotency_store,
+                server_instance_control,
+                scoped_keys,
+                auth_lockout,
+                maintenance,
+                inflight_tracker,
                 None, // dev_bridge_callback: 由主 crate 在重新导出层注入
             )
             .await
@@ -581,6 +726,22 @@ impl ServerState {
         self.running_api_key = Some(api_key_for_state);
         // 保存服务器实际监听的 host（可能与配置不同）
         self.running_host = Some(running_host);
+        self.running_port = Some(port);
+        self.instance_control = Some(instance_control);
+        if let Err(e) = instance_guard::write_instance_lock(&lock_path, &instance_lock) {
+            tracing::warn!("[SERVER] 写入实例锁失败: {}", e);
+        }
+        let lan_discovery = &self.config.server.lan_discovery;
+        if lan_discovery.enabled {
+            match lan_discovery::LanAnnouncer::start(
+                &instance_lock.host,
+                port,
+                lan_discovery.instance_name.as_deref(),
+            ) {
+                Ok(announcer) => self.lan_announcer = announcer,
+                Err(e) => tracing::warn!("[LAN] {}", e),
+            }
+        }
         Ok(())
     }
 
@@ -592,7 +753,14 @@ impl ServerState {
         self.start_time = None;
         self.running_api_key = None;
         self.running_host = None;
+        self.running_port = None;
+        self.instance_control = None;
+        self.lan_announcer = None;
         self.router_ref = None;
+        instance_guard::remove_instance_lock(
+            &instance_guard::instance_lock_path(),
+            std::process::id(),
+        );
     }
 }
 
@@ -640,6 +808,18 @@ pub struct AppState {
     pub api_key_service: Arc<lime_services::api_key_provider_service::ApiKeyProviderService>,
     /// 速率限制器
     pub rate_limiter: Option<Arc<middleware::rate_limit::SlidingWindowRateLimiter>>,
+    /// 按凭证的出站限额（未配置时为空）
+    pub outbound_limiter: Option<Arc<middleware::outbound_limit::OutboundLimiter>>,
+    /// 按客户端的突发流量平滑（未启用时为 None）
+    pub burst_smoother: Option<Arc<middleware::burst_smoothing::BurstSmoother>>,
+    /// 交互 / 后台双通道调度（未启用时为 None）
+    pub priority_scheduler: Option<Arc<middleware::priority_lanes::PriorityScheduler>>,
+    /// 模型级并发与排队（未启用时为 None）
+    pub model_concurrency: Option<Arc<middleware::model_concurrency::ModelConcurrency>>,
+    /// 在途请求日志（未启用时为 None）
+    pub request_journal: Option<Arc<middleware::request_journal::RequestJournal>>,
+    /// 对话记录采集（未启用或没有数据库时为 None）
+    pub transcript_capture: Option<Arc<middleware::transcript_capture::TranscriptCapture>>,
     /// 幂等性存储
     pub idempotency_store: Arc<middleware::idempotency::IdempotencyStore>,
     /// 请求去重存储（请求指纹 in-flight + 短 TTL 回放）
@@ -649,8 +829,88 @@ pub struct AppState {
     /// 能力路由指标（能力过滤/模型回退/Provider 回退）
     pub capability_routing_metrics_store:
         Arc<middleware::capability_routing_metrics::CapabilityRoutingMetricsStore>,
+    /// 嵌入缓存存储（持久化 `/v1/embeddings` 向量）
+    pub embedding_cache_store: Arc<EmbeddingCacheStore>,
+    /// 本地 RAG 向量存储
+    pub rag_store: Arc<rag::RagStore>,
+    /// 重排序配置（`/v1/rerank`）
+    pub rerank_settings: lime_core::config::RerankSettings,
+    /// 开发辅助端点配置（`/utils/*`）
+    pub dev_utils: lime_core::config::DevUtilsSettings,
+    /// 容量错误自动降级模型
+    pub model_downgrade: lime_core::config::ModelDowngradeSettings,
+    /// 对等实例转发配置（本地无可用凭证时转发给其他实例）
+    pub peer_forwarding: lime_core::config::PeerForwardingSettings,
+    /// 伪流式配置
+    pub fake_streaming: lime_core::config::FakeStreamingSettings,
+    /// 流式响应合并与 NDJSON 配置
+    pub stream_transform: lime_core::config::StreamTransformSettings,
+    /// SSE 心跳配置
+    pub sse_heartbeat: lime_core::config::SseHeartbeatSettings,
+    /// 响应归属标注配置
+    pub attribution: lime_core::config::AttributionSettings,
+    /// 搜索接地引用透传配置
+    pub citations: lime_core::config::CitationSettings,
+    /// Gemini 代码执行工具配置
+    pub code_execution: lime_core::config::CodeExecutionSettings,
+    /// 对话响应图片输出配置
+    pub chat_images: lime_core::config::ChatImageSettings,
+    /// 按凭证类型创建 Provider 的注册表
+    pub provider_registry: Arc<lime_providers::providers::ProviderRegistry>,
+    /// 在途请求统计（凭证并发占用与客户端中断）
+    pub inflight: Arc<handlers::abort::InflightTracker>,
+    /// 管理接口 OIDC 验证器（未启用时为 None）
+    pub admin_oidc: Option<Arc<auth::oidc::OidcVerifier>>,
     /// 凭证清理器
     pub sanitizer: Arc<lime_core::sanitizer::CredentialSanitizer>,
+    /// 实例控制（供其他实例通过控制接口请求接管）
+    pub instance_control: Arc<InstanceControl>,
+    /// 受限 API Key（仅可调用推理端点）
+    pub scoped_keys: Arc<auth::scoped_keys::ScopedKeyStore>,
+    /// 启用公网隧道时，经转发的请求要求强 API Key
+    pub tunnel_requires_strong_key: bool,
+    /// 通过 Cloudflare 隧道暴露（此时才信任 `cf-connecting-ip`）
+    pub behind_cloudflare: bool,
+    /// 认证失败锁定表
+    pub auth_lockout: Arc<auth::lockout::AuthLockout>,
+    /// HMAC 请求签名验证
+    pub request_signer: Arc<middleware::request_signing::RequestSigner>,
+    /// 远程控制接口签名验证（未启用时为 None）
+    pub remote_control: Option<Arc<handlers::remote_control::RemoteControl>>,
+    /// 上游响应头透传策略
+    pub upstream_header_policy: Arc<middleware::upstream_headers::UpstreamHeaderPolicy>,
+    /// 提示词防火墙
+    pub prompt_firewall: Arc<middleware::prompt_firewall::PromptFirewall>,
+    /// 响应内容策略
+    pub content_policy: Arc<handlers::content_policy::ContentPolicy>,
+    /// 最大输出 Token 上限
+    pub max_output_tokens: lime_core::config::MaxOutputTokenSettings,
+    /// 流式输出上限
+    pub stream_output_caps: lime_core::config::StreamOutputCapSettings,
+    /// 端点开关
+    pub endpoints: lime_core::config::EndpointToggleSettings,
+    /// 维护模式
+    pub maintenance: Arc<middleware::maintenance::MaintenanceMode>,
+    /// 数据库不可用时的内存降级凭证池
+    pub degraded_pool: Arc<degraded_pool::DegradedPool>,
+    /// 凭证池（处理器通过 trait 对象访问，便于测试替换）
+    pub credential_pool: Arc<dyn deps::CredentialPool>,
+    /// 日志输出
+    pub log_sink: Arc<dyn deps::LogSink>,
+    /// 时间来源
+    pub clock: Arc<dyn deps::Clock>,
+}
+
+impl AppState {
+    /// 处理器依赖集合
+    pub fn handler_deps(&self) -> deps::HandlerDeps {
+        deps::HandlerDeps {
+            pool: self.credential_pool.clone(),
+            logs: self.log_sink.clone(),
+            clock: self.clock.clone(),
+            providers: self.provider_registry.clone(),
+        }
+    }
 }
 
 /// 启动配置文件监控
@@ -671,6 +931,7 @@ async fn start_config_watcher(
     logs: Arc<RwLock<LogStore>>,
     db: Option<DbConnection>,
     config_manager: Option<Arc<std::sync::RwLock<ConfigManager>>>,
+    degraded_pool: Arc<degraded_pool::DegradedPool>,
 ) -> Option<FileWatcher> {
     let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<FileChangeEvent>();
 
@@ -717,6 +978,10 @@ async fn start_config_watcher(
                 match &result {
                     ReloadResult::Success { .. } => {
                         tracing::info!("[HOT_RELOAD] 配置热重载成功");
+                        publish_app_event(AppEvent::Config(ConfigEvent::Reloaded {
+                            success: true,
+                            message: None,
+                        }));
                         logs_clone
                             .write()
                             .await
@@ -725,6 +990,8 @@ async fn start_config_watcher(
                         // 更新处理器中的组件
                         let new_config = manager.config();
                         update_processor_config(&processor_clone, &new_config).await;
+                        degraded_pool.reload(degraded_pool::credentials_from_config(&new_config));
+                        lime_providers::providers::endpoints::configure(&new_config.providers);
 
                         // 同步凭证池
                         if let (Some(ref db), Some(ref cfg_manager)) =
@@ -755,6 +1022,10 @@ async fn start_config_watcher(
                     }
                     ReloadResult::RolledBack { error, .. } => {
                         tracing::warn!("[HOT_RELOAD] 配置热重载失败，已回滚: {}", error);
+                        publish_app_event(AppEvent::Config(ConfigEvent::Reloaded {
+                            success: false,
+                            message: Some(format!("已回滚: {error}")),
+                        }));
                         logs_clone.write().await.add(
                             "warn",
                             &format!("[HOT_RELOAD] 配置热重载失败，已回滚: {error}"),
@@ -770,6 +1041,10 @@ async fn start_config_watcher(
                             error,
                             rollback_error
                         );
+                        publish_app_event(AppEvent::Config(ConfigEvent::Reloaded {
+                            success: false,
+                            message: Some(error.to_string()),
+                        }));
                         logs_clone.write().await.add(
                             "error",
                             &format!(
@@ -851,6 +1126,42 @@ async fn update_processor_config(processor: &RequestProcessor, config: &Config)
         );
     }
 
+    // 更新弃用模型表
+    *processor.deprecations.write().await =
+        lime_core::router::ModelDeprecations::from_config(&config.routing.model_deprecation);
+
+    // 更新规模路由
+    *processor.size_router.write().await =
+        lime_core::router::SizeRouter::from_config(&config.routing.size_routing);
+
+    // 更新时间段路由
+    *processor.time_window_router.write().await =
+        lime_core::router::TimeWindowRouter::from_config(&config.routing.time_windows);
+
+    // 更新按地区代理
+    lime_providers::regional_proxy::configure(
+        &config.server.regional_proxy,
+        config.proxy_url.as_deref(),
+    );
+
+    // 更新转换器影子验证
+    lime_providers::converter::shadow::configure(&config.server.converter_shadow);
+
+    // 更新分词器
+    lime_infra::tokenizer::configure(&config.server.tokenizer);
+
+    // 更新多模态上传去重
+    lime_providers::upload_dedup::configure(&config.server.upload_dedup);
+
+    // 更新 Gemini 上下文缓存
+    lime_providers::context_cache::configure(&config.server.context_cache);
+
+    // 更新后端文案语言
+    lime_core::i18n::configure(config.server.locale.as_deref(), &config.language);
+
+    // 更新匿名使用统计开关
+    lime_core::anonymous_stats::configure(&config.anonymous_stats);
+
     // 注意：重试配置目前不支持热更新，因为 Retrier 是不可变的
     // 如果需要更新重试配置，需要重启服务器
     tracing::debug!(
@@ -883,17 +1194,16 @@ async fn sync_credential_pool_from_config(
     // 从配置加载凭证
     let credentials = sync_service.load_from_config().map_err(|e| e.to_string())?;
 
-    let conn = lime_core::database::lock_db(db)?;
+    let conn = lock_pool(db)?;
     let mut synced_count = 0;
 
     for cred in &credentials {
         // 检查凭证是否已存在
-        let existing =
-            ProviderPoolDao::get_by_uuid(&conn, &cred.uuid).map_err(|e| e.to_string())?;
+        let existing = pool_storage().get_by_uuid(&conn, &cred.uuid)?;
 
         if existing.is_some() {
             // 更新现有凭证
-            ProviderPoolDao::update(&conn, cred).map_err(|e| e.to_string())?;
+            pool_storage().update(&conn, cred)?;
             tracing::debug!(
                 "[HOT_RELOAD] 更新凭证: {} ({})",
                 cred.uuid,
@@ -901,7 +1211,7 @@ async fn sync_credential_pool_from_config(
             );
         } else {
             // 添加新凭证
-            ProviderPoolDao::insert(&conn, cred).map_err(|e| e.to_string())?;
+            pool_storage().insert(&conn, cred)?;
             tracing::debug!(
                 "[HOT_RELOAD] 添加凭证: {} ({})",
                 cred.uuid,
@@ -942,6 +1252,11 @@ async fn run_server(
     response_cache_store: Arc<middleware::response_cache::ResponseCacheStore>,
     request_dedup_store: Arc<middleware::request_dedup::RequestDedupStore>,
     idempotency_store: Arc<middleware::idempotency::IdempotencyStore>,
+    instance_control: Arc<InstanceControl>,
+    scoped_keys: Arc<auth::scoped_keys::ScopedKeyStore>,
+    auth_lockout: Arc<auth::lockout::AuthLockout>,
+    maintenance: Arc<middleware::maintenance::MaintenanceMode>,
+    inflight_tracker: Arc<handlers::abort::InflightTracker>,
     dev_bridge_callback: Option<DevBridgeCallback>,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let base_url = format!("http://{host}:{port}");
@@ -967,6 +1282,27 @@ async fn run_server(
         }
     }
 
+    // 从配置初始化弃用模型表、规模路由、时间段路由与按地区代理
+    if let Some(cfg) = &config {
+        *processor.deprecations.write().await =
+            lime_core::router::ModelDeprecations::from_config(&cfg.routing.model_deprecation);
+        *processor.size_router.write().await =
+            lime_core::router::SizeRouter::from_config(&cfg.routing.size_routing);
+        *processor.time_window_router.write().await =
+            lime_core::router::TimeWindowRouter::from_config(&cfg.routing.time_windows);
+        lime_providers::regional_proxy::configure(
+            &cfg.server.regional_proxy,
+            cfg.proxy_url.as_deref(),
+        );
+        lime_providers::converter::shadow::configure(&cfg.server.converter_shadow);
+        lime_infra::tokenizer::configure(&cfg.server.tokenizer);
+        lime_providers::upload_dedup::configure(&cfg.server.upload_dedup);
+        lime_providers::context_cache::configure(&cfg.server.context_cache);
+        lime_core::i18n::configure(cfg.server.locale.as_deref(), &cfg.language);
+        lime_core::anonymous_stats::configure(&cfg.anonymous_stats);
+    }
+    lime_providers::upload_dedup::set_database(db.clone());
+
     // 从配置初始化 Router 的默认 Provider
     if let Some(cfg) = &config {
         let default_provider_str = &cfg.routing.default_provider;
@@ -1051,6 +1387,78 @@ async fn run_server(
         .as_ref()
         .map(|c| c.retry.auto_switch_provider)
         .unwrap_or(true);
+
+    // 初始化嵌入缓存（依赖数据库持久化），启动时清理过期条目
+    let embedding_cache_store = Arc::new(EmbeddingCacheStore::new(
+        config
+            .as_ref()
+            .map(|c| (&c.server.embedding_cache).into())
+            .unwrap_or_default(),
+        db.clone(),
+    ));
+    let pruned = embedding_cache_store.prune();
+    if pruned > 0 {
+        tracing::info!("[SERVER] 嵌入缓存启动清理 {} 条过期条目", pruned);
+    }
+    let rag_store = Arc::new(rag::RagStore::new(
+        config
+            .as_ref()
+            .map(|c| c.server.rag.clone())
+            .unwrap_or_default(),
+        db.clone(),
+    ));
+
+    // 限流计数（配置 Redis 时多实例共享）
+    let distributed_rate_limit = config
+        .as_ref()
+        .map(|c| c.server.distributed_rate_limit.clone())
+        .unwrap_or_default();
+    let limit_store = Arc::new(
+        middleware::shared_counter::CounterStore::from_settings(&distributed_rate_limit).await,
+    );
+    let rate_limit_config = config
+        .as_ref()
+        .map(|c| middleware::rate_limit::RateLimitConfig::from(&c.rate_limit))
+        .unwrap_or_default();
+    let outbound_limiter = middleware::outbound_limit::OutboundLimiter::from_settings(
+        &distributed_rate_limit,
+        limit_store.clone(),
+    )
+    .map(Arc::new);
+    lime_providers::outbound_limit::install(
+        outbound_limiter
+            .clone()
+            .map(middleware::outbound_limit::OutboundLimiter::into_check),
+    );
+
+    let degraded_pool = Arc::new(degraded_pool::DegradedPool::new(
+        config
+            .as_ref()
+            .map(degraded_pool::credentials_from_config)
+            .unwrap_or_default(),
+    ));
+    let credential_pool: Arc<dyn deps::CredentialPool> = Arc::new(deps::DbCredentialPool::new(
+        pool_service.clone(),
+        db.clone(),
+        degraded_pool.clone(),
+    ));
+    let log_sink: Arc<dyn deps::LogSink> = logs.clone();
+    let request_journal = config
+        .as_ref()
+        .and_then(|c| {
+            middleware::request_journal::RequestJournal::from_settings(&c.server.request_journal)
+        })
+        .map(Arc::new);
+    let transcript_capture = config
+        .as_ref()
+        .and_then(|c| {
+            middleware::transcript_capture::TranscriptCapture::from_settings(
+                &c.server.transcript_capture,
+                db.as_ref(),
+            )
+        })
+        .map(Arc::new);
+
     let state = AppState {
         api_key: api_key.to_string(),
         base_url,
@@ -1076,15 +1484,147 @@ async fn run_server(
         kiro_event_service,
         api_key_service,
         rate_limiter: Some(Arc::new(
-            middleware::rate_limit::SlidingWindowRateLimiter::new(
-                middleware::rate_limit::RateLimitConfig::default(),
-            ),
+            middleware::rate_limit::SlidingWindowRateLimiter::new(rate_limit_config)
+                .with_shared_store(limit_store),
         )),
+        outbound_limiter,
+        burst_smoother: config
+            .as_ref()
+            .and_then(|c| {
+                middleware::burst_smoothing::BurstSmoother::from_settings(&c.server.burst_smoothing)
+            })
+            .map(Arc::new),
+        priority_scheduler: config
+            .as_ref()
+            .and_then(|c| {
+                middleware::priority_lanes::PriorityScheduler::from_settings(
+                    &c.server.priority_lanes,
+                )
+            })
+            .map(Arc::new),
+        model_concurrency: config
+            .as_ref()
+            .and_then(|c| {
+                middleware::model_concurrency::ModelConcurrency::from_settings(
+                    &c.server.model_concurrency,
+                )
+            })
+            .map(Arc::new),
+        request_journal: request_journal.clone(),
+        transcript_capture,
         idempotency_store,
         request_dedup_store,
         response_cache_store,
         capability_routing_metrics_store,
+        embedding_cache_store,
+        rag_store,
+        rerank_settings: config
+            .as_ref()
+            .map(|c| c.server.rerank.clone())
+            .unwrap_or_default(),
+        dev_utils: config
+            .as_ref()
+            .map(|c| c.server.dev_utils.clone())
+            .unwrap_or_default(),
+        model_downgrade: config
+            .as_ref()
+            .map(|c| c.server.model_downgrade.clone())
+            .unwrap_or_default(),
+        peer_forwarding: config
+            .as_ref()
+            .map(|c| c.server.peer_forwarding.clone())
+            .unwrap_or_default(),
+        fake_streaming: config
+            .as_ref()
+            .map(|c| c.server.fake_streaming.clone())
+            .unwrap_or_default(),
+        stream_transform: config
+            .as_ref()
+            .map(|c| c.server.stream_transform.clone())
+            .unwrap_or_default(),
+        sse_heartbeat: config
+            .as_ref()
+            .map(|c| c.server.sse_heartbeat.clone())
+            .unwrap_or_default(),
+        attribution: config
+            .as_ref()
+            .map(|c| c.server.attribution.clone())
+            .unwrap_or_default(),
+        citations: config
+            .as_ref()
+            .map(|c| c.server.citations.clone())
+            .unwrap_or_default(),
+        code_execution: config
+            .as_ref()
+            .map(|c| c.server.code_execution.clone())
+            .unwrap_or_default(),
+        chat_images: config
+            .as_ref()
+            .map(|c| c.server.chat_images.clone())
+            .unwrap_or_default(),
+        provider_registry: Arc::new(lime_providers::providers::ProviderRegistry::with_builtin()),
+        inflight: inflight_tracker,
+        admin_oidc: config
+            .as_ref()
+            .and_then(|c| auth::oidc::OidcVerifier::from_settings(&c.server.admin_oidc))
+            .map(Arc::new),
         sanitizer: Arc::new(lime_core::sanitizer::CredentialSanitizer::with_defaults()),
+        instance_control: instance_control.clone(),
+        scoped_keys,
+        tunnel_requires_strong_key: config
+            .as_ref()
+            .is_some_and(|c| c.gateway.tunnel.enabled && c.gateway.tunnel.require_strong_api_key),
+        behind_cloudflare: config.as_ref().is_some_and(|c| {
+            c.gateway.tunnel.enabled && c.gateway.tunnel.provider.eq_ignore_ascii_case("cloudflare")
+        }),
+        auth_lockout,
+        request_signer: Arc::new(middleware::request_signing::RequestSigner::new(
+            config
+                .as_ref()
+                .map(|c| c.server.request_signing.clone())
+                .unwrap_or_default(),
+        )),
+        remote_control: config
+            .as_ref()
+            .and_then(|c| {
+                handlers::remote_control::RemoteControl::from_settings(&c.server.remote_control)
+            })
+            .map(Arc::new),
+        upstream_header_policy: Arc::new(middleware::upstream_headers::UpstreamHeaderPolicy::new(
+            &config
+                .as_ref()
+                .map(|c| c.server.upstream_headers.clone())
+                .unwrap_or_default(),
+        )),
+        prompt_firewall: Arc::new(middleware::prompt_firewall::PromptFirewall::new(
+            &config
+                .as_ref()
+                .map(|c| c.server.prompt_firewall.clone())
+                .unwrap_or_default(),
+        )),
+        content_policy: Arc::new(handlers::content_policy::ContentPolicy::new(
+            &config
+                .as_ref()
+                .map(|c| c.server.content_policy.clone())
+                .unwrap_or_default(),
+        )),
+        max_output_tokens: config
+            .as_ref()
+            .map(|c| c.server.max_output_tokens.clone())
+            .unwrap_or_default(),
+        stream_output_caps: config
+            .as_ref()
+            .map(|c| c.server.stream_output_caps.clone())
+            .unwrap_or_default(),
+        endpoints: config
+            .as_ref()
+            .map(|c| c.server.endpoints.clone())
+            .unwrap_or_default(),
+        maintenance,
+        degraded_pool,
+        credential_pool,
+        log_sink,
+        clock: Arc::new(deps::SystemClock),
     };
 
     // ========== 开发模式：通过回调启动桥接服务器 ==========
@@ -1101,12 +1641,29 @@ async fn run_server(
             logs_clone,
             db_clone,
             config_manager,
+            state.degraded_pool.clone(),
         )
         .await
     } else {
         None
     };
 
+    // 滚动统计快照的数据来源
+    let telemetry_stats = state.processor.stats.clone();
+    let telemetry_tokens = state.processor.tokens.clone();
+    let telemetry_db = state.db.clone();
+    let telemetry_api_keys = state.api_key_service.clone();
+    let warm_pool = config
+        .as_ref()
+        .and_then(|c| {
+            lime_services::warm_pool_service::WarmPool::from_settings(&c.server.warm_pool)
+        })
+        .map(Arc::new);
+    let warm_pool_for_stats = warm_pool.clone();
+    let warm_pool_db = state.db.clone();
+    let warm_pool_api_keys = state.api_key_service.clone();
+    let scoped_keys_for_rotation = state.scoped_keys.clone();
+
     // 设置请求体大小限制为 100MB，支持大型上下文请求（如 Claude Code 的 /compact 命令）
     let body_limit = 100 * 1024 * 1024; // 100MB
 
@@ -1137,51 +1694,159 @@ async fn run_server(
             get(handlers::credentials_get_token),
         );
 
-    let allowed_origins = vec![
-        HeaderValue::from_static("http://localhost:1420"),
-        HeaderValue::from_static("http://127.0.0.1:1420"),
-        HeaderValue::from_static("http://localhost:5173"),
-        HeaderValue::from_static("http://127.0.0.1:5173"),
-        HeaderValue::from_static("tauri://localhost"),
-        HeaderValue::from_static("http://tauri.localhost"),
-        HeaderValue::from_static("https://tauri.localhost"),
-    ];
-
-    let cors_layer = CorsLayer::new()
-        .allow_origin(allowed_origins)
-        .allow_methods([
-            Method::GET,
-            Method::POST,
-            Method::PUT,
-            Method::PATCH,
-            Method::DELETE,
-            Method::OPTIONS,
-        ])
-        .allow_headers([
-            header::AUTHORIZATION,
-            header::CONTENT_TYPE,
-            header::ACCEPT,
-            header::ORIGIN,
-        ]);
+    // CORS：内置桌面端来源 + 配置的局域网来源（按来源控制预检缓存与私有网络访问）
+    let cors_layer = middleware::cors::build_cors_layer(
+        &config
+            .as_ref()
+            .map(|c| c.server.cors.clone())
+            .unwrap_or_default(),
+    );
 
     let app = Router::new()
         .route("/health", get(health))
         .route("/cache", get(cache_diagnostics))
         .route("/stats", get(stats_diagnostics))
+        .route(
+            instance_guard::CONTROL_SHUTDOWN_PATH,
+            post(control_shutdown),
+        )
         .route("/v1/models", get(models))
         .route("/v1/routes", get(list_routes))
+        .route(
+            "/admin/maintenance",
+            get(handlers::maintenance::get_maintenance)
+                .put(handlers::maintenance::set_maintenance),
+        )
+        .route(
+            "/admin/journal",
+            get(handlers::request_journal::get_journal)
+                .delete(handlers::request_journal::clear_journal),
+        )
+        .route(
+            "/admin/journal/replay",
+            post(handlers::request_journal::replay_journal),
+        )
+        .route(
+            "/admin/journal/:id/replay",
+            post(handlers::request_journal::replay_lost_request),
+        )
+        .route(
+            "/admin/journal/:id",
+            get(handlers::request_journal::get_lost_request),
+        )
+        .route(
+            "/admin/transcripts",
+            get(handlers::transcripts::get_transcripts)
+                .delete(handlers::transcripts::delete_transcripts),
+        )
+        .route(
+            "/admin/transcripts/export",
+            get(handlers::transcripts::export_transcripts),
+        )
+        .route(
+            "/admin/transcripts/consent",
+            post(handlers::transcripts::set_transcript_consent),
+        )
+        .route(
+            "/admin/regional-proxy",
+            get(handlers::regional_proxy::get_regional_proxy)
+                .delete(handlers::regional_proxy::clear_regional_proxy),
+        )
+        .route("/admin/route-test", post(handlers::route_test::test_route))
+        .route(
+            "/admin/converter-shadow",
+            get(handlers::converter_shadow::get_converter_shadow)
+                .delete(handlers::converter_shadow::clear_converter_shadow),
+        )
+        .route(
+            "/admin/upload-dedup",
+            get(handlers::upload_dedup::get_upload_dedup)
+                .delete(handlers::upload_dedup::clear_upload_dedup),
+        )
+        .route(
+            "/admin/context-cache",
+            get(handlers::context_cache::get_context_cache)
+                .delete(handlers::context_cache::clear_context_cache),
+        )
+        .route(
+            "/admin/bulk/disable-failing",
+            post(handlers::bulk_admin::disable_failing_credentials),
+        )
+        .route(
+            "/admin/bulk/clear-cooldowns",
+            post(handlers::bulk_admin::clear_credential_cooldowns),
+        )
+        .route(
+            "/admin/bulk/health-check",
+            post(handlers::bulk_admin::bulk_health_check),
+        )
+        .route(
+            "/admin/bulk/rotate-keys",
+            post(handlers::bulk_admin::rotate_scoped_keys),
+        )
+        .route(
+            "/admin/remote/reload-config",
+            post(handlers::remote_control::reload_config),
+        )
+        .route(
+            "/admin/remote/providers/toggle",
+            post(handlers::remote_control::toggle_provider),
+        )
+        .route(
+            "/admin/remote/credentials/rotate",
+            post(handlers::remote_control::rotate_credentials),
+        )
+        .route("/v1/signing/snippet", get(handlers::signing_snippet))
+        .route(
+            "/v1/keys",
+            get(handlers::scoped_keys::list_scoped_keys)
+                .post(handlers::scoped_keys::create_scoped_key),
+        )
+        .route(
+            "/v1/keys/:id",
+            axum::routing::delete(handlers::scoped_keys::revoke_scoped_key),
+        )
+        .route(
+            "/v1/keys/:id/successor",
+            post(handlers::scoped_keys::claim_scoped_key_successor),
+        )
+        .route("/v1/embeddings", post(handlers::handle_embeddings))
+        .route("/v1/rag/documents", post(handlers::handle_rag_upsert))
+        .route(
+            "/v1/rag/documents/:id",
+            axum::routing::delete(handlers::handle_rag_delete),
+        )
+        .route("/v1/rag/query", post(handlers::handle_rag_query))
+        .route("/v1/rerank", post(handlers::handle_rerank))
+        .route(
+            "/utils/summarize-diff",
+            post(handlers::dev_utils::handle_summarize_diff),
+        )
+        .route(
+            "/utils/commit-message",
+            post(handlers::dev_utils::handle_commit_message),
+        )
         .route("/v1/chat/completions", post(
             |State(state): State<AppState>,
              headers: HeaderMap,
+             admin: Option<axum::Extension<auth::oidc::AdminIdentity>>,
+             logprobs: Option<axum::Extension<lime_core::models::LogprobsOptions>>,
+             seed: Option<axum::Extension<lime_core::models::SeedOptions>>,
              Json(request): Json<lime_core::models::openai::ChatCompletionRequest>| async {
-                handlers::chat_completions(State(state), headers, Json(request)).await
+                let logprobs = logprobs.map(|axum::Extension(options)| options);
+                let seed = seed.map(|axum::Extension(options)| options);
+                let admin = admin.map(|axum::Extension(identity)| identity);
+                handlers::chat_completions(State(state), headers, admin, Json(request), logprobs, seed)
+                    .await
             }
         ))
         .route("/v1/messages", post(
             |State(state): State<AppState>,
              headers: HeaderMap,
+             admin: Option<axum::Extension<auth::oidc::AdminIdentity>>,
              Json(request): Json<AnthropicMessagesRequest>| async {
-                handlers::anthropic_messages(State(state), headers, Json(request)).await
+                let admin = admin.map(|axum::Extension(identity)| identity);
+                handlers::anthropic_messages(State(state), headers, admin, Json(request)).await
             }
         ))
         .route("/v1/messages/count_tokens", post(count_tokens))
@@ -1190,6 +1855,8 @@ async fn run_server(
             "/v1/images/generations",
             post(handlers::handle_image_generation),
         )
+        // 对话图片媒体文件
+        .route("/v1/media/:name", get(handlers::media::serve_media))
         // WebSocket 路由
         .route("/v1/ws", get(handlers::ws_upgrade_handler))
         .route("/ws", get(handlers::ws_upgrade_handler))
@@ -1214,6 +1881,71 @@ async fn run_server(
         .merge(kiro_api_routes)
         // 凭证 API 路由（用于 aster Agent 集成）
         .merge(credentials_api_routes)
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::stream_output_cap::stream_output_cap_middleware,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::sse_heartbeat::sse_heartbeat_middleware,
+        ))
+        .layer(axum::middleware::from_fn(
+            middleware::logprobs::logprobs_middleware,
+        ))
+        .layer(axum::middleware::from_fn(middleware::seed::seed_middleware))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::upstream_headers::upstream_headers_middleware,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::priority_lanes::priority_lane_middleware,
+        ))
+        // 先按模型排队，拿到模型名额后再占用优先级通道名额；两者只处理已认证的请求
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::model_concurrency::model_concurrency_middleware,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::transcript_capture::transcript_capture_middleware,
+        ))
+        // 在途请求日志与对话记录采集只处理已认证的请求（见 auth::caller）
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::request_journal::request_journal_middleware,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth::caller::caller_identity_middleware,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::request_signing::request_signing_middleware,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth::oidc::admin_oidc_middleware,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth::lockout::auth_lockout_middleware,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::maintenance::maintenance_middleware,
+        ))
+        // 端点开关在认证之前生效，关闭的端点一律 404
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::endpoint_toggle::endpoint_toggle_middleware,
+        ))
+        .layer(axum::middleware::from_fn(
+            middleware::anonymous_stats::anonymous_stats_middleware,
+        ))
+        .layer(axum::middleware::from_fn(
+            middleware::locale::locale_middleware,
+        ))
         .layer(cors_layer)
         .layer(DefaultBodyLimit::max(body_limit))
         .layer(TimeoutLayer::with_status_code(
@@ -1231,16 +1963,128 @@ async fn run_server(
     })?;
 
     tracing::info!("Server listening on {}", addr);
+    publish_app_event(AppEvent::Server(ServerEvent::Started {
+        host: host.to_string(),
+        port,
+    }));
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(async move {
-            let _ = shutdown.await;
-        })
-        .await?;
+    // 重新执行上次运行中丢失的后台请求
+    if let Some(journal) = request_journal.filter(|journal| journal.replay_on_startup()) {
+        let replay_base_url =
+            middleware::request_journal::loopback_base_url(&format!("http://{host}:{port}"));
+        let api_key = api_key.to_string();
+        tokio::spawn(async move {
+            // 只自动重放主 Key 发起的请求，受限 Key 的请求需管理员提供原 Key 后重放
+            let replayed = journal
+                .replay(
+                    &replay_base_url,
+                    lime_core::database::dao::proxy_transcript::PRIMARY_KEY_ID,
+                    &api_key,
+                    None,
+                )
+                .await;
+            if replayed > 0 {
+                tracing::info!("[JOURNAL] 已重新执行 {} 个丢失的后台请求", replayed);
+            }
+        });
+    }
+
+    // 按轮换策略推进受限 Key 状态并发送通知
+    let key_rotation_task = auth::key_rotation::spawn(
+        scoped_keys_for_rotation.clone(),
+        config
+            .as_ref()
+            .map(|c| c.server.key_rotation.clone())
+            .unwrap_or_default(),
+    );
+
+    // 预热本地模型并定期保活
+    let warm_pool_task = warm_pool.map(|pool| {
+        lime_services::warm_pool_service::spawn(pool, warm_pool_db, warm_pool_api_keys)
+    });
+
+    // 定期向前端推送用量快照
+    let usage_tick_task = tokio::spawn(async {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(USAGE_TICK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            app_event_bus().flush_usage_tick(USAGE_TICK_INTERVAL_SECS);
+        }
+    });
+
+    // 定期预计算滚动统计并推送给前端（与上次相同时跳过）
+    let stats_snapshot_task = tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(STATS_SNAPSHOT_INTERVAL_SECS));
+        let mut last: Option<lime_core::app_events::StatsSnapshot> = None;
+        let local_monitor = lime_services::local_resource_service::LocalResourceMonitor::new();
+        loop {
+            interval.tick().await;
+            let now = chrono::Utc::now();
+            let start = now - chrono::Duration::seconds(lime_infra::telemetry::ROLLING_WINDOW_SECS);
+            let logs = telemetry_stats.read().get_since(start);
+            let records = telemetry_tokens.read().get_by_time_range(start, now);
+            let mut snapshot = lime_infra::telemetry::rolling_snapshot(&logs, &records, now);
+            // 附带已启用的本地推理服务资源占用
+            let local_targets = telemetry_db
+                .as_ref()
+                .and_then(|db| telemetry_api_keys.get_all_providers(db).ok())
+                .map(|providers| lime_services::local_resource_service::local_targets(&providers))
+                .unwrap_or_default();
+            (snapshot.local_providers, snapshot.gpus) = local_monitor.collect(&local_targets).await;
+            snapshot.warm_models = warm_pool_for_stats
+                .as_ref()
+                .map(|pool| pool.statuses())
+                .unwrap_or_default();
+            if last.as_ref() == Some(&snapshot) {
+                continue;
+            }
+            last = Some(snapshot.clone());
+            publish_app_event(AppEvent::Stats(snapshot));
+        }
+    });
+
+    let result = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown(async move {
+        tokio::select! {
+            _ = shutdown => {}
+            _ = instance_control.wait_for_shutdown() => {
+                tracing::info!("[SERVER] 收到接管请求，正在退出");
+            }
+        }
+    })
+    .await;
+
+    key_rotation_task.abort();
+    scoped_keys_for_rotation.flush();
+    if let Some(task) = warm_pool_task {
+        task.abort();
+    }
+    usage_tick_task.abort();
+    stats_snapshot_task.abort();
+    publish_app_event(AppEvent::Server(ServerEvent::Stopped));
+    result?;
 
     Ok(())
 }
 
+/// 本地控制接口：新实例接管端口前请求本实例优雅退出
+async fn control_shutdown(State(state): State<AppState>, headers: HeaderMap) -> StatusCode {
+    let token = headers
+        .get(instance_guard::CONTROL_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if state.instance_control.request_shutdown(token) {
+        StatusCode::ACCEPTED
+    } else {
+        StatusCode::UNAUTHORIZED
+    }
+}
+
 #[derive(Debug, Default, Deserialize)]
 struct HealthQuery {
     #[serde(default)]
@@ -1286,9 +2130,15 @@ fn build_diagnostics_from_app_state(
 }
 
 async fn health(State(state): State<AppState>, Query(query): Query<HealthQuery>) -> Response {
+    let degraded = state.degraded_pool.status();
+    let status = if degraded.active {
+        "degraded"
+    } else {
+        "healthy"
+    };
     if !query.full {
         return Json(serde_json::json!({
-            "status": "healthy",
+            "status": status,
             "version": env!("CARGO_PKG_VERSION")
         }))
         .into_response();
@@ -1301,8 +2151,9 @@ async fn health(State(state): State<AppState>, Query(query): Query<HealthQuery>)
             (header::PRAGMA, "no-cache"),
         ],
         Json(serde_json::json!({
-            "status": "healthy",
+            "status": status,
             "version": env!("CARGO_PKG_VERSION"),
+            "degraded": degraded,
             "diagnostics": diagnostics
         })),
     )
@@ -1341,15 +2192,20 @@ async fn stats_diagnostics(
 async fn count_tokens(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Json(_request): Json<serde_json::Value>,
+    Json(request): Json<serde_json::Value>,
 ) -> Response {
-    if let Err(e) = handlers::verify_api_key(&headers, &state.api_key).await {
+    if let Err(e) = handlers::verify_inbound_api_key(&headers, &state).await {
         return e.into_response();
     }
 
-    // Claude Code 需要这个端点，返回估算值
+    // Claude Code 需要这个端点，按模型对应的分词器估算
+    let model = request["model"].as_str();
+    let input_tokens: usize = ["system", "messages", "tools"]
+        .iter()
+        .map(|field| lime_infra::tokenizer::count_json(&request[*field], model))
+        .sum();
     Json(serde_json::json!({
-        "input_tokens": 100
+        "input_tokens": input_tokens
     }))
     .into_response()
 }
@@ -1364,7 +2220,7 @@ async fn gemini_generate_content(
     Path(path): Path<String>,
     Json(request): Json<serde_json::Value>,
 ) -> Response {
-    if let Err(e) = handlers::verify_api_key(&headers, &state.api_key).await {
+    if let Err(e) = handlers::verify_inbound_api_key(&headers, &state).await {
         return e.into_response();
     }
 
@@ -1385,6 +2241,9 @@ async fn gemini_generate_content(
 
     let model = parts[0];
     let method = parts[1];
+    if let Err(e) = handlers::verify_scoped_key_model(&headers, &state, model) {
+        return e.into_response();
+    }
 
     state.logs.write().await.add(
         "info",
@@ -1450,82 +2309,52 @@ async fn gemini_generate_content(
             creds_file_path,
             project_id,
         } => {
-            let mut antigravity = AntigravityProvider::new();
-            if let Err(e) = antigravity
-                .load_credentials_from_path(creds_file_path)
-                .await
+            // 原生 Gemini 透传需要直接调用 call_api，这里只复用 Provider 的凭证准备流程
+            let mut antigravity = match AntigravityProvider::prepare(
+                &cred.uuid,
+                creds_file_path,
+                project_id.as_deref(),
+            )
+            .await
             {
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({
-                        "error": {
-                            "message": format!("加载 Antigravity 凭证失败: {}", e)
-                        }
-                    })),
-                )
-                    .into_response();
-            }
-
-            // 使用新的 validate_token() 方法检查 Token 状态
-            let validation_result = antigravity.validate_token();
-            tracing::info!(
-                "[Antigravity Gemini] Token 验证结果: {:?}",
-                validation_result
-            );
-
-            // 根据验证结果决定是否刷新
-            if validation_result.needs_refresh() {
-                tracing::info!("[Antigravity Gemini] Token 需要刷新，开始刷新...");
-                match antigravity.refresh_token_with_retry(3).await {
-                    Ok(new_token) => {
-                        tracing::info!(
-                            "[Antigravity Gemini] Token 刷新成功，新 token 长度: {}",
-                            new_token.len()
-                        );
-                    }
-                    Err(refresh_error) => {
-                        tracing::error!("[Antigravity Gemini] Token 刷新失败: {:?}", refresh_error);
-
-                        // 根据错误类型返回不同的状态码和消息
-                        let (status, message) = if refresh_error.requires_reauth() {
+                Ok((antigravity, _)) => antigravity,
+                Err(e) => {
+                    let (status, message) = match &e {
+                        ProviderSetupError::LoadCredentials { message, .. } => (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            format!("加载 Antigravity 凭证失败: {message}"),
+                        ),
+                        ProviderSetupError::TokenRefresh(refresh_error)
+                            if refresh_error.requires_reauth() =>
+                        {
                             (StatusCode::UNAUTHORIZED, refresh_error.user_message())
-                        } else {
-                            (
-                                StatusCode::INTERNAL_SERVER_ERROR,
-                                refresh_error.user_message(),
-                            )
-                        };
-
-                        return (
-                            status,
-                            Json(serde_json::json!({
-                                "error": {
-                                    "message": message
-                                }
-                            })),
-                        )
-                            .into_response();
-                    }
+                        }
+                        _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+                    };
+                    return (
+                        status,
+                        Json(serde_json::json!({
+                            "error": {
+                                "message": message
+                            }
+                        })),
+                    )
+                        .into_response();
                 }
-            }
+            };
+            antigravity.code_execution = state.code_execution.enabled_for(model);
 
-            // 设置项目 ID
-            if let Some(pid) = project_id {
-                antigravity.project_id = Some(pid.clone());
-            } else if antigravity.project_id.is_none() {
-                // 如果凭证中没有 project_id，尝试从 API 获取或生成随机 ID
-                if let Err(e) = antigravity.discover_project().await {
-                    tracing::warn!("[Antigravity] 获取项目 ID 失败: {}，使用随机生成的 ID", e);
-                    // 生成随机项目 ID
-                    let uuid = uuid::Uuid::new_v4();
-                    let bytes = uuid.as_bytes();
-                    let adjectives = ["useful", "bright", "swift", "calm", "bold"];
-                    let nouns = ["fuze", "wave", "spark", "flow", "core"];
-                    let adj = adjectives[(bytes[0] as usize) % adjectives.len()];
-                    let noun = nouns[(bytes[1] as usize) % nouns.len()];
-                    let random_part: String = uuid.to_string()[..5].to_lowercase();
-                    antigravity.project_id = Some(format!("{adj}-{noun}-{random_part}"));
-                }
+            // 凭证中没有 project_id 且获取失败时，使用随机生成的 ID
+            if antigravity.project_id.is_none() {
+                tracing::warn!("[Antigravity] 获取项目 ID 失败，使用随机生成的 ID");
+                let uuid = uuid::Uuid::new_v4();
+                let bytes = uuid.as_bytes();
+                let adjectives = ["useful", "bright", "swift", "calm", "bold"];
+                let nouns = ["fuze", "wave", "spark", "flow", "core"];
+                let adj = adjectives[(bytes[0] as usize) % adjectives.len()];
+                let noun = nouns[(bytes[1] as usize) % nouns.len()];
+                let random_part: String = uuid.to_string()[..5].to_lowercase();
+                antigravity.project_id = Some(format!("{adj}-{noun}-{random_part}"));
             }
 
             let proj_id = antigravity.project_id.clone().unwrap_or_else(|| {
@@ -1566,9 +2395,11 @@ async fn gemini_generate_content(
             }
 
             // 非流式响应
-            match antigravity
-                .call_api("generateContent", &antigravity_request)
-                .await
+            match lime_providers::outbound_limit::scope(
+                &cred.uuid,
+                antigravity.call_api("generateContent", &antigravity_request),
+            )
+            .await
             {
                 Ok(resp) => {
                     state.logs.write().await.add(
@@ -1674,7 +2505,13 @@ async fn gemini_generate_content(
 
             // 构建 Gemini CLI 请求体
             // Gemini CLI 使用 Cloud Code Assist 端点，不做模型名称映射
-            let gemini_request = build_gemini_cli_request(&request, model, &proj_id);
+            let mut gemini_request = build_gemini_cli_request(&request, model, &proj_id);
+            if let Some(enabled) = state.code_execution.enabled_for(model) {
+                lime_providers::converter::code_execution::apply_code_execution_tool(
+                    &mut gemini_request,
+                    enabled,
+                );
+            }
 
             state.logs.write().await.add(
                 "debug",
@@ -1698,7 +2535,12 @@ async fn gemini_generate_content(
             }
 
             // 非流式响应
-            match gemini.call_api("generateContent", &gemini_request).await {
+            match lime_providers::outbound_limit::scope(
+                &cred.uuid,
+                gemini.call_api("generateContent", &gemini_request),
+            )
+            .await
+            {
                 Ok(resp) => {
                     state.logs.write().await.add(
                         "info",
@@ -1835,13 +2677,16 @@ async fn anthropic_messages_with_selector(
     Json(request): Json<AnthropicMessagesRequest>,
 ) -> Response {
     // 使用 Anthropic 格式的认证验证
-    if let Err(e) = handlers::verify_api_key_anthropic(&headers, &state.api_key).await {
+    if let Err(e) = handlers::verify_inbound_api_key_anthropic(&headers, &state).await {
         state.logs.write().await.add(
             "warn",
             &format!("Unauthorized request to /{selector}/v1/messages"),
         );
         return e.into_response();
     }
+    if let Err(e) = handlers::verify_scoped_key_model(&headers, &state, &request.model) {
+        return e.into_response();
+    }
 
     state.logs.write().await.add(
         "info",
@@ -1919,15 +2764,22 @@ async fn chat_completions_with_selector(
     State(state): State<AppState>,
     Path(selector): Path<String>,
     headers: HeaderMap,
-    Json(request): Json<ChatCompletionRequest>,
+    logprobs: Option<axum::Extension<lime_core::models::LogprobsOptions>>,
+    seed: Option<axum::Extension<lime_core::models::SeedOptions>>,
+    Json(mut request): Json<ChatCompletionRequest>,
 ) -> Response {
-    if let Err(e) = handlers::verify_api_key(&headers, &state.api_key).await {
+    let logprobs = logprobs.map(|axum::Extension(options)| options);
+    let seed = seed.map(|axum::Extension(options)| options);
+    if let Err(e) = handlers::verify_inbound_api_key(&headers, &state).await {
         state.logs.write().await.add(
             "warn",
             &format!("Unauthorized request to /{selector}/v1/chat/completions"),
         );
         return e.into_response();
     }
+    if let Err(e) = handlers::verify_scoped_key_model(&headers, &state, &request.model) {
+        return e.into_response();
+    }
 
     state.logs.write().await.add(
         "info",
@@ -1969,8 +2821,39 @@ async fn chat_completions_with_selector(
                 ),
             );
 
+            if let Some(response) =
+                middleware::logprobs::reject_unsupported(logprobs.as_ref(), &cred, None)
+            {
+                return response;
+            }
+            let mut extra_params = logprobs
+                .map(|options| options.to_params())
+                .unwrap_or_default();
+            let seed_forwarded = seed.map(|seed| {
+                let forwarded = middleware::logprobs::credential_supports(&cred.credential);
+                if forwarded {
+                    extra_params.extend(seed.to_params());
+                } else if request.temperature.is_none() {
+                    request.temperature = Some(0.0);
+                }
+                forwarded
+            });
+
             // 注意：这里没有 Flow 捕获，因为是通过 selector 路由的请求
-            handlers::call_provider_openai(&state, &cred, &request, None).await
+            let response =
+                handlers::call_provider_openai(&state, &cred, &request, &extra_params, None).await;
+            let mut response = middleware::seed::attach_system_fingerprint(
+                response,
+                &lime_core::models::seed::system_fingerprint(
+                    &cred.provider_type.to_string(),
+                    &request.model,
+                ),
+            )
+            .await;
+            if let Some(forwarded) = seed_forwarded {
+                middleware::seed::mark_seed(&mut response, forwarded);
+            }
+            response
         }
         None => {
             // 不再回退到默认 provider，直接返回错误
@@ -0,0 +1,18 @@
+//! 匿名使用统计计数
+//!
+//! 启用 `anonymous_stats` 时按端点类别与响应状态累计计数（见 `lime_core::anonymous_stats`），
+//! 不读取请求或响应内容。
+
+use axum::{extract::Request, middleware::Next, response::Response};
+use lime_core::anonymous_stats;
+
+/// 匿名使用统计中间件
+pub async fn anonymous_stats_middleware(request: Request, next: Next) -> Response {
+    if !anonymous_stats::is_enabled() {
+        return next.run(request).await;
+    }
+    let path = request.uri().path().to_string();
+    let response = next.run(request).await;
+    anonymous_stats::record(&path, response.status().as_u16());
+    response
+}
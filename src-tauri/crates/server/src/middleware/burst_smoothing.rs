@@ -0,0 +1,144 @@
+//! 突发流量平滑
+//!
+//! 按客户端维护令牌桶：桶内有令牌时立即放行，令牌耗尽后请求按速率依次排队，
+//! 实际放行时间被分摊到平滑窗口内。排队时间超过窗口的请求直接拒绝，
+//! 由客户端按 `Retry-After` 重试。
+//!
+//! 令牌允许透支：每个排队请求预占一个未来的令牌，等待时长即透支量除以速率，
+//! 因此无需额外的队列结构，排队顺序与到达顺序一致。
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use lime_core::config::BurstSmoothingSettings;
+use parking_lot::Mutex;
+
+/// 客户端数量超过该值时清理已回满的令牌桶
+const CLEANUP_THRESHOLD: usize = 1024;
+
+/// 单个客户端的令牌桶
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    /// 当前令牌数，负数表示已被排队请求预占
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// 排队时间超出平滑窗口
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BurstRejected {
+    pub retry_after: Duration,
+}
+
+/// 按客户端的突发流量平滑器
+pub struct BurstSmoother {
+    rate: f64,
+    burst: f64,
+    max_delay: Duration,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl BurstSmoother {
+    /// 未启用或速率无效时返回 `None`
+    pub fn from_settings(settings: &BurstSmoothingSettings) -> Option<Self> {
+        if !settings.enabled || settings.requests_per_sec <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            rate: settings.requests_per_sec,
+            burst: f64::from(settings.burst.max(1)),
+            max_delay: Duration::from_millis(settings.max_delay_ms),
+            buckets: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// 预占一个令牌，返回需要等待的时长
+    fn reserve(&self, client_id: &str, now: Instant) -> Result<Duration, BurstRejected> {
+        let mut buckets = self.buckets.lock();
+        if buckets.len() > CLEANUP_THRESHOLD {
+            let (rate, burst) = (self.rate, self.burst);
+            buckets.retain(|_, bucket| {
+                let elapsed = now.saturating_duration_since(bucket.updated_at);
+                bucket.tokens + elapsed.as_secs_f64() * rate < burst
+            });
+        }
+
+        let bucket = buckets.entry(client_id.to_string()).or_insert(Bucket {
+            tokens: self.burst,
+            updated_at: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * self.rate).min(self.burst);
+        bucket.updated_at = now;
+
+        let remaining = bucket.tokens - 1.0;
+        if remaining >= 0.0 {
+            bucket.tokens = remaining;
+            return Ok(Duration::ZERO);
+        }
+        let wait = Duration::from_secs_f64(-remaining / self.rate);
+        if wait > self.max_delay {
+            return Err(BurstRejected {
+                retry_after: wait
+                    .saturating_sub(self.max_delay)
+                    .max(Duration::from_secs(1)),
+            });
+        }
+        bucket.tokens = remaining;
+        Ok(wait)
+    }
+
+    /// 等待轮到该客户端的请求，返回实际排队时长
+    pub async fn acquire(&self, client_id: &str) -> Result<Duration, BurstRejected> {
+        let wait = self.reserve(client_id, Instant::now())?;
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        Ok(wait)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn smoother(rate: f64, burst: u32, max_delay_ms: u64) -> BurstSmoother {
+        BurstSmoother::from_settings(&BurstSmoothingSettings {
+            enabled: true,
+            requests_per_sec: rate,
+            burst,
+            max_delay_ms,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_burst_is_spread_over_window() {
+        assert!(BurstSmoother::from_settings(&BurstSmoothingSettings::default()).is_none());
+
+        let smoother = smoother(2.0, 2, 2_000);
+        let now = Instant::now();
+        let waits: Vec<Duration> = (0..6)
+            .map(|_| smoother.reserve("ide", now).unwrap_or(Duration::MAX))
+            .collect();
+        assert_eq!(
+            waits,
+            vec![
+                Duration::ZERO,
+                Duration::ZERO,
+                Duration::from_millis(500),
+                Duration::from_millis(1_000),
+                Duration::from_millis(1_500),
+                Duration::from_millis(2_000),
+            ]
+        );
+        // 超出窗口的请求被拒绝，且不占用令牌
+        assert!(smoother.reserve("ide", now).is_err());
+        assert_eq!(
+            smoother.reserve("ide", now + Duration::from_millis(500)),
+            Ok(Duration::from_millis(2_000))
+        );
+        // 不同客户端互不影响
+        assert_eq!(smoother.reserve("cli", now), Ok(Duration::ZERO));
+    }
+}
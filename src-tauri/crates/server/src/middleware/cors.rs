@@ -0,0 +1,207 @@
+//! CORS 策略
+//!
+//! 在内置桌面端来源之外，按配置放行局域网 Web UI 来源：
+//! - 每个来源可单独设置预检缓存时长（`Access-Control-Max-Age`）
+//! - 每个来源可单独允许私有网络访问（`Access-Control-Request-Private-Network` 预检
+//!   返回 `Access-Control-Allow-Private-Network: true`）
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::http::{header, request::Parts, HeaderName, HeaderValue, Method};
+use lime_core::config::{CorsOriginRule, CorsSettings};
+use tower_http::cors::{AllowOrigin, AllowPrivateNetwork, CorsLayer, MaxAge};
+
+/// 内置允许的来源（Tauri 与本地开发服务器）
+const BUILTIN_ORIGINS: &[&str] = &[
+    "http://localhost:1420",
+    "http://127.0.0.1:1420",
+    "http://localhost:5173",
+    "http://127.0.0.1:5173",
+    "tauri://localhost",
+    "http://tauri.localhost",
+    "https://tauri.localhost",
+];
+
+/// 已解析的 CORS 策略
+#[derive(Debug, Clone)]
+pub struct CorsPolicy {
+    rules: Vec<CorsOriginRule>,
+    default_max_age: Duration,
+}
+
+/// 简单通配匹配（`*` 匹配任意字符序列）
+pub(crate) fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern.eq_ignore_ascii_case(value);
+    }
+
+    let pattern_lower = parts
+        .iter()
+        .map(|part| part.to_ascii_lowercase())
+        .collect::<Vec<_>>();
+    let value = value.to_ascii_lowercase();
+    let first = &pattern_lower[0];
+    let last = &pattern_lower[pattern_lower.len() - 1];
+    if !value.starts_with(first.as_str()) || value.len() < first.len() + last.len() {
+        return false;
+    }
+
+    let mut rest = &value[first.len()..];
+    for middle in &pattern_lower[1..pattern_lower.len() - 1] {
+        match rest.find(middle.as_str()) {
+            Some(pos) => rest = &rest[pos + middle.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last.as_str())
+}
+
+impl CorsPolicy {
+    pub fn new(settings: &CorsSettings) -> Self {
+        Self {
+            rules: settings.origins.clone(),
+            default_max_age: Duration::from_secs(settings.max_age_secs),
+        }
+    }
+
+    fn matching_rule(&self, origin: &str) -> Option<&CorsOriginRule> {
+        self.rules
+            .iter()
+            .find(|rule| wildcard_match(rule.origin.trim(), origin))
+    }
+
+    pub fn is_origin_allowed(&self, origin: &str) -> bool {
+        BUILTIN_ORIGINS.contains(&origin) || self.matching_rule(origin).is_some()
+    }
+
+    pub fn max_age_for(&self, origin: &str) -> Duration {
+        self.matching_rule(origin)
+            .and_then(|rule| rule.max_age_secs)
+            .map(Duration::from_secs)
+            .unwrap_or(self.default_max_age)
+    }
+
+    pub fn allows_private_network(&self, origin: &str) -> bool {
+        self.matching_rule(origin)
+            .map(|rule| rule.allow_private_network)
+            .unwrap_or(false)
+    }
+}
+
+/// 根据配置构建 CORS 层
+pub fn build_cors_layer(settings: &CorsSettings) -> CorsLayer {
+    let policy = Arc::new(CorsPolicy::new(settings));
+    let origin_policy = policy.clone();
+    let max_age_policy = policy.clone();
+    let pna_policy = policy;
+
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::predicate(
+            move |origin: &HeaderValue, _parts: &Parts| {
+                origin
+                    .to_str()
+                    .map(|origin| origin_policy.is_origin_allowed(origin))
+                    .unwrap_or(false)
+            },
+        ))
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+        ])
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            header::ACCEPT,
+            header::ORIGIN,
+            HeaderName::from_static("x-api-key"),
+            HeaderName::from_static("anthropic-version"),
+            HeaderName::from_static("x-provider-id"),
+            HeaderName::from_static("x-lime-provider"),
+            HeaderName::from_static("x-lime-credential"),
+            HeaderName::from_static("idempotency-key"),
+        ])
+        .max_age(MaxAge::dynamic(
+            move |origin: HeaderValue, _parts: &Parts| {
+                origin
+                    .to_str()
+                    .map(|origin| max_age_policy.max_age_for(origin))
+                    .unwrap_or(max_age_policy.default_max_age)
+            },
+        ))
+        .allow_private_network(AllowPrivateNetwork::predicate(
+            move |origin: &HeaderValue, _parts: &Parts| {
+                origin
+                    .to_str()
+                    .map(|origin| pna_policy.allows_private_network(origin))
+                    .unwrap_or(false)
+            },
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> CorsSettings {
+        CorsSettings {
+            origins: vec![
+                CorsOriginRule {
+                    origin: "http://192.168.*:3000".to_string(),
+                    allow_private_network: true,
+                    max_age_secs: Some(86400),
+                },
+                CorsOriginRule {
+                    origin: "https://chat.example.com".to_string(),
+                    allow_private_network: false,
+                    max_age_secs: None,
+                },
+            ],
+            max_age_secs: 600,
+        }
+    }
+
+    #[test]
+    fn should_match_wildcard_origins() {
+        assert!(wildcard_match(
+            "http://192.168.*:3000",
+            "http://192.168.1.20:3000"
+        ));
+        assert!(!wildcard_match(
+            "http://192.168.*:3000",
+            "http://192.168.1.20:8080"
+        ));
+        assert!(wildcard_match("*", "http://anything"));
+        assert!(wildcard_match(
+            "HTTPS://Chat.example.com",
+            "https://chat.example.com"
+        ));
+    }
+
+    #[test]
+    fn should_resolve_policy_per_origin() {
+        let policy = CorsPolicy::new(&settings());
+
+        assert!(policy.is_origin_allowed("tauri://localhost"));
+        assert!(policy.is_origin_allowed("http://192.168.0.8:3000"));
+        assert!(!policy.is_origin_allowed("http://evil.example.com"));
+
+        assert_eq!(
+            policy.max_age_for("http://192.168.0.8:3000"),
+            Duration::from_secs(86400)
+        );
+        assert_eq!(
+            policy.max_age_for("https://chat.example.com"),
+            Duration::from_secs(600)
+        );
+
+        assert!(policy.allows_private_network("http://192.168.0.8:3000"));
+        assert!(!policy.allows_private_network("https://chat.example.com"));
+        assert!(!policy.allows_private_network("tauri://localhost"));
+    }
+}
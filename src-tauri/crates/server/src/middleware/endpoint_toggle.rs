@@ -0,0 +1,136 @@
+//! 端点开关
+//!
+//! 按 `server.endpoints` 关闭整组端点。关闭的端点在认证之前直接返回空 404，
+//! 与不存在的路由无法区分，避免暴露功能面。
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use lime_core::config::EndpointToggleSettings;
+
+use super::cors::wildcard_match;
+use crate::AppState;
+
+/// Ollama 兼容接口路径（需先于管理接口的 `/api/*` 判断）
+const OLLAMA_PATHS: &[&str] = &[
+    "/api/chat",
+    "/api/generate",
+    "/api/tags",
+    "/api/show",
+    "/api/ps",
+    "/api/embed",
+    "/api/embeddings",
+    "/api/version",
+];
+
+const IMAGE_PATHS: &[&str] = &["/v1/images/*", "/v1/media/*"];
+const AUDIO_PATHS: &[&str] = &["/v1/audio/*"];
+const EMBEDDING_PATHS: &[&str] = &["/v1/embeddings", "/v1/rag/*", "/v1/rerank"];
+const ADMIN_PATHS: &[&str] = &[
+    "/admin/*",
+    "/api/*",
+    "/v1/credentials/*",
+    "/v1/keys*",
+    "/v1/signing/*",
+];
+
+/// 端点分组
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointGroup {
+    Images,
+    Audio,
+    Embeddings,
+    Admin,
+    Ollama,
+}
+
+fn matches_any(patterns: &[&str], path: &str) -> bool {
+    patterns.iter().any(|pattern| wildcard_match(pattern, path))
+}
+
+/// 路径所属的端点分组，不受开关控制的路径返回 `None`
+pub fn endpoint_group(path: &str) -> Option<EndpointGroup> {
+    if OLLAMA_PATHS.contains(&path) {
+        Some(EndpointGroup::Ollama)
+    } else if matches_any(IMAGE_PATHS, path) {
+        Some(EndpointGroup::Images)
+    } else if matches_any(AUDIO_PATHS, path) {
+        Some(EndpointGroup::Audio)
+    } else if matches_any(EMBEDDING_PATHS, path) {
+        Some(EndpointGroup::Embeddings)
+    } else if matches_any(ADMIN_PATHS, path) {
+        Some(EndpointGroup::Admin)
+    } else {
+        None
+    }
+}
+
+/// 路径是否启用
+pub fn is_enabled(settings: &EndpointToggleSettings, path: &str) -> bool {
+    match endpoint_group(path) {
+        Some(EndpointGroup::Images) => settings.images,
+        Some(EndpointGroup::Audio) => settings.audio,
+        Some(EndpointGroup::Embeddings) => settings.embeddings,
+        Some(EndpointGroup::Admin) => settings.admin,
+        Some(EndpointGroup::Ollama) => settings.ollama,
+        None => true,
+    }
+}
+
+/// 端点开关中间件
+pub async fn endpoint_toggle_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !is_enabled(&state.endpoints, request.uri().path()) {
+        tracing::debug!("[ENDPOINTS] 端点已关闭: {}", request.uri().path());
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_groups_are_blocked() {
+        let settings = EndpointToggleSettings {
+            images: false,
+            admin: false,
+            ..Default::default()
+        };
+
+        assert!(!is_enabled(&settings, "/v1/images/generations"));
+        assert!(!is_enabled(&settings, "/v1/media/abc.png"));
+        assert!(!is_enabled(&settings, "/api/kiro/credentials/available"));
+        assert!(!is_enabled(&settings, "/v1/keys/123"));
+        // Ollama 接口独立于管理接口
+        assert!(is_enabled(&settings, "/api/chat"));
+        assert!(is_enabled(&settings, "/v1/embeddings"));
+        assert!(is_enabled(&settings, "/v1/chat/completions"));
+        assert!(is_enabled(&settings, "/health"));
+    }
+
+    #[test]
+    fn test_endpoint_group() {
+        assert_eq!(endpoint_group("/api/tags"), Some(EndpointGroup::Ollama));
+        assert_eq!(
+            endpoint_group("/v1/audio/speech"),
+            Some(EndpointGroup::Audio)
+        );
+        assert_eq!(
+            endpoint_group("/v1/rag/query"),
+            Some(EndpointGroup::Embeddings)
+        );
+        assert_eq!(
+            endpoint_group("/v1/credentials/select"),
+            Some(EndpointGroup::Admin)
+        );
+        assert_eq!(endpoint_group("/v1/messages"), None);
+    }
+}
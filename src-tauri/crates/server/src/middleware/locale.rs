@@ -0,0 +1,20 @@
+//! 按请求协商后端文案语言
+//!
+//! 请求携带 `Accept-Language` 且包含受支持的语言时，在该语言的作用域内处理请求，
+//! 错误体等后端文案随之本地化；否则使用全局语言（`server.locale` 或界面语言）。
+
+use axum::{extract::Request, http::header, middleware::Next, response::Response};
+use lime_core::i18n::{self, Locale};
+
+/// 语言协商中间件
+pub async fn locale_middleware(request: Request, next: Next) -> Response {
+    let locale = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(Locale::from_accept_language);
+    match locale {
+        Some(locale) => i18n::scope(locale, next.run(request)).await,
+        None => next.run(request).await,
+    }
+}
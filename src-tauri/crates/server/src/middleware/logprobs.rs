@@ -0,0 +1,116 @@
+//! `logprobs` / `top_logprobs` 请求参数
+//!
+//! 类型化的 Chat Completions 请求不包含这两个字段，中间件从原始请求体中解析出
+//! [`LogprobsOptions`] 放入请求扩展，处理器选定凭证后：
+//! - OpenAI 兼容接口（OpenAI Key、Vertex、自定义地址的 Anthropic Key）原样转发，
+//!   流式响应中的逐 Token logprobs 随上游 SSE 一起透传
+//! - 其他 Provider 返回 400，而不是静默丢弃参数
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use lime_core::errors::GatewayErrorCode;
+use lime_core::models::provider_pool_model::{CredentialData, ProviderCredential};
+use lime_core::models::LogprobsOptions;
+use lime_server_utils::build_error_response_with_meta;
+use serde_json::Value;
+
+/// 读取请求体的上限（与服务器请求体上限一致）
+const MAX_REQUEST_BYTES: usize = 100 * 1024 * 1024;
+
+fn contains_logprobs(bytes: &[u8]) -> bool {
+    bytes.windows(b"logprobs".len()).any(|w| w == b"logprobs")
+}
+
+/// 解析 Chat Completions 请求中的 logprobs 参数
+pub async fn logprobs_middleware(request: Request, next: Next) -> Response {
+    if request.method() != Method::POST || !request.uri().path().ends_with("/chat/completions") {
+        return next.run(request).await;
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, MAX_REQUEST_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+    // 绝大多数请求不带 logprobs，先做字节匹配避免重复解析
+    if contains_logprobs(&bytes) {
+        if let Ok(value) = serde_json::from_slice::<Value>(&bytes) {
+            match LogprobsOptions::from_body(&value) {
+                Ok(Some(options)) => {
+                    parts.extensions.insert(options);
+                }
+                Ok(None) => {}
+                Err(message) => {
+                    return build_error_response_with_meta(
+                        StatusCode::BAD_REQUEST.as_u16(),
+                        &message,
+                        None,
+                        None,
+                        Some(GatewayErrorCode::InvalidRequest),
+                    );
+                }
+            }
+        }
+    }
+    next.run(Request::from_parts(parts, Body::from(bytes)))
+        .await
+}
+
+/// 凭证对应的上游接口是否支持 logprobs
+pub fn credential_supports(credential: &CredentialData) -> bool {
+    match credential {
+        CredentialData::OpenAIKey { .. } | CredentialData::VertexKey { .. } => true,
+        // 自定义地址按 OpenAI 兼容接口调用
+        CredentialData::AnthropicKey { base_url, .. } => base_url.is_some(),
+        _ => false,
+    }
+}
+
+/// 请求了 logprobs 但选中的凭证不支持时返回错误响应
+pub fn reject_unsupported(
+    options: Option<&LogprobsOptions>,
+    credential: &ProviderCredential,
+    request_id: Option<&str>,
+) -> Option<Response> {
+    if options.is_none() || credential_supports(&credential.credential) {
+        return None;
+    }
+    Some(build_error_response_with_meta(
+        StatusCode::BAD_REQUEST.as_u16(),
+        &format!(
+            "logprobs is not supported by provider '{}'. Route the request to an OpenAI-compatible provider or remove `logprobs` / `top_logprobs`",
+            credential.provider_type
+        ),
+        request_id,
+        None,
+        Some(GatewayErrorCode::InvalidRequest),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credential_supports() {
+        assert!(credential_supports(&CredentialData::OpenAIKey {
+            api_key: "sk".to_string(),
+            base_url: None,
+        }));
+        assert!(!credential_supports(&CredentialData::AnthropicKey {
+            api_key: "sk".to_string(),
+            base_url: None,
+        }));
+        assert!(credential_supports(&CredentialData::AnthropicKey {
+            api_key: "sk".to_string(),
+            base_url: Some("https://api.example.com/v1".to_string()),
+        }));
+        assert!(contains_logprobs(br#"{"logprobs":true}"#));
+        assert!(!contains_logprobs(br#"{"model":"gpt-4o"}"#));
+    }
+}
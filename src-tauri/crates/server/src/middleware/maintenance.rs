@@ -0,0 +1,191 @@
+//! 维护模式
+//!
+//! 开启后推理端点（对话、消息、向量、图像、WebSocket 等）统一返回 503，
+//! 管理接口、健康检查与诊断接口照常可用。状态可在运行时通过 Tauri 命令或
+//! `PUT /admin/maintenance` 切换，无需重启服务。
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use lime_core::config::MaintenanceModeSettings;
+use parking_lot::RwLock;
+use serde::Serialize;
+
+use crate::AppState;
+
+/// 推理端点路径后缀（同时覆盖 `/{selector}/v1/...` 多供应商路由）
+const INFERENCE_SUFFIXES: &[&str] = &[
+    "/v1/chat/completions",
+    "/v1/completions",
+    "/v1/messages",
+    "/v1/messages/count_tokens",
+    "/v1/responses",
+];
+
+/// 推理端点路径前缀
+const INFERENCE_PREFIXES: &[&str] = &[
+    "/v1/embeddings",
+    "/v1/images/",
+    "/v1/audio/",
+    "/v1/rag/",
+    "/v1/rerank",
+    "/v1beta/",
+    "/api/chat",
+    "/api/generate",
+    "/api/embed",
+];
+
+/// WebSocket 入口
+const INFERENCE_EXACT: &[&str] = &["/v1/ws", "/ws"];
+
+/// 路径是否为推理端点
+pub fn is_inference_path(path: &str) -> bool {
+    INFERENCE_EXACT.contains(&path)
+        || INFERENCE_SUFFIXES
+            .iter()
+            .any(|suffix| path.ends_with(suffix))
+        || INFERENCE_PREFIXES
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+}
+
+/// 维护模式状态
+#[derive(Debug, Clone, Serialize)]
+pub struct MaintenanceStatus {
+    pub enabled: bool,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+    /// 本次进入维护模式的时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<DateTime<Utc>>,
+}
+
+struct Inner {
+    settings: MaintenanceModeSettings,
+    since: Option<DateTime<Utc>>,
+}
+
+/// 运行时维护模式开关
+pub struct MaintenanceMode {
+    inner: RwLock<Inner>,
+}
+
+impl MaintenanceMode {
+    pub fn new(settings: MaintenanceModeSettings) -> Self {
+        let since = settings.enabled.then(Utc::now);
+        Self {
+            inner: RwLock::new(Inner { settings, since }),
+        }
+    }
+
+    /// 应用新的设置，已处于维护模式时保留进入时间
+    pub fn apply_settings(&self, settings: MaintenanceModeSettings) {
+        let mut inner = self.inner.write();
+        inner.since = match (settings.enabled, inner.since) {
+            (true, Some(since)) => Some(since),
+            (true, None) => Some(Utc::now()),
+            (false, _) => None,
+        };
+        inner.settings = settings;
+    }
+
+    /// 切换维护模式，`message` 为空时沿用当前提示
+    pub fn set(&self, enabled: bool, message: Option<&str>) -> MaintenanceStatus {
+        let mut settings = self.inner.read().settings.clone();
+        settings.enabled = enabled;
+        if let Some(message) = message.map(str::trim).filter(|m| !m.is_empty()) {
+            settings.message = message.to_string();
+        }
+        self.apply_settings(settings);
+        self.status()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.inner.read().settings.enabled
+    }
+
+    pub fn status(&self) -> MaintenanceStatus {
+        let inner = self.inner.read();
+        MaintenanceStatus {
+            enabled: inner.settings.enabled,
+            message: inner.settings.message.clone(),
+            retry_after_secs: inner.settings.retry_after_secs,
+            since: inner.since,
+        }
+    }
+
+    /// 维护模式下返回给推理请求的 503 响应
+    pub fn unavailable_response(&self) -> Response {
+        let status = self.status();
+        let mut response = (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({
+                "error": {
+                    "message": status.message,
+                    "type": "service_unavailable",
+                    "code": "maintenance"
+                }
+            })),
+        )
+            .into_response();
+        if let Some(secs) = status.retry_after_secs {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response
+    }
+}
+
+/// 维护模式中间件
+pub async fn maintenance_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if state.maintenance.is_enabled() && is_inference_path(request.uri().path()) {
+        return state.maintenance.unavailable_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inference_paths() {
+        assert!(is_inference_path("/v1/chat/completions"));
+        assert!(is_inference_path("/kiro/v1/messages"));
+        assert!(is_inference_path("/v1/images/generations"));
+        assert!(is_inference_path("/ws"));
+        assert!(!is_inference_path("/health"));
+        assert!(!is_inference_path("/v1/models"));
+        assert!(!is_inference_path("/admin/maintenance"));
+        assert!(!is_inference_path("/api/kiro/credentials/available"));
+    }
+
+    #[test]
+    fn test_set_keeps_message_when_empty() {
+        let mode = MaintenanceMode::new(MaintenanceModeSettings::default());
+        let status = mode.set(true, Some("migrating credentials"));
+        assert!(status.enabled && status.since.is_some());
+        assert_eq!(status.message, "migrating credentials");
+
+        let status = mode.set(true, Some("  "));
+        assert_eq!(status.message, "migrating credentials");
+
+        let status = mode.set(false, None);
+        assert!(!status.enabled && status.since.is_none());
+        assert_eq!(
+            mode.unavailable_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+}
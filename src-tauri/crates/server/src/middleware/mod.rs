@@ -1,7 +1,26 @@
 //! 服务器中间件模块
 
+pub mod anonymous_stats;
+pub mod burst_smoothing;
 pub mod capability_routing_metrics;
+pub mod cors;
+pub mod endpoint_toggle;
 pub mod idempotency;
+pub mod locale;
+pub mod logprobs;
+pub mod maintenance;
+pub mod model_concurrency;
+pub mod outbound_limit;
+pub mod priority_lanes;
+pub mod prompt_firewall;
 pub mod rate_limit;
 pub mod request_dedup;
+pub mod request_journal;
+pub mod request_signing;
 pub mod response_cache;
+pub mod seed;
+pub mod shared_counter;
+pub mod sse_heartbeat;
+pub mod stream_output_cap;
+pub mod transcript_capture;
+pub mod upstream_headers;
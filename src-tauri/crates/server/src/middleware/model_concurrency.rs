@@ -0,0 +1,417 @@
+//! 模型级并发与排队
+//!
+//! 凭证级限额之外，按 `server.model_concurrency.rules` 为每个模型（别名解析后）
+//! 限制同时转发的请求数。超出上限的请求排队等待；队列已满或排队超时时，
+//! 规则配置了 `fallback` 则改写请求体中的模型改用备用模型，否则返回 429。
+//! 名额在响应结束（流式响应读完或客户端断开）时归还。
+//!
+//! 只处理已认证的请求（带 [`Caller`] 扩展），未认证的请求不占名额、不排队也不改写模型。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures::StreamExt;
+use lime_core::config::{ModelConcurrencyRule, ModelConcurrencySettings};
+use lime_core::errors::GatewayErrorCode;
+use lime_server_utils::build_error_response_with_meta;
+use parking_lot::Mutex;
+use serde_json::Value;
+use tokio::sync::Notify;
+
+use super::maintenance::is_inference_path;
+use crate::auth::caller::Caller;
+use crate::AppState;
+
+/// 请求体大小上限
+const MAX_REQUEST_BYTES: usize = 100 * 1024 * 1024;
+
+/// 改用备用模型时的响应头：原模型
+pub const FALLBACK_FROM_HEADER: &str = "x-lime-model-fallback-from";
+/// 改用备用模型时的响应头：备用模型
+pub const FALLBACK_TO_HEADER: &str = "x-lime-model-fallback-to";
+
+/// 模型饱和的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Saturation {
+    /// 排队请求数已达上限
+    QueueFull,
+    /// 排队超时
+    Timeout,
+}
+
+impl Saturation {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::QueueFull => "队列已满",
+            Self::Timeout => "排队超时",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct GateCounters {
+    running: u32,
+    waiting: u32,
+}
+
+/// 单个模型的并发闸门
+struct ModelGate {
+    max_concurrent: u32,
+    queue_length: u32,
+    max_wait: Duration,
+    counters: Mutex<GateCounters>,
+    notify: Notify,
+}
+
+/// 模型并发名额，drop 时归还并唤醒排队请求
+pub struct ModelPermit {
+    gate: Arc<ModelGate>,
+}
+
+impl Drop for ModelPermit {
+    fn drop(&mut self) {
+        let mut counters = self.gate.counters.lock();
+        counters.running = counters.running.saturating_sub(1);
+        drop(counters);
+        self.gate.notify.notify_waiters();
+    }
+}
+
+/// 排队登记，放弃排队（超时或客户端断开）时撤销
+struct Waiting<'a> {
+    gate: &'a ModelGate,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        let mut counters = self.gate.counters.lock();
+        counters.waiting = counters.waiting.saturating_sub(1);
+    }
+}
+
+impl ModelGate {
+    fn new(rule: &ModelConcurrencyRule) -> Self {
+        Self {
+            max_concurrent: rule.max_concurrent,
+            queue_length: rule.queue_length,
+            max_wait: Duration::from_millis(rule.max_wait_ms),
+            counters: Mutex::new(GateCounters::default()),
+            notify: Notify::new(),
+        }
+    }
+
+    fn admit(self: &Arc<Self>, counters: &mut GateCounters) -> Option<ModelPermit> {
+        if counters.running >= self.max_concurrent {
+            return None;
+        }
+        counters.running += 1;
+        Some(ModelPermit { gate: self.clone() })
+    }
+
+    async fn acquire(self: &Arc<Self>) -> Result<ModelPermit, Saturation> {
+        let _waiting = {
+            let mut counters = self.counters.lock();
+            if let Some(permit) = self.admit(&mut counters) {
+                return Ok(permit);
+            }
+            if counters.waiting >= self.queue_length {
+                return Err(Saturation::QueueFull);
+            }
+            counters.waiting += 1;
+            Waiting { gate: self }
+        };
+
+        let deadline = tokio::time::Instant::now() + self.max_wait;
+        loop {
+            // 先登记唤醒再检查名额，避免检查后、等待前的释放被错过
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let admitted = self.admit(&mut self.counters.lock());
+            if let Some(permit) = admitted {
+                return Ok(permit);
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return Err(Saturation::Timeout);
+            }
+        }
+    }
+}
+
+/// 模型并发调度器
+pub struct ModelConcurrency {
+    settings: ModelConcurrencySettings,
+    gates: Mutex<HashMap<String, Arc<ModelGate>>>,
+}
+
+impl ModelConcurrency {
+    /// 未启用或没有有效规则时返回 `None`
+    pub fn from_settings(settings: &ModelConcurrencySettings) -> Option<Self> {
+        if !settings.enabled || !settings.rules.iter().any(|rule| rule.max_concurrent > 0) {
+            return None;
+        }
+        Some(Self {
+            settings: settings.clone(),
+            gates: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// 模型适用的规则
+    pub fn rule_for(&self, model: &str) -> Option<&ModelConcurrencyRule> {
+        self.settings.rule_for(model)
+    }
+
+    fn gate_for(&self, model: &str, rule: &ModelConcurrencyRule) -> Arc<ModelGate> {
+        self.gates
+            .lock()
+            .entry(model.to_string())
+            .or_insert_with(|| Arc::new(ModelGate::new(rule)))
+            .clone()
+    }
+
+    /// 获取模型的并发名额；模型没有匹配规则时返回 `Ok(None)`
+    pub async fn acquire(&self, model: &str) -> Result<Option<ModelPermit>, Saturation> {
+        let Some(rule) = self.rule_for(model) else {
+            return Ok(None);
+        };
+        self.gate_for(model, rule).acquire().await.map(Some)
+    }
+
+    /// 模型当前的在途与排队请求数
+    pub fn load(&self, model: &str) -> (u32, u32) {
+        self.gates
+            .lock()
+            .get(model)
+            .map(|gate| {
+                let counters = gate.counters.lock();
+                (counters.running, counters.waiting)
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn saturated_response(model: &str, saturation: Saturation) -> Response {
+    let response = build_error_response_with_meta(
+        StatusCode::TOO_MANY_REQUESTS.as_u16(),
+        &format!(
+            "Model {model} is at capacity ({}). Retry later",
+            match saturation {
+                Saturation::QueueFull => "queue full",
+                Saturation::Timeout => "queue timeout",
+            }
+        ),
+        None,
+        None,
+        Some(GatewayErrorCode::RateLimited),
+    );
+    let (mut parts, body) = response.into_parts();
+    parts
+        .headers
+        .insert(header::RETRY_AFTER, HeaderValue::from(1u64));
+    Response::from_parts(parts, body)
+}
+
+/// 模型级并发中间件
+pub async fn model_concurrency_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(scheduler) = state.model_concurrency.clone() else {
+        return next.run(request).await;
+    };
+    if request.method() != Method::POST
+        || !is_inference_path(request.uri().path())
+        || request.extensions().get::<Caller>().is_none()
+    {
+        return next.run(request).await;
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, MAX_REQUEST_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+    let mut payload = serde_json::from_slice::<Value>(&bytes).ok();
+    let requested = payload
+        .as_ref()
+        .and_then(|value| value.get("model"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let Some(requested) = requested else {
+        return next
+            .run(Request::from_parts(parts, Body::from(bytes)))
+            .await;
+    };
+    let model = state.processor.resolve_model(&requested).await;
+
+    let mut fallback_to = None;
+    let permit = match scheduler.acquire(&model).await {
+        Ok(permit) => permit,
+        Err(saturation) => {
+            let fallback = scheduler
+                .rule_for(&model)
+                .and_then(|rule| rule.fallback.clone())
+                .filter(|fallback| !fallback.trim().is_empty() && *fallback != model);
+            let Some(fallback) = fallback else {
+                tracing::warn!(
+                    "[MODEL_QUEUE] 模型 {} {}，返回 429",
+                    model,
+                    saturation.as_str()
+                );
+                return saturated_response(&model, saturation);
+            };
+            // 备用模型只尝试一次，不再继续回退
+            let fallback_model = state.processor.resolve_model(&fallback).await;
+            match scheduler.acquire(&fallback_model).await {
+                Ok(permit) => {
+                    tracing::info!(
+                        "[MODEL_QUEUE] 模型 {} {}，改用 {}",
+                        model,
+                        saturation.as_str(),
+                        fallback
+                    );
+                    fallback_to = Some(fallback);
+                    permit
+                }
+                Err(fallback_saturation) => {
+                    tracing::warn!(
+                        "[MODEL_QUEUE] 模型 {} {}，备用模型 {} 也{}，返回 429",
+                        model,
+                        saturation.as_str(),
+                        fallback,
+                        fallback_saturation.as_str()
+                    );
+                    return saturated_response(&model, saturation);
+                }
+            }
+        }
+    };
+
+    let body = match (&fallback_to, payload.as_mut()) {
+        (Some(fallback), Some(Value::Object(map))) => {
+            map.insert("model".to_string(), Value::String(fallback.clone()));
+            Body::from(serde_json::to_vec(&payload).unwrap_or_else(|_| bytes.to_vec()))
+        }
+        _ => Body::from(bytes),
+    };
+    let mut response = next.run(Request::from_parts(parts, body)).await;
+
+    if let Some(fallback) = &fallback_to {
+        let headers = response.headers_mut();
+        if let Ok(value) = HeaderValue::from_str(&requested) {
+            headers.insert(FALLBACK_FROM_HEADER, value);
+        }
+        if let Ok(value) = HeaderValue::from_str(fallback) {
+            headers.insert(FALLBACK_TO_HEADER, value);
+        }
+    }
+
+    let Some(permit) = permit else {
+        return response;
+    };
+    let is_event_stream = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    if !is_event_stream {
+        return response;
+    }
+
+    // 流式响应读完（或客户端断开）后才归还名额
+    let (parts, body) = response.into_parts();
+    let stream = body.into_data_stream().map(move |chunk| {
+        let _permit = &permit;
+        chunk
+    });
+    Response::from_parts(parts, Body::from_stream(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler(queue_length: u32, max_wait_ms: u64) -> ModelConcurrency {
+        ModelConcurrency::from_settings(&ModelConcurrencySettings {
+            enabled: true,
+            rules: vec![ModelConcurrencyRule {
+                pattern: "claude-opus-*".to_string(),
+                max_concurrent: 1,
+                queue_length,
+                max_wait_ms,
+                fallback: Some("claude-sonnet-4".to_string()),
+            }],
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_per_model_ceiling_and_queue_full() {
+        assert!(ModelConcurrency::from_settings(&ModelConcurrencySettings::default()).is_none());
+
+        let scheduler = scheduler(0, 50);
+        // 未匹配规则的模型不受限制
+        assert!(scheduler.acquire("gpt-4o").await.unwrap().is_none());
+
+        let held = scheduler.acquire("claude-opus-4").await.unwrap();
+        assert!(held.is_some());
+        // 不同模型各自计数
+        assert!(scheduler
+            .acquire("claude-opus-4-1")
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            scheduler.acquire("claude-opus-4").await.err(),
+            Some(Saturation::QueueFull)
+        );
+
+        drop(held);
+        assert_eq!(scheduler.load("claude-opus-4"), (0, 0));
+        assert!(scheduler.acquire("claude-opus-4").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_queued_request_admitted_or_times_out() {
+        let scheduler = Arc::new(scheduler(1, 200));
+        let held = scheduler.acquire("claude-opus-4").await.unwrap();
+
+        let waiter = {
+            let scheduler = scheduler.clone();
+            tokio::spawn(async move {
+                scheduler
+                    .acquire("claude-opus-4")
+                    .await
+                    .map(|p| p.is_some())
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(scheduler.load("claude-opus-4"), (1, 1));
+        // 队列已满
+        assert_eq!(
+            scheduler.acquire("claude-opus-4").await.err(),
+            Some(Saturation::QueueFull)
+        );
+
+        drop(held);
+        assert_eq!(waiter.await.unwrap(), Ok(true));
+        assert_eq!(scheduler.load("claude-opus-4"), (0, 0));
+
+        // 排队超时
+        let _held = scheduler.acquire("claude-opus-4").await.unwrap();
+        assert_eq!(
+            scheduler.acquire("claude-opus-4").await.err(),
+            Some(Saturation::Timeout)
+        );
+    }
+}
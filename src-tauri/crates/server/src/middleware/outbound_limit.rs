@@ -0,0 +1,126 @@
+//! 出站限额
+//!
+//! 按凭证限制发往上游的请求速率（RPS）与每分钟 Token 消耗（TPM），避免多实例共用
+//! 同一凭证时叠加超出上游配额。计数存储与入站限流共用 [`CounterStore`]。
+//! 检查注册到 [`lime_providers::outbound_limit`]，在共享发送路径上对每个上游请求生效。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use lime_core::config::DistributedRateLimitSettings;
+use lime_providers::outbound_limit::OutboundCheck;
+pub use lime_providers::outbound_limit::OutboundLimited;
+
+use super::shared_counter::{current_window, CounterStore};
+
+const SECOND: Duration = Duration::from_secs(1);
+const MINUTE: Duration = Duration::from_secs(60);
+
+/// 按凭证的出站限额
+pub struct OutboundLimiter {
+    rps: u32,
+    tpm: u64,
+    store: Arc<CounterStore>,
+}
+
+impl OutboundLimiter {
+    /// 未配置任何出站限额时返回 `None`
+    pub fn from_settings(
+        settings: &DistributedRateLimitSettings,
+        store: Arc<CounterStore>,
+    ) -> Option<Self> {
+        if settings.outbound_rps == 0 && settings.outbound_tpm == 0 {
+            return None;
+        }
+        Some(Self {
+            rps: settings.outbound_rps,
+            tpm: settings.outbound_tpm,
+            store,
+        })
+    }
+
+    /// 发送请求前检查并占用一次请求额度
+    pub async fn acquire(&self, credential_id: &str) -> Result<(), OutboundLimited> {
+        if self.tpm > 0 {
+            let (index, retry_after) = current_window(MINUTE);
+            let used = self
+                .store
+                .get(&format!("out:tpm:{credential_id}:{index}"))
+                .await;
+            if used >= self.tpm {
+                return Err(OutboundLimited {
+                    limit: "tpm",
+                    retry_after,
+                });
+            }
+        }
+        if self.rps > 0 {
+            let (index, retry_after) = current_window(SECOND);
+            let count = self
+                .store
+                .incr(&format!("out:rps:{credential_id}:{index}"), 1, SECOND * 2)
+                .await;
+            if count > u64::from(self.rps) {
+                return Err(OutboundLimited {
+                    limit: "rps",
+                    retry_after,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// 转换为发送路径上的限额检查
+    pub fn into_check(self: Arc<Self>) -> OutboundCheck {
+        Arc::new(
+            move |credential_id: String| -> BoxFuture<'static, Result<(), OutboundLimited>> {
+                let limiter = self.clone();
+                Box::pin(async move { limiter.acquire(&credential_id).await })
+            },
+        )
+    }
+
+    /// 请求完成后记录消耗的 Token
+    pub async fn record_tokens(&self, credential_id: &str, tokens: u64) {
+        if self.tpm == 0 || tokens == 0 {
+            return;
+        }
+        let (index, _) = current_window(MINUTE);
+        self.store
+            .incr(
+                &format!("out:tpm:{credential_id}:{index}"),
+                tokens,
+                MINUTE * 2,
+            )
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(rps: u32, tpm: u64) -> Option<OutboundLimiter> {
+        OutboundLimiter::from_settings(
+            &DistributedRateLimitSettings {
+                outbound_rps: rps,
+                outbound_tpm: tpm,
+                ..Default::default()
+            },
+            Arc::new(CounterStore::local("test")),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_outbound_limits_per_credential() {
+        assert!(limiter(0, 0).is_none());
+
+        let limiter = limiter(0, 100).unwrap();
+        assert!(limiter.acquire("cred-a").await.is_ok());
+        limiter.record_tokens("cred-a", 120).await;
+        let limited = limiter.acquire("cred-a").await.unwrap_err();
+        assert_eq!(limited.limit, "tpm");
+        assert!(limiter.acquire("cred-b").await.is_ok());
+    }
+}
@@ -0,0 +1,394 @@
+//! 请求优先级通道
+//!
+//! 推理请求分为交互与后台两个通道，共享 `max_concurrent` 个并发名额：
+//! - 交互请求只要有空闲名额就立即放行
+//! - 后台请求最多占用 `background_max_concurrent` 个名额，且有交互请求排队时不获得新名额
+//!
+//! 批处理任务运行时 IDE 补全等交互请求因此无需排在后台请求之后。
+//! 名额在响应结束（流式响应读完或客户端断开）时归还。
+//!
+//! 只调度已认证的请求（带 [`Caller`] 扩展），未认证的请求不占名额。受限 Key 设置的通道
+//! 优先于请求头；请求头可以把请求降到后台通道，但只有主 Key 能通过请求头进入交互通道。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use futures::StreamExt;
+use lime_core::config::{PriorityLane, PriorityLaneSettings};
+use lime_core::errors::GatewayErrorCode;
+use lime_server_utils::build_error_response_with_meta;
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+
+use super::maintenance::is_inference_path;
+use crate::auth::caller::Caller;
+use crate::AppState;
+
+#[derive(Debug, Default)]
+struct Counters {
+    interactive: u32,
+    background: u32,
+    /// 正在排队的交互请求数
+    waiting_interactive: u32,
+}
+
+/// 排队超时
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaneRejected {
+    pub lane: PriorityLane,
+    pub retry_after: Duration,
+}
+
+/// 双通道调度器
+pub struct PriorityScheduler {
+    header: String,
+    default_lane: PriorityLane,
+    max_concurrent: u32,
+    background_max: u32,
+    max_wait: Duration,
+    counters: Mutex<Counters>,
+    notify: Notify,
+}
+
+/// 并发名额，drop 时归还并唤醒排队请求
+pub struct LanePermit {
+    scheduler: Arc<PriorityScheduler>,
+    lane: PriorityLane,
+}
+
+impl Drop for LanePermit {
+    fn drop(&mut self) {
+        let mut counters = self.scheduler.counters.lock();
+        match self.lane {
+            PriorityLane::Interactive => {
+                counters.interactive = counters.interactive.saturating_sub(1)
+            }
+            PriorityLane::Background => counters.background = counters.background.saturating_sub(1),
+        }
+        drop(counters);
+        self.scheduler.notify.notify_waiters();
+    }
+}
+
+/// 交互请求的排队登记，放弃排队（超时或客户端断开）时撤销
+struct WaitingInteractive<'a> {
+    scheduler: &'a PriorityScheduler,
+    admitted: bool,
+}
+
+impl Drop for WaitingInteractive<'_> {
+    fn drop(&mut self) {
+        if self.admitted {
+            return;
+        }
+        let mut counters = self.scheduler.counters.lock();
+        counters.waiting_interactive = counters.waiting_interactive.saturating_sub(1);
+        drop(counters);
+        // 后台请求可能正在等待交互请求让出
+        self.scheduler.notify.notify_waiters();
+    }
+}
+
+impl PriorityScheduler {
+    /// 未启用或并发上限为 0 时返回 `None`
+    pub fn from_settings(settings: &PriorityLaneSettings) -> Option<Self> {
+        if !settings.enabled || settings.max_concurrent == 0 {
+            return None;
+        }
+        Some(Self {
+            header: settings.header.trim().to_ascii_lowercase(),
+            default_lane: settings.default_lane,
+            max_concurrent: settings.max_concurrent,
+            background_max: settings
+                .background_max_concurrent
+                .clamp(1, settings.max_concurrent),
+            max_wait: Duration::from_millis(settings.max_wait_ms),
+            counters: Mutex::new(Counters::default()),
+            notify: Notify::new(),
+        })
+    }
+
+    /// 请求所属通道（见 [`resolve_lane`]）
+    pub fn lane_for(&self, headers: &HeaderMap, caller: &Caller) -> PriorityLane {
+        resolve_lane(&self.header, self.default_lane, headers, caller)
+    }
+
+    fn can_admit(&self, counters: &Counters, lane: PriorityLane) -> bool {
+        let running = counters.interactive + counters.background;
+        match lane {
+            PriorityLane::Interactive => running < self.max_concurrent,
+            PriorityLane::Background => {
+                running < self.max_concurrent
+                    && counters.background < self.background_max
+                    && counters.waiting_interactive == 0
+            }
+        }
+    }
+
+    /// 有空闲名额时占用一个
+    fn admit(self: &Arc<Self>, counters: &mut Counters, lane: PriorityLane) -> Option<LanePermit> {
+        if !self.can_admit(counters, lane) {
+            return None;
+        }
+        match lane {
+            PriorityLane::Interactive => counters.interactive += 1,
+            PriorityLane::Background => counters.background += 1,
+        }
+        Some(LanePermit {
+            scheduler: self.clone(),
+            lane,
+        })
+    }
+
+    /// 不等待地尝试获取名额
+    fn try_acquire(self: &Arc<Self>, lane: PriorityLane) -> Option<LanePermit> {
+        self.admit(&mut self.counters.lock(), lane)
+    }
+
+    /// 等待所属通道的名额，排队超过 `max_wait_ms` 时返回错误
+    pub async fn acquire(self: &Arc<Self>, lane: PriorityLane) -> Result<LanePermit, LaneRejected> {
+        if let Some(permit) = self.try_acquire(lane) {
+            return Ok(permit);
+        }
+        let deadline = tokio::time::Instant::now() + self.max_wait;
+        let mut waiting: Option<WaitingInteractive<'_>> = None;
+        loop {
+            // 先登记唤醒再检查名额，避免检查后、等待前的释放被错过
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            {
+                let mut counters = self.counters.lock();
+                if let Some(permit) = self.admit(&mut counters, lane) {
+                    if let Some(waiting) = waiting.as_mut() {
+                        counters.waiting_interactive =
+                            counters.waiting_interactive.saturating_sub(1);
+                        waiting.admitted = true;
+                    }
+                    return Ok(permit);
+                }
+                if lane == PriorityLane::Interactive && waiting.is_none() {
+                    counters.waiting_interactive += 1;
+                    waiting = Some(WaitingInteractive {
+                        scheduler: self,
+                        admitted: false,
+                    });
+                }
+            }
+
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return Err(LaneRejected {
+                    lane,
+                    retry_after: Duration::from_secs(1),
+                });
+            }
+        }
+    }
+}
+
+/// 请求所属通道：受限 Key 设置的通道优先；其次为请求头，但只有主 Key 可以通过请求头
+/// 进入交互通道（避免后台 Key 插队），其他调用方请求交互通道时使用默认通道
+pub fn resolve_lane(
+    header: &str,
+    default_lane: PriorityLane,
+    headers: &HeaderMap,
+    caller: &Caller,
+) -> PriorityLane {
+    if let Some(lane) = caller.scoped.as_ref().and_then(|record| record.priority) {
+        return lane;
+    }
+    let requested = headers
+        .get(header)
+        .and_then(|v| v.to_str().ok())
+        .and_then(PriorityLane::parse);
+    match requested {
+        Some(PriorityLane::Interactive) if !caller.is_primary() => default_lane,
+        Some(lane) => lane,
+        None => default_lane,
+    }
+}
+
+/// 优先级通道中间件
+pub async fn priority_lane_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(scheduler) = state.priority_scheduler.clone() else {
+        return next.run(request).await;
+    };
+    if !is_inference_path(request.uri().path()) {
+        return next.run(request).await;
+    }
+    let Some(caller) = request.extensions().get::<Caller>() else {
+        return next.run(request).await;
+    };
+
+    let lane = scheduler.lane_for(request.headers(), caller);
+    let started = std::time::Instant::now();
+    let permit = match scheduler.acquire(lane).await {
+        Ok(permit) => permit,
+        Err(rejected) => {
+            tracing::warn!(
+                "[PRIORITY] {} 通道排队超时: {}",
+                rejected.lane.as_str(),
+                request.uri().path()
+            );
+            let retry_after_secs = rejected.retry_after.as_secs().max(1);
+            let response = build_error_response_with_meta(
+                StatusCode::TOO_MANY_REQUESTS.as_u16(),
+                &format!(
+                    "Server is busy with higher priority requests. Retry after {} seconds",
+                    retry_after_secs
+                ),
+                None,
+                None,
+                Some(GatewayErrorCode::RateLimited),
+            );
+            let (mut parts, body) = response.into_parts();
+            parts.headers.insert(
+                header::RETRY_AFTER,
+                header::HeaderValue::from(retry_after_secs),
+            );
+            return Response::from_parts(parts, body);
+        }
+    };
+    let waited = started.elapsed();
+    if waited >= Duration::from_millis(100) {
+        tracing::debug!(
+            "[PRIORITY] {} 通道请求排队 {}ms 后放行",
+            lane.as_str(),
+            waited.as_millis()
+        );
+    }
+
+    let response = next.run(request).await;
+    let is_event_stream = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    if !is_event_stream {
+        return response;
+    }
+
+    // 流式响应读完（或客户端断开）后才归还名额
+    let (parts, body) = response.into_parts();
+    let stream = body.into_data_stream().map(move |chunk| {
+        let _permit = &permit;
+        chunk
+    });
+    Response::from_parts(parts, Body::from_stream(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler(max_concurrent: u32, background_max: u32) -> Arc<PriorityScheduler> {
+        Arc::new(
+            PriorityScheduler::from_settings(&PriorityLaneSettings {
+                enabled: true,
+                max_concurrent,
+                background_max_concurrent: background_max,
+                max_wait_ms: 50,
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_only_primary_key_elevates_by_header() {
+        let scheduler = PriorityScheduler::from_settings(&PriorityLaneSettings {
+            enabled: true,
+            default_lane: PriorityLane::Background,
+            ..Default::default()
+        })
+        .unwrap();
+        let store = crate::auth::scoped_keys::ScopedKeyStore::in_memory();
+        let issued = store.issue("batch", 0).unwrap();
+        let scoped = Caller {
+            scoped: Some(issued.record.clone()),
+        };
+        let pinned = Caller {
+            scoped: Some(crate::auth::scoped_keys::ScopedKeyRecord {
+                priority: Some(PriorityLane::Background),
+                ..issued.record
+            }),
+        };
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            scheduler.lane_for(&headers, &Caller::primary()),
+            PriorityLane::Background
+        );
+        headers.insert(
+            scheduler.header.as_str(),
+            header::HeaderValue::from_static("interactive"),
+        );
+        assert_eq!(
+            scheduler.lane_for(&headers, &Caller::primary()),
+            PriorityLane::Interactive
+        );
+        assert_eq!(
+            scheduler.lane_for(&headers, &scoped),
+            PriorityLane::Background
+        );
+        assert_eq!(
+            scheduler.lane_for(&headers, &pinned),
+            PriorityLane::Background
+        );
+    }
+
+    #[test]
+    fn test_background_capped_and_yields() {
+        assert!(PriorityScheduler::from_settings(&PriorityLaneSettings::default()).is_none());
+
+        let scheduler = scheduler(3, 2);
+        let b1 = scheduler.try_acquire(PriorityLane::Background).unwrap();
+        let _b2 = scheduler.try_acquire(PriorityLane::Background).unwrap();
+        // 后台名额用尽，交互请求仍可获得剩余名额
+        assert!(scheduler.try_acquire(PriorityLane::Background).is_none());
+        let _i1 = scheduler.try_acquire(PriorityLane::Interactive).unwrap();
+        assert!(scheduler.try_acquire(PriorityLane::Interactive).is_none());
+
+        // 有交互请求排队时，释放的名额不会被后台请求拿走
+        drop(b1);
+        scheduler.counters.lock().waiting_interactive = 1;
+        assert!(scheduler.try_acquire(PriorityLane::Background).is_none());
+        scheduler.counters.lock().waiting_interactive = 0;
+        assert!(scheduler.try_acquire(PriorityLane::Background).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_interactive_waiter_preempts_background() {
+        let scheduler = scheduler(1, 1);
+        let held = scheduler.acquire(PriorityLane::Background).await.unwrap();
+
+        let waiter = {
+            let scheduler = scheduler.clone();
+            tokio::spawn(async move { scheduler.acquire(PriorityLane::Interactive).await })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(scheduler.counters.lock().waiting_interactive, 1);
+
+        drop(held);
+        assert!(scheduler.try_acquire(PriorityLane::Background).is_none());
+        let permit = waiter.await.unwrap().unwrap();
+        assert_eq!(permit.lane, PriorityLane::Interactive);
+        assert_eq!(scheduler.counters.lock().waiting_interactive, 0);
+
+        // 排队超时
+        let rejected = scheduler.acquire(PriorityLane::Background).await.err();
+        assert_eq!(rejected.map(|r| r.lane), Some(PriorityLane::Background));
+    }
+}
@@ -1,14 +1,20 @@
 //! 滑动窗口速率限制中间件
 //!
 //! 基于客户端 IP 的请求速率限制，防止 API 滥用
+//!
+//! 配置共享计数存储后（见 [`CounterStore`]），改为按固定窗口在多实例间共享计数。
 
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::{
     collections::HashMap,
+    sync::Arc,
     time::{Duration, Instant},
 };
 
+use super::shared_counter::{current_window, CounterStore};
+
 /// 速率限制配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RateLimitConfig {
@@ -33,6 +39,16 @@ fn default_window_secs() -> u64 {
     60
 }
 
+impl From<&lime_core::config::RateLimitSettings> for RateLimitConfig {
+    fn from(settings: &lime_core::config::RateLimitSettings) -> Self {
+        Self {
+            enabled: settings.enabled,
+            requests_per_minute: settings.requests_per_minute,
+            window_secs: settings.window_secs,
+        }
+    }
+}
+
 impl Default for RateLimitConfig {
     fn default() -> Self {
         Self {
@@ -48,6 +64,8 @@ pub struct SlidingWindowRateLimiter {
     config: RateLimitConfig,
     /// 客户端 IP -> 请求时间戳列表
     requests: Mutex<HashMap<String, Vec<Instant>>>,
+    /// 多实例共享计数（为空时仅本地滑动窗口）
+    shared: Option<Arc<CounterStore>>,
 }
 
 impl SlidingWindowRateLimiter {
@@ -55,6 +73,36 @@ impl SlidingWindowRateLimiter {
         Self {
             config,
             requests: Mutex::new(HashMap::new()),
+            shared: None,
+        }
+    }
+
+    /// 使用共享计数存储（仅当存储为多实例共享时生效）
+    pub fn with_shared_store(mut self, store: Arc<CounterStore>) -> Self {
+        if store.is_shared() {
+            self.shared = Some(store);
+        }
+        self
+    }
+
+    /// 检查是否允许请求（优先使用共享计数）
+    pub async fn check(&self, client_id: &str) -> RateLimitResult {
+        if !self.config.enabled {
+            return RateLimitResult::Allowed;
+        }
+        let Some(store) = &self.shared else {
+            return self.check_rate_limit(client_id);
+        };
+
+        let window = Duration::from_secs(self.config.window_secs.max(1));
+        let (index, retry_after) = current_window(window);
+        // 客户端标识可能是 API Key，写入共享存储前先做摘要
+        let digest = hex::encode(&Sha256::digest(client_id.as_bytes())[..12]);
+        let count = store.incr(&format!("in:{digest}:{index}"), 1, window).await;
+        if count > u64::from(self.config.requests_per_minute) {
+            RateLimitResult::Limited { retry_after }
+        } else {
+            RateLimitResult::Allowed
         }
     }
 
@@ -228,6 +276,25 @@ mod tests {
         assert!(requests.is_empty(), "清理后应无过期条目");
     }
 
+    #[tokio::test]
+    async fn test_check_uses_local_window_without_shared_store() {
+        let limiter = SlidingWindowRateLimiter::new(RateLimitConfig {
+            enabled: true,
+            requests_per_minute: 1,
+            window_secs: 60,
+        })
+        .with_shared_store(Arc::new(CounterStore::local("test")));
+
+        assert!(matches!(
+            limiter.check("client1").await,
+            RateLimitResult::Allowed
+        ));
+        assert!(matches!(
+            limiter.check("client1").await,
+            RateLimitResult::Limited { .. }
+        ));
+    }
+
     #[test]
     fn test_default_config() {
         let config = RateLimitConfig::default();
@@ -0,0 +1,629 @@
+//! 在途请求日志
+//!
+//! 非流式推理请求被接收时向日志文件追加一条 `begin` 记录，产生响应（或客户端断开）后
+//! 追加 `end` 记录。进程崩溃或被强制结束时未完成的请求只有 `begin` 记录，
+//! 下次启动时据此列出丢失的请求。
+//!
+//! 后台通道（批处理）请求额外保存请求体，启动后可经本机回环地址重新执行，
+//! 结果保存在内存中供 `GET /admin/journal/:id` 查询。
+//!
+//! 本中间件位于调用方识别之后，只记录已认证的请求，并保存发起请求的 Key 标识：
+//! 重新执行时只使用同一个 Key，主 Key 发起的请求可在启动后自动重放，
+//! 受限 Key 发起的请求需管理员经 `POST /admin/journal/:id/replay` 提供该 Key 后重放。
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use lime_core::config::{PriorityLane, PriorityLaneSettings, RequestJournalSettings};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::maintenance::is_inference_path;
+use super::priority_lanes::resolve_lane;
+use crate::auth::caller::Caller;
+use crate::AppState;
+
+/// 日志文件名（位于应用数据目录）
+const JOURNAL_FILE: &str = "request-journal.jsonl";
+
+/// 重新执行的请求携带该请求头，不再写入日志，避免反复崩溃时无限重放
+pub const REPLAY_HEADER: &str = "x-lime-journal-replay";
+
+/// 读取请求体的上限（与服务器请求体上限一致）
+const MAX_REQUEST_BYTES: usize = 100 * 1024 * 1024;
+
+/// 已完成记录超过该数量时压缩日志文件
+const COMPACT_THRESHOLD: usize = 10_000;
+
+/// 日志中的请求信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: String,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub lane: PriorityLane,
+    /// 发起请求的 Key 标识（受限 Key ID 或 `primary`），旧版日志中缺失时不会被重放
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+    pub accepted_at: DateTime<Utc>,
+    /// 请求体（仅后台通道且未超出大小上限时保存）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum JournalRecord {
+    Begin(JournalEntry),
+    End { id: String },
+}
+
+/// 重新执行结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayOutcome {
+    pub status: u16,
+    pub completed_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 上次运行中丢失的请求
+#[derive(Debug, Clone, Serialize)]
+pub struct LostRequest {
+    #[serde(flatten)]
+    pub entry: JournalEntry,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replay: Option<ReplayOutcome>,
+}
+
+impl LostRequest {
+    /// 是否可以重新执行（保存了请求体且尚未成功重放）
+    pub fn is_replayable(&self) -> bool {
+        self.entry.body.is_some()
+            && self.replay.as_ref().map_or(true, |outcome| {
+                outcome.status >= 500 || outcome.error.is_some()
+            })
+    }
+}
+
+/// 丢失请求的摘要（不含请求体与响应）
+#[derive(Debug, Clone, Serialize)]
+pub struct LostRequestSummary {
+    pub id: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub lane: PriorityLane,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+    pub accepted_at: DateTime<Utc>,
+    pub replayable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replay_status: Option<u16>,
+}
+
+impl From<&LostRequest> for LostRequestSummary {
+    fn from(lost: &LostRequest) -> Self {
+        Self {
+            id: lost.entry.id.clone(),
+            path: lost.entry.path.clone(),
+            model: lost.entry.model.clone(),
+            lane: lost.entry.lane,
+            key_id: lost.entry.key_id.clone(),
+            accepted_at: lost.entry.accepted_at,
+            replayable: lost.is_replayable(),
+            replay_status: lost.replay.as_ref().map(|outcome| outcome.status),
+        }
+    }
+}
+
+struct Writer {
+    file: Option<File>,
+    in_flight: HashMap<String, JournalEntry>,
+    /// 上次压缩后写入的 `end` 记录数
+    ended: usize,
+}
+
+/// 在途请求日志
+pub struct RequestJournal {
+    path: Option<PathBuf>,
+    settings: RequestJournalSettings,
+    writer: Mutex<Writer>,
+    lost: RwLock<Vec<LostRequest>>,
+}
+
+/// 读取日志，返回未完成的请求（按接收时间排序）
+fn read_unfinished(path: &Path) -> Vec<JournalEntry> {
+    let Ok(file) = File::open(path) else {
+        return Vec::new();
+    };
+    let mut pending: HashMap<String, JournalEntry> = HashMap::new();
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        // 崩溃时最后一行可能只写了一半，直接跳过
+        match serde_json::from_str::<JournalRecord>(&line) {
+            Ok(JournalRecord::Begin(entry)) => {
+                pending.insert(entry.id.clone(), entry);
+            }
+            Ok(JournalRecord::End { id }) => {
+                pending.remove(&id);
+            }
+            Err(_) => {}
+        }
+    }
+    let mut entries: Vec<JournalEntry> = pending.into_values().collect();
+    entries.sort_by_key(|entry| entry.accepted_at);
+    entries
+}
+
+impl RequestJournal {
+    /// 未启用时返回 `None`
+    pub fn from_settings(settings: &RequestJournalSettings) -> Option<Self> {
+        settings.enabled.then(|| {
+            Self::open(
+                lime_core::app_paths::best_effort_app_data_file(JOURNAL_FILE),
+                settings.clone(),
+            )
+        })
+    }
+
+    /// 打开日志文件：读出上次未完成的请求后清空文件
+    pub fn open(path: PathBuf, settings: RequestJournalSettings) -> Self {
+        let lost: Vec<LostRequest> = read_unfinished(&path)
+            .into_iter()
+            .map(|entry| LostRequest {
+                entry,
+                replay: None,
+            })
+            .collect();
+        if !lost.is_empty() {
+            tracing::warn!(
+                "[JOURNAL] 上次运行有 {} 个请求未完成（进程异常退出）",
+                lost.len()
+            );
+        }
+        let file = File::create(&path)
+            .map_err(|e| tracing::warn!("[JOURNAL] 无法创建请求日志 {:?}: {}", path, e))
+            .ok();
+        Self {
+            path: Some(path),
+            settings,
+            writer: Mutex::new(Writer {
+                file,
+                in_flight: HashMap::new(),
+                ended: 0,
+            }),
+            lost: RwLock::new(lost),
+        }
+    }
+
+    #[cfg(test)]
+    fn in_memory(settings: RequestJournalSettings) -> Self {
+        Self {
+            path: None,
+            settings,
+            writer: Mutex::new(Writer {
+                file: None,
+                in_flight: HashMap::new(),
+                ended: 0,
+            }),
+            lost: RwLock::new(Vec::new()),
+        }
+    }
+
+    fn append(file: &mut Option<File>, record: &JournalRecord) {
+        let Some(handle) = file.as_mut() else {
+            return;
+        };
+        let Ok(mut line) = serde_json::to_string(record) else {
+            return;
+        };
+        line.push('\n');
+        if let Err(e) = handle.write_all(line.as_bytes()) {
+            tracing::warn!("[JOURNAL] 写入请求日志失败: {}", e);
+        }
+    }
+
+    /// 只保留仍在途的请求，重写日志文件
+    fn compact(&self, writer: &mut Writer) {
+        let Some(path) = self.path.as_ref() else {
+            return;
+        };
+        let mut content = String::new();
+        for entry in writer.in_flight.values() {
+            if let Ok(line) = serde_json::to_string(&JournalRecord::Begin(entry.clone())) {
+                content.push_str(&line);
+                content.push('\n');
+            }
+        }
+        let tmp = path.with_extension("jsonl.tmp");
+        let result = std::fs::write(&tmp, content).and_then(|_| std::fs::rename(&tmp, path));
+        if let Err(e) = result {
+            tracing::warn!("[JOURNAL] 压缩请求日志失败: {}", e);
+            return;
+        }
+        writer.file = OpenOptions::new().append(true).open(path).ok();
+        writer.ended = 0;
+    }
+
+    /// 记录接收的请求
+    pub fn begin(&self, entry: JournalEntry) {
+        let mut writer = self.writer.lock();
+        Self::append(&mut writer.file, &JournalRecord::Begin(entry.clone()));
+        writer.in_flight.insert(entry.id.clone(), entry);
+    }
+
+    /// 记录请求完成
+    pub fn finish(&self, id: &str) {
+        let mut writer = self.writer.lock();
+        if writer.in_flight.remove(id).is_none() {
+            return;
+        }
+        Self::append(&mut writer.file, &JournalRecord::End { id: id.to_string() });
+        writer.ended += 1;
+        if writer.ended >= COMPACT_THRESHOLD {
+            self.compact(&mut writer);
+        }
+    }
+
+    /// 当前在途请求数
+    pub fn in_flight(&self) -> usize {
+        self.writer.lock().in_flight.len()
+    }
+
+    /// 是否在启动后自动重新执行后台请求
+    pub fn replay_on_startup(&self) -> bool {
+        self.settings.replay_background
+    }
+
+    /// 上次运行中丢失的请求摘要
+    pub fn lost(&self) -> Vec<LostRequestSummary> {
+        self.lost
+            .read()
+            .iter()
+            .map(LostRequestSummary::from)
+            .collect()
+    }
+
+    /// 查询单个丢失请求（含请求体与重放结果）
+    pub fn lost_request(&self, id: &str) -> Option<LostRequest> {
+        self.lost
+            .read()
+            .iter()
+            .find(|lost| lost.entry.id == id)
+            .cloned()
+    }
+
+    /// 清除丢失请求列表，返回清除的数量
+    pub fn clear_lost(&self) -> usize {
+        std::mem::take(&mut *self.lost.write()).len()
+    }
+
+    fn record_replay(&self, id: &str, outcome: ReplayOutcome) {
+        if let Some(lost) = self
+            .lost
+            .write()
+            .iter_mut()
+            .find(|lost| lost.entry.id == id)
+        {
+            lost.replay = Some(outcome);
+        }
+    }
+
+    /// 以 `key_id` 对应的 Key（明文为 `api_key`）经本机服务依次重新执行该 Key 发起的
+    /// 可重放请求（`only` 限定单个请求），返回重新执行的数量
+    pub async fn replay(
+        &self,
+        base_url: &str,
+        key_id: &str,
+        api_key: &str,
+        only: Option<&str>,
+    ) -> usize {
+        let pending: Vec<JournalEntry> = self
+            .lost
+            .read()
+            .iter()
+            .filter(|lost| lost.is_replayable())
+            .filter(|lost| lost.entry.key_id.as_deref() == Some(key_id))
+            .filter(|lost| only.is_none() || only == Some(lost.entry.id.as_str()))
+            .map(|lost| lost.entry.clone())
+            .collect();
+        if pending.is_empty() {
+            return 0;
+        }
+
+        let client = reqwest::Client::new();
+        for entry in &pending {
+            let Some(body) = entry.body.as_ref() else {
+                continue;
+            };
+            let result = client
+                .post(format!("{}{}", base_url.trim_end_matches('/'), entry.path))
+                .bearer_auth(api_key)
+                .header(REPLAY_HEADER, &entry.id)
+                .header("x-lime-priority", PriorityLane::Background.as_str())
+                .json(body)
+                .send()
+                .await;
+            let outcome = match result {
+                Ok(response) => {
+                    let status = response.status().as_u16();
+                    ReplayOutcome {
+                        status,
+                        completed_at: Utc::now(),
+                        response: response.json::<Value>().await.ok(),
+                        error: None,
+                    }
+                }
+                Err(e) => ReplayOutcome {
+                    status: StatusCode::BAD_GATEWAY.as_u16(),
+                    completed_at: Utc::now(),
+                    response: None,
+                    error: Some(e.to_string()),
+                },
+            };
+            tracing::info!(
+                "[JOURNAL] 已重新执行请求 {} ({})，状态 {}",
+                entry.id,
+                entry.path,
+                outcome.status
+            );
+            self.record_replay(&entry.id, outcome);
+        }
+        pending.len()
+    }
+}
+
+/// 本机回环地址（监听所有网卡时改用 127.0.0.1）
+pub fn loopback_base_url(base_url: &str) -> String {
+    let (host, port) = crate::parse_base_url_host_port(base_url);
+    let host = match host.as_str() {
+        "0.0.0.0" | "::" | "[::]" => "127.0.0.1".to_string(),
+        _ => host,
+    };
+    format!("http://{host}:{port}")
+}
+
+/// 请求结束（含客户端断开）时写入 `end` 记录
+struct JournalGuard {
+    journal: Arc<RequestJournal>,
+    id: String,
+}
+
+impl Drop for JournalGuard {
+    fn drop(&mut self) {
+        self.journal.finish(&self.id);
+    }
+}
+
+/// 请求所属通道（与优先级通道中间件的判定一致）
+fn lane_of(state: &AppState, headers: &HeaderMap, caller: &Caller) -> PriorityLane {
+    if let Some(scheduler) = state.priority_scheduler.as_ref() {
+        return scheduler.lane_for(headers, caller);
+    }
+    let settings = PriorityLaneSettings::default();
+    resolve_lane(&settings.header, settings.default_lane, headers, caller)
+}
+
+/// 在途请求日志中间件
+pub async fn request_journal_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(journal) = state.request_journal.clone() else {
+        return next.run(request).await;
+    };
+    // 未认证的请求交给处理器拒绝，不写入日志
+    let Some(caller) = request.extensions().get::<Caller>().cloned() else {
+        return next.run(request).await;
+    };
+    if request.method() != Method::POST
+        || !is_inference_path(request.uri().path())
+        || request.headers().contains_key(REPLAY_HEADER)
+    {
+        return next.run(request).await;
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, MAX_REQUEST_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+    let value = serde_json::from_slice::<Value>(&bytes).ok();
+    let is_stream = value
+        .as_ref()
+        .is_some_and(|v| v["stream"].as_bool() == Some(true));
+    let path = parts.uri.path().to_string();
+    let lane = lane_of(&state, &parts.headers, &caller);
+    let request = Request::from_parts(parts, Body::from(bytes.clone()));
+    if is_stream {
+        return next.run(request).await;
+    }
+
+    let entry = JournalEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        path,
+        model: value
+            .as_ref()
+            .and_then(|v| v["model"].as_str())
+            .map(str::to_string),
+        lane,
+        key_id: Some(caller.key_id().to_string()),
+        accepted_at: Utc::now(),
+        body: value.filter(|_| {
+            lane == PriorityLane::Background && bytes.len() <= journal.settings.max_body_bytes
+        }),
+    };
+    let guard = JournalGuard {
+        journal: journal.clone(),
+        id: entry.id.clone(),
+    };
+    journal.begin(entry);
+    let response = next.run(request).await;
+    drop(guard);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, body: Option<Value>) -> JournalEntry {
+        JournalEntry {
+            id: id.to_string(),
+            path: "/v1/chat/completions".to_string(),
+            model: Some("gpt-4o".to_string()),
+            lane: if body.is_some() {
+                PriorityLane::Background
+            } else {
+                PriorityLane::Interactive
+            },
+            key_id: Some("primary".to_string()),
+            accepted_at: Utc::now(),
+            body,
+        }
+    }
+
+    #[test]
+    fn test_unfinished_requests_reported_after_restart() {
+        let dir = tempfile::tempdir().expect("创建临时目录失败");
+        let path = dir.path().join(JOURNAL_FILE);
+        let settings = RequestJournalSettings {
+            enabled: true,
+            ..Default::default()
+        };
+
+        let journal = RequestJournal::open(path.clone(), settings.clone());
+        journal.begin(entry("done", None));
+        journal.begin(entry(
+            "lost-batch",
+            Some(serde_json::json!({"model": "gpt-4o"})),
+        ));
+        journal.begin(entry("lost-chat", None));
+        journal.finish("done");
+        assert_eq!(journal.in_flight(), 2);
+        // 模拟崩溃：不调用 finish，并追加半行
+        {
+            let mut writer = journal.writer.lock();
+            writer
+                .file
+                .as_mut()
+                .unwrap()
+                .write_all(b"{\"op\":\"beg")
+                .unwrap();
+        }
+        drop(journal);
+
+        let restarted = RequestJournal::open(path.clone(), settings.clone());
+        let lost = restarted.lost();
+        assert_eq!(lost.len(), 2);
+        let batch = lost.iter().find(|l| l.id == "lost-batch").unwrap();
+        assert!(batch.replayable);
+        assert!(
+            !lost
+                .iter()
+                .find(|l| l.id == "lost-chat")
+                .unwrap()
+                .replayable
+        );
+        assert!(restarted
+            .lost_request("lost-batch")
+            .unwrap()
+            .entry
+            .body
+            .is_some());
+
+        // 再次启动时日志已清空
+        drop(restarted);
+        assert!(RequestJournal::open(path, settings).lost().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_replay_only_uses_originating_key() {
+        let dir = tempfile::tempdir().expect("创建临时目录失败");
+        let path = dir.path().join(JOURNAL_FILE);
+        let settings = RequestJournalSettings {
+            enabled: true,
+            ..Default::default()
+        };
+        let journal = RequestJournal::open(path.clone(), settings.clone());
+        let body = Some(serde_json::json!({"model": "gpt-4o"}));
+        journal.begin(entry("by-primary", body.clone()));
+        journal.begin(JournalEntry {
+            key_id: Some("scoped-1".to_string()),
+            ..entry("by-scoped", body.clone())
+        });
+        journal.begin(JournalEntry {
+            key_id: None,
+            ..entry("legacy", body)
+        });
+        drop(journal);
+
+        let restarted = RequestJournal::open(path, settings);
+        // 回环端口不可达，重放结果记为失败，只检查选中的请求
+        let base_url = "http://127.0.0.1:9";
+        assert_eq!(restarted.replay(base_url, "primary", "sk", None).await, 1);
+        assert!(restarted
+            .lost_request("by-primary")
+            .unwrap()
+            .replay
+            .is_some());
+        assert!(restarted
+            .lost_request("by-scoped")
+            .unwrap()
+            .replay
+            .is_none());
+        assert!(restarted.lost_request("legacy").unwrap().replay.is_none());
+
+        assert_eq!(
+            restarted
+                .replay(base_url, "scoped-1", "pc_m_x", Some("by-primary"))
+                .await,
+            0
+        );
+        assert_eq!(
+            restarted
+                .replay(base_url, "scoped-1", "pc_m_x", Some("by-scoped"))
+                .await,
+            1
+        );
+    }
+
+    #[test]
+    fn test_loopback_base_url() {
+        assert_eq!(
+            loopback_base_url("http://0.0.0.0:8999"),
+            "http://127.0.0.1:8999"
+        );
+        assert_eq!(
+            loopback_base_url("http://192.168.1.5:8999"),
+            "http://192.168.1.5:8999"
+        );
+    }
+
+    #[test]
+    fn test_finish_ignores_unknown_ids() {
+        let journal = RequestJournal::in_memory(RequestJournalSettings::default());
+        journal.begin(entry("a", None));
+        journal.finish("a");
+        journal.finish("unknown");
+        assert_eq!(journal.writer.lock().ended, 1);
+        assert_eq!(journal.in_flight(), 0);
+        assert_eq!(journal.clear_lost(), 0);
+    }
+}
@@ -0,0 +1,319 @@
+//! HMAC 请求签名
+//!
+//! 公网暴露（隧道、共享地址）时替代明文 API Key 的认证方式，密钥不随请求传输：
+//! - 签名串：`{timestamp}\n{METHOD}\n{path?query}\n{hex(sha256(body))}`
+//! - 请求头：`x-lime-timestamp`（Unix 秒）、`x-lime-signature`（`hex(HMAC-SHA256(secret, 签名串))`）
+//! - 时间戳超出允许偏差或签名在窗口内重复出现时拒绝（防重放）
+//!
+//! 验证通过后写入内部标记头，推理端点据此放行；外部传入的同名头会先被移除。
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use hmac::{Hmac, Mac};
+use lime_core::config::RequestSigningSettings;
+use lime_core::errors::GatewayErrorCode;
+use lime_server_utils::build_gateway_error_json;
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+use crate::auth::lockout::note_auth_failure;
+use crate::handlers::remote_control::REMOTE_CONTROL_PATH_PREFIX;
+use crate::AppState;
+
+/// 时间戳请求头
+pub const TIMESTAMP_HEADER: &str = "x-lime-timestamp";
+/// 签名请求头
+pub const SIGNATURE_HEADER: &str = "x-lime-signature";
+/// 内部标记头（仅由本中间件写入）
+const VERIFIED_HEADER: &str = "x-lime-signature-verified";
+
+/// 签名请求体的最大长度（与全局请求体限制一致）
+const MAX_SIGNED_BODY_BYTES: usize = 100 * 1024 * 1024;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 进程内随机标记值，避免内部标记头被伪造
+fn verified_marker() -> &'static str {
+    static MARKER: OnceLock<String> = OnceLock::new();
+    MARKER.get_or_init(|| uuid::Uuid::new_v4().simple().to_string())
+}
+
+/// 签名验证错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SigningError {
+    Missing,
+    Malformed,
+    Expired,
+    BadSignature,
+    Replayed,
+}
+
+impl SigningError {
+    pub(crate) fn message(&self) -> &'static str {
+        match self {
+            SigningError::Missing => "Request signature required",
+            SigningError::Malformed => "Malformed request signature headers",
+            SigningError::Expired => "Request timestamp outside allowed skew",
+            SigningError::BadSignature => "Invalid request signature",
+            SigningError::Replayed => "Request signature already used",
+        }
+    }
+}
+
+/// 构建签名串
+pub fn canonical_string(timestamp: i64, method: &str, path: &str, body: &[u8]) -> String {
+    format!(
+        "{timestamp}\n{}\n{path}\n{}",
+        method.to_ascii_uppercase(),
+        hex::encode(Sha256::digest(body))
+    )
+}
+
+/// 计算签名
+pub fn sign(secret: &str, canonical: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC 支持任意长度的密钥");
+    mac.update(canonical.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// 请求是否经过隧道/反向代理转发
+pub fn is_forwarded_request(headers: &HeaderMap) -> bool {
+    ["cf-connecting-ip", "x-forwarded-for", "forwarded"]
+        .iter()
+        .any(|name| headers.contains_key(*name))
+}
+
+/// 请求是否已通过签名验证
+pub fn is_signature_verified(headers: &HeaderMap) -> bool {
+    headers
+        .get(VERIFIED_HEADER)
+        .is_some_and(|value| bool::from(value.as_bytes().ct_eq(verified_marker().as_bytes())))
+}
+
+/// 签名验证器
+pub struct RequestSigner {
+    settings: RequestSigningSettings,
+    /// 已使用的签名 -> 时间戳（窗口过后清理）
+    seen: Mutex<HashMap<String, i64>>,
+}
+
+impl RequestSigner {
+    pub fn new(settings: RequestSigningSettings) -> Self {
+        if settings.enabled && settings.secret.is_empty() {
+            tracing::warn!("[SIGNING] 已启用请求签名但未配置 secret，签名认证不会生效");
+        }
+        Self {
+            settings,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 是否接受签名请求
+    pub fn is_active(&self) -> bool {
+        self.settings.enabled && !self.settings.secret.is_empty()
+    }
+
+    pub fn settings(&self) -> &RequestSigningSettings {
+        &self.settings
+    }
+
+    /// 验证签名
+    pub fn verify(
+        &self,
+        timestamp: Option<&str>,
+        signature: Option<&str>,
+        method: &str,
+        path: &str,
+        body: &[u8],
+        now: i64,
+    ) -> Result<(), SigningError> {
+        let (Some(timestamp), Some(signature)) = (timestamp, signature) else {
+            return Err(SigningError::Missing);
+        };
+        let timestamp: i64 = timestamp
+            .trim()
+            .parse()
+            .map_err(|_| SigningError::Malformed)?;
+        let max_skew = self.settings.max_skew_secs as i64;
+        if (now - timestamp).abs() > max_skew {
+            return Err(SigningError::Expired);
+        }
+
+        let expected = sign(
+            &self.settings.secret,
+            &canonical_string(timestamp, method, path, body),
+        );
+        let signature = signature.trim().to_ascii_lowercase();
+        if !bool::from(expected.as_bytes().ct_eq(signature.as_bytes())) {
+            return Err(SigningError::BadSignature);
+        }
+
+        let mut seen = self.seen.lock();
+        seen.retain(|_, ts| (now - *ts).abs() <= max_skew);
+        if seen.insert(signature, timestamp).is_some() {
+            return Err(SigningError::Replayed);
+        }
+        Ok(())
+    }
+}
+
+fn unauthorized(error: SigningError) -> Response {
+    note_auth_failure();
+    let body = build_gateway_error_json(
+        StatusCode::UNAUTHORIZED.as_u16(),
+        error.message(),
+        None,
+        None,
+        Some(GatewayErrorCode::AuthenticationFailed),
+    );
+    (StatusCode::UNAUTHORIZED, Json(body)).into_response()
+}
+
+/// 签名验证中间件
+pub async fn request_signing_middleware(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    request.headers_mut().remove(VERIFIED_HEADER);
+
+    // 远程控制接口使用独立密钥，由处理器自行验签
+    let signer = &state.request_signer;
+    if !signer.is_active() || request.uri().path().starts_with(REMOTE_CONTROL_PATH_PREFIX) {
+        return next.run(request).await;
+    }
+
+    let has_signature = request.headers().contains_key(SIGNATURE_HEADER);
+    if !has_signature {
+        if signer.settings().require_for_forwarded && is_forwarded_request(request.headers()) {
+            return unauthorized(SigningError::Missing);
+        }
+        return next.run(request).await;
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let bytes = match axum::body::to_bytes(body, MAX_SIGNED_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return unauthorized(SigningError::Malformed),
+    };
+    let header = |name: &str| parts.headers.get(name).and_then(|v| v.to_str().ok());
+    let path = parts
+        .uri
+        .path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or("/");
+    if let Err(e) = signer.verify(
+        header(TIMESTAMP_HEADER),
+        header(SIGNATURE_HEADER),
+        parts.method.as_str(),
+        path,
+        &bytes,
+        chrono::Utc::now().timestamp(),
+    ) {
+        tracing::warn!("[SIGNING] 签名验证失败: {:?} {}", e, path);
+        return unauthorized(e);
+    }
+
+    if let Ok(marker) = HeaderValue::from_str(verified_marker()) {
+        parts.headers.insert(VERIFIED_HEADER, marker);
+    }
+    next.run(Request::from_parts(parts, Body::from(bytes)))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signer() -> RequestSigner {
+        RequestSigner::new(RequestSigningSettings {
+            enabled: true,
+            secret: "shared-secret".to_string(),
+            max_skew_secs: 300,
+            require_for_forwarded: true,
+        })
+    }
+
+    fn signed(ts: i64, body: &[u8]) -> String {
+        sign(
+            "shared-secret",
+            &canonical_string(ts, "post", "/v1/chat/completions", body),
+        )
+    }
+
+    #[test]
+    fn should_accept_valid_signature_once() {
+        let signer = signer();
+        let body = br#"{"model":"gpt"}"#;
+        let signature = signed(1_000, body);
+        let verify = |sig: &str| {
+            signer.verify(
+                Some("1000"),
+                Some(sig),
+                "POST",
+                "/v1/chat/completions",
+                body,
+                1_010,
+            )
+        };
+
+        assert_eq!(verify(&signature), Ok(()));
+        assert_eq!(verify(&signature), Err(SigningError::Replayed));
+    }
+
+    #[test]
+    fn should_reject_tampered_or_expired_requests() {
+        let signer = signer();
+        let signature = signed(1_000, b"original");
+
+        assert_eq!(
+            signer.verify(
+                Some("1000"),
+                Some(&signature),
+                "POST",
+                "/v1/chat/completions",
+                b"tampered",
+                1_000
+            ),
+            Err(SigningError::BadSignature)
+        );
+        assert_eq!(
+            signer.verify(
+                Some("1000"),
+                Some(&signature),
+                "POST",
+                "/v1/chat/completions",
+                b"original",
+                2_000
+            ),
+            Err(SigningError::Expired)
+        );
+        assert_eq!(
+            signer.verify(None, Some(&signature), "POST", "/", b"", 1_000),
+            Err(SigningError::Missing)
+        );
+    }
+
+    #[test]
+    fn should_not_trust_external_verified_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(VERIFIED_HEADER, HeaderValue::from_static("1"));
+        assert!(!is_signature_verified(&headers));
+
+        headers.insert(
+            VERIFIED_HEADER,
+            HeaderValue::from_str(verified_marker()).expect("合法头值"),
+        );
+        assert!(is_signature_verified(&headers));
+    }
+}
@@ -0,0 +1,134 @@
+//! `seed` 透传与 `system_fingerprint`
+//!
+//! 中间件从 Chat Completions 原始请求体中解析 [`SeedOptions`] 放入请求扩展，处理器选定凭证后：
+//! - OpenAI 兼容接口原样转发 `seed`
+//! - 其他接口无法指定 seed，客户端未设置温度时改用温度 0，尽量保持输出稳定
+//!
+//! 响应头 `x-lime-seed` 为 `forwarded` 或 `ignored`；非流式响应缺少 `system_fingerprint`
+//! 时按 Provider 与模型补上。
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use lime_core::errors::GatewayErrorCode;
+use lime_core::models::SeedOptions;
+use lime_server_utils::build_error_response_with_meta;
+use serde_json::Value;
+
+/// 读取请求体的上限（与服务器请求体上限一致）
+const MAX_REQUEST_BYTES: usize = 100 * 1024 * 1024;
+
+/// 补充指纹时读取的最大响应体
+const MAX_RESPONSE_BYTES: usize = 32 * 1024 * 1024;
+
+fn contains_seed(bytes: &[u8]) -> bool {
+    bytes.windows(b"\"seed\"".len()).any(|w| w == b"\"seed\"")
+}
+
+/// 解析 Chat Completions 请求中的 seed
+pub async fn seed_middleware(request: Request, next: Next) -> Response {
+    if request.method() != Method::POST || !request.uri().path().ends_with("/chat/completions") {
+        return next.run(request).await;
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, MAX_REQUEST_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+    if contains_seed(&bytes) {
+        if let Ok(value) = serde_json::from_slice::<Value>(&bytes) {
+            match SeedOptions::from_body(&value) {
+                Ok(Some(options)) => {
+                    parts.extensions.insert(options);
+                }
+                Ok(None) => {}
+                Err(message) => {
+                    return build_error_response_with_meta(
+                        StatusCode::BAD_REQUEST.as_u16(),
+                        &message,
+                        None,
+                        None,
+                        Some(GatewayErrorCode::InvalidRequest),
+                    );
+                }
+            }
+        }
+    }
+    next.run(Request::from_parts(parts, Body::from(bytes)))
+        .await
+}
+
+/// 标记 seed 是否已转发给上游
+pub fn mark_seed(response: &mut Response, forwarded: bool) {
+    let value = if forwarded { "forwarded" } else { "ignored" };
+    response
+        .headers_mut()
+        .insert("x-lime-seed", HeaderValue::from_static(value));
+}
+
+/// 为缺少 `system_fingerprint` 的非流式成功响应补上指纹
+pub async fn attach_system_fingerprint(response: Response, fingerprint: &str) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if !response.status().is_success() || !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_RESPONSE_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("[SEED] 读取响应失败: {}", e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+    let Ok(mut value) = serde_json::from_slice::<Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    let Some(object) = value.as_object_mut().filter(|object| {
+        object
+            .get("system_fingerprint")
+            .map_or(true, Value::is_null)
+    }) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    object.insert(
+        "system_fingerprint".to_string(),
+        Value::String(fingerprint.to_string()),
+    );
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Json;
+
+    #[tokio::test]
+    async fn test_attach_system_fingerprint_keeps_upstream_value() {
+        let response = Json(serde_json::json!({"id": "chatcmpl-1"})).into_response();
+        let response = attach_system_fingerprint(response, "fp_lime_test").await;
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let value: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(value["system_fingerprint"], "fp_lime_test");
+
+        let response =
+            Json(serde_json::json!({"system_fingerprint": "fp_upstream"})).into_response();
+        let response = attach_system_fingerprint(response, "fp_lime_test").await;
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let value: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(value["system_fingerprint"], "fp_upstream");
+
+        assert!(contains_seed(br#"{"seed":1}"#));
+        assert!(!contains_seed(br#"{"model":"seed-1"}"#));
+    }
+}
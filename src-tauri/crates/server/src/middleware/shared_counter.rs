@@ -0,0 +1,229 @@
+//! 限流计数存储
+//!
+//! 入站/出站限流共用的固定窗口计数器。默认为进程内计数；配置 Redis 后多个实例
+//! 共享同一组计数（`INCRBY` + `EXPIRE`），限额在全局范围内生效。
+//! Redis 不可用时由调用方回退到本地计数，避免因计数存储故障拒绝全部请求。
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use lime_core::config::{DistributedRateLimitSettings, RateLimitStoreBackend};
+use parking_lot::Mutex;
+
+/// 本地计数条目上限，超过时先清理过期条目
+const LOCAL_MAX_ENTRIES: usize = 10_000;
+
+/// 固定窗口：返回窗口序号与距窗口结束的剩余时间
+pub fn current_window(window: Duration) -> (u64, Duration) {
+    let window_ms = window.as_millis().max(1) as u64;
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let index = now_ms / window_ms;
+    let remaining = window_ms - now_ms % window_ms;
+    (index, Duration::from_millis(remaining))
+}
+
+/// 进程内计数
+#[derive(Default)]
+struct LocalCounters {
+    counters: Mutex<HashMap<String, (u64, Instant)>>,
+}
+
+impl LocalCounters {
+    fn incr(&self, key: &str, amount: u64, ttl: Duration) -> u64 {
+        let now = Instant::now();
+        let mut counters = self.counters.lock();
+        if counters.len() >= LOCAL_MAX_ENTRIES {
+            counters.retain(|_, (_, expires_at)| *expires_at > now);
+        }
+        let entry = counters.entry(key.to_string()).or_insert((0, now + ttl));
+        if entry.1 <= now {
+            *entry = (0, now + ttl);
+        }
+        entry.0 = entry.0.saturating_add(amount);
+        entry.0
+    }
+
+    fn get(&self, key: &str) -> u64 {
+        let now = Instant::now();
+        self.counters
+            .lock()
+            .get(key)
+            .filter(|(_, expires_at)| *expires_at > now)
+            .map(|(count, _)| *count)
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(feature = "redis-limits")]
+struct RedisCounters {
+    connection: redis::aio::ConnectionManager,
+}
+
+#[cfg(feature = "redis-limits")]
+impl RedisCounters {
+    async fn connect(url: &str) -> Result<Self, String> {
+        let client = redis::Client::open(url).map_err(|e| format!("Redis 地址无效: {e}"))?;
+        let connection = redis::aio::ConnectionManager::new(client)
+            .await
+            .map_err(|e| format!("连接 Redis 失败: {e}"))?;
+        Ok(Self { connection })
+    }
+
+    async fn incr(&self, key: &str, amount: u64, ttl: Duration) -> Result<u64, String> {
+        let mut connection = self.connection.clone();
+        let (count,): (u64,) = redis::pipe()
+            .atomic()
+            .incr(key, amount)
+            .expire(key, ttl.as_secs().max(1) as i64)
+            .ignore()
+            .query_async(&mut connection)
+            .await
+            .map_err(|e| format!("Redis 计数失败: {e}"))?;
+        Ok(count)
+    }
+
+    async fn get(&self, key: &str) -> Result<u64, String> {
+        let mut connection = self.connection.clone();
+        let count: Option<u64> = redis::cmd("GET")
+            .arg(key)
+            .query_async(&mut connection)
+            .await
+            .map_err(|e| format!("Redis 读取计数失败: {e}"))?;
+        Ok(count.unwrap_or(0))
+    }
+}
+
+enum Backend {
+    Local,
+    #[cfg(feature = "redis-limits")]
+    Redis(RedisCounters),
+}
+
+/// 限流计数存储
+pub struct CounterStore {
+    backend: Backend,
+    /// 共享存储故障时使用的本地计数
+    local: LocalCounters,
+    prefix: String,
+}
+
+impl CounterStore {
+    /// 进程内计数
+    pub fn local(prefix: &str) -> Self {
+        Self {
+            backend: Backend::Local,
+            local: LocalCounters::default(),
+            prefix: prefix.to_string(),
+        }
+    }
+
+    /// 按配置创建，连接 Redis 失败时回退到进程内计数
+    pub async fn from_settings(settings: &DistributedRateLimitSettings) -> Self {
+        let prefix = settings.key_prefix.trim();
+        let prefix = if prefix.is_empty() { "lime_rl" } else { prefix };
+        match settings.store {
+            RateLimitStoreBackend::Local => Self::local(prefix),
+            RateLimitStoreBackend::Redis => match Self::connect_redis(settings, prefix).await {
+                Ok(store) => {
+                    tracing::info!("[RATE_LIMIT] 使用 Redis 共享限流计数");
+                    store
+                }
+                Err(e) => {
+                    tracing::error!("[RATE_LIMIT] {}，限流仅对当前实例生效", e);
+                    Self::local(prefix)
+                }
+            },
+        }
+    }
+
+    #[cfg(feature = "redis-limits")]
+    async fn connect_redis(
+        settings: &DistributedRateLimitSettings,
+        prefix: &str,
+    ) -> Result<Self, String> {
+        if settings.redis_url.trim().is_empty() {
+            return Err("未配置 server.distributed_rate_limit.redis_url".to_string());
+        }
+        Ok(Self {
+            backend: Backend::Redis(RedisCounters::connect(settings.redis_url.trim()).await?),
+            local: LocalCounters::default(),
+            prefix: prefix.to_string(),
+        })
+    }
+
+    #[cfg(not(feature = "redis-limits"))]
+    async fn connect_redis(
+        _settings: &DistributedRateLimitSettings,
+        _prefix: &str,
+    ) -> Result<Self, String> {
+        Err("当前构建未包含 Redis 限流支持（redis-limits 特性）".to_string())
+    }
+
+    /// 是否为多实例共享计数
+    pub fn is_shared(&self) -> bool {
+        !matches!(self.backend, Backend::Local)
+    }
+
+    fn full_key(&self, key: &str) -> String {
+        format!("{}:{key}", self.prefix)
+    }
+
+    /// 计数加 `amount` 并返回新值；键在 `ttl` 后过期
+    pub async fn incr(&self, key: &str, amount: u64, ttl: Duration) -> u64 {
+        let key = self.full_key(key);
+        match &self.backend {
+            Backend::Local => self.local.incr(&key, amount, ttl),
+            #[cfg(feature = "redis-limits")]
+            Backend::Redis(redis) => match redis.incr(&key, amount, ttl).await {
+                Ok(count) => count,
+                Err(e) => {
+                    tracing::warn!("[RATE_LIMIT] {}，本次使用本地计数", e);
+                    self.local.incr(&key, amount, ttl)
+                }
+            },
+        }
+    }
+
+    /// 读取当前计数
+    pub async fn get(&self, key: &str) -> u64 {
+        let key = self.full_key(key);
+        match &self.backend {
+            Backend::Local => self.local.get(&key),
+            #[cfg(feature = "redis-limits")]
+            Backend::Redis(redis) => match redis.get(&key).await {
+                Ok(count) => count,
+                Err(e) => {
+                    tracing::warn!("[RATE_LIMIT] {}，本次使用本地计数", e);
+                    self.local.get(&key)
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_counter_expires() {
+        let store = CounterStore::local("test");
+        assert_eq!(store.incr("k", 2, Duration::from_millis(50)).await, 2);
+        assert_eq!(store.incr("k", 3, Duration::from_millis(50)).await, 5);
+        assert_eq!(store.get("k").await, 5);
+
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert_eq!(store.get("k").await, 0);
+        assert_eq!(store.incr("k", 1, Duration::from_millis(50)).await, 1);
+    }
+
+    #[test]
+    fn test_current_window_remaining_within_window() {
+        let (_, remaining) = current_window(Duration::from_secs(60));
+        assert!(remaining <= Duration::from_secs(60));
+        assert!(remaining > Duration::ZERO);
+    }
+}
@@ -0,0 +1,156 @@
+//! SSE 心跳
+//!
+//! 流式响应在 `interval` 内没有输出时插入 `: ping` 注释行（SSE 客户端会忽略注释），
+//! 避免上游长时间思考期间被反向代理或客户端的空闲超时断开。
+//! 心跳只在事件边界处插入，不会打断正在输出的事件。
+
+use std::time::Duration;
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use lime_core::config::SseHeartbeatSettings;
+
+use super::cors::wildcard_match;
+use crate::AppState;
+
+/// 心跳内容
+const HEARTBEAT: &[u8] = b": ping\n\n";
+
+/// 请求路径对应的心跳间隔，`None` 表示不发送心跳
+pub fn interval_for(settings: &SseHeartbeatSettings, path: &str) -> Option<Duration> {
+    if !settings.enabled {
+        return None;
+    }
+    let secs = settings
+        .routes
+        .iter()
+        .find(|route| wildcard_match(route.path.trim(), path))
+        .map(|route| route.interval_secs)
+        .unwrap_or(settings.interval_secs);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// 在空闲时插入心跳
+fn with_heartbeat<S, E>(
+    upstream: S,
+    interval: Duration,
+) -> impl Stream<Item = Result<Bytes, E>> + Send
+where
+    S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+    E: Send + 'static,
+{
+    async_stream::stream! {
+        let mut upstream = Box::pin(upstream);
+        // 尚未输出任何内容时也处于事件边界
+        let mut at_boundary = true;
+        loop {
+            match tokio::time::timeout(interval, upstream.next()).await {
+                Ok(Some(Ok(bytes))) => {
+                    if !bytes.is_empty() {
+                        at_boundary = bytes.ends_with(b"\n\n") || bytes.ends_with(b"\r\n\r\n");
+                    }
+                    yield Ok(bytes);
+                }
+                Ok(Some(Err(e))) => {
+                    yield Err(e);
+                    break;
+                }
+                Ok(None) => break,
+                Err(_) => {
+                    if at_boundary {
+                        yield Ok(Bytes::from_static(HEARTBEAT));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// SSE 心跳中间件
+pub async fn sse_heartbeat_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(interval) = interval_for(&state.sse_heartbeat, request.uri().path()) else {
+        return next.run(request).await;
+    };
+
+    let response = next.run(request).await;
+    let is_event_stream = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    if !is_event_stream {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let stream = with_heartbeat(body.into_data_stream(), interval);
+    Response::from_parts(parts, Body::from_stream(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lime_core::config::SseHeartbeatRoute;
+
+    #[test]
+    fn test_interval_for_route_overrides() {
+        let settings = SseHeartbeatSettings {
+            enabled: true,
+            interval_secs: 15,
+            routes: vec![
+                SseHeartbeatRoute {
+                    path: "/*/v1/messages".to_string(),
+                    interval_secs: 5,
+                },
+                SseHeartbeatRoute {
+                    path: "/v1/chat/completions".to_string(),
+                    interval_secs: 0,
+                },
+            ],
+        };
+
+        assert_eq!(
+            interval_for(&settings, "/claude/v1/messages"),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(interval_for(&settings, "/v1/chat/completions"), None);
+        assert_eq!(
+            interval_for(&settings, "/v1/messages"),
+            Some(Duration::from_secs(15))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_only_at_event_boundary() {
+        let upstream = async_stream::stream! {
+            yield Ok::<_, std::io::Error>(Bytes::from_static(b"data: {\"a\":"));
+            tokio::time::sleep(Duration::from_millis(120)).await;
+            yield Ok(Bytes::from_static(b"1}\n\n"));
+            tokio::time::sleep(Duration::from_millis(120)).await;
+            yield Ok(Bytes::from_static(b"data: [DONE]\n\n"));
+        };
+
+        let chunks: Vec<Bytes> = with_heartbeat(upstream, Duration::from_millis(40))
+            .map(|r| r.unwrap())
+            .collect()
+            .await;
+        let text = String::from_utf8(chunks.concat()).unwrap();
+
+        assert!(text.starts_with("data: {\"a\":1}\n\n: ping\n\n"));
+        assert_eq!(
+            text.replace(": ping\n\n", ""),
+            "data: {\"a\":1}\n\ndata: [DONE]\n\n"
+        );
+    }
+}
@@ -0,0 +1,440 @@
+//! 流式输出上限
+//!
+//! 上游忽略 `max_tokens` 或生成失控时，防止单次流式响应无限消耗额度：
+//! 按 SSE 事件累计输出 Token（按默认分词器估算）与响应字节数，
+//! 下一个事件会超出上限时不再转发，改为输出收尾事件正常结束流：
+//! - OpenAI 格式（`/chat/completions`）：`finish_reason: "length"` 的分块与 `[DONE]`
+//! - Anthropic 格式（`/messages`）：`stop_reason: "max_tokens"` 的 `message_delta` 与 `message_stop`
+//!
+//! 截断后断开上游连接，并写入请求日志。
+
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use lime_core::config::StreamOutputLimit;
+use lime_core::logger::LogStore;
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+
+use crate::sse::SseEventSplitter;
+use crate::AppState;
+
+/// 读取请求体的上限（与服务器请求体上限一致）
+const MAX_REQUEST_BYTES: usize = 100 * 1024 * 1024;
+
+/// 流式响应格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFormat {
+    OpenAi,
+    Anthropic,
+}
+
+impl StreamFormat {
+    pub fn from_path(path: &str) -> Option<Self> {
+        if path.ends_with("/chat/completions") {
+            Some(Self::OpenAi)
+        } else if path.ends_with("/messages") {
+            Some(Self::Anthropic)
+        } else {
+            None
+        }
+    }
+}
+
+/// 单次流式响应的输出计数
+#[derive(Debug)]
+pub struct OutputCapper {
+    format: StreamFormat,
+    limit: StreamOutputLimit,
+    model: String,
+    tokens: u64,
+    bytes: u64,
+    /// 最近一个 OpenAI 分块的 `id` / `created`，收尾分块沿用
+    chunk_id: Option<String>,
+    created: Option<i64>,
+    /// 尚未结束的 Anthropic 内容块
+    open_block: Option<u64>,
+}
+
+impl OutputCapper {
+    pub fn new(format: StreamFormat, limit: StreamOutputLimit, model: &str) -> Self {
+        Self {
+            format,
+            limit,
+            model: model.to_string(),
+            tokens: 0,
+            bytes: 0,
+            chunk_id: None,
+            created: None,
+            open_block: None,
+        }
+    }
+
+    pub fn tokens(&self) -> u64 {
+        self.tokens
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// 计入一个完整的 SSE 事件，超出上限时返回 `false`（该事件不应转发）
+    pub fn accept(&mut self, event: &[u8]) -> bool {
+        let data = event_data(event);
+        let value = data
+            .as_deref()
+            .filter(|data| *data != "[DONE]")
+            .and_then(|data| serde_json::from_str::<Value>(data).ok());
+        let text = value
+            .as_ref()
+            .map(|value| self.output_text(value))
+            .unwrap_or_default();
+
+        let tokens = if text.is_empty() {
+            0
+        } else {
+            lime_infra::tokenizer::count_text(&text, Some(&self.model)) as u64
+        };
+        let bytes = event.len() as u64;
+        let over_tokens = self
+            .limit
+            .max_tokens
+            .is_some_and(|max| self.tokens + tokens > u64::from(max));
+        let over_bytes = self
+            .limit
+            .max_bytes
+            .is_some_and(|max| self.bytes + bytes > max);
+        if over_tokens || over_bytes {
+            return false;
+        }
+
+        self.tokens += tokens;
+        self.bytes += bytes;
+        if let Some(value) = &value {
+            self.track(value);
+        }
+        true
+    }
+
+    /// 事件中的输出文本（正文、思考与工具参数）
+    fn output_text(&self, value: &Value) -> String {
+        let mut text = String::new();
+        match self.format {
+            StreamFormat::OpenAi => {
+                let choices = value.get("choices").and_then(Value::as_array);
+                for delta in choices
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|choice| choice.get("delta"))
+                {
+                    for field in ["content", "reasoning_content"] {
+                        if let Some(part) = delta.get(field).and_then(Value::as_str) {
+                            text.push_str(part);
+                        }
+                    }
+                    let calls = delta.get("tool_calls").and_then(Value::as_array);
+                    for call in calls.into_iter().flatten() {
+                        if let Some(args) =
+                            call.pointer("/function/arguments").and_then(Value::as_str)
+                        {
+                            text.push_str(args);
+                        }
+                    }
+                }
+            }
+            StreamFormat::Anthropic => {
+                if value.get("type").and_then(Value::as_str) == Some("content_block_delta") {
+                    if let Some(delta) = value.get("delta") {
+                        for field in ["text", "thinking", "partial_json"] {
+                            if let Some(part) = delta.get(field).and_then(Value::as_str) {
+                                text.push_str(part);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        text
+    }
+
+    fn track(&mut self, value: &Value) {
+        match self.format {
+            StreamFormat::OpenAi => {
+                if let Some(id) = value.get("id").and_then(Value::as_str) {
+                    self.chunk_id = Some(id.to_string());
+                }
+                if let Some(created) = value.get("created").and_then(Value::as_i64) {
+                    self.created = Some(created);
+                }
+            }
+            StreamFormat::Anthropic => match value.get("type").and_then(Value::as_str) {
+                Some("content_block_start") => {
+                    self.open_block = value.get("index").and_then(Value::as_u64);
+                }
+                Some("content_block_stop") => self.open_block = None,
+                _ => {}
+            },
+        }
+    }
+
+    /// 截断时输出的收尾事件
+    pub fn closing_events(&self) -> String {
+        match self.format {
+            StreamFormat::OpenAi => {
+                let chunk = json!({
+                    "id": self.chunk_id.clone().unwrap_or_else(|| "chatcmpl-truncated".to_string()),
+                    "object": "chat.completion.chunk",
+                    "created": self.created.unwrap_or_else(|| chrono::Utc::now().timestamp()),
+                    "model": self.model,
+                    "choices": [{"index": 0, "delta": {}, "finish_reason": "length"}],
+                });
+                format!("data: {chunk}\n\ndata: [DONE]\n\n")
+            }
+            StreamFormat::Anthropic => {
+                let mut events = String::new();
+                if let Some(index) = self.open_block {
+                    let stop = json!({"type": "content_block_stop", "index": index});
+                    events.push_str(&format!("event: content_block_stop\ndata: {stop}\n\n"));
+                }
+                let delta = json!({
+                    "type": "message_delta",
+                    "delta": {"stop_reason": "max_tokens", "stop_sequence": null},
+                    "usage": {"output_tokens": self.tokens},
+                });
+                events.push_str(&format!("event: message_delta\ndata: {delta}\n\n"));
+                events.push_str("event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n");
+                events
+            }
+        }
+    }
+}
+
+/// 拼接事件中的 `data:` 行
+fn event_data(event: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(event).ok()?;
+    let lines: Vec<&str> = text
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(|data| data.strip_prefix(' ').unwrap_or(data))
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// 按事件转发上游输出，超限时输出收尾事件并结束
+fn with_output_cap<S, E>(
+    upstream: S,
+    mut capper: OutputCapper,
+    logs: Arc<RwLock<LogStore>>,
+) -> impl Stream<Item = Result<Bytes, E>> + Send
+where
+    S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+    E: Send + 'static,
+{
+    async_stream::stream! {
+        let mut upstream = Box::pin(upstream);
+        let mut events = SseEventSplitter::default();
+        let mut truncated = false;
+        'outer: while let Some(chunk) = upstream.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    yield Err(e);
+                    break;
+                }
+            };
+            events.push(&chunk);
+            while let Some(event) = events.next_event() {
+                if !capper.accept(&event) {
+                    truncated = true;
+                    break 'outer;
+                }
+                yield Ok(event);
+            }
+        }
+
+        if truncated {
+            tracing::warn!(
+                "[STREAM_CAP] 模型 {} 的流式输出超出上限，已截断（约 {} Token，{} 字节）",
+                capper.model,
+                capper.tokens(),
+                capper.bytes()
+            );
+            logs.write().await.add(
+                "warn",
+                &format!(
+                    "[STREAM_CAP] 模型 {} 的流式输出超出上限 {:?}，已截断（约 {} Token，{} 字节）",
+                    capper.model,
+                    capper.limit,
+                    capper.tokens(),
+                    capper.bytes()
+                ),
+            );
+            yield Ok(Bytes::from(capper.closing_events()));
+        } else if !events.is_empty() {
+            yield Ok(events.take_rest());
+        }
+    }
+}
+
+/// 流式输出上限中间件
+pub async fn stream_output_cap_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let format = if request.method() == Method::POST {
+        StreamFormat::from_path(request.uri().path())
+    } else {
+        None
+    };
+    let Some(format) = format else {
+        return next.run(request).await;
+    };
+    let key_limit = ["authorization", "x-api-key"]
+        .iter()
+        .filter_map(|name| request.headers().get(*name).and_then(|v| v.to_str().ok()))
+        .map(|value| value.strip_prefix("Bearer ").unwrap_or(value))
+        .map(|key| state.scoped_keys.stream_limit_for(key))
+        .find(StreamOutputLimit::is_limited)
+        .unwrap_or_default();
+    if !state.stream_output_caps.enabled && !key_limit.is_limited() {
+        return next.run(request).await;
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, MAX_REQUEST_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+    let (stream, model) = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(value) => (
+            value.get("stream").and_then(Value::as_bool) == Some(true),
+            value
+                .get("model")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+        ),
+        Err(_) => (false, String::new()),
+    };
+    let limit = state
+        .stream_output_caps
+        .limit_for(&model)
+        .tighten(key_limit);
+    let request = Request::from_parts(parts, Body::from(bytes));
+    if !stream || !limit.is_limited() {
+        return next.run(request).await;
+    }
+
+    let response = next.run(request).await;
+    let is_event_stream = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    if !is_event_stream {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let capper = OutputCapper::new(format, limit, &model);
+    let stream = with_output_cap(body.into_data_stream(), capper, state.logs.clone());
+    Response::from_parts(parts, Body::from_stream(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn openai_chunk(content: &str) -> String {
+        let chunk = json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion.chunk",
+            "created": 1,
+            "model": "gpt-4o",
+            "choices": [{"index": 0, "delta": {"content": content}, "finish_reason": null}],
+        });
+        format!("data: {chunk}\n\n")
+    }
+
+    async fn run(chunks: Vec<String>, capper: OutputCapper) -> String {
+        let upstream = futures::stream::iter(
+            chunks
+                .into_iter()
+                .map(|c| Ok::<_, std::io::Error>(Bytes::from(c))),
+        );
+        let logs = Arc::new(RwLock::new(LogStore::with_custom_config(1, false)));
+        let output: Vec<Bytes> = with_output_cap(upstream, capper, logs)
+            .map(|r| r.unwrap())
+            .collect()
+            .await;
+        String::from_utf8(output.concat()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_openai_stream_truncated_with_length_finish_reason() {
+        let first = openai_chunk("hello");
+        let limit = StreamOutputLimit {
+            max_tokens: None,
+            max_bytes: Some(first.len() as u64 + 10),
+        };
+        let capper = OutputCapper::new(StreamFormat::OpenAi, limit, "gpt-4o");
+        // 第二个分块被拆成两段到达，也应按完整事件计数
+        let second = openai_chunk("world");
+        let (a, b) = second.split_at(12);
+        let text = run(
+            vec![
+                first.clone(),
+                a.to_string(),
+                b.to_string(),
+                "data: [DONE]\n\n".to_string(),
+            ],
+            capper,
+        )
+        .await;
+
+        assert!(text.starts_with(&first));
+        assert!(!text.contains("world"));
+        assert!(text.contains("\"finish_reason\":\"length\""));
+        assert!(text.contains("\"id\":\"chatcmpl-1\""));
+        assert!(text.ends_with("data: [DONE]\n\n"));
+    }
+
+    #[tokio::test]
+    async fn test_anthropic_stream_closes_open_block() {
+        let events = vec![
+            "event: message_start\ndata: {\"type\":\"message_start\"}\n\n".to_string(),
+            "event: content_block_start\ndata: {\"type\":\"content_block_start\",\"index\":0}\n\n"
+                .to_string(),
+            format!(
+                "event: content_block_delta\ndata: {}\n\n",
+                json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "a ".repeat(200)}})
+            ),
+        ];
+        let limit = StreamOutputLimit {
+            max_tokens: Some(5),
+            max_bytes: None,
+        };
+        let capper = OutputCapper::new(StreamFormat::Anthropic, limit, "claude-sonnet-4-5");
+        let text = run(events, capper).await;
+
+        assert!(!text.contains("text_delta"));
+        assert!(text.contains("event: content_block_stop\n"));
+        assert!(text.contains("\"stop_reason\":\"max_tokens\""));
+        assert!(text.ends_with("event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n"));
+
+        assert_eq!(
+            StreamFormat::from_path("/claude/v1/messages"),
+            Some(StreamFormat::Anthropic)
+        );
+        assert_eq!(StreamFormat::from_path("/v1/messages/count_tokens"), None);
+    }
+}
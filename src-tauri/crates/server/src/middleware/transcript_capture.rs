@@ -0,0 +1,205 @@
+//! 对话记录采集
+//!
+//! 启用 `server.transcript_capture` 后，把成功完成的非流式对话请求
+//! （`/v1/chat/completions`、`/v1/messages`，含 `/{selector}/...` 路由）连同响应保存到
+//! `proxy_transcripts` 表，供 `/admin/transcripts/export` 导出为微调数据集。
+//!
+//! 只采集已认证的请求（带 [`Caller`] 扩展），记录归属于认证通过的 Key。同意标记只来自
+//! Key 的配置：受限 Key 的 `training_consent`，主 Key 为 `primary_key_consent`；
+//! 未同意的请求默认不保存。流式请求、失败响应与超出大小上限的请求不保存。
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use lime_core::config::TranscriptCaptureSettings;
+use lime_core::database::dao::proxy_transcript::{ProxyTranscript, ProxyTranscriptDao};
+use lime_core::database::DbConnection;
+use serde_json::Value;
+
+use crate::auth::caller::Caller;
+use crate::AppState;
+
+/// 采集的对话端点路径后缀
+const CAPTURE_SUFFIXES: &[&str] = &["/v1/chat/completions", "/v1/messages"];
+
+/// 读取请求体的上限（与服务器请求体上限一致）
+const MAX_REQUEST_BYTES: usize = 100 * 1024 * 1024;
+
+/// 对话记录采集器
+pub struct TranscriptCapture {
+    settings: TranscriptCaptureSettings,
+    db: DbConnection,
+}
+
+impl TranscriptCapture {
+    /// 未启用或没有数据库时返回 `None`
+    pub fn from_settings(
+        settings: &TranscriptCaptureSettings,
+        db: Option<&DbConnection>,
+    ) -> Option<Self> {
+        if !settings.enabled {
+            return None;
+        }
+        let Some(db) = db else {
+            tracing::warn!("[TRANSCRIPT] 已启用对话记录采集，但数据库不可用，采集不生效");
+            return None;
+        };
+        Some(Self {
+            settings: settings.clone(),
+            db: db.clone(),
+        })
+    }
+
+    /// 请求使用的 Key 标识与是否同意用于训练
+    fn key_and_consent(&self, caller: &Caller) -> (String, bool) {
+        let consent = match &caller.scoped {
+            Some(record) => record.training_consent,
+            None => self.settings.primary_key_consent,
+        };
+        (caller.key_id().to_string(), consent)
+    }
+
+    fn save(&self, transcript: ProxyTranscript) {
+        let db = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            let result = lime_core::database::lock_db(&db).and_then(|conn| {
+                ProxyTranscriptDao::insert(&conn, &transcript).map_err(|e| e.to_string())
+            });
+            if let Err(e) = result {
+                tracing::warn!("[TRANSCRIPT] 保存对话记录失败: {}", e);
+            }
+        });
+    }
+}
+
+/// 对话记录采集中间件
+pub async fn transcript_capture_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(capture) = state.transcript_capture.clone() else {
+        return next.run(request).await;
+    };
+    let path = request.uri().path().to_string();
+    if request.method() != Method::POST
+        || !CAPTURE_SUFFIXES.iter().any(|suffix| path.ends_with(suffix))
+    {
+        return next.run(request).await;
+    }
+    let Some(caller) = request.extensions().get::<Caller>() else {
+        return next.run(request).await;
+    };
+    let (key_id, consent) = capture.key_and_consent(caller);
+    if !consent && !capture.settings.record_without_consent {
+        return next.run(request).await;
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, MAX_REQUEST_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+    let request_value = (bytes.len() <= capture.settings.max_body_bytes)
+        .then(|| serde_json::from_slice::<Value>(&bytes).ok())
+        .flatten()
+        .filter(|v| v["stream"].as_bool() != Some(true));
+    let response = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+    let Some(request_value) = request_value else {
+        return response;
+    };
+    if !response.status().is_success() {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("[TRANSCRIPT] 读取响应失败: {}", e);
+            return StatusCode::BAD_GATEWAY.into_response();
+        }
+    };
+    if bytes.len() <= capture.settings.max_body_bytes {
+        if let Ok(response_value) = serde_json::from_slice::<Value>(&bytes) {
+            capture.save(ProxyTranscript {
+                id: 0,
+                created_at: Utc::now(),
+                model: request_value["model"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                path,
+                key_id,
+                consent,
+                request: request_value,
+                response: response_value,
+            });
+        }
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::scoped_keys::{ScopedKeyOptions, ScopedKeyStore};
+    use lime_core::database::dao::proxy_transcript::PRIMARY_KEY_ID;
+
+    #[test]
+    fn test_key_and_consent() {
+        let db: DbConnection = std::sync::Arc::new(std::sync::Mutex::new(
+            rusqlite::Connection::open_in_memory().unwrap(),
+        ));
+        let settings = TranscriptCaptureSettings {
+            enabled: true,
+            ..Default::default()
+        };
+        let capture = TranscriptCapture::from_settings(&settings, Some(&db)).unwrap();
+        let store = ScopedKeyStore::in_memory();
+        let issued = store
+            .issue_with(&ScopedKeyOptions {
+                label: "dataset".to_string(),
+                training_consent: true,
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert_eq!(
+            capture.key_and_consent(&Caller::primary()),
+            (PRIMARY_KEY_ID.to_string(), false)
+        );
+        let scoped = Caller {
+            scoped: Some(issued.record.clone()),
+        };
+        assert_eq!(
+            capture.key_and_consent(&scoped),
+            (issued.record.id.clone(), true)
+        );
+
+        let capture = TranscriptCapture::from_settings(
+            &TranscriptCaptureSettings {
+                primary_key_consent: true,
+                ..settings
+            },
+            Some(&db),
+        )
+        .unwrap();
+        assert_eq!(
+            capture.key_and_consent(&Caller::primary()),
+            (PRIMARY_KEY_ID.to_string(), true)
+        );
+
+        assert!(
+            TranscriptCapture::from_settings(&TranscriptCaptureSettings::default(), Some(&db))
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! 上游响应头透传
+//!
+//! 中间件在 [`lime_providers::response_headers`] 作用域内处理请求，Provider 调用
+//! 记录的上游响应头按配置过滤后，以 `x-lime-upstream-<name>` 头返回给客户端。
+//! 同一请求多次调用上游（重试、降级）时以最后一次为准。
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use lime_core::config::UpstreamHeaderSettings;
+use lime_providers::response_headers;
+
+use crate::AppState;
+
+/// 透传头前缀
+pub const UPSTREAM_HEADER_PREFIX: &str = "x-lime-upstream-";
+
+/// 单个响应最多透传的头数量
+const MAX_FORWARDED_HEADERS: usize = 32;
+
+/// 透传策略
+#[derive(Debug, Clone, Default)]
+pub struct UpstreamHeaderPolicy {
+    enabled: bool,
+    exact: Vec<String>,
+    prefixes: Vec<String>,
+}
+
+impl UpstreamHeaderPolicy {
+    pub fn new(settings: &UpstreamHeaderSettings) -> Self {
+        let mut exact = Vec::new();
+        let mut prefixes = Vec::new();
+        for pattern in &settings.allow {
+            let pattern = pattern.trim().to_ascii_lowercase();
+            if pattern.is_empty() {
+                continue;
+            }
+            match pattern.strip_suffix('*') {
+                Some(prefix) => prefixes.push(prefix.to_string()),
+                None => exact.push(pattern),
+            }
+        }
+        Self {
+            enabled: settings.enabled && !(exact.is_empty() && prefixes.is_empty()),
+            exact,
+            prefixes,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 上游响应头是否允许透传（`name` 须为小写）
+    pub fn allows(&self, name: &str) -> bool {
+        self.exact.iter().any(|allowed| allowed == name)
+            || self
+                .prefixes
+                .iter()
+                .any(|prefix| name.starts_with(prefix.as_str()))
+    }
+
+    /// 将允许透传的上游响应头加到响应上
+    pub fn apply(&self, upstream: &reqwest::header::HeaderMap, response: &mut Response) {
+        let headers = response.headers_mut();
+        let mut forwarded = 0usize;
+        for (name, value) in upstream {
+            if forwarded >= MAX_FORWARDED_HEADERS {
+                break;
+            }
+            if !self.allows(name.as_str()) {
+                continue;
+            }
+            let Ok(header_name) = HeaderName::from_bytes(
+                format!("{UPSTREAM_HEADER_PREFIX}{}", name.as_str()).as_bytes(),
+            ) else {
+                continue;
+            };
+            if let Ok(header_value) = HeaderValue::from_bytes(value.as_bytes()) {
+                headers.append(header_name, header_value);
+                forwarded += 1;
+            }
+        }
+    }
+}
+
+/// 上游响应头透传中间件
+pub async fn upstream_headers_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let policy = state.upstream_header_policy.clone();
+    if !policy.is_enabled() {
+        return next.run(request).await;
+    }
+
+    let (mut response, upstream) = response_headers::scope(next.run(request)).await;
+    if let Some(upstream) = upstream {
+        policy.apply(&upstream, &mut response);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_only_allowed_headers() {
+        let policy = UpstreamHeaderPolicy::new(&UpstreamHeaderSettings {
+            enabled: true,
+            allow: vec!["X-RateLimit-*".to_string(), "openai-model".to_string()],
+        });
+        let mut upstream = reqwest::header::HeaderMap::new();
+        upstream.insert("x-ratelimit-remaining-requests", "42".parse().unwrap());
+        upstream.insert("openai-model", "gpt-4o-2024-08-06".parse().unwrap());
+        upstream.insert("set-cookie", "session=secret".parse().unwrap());
+
+        let mut response = Response::new(axum::body::Body::empty());
+        policy.apply(&upstream, &mut response);
+
+        let headers = response.headers();
+        assert_eq!(
+            headers["x-lime-upstream-x-ratelimit-remaining-requests"],
+            "42"
+        );
+        assert_eq!(headers["x-lime-upstream-openai-model"], "gpt-4o-2024-08-06");
+        assert!(!headers.contains_key("x-lime-upstream-set-cookie"));
+    }
+
+    #[test]
+    fn test_policy_disabled_without_patterns() {
+        let settings = UpstreamHeaderSettings {
+            enabled: true,
+            allow: vec![" ".to_string()],
+        };
+        assert!(!UpstreamHeaderPolicy::new(&settings).is_enabled());
+    }
+}
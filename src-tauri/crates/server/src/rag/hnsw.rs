@@ -0,0 +1,285 @@
+//! HNSW 近似最近邻索引（余弦相似度）
+//!
+//! 按 Malkov & Yashunin 的分层可导航小世界图实现：节点层级按 `-ln(U) / ln(M)` 抽取，
+//! 插入时自顶层贪心下降，在每层以 `ef_construction` 宽度搜索并连接最近的 `M` 个邻居
+//! （第 0 层为 `2M`），超出上限的邻接表按相似度裁剪。向量写入前归一化，相似度即点积。
+//!
+//! 索引只支持追加；覆盖或删除文档后由 [`RagStore`](super::RagStore) 丢弃并从 SQLite 重建。
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// 每层最大邻居数（第 0 层为两倍）
+const M: usize = 16;
+/// 构建时的候选列表宽度
+const EF_CONSTRUCTION: usize = 100;
+/// 检索时的最小候选列表宽度
+const EF_SEARCH: usize = 64;
+
+/// 按相似度排序的候选
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Scored {
+    score: f32,
+    node: usize,
+}
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+struct Node<T> {
+    vector: Vec<f32>,
+    /// 各层邻接表，长度为节点层级 + 1
+    neighbors: Vec<Vec<usize>>,
+    item: T,
+}
+
+/// HNSW 索引，`T` 为随向量保存的文档数据
+pub struct HnswIndex<T> {
+    nodes: Vec<Node<T>>,
+    ids: HashMap<String, usize>,
+    entry: Option<usize>,
+    level_mult: f64,
+}
+
+fn normalize(vector: &[f32]) -> Vec<f32> {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return vector.to_vec();
+    }
+    vector.iter().map(|x| x / norm).collect()
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+impl<T> Default for HnswIndex<T> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            entry: None,
+            level_mult: 1.0 / (M as f64).ln(),
+        }
+    }
+}
+
+impl<T> HnswIndex<T> {
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains_key(id)
+    }
+
+    /// 按文档 ID 哈希抽取层级，同一批文档总是得到相同的图结构
+    fn level_for(&self, id: &str) -> usize {
+        let mut hasher = DefaultHasher::new();
+        id.hash(&mut hasher);
+        let uniform = ((hasher.finish() >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        (-uniform.ln() * self.level_mult).floor() as usize
+    }
+
+    fn max_neighbors(level: usize) -> usize {
+        if level == 0 {
+            M * 2
+        } else {
+            M
+        }
+    }
+
+    fn score(&self, query: &[f32], node: usize) -> Scored {
+        Scored {
+            score: dot(query, &self.nodes[node].vector),
+            node,
+        }
+    }
+
+    /// 在单层内做宽度为 `ef` 的最佳优先搜索，结果按相似度降序
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entries: &[usize],
+        ef: usize,
+        level: usize,
+    ) -> Vec<Scored> {
+        let mut visited: HashSet<usize> = entries.iter().copied().collect();
+        let mut candidates: BinaryHeap<Scored> = BinaryHeap::new();
+        let mut results: BinaryHeap<Reverse<Scored>> = BinaryHeap::new();
+        for &entry in entries {
+            let scored = self.score(query, entry);
+            candidates.push(scored);
+            results.push(Reverse(scored));
+        }
+        while results.len() > ef {
+            results.pop();
+        }
+
+        while let Some(candidate) = candidates.pop() {
+            let worst = results.peek().map(|Reverse(s)| s.score);
+            if results.len() >= ef && worst.is_some_and(|worst| candidate.score < worst) {
+                break;
+            }
+            let Some(neighbors) = self.nodes[candidate.node].neighbors.get(level) else {
+                continue;
+            };
+            for &neighbor in neighbors {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let scored = self.score(query, neighbor);
+                let worst = results.peek().map(|Reverse(s)| s.score);
+                if results.len() < ef || worst.is_some_and(|worst| scored.score > worst) {
+                    candidates.push(scored);
+                    results.push(Reverse(scored));
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        let mut sorted: Vec<Scored> = results.into_iter().map(|Reverse(s)| s).collect();
+        sorted.sort_by(|a, b| b.cmp(a));
+        sorted
+    }
+
+    /// 自顶层贪心下降到 `target_level + 1` 层，返回下一层的入口
+    fn descend(&self, query: &[f32], target_level: usize) -> Option<usize> {
+        let mut current = self.entry?;
+        let top = self.nodes[current].neighbors.len() - 1;
+        for level in (target_level + 1..=top).rev() {
+            if let Some(best) = self.search_layer(query, &[current], 1, level).first() {
+                current = best.node;
+            }
+        }
+        Some(current)
+    }
+
+    /// 裁剪节点在某层的邻接表，只保留最相似的邻居
+    fn prune(&mut self, node: usize, level: usize) {
+        let limit = Self::max_neighbors(level);
+        if self.nodes[node].neighbors[level].len() <= limit {
+            return;
+        }
+        let vector = self.nodes[node].vector.clone();
+        let mut scored: Vec<Scored> = self.nodes[node].neighbors[level]
+            .iter()
+            .map(|&neighbor| self.score(&vector, neighbor))
+            .collect();
+        scored.sort_by(|a, b| b.cmp(a));
+        scored.truncate(limit);
+        self.nodes[node].neighbors[level] = scored.into_iter().map(|s| s.node).collect();
+    }
+
+    /// 插入文档；ID 已存在时忽略并返回 false
+    pub fn insert(&mut self, id: String, vector: &[f32], item: T) -> bool {
+        if self.ids.contains_key(&id) {
+            return false;
+        }
+        let vector = normalize(vector);
+        let level = self.level_for(&id);
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            vector,
+            neighbors: vec![Vec::new(); level + 1],
+            item,
+        });
+        self.ids.insert(id, node);
+
+        let Some(entry) = self.entry else {
+            self.entry = Some(node);
+            return true;
+        };
+        let top = self.nodes[entry].neighbors.len() - 1;
+        let query = self.nodes[node].vector.clone();
+        let mut entries = vec![self.descend(&query, level).unwrap_or(entry)];
+
+        for layer in (0..=level.min(top)).rev() {
+            let found = self.search_layer(&query, &entries, EF_CONSTRUCTION, layer);
+            let selected: Vec<usize> = found.iter().take(M).map(|s| s.node).collect();
+            for &neighbor in &selected {
+                self.nodes[neighbor].neighbors[layer].push(node);
+                self.prune(neighbor, layer);
+            }
+            self.nodes[node].neighbors[layer] = selected;
+            entries = found.into_iter().map(|s| s.node).collect();
+        }
+
+        if level > top {
+            self.entry = Some(node);
+        }
+        true
+    }
+
+    /// 检索最相似的 `top_k` 个文档，结果按相似度降序
+    pub fn search(&self, query: &[f32], top_k: usize) -> Vec<(f32, &T)> {
+        if top_k == 0 {
+            return Vec::new();
+        }
+        let query = normalize(query);
+        let Some(entry) = self.descend(&query, 0) else {
+            return Vec::new();
+        };
+        self.search_layer(&query, &[entry], EF_SEARCH.max(top_k), 0)
+            .into_iter()
+            .take(top_k)
+            .map(|scored| (scored.score, &self.nodes[scored.node].item))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_exact_search_on_small_sets() {
+        let mut index = HnswIndex::default();
+        let vectors: Vec<Vec<f32>> = (0..500)
+            .map(|i| {
+                let angle = i as f32 * 0.0125;
+                vec![angle.cos(), angle.sin(), (i % 7) as f32 * 0.01]
+            })
+            .collect();
+        for (i, vector) in vectors.iter().enumerate() {
+            assert!(index.insert(format!("doc-{i}"), vector, i));
+        }
+        assert!(!index.insert("doc-0".to_string(), &vectors[0], 0));
+        assert_eq!(index.len(), 500);
+
+        let query = [0.8_f32.cos(), 0.8_f32.sin(), 0.0];
+        let mut exact: Vec<(f32, usize)> = vectors
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (dot(&normalize(&query), &normalize(v)), i))
+            .collect();
+        exact.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let hits = index.search(&query, 5);
+        assert_eq!(
+            hits.iter().map(|(_, &i)| i).collect::<Vec<_>>(),
+            exact.iter().take(5).map(|&(_, i)| i).collect::<Vec<_>>()
+        );
+        assert!(hits.windows(2).all(|w| w[0].0 >= w[1].0));
+    }
+}
@@ -0,0 +1,308 @@
+//! 本地 RAG 向量存储
+//!
+//! 文档向量持久化在 SQLite 的 `rag_documents` 表中；检索走按集合维护的内存 HNSW 索引
+//! （见 [`hnsw`]），首次检索时从 SQLite 加载构建。新文档增量插入已有索引，覆盖或删除
+//! 文档后丢弃该集合的索引，下次检索时重建。
+
+mod hnsw;
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use lime_core::config::RagSettings;
+use lime_core::database::dao::rag_document::{RagDocumentDao, RagDocumentRecord};
+use lime_core::database::DbConnection;
+use serde::Serialize;
+
+use hnsw::HnswIndex;
+
+/// 单条检索结果
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RagHit {
+    pub id: String,
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+    pub score: f32,
+}
+
+/// 索引中随向量保存的文档内容
+struct IndexedDocument {
+    id: String,
+    content: String,
+    metadata: Option<serde_json::Value>,
+}
+
+pub struct RagStore {
+    settings: RagSettings,
+    db: Option<DbConnection>,
+    /// 集合名 -> HNSW 索引（加锁顺序：先索引后数据库）
+    indexes: Mutex<HashMap<String, HnswIndex<IndexedDocument>>>,
+}
+
+impl RagStore {
+    pub fn new(settings: RagSettings, db: Option<DbConnection>) -> Self {
+        Self {
+            settings,
+            db,
+            indexes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.settings.enabled && self.db.is_some()
+    }
+
+    pub fn settings(&self) -> &RagSettings {
+        &self.settings
+    }
+
+    /// 解析集合名（未指定时使用默认集合）
+    pub fn collection_or_default(&self, collection: Option<&str>) -> String {
+        collection
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .unwrap_or(&self.settings.default_collection)
+            .to_string()
+    }
+
+    /// 写入文档（`documents` 为 (id, 内容, 元数据, 向量)）
+    pub fn upsert(
+        &self,
+        collection: &str,
+        documents: Vec<(String, String, Option<serde_json::Value>, Vec<f32>)>,
+    ) -> Result<usize, String> {
+        let db = self.db.as_ref().ok_or("Database not available")?;
+        let mut indexes = self.indexes.lock().unwrap_or_else(|e| e.into_inner());
+        let conn = lime_core::database::lock_db(db)?;
+        let mut written = 0;
+        for (id, content, metadata, embedding) in documents {
+            RagDocumentDao::upsert(
+                &conn,
+                &RagDocumentRecord {
+                    collection: collection.to_string(),
+                    id: id.clone(),
+                    content: content.clone(),
+                    metadata: metadata.clone(),
+                    embedding_model: self.settings.embedding_model.clone(),
+                    embedding: embedding.clone(),
+                },
+            )
+            .map_err(|e| format!("写入 RAG 文档失败: {e}"))?;
+            written += 1;
+
+            // 新文档增量插入；覆盖已有文档时索引无法原地更新，丢弃后重建
+            if let Some(index) = indexes.get_mut(collection) {
+                if index.contains(&id) {
+                    indexes.remove(collection);
+                } else {
+                    let document = IndexedDocument {
+                        id: id.clone(),
+                        content,
+                        metadata,
+                    };
+                    index.insert(id, &embedding, document);
+                }
+            }
+        }
+        Ok(written)
+    }
+
+    /// 删除文档，返回文档是否存在
+    pub fn delete(&self, collection: &str, id: &str) -> Result<bool, String> {
+        let db = self.db.as_ref().ok_or("Database not available")?;
+        let mut indexes = self.indexes.lock().unwrap_or_else(|e| e.into_inner());
+        let conn = lime_core::database::lock_db(db)?;
+        let deleted = RagDocumentDao::delete(&conn, collection, id)
+            .map_err(|e| format!("删除 RAG 文档失败: {e}"))?;
+        if deleted {
+            indexes.remove(collection);
+        }
+        Ok(deleted)
+    }
+
+    /// 从 SQLite 加载集合并构建索引
+    fn build_index(
+        &self,
+        db: &DbConnection,
+        collection: &str,
+    ) -> Result<HnswIndex<IndexedDocument>, String> {
+        let documents = {
+            let conn = lime_core::database::lock_db(db)?;
+            RagDocumentDao::list_by_collection(&conn, collection, &self.settings.embedding_model)
+                .map_err(|e| format!("读取 RAG 文档失败: {e}"))?
+        };
+        let mut index = HnswIndex::default();
+        for doc in documents {
+            let document = IndexedDocument {
+                id: doc.id.clone(),
+                content: doc.content,
+                metadata: doc.metadata,
+            };
+            index.insert(doc.id, &doc.embedding, document);
+        }
+        tracing::debug!(
+            "[RAG] 构建 HNSW 索引 collection={} size={}",
+            collection,
+            index.len()
+        );
+        Ok(index)
+    }
+
+    /// 在集合内检索与查询向量最相近的文档
+    pub fn search(
+        &self,
+        collection: &str,
+        query: &[f32],
+        top_k: usize,
+        min_score: f32,
+    ) -> Result<Vec<RagHit>, String> {
+        let db = self.db.as_ref().ok_or("Database not available")?;
+        let mut indexes = self.indexes.lock().unwrap_or_else(|e| e.into_inner());
+        let index = match indexes.entry(collection.to_string()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(self.build_index(db, collection)?),
+        };
+
+        Ok(index
+            .search(query, top_k)
+            .into_iter()
+            .filter(|(score, _)| *score >= min_score)
+            .map(|(score, doc)| RagHit {
+                id: doc.id.clone(),
+                content: doc.content.clone(),
+                metadata: doc.metadata.clone(),
+                score,
+            })
+            .collect())
+    }
+}
+
+/// 将检索结果拼装为注入给模型的上下文（按 `max_chars` 截断）
+pub fn build_context_prompt(hits: &[RagHit], max_chars: usize) -> Option<String> {
+    let mut context = String::new();
+    for (index, hit) in hits.iter().enumerate() {
+        let block = format!("[{}] {}\n\n", index + 1, hit.content.trim());
+        if context.chars().count() + block.chars().count() > max_chars {
+            break;
+        }
+        context.push_str(&block);
+    }
+
+    if context.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "以下是从本地知识库检索到的参考资料，请在回答时优先参考，并在不相关时忽略：\n\n{}",
+        context.trim_end()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex as StdMutex};
+
+    fn create_store() -> RagStore {
+        let conn = rusqlite::Connection::open_in_memory().expect("创建内存数据库失败");
+        lime_core::database::schema::create_tables(&conn).expect("创建表结构失败");
+        RagStore::new(
+            RagSettings {
+                enabled: true,
+                ..RagSettings::default()
+            },
+            Some(Arc::new(StdMutex::new(conn))),
+        )
+    }
+
+    #[test]
+    fn should_rank_documents_by_similarity() {
+        let store = create_store();
+        store
+            .upsert(
+                "docs",
+                vec![
+                    ("a".to_string(), "apple".to_string(), None, vec![1.0, 0.0]),
+                    ("b".to_string(), "banana".to_string(), None, vec![0.6, 0.8]),
+                    ("c".to_string(), "cherry".to_string(), None, vec![0.0, 1.0]),
+                ],
+            )
+            .expect("写入应成功");
+
+        let hits = store
+            .search("docs", &[1.0, 0.1], 2, 0.0)
+            .expect("检索应成功");
+        assert_eq!(
+            hits.iter().map(|h| h.id.as_str()).collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+
+        let filtered = store
+            .search("docs", &[1.0, 0.0], 10, 0.5)
+            .expect("检索应成功");
+        assert_eq!(filtered.len(), 2);
+    }
+
+    #[test]
+    fn should_keep_index_in_sync_with_upsert_and_delete() {
+        let store = create_store();
+        let doc = |id: &str, content: &str, vector: Vec<f32>| {
+            (id.to_string(), content.to_string(), None, vector)
+        };
+        store
+            .upsert("docs", vec![doc("a", "apple", vec![1.0, 0.0])])
+            .expect("写入应成功");
+        assert_eq!(
+            store.search("docs", &[0.0, 1.0], 1, 0.0).unwrap()[0].id,
+            "a"
+        );
+
+        // 已构建索引后增量插入
+        store
+            .upsert("docs", vec![doc("c", "cherry", vec![0.0, 1.0])])
+            .expect("写入应成功");
+        assert_eq!(
+            store.search("docs", &[0.0, 1.0], 1, 0.0).unwrap()[0].id,
+            "c"
+        );
+
+        // 覆盖已有文档后索引重建
+        store
+            .upsert("docs", vec![doc("a", "avocado", vec![0.0, 1.0])])
+            .expect("覆盖应成功");
+        let hits = store.search("docs", &[0.0, 1.0], 2, 0.9).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().any(|hit| hit.content == "avocado"));
+
+        assert!(store.delete("docs", "c").expect("删除应成功"));
+        assert!(!store.delete("docs", "c").expect("删除应成功"));
+        let hits = store.search("docs", &[0.0, 1.0], 10, 0.0).unwrap();
+        assert_eq!(
+            hits.iter().map(|h| h.id.as_str()).collect::<Vec<_>>(),
+            vec!["a"]
+        );
+    }
+
+    #[test]
+    fn should_truncate_context_prompt() {
+        let hits = vec![
+            RagHit {
+                id: "a".to_string(),
+                content: "短内容".to_string(),
+                metadata: None,
+                score: 0.9,
+            },
+            RagHit {
+                id: "b".to_string(),
+                content: "x".repeat(100),
+                metadata: None,
+                score: 0.8,
+            },
+        ];
+        let prompt = build_context_prompt(&hits, 50).expect("应生成上下文");
+        assert!(prompt.contains("[1] 短内容"));
+        assert!(!prompt.contains("[2]"));
+        assert!(build_context_prompt(&[], 50).is_none());
+    }
+}
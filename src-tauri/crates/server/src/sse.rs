@@ -0,0 +1,125 @@
+//! SSE 事件切分
+//!
+//! 上游输出按字节缓冲，只在完整事件（以空行结束）的边界处切分，切出的事件再解码为文本。
+//! 多字节 UTF-8 字符被拆在两个网络包之间时不会被替换为 U+FFFD；
+//! 同时支持 `\n\n` 与 `\r\n\r\n` 分隔，文本形式的事件统一使用 `\n` 换行。
+
+use bytes::{Bytes, BytesMut};
+
+/// 缓冲区中第一个完整事件的结束位置（含分隔空行）
+fn first_event_end(buffer: &[u8]) -> Option<usize> {
+    let lf = buffer
+        .windows(2)
+        .position(|w| w == b"\n\n")
+        .map(|pos| pos + 2);
+    let crlf = buffer
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .map(|pos| pos + 4);
+    match (lf, crlf) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (end, None) | (None, end) => end,
+    }
+}
+
+/// 缓冲区中最后一个完整事件的结束位置（含分隔空行）
+fn last_event_end(buffer: &[u8]) -> Option<usize> {
+    let lf = buffer
+        .windows(2)
+        .rposition(|w| w == b"\n\n")
+        .map(|pos| pos + 2);
+    let crlf = buffer
+        .windows(4)
+        .rposition(|w| w == b"\r\n\r\n")
+        .map(|pos| pos + 4);
+    lf.max(crlf)
+}
+
+/// 事件原始字节转为文本（换行统一为 `\n`，去掉结尾的分隔空行）
+fn event_text(raw: &[u8]) -> String {
+    String::from_utf8_lossy(raw)
+        .replace("\r\n", "\n")
+        .trim_end_matches('\n')
+        .to_string()
+}
+
+/// SSE 事件切分器
+#[derive(Debug, Default)]
+pub struct SseEventSplitter {
+    buffer: BytesMut,
+}
+
+impl SseEventSplitter {
+    /// 追加上游数据
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// 缓冲中尚未取出的字节数
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// 取出下一个完整事件的原始字节（含分隔空行）
+    pub fn next_event(&mut self) -> Option<Bytes> {
+        let end = first_event_end(&self.buffer)?;
+        Some(self.buffer.split_to(end).freeze())
+    }
+
+    /// 取出下一个完整事件的文本（不含分隔空行）
+    pub fn next_text(&mut self) -> Option<String> {
+        self.next_event().map(|event| event_text(&event))
+    }
+
+    /// 一次取出全部完整事件的原始字节
+    pub fn take_complete(&mut self) -> Option<Bytes> {
+        let end = last_event_end(&self.buffer)?;
+        Some(self.buffer.split_to(end).freeze())
+    }
+
+    /// 取出剩余的原始字节（上游结束或出错时）
+    pub fn take_rest(&mut self) -> Bytes {
+        self.buffer.split().freeze()
+    }
+
+    /// 取出剩余数据的文本（上游未以空行结束最后一个事件时）
+    pub fn take_rest_text(&mut self) -> String {
+        event_text(&self.take_rest()).trim_end().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_multibyte_chars_split_across_chunks() {
+        let event = "data: {\"text\":\"你好\"}\r\n\r\ndata: [DONE]\n\n";
+        let bytes = event.as_bytes();
+        // 在“你”的第二个字节处切开
+        let cut = event.find('你').unwrap() + 1;
+
+        let mut splitter = SseEventSplitter::default();
+        splitter.push(&bytes[..cut]);
+        assert_eq!(splitter.next_text(), None);
+        assert_eq!(splitter.take_complete(), None);
+        splitter.push(&bytes[cut..]);
+        assert_eq!(
+            splitter.next_text().as_deref(),
+            Some("data: {\"text\":\"你好\"}")
+        );
+        assert_eq!(splitter.next_text().as_deref(), Some("data: [DONE]"));
+        assert!(splitter.is_empty());
+
+        splitter.push(b"data: a\n\ndata: b\n\ndata: c");
+        assert_eq!(
+            splitter.take_complete().as_deref(),
+            Some(&b"data: a\n\ndata: b\n\n"[..])
+        );
+        assert_eq!(splitter.take_rest_text(), "data: c");
+    }
+}
@@ -15,6 +15,7 @@ use lime_core::database::dao::api_key_provider::{
 use lime_core::database::system_providers::{get_system_providers, to_api_key_provider};
 use lime_core::database::DbConnection;
 use lime_core::models::{CredentialData, CredentialSource, PoolProviderType, ProviderCredential};
+use lime_core::secret_store::{content_account, is_keychain_ref, secret_store};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
@@ -553,9 +554,13 @@ struct EncryptionService {
     legacy_keys: Vec<Vec<u8>>,
 }
 
+/// Provider API Key 在钥匙串中的账户类型
+const API_KEY_ACCOUNT_KIND: &str = "api-key";
+
 struct DecryptionResult {
     plaintext: String,
-    used_legacy_key: bool,
+    /// 需要重新写入（旧版密钥加密，或待迁入钥匙串）
+    needs_rewrite: bool,
 }
 
 impl EncryptionService {
@@ -638,13 +643,27 @@ impl EncryptionService {
                 .any(|ch| ch.is_ascii_control() || ch.is_whitespace())
     }
 
-    /// 加密 API Key
+    /// 加密 API Key（优先保存到系统钥匙串，数据库只保存引用）
     fn encrypt(&self, plaintext: &str) -> String {
+        // 按内容生成账户名，相同 Key 得到相同引用，重复检测依然有效
+        if let Some(reference) =
+            secret_store().store(&content_account(API_KEY_ACCOUNT_KIND, plaintext), plaintext)
+        {
+            return reference;
+        }
         let encrypted = Self::xor_bytes(plaintext.as_bytes(), &self.current_key);
         BASE64.encode(encrypted)
     }
 
     fn decrypt_with_compatibility(&self, ciphertext: &str) -> Result<DecryptionResult, String> {
+        if is_keychain_ref(ciphertext) {
+            return Ok(DecryptionResult {
+                plaintext: secret_store().resolve(ciphertext)?,
+                needs_rewrite: false,
+            });
+        }
+
+        let migrate_to_keychain = secret_store().is_active();
         let encrypted = BASE64
             .decode(ciphertext)
             .map_err(|e| format!("Base64 解码失败: {e}"))?;
@@ -659,7 +678,7 @@ impl EncryptionService {
                 Ok(plaintext) if Self::looks_like_api_key_candidate(&plaintext) => {
                     return Ok(DecryptionResult {
                         plaintext,
-                        used_legacy_key,
+                        needs_rewrite: used_legacy_key || migrate_to_keychain,
                     });
                 }
                 Ok(_) => {
@@ -682,6 +701,9 @@ impl EncryptionService {
 
     /// 检查是否为加密后的值（非明文）
     fn is_encrypted(&self, value: &str) -> bool {
+        if is_keychain_ref(value) {
+            return true;
+        }
         // 加密后的值是 Base64 编码的，通常不包含常见的 API Key 前缀
         !value.starts_with("sk-")
             && !value.starts_with("pk-")
@@ -726,7 +748,7 @@ impl ApiKeyProviderService {
             .encryption
             .decrypt_with_compatibility(&key.api_key_encrypted)?;
 
-        if result.used_legacy_key {
+        if result.needs_rewrite {
             let reencrypted = self.encryption.encrypt(&result.plaintext);
             if reencrypted != key.api_key_encrypted {
                 ApiKeyProviderDao::update_api_key_encrypted(conn, &key.id, &reencrypted)
@@ -753,7 +775,7 @@ impl ApiKeyProviderService {
                     .encryption
                     .decrypt_with_compatibility(&key.api_key_encrypted)
                 {
-                    Ok(result) if result.used_legacy_key => {
+                    Ok(result) if result.needs_rewrite => {
                         let reencrypted = self.encryption.encrypt(&result.plaintext);
                         if reencrypted != key.api_key_encrypted {
                             ApiKeyProviderDao::update_api_key_encrypted(
@@ -1568,7 +1590,22 @@ impl ApiKeyProviderService {
     /// 删除 API Key
     pub fn delete_api_key(&self, db: &DbConnection, key_id: &str) -> Result<bool, String> {
         let conn = lime_core::database::lock_db(db)?;
-        ApiKeyProviderDao::delete_api_key(&conn, key_id).map_err(|e| e.to_string())
+        let stored = ApiKeyProviderDao::get_api_key_by_id(&conn, key_id)
+            .map_err(|e| e.to_string())?
+            .map(|key| key.api_key_encrypted);
+        let deleted =
+            ApiKeyProviderDao::delete_api_key(&conn, key_id).map_err(|e| e.to_string())?;
+
+        // 同一 Key 可能被多个 Provider 引用，仅在无引用时删除钥匙串条目
+        if let Some(reference) = stored.filter(|value| is_keychain_ref(value)) {
+            let still_used = ApiKeyProviderDao::count_api_keys_by_encrypted(&conn, &reference)
+                .map_err(|e| e.to_string())?
+                > 0;
+            if !still_used {
+                secret_store().remove(&reference);
+            }
+        }
+        Ok(deleted)
     }
 
     /// 切换 API Key 启用状态
@@ -2273,6 +2310,7 @@ impl ApiKeyProviderService {
             cached_token: None,
             source: CredentialSource::Imported,
             proxy_url: None,
+            deleted_at: None,
         })
     }
 
@@ -2331,6 +2369,7 @@ impl ApiKeyProviderService {
             cached_token: None,
             source: CredentialSource::Imported, // 标记为导入来源
             proxy_url: None,
+            deleted_at: None,
         })
     }
 
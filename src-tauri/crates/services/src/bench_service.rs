@@ -0,0 +1,454 @@
+//! 压测服务（Bench 模式）
+//!
+//! 对本地服务发起可配置的合成负载（并发数、请求体大小、是否流式），
+//! 统计吞吐与延迟分布，帮助评估凭证池规模与限流设置。
+//! 每个请求带唯一前缀，避免命中响应缓存与请求去重。
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+
+/// 并发数上限
+const MAX_CONCURRENCY: usize = 256;
+/// 请求总数上限
+const MAX_TOTAL_REQUESTS: usize = 10_000;
+/// 请求体填充内容上限（字节）
+const MAX_PROMPT_BYTES: usize = 1024 * 1024;
+/// 单个请求超时
+const REQUEST_TIMEOUT_SECS: u64 = 300;
+
+fn default_concurrency() -> usize {
+    4
+}
+
+fn default_total_requests() -> usize {
+    20
+}
+
+fn default_prompt_bytes() -> usize {
+    256
+}
+
+fn default_max_tokens() -> u32 {
+    64
+}
+
+/// 压测参数
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BenchOptions {
+    /// 请求的模型
+    pub model: String,
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    #[serde(default = "default_total_requests")]
+    pub total_requests: usize,
+    /// 最长运行时间（秒），到时后不再发起新请求
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<u64>,
+    /// 用户消息的大小（字节）
+    #[serde(default = "default_prompt_bytes")]
+    pub prompt_bytes: usize,
+    #[serde(default = "default_max_tokens")]
+    pub max_tokens: u32,
+    #[serde(default)]
+    pub stream: bool,
+}
+
+impl BenchOptions {
+    /// 把参数限制在允许范围内
+    pub fn normalized(mut self) -> Result<Self, String> {
+        if self.model.trim().is_empty() {
+            return Err("请指定压测使用的模型".to_string());
+        }
+        self.concurrency = self.concurrency.clamp(1, MAX_CONCURRENCY);
+        self.total_requests = self.total_requests.clamp(1, MAX_TOTAL_REQUESTS);
+        self.prompt_bytes = self.prompt_bytes.min(MAX_PROMPT_BYTES);
+        self.max_tokens = self.max_tokens.max(1);
+        Ok(self)
+    }
+}
+
+/// 延迟分布（毫秒）
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct LatencyStats {
+    pub min: u64,
+    pub mean: u64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub max: u64,
+}
+
+impl LatencyStats {
+    /// 最近秩法计算分位数
+    pub fn from_samples(samples: &[u64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let rank = |q: f64| {
+            let index = ((q * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len());
+            sorted[index - 1]
+        };
+        Some(Self {
+            min: sorted[0],
+            mean: sorted.iter().sum::<u64>() / sorted.len() as u64,
+            p50: rank(0.50),
+            p90: rank(0.90),
+            p99: rank(0.99),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// 压测结果
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BenchReport {
+    pub options: BenchOptions,
+    /// 实际发出的请求数（取消或超时后可能少于 `total_requests`）
+    pub completed: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// 按状态码统计，网络错误记为 `error`
+    pub status_counts: BTreeMap<String, usize>,
+    pub duration_ms: u64,
+    pub requests_per_sec: f64,
+    /// 成功请求的总耗时分布
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<LatencyStats>,
+    /// 流式请求的首字节时间分布
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttfb_ms: Option<LatencyStats>,
+    pub bytes_received: u64,
+    /// 第一个失败请求的错误信息
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_error: Option<String>,
+    pub cancelled: bool,
+}
+
+struct Sample {
+    status: Option<u16>,
+    latency_ms: u64,
+    ttfb_ms: Option<u64>,
+    bytes: u64,
+    error: Option<String>,
+}
+
+impl Sample {
+    fn succeeded(&self) -> bool {
+        self.error.is_none() && self.status.is_some_and(|s| (200..300).contains(&s))
+    }
+}
+
+/// 构造请求体（OpenAI Chat Completions 格式）
+pub fn build_payload(options: &BenchOptions, index: usize, nonce: &str) -> serde_json::Value {
+    let prefix = format!("[bench {nonce}-{index}] ");
+    let mut content = prefix.clone();
+    let filler = "The quick brown fox jumps over the lazy dog. ";
+    while content.len() < options.prompt_bytes.max(prefix.len()) {
+        content.push_str(filler);
+    }
+    content.truncate(options.prompt_bytes.max(prefix.len()));
+    serde_json::json!({
+        "model": options.model,
+        "messages": [{"role": "user", "content": content}],
+        "max_tokens": options.max_tokens,
+        "stream": options.stream,
+    })
+}
+
+/// 运行压测
+///
+/// `base_url` 为本地服务地址（如 `http://127.0.0.1:8999`），`cancel` 置位后不再发起新请求。
+pub async fn run_bench(
+    base_url: &str,
+    api_key: &str,
+    options: BenchOptions,
+    cancel: Arc<AtomicBool>,
+) -> Result<BenchReport, String> {
+    let options = options.normalized()?;
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .pool_max_idle_per_host(options.concurrency)
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {e}"))?;
+    let url = format!("{}/v1/chat/completions", base_url.trim_end_matches('/'));
+    let nonce = uuid::Uuid::new_v4().simple().to_string()[..8].to_string();
+    let deadline = options
+        .duration_secs
+        .map(|secs| Instant::now() + Duration::from_secs(secs.max(1)));
+    let next_index = Arc::new(AtomicUsize::new(0));
+
+    tracing::info!(
+        "[BENCH] 开始压测: model={} concurrency={} total={} stream={} prompt_bytes={}",
+        options.model,
+        options.concurrency,
+        options.total_requests,
+        options.stream,
+        options.prompt_bytes
+    );
+
+    let started = Instant::now();
+    let workers = (0..options.concurrency).map(|_| {
+        let client = client.clone();
+        let url = url.clone();
+        let api_key = api_key.to_string();
+        let options = options.clone();
+        let nonce = nonce.clone();
+        let next_index = next_index.clone();
+        let cancel = cancel.clone();
+        async move {
+            let mut samples = Vec::new();
+            loop {
+                if cancel.load(Ordering::Relaxed) || deadline.is_some_and(|d| Instant::now() >= d) {
+                    break;
+                }
+                let index = next_index.fetch_add(1, Ordering::Relaxed);
+                if index >= options.total_requests {
+                    break;
+                }
+                let payload = build_payload(&options, index, &nonce);
+                samples.push(send_one(&client, &url, &api_key, &payload, options.stream).await);
+            }
+            samples
+        }
+    });
+    let samples: Vec<Sample> = futures::future::join_all(workers)
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+    let elapsed = started.elapsed();
+
+    let report = summarize(options, samples, elapsed, cancel.load(Ordering::Relaxed));
+    tracing::info!(
+        "[BENCH] 压测完成: {}/{} 成功, {:.2} req/s, p50={:?}ms",
+        report.succeeded,
+        report.completed,
+        report.requests_per_sec,
+        report.latency_ms.as_ref().map(|l| l.p50)
+    );
+    Ok(report)
+}
+
+async fn send_one(
+    client: &reqwest::Client,
+    url: &str,
+    api_key: &str,
+    payload: &serde_json::Value,
+    stream: bool,
+) -> Sample {
+    let started = Instant::now();
+    let response = client
+        .post(url)
+        .bearer_auth(api_key)
+        .json(payload)
+        .send()
+        .await;
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => {
+            return Sample {
+                status: None,
+                latency_ms: started.elapsed().as_millis() as u64,
+                ttfb_ms: None,
+                bytes: 0,
+                error: Some(e.to_string()),
+            }
+        }
+    };
+
+    let status = response.status();
+    let mut bytes = 0u64;
+    let mut ttfb_ms = None;
+    let mut error = None;
+    let mut body_head = Vec::new();
+    let mut chunks = response.bytes_stream();
+    while let Some(chunk) = chunks.next().await {
+        match chunk {
+            Ok(chunk) => {
+                if ttfb_ms.is_none() && !chunk.is_empty() {
+                    ttfb_ms = Some(started.elapsed().as_millis() as u64);
+                }
+                if body_head.len() < 512 {
+                    body_head.extend_from_slice(&chunk[..chunk.len().min(512 - body_head.len())]);
+                }
+                bytes += chunk.len() as u64;
+            }
+            Err(e) => {
+                error = Some(format!("读取响应失败: {e}"));
+                break;
+            }
+        }
+    }
+    if error.is_none() && !status.is_success() {
+        error = Some(format!(
+            "HTTP {}: {}",
+            status.as_u16(),
+            String::from_utf8_lossy(&body_head)
+        ));
+    }
+
+    Sample {
+        status: Some(status.as_u16()),
+        latency_ms: started.elapsed().as_millis() as u64,
+        ttfb_ms: if stream { ttfb_ms } else { None },
+        bytes,
+        error,
+    }
+}
+
+fn summarize(
+    options: BenchOptions,
+    samples: Vec<Sample>,
+    elapsed: Duration,
+    cancelled: bool,
+) -> BenchReport {
+    let mut status_counts = BTreeMap::new();
+    for sample in &samples {
+        let key = sample
+            .status
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "error".to_string());
+        *status_counts.entry(key).or_insert(0) += 1;
+    }
+    let succeeded: Vec<&Sample> = samples.iter().filter(|s| s.succeeded()).collect();
+    let latencies: Vec<u64> = succeeded.iter().map(|s| s.latency_ms).collect();
+    let ttfbs: Vec<u64> = succeeded.iter().filter_map(|s| s.ttfb_ms).collect();
+    let secs = elapsed.as_secs_f64();
+
+    BenchReport {
+        completed: samples.len(),
+        succeeded: succeeded.len(),
+        failed: samples.len() - succeeded.len(),
+        status_counts,
+        duration_ms: elapsed.as_millis() as u64,
+        requests_per_sec: if secs > 0.0 {
+            succeeded.len() as f64 / secs
+        } else {
+            0.0
+        },
+        latency_ms: LatencyStats::from_samples(&latencies),
+        ttfb_ms: LatencyStats::from_samples(&ttfbs),
+        bytes_received: samples.iter().map(|s| s.bytes).sum(),
+        first_error: samples.iter().find_map(|s| s.error.clone()),
+        cancelled,
+        options,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn options() -> BenchOptions {
+        BenchOptions {
+            model: "gpt-4o-mini".to_string(),
+            concurrency: 3,
+            total_requests: 10,
+            duration_secs: None,
+            prompt_bytes: 100,
+            max_tokens: 8,
+            stream: false,
+        }
+    }
+
+    #[test]
+    fn test_latency_stats_nearest_rank() {
+        let samples: Vec<u64> = (1..=100).collect();
+        let stats = LatencyStats::from_samples(&samples).unwrap();
+        assert_eq!(stats.min, 1);
+        assert_eq!(stats.p50, 50);
+        assert_eq!(stats.p90, 90);
+        assert_eq!(stats.p99, 99);
+        assert_eq!(stats.max, 100);
+        assert!(LatencyStats::from_samples(&[]).is_none());
+    }
+
+    #[test]
+    fn test_payload_size_and_unique_prefix() {
+        let options = options();
+        let a = build_payload(&options, 0, "abcd");
+        let b = build_payload(&options, 1, "abcd");
+        let content = a["messages"][0]["content"].as_str().unwrap();
+        assert_eq!(content.len(), 100);
+        assert!(content.starts_with("[bench abcd-0] "));
+        assert_ne!(a, b);
+        assert_eq!(a["stream"], false);
+    }
+
+    #[tokio::test]
+    async fn test_run_bench_against_local_server() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let served = Arc::new(AtomicUsize::new(0));
+        let served_clone = served.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    break;
+                };
+                let index = served_clone.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    // 读完请求头与请求体后再响应
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    loop {
+                        let Ok(n) = socket.read(&mut buf).await else {
+                            return;
+                        };
+                        if n == 0 {
+                            return;
+                        }
+                        request.extend_from_slice(&buf[..n]);
+                        let text = String::from_utf8_lossy(&request).to_string();
+                        if let Some(head_end) = text.find("\r\n\r\n") {
+                            let content_length = text[..head_end]
+                                .lines()
+                                .find_map(|line| {
+                                    let (name, value) = line.split_once(':')?;
+                                    name.eq_ignore_ascii_case("content-length")
+                                        .then(|| value.trim().parse::<usize>().ok())?
+                                })
+                                .unwrap_or(0);
+                            if request.len() >= head_end + 4 + content_length {
+                                break;
+                            }
+                        }
+                    }
+                    // 每 5 个请求返回一次 429
+                    let response = if index % 5 == 4 {
+                        "HTTP/1.1 429 Too Many Requests\r\nContent-Length: 4\r\nConnection: close\r\n\r\nslow"
+                    } else {
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}"
+                    };
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        let report = run_bench(
+            &format!("http://{addr}"),
+            "pc_test",
+            options(),
+            Arc::new(AtomicBool::new(false)),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.completed, 10);
+        assert_eq!(report.succeeded, 8);
+        assert_eq!(report.status_counts.get("429"), Some(&2));
+        assert!(report.first_error.unwrap().starts_with("HTTP 429"));
+        assert!(report.latency_ms.is_some());
+        assert!(report.ttfb_ms.is_none());
+        assert!(!report.cancelled);
+    }
+}
@@ -0,0 +1,460 @@
+//! CLI 工具接入预设
+//!
+//! 一键把 Claude Code、Codex CLI 与 Cline 指向本机 Lime 网关：
+//! - Claude Code：合并写入 `~/.claude/settings.json` 的 `env`（地址、Key、模型）
+//! - Codex CLI：在 `~/.codex/config.toml` 中添加 `lime` 模型提供方并设为默认，
+//!   Key 通过环境变量 `LIME_API_KEY` 提供
+//! - Cline：配置保存在 VS Code 扩展存储中，无法直接写入，只生成手动填写的配置项
+//!
+//! 写入前会把目标文件（或「原本不存在」这一事实）备份到应用数据目录，
+//! 回滚时按最近一次备份还原，多次回滚逐次向前恢复。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Codex 配置中的模型提供方名称
+const CODEX_PROVIDER_ID: &str = "lime";
+/// Codex 读取 Key 的环境变量
+const CODEX_KEY_ENV: &str = "LIME_API_KEY";
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// 支持的 CLI 工具
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CliTool {
+    ClaudeCode,
+    CodexCli,
+    Cline,
+}
+
+impl CliTool {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CliTool::ClaudeCode => "claude_code",
+            CliTool::CodexCli => "codex_cli",
+            CliTool::Cline => "cline",
+        }
+    }
+
+    /// 默认模型（主模型，快速模型）
+    fn default_models(&self) -> (&'static str, Option<&'static str>) {
+        match self {
+            CliTool::ClaudeCode => ("claude-sonnet-4-5", Some("claude-haiku-4-5")),
+            CliTool::CodexCli => ("gpt-5-codex", None),
+            CliTool::Cline => ("claude-sonnet-4-5", None),
+        }
+    }
+}
+
+/// 预设使用的模型别名
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CliPresetModels {
+    /// 主模型，为空时使用工具默认值
+    #[serde(default)]
+    pub model: Option<String>,
+    /// 快速模型（仅 Claude Code 使用，对应后台任务与 Haiku 档位）
+    #[serde(default)]
+    pub small_model: Option<String>,
+}
+
+/// 生成预设所需的网关信息
+#[derive(Debug, Clone)]
+pub struct CliPresetOptions {
+    /// 网关根地址（不含 `/v1`）
+    pub base_url: String,
+    pub api_key: String,
+    pub models: CliPresetModels,
+}
+
+/// 将写入的文件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CliPresetFile {
+    pub path: String,
+    pub content: String,
+}
+
+/// 需要用户设置的环境变量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CliPresetEnvVar {
+    pub name: String,
+    pub value: String,
+}
+
+/// 预设内容（预览与应用共用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CliPresetPlan {
+    pub tool: CliTool,
+    pub files: Vec<CliPresetFile>,
+    pub env: Vec<CliPresetEnvVar>,
+    /// 无法自动写入、需要手动完成的步骤
+    pub manual_steps: Vec<String>,
+}
+
+/// 应用预设的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CliPresetApplyResult {
+    #[serde(flatten)]
+    pub plan: CliPresetPlan,
+    /// 本次备份 ID，没有写入文件时为空
+    pub backup_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupEntry {
+    path: String,
+    /// 备份内容；为空表示写入前文件不存在，回滚时删除
+    content: Option<String>,
+}
+
+fn home_dir() -> Result<PathBuf, String> {
+    dirs::home_dir().ok_or_else(|| "无法获取主目录".to_string())
+}
+
+fn backup_root() -> PathBuf {
+    lime_core::app_paths::best_effort_runtime_subdir("cli_preset_backups")
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>, String> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("读取 {} 失败: {e}", path.display())),
+    }
+}
+
+fn escape_toml_string(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t")
+}
+
+/// 合并 Claude Code settings.json，保留其他字段
+fn render_claude_settings(
+    existing: Option<&str>,
+    base_url: &str,
+    api_key: &str,
+    model: &str,
+    small_model: Option<&str>,
+) -> Result<String, String> {
+    let mut settings: Value = match existing.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(content) => serde_json::from_str(content)
+            .map_err(|e| format!("Claude Code settings.json 格式有误，请先修复: {e}"))?,
+        None => json!({}),
+    };
+    let settings_obj = settings
+        .as_object_mut()
+        .ok_or("Claude Code settings.json 不是 JSON 对象")?;
+    let env = settings_obj
+        .entry("env")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .ok_or("Claude Code settings.json 的 env 不是 JSON 对象")?;
+
+    env.insert("ANTHROPIC_BASE_URL".to_string(), json!(base_url));
+    env.insert("ANTHROPIC_AUTH_TOKEN".to_string(), json!(api_key));
+    // 与 AUTH_TOKEN 同时存在时 Claude Code 会报冲突
+    env.remove("ANTHROPIC_API_KEY");
+    env.insert("ANTHROPIC_MODEL".to_string(), json!(model));
+    if let Some(small_model) = small_model {
+        env.insert(
+            "ANTHROPIC_DEFAULT_HAIKU_MODEL".to_string(),
+            json!(small_model),
+        );
+    }
+
+    serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())
+}
+
+/// 改写 Codex config.toml：顶层设置默认提供方与模型，并替换 `[model_providers.lime]` 段
+fn render_codex_config(existing: Option<&str>, base_url: &str, model: &str) -> String {
+    let section_header = format!("[model_providers.{CODEX_PROVIDER_ID}]");
+    let mut top_level = true;
+    let mut in_lime_section = false;
+    let mut kept: Vec<&str> = Vec::new();
+
+    for line in existing.unwrap_or_default().lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            top_level = false;
+            in_lime_section = trimmed == section_header;
+        }
+        if in_lime_section {
+            continue;
+        }
+        if top_level {
+            let key = trimmed.split('=').next().unwrap_or_default().trim();
+            if key == "model" || key == "model_provider" {
+                continue;
+            }
+        }
+        kept.push(line);
+    }
+
+    let mut lines = vec![
+        format!("model_provider = \"{CODEX_PROVIDER_ID}\""),
+        format!("model = \"{}\"", escape_toml_string(model)),
+    ];
+    let rest = kept.join("\n");
+    let rest = rest.trim();
+    if !rest.is_empty() {
+        lines.push(rest.to_string());
+    }
+    lines.push(String::new());
+    lines.push(section_header);
+    lines.push("name = \"Lime\"".to_string());
+    lines.push(format!(
+        "base_url = \"{}/v1\"",
+        escape_toml_string(base_url.trim_end_matches('/'))
+    ));
+    lines.push(format!("env_key = \"{CODEX_KEY_ENV}\""));
+    lines.push("wire_api = \"chat\"".to_string());
+    lines.join("\n") + "\n"
+}
+
+/// 生成预设内容（读取现有配置用于合并，不写盘）
+fn plan_preset_in(
+    home: &Path,
+    tool: CliTool,
+    options: &CliPresetOptions,
+) -> Result<CliPresetPlan, String> {
+    if options.api_key.trim().is_empty() {
+        return Err("网关未设置 API Key，无法生成接入配置".to_string());
+    }
+    let (default_model, default_small) = tool.default_models();
+    let model = options
+        .models
+        .model
+        .as_deref()
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .unwrap_or(default_model);
+    let small_model = options
+        .models
+        .small_model
+        .as_deref()
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .or(default_small);
+    let base_url = options.base_url.trim_end_matches('/');
+
+    let mut plan = CliPresetPlan {
+        tool,
+        files: Vec::new(),
+        env: Vec::new(),
+        manual_steps: Vec::new(),
+    };
+    match tool {
+        CliTool::ClaudeCode => {
+            let path = home.join(".claude").join("settings.json");
+            let content = render_claude_settings(
+                read_optional(&path)?.as_deref(),
+                base_url,
+                &options.api_key,
+                model,
+                small_model,
+            )?;
+            plan.files.push(CliPresetFile {
+                path: path.to_string_lossy().to_string(),
+                content,
+            });
+        }
+        CliTool::CodexCli => {
+            let path = home.join(".codex").join("config.toml");
+            let content = render_codex_config(read_optional(&path)?.as_deref(), base_url, model);
+            plan.files.push(CliPresetFile {
+                path: path.to_string_lossy().to_string(),
+                content,
+            });
+            plan.env.push(CliPresetEnvVar {
+                name: CODEX_KEY_ENV.to_string(),
+                value: options.api_key.clone(),
+            });
+            plan.manual_steps.push(format!(
+                "在 shell 配置中导出 {CODEX_KEY_ENV} 后重新打开终端"
+            ));
+        }
+        CliTool::Cline => {
+            plan.manual_steps = vec![
+                "在 Cline 设置中将 API Provider 选择为 OpenAI Compatible".to_string(),
+                format!("Base URL 填写 {base_url}/v1"),
+                format!("API Key 填写 {}", options.api_key),
+                format!("Model ID 填写 {model}"),
+            ];
+        }
+    }
+    Ok(plan)
+}
+
+/// 备份目标文件并写入预设，返回备份 ID
+fn apply_plan_in(backup_root: &Path, plan: &CliPresetPlan) -> Result<Option<String>, String> {
+    if plan.files.is_empty() {
+        return Ok(None);
+    }
+
+    let backup_id = Utc::now().format("%Y%m%dT%H%M%S%3fZ").to_string();
+    let backup_dir = backup_root.join(plan.tool.as_str()).join(&backup_id);
+    fs::create_dir_all(&backup_dir).map_err(|e| format!("创建备份目录失败: {e}"))?;
+    let entries = plan
+        .files
+        .iter()
+        .map(|file| {
+            Ok(BackupEntry {
+                path: file.path.clone(),
+                content: read_optional(Path::new(&file.path))?,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let manifest = serde_json::to_string_pretty(&entries).map_err(|e| e.to_string())?;
+    fs::write(backup_dir.join(MANIFEST_FILE), manifest)
+        .map_err(|e| format!("写入备份失败: {e}"))?;
+
+    for file in &plan.files {
+        let path = Path::new(&file.path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("创建目录 {} 失败: {e}", parent.display()))?;
+        }
+        let temp_path = path.with_extension("lime-tmp");
+        fs::write(&temp_path, &file.content)
+            .and_then(|_| fs::rename(&temp_path, path))
+            .map_err(|e| format!("写入 {} 失败: {e}", path.display()))?;
+        tracing::info!("[CLI_PRESET] 已写入 {}", path.display());
+    }
+    Ok(Some(backup_id))
+}
+
+/// 还原最近一次备份并删除该备份，返回还原的文件
+fn rollback_in(backup_root: &Path, tool: CliTool) -> Result<Vec<String>, String> {
+    let tool_dir = backup_root.join(tool.as_str());
+    let latest = fs::read_dir(&tool_dir)
+        .ok()
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().join(MANIFEST_FILE).is_file())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .max()
+        .ok_or_else(|| "没有可回滚的备份".to_string())?;
+    let backup_dir = tool_dir.join(&latest);
+
+    let manifest = fs::read_to_string(backup_dir.join(MANIFEST_FILE))
+        .map_err(|e| format!("读取备份失败: {e}"))?;
+    let entries: Vec<BackupEntry> =
+        serde_json::from_str(&manifest).map_err(|e| format!("备份清单无效: {e}"))?;
+
+    let mut restored = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let path = Path::new(&entry.path);
+        let result = match &entry.content {
+            Some(content) => fs::write(path, content),
+            None => match fs::remove_file(path) {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                other => other,
+            },
+        };
+        result.map_err(|e| format!("还原 {} 失败: {e}", path.display()))?;
+        tracing::info!("[CLI_PRESET] 已还原 {}", path.display());
+        restored.push(entry.path);
+    }
+
+    if let Err(e) = fs::remove_dir_all(&backup_dir) {
+        tracing::warn!("[CLI_PRESET] 删除已还原的备份失败: {}", e);
+    }
+    Ok(restored)
+}
+
+/// 预览预设内容
+pub fn preview_preset(tool: CliTool, options: &CliPresetOptions) -> Result<CliPresetPlan, String> {
+    plan_preset_in(&home_dir()?, tool, options)
+}
+
+/// 应用预设（写入前自动备份）
+pub fn apply_preset(
+    tool: CliTool,
+    options: &CliPresetOptions,
+) -> Result<CliPresetApplyResult, String> {
+    let plan = plan_preset_in(&home_dir()?, tool, options)?;
+    let backup_id = apply_plan_in(&backup_root(), &plan)?;
+    Ok(CliPresetApplyResult { plan, backup_id })
+}
+
+/// 回滚最近一次应用的预设
+pub fn rollback_preset(tool: CliTool) -> Result<Vec<String>, String> {
+    rollback_in(&backup_root(), tool)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> CliPresetOptions {
+        CliPresetOptions {
+            base_url: "http://127.0.0.1:8999/".to_string(),
+            api_key: "sk-lime".to_string(),
+            models: CliPresetModels::default(),
+        }
+    }
+
+    #[test]
+    fn test_codex_config_replaces_top_level_keys_and_section() {
+        let existing = "model = \"o3\"\napproval_policy = \"never\"\n\n[model_providers.lime]\nname = \"old\"\n\n[mcp_servers.fs]\ncommand = \"npx\"\nmodel = \"keep\"\n";
+        let rendered = render_codex_config(Some(existing), "http://127.0.0.1:8999", "gpt-5");
+        assert!(rendered.starts_with("model_provider = \"lime\"\nmodel = \"gpt-5\"\n"));
+        assert!(rendered.contains("approval_policy = \"never\""));
+        assert!(rendered.contains("[mcp_servers.fs]\ncommand = \"npx\"\nmodel = \"keep\""));
+        assert!(!rendered.contains("name = \"old\""));
+        assert!(!rendered.contains("\"o3\""));
+        assert!(rendered.contains("base_url = \"http://127.0.0.1:8999/v1\""));
+        assert_eq!(rendered.matches("[model_providers.lime]").count(), 1);
+    }
+
+    #[test]
+    fn test_apply_and_rollback_restore_previous_state() {
+        let root = tempfile::tempdir().unwrap();
+        let home = root.path().join("home");
+        let backups = root.path().join("backups");
+        let settings_path = home.join(".claude").join("settings.json");
+        fs::create_dir_all(settings_path.parent().unwrap()).unwrap();
+        let original = r#"{"env":{"ANTHROPIC_API_KEY":"old"},"theme":"dark"}"#;
+        fs::write(&settings_path, original).unwrap();
+
+        let plan = plan_preset_in(&home, CliTool::ClaudeCode, &options()).unwrap();
+        assert!(apply_plan_in(&backups, &plan).unwrap().is_some());
+        let written: Value =
+            serde_json::from_str(&fs::read_to_string(&settings_path).unwrap()).unwrap();
+        assert_eq!(written["theme"], "dark");
+        assert_eq!(
+            written["env"]["ANTHROPIC_BASE_URL"],
+            "http://127.0.0.1:8999"
+        );
+        assert_eq!(written["env"]["ANTHROPIC_AUTH_TOKEN"], "sk-lime");
+        assert!(written["env"].get("ANTHROPIC_API_KEY").is_none());
+        assert_eq!(
+            written["env"]["ANTHROPIC_DEFAULT_HAIKU_MODEL"],
+            "claude-haiku-4-5"
+        );
+
+        // Codex 配置原本不存在，回滚时删除
+        let plan = plan_preset_in(&home, CliTool::CodexCli, &options()).unwrap();
+        assert_eq!(plan.env[0].name, CODEX_KEY_ENV);
+        apply_plan_in(&backups, &plan).unwrap();
+        let codex_path = home.join(".codex").join("config.toml");
+        assert!(codex_path.exists());
+
+        assert_eq!(rollback_in(&backups, CliTool::CodexCli).unwrap().len(), 1);
+        assert!(!codex_path.exists());
+        rollback_in(&backups, CliTool::ClaudeCode).unwrap();
+        assert_eq!(fs::read_to_string(&settings_path).unwrap(), original);
+        assert!(rollback_in(&backups, CliTool::ClaudeCode).is_err());
+
+        let plan = plan_preset_in(&home, CliTool::Cline, &options()).unwrap();
+        assert!(plan.files.is_empty());
+        assert_eq!(apply_plan_in(&backups, &plan).unwrap(), None);
+    }
+}
@@ -0,0 +1,604 @@
+//! 连接诊断（Connection Doctor）
+//!
+//! 端到端检查 DNS 解析、上游可达性、TLS 握手、时钟偏差、Token 有效性与端口可绑定性，
+//! 生成结构化报告，可导出为 JSON 或 Markdown 附在问题反馈中。
+//! 报告中不包含任何密钥明文。
+
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 单个上游请求的超时
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+/// 时钟偏差超过该值给出警告（秒）
+const CLOCK_SKEW_WARN_SECS: i64 = 30;
+/// 时钟偏差超过该值判定失败（秒），AWS SigV4 允许的最大偏差为 5 分钟
+const CLOCK_SKEW_FAIL_SECS: i64 = 300;
+
+/// 检查结果
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticStatus {
+    Pass,
+    Warn,
+    Fail,
+    Skipped,
+}
+
+impl DiagnosticStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pass => "pass",
+            Self::Warn => "warn",
+            Self::Fail => "fail",
+            Self::Skipped => "skipped",
+        }
+    }
+}
+
+/// 检查类别
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticCategory {
+    Dns,
+    Reachability,
+    Tls,
+    ClockSkew,
+    Token,
+    Port,
+}
+
+/// 单项检查
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DiagnosticCheck {
+    pub category: DiagnosticCategory,
+    /// 检查对象，如上游主机名、凭证名称或监听地址
+    pub target: String,
+    pub status: DiagnosticStatus,
+    pub message: String,
+    pub duration_ms: u64,
+}
+
+impl DiagnosticCheck {
+    fn new(
+        category: DiagnosticCategory,
+        target: impl Into<String>,
+        status: DiagnosticStatus,
+        message: impl Into<String>,
+        started: Instant,
+    ) -> Self {
+        Self {
+            category,
+            target: target.into(),
+            status,
+            message: message.into(),
+            duration_ms: started.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+/// 各状态的数量
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DiagnosticSummary {
+    pub pass: usize,
+    pub warn: usize,
+    pub fail: usize,
+    pub skipped: usize,
+}
+
+/// 诊断报告
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DiagnosticReport {
+    pub generated_at: DateTime<Utc>,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    /// 是否配置了全局代理（不含代理地址）
+    pub proxy_configured: bool,
+    pub summary: DiagnosticSummary,
+    pub checks: Vec<DiagnosticCheck>,
+}
+
+impl DiagnosticReport {
+    pub fn new(proxy_configured: bool, checks: Vec<DiagnosticCheck>) -> Self {
+        let mut summary = DiagnosticSummary::default();
+        for check in &checks {
+            match check.status {
+                DiagnosticStatus::Pass => summary.pass += 1,
+                DiagnosticStatus::Warn => summary.warn += 1,
+                DiagnosticStatus::Fail => summary.fail += 1,
+                DiagnosticStatus::Skipped => summary.skipped += 1,
+            }
+        }
+        Self {
+            generated_at: Utc::now(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            proxy_configured,
+            summary,
+            checks,
+        }
+    }
+
+    /// 渲染为 Markdown（便于粘贴到 Issue）
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "## Lime 连接诊断报告\n\n- 时间：{}\n- 版本：{}（{} / {}）\n- 全局代理：{}\n- 结果：{} 通过 / {} 警告 / {} 失败 / {} 跳过\n\n| 类别 | 对象 | 结果 | 说明 | 耗时 |\n| --- | --- | --- | --- | --- |\n",
+            self.generated_at.to_rfc3339(),
+            self.app_version,
+            self.os,
+            self.arch,
+            if self.proxy_configured { "已配置" } else { "未配置" },
+            self.summary.pass,
+            self.summary.warn,
+            self.summary.fail,
+            self.summary.skipped,
+        );
+        for check in &self.checks {
+            let category = serde_json::to_value(check.category)
+                .ok()
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} ms |\n",
+                category,
+                check.target.replace('|', "\\|"),
+                check.status.as_str(),
+                check.message.replace('|', "\\|").replace('\n', " "),
+                check.duration_ms
+            ));
+        }
+        out
+    }
+}
+
+/// 待检查的上游
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpstreamTarget {
+    /// Provider 名称（用于展示）
+    pub provider: String,
+    pub url: String,
+}
+
+/// 凭证 Token 状态（由调用方从凭证池读取）
+#[derive(Debug, Clone)]
+pub struct TokenProbe {
+    pub name: String,
+    pub has_access_token: bool,
+    pub has_refresh_token: bool,
+    pub is_token_valid: bool,
+    pub expiry_info: Option<String>,
+}
+
+/// 诊断输入
+#[derive(Debug, Clone, Default)]
+pub struct DoctorOptions {
+    pub listen_host: String,
+    pub listen_port: u16,
+    /// 本应用正在监听的端口（视为可绑定）
+    pub running_port: Option<u16>,
+    pub proxy_url: Option<String>,
+    pub upstreams: Vec<UpstreamTarget>,
+    pub tokens: Vec<TokenProbe>,
+}
+
+/// 运行全部检查
+pub async fn run_doctor(options: DoctorOptions) -> DiagnosticReport {
+    let mut checks = vec![check_port(
+        &options.listen_host,
+        options.listen_port,
+        options.running_port,
+    )];
+
+    let client = build_client(options.proxy_url.as_deref());
+    let mut clock_checked = false;
+    for upstream in &options.upstreams {
+        let Some(host) = url::Url::parse(&upstream.url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+        else {
+            checks.push(DiagnosticCheck::new(
+                DiagnosticCategory::Dns,
+                &upstream.url,
+                DiagnosticStatus::Fail,
+                format!("{} 的上游地址无效", upstream.provider),
+                Instant::now(),
+            ));
+            continue;
+        };
+
+        // 配置了代理时由代理解析域名，本机解析失败不代表不可用
+        let dns = check_dns(&host).await;
+        let dns_failed = dns.status == DiagnosticStatus::Fail;
+        checks.push(if dns_failed && options.proxy_url.is_some() {
+            DiagnosticCheck {
+                status: DiagnosticStatus::Warn,
+                message: format!("{}（已配置代理，由代理解析）", dns.message),
+                ..dns
+            }
+        } else {
+            dns
+        });
+        if dns_failed && options.proxy_url.is_none() {
+            for category in [DiagnosticCategory::Reachability, DiagnosticCategory::Tls] {
+                checks.push(DiagnosticCheck::new(
+                    category,
+                    &host,
+                    DiagnosticStatus::Skipped,
+                    "DNS 解析失败，跳过",
+                    Instant::now(),
+                ));
+            }
+            continue;
+        }
+
+        let Some(client) = client.as_ref() else {
+            checks.push(DiagnosticCheck::new(
+                DiagnosticCategory::Reachability,
+                &host,
+                DiagnosticStatus::Fail,
+                "无法创建 HTTP 客户端（代理地址无效？）",
+                Instant::now(),
+            ));
+            continue;
+        };
+        let probe = probe_upstream(client, &upstream.url, &host).await;
+        checks.extend(probe.checks);
+        if !clock_checked {
+            if let Some(server_date) = probe.server_date {
+                checks.push(check_clock_skew(&host, server_date, Utc::now()));
+                clock_checked = true;
+            }
+        }
+    }
+    if !clock_checked {
+        checks.push(DiagnosticCheck::new(
+            DiagnosticCategory::ClockSkew,
+            "system",
+            DiagnosticStatus::Skipped,
+            "没有可用的上游响应时间，跳过",
+            Instant::now(),
+        ));
+    }
+
+    checks.extend(options.tokens.iter().map(check_token));
+    DiagnosticReport::new(options.proxy_url.is_some(), checks)
+}
+
+fn build_client(proxy_url: Option<&str>) -> Option<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .redirect(reqwest::redirect::Policy::none());
+    if let Some(proxy_url) = proxy_url.filter(|p| !p.trim().is_empty()) {
+        builder = builder.proxy(reqwest::Proxy::all(proxy_url).ok()?);
+    }
+    builder.build().ok()
+}
+
+/// 监听地址是否可绑定
+pub fn check_port(host: &str, port: u16, running_port: Option<u16>) -> DiagnosticCheck {
+    let started = Instant::now();
+    let target = format!("{host}:{port}");
+    if running_port == Some(port) {
+        return DiagnosticCheck::new(
+            DiagnosticCategory::Port,
+            target,
+            DiagnosticStatus::Pass,
+            "服务正在该端口监听",
+            started,
+        );
+    }
+    match TcpListener::bind((host, port)) {
+        Ok(_) => DiagnosticCheck::new(
+            DiagnosticCategory::Port,
+            target,
+            DiagnosticStatus::Pass,
+            "端口可绑定",
+            started,
+        ),
+        Err(e) => DiagnosticCheck::new(
+            DiagnosticCategory::Port,
+            target,
+            if running_port.is_some() {
+                // 服务已通过端口回退在其他端口运行
+                DiagnosticStatus::Warn
+            } else {
+                DiagnosticStatus::Fail
+            },
+            format!("端口无法绑定: {e}"),
+            started,
+        ),
+    }
+}
+
+async fn check_dns(host: &str) -> DiagnosticCheck {
+    let started = Instant::now();
+    let lookup = tokio::time::timeout(
+        Duration::from_secs(REQUEST_TIMEOUT_SECS),
+        tokio::net::lookup_host((host, 443)),
+    )
+    .await;
+    match lookup {
+        Ok(Ok(addrs)) => {
+            let addrs: Vec<String> = addrs.map(|addr| addr.ip().to_string()).collect();
+            if addrs.is_empty() {
+                DiagnosticCheck::new(
+                    DiagnosticCategory::Dns,
+                    host,
+                    DiagnosticStatus::Fail,
+                    "未解析到任何地址",
+                    started,
+                )
+            } else {
+                DiagnosticCheck::new(
+                    DiagnosticCategory::Dns,
+                    host,
+                    DiagnosticStatus::Pass,
+                    format!("解析到 {}", addrs.join(", ")),
+                    started,
+                )
+            }
+        }
+        Ok(Err(e)) => DiagnosticCheck::new(
+            DiagnosticCategory::Dns,
+            host,
+            DiagnosticStatus::Fail,
+            format!("DNS 解析失败: {e}"),
+            started,
+        ),
+        Err(_) => DiagnosticCheck::new(
+            DiagnosticCategory::Dns,
+            host,
+            DiagnosticStatus::Fail,
+            "DNS 解析超时",
+            started,
+        ),
+    }
+}
+
+struct UpstreamProbe {
+    checks: Vec<DiagnosticCheck>,
+    server_date: Option<DateTime<Utc>>,
+}
+
+/// 请求上游一次，同时得出可达性与 TLS 结果（任何 HTTP 状态码都视为可达）
+async fn probe_upstream(client: &reqwest::Client, url: &str, host: &str) -> UpstreamProbe {
+    let started = Instant::now();
+    let is_https = url.starts_with("https://");
+    match client.get(url).send().await {
+        Ok(response) => {
+            let server_date = response
+                .headers()
+                .get(reqwest::header::DATE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+                .map(|d| d.with_timezone(&Utc));
+            let reachability = DiagnosticCheck::new(
+                DiagnosticCategory::Reachability,
+                host,
+                DiagnosticStatus::Pass,
+                format!("HTTP {}", response.status().as_u16()),
+                started,
+            );
+            let tls = if is_https {
+                DiagnosticCheck::new(
+                    DiagnosticCategory::Tls,
+                    host,
+                    DiagnosticStatus::Pass,
+                    format!("握手成功（{:?}）", response.version()),
+                    started,
+                )
+            } else {
+                DiagnosticCheck::new(
+                    DiagnosticCategory::Tls,
+                    host,
+                    DiagnosticStatus::Skipped,
+                    "非 HTTPS 地址",
+                    started,
+                )
+            };
+            UpstreamProbe {
+                checks: vec![reachability, tls],
+                server_date,
+            }
+        }
+        Err(e) => {
+            let detail = error_chain(&e);
+            let tls_error = is_tls_error(&detail);
+            let message = if e.is_timeout() {
+                "请求超时".to_string()
+            } else {
+                detail.clone()
+            };
+            let reachability = DiagnosticCheck::new(
+                DiagnosticCategory::Reachability,
+                host,
+                if tls_error {
+                    // TCP 已连通，失败发生在 TLS 阶段
+                    DiagnosticStatus::Pass
+                } else {
+                    DiagnosticStatus::Fail
+                },
+                if tls_error {
+                    "TCP 连接成功".to_string()
+                } else {
+                    message.clone()
+                },
+                started,
+            );
+            let tls = DiagnosticCheck::new(
+                DiagnosticCategory::Tls,
+                host,
+                if tls_error {
+                    DiagnosticStatus::Fail
+                } else {
+                    DiagnosticStatus::Skipped
+                },
+                if tls_error {
+                    format!("TLS 握手失败（可能被中间人代理或证书过期）: {message}")
+                } else {
+                    "连接失败，跳过".to_string()
+                },
+                started,
+            );
+            UpstreamProbe {
+                checks: vec![reachability, tls],
+                server_date: None,
+            }
+        }
+    }
+}
+
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut parts = vec![error.to_string()];
+    let mut source = error.source();
+    while let Some(inner) = source {
+        parts.push(inner.to_string());
+        source = inner.source();
+    }
+    parts.join(": ")
+}
+
+fn is_tls_error(detail: &str) -> bool {
+    let detail = detail.to_ascii_lowercase();
+    ["certificate", "tls", "ssl", "handshake", "x509"]
+        .iter()
+        .any(|needle| detail.contains(needle))
+}
+
+/// 本机时间与上游 `Date` 响应头的偏差
+pub fn check_clock_skew(
+    host: &str,
+    server_date: DateTime<Utc>,
+    local: DateTime<Utc>,
+) -> DiagnosticCheck {
+    let started = Instant::now();
+    let skew = (local - server_date).num_seconds();
+    let status = match skew.abs() {
+        s if s > CLOCK_SKEW_FAIL_SECS => DiagnosticStatus::Fail,
+        s if s > CLOCK_SKEW_WARN_SECS => DiagnosticStatus::Warn,
+        _ => DiagnosticStatus::Pass,
+    };
+    let message = if status == DiagnosticStatus::Pass {
+        format!("本机时间与 {host} 相差 {skew} 秒")
+    } else {
+        format!("本机时间与 {host} 相差 {skew} 秒，请同步系统时间（签名与 Token 校验可能失败）")
+    };
+    DiagnosticCheck::new(
+        DiagnosticCategory::ClockSkew,
+        host,
+        status,
+        message,
+        started,
+    )
+}
+
+/// 凭证 Token 是否可用：有效，或已过期但可刷新
+pub fn check_token(probe: &TokenProbe) -> DiagnosticCheck {
+    let started = Instant::now();
+    let expiry = probe
+        .expiry_info
+        .as_deref()
+        .map(|info| format!("（{info}）"))
+        .unwrap_or_default();
+    let (status, message) = if probe.is_token_valid {
+        (DiagnosticStatus::Pass, format!("Token 有效{expiry}"))
+    } else if probe.has_refresh_token {
+        (
+            DiagnosticStatus::Warn,
+            format!("Token 已过期，将在下次请求时刷新{expiry}"),
+        )
+    } else if probe.has_access_token {
+        (
+            DiagnosticStatus::Fail,
+            format!("Token 已过期且没有 refresh_token，请重新登录{expiry}"),
+        )
+    } else {
+        (
+            DiagnosticStatus::Fail,
+            "凭证文件中没有 Token，请重新登录".to_string(),
+        )
+    };
+    DiagnosticCheck::new(
+        DiagnosticCategory::Token,
+        &probe.name,
+        status,
+        message,
+        started,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    #[test]
+    fn test_clock_skew_thresholds() {
+        let now = Utc::now();
+        assert_eq!(
+            check_clock_skew("h", now - ChronoDuration::seconds(5), now).status,
+            DiagnosticStatus::Pass
+        );
+        assert_eq!(
+            check_clock_skew("h", now + ChronoDuration::seconds(90), now).status,
+            DiagnosticStatus::Warn
+        );
+        assert_eq!(
+            check_clock_skew("h", now - ChronoDuration::minutes(10), now).status,
+            DiagnosticStatus::Fail
+        );
+    }
+
+    #[test]
+    fn test_token_check_distinguishes_refreshable() {
+        let probe = |valid, access, refresh| TokenProbe {
+            name: "kiro-1".to_string(),
+            has_access_token: access,
+            has_refresh_token: refresh,
+            is_token_valid: valid,
+            expiry_info: None,
+        };
+        assert_eq!(
+            check_token(&probe(true, true, true)).status,
+            DiagnosticStatus::Pass
+        );
+        assert_eq!(
+            check_token(&probe(false, true, true)).status,
+            DiagnosticStatus::Warn
+        );
+        assert_eq!(
+            check_token(&probe(false, true, false)).status,
+            DiagnosticStatus::Fail
+        );
+    }
+
+    #[test]
+    fn test_port_check_and_report_summary() {
+        let busy = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = busy.local_addr().unwrap().port();
+
+        let busy_check = check_port("127.0.0.1", port, None);
+        assert_eq!(busy_check.status, DiagnosticStatus::Fail);
+        let own_check = check_port("127.0.0.1", port, Some(port));
+        assert_eq!(own_check.status, DiagnosticStatus::Pass);
+
+        let report = DiagnosticReport::new(false, vec![busy_check, own_check]);
+        assert_eq!(report.summary.pass, 1);
+        assert_eq!(report.summary.fail, 1);
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("| port | 127.0.0.1:"));
+        assert!(markdown.contains("1 通过 / 0 警告 / 1 失败"));
+    }
+
+    #[test]
+    fn test_tls_error_detection() {
+        assert!(is_tls_error(
+            "error sending request: invalid peer certificate: UnknownIssuer"
+        ));
+        assert!(!is_tls_error("error sending request: connection refused"));
+    }
+}
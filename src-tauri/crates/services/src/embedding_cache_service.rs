@@ -0,0 +1,307 @@
+//! 嵌入缓存（持久化）
+//!
+//! 以 (Provider + 模型, 规范化文本哈希) 为键持久化 `/v1/embeddings` 的向量结果，
+//! 不同 Provider 的同名模型可能返回不同的向量，分开缓存：
+//! - 请求进入时：逐条规范化输入文本，先查缓存，只把未命中的文本发往上游
+//! - 同一批次内重复的文本只请求一次，再按原始顺序回填
+//! - 上游返回后：将新向量写入数据库，供后续请求复用
+
+use std::collections::HashMap;
+
+use lime_core::database::dao::embedding_cache::EmbeddingCacheDao;
+use lime_core::database::DbConnection;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingCacheConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_max_entries")]
+    pub max_entries: usize,
+    #[serde(default = "default_ttl_days")]
+    pub ttl_days: u32,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+fn default_max_entries() -> usize {
+    100_000
+}
+fn default_ttl_days() -> u32 {
+    30
+}
+
+impl Default for EmbeddingCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            max_entries: default_max_entries(),
+            ttl_days: default_ttl_days(),
+        }
+    }
+}
+
+impl From<&lime_core::config::EmbeddingCacheSettings> for EmbeddingCacheConfig {
+    fn from(settings: &lime_core::config::EmbeddingCacheSettings) -> Self {
+        Self {
+            enabled: settings.enabled,
+            max_entries: settings.max_entries,
+            ttl_days: settings.ttl_days,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EmbeddingCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub writes: u64,
+    /// 同一批次内因文本重复而省去的上游条目数
+    pub deduplicated: u64,
+}
+
+/// 一次批量查询的结果
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddingCacheLookup {
+    /// 与输入等长，命中的位置为 Some
+    pub vectors: Vec<Option<Vec<f32>>>,
+    /// 需要请求上游的去重文本（保持首次出现顺序）
+    pub pending_texts: Vec<String>,
+    /// 每个输入位置对应的 `pending_texts` 下标（命中缓存的位置为 None）
+    pub pending_index: Vec<Option<usize>>,
+}
+
+pub struct EmbeddingCacheStore {
+    config: EmbeddingCacheConfig,
+    db: Option<DbConnection>,
+    counters: Mutex<EmbeddingCacheStats>,
+}
+
+/// 规范化嵌入文本：去除首尾空白并将连续空白折叠为单个空格
+pub fn normalize_embedding_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 计算规范化文本的哈希
+pub fn embedding_text_hash(text: &str) -> String {
+    hex::encode(Sha256::digest(normalize_embedding_text(text).as_bytes()))
+}
+
+/// 缓存使用的模型键（按 Provider 区分；指定 dimensions 时向量维度不同，也需要分开缓存）
+pub fn embedding_model_key(provider: &str, model: &str, dimensions: Option<u32>) -> String {
+    match dimensions {
+        Some(d) => format!("{provider}:{model}#{d}"),
+        None => format!("{provider}:{model}"),
+    }
+}
+
+impl EmbeddingCacheStore {
+    pub fn new(config: EmbeddingCacheConfig, db: Option<DbConnection>) -> Self {
+        Self {
+            config,
+            db,
+            counters: Mutex::new(EmbeddingCacheStats::default()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled && self.db.is_some()
+    }
+
+    pub fn config(&self) -> EmbeddingCacheConfig {
+        self.config.clone()
+    }
+
+    /// 批量查询缓存，并计算需要请求上游的去重文本
+    pub fn lookup(&self, model_key: &str, texts: &[String]) -> EmbeddingCacheLookup {
+        let mut result = EmbeddingCacheLookup {
+            vectors: Vec::with_capacity(texts.len()),
+            pending_texts: Vec::new(),
+            pending_index: Vec::with_capacity(texts.len()),
+        };
+        // 文本哈希 -> `pending_texts` 下标
+        let mut pending_hashes: HashMap<String, usize> = HashMap::new();
+        let mut counters = EmbeddingCacheStats::default();
+
+        for text in texts {
+            let hash = embedding_text_hash(text);
+            let cached = if self.is_enabled() {
+                self.get(model_key, &hash)
+            } else {
+                None
+            };
+
+            match cached {
+                Some(vector) => {
+                    counters.hits += 1;
+                    result.vectors.push(Some(vector));
+                    result.pending_index.push(None);
+                }
+                None => {
+                    result.vectors.push(None);
+                    if let Some(&pos) = pending_hashes.get(&hash) {
+                        counters.deduplicated += 1;
+                        result.pending_index.push(Some(pos));
+                    } else {
+                        counters.misses += 1;
+                        let pos = result.pending_texts.len();
+                        pending_hashes.insert(hash, pos);
+                        result.pending_texts.push(text.clone());
+                        result.pending_index.push(Some(pos));
+                    }
+                }
+            }
+        }
+
+        let mut total = self.counters.lock();
+        total.hits += counters.hits;
+        total.misses += counters.misses;
+        total.deduplicated += counters.deduplicated;
+        result
+    }
+
+    /// 将上游返回的向量回填到查询结果中，并写入缓存
+    pub fn fill(
+        &self,
+        model_key: &str,
+        lookup: &mut EmbeddingCacheLookup,
+        upstream_vectors: Vec<Vec<f32>>,
+    ) -> Result<(), String> {
+        if upstream_vectors.len() != lookup.pending_texts.len() {
+            return Err(format!(
+                "上游返回 {} 条向量，期望 {} 条",
+                upstream_vectors.len(),
+                lookup.pending_texts.len()
+            ));
+        }
+
+        for (text, vector) in lookup.pending_texts.iter().zip(upstream_vectors.iter()) {
+            self.set(model_key, &embedding_text_hash(text), vector);
+        }
+
+        for (slot, pending) in lookup.vectors.iter_mut().zip(lookup.pending_index.iter()) {
+            if let Some(idx) = pending {
+                *slot = Some(upstream_vectors[*idx].clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get(&self, model_key: &str, hash: &str) -> Option<Vec<f32>> {
+        let db = self.db.as_ref()?;
+        let conn = lime_core::database::lock_db(db).ok()?;
+        match EmbeddingCacheDao::get(&conn, model_key, hash) {
+            Ok(vector) => vector,
+            Err(e) => {
+                tracing::warn!("[EMBEDDING_CACHE] 查询缓存失败: {}", e);
+                None
+            }
+        }
+    }
+
+    fn set(&self, model_key: &str, hash: &str, vector: &[f32]) {
+        if !self.is_enabled() || vector.is_empty() {
+            return;
+        }
+        let Some(db) = self.db.as_ref() else {
+            return;
+        };
+        let Ok(conn) = lime_core::database::lock_db(db) else {
+            return;
+        };
+        match EmbeddingCacheDao::upsert(&conn, model_key, hash, vector) {
+            Ok(()) => self.counters.lock().writes += 1,
+            Err(e) => tracing::warn!("[EMBEDDING_CACHE] 写入缓存失败: {}", e),
+        }
+    }
+
+    /// 按配置清理过期与超量条目
+    pub fn prune(&self) -> usize {
+        let Some(db) = self.db.as_ref() else {
+            return 0;
+        };
+        let Ok(conn) = lime_core::database::lock_db(db) else {
+            return 0;
+        };
+        EmbeddingCacheDao::prune(&conn, self.config.max_entries, self.config.ttl_days)
+            .unwrap_or_else(|e| {
+                tracing::warn!("[EMBEDDING_CACHE] 清理缓存失败: {}", e);
+                0
+            })
+    }
+
+    pub fn stats(&self) -> EmbeddingCacheStats {
+        *self.counters.lock()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex as StdMutex};
+
+    fn create_store() -> EmbeddingCacheStore {
+        let conn = rusqlite::Connection::open_in_memory().expect("创建内存数据库失败");
+        lime_core::database::schema::create_tables(&conn).expect("创建表结构失败");
+        EmbeddingCacheStore::new(
+            EmbeddingCacheConfig::default(),
+            Some(Arc::new(StdMutex::new(conn))),
+        )
+    }
+
+    #[test]
+    fn should_normalize_whitespace_before_hashing() {
+        assert_eq!(
+            normalize_embedding_text("  hello \n\t world "),
+            "hello world"
+        );
+        assert_eq!(
+            embedding_text_hash("hello world"),
+            embedding_text_hash(" hello   world\n")
+        );
+    }
+
+    #[test]
+    fn should_dedup_identical_texts_within_batch() {
+        let store = create_store();
+        let texts = vec!["a".to_string(), "b".to_string(), " a ".to_string()];
+        let mut lookup = store.lookup("m", &texts);
+
+        assert_eq!(lookup.pending_texts, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(lookup.pending_index, vec![Some(0), Some(1), Some(0)]);
+
+        store
+            .fill("m", &mut lookup, vec![vec![1.0], vec![2.0]])
+            .expect("回填应成功");
+        assert_eq!(
+            lookup.vectors,
+            vec![Some(vec![1.0]), Some(vec![2.0]), Some(vec![1.0])]
+        );
+        assert_eq!(store.stats().deduplicated, 1);
+    }
+
+    #[test]
+    fn should_hit_cache_on_second_lookup() {
+        let store = create_store();
+        let texts = vec!["chunk".to_string()];
+        let mut first = store.lookup("m", &texts);
+        store
+            .fill("m", &mut first, vec![vec![0.25, 0.5]])
+            .expect("回填应成功");
+
+        let second = store.lookup("m", &texts);
+        assert!(second.pending_texts.is_empty());
+        assert_eq!(second.vectors, vec![Some(vec![0.25, 0.5])]);
+
+        let other_model = store.lookup(&embedding_model_key("openai", "m", Some(256)), &texts);
+        assert_eq!(other_model.pending_texts.len(), 1);
+        assert_ne!(
+            embedding_model_key("openai", "m", None),
+            embedding_model_key("azure", "m", None)
+        );
+    }
+}
@@ -30,9 +30,18 @@
 //! - `prompt_sync` - Prompt 同步
 //! - `skill_service` - 技能服务
 //! - `backup_service` - 备份服务
+//! - `bench_service` - 压测服务
+//! - `cli_preset_service` - CLI 工具接入预设
+//! - `cloud_backup_service` - 云备份服务
+//! - `connection_doctor_service` - 连接诊断
+//! - `embedding_cache_service` - 嵌入缓存
+//! - `local_resource_service` - 本地推理服务资源监控
 //! - `material_service` - 素材服务
 //! - `persona_service` - 人设服务
+//! - `pool_insights_service` - 凭证池洞察
+//! - `scenario_service` - 声明式测试场景
 //! - `template_service` - 模板服务
+//! - `warm_pool_service` - 本地模型预热
 //! - `model_registry_service` - 模型注册服务
 //! - `model_service` - 模型服务
 //! - `prompt_service` - Prompt 服务
@@ -73,12 +82,20 @@ pub mod skill_service;
 // 依赖 database + models 的服务
 pub mod aster_session_store;
 pub mod backup_service;
+pub mod bench_service;
+pub mod cli_preset_service;
+pub mod cloud_backup_service;
+pub mod connection_doctor_service;
+pub mod embedding_cache_service;
+pub mod local_resource_service;
 pub mod material_service;
 pub mod mcp_service;
 pub mod model_registry_service;
 pub mod model_service;
 pub mod persona_service;
+pub mod pool_insights_service;
 pub mod prompt_service;
+pub mod scenario_service;
 pub mod switch;
 pub mod template_service;
 
@@ -99,3 +116,4 @@ pub mod provider_pool_service;
 pub mod provider_type_mapping;
 pub mod token_cache_service;
 pub mod video_generation_service;
+pub mod warm_pool_service;
@@ -0,0 +1,302 @@
+//! 本地推理服务资源监控
+//!
+//! 对已启用的本地 Provider（Ollama、llama.cpp 及其他监听本机的 OpenAI 兼容服务）采集
+//! 进程 CPU/内存、已加载模型的显存与排队情况，并通过 `nvidia-smi` 读取本机 GPU 占用，
+//! 随仪表盘统计快照推送给前端，便于判断本地推理是否为瓶颈。
+
+use std::time::Duration;
+
+use lime_core::app_events::{GpuUsage, LocalProviderResources};
+use lime_core::app_utils::is_loopback_host;
+use lime_core::database::dao::api_key_provider::{ApiProviderType, ProviderWithKeys};
+use serde::Deserialize;
+use sysinfo::{ProcessesToUpdate, System};
+use tokio::sync::Mutex;
+
+/// 探测本地服务接口的超时
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Ollama 默认端口
+const OLLAMA_PORT: u16 = 11434;
+
+/// 本地服务类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalProviderKind {
+    Ollama,
+    /// 监听本机的 OpenAI 兼容服务，提供 `/metrics` 时按 llama.cpp 解析
+    OpenAiCompatible,
+}
+
+/// 待监控的本地服务
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalTarget {
+    pub provider_id: String,
+    pub name: String,
+    pub kind: LocalProviderKind,
+    /// 去掉路径后的服务地址，如 `http://localhost:11434`
+    pub base_url: String,
+}
+
+/// 从 API Key Provider 中筛选已启用且指向本机的服务
+pub fn local_targets(providers: &[ProviderWithKeys]) -> Vec<LocalTarget> {
+    providers
+        .iter()
+        .map(|p| &p.provider)
+        .filter(|p| p.enabled)
+        .filter_map(|p| {
+            let url = url::Url::parse(p.api_host.trim()).ok()?;
+            let host = url
+                .host_str()?
+                .trim_start_matches('[')
+                .trim_end_matches(']');
+            if !is_loopback_host(host) {
+                return None;
+            }
+            let port = url.port_or_known_default()?;
+            let kind = if p.provider_type == ApiProviderType::Ollama || port == OLLAMA_PORT {
+                LocalProviderKind::Ollama
+            } else {
+                LocalProviderKind::OpenAiCompatible
+            };
+            Some(LocalTarget {
+                provider_id: p.id.clone(),
+                name: p.name.clone(),
+                kind,
+                base_url: format!("{}://{}:{}", url.scheme(), url.host_str()?, port),
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaPs {
+    #[serde(default)]
+    models: Vec<OllamaRunningModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaRunningModel {
+    name: String,
+    #[serde(default)]
+    size_vram: u64,
+}
+
+/// llama.cpp `/metrics` 中关心的指标
+#[derive(Debug, Default, PartialEq)]
+pub struct LlamaCppMetrics {
+    pub requests_processing: Option<u64>,
+    pub requests_deferred: Option<u64>,
+    pub kv_cache_usage: Option<f64>,
+}
+
+/// 解析 llama.cpp 的 Prometheus 指标文本，不含 llama.cpp 指标时返回 `None`
+pub fn parse_llamacpp_metrics(text: &str) -> Option<LlamaCppMetrics> {
+    let mut metrics = LlamaCppMetrics::default();
+    let mut found = false;
+    for line in text.lines().filter(|l| !l.starts_with('#')) {
+        let Some((name, value)) = line.split_once(char::is_whitespace) else {
+            continue;
+        };
+        let Some(name) = name.strip_prefix("llamacpp:") else {
+            continue;
+        };
+        let Ok(value) = value.trim().parse::<f64>() else {
+            continue;
+        };
+        found = true;
+        match name {
+            "requests_processing" => metrics.requests_processing = Some(value as u64),
+            "requests_deferred" => metrics.requests_deferred = Some(value as u64),
+            "kv_cache_usage_ratio" => metrics.kv_cache_usage = Some(value),
+            _ => {}
+        }
+    }
+    found.then_some(metrics)
+}
+
+/// 解析 `nvidia-smi --query-gpu=index,name,memory.used,memory.total,utilization.gpu
+/// --format=csv,noheader,nounits` 的输出（显存单位为 MiB）
+pub fn parse_nvidia_smi(text: &str) -> Vec<GpuUsage> {
+    text.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [index, name, used, total, utilization] = fields.as_slice() else {
+                return None;
+            };
+            Some(GpuUsage {
+                index: index.parse().ok()?,
+                name: name.to_string(),
+                memory_used_bytes: used.parse::<u64>().ok()? * 1024 * 1024,
+                memory_total_bytes: total.parse::<u64>().ok()? * 1024 * 1024,
+                utilization_percent: utilization.parse().unwrap_or(0.0),
+            })
+        })
+        .collect()
+}
+
+/// 进程名是否属于该类型的本地服务
+fn process_matches(kind: LocalProviderKind, name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    match kind {
+        // 包含 `ollama serve` 及其加载模型的 runner 子进程
+        LocalProviderKind::Ollama => name.starts_with("ollama"),
+        LocalProviderKind::OpenAiCompatible => name.starts_with("llama-server"),
+    }
+}
+
+/// 本地服务资源监控器
+pub struct LocalResourceMonitor {
+    client: reqwest::Client,
+    /// 保留进程表以便计算两次刷新之间的 CPU 占用
+    system: Mutex<System>,
+}
+
+impl LocalResourceMonitor {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(PROBE_TIMEOUT)
+                .no_proxy()
+                .build()
+                .unwrap_or_default(),
+            system: Mutex::new(System::new()),
+        }
+    }
+
+    /// 采集各本地服务与本机 GPU 的资源占用
+    pub async fn collect(
+        &self,
+        targets: &[LocalTarget],
+    ) -> (Vec<LocalProviderResources>, Vec<GpuUsage>) {
+        if targets.is_empty() {
+            return (Vec::new(), Vec::new());
+        }
+
+        let mut resources = Vec::with_capacity(targets.len());
+        for target in targets {
+            resources.push(self.probe(target).await);
+        }
+
+        {
+            let mut system = self.system.lock().await;
+            system.refresh_processes(ProcessesToUpdate::All, true);
+            for (target, resource) in targets.iter().zip(resources.iter_mut()) {
+                let mut matched = false;
+                let mut cpu = 0.0f32;
+                let mut memory = 0u64;
+                for process in system.processes().values() {
+                    if process_matches(target.kind, &process.name().to_string_lossy()) {
+                        matched = true;
+                        cpu += process.cpu_usage();
+                        memory += process.memory();
+                    }
+                }
+                if matched {
+                    resource.cpu_percent = Some(cpu);
+                    resource.memory_bytes = Some(memory);
+                }
+            }
+        }
+
+        (resources, query_gpus().await)
+    }
+
+    async fn probe(&self, target: &LocalTarget) -> LocalProviderResources {
+        let mut resource = LocalProviderResources {
+            provider_id: target.provider_id.clone(),
+            name: target.name.clone(),
+            kind: "openai_compatible".to_string(),
+            ..Default::default()
+        };
+        match target.kind {
+            LocalProviderKind::Ollama => {
+                resource.kind = "ollama".to_string();
+                let url = format!("{}/api/ps", target.base_url);
+                let Ok(response) = self.client.get(&url).send().await else {
+                    return resource;
+                };
+                resource.reachable = true;
+                if let Ok(ps) = response.json::<OllamaPs>().await {
+                    resource.vram_bytes = Some(ps.models.iter().map(|m| m.size_vram).sum());
+                    resource.loaded_models = ps.models.into_iter().map(|m| m.name).collect();
+                }
+            }
+            LocalProviderKind::OpenAiCompatible => {
+                let url = format!("{}/metrics", target.base_url);
+                let Ok(response) = self.client.get(&url).send().await else {
+                    return resource;
+                };
+                resource.reachable = true;
+                let metrics = match response.error_for_status() {
+                    Ok(response) => response
+                        .text()
+                        .await
+                        .ok()
+                        .and_then(|text| parse_llamacpp_metrics(&text)),
+                    Err(_) => None,
+                };
+                if let Some(metrics) = metrics {
+                    resource.kind = "llama_cpp".to_string();
+                    resource.requests_processing = metrics.requests_processing;
+                    resource.requests_deferred = metrics.requests_deferred;
+                    resource.kv_cache_usage = metrics.kv_cache_usage;
+                }
+            }
+        }
+        resource
+    }
+}
+
+impl Default for LocalResourceMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 通过 `nvidia-smi` 读取 GPU 占用，未安装或执行失败时返回空
+async fn query_gpus() -> Vec<GpuUsage> {
+    let output = tokio::time::timeout(
+        PROBE_TIMEOUT,
+        tokio::process::Command::new("nvidia-smi")
+            .args([
+                "--query-gpu=index,name,memory.used,memory.total,utilization.gpu",
+                "--format=csv,noheader,nounits",
+            ])
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await;
+    match output {
+        Ok(Ok(output)) if output.status.success() => {
+            parse_nvidia_smi(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_llamacpp_metrics_and_nvidia_smi() {
+        let text = "# HELP llamacpp:kv_cache_usage_ratio KV-cache usage\n\
+                    llamacpp:kv_cache_usage_ratio 0.25\n\
+                    llamacpp:requests_processing 2\n\
+                    llamacpp:requests_deferred 3\n";
+        assert_eq!(
+            parse_llamacpp_metrics(text),
+            Some(LlamaCppMetrics {
+                requests_processing: Some(2),
+                requests_deferred: Some(3),
+                kv_cache_usage: Some(0.25),
+            })
+        );
+        assert_eq!(parse_llamacpp_metrics("process_cpu_seconds_total 1"), None);
+
+        let gpus = parse_nvidia_smi("0, NVIDIA GeForce RTX 4090, 8192, 24564, 87\nbad line\n");
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].memory_used_bytes, 8192 * 1024 * 1024);
+        assert_eq!(gpus[0].utilization_percent, 87.0);
+    }
+}
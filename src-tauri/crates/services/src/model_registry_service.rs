@@ -565,6 +565,68 @@ impl ModelRegistryService {
         Ok(())
     }
 
+    /// 按实测结果更新模型能力（供应商接入测试使用）
+    ///
+    /// 模型不在注册表中时以自定义来源新增。注册表从内嵌资源重新加载时会被覆盖。
+    pub async fn update_model_capabilities(
+        &self,
+        model_id: &str,
+        provider_id: &str,
+        update: impl FnOnce(&mut ModelCapabilities),
+    ) -> Result<ModelCapabilities, String> {
+        let mut cache = self.models_cache.write().await;
+        let index = match cache.iter().position(|m| m.id == model_id) {
+            Some(index) => index,
+            None => {
+                let mut model = EnhancedModelMetadata::new(
+                    model_id.to_string(),
+                    model_id.to_string(),
+                    provider_id.to_string(),
+                    provider_id.to_string(),
+                );
+                model.source = ModelSource::Custom;
+                cache.push(model);
+                cache.len() - 1
+            }
+        };
+        let model = &mut cache[index];
+        update(&mut model.capabilities);
+        model.updated_at = chrono::Utc::now().timestamp();
+
+        let conn = self.db.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO model_registry (
+                id, display_name, provider_id, provider_name, family, tier,
+                capabilities, pricing, limits, status, release_date, is_latest,
+                description, source, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                model.id,
+                model.display_name,
+                model.provider_id,
+                model.provider_name,
+                model.family,
+                model.tier.to_string(),
+                serde_json::to_string(&model.capabilities).unwrap_or_default(),
+                model
+                    .pricing
+                    .as_ref()
+                    .map(|p| serde_json::to_string(p).unwrap_or_default()),
+                serde_json::to_string(&model.limits).unwrap_or_default(),
+                model.status.to_string(),
+                model.release_date,
+                model.is_latest as i32,
+                model.description,
+                model.source.to_string(),
+                model.created_at,
+                model.updated_at,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+        Ok(model.capabilities.clone())
+    }
+
     /// 获取所有模型
     pub async fn get_all_models(&self) -> Vec<EnhancedModelMetadata> {
         self.models_cache.read().await.clone()
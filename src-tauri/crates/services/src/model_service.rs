@@ -2,7 +2,7 @@
 //!
 //! 提供统一的模型获取、缓存和查询接口，支持从不同 Provider 获取模型列表。
 
-use lime_core::database::dao::provider_pool::ProviderPoolDao;
+use lime_core::database::pool_storage::{lock_pool, pool_storage};
 use lime_core::database::DbConnection;
 use lime_core::models::provider_pool_model::{
     CredentialData, PoolProviderType, ProviderCredential,
@@ -209,7 +209,10 @@ impl ModelService {
             .client
             .get(&url)
             .header("x-api-key", api_key)
-            .header("anthropic-version", "2023-06-01")
+            .header(
+                "anthropic-version",
+                lime_providers::providers::claude_custom::anthropic_version(),
+            )
             .timeout(self.timeout)
             .send()
             .await
@@ -405,7 +408,8 @@ impl ModelService {
         let models_json = serde_json::to_string(&models).map_err(|e| e.to_string())?;
 
         conn.execute(
-            "UPDATE provider_pool_credentials SET supported_models = ?1, updated_at = ?2 WHERE uuid = ?3",
+            "UPDATE provider_pool_credentials SET supported_models = ?1, updated_at = ?2
+             WHERE uuid = ?3 AND deleted_at IS NULL",
             rusqlite::params![models_json, chrono::Utc::now().timestamp(), credential_uuid],
         )
         .map_err(|e| e.to_string())?;
@@ -422,7 +426,10 @@ impl ModelService {
         let conn = db.lock().map_err(|e| e.to_string())?;
 
         let mut stmt = conn
-            .prepare("SELECT supported_models FROM provider_pool_credentials WHERE uuid = ?1")
+            .prepare(
+                "SELECT supported_models FROM provider_pool_credentials
+                 WHERE uuid = ?1 AND deleted_at IS NULL",
+            )
             .map_err(|e| e.to_string())?;
 
         let models_json: Option<String> = stmt.query_row([credential_uuid], |row| row.get(0)).ok();
@@ -438,8 +445,8 @@ impl ModelService {
         &self,
         db: &DbConnection,
     ) -> Result<HashMap<String, Vec<String>>, String> {
-        let conn = db.lock().map_err(|e| e.to_string())?;
-        let credentials = ProviderPoolDao::get_all(&conn).map_err(|e| e.to_string())?;
+        let conn = lock_pool(db)?;
+        let credentials = pool_storage().get_all(&conn)?;
         drop(conn);
 
         let mut models_by_provider: HashMap<String, Vec<String>> = HashMap::new();
@@ -483,14 +490,14 @@ impl ModelService {
 mod tests {
     use super::*;
     use lime_core::database::dao::provider_pool::ProviderPoolDao;
-    use lime_core::database::schema;
+    use lime_core::database::schema_migrations::run_pending_migrations;
     use lime_core::models::provider_pool_model::{CredentialData, PoolProviderType};
     use rusqlite::Connection;
     use std::sync::{Arc, Mutex};
 
     fn setup_test_db() -> DbConnection {
         let conn = Connection::open_in_memory().expect("open in-memory db");
-        schema::create_tables(&conn).expect("create schema");
+        run_pending_migrations(&conn).expect("create schema");
         Arc::new(Mutex::new(conn))
     }
 
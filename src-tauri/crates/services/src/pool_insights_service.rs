@@ -0,0 +1,352 @@
+//! 凭证池洞察
+//!
+//! 根据凭证的使用次数、最后使用时间与错误记录，找出需要关注的凭证并给出扩缩容建议：
+//! - 从未被选中：启用且健康，但添加超过一天仍未使用（通常是模型黑名单或 Provider 配置有误）
+//! - 长期不健康：连续报错且近期没有成功使用
+//! - 闲置：同类凭证近期都在使用，只有它长时间未被选中
+//! - 饱和：近期被限流，或承担了远超平均水平的请求量
+//!
+//! 分析只读取凭证池中的统计字段，不发起任何请求。
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use lime_core::database::pool_storage::{lock_pool, pool_storage};
+use lime_core::database::DbConnection;
+use lime_core::models::provider_pool_model::ProviderCredential;
+use serde::{Deserialize, Serialize};
+
+/// 添加后多久仍未使用视为「从未被选中」
+const NEVER_SELECTED_GRACE_HOURS: i64 = 24;
+/// 多久未使用视为闲置
+const IDLE_DAYS: i64 = 7;
+/// 错误次数达到该值且近期无成功使用时视为长期不健康
+const UNHEALTHY_ERROR_COUNT: u32 = 5;
+/// 限流错误在多长时间内视为近期
+const SATURATION_WINDOW_HOURS: i64 = 24;
+/// 使用量超过同类平均值的倍数时视为承压过重
+const SATURATION_SHARE_FACTOR: f64 = 2.0;
+
+/// 凭证洞察类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialInsightKind {
+    NeverSelected,
+    AlwaysUnhealthy,
+    Idle,
+    Saturated,
+}
+
+/// 单个凭证的洞察
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CredentialInsight {
+    pub uuid: String,
+    pub name: Option<String>,
+    pub provider_type: String,
+    pub kind: CredentialInsightKind,
+    pub detail: String,
+}
+
+/// 建议操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolRecommendationAction {
+    /// 增加凭证分担负载
+    AddCredentials,
+    /// 移除或禁用闲置凭证
+    RemoveIdle,
+    /// 检查凭证配置（模型黑名单、凭证文件等）
+    ReviewConfig,
+}
+
+/// Provider 级别的建议
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolRecommendation {
+    pub provider_type: String,
+    pub action: PoolRecommendationAction,
+    pub message: String,
+}
+
+/// 凭证池洞察报告
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoolInsights {
+    pub generated_at: DateTime<Utc>,
+    pub credentials: Vec<CredentialInsight>,
+    pub recommendations: Vec<PoolRecommendation>,
+}
+
+fn is_rate_limit_error(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    lower.contains("429") || lower.contains("rate limit") || lower.contains("限流")
+}
+
+fn insight(
+    cred: &ProviderCredential,
+    kind: CredentialInsightKind,
+    detail: String,
+) -> CredentialInsight {
+    CredentialInsight {
+        uuid: cred.uuid.clone(),
+        name: cred.name.clone(),
+        provider_type: cred.provider_type.to_string(),
+        kind,
+        detail,
+    }
+}
+
+/// 分析同一 Provider 下的凭证
+fn analyze_group(
+    provider_type: &str,
+    credentials: &[&ProviderCredential],
+    now: DateTime<Utc>,
+    insights: &mut Vec<CredentialInsight>,
+    recommendations: &mut Vec<PoolRecommendation>,
+) {
+    let active: Vec<&ProviderCredential> = credentials
+        .iter()
+        .copied()
+        .filter(|cred| !cred.is_disabled)
+        .collect();
+    if active.is_empty() {
+        return;
+    }
+
+    let idle_cutoff = now - Duration::days(IDLE_DAYS);
+    let recently_used =
+        |cred: &ProviderCredential| cred.last_used.is_some_and(|t| t >= idle_cutoff);
+    let any_recent_use = active.iter().any(|cred| recently_used(*cred));
+    // 平均使用量只统计被用过的凭证，避免新凭证拉低基准
+    let used: Vec<u64> = active
+        .iter()
+        .map(|cred| cred.usage_count)
+        .filter(|count| *count > 0)
+        .collect();
+    let fair_share = if used.is_empty() {
+        0.0
+    } else {
+        used.iter().sum::<u64>() as f64 / used.len() as f64
+    };
+
+    let mut unhealthy = 0;
+    let mut saturated = 0;
+    let mut idle = 0;
+    let mut never_selected = 0;
+    for cred in &active {
+        if !cred.is_healthy && cred.error_count >= UNHEALTHY_ERROR_COUNT && !recently_used(*cred) {
+            unhealthy += 1;
+            insights.push(insight(
+                cred,
+                CredentialInsightKind::AlwaysUnhealthy,
+                format!(
+                    "连续 {} 次错误且 {} 天内没有成功使用：{}",
+                    cred.error_count,
+                    IDLE_DAYS,
+                    cred.last_error_message.as_deref().unwrap_or("未知错误")
+                ),
+            ));
+            continue;
+        }
+
+        if cred.usage_count == 0 && cred.last_used.is_none() {
+            if now - cred.created_at >= Duration::hours(NEVER_SELECTED_GRACE_HOURS) {
+                never_selected += 1;
+                let detail = if cred.not_supported_models.is_empty() {
+                    "添加后从未被选中，请确认 Provider 类型与客户端路由是否匹配".to_string()
+                } else {
+                    format!(
+                        "添加后从未被选中，模型黑名单包含 {} 项，可能排除了所有请求的模型",
+                        cred.not_supported_models.len()
+                    )
+                };
+                insights.push(insight(cred, CredentialInsightKind::NeverSelected, detail));
+            }
+            continue;
+        }
+
+        let rate_limited = cred
+            .last_error_time
+            .is_some_and(|t| now - t <= Duration::hours(SATURATION_WINDOW_HOURS))
+            && cred
+                .last_error_message
+                .as_deref()
+                .is_some_and(is_rate_limit_error);
+        let overloaded = used.len() > 1
+            && fair_share > 0.0
+            && cred.usage_count as f64 > fair_share * SATURATION_SHARE_FACTOR;
+        if rate_limited || overloaded {
+            saturated += 1;
+            let detail = if rate_limited {
+                format!("{} 小时内被上游限流", SATURATION_WINDOW_HOURS)
+            } else {
+                format!(
+                    "承担了 {} 次请求，约为同类平均值的 {:.1} 倍",
+                    cred.usage_count,
+                    cred.usage_count as f64 / fair_share
+                )
+            };
+            insights.push(insight(cred, CredentialInsightKind::Saturated, detail));
+            continue;
+        }
+
+        if any_recent_use && !recently_used(*cred) {
+            idle += 1;
+            insights.push(insight(
+                cred,
+                CredentialInsightKind::Idle,
+                format!("{IDLE_DAYS} 天内未被使用，同类其他凭证仍在使用"),
+            ));
+        }
+    }
+
+    let usable = active.len() - unhealthy;
+    if usable == 0 || saturated * 2 >= active.len().max(2) {
+        recommendations.push(PoolRecommendation {
+            provider_type: provider_type.to_string(),
+            action: PoolRecommendationAction::AddCredentials,
+            message: format!(
+                "{} 个启用的凭证中 {} 个不健康、{} 个接近饱和，建议增加凭证",
+                active.len(),
+                unhealthy,
+                saturated
+            ),
+        });
+    }
+    if idle > 0 && saturated == 0 && idle < active.len() {
+        recommendations.push(PoolRecommendation {
+            provider_type: provider_type.to_string(),
+            action: PoolRecommendationAction::RemoveIdle,
+            message: format!("{idle} 个凭证长期闲置，可以禁用或移除以简化凭证池"),
+        });
+    }
+    if never_selected > 0 || (unhealthy > 0 && usable > 0) {
+        recommendations.push(PoolRecommendation {
+            provider_type: provider_type.to_string(),
+            action: PoolRecommendationAction::ReviewConfig,
+            message: format!(
+                "{never_selected} 个凭证从未被选中、{unhealthy} 个长期不健康，请检查其配置"
+            ),
+        });
+    }
+}
+
+/// 分析凭证池
+pub fn analyze_pool(credentials: &[ProviderCredential], now: DateTime<Utc>) -> PoolInsights {
+    let mut groups: BTreeMap<String, Vec<&ProviderCredential>> = BTreeMap::new();
+    for cred in credentials {
+        groups
+            .entry(cred.provider_type.to_string())
+            .or_default()
+            .push(cred);
+    }
+
+    let mut insights = Vec::new();
+    let mut recommendations = Vec::new();
+    for (provider_type, group) in &groups {
+        analyze_group(
+            provider_type,
+            group,
+            now,
+            &mut insights,
+            &mut recommendations,
+        );
+    }
+
+    PoolInsights {
+        generated_at: now,
+        credentials: insights,
+        recommendations,
+    }
+}
+
+/// 读取凭证池并生成洞察报告
+pub fn pool_insights(db: &DbConnection) -> Result<PoolInsights, String> {
+    let credentials = {
+        let conn = lock_pool(db)?;
+        pool_storage().get_all(&conn)?
+    };
+    Ok(analyze_pool(&credentials, Utc::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lime_core::models::provider_pool_model::{CredentialData, PoolProviderType};
+
+    fn credential(usage_count: u64, last_used_days_ago: Option<i64>) -> ProviderCredential {
+        let now = Utc::now();
+        let mut cred = ProviderCredential::new(
+            PoolProviderType::OpenAI,
+            CredentialData::OpenAIKey {
+                api_key: "sk-test".to_string(),
+                base_url: None,
+            },
+        );
+        cred.created_at = now - Duration::days(30);
+        cred.usage_count = usage_count;
+        cred.last_used = last_used_days_ago.map(|days| now - Duration::days(days));
+        cred
+    }
+
+    #[test]
+    fn should_flag_never_selected_unhealthy_and_idle_credentials() {
+        let now = Utc::now();
+        let busy = credential(100, Some(0));
+        let never = credential(0, None);
+        let mut broken = credential(3, Some(20));
+        broken.is_healthy = false;
+        broken.error_count = 9;
+        broken.last_error_message = Some("HTTP 401".to_string());
+        let idle = credential(80, Some(10));
+        let mut fresh = credential(0, None);
+        fresh.created_at = now - Duration::hours(1);
+
+        let report = analyze_pool(
+            &[busy, never.clone(), broken.clone(), idle.clone(), fresh],
+            now,
+        );
+        let kinds: Vec<(String, CredentialInsightKind)> = report
+            .credentials
+            .iter()
+            .map(|i| (i.uuid.clone(), i.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (never.uuid, CredentialInsightKind::NeverSelected),
+                (broken.uuid, CredentialInsightKind::AlwaysUnhealthy),
+                (idle.uuid, CredentialInsightKind::Idle),
+            ]
+        );
+        let actions: Vec<PoolRecommendationAction> =
+            report.recommendations.iter().map(|r| r.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                PoolRecommendationAction::RemoveIdle,
+                PoolRecommendationAction::ReviewConfig
+            ]
+        );
+    }
+
+    #[test]
+    fn should_recommend_scaling_up_when_saturated() {
+        let now = Utc::now();
+        let mut limited = credential(50, Some(0));
+        limited.last_error_time = Some(now - Duration::hours(1));
+        limited.last_error_message = Some("HTTP 429 Too Many Requests".to_string());
+        let heavy = credential(500, Some(0));
+        let light = credential(40, Some(0));
+
+        let report = analyze_pool(&[limited, heavy, light], now);
+        let saturated = report
+            .credentials
+            .iter()
+            .filter(|i| i.kind == CredentialInsightKind::Saturated)
+            .count();
+        assert_eq!(saturated, 2);
+        assert_eq!(
+            report.recommendations[0].action,
+            PoolRecommendationAction::AddCredentials
+        );
+    }
+}
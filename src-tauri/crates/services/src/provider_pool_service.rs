@@ -10,8 +10,10 @@ use crate::provider_type_mapping::{
     resolve_pool_provider_type_or_default,
 };
 use chrono::Utc;
-use lime_core::database::dao::provider_pool::ProviderPoolDao;
-use lime_core::database::DbConnection;
+use lime_core::app_events::{publish_app_event, AppEvent, PoolEvent};
+use lime_core::credential::HealthProbeOutcome;
+use lime_core::database::pool_storage::{lock_pool, pool_storage};
+use lime_core::database::{lock_db, DbConnection};
 use lime_core::models::client_type::ClientType;
 use lime_core::models::provider_pool_model::{
     get_default_check_model, get_oauth_creds_path, CredentialData, CredentialDisplay,
@@ -22,11 +24,29 @@ use lime_core::models::route_model::RouteInfo;
 use lime_providers::providers::antigravity::TokenRefreshError;
 use lime_providers::providers::kiro::KiroProvider;
 use reqwest::Client;
+use rusqlite::OptionalExtension;
 use serde::{Deserialize, Serialize};
 
+/// Provider 暂停记录在 settings 表中的键前缀，值为暂停时被禁用的凭证 UUID 列表
+const PROVIDER_PAUSE_SETTING_PREFIX: &str = "provider_pause:";
+
+/// 读取 Provider 暂停记录，未暂停时返回 `None`
+fn read_provider_pause(db: &DbConnection, key: &str) -> Result<Option<Vec<String>>, String> {
+    let conn = lock_db(db)?;
+    let value = conn
+        .query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| {
+            row.get::<_, String>(0)
+        })
+        .optional()
+        .map_err(|e| format!("读取 Provider 暂停状态失败: {e}"))?;
+    value
+        .map(|value| serde_json::from_str(&value).map_err(|e| e.to_string()))
+        .transpose()
+}
+
 /// 扩展 ProviderCredential 的客户端兼容性检查
 /// （此方法依赖 server::client_detector，不适合放在 core crate）
-trait ProviderCredentialClientCompat {
+pub trait ProviderCredentialClientCompat {
     fn is_compatible_with_client(&self, client_type: Option<&ClientType>) -> bool;
 }
 
@@ -111,14 +131,15 @@ impl ProviderPoolService {
 
     /// 获取所有凭证概览
     pub fn get_overview(&self, db: &DbConnection) -> Result<Vec<ProviderPoolOverview>, String> {
-        let conn = lime_core::database::lock_db(db)?;
-        let grouped = ProviderPoolDao::get_grouped(&conn).map_err(|e| e.to_string())?;
+        let conn = lock_pool(db)?;
+        let grouped = pool_storage().get_grouped(&conn)?;
 
         let mut overview = Vec::new();
         for (provider_type, mut credentials) in grouped {
             // 为每个凭证加载 token 缓存
             for cred in &mut credentials {
-                cred.cached_token = ProviderPoolDao::get_token_cache(&conn, &cred.uuid)
+                cred.cached_token = pool_storage()
+                    .get_token_cache(&conn, &cred.uuid)
                     .ok()
                     .flatten();
             }
@@ -145,13 +166,13 @@ impl ProviderPoolService {
         provider_type: &str,
     ) -> Result<Vec<CredentialDisplay>, String> {
         let pt = parse_pool_provider_type(provider_type)?;
-        let conn = lime_core::database::lock_db(db)?;
-        let mut credentials =
-            ProviderPoolDao::get_by_type(&conn, &pt).map_err(|e| e.to_string())?;
+        let conn = lock_pool(db)?;
+        let mut credentials = pool_storage().get_by_type(&conn, &pt)?;
 
         // 为每个凭证加载 token 缓存
         for cred in &mut credentials {
-            cred.cached_token = ProviderPoolDao::get_token_cache(&conn, &cred.uuid)
+            cred.cached_token = pool_storage()
+                .get_token_cache(&conn, &cred.uuid)
                 .ok()
                 .flatten();
         }
@@ -176,8 +197,46 @@ impl ProviderPoolService {
         cred.check_health = check_health.unwrap_or(true);
         cred.check_model_name = check_model_name;
 
-        let conn = lime_core::database::lock_db(db)?;
-        ProviderPoolDao::insert(&conn, &cred).map_err(|e| e.to_string())?;
+        let conn = lock_pool(db)?;
+        pool_storage().insert(&conn, &cred)?;
+
+        Ok(cred)
+    }
+
+    /// 批量插入从其他实例导入的凭证
+    pub fn import_credentials(
+        &self,
+        db: &DbConnection,
+        credentials: &[ProviderCredential],
+    ) -> Result<(), String> {
+        let conn = lock_pool(db)?;
+        for cred in credentials {
+            pool_storage().insert(&conn, cred)?;
+        }
+        Ok(())
+    }
+
+    /// 以已有凭证为模板创建新凭证
+    ///
+    /// `credential` 为替换了账号信息的凭证数据，名称为空时使用「原名称 (副本)」。
+    pub fn clone_credential(
+        &self,
+        db: &DbConnection,
+        source: &ProviderCredential,
+        credential: CredentialData,
+        name: Option<String>,
+    ) -> Result<ProviderCredential, String> {
+        let mut cred = source.clone_with(credential);
+        cred.name = name.filter(|n| !n.trim().is_empty()).or_else(|| {
+            let base = source
+                .name
+                .clone()
+                .unwrap_or_else(|| source.provider_type.to_string());
+            Some(format!("{base} (副本)"))
+        });
+
+        let conn = lock_pool(db)?;
+        pool_storage().insert(&conn, &cred)?;
 
         Ok(cred)
     }
@@ -194,9 +253,9 @@ impl ProviderPoolService {
         not_supported_models: Option<Vec<String>>,
         proxy_url: Option<String>,
     ) -> Result<ProviderCredential, String> {
-        let conn = lime_core::database::lock_db(db)?;
-        let mut cred = ProviderPoolDao::get_by_uuid(&conn, uuid)
-            .map_err(|e| e.to_string())?
+        let conn = lock_pool(db)?;
+        let mut cred = pool_storage()
+            .get_by_uuid(&conn, uuid)?
             .ok_or_else(|| format!("Credential not found: {uuid}"))?;
 
         // 处理 name：空字符串表示清除，None 表示不修改
@@ -222,14 +281,150 @@ impl ProviderPoolService {
         }
         cred.updated_at = Utc::now();
 
-        ProviderPoolDao::update(&conn, &cred).map_err(|e| e.to_string())?;
+        pool_storage().update(&conn, &cred)?;
         Ok(cred)
     }
 
-    /// 删除凭证
+    /// 暂停/恢复整个 Provider，返回状态发生变化的凭证数
+    ///
+    /// 暂停时只禁用当前启用的凭证并单独记录其 UUID，恢复时只重新启用这些凭证，
+    /// 单独禁用的凭证不受影响。
+    pub fn set_provider_paused(
+        &self,
+        db: &DbConnection,
+        provider_type: &str,
+        paused: bool,
+    ) -> Result<usize, String> {
+        let pt = parse_pool_provider_type(provider_type)?;
+        let key = format!("{PROVIDER_PAUSE_SETTING_PREFIX}{pt}");
+        let recorded = read_provider_pause(db, &key)?;
+
+        if paused {
+            if recorded.is_some() {
+                return Ok(0);
+            }
+            let to_pause: Vec<String> = {
+                let conn = lock_pool(db)?;
+                pool_storage()
+                    .get_by_type(&conn, &pt)?
+                    .into_iter()
+                    .filter(|cred| !cred.is_disabled)
+                    .map(|cred| cred.uuid)
+                    .collect()
+            };
+            // 先写入记录，中途失败时仍可通过恢复重新启用
+            let value = serde_json::to_string(&to_pause).map_err(|e| e.to_string())?;
+            lock_db(db)?
+                .execute(
+                    "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+                    rusqlite::params![key, value],
+                )
+                .map_err(|e| format!("保存 Provider 暂停状态失败: {e}"))?;
+            self.set_credentials_disabled(db, &to_pause, true)
+        } else {
+            let Some(recorded) = recorded else {
+                return Ok(0);
+            };
+            let changed = self.set_credentials_disabled(db, &recorded, false)?;
+            lock_db(db)?
+                .execute("DELETE FROM settings WHERE key = ?1", [&key])
+                .map_err(|e| format!("清除 Provider 暂停状态失败: {e}"))?;
+            Ok(changed)
+        }
+    }
+
+    /// 已暂停的 Provider 类型
+    pub fn paused_provider_types(&self, db: &DbConnection) -> Result<HashSet<String>, String> {
+        let conn = lock_db(db)?;
+        let mut stmt = conn
+            .prepare("SELECT key FROM settings WHERE key LIKE ?1")
+            .map_err(|e| e.to_string())?;
+        let keys = stmt
+            .query_map([format!("{PROVIDER_PAUSE_SETTING_PREFIX}%")], |row| {
+                row.get::<_, String>(0)
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok(keys
+            .iter()
+            .filter_map(|key| key.strip_prefix(PROVIDER_PAUSE_SETTING_PREFIX))
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// 批量设置凭证的禁用状态，返回状态发生变化的凭证数
+    fn set_credentials_disabled(
+        &self,
+        db: &DbConnection,
+        uuids: &[String],
+        disabled: bool,
+    ) -> Result<usize, String> {
+        let conn = lock_pool(db)?;
+        let mut changed = 0;
+        for uuid in uuids {
+            let Some(mut cred) = pool_storage().get_by_uuid(&conn, uuid)? else {
+                continue;
+            };
+            if cred.is_disabled == disabled {
+                continue;
+            }
+            cred.is_disabled = disabled;
+            cred.updated_at = Utc::now();
+            pool_storage().update(&conn, &cred)?;
+            changed += 1;
+        }
+        Ok(changed)
+    }
+
+    /// 删除凭证（移入回收站，可在保留期内恢复）
     pub fn delete_credential(&self, db: &DbConnection, uuid: &str) -> Result<bool, String> {
-        let conn = lime_core::database::lock_db(db)?;
-        ProviderPoolDao::delete(&conn, uuid).map_err(|e| e.to_string())
+        let conn = lock_pool(db)?;
+        pool_storage().soft_delete(&conn, uuid, Utc::now())
+    }
+
+    /// 永久删除凭证（不经过回收站）
+    pub fn delete_credential_permanently(
+        &self,
+        db: &DbConnection,
+        uuid: &str,
+    ) -> Result<bool, String> {
+        let conn = lock_pool(db)?;
+        pool_storage().delete(&conn, uuid)
+    }
+
+    /// 获取回收站中的凭证
+    pub fn get_deleted_credentials(
+        &self,
+        db: &DbConnection,
+    ) -> Result<Vec<ProviderCredential>, String> {
+        let conn = lock_pool(db)?;
+        pool_storage().get_deleted(&conn)
+    }
+
+    /// 从回收站恢复凭证
+    pub fn restore_credential(&self, db: &DbConnection, uuid: &str) -> Result<bool, String> {
+        let conn = lock_pool(db)?;
+        pool_storage().restore(&conn, uuid)
+    }
+
+    /// 从回收站永久删除凭证，返回被删除的凭证（不在回收站时返回 None）
+    pub fn purge_deleted_credential(
+        &self,
+        db: &DbConnection,
+        uuid: &str,
+    ) -> Result<Option<ProviderCredential>, String> {
+        let conn = lock_pool(db)?;
+        let storage = pool_storage();
+        let Some(cred) = storage
+            .get_deleted(&conn)?
+            .into_iter()
+            .find(|cred| cred.uuid == uuid)
+        else {
+            return Ok(None);
+        };
+        storage.delete(&conn, uuid)?;
+        Ok(Some(cred))
     }
 
     /// 选择一个可用的凭证（智能轮换策略）
@@ -274,11 +469,10 @@ impl ProviderPoolService {
                 return Ok(None);
             }
         };
-        let conn = lime_core::database::lock_db(db)?;
+        let conn = lock_pool(db)?;
 
         // 获取凭证，对于 AI Provider 类型，也查找 Assistant 类型的凭证
-        let mut credentials =
-            ProviderPoolDao::get_by_type(&conn, &pt).map_err(|e| e.to_string())?;
+        let mut credentials = pool_storage().get_by_type(&conn, &pt)?;
         eprintln!(
             "[SELECT_CREDENTIAL] provider_type={}, pt={:?}, initial_count={}",
             provider_type,
@@ -288,8 +482,7 @@ impl ProviderPoolService {
 
         // AI Provider 和 Assistant 共享凭证（都使用 AI Provider API）
         if pt == PoolProviderType::Anthropic {
-            let assistant_creds = ProviderPoolDao::get_by_type(&conn, &PoolProviderType::Claude)
-                .map_err(|e| e.to_string())?;
+            let assistant_creds = pool_storage().get_by_type(&conn, &PoolProviderType::Claude)?;
             eprintln!(
                 "[SELECT_CREDENTIAL] AI Provider: adding {} Assistant credentials",
                 assistant_creds.len()
@@ -297,8 +490,7 @@ impl ProviderPoolService {
             credentials.extend(assistant_creds);
         } else if pt == PoolProviderType::Claude {
             let ai_provider_creds =
-                ProviderPoolDao::get_by_type(&conn, &PoolProviderType::Anthropic)
-                    .map_err(|e| e.to_string())?;
+                pool_storage().get_by_type(&conn, &PoolProviderType::Anthropic)?;
             eprintln!(
                 "[SELECT_CREDENTIAL] Assistant: adding {} AI Provider credentials",
                 ai_provider_creds.len()
@@ -574,13 +766,18 @@ impl ProviderPoolService {
 
     /// 记录凭证使用
     pub fn record_usage(&self, db: &DbConnection, uuid: &str) -> Result<(), String> {
-        let conn = lime_core::database::lock_db(db)?;
-        let cred = ProviderPoolDao::get_by_uuid(&conn, uuid)
-            .map_err(|e| e.to_string())?
+        let conn = lock_pool(db)?;
+        let cred = pool_storage()
+            .get_by_uuid(&conn, uuid)?
             .ok_or_else(|| format!("Credential not found: {uuid}"))?;
 
-        ProviderPoolDao::update_usage(&conn, uuid, cred.usage_count + 1, Utc::now())
-            .map_err(|e| e.to_string())
+        let usage_count = cred.usage_count + 1;
+        pool_storage().update_usage(&conn, uuid, usage_count, Utc::now())?;
+        publish_app_event(AppEvent::Pool(PoolEvent::CredentialUsed {
+            uuid: uuid.to_string(),
+            usage_count,
+        }));
+        Ok(())
     }
 
     /// 标记凭证为健康
@@ -590,8 +787,8 @@ impl ProviderPoolService {
         uuid: &str,
         check_model: Option<&str>,
     ) -> Result<(), String> {
-        let conn = lime_core::database::lock_db(db)?;
-        ProviderPoolDao::update_health_status(
+        let conn = lock_pool(db)?;
+        pool_storage().update_health_status(
             &conn,
             uuid,
             true,
@@ -600,8 +797,14 @@ impl ProviderPoolService {
             None,
             Some(Utc::now()),
             check_model,
-        )
-        .map_err(|e| e.to_string())
+        )?;
+        publish_app_event(AppEvent::Pool(PoolEvent::CredentialHealthChanged {
+            uuid: uuid.to_string(),
+            healthy: true,
+            error_count: 0,
+            message: None,
+        }));
+        Ok(())
     }
 
     /// 标记凭证为不健康
@@ -611,15 +814,15 @@ impl ProviderPoolService {
         uuid: &str,
         error_message: Option<&str>,
     ) -> Result<(), String> {
-        let conn = lime_core::database::lock_db(db)?;
-        let cred = ProviderPoolDao::get_by_uuid(&conn, uuid)
-            .map_err(|e| e.to_string())?
+        let conn = lock_pool(db)?;
+        let cred = pool_storage()
+            .get_by_uuid(&conn, uuid)?
             .ok_or_else(|| format!("Credential not found: {uuid}"))?;
 
         let new_error_count = cred.error_count + 1;
         let is_healthy = new_error_count < self.max_error_count;
 
-        ProviderPoolDao::update_health_status(
+        pool_storage().update_health_status(
             &conn,
             uuid,
             is_healthy,
@@ -628,14 +831,20 @@ impl ProviderPoolService {
             error_message,
             None,
             None,
-        )
-        .map_err(|e| e.to_string())
+        )?;
+        publish_app_event(AppEvent::Pool(PoolEvent::CredentialHealthChanged {
+            uuid: uuid.to_string(),
+            healthy: is_healthy,
+            error_count: new_error_count,
+            message: error_message.map(str::to_string),
+        }));
+        Ok(())
     }
 
     /// 重置凭证计数器
     pub fn reset_counters(&self, db: &DbConnection, uuid: &str) -> Result<(), String> {
-        let conn = lime_core::database::lock_db(db)?;
-        ProviderPoolDao::reset_counters(&conn, uuid).map_err(|e| e.to_string())
+        let conn = lock_pool(db)?;
+        pool_storage().reset_counters(&conn, uuid)
     }
 
     /// 重置指定类型的所有凭证健康状态
@@ -645,8 +854,8 @@ impl ProviderPoolService {
         provider_type: &str,
     ) -> Result<usize, String> {
         let pt = parse_pool_provider_type(provider_type)?;
-        let conn = lime_core::database::lock_db(db)?;
-        ProviderPoolDao::reset_health_by_type(&conn, &pt).map_err(|e| e.to_string())
+        let conn = lock_pool(db)?;
+        pool_storage().reset_health_by_type(&conn, &pt)
     }
 
     /// 获取凭证健康状态
@@ -656,8 +865,8 @@ impl ProviderPoolService {
         db: &DbConnection,
         uuid: &str,
     ) -> Result<Option<CredentialHealthInfo>, String> {
-        let conn = lime_core::database::lock_db(db)?;
-        let cred = ProviderPoolDao::get_by_uuid(&conn, uuid).map_err(|e| e.to_string())?;
+        let conn = lock_pool(db)?;
+        let cred = pool_storage().get_by_uuid(&conn, uuid)?;
 
         Ok(cred.map(|c| CredentialHealthInfo {
             uuid: c.uuid.clone(),
@@ -681,8 +890,8 @@ impl ProviderPoolService {
         &self,
         db: &DbConnection,
     ) -> Result<Vec<CredentialHealthInfo>, String> {
-        let conn = lime_core::database::lock_db(db)?;
-        let credentials = ProviderPoolDao::get_all(&conn).map_err(|e| e.to_string())?;
+        let conn = lock_pool(db)?;
+        let credentials = pool_storage().get_all(&conn)?;
 
         Ok(credentials
             .into_iter()
@@ -714,9 +923,9 @@ impl ProviderPoolService {
         let error_message = error.user_message();
         let requires_reauth = error.requires_reauth();
 
-        let conn = lime_core::database::lock_db(db)?;
-        let cred = ProviderPoolDao::get_by_uuid(&conn, uuid)
-            .map_err(|e| e.to_string())?
+        let conn = lock_pool(db)?;
+        let cred = pool_storage()
+            .get_by_uuid(&conn, uuid)?
             .ok_or_else(|| format!("Credential not found: {uuid}"))?;
 
         let new_error_count = cred.error_count + 1;
@@ -733,7 +942,7 @@ impl ProviderPoolService {
             error_message
         };
 
-        ProviderPoolDao::update_health_status(
+        pool_storage().update_health_status(
             &conn,
             uuid,
             is_healthy,
@@ -742,8 +951,14 @@ impl ProviderPoolService {
             Some(&error_msg),
             None,
             None,
-        )
-        .map_err(|e| e.to_string())
+        )?;
+        publish_app_event(AppEvent::Pool(PoolEvent::CredentialHealthChanged {
+            uuid: uuid.to_string(),
+            healthy: is_healthy,
+            error_count: new_error_count,
+            message: Some(error_msg),
+        }));
+        Ok(())
     }
 
     /// 选择一个健康的凭证
@@ -757,9 +972,10 @@ impl ProviderPoolService {
         let pt: PoolProviderType = provider_type
             .parse()
             .map_err(|_| SelectionError::NoCredentials)?;
-        let conn = lime_core::database::lock_db(db).map_err(|_| SelectionError::NoCredentials)?;
-        let credentials =
-            ProviderPoolDao::get_by_type(&conn, &pt).map_err(|_| SelectionError::NoCredentials)?;
+        let conn = lock_pool(db).map_err(|_| SelectionError::NoCredentials)?;
+        let credentials = pool_storage()
+            .get_by_type(&conn, &pt)
+            .map_err(|_| SelectionError::NoCredentials)?;
         drop(conn);
 
         if credentials.is_empty() {
@@ -867,9 +1083,9 @@ impl ProviderPoolService {
         uuid: &str,
     ) -> Result<HealthCheckResult, String> {
         let cred = {
-            let conn = lime_core::database::lock_db(db)?;
-            ProviderPoolDao::get_by_uuid(&conn, uuid)
-                .map_err(|e| e.to_string())?
+            let conn = lock_pool(db)?;
+            pool_storage()
+                .get_by_uuid(&conn, uuid)?
                 .ok_or_else(|| format!("Credential not found: {uuid}"))?
         };
 
@@ -907,9 +1123,9 @@ impl ProviderPoolService {
 
                             // 重新获取凭证（token 已更新）
                             let updated_cred = {
-                                let conn = lime_core::database::lock_db(db)?;
-                                ProviderPoolDao::get_by_uuid(&conn, uuid)
-                                    .map_err(|e| e.to_string())?
+                                let conn = lock_pool(db)?;
+                                pool_storage()
+                                    .get_by_uuid(&conn, uuid)?
                                     .ok_or_else(|| format!("Credential not found: {uuid}"))?
                             };
 
@@ -973,6 +1189,47 @@ impl ProviderPoolService {
         }
     }
 
+    /// 按需探测单个凭证（试运行）
+    ///
+    /// 使用指定模型（为空时使用凭证的健康检查模型）发送一次探测请求并返回原始结果；
+    /// 不刷新 Token，也不更新健康状态与错误计数。
+    pub async fn probe_credential_health(
+        &self,
+        db: &DbConnection,
+        uuid: &str,
+        model: Option<String>,
+    ) -> Result<HealthProbeOutcome, String> {
+        let cred = {
+            let conn = lock_pool(db)?;
+            pool_storage()
+                .get_by_uuid(&conn, uuid)?
+                .ok_or_else(|| format!("Credential not found: {uuid}"))?
+        };
+
+        let model = model
+            .filter(|m| !m.trim().is_empty())
+            .or_else(|| cred.check_model_name.clone())
+            .unwrap_or_else(|| get_default_check_model(cred.provider_type).to_string());
+
+        let start = std::time::Instant::now();
+        let result = self.perform_health_check(&cred.credential, &model).await;
+        let outcome = HealthProbeOutcome::from_result(
+            uuid,
+            &model,
+            &result,
+            start.elapsed().as_millis() as u64,
+        );
+        tracing::info!(
+            "[健康检查] 试运行 uuid={} model={} success={} latency={}ms status={:?}",
+            uuid,
+            model,
+            outcome.success,
+            outcome.latency_ms,
+            outcome.status_code
+        );
+        Ok(outcome)
+    }
+
     /// 执行指定类型的所有凭证健康检查
     pub async fn check_type_health(
         &self,
@@ -981,8 +1238,8 @@ impl ProviderPoolService {
     ) -> Result<Vec<HealthCheckResult>, String> {
         let pt = parse_pool_provider_type(provider_type)?;
         let credentials = {
-            let conn = lime_core::database::lock_db(db)?;
-            ProviderPoolDao::get_by_type(&conn, &pt).map_err(|e| e.to_string())?
+            let conn = lock_pool(db)?;
+            pool_storage().get_by_type(&conn, &pt)?
         };
 
         let mut results = Vec::new();
@@ -1481,7 +1738,10 @@ impl ProviderPoolService {
             .client
             .post(&url)
             .header("x-api-key", api_key)
-            .header("anthropic-version", "2023-06-01")
+            .header(
+                "anthropic-version",
+                lime_providers::providers::claude_custom::anthropic_version(),
+            )
             .json(&request_body)
             .timeout(self.health_check_timeout)
             .send()
@@ -1698,7 +1958,10 @@ impl ProviderPoolService {
             .client
             .post(url)
             .header("Authorization", format!("Bearer {token}"))
-            .header("anthropic-version", "2023-06-01")
+            .header(
+                "anthropic-version",
+                lime_providers::providers::claude_custom::anthropic_version(),
+            )
             .json(&request_body)
             .timeout(self.health_check_timeout)
             .send()
@@ -1718,8 +1981,8 @@ impl ProviderPoolService {
         db: &DbConnection,
         name: &str,
     ) -> Result<Option<ProviderCredential>, String> {
-        let conn = lime_core::database::lock_db(db)?;
-        ProviderPoolDao::get_by_name(&conn, name).map_err(|e| e.to_string())
+        let conn = lock_pool(db)?;
+        pool_storage().get_by_name(&conn, name)
     }
 
     /// 根据 UUID 获取凭证
@@ -1728,8 +1991,8 @@ impl ProviderPoolService {
         db: &DbConnection,
         uuid: &str,
     ) -> Result<Option<ProviderCredential>, String> {
-        let conn = lime_core::database::lock_db(db)?;
-        ProviderPoolDao::get_by_uuid(&conn, uuid).map_err(|e| e.to_string())
+        let conn = lock_pool(db)?;
+        pool_storage().get_by_uuid(&conn, uuid)
     }
 
     /// 获取所有可用的路由端点
@@ -1738,8 +2001,8 @@ impl ProviderPoolService {
         db: &DbConnection,
         base_url: &str,
     ) -> Result<Vec<RouteInfo>, String> {
-        let conn = lime_core::database::lock_db(db)?;
-        let grouped = ProviderPoolDao::get_grouped(&conn).map_err(|e| e.to_string())?;
+        let conn = lock_pool(db)?;
+        let grouped = pool_storage().get_grouped(&conn)?;
         drop(conn);
 
         let mut routes = Vec::new();
@@ -1901,9 +2164,9 @@ impl ProviderPoolService {
         uuid: &str,
     ) -> Result<String, String> {
         let cred = {
-            let conn = lime_core::database::lock_db(db)?;
-            ProviderPoolDao::get_by_uuid(&conn, uuid)
-                .map_err(|e| e.to_string())?
+            let conn = lock_pool(db)?;
+            pool_storage()
+                .get_by_uuid(&conn, uuid)?
                 .ok_or_else(|| format!("Credential not found: {uuid}"))?
         };
 
@@ -1928,9 +2191,9 @@ impl ProviderPoolService {
         uuid: &str,
     ) -> Result<OAuthStatus, String> {
         let cred = {
-            let conn = lime_core::database::lock_db(db)?;
-            ProviderPoolDao::get_by_uuid(&conn, uuid)
-                .map_err(|e| e.to_string())?
+            let conn = lock_pool(db)?;
+            pool_storage()
+                .get_by_uuid(&conn, uuid)?
                 .ok_or_else(|| format!("Credential not found: {uuid}"))?
         };
 
@@ -1958,8 +2221,8 @@ impl ProviderPoolService {
         cred.check_health = check_health.unwrap_or(true);
         cred.check_model_name = check_model_name;
 
-        let conn = lime_core::database::lock_db(db)?;
-        ProviderPoolDao::insert(&conn, &cred).map_err(|e| e.to_string())?;
+        let conn = lock_pool(db)?;
+        pool_storage().insert(&conn, &cred)?;
 
         Ok(cred)
     }
@@ -2090,8 +2353,8 @@ impl ProviderPoolService {
 
     /// 检查是否存在相同路径的凭证
     fn credential_exists_by_path(&self, db: &DbConnection, path: &str) -> Result<bool, String> {
-        let conn = lime_core::database::lock_db(db)?;
-        let all_creds = ProviderPoolDao::get_all(&conn).map_err(|e| e.to_string())?;
+        let conn = lock_pool(db)?;
+        let all_creds = pool_storage().get_all(&conn)?;
 
         for cred in all_creds {
             if let Some(cred_path) = get_oauth_creds_path(&cred.credential) {
@@ -2109,8 +2372,8 @@ impl ProviderPoolService {
         db: &DbConnection,
         api_key: &str,
     ) -> Result<bool, String> {
-        let conn = lime_core::database::lock_db(db)?;
-        let all_creds = ProviderPoolDao::get_all(&conn).map_err(|e| e.to_string())?;
+        let conn = lock_pool(db)?;
+        let all_creds = pool_storage().get_all(&conn)?;
 
         for cred in all_creds {
             match &cred.credential {
@@ -2144,6 +2407,48 @@ pub struct MigrationResult {
 mod tests {
     use super::*;
     use lime_core::database::dao::api_key_provider::ApiProviderType;
+    use lime_core::database::dao::provider_pool::ProviderPoolDao;
+    use lime_core::database::schema_migrations::run_pending_migrations;
+
+    #[test]
+    fn test_provider_pause_keeps_individually_disabled_credentials() {
+        let conn = rusqlite::Connection::open_in_memory().expect("open in-memory db");
+        run_pending_migrations(&conn).expect("create schema");
+        let key = |api_key: &str, disabled: bool| {
+            let mut cred = ProviderCredential::new(
+                PoolProviderType::OpenAI,
+                CredentialData::OpenAIKey {
+                    api_key: api_key.to_string(),
+                    base_url: None,
+                },
+            );
+            cred.is_disabled = disabled;
+            ProviderPoolDao::insert(&conn, &cred).expect("insert credential");
+            cred.uuid
+        };
+        let active = key("sk-active", false);
+        let disabled = key("sk-disabled", true);
+        let db: DbConnection = std::sync::Arc::new(std::sync::Mutex::new(conn));
+        let service = ProviderPoolService::new();
+
+        assert_eq!(service.set_provider_paused(&db, "openai", true), Ok(1));
+        assert!(service
+            .paused_provider_types(&db)
+            .unwrap()
+            .contains("openai"));
+        assert_eq!(service.set_provider_paused(&db, "openai", false), Ok(1));
+        assert!(service.paused_provider_types(&db).unwrap().is_empty());
+
+        let conn = lock_db(&db).unwrap();
+        let is_disabled = |uuid: &str| {
+            ProviderPoolDao::get_by_uuid(&conn, uuid)
+                .unwrap()
+                .unwrap()
+                .is_disabled
+        };
+        assert!(!is_disabled(&active));
+        assert!(is_disabled(&disabled));
+    }
 
     // ==================== Property 3: 不健康凭证排除 ====================
     // Feature: antigravity-token-refresh, Property 3: 不健康凭证排除
@@ -0,0 +1,433 @@
+//! 声明式测试场景
+//!
+//! 用户在 YAML 文件中描述一组按顺序发送的请求及其期望（状态码、延迟上下限、响应内容），
+//! 对正在运行的本地服务逐个执行并生成通过/失败报告，相当于针对自身配置的冒烟测试。
+//! 场景文件默认放在应用数据目录的 `scenarios/` 下（`*.yaml` / `*.yml`）：
+//!
+//! ```yaml
+//! name: 基础冒烟
+//! steps:
+//!   - name: 模型列表
+//!     method: GET
+//!     path: /v1/models
+//!   - name: 对话
+//!     path: /v1/chat/completions
+//!     body: { model: gpt-4o-mini, messages: [{ role: user, content: ping }], max_tokens: 8 }
+//!     expect: { status: 200, max_latency_ms: 10000, body_contains: ["choices"] }
+//!   - name: 错误 Key 被拒绝
+//!     path: /v1/models
+//!     method: GET
+//!     auth: false
+//!     headers: { Authorization: "Bearer wrong" }
+//!     expect: { status: 401 }
+//! ```
+//!
+//! 未设置 `expect.status` 时要求 2xx；`auth` 默认为 `true`，使用服务的 API Key 认证。
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// 场景文件目录名（位于应用数据目录）
+const SCENARIOS_DIR: &str = "scenarios";
+/// 单步请求的默认超时
+const DEFAULT_STEP_TIMEOUT_SECS: u64 = 120;
+/// 失败信息中保留的响应体长度
+const BODY_EXCERPT_CHARS: usize = 200;
+
+fn default_auth() -> bool {
+    true
+}
+
+/// 测试场景
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TestScenario {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// 某一步失败后是否继续执行后续步骤
+    #[serde(default)]
+    pub continue_on_failure: bool,
+    pub steps: Vec<ScenarioStep>,
+}
+
+/// 场景中的一个请求
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScenarioStep {
+    pub name: String,
+    /// HTTP 方法，未设置时有请求体为 POST，否则为 GET
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// 请求路径（相对本地服务地址）
+    pub path: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+    /// 是否携带服务的 API Key
+    #[serde(default = "default_auth")]
+    pub auth: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub expect: StepExpectation,
+}
+
+/// 单步期望
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct StepExpectation {
+    /// 期望的状态码，未设置时要求 2xx
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_latency_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_latency_ms: Option<u64>,
+    /// 响应体需要包含的文本
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub body_contains: Vec<String>,
+}
+
+/// 单步结果
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StepReport {
+    pub name: String,
+    pub passed: bool,
+    /// 响应状态码，网络错误时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    pub latency_ms: u64,
+    /// 未满足的期望
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<String>,
+}
+
+/// 场景结果
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScenarioReport {
+    pub name: String,
+    /// 场景文件路径（从文件加载时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub passed: bool,
+    pub steps: Vec<StepReport>,
+    /// 因前序步骤失败而未执行的步骤数
+    pub skipped: usize,
+    pub duration_ms: u64,
+}
+
+/// 场景文件目录
+pub fn scenarios_dir() -> PathBuf {
+    lime_core::app_paths::best_effort_runtime_subdir(SCENARIOS_DIR)
+}
+
+/// 解析场景 YAML
+pub fn parse_scenario(content: &str) -> Result<TestScenario, String> {
+    let scenario: TestScenario =
+        serde_yaml::from_str(content).map_err(|e| format!("解析场景失败: {e}"))?;
+    if scenario.steps.is_empty() {
+        return Err(format!("场景 {} 没有任何步骤", scenario.name));
+    }
+    for step in &scenario.steps {
+        if !step.path.starts_with('/') {
+            return Err(format!("步骤 {} 的路径必须以 / 开头", step.name));
+        }
+    }
+    Ok(scenario)
+}
+
+/// 从文件加载场景
+pub fn load_scenario(path: &Path) -> Result<TestScenario, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("读取场景文件 {} 失败: {e}", path.display()))?;
+    parse_scenario(&content).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// 列出目录中的场景文件（按文件名排序，目录不存在时为空）
+pub fn list_scenario_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ext == "yaml" || ext == "yml")
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// 检查响应是否满足期望，返回未满足的项
+pub fn check_expectation(
+    expect: &StepExpectation,
+    status: u16,
+    latency_ms: u64,
+    body: &str,
+) -> Vec<String> {
+    let mut failures = Vec::new();
+    match expect.status {
+        Some(expected) if expected != status => {
+            failures.push(format!("状态码 {status}，期望 {expected}"));
+        }
+        None if !(200..300).contains(&status) => {
+            failures.push(format!("状态码 {status}，期望 2xx"));
+        }
+        _ => {}
+    }
+    if let Some(max) = expect.max_latency_ms {
+        if latency_ms > max {
+            failures.push(format!("耗时 {latency_ms}ms，超过上限 {max}ms"));
+        }
+    }
+    if let Some(min) = expect.min_latency_ms {
+        if latency_ms < min {
+            failures.push(format!("耗时 {latency_ms}ms，低于下限 {min}ms"));
+        }
+    }
+    for needle in &expect.body_contains {
+        if !body.contains(needle.as_str()) {
+            failures.push(format!("响应体不包含 {needle:?}"));
+        }
+    }
+    if !failures.is_empty() && !(200..300).contains(&status) {
+        let excerpt: String = body.chars().take(BODY_EXCERPT_CHARS).collect();
+        failures.push(format!("响应体: {excerpt}"));
+    }
+    failures
+}
+
+async fn run_step(
+    client: &reqwest::Client,
+    base_url: &str,
+    api_key: &str,
+    step: &ScenarioStep,
+) -> StepReport {
+    let method = step
+        .method
+        .as_deref()
+        .unwrap_or(if step.body.is_some() { "POST" } else { "GET" })
+        .to_ascii_uppercase();
+    let started = Instant::now();
+    let method = match reqwest::Method::from_bytes(method.as_bytes()) {
+        Ok(method) => method,
+        Err(_) => {
+            return StepReport {
+                name: step.name.clone(),
+                passed: false,
+                status: None,
+                latency_ms: 0,
+                failures: vec![format!("无效的 HTTP 方法: {method}")],
+            }
+        }
+    };
+    let mut request = client
+        .request(method, format!("{base_url}{}", step.path))
+        .timeout(Duration::from_secs(
+            step.timeout_secs.unwrap_or(DEFAULT_STEP_TIMEOUT_SECS),
+        ));
+    if step.auth {
+        request = request.bearer_auth(api_key);
+    }
+    for (name, value) in &step.headers {
+        request = request.header(name, value);
+    }
+    if let Some(body) = &step.body {
+        request = request.json(body);
+    }
+
+    let result = match request.send().await {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            response.text().await.map(|body| (status, body))
+        }
+        Err(e) => Err(e),
+    };
+    let latency_ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok((status, body)) => {
+            let failures = check_expectation(&step.expect, status, latency_ms, &body);
+            StepReport {
+                name: step.name.clone(),
+                passed: failures.is_empty(),
+                status: Some(status),
+                latency_ms,
+                failures,
+            }
+        }
+        Err(e) => StepReport {
+            name: step.name.clone(),
+            passed: false,
+            status: None,
+            latency_ms,
+            failures: vec![format!("请求失败: {e}")],
+        },
+    }
+}
+
+/// 对本地服务执行场景
+///
+/// `base_url` 为本地服务地址（如 `http://127.0.0.1:8999`）。请求会真实转发到上游并计入用量。
+pub async fn run_scenario(
+    base_url: &str,
+    api_key: &str,
+    scenario: &TestScenario,
+) -> Result<ScenarioReport, String> {
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {e}"))?;
+    let base_url = base_url.trim_end_matches('/');
+    let started = Instant::now();
+    let mut steps = Vec::new();
+    for step in &scenario.steps {
+        let report = run_step(&client, base_url, api_key, step).await;
+        let passed = report.passed;
+        tracing::info!(
+            "[SCENARIO] {} / {}: {} status={:?} {}ms",
+            scenario.name,
+            report.name,
+            if passed { "通过" } else { "失败" },
+            report.status,
+            report.latency_ms
+        );
+        steps.push(report);
+        if !passed && !scenario.continue_on_failure {
+            break;
+        }
+    }
+    Ok(ScenarioReport {
+        name: scenario.name.clone(),
+        source: None,
+        passed: steps.len() == scenario.steps.len() && steps.iter().all(|s| s.passed),
+        skipped: scenario.steps.len() - steps.len(),
+        steps,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// 依次执行多个场景文件，加载失败的文件记为未通过的场景
+pub async fn run_scenario_files(
+    base_url: &str,
+    api_key: &str,
+    files: &[PathBuf],
+) -> Vec<ScenarioReport> {
+    let mut reports = Vec::new();
+    for path in files {
+        let source = Some(path.display().to_string());
+        let report = match load_scenario(path) {
+            Ok(scenario) => run_scenario(base_url, api_key, &scenario).await,
+            Err(e) => Err(e),
+        };
+        reports.push(match report {
+            Ok(report) => ScenarioReport { source, ..report },
+            Err(e) => ScenarioReport {
+                name: path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                source,
+                passed: false,
+                steps: vec![StepReport {
+                    name: "加载场景".to_string(),
+                    passed: false,
+                    status: None,
+                    latency_ms: 0,
+                    failures: vec![e],
+                }],
+                skipped: 0,
+                duration_ms: 0,
+            },
+        });
+    }
+    reports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_parse_and_check_expectation() {
+        let scenario = parse_scenario(
+            r#"
+name: smoke
+steps:
+  - name: models
+    path: /v1/models
+  - name: reject
+    path: /v1/models
+    auth: false
+    expect: { status: 401, max_latency_ms: 50, body_contains: ["invalid"] }
+"#,
+        )
+        .unwrap();
+        assert!(scenario.steps[0].auth);
+        assert_eq!(scenario.steps[1].expect.status, Some(401));
+        assert!(parse_scenario("name: empty\nsteps: []").is_err());
+        assert!(parse_scenario("name: x\nsteps:\n  - name: a\n    path: v1").is_err());
+
+        let expect = &scenario.steps[1].expect;
+        assert!(check_expectation(expect, 401, 10, "invalid key").is_empty());
+        let failures = check_expectation(expect, 200, 80, "{}");
+        assert_eq!(failures.len(), 3);
+        assert!(check_expectation(&StepExpectation::default(), 204, 1, "").is_empty());
+        assert!(!check_expectation(&StepExpectation::default(), 500, 1, "boom").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_run_scenario_stops_after_failure() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    let n = socket.read(&mut buf).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buf[..n]).to_ascii_lowercase();
+                    let response = if request.contains("authorization: bearer pc_test") {
+                        "HTTP/1.1 200 OK\r\nContent-Length: 8\r\nConnection: close\r\n\r\n{\"ok\":1}"
+                    } else {
+                        "HTTP/1.1 401 Unauthorized\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}"
+                    };
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        let scenario = parse_scenario(
+            r#"
+name: local
+steps:
+  - name: authorized
+    path: /v1/models
+    expect: { body_contains: ["ok"] }
+  - name: unauthorized should pass
+    path: /v1/models
+    auth: false
+    expect: { status: 401 }
+  - name: unauthorized fails
+    path: /v1/models
+    auth: false
+  - name: never runs
+    path: /v1/models
+"#,
+        )
+        .unwrap();
+        let report = run_scenario(&format!("http://{addr}/"), "pc_test", &scenario)
+            .await
+            .unwrap();
+        assert!(!report.passed);
+        assert_eq!(report.steps.len(), 3);
+        assert!(report.steps[0].passed);
+        assert!(report.steps[1].passed);
+        assert_eq!(report.steps[2].status, Some(401));
+        assert_eq!(report.skipped, 1);
+    }
+}
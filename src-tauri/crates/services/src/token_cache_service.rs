@@ -11,7 +11,7 @@
 use crate::kiro_event_service::KiroEventService;
 use chrono::Utc;
 use dashmap::DashMap;
-use lime_core::database::dao::provider_pool::ProviderPoolDao;
+use lime_core::database::pool_storage::{lock_pool, pool_storage};
 use lime_core::database::DbConnection;
 use lime_core::models::provider_pool_model::{
     CachedTokenInfo, CredentialData, PoolProviderType, ProviderCredential,
@@ -81,13 +81,13 @@ impl TokenCacheService {
     pub async fn get_valid_token(&self, db: &DbConnection, uuid: &str) -> Result<String, String> {
         // 首先检查缓存
         let cached = {
-            let conn = db.lock().map_err(|e| e.to_string())?;
-            ProviderPoolDao::get_token_cache(&conn, uuid).map_err(|e| e.to_string())?
+            let conn = lock_pool(db)?;
+            pool_storage().get_token_cache(&conn, uuid)?
         };
 
-        // 缓存有效且未即将过期，直接返回
+        // 缓存有效且未即将过期，直接返回；非主实例不提前刷新，由主实例后台刷新
         if let Some(ref cache) = cached {
-            if cache.is_valid() && !cache.is_expiring_soon() {
+            if cache.is_valid() && (!cache.is_expiring_soon() || !lime_core::cluster::is_leader()) {
                 if let Some(token) = &cache.access_token {
                     tracing::debug!(
                         "[TOKEN_CACHE] Using cached token for {}, expires at {:?}",
@@ -121,9 +121,9 @@ impl TokenCacheService {
 
                         // 获取凭证信息
                         let credential = {
-                            let conn = db.lock().map_err(|e| e.to_string())?;
-                            ProviderPoolDao::get_by_uuid(&conn, uuid)
-                                .map_err(|e| e.to_string())?
+                            let conn = lock_pool(db)?;
+                            pool_storage()
+                                .get_by_uuid(&conn, uuid)?
                                 .ok_or_else(|| format!("Credential not found: {uuid}"))?
                         };
 
@@ -150,8 +150,8 @@ impl TokenCacheService {
                                     };
 
                                     // 缓存到数据库
-                                    if let Ok(conn) = db.lock() {
-                                        let _ = ProviderPoolDao::update_token_cache(
+                                    if let Ok(conn) = lock_pool(db) {
+                                        let _ = pool_storage().update_token_cache(
                                             &conn,
                                             uuid,
                                             &cache_info,
@@ -192,8 +192,8 @@ impl TokenCacheService {
                 }
 
                 // 更新错误计数
-                if let Ok(conn) = db.lock() {
-                    let _ = ProviderPoolDao::record_token_refresh_error(
+                if let Ok(conn) = lock_pool(db) {
+                    let _ = pool_storage().record_token_refresh_error(
                         &conn,
                         uuid,
                         &format!(
@@ -253,8 +253,8 @@ impl TokenCacheService {
         // 双重检查：可能其他线程已完成刷新
         if !force {
             let cached = {
-                let conn = db.lock().map_err(|e| e.to_string())?;
-                ProviderPoolDao::get_token_cache(&conn, uuid).map_err(|e| e.to_string())?
+                let conn = lock_pool(db)?;
+                pool_storage().get_token_cache(&conn, uuid)?
             };
 
             if let Some(cache) = cached {
@@ -272,9 +272,9 @@ impl TokenCacheService {
 
         // 获取凭证信息
         let credential = {
-            let conn = db.lock().map_err(|e| e.to_string())?;
-            ProviderPoolDao::get_by_uuid(&conn, uuid)
-                .map_err(|e| e.to_string())?
+            let conn = lock_pool(db)?;
+            pool_storage()
+                .get_by_uuid(&conn, uuid)?
                 .ok_or_else(|| format!("Credential not found: {uuid}"))?
         };
 
@@ -298,9 +298,8 @@ impl TokenCacheService {
             Ok(token_info) => {
                 // 缓存到数据库
                 {
-                    let conn = db.lock().map_err(|e| e.to_string())?;
-                    ProviderPoolDao::update_token_cache(&conn, uuid, &token_info)
-                        .map_err(|e| e.to_string())?;
+                    let conn = lock_pool(db)?;
+                    pool_storage().update_token_cache(&conn, uuid, &token_info)?;
                 }
 
                 let token = token_info
@@ -336,8 +335,8 @@ impl TokenCacheService {
             Err(e) => {
                 // 记录刷新错误
                 {
-                    let conn = db.lock().map_err(|e| e.to_string())?;
-                    let _ = ProviderPoolDao::record_token_refresh_error(&conn, uuid, &e);
+                    let conn = lock_pool(db)?;
+                    let _ = pool_storage().record_token_refresh_error(&conn, uuid, &e);
                 }
 
                 tracing::error!(
@@ -661,9 +660,9 @@ impl TokenCacheService {
         uuid: &str,
     ) -> Result<String, String> {
         let credential = {
-            let conn = db.lock().map_err(|e| e.to_string())?;
-            ProviderPoolDao::get_by_uuid(&conn, uuid)
-                .map_err(|e| e.to_string())?
+            let conn = lock_pool(db)?;
+            pool_storage()
+                .get_by_uuid(&conn, uuid)?
                 .ok_or_else(|| format!("Credential not found: {uuid}"))?
         };
 
@@ -672,9 +671,8 @@ impl TokenCacheService {
 
         // 缓存到数据库
         {
-            let conn = db.lock().map_err(|e| e.to_string())?;
-            ProviderPoolDao::update_token_cache(&conn, uuid, &token_info)
-                .map_err(|e| e.to_string())?;
+            let conn = lock_pool(db)?;
+            pool_storage().update_token_cache(&conn, uuid, &token_info)?;
         }
 
         token_info
@@ -854,8 +852,8 @@ impl TokenCacheService {
 
     /// 清除凭证的 Token 缓存
     pub fn clear_cache(&self, db: &DbConnection, uuid: &str) -> Result<(), String> {
-        let conn = db.lock().map_err(|e| e.to_string())?;
-        ProviderPoolDao::clear_token_cache(&conn, uuid).map_err(|e| e.to_string())
+        let conn = lock_pool(db)?;
+        pool_storage().clear_token_cache(&conn, uuid)
     }
 
     /// 检查凭证类型是否支持 Token 刷新
@@ -872,8 +870,8 @@ impl TokenCacheService {
         db: &DbConnection,
         uuid: &str,
     ) -> Result<Option<CachedTokenInfo>, String> {
-        let conn = db.lock().map_err(|e| e.to_string())?;
-        ProviderPoolDao::get_token_cache(&conn, uuid).map_err(|e| e.to_string())
+        let conn = lock_pool(db)?;
+        pool_storage().get_token_cache(&conn, uuid)
     }
 
     /// 计算刷新延迟时间（毫秒）
@@ -1028,14 +1026,15 @@ impl TokenCacheService {
     ) -> Result<String, String> {
         // 首先检查缓存
         let cached = {
-            let conn = db.lock().map_err(|e| e.to_string())?;
-            lime_core::database::dao::provider_pool::ProviderPoolDao::get_token_cache(&conn, uuid)
-                .map_err(|e| e.to_string())?
+            let conn = lock_pool(db)?;
+            pool_storage().get_token_cache(&conn, uuid)?
         };
 
         // 检查是否需要提前刷新（使用指定的分钟数阈值）
         if let Some(ref cache) = cached {
-            if cache.is_valid() && !cache.is_expiring_within_minutes(minutes) {
+            if cache.is_valid()
+                && (!cache.is_expiring_within_minutes(minutes) || !lime_core::cluster::is_leader())
+            {
                 if let Some(token) = &cache.access_token {
                     tracing::debug!(
                         "[TOKEN_CACHE] Token valid for streaming ({}min threshold) for {}, expires at {:?}",
@@ -1,4 +1,7 @@
+use lime_core::config::UpdateChannel;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -23,6 +26,22 @@ pub struct UpdateInfo {
     pub checked_at: u64,
     /// 错误信息
     pub error: Option<String>,
+    /// 检查所用的更新通道
+    #[serde(default)]
+    pub channel: UpdateChannel,
+    /// 分阶段推送比例（0-100），清单未声明时为 `None`（全量）
+    #[serde(default)]
+    pub rollout_percent: Option<u8>,
+    /// 本机是否已进入推送范围
+    #[serde(default = "default_in_rollout")]
+    pub in_rollout: bool,
+    /// 安装包已在后台下载并通过签名校验，可直接安装
+    #[serde(default)]
+    pub ready_to_install: bool,
+}
+
+fn default_in_rollout() -> bool {
+    true
 }
 
 /// 更新检查服务状态
@@ -34,6 +53,8 @@ pub struct UpdateCheckState {
     pub last_result: Option<UpdateInfo>,
     /// 下次检查时间（Unix 时间戳）
     pub next_check_at: Option<u64>,
+    /// 已在后台下载完成的版本
+    pub downloaded_version: Option<String>,
 }
 
 /// 更新检查服务
@@ -69,10 +90,24 @@ impl UpdateCheckService {
     pub async fn finish_check(&self, result: UpdateInfo) -> UpdateInfo {
         let mut state = self.state.write().await;
         state.is_checking = false;
+        let mut result = result;
+        result.ready_to_install = result.has_update
+            && state.downloaded_version.is_some()
+            && state.downloaded_version == result.latest_version;
         state.last_result = Some(result.clone());
         result
     }
 
+    /// 记录后台下载完成的版本（`None` 表示清除）
+    pub async fn set_downloaded_version(&self, version: Option<String>) {
+        let mut state = self.state.write().await;
+        if let Some(result) = state.last_result.as_mut() {
+            result.ready_to_install =
+                result.has_update && version.is_some() && version == result.latest_version;
+        }
+        state.downloaded_version = version;
+    }
+
     /// 检查是否需要执行更新检查
     pub fn should_check(
         last_check_timestamp: u64,
@@ -102,12 +137,21 @@ impl UpdateCheckService {
         true
     }
     /// 版本比较：返回 true 如果 latest > current
+    ///
+    /// 支持 `-beta.1` 这类预发布后缀：同一版本号下正式版高于预发布版，
+    /// 预发布标识逐段比较（数字段按数值，其余按字符串）。
     pub fn version_compare(current: &str, latest: &str) -> bool {
-        let current = current.trim_start_matches('v');
-        let latest = latest.trim_start_matches('v');
+        let (current_core, current_pre) = split_prerelease(current);
+        let (latest_core, latest_pre) = split_prerelease(latest);
 
-        let current_parts: Vec<u32> = current.split('.').filter_map(|s| s.parse().ok()).collect();
-        let latest_parts: Vec<u32> = latest.split('.').filter_map(|s| s.parse().ok()).collect();
+        let current_parts: Vec<u32> = current_core
+            .split('.')
+            .filter_map(|s| s.parse().ok())
+            .collect();
+        let latest_parts: Vec<u32> = latest_core
+            .split('.')
+            .filter_map(|s| s.parse().ok())
+            .collect();
 
         let max_len = current_parts.len().max(latest_parts.len());
 
@@ -122,7 +166,69 @@ impl UpdateCheckService {
             }
         }
 
-        false
+        match (current_pre, latest_pre) {
+            (Some(_), None) => true,
+            (Some(current_pre), Some(latest_pre)) => {
+                compare_prerelease(latest_pre, current_pre) == Ordering::Greater
+            }
+            _ => false,
+        }
+    }
+
+    /// 是否为预发布版本（带 `-` 后缀）
+    pub fn is_prerelease(version: &str) -> bool {
+        split_prerelease(version).1.is_some()
+    }
+
+    /// 分阶段推送分桶：同一安装标识与版本总是落在同一个 0-99 的桶
+    pub fn rollout_bucket(install_id: &str, version: &str) -> u8 {
+        let digest = Sha256::digest(format!(
+            "{}:{}",
+            install_id,
+            version.trim_start_matches('v')
+        ));
+        (u16::from_be_bytes([digest[0], digest[1]]) % 100) as u8
+    }
+
+    /// 本机是否在推送范围内；未声明比例视为全量
+    pub fn in_rollout(install_id: &str, version: &str, rollout_percent: Option<u8>) -> bool {
+        match rollout_percent {
+            None => true,
+            Some(percent) => Self::rollout_bucket(install_id, version) < percent.min(100),
+        }
+    }
+}
+
+/// 拆分版本号与预发布后缀（忽略 `+` 之后的构建元数据）
+fn split_prerelease(version: &str) -> (&str, Option<&str>) {
+    let version = version.trim_start_matches('v');
+    let version = version.split('+').next().unwrap_or(version);
+    match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    }
+}
+
+fn compare_prerelease(a: &str, b: &str) -> Ordering {
+    let mut a_parts = a.split('.');
+    let mut b_parts = b.split('.');
+    loop {
+        match (a_parts.next(), b_parts.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => {
+                let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+                    (Ok(x), Ok(y)) => x.cmp(&y),
+                    (Ok(_), Err(_)) => Ordering::Less,
+                    (Err(_), Ok(_)) => Ordering::Greater,
+                    (Err(_), Err(_)) => x.cmp(y),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
     }
 }
 
@@ -162,6 +268,63 @@ mod tests {
         assert!(!UpdateCheckService::version_compare("1.0.0", "0.14.0"));
     }
 
+    #[test]
+    fn test_prerelease_compare_and_rollout() {
+        assert!(UpdateCheckService::version_compare(
+            "0.14.0",
+            "0.15.0-beta.1"
+        ));
+        assert!(UpdateCheckService::version_compare(
+            "0.15.0-beta.1",
+            "0.15.0-beta.2"
+        ));
+        assert!(UpdateCheckService::version_compare(
+            "0.15.0-beta.2",
+            "0.15.0-beta.10"
+        ));
+        assert!(UpdateCheckService::version_compare(
+            "0.15.0-beta.2",
+            "0.15.0"
+        ));
+        assert!(!UpdateCheckService::version_compare(
+            "0.15.0",
+            "0.15.0-beta.3"
+        ));
+        assert!(!UpdateCheckService::version_compare(
+            "0.15.0-beta.1",
+            "0.15.0-beta.1"
+        ));
+        assert!(UpdateCheckService::is_prerelease("v0.15.0-rc.1"));
+
+        let bucket = UpdateCheckService::rollout_bucket("install-a", "0.15.0");
+        assert_eq!(
+            bucket,
+            UpdateCheckService::rollout_bucket("install-a", "v0.15.0")
+        );
+        assert!(bucket < 100);
+        assert!(UpdateCheckService::in_rollout("install-a", "0.15.0", None));
+        assert!(UpdateCheckService::in_rollout(
+            "install-a",
+            "0.15.0",
+            Some(100)
+        ));
+        assert!(!UpdateCheckService::in_rollout(
+            "install-a",
+            "0.15.0",
+            Some(0)
+        ));
+        assert!(UpdateCheckService::in_rollout(
+            "install-a",
+            "0.15.0",
+            Some(bucket + 1)
+        ));
+        assert!(!UpdateCheckService::in_rollout(
+            "install-a",
+            "0.15.0",
+            Some(bucket)
+        ));
+    }
+
     #[test]
     fn test_should_check() {
         let now = std::time::SystemTime::now()
@@ -32,7 +32,7 @@ use lime_core::config::{AsrCredentialEntry, AsrProviderType};
 
 use super::voice_config_service;
 use voice_core::asr_client::{AsrClient, BaiduClient, OpenAIWhisperClient, XunfeiClient};
-use voice_core::types::AudioData;
+use voice_core::types::{AudioData, TranscribeOptions, TranscribeResult};
 
 /// ASR 服务
 pub struct AsrService;
@@ -56,6 +56,19 @@ impl AsrService {
         audio_data: &[u8],
         sample_rate: u32,
     ) -> Result<String, String> {
+        Self::transcribe_detailed(credential, audio_data, sample_rate)
+            .await
+            .map(|result| result.text)
+    }
+
+    /// 使用指定凭证进行语音识别，返回分段、词级时间戳与说话人信息
+    ///
+    /// 可通过 [`TranscribeResult::to_verbose_json`] 转换为 OpenAI `verbose_json` 格式。
+    pub async fn transcribe_detailed(
+        credential: &AsrCredentialEntry,
+        audio_data: &[u8],
+        sample_rate: u32,
+    ) -> Result<TranscribeResult, String> {
         // 如果是本地 Whisper，直接调用
         if matches!(credential.provider, AsrProviderType::WhisperLocal) {
             return Self::transcribe_whisper_local(credential, audio_data, sample_rate).await;
@@ -95,9 +108,9 @@ impl AsrService {
                 match Self::transcribe_whisper_local(&whisper_credential, audio_data, sample_rate)
                     .await
                 {
-                    Ok(text) => {
+                    Ok(result) => {
                         tracing::info!("本地 Whisper 回退识别成功");
-                        Ok(text)
+                        Ok(result)
                     }
                     Err(whisper_error) => {
                         tracing::error!("本地 Whisper 回退也失败: {}", whisper_error);
@@ -134,7 +147,7 @@ impl AsrService {
         credential: &AsrCredentialEntry,
         audio_data: &[u8],
         sample_rate: u32,
-    ) -> Result<String, String> {
+    ) -> Result<TranscribeResult, String> {
         // 获取 Whisper 配置
         let whisper_config = credential
             .whisper_config
@@ -165,7 +178,7 @@ impl AsrService {
             .transcribe(&audio)
             .map_err(|e| format!("Whisper 识别失败: {e}"))?;
 
-        Ok(result.text)
+        Ok(result)
     }
 
     /// 本地 Whisper 识别（未启用 local-whisper feature 时的 stub）
@@ -174,7 +187,7 @@ impl AsrService {
         _credential: &AsrCredentialEntry,
         _audio_data: &[u8],
         _sample_rate: u32,
-    ) -> Result<String, String> {
+    ) -> Result<TranscribeResult, String> {
         Err("本地 Whisper 功能未启用。请使用云端 ASR 服务（OpenAI、百度、讯飞）".to_string())
     }
 
@@ -226,16 +239,21 @@ impl AsrService {
         credential: &AsrCredentialEntry,
         audio_data: &[u8],
         sample_rate: u32,
-    ) -> Result<String, String> {
+    ) -> Result<TranscribeResult, String> {
         let config = credential.openai_config.as_ref().ok_or("OpenAI 配置缺失")?;
         let audio = Self::build_audio_data(audio_data, sample_rate)?;
 
-        let mut client = OpenAIWhisperClient::new(config.api_key.clone());
+        let mut client =
+            OpenAIWhisperClient::new(config.api_key.clone()).with_options(TranscribeOptions {
+                language: Some(credential.language.clone()).filter(|lang| !lang.is_empty()),
+                word_timestamps: config.word_timestamps,
+                diarization: config.diarization,
+            });
         if let Some(base_url) = config.base_url.clone() {
             client = client.with_host(base_url);
         }
-        if !credential.language.is_empty() {
-            client = client.with_language(credential.language.clone());
+        if let Some(model) = config.model.clone().filter(|model| !model.is_empty()) {
+            client = client.with_model(model);
         }
 
         let result = client
@@ -243,7 +261,7 @@ impl AsrService {
             .await
             .map_err(|e| format!("OpenAI Whisper 识别失败: {e}"))?;
 
-        Ok(result.text)
+        Ok(result)
     }
 
     /// 百度语音识别
@@ -251,7 +269,7 @@ impl AsrService {
         credential: &AsrCredentialEntry,
         audio_data: &[u8],
         sample_rate: u32,
-    ) -> Result<String, String> {
+    ) -> Result<TranscribeResult, String> {
         let config = credential.baidu_config.as_ref().ok_or("百度配置缺失")?;
         let audio = Self::build_audio_data(audio_data, sample_rate)?;
 
@@ -261,7 +279,7 @@ impl AsrService {
             .await
             .map_err(|e| format!("百度识别失败: {e}"))?;
 
-        Ok(result.text)
+        Ok(result)
     }
 
     /// 讯飞语音识别
@@ -271,7 +289,7 @@ impl AsrService {
         credential: &AsrCredentialEntry,
         audio_data: &[u8],
         sample_rate: u32,
-    ) -> Result<String, String> {
+    ) -> Result<TranscribeResult, String> {
         let config = credential.xunfei_config.as_ref().ok_or("讯飞配置缺失")?;
         let audio = Self::build_audio_data(audio_data, sample_rate)?;
 
@@ -295,7 +313,7 @@ impl AsrService {
             .await
             .map_err(|e| format!("讯飞识别失败: {e}"))?;
 
-        Ok(result.text)
+        Ok(result)
     }
 
     /// 将 PCM 字节构造成 voice-core 的 AudioData
@@ -16,6 +16,9 @@ pub struct TranscribeResult {
     pub text: String,
     /// 使用的 ASR 服务
     pub provider: String,
+    /// OpenAI `verbose_json` 格式的详细结果（服务返回分段或词级时间戳时提供）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verbose: Option<serde_json::Value>,
 }
 
 /// 润色文本结果
@@ -99,12 +102,15 @@ pub async fn transcribe_audio(
     let provider_name = voice_config_service::asr_provider_name(credential.provider);
     tracing::info!("[语音识别] 使用服务: {}", provider_name);
 
-    let text = AsrService::transcribe(&credential, audio_data, sample_rate).await?;
-    tracing::info!("[语音识别] 识别完成，文本长度: {} 字符", text.len());
+    let result = AsrService::transcribe_detailed(&credential, audio_data, sample_rate).await?;
+    tracing::info!("[语音识别] 识别完成，文本长度: {} 字符", result.text.len());
 
+    let verbose =
+        (!result.segments.is_empty() || !result.words.is_empty()).then(|| result.to_verbose_json());
     Ok(TranscribeResult {
-        text,
+        text: result.text,
         provider: provider_name.to_string(),
+        verbose,
     })
 }
 
@@ -0,0 +1,252 @@
+//! 本地模型预热
+//!
+//! 冷启动较慢的本地后端（Ollama、llama.cpp 等）首次请求需要先把模型加载进内存，
+//! 可能等待数十秒。启用 `server.warm_pool` 后，服务启动时即对配置的模型发送预热请求，
+//! 之后按间隔发送保活请求使其常驻：
+//! - Ollama：`POST /api/generate`，空 prompt 只加载模型，`keep_alive` 控制保留时长
+//! - 其他 OpenAI 兼容服务：`POST /v1/chat/completions`，生成 1 个 Token
+//!
+//! 各模型的状态随仪表盘统计快照推送给前端。
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use lime_core::app_events::{WarmModelState, WarmModelStatus};
+use lime_core::config::{WarmModel, WarmPoolSettings};
+use lime_core::database::dao::api_key_provider::ProviderWithKeys;
+use lime_core::database::DbConnection;
+use parking_lot::RwLock;
+use serde_json::{json, Value};
+
+use crate::api_key_provider_service::ApiKeyProviderService;
+use crate::local_resource_service::{local_targets, LocalProviderKind, LocalTarget};
+
+/// 单次预热请求的超时（冷启动加载大模型可能较慢）
+const WARM_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// 预热请求的地址与请求体
+pub fn warm_request(target: &LocalTarget, model: &str, keep_alive: &str) -> (String, Value) {
+    // Ollama 的 `keep_alive` 接受时长字符串或秒数（负数表示一直保留）
+    let keep_alive = keep_alive
+        .trim()
+        .parse::<i64>()
+        .map(Value::from)
+        .unwrap_or_else(|_| Value::from(keep_alive.trim()));
+    match target.kind {
+        LocalProviderKind::Ollama => (
+            format!("{}/api/generate", target.base_url),
+            json!({ "model": model, "prompt": "", "keep_alive": keep_alive, "stream": false }),
+        ),
+        LocalProviderKind::OpenAiCompatible => (
+            format!("{}/v1/chat/completions", target.base_url),
+            json!({
+                "model": model,
+                "messages": [{ "role": "user", "content": "ping" }],
+                "max_tokens": 1,
+                "stream": false,
+            }),
+        ),
+    }
+}
+
+/// 按 Provider ID 或名称（不区分大小写）查找本地服务
+pub fn resolve_target<'a>(targets: &'a [LocalTarget], provider: &str) -> Option<&'a LocalTarget> {
+    let provider = provider.trim();
+    targets
+        .iter()
+        .find(|target| target.provider_id == provider)
+        .or_else(|| {
+            targets
+                .iter()
+                .find(|target| target.name.eq_ignore_ascii_case(provider))
+        })
+}
+
+/// 本地模型预热管理器
+pub struct WarmPool {
+    settings: WarmPoolSettings,
+    client: reqwest::Client,
+    statuses: RwLock<Vec<WarmModelStatus>>,
+}
+
+impl WarmPool {
+    /// 未启用或未配置模型时返回 `None`
+    pub fn from_settings(settings: &WarmPoolSettings) -> Option<Self> {
+        if !settings.enabled || settings.models.is_empty() {
+            return None;
+        }
+        let statuses = settings
+            .models
+            .iter()
+            .map(|warm| WarmModelStatus {
+                provider: warm.provider.clone(),
+                model: warm.model.clone(),
+                ..Default::default()
+            })
+            .collect();
+        Some(Self {
+            settings: settings.clone(),
+            client: reqwest::Client::builder()
+                .timeout(WARM_TIMEOUT)
+                .no_proxy()
+                .build()
+                .unwrap_or_default(),
+            statuses: RwLock::new(statuses),
+        })
+    }
+
+    /// 保活间隔
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.settings.keep_alive_interval_secs.max(10))
+    }
+
+    /// 各模型的当前状态
+    pub fn statuses(&self) -> Vec<WarmModelStatus> {
+        self.statuses.read().clone()
+    }
+
+    /// 对全部配置的模型发送预热 / 保活请求
+    pub async fn warm_all(&self, providers: &[ProviderWithKeys]) {
+        let targets = local_targets(providers);
+        for (index, warm) in self.settings.models.iter().enumerate() {
+            let outcome = match resolve_target(&targets, &warm.provider) {
+                Some(target) => Some(self.warm_one(target, warm).await),
+                None => None,
+            };
+            let mut statuses = self.statuses.write();
+            let Some(status) = statuses.get_mut(index) else {
+                continue;
+            };
+            match outcome {
+                Some(Ok(latency_ms)) => {
+                    if status.state != WarmModelState::Ready {
+                        tracing::info!(
+                            "[WARM_POOL] 模型 {} 已加载（{}，耗时 {}ms）",
+                            warm.model,
+                            warm.provider,
+                            latency_ms
+                        );
+                    }
+                    status.state = WarmModelState::Ready;
+                    status.last_warmed_at_ms = Some(chrono::Utc::now().timestamp_millis());
+                    status.latency_ms = Some(latency_ms);
+                    status.error = None;
+                }
+                Some(Err(e)) => {
+                    tracing::warn!("[WARM_POOL] 模型 {} 预热失败: {}", warm.model, e);
+                    status.state = WarmModelState::Failed;
+                    status.error = Some(e);
+                }
+                None => {
+                    status.state = WarmModelState::Unavailable;
+                    status.error = Some(format!("未找到已启用的本地 Provider: {}", warm.provider));
+                }
+            }
+        }
+    }
+
+    async fn warm_one(&self, target: &LocalTarget, warm: &WarmModel) -> Result<u64, String> {
+        let (url, body) = warm_request(target, &warm.model, &self.settings.keep_alive);
+        let started = Instant::now();
+        let response = self
+            .client
+            .post(&url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("请求失败: {e}"))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            let text: String = text.chars().take(200).collect();
+            return Err(format!("HTTP {status}: {text}"));
+        }
+        // 读完响应体，确保模型确实完成加载
+        let _ = response.bytes().await;
+        Ok(started.elapsed().as_millis() as u64)
+    }
+}
+
+/// 启动预热任务：立即预热一次，之后按间隔保活
+pub fn spawn(
+    pool: Arc<WarmPool>,
+    db: Option<DbConnection>,
+    api_keys: Arc<ApiKeyProviderService>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(pool.interval());
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let providers = db
+                .as_ref()
+                .and_then(|db| api_keys.get_all_providers(db).ok())
+                .unwrap_or_default();
+            pool.warm_all(&providers).await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(kind: LocalProviderKind, id: &str, name: &str) -> LocalTarget {
+        LocalTarget {
+            provider_id: id.to_string(),
+            name: name.to_string(),
+            kind,
+            base_url: "http://localhost:11434".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_warm_request_per_backend() {
+        let (url, body) = warm_request(
+            &target(LocalProviderKind::Ollama, "p1", "Ollama"),
+            "qwen3:8b",
+            "-1",
+        );
+        assert_eq!(url, "http://localhost:11434/api/generate");
+        assert_eq!(body["prompt"], "");
+        assert_eq!(body["keep_alive"], -1);
+
+        let (url, body) = warm_request(
+            &target(LocalProviderKind::OpenAiCompatible, "p2", "llama"),
+            "local",
+            "30m",
+        );
+        assert_eq!(url, "http://localhost:11434/v1/chat/completions");
+        assert_eq!(body["max_tokens"], 1);
+        assert!(body.get("keep_alive").is_none());
+    }
+
+    #[test]
+    fn test_resolve_target_and_disabled_pool() {
+        let targets = vec![
+            target(LocalProviderKind::Ollama, "ollama-1", "Home Ollama"),
+            target(LocalProviderKind::OpenAiCompatible, "llama-1", "llama.cpp"),
+        ];
+        assert_eq!(
+            resolve_target(&targets, "llama-1").map(|t| t.name.as_str()),
+            Some("llama.cpp")
+        );
+        assert_eq!(
+            resolve_target(&targets, " home ollama ").map(|t| t.provider_id.as_str()),
+            Some("ollama-1")
+        );
+        assert!(resolve_target(&targets, "missing").is_none());
+
+        assert!(WarmPool::from_settings(&WarmPoolSettings::default()).is_none());
+        let pool = WarmPool::from_settings(&WarmPoolSettings {
+            enabled: true,
+            models: vec![WarmModel {
+                provider: "ollama-1".to_string(),
+                model: "qwen3:8b".to_string(),
+            }],
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(pool.statuses()[0].state, WarmModelState::Pending);
+    }
+}
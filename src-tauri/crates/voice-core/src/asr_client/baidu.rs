@@ -135,6 +135,7 @@ impl AsrClient for BaiduClient {
             language: Some("zh".to_string()),
             confidence: None,
             segments: vec![],
+            words: vec![],
         })
     }
 
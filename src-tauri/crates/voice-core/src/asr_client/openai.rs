@@ -8,14 +8,99 @@ use serde::Deserialize;
 
 use super::AsrClient;
 use crate::error::{Result, VoiceError};
-use crate::types::{AudioData, TranscribeResult};
+use crate::types::{AudioData, Segment, TranscribeOptions, TranscribeResult, Word};
 
-/// OpenAI Whisper 响应
+/// OpenAI Whisper 响应（兼容 json / verbose_json / diarized_json）
 #[derive(Debug, Deserialize)]
 struct WhisperResponse {
     text: String,
     #[serde(default)]
     language: Option<String>,
+    #[serde(default)]
+    segments: Vec<WhisperSegment>,
+    #[serde(default)]
+    words: Vec<WhisperWord>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperSegment {
+    start: f32,
+    end: f32,
+    text: String,
+    /// 说话人标识（不同服务可能返回字符串 "A" 或数字）
+    #[serde(default)]
+    speaker: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperWord {
+    word: String,
+    start: f32,
+    end: f32,
+}
+
+/// 将服务返回的说话人标识按首次出现顺序映射为 `speaker_0`、`speaker_1`…
+fn map_speakers(segments: &[WhisperSegment]) -> Vec<Option<String>> {
+    let mut seen: Vec<String> = Vec::new();
+    segments
+        .iter()
+        .map(|segment| {
+            let raw = match segment.speaker.as_ref()? {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            let index = match seen.iter().position(|s| s == &raw) {
+                Some(index) => index,
+                None => {
+                    seen.push(raw);
+                    seen.len() - 1
+                }
+            };
+            Some(format!("speaker_{index}"))
+        })
+        .collect()
+}
+
+/// 将 Whisper 响应转换为统一的识别结果
+fn convert_response(response: WhisperResponse) -> TranscribeResult {
+    let speakers = map_speakers(&response.segments);
+    let segments: Vec<Segment> = response
+        .segments
+        .into_iter()
+        .zip(speakers)
+        .map(|(segment, speaker)| Segment {
+            start: segment.start,
+            end: segment.end,
+            text: segment.text.trim().to_string(),
+            speaker,
+        })
+        .collect();
+
+    // 词条按开始时间归属到所在分段，继承分段的说话人
+    let words = response
+        .words
+        .into_iter()
+        .map(|word| {
+            let speaker = segments
+                .iter()
+                .find(|segment| word.start >= segment.start && word.start < segment.end)
+                .and_then(|segment| segment.speaker.clone());
+            Word {
+                word: word.word,
+                start: word.start,
+                end: word.end,
+                speaker,
+            }
+        })
+        .collect();
+
+    TranscribeResult {
+        text: response.text,
+        language: response.language,
+        confidence: None,
+        segments,
+        words,
+    }
 }
 
 /// OpenAI Whisper 客户端
@@ -23,7 +108,7 @@ pub struct OpenAIWhisperClient {
     api_key: String,
     api_host: String,
     model: String,
-    language: Option<String>,
+    options: TranscribeOptions,
 }
 
 impl OpenAIWhisperClient {
@@ -33,7 +118,7 @@ impl OpenAIWhisperClient {
             api_key,
             api_host: "https://api.openai.com".to_string(),
             model: "whisper-1".to_string(),
-            language: None,
+            options: TranscribeOptions::default(),
         }
     }
 
@@ -43,9 +128,21 @@ impl OpenAIWhisperClient {
         self
     }
 
+    /// 设置模型（说话人分离需使用支持 diarization 的模型）
+    pub fn with_model(mut self, model: String) -> Self {
+        self.model = model;
+        self
+    }
+
     /// 设置语言
     pub fn with_language(mut self, language: String) -> Self {
-        self.language = Some(language);
+        self.options.language = Some(language);
+        self
+    }
+
+    /// 设置识别选项（语言提示、词级时间戳、说话人分离）
+    pub fn with_options(mut self, options: TranscribeOptions) -> Self {
+        self.options = options;
         self
     }
 }
@@ -66,10 +163,27 @@ impl AsrClient for OpenAIWhisperClient {
             .part("file", file_part)
             .text("model", self.model.clone());
 
-        if let Some(ref lang) = self.language {
+        if let Some(lang) = self
+            .options
+            .language
+            .as_ref()
+            .filter(|lang| !lang.is_empty() && lang.as_str() != "auto")
+        {
             form = form.text("language", lang.clone());
         }
 
+        if self.options.diarization {
+            // 说话人分离：分段附带 speaker 字段（不支持词级时间戳）
+            form = form
+                .text("response_format", "diarized_json")
+                .text("chunking_strategy", "auto");
+        } else if self.options.word_timestamps {
+            form = form
+                .text("response_format", "verbose_json")
+                .text("timestamp_granularities[]", "word")
+                .text("timestamp_granularities[]", "segment");
+        }
+
         // 发送请求
         let client = reqwest::Client::new();
         let response = client
@@ -93,15 +207,89 @@ impl AsrClient for OpenAIWhisperClient {
             .await
             .map_err(|e| VoiceError::AsrError(e.to_string()))?;
 
-        Ok(TranscribeResult {
-            text: result.text,
-            language: result.language,
-            confidence: None,
-            segments: vec![],
-        })
+        Ok(convert_response(result))
     }
 
     fn name(&self) -> &'static str {
         "OpenAI Whisper"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(body: &str) -> WhisperResponse {
+        serde_json::from_str(body).expect("样例响应应能解析")
+    }
+
+    #[test]
+    fn test_diarized_response_maps_speakers_in_order() {
+        let response = parse(
+            r#"{
+                "text": "你好 在吗 在的",
+                "segments": [
+                    {"start": 0.0, "end": 1.2, "text": " 你好 ", "speaker": "B"},
+                    {"start": 1.2, "end": 2.0, "text": "在吗", "speaker": "A"},
+                    {"start": 2.0, "end": 3.5, "text": "在的", "speaker": "B"},
+                    {"start": 3.5, "end": 4.0, "text": "嗯", "speaker": 7},
+                    {"start": 4.0, "end": 4.5, "text": "好"}
+                ]
+            }"#,
+        );
+
+        assert_eq!(
+            map_speakers(&response.segments),
+            vec![
+                Some("speaker_0".to_string()),
+                Some("speaker_1".to_string()),
+                Some("speaker_0".to_string()),
+                Some("speaker_2".to_string()),
+                None,
+            ]
+        );
+
+        let result = convert_response(response);
+        assert_eq!(result.segments[0].text, "你好");
+        assert_eq!(result.segments[2].speaker.as_deref(), Some("speaker_0"));
+        assert!(result.segments[4].speaker.is_none());
+        assert!(result.words.is_empty());
+    }
+
+    #[test]
+    fn test_verbose_response_assigns_words_to_segments() {
+        let response = parse(
+            r#"{
+                "text": "hello world again",
+                "language": "english",
+                "segments": [
+                    {"start": 0.0, "end": 1.0, "text": "hello world", "speaker": "A"},
+                    {"start": 1.0, "end": 2.0, "text": "again", "speaker": "B"}
+                ],
+                "words": [
+                    {"word": "hello", "start": 0.0, "end": 0.4},
+                    {"word": "world", "start": 0.5, "end": 0.9},
+                    {"word": "again", "start": 1.0, "end": 1.6},
+                    {"word": "tail", "start": 2.5, "end": 2.8}
+                ]
+            }"#,
+        );
+
+        let result = convert_response(response);
+        assert_eq!(result.language.as_deref(), Some("english"));
+        let speakers: Vec<Option<&str>> = result
+            .words
+            .iter()
+            .map(|word| word.speaker.as_deref())
+            .collect();
+        assert_eq!(
+            speakers,
+            vec![
+                Some("speaker_0"),
+                Some("speaker_0"),
+                Some("speaker_1"),
+                None
+            ]
+        );
+    }
+}
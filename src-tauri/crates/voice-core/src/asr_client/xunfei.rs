@@ -269,6 +269,7 @@ impl XunfeiClient {
                 start: 0.0,
                 end: 0.0, // 讯飞不返回时间戳
                 text: full_text.clone(),
+                speaker: None,
             });
         }
 
@@ -277,6 +278,7 @@ impl XunfeiClient {
             language: Some("zh".to_string()),
             confidence: None,
             segments,
+            words: vec![],
         }
     }
 }
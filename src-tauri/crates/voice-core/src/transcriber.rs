@@ -90,6 +90,7 @@ impl WhisperTranscriber {
                     start,
                     end,
                     text: segment_text,
+                    speaker: None,
                 });
             }
         }
@@ -109,6 +110,7 @@ impl WhisperTranscriber {
             language: detected_language,
             confidence: None,
             segments,
+            words: vec![],
         })
     }
 
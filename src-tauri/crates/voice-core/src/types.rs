@@ -84,6 +84,53 @@ pub struct TranscribeResult {
     pub confidence: Option<f32>,
     /// 分段信息
     pub segments: Vec<Segment>,
+    /// 词级时间戳（仅在请求词级时间戳且服务支持时返回）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<Word>,
+}
+
+impl TranscribeResult {
+    /// 转换为 OpenAI `verbose_json` 格式
+    ///
+    /// 在标准字段之外，分段与词条上附带 `speaker`（开启说话人分离时）。
+    pub fn to_verbose_json(&self) -> serde_json::Value {
+        let duration = self
+            .segments
+            .iter()
+            .map(|segment| segment.end)
+            .chain(self.words.iter().map(|word| word.end))
+            .fold(0.0_f32, f32::max);
+
+        let segments: Vec<serde_json::Value> = self
+            .segments
+            .iter()
+            .enumerate()
+            .map(|(id, segment)| {
+                let mut value = serde_json::json!({
+                    "id": id,
+                    "start": segment.start,
+                    "end": segment.end,
+                    "text": segment.text,
+                });
+                if let Some(speaker) = &segment.speaker {
+                    value["speaker"] = serde_json::json!(speaker);
+                }
+                value
+            })
+            .collect();
+
+        let mut body = serde_json::json!({
+            "task": "transcribe",
+            "language": self.language,
+            "duration": duration,
+            "text": self.text,
+            "segments": segments,
+        });
+        if !self.words.is_empty() {
+            body["words"] = serde_json::json!(self.words);
+        }
+        body
+    }
 }
 
 /// 识别分段
@@ -95,6 +142,39 @@ pub struct Segment {
     pub end: f32,
     /// 文本内容
     pub text: String,
+    /// 说话人标签（开启说话人分离时，如 "speaker_0"）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+}
+
+/// 词级时间戳
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Word {
+    /// 词文本
+    pub word: String,
+    /// 开始时间（秒）
+    pub start: f32,
+    /// 结束时间（秒）
+    pub end: f32,
+    /// 说话人标签（按所属分段映射）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+}
+
+/// 识别选项
+///
+/// 云端服务不支持的选项会被忽略。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscribeOptions {
+    /// 语言提示（ISO-639-1，如 "zh"；为空或 "auto" 时自动检测）
+    #[serde(default)]
+    pub language: Option<String>,
+    /// 是否返回词级时间戳
+    #[serde(default)]
+    pub word_timestamps: bool,
+    /// 是否进行说话人分离
+    #[serde(default)]
+    pub diarization: bool,
 }
 
 /// ASR 引擎类型
@@ -153,3 +233,63 @@ pub enum OutputMode {
     /// 两者都做
     Both,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_verbose_json_includes_speakers_and_words() {
+        let result = TranscribeResult {
+            text: "hello again".to_string(),
+            language: Some("en".to_string()),
+            confidence: None,
+            segments: vec![
+                Segment {
+                    start: 0.0,
+                    end: 1.0,
+                    text: "hello".to_string(),
+                    speaker: Some("speaker_0".to_string()),
+                },
+                Segment {
+                    start: 1.0,
+                    end: 2.0,
+                    text: "again".to_string(),
+                    speaker: None,
+                },
+            ],
+            words: vec![Word {
+                word: "again".to_string(),
+                start: 1.0,
+                end: 2.5,
+                speaker: None,
+            }],
+        };
+
+        let body = result.to_verbose_json();
+        assert_eq!(body["task"], "transcribe");
+        assert_eq!(body["language"], "en");
+        assert_eq!(body["duration"], 2.5);
+        assert_eq!(body["segments"][0]["id"], 0);
+        assert_eq!(body["segments"][0]["speaker"], "speaker_0");
+        assert!(body["segments"][1].get("speaker").is_none());
+        assert_eq!(body["words"][0]["word"], "again");
+        assert!(body["words"][0].get("speaker").is_none());
+    }
+
+    #[test]
+    fn test_to_verbose_json_omits_empty_words() {
+        let result = TranscribeResult {
+            text: "hi".to_string(),
+            language: None,
+            confidence: None,
+            segments: Vec::new(),
+            words: Vec::new(),
+        };
+
+        let body = result.to_verbose_json();
+        assert_eq!(body["duration"], 0.0);
+        assert!(body["segments"].as_array().is_some_and(Vec::is_empty));
+        assert!(body.get("words").is_none());
+    }
+}
@@ -107,6 +107,11 @@ pub fn init_states(config: &Config) -> Result<AppStates, String> {
 
     // 数据库
     let db = database::init_database().map_err(|e| format!("数据库初始化失败: {e}"))?;
+    super::db_maintenance::rebuild_credentials_after_repair(&db, config);
+    lime_core::database::pool_storage::configure_pool_storage(&config.server.pool_storage)
+        .map_err(|e| format!("凭证池共享存储初始化失败: {e}"))?;
+    lime_providers::providers::endpoints::configure(&config.providers);
+    lime_core::anonymous_stats::configure(&config.anonymous_stats);
 
     // Windows 特定：验证数据库可写性
     #[cfg(target_os = "windows")]
@@ -142,7 +147,10 @@ pub fn init_states(config: &Config) -> Result<AppStates, String> {
             tracing::info!("[Bootstrap] API Key 加密格式无需迁移");
         }
         Ok(count) => {
-            tracing::info!("[Bootstrap] 已自动迁移 {} 条旧版 API Key 加密数据", count);
+            tracing::info!(
+                "[Bootstrap] 已自动迁移 {} 条 API Key 存储（旧版加密或迁入钥匙串）",
+                count
+            );
         }
         Err(error) => {
             tracing::warn!(
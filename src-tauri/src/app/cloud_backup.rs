@@ -0,0 +1,78 @@
+//! 定时云备份任务
+//!
+//! 按配置间隔把配置与数据库快照加密上传到 S3 / WebDAV，每轮重新读取配置，
+//! 在设置页修改备份目标或关闭备份后无需重启即可生效。
+
+use std::time::Duration;
+
+use lime_core::config::CloudBackupSettings;
+use lime_core::database::DbConnection;
+use lime_services::cloud_backup_service::{self, CloudBackupReport};
+
+use crate::app::types::AppState;
+
+/// 启动后首次备份的最小延迟，避免影响启动性能
+const INITIAL_DELAY_SECS: u64 = 15 * 60;
+/// 备份未启用时重新检查配置的间隔
+const IDLE_CHECK_SECS: u64 = 10 * 60;
+
+/// 立即执行一次云备份
+pub async fn run_backup_once(
+    db: &DbConnection,
+    state: &AppState,
+) -> Result<CloudBackupReport, String> {
+    let config = state.read().await.config.clone();
+    let settings = &config.cloud_backup;
+    if settings.passphrase.is_empty() {
+        return Err("未设置备份加密口令".to_string());
+    }
+    cloud_backup_service::run_backup(settings, &config, db).await
+}
+
+/// 距最近一次远端备份不足一个间隔时，等到间隔结束再执行
+async fn initial_delay(settings: &CloudBackupSettings, interval: Duration) -> Duration {
+    let minimum = Duration::from_secs(INITIAL_DELAY_SECS);
+    let latest = match cloud_backup_service::storage_for(settings) {
+        Ok(storage) => cloud_backup_service::list_backups(storage.as_ref())
+            .await
+            .ok()
+            .and_then(|names| names.first().cloned())
+            .and_then(|name| cloud_backup_service::parse_backup_time(&name)),
+        Err(_) => None,
+    };
+    let Some(latest) = latest else {
+        return minimum;
+    };
+    let elapsed = (chrono::Utc::now() - latest).to_std().unwrap_or_default();
+    interval.saturating_sub(elapsed).max(minimum)
+}
+
+fn interval_of(settings: &CloudBackupSettings) -> Duration {
+    Duration::from_secs(settings.interval_hours.max(1) * 3600)
+}
+
+/// 定时云备份循环
+pub async fn run_cloud_backup_loop(db: DbConnection, state: AppState) {
+    let settings = state.read().await.config.cloud_backup.clone();
+    if settings.enabled {
+        tracing::info!(
+            "[CLOUD_BACKUP] 定时云备份已启动，间隔 {} 小时",
+            settings.interval_hours.max(1)
+        );
+        tokio::time::sleep(initial_delay(&settings, interval_of(&settings)).await).await;
+    } else {
+        tokio::time::sleep(Duration::from_secs(IDLE_CHECK_SECS)).await;
+    }
+
+    loop {
+        let settings = state.read().await.config.cloud_backup.clone();
+        if !settings.enabled {
+            tokio::time::sleep(Duration::from_secs(IDLE_CHECK_SECS)).await;
+            continue;
+        }
+        if let Err(e) = run_backup_once(&db, &state).await {
+            tracing::warn!("[CLOUD_BACKUP] 定时云备份失败: {}", e);
+        }
+        tokio::time::sleep(interval_of(&settings)).await;
+    }
+}
@@ -12,6 +12,8 @@ use crate::config::{
 use crate::services::environment_service::{
     apply_configured_environment, build_environment_preview,
 };
+use lime_core::database::dao::config_audit::{ConfigAuditDao, ConfigAuditEntry};
+use lime_core::database::{lock_db, DbConnection};
 
 /// 获取配置
 #[tauri::command]
@@ -50,15 +52,29 @@ pub async fn save_config(
         return Err("安全限制：不允许开启远程管理功能".to_string());
     }
 
+    persist_config(&state, &config_manager, config).await
+}
+
+/// 写入运行时状态与配置文件，并通知观察者热重载
+async fn persist_config(
+    state: &AppState,
+    config_manager: &GlobalConfigManagerState,
+    config: config::Config,
+) -> Result<(), String> {
     {
         let mut s = state.write().await;
         s.config = config.clone();
     }
 
-    let save_result = config_manager.0.save_config(&config).await;
+    let save_result = config_manager
+        .0
+        .save_config_from(&config, config::ConfigAuditSource::Ui)
+        .await;
     match save_result {
         Ok(()) => {
             apply_configured_environment(&config).await;
+            lime_providers::providers::endpoints::configure(&config.providers);
+            lime_core::anonymous_stats::configure(&config.anonymous_stats);
             tracing::info!("[CONFIG] 配置保存成功: host={}", config.server.host);
             Ok(())
         }
@@ -69,6 +85,63 @@ pub async fn save_config(
     }
 }
 
+/// 获取当前生效配置的 JSON 树（默认脱敏密钥），供结构化配置编辑器使用
+#[tauri::command]
+pub async fn get_effective_config(
+    state: tauri::State<'_, AppState>,
+    redact_secrets: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    let s = state.read().await;
+    config::effective_config_value(&s.config, redact_secrets.unwrap_or(true))
+        .map_err(|e| e.to_string())
+}
+
+/// 校验配置补丁（JSON Merge Patch）并预览字段变更，不写盘
+#[tauri::command]
+pub async fn validate_config_patch(
+    state: tauri::State<'_, AppState>,
+    patch: serde_json::Value,
+) -> Result<config::ConfigPatchPreview, String> {
+    let s = state.read().await;
+    Ok(config::ConfigPatchPreview::from_result(
+        &config::apply_config_patch(&s.config, &patch),
+    ))
+}
+
+/// 应用配置补丁：校验通过后原子写入配置文件并触发热重载，返回字段变更
+#[tauri::command]
+pub async fn apply_config_patch(
+    state: tauri::State<'_, AppState>,
+    config_manager: tauri::State<'_, GlobalConfigManagerState>,
+    patch: serde_json::Value,
+) -> Result<Vec<config::ConfigFieldChange>, String> {
+    let outcome = {
+        let s = state.read().await;
+        config::apply_config_patch(&s.config, &patch).map_err(|e| e.to_string())?
+    };
+    if outcome.changes.is_empty() {
+        return Ok(Vec::new());
+    }
+    tracing::info!(
+        "[CONFIG] 应用配置补丁: {} 个字段变更",
+        outcome.changes.len()
+    );
+    persist_config(&state, &config_manager, outcome.config).await?;
+    Ok(outcome.changes)
+}
+
+/// 查询配置变更审计记录（按时间倒序），`path` 可按字段路径过滤，如 `routing.rules`
+#[tauri::command]
+pub async fn get_config_audit_log(
+    db: tauri::State<'_, DbConnection>,
+    limit: Option<usize>,
+    path: Option<String>,
+) -> Result<Vec<ConfigAuditEntry>, String> {
+    let conn = lock_db(&db)?;
+    ConfigAuditDao::list(&conn, limit.unwrap_or(100).clamp(1, 1000), path.as_deref())
+        .map_err(|e| e.to_string())
+}
+
 /// 获取统一环境变量预览
 #[tauri::command]
 pub async fn get_environment_preview(
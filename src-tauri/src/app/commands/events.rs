@@ -0,0 +1,30 @@
+//! 应用事件命令
+//!
+//! 提供命令/事件协议版本信息，供前端在订阅类型化事件前进行兼容性检查。
+
+use lime_core::app_events::{APP_EVENT_API_VERSION, APP_EVENT_CHANNEL, COMMAND_API_VERSION};
+use serde::Serialize;
+
+/// 前后端协议版本信息
+#[derive(Debug, Clone, Serialize)]
+pub struct AppApiInfo {
+    pub command_api_version: u32,
+    pub event_api_version: u32,
+    pub event_channel: String,
+}
+
+impl AppApiInfo {
+    pub fn current() -> Self {
+        Self {
+            command_api_version: COMMAND_API_VERSION,
+            event_api_version: APP_EVENT_API_VERSION,
+            event_channel: APP_EVENT_CHANNEL.to_string(),
+        }
+    }
+}
+
+/// 获取前后端协议版本信息
+#[tauri::command]
+pub fn get_app_api_info() -> AppApiInfo {
+    AppApiInfo::current()
+}
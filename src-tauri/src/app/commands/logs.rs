@@ -502,6 +502,40 @@ pub async fn report_frontend_crash(
     Ok(())
 }
 
+/// 列出本地保存的崩溃报告（已脱敏）
+#[tauri::command]
+pub async fn list_crash_reports() -> Result<Vec<crate::crash_reporting::CrashReportSummary>, String>
+{
+    Ok(crate::crash_reporting::LocalCrashStore::default_store().list())
+}
+
+/// 由用户主动导出崩溃报告到桌面或下载目录；`ids` 为空时导出全部
+#[tauri::command]
+pub async fn export_crash_report(
+    logs: tauri::State<'_, LogState>,
+    ids: Option<Vec<String>>,
+) -> Result<crate::crash_reporting::CrashReportExportResult, String> {
+    let result = crate::crash_reporting::LocalCrashStore::default_store()
+        .export(ids.as_deref(), &default_support_bundle_output_dir())?;
+
+    logs.write().await.add(
+        "info",
+        &format!(
+            "[CrashReporting] 已导出 {} 份崩溃报告: {}",
+            result.report_count,
+            logger::sanitize_log_message(&result.export_path)
+        ),
+    );
+
+    Ok(result)
+}
+
+/// 删除本地保存的全部崩溃报告
+#[tauri::command]
+pub async fn clear_crash_reports() -> Result<usize, String> {
+    Ok(crate::crash_reporting::LocalCrashStore::default_store().clear())
+}
+
 fn summarize_frontend_debug_context(context: Option<&Value>) -> String {
     let Some(context) = context else {
         return String::new();
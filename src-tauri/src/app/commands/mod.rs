@@ -5,6 +5,7 @@
 //! ## 模块结构
 //! - `server` - 服务器控制命令
 //! - `config` - 配置管理命令
+//! - `events` - 应用事件协议版本命令
 //! - `kiro` - Kiro Provider 命令 (legacy)
 //! - `gemini` - Gemini Provider 命令 (legacy)
 //! - `custom_providers` - 自定义 Provider 命令 (OpenAI/Claude Custom)
@@ -14,6 +15,7 @@
 mod api_test;
 mod config;
 mod custom_providers;
+mod events;
 mod gemini;
 mod kiro;
 mod logs;
@@ -23,6 +25,7 @@ mod server;
 pub use api_test::*;
 pub use config::*;
 pub use custom_providers::*;
+pub use events::*;
 pub use gemini::*;
 pub use kiro::*;
 pub use logs::*;
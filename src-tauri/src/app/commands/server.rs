@@ -114,11 +114,6 @@ pub async fn get_server_status(
             0
         }
     };
-    // 使用 Retry 状态日志作为活跃请求近似值
-    status.active_requests = recent_logs
-        .iter()
-        .filter(|log| matches!(log.status, RequestStatus::Retrying))
-        .count() as u64;
     status.capability_routing = s.capability_routing_metrics_store.snapshot();
     status.response_cache = s.response_cache_store.stats();
     status.request_dedup = s.request_dedup_store.stats();
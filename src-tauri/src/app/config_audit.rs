@@ -0,0 +1,43 @@
+//! 配置变更审计落地
+//!
+//! 配置写入可能发生在持有数据库锁或应用状态锁的调用链中，
+//! 审计记录经通道交给独立线程写入 `config_audit_log`，避免重入死锁。
+
+use std::sync::mpsc;
+
+use lime_core::config::{install_config_audit_sink, ConfigAuditRecord};
+use lime_core::database::dao::config_audit::ConfigAuditDao;
+use lime_core::database::{lock_db, DbConnection};
+
+use super::rbac::AppRoleState;
+
+/// 审计表最多保留的记录数
+pub const MAX_AUDIT_ENTRIES: usize = 5000;
+
+/// 注册配置审计落地函数，操作者记为“系统用户名 (应用角色)”
+pub fn install(db: DbConnection, role: AppRoleState) {
+    let (tx, rx) = mpsc::channel::<(ConfigAuditRecord, String)>();
+    let writer = std::thread::Builder::new()
+        .name("config-audit".to_string())
+        .spawn(move || {
+            for (record, actor) in rx {
+                let result = lock_db(&db).and_then(|conn| {
+                    ConfigAuditDao::insert(&conn, &record, &actor)
+                        .and_then(|_| ConfigAuditDao::prune(&conn, MAX_AUDIT_ENTRIES))
+                        .map_err(|e| e.to_string())
+                });
+                if let Err(e) = result {
+                    tracing::warn!("[CONFIG_AUDIT] 写入审计记录失败: {}", e);
+                }
+            }
+        });
+    if let Err(e) = writer {
+        tracing::warn!("[CONFIG_AUDIT] 启动审计写入线程失败: {}", e);
+        return;
+    }
+
+    install_config_audit_sink(move |record| {
+        let actor = format!("{} ({})", whoami::username(), role.get().as_str());
+        let _ = tx.send((record, actor));
+    });
+}
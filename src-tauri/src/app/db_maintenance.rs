@@ -0,0 +1,109 @@
+//! 数据库维护任务
+//!
+//! - 启动时若数据库因损坏被重建，从 YAML 配置重新导入凭证池
+//! - 按配置间隔定时执行完整性检查、WAL checkpoint 与 VACUUM
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use lime_core::config::{Config, ConfigManager, DbMaintenanceSettings};
+use lime_core::database::dao::provider_pool::ProviderPoolDao;
+use lime_core::database::{self, lock_db, maintenance, repair, DbConnection};
+use lime_credential::CredentialSyncService;
+
+/// 启动后首次定时维护的延迟，避免影响启动性能
+const INITIAL_DELAY_SECS: u64 = 10 * 60;
+
+/// 数据库被重建后，从配置文件重新导入凭证池（已抢救的凭证保留）
+pub fn rebuild_credentials_after_repair(db: &DbConnection, config: &Config) {
+    let Some(report) = repair::take_last_repair() else {
+        return;
+    };
+    tracing::warn!(
+        "[数据库] 数据库已从损坏中恢复（{}），备份位于 {}",
+        report.reason,
+        report.backup_path.display()
+    );
+
+    let config_manager =
+        ConfigManager::with_config(config.clone(), ConfigManager::default_config_path());
+    let sync_service = CredentialSyncService::new(Arc::new(std::sync::RwLock::new(config_manager)));
+    let credentials = match sync_service.load_from_config() {
+        Ok(credentials) => credentials,
+        Err(e) => {
+            tracing::error!("[数据库] 从配置重建凭证池失败: {}", e);
+            return;
+        }
+    };
+
+    let conn = match lock_db(db) {
+        Ok(conn) => conn,
+        Err(e) => {
+            tracing::error!("[数据库] {}", e);
+            return;
+        }
+    };
+    let mut restored = 0usize;
+    for credential in &credentials {
+        if matches!(
+            ProviderPoolDao::get_by_uuid(&conn, &credential.uuid),
+            Ok(Some(_))
+        ) {
+            continue;
+        }
+        match ProviderPoolDao::insert(&conn, credential) {
+            Ok(()) => restored += 1,
+            Err(e) => tracing::warn!("[数据库] 恢复凭证 {} 失败: {}", credential.uuid, e),
+        }
+    }
+    tracing::info!("[数据库] 已从配置恢复 {} 条凭证", restored);
+}
+
+/// 执行一次维护（在阻塞线程中运行，VACUUM 期间持有数据库锁）
+pub async fn run_maintenance_once(
+    db: DbConnection,
+    vacuum: bool,
+) -> Result<maintenance::DbMaintenanceReport, String> {
+    tokio::task::spawn_blocking(move || {
+        let db_path = database::get_db_path().ok();
+        let conn = lock_db(&db)?;
+        maintenance::run_maintenance(&conn, db_path.as_deref(), vacuum)
+    })
+    .await
+    .map_err(|e| format!("数据库维护任务异常退出: {e}"))?
+}
+
+/// 距上次维护不足一个间隔时，等到间隔结束再执行
+fn initial_delay(db: &DbConnection, interval: Duration) -> Duration {
+    let minimum = Duration::from_secs(INITIAL_DELAY_SECS);
+    let last_run = lock_db(db)
+        .ok()
+        .and_then(|conn| maintenance::last_maintenance_report(&conn))
+        .map(|report| report.ran_at);
+    let Some(last_run) = last_run else {
+        return minimum;
+    };
+    let elapsed = (chrono::Utc::now() - last_run).to_std().unwrap_or_default();
+    interval.saturating_sub(elapsed).max(minimum)
+}
+
+/// 定时维护循环
+pub async fn run_db_maintenance_loop(db: DbConnection, settings: DbMaintenanceSettings) {
+    if !settings.enabled {
+        tracing::info!("[数据库] 定时维护已禁用");
+        return;
+    }
+    let interval = Duration::from_secs(settings.interval_hours.max(1) * 3600);
+    tracing::info!(
+        "[数据库] 定时维护已启动，间隔 {} 小时",
+        settings.interval_hours.max(1)
+    );
+
+    tokio::time::sleep(initial_delay(&db, interval)).await;
+    loop {
+        if let Err(e) = run_maintenance_once(db.clone(), settings.vacuum).await {
+            tracing::warn!("[数据库] 定时维护失败: {}", e);
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
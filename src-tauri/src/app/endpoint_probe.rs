@@ -0,0 +1,53 @@
+//! 多区域端点延迟探测任务
+//!
+//! 按 `providers.endpoint_selection` 定期探测各区域端点，结果用于请求时的端点排序；
+//! 每轮重新读取配置，关闭探测或修改间隔后无需重启即可生效。
+
+use std::time::Duration;
+
+use lime_providers::providers::endpoint_latency::{self, ProviderEndpointReport};
+
+use crate::app::types::AppState;
+
+/// 启动后首次探测的延迟，避免影响启动性能
+const INITIAL_DELAY_SECS: u64 = 30;
+/// 探测间隔下限
+const MIN_INTERVAL_SECS: u64 = 30;
+
+fn probe_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(5))
+        .build()
+        .unwrap_or_default()
+}
+
+/// 立即探测一次
+pub async fn probe_endpoints_once() -> Vec<ProviderEndpointReport> {
+    endpoint_latency::probe_all(&probe_client()).await
+}
+
+/// 端点延迟探测循环
+pub async fn run_endpoint_probe_loop(state: AppState) {
+    tokio::time::sleep(Duration::from_secs(INITIAL_DELAY_SECS)).await;
+    let client = probe_client();
+    loop {
+        let settings = state
+            .read()
+            .await
+            .config
+            .providers
+            .endpoint_selection
+            .clone();
+        if settings.enabled {
+            for report in endpoint_latency::probe_all(&client).await {
+                if let Some(selected) = &report.selected {
+                    tracing::debug!("[ENDPOINT] {} 当前优先端点: {}", report.provider, selected);
+                }
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(
+            settings.probe_interval_secs.max(MIN_INTERVAL_SECS),
+        ))
+        .await;
+    }
+}
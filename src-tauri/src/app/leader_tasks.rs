@@ -0,0 +1,214 @@
+//! 主实例后台任务
+//!
+//! - 多实例共享凭证池存储时参与选主（见 `lime_core::cluster`）
+//! - 仅主实例定时提前刷新即将过期的 Token（默认关闭）、探测不健康的凭证
+//!   （开启 `adaptive_health_probe` 时也探测空闲凭证，近期有真实请求的凭证不探测）
+//!
+//! 单实例（本地 SQLite）时当前实例始终为主，行为与选主前一致。
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use lime_core::cluster;
+use lime_core::config::ClusterSettings;
+use lime_core::credential::{HealthCheckConfig, HealthChecker};
+use lime_core::database::pool_storage::{lock_pool, pool_storage};
+use lime_core::database::DbConnection;
+use lime_services::provider_pool_service::ProviderPoolService;
+use lime_services::token_cache_service::TokenCacheService;
+
+/// 启动后首次执行的延迟，避免影响启动性能
+const INITIAL_DELAY_SECS: u64 = 60;
+
+/// 刷新距过期不足 `ahead_minutes` 分钟的 Token
+async fn refresh_expiring_tokens(
+    db: &DbConnection,
+    token_cache: &TokenCacheService,
+    ahead_minutes: i64,
+) -> Result<usize, String> {
+    let expiring: Vec<String> = {
+        let conn = lock_pool(db)?;
+        let storage = pool_storage();
+        storage
+            .get_all(&conn)?
+            .into_iter()
+            .filter(|cred| !cred.is_disabled)
+            .filter(|cred| TokenCacheService::supports_refresh(cred.provider_type))
+            .filter(|cred| {
+                storage
+                    .get_token_cache(&conn, &cred.uuid)
+                    .ok()
+                    .flatten()
+                    .is_some_and(|cache| cache.is_expiring_within_minutes(ahead_minutes))
+            })
+            .map(|cred| cred.uuid)
+            .collect()
+    };
+
+    let mut refreshed = 0usize;
+    for uuid in &expiring {
+        match token_cache.refresh_and_cache(db, uuid, true).await {
+            Ok(_) => refreshed += 1,
+            Err(e) => tracing::warn!("[集群] 后台刷新 Token 失败 {}: {}", uuid, e),
+        }
+    }
+    Ok(refreshed)
+}
+
+/// 探测不健康的凭证，成功时由健康检查恢复其状态
+async fn probe_unhealthy_credentials(
+    db: &DbConnection,
+    pool_service: &ProviderPoolService,
+) -> Result<usize, String> {
+    let unhealthy: Vec<String> = {
+        let conn = lock_pool(db)?;
+        pool_storage()
+            .get_all(&conn)?
+            .into_iter()
+            .filter(|cred| !cred.is_disabled && !cred.is_healthy)
+            .map(|cred| cred.uuid)
+            .collect()
+    };
+
+    let mut recovered = 0usize;
+    for uuid in &unhealthy {
+        match pool_service.check_credential_health(db, uuid).await {
+            Ok(result) if result.success => recovered += 1,
+            Ok(_) => {}
+            Err(e) => tracing::debug!("[集群] 探测凭证 {} 失败: {}", uuid, e),
+        }
+    }
+    Ok(recovered)
+}
+
+/// 按流量自适应探测：空闲与不健康的凭证到期即探测，近期有真实请求的凭证跳过
+///
+/// 返回（探测数, 恢复数）。以凭证的最后健康检查时间作为上次探测时间。
+async fn probe_idle_credentials(
+    db: &DbConnection,
+    pool_service: &ProviderPoolService,
+    checker: &HealthChecker,
+) -> Result<(usize, usize), String> {
+    let now = Utc::now();
+    let due: Vec<(String, bool)> = {
+        let conn = lock_pool(db)?;
+        pool_storage()
+            .get_all(&conn)?
+            .into_iter()
+            .filter(|cred| !cred.is_disabled && cred.check_health && cred.deleted_at.is_none())
+            .filter(|cred| {
+                checker.probe_due(
+                    cred.is_healthy,
+                    cred.last_used,
+                    cred.last_health_check_time,
+                    now,
+                )
+            })
+            .map(|cred| (cred.uuid, cred.is_healthy))
+            .collect()
+    };
+
+    let mut recovered = 0usize;
+    for (uuid, was_healthy) in &due {
+        match pool_service.check_credential_health(db, uuid).await {
+            Ok(result) if result.success && !was_healthy => recovered += 1,
+            Ok(result) if !result.success && *was_healthy => {
+                tracing::warn!("[集群] 空闲凭证 {} 探测失败: {:?}", uuid, result.message)
+            }
+            Ok(_) => {}
+            Err(e) => tracing::debug!("[集群] 探测凭证 {} 失败: {}", uuid, e),
+        }
+    }
+    Ok((due.len(), recovered))
+}
+
+async fn run_token_refresh_loop(
+    db: DbConnection,
+    token_cache: Arc<TokenCacheService>,
+    interval: Duration,
+    ahead_minutes: i64,
+) {
+    tokio::time::sleep(Duration::from_secs(INITIAL_DELAY_SECS)).await;
+    loop {
+        if cluster::is_leader() {
+            match refresh_expiring_tokens(&db, &token_cache, ahead_minutes).await {
+                Ok(count) if count > 0 => tracing::info!("[集群] 后台提前刷新 {} 个 Token", count),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("[集群] 后台 Token 刷新失败: {}", e),
+            }
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+async fn run_health_probe_loop(
+    db: DbConnection,
+    pool_service: Arc<ProviderPoolService>,
+    interval: Duration,
+    adaptive: Option<HealthChecker>,
+) {
+    tokio::time::sleep(Duration::from_secs(INITIAL_DELAY_SECS)).await;
+    loop {
+        if cluster::is_leader() {
+            match &adaptive {
+                Some(checker) => match probe_idle_credentials(&db, &pool_service, checker).await {
+                    Ok((probed, recovered)) if probed > 0 => tracing::debug!(
+                        "[集群] 自适应探测 {} 个凭证，恢复 {} 个",
+                        probed,
+                        recovered
+                    ),
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("[集群] 后台健康探测失败: {}", e),
+                },
+                None => match probe_unhealthy_credentials(&db, &pool_service).await {
+                    Ok(count) if count > 0 => {
+                        tracing::info!("[集群] 后台探测恢复 {} 个凭证", count)
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("[集群] 后台健康探测失败: {}", e),
+                },
+            }
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// 启动选主与主实例后台任务
+pub fn spawn_leader_tasks(
+    db: DbConnection,
+    pool_service: Arc<ProviderPoolService>,
+    token_cache: Arc<TokenCacheService>,
+    settings: ClusterSettings,
+) {
+    tauri::async_runtime::spawn(cluster::run_leader_election(Duration::from_secs(
+        settings.lease_ttl_secs,
+    )));
+
+    if settings.token_refresh_interval_secs > 0 {
+        tauri::async_runtime::spawn(run_token_refresh_loop(
+            db.clone(),
+            token_cache,
+            Duration::from_secs(settings.token_refresh_interval_secs.max(30)),
+            settings.token_refresh_ahead_minutes.max(1),
+        ));
+    }
+    if settings.health_probe_interval_secs > 0 {
+        let interval = Duration::from_secs(settings.health_probe_interval_secs.max(60));
+        let adaptive = settings.adaptive_health_probe.then(|| {
+            HealthChecker::new(HealthCheckConfig {
+                check_interval: interval,
+                adaptive: true,
+                idle_check_interval: Duration::from_secs(settings.idle_probe_interval_secs.max(30)),
+                traffic_window: Duration::from_secs(settings.probe_traffic_window_secs),
+                ..Default::default()
+            })
+        });
+        // 自适应时按空闲探测间隔轮询，是否探测由各凭证的到期时间决定
+        let tick = match &adaptive {
+            Some(checker) => interval.min(checker.config().idle_check_interval),
+            None => interval,
+        };
+        tauri::async_runtime::spawn(run_health_probe_loop(db, pool_service, tick, adaptive));
+    }
+}
@@ -8,12 +8,26 @@
 //! - `commands` - 内置 Tauri 命令
 //! - `utils` - 辅助函数
 //! - `bootstrap` - 应用启动引导（配置验证、状态初始化）
+//! - `config_audit` - 配置变更审计落地
+//! - `db_maintenance` - 数据库定时维护与损坏后的凭证重建
+//! - `endpoint_probe` - 多区域端点延迟探测
+//! - `leader_tasks` - 多实例选主与主实例后台任务
+//! - `rbac` - 应用内角色访问控制（命令分发前统一校验）
+//! - `retention` - 数据保留策略定时清理
 //! - `runner` - 应用运行器（Tauri Builder 配置、setup 和命令注册）
 
 pub mod bootstrap;
+pub mod cloud_backup;
 pub mod commands;
+pub mod config_audit;
+pub mod db_maintenance;
+pub mod endpoint_probe;
+pub mod leader_tasks;
+pub mod rbac;
+pub mod retention;
 pub mod runner;
 pub mod scheduler_service;
+pub mod setup_wizard;
 mod state;
 mod types;
 mod utils;
@@ -0,0 +1,841 @@
+//! 应用内角色访问控制
+//!
+//! 所有 Tauri 命令在分发前由 [`guard_invoke_handler`] 按命令名统一校验当前角色，
+//! 命令实现本身无需关心角色。例如展示用的 Kiosk 设备可切到 `viewer`，
+//! 只能查看仪表盘，不能删除凭证或修改配置。
+//!
+//! 命令所需角色由显式清单决定：
+//! - [`VIEWER_COMMANDS`]：只读且不返回密钥或凭证的命令
+//! - [`OPERATOR_COMMANDS`]：启停、刷新、测试等运行时操作，以及返回配置或凭证的只读命令
+//! - 其余命令（包括新增但未登记的命令）一律需要 `admin`
+//!
+//! 升级角色的 PIN 以 PBKDF2-HMAC-SHA256 加盐摘要保存。未设置 PIN 时不允许升级角色；
+//! 连续输错 PIN 后按指数退避暂停校验。
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use lime_core::config::{derive_key, AppRole};
+use parking_lot::{Mutex, RwLock};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use tauri::ipc::Invoke;
+use tauri::Runtime;
+
+/// PIN 摘要的格式标识，完整格式为 `pbkdf2-sha256$<盐>$<摘要>`（十六进制）
+const PIN_HASH_SCHEME: &str = "pbkdf2-sha256";
+
+/// PIN 盐长度（字节）
+const PIN_SALT_LEN: usize = 16;
+
+/// 不触发退避的连续 PIN 错误次数
+const PIN_FREE_ATTEMPTS: u32 = 3;
+
+/// 首次退避时长，之后每次错误翻倍
+const PIN_BASE_BACKOFF: Duration = Duration::from_secs(30);
+
+/// 最长退避时长
+const PIN_MAX_BACKOFF: Duration = Duration::from_secs(15 * 60);
+
+/// `viewer` 可调用的命令（只读，且不返回密钥或凭证）
+const VIEWER_COMMANDS: &[&str] = &[
+    "agent_get_process_status",
+    "agent_runtime_get_session",
+    "agent_runtime_get_thread_read",
+    "agent_runtime_get_tool_inventory",
+    "agent_runtime_list_sessions",
+    "aster_agent_status",
+    "aster_agent_theme_context_search",
+    "center_window",
+    "character_get",
+    "character_list",
+    "check_codex_cli_status",
+    "check_config_sync_status",
+    "claw_solution_list",
+    "close_update_window",
+    "connection_get_config_path",
+    "connection_list",
+    "content_get",
+    "content_get_theme_workbench_document_state",
+    "content_list",
+    "content_stats",
+    "content_workflow_get",
+    "content_workflow_get_by_content",
+    "dismiss_update_notification",
+    "execution_run_get",
+    "execution_run_get_theme_workbench_state",
+    "execution_run_list",
+    "execution_run_list_theme_workbench_history",
+    "frontend_debug_log",
+    "gateway_channel_status",
+    "gateway_tunnel_status",
+    "get_a2ui_form",
+    "get_a2ui_forms_by_message",
+    "get_a2ui_forms_by_session",
+    "get_all_alias_configs",
+    "get_all_available_models",
+    "get_all_credential_health",
+    "get_all_models",
+    "get_all_models_by_provider",
+    "get_all_provider_models",
+    "get_anonymous_stats_preview",
+    "get_app_role",
+    "get_auto_launch_status",
+    "get_automation_health",
+    "get_automation_job",
+    "get_automation_jobs",
+    "get_automation_run_history",
+    "get_automation_scheduler_config",
+    "get_automation_status",
+    "get_available_models",
+    "get_available_routes",
+    "get_available_voices",
+    "get_brand_extension",
+    "get_brand_persona",
+    "get_browser_action_audit_logs",
+    "get_browser_backend_policy",
+    "get_browser_backends_status",
+    "get_browser_event_buffer",
+    "get_browser_session_state",
+    "get_chrome_bridge_status",
+    "get_chrome_profile_sessions",
+    "get_claude_custom_status",
+    "get_config_audit_log",
+    "get_config_dir_path",
+    "get_config_paths",
+    "get_config_status",
+    "get_conversation_config",
+    "get_credential_health",
+    "get_credential_models",
+    "get_current_machine_id",
+    "get_current_prompt_file_content",
+    "get_daily_usage_trends",
+    "get_db_maintenance_status",
+    "get_default_models_for_provider",
+    "get_default_persona",
+    "get_default_provider",
+    "get_default_template",
+    "get_endpoint_latency",
+    "get_endpoint_providers",
+    "get_experimental_config",
+    "get_external_tools",
+    "get_failover_config",
+    "get_file_name",
+    "get_gemini_token_file_hash",
+    "get_har_capture_status",
+    "get_hint_routes",
+    "get_home_dir",
+    "get_injection_config",
+    "get_injection_rules",
+    "get_installed_lime_skills",
+    "get_installed_plugin",
+    "get_kiro_credential_fingerprint",
+    "get_kiro_fingerprint_info",
+    "get_kiro_usage",
+    "get_local_kiro_credential_uuid",
+    "get_local_skills_for_app",
+    "get_log_storage_diagnostics",
+    "get_logs",
+    "get_machine_id_history",
+    "get_maintenance_mode",
+    "get_material",
+    "get_material_content",
+    "get_material_count",
+    "get_materials_content",
+    "get_memory_feedback_stats",
+    "get_model_host_alias_user_file_info",
+    "get_model_preferences",
+    "get_model_registry",
+    "get_model_registry_provider_ids",
+    "get_model_sync_state",
+    "get_model_usage_ranking",
+    "get_models_by_tier",
+    "get_models_config",
+    "get_models_for_provider",
+    "get_network_info",
+    "get_oauth_token_file_hash",
+    "get_openai_custom_status",
+    "get_orchestrator_config",
+    "get_os_type",
+    "get_persisted_logs_tail",
+    "get_persona",
+    "get_plugin_info",
+    "get_plugin_queue_stats",
+    "get_plugin_status",
+    "get_plugin_task",
+    "get_plugin_ui",
+    "get_plugins",
+    "get_plugins_dir",
+    "get_plugins_with_ui",
+    "get_pool_credential_oauth_status",
+    "get_pool_stats",
+    "get_poster_material",
+    "get_poster_metadata",
+    "get_project_context",
+    "get_prompts",
+    "get_provider_alias_config",
+    "get_provider_models",
+    "get_provider_pool_overview",
+    "get_provider_ui_state",
+    "get_rate_limit_config",
+    "get_recording_status",
+    "get_relay_info",
+    "get_request_logs",
+    "get_retry_config",
+    "get_screenshot_shortcut_runtime_status",
+    "get_server_diagnostics",
+    "get_server_status",
+    "get_skill_detail",
+    "get_skill_repos",
+    "get_skills",
+    "get_skills_for_app",
+    "get_stats_by_model",
+    "get_stats_by_provider",
+    "get_stats_summary",
+    "get_switch_log",
+    "get_sysinfo",
+    "get_system_info",
+    "get_system_provider_catalog",
+    "get_telegram_remote_status",
+    "get_template",
+    "get_tier_models",
+    "get_token_file_hash",
+    "get_token_stats_by_day",
+    "get_token_stats_by_model",
+    "get_token_stats_by_provider",
+    "get_token_summary",
+    "get_tool_versions",
+    "get_tray_state",
+    "get_usage_stats",
+    "get_video_generation_task",
+    "get_voice_instructions",
+    "get_voice_shortcut_runtime_status",
+    "get_websocket_connections",
+    "get_websocket_status",
+    "get_webview_panels",
+    "get_window_size",
+    "get_windows_startup_diagnostics",
+    "inspect_local_skill_for_app",
+    "inspect_remote_skill",
+    "is_fullscreen",
+    "is_plugin_installed",
+    "list_audio_devices",
+    "list_banned_ips",
+    "list_brand_persona_templates",
+    "list_browser_environment_presets_cmd",
+    "list_browser_profiles_cmd",
+    "list_by_image_category",
+    "list_by_layout_category",
+    "list_by_mood",
+    "list_cdp_targets",
+    "list_cloud_backups",
+    "list_config_profiles",
+    "list_crash_reports",
+    "list_executable_skills",
+    "list_installed_plugins",
+    "list_materials",
+    "list_persona_templates",
+    "list_personas",
+    "list_plugin_tasks",
+    "list_relay_providers",
+    "list_scoped_api_keys",
+    "list_service_tiers",
+    "list_strategies",
+    "list_task_hints",
+    "list_templates",
+    "list_test_scenarios",
+    "list_video_generation_tasks",
+    "mcp_get_prompt",
+    "mcp_list_prompts",
+    "mcp_list_resources",
+    "mcp_list_servers_with_status",
+    "mcp_list_tools",
+    "mcp_list_tools_for_context",
+    "mcp_read_resource",
+    "mcp_search_tools",
+    "memory_get_auto_index",
+    "memory_get_effective_sources",
+    "memory_runtime_get_overview",
+    "memory_runtime_get_stats",
+    "novel_get_project_snapshot",
+    "novel_list_runs",
+    "openclaw_get_channels",
+    "openclaw_get_command_preview",
+    "openclaw_get_environment_status",
+    "openclaw_get_git_download_url",
+    "openclaw_get_node_download_url",
+    "openclaw_get_progress_logs",
+    "openclaw_get_status",
+    "openclaw_list_runtime_candidates",
+    "outline_node_get",
+    "outline_node_list",
+    "preview_automation_schedule",
+    "preview_cli_preset",
+    "project_memory_get",
+    "read_plugin_manifest_cmd",
+    "refresh_tray_menu",
+    "refresh_tray_with_stats",
+    "remind_update_later",
+    "report_frontend_crash",
+    "report_frontend_debug_log",
+    "search_models",
+    "search_pixabay_images",
+    "search_web_images",
+    "session_files_get_detail",
+    "session_files_list",
+    "session_files_list_files",
+    "set_app_role",
+    "set_window_size",
+    "site_get_adapter_catalog_status",
+    "site_get_adapter_info",
+    "site_list_adapters",
+    "site_search_adapters",
+    "style_guide_get",
+    "sync_tray_state",
+    "terminal_get_session",
+    "terminal_list_sessions",
+    "toggle_fullscreen",
+    "unified_memory_get",
+    "unified_memory_hybrid_search",
+    "unified_memory_list",
+    "unified_memory_search",
+    "unified_memory_semantic_search",
+    "unified_memory_stats",
+    "update_tray_credential_status",
+    "update_tray_server_status",
+    "workspace_get",
+    "workspace_get_by_path",
+    "workspace_get_default",
+    "workspace_get_projects_root",
+    "workspace_list",
+    "world_building_get",
+];
+
+/// `operator` 可调用的命令（运行时操作与含密钥的只读命令）
+const OPERATOR_COMMANDS: &[&str] = &[
+    "agent_generate_title",
+    "agent_runtime_close_subagent",
+    "agent_runtime_compact_session",
+    "agent_runtime_create_session",
+    "agent_runtime_interrupt_turn",
+    "agent_runtime_promote_queued_turn",
+    "agent_runtime_replay_request",
+    "agent_runtime_respond_action",
+    "agent_runtime_resume_subagent",
+    "agent_runtime_resume_thread",
+    "agent_runtime_send_subagent_input",
+    "agent_runtime_spawn_subagent",
+    "agent_runtime_submit_turn",
+    "agent_runtime_wait_subagents",
+    "agent_start_process",
+    "agent_stop_process",
+    "analyze_midi",
+    "archive_browser_environment_preset_cmd",
+    "archive_browser_profile_cmd",
+    "aster_agent_init",
+    "browser_execute_action",
+    "build_project_system_prompt",
+    "cancel_benchmark",
+    "cancel_kiro_builder_id_login",
+    "cancel_kiro_playwright_login",
+    "cancel_kiro_social_auth_login",
+    "cancel_plugin_task",
+    "cancel_recording",
+    "cancel_subagent_tasks",
+    "cancel_video_generation_task",
+    "character_create",
+    "check_admin_privileges",
+    "check_and_reload_credentials",
+    "check_and_reload_gemini_credentials",
+    "check_and_reload_oauth_credentials",
+    "check_api_compatibility",
+    "check_for_updates",
+    "check_playwright_available",
+    "check_provider_pool_credential_health",
+    "check_provider_pool_type_health",
+    "check_python_env",
+    "chrome_bridge_execute_command",
+    "claw_solution_check_readiness",
+    "claw_solution_detail",
+    "claw_solution_prepare",
+    "close_browser_runtime_debugger_window",
+    "close_cdp_session",
+    "close_chrome_profile_session",
+    "close_screenshot_chat_window",
+    "close_voice_window",
+    "close_webview_panel",
+    "connection_get",
+    "connection_get_raw_config",
+    "connection_test",
+    "content_create",
+    "content_reorder",
+    "content_workflow_advance",
+    "content_workflow_cancel",
+    "content_workflow_create",
+    "content_workflow_retry",
+    "convert_machine_id_format",
+    "convert_mp3_to_midi",
+    "copy_api_credentials",
+    "copy_machine_id_to_clipboard",
+    "create_a2ui_form",
+    "create_persona",
+    "create_poster_metadata",
+    "create_template",
+    "create_video_generation_task",
+    "create_webview_panel",
+    "detect_machine_id_format",
+    "discord_channel_probe",
+    "execute_ecommerce_review_reply",
+    "execute_skill",
+    "execute_subagent_tasks",
+    "expand_path",
+    "export_connection_doctor_report",
+    "export_crash_report",
+    "feishu_channel_probe",
+    "fetch_provider_models_auto",
+    "fetch_provider_models_from_api",
+    "focus_webview_panel",
+    "format_machine_id",
+    "gateway_channel_start",
+    "gateway_channel_stop",
+    "gateway_tunnel_detect_cloudflared",
+    "gateway_tunnel_probe",
+    "gateway_tunnel_restart",
+    "gateway_tunnel_start",
+    "gateway_tunnel_stop",
+    "generate_persona",
+    "generate_random_machine_id",
+    "get_ai_channel",
+    "get_ai_channels",
+    "get_all_oauth_credentials",
+    "get_api_key_provider",
+    "get_api_key_providers",
+    "get_app_api_info",
+    "get_asr_credentials",
+    "get_chrome_bridge_endpoint_info",
+    "get_config",
+    "get_current_switch_provider",
+    "get_deleted_provider_pool_credentials",
+    "get_effective_config",
+    "get_env_variables",
+    "get_environment_preview",
+    "get_gemini_credentials",
+    "get_gemini_env_variables",
+    "get_kiro_credentials",
+    "get_mcp_servers",
+    "get_next_api_key",
+    "get_notification_channel",
+    "get_notification_channels",
+    "get_oauth_credentials",
+    "get_oauth_env_variables",
+    "get_or_create_default_project",
+    "get_pairing_config",
+    "get_plugin_config",
+    "get_provider_pool_credentials",
+    "get_request_log_detail",
+    "get_route_curl_examples",
+    "get_setup_wizard_state",
+    "get_switch_providers",
+    "get_voice_input_config",
+    "handle_plugin_action",
+    "init_orchestrator",
+    "init_subagent_scheduler",
+    "launch_browser_profile_runtime_assist_cmd",
+    "launch_browser_runtime_assist",
+    "launch_browser_session",
+    "launch_plugin_ui",
+    "lint_config",
+    "list_dir",
+    "load_music_resource",
+    "mark_credential_healthy",
+    "mark_credential_unhealthy",
+    "mcp_call_tool",
+    "mcp_call_tool_with_caller",
+    "mcp_start_server",
+    "mcp_stop_server",
+    "memory_ensure_workspace_local_agents_gitignore",
+    "memory_runtime_cleanup",
+    "memory_runtime_request_analysis",
+    "memory_scaffold_runtime_agents_template",
+    "navigate_webview_panel",
+    "novel_check_consistency",
+    "novel_continue_chapter",
+    "novel_create_project",
+    "novel_generate_chapter",
+    "novel_generate_characters",
+    "novel_generate_outline",
+    "novel_polish_chapter",
+    "novel_rewrite_chapter",
+    "open_auth_dir",
+    "open_browser_runtime_debugger_window",
+    "open_cdp_session",
+    "open_chrome_profile_window",
+    "open_config_folder",
+    "open_input_with_text",
+    "open_voice_window",
+    "open_with_default_app",
+    "openclaw_check_git_available",
+    "openclaw_check_health",
+    "openclaw_check_installed",
+    "openclaw_check_node_version",
+    "openclaw_cleanup_temp_artifacts",
+    "openclaw_get_dashboard_url",
+    "openclaw_restart_gateway",
+    "openclaw_start_gateway",
+    "openclaw_stop_gateway",
+    "outline_node_create",
+    "output_voice_text",
+    "plugin_rpc_call",
+    "plugin_rpc_connect",
+    "plugin_rpc_disconnect",
+    "polish_voice_text",
+    "pool_insights",
+    "probe_endpoint_latency",
+    "probe_provider_pool_credential_health",
+    "quick_select_model",
+    "read_file_preview_cmd",
+    "read_image_as_base64",
+    "read_image_from_session",
+    "read_live_provider_settings",
+    "record_api_key_error",
+    "record_api_key_usage",
+    "record_model_usage",
+    "refresh_all_credential_models",
+    "refresh_credential_models",
+    "refresh_gemini_token",
+    "refresh_kiro_token",
+    "refresh_model_registry",
+    "refresh_oauth_token",
+    "refresh_pool_credential_token",
+    "refresh_relay_registry",
+    "refresh_skill_cache",
+    "release_browser_session",
+    "reload_credentials",
+    "reload_gemini_credentials",
+    "reload_oauth_credentials",
+    "reload_plugins",
+    "resize_webview_panel",
+    "resume_browser_session",
+    "reveal_in_finder",
+    "run_automation_job_now",
+    "run_benchmark",
+    "run_cloud_backup_now",
+    "run_connection_doctor",
+    "run_converter_golden_tests",
+    "run_db_maintenance",
+    "run_test_scenarios",
+    "select_model",
+    "select_model_for_task",
+    "send_connect_callback",
+    "send_screenshot_chat",
+    "session_files_cleanup_empty",
+    "session_files_cleanup_expired",
+    "session_files_create",
+    "session_files_exists",
+    "session_files_get_or_create",
+    "session_files_read_file",
+    "session_files_resolve_file_path",
+    "setup_start_server",
+    "simulate_credential_pool",
+    "site_apply_adapter_catalog_bootstrap",
+    "site_debug_run_adapter",
+    "site_run_adapter",
+    "social_generate_cover_image_cmd",
+    "start_browser_stream",
+    "start_har_capture",
+    "start_recording",
+    "start_screenshot",
+    "start_server",
+    "start_telegram_remote",
+    "stop_browser_stream",
+    "stop_har_capture",
+    "stop_recording",
+    "stop_server",
+    "stop_telegram_remote",
+    "submit_a2ui_form",
+    "subscribe_sysinfo",
+    "sync_tray_model_shortcuts",
+    "take_over_browser_session",
+    "telegram_channel_probe",
+    "test_ai_channel",
+    "test_api",
+    "test_api_key_provider_chat",
+    "test_api_key_provider_connection",
+    "test_asr_credential",
+    "test_notification_channel",
+    "test_provider_capabilities",
+    "test_tts",
+    "test_user_credentials",
+    "toggle_model_favorite",
+    "toggle_provider_pause",
+    "transcribe_audio",
+    "unified_memory_analyze",
+    "unified_memory_create",
+    "unified_memory_feedback",
+    "unload_plugin",
+    "unsubscribe_sysinfo",
+    "validate_automation_schedule",
+    "validate_config_patch",
+    "validate_config_yaml",
+    "validate_machine_id",
+    "validate_routing_preset",
+    "validate_shortcut",
+    "wechat_channel_list_accounts",
+    "wechat_channel_probe",
+    "workspace_create",
+    "workspace_ensure_default_ready",
+    "workspace_ensure_ready",
+    "workspace_resolve_project_path",
+];
+
+/// 命令所需的最低角色
+pub fn required_role(command: &str) -> AppRole {
+    if VIEWER_COMMANDS.contains(&command) {
+        AppRole::Viewer
+    } else if OPERATOR_COMMANDS.contains(&command) {
+        AppRole::Operator
+    } else {
+        AppRole::Admin
+    }
+}
+
+/// PIN 的加盐摘要（存入 `access_control.pin_hash`）
+pub fn hash_pin(pin: &str) -> String {
+    let mut salt = [0u8; PIN_SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    format!(
+        "{PIN_HASH_SCHEME}${}${}",
+        hex::encode(salt),
+        hex::encode(derive_key(pin.trim(), &salt))
+    )
+}
+
+/// 是否为旧版无盐的 SHA-256 摘要
+pub fn is_legacy_pin_hash(stored: &str) -> bool {
+    !stored.starts_with(&format!("{PIN_HASH_SCHEME}$"))
+}
+
+/// 校验 PIN（兼容旧版无盐的 SHA-256 摘要）
+fn verify_pin(pin: &str, stored: &str) -> bool {
+    let pin = pin.trim();
+    let (provided, expected) = match stored.split('$').collect::<Vec<_>>()[..] {
+        [PIN_HASH_SCHEME, salt, hash] => {
+            let Ok(salt) = hex::decode(salt) else {
+                return false;
+            };
+            (hex::encode(derive_key(pin, &salt)), hash)
+        }
+        _ => (hex::encode(Sha256::digest(pin.as_bytes())), stored),
+    };
+    bool::from(provided.as_bytes().ct_eq(expected.as_bytes()))
+}
+
+/// 连续 PIN 错误记录
+#[derive(Debug, Default)]
+struct PinAttempts {
+    failures: u32,
+    locked_until: Option<Instant>,
+}
+
+/// 当前生效的角色
+#[derive(Debug, Clone)]
+pub struct AppRoleState {
+    role: Arc<RwLock<AppRole>>,
+    attempts: Arc<Mutex<PinAttempts>>,
+}
+
+impl AppRoleState {
+    pub fn new(role: AppRole) -> Self {
+        Self {
+            role: Arc::new(RwLock::new(role)),
+            attempts: Arc::new(Mutex::new(PinAttempts::default())),
+        }
+    }
+
+    pub fn get(&self) -> AppRole {
+        *self.role.read()
+    }
+
+    /// 切换角色
+    ///
+    /// 降级总是允许；升级必须已设置 PIN 并提供正确的 PIN。返回是否校验通过了 PIN。
+    pub fn switch(
+        &self,
+        role: AppRole,
+        pin: Option<&str>,
+        pin_hash: Option<&str>,
+    ) -> Result<bool, String> {
+        self.switch_at(role, pin, pin_hash, Instant::now())
+    }
+
+    fn switch_at(
+        &self,
+        role: AppRole,
+        pin: Option<&str>,
+        pin_hash: Option<&str>,
+        now: Instant,
+    ) -> Result<bool, String> {
+        let mut current = self.role.write();
+        if role <= *current {
+            *current = role;
+            return Ok(false);
+        }
+        let Some(stored) = pin_hash.filter(|hash| !hash.is_empty()) else {
+            return Err("尚未设置 PIN，无法升级角色".to_string());
+        };
+
+        let mut attempts = self.attempts.lock();
+        if let Some(until) = attempts.locked_until.filter(|until| *until > now) {
+            let seconds = (until - now).as_secs().max(1);
+            return Err(format!("PIN 错误次数过多，请 {seconds} 秒后重试"));
+        }
+        if !verify_pin(pin.unwrap_or_default(), stored) {
+            attempts.failures += 1;
+            if attempts.failures >= PIN_FREE_ATTEMPTS {
+                let exponent = (attempts.failures - PIN_FREE_ATTEMPTS).min(16);
+                let backoff = PIN_BASE_BACKOFF
+                    .saturating_mul(1 << exponent)
+                    .min(PIN_MAX_BACKOFF);
+                attempts.locked_until = Some(now + backoff);
+                tracing::warn!(
+                    "[RBAC] PIN 连续错误 {} 次，暂停校验 {} 秒",
+                    attempts.failures,
+                    backoff.as_secs()
+                );
+            }
+            return Err("PIN 不正确".to_string());
+        }
+        *attempts = PinAttempts::default();
+        *current = role;
+        Ok(true)
+    }
+}
+
+/// 为命令分发器加上角色校验，权限不足的调用直接以错误结束
+pub fn guard_invoke_handler<R, F>(
+    role: AppRoleState,
+    handler: F,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static
+where
+    R: Runtime,
+    F: Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+{
+    move |invoke: Invoke<R>| {
+        let current = role.get();
+        let required = required_role(invoke.message.command());
+        if current < required {
+            let command = invoke.message.command().to_string();
+            tracing::warn!(
+                "[RBAC] 已拒绝命令 {}: 当前角色 {:?}，需要 {:?}",
+                command,
+                current,
+                required
+            );
+            invoke
+                .resolver
+                .reject(format!("权限不足：命令 {command} 需要 {required:?} 角色"));
+            return true;
+        }
+        handler(invoke)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_role_from_allowlists() {
+        assert_eq!(required_role("get_server_status"), AppRole::Viewer);
+        assert_eq!(required_role("set_app_role"), AppRole::Viewer);
+        assert_eq!(required_role("get_config"), AppRole::Operator);
+        assert_eq!(required_role("get_next_api_key"), AppRole::Operator);
+        assert_eq!(required_role("start_server"), AppRole::Operator);
+        assert_eq!(required_role("save_config"), AppRole::Admin);
+        assert_eq!(required_role("create_scoped_api_key"), AppRole::Admin);
+        assert_eq!(required_role("some_unregistered_command"), AppRole::Admin);
+
+        for list in [VIEWER_COMMANDS, OPERATOR_COMMANDS] {
+            assert!(list.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+        assert!(VIEWER_COMMANDS
+            .iter()
+            .all(|command| !OPERATOR_COMMANDS.contains(command)));
+    }
+
+    #[test]
+    fn test_switch_requires_pin_only_when_elevating() {
+        let state = AppRoleState::new(AppRole::Viewer);
+        let pin_hash = hash_pin("1234");
+        assert!(!is_legacy_pin_hash(&pin_hash));
+        assert_ne!(pin_hash, hash_pin("1234"));
+
+        assert!(state
+            .switch(AppRole::Admin, Some("0000"), Some(&pin_hash))
+            .is_err());
+        assert_eq!(state.get(), AppRole::Viewer);
+
+        assert!(state
+            .switch(AppRole::Admin, Some("1234"), Some(&pin_hash))
+            .unwrap());
+        assert_eq!(state.get(), AppRole::Admin);
+
+        assert!(!state
+            .switch(AppRole::Viewer, None, Some(&pin_hash))
+            .unwrap());
+        assert_eq!(state.get(), AppRole::Viewer);
+
+        let legacy = hex::encode(Sha256::digest(b"1234"));
+        assert!(is_legacy_pin_hash(&legacy));
+        assert!(state
+            .switch(AppRole::Operator, Some("1234"), Some(&legacy))
+            .unwrap());
+        assert_eq!(state.get(), AppRole::Operator);
+
+        // 未设置 PIN 时不能升级
+        assert!(state.switch(AppRole::Admin, None, None).is_err());
+        assert!(state
+            .switch(AppRole::Admin, Some("1234"), Some(""))
+            .is_err());
+        assert_eq!(state.get(), AppRole::Operator);
+    }
+
+    #[test]
+    fn test_pin_failures_back_off() {
+        let state = AppRoleState::new(AppRole::Viewer);
+        let pin_hash = hash_pin("1234");
+        let now = Instant::now();
+
+        for _ in 0..PIN_FREE_ATTEMPTS {
+            assert!(state
+                .switch_at(AppRole::Admin, Some("0000"), Some(&pin_hash), now)
+                .is_err());
+        }
+        // 退避期间即使 PIN 正确也不校验
+        assert!(state
+            .switch_at(AppRole::Admin, Some("1234"), Some(&pin_hash), now)
+            .is_err());
+        assert_eq!(state.get(), AppRole::Viewer);
+
+        let later = now + PIN_BASE_BACKOFF;
+        assert!(state
+            .switch_at(AppRole::Admin, Some("0000"), Some(&pin_hash), later)
+            .is_err());
+        assert!(state
+            .switch_at(
+                AppRole::Admin,
+                Some("1234"),
+                Some(&pin_hash),
+                later + PIN_BASE_BACKOFF
+            )
+            .is_err());
+
+        let much_later = later + PIN_BASE_BACKOFF * 2;
+        assert!(state
+            .switch_at(AppRole::Admin, Some("1234"), Some(&pin_hash), much_later)
+            .unwrap());
+        assert_eq!(state.get(), AppRole::Admin);
+        assert_eq!(state.attempts.lock().failures, 0);
+    }
+}
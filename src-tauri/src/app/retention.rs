@@ -0,0 +1,46 @@
+//! 数据保留定时清理任务
+//!
+//! 按 `server.retention` 定时清理审计日志、用量统计、媒体文件、调试抓包与会话文件。
+
+use std::time::Duration;
+
+use lime_core::config::RetentionSettings;
+use lime_core::database::DbConnection;
+use lime_core::retention::{self, RetentionDirs, RetentionReport};
+
+/// 启动后首次清理的延迟，避免影响启动性能
+const INITIAL_DELAY_SECS: u64 = 15 * 60;
+
+/// 执行一次清理（在阻塞线程中运行，数据库不可用时只清理文件存储）
+pub async fn run_retention_once(
+    db: DbConnection,
+    settings: RetentionSettings,
+) -> Result<RetentionReport, String> {
+    tokio::task::spawn_blocking(move || {
+        let dirs = RetentionDirs::resolve();
+        retention::run_retention(Some(&db), &settings, &dirs)
+    })
+    .await
+    .map_err(|e| format!("数据保留清理任务异常退出: {e}"))
+}
+
+/// 定时清理循环
+pub async fn run_retention_loop(db: DbConnection, settings: RetentionSettings) {
+    if !settings.enabled {
+        tracing::info!("[数据保留] 定时清理已禁用");
+        return;
+    }
+    let interval = Duration::from_secs(settings.interval_hours.max(1) * 3600);
+    tracing::info!(
+        "[数据保留] 定时清理已启动，间隔 {} 小时",
+        settings.interval_hours.max(1)
+    );
+
+    tokio::time::sleep(Duration::from_secs(INITIAL_DELAY_SECS)).await;
+    loop {
+        if let Err(e) = run_retention_once(db.clone(), settings.clone()).await {
+            tracing::warn!("[数据保留] 定时清理失败: {}", e);
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
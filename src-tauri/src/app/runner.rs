@@ -13,6 +13,7 @@ use crate::tray::{TrayIconStatus, TrayManager, TrayStateSnapshot};
 
 use super::bootstrap::{self, AppStates};
 use super::commands as app_commands;
+use super::rbac;
 use super::types::{AppState, TrayManagerState};
 
 const MAIN_WINDOW_LABEL: &str = "main";
@@ -138,6 +139,9 @@ pub fn run() {
     let gateway_tunnel_state_for_setup = gateway_tunnel_state.clone();
     let global_config_manager_for_setup = global_config_manager_state.clone();
     let tunnel_logs_clone = logs.clone();
+    let app_role_state = rbac::AppRoleState::new(config.access_control.default_role);
+    let app_role_for_invoke = app_role_state.clone();
+    super::config_audit::install(db.clone(), app_role_state.clone());
 
     let mut builder = tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
@@ -224,6 +228,7 @@ pub fn run() {
         .manage(lime_gateway::wechat::WechatGatewayState::default())
         .manage(lime_gateway::wechat::WechatLoginState::default())
         .manage(gateway_tunnel_state)
+        .manage(app_role_state)
         .manage(crate::services::openclaw_service::OpenClawServiceState::default())
         .manage(commands::telegram_remote_cmd::TelegramRemoteState::default())
         .on_window_event(move |window, event| {
@@ -291,6 +296,13 @@ pub fn run() {
                     let tray_state: TrayManagerState<tauri::Wry> =
                         TrayManagerState(Arc::new(tokio::sync::RwLock::new(Some(tray_manager))));
                     app.manage(tray_state);
+
+                    // 订阅类型化应用事件，并加载 Provider 开关与配置方案
+                    crate::tray::register_app_event_sync(app.handle());
+                    let app_handle = app.handle().clone();
+                    tauri::async_runtime::spawn(async move {
+                        crate::tray::refresh_tray_controls(&app_handle).await;
+                    });
                 }
                 Err(e) => {
                     tracing::error!("[启动] 托盘管理器初始化失败: {}", e);
@@ -325,6 +337,11 @@ pub fn run() {
                 tracing::info!("[启动] MCP Manager 事件发射器已设置");
             }
 
+            // 设置类型化应用事件总线的发射器（lime://app-event）
+            lime_core::app_events::app_event_bus().set_emitter(lime_core::DynEmitter::new(
+                crate::app::TauriEventEmitter(app.handle().clone()),
+            ));
+
             // 设置 PluginManager 的任务事件发射器（用于发送 plugin-task-event）
             if let Some(plugin_manager) =
                 app.try_state::<crate::commands::plugin_cmd::PluginManagerState>()
@@ -826,6 +843,9 @@ pub fn run() {
                             current_model: current_state.current_model,
                             current_theme_label: current_state.current_theme_label,
                             quick_model_groups: current_state.quick_model_groups,
+                            provider_toggles: current_state.provider_toggles,
+                            config_profiles: current_state.config_profiles,
+                            active_config_profile: current_state.active_config_profile,
                         };
 
                         if let Err(e) = tray_manager.update_state(snapshot).await {
@@ -848,6 +868,64 @@ pub fn run() {
             });
             tracing::info!("[启动] 后台更新检查任务已启动");
 
+            // 启动数据库定时维护任务
+            let db_for_maintenance = db_clone.clone();
+            let state_for_maintenance = state_clone.clone();
+            tauri::async_runtime::spawn(async move {
+                let settings = state_for_maintenance
+                    .read()
+                    .await
+                    .config
+                    .server
+                    .db_maintenance
+                    .clone();
+                crate::app::db_maintenance::run_db_maintenance_loop(db_for_maintenance, settings)
+                    .await;
+            });
+
+            // 启动数据保留定时清理任务
+            let db_for_retention = db_clone.clone();
+            let state_for_retention = state_clone.clone();
+            tauri::async_runtime::spawn(async move {
+                let settings = state_for_retention
+                    .read()
+                    .await
+                    .config
+                    .server
+                    .retention
+                    .clone();
+                crate::app::retention::run_retention_loop(db_for_retention, settings).await;
+            });
+
+            // 启动定时云备份任务
+            let db_for_backup = db_clone.clone();
+            let state_for_backup = state_clone.clone();
+            tauri::async_runtime::spawn(async move {
+                crate::app::cloud_backup::run_cloud_backup_loop(db_for_backup, state_for_backup)
+                    .await;
+            });
+
+            // 启动多区域端点延迟探测任务
+            let state_for_probe = state_clone.clone();
+            tauri::async_runtime::spawn(async move {
+                crate::app::endpoint_probe::run_endpoint_probe_loop(state_for_probe).await;
+            });
+
+            // 启动选主与主实例后台任务（Token 提前刷新、凭证健康探测）
+            let db_for_leader = db_clone.clone();
+            let state_for_leader = state_clone.clone();
+            let pool_service_for_leader = pool_service_clone.clone();
+            let token_cache_for_leader = token_cache_clone.clone();
+            tauri::async_runtime::spawn(async move {
+                let settings = state_for_leader.read().await.config.server.cluster.clone();
+                crate::app::leader_tasks::spawn_leader_tasks(
+                    db_for_leader,
+                    pool_service_for_leader,
+                    token_cache_for_leader,
+                    settings,
+                );
+            });
+
             // 启动会话文件清理任务（清理 30 天前的过期会话）
             tauri::async_runtime::spawn(async move {
                 // 延迟 10 秒执行，避免影响启动性能
@@ -1036,15 +1114,22 @@ pub fn run() {
 
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(rbac::guard_invoke_handler(
+            app_role_for_invoke,
+            tauri::generate_handler![
             // Server commands (from app::commands)
             app_commands::start_server,
             app_commands::stop_server,
             app_commands::get_server_status,
             app_commands::get_server_diagnostics,
+            app_commands::get_app_api_info,
             // Config commands (from app::commands)
             app_commands::get_config,
             app_commands::save_config,
+            app_commands::get_effective_config,
+            app_commands::validate_config_patch,
+            app_commands::apply_config_patch,
+            app_commands::get_config_audit_log,
             app_commands::get_environment_preview,
             app_commands::get_default_provider,
             app_commands::set_default_provider,
@@ -1087,11 +1172,18 @@ pub fn run() {
             app_commands::clear_logs,
             app_commands::clear_diagnostic_log_history,
             app_commands::report_frontend_crash,
+            app_commands::list_crash_reports,
+            app_commands::export_crash_report,
+            app_commands::clear_crash_reports,
             app_commands::report_frontend_debug_log,
+            commands::anonymous_stats_cmd::get_anonymous_stats_preview,
+            commands::anonymous_stats_cmd::upload_anonymous_stats,
+            commands::anonymous_stats_cmd::clear_anonymous_stats,
             // API test commands (from app::commands)
             app_commands::test_api,
             app_commands::get_available_models,
             app_commands::check_api_compatibility,
+            commands::provider_test_cmd::test_provider_capabilities,
             // Switch commands
             commands::switch_cmd::get_switch_providers,
             commands::switch_cmd::get_current_switch_provider,
@@ -1113,6 +1205,7 @@ pub fn run() {
             // Config import/export commands
             commands::config_cmd::export_config,
             commands::config_cmd::validate_config_yaml,
+            commands::config_cmd::lint_config,
             commands::config_cmd::import_config,
             commands::config_cmd::get_config_paths,
             // Enhanced export/import commands (using ExportService/ImportService)
@@ -1120,6 +1213,9 @@ pub fn run() {
             commands::config_cmd::export_config_yaml,
             commands::config_cmd::validate_import,
             commands::config_cmd::import_bundle,
+            commands::config_cmd::export_routing_preset,
+            commands::config_cmd::validate_routing_preset,
+            commands::config_cmd::import_routing_preset,
             // Path utility commands
             commands::config_cmd::expand_path,
             commands::config_cmd::open_auth_dir,
@@ -1233,11 +1329,19 @@ pub fn run() {
             commands::provider_pool_cmd::get_provider_pool_credentials,
             commands::provider_pool_cmd::add_provider_pool_credential,
             commands::provider_pool_cmd::update_provider_pool_credential,
+            commands::provider_pool_cmd::clone_provider_pool_credential,
+            commands::provider_pool_cmd::export_credential_pairing,
+            commands::provider_pool_cmd::import_credential_pairing,
             commands::provider_pool_cmd::delete_provider_pool_credential,
+            commands::provider_pool_cmd::get_deleted_provider_pool_credentials,
+            commands::provider_pool_cmd::restore_provider_pool_credential,
+            commands::provider_pool_cmd::purge_provider_pool_credential,
             commands::provider_pool_cmd::toggle_provider_pool_credential,
             commands::provider_pool_cmd::reset_provider_pool_credential,
             commands::provider_pool_cmd::reset_provider_pool_health,
             commands::provider_pool_cmd::check_provider_pool_credential_health,
+            commands::provider_pool_cmd::probe_provider_pool_credential_health,
+            commands::provider_pool_cmd::pool_insights,
             commands::provider_pool_cmd::check_provider_pool_type_health,
             commands::provider_pool_cmd::add_kiro_oauth_credential,
             commands::provider_pool_cmd::add_kiro_from_json,
@@ -1338,6 +1442,7 @@ pub fn run() {
             commands::telemetry_cmd::get_token_stats_by_provider,
             commands::telemetry_cmd::get_token_stats_by_model,
             commands::telemetry_cmd::get_token_stats_by_day,
+            commands::telemetry_cmd::simulate_credential_pool,
             // Injection commands
             commands::injection_cmd::get_injection_config,
             commands::injection_cmd::set_injection_enabled,
@@ -1354,6 +1459,45 @@ pub fn run() {
             commands::security_perf_cmd::update_hint_routes,
             commands::security_perf_cmd::get_pairing_config,
             commands::security_perf_cmd::update_pairing_config,
+            commands::security_perf_cmd::list_banned_ips,
+            commands::security_perf_cmd::clear_banned_ips,
+            commands::db_maintenance_cmd::run_db_maintenance,
+            commands::db_maintenance_cmd::get_db_maintenance_status,
+            commands::db_maintenance_cmd::run_retention_now,
+            commands::db_maintenance_cmd::run_cloud_backup_now,
+            commands::db_maintenance_cmd::list_cloud_backups,
+            commands::db_maintenance_cmd::restore_cloud_backup,
+            commands::setup_wizard_cmd::get_setup_wizard_state,
+            commands::setup_wizard_cmd::reset_setup_wizard,
+            commands::setup_wizard_cmd::setup_detect_credentials,
+            commands::setup_wizard_cmd::setup_test_credentials,
+            commands::setup_wizard_cmd::setup_generate_api_key,
+            commands::setup_wizard_cmd::setup_choose_port,
+            commands::setup_wizard_cmd::setup_write_config,
+            commands::setup_wizard_cmd::setup_start_server,
+            commands::connection_doctor_cmd::run_connection_doctor,
+            commands::connection_doctor_cmd::export_connection_doctor_report,
+            commands::bench_cmd::run_benchmark,
+            commands::bench_cmd::cancel_benchmark,
+            commands::scenario_cmd::list_test_scenarios,
+            commands::scenario_cmd::run_test_scenarios,
+            commands::endpoint_latency_cmd::get_endpoint_latency,
+            commands::endpoint_latency_cmd::probe_endpoint_latency,
+            commands::converter_golden_cmd::run_converter_golden_tests,
+            commands::har_capture_cmd::start_har_capture,
+            commands::har_capture_cmd::stop_har_capture,
+            commands::har_capture_cmd::get_har_capture_status,
+            commands::har_capture_cmd::clear_har_capture,
+            commands::har_capture_cmd::export_har_capture,
+            commands::maintenance_cmd::get_maintenance_mode,
+            commands::maintenance_cmd::set_maintenance_mode,
+            commands::lan_pairing_cmd::create_lan_pairing,
+            commands::lan_pairing_cmd::create_scoped_api_key,
+            commands::lan_pairing_cmd::list_scoped_api_keys,
+            commands::lan_pairing_cmd::revoke_scoped_api_key,
+            commands::cli_preset_cmd::preview_cli_preset,
+            commands::cli_preset_cmd::apply_cli_preset,
+            commands::cli_preset_cmd::rollback_cli_preset,
             // Usage commands
             commands::usage_cmd::get_kiro_usage,
             // Tray commands
@@ -1364,6 +1508,12 @@ pub fn run() {
             commands::tray_cmd::refresh_tray_menu,
             commands::tray_cmd::refresh_tray_with_stats,
             commands::tray_cmd::sync_tray_model_shortcuts,
+            commands::tray_cmd::toggle_provider_pause,
+            commands::tray_cmd::copy_api_credentials,
+            commands::tray_cmd::list_config_profiles,
+            commands::tray_cmd::save_config_profile,
+            commands::tray_cmd::delete_config_profile,
+            commands::tray_cmd::switch_config_profile,
             // Plugin commands
             commands::plugin_cmd::get_plugin_status,
             commands::plugin_cmd::get_plugins,
@@ -1619,6 +1769,7 @@ pub fn run() {
             // Update Check commands
             commands::update_cmd::check_update,
             commands::update_cmd::check_for_updates,
+            commands::update_cmd::get_last_update_result,
             commands::update_cmd::download_update,
             commands::update_cmd::get_update_check_settings,
             commands::update_cmd::set_update_check_settings,
@@ -1882,7 +2033,10 @@ pub fn run() {
             commands::telegram_remote_cmd::start_telegram_remote,
             commands::telegram_remote_cmd::stop_telegram_remote,
             commands::telegram_remote_cmd::get_telegram_remote_status,
-        ])
+            commands::security_perf_cmd::get_app_role,
+            commands::security_perf_cmd::set_app_role,
+            commands::security_perf_cmd::set_app_role_pin,
+        ]))
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -80,6 +80,10 @@ impl SchedulerService {
             loop {
                 tokio::select! {
                     _ = ticker.tick() => {
+                        // 多实例共享存储时仅由主实例执行到期任务
+                        if !lime_core::cluster::is_leader() {
+                            continue;
+                        }
                         if let Err(e) = Self::poll_and_execute(
                             &scheduler,
                             &executor,
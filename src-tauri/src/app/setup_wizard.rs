@@ -0,0 +1,384 @@
+//! 首次运行设置向导
+//!
+//! 向导按固定顺序推进：检测本机凭证 → 测试凭证 → 生成入站 API Key → 选择端口 →
+//! 写入初始配置 → 启动服务。进度保存在数据库 `settings` 表中，中途关闭应用后可继续。
+
+use std::net::TcpListener;
+
+use lime_core::config::{is_strong_api_key, Config};
+use lime_core::models::provider_pool_model::{CredentialData, ProviderCredential};
+use lime_providers::providers::antigravity::AntigravityProvider;
+use lime_providers::providers::claude_oauth::ClaudeOAuthProvider;
+use lime_providers::providers::codex::CodexProvider;
+use lime_providers::providers::gemini::GeminiProvider;
+use lime_providers::providers::kiro::KiroProvider;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// 向导进度在 `settings` 表中的键
+const STATE_SETTING_KEY: &str = "setup_wizard_state";
+
+/// 选择端口时最多尝试的连续端口数
+const PORT_SCAN_ATTEMPTS: u16 = 20;
+
+/// 可读取 API Key 的环境变量：(变量名, Provider 类型)
+const API_KEY_ENV_VARS: &[(&str, &str)] = &[
+    ("OPENAI_API_KEY", "openai"),
+    ("ANTHROPIC_API_KEY", "claude"),
+    ("GEMINI_API_KEY", "gemini_api_key"),
+];
+
+/// 向导步骤（按执行顺序）
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupStep {
+    #[default]
+    DetectCredentials,
+    TestCredentials,
+    GenerateApiKey,
+    ChoosePort,
+    WriteConfig,
+    StartServer,
+    Completed,
+}
+
+/// 检测到的本机凭证（不含密钥明文）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DetectedCredential {
+    /// 稳定标识，如 `file:kiro`、`env:OPENAI_API_KEY`
+    pub id: String,
+    pub provider_type: String,
+    /// 来源：凭证文件路径或环境变量名
+    pub source: String,
+    /// 展示名称（密钥已脱敏）
+    pub label: String,
+    /// 凭证池中已存在的同一凭证
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_uuid: Option<String>,
+}
+
+/// 凭证测试结果
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CredentialTestOutcome {
+    pub id: String,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// 测试通过后保留在凭证池中的凭证
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_uuid: Option<String>,
+}
+
+/// 向导进度
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SetupWizardState {
+    /// 下一个待执行的步骤
+    pub step: SetupStep,
+    #[serde(default)]
+    pub detected: Vec<DetectedCredential>,
+    #[serde(default)]
+    pub tested: Vec<CredentialTestOutcome>,
+    /// 生成的入站 API Key（写入配置后清除）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+}
+
+impl SetupWizardState {
+    /// 校验前置步骤已完成（允许重做已完成的步骤）
+    pub fn require(&self, step: SetupStep) -> Result<(), String> {
+        if self.step < step {
+            return Err(format!(
+                "请先完成设置向导的前置步骤（当前步骤: {}）",
+                self.step.as_str()
+            ));
+        }
+        Ok(())
+    }
+
+    /// 标记 `step` 已完成，推进到下一步
+    pub fn advance_past(&mut self, step: SetupStep) {
+        self.step = self.step.max(step.next());
+    }
+}
+
+impl SetupStep {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::DetectCredentials => "detect_credentials",
+            Self::TestCredentials => "test_credentials",
+            Self::GenerateApiKey => "generate_api_key",
+            Self::ChoosePort => "choose_port",
+            Self::WriteConfig => "write_config",
+            Self::StartServer => "start_server",
+            Self::Completed => "completed",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::DetectCredentials => Self::TestCredentials,
+            Self::TestCredentials => Self::GenerateApiKey,
+            Self::GenerateApiKey => Self::ChoosePort,
+            Self::ChoosePort => Self::WriteConfig,
+            Self::WriteConfig => Self::StartServer,
+            Self::StartServer | Self::Completed => Self::Completed,
+        }
+    }
+}
+
+/// 读取向导进度（未开始时返回初始状态）
+pub fn load_state(conn: &Connection) -> SetupWizardState {
+    conn.query_row(
+        "SELECT value FROM settings WHERE key = ?1",
+        [STATE_SETTING_KEY],
+        |row| row.get::<_, String>(0),
+    )
+    .ok()
+    .and_then(|value| serde_json::from_str(&value).ok())
+    .unwrap_or_default()
+}
+
+/// 保存向导进度
+pub fn save_state(conn: &Connection, state: &SetupWizardState) -> Result<(), String> {
+    let value = serde_json::to_string(state).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+        params![STATE_SETTING_KEY, value],
+    )
+    .map_err(|e| format!("保存设置向导进度失败: {e}"))?;
+    Ok(())
+}
+
+/// 清除向导进度
+pub fn reset_state(conn: &Connection) -> Result<(), String> {
+    conn.execute("DELETE FROM settings WHERE key = ?1", [STATE_SETTING_KEY])
+        .map_err(|e| format!("重置设置向导失败: {e}"))?;
+    Ok(())
+}
+
+/// 扫描本机已有的凭证：各 CLI 的 OAuth 凭证文件与常见的 API Key 环境变量
+pub fn scan_local_credentials() -> Vec<(DetectedCredential, CredentialData)> {
+    let oauth_files = [
+        ("kiro", KiroProvider::default_creds_path(), "Kiro"),
+        ("gemini", GeminiProvider::default_creds_path(), "Gemini CLI"),
+        (
+            "antigravity",
+            AntigravityProvider::default_creds_path(),
+            "Antigravity",
+        ),
+        ("codex", CodexProvider::default_creds_path(), "Codex CLI"),
+        (
+            "claude_oauth",
+            ClaudeOAuthProvider::default_creds_path(),
+            "Claude OAuth",
+        ),
+    ];
+
+    let mut found = Vec::new();
+    for (provider_type, path, name) in oauth_files {
+        if !path.is_file() {
+            continue;
+        }
+        let creds_file_path = path.to_string_lossy().to_string();
+        let data = match provider_type {
+            "kiro" => CredentialData::KiroOAuth { creds_file_path },
+            "gemini" => CredentialData::GeminiOAuth {
+                creds_file_path,
+                project_id: None,
+            },
+            "antigravity" => CredentialData::AntigravityOAuth {
+                creds_file_path,
+                project_id: None,
+            },
+            "codex" => CredentialData::CodexOAuth {
+                creds_file_path,
+                api_base_url: None,
+            },
+            _ => CredentialData::ClaudeOAuth { creds_file_path },
+        };
+        found.push((
+            DetectedCredential {
+                id: format!("file:{provider_type}"),
+                provider_type: provider_type.to_string(),
+                source: path.to_string_lossy().to_string(),
+                label: format!("{name}（{}）", path.display()),
+                pool_uuid: None,
+            },
+            data,
+        ));
+    }
+
+    for (var, provider_type) in API_KEY_ENV_VARS {
+        let Some(api_key) = std::env::var(var).ok().filter(|v| !v.trim().is_empty()) else {
+            continue;
+        };
+        let data = match *provider_type {
+            "openai" => CredentialData::OpenAIKey {
+                api_key,
+                base_url: None,
+            },
+            "claude" => CredentialData::ClaudeKey {
+                api_key,
+                base_url: None,
+            },
+            _ => CredentialData::GeminiApiKey {
+                api_key,
+                base_url: None,
+                excluded_models: Vec::new(),
+            },
+        };
+        found.push((
+            DetectedCredential {
+                id: format!("env:{var}"),
+                provider_type: provider_type.to_string(),
+                source: var.to_string(),
+                label: data.display_name(),
+                pool_uuid: None,
+            },
+            data,
+        ));
+    }
+    found
+}
+
+/// 凭证的身份（文件路径或 API Key），用于判断凭证池中是否已有同一凭证
+fn credential_identity(data: &CredentialData) -> String {
+    match data {
+        CredentialData::KiroOAuth { creds_file_path }
+        | CredentialData::GeminiOAuth {
+            creds_file_path, ..
+        }
+        | CredentialData::AntigravityOAuth {
+            creds_file_path, ..
+        }
+        | CredentialData::CodexOAuth {
+            creds_file_path, ..
+        }
+        | CredentialData::ClaudeOAuth { creds_file_path } => format!("file:{creds_file_path}"),
+        CredentialData::OpenAIKey { api_key, .. }
+        | CredentialData::ClaudeKey { api_key, .. }
+        | CredentialData::VertexKey { api_key, .. }
+        | CredentialData::GeminiApiKey { api_key, .. }
+        | CredentialData::AnthropicKey { api_key, .. } => format!("key:{api_key}"),
+    }
+}
+
+/// 在凭证池中查找与 `data` 相同的凭证
+pub fn find_in_pool<'a>(
+    pool: &'a [ProviderCredential],
+    data: &CredentialData,
+) -> Option<&'a ProviderCredential> {
+    let identity = credential_identity(data);
+    pool.iter()
+        .find(|cred| credential_identity(&cred.credential) == identity)
+}
+
+/// 从 `preferred` 开始查找可监听的端口；`own_port` 为本应用已在监听的端口，视为可用
+pub fn find_available_port(host: &str, preferred: u16, own_port: Option<u16>) -> Option<u16> {
+    (0..PORT_SCAN_ATTEMPTS)
+        .filter_map(|offset| preferred.checked_add(offset))
+        .find(|&port| Some(port) == own_port || TcpListener::bind((host, port)).is_ok())
+}
+
+/// 生成初始配置：在当前配置基础上写入入站 API Key 与端口，其余设置保持不变
+pub fn build_starter_config(current: &Config, api_key: &str, port: u16) -> Result<Config, String> {
+    if !is_strong_api_key(api_key) {
+        return Err("入站 API Key 强度不足".to_string());
+    }
+    let mut config = current.clone();
+    config.server.api_key = api_key.to_string();
+    config.server.port = port;
+    if config.server.host.trim().is_empty() {
+        config.server.host = "127.0.0.1".to_string();
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lime_core::config::generate_secure_api_key;
+    use lime_core::database::schema::create_tables;
+
+    #[test]
+    fn test_state_persists_and_steps_advance_in_order() {
+        let conn = Connection::open_in_memory().expect("创建内存数据库失败");
+        create_tables(&conn).expect("创建表结构失败");
+
+        let mut state = load_state(&conn);
+        assert_eq!(state.step, SetupStep::DetectCredentials);
+        assert!(state.require(SetupStep::WriteConfig).is_err());
+
+        state.advance_past(SetupStep::DetectCredentials);
+        state.advance_past(SetupStep::TestCredentials);
+        state.port = Some(8999);
+        save_state(&conn, &state).expect("保存应成功");
+
+        let mut resumed = load_state(&conn);
+        assert_eq!(resumed, state);
+        assert_eq!(resumed.step, SetupStep::GenerateApiKey);
+        assert!(resumed.require(SetupStep::TestCredentials).is_ok());
+
+        // 重做较早的步骤不会让进度倒退
+        resumed.advance_past(SetupStep::DetectCredentials);
+        assert_eq!(resumed.step, SetupStep::GenerateApiKey);
+
+        reset_state(&conn).expect("重置应成功");
+        assert_eq!(load_state(&conn), SetupWizardState::default());
+    }
+
+    #[test]
+    fn test_find_available_port_skips_busy_ports() {
+        let busy = TcpListener::bind(("127.0.0.1", 0)).expect("绑定端口失败");
+        let busy_port = busy.local_addr().unwrap().port();
+
+        let port = find_available_port("127.0.0.1", busy_port, None).expect("应找到可用端口");
+        assert_ne!(port, busy_port);
+        assert_eq!(
+            find_available_port("127.0.0.1", busy_port, Some(busy_port)),
+            Some(busy_port)
+        );
+    }
+
+    #[test]
+    fn test_starter_config_keeps_other_settings() {
+        let mut current = Config::default();
+        current.routing.default_provider = "gemini".to_string();
+        let key = generate_secure_api_key();
+
+        let config = build_starter_config(&current, &key, 9100).expect("应生成配置");
+        assert_eq!(config.server.api_key, key);
+        assert_eq!(config.server.port, 9100);
+        assert_eq!(config.routing.default_provider, "gemini");
+        assert!(build_starter_config(&current, "weak", 9100).is_err());
+    }
+
+    #[test]
+    fn test_find_in_pool_matches_by_file_or_key() {
+        let pool = vec![ProviderCredential::new(
+            "kiro".parse().unwrap(),
+            CredentialData::KiroOAuth {
+                creds_file_path: "/home/u/.aws/sso/cache/kiro-auth-token.json".to_string(),
+            },
+        )];
+        assert!(find_in_pool(
+            &pool,
+            &CredentialData::KiroOAuth {
+                creds_file_path: "/home/u/.aws/sso/cache/kiro-auth-token.json".to_string(),
+            }
+        )
+        .is_some());
+        assert!(find_in_pool(
+            &pool,
+            &CredentialData::OpenAIKey {
+                api_key: "sk-test".to_string(),
+                base_url: None,
+            }
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,125 @@
+//! 命令行子命令
+//!
+//! `lime test [--base-url URL] [--api-key KEY] [--json] [场景文件...]`：对正在运行的本地服务
+//! 执行声明式测试场景（未指定文件时运行场景目录中的全部场景），全部通过时退出码为 0，
+//! 有场景失败时为 1，参数或配置错误时为 2。
+
+use std::path::PathBuf;
+
+use lime_services::scenario_service::{self, ScenarioReport};
+
+const USAGE: &str = "用法: lime test [--base-url URL] [--api-key KEY] [--json] [场景文件...]";
+
+/// 执行子命令，`args` 不含程序名；不是已知子命令时返回 `None`
+pub fn try_run(args: &[String]) -> Option<i32> {
+    match args.first().map(String::as_str) {
+        Some("test") => Some(run_test(&args[1..])),
+        _ => None,
+    }
+}
+
+fn run_test(args: &[String]) -> i32 {
+    let mut base_url = None;
+    let mut api_key = None;
+    let mut json = false;
+    let mut files = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--base-url" => base_url = iter.next().cloned(),
+            "--api-key" => api_key = iter.next().cloned(),
+            "--json" => json = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return 0;
+            }
+            other if other.starts_with("--") => {
+                eprintln!("未知参数: {other}\n{USAGE}");
+                return 2;
+            }
+            other => files.push(PathBuf::from(other)),
+        }
+    }
+
+    if base_url.is_none() || api_key.is_none() {
+        let config = match lime_core::config::load_config() {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("加载配置失败: {e}");
+                return 2;
+            }
+        };
+        // 监听所有地址时通过回环地址访问
+        let host = match config.server.host.as_str() {
+            "0.0.0.0" | "::" | "[::]" => "127.0.0.1".to_string(),
+            host => host.to_string(),
+        };
+        base_url.get_or_insert_with(|| format!("http://{host}:{}", config.server.port));
+        api_key.get_or_insert(config.server.api_key);
+    }
+    let (Some(base_url), Some(api_key)) = (base_url, api_key) else {
+        return 2;
+    };
+
+    if files.is_empty() {
+        files = scenario_service::list_scenario_files(&scenario_service::scenarios_dir());
+    }
+    if files.is_empty() {
+        eprintln!(
+            "没有找到测试场景，请把 YAML 场景文件放到 {}",
+            scenario_service::scenarios_dir().display()
+        );
+        return 2;
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("创建运行时失败: {e}");
+            return 2;
+        }
+    };
+    let reports = runtime.block_on(scenario_service::run_scenario_files(
+        &base_url, &api_key, &files,
+    ));
+
+    if json {
+        match serde_json::to_string_pretty(&reports) {
+            Ok(output) => println!("{output}"),
+            Err(e) => {
+                eprintln!("序列化报告失败: {e}");
+                return 2;
+            }
+        }
+    } else {
+        print_reports(&reports);
+    }
+    if reports.iter().all(|report| report.passed) {
+        0
+    } else {
+        1
+    }
+}
+
+fn print_reports(reports: &[ScenarioReport]) {
+    for report in reports {
+        let mark = if report.passed { "PASS" } else { "FAIL" };
+        println!("[{mark}] {} ({}ms)", report.name, report.duration_ms);
+        for step in &report.steps {
+            let mark = if step.passed { "ok" } else { "x" };
+            let status = step
+                .status
+                .map(|status| status.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            println!("  {mark:>2} {} [{status}] {}ms", step.name, step.latency_ms);
+            for failure in &step.failures {
+                println!("       {failure}");
+            }
+        }
+        if report.skipped > 0 {
+            println!("  跳过 {} 个步骤", report.skipped);
+        }
+    }
+    let passed = reports.iter().filter(|report| report.passed).count();
+    println!("{passed}/{} 个场景通过", reports.len());
+}
@@ -0,0 +1,55 @@
+//! 匿名使用统计命令
+//!
+//! 上传只能由用户在查看预览后主动触发，上传内容即预览内容。
+
+use std::time::Duration;
+
+use crate::app::types::AppState;
+use lime_core::anonymous_stats::{self, UsageReport};
+use tauri::State;
+
+/// 上传请求超时
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 查看待上传的统计内容（未启用或暂无数据时为空）
+#[tauri::command]
+pub async fn get_anonymous_stats_preview(
+    state: State<'_, AppState>,
+) -> Result<Option<UsageReport>, String> {
+    let epsilon = state.read().await.config.anonymous_stats.epsilon;
+    Ok(anonymous_stats::preview(epsilon, env!("CARGO_PKG_VERSION")))
+}
+
+/// 上传已预览的统计内容
+#[tauri::command]
+pub async fn upload_anonymous_stats(state: State<'_, AppState>) -> Result<UsageReport, String> {
+    let settings = state.read().await.config.anonymous_stats.clone();
+    if !settings.enabled {
+        return Err("匿名使用统计未启用".to_string());
+    }
+    let url = settings
+        .upload_url
+        .filter(|url| !url.trim().is_empty())
+        .ok_or_else(|| "未配置上传地址".to_string())?;
+    let report =
+        anonymous_stats::pending_report().ok_or_else(|| "请先查看待上传的统计内容".to_string())?;
+
+    reqwest::Client::new()
+        .post(&url)
+        .timeout(UPLOAD_TIMEOUT)
+        .json(&report)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("上传匿名使用统计失败: {e}"))?;
+    anonymous_stats::commit_upload();
+    tracing::info!("[ANONYMOUS_STATS] 已上传匿名使用统计");
+    Ok(report)
+}
+
+/// 清空已累计的统计数据与预览
+#[tauri::command]
+pub async fn clear_anonymous_stats() -> Result<(), String> {
+    anonymous_stats::clear();
+    Ok(())
+}
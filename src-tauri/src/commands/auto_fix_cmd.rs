@@ -2,7 +2,7 @@
 //!
 //! 提供自动检测和修复常见配置问题的功能
 
-use crate::database::dao::provider_pool::ProviderPoolDao;
+use crate::database::pool_storage::{lock_pool, pool_storage};
 use crate::database::DbConnection;
 use crate::models::provider_pool_model::PoolProviderType;
 use crate::{config, AppState, LogState, ProviderType};
@@ -135,11 +135,11 @@ struct CredentialStats {
 }
 
 async fn get_credential_stats(db: &State<'_, DbConnection>) -> Result<CredentialStats, String> {
-    let conn = db.lock().map_err(|e| e.to_string())?;
+    let conn = lock_pool(&db)?;
     let mut stats = CredentialStats::default();
 
     // 统计各类型凭证数量（只计算启用且健康的凭证）
-    let all_credentials = ProviderPoolDao::get_all(&conn).map_err(|e| e.to_string())?;
+    let all_credentials = pool_storage().get_all(&conn)?;
 
     for cred in all_credentials
         .iter()
@@ -229,8 +229,8 @@ async fn check_credential_pool_issues(
     db: &State<'_, DbConnection>,
     result: &mut AutoFixResult,
 ) -> Result<(), String> {
-    let conn = db.lock().map_err(|e| e.to_string())?;
-    let credentials = ProviderPoolDao::get_all(&conn).map_err(|e| e.to_string())?;
+    let conn = lock_pool(&db)?;
+    let credentials = pool_storage().get_all(&conn)?;
 
     // 检查是否有过期的token缓存
     let mut expired_tokens = 0;
@@ -0,0 +1,58 @@
+//! 压测命令
+//!
+//! 对正在运行的本地服务发起合成负载，同一时间只允许一个压测任务。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::app::types::AppState;
+use lime_services::bench_service::{self, BenchOptions, BenchReport};
+use parking_lot::{const_mutex, Mutex};
+use tauri::State;
+
+/// 当前压测任务的取消标记
+static RUNNING_BENCH: Mutex<Option<Arc<AtomicBool>>> = const_mutex(None);
+
+/// 运行压测（注意：请求会真实转发到上游并计入用量）
+#[tauri::command]
+pub async fn run_benchmark(
+    state: State<'_, AppState>,
+    options: BenchOptions,
+) -> Result<BenchReport, String> {
+    let (base_url, api_key) = {
+        let s = state.read().await;
+        if !s.running {
+            return Err("服务未启动，请先启动服务".to_string());
+        }
+        let status = s.status();
+        let api_key = s
+            .running_api_key
+            .clone()
+            .unwrap_or_else(|| s.config.server.api_key.clone());
+        (format!("http://{}:{}", status.host, status.port), api_key)
+    };
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut running = RUNNING_BENCH.lock();
+        if running.is_some() {
+            return Err("已有压测任务在运行".to_string());
+        }
+        *running = Some(cancel.clone());
+    }
+    let result = bench_service::run_bench(&base_url, &api_key, options, cancel).await;
+    *RUNNING_BENCH.lock() = None;
+    result
+}
+
+/// 取消正在运行的压测（已发出的请求会继续完成）
+#[tauri::command]
+pub async fn cancel_benchmark() -> Result<bool, String> {
+    Ok(match RUNNING_BENCH.lock().as_ref() {
+        Some(cancel) => {
+            cancel.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    })
+}
@@ -0,0 +1,50 @@
+//! CLI 工具接入预设命令
+//!
+//! 为 Claude Code、Codex CLI 与 Cline 生成指向本机网关的配置，支持预览、应用与回滚。
+
+use crate::AppState;
+use lime_core::network::get_local_url;
+use lime_services::cli_preset_service::{
+    self, CliPresetApplyResult, CliPresetModels, CliPresetOptions, CliPresetPlan, CliTool,
+};
+
+/// 由当前网关配置构建预设参数（CLI 与网关在同一台机器上，使用本地地址）
+async fn preset_options(
+    state: &tauri::State<'_, AppState>,
+    models: Option<CliPresetModels>,
+) -> CliPresetOptions {
+    let s = state.read().await;
+    CliPresetOptions {
+        base_url: get_local_url(&s.config.server.host, s.config.server.port),
+        api_key: s.config.server.api_key.clone(),
+        models: models.unwrap_or_default(),
+    }
+}
+
+/// 预览接入预设（不写入文件）
+#[tauri::command]
+pub async fn preview_cli_preset(
+    state: tauri::State<'_, AppState>,
+    tool: CliTool,
+    models: Option<CliPresetModels>,
+) -> Result<CliPresetPlan, String> {
+    let options = preset_options(&state, models).await;
+    cli_preset_service::preview_preset(tool, &options)
+}
+
+/// 应用接入预设（写入前自动备份）
+#[tauri::command]
+pub async fn apply_cli_preset(
+    state: tauri::State<'_, AppState>,
+    tool: CliTool,
+    models: Option<CliPresetModels>,
+) -> Result<CliPresetApplyResult, String> {
+    let options = preset_options(&state, models).await;
+    cli_preset_service::apply_preset(tool, &options)
+}
+
+/// 回滚最近一次应用的接入预设，返回还原的文件
+#[tauri::command]
+pub async fn rollback_cli_preset(tool: CliTool) -> Result<Vec<String>, String> {
+    cli_preset_service::rollback_preset(tool)
+}
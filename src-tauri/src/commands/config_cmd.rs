@@ -1,6 +1,7 @@
 use crate::config::{
-    Config, ConfigManager, ExportBundle, ExportOptions as ExportServiceOptions, ExportService,
-    ImportOptions as ImportServiceOptions, ImportService, ValidationResult,
+    lint_config as lint_config_rules, Config, ConfigLintWarning, ConfigManager, ExportBundle,
+    ExportOptions as ExportServiceOptions, ExportService, ImportOptions as ImportServiceOptions,
+    ImportService, PresetConflictStrategy, RoutingPreset, ValidationResult,
 };
 use crate::models::app_type::AppType;
 use serde::{Deserialize, Serialize};
@@ -267,6 +268,8 @@ pub struct ImportResult {
     pub config: Config,
     /// 警告信息（如果有）
     pub warnings: Vec<String>,
+    /// 导入后配置的逻辑检查警告
+    pub lint: Vec<ConfigLintWarning>,
 }
 
 /// 验证配置 YAML 格式
@@ -333,11 +336,18 @@ pub fn import_config(
 
     Ok(ImportResult {
         success: true,
+        lint: lint_config_rules(&final_config),
         config: final_config,
         warnings,
     })
 }
 
+/// 检查配置中的逻辑问题（规则覆盖、未定义的 Provider、无主凭证、回退链）
+#[tauri::command]
+pub fn lint_config(config: Config) -> Vec<ConfigLintWarning> {
+    lint_config_rules(&config)
+}
+
 /// 获取配置文件路径信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigPathInfo {
@@ -504,6 +514,7 @@ pub fn import_bundle(
             success: result.success,
             config: result.config,
             warnings: result.warnings,
+            lint: result.lint,
         });
     }
 
@@ -516,6 +527,83 @@ pub fn import_bundle(
         success: result.success,
         config: result.config,
         warnings: result.warnings,
+        lint: result.lint,
+    })
+}
+
+// ============ Routing Preset Commands ============
+
+/// 导出路由预设（模型别名、规模 / 时段路由规则、参数注入规则）
+///
+/// # Arguments
+/// * `config` - 当前配置
+/// * `name` - 预设名称
+/// * `description` - 预设说明
+#[tauri::command]
+pub fn export_routing_preset(
+    config: Config,
+    name: String,
+    description: Option<String>,
+) -> Result<ExportResult, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("预设名称不能为空".to_string());
+    }
+    let app_version = env!("CARGO_PKG_VERSION");
+    let preset = ExportService::export_preset(
+        &config,
+        name,
+        description.as_deref().unwrap_or_default().trim(),
+        app_version,
+    );
+    let content = preset.to_json().map_err(|e| e.to_string())?;
+
+    let slug: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    let suggested_filename = format!("lime_preset_{slug}_{timestamp}.json");
+
+    Ok(ExportResult {
+        content,
+        suggested_filename,
+    })
+}
+
+/// 验证路由预设并列出与当前配置冲突的条目
+///
+/// # Arguments
+/// * `current_config` - 当前配置
+/// * `content` - 预设内容（JSON）
+#[tauri::command]
+pub fn validate_routing_preset(
+    current_config: Config,
+    content: String,
+) -> Result<ValidationResult, String> {
+    Ok(ImportService::validate_preset(&content, &current_config))
+}
+
+/// 导入路由预设
+///
+/// # Arguments
+/// * `current_config` - 当前配置
+/// * `content` - 预设内容（JSON）
+/// * `strategy` - 冲突处理方式（rename / overwrite / skip）
+#[tauri::command]
+pub fn import_routing_preset(
+    current_config: Config,
+    content: String,
+    strategy: PresetConflictStrategy,
+) -> Result<ImportResult, String> {
+    let preset = RoutingPreset::from_json(&content).map_err(|e| e.to_string())?;
+    let result = ImportService::import_preset(&preset, &current_config, strategy);
+
+    Ok(ImportResult {
+        success: result.success,
+        config: result.config,
+        warnings: result.warnings,
+        lint: result.lint,
     })
 }
 
@@ -0,0 +1,189 @@
+//! 连接诊断命令
+//!
+//! 按当前配置与凭证池收集待检查的上游与 OAuth 凭证，运行连接诊断并导出报告。
+
+use std::collections::HashSet;
+
+use crate::app::types::AppState;
+use crate::commands::provider_pool_cmd::ProviderPoolServiceState;
+use crate::database::DbConnection;
+use lime_core::database::pool_storage::{lock_pool, pool_storage};
+use lime_core::models::provider_pool_model::{
+    get_oauth_creds_path, CredentialData, ProviderCredential,
+};
+use lime_providers::providers::endpoints;
+use lime_services::connection_doctor_service::{
+    self, DiagnosticReport, DoctorOptions, TokenProbe, UpstreamTarget,
+};
+use tauri::State;
+
+/// 凭证对应的上游地址（凭证自带 base_url 优先）
+fn upstream_for(credential: &ProviderCredential) -> UpstreamTarget {
+    let (provider, url) = match &credential.credential {
+        CredentialData::KiroOAuth { .. } => (
+            endpoints::KIRO,
+            endpoints::base_url(
+                endpoints::KIRO,
+                "https://codewhisperer.{region}.amazonaws.com",
+            )
+            .replace("{region}", "us-east-1"),
+        ),
+        CredentialData::GeminiOAuth { .. } => (
+            endpoints::GEMINI,
+            endpoints::base_url(endpoints::GEMINI, "https://cloudcode-pa.googleapis.com"),
+        ),
+        CredentialData::AntigravityOAuth { .. } => (
+            endpoints::ANTIGRAVITY,
+            endpoints::base_url(
+                endpoints::ANTIGRAVITY,
+                "https://cloudcode-pa.googleapis.com",
+            ),
+        ),
+        CredentialData::CodexOAuth { api_base_url, .. } => (
+            endpoints::CODEX,
+            api_base_url.clone().unwrap_or_else(|| {
+                endpoints::base_url(endpoints::CODEX, "https://chatgpt.com/backend-api/codex")
+            }),
+        ),
+        CredentialData::OpenAIKey { base_url, .. } => (
+            endpoints::OPENAI,
+            base_url.clone().unwrap_or_else(|| {
+                endpoints::base_url(endpoints::OPENAI, "https://api.openai.com")
+            }),
+        ),
+        CredentialData::ClaudeKey { base_url, .. }
+        | CredentialData::AnthropicKey { base_url, .. } => (
+            endpoints::CLAUDE,
+            base_url.clone().unwrap_or_else(|| {
+                endpoints::base_url(endpoints::CLAUDE, "https://api.anthropic.com")
+            }),
+        ),
+        CredentialData::ClaudeOAuth { .. } => (
+            endpoints::CLAUDE,
+            endpoints::base_url(endpoints::CLAUDE, "https://api.anthropic.com"),
+        ),
+        CredentialData::VertexKey { base_url, .. } => (
+            endpoints::VERTEX,
+            base_url.clone().unwrap_or_else(|| {
+                endpoints::base_url(
+                    endpoints::VERTEX,
+                    "https://generativelanguage.googleapis.com",
+                )
+            }),
+        ),
+        CredentialData::GeminiApiKey { base_url, .. } => (
+            endpoints::GEMINI_API_KEY,
+            base_url.clone().unwrap_or_else(|| {
+                endpoints::base_url(
+                    endpoints::GEMINI_API_KEY,
+                    "https://generativelanguage.googleapis.com",
+                )
+            }),
+        ),
+    };
+    UpstreamTarget {
+        provider: provider.to_string(),
+        url,
+    }
+}
+
+/// 运行连接诊断
+#[tauri::command]
+pub async fn run_connection_doctor(
+    state: State<'_, AppState>,
+    db: State<'_, DbConnection>,
+    pool_service: State<'_, ProviderPoolServiceState>,
+) -> Result<DiagnosticReport, String> {
+    let (listen_host, listen_port, running_port, proxy_url) = {
+        let s = state.read().await;
+        (
+            s.config.server.host.clone(),
+            s.config.server.port,
+            if s.running { s.running_port } else { None },
+            s.config.proxy_url.clone(),
+        )
+    };
+
+    let credentials: Vec<ProviderCredential> = {
+        let conn = lock_pool(&db)?;
+        pool_storage().get_all(&conn)?
+    }
+    .into_iter()
+    .filter(|cred| !cred.is_disabled)
+    .collect();
+
+    // 同一主机只检查一次
+    let mut seen_hosts = HashSet::new();
+    let upstreams = credentials
+        .iter()
+        .map(upstream_for)
+        .filter(|upstream| {
+            let host = url::Url::parse(&upstream.url)
+                .ok()
+                .and_then(|u| u.host_str().map(str::to_string))
+                .unwrap_or_else(|| upstream.url.clone());
+            seen_hosts.insert(host)
+        })
+        .collect();
+
+    let tokens = credentials
+        .iter()
+        .filter(|cred| get_oauth_creds_path(&cred.credential).is_some())
+        .filter_map(|cred| {
+            let name = cred.name.clone().unwrap_or_else(|| {
+                format!(
+                    "{} {}",
+                    cred.provider_type,
+                    &cred.uuid[..8.min(cred.uuid.len())]
+                )
+            });
+            match pool_service.0.get_credential_oauth_status(&db, &cred.uuid) {
+                Ok(status) => Some(TokenProbe {
+                    name,
+                    has_access_token: status.has_access_token,
+                    has_refresh_token: status.has_refresh_token,
+                    is_token_valid: status.is_token_valid,
+                    expiry_info: status.expiry_info,
+                }),
+                Err(e) => {
+                    tracing::warn!("[DOCTOR] 读取凭证 {} 的 Token 状态失败: {}", name, e);
+                    None
+                }
+            }
+        })
+        .collect();
+
+    let report = connection_doctor_service::run_doctor(DoctorOptions {
+        listen_host,
+        listen_port,
+        running_port,
+        proxy_url,
+        upstreams,
+        tokens,
+    })
+    .await;
+    tracing::info!(
+        "[DOCTOR] 连接诊断完成: {} 通过 / {} 警告 / {} 失败",
+        report.summary.pass,
+        report.summary.warn,
+        report.summary.fail
+    );
+    Ok(report)
+}
+
+/// 导出诊断报告：`.md` 结尾导出 Markdown，其余导出 JSON
+#[tauri::command]
+pub async fn export_connection_doctor_report(
+    report: DiagnosticReport,
+    path: String,
+) -> Result<String, String> {
+    let content = if path.to_ascii_lowercase().ends_with(".md") {
+        report.to_markdown()
+    } else {
+        serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?
+    };
+    tokio::fs::write(&path, content)
+        .await
+        .map_err(|e| format!("写入诊断报告失败: {e}"))?;
+    Ok(path)
+}
@@ -0,0 +1,31 @@
+//! 协议转换黄金用例命令
+
+use std::path::PathBuf;
+
+use lime_core::app_paths;
+use lime_providers::converter::golden::{self, GoldenReport};
+
+/// 用户自定义用例的默认目录
+const CUSTOM_FIXTURES_DIR: &str = "converter-fixtures";
+
+/// 运行协议转换黄金用例
+///
+/// 始终运行内置用例；`fixtures_dir` 为空时额外加载数据目录下
+/// `converter-fixtures/` 中的用例（目录存在时）。
+#[tauri::command]
+pub async fn run_converter_golden_tests(
+    fixtures_dir: Option<String>,
+) -> Result<GoldenReport, String> {
+    let mut cases = golden::builtin_cases()?;
+
+    let custom_dir = match fixtures_dir.filter(|dir| !dir.trim().is_empty()) {
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => Some(app_paths::best_effort_runtime_subdir(CUSTOM_FIXTURES_DIR))
+            .filter(|dir| dir.is_dir()),
+    };
+    if let Some(dir) = custom_dir {
+        cases.extend(golden::load_cases_from_dir(&dir)?);
+    }
+
+    Ok(golden::run_cases(&cases))
+}
@@ -0,0 +1,151 @@
+//! 数据库维护命令
+//!
+//! 手动触发 VACUUM / 完整性检查 / WAL checkpoint，并查询最近一次维护结果；
+//! 手动执行数据保留清理；
+//! 以及手动云备份、列出远端备份与从备份恢复。
+
+use crate::app::types::{AppState, LogState};
+use crate::app::TokenCacheServiceState;
+use crate::commands::provider_pool_cmd::ProviderPoolServiceState;
+use crate::config::{ConfigAuditSource, GlobalConfigManagerState};
+use crate::database::{lock_db, maintenance::DbMaintenanceReport, schema_migrations, DbConnection};
+use lime_core::retention::RetentionReport;
+use lime_services::cloud_backup_service::{self, CloudBackupReport};
+use serde::Serialize;
+use tauri::State;
+
+/// 数据库维护状态
+#[derive(Debug, Clone, Serialize)]
+pub struct DbMaintenanceStatus {
+    /// 当前数据库版本（`PRAGMA user_version`）
+    pub schema_version: i64,
+    pub last_report: Option<DbMaintenanceReport>,
+}
+
+/// 立即执行一次数据库维护
+#[tauri::command]
+pub async fn run_db_maintenance(
+    db: State<'_, DbConnection>,
+    vacuum: Option<bool>,
+) -> Result<DbMaintenanceReport, String> {
+    crate::app::db_maintenance::run_maintenance_once(db.inner().clone(), vacuum.unwrap_or(true))
+        .await
+}
+
+/// 获取数据库维护状态
+#[tauri::command]
+pub async fn get_db_maintenance_status(
+    db: State<'_, DbConnection>,
+) -> Result<DbMaintenanceStatus, String> {
+    let conn = lock_db(&db)?;
+    Ok(DbMaintenanceStatus {
+        schema_version: schema_migrations::read_schema_version(&conn)?,
+        last_report: crate::database::maintenance::last_maintenance_report(&conn),
+    })
+}
+
+/// 立即按 `server.retention` 执行一次数据保留清理
+#[tauri::command]
+pub async fn run_retention_now(
+    db: State<'_, DbConnection>,
+    state: State<'_, AppState>,
+) -> Result<RetentionReport, String> {
+    let settings = state.read().await.config.server.retention.clone();
+    crate::app::retention::run_retention_once(db.inner().clone(), settings).await
+}
+
+/// 远端备份条目
+#[derive(Debug, Clone, Serialize)]
+pub struct CloudBackupEntry {
+    pub name: String,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// 立即执行一次云备份
+#[tauri::command]
+pub async fn run_cloud_backup_now(
+    db: State<'_, DbConnection>,
+    state: State<'_, AppState>,
+) -> Result<CloudBackupReport, String> {
+    crate::app::cloud_backup::run_backup_once(db.inner(), state.inner()).await
+}
+
+/// 列出远端备份（按时间从新到旧）
+#[tauri::command]
+pub async fn list_cloud_backups(
+    state: State<'_, AppState>,
+) -> Result<Vec<CloudBackupEntry>, String> {
+    let settings = state.read().await.config.cloud_backup.clone();
+    let storage = cloud_backup_service::storage_for(&settings)?;
+    Ok(cloud_backup_service::list_backups(storage.as_ref())
+        .await?
+        .into_iter()
+        .map(|name| CloudBackupEntry {
+            created_at: cloud_backup_service::parse_backup_time(&name),
+            name,
+        })
+        .collect())
+}
+
+/// 从远端备份恢复配置与数据库
+///
+/// 保留当前的云备份设置（目标与口令），避免恢复旧备份后无法再访问远端。
+/// 恢复数据库期间停止服务器，完成后重新启动，使凭证池与各类内存缓存按恢复后的数据重建。
+#[tauri::command]
+pub async fn restore_cloud_backup(
+    db: State<'_, DbConnection>,
+    state: State<'_, AppState>,
+    logs: State<'_, LogState>,
+    pool_service: State<'_, ProviderPoolServiceState>,
+    token_cache: State<'_, TokenCacheServiceState>,
+    config_manager: State<'_, GlobalConfigManagerState>,
+    name: String,
+) -> Result<(), String> {
+    let current = state.read().await.config.clone();
+    let restored = cloud_backup_service::fetch_backup(&current.cloud_backup, &name).await?;
+
+    let mut config = restored.config;
+    config.cloud_backup = current.cloud_backup;
+
+    let mut s = state.write().await;
+    let was_running = s.running;
+    if was_running {
+        s.stop().await;
+    }
+    let restore_result = match restored.database {
+        Some(snapshot) => {
+            cloud_backup_service::restore_database(db.inner().clone(), snapshot, restored.field_key)
+                .await
+        }
+        None => Ok(()),
+    };
+    if restore_result.is_ok() {
+        s.config = config.clone();
+    }
+    // 恢复失败时也按当前数据重新启动，避免服务停留在关闭状态
+    if was_running {
+        s.start(
+            logs.inner().clone(),
+            pool_service.0.clone(),
+            token_cache.0.clone(),
+            Some(db.inner().clone()),
+        )
+        .await
+        .map_err(|e| format!("恢复后重新启动服务器失败: {e}"))?;
+    }
+    drop(s);
+    restore_result?;
+
+    config_manager
+        .0
+        .save_config_from(&config, ConfigAuditSource::Import)
+        .await?;
+    crate::services::environment_service::apply_configured_environment(&config).await;
+    lime_providers::providers::endpoints::configure(&config.providers);
+    tracing::info!(
+        "[CLOUD_BACKUP] 已从备份 {} 恢复（备份时间 {}）",
+        name,
+        restored.created_at
+    );
+    Ok(())
+}
@@ -0,0 +1,15 @@
+//! 多区域端点延迟命令
+
+use lime_providers::providers::endpoint_latency::{self, ProviderEndpointReport};
+
+/// 获取各 Provider 的端点探测结果与当前优先端点
+#[tauri::command]
+pub async fn get_endpoint_latency() -> Result<Vec<ProviderEndpointReport>, String> {
+    Ok(endpoint_latency::snapshot())
+}
+
+/// 立即探测所有区域端点
+#[tauri::command]
+pub async fn probe_endpoint_latency() -> Result<Vec<ProviderEndpointReport>, String> {
+    Ok(crate::app::endpoint_probe::probe_endpoints_once().await)
+}
@@ -0,0 +1,45 @@
+//! 上游 HTTP 抓包命令
+
+use std::time::Duration;
+
+use lime_providers::har_capture::{self, CaptureStatus};
+
+/// 默认抓包窗口（秒）
+const DEFAULT_WINDOW_SECS: u64 = 300;
+
+/// 开启抓包窗口，会清空上一次的记录
+#[tauri::command]
+pub async fn start_har_capture(duration_secs: Option<u64>) -> Result<CaptureStatus, String> {
+    let secs = duration_secs.unwrap_or(DEFAULT_WINDOW_SECS);
+    Ok(har_capture::start(Duration::from_secs(secs)))
+}
+
+/// 提前结束抓包窗口
+#[tauri::command]
+pub async fn stop_har_capture() -> Result<CaptureStatus, String> {
+    Ok(har_capture::stop())
+}
+
+/// 获取抓包状态
+#[tauri::command]
+pub async fn get_har_capture_status() -> Result<CaptureStatus, String> {
+    Ok(har_capture::status())
+}
+
+/// 清空已记录的内容
+#[tauri::command]
+pub async fn clear_har_capture() -> Result<CaptureStatus, String> {
+    har_capture::clear();
+    Ok(har_capture::status())
+}
+
+/// 将已记录的内容导出为 HAR 文件
+#[tauri::command]
+pub async fn export_har_capture(path: String) -> Result<String, String> {
+    let content =
+        serde_json::to_string_pretty(&har_capture::export()).map_err(|e| e.to_string())?;
+    tokio::fs::write(&path, content)
+        .await
+        .map_err(|e| format!("写入 HAR 文件失败: {e}"))?;
+    Ok(path)
+}
@@ -0,0 +1,146 @@
+//! 局域网扫码配对命令
+//!
+//! 为移动端签发受限 API Key，并生成包含网关地址与 Key 的二维码，
+//! 移动端扫码即可连接桌面网关，无需手动输入。
+
+use crate::commands::network_cmd::get_accessible_url;
+use crate::AppState;
+use lime_server::auth::scoped_keys::{IssuedScopedKey, ScopedKeyOptions, ScopedKeyRecord};
+use serde::Serialize;
+
+/// 默认设备名称
+const DEFAULT_DEVICE_LABEL: &str = "移动设备";
+
+/// 配对信息
+#[derive(Debug, Clone, Serialize)]
+pub struct LanPairingInfo {
+    /// OpenAI 兼容的 Base URL（含 `/v1`）
+    pub base_url: String,
+    /// 受限 API Key 明文（仅返回一次）
+    pub api_key: String,
+    pub key_id: String,
+    pub expires_at: Option<String>,
+    /// 二维码内容
+    pub pairing_uri: String,
+    /// 二维码 SVG
+    pub qr_svg: String,
+}
+
+/// 生成配对 URI（`lime://pair?base_url=...&api_key=...`）
+fn build_pairing_uri(base_url: &str, api_key: &str) -> String {
+    format!(
+        "lime://pair?base_url={}&api_key={}",
+        urlencoding::encode(base_url),
+        urlencoding::encode(api_key)
+    )
+}
+
+/// 渲染二维码 SVG
+pub(crate) fn render_qr_svg(content: &str) -> Result<String, String> {
+    use qrcode::render::svg;
+    use qrcode::QrCode;
+
+    let code = QrCode::new(content.as_bytes()).map_err(|e| format!("生成二维码失败: {e}"))?;
+    Ok(code
+        .render::<svg::Color>()
+        .min_dimensions(240, 240)
+        .quiet_zone(true)
+        .build())
+}
+
+/// 创建局域网配对（签发受限 Key 并生成二维码）
+#[tauri::command]
+pub async fn create_lan_pairing(
+    state: tauri::State<'_, AppState>,
+    label: Option<String>,
+) -> Result<LanPairingInfo, String> {
+    let s = state.read().await;
+    if !s.running {
+        return Err("服务器未运行".to_string());
+    }
+
+    let status = s.status();
+    if matches!(status.host.as_str(), "127.0.0.1" | "localhost" | "::1") {
+        return Err(
+            "当前监听地址仅限本机访问，请将监听地址设为 0.0.0.0 或局域网 IP 后重试".to_string(),
+        );
+    }
+
+    let base_url = format!("{}/v1", get_accessible_url(&status.host, status.port));
+    let label = label
+        .as_deref()
+        .map(str::trim)
+        .filter(|label| !label.is_empty())
+        .unwrap_or(DEFAULT_DEVICE_LABEL);
+    let issued = s
+        .scoped_keys
+        .issue(label, s.config.server.lan_discovery.pairing_key_ttl_hours)?;
+
+    let pairing_uri = build_pairing_uri(&base_url, &issued.api_key);
+    let qr_svg = render_qr_svg(&pairing_uri)?;
+    tracing::info!(
+        "[LAN] 已为 {} 签发受限 API Key: {}",
+        label,
+        issued.record.key_prefix
+    );
+
+    Ok(LanPairingInfo {
+        base_url,
+        api_key: issued.api_key,
+        key_id: issued.record.id,
+        expires_at: issued.record.expires_at.map(|t| t.to_rfc3339()),
+        pairing_uri,
+        qr_svg,
+    })
+}
+
+/// 签发临时受限 API Key（限定模型、有效期与请求次数，供协作者或演示应用使用）
+#[tauri::command]
+pub async fn create_scoped_api_key(
+    state: tauri::State<'_, AppState>,
+    options: ScopedKeyOptions,
+) -> Result<IssuedScopedKey, String> {
+    let issued = state.read().await.scoped_keys.issue_with(&options)?;
+    tracing::info!(
+        "[SCOPED_KEY] 已签发临时 Key: label={} prefix={}",
+        issued.record.label,
+        issued.record.key_prefix
+    );
+    Ok(issued)
+}
+
+/// 列出已签发的受限 API Key
+#[tauri::command]
+pub async fn list_scoped_api_keys(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<ScopedKeyRecord>, String> {
+    Ok(state.read().await.scoped_keys.list())
+}
+
+/// 吊销受限 API Key
+#[tauri::command]
+pub async fn revoke_scoped_api_key(
+    state: tauri::State<'_, AppState>,
+    id: String,
+) -> Result<bool, String> {
+    state.read().await.scoped_keys.revoke(&id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_encode_pairing_uri() {
+        assert_eq!(
+            build_pairing_uri("http://192.168.1.2:8999/v1", "pc_m_abc"),
+            "lime://pair?base_url=http%3A%2F%2F192.168.1.2%3A8999%2Fv1&api_key=pc_m_abc"
+        );
+    }
+
+    #[test]
+    fn should_render_qr_svg() {
+        let svg = render_qr_svg("lime://pair?base_url=x&api_key=y").expect("渲染应成功");
+        assert!(svg.contains("<svg"));
+    }
+}
@@ -0,0 +1,30 @@
+//! 维护模式命令
+
+use lime_server::middleware::maintenance::MaintenanceStatus;
+
+use crate::config::save_config;
+use crate::AppState;
+
+/// 获取维护模式状态
+#[tauri::command]
+pub async fn get_maintenance_mode(
+    state: tauri::State<'_, AppState>,
+) -> Result<MaintenanceStatus, String> {
+    let s = state.read().await;
+    Ok(s.maintenance.status())
+}
+
+/// 开启或关闭维护模式，立即生效并写回配置
+#[tauri::command]
+pub async fn set_maintenance_mode(
+    state: tauri::State<'_, AppState>,
+    enabled: bool,
+    message: Option<String>,
+) -> Result<MaintenanceStatus, String> {
+    let mut s = state.write().await;
+    let status = s.maintenance.set(enabled, message.as_deref());
+    s.config.server.maintenance_mode.enabled = status.enabled;
+    s.config.server.maintenance_mode.message = status.message.clone();
+    save_config(&s.config).map_err(|e| e.to_string())?;
+    Ok(status)
+}
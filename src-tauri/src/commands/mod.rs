@@ -1,33 +1,43 @@
 pub mod a2ui_form_cmd;
 pub mod agent_cmd;
+pub mod anonymous_stats_cmd;
 pub mod api_key_provider_cmd;
 pub mod asr_cmd;
 pub mod aster_agent_cmd;
 pub mod auto_fix_cmd;
 pub mod automation_cmd;
+pub mod bench_cmd;
 pub mod browser_environment_cmd;
 pub mod browser_profile_cmd;
 pub mod browser_runtime_cmd;
 pub mod channels_cmd;
 pub mod claw_solution_cmd;
+pub mod cli_preset_cmd;
 pub mod config_cmd;
 pub mod connect_cmd;
 pub mod connection_cmd;
+pub mod connection_doctor_cmd;
 pub mod content_cmd;
 pub mod content_workflow_cmd;
 pub mod context_memory;
+pub mod converter_golden_cmd;
+pub mod db_maintenance_cmd;
 pub mod document_import_cmd;
 pub mod ecommerce_review_reply_cmd;
+pub mod endpoint_latency_cmd;
 pub mod execution_run_cmd;
 pub mod external_tools_cmd;
 pub mod file_upload_cmd;
 pub mod gateway_channel_cmd;
 pub mod gateway_tunnel_cmd;
+pub mod har_capture_cmd;
 pub mod image_search_cmd;
 pub mod image_upload_cmd;
 pub mod injection_cmd;
 pub mod kiro_local;
+pub mod lan_pairing_cmd;
 pub mod machine_id_cmd;
+pub mod maintenance_cmd;
 pub mod material_cmd;
 pub mod mcp_cmd;
 pub mod memory_cmd;
@@ -50,11 +60,14 @@ pub mod plugin_rpc_cmd;
 pub mod poster_material_cmd;
 pub mod prompt_cmd;
 pub mod provider_pool_cmd;
+pub mod provider_test_cmd;
 pub mod resilience_cmd;
 pub mod route_cmd;
+pub mod scenario_cmd;
 pub mod screenshot_cmd;
 pub mod security_perf_cmd;
 pub mod session_files_cmd;
+pub mod setup_wizard_cmd;
 pub mod site_capability_cmd;
 pub mod skill_cmd;
 pub mod skill_error;
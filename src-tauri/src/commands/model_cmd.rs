@@ -1,6 +1,6 @@
 //! 模型管理相关命令
 
-use crate::database::dao::provider_pool::ProviderPoolDao;
+use crate::database::pool_storage::{lock_pool, pool_storage};
 use crate::database::DbConnection;
 use lime_services::model_service::ModelService;
 use std::collections::HashMap;
@@ -37,9 +37,9 @@ pub async fn refresh_credential_models(
 
     // 从数据库获取凭证信息
     let credential = {
-        let conn = db.lock().map_err(|e| e.to_string())?;
-        ProviderPoolDao::get_by_uuid(&conn, &credential_uuid)
-            .map_err(|e| e.to_string())?
+        let conn = lock_pool(&db)?;
+        pool_storage()
+            .get_by_uuid(&conn, &credential_uuid)?
             .ok_or_else(|| format!("凭证不存在: {credential_uuid}"))?
     };
 
@@ -101,8 +101,8 @@ pub async fn refresh_all_credential_models(
 
     // 获取所有凭证
     let credentials = {
-        let conn = db.lock().map_err(|e| e.to_string())?;
-        ProviderPoolDao::get_all(&conn).map_err(|e| e.to_string())?
+        let conn = lock_pool(&db)?;
+        pool_storage().get_all(&conn)?
     };
 
     let mut results = HashMap::new();
@@ -2,7 +2,7 @@
 //!
 //! 提供前端访问模型编排器的接口。
 
-use crate::database::dao::provider_pool::ProviderPoolDao;
+use crate::database::pool_storage::{lock_pool, pool_storage};
 use crate::database::DbConnection;
 use lime_core::orchestrator::{
     get_global_orchestrator, init_global_orchestrator, AvailableModel, CredentialInfo,
@@ -52,8 +52,10 @@ pub async fn init_orchestrator(
 
     // 从数据库加载凭证并同步到 orchestrator
     let credentials = {
-        let conn = db.lock().map_err(|e| format!("获取数据库连接失败: {e}"))?;
-        ProviderPoolDao::get_all(&conn).map_err(|e| format!("获取凭证列表失败: {e}"))?
+        let conn = lock_pool(&db).map_err(|e| format!("获取数据库连接失败: {e}"))?;
+        pool_storage()
+            .get_all(&conn)
+            .map_err(|e| format!("获取凭证列表失败: {e}"))?
     };
 
     // 转换凭证格式
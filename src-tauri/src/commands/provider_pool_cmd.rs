@@ -2,14 +2,19 @@
 
 #![allow(dead_code)]
 
-use crate::database::dao::provider_pool::ProviderPoolDao;
+use crate::commands::lan_pairing_cmd::render_qr_svg;
+use crate::database::pool_storage::{lock_pool, pool_storage};
 use crate::database::DbConnection;
 use crate::models::provider_pool_model::{
-    AddCredentialRequest, CredentialData, CredentialDisplay, HealthCheckResult, OAuthStatus,
-    PoolProviderType, ProviderCredential, ProviderPoolOverview, UpdateCredentialRequest,
+    AddCredentialRequest, CloneCredentialRequest, CredentialData, CredentialDisplay,
+    HealthCheckResult, OAuthStatus, PoolProviderType, ProviderCredential, ProviderPoolOverview,
+    UpdateCredentialRequest,
 };
 use chrono::Utc;
+use lime_core::config::{CredentialPairingBundle, PairedCredential};
+use lime_core::credential::HealthProbeOutcome;
 use lime_credential::CredentialSyncService;
+use lime_services::pool_insights_service::PoolInsights;
 use lime_services::provider_pool_service::ProviderPoolService;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -296,6 +301,235 @@ pub fn add_provider_pool_credential(
     Ok(credential)
 }
 
+/// OAuth 凭证文件在凭证目录中的前缀，API Key 凭证返回 None
+fn oauth_file_prefix(credential: &CredentialData) -> Option<&'static str> {
+    match credential {
+        CredentialData::KiroOAuth { .. } => Some("kiro"),
+        CredentialData::GeminiOAuth { .. } => Some("gemini"),
+        CredentialData::AntigravityOAuth { .. } => Some("antigravity"),
+        CredentialData::CodexOAuth { .. } => Some("codex"),
+        CredentialData::ClaudeOAuth { .. } => Some("claude_oauth"),
+        _ => None,
+    }
+}
+
+/// 复制凭证
+///
+/// 以已有凭证为模板创建新凭证：沿用模型列表、健康检查、代理与 Provider 设置，
+/// 只替换账号信息（OAuth 凭证文件或 API Key），并同步到 YAML 配置文件。
+#[tauri::command]
+pub fn clone_provider_pool_credential(
+    db: State<'_, DbConnection>,
+    pool_service: State<'_, ProviderPoolServiceState>,
+    sync_service: State<'_, CredentialSyncServiceState>,
+    uuid: String,
+    request: CloneCredentialRequest,
+) -> Result<ProviderCredential, String> {
+    let source = pool_service
+        .0
+        .get_by_uuid(&db, &uuid)?
+        .ok_or_else(|| format!("凭证不存在: {uuid}"))?;
+
+    // OAuth 凭证文件复制到应用凭证目录，与新增凭证一致
+    let creds_file_path = match (
+        oauth_file_prefix(&source.credential),
+        request.creds_file_path,
+    ) {
+        (Some(prefix), Some(path)) if !path.trim().is_empty() => {
+            Some(copy_and_rename_credential_file(&path, prefix)?)
+        }
+        (_, path) => path,
+    };
+    let stored_path = creds_file_path.clone();
+    let credential =
+        match source
+            .credential
+            .with_account(creds_file_path, request.api_key, request.project_id)
+        {
+            Ok(credential) => credential,
+            Err(e) => {
+                if let Some(path) = stored_path.as_deref() {
+                    cleanup_credential_file(path)?;
+                }
+                return Err(e);
+            }
+        };
+
+    let credential = pool_service
+        .0
+        .clone_credential(&db, &source, credential, request.name)?;
+
+    // 同步到 YAML 配置（如果同步服务可用）
+    if let Some(ref sync) = sync_service.0 {
+        if let Err(e) = sync.add_credential(&credential) {
+            // 记录警告但不中断操作
+            tracing::warn!("同步凭证到 YAML 失败: {}", e);
+        }
+    }
+
+    Ok(credential)
+}
+
+/// OAuth 凭证文件路径
+fn oauth_creds_file_path(credential: &CredentialData) -> Option<&str> {
+    match credential {
+        CredentialData::KiroOAuth { creds_file_path }
+        | CredentialData::GeminiOAuth {
+            creds_file_path, ..
+        }
+        | CredentialData::AntigravityOAuth {
+            creds_file_path, ..
+        }
+        | CredentialData::CodexOAuth {
+            creds_file_path, ..
+        }
+        | CredentialData::ClaudeOAuth { creds_file_path } => Some(creds_file_path),
+        _ => None,
+    }
+}
+
+/// 将配对串中的 OAuth 凭证文件内容写入应用凭证目录
+fn write_paired_credential_file(content: &str, provider_type: &str) -> Result<String, String> {
+    serde_json::from_str::<serde_json::Value>(content)
+        .map_err(|e| format!("配对串中的凭证文件无效: {e}"))?;
+    let uuid = Uuid::new_v4().to_string();
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let target_path = get_credentials_dir()?.join(format!(
+        "{}_{}_{}_{}.json",
+        provider_type,
+        &uuid[..8],
+        timestamp,
+        provider_type
+    ));
+    fs::write(&target_path, content).map_err(|e| format!("写入凭证文件失败: {e}"))?;
+    Ok(target_path.to_string_lossy().to_string())
+}
+
+/// 凭证配对串导出结果
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CredentialPairingExport {
+    pub pairing_string: String,
+    /// 二维码 SVG（内容超出二维码容量时为空，只能复制配对串）
+    pub qr_svg: Option<String>,
+    pub credential_count: usize,
+}
+
+/// 导出凭证配对串
+///
+/// 将选中的凭证（含 OAuth 凭证文件内容）用口令加密为配对串与二维码，
+/// 在另一台 Lime 上通过 `import_credential_pairing` 导入。
+#[tauri::command]
+pub fn export_credential_pairing(
+    db: State<'_, DbConnection>,
+    pool_service: State<'_, ProviderPoolServiceState>,
+    uuids: Vec<String>,
+    passphrase: String,
+) -> Result<CredentialPairingExport, String> {
+    let mut credentials = Vec::with_capacity(uuids.len());
+    for uuid in &uuids {
+        let cred = pool_service
+            .0
+            .get_by_uuid(&db, uuid)?
+            .ok_or_else(|| format!("凭证不存在: {uuid}"))?;
+        let creds_file = match oauth_creds_file_path(&cred.credential) {
+            Some(path) => Some(
+                fs::read_to_string(expand_tilde(path))
+                    .map_err(|e| format!("读取凭证文件失败 ({path}): {e}"))?,
+            ),
+            None => None,
+        };
+        credentials.push(PairedCredential::from_credential(&cred, creds_file));
+    }
+
+    let pairing_string = CredentialPairingBundle::new(credentials).seal(&passphrase)?;
+    let qr_svg = match render_qr_svg(&pairing_string) {
+        Ok(svg) => Some(svg),
+        Err(e) => {
+            tracing::info!("配对串过长，不生成二维码: {}", e);
+            None
+        }
+    };
+
+    Ok(CredentialPairingExport {
+        pairing_string,
+        qr_svg,
+        credential_count: uuids.len(),
+    })
+}
+
+/// 从配对串导入凭证
+///
+/// OAuth 凭证文件写入本机凭证目录，导入的凭证来源标记为「导入」，并同步到 YAML 配置文件。
+#[tauri::command]
+pub fn import_credential_pairing(
+    db: State<'_, DbConnection>,
+    pool_service: State<'_, ProviderPoolServiceState>,
+    sync_service: State<'_, CredentialSyncServiceState>,
+    pairing_string: String,
+    passphrase: String,
+) -> Result<Vec<ProviderCredential>, String> {
+    let bundle = CredentialPairingBundle::open(&pairing_string, &passphrase)?;
+
+    let mut written_files = Vec::new();
+    let mut credentials = Vec::with_capacity(bundle.credentials.len());
+    for paired in bundle.credentials {
+        let data = match (
+            oauth_file_prefix(&paired.credential),
+            paired.creds_file.as_deref(),
+        ) {
+            (Some(prefix), Some(content)) => {
+                let path = write_paired_credential_file(content, prefix);
+                let data = path.and_then(|path| {
+                    written_files.push(path.clone());
+                    paired.credential.with_account(Some(path), None, None)
+                });
+                match data {
+                    Ok(data) => data,
+                    Err(e) => {
+                        for path in &written_files {
+                            cleanup_credential_file(path)?;
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+            (Some(_), None) => {
+                for path in &written_files {
+                    cleanup_credential_file(path)?;
+                }
+                return Err(format!(
+                    "配对串中的 OAuth 凭证缺少凭证文件: {}",
+                    paired.name.as_deref().unwrap_or("未命名")
+                ));
+            }
+            (None, _) => paired.credential.clone(),
+        };
+        credentials.push(paired.into_credential(data));
+    }
+
+    if let Err(e) = pool_service.0.import_credentials(&db, &credentials) {
+        for path in &written_files {
+            cleanup_credential_file(path)?;
+        }
+        return Err(e);
+    }
+
+    // 同步到 YAML 配置（如果同步服务可用）
+    if let Some(ref sync) = sync_service.0 {
+        for credential in &credentials {
+            if let Err(e) = sync.add_credential(credential) {
+                // 记录警告但不中断操作
+                tracing::warn!("同步凭证到 YAML 失败: {}", e);
+            }
+        }
+    }
+
+    Ok(credentials)
+}
+
 /// 更新凭证
 /// 更新凭证
 ///
@@ -319,9 +553,9 @@ pub fn update_provider_pool_credential(
     // 如果需要重新上传文件，先处理文件上传
     let credential = if let Some(new_file_path) = request.new_creds_file_path {
         // 获取当前凭证以确定类型
-        let conn = db.lock().map_err(|e| e.to_string())?;
-        let current_credential = ProviderPoolDao::get_by_uuid(&conn, &uuid)
-            .map_err(|e| e.to_string())?
+        let conn = lock_pool(&db)?;
+        let current_credential = pool_storage()
+            .get_by_uuid(&conn, &uuid)?
             .ok_or_else(|| format!("凭证不存在: {uuid}"))?;
 
         // 根据凭证类型复制新文件
@@ -405,14 +639,14 @@ pub fn update_provider_pool_credential(
         updated_cred.updated_at = Utc::now();
 
         // 保存到数据库
-        ProviderPoolDao::update(&conn, &updated_cred).map_err(|e| e.to_string())?;
+        pool_storage().update(&conn, &updated_cred)?;
 
         updated_cred
     } else if request.new_base_url.is_some() || request.new_api_key.is_some() {
         // 更新 API Key 凭证的 api_key 和/或 base_url
-        let conn = db.lock().map_err(|e| e.to_string())?;
-        let mut current_credential = ProviderPoolDao::get_by_uuid(&conn, &uuid)
-            .map_err(|e| e.to_string())?
+        let conn = lock_pool(&db)?;
+        let mut current_credential = pool_storage()
+            .get_by_uuid(&conn, &uuid)?
             .ok_or_else(|| format!("凭证不存在: {uuid}"))?;
 
         // 更新 api_key 和 base_url
@@ -476,7 +710,7 @@ pub fn update_provider_pool_credential(
         current_credential.updated_at = Utc::now();
 
         // 保存到数据库
-        ProviderPoolDao::update(&conn, &current_credential).map_err(|e| e.to_string())?;
+        pool_storage().update(&conn, &current_credential)?;
 
         current_credential
     } else {
@@ -504,35 +738,65 @@ pub fn update_provider_pool_credential(
     Ok(credential)
 }
 
-/// 删除凭证
 /// 删除凭证
 ///
-/// 从数据库删除凭证，并同步到 YAML 配置文件
+/// 凭证移入回收站，不再参与选择；YAML 配置与 OAuth Token 文件保留到永久删除时再清理，
+/// 保证恢复后可以直接使用。
 /// Requirements: 1.1, 1.2
 #[tauri::command]
 pub fn delete_provider_pool_credential(
+    db: State<'_, DbConnection>,
+    pool_service: State<'_, ProviderPoolServiceState>,
+    uuid: String,
+) -> Result<bool, String> {
+    pool_service.0.delete_credential(&db, &uuid)
+}
+
+/// 获取回收站中的凭证
+#[tauri::command]
+pub fn get_deleted_provider_pool_credentials(
+    db: State<'_, DbConnection>,
+    pool_service: State<'_, ProviderPoolServiceState>,
+) -> Result<Vec<CredentialDisplay>, String> {
+    Ok(pool_service
+        .0
+        .get_deleted_credentials(&db)?
+        .iter()
+        .map(CredentialDisplay::from)
+        .collect())
+}
+
+/// 从回收站恢复凭证
+#[tauri::command]
+pub fn restore_provider_pool_credential(
+    db: State<'_, DbConnection>,
+    pool_service: State<'_, ProviderPoolServiceState>,
+    uuid: String,
+) -> Result<bool, String> {
+    pool_service.0.restore_credential(&db, &uuid)
+}
+
+/// 从回收站永久删除凭证，并同步到 YAML 配置文件
+#[tauri::command]
+pub fn purge_provider_pool_credential(
     db: State<'_, DbConnection>,
     pool_service: State<'_, ProviderPoolServiceState>,
     sync_service: State<'_, CredentialSyncServiceState>,
     uuid: String,
-    provider_type: Option<String>,
 ) -> Result<bool, String> {
-    // 从数据库删除
-    let result = pool_service.0.delete_credential(&db, &uuid)?;
+    let Some(credential) = pool_service.0.purge_deleted_credential(&db, &uuid)? else {
+        return Ok(false);
+    };
 
-    // 同步到 YAML 配置（如果同步服务可用且提供了 provider_type）
+    // 同步到 YAML 配置（如果同步服务可用）
     if let Some(ref sync) = sync_service.0 {
-        if let Some(pt) = provider_type {
-            if let Ok(pool_type) = pt.parse::<PoolProviderType>() {
-                if let Err(e) = sync.remove_credential(pool_type, &uuid) {
-                    // 记录警告但不中断操作
-                    tracing::warn!("从 YAML 删除凭证失败: {}", e);
-                }
-            }
+        if let Err(e) = sync.remove_credential(credential.provider_type, &uuid) {
+            // 记录警告但不中断操作
+            tracing::warn!("从 YAML 删除凭证失败: {}", e);
         }
     }
 
-    Ok(result)
+    Ok(true)
 }
 
 /// 切换凭证启用/禁用状态
@@ -568,6 +832,26 @@ pub fn reset_provider_pool_health(
     pool_service.0.reset_health_by_type(&db, &provider_type)
 }
 
+/// 凭证池洞察：找出从未被选中、长期不健康、闲置或饱和的凭证，并给出扩缩容建议
+#[tauri::command]
+pub fn pool_insights(db: State<'_, DbConnection>) -> Result<PoolInsights, String> {
+    lime_services::pool_insights_service::pool_insights(&db)
+}
+
+/// 按需探测单个凭证（试运行），返回原始结果且不更新健康状态
+#[tauri::command]
+pub async fn probe_provider_pool_credential_health(
+    db: State<'_, DbConnection>,
+    pool_service: State<'_, ProviderPoolServiceState>,
+    uuid: String,
+    model: Option<String>,
+) -> Result<HealthProbeOutcome, String> {
+    pool_service
+        .0
+        .probe_credential_health(&db, &uuid, model)
+        .await
+}
+
 /// 执行单个凭证的健康检查
 #[tauri::command]
 pub async fn check_provider_pool_credential_health(
@@ -1678,14 +1962,14 @@ pub async fn get_kiro_credential_fingerprint(
     db: State<'_, DbConnection>,
     uuid: String,
 ) -> Result<KiroFingerprintInfo, String> {
-    use crate::database::dao::provider_pool::ProviderPoolDao;
+    use crate::database::pool_storage::{lock_pool, pool_storage};
     use crate::providers::kiro::{generate_machine_id_from_credentials, KiroProvider};
 
     // 获取凭证文件路径（在锁释放前完成）
     let creds_file_path = {
-        let conn = db.lock().map_err(|e| e.to_string())?;
-        let credential = ProviderPoolDao::get_by_uuid(&conn, &uuid)
-            .map_err(|e| e.to_string())?
+        let conn = lock_pool(&db)?;
+        let credential = pool_storage()
+            .get_by_uuid(&conn, &uuid)?
             .ok_or_else(|| format!("凭证不存在: {uuid}"))?;
 
         // 检查是否为 Kiro 凭证
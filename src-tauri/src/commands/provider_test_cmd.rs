@@ -0,0 +1,447 @@
+//! 供应商接入测试命令
+//!
+//! 新配置的供应商（或凭证）接入后，通过本地代理服务按 `X-Provider-Id` 精确路由，
+//! 依次执行一组标准测试：普通对话、流式输出、工具调用、长提示词、图片输入（模型支持时），
+//! 返回每项能力的通过情况，并把实测结果写回模型注册表的能力字段。
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::app::types::{AppState, LogState};
+use crate::commands::model_registry_cmd::ModelRegistryState;
+
+/// 单项测试超时
+const CASE_TIMEOUT_SECS: u64 = 90;
+
+/// 长提示词测试的填充句子重复次数（约 5k token）
+const LONG_PROMPT_REPEAT: usize = 300;
+
+/// 长提示词中埋入的校验码
+const LONG_PROMPT_NEEDLE: &str = "LIME-4217";
+
+/// 1x1 PNG，用于图片输入测试
+const TEST_IMAGE_PNG_BASE64: &str =
+    "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mP8z8DwHwAFBQIAX8jx0gAAAABJRU5ErkJggg==";
+
+/// 测试项
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderTestCapability {
+    Chat,
+    Streaming,
+    ToolCall,
+    LongPrompt,
+    Image,
+}
+
+impl ProviderTestCapability {
+    const ALL: [Self; 5] = [
+        Self::Chat,
+        Self::Streaming,
+        Self::ToolCall,
+        Self::LongPrompt,
+        Self::Image,
+    ];
+}
+
+/// 测试结果状态
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderTestStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// 单项测试结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderCapabilityResult {
+    pub capability: ProviderTestCapability,
+    pub status: ProviderTestStatus,
+    pub http_status: Option<u16>,
+    pub duration_ms: u64,
+    pub message: Option<String>,
+}
+
+/// 接入测试报告
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderTestReport {
+    pub provider: String,
+    pub model: String,
+    pub results: Vec<ProviderCapabilityResult>,
+    /// 是否已写回模型注册表（普通对话失败时不写回）
+    pub registry_updated: bool,
+    pub tested_at: String,
+}
+
+fn user_message(content: Value) -> Value {
+    json!([{ "role": "user", "content": content }])
+}
+
+/// 构建测试请求体（OpenAI Chat Completions 格式）
+fn build_request(capability: ProviderTestCapability, model: &str) -> Value {
+    match capability {
+        ProviderTestCapability::Chat => json!({
+            "model": model,
+            "messages": user_message(json!("Say 'OK' only.")),
+            "max_tokens": 16,
+        }),
+        ProviderTestCapability::Streaming => json!({
+            "model": model,
+            "messages": user_message(json!("Count from 1 to 5, separated by spaces.")),
+            "max_tokens": 32,
+            "stream": true,
+        }),
+        ProviderTestCapability::ToolCall => json!({
+            "model": model,
+            "messages": user_message(json!("What is the weather in Paris? Use the get_weather tool.")),
+            "max_tokens": 128,
+            "tools": [{
+                "type": "function",
+                "function": {
+                    "name": "get_weather",
+                    "description": "Get the current weather for a city",
+                    "parameters": {
+                        "type": "object",
+                        "properties": {"city": {"type": "string"}},
+                        "required": ["city"]
+                    }
+                }
+            }],
+            "tool_choice": "auto",
+        }),
+        ProviderTestCapability::LongPrompt => {
+            let filler = "The quick brown fox jumps over the lazy dog near the quiet river bank. "
+                .repeat(LONG_PROMPT_REPEAT / 2);
+            let prompt = format!(
+                "{filler}\nThe verification code is {LONG_PROMPT_NEEDLE}.\n{filler}\n\
+                 What is the verification code mentioned above? Reply with the code only."
+            );
+            json!({
+                "model": model,
+                "messages": user_message(json!(prompt)),
+                "max_tokens": 32,
+            })
+        }
+        ProviderTestCapability::Image => json!({
+            "model": model,
+            "messages": user_message(json!([
+                {"type": "text", "text": "What color is this image? Reply with one word."},
+                {
+                    "type": "image_url",
+                    "image_url": {"url": format!("data:image/png;base64,{TEST_IMAGE_PNG_BASE64}")}
+                }
+            ])),
+            "max_tokens": 16,
+        }),
+    }
+}
+
+fn message_text(body: &Value) -> String {
+    match body.pointer("/choices/0/message/content") {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect(),
+        _ => String::new(),
+    }
+}
+
+/// 按测试项检查 2xx 响应体，失败时返回原因
+fn evaluate(capability: ProviderTestCapability, body: &str) -> Result<(), String> {
+    if capability == ProviderTestCapability::Streaming {
+        let mut chunks = 0usize;
+        let mut text = String::new();
+        for data in body
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(str::trim)
+            .filter(|data| *data != "[DONE]")
+        {
+            let Ok(chunk) = serde_json::from_str::<Value>(data) else {
+                continue;
+            };
+            chunks += 1;
+            if let Some(delta) = chunk
+                .pointer("/choices/0/delta/content")
+                .and_then(Value::as_str)
+            {
+                text.push_str(delta);
+            }
+        }
+        return match (chunks, text.trim().is_empty()) {
+            (0, _) => Err("响应不是 SSE 流".to_string()),
+            (_, true) => Err("流式响应中没有文本增量".to_string()),
+            _ => Ok(()),
+        };
+    }
+
+    let json: Value =
+        serde_json::from_str(body).map_err(|e| format!("响应不是有效的 JSON: {e}"))?;
+    match capability {
+        ProviderTestCapability::ToolCall => {
+            let called = json
+                .pointer("/choices/0/message/tool_calls")
+                .and_then(Value::as_array)
+                .is_some_and(|calls| {
+                    calls.iter().any(|call| {
+                        call.pointer("/function/name").and_then(Value::as_str)
+                            == Some("get_weather")
+                    })
+                });
+            if called {
+                Ok(())
+            } else {
+                Err("响应中没有 get_weather 工具调用".to_string())
+            }
+        }
+        ProviderTestCapability::LongPrompt => {
+            let code = LONG_PROMPT_NEEDLE.trim_start_matches("LIME-");
+            if message_text(&json).contains(code) {
+                Ok(())
+            } else {
+                Err("模型未能从长提示词中找回校验码".to_string())
+            }
+        }
+        _ => {
+            if message_text(&json).trim().is_empty() {
+                Err("响应中没有文本内容".to_string())
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+fn truncate(text: &str) -> String {
+    text.chars().take(200).collect()
+}
+
+async fn run_case(
+    client: &reqwest::Client,
+    url: &str,
+    api_key: &str,
+    provider: &str,
+    model: &str,
+    capability: ProviderTestCapability,
+) -> ProviderCapabilityResult {
+    let start = Instant::now();
+    let response = client
+        .post(url)
+        .bearer_auth(api_key)
+        .header("x-provider-id", provider)
+        .json(&build_request(capability, model))
+        .send()
+        .await;
+    let (status, http_status, message) = match response {
+        Ok(response) => {
+            let http_status = response.status().as_u16();
+            let success = response.status().is_success();
+            match response.text().await {
+                Ok(body) if success => match evaluate(capability, &body) {
+                    Ok(()) => (ProviderTestStatus::Passed, Some(http_status), None),
+                    Err(reason) => (ProviderTestStatus::Failed, Some(http_status), Some(reason)),
+                },
+                Ok(body) => (
+                    ProviderTestStatus::Failed,
+                    Some(http_status),
+                    Some(format!("HTTP {http_status}: {}", truncate(&body))),
+                ),
+                Err(e) => (
+                    ProviderTestStatus::Failed,
+                    Some(http_status),
+                    Some(format!("读取响应失败: {e}")),
+                ),
+            }
+        }
+        Err(e) => (
+            ProviderTestStatus::Failed,
+            None,
+            Some(format!("请求失败: {e}")),
+        ),
+    };
+    ProviderCapabilityResult {
+        capability,
+        status,
+        http_status,
+        duration_ms: start.elapsed().as_millis() as u64,
+        message,
+    }
+}
+
+fn skipped(capability: ProviderTestCapability, reason: &str) -> ProviderCapabilityResult {
+    ProviderCapabilityResult {
+        capability,
+        status: ProviderTestStatus::Skipped,
+        http_status: None,
+        duration_ms: 0,
+        message: Some(reason.to_string()),
+    }
+}
+
+fn result_of(
+    results: &[ProviderCapabilityResult],
+    capability: ProviderTestCapability,
+) -> Option<bool> {
+    results
+        .iter()
+        .find(|r| r.capability == capability && r.status != ProviderTestStatus::Skipped)
+        .map(|r| r.status == ProviderTestStatus::Passed)
+}
+
+/// 对指定供应商执行接入测试
+///
+/// `include_image` 未指定时按模型注册表中的视觉能力决定是否测试图片输入。
+#[tauri::command]
+pub async fn test_provider_capabilities(
+    state: tauri::State<'_, AppState>,
+    logs: tauri::State<'_, LogState>,
+    registry: tauri::State<'_, ModelRegistryState>,
+    provider: String,
+    model: String,
+    include_image: Option<bool>,
+) -> Result<ProviderTestReport, String> {
+    let (url, api_key) = {
+        let s = state.read().await;
+        if !s.running {
+            return Err("代理服务未启动，无法执行接入测试".to_string());
+        }
+        let status = s.status();
+        let api_key = s
+            .running_api_key
+            .clone()
+            .unwrap_or_else(|| s.config.server.api_key.clone());
+        (
+            format!("http://{}:{}/v1/chat/completions", status.host, status.port),
+            api_key,
+        )
+    };
+
+    let registry_guard = registry.read().await;
+    let registry_service = registry_guard.as_ref();
+    let test_image = match include_image {
+        Some(include) => include,
+        None => match registry_service {
+            Some(service) => service
+                .get_all_models()
+                .await
+                .iter()
+                .any(|m| m.id == model && m.capabilities.vision),
+            None => false,
+        },
+    };
+
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .timeout(Duration::from_secs(CASE_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    logs.write().await.add(
+        "info",
+        &format!("[接入测试] 开始测试 provider={provider} model={model}"),
+    );
+
+    let mut results = Vec::new();
+    for capability in ProviderTestCapability::ALL {
+        if capability == ProviderTestCapability::Image && !test_image {
+            results.push(skipped(capability, "模型未声明支持图片输入"));
+            continue;
+        }
+        let result = run_case(&client, &url, &api_key, &provider, &model, capability).await;
+        let chat_failed = capability == ProviderTestCapability::Chat
+            && result.status == ProviderTestStatus::Failed;
+        results.push(result);
+        if chat_failed {
+            // 基础对话不可用时其余测试没有意义
+            for rest in ProviderTestCapability::ALL.iter().skip(1) {
+                results.push(skipped(*rest, "普通对话测试未通过"));
+            }
+            break;
+        }
+    }
+
+    let mut registry_updated = false;
+    if result_of(&results, ProviderTestCapability::Chat) == Some(true) {
+        if let Some(service) = registry_service {
+            let streaming = result_of(&results, ProviderTestCapability::Streaming);
+            let tools = result_of(&results, ProviderTestCapability::ToolCall);
+            let vision = result_of(&results, ProviderTestCapability::Image);
+            match service
+                .update_model_capabilities(&model, &provider, |capabilities| {
+                    if let Some(streaming) = streaming {
+                        capabilities.streaming = streaming;
+                    }
+                    if let Some(tools) = tools {
+                        capabilities.tools = tools;
+                        capabilities.function_calling = tools;
+                    }
+                    if let Some(vision) = vision {
+                        capabilities.vision = vision;
+                    }
+                })
+                .await
+            {
+                Ok(_) => registry_updated = true,
+                Err(e) => tracing::warn!("[接入测试] 写回模型注册表失败: {}", e),
+            }
+        }
+    }
+
+    let passed = results
+        .iter()
+        .filter(|r| r.status == ProviderTestStatus::Passed)
+        .count();
+    let tested = results
+        .iter()
+        .filter(|r| r.status != ProviderTestStatus::Skipped)
+        .count();
+    logs.write().await.add(
+        "info",
+        &format!("[接入测试] provider={provider} model={model} 通过 {passed}/{tested}"),
+    );
+
+    Ok(ProviderTestReport {
+        provider,
+        model,
+        results,
+        registry_updated,
+        tested_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_responses() {
+        let chat = r#"{"choices":[{"message":{"role":"assistant","content":"OK"}}]}"#;
+        assert!(evaluate(ProviderTestCapability::Chat, chat).is_ok());
+        assert!(evaluate(ProviderTestCapability::ToolCall, chat).is_err());
+
+        let tool = r#"{"choices":[{"message":{"tool_calls":[{"type":"function","function":{"name":"get_weather","arguments":"{}"}}]}}]}"#;
+        assert!(evaluate(ProviderTestCapability::ToolCall, tool).is_ok());
+
+        let long = r#"{"choices":[{"message":{"content":"LIME-4217"}}]}"#;
+        assert!(evaluate(ProviderTestCapability::LongPrompt, long).is_ok());
+        assert!(evaluate(ProviderTestCapability::LongPrompt, chat).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_streaming() {
+        let stream = "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n\
+                      data: {\"choices\":[{\"delta\":{\"content\":\"1 2\"}}]}\n\n\
+                      data: [DONE]\n\n";
+        assert!(evaluate(ProviderTestCapability::Streaming, stream).is_ok());
+        assert!(evaluate(ProviderTestCapability::Streaming, "{\"choices\":[]}").is_err());
+        assert!(evaluate(
+            ProviderTestCapability::Streaming,
+            "data: {\"choices\":[]}\n\n"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,52 @@
+//! 声明式测试场景命令
+
+use std::path::PathBuf;
+
+use crate::app::types::AppState;
+use lime_services::scenario_service::{self, ScenarioReport};
+use tauri::State;
+
+/// 列出场景目录中的场景文件
+#[tauri::command]
+pub async fn list_test_scenarios() -> Result<Vec<String>, String> {
+    Ok(
+        scenario_service::list_scenario_files(&scenario_service::scenarios_dir())
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect(),
+    )
+}
+
+/// 对本地服务运行测试场景（注意：请求会真实转发到上游并计入用量）
+///
+/// `paths` 为空时运行场景目录中的全部场景文件。
+#[tauri::command]
+pub async fn run_test_scenarios(
+    state: State<'_, AppState>,
+    paths: Option<Vec<String>>,
+) -> Result<Vec<ScenarioReport>, String> {
+    let (base_url, api_key) = {
+        let s = state.read().await;
+        if !s.running {
+            return Err("服务未启动，请先启动服务".to_string());
+        }
+        let status = s.status();
+        let api_key = s
+            .running_api_key
+            .clone()
+            .unwrap_or_else(|| s.config.server.api_key.clone());
+        (format!("http://{}:{}", status.host, status.port), api_key)
+    };
+
+    let files: Vec<PathBuf> = match paths.filter(|paths| !paths.is_empty()) {
+        Some(paths) => paths.into_iter().map(PathBuf::from).collect(),
+        None => scenario_service::list_scenario_files(&scenario_service::scenarios_dir()),
+    };
+    if files.is_empty() {
+        return Err(format!(
+            "没有找到测试场景，请把 YAML 场景文件放到 {}",
+            scenario_service::scenarios_dir().display()
+        ));
+    }
+    Ok(scenario_service::run_scenario_files(&base_url, &api_key, &files).await)
+}
@@ -1,7 +1,9 @@
 //! 安全与性能配置命令
 
+use crate::app::rbac::{hash_pin, is_legacy_pin_hash, AppRoleState};
 use crate::config::save_config;
 use crate::AppState;
+use lime_core::config::AppRole;
 use serde::{Deserialize, Serialize};
 
 // ========== 速率限制 ==========
@@ -142,3 +144,75 @@ pub async fn update_pairing_config(
     s.config.pairing.enabled = config.enabled;
     save_config(&s.config).map_err(|e| e.to_string())
 }
+
+// ========== 认证失败锁定 ==========
+
+/// 列出因认证失败被锁定的 IP
+#[tauri::command]
+pub async fn list_banned_ips(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<lime_server::auth::lockout::BannedIp>, String> {
+    let s = state.read().await;
+    Ok(s.auth_lockout.list_banned(chrono::Utc::now()))
+}
+
+/// 解除锁定（不传 `ip` 时清除全部），返回清除的记录数
+#[tauri::command]
+pub async fn clear_banned_ips(
+    state: tauri::State<'_, AppState>,
+    ip: Option<String>,
+) -> Result<usize, String> {
+    let ip = ip
+        .as_deref()
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty())
+        .map(|ip| {
+            ip.parse::<std::net::IpAddr>()
+                .map_err(|_| format!("无效的 IP 地址: {ip}"))
+        })
+        .transpose()?;
+    let s = state.read().await;
+    let cleared = s.auth_lockout.clear(ip);
+    tracing::info!("[AUTH] 已解除 {} 条认证失败锁定", cleared);
+    Ok(cleared)
+}
+
+// ========== 应用角色 ==========
+
+/// 当前角色
+#[tauri::command]
+pub async fn get_app_role(role_state: tauri::State<'_, AppRoleState>) -> Result<AppRole, String> {
+    Ok(role_state.get())
+}
+
+/// 切换角色（升级到更高角色时必须已设置 PIN 并通过校验）
+#[tauri::command]
+pub async fn set_app_role(
+    state: tauri::State<'_, AppState>,
+    role_state: tauri::State<'_, AppRoleState>,
+    role: AppRole,
+    pin: Option<String>,
+) -> Result<(), String> {
+    let pin_hash = state.read().await.config.access_control.pin_hash.clone();
+    let verified = role_state.switch(role, pin.as_deref(), pin_hash.as_deref())?;
+    tracing::info!("[RBAC] 已切换应用角色: {:?}", role);
+    // 旧版无盐摘要在校验通过后改存为加盐摘要
+    if verified && pin_hash.as_deref().is_some_and(is_legacy_pin_hash) {
+        let mut s = state.write().await;
+        s.config.access_control.pin_hash = Some(hash_pin(pin.as_deref().unwrap_or_default()));
+        save_config(&s.config).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 设置升级角色所需的 PIN（不传或为空时取消 PIN）
+#[tauri::command]
+pub async fn set_app_role_pin(
+    state: tauri::State<'_, AppState>,
+    pin: Option<String>,
+) -> Result<(), String> {
+    let pin = pin.as_deref().map(str::trim).filter(|pin| !pin.is_empty());
+    let mut s = state.write().await;
+    s.config.access_control.pin_hash = pin.map(hash_pin);
+    save_config(&s.config).map_err(|e| e.to_string())
+}
@@ -0,0 +1,286 @@
+//! 首次运行设置向导命令
+//!
+//! 每个步骤一个命令，进度保存在数据库中，前端可随时通过 `get_setup_wizard_state` 恢复。
+
+use crate::app::setup_wizard::{self, CredentialTestOutcome, SetupStep, SetupWizardState};
+use crate::app::types::{AppState, LogState};
+use crate::app::TokenCacheServiceState;
+use crate::commands::provider_pool_cmd::ProviderPoolServiceState;
+use crate::config::{generate_secure_api_key, ConfigAuditSource, GlobalConfigManagerState};
+use crate::database::{lock_db, DbConnection};
+use lime_core::database::pool_storage::{lock_pool, pool_storage};
+use serde::Serialize;
+use tauri::State;
+
+/// 向导完成后的服务信息
+#[derive(Debug, Clone, Serialize)]
+pub struct SetupServerInfo {
+    pub host: String,
+    pub port: u16,
+    pub base_url: String,
+}
+
+fn update_state<T>(
+    db: &DbConnection,
+    f: impl FnOnce(&mut SetupWizardState) -> Result<T, String>,
+) -> Result<(T, SetupWizardState), String> {
+    let conn = lock_db(db)?;
+    let mut state = setup_wizard::load_state(&conn);
+    let value = f(&mut state)?;
+    setup_wizard::save_state(&conn, &state)?;
+    Ok((value, state))
+}
+
+/// 获取设置向导进度
+#[tauri::command]
+pub async fn get_setup_wizard_state(
+    db: State<'_, DbConnection>,
+) -> Result<SetupWizardState, String> {
+    let conn = lock_db(&db)?;
+    Ok(setup_wizard::load_state(&conn))
+}
+
+/// 重新开始设置向导
+#[tauri::command]
+pub async fn reset_setup_wizard(db: State<'_, DbConnection>) -> Result<(), String> {
+    let conn = lock_db(&db)?;
+    setup_wizard::reset_state(&conn)
+}
+
+/// 步骤 1：检测本机已有的凭证
+#[tauri::command]
+pub async fn setup_detect_credentials(
+    db: State<'_, DbConnection>,
+) -> Result<SetupWizardState, String> {
+    let found = setup_wizard::scan_local_credentials();
+    let pool = {
+        let conn = lock_pool(&db)?;
+        pool_storage().get_all(&conn)?
+    };
+    let detected = found
+        .into_iter()
+        .map(|(mut detected, data)| {
+            detected.pool_uuid =
+                setup_wizard::find_in_pool(&pool, &data).map(|cred| cred.uuid.clone());
+            detected
+        })
+        .collect();
+
+    let ((), state) = update_state(&db, |state| {
+        state.detected = detected;
+        state.tested.clear();
+        state.advance_past(SetupStep::DetectCredentials);
+        Ok(())
+    })?;
+    Ok(state)
+}
+
+/// 步骤 2：把检测到的凭证加入凭证池并做健康检查，未通过的凭证不保留
+///
+/// `ids` 为空时测试全部检测到的凭证。
+#[tauri::command]
+pub async fn setup_test_credentials(
+    db: State<'_, DbConnection>,
+    pool_service: State<'_, ProviderPoolServiceState>,
+    ids: Option<Vec<String>>,
+) -> Result<SetupWizardState, String> {
+    let state = {
+        let conn = lock_db(&db)?;
+        setup_wizard::load_state(&conn)
+    };
+    state.require(SetupStep::TestCredentials)?;
+
+    let selected = |id: &str| match &ids {
+        Some(ids) => ids.iter().any(|i| i == id),
+        None => true,
+    };
+    let mut outcomes = Vec::new();
+    for (detected, data) in setup_wizard::scan_local_credentials() {
+        if !selected(&detected.id) {
+            continue;
+        }
+        let pool = {
+            let conn = lock_pool(&db)?;
+            pool_storage().get_all(&conn)?
+        };
+        let existing = setup_wizard::find_in_pool(&pool, &data).map(|cred| cred.uuid.clone());
+        let uuid = match existing.clone() {
+            Some(uuid) => uuid,
+            None => {
+                match pool_service.0.add_credential(
+                    &db,
+                    &data.provider_type().to_string(),
+                    data,
+                    Some(format!("设置向导导入: {}", detected.label)),
+                    None,
+                    None,
+                ) {
+                    Ok(cred) => cred.uuid,
+                    Err(e) => {
+                        outcomes.push(CredentialTestOutcome {
+                            id: detected.id,
+                            success: false,
+                            message: Some(e),
+                            pool_uuid: None,
+                        });
+                        continue;
+                    }
+                }
+            }
+        };
+
+        let result = pool_service.0.check_credential_health(&db, &uuid).await;
+        let (success, message) = match result {
+            Ok(result) => (result.success, result.message),
+            Err(e) => (false, Some(e)),
+        };
+        // 只清理本次导入且未通过的凭证，已有凭证保持原样
+        if !success && existing.is_none() {
+            if let Err(e) = pool_service.0.delete_credential_permanently(&db, &uuid) {
+                tracing::warn!("[SETUP] 清理未通过测试的凭证 {} 失败: {}", uuid, e);
+            }
+        }
+        outcomes.push(CredentialTestOutcome {
+            id: detected.id,
+            success,
+            message,
+            pool_uuid: (success || existing.is_some()).then_some(uuid),
+        });
+    }
+
+    let ((), state) = update_state(&db, |state| {
+        state.tested = outcomes;
+        state.advance_past(SetupStep::TestCredentials);
+        Ok(())
+    })?;
+    Ok(state)
+}
+
+/// 步骤 3：生成强随机的入站 API Key
+#[tauri::command]
+pub async fn setup_generate_api_key(
+    db: State<'_, DbConnection>,
+) -> Result<SetupWizardState, String> {
+    let ((), state) = update_state(&db, |state| {
+        state.require(SetupStep::GenerateApiKey)?;
+        state.api_key = Some(generate_secure_api_key());
+        state.advance_past(SetupStep::GenerateApiKey);
+        Ok(())
+    })?;
+    Ok(state)
+}
+
+/// 步骤 4：选择可用端口（从 `preferred` 或当前配置端口开始向后查找）
+#[tauri::command]
+pub async fn setup_choose_port(
+    db: State<'_, DbConnection>,
+    state: State<'_, AppState>,
+    preferred: Option<u16>,
+) -> Result<SetupWizardState, String> {
+    let (host, preferred, own_port) = {
+        let s = state.read().await;
+        let own_port = if s.running { s.running_port } else { None };
+        (
+            s.config.server.host.clone(),
+            preferred.unwrap_or(s.config.server.port),
+            own_port,
+        )
+    };
+    let port = setup_wizard::find_available_port(&host, preferred, own_port)
+        .ok_or_else(|| format!("端口 {preferred} 附近没有可用端口，请指定其他端口"))?;
+
+    let ((), wizard) = update_state(&db, |wizard| {
+        wizard.require(SetupStep::ChoosePort)?;
+        wizard.port = Some(port);
+        wizard.advance_past(SetupStep::ChoosePort);
+        Ok(())
+    })?;
+    Ok(wizard)
+}
+
+/// 步骤 5：写入初始配置
+#[tauri::command]
+pub async fn setup_write_config(
+    db: State<'_, DbConnection>,
+    state: State<'_, AppState>,
+    config_manager: State<'_, GlobalConfigManagerState>,
+) -> Result<SetupWizardState, String> {
+    let wizard = {
+        let conn = lock_db(&db)?;
+        setup_wizard::load_state(&conn)
+    };
+    wizard.require(SetupStep::WriteConfig)?;
+    let current = state.read().await.config.clone();
+    let (api_key, port) = match (wizard.api_key.as_deref(), wizard.port) {
+        (Some(api_key), Some(port)) => (api_key.to_string(), port),
+        // 配置已写入过（重做此步骤）时沿用当前配置
+        _ if wizard.step > SetupStep::WriteConfig => {
+            (current.server.api_key.clone(), current.server.port)
+        }
+        _ => return Err("请先生成 API Key 并选择端口".to_string()),
+    };
+
+    let config = setup_wizard::build_starter_config(&current, &api_key, port)?;
+    config_manager
+        .0
+        .save_config_from(&config, ConfigAuditSource::Ui)
+        .await?;
+    state.write().await.config = config;
+
+    let ((), wizard) = update_state(&db, |wizard| {
+        wizard.api_key = None;
+        wizard.advance_past(SetupStep::WriteConfig);
+        Ok(())
+    })?;
+    Ok(wizard)
+}
+
+/// 步骤 6：按新配置（重新）启动服务并完成向导
+#[tauri::command]
+pub async fn setup_start_server(
+    db: State<'_, DbConnection>,
+    state: State<'_, AppState>,
+    logs: State<'_, LogState>,
+    pool_service: State<'_, ProviderPoolServiceState>,
+    token_cache: State<'_, TokenCacheServiceState>,
+) -> Result<SetupServerInfo, String> {
+    {
+        let conn = lock_db(&db)?;
+        setup_wizard::load_state(&conn).require(SetupStep::StartServer)?;
+    }
+
+    let status = {
+        let mut s = state.write().await;
+        if s.running {
+            s.stop().await;
+        }
+        logs.write()
+            .await
+            .add("info", "Starting server (setup wizard)...");
+        s.start(
+            logs.inner().clone(),
+            pool_service.0.clone(),
+            token_cache.0.clone(),
+            Some(db.inner().clone()),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+        s.status()
+    };
+    logs.write().await.add(
+        "info",
+        &format!("Server started on {}:{}", status.host, status.port),
+    );
+
+    update_state(&db, |wizard| {
+        wizard.advance_past(SetupStep::StartServer);
+        wizard.completed_at = Some(chrono::Utc::now().to_rfc3339());
+        Ok(())
+    })?;
+    tracing::info!("[SETUP] 设置向导完成: {}:{}", status.host, status.port);
+    Ok(SetupServerInfo {
+        base_url: format!("http://{}:{}", status.host, status.port),
+        host: status.host,
+        port: status.port,
+    })
+}
@@ -2,12 +2,14 @@
 //!
 //! 提供请求日志、统计数据和 Token 追踪的 Tauri 命令
 
+use crate::app::types::AppState;
 use crate::telemetry::{
     ModelStats, ModelTokenStats, ProviderStats, ProviderTokenStats, RequestLog, RequestLogger,
     RequestStatus, StatsAggregator, StatsSummary, TimeRange, TokenStatsSummary, TokenTracker,
 };
 use crate::ProviderType;
 use chrono::{DateTime, Utc};
+use lime_credential::{simulate, BalanceStrategy, PoolScenario, SimulationReport, TrafficProfile};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -284,3 +286,48 @@ pub async fn get_token_stats_by_day(
     let tokens = state.tokens.read();
     Ok(tokens.by_day(days.unwrap_or(7)))
 }
+
+// ========== 凭证池模拟 ==========
+
+/// 凭证池模拟（What-if 分析）
+///
+/// 用请求日志中指定 Provider 的流量离线回放各场景，不发送上游请求。
+/// `include_current` 为 true 时，在结果最前面加入由当前 `credential_pool` 配置生成的基准场景。
+#[tauri::command]
+pub async fn simulate_credential_pool(
+    state: tauri::State<'_, TelemetryState>,
+    app_state: tauri::State<'_, AppState>,
+    provider: String,
+    time_range: Option<TimeRangeParam>,
+    scenarios: Vec<PoolScenario>,
+    include_current: Option<bool>,
+) -> Result<Vec<SimulationReport>, String> {
+    let provider_type: ProviderType = provider.parse().map_err(|e: String| e)?;
+    let range = time_range.map(|r| r.to_time_range()).transpose()?.flatten();
+    let logs = match range {
+        Some(range) => state.logger.get_by_time_range(range),
+        None => state.logger.get_all(),
+    };
+    let profile = TrafficProfile::from_logs(provider_type, &logs);
+    if profile.samples.is_empty() {
+        return Err(format!("请求日志中没有 {provider} 的流量"));
+    }
+
+    let mut scenarios = scenarios;
+    if include_current.unwrap_or(false) {
+        let config = app_state.read().await.config.credential_pool.clone();
+        scenarios.insert(
+            0,
+            PoolScenario::from_pool_config(
+                "当前配置",
+                &config,
+                provider_type,
+                BalanceStrategy::default(),
+            ),
+        );
+    }
+    Ok(scenarios
+        .iter()
+        .map(|scenario| simulate(&profile, scenario))
+        .collect())
+}
@@ -6,10 +6,13 @@
 //! - 7.1: API 服务器状态变化时在 1 秒内更新托盘图标
 //! - 7.2: 凭证健康状态变化时在 1 秒内更新托盘图标
 //! - 7.3: 托盘菜单打开时获取并显示最新信息
+//!
+//! 同时暴露托盘快捷操作（暂停 Provider、配置方案、复制地址和密钥）供前端调用。
 
+use crate::config;
 use crate::tray::{TrayIconStatus, TrayQuickModelGroup, TrayStateSnapshot};
-use crate::TrayManagerState;
-use tauri::State;
+use crate::{AppState, TrayManagerState};
+use tauri::{AppHandle, State};
 use tracing::{debug, info};
 
 /// 同步托盘状态
@@ -59,6 +62,9 @@ pub async fn sync_tray_state(
         current_model: current_state.current_model,
         current_theme_label: current_state.current_theme_label,
         quick_model_groups: current_state.quick_model_groups,
+        provider_toggles: current_state.provider_toggles,
+        config_profiles: current_state.config_profiles,
+        active_config_profile: current_state.active_config_profile,
     };
 
     tray_manager
@@ -258,6 +264,9 @@ pub async fn refresh_tray_with_stats(
         current_model: current_state.current_model,
         current_theme_label: current_state.current_theme_label,
         quick_model_groups: current_state.quick_model_groups,
+        provider_toggles: current_state.provider_toggles,
+        config_profiles: current_state.config_profiles,
+        active_config_profile: current_state.active_config_profile,
     };
 
     // 更新状态并刷新菜单
@@ -307,3 +316,51 @@ pub async fn sync_tray_model_shortcuts(
 
     Ok(())
 }
+
+/// 暂停或恢复整个 Provider，返回切换后是否处于暂停状态
+#[tauri::command]
+pub async fn toggle_provider_pause(app: AppHandle, provider_type: String) -> Result<bool, String> {
+    crate::tray::toggle_provider_pause(&app, &provider_type).await
+}
+
+/// 复制网关地址和 API Key 到剪贴板，并返回复制的文本
+#[tauri::command]
+pub async fn copy_api_credentials(app: AppHandle) -> Result<String, String> {
+    let text = crate::tray::api_credentials_text(&app).await?;
+    crate::tray::copy_to_clipboard(&text)?;
+    Ok(text)
+}
+
+/// 列出已保存的配置方案
+#[tauri::command]
+pub async fn list_config_profiles() -> Result<Vec<String>, String> {
+    Ok(config::list_config_profiles())
+}
+
+/// 将当前配置保存为配置方案
+#[tauri::command]
+pub async fn save_config_profile(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<(), String> {
+    let current = state.read().await.config.clone();
+    config::save_config_profile(&name, &current).map_err(|e| e.to_string())?;
+    info!("配置方案已保存: {}", name);
+    crate::tray::refresh_tray_controls(&app).await;
+    Ok(())
+}
+
+/// 删除配置方案
+#[tauri::command]
+pub async fn delete_config_profile(app: AppHandle, name: String) -> Result<bool, String> {
+    let deleted = config::delete_config_profile(&name).map_err(|e| e.to_string())?;
+    crate::tray::refresh_tray_controls(&app).await;
+    Ok(deleted)
+}
+
+/// 切换到指定配置方案
+#[tauri::command]
+pub async fn switch_config_profile(app: AppHandle, name: String) -> Result<(), String> {
+    crate::tray::apply_config_profile(&app, &name).await
+}
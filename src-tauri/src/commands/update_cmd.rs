@@ -2,10 +2,18 @@
 //!
 //! 提供自动更新检查相关的 Tauri 命令。
 //! 检查逻辑走静态 `latest.json` 清单，安装逻辑走 Tauri updater。
+//!
+//! - 通道：`stable` 与 `beta` 各自使用独立清单，stable 通道忽略预发布版本
+//! - 分阶段推送：清单可声明 `rollout_percent`，按本机安装标识稳定分桶，
+//!   未进入推送范围时不自动提醒、不后台下载，手动检查仍可见
+//! - 签名：清单中缺少当前平台签名的版本不提供应用内升级；
+//!   安装包由 updater 用内置公钥校验签名后才会保留或安装
+//! - 后台下载：开启 `auto_download` 后预先下载并校验安装包，用户确认后直接安装
 
 use crate::app::AppState;
 use crate::config;
 use crate::services::update_window;
+use lime_core::config::UpdateChannel;
 use lime_services::update_check_service::{
     UpdateCheckService, UpdateCheckServiceState, UpdateInfo,
 };
@@ -21,11 +29,33 @@ const UPDATE_CHECK_CACHE_TTL_SECS: u64 = 10 * 60;
 const FALLBACK_RELEASES_URL: &str = "https://github.com/aiclientproxy/lime/releases";
 const DEFAULT_UPDATE_MANIFEST_URL: &str =
     "https://github.com/aiclientproxy/lime/releases/latest/download/latest.json";
+const DEFAULT_BETA_UPDATE_MANIFEST_URL: &str =
+    "https://github.com/aiclientproxy/lime/releases/download/beta/latest.json";
 
 /// 编译期注入 updater 公钥；开发环境可为空，此时仅保留手动下载兜底。
 const COMPILED_UPDATER_PUBLIC_KEY: Option<&str> = option_env!("LIME_UPDATER_PUBLIC_KEY");
 /// 编译期注入 updater manifest 地址；未配置时使用 GitHub Releases latest.json。
 const COMPILED_UPDATER_ENDPOINT: Option<&str> = option_env!("LIME_UPDATER_ENDPOINT");
+/// 编译期注入 beta 通道 manifest 地址；未配置时使用 GitHub Releases 的 `beta` 标签。
+const COMPILED_UPDATER_BETA_ENDPOINT: Option<&str> = option_env!("LIME_UPDATER_BETA_ENDPOINT");
+
+/// 后台下载完成、已通过签名校验的安装包
+struct DownloadedUpdate {
+    version: String,
+    channel: UpdateChannel,
+    update: tauri_plugin_updater::Update,
+    bytes: Vec<u8>,
+}
+
+static DOWNLOADED_UPDATE: tokio::sync::Mutex<Option<DownloadedUpdate>> =
+    tokio::sync::Mutex::const_new(None);
+
+/// 单次检查所需的本机配置
+#[derive(Debug, Clone)]
+struct UpdateCheckContext {
+    channel: UpdateChannel,
+    install_id: String,
+}
 
 /// 更新检查配置（前端可见）
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +66,10 @@ pub struct UpdateCheckSettings {
     pub last_check_timestamp: u64,
     pub skipped_version: Option<String>,
     pub remind_later_until: Option<u64>,
+    #[serde(default)]
+    pub channel: UpdateChannel,
+    #[serde(default)]
+    pub auto_download: bool,
 }
 
 /// 更新提醒埋点指标
@@ -65,6 +99,13 @@ pub struct VersionCheckResult {
     #[serde(rename = "pubDate")]
     pub pub_date: Option<String>,
     pub error: Option<String>,
+    pub channel: UpdateChannel,
+    #[serde(rename = "rolloutPercent")]
+    pub rollout_percent: Option<u8>,
+    #[serde(rename = "inRollout")]
+    pub in_rollout: bool,
+    #[serde(rename = "readyToInstall")]
+    pub ready_to_install: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,6 +123,10 @@ struct UpdateCheckCache {
     release_notes: Option<String>,
     pub_date: Option<String>,
     last_checked_unix: u64,
+    #[serde(default)]
+    channel: UpdateChannel,
+    #[serde(default)]
+    rollout_percent: Option<u8>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -91,13 +136,15 @@ struct StaticUpdateManifest {
     notes: Option<String>,
     #[serde(default)]
     pub_date: Option<String>,
+    /// 分阶段推送比例（0-100），缺省为全量
+    #[serde(default)]
+    rollout_percent: Option<u8>,
     platforms: HashMap<String, StaticUpdatePlatform>,
 }
 
 #[derive(Debug, Deserialize)]
 struct StaticUpdatePlatform {
     url: String,
-    #[allow(dead_code)]
     signature: Option<String>,
 }
 
@@ -116,11 +163,18 @@ fn current_unix_timestamp() -> u64 {
         .unwrap_or(0)
 }
 
-fn updater_manifest_url() -> &'static str {
-    COMPILED_UPDATER_ENDPOINT
+fn updater_manifest_url(channel: UpdateChannel) -> &'static str {
+    let (compiled, default) = match channel {
+        UpdateChannel::Stable => (COMPILED_UPDATER_ENDPOINT, DEFAULT_UPDATE_MANIFEST_URL),
+        UpdateChannel::Beta => (
+            COMPILED_UPDATER_BETA_ENDPOINT,
+            DEFAULT_BETA_UPDATE_MANIFEST_URL,
+        ),
+    };
+    compiled
         .map(str::trim)
         .filter(|value| !value.is_empty())
-        .unwrap_or(DEFAULT_UPDATE_MANIFEST_URL)
+        .unwrap_or(default)
 }
 
 fn updater_public_key() -> Option<&'static str> {
@@ -169,8 +223,13 @@ fn get_update_check_cache_path() -> PathBuf {
     base_dir.join("lime").join("update-check-cache.json")
 }
 
-fn is_update_cache_fresh(cache: &UpdateCheckCache, now_unix: u64, ttl_secs: u64) -> bool {
-    if cache.latest.is_none() {
+fn is_update_cache_fresh(
+    cache: &UpdateCheckCache,
+    channel: UpdateChannel,
+    now_unix: u64,
+    ttl_secs: u64,
+) -> bool {
+    if cache.latest.is_none() || cache.channel != channel {
         return false;
     }
 
@@ -220,14 +279,37 @@ fn build_update_info(
         pub_date,
         checked_at: current_unix_timestamp(),
         error,
+        channel: UpdateChannel::default(),
+        rollout_percent: None,
+        in_rollout: true,
+        ready_to_install: false,
+    }
+}
+
+/// 按通道与分阶段推送修正检查结果
+fn apply_channel_and_rollout(
+    mut info: UpdateInfo,
+    context: &UpdateCheckContext,
+    rollout_percent: Option<u8>,
+) -> UpdateInfo {
+    info.channel = context.channel;
+    info.rollout_percent = rollout_percent;
+    if let Some(latest) = info.latest_version.as_deref() {
+        if context.channel == UpdateChannel::Stable && UpdateCheckService::is_prerelease(latest) {
+            info.has_update = false;
+        }
+        info.in_rollout =
+            UpdateCheckService::in_rollout(&context.install_id, latest, rollout_percent);
     }
+    info
 }
 
 fn build_update_info_from_cache_or_default(
     cache: Option<&UpdateCheckCache>,
+    context: &UpdateCheckContext,
     error: Option<String>,
 ) -> UpdateInfo {
-    if let Some(cached) = cache {
+    if let Some(cached) = cache.filter(|cached| cached.channel == context.channel) {
         let mut info = build_update_info(
             cached.latest.clone(),
             cached.release_notes.clone(),
@@ -238,10 +320,10 @@ fn build_update_info_from_cache_or_default(
             info.download_url = cached.download_url.clone();
             info.release_notes_url = cached.download_url.clone();
         }
-        return info;
+        return apply_channel_and_rollout(info, context, cached.rollout_percent);
     }
 
-    build_update_info(None, None, None, error)
+    apply_channel_and_rollout(build_update_info(None, None, None, error), context, None)
 }
 
 fn build_version_check_result(info: UpdateInfo) -> VersionCheckResult {
@@ -253,57 +335,83 @@ fn build_version_check_result(info: UpdateInfo) -> VersionCheckResult {
         release_notes: info.release_notes,
         pub_date: info.pub_date,
         error: info.error,
+        channel: info.channel,
+        rollout_percent: info.rollout_percent,
+        in_rollout: info.in_rollout,
+        ready_to_install: info.ready_to_install,
     }
 }
 
-fn manifest_to_cache(manifest: &StaticUpdateManifest, checked_at: u64) -> UpdateCheckCache {
+fn manifest_to_cache(
+    manifest: &StaticUpdateManifest,
+    channel: UpdateChannel,
+    checked_at: u64,
+) -> UpdateCheckCache {
     UpdateCheckCache {
         latest: Some(manifest.version.trim_start_matches('v').to_string()),
         download_url: Some(release_tag_url(&manifest.version)),
         release_notes: manifest.notes.clone(),
         pub_date: manifest.pub_date.clone(),
         last_checked_unix: checked_at,
+        channel,
+        rollout_percent: manifest.rollout_percent,
     }
 }
 
-fn build_update_info_from_manifest(manifest: StaticUpdateManifest) -> UpdateInfo {
+fn build_update_info_from_manifest(
+    manifest: StaticUpdateManifest,
+    context: &UpdateCheckContext,
+) -> UpdateInfo {
     let latest_version = manifest.version.trim_start_matches('v').to_string();
     let platform_error = match current_platform_key() {
-        Some(platform_key)
-            if manifest
-                .platforms
-                .get(platform_key)
-                .is_some_and(|platform| !platform.url.trim().is_empty()) =>
-        {
-            None
-        }
-        Some(platform_key) if manifest.platforms.contains_key(platform_key) => Some(format!(
-            "已检测到新版本，但当前平台 {} 的安装包地址为空，请前往发布页手动下载",
-            platform_key
-        )),
-        Some(platform_key) => Some(format!(
-            "已检测到新版本，但当前平台 {} 暂无安装包，请前往发布页手动下载",
-            platform_key
-        )),
+        Some(platform_key) => match manifest.platforms.get(platform_key) {
+            Some(platform) if platform.url.trim().is_empty() => Some(format!(
+                "已检测到新版本，但当前平台 {} 的安装包地址为空，请前往发布页手动下载",
+                platform_key
+            )),
+            Some(platform)
+                if !platform
+                    .signature
+                    .as_deref()
+                    .is_some_and(|signature| !signature.trim().is_empty()) =>
+            {
+                Some(format!(
+                    "已检测到新版本，但当前平台 {} 的安装包缺少签名，已停止应用内升级，请前往发布页手动下载",
+                    platform_key
+                ))
+            }
+            Some(_) => None,
+            None => Some(format!(
+                "已检测到新版本，但当前平台 {} 暂无安装包，请前往发布页手动下载",
+                platform_key
+            )),
+        },
         None => Some("当前平台暂不支持应用内升级，请前往发布页手动下载".to_string()),
     };
 
-    build_update_info(
+    let rollout_percent = manifest.rollout_percent;
+    let info = build_update_info(
         Some(latest_version),
         manifest.notes,
         manifest.pub_date,
         platform_error,
-    )
+    );
+    apply_channel_and_rollout(info, context, rollout_percent)
 }
 
-async fn fetch_update_info() -> UpdateInfo {
+async fn fetch_update_info(context: &UpdateCheckContext) -> UpdateInfo {
     let now_unix = current_unix_timestamp();
     let cache_path = get_update_check_cache_path();
     let cached = load_update_check_cache(&cache_path);
 
     if let Some(cache) = &cached {
-        if is_update_cache_fresh(cache, now_unix, UPDATE_CHECK_CACHE_TTL_SECS) {
-            return build_update_info_from_cache_or_default(cached.as_ref(), None);
+        if is_update_cache_fresh(
+            cache,
+            context.channel,
+            now_unix,
+            UPDATE_CHECK_CACHE_TTL_SECS,
+        ) {
+            return build_update_info_from_cache_or_default(cached.as_ref(), context, None);
         }
     }
 
@@ -315,13 +423,14 @@ async fn fetch_update_info() -> UpdateInfo {
         Err(error) => {
             return build_update_info_from_cache_or_default(
                 cached.as_ref(),
+                context,
                 Some(format!("创建更新检查客户端失败，已回退本地缓存: {error}")),
             );
         }
     };
 
     match client
-        .get(updater_manifest_url())
+        .get(updater_manifest_url(context.channel))
         .header("User-Agent", "Lime")
         .send()
         .await
@@ -330,6 +439,7 @@ async fn fetch_update_info() -> UpdateInfo {
             if !response.status().is_success() {
                 return build_update_info_from_cache_or_default(
                     cached.as_ref(),
+                    context,
                     Some(format!(
                         "更新清单请求失败（HTTP {}），已回退本地缓存",
                         response.status()
@@ -339,50 +449,159 @@ async fn fetch_update_info() -> UpdateInfo {
 
             match response.json::<StaticUpdateManifest>().await {
                 Ok(manifest) => {
-                    let cache = manifest_to_cache(&manifest, now_unix);
+                    let cache = manifest_to_cache(&manifest, context.channel, now_unix);
                     let _ = save_update_check_cache(&cache_path, &cache);
-                    build_update_info_from_manifest(manifest)
+                    build_update_info_from_manifest(manifest, context)
                 }
                 Err(error) => build_update_info_from_cache_or_default(
                     cached.as_ref(),
+                    context,
                     Some(format!("解析更新清单失败，已回退本地缓存: {error}")),
                 ),
             }
         }
         Err(error) => build_update_info_from_cache_or_default(
             cached.as_ref(),
+            context,
             Some(format!("请求更新清单失败，已回退本地缓存: {error}")),
         ),
     }
 }
 
-async fn perform_update_check(update_service: &UpdateCheckServiceState) -> UpdateInfo {
+/// 读取通道与安装标识；安装标识缺失时生成并保存
+async fn load_update_check_context(app_handle: &AppHandle) -> UpdateCheckContext {
+    let Some(app_state) = app_handle.try_state::<AppState>() else {
+        return UpdateCheckContext {
+            channel: UpdateChannel::default(),
+            install_id: uuid::Uuid::new_v4().to_string(),
+        };
+    };
+    let mut state = app_state.write().await;
+    let update_config = &mut state.config.experimental.update_check;
+    let channel = update_config.channel;
+    let install_id = match update_config.install_id.clone() {
+        Some(install_id) => install_id,
+        None => {
+            let install_id = uuid::Uuid::new_v4().to_string();
+            update_config.install_id = Some(install_id.clone());
+            if let Err(error) = config::save_config(&state.config) {
+                tracing::warn!("[更新检查] 保存安装标识失败: {}", error);
+            }
+            install_id
+        }
+    };
+    UpdateCheckContext {
+        channel,
+        install_id,
+    }
+}
+
+async fn perform_update_check(
+    update_service: &UpdateCheckServiceState,
+    context: &UpdateCheckContext,
+) -> UpdateInfo {
     {
         let service = update_service.0.read().await;
         service.begin_check().await;
     }
 
-    let result = fetch_update_info().await;
+    let result = fetch_update_info(context).await;
 
     let service = update_service.0.read().await;
     service.finish_check(result).await
 }
 
-async fn install_update_via_updater(app_handle: &AppHandle) -> Result<(), String> {
+fn build_updater(
+    app_handle: &AppHandle,
+    channel: UpdateChannel,
+) -> Result<tauri_plugin_updater::Updater, String> {
     let public_key = updater_public_key()
         .ok_or_else(|| "当前构建未内置更新签名公钥，请前往网页下载最新版".to_string())?;
-    let manifest_url = url::Url::parse(updater_manifest_url())
+    let manifest_url = url::Url::parse(updater_manifest_url(channel))
         .map_err(|error| format!("更新清单地址无效: {error}"))?;
 
-    let updater = app_handle
+    app_handle
         .updater_builder()
         .pubkey(public_key)
         .endpoints(vec![manifest_url])
         .map_err(|error| format!("初始化更新源失败: {error}"))?
         .build()
-        .map_err(|error| format!("创建 updater 失败: {error}"))?;
+        .map_err(|error| format!("创建 updater 失败: {error}"))
+}
+
+/// 清除后台下载的安装包（切换通道或安装完成后）
+async fn clear_downloaded_update(update_service: &UpdateCheckServiceState) {
+    *DOWNLOADED_UPDATE.lock().await = None;
+    let service = update_service.0.read().await;
+    service.set_downloaded_version(None).await;
+}
+
+/// 在后台下载指定版本的安装包
+///
+/// 下载完成后由 updater 用内置公钥校验签名，校验失败的安装包直接丢弃。
+async fn download_update_in_background(
+    app_handle: &AppHandle,
+    update_service: &UpdateCheckServiceState,
+    channel: UpdateChannel,
+    version: &str,
+) -> Result<(), String> {
+    {
+        let downloaded = DOWNLOADED_UPDATE.lock().await;
+        if downloaded
+            .as_ref()
+            .is_some_and(|pending| pending.channel == channel && pending.version == version)
+        {
+            return Ok(());
+        }
+    }
+
+    let update = build_updater(app_handle, channel)?
+        .check()
+        .await
+        .map_err(|error| format!("检查更新安装包失败: {error}"))?
+        .ok_or_else(|| "当前已是最新版本".to_string())?;
+    let update_version = update.version.trim_start_matches('v').to_string();
+    if update_version != version {
+        return Err(format!(
+            "更新清单版本 {} 与检查结果 {} 不一致，跳过后台下载",
+            update_version, version
+        ));
+    }
+
+    let bytes = update
+        .download(|_, _| {}, || {})
+        .await
+        .map_err(|error| format!("下载或校验安装包签名失败: {error}"))?;
+    tracing::info!(
+        "[更新检查] 已在后台下载 {} 安装包（{} 字节），签名校验通过",
+        update_version,
+        bytes.len()
+    );
+
+    *DOWNLOADED_UPDATE.lock().await = Some(DownloadedUpdate {
+        version: update_version.clone(),
+        channel,
+        update,
+        bytes,
+    });
+    let service = update_service.0.read().await;
+    service.set_downloaded_version(Some(update_version)).await;
+    Ok(())
+}
+
+async fn install_update_via_updater(
+    app_handle: &AppHandle,
+    channel: UpdateChannel,
+) -> Result<(), String> {
+    let downloaded = DOWNLOADED_UPDATE.lock().await.take();
+    if let Some(pending) = downloaded.filter(|pending| pending.channel == channel) {
+        return pending
+            .update
+            .install(&pending.bytes)
+            .map_err(|error| format!("安装更新失败: {error}"));
+    }
 
-    let update = updater
+    let update = build_updater(app_handle, channel)?
         .check()
         .await
         .map_err(|error| format!("检查更新安装包失败: {error}"))?
@@ -399,27 +618,45 @@ async fn install_update_via_updater(app_handle: &AppHandle) -> Result<(), String
 /// 手动检查更新，返回完整检查结果
 #[tauri::command]
 pub async fn check_update(
+    app_handle: AppHandle,
     update_service: State<'_, UpdateCheckServiceState>,
 ) -> Result<UpdateInfo, String> {
-    Ok(perform_update_check(update_service.inner()).await)
+    let context = load_update_check_context(&app_handle).await;
+    Ok(perform_update_check(update_service.inner(), &context).await)
 }
 
 /// 手动检查更新，返回前端兼容结构
 #[tauri::command]
 pub async fn check_for_updates(
+    app_handle: AppHandle,
     update_service: State<'_, UpdateCheckServiceState>,
 ) -> Result<VersionCheckResult, String> {
-    let info = perform_update_check(update_service.inner()).await;
+    let context = load_update_check_context(&app_handle).await;
+    let info = perform_update_check(update_service.inner(), &context).await;
     Ok(build_version_check_result(info))
 }
 
+/// 获取最近一次检查结果（含发布说明），不触发网络请求
+#[tauri::command]
+pub async fn get_last_update_result(
+    update_service: State<'_, UpdateCheckServiceState>,
+) -> Result<Option<VersionCheckResult>, String> {
+    let service = update_service.0.read().await;
+    Ok(service
+        .get_state()
+        .await
+        .last_result
+        .map(build_version_check_result))
+}
+
 /// 下载并安装更新
 #[tauri::command]
 pub async fn download_update(
     app_handle: AppHandle,
     update_service: State<'_, UpdateCheckServiceState>,
 ) -> Result<DownloadResult, String> {
-    let update_info = perform_update_check(update_service.inner()).await;
+    let context = load_update_check_context(&app_handle).await;
+    let update_info = perform_update_check(update_service.inner(), &context).await;
 
     if !update_info.has_update {
         return Ok(DownloadResult {
@@ -429,7 +666,9 @@ pub async fn download_update(
         });
     }
 
-    match install_update_via_updater(&app_handle).await {
+    let install_result = install_update_via_updater(&app_handle, context.channel).await;
+    clear_downloaded_update(update_service.inner()).await;
+    match install_result {
         Ok(()) => {
             let app_handle_clone = app_handle.clone();
             tauri::async_runtime::spawn(async move {
@@ -466,6 +705,8 @@ pub async fn get_update_check_settings(
         last_check_timestamp: update_config.last_check_timestamp,
         skipped_version: update_config.skipped_version.clone(),
         remind_later_until: update_config.remind_later_until,
+        channel: update_config.channel,
+        auto_download: update_config.auto_download,
     })
 }
 
@@ -473,18 +714,35 @@ pub async fn get_update_check_settings(
 #[tauri::command]
 pub async fn set_update_check_settings(
     app_state: State<'_, AppState>,
+    update_service: State<'_, UpdateCheckServiceState>,
     settings: UpdateCheckSettings,
 ) -> Result<(), String> {
     let mut state = app_state.write().await;
     let update_config = &mut state.config.experimental.update_check;
 
+    let channel_changed = update_config.channel != settings.channel;
+    if channel_changed {
+        // 切换通道后立即按新通道重新检查
+        update_config.last_check_timestamp = 0;
+        update_config.skipped_version = None;
+    }
+    update_config.channel = settings.channel;
+    update_config.auto_download = settings.auto_download;
     update_config.enabled = settings.enabled;
     update_config.check_interval_hours = settings.check_interval_hours;
     update_config.show_notification = settings.show_notification;
-    update_config.skipped_version = settings.skipped_version;
+    if !channel_changed {
+        update_config.skipped_version = settings.skipped_version;
+    }
     update_config.remind_later_until = settings.remind_later_until;
 
-    config::save_config(&state.config).map_err(|e| format!("保存配置失败: {e}"))
+    config::save_config(&state.config).map_err(|e| format!("保存配置失败: {e}"))?;
+    drop(state);
+
+    if channel_changed {
+        clear_downloaded_update(update_service.inner()).await;
+    }
+    Ok(())
 }
 
 /// 获取更新提醒埋点指标
@@ -644,6 +902,10 @@ pub fn test_update_window(app_handle: AppHandle) -> Result<(), String> {
         pub_date: Some("2026-03-21T00:00:00Z".to_string()),
         checked_at: current_unix_timestamp(),
         error: None,
+        channel: UpdateChannel::default(),
+        rollout_percent: None,
+        in_rollout: true,
+        ready_to_install: false,
     };
 
     update_window::open_update_window(&app_handle, &test_info)
@@ -686,6 +948,7 @@ pub async fn start_background_update_check(
                 last_notified_version,
                 last_notified_at,
                 next_notify_after,
+                auto_download,
             ) = {
                 if let Some(app_state) = app_handle_clone.try_state::<AppState>() {
                     let state = app_state.read().await;
@@ -700,9 +963,10 @@ pub async fn start_background_update_check(
                         update_config.last_notified_version.clone(),
                         update_config.last_notified_at,
                         update_config.next_notify_after,
+                        update_config.auto_download,
                     )
                 } else {
-                    (true, 24, true, 0, None, None, None, 0, None)
+                    (true, 24, true, 0, None, None, None, 0, None, false)
                 }
             };
 
@@ -725,22 +989,53 @@ pub async fn start_background_update_check(
                 skipped_version.as_deref(),
                 latest_version,
             ) {
-                let result = perform_update_check(&update_service).await;
+                let context = load_update_check_context(&app_handle_clone).await;
+                let mut result = perform_update_check(&update_service, &context).await;
 
                 tracing::info!(
-                    "[更新检查] 当前版本: {}, 最新版本: {:?}, 有更新: {}",
+                    "[更新检查] 通道: {:?}, 当前版本: {}, 最新版本: {:?}, 有更新: {}, 推送范围内: {}",
+                    result.channel,
                     result.current_version,
                     result.latest_version,
-                    result.has_update
+                    result.has_update,
+                    result.in_rollout
                 );
 
+                // 分阶段推送：未进入推送范围时不提醒、不下载
+                let skipped_latest = result
+                    .latest_version
+                    .as_ref()
+                    .is_some_and(|latest| skipped_version.as_ref() == Some(latest));
+                if result.has_update
+                    && result.in_rollout
+                    && auto_download
+                    && result.error.is_none()
+                    && !skipped_latest
+                {
+                    if let Some(latest) = result.latest_version.clone() {
+                        match download_update_in_background(
+                            &app_handle_clone,
+                            &update_service,
+                            context.channel,
+                            &latest,
+                        )
+                        .await
+                        {
+                            Ok(()) => result.ready_to_install = true,
+                            Err(error) => {
+                                tracing::warn!("[更新检查] 后台下载更新失败: {}", error)
+                            }
+                        }
+                    }
+                }
+
                 if let Some(app_state) = app_handle_clone.try_state::<AppState>() {
                     let mut state = app_state.write().await;
                     state.config.experimental.update_check.last_check_timestamp = result.checked_at;
                     let _ = config::save_config(&state.config);
                 }
 
-                if result.has_update && show_notification {
+                if result.has_update && result.in_rollout && show_notification {
                     let now = current_unix_timestamp();
                     let in_remind_later =
                         remind_later_until.is_some_and(|timestamp| timestamp > now);
@@ -812,6 +1107,35 @@ mod tests {
         format!("v{}.{}.{}", parts[0], parts[1], parts[2])
     }
 
+    fn stable_context() -> UpdateCheckContext {
+        UpdateCheckContext {
+            channel: UpdateChannel::Stable,
+            install_id: "install-test".to_string(),
+        }
+    }
+
+    fn manifest_for_current_platform(
+        version: String,
+        signature: Option<&str>,
+        rollout_percent: Option<u8>,
+    ) -> StaticUpdateManifest {
+        StaticUpdateManifest {
+            version,
+            notes: Some("bug fixes".to_string()),
+            pub_date: Some("2026-03-21T00:00:00Z".to_string()),
+            rollout_percent,
+            platforms: HashMap::from([(
+                current_platform_key()
+                    .unwrap_or("windows-x86_64")
+                    .to_string(),
+                StaticUpdatePlatform {
+                    url: "https://example.com/lime.nsis.zip".to_string(),
+                    signature: signature.map(str::to_string),
+                },
+            )]),
+        }
+    }
+
     #[test]
     fn test_is_update_cache_fresh() {
         let cache = UpdateCheckCache {
@@ -820,40 +1144,76 @@ mod tests {
             release_notes: Some("notes".to_string()),
             pub_date: Some("2026-03-21T00:00:00Z".to_string()),
             last_checked_unix: 100,
+            channel: UpdateChannel::Stable,
+            rollout_percent: None,
         };
 
-        assert!(is_update_cache_fresh(&cache, 150, 60));
-        assert!(!is_update_cache_fresh(&cache, 170, 60));
+        assert!(is_update_cache_fresh(
+            &cache,
+            UpdateChannel::Stable,
+            150,
+            60
+        ));
+        assert!(!is_update_cache_fresh(
+            &cache,
+            UpdateChannel::Stable,
+            170,
+            60
+        ));
+        assert!(!is_update_cache_fresh(&cache, UpdateChannel::Beta, 150, 60));
 
         let cache_without_latest = UpdateCheckCache {
             latest: None,
             ..cache
         };
-        assert!(!is_update_cache_fresh(&cache_without_latest, 120, 60));
+        assert!(!is_update_cache_fresh(
+            &cache_without_latest,
+            UpdateChannel::Stable,
+            120,
+            60
+        ));
     }
 
     #[test]
     fn test_build_update_info_from_manifest() {
         let next_version_tag = next_patch_version_tag();
         let next_version = next_version_tag.trim_start_matches('v').to_string();
-        let manifest = StaticUpdateManifest {
-            version: next_version_tag,
-            notes: Some("bug fixes".to_string()),
-            pub_date: Some("2026-03-21T00:00:00Z".to_string()),
-            platforms: HashMap::from([(
-                current_platform_key()
-                    .unwrap_or("windows-x86_64")
-                    .to_string(),
-                StaticUpdatePlatform {
-                    url: "https://example.com/lime.nsis.zip".to_string(),
-                    signature: Some("sig".to_string()),
-                },
-            )]),
-        };
+        let manifest = manifest_for_current_platform(next_version_tag, Some("sig"), None);
 
-        let info = build_update_info_from_manifest(manifest);
+        let info = build_update_info_from_manifest(manifest, &stable_context());
         assert_eq!(info.latest_version.as_deref(), Some(next_version.as_str()));
         assert!(info.has_update);
+        assert!(info.in_rollout);
         assert_eq!(info.error, None);
     }
+
+    #[test]
+    fn test_manifest_channel_signature_and_rollout() {
+        if current_platform_key().is_none() {
+            return;
+        }
+        let next_version_tag = next_patch_version_tag();
+
+        let unsigned = manifest_for_current_platform(next_version_tag.clone(), Some(" "), None);
+        let info = build_update_info_from_manifest(unsigned, &stable_context());
+        assert!(info.error.is_some_and(|error| error.contains("缺少签名")));
+
+        let beta_tag = format!("{next_version_tag}-beta.1");
+        let beta = manifest_for_current_platform(beta_tag.clone(), Some("sig"), None);
+        assert!(!build_update_info_from_manifest(beta, &stable_context()).has_update);
+        let beta_context = UpdateCheckContext {
+            channel: UpdateChannel::Beta,
+            ..stable_context()
+        };
+        let beta = manifest_for_current_platform(beta_tag, Some("sig"), None);
+        let info = build_update_info_from_manifest(beta, &beta_context);
+        assert!(info.has_update);
+        assert_eq!(info.channel, UpdateChannel::Beta);
+
+        let staged = manifest_for_current_platform(next_version_tag, Some("sig"), Some(0));
+        let info = build_update_info_from_manifest(staged, &stable_context());
+        assert!(info.has_update);
+        assert!(!info.in_rollout);
+        assert_eq!(info.rollout_percent, Some(0));
+    }
 }
@@ -2,7 +2,7 @@
 //!
 //! 提供 Kiro 用量查询的 Tauri 命令接口。
 
-use crate::database::dao::provider_pool::ProviderPoolDao;
+use crate::database::pool_storage::{lock_pool, pool_storage};
 use crate::database::DbConnection;
 use crate::models::provider_pool_model::{CredentialData, PoolProviderType};
 use crate::TokenCacheServiceState;
@@ -32,9 +32,9 @@ pub async fn get_kiro_usage(
 ) -> Result<UsageInfo, String> {
     // 1. 获取凭证信息
     let credential = {
-        let conn = db.lock().map_err(|e| e.to_string())?;
-        ProviderPoolDao::get_by_uuid(&conn, &credential_uuid)
-            .map_err(|e| e.to_string())?
+        let conn = lock_pool(&db)?;
+        pool_storage()
+            .get_by_uuid(&conn, &credential_uuid)?
             .ok_or_else(|| format!("凭证不存在: {credential_uuid}"))?
     };
 
@@ -37,8 +37,7 @@ fn arb_server_config() -> impl Strategy<Value = ServerConfig> {
         host,
         port,
         api_key,
-        tls: lime_core::config::TlsConfig::default(),
-        response_cache: lime_core::config::ResponseCacheSettings::default(),
+        ..ServerConfig::default()
     })
 }
 
@@ -60,6 +59,7 @@ fn arb_provider_config() -> impl Strategy<Value = ProviderConfig> {
                 credentials_path,
                 region,
                 project_id,
+                ..Default::default()
             },
         )
 }
@@ -79,6 +79,7 @@ fn arb_custom_provider_config() -> impl Strategy<Value = CustomProviderConfig> {
             enabled,
             api_key,
             base_url,
+            ..Default::default()
         })
 }
 
@@ -97,6 +98,9 @@ fn arb_providers_config() -> impl Strategy<Value = ProvidersConfig> {
             qwen,
             openai,
             claude,
+            endpoints: Default::default(),
+            endpoint_selection: Default::default(),
+            upstream_auth: Default::default(),
         })
 }
 
@@ -117,6 +121,9 @@ fn arb_routing_config() -> impl Strategy<Value = RoutingConfig> {
         .prop_map(|(default_provider, model_aliases)| RoutingConfig {
             default_provider,
             model_aliases,
+            model_deprecation: Default::default(),
+            size_routing: Default::default(),
+            time_windows: Default::default(),
         })
 }
 
@@ -355,8 +362,7 @@ fn arb_valid_server_config() -> impl Strategy<Value = ServerConfig> {
         host,
         port,
         api_key,
-        tls: lime_core::config::TlsConfig::default(),
-        response_cache: lime_core::config::ResponseCacheSettings::default(),
+        ..ServerConfig::default()
     })
 }
 
@@ -1,11 +1,352 @@
 //! 崩溃上报初始化与上报辅助（Sentry 协议兼容）
+//!
+//! 本地优先：panic 与前端异常先脱敏再保存到 `crash_reports` 目录，
+//! 由用户在设置页主动导出；只有 `upload_consent` 为 true 且配置了 DSN 时才会自动上传。
 
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use chrono::Utc;
 use lime_core::config::{Config, CrashReportingConfig};
-use serde_json::Value;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// 本地崩溃报告目录名（位于应用运行时目录下）
+const CRASH_REPORTS_DIR: &str = "crash_reports";
+
+/// 单个字段保留的最大字符数，避免超长 backtrace 撑大报告
+const MAX_FIELD_CHARS: usize = 32 * 1024;
+
+static LOCAL_STORE: OnceLock<LocalCrashStore> = OnceLock::new();
+static PROCESS_STARTED_AT: OnceLock<Instant> = OnceLock::new();
+
+/// 崩溃时的运行环境快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashContext {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub pid: u32,
+    pub uptime_secs: u64,
+    pub cpu_count: usize,
+    /// 可执行文件名（不含目录）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executable: Option<String>,
+}
+
+impl CrashContext {
+    fn capture() -> Self {
+        Self {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            pid: std::process::id(),
+            uptime_secs: PROCESS_STARTED_AT
+                .get()
+                .map(|started| started.elapsed().as_secs())
+                .unwrap_or_default(),
+            cpu_count: std::thread::available_parallelism()
+                .map(|count| count.get())
+                .unwrap_or(1),
+            executable: std::env::current_exe().ok().and_then(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+            }),
+        }
+    }
+}
+
+/// 本地崩溃报告（保存前已脱敏）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    pub created_at: String,
+    /// `panic` 或 `frontend`
+    pub origin: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backtrace: Option<String>,
+    pub context: CrashContext,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra: Option<Value>,
+}
+
+/// 崩溃报告列表项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReportSummary {
+    pub id: String,
+    pub created_at: String,
+    pub origin: String,
+    pub message: String,
+}
+
+/// 崩溃报告导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReportExportResult {
+    pub export_path: String,
+    pub report_count: usize,
+}
+
+fn truncate_chars(text: String) -> String {
+    if text.chars().count() <= MAX_FIELD_CHARS {
+        return text;
+    }
+    let mut truncated: String = text.chars().take(MAX_FIELD_CHARS).collect();
+    truncated.push_str("\n...[truncated]");
+    truncated
+}
+
+/// 脱敏：密钥与令牌、邮箱、IP 地址，以及用户主目录路径
+pub fn redact(text: &str) -> String {
+    let mut redacted = lime_core::logger::sanitize_log_message(text);
+    let patterns = [
+        (r"\b(?:sk|pk|rk)-[A-Za-z0-9_-]{12,}", "sk-***"),
+        (r"\bAIza[0-9A-Za-z_-]{20,}", "AIza***"),
+        (r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}", "<email>"),
+        (r"\b(?:\d{1,3}\.){3}\d{1,3}\b", "<ip>"),
+    ];
+    for (pattern, replacement) in patterns {
+        if let Ok(re) = Regex::new(pattern) {
+            redacted = re.replace_all(&redacted, replacement).to_string();
+        }
+    }
+    if let Some(home) = dirs::home_dir() {
+        let home = home.to_string_lossy().to_string();
+        if home.len() > 1 {
+            redacted = redacted.replace(&home, "~");
+        }
+    }
+    redacted
+}
+
+fn redact_value(value: Value) -> Value {
+    match value {
+        Value::String(text) => Value::String(redact(&text)),
+        Value::Array(items) => Value::Array(items.into_iter().map(redact_value).collect()),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, redact_value(value)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+impl CrashReport {
+    fn new(origin: &str, message: &str) -> Self {
+        let now = Utc::now();
+        let short_id = uuid::Uuid::new_v4().simple().to_string();
+        Self {
+            id: format!("crash-{}-{}", now.format("%Y%m%d%H%M%S"), &short_id[..8]),
+            created_at: now.to_rfc3339(),
+            origin: origin.to_string(),
+            message: message.to_string(),
+            location: None,
+            thread: None,
+            backtrace: None,
+            context: CrashContext::capture(),
+            extra: None,
+        }
+    }
+
+    /// 对所有自由文本字段做脱敏与截断
+    fn redacted(self) -> Self {
+        Self {
+            message: truncate_chars(redact(&self.message)),
+            location: self.location.map(|text| redact(&text)),
+            thread: self.thread,
+            backtrace: self.backtrace.map(|text| truncate_chars(redact(&text))),
+            extra: self.extra.map(redact_value),
+            ..self
+        }
+    }
+
+    fn summary(&self) -> CrashReportSummary {
+        CrashReportSummary {
+            id: self.id.clone(),
+            created_at: self.created_at.clone(),
+            origin: self.origin.clone(),
+            message: self.message.chars().take(200).collect(),
+        }
+    }
+}
+
+/// 本地崩溃报告存储
+#[derive(Debug, Clone)]
+pub struct LocalCrashStore {
+    dir: PathBuf,
+    max_reports: usize,
+}
+
+impl LocalCrashStore {
+    pub fn new(dir: PathBuf, max_reports: usize) -> Self {
+        Self {
+            dir,
+            max_reports: max_reports.max(1),
+        }
+    }
+
+    /// 默认目录下的存储（列表与导出在未启用本地采集时也可使用）
+    pub fn default_store() -> Self {
+        LOCAL_STORE.get().cloned().unwrap_or_else(|| {
+            Self::new(
+                lime_core::app_paths::best_effort_runtime_subdir(CRASH_REPORTS_DIR),
+                usize::MAX,
+            )
+        })
+    }
 
-/// 根据配置初始化 Sentry 客户端
+    fn report_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&self.dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| {
+                        path.extension().is_some_and(|ext| ext == "json")
+                            && path
+                                .file_name()
+                                .is_some_and(|name| name.to_string_lossy().starts_with("crash-"))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        // 文件名以时间开头，按名称排序即按时间排序
+        paths.sort();
+        paths
+    }
+
+    /// 脱敏后写入磁盘，并删除超出上限的旧报告
+    pub fn save(&self, report: CrashReport) -> Result<PathBuf, String> {
+        std::fs::create_dir_all(&self.dir).map_err(|e| format!("创建崩溃报告目录失败: {e}"))?;
+        let report = report.redacted();
+        let path = self.dir.join(format!("{}.json", report.id));
+        let content = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+        std::fs::write(&path, content).map_err(|e| format!("写入崩溃报告失败: {e}"))?;
+
+        let paths = self.report_paths();
+        if paths.len() > self.max_reports {
+            for old in &paths[..paths.len() - self.max_reports] {
+                let _ = std::fs::remove_file(old);
+            }
+        }
+        Ok(path)
+    }
+
+    fn read(path: &Path) -> Option<CrashReport> {
+        let content = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// 按时间倒序列出报告
+    pub fn list(&self) -> Vec<CrashReportSummary> {
+        self.report_paths()
+            .iter()
+            .rev()
+            .filter_map(|path| Self::read(path))
+            .map(|report| report.summary())
+            .collect()
+    }
+
+    /// 导出指定报告（`ids` 为空时导出全部）到一个 JSON 文件，导出前再次脱敏
+    pub fn export(
+        &self,
+        ids: Option<&[String]>,
+        output_dir: &Path,
+    ) -> Result<CrashReportExportResult, String> {
+        let reports: Vec<CrashReport> = self
+            .report_paths()
+            .iter()
+            .filter_map(|path| Self::read(path))
+            .filter(|report| match ids {
+                Some(ids) => ids.contains(&report.id),
+                None => true,
+            })
+            .map(CrashReport::redacted)
+            .collect();
+        if reports.is_empty() {
+            return Err("没有可导出的崩溃报告".to_string());
+        }
+
+        std::fs::create_dir_all(output_dir).map_err(|e| format!("创建导出目录失败: {e}"))?;
+        let export_path = output_dir.join(format!(
+            "lime-crash-report-{}.json",
+            Utc::now().format("%Y%m%d-%H%M%S")
+        ));
+        let payload = json!({
+            "generated_at": Utc::now().to_rfc3339(),
+            "app_version": env!("CARGO_PKG_VERSION"),
+            "reports": reports,
+        });
+        let content = serde_json::to_string_pretty(&payload).map_err(|e| e.to_string())?;
+        std::fs::write(&export_path, content).map_err(|e| format!("写入导出文件失败: {e}"))?;
+
+        Ok(CrashReportExportResult {
+            export_path: export_path.to_string_lossy().to_string(),
+            report_count: reports.len(),
+        })
+    }
+
+    /// 删除全部报告，返回删除数量
+    pub fn clear(&self) -> usize {
+        self.report_paths()
+            .iter()
+            .filter(|path| std::fs::remove_file(path).is_ok())
+            .count()
+    }
+}
+
+/// 安装 panic hook：在原有 hook（含 Sentry）之前把 panic 写入本地
+fn install_panic_hook(store: LocalCrashStore) {
+    if LOCAL_STORE.set(store).is_err() {
+        return;
+    }
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|text| text.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "<non-string panic payload>".to_string());
+        let mut report = CrashReport::new("panic", &message);
+        report.location = info.location().map(|location| {
+            format!(
+                "{}:{}:{}",
+                location.file(),
+                location.line(),
+                location.column()
+            )
+        });
+        report.thread = std::thread::current().name().map(str::to_string);
+        report.backtrace = Some(std::backtrace::Backtrace::force_capture().to_string());
+        if let Some(store) = LOCAL_STORE.get() {
+            if let Err(error) = store.save(report) {
+                eprintln!("[CrashReporting] 保存本地崩溃报告失败: {error}");
+            }
+        }
+        previous(info);
+    }));
+}
+
+/// 根据配置初始化本地采集与 Sentry 客户端
 pub fn init_from_config(config: &Config) -> Option<sentry::ClientInitGuard> {
-    init(&config.crash_reporting)
+    let _ = PROCESS_STARTED_AT.set(Instant::now());
+    let guard = init(&config.crash_reporting);
+    // 在 Sentry 之后安装，保证本地保存先于上传执行
+    let crash_config = &config.crash_reporting;
+    if crash_config.enabled && crash_config.local_capture {
+        install_panic_hook(LocalCrashStore::new(
+            lime_core::app_paths::best_effort_runtime_subdir(CRASH_REPORTS_DIR),
+            crash_config.max_local_reports,
+        ));
+    }
+    guard
 }
 
 fn init(config: &CrashReportingConfig) -> Option<sentry::ClientInitGuard> {
@@ -14,6 +355,11 @@ fn init(config: &CrashReportingConfig) -> Option<sentry::ClientInitGuard> {
         return None;
     }
 
+    if !config.upload_consent {
+        tracing::info!("[CrashReporting] 未同意自动上传，崩溃报告仅保存在本地");
+        return None;
+    }
+
     let dsn = config
         .dsn
         .as_ref()
@@ -68,6 +414,19 @@ pub fn capture_frontend_report(
         return;
     }
 
+    if let Some(store) = LOCAL_STORE.get() {
+        let mut report = CrashReport::new("frontend", message);
+        report.extra = Some(json!({
+            "component": component,
+            "workflow_step": workflow_step,
+            "creation_mode": creation_mode,
+            "metadata": metadata.clone(),
+        }));
+        if let Err(error) = store.save(report) {
+            tracing::warn!("[CrashReporting] 保存前端崩溃报告失败: {}", error);
+        }
+    }
+
     sentry::with_scope(
         |scope| {
             scope.set_tag("origin", "frontend");
@@ -89,3 +448,46 @@ pub fn capture_frontend_report(
         },
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_secrets_and_contact_details() {
+        let input = "call failed: Bearer abc.def key=sk-ant-0123456789abcdef from 10.1.2.3 user a.b@example.com";
+        let output = redact(input);
+        assert!(!output.contains("abc.def"));
+        assert!(!output.contains("0123456789abcdef"));
+        assert!(!output.contains("10.1.2.3"));
+        assert!(!output.contains("a.b@example.com"));
+        assert!(output.contains("<ip>"));
+        assert!(output.contains("<email>"));
+    }
+
+    #[test]
+    fn test_store_prunes_and_exports_redacted_reports() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalCrashStore::new(dir.path().join("reports"), 2);
+        for index in 0..3 {
+            let mut report = CrashReport::new("panic", &format!("boom {index} api_key=secret123"));
+            report.id = format!("crash-2026010100000{index}-test");
+            store.save(report).unwrap();
+        }
+
+        let listed = store.list();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].id, "crash-20260101000002-test");
+        assert!(!listed[0].message.contains("secret123"));
+
+        let ids = vec!["crash-20260101000001-test".to_string()];
+        let exported = store.export(Some(&ids), &dir.path().join("out")).unwrap();
+        assert_eq!(exported.report_count, 1);
+        let content = std::fs::read_to_string(&exported.export_path).unwrap();
+        assert!(content.contains("boom 1"));
+        assert!(!content.contains("secret123"));
+
+        assert_eq!(store.clear(), 2);
+        assert!(store.export(None, dir.path()).is_err());
+    }
+}
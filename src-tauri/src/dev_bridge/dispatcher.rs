@@ -164,7 +164,7 @@ pub async fn handle_command(
 mod tests {
     use super::*;
     use crate::commands::content_cmd::{ContentDetail, ContentListItem};
-    use lime_core::{config::Config, database::schema::create_tables};
+    use lime_core::{config::Config, database::schema_migrations::run_pending_migrations};
     use rusqlite::Connection;
     use std::sync::{Arc, Mutex};
     use tempfile::TempDir;
@@ -172,7 +172,7 @@ mod tests {
 
     fn make_test_db() -> crate::database::DbConnection {
         let conn = Connection::open_in_memory().unwrap();
-        create_tables(&conn).unwrap();
+        run_pending_migrations(&conn).unwrap();
         Arc::new(Mutex::new(conn))
     }
 
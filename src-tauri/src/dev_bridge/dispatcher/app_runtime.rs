@@ -42,6 +42,7 @@ pub(super) async fn try_handle(
             let status = { state.server.read().await.status() };
             serde_json::to_value(status)?
         }
+        "get_app_api_info" => serde_json::to_value(crate::app::commands::AppApiInfo::current())?,
         "get_server_diagnostics" => {
             let (status, capability_routing, response_cache, request_dedup, idempotency) = {
                 let server = state.server.read().await;
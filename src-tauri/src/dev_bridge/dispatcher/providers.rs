@@ -111,10 +111,10 @@ pub(super) async fn try_handle(
         }
         "get_provider_pool_credentials" => {
             if let Some(db) = &state.db {
-                let conn = db.lock().map_err(|e| e.to_string())?;
-                let credentials =
-                    crate::database::dao::provider_pool::ProviderPoolDao::get_all(&conn)
-                        .unwrap_or_default();
+                let conn = crate::database::pool_storage::lock_pool(db)?;
+                let credentials = crate::database::pool_storage::pool_storage()
+                    .get_all(&conn)
+                    .unwrap_or_default();
                 serde_json::to_value(credentials)?
             } else {
                 serde_json::json!([])
@@ -1,4 +1,4 @@
-use super::{args_or_default, require_app_handle};
+use super::{args_or_default, get_string_arg, require_app_handle};
 use crate::dev_bridge::DevBridgeState;
 use serde_json::Value as JsonValue;
 use tauri::Manager;
@@ -10,7 +10,15 @@ pub(super) async fn try_handle(
     cmd: &str,
     args: Option<&JsonValue>,
 ) -> Result<Option<JsonValue>, DynError> {
-    if cmd != "sync_tray_model_shortcuts" {
+    if !matches!(
+        cmd,
+        "sync_tray_model_shortcuts"
+            | "toggle_provider_pause"
+            | "list_config_profiles"
+            | "save_config_profile"
+            | "delete_config_profile"
+            | "switch_config_profile"
+    ) {
         return Ok(None);
     }
 
@@ -70,6 +78,33 @@ pub(super) async fn try_handle(
                 Err(error) => return Err(error.into()),
             }
         }
+        "toggle_provider_pause" => {
+            let args = args_or_default(args);
+            let provider_type = get_string_arg(&args, "providerType", "provider_type")?;
+            JsonValue::Bool(crate::tray::toggle_provider_pause(&app_handle, &provider_type).await?)
+        }
+        "list_config_profiles" => serde_json::to_value(crate::config::list_config_profiles())?,
+        "save_config_profile" => {
+            let args = args_or_default(args);
+            let name = get_string_arg(&args, "name", "name")?;
+            let current = { state.server.read().await.config.clone() };
+            crate::config::save_config_profile(&name, &current)?;
+            crate::tray::refresh_tray_controls(&app_handle).await;
+            JsonValue::Null
+        }
+        "delete_config_profile" => {
+            let args = args_or_default(args);
+            let name = get_string_arg(&args, "name", "name")?;
+            let deleted = crate::config::delete_config_profile(&name)?;
+            crate::tray::refresh_tray_controls(&app_handle).await;
+            JsonValue::Bool(deleted)
+        }
+        "switch_config_profile" => {
+            let args = args_or_default(args);
+            let name = get_string_arg(&args, "name", "name")?;
+            crate::tray::apply_config_profile(&app_handle, &name).await?;
+            JsonValue::Null
+        }
         _ => unreachable!("已通过前置判断过滤托盘命令"),
     };
 
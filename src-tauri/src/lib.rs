@@ -41,6 +41,7 @@ pub use lime_mcp as mcp;
 pub mod agent;
 pub mod agent_tools;
 pub mod app;
+pub mod cli;
 pub mod plugin;
 pub mod screenshot;
 pub mod services;
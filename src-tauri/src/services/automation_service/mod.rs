@@ -209,6 +209,10 @@ impl AutomationService {
             loop {
                 tokio::select! {
                     _ = ticker.tick() => {
+                        // 多实例共享存储时仅由主实例执行定时任务
+                        if !lime_core::cluster::is_leader() {
+                            continue;
+                        }
                         let result = Self::execute_due_jobs(&self_ref, &db, &app_handle).await;
                         if let Err(error) = result {
                             tracing::warn!("[Automation] 轮询执行失败: {}", error);
@@ -427,7 +427,7 @@ impl ResolvedReadiness {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::database::schema::create_tables;
+    use crate::database::schema_migrations::run_pending_migrations;
     use lime_core::database::dao::api_key_provider::ApiProviderType;
     use lime_core::database::dao::provider_pool::ProviderPoolDao;
     use lime_core::models::provider_pool_model::{
@@ -439,7 +439,7 @@ mod tests {
 
     fn setup_db() -> DbConnection {
         let conn = Connection::open_in_memory().expect("创建内存数据库失败");
-        create_tables(&conn).expect("创建数据表失败");
+        run_pending_migrations(&conn).expect("创建数据表失败");
         Arc::new(Mutex::new(conn))
     }
 
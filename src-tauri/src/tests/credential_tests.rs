@@ -364,6 +364,7 @@ proptest! {
             check_interval: Duration::from_secs(60),
             failure_threshold,
             recovery_threshold: 1,
+            ..Default::default()
         };
         let checker = HealthChecker::new(config);
         let pool = CredentialPool::new(provider);
@@ -0,0 +1,268 @@
+//! 托盘快捷操作模块
+//!
+//! 托盘菜单与 Tauri 命令共用的后端操作：
+//! - 暂停/恢复整个 Provider
+//! - 切换配置方案
+//! - 复制网关地址和 API Key
+//! - 根据类型化应用事件同步托盘状态
+
+use super::format::{format_api_address, format_api_credentials};
+use super::state::{TrayIconStatus, TrayProviderToggle};
+use crate::app::AppState;
+use crate::commands::provider_pool_cmd::ProviderPoolServiceState;
+use crate::config::{self, GlobalConfigManagerState};
+use crate::database::DbConnection;
+use crate::TrayManagerState;
+use lime_core::app_events::{
+    publish_app_event, AppEvent, AppEventEnvelope, ConfigEvent, PoolEvent, ServerEvent,
+    APP_EVENT_CHANNEL,
+};
+use tauri::{AppHandle, Listener, Manager, Runtime};
+use tracing::{debug, info, warn};
+
+/// 复制文本到系统剪贴板
+pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| format!("访问剪贴板失败: {e}"))?;
+    clipboard
+        .set_text(text)
+        .map_err(|e| format!("写入剪贴板失败: {e}"))
+}
+
+/// 汇总凭证池中各 Provider 的暂停状态
+pub fn collect_provider_toggles<R: Runtime>(app: &AppHandle<R>) -> Vec<TrayProviderToggle> {
+    let (Some(db), Some(pool)) = (
+        app.try_state::<DbConnection>(),
+        app.try_state::<ProviderPoolServiceState>(),
+    ) else {
+        return Vec::new();
+    };
+
+    let paused = match pool.0.paused_provider_types(&db) {
+        Ok(paused) => paused,
+        Err(e) => {
+            warn!("[托盘] 读取 Provider 暂停状态失败: {}", e);
+            Default::default()
+        }
+    };
+    match pool.0.get_overview(&db) {
+        Ok(overview) => overview
+            .into_iter()
+            .filter(|item| item.stats.total_count > 0)
+            .map(|item| TrayProviderToggle {
+                paused: paused.contains(&item.provider_type),
+                provider_type: item.provider_type,
+                total_credentials: item.stats.total_count,
+            })
+            .collect(),
+        Err(e) => {
+            warn!("[托盘] 读取凭证池概览失败: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// 刷新托盘中的 Provider 开关与配置方案列表
+pub async fn refresh_tray_controls<R: Runtime>(app: &AppHandle<R>) {
+    let Some(tray_state) = app.try_state::<TrayManagerState<R>>() else {
+        return;
+    };
+    let tray_guard = tray_state.0.read().await;
+    let Some(tray_manager) = tray_guard.as_ref() else {
+        return;
+    };
+
+    let mut current_state = tray_manager.get_state().await;
+    current_state.provider_toggles = collect_provider_toggles(app);
+    current_state.config_profiles = config::list_config_profiles();
+    if let Err(e) = tray_manager.update_state(current_state).await {
+        warn!("[托盘] 刷新托盘控制项失败: {}", e);
+    }
+}
+
+/// 暂停或恢复整个 Provider（切换当前状态），返回切换后是否处于暂停状态
+pub async fn toggle_provider_pause<R: Runtime>(
+    app: &AppHandle<R>,
+    provider_type: &str,
+) -> Result<bool, String> {
+    let db = app
+        .try_state::<DbConnection>()
+        .ok_or_else(|| "数据库未初始化".to_string())?;
+    let pool = app
+        .try_state::<ProviderPoolServiceState>()
+        .ok_or_else(|| "凭证池服务未初始化".to_string())?;
+
+    let currently_paused = collect_provider_toggles(app)
+        .into_iter()
+        .find(|toggle| toggle.provider_type == provider_type)
+        .map(|toggle| toggle.paused)
+        .ok_or_else(|| format!("凭证池中没有 {provider_type} 类型的凭证"))?;
+    let paused = !currently_paused;
+
+    let affected = pool.0.set_provider_paused(&db, provider_type, paused)?;
+    info!(
+        "[托盘] Provider {} 已{}，影响 {} 个凭证",
+        provider_type,
+        if paused { "暂停" } else { "恢复" },
+        affected
+    );
+    publish_app_event(AppEvent::Pool(PoolEvent::ProviderPaused {
+        provider_type: provider_type.to_string(),
+        paused,
+        affected,
+    }));
+
+    refresh_tray_controls(app).await;
+    Ok(paused)
+}
+
+/// 应用配置方案：校验后写入当前配置并通知观察者
+pub async fn apply_config_profile<R: Runtime>(
+    app: &AppHandle<R>,
+    name: &str,
+) -> Result<(), String> {
+    let profile = config::load_config_profile(name).map_err(|e| e.to_string())?;
+
+    if !crate::app::is_valid_bind_host(&profile.server.host.to_lowercase()) {
+        return Err(format!(
+            "配置方案 {name} 的监听地址无效: {}",
+            profile.server.host
+        ));
+    }
+    if profile.remote_management.allow_remote {
+        return Err("安全限制：不允许开启远程管理功能".to_string());
+    }
+
+    if let Some(state) = app.try_state::<AppState>() {
+        state.write().await.config = profile.clone();
+    }
+    match app.try_state::<GlobalConfigManagerState>() {
+        Some(manager) => {
+            manager
+                .0
+                .save_config_from(&profile, config::ConfigAuditSource::Import)
+                .await?
+        }
+        None => config::save_config_with_source(&profile, config::ConfigAuditSource::Import)
+            .map_err(|e| e.to_string())?,
+    }
+    crate::services::environment_service::apply_configured_environment(&profile).await;
+
+    info!("[托盘] 已切换到配置方案: {}", name);
+    publish_app_event(AppEvent::Config(ConfigEvent::ProfileApplied {
+        name: name.to_string(),
+    }));
+
+    if let Some(tray_state) = app.try_state::<TrayManagerState<R>>() {
+        let tray_guard = tray_state.0.read().await;
+        if let Some(tray_manager) = tray_guard.as_ref() {
+            let mut current_state = tray_manager.get_state().await;
+            current_state.active_config_profile = name.to_string();
+            current_state.config_profiles = config::list_config_profiles();
+            if let Err(e) = tray_manager.update_state(current_state).await {
+                warn!("[托盘] 更新当前配置方案失败: {}", e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 生成网关地址和 API Key 文本（服务器未运行时返回错误）
+pub async fn api_credentials_text<R: Runtime>(app: &AppHandle<R>) -> Result<String, String> {
+    let state = app
+        .try_state::<AppState>()
+        .ok_or_else(|| "应用状态未初始化".to_string())?;
+    let server = state.read().await;
+    if !server.running {
+        return Err("服务器未运行".to_string());
+    }
+
+    let host = server
+        .running_host
+        .clone()
+        .unwrap_or_else(|| server.config.server.host.clone());
+    let api_key = server
+        .running_api_key
+        .clone()
+        .unwrap_or_else(|| server.config.server.api_key.clone());
+    Ok(format_api_credentials(
+        &format_api_address(&host, server.config.server.port),
+        &api_key,
+    ))
+}
+
+/// 订阅类型化应用事件，保持托盘状态与后端一致
+pub fn register_app_event_sync<R: Runtime>(app: &AppHandle<R>) {
+    let app_handle = app.clone();
+    app.listen(APP_EVENT_CHANNEL, move |event| {
+        let Ok(envelope) = serde_json::from_str::<AppEventEnvelope>(event.payload()) else {
+            return;
+        };
+        let needs_refresh = match &envelope.event {
+            AppEvent::Pool(PoolEvent::CredentialUsed { .. })
+            | AppEvent::Usage(_)
+            | AppEvent::Stats(_) => false,
+            AppEvent::Pool(_) | AppEvent::Config(_) => true,
+            AppEvent::Server(server_event) => {
+                let app_handle = app_handle.clone();
+                let server_event = server_event.clone();
+                tauri::async_runtime::spawn(async move {
+                    sync_server_event(&app_handle, server_event).await;
+                });
+                false
+            }
+        };
+        if needs_refresh {
+            debug!("[托盘] 收到应用事件 seq={}，刷新托盘控制项", envelope.seq);
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                refresh_tray_controls(&app_handle).await;
+            });
+        }
+    });
+}
+
+async fn sync_server_event<R: Runtime>(app: &AppHandle<R>, event: ServerEvent) {
+    let Some(tray_state) = app.try_state::<TrayManagerState<R>>() else {
+        return;
+    };
+    let tray_guard = tray_state.0.read().await;
+    let Some(tray_manager) = tray_guard.as_ref() else {
+        return;
+    };
+
+    let mut current_state = tray_manager.get_state().await;
+    match event {
+        ServerEvent::Started { host, port } => {
+            current_state.server_running = true;
+            current_state.server_address = format!("{host}:{port}");
+        }
+        ServerEvent::Stopped => {
+            current_state.server_running = false;
+            current_state.server_address = String::new();
+        }
+        // 最终地址以随后的 Started 事件为准
+        ServerEvent::PortFallback { .. } | ServerEvent::TookOver { .. } => return,
+        ServerEvent::AuthLockout { .. } | ServerEvent::KeyRotation { .. } => return,
+        // 降级模式期间显示警告图标，退出后按凭证状态重新计算
+        ServerEvent::DegradedMode { active: true, .. } => {
+            current_state.icon_status = TrayIconStatus::Warning;
+            if let Err(e) = tray_manager.update_state(current_state).await {
+                warn!("[托盘] 同步服务器状态失败: {}", e);
+            }
+            return;
+        }
+        ServerEvent::DegradedMode { active: false, .. } => {}
+    }
+    current_state.icon_status = if !current_state.server_running {
+        TrayIconStatus::Stopped
+    } else if current_state.total_credentials > 0 && current_state.available_credentials == 0 {
+        TrayIconStatus::Error
+    } else if current_state.available_credentials < current_state.total_credentials {
+        TrayIconStatus::Warning
+    } else {
+        TrayIconStatus::Running
+    };
+    if let Err(e) = tray_manager.update_state(current_state).await {
+        warn!("[托盘] 同步服务器状态失败: {}", e);
+    }
+}
@@ -14,7 +14,8 @@ use tauri::{
 
 pub use lime_core::tray_menu_meta::menu_ids;
 pub use lime_core::tray_menu_meta::{
-    build_quick_model_item_id, get_menu_item_ids, parse_server_address,
+    build_config_profile_item_id, build_provider_toggle_item_id, build_quick_model_item_id,
+    get_menu_item_ids, parse_server_address,
 };
 
 /// 托盘菜单构建错误
@@ -88,12 +89,91 @@ fn build_quick_model_submenu<R: Runtime>(
     Ok(Some(submenu))
 }
 
+fn build_provider_toggle_submenu<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &TrayStateSnapshot,
+) -> Result<Option<Submenu<R>>, MenuBuildError> {
+    if state.provider_toggles.is_empty() {
+        return Ok(None);
+    }
+
+    let mut toggle_items: Vec<CheckMenuItem<R>> = Vec::new();
+    for toggle in &state.provider_toggles {
+        let label = format!("{} ({})", toggle.provider_type, toggle.total_credentials);
+        let item = CheckMenuItem::with_id(
+            app,
+            build_provider_toggle_item_id(&toggle.provider_type),
+            &label,
+            true,
+            toggle.paused,
+            None::<&str>,
+        )
+        .map_err(|e| MenuBuildError::MenuItemError(e.to_string()))?;
+        toggle_items.push(item);
+    }
+
+    let item_refs: Vec<&dyn IsMenuItem<R>> = toggle_items
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<R>)
+        .collect();
+
+    let submenu = Submenu::with_id_and_items(
+        app,
+        menu_ids::PROVIDER_TOGGLE_ROOT,
+        "暂停 Provider",
+        true,
+        &item_refs,
+    )
+    .map_err(|e| MenuBuildError::MenuItemError(e.to_string()))?;
+
+    Ok(Some(submenu))
+}
+
+fn build_config_profile_submenu<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &TrayStateSnapshot,
+) -> Result<Option<Submenu<R>>, MenuBuildError> {
+    if state.config_profiles.is_empty() {
+        return Ok(None);
+    }
+
+    let mut profile_items: Vec<CheckMenuItem<R>> = Vec::new();
+    for profile in &state.config_profiles {
+        let item = CheckMenuItem::with_id(
+            app,
+            build_config_profile_item_id(profile),
+            profile,
+            true,
+            *profile == state.active_config_profile,
+            None::<&str>,
+        )
+        .map_err(|e| MenuBuildError::MenuItemError(e.to_string()))?;
+        profile_items.push(item);
+    }
+
+    let item_refs: Vec<&dyn IsMenuItem<R>> = profile_items
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<R>)
+        .collect();
+
+    let submenu = Submenu::with_id_and_items(
+        app,
+        menu_ids::CONFIG_PROFILE_ROOT,
+        "切换配置方案",
+        true,
+        &item_refs,
+    )
+    .map_err(|e| MenuBuildError::MenuItemError(e.to_string()))?;
+
+    Ok(Some(submenu))
+}
+
 /// 构建托盘菜单
 ///
 /// 根据当前状态快照构建完整的托盘菜单，包含：
 /// - 状态信息（服务器状态、凭证状态、请求统计）
-/// - 服务器控制（启动/停止、刷新 Token、健康检查）
-/// - 快捷工具（打开主窗口、复制 API 地址、打开日志目录）
+/// - 服务器控制（启动/停止、刷新 Token、健康检查、暂停 Provider、切换配置方案）
+/// - 快捷工具（打开主窗口、复制 API 地址/密钥、打开日志目录）
 /// - 设置（开机自启）
 /// - 退出
 ///
@@ -211,6 +291,9 @@ pub fn build_tray_menu<R: Runtime>(
     )
     .map_err(|e| MenuBuildError::MenuItemError(e.to_string()))?;
 
+    let provider_toggle_submenu = build_provider_toggle_submenu(app, state)?;
+    let config_profile_submenu = build_config_profile_submenu(app, state)?;
+
     // === 分隔符 2 ===
     let separator_2 = PredefinedMenuItem::separator(app)
         .map_err(|e| MenuBuildError::MenuItemError(e.to_string()))?;
@@ -229,6 +312,15 @@ pub fn build_tray_menu<R: Runtime>(
     )
     .map_err(|e| MenuBuildError::MenuItemError(e.to_string()))?;
 
+    let copy_api_credentials = MenuItem::with_id(
+        app,
+        menu_ids::COPY_API_CREDENTIALS,
+        "复制网关地址和密钥",
+        state.server_running,
+        None::<&str>,
+    )
+    .map_err(|e| MenuBuildError::MenuItemError(e.to_string()))?;
+
     let open_log_dir = MenuItem::with_id(
         app,
         menu_ids::OPEN_LOG_DIR,
@@ -276,9 +368,18 @@ pub fn build_tray_menu<R: Runtime>(
         &stop_server,
         &refresh_tokens,
         &health_check,
-        &separator_2,
+    ]);
+    if let Some(submenu) = provider_toggle_submenu.as_ref() {
+        items.push(submenu);
+    }
+    if let Some(submenu) = config_profile_submenu.as_ref() {
+        items.push(submenu);
+    }
+    items.extend([
+        &separator_2 as &dyn IsMenuItem<R>,
         &open_window,
         &copy_api_address,
+        &copy_api_credentials,
         &open_log_dir,
         &separator_3,
         &auto_start,
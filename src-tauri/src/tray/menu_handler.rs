@@ -6,17 +6,20 @@
 //! - 3.1, 3.2, 3.3, 3.4: 服务器控制事件处理
 //! - 4.1, 4.2, 4.3, 4.4: 快捷工具事件处理
 //! - 5.1, 5.2: 设置切换事件处理
+//! - Provider 暂停、配置方案切换、复制地址和密钥：直接在后端执行
 
+use super::actions::{
+    api_credentials_text, apply_config_profile, copy_to_clipboard, toggle_provider_pause,
+};
 use super::menu::menu_ids;
-use lime_core::tray_menu_meta::parse_quick_model_item_id;
+use lime_core::tray_menu_meta::{
+    parse_config_profile_item_id, parse_provider_toggle_item_id, parse_quick_model_item_id,
+};
 use serde::Serialize;
 use tauri::{AppHandle, Emitter, Manager, Runtime};
 use tauri_plugin_autostart::ManagerExt;
 use tracing::{debug, error, info, warn};
 
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
-
 /// 菜单事件类型
 ///
 /// 用于前端监听的事件名称
@@ -58,6 +61,16 @@ pub fn handle_menu_event<R: Runtime>(app: &AppHandle<R>, menu_id: &str) {
         return;
     }
 
+    if let Some(provider_type) = parse_provider_toggle_item_id(menu_id) {
+        handle_provider_toggle(app, provider_type);
+        return;
+    }
+
+    if let Some(profile) = parse_config_profile_item_id(menu_id) {
+        handle_config_profile_selected(app, profile);
+        return;
+    }
+
     match menu_id {
         // === 服务器控制 ===
         menu_ids::START_SERVER => handle_start_server(app),
@@ -68,6 +81,7 @@ pub fn handle_menu_event<R: Runtime>(app: &AppHandle<R>, menu_id: &str) {
         // === 快捷工具 ===
         menu_ids::OPEN_WINDOW => handle_open_window(app),
         menu_ids::COPY_API_ADDRESS => handle_copy_api_address(app),
+        menu_ids::COPY_API_CREDENTIALS => handle_copy_api_credentials(app),
         menu_ids::OPEN_LOG_DIR => handle_open_log_dir(app),
         menu_ids::QUIT => handle_quit(app),
 
@@ -213,46 +227,9 @@ fn handle_copy_api_address<R: Runtime>(app: &AppHandle<R>) {
 
                 if state.server_running && !state.server_address.is_empty() {
                     let api_address = format!("http://{}", state.server_address);
-
-                    // 使用剪贴板 API 复制地址
-                    #[cfg(target_os = "macos")]
-                    {
-                        let _ = std::process::Command::new("pbcopy")
-                            .stdin(std::process::Stdio::piped())
-                            .spawn()
-                            .and_then(|mut child| {
-                                use std::io::Write;
-                                if let Some(stdin) = child.stdin.as_mut() {
-                                    stdin.write_all(api_address.as_bytes())?;
-                                }
-                                child.wait()
-                            });
-                        info!("[托盘] API 地址已复制到剪贴板: {}", api_address);
-                    }
-
-                    #[cfg(target_os = "windows")]
-                    {
-                        let _ = std::process::Command::new("cmd")
-                            .args(["/C", &format!("echo {} | clip", api_address)])
-                            .creation_flags(0x08000000) // CREATE_NO_WINDOW
-                            .spawn();
-                        info!("[托盘] API 地址已复制到剪贴板: {}", api_address);
-                    }
-
-                    #[cfg(target_os = "linux")]
-                    {
-                        let _ = std::process::Command::new("xclip")
-                            .args(["-selection", "clipboard"])
-                            .stdin(std::process::Stdio::piped())
-                            .spawn()
-                            .and_then(|mut child| {
-                                use std::io::Write;
-                                if let Some(stdin) = child.stdin.as_mut() {
-                                    stdin.write_all(api_address.as_bytes())?;
-                                }
-                                child.wait()
-                            });
-                        info!("[托盘] API 地址已复制到剪贴板: {}", api_address);
+                    match copy_to_clipboard(&api_address) {
+                        Ok(()) => info!("[托盘] API 地址已复制到剪贴板: {}", api_address),
+                        Err(e) => error!("[托盘] 复制 API 地址失败: {}", e),
                     }
                 } else {
                     warn!("[托盘] 服务器未运行，无法复制 API 地址");
@@ -262,6 +239,46 @@ fn handle_copy_api_address<R: Runtime>(app: &AppHandle<R>) {
     });
 }
 
+/// 处理复制网关地址和 API Key 事件
+fn handle_copy_api_credentials<R: Runtime>(app: &AppHandle<R>) {
+    info!("[托盘] 用户请求复制网关地址和密钥");
+
+    let app_clone = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match api_credentials_text(&app_clone).await {
+            Ok(text) => match copy_to_clipboard(&text) {
+                Ok(()) => info!("[托盘] 网关地址和密钥已复制到剪贴板"),
+                Err(e) => error!("[托盘] 复制网关地址和密钥失败: {}", e),
+            },
+            Err(e) => warn!("[托盘] 无法复制网关地址和密钥: {}", e),
+        }
+    });
+}
+
+/// 处理 Provider 暂停开关事件
+fn handle_provider_toggle<R: Runtime>(app: &AppHandle<R>, provider_type: String) {
+    info!("[托盘] 用户请求切换 Provider 暂停状态: {}", provider_type);
+
+    let app_clone = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = toggle_provider_pause(&app_clone, &provider_type).await {
+            error!("[托盘] 切换 Provider 暂停状态失败: {}", e);
+        }
+    });
+}
+
+/// 处理配置方案切换事件
+fn handle_config_profile_selected<R: Runtime>(app: &AppHandle<R>, profile: String) {
+    info!("[托盘] 用户请求切换配置方案: {}", profile);
+
+    let app_clone = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = apply_config_profile(&app_clone, &profile).await {
+            error!("[托盘] 切换配置方案失败: {}", e);
+        }
+    });
+}
+
 /// 处理打开日志目录事件
 ///
 /// # Requirements
@@ -309,6 +326,9 @@ fn handle_quit<R: Runtime>(app: &AppHandle<R>) {
         warn!("[托盘] 发送停止服务器事件失败: {}", e);
     }
 
+    // 释放主实例租约，让其他实例尽快接管后台任务
+    lime_core::cluster::release_leadership();
+
     // 退出应用
     app.exit(0);
 }
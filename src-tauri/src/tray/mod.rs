@@ -6,7 +6,9 @@
 //! - 菜单事件处理
 //! - 托盘图标点击事件处理
 //! - 状态同步
+//! - 快捷操作（暂停 Provider、切换配置方案、复制地址和密钥）
 
+mod actions;
 mod events;
 mod format;
 mod manager;
@@ -15,6 +17,7 @@ mod menu_handler;
 mod state;
 mod sync;
 
+pub use actions::*;
 pub use events::*;
 pub use format::*;
 pub use manager::*;
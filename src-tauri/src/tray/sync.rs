@@ -78,6 +78,9 @@ impl<R: Runtime> TraySynchronizer<R> {
             current_model: current_state.current_model,
             current_theme_label: current_state.current_theme_label,
             quick_model_groups: current_state.quick_model_groups,
+            provider_toggles: current_state.provider_toggles,
+            config_profiles: current_state.config_profiles,
+            active_config_profile: current_state.active_config_profile,
         };
 
         // 更新托盘状态
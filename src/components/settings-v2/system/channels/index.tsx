@@ -129,6 +129,8 @@ const DEFAULT_GATEWAY: GatewayConfig = {
     local_host: "127.0.0.1",
     local_port: 3000,
     cloudflare: {},
+    ngrok: {},
+    require_strong_api_key: true,
   },
 };
 
@@ -659,8 +661,11 @@ function GatewayTunnelPanel({
 }) {
   const tunnel = config.tunnel ?? DEFAULT_GATEWAY.tunnel!;
   const cloudflare = tunnel.cloudflare ?? {};
+  const ngrok = tunnel.ngrok ?? {};
+  const isNgrok = (tunnel.provider || "cloudflare").toLowerCase() === "ngrok";
   const [busyAction, setBusyAction] = useState<string | null>(null);
   const [output, setOutput] = useState("");
+  const [publicUrl, setPublicUrl] = useState<string | null>(null);
   const [feishuAccountId, setFeishuAccountId] = useState(
     defaultFeishuAccountId ?? "default",
   );
@@ -692,6 +697,17 @@ function GatewayTunnelPanel({
     });
   };
 
+  const patchNgrok = (
+    patch: Partial<NonNullable<NonNullable<GatewayConfig["tunnel"]>["ngrok"]>>,
+  ) => {
+    patchTunnel({
+      ngrok: {
+        ...ngrok,
+        ...patch,
+      },
+    });
+  };
+
   const runAction = async (
     action: string,
     executor: () => Promise<unknown>,
@@ -700,6 +716,11 @@ function GatewayTunnelPanel({
     try {
       const result = await executor();
       setOutput(JSON.stringify(result, null, 2));
+      if (result && typeof result === "object" && "public_base_url" in result) {
+        const url = (result as { public_base_url?: string | null })
+          .public_base_url;
+        setPublicUrl(url || null);
+      }
       if (action === "create" || action === "sync") {
         await onReloadConfig();
       }
@@ -747,7 +768,7 @@ function GatewayTunnelPanel({
             className="h-9 w-full rounded-md border bg-background px-3 text-sm"
           >
             <option value="cloudflare">cloudflare</option>
-            <option value="ngrok">ngrok（预留）</option>
+            <option value="ngrok">ngrok</option>
           </select>
         </label>
         <label className="space-y-1">
@@ -763,14 +784,14 @@ function GatewayTunnelPanel({
         </label>
         <label className="space-y-1">
           <span className="text-xs text-muted-foreground">
-            cloudflared 二进制（可选）
+            {isNgrok ? "ngrok" : "cloudflared"} 二进制（可选）
           </span>
           <input
             value={tunnel.binary_path || ""}
             onChange={(event) =>
               patchTunnel({ binary_path: event.target.value || undefined })
             }
-            placeholder="默认使用 PATH 中 cloudflared"
+            placeholder={`默认使用 PATH 中 ${isNgrok ? "ngrok" : "cloudflared"}`}
             className="h-9 w-full rounded-md border bg-background px-3 text-sm"
           />
         </label>
@@ -821,64 +842,108 @@ function GatewayTunnelPanel({
         </label>
       </div>
 
-      <div className="grid grid-cols-1 md:grid-cols-3 gap-3">
-        <label className="space-y-1">
-          <span className="text-xs text-muted-foreground">Tunnel Name</span>
-          <input
-            value={cloudflare.tunnel_name || ""}
-            onChange={(event) =>
-              patchCloudflare({ tunnel_name: event.target.value || undefined })
-            }
-            placeholder="lime-gateway"
-            className="h-9 w-full rounded-md border bg-background px-3 text-sm"
-          />
-        </label>
-        <label className="space-y-1">
-          <span className="text-xs text-muted-foreground">Tunnel ID</span>
-          <input
-            value={cloudflare.tunnel_id || ""}
-            onChange={(event) =>
-              patchCloudflare({ tunnel_id: event.target.value || undefined })
-            }
-            placeholder="uuid"
-            className="h-9 w-full rounded-md border bg-background px-3 text-sm"
-          />
-        </label>
-        <label className="space-y-1">
-          <span className="text-xs text-muted-foreground">DNS Name</span>
-          <input
-            value={cloudflare.dns_name || ""}
-            onChange={(event) =>
-              patchCloudflare({ dns_name: event.target.value || undefined })
-            }
-            placeholder="bot.example.com"
-            className="h-9 w-full rounded-md border bg-background px-3 text-sm"
-          />
-        </label>
-      </div>
-
-      <PasswordInput
-        label="Run Token（可选，优先于 tunnel_id）"
-        value={cloudflare.run_token || ""}
-        onChange={(value) => patchCloudflare({ run_token: value || undefined })}
-        placeholder="cloudflared tunnel run --token ..."
-      />
+      {publicUrl && (
+        <p className="text-xs text-muted-foreground">
+          公网地址：
+          <span className="font-mono text-foreground">{publicUrl}</span>
+        </p>
+      )}
 
-      <div>
-        <label className="block text-sm font-medium mb-1.5">
-          Credentials File（可选）
-        </label>
+      <label className="flex items-center gap-2 text-xs text-muted-foreground">
         <input
-          value={cloudflare.credentials_file || ""}
+          type="checkbox"
+          checked={tunnel.require_strong_api_key ?? true}
           onChange={(event) =>
-            patchCloudflare({
-              credentials_file: event.target.value || undefined,
-            })
+            patchTunnel({ require_strong_api_key: event.target.checked })
           }
-          placeholder="~/.cloudflared/<tunnel-id>.json"
-          className="w-full px-3 py-2 rounded-lg border bg-background text-sm focus:ring-2 focus:ring-primary/20 focus:border-primary outline-none"
         />
-      </div>
+        隧道暴露 API 服务器时要求强 API Key（推荐）
+      </label>
+
+      {isNgrok ? (
+        <div className="grid grid-cols-1 md:grid-cols-2 gap-3">
+          <PasswordInput
+            label="ngrok Authtoken"
+            value={ngrok.authtoken || ""}
+            onChange={(value) => patchNgrok({ authtoken: value || undefined })}
+            placeholder="从 ngrok 控制台复制"
+          />
+          <label className="space-y-1">
+            <span className="text-xs text-muted-foreground">
+              固定域名（可选）
+            </span>
+            <input
+              value={ngrok.domain || ""}
+              onChange={(event) =>
+                patchNgrok({ domain: event.target.value.trim() || undefined })
+              }
+              placeholder="lime.ngrok.app"
+              className="h-9 w-full rounded-md border bg-background px-3 text-sm"
+            />
+          </label>
+        </div>
+      ) : (
+        <>
+          <div className="grid grid-cols-1 md:grid-cols-3 gap-3">
+            <label className="space-y-1">
+              <span className="text-xs text-muted-foreground">Tunnel Name</span>
+              <input
+                value={cloudflare.tunnel_name || ""}
+                onChange={(event) =>
+                  patchCloudflare({ tunnel_name: event.target.value || undefined })
+                }
+                placeholder="lime-gateway"
+                className="h-9 w-full rounded-md border bg-background px-3 text-sm"
+              />
+            </label>
+            <label className="space-y-1">
+              <span className="text-xs text-muted-foreground">Tunnel ID</span>
+              <input
+                value={cloudflare.tunnel_id || ""}
+                onChange={(event) =>
+                  patchCloudflare({ tunnel_id: event.target.value || undefined })
+                }
+                placeholder="uuid"
+                className="h-9 w-full rounded-md border bg-background px-3 text-sm"
+              />
+            </label>
+            <label className="space-y-1">
+              <span className="text-xs text-muted-foreground">DNS Name</span>
+              <input
+                value={cloudflare.dns_name || ""}
+                onChange={(event) =>
+                  patchCloudflare({ dns_name: event.target.value || undefined })
+                }
+                placeholder="bot.example.com"
+                className="h-9 w-full rounded-md border bg-background px-3 text-sm"
+              />
+            </label>
+          </div>
+
+          <PasswordInput
+            label="Run Token（可选，优先于 tunnel_id）"
+            value={cloudflare.run_token || ""}
+            onChange={(value) => patchCloudflare({ run_token: value || undefined })}
+            placeholder="cloudflared tunnel run --token ..."
+          />
+
+          <div>
+            <label className="block text-sm font-medium mb-1.5">
+              Credentials File（可选）
+            </label>
+            <input
+              value={cloudflare.credentials_file || ""}
+              onChange={(event) =>
+                patchCloudflare({
+                  credentials_file: event.target.value || undefined,
+                })
+              }
+              placeholder="~/.cloudflared/<tunnel-id>.json"
+              className="w-full px-3 py-2 rounded-lg border bg-background text-sm focus:ring-2 focus:ring-primary/20 focus:border-primary outline-none"
+            />
+          </div>
+        </>
+      )}
 
       <div className="grid grid-cols-1 md:grid-cols-2 gap-3">
         <label className="space-y-1">
@@ -17,6 +17,7 @@ import {
   getUpdateNotificationMetrics,
   setUpdateCheckSettings,
   testUpdateWindow,
+  type UpdateChannel,
   type UpdateCheckConfig,
   type UpdateNotificationMetrics,
 } from "@/lib/api/appUpdate";
@@ -33,6 +34,8 @@ export function UpdateCheckSettings() {
     last_check_timestamp: 0,
     skipped_version: null,
     remind_later_until: null,
+    channel: "stable",
+    auto_download: false,
   });
   const [metrics, setMetrics] = useState<UpdateNotificationMetrics>({
     shown_count: 0,
@@ -93,6 +96,14 @@ export function UpdateCheckSettings() {
     saveSettings({ ...settings, check_interval_hours: hours });
   };
 
+  const handleChannelChange = (channel: UpdateChannel) => {
+    saveSettings({ ...settings, channel });
+  };
+
+  const handleToggleAutoDownload = () => {
+    saveSettings({ ...settings, auto_download: !settings.auto_download });
+  };
+
   const handleClearSkipped = () => {
     saveSettings({ ...settings, skipped_version: null });
   };
@@ -177,6 +188,52 @@ export function UpdateCheckSettings() {
           </div>
         </div>
 
+        {/* 更新通道 */}
+        <div className="p-3 rounded-lg border">
+          <div className="text-sm font-medium mb-1">更新通道</div>
+          <div className="text-xs text-muted-foreground mb-2">
+            Beta 通道会提前收到预发布版本，可能不够稳定
+          </div>
+          <div className="flex gap-2">
+            {(["stable", "beta"] as const).map((channel) => (
+              <button
+                key={channel}
+                onClick={() => handleChannelChange(channel)}
+                className={`px-3 py-1.5 rounded-md text-xs transition-colors ${
+                  (settings.channel ?? "stable") === channel
+                    ? "bg-blue-600 text-white"
+                    : "bg-muted hover:bg-muted/80"
+                }`}
+              >
+                {channel === "stable" ? "正式版" : "Beta"}
+              </button>
+            ))}
+          </div>
+        </div>
+
+        {/* 后台下载 */}
+        <div className="flex items-center justify-between p-3 rounded-lg border">
+          <div>
+            <div className="text-sm font-medium">后台下载更新</div>
+            <div className="text-xs text-muted-foreground">
+              发现新版本后预先下载并校验签名，确认后即可安装
+            </div>
+          </div>
+          <button
+            onClick={handleToggleAutoDownload}
+            disabled={!settings.enabled}
+            className={`relative w-11 h-6 rounded-full transition-colors disabled:opacity-50 ${
+              settings.auto_download ? "bg-blue-600" : "bg-muted"
+            }`}
+          >
+            <span
+              className={`absolute top-0.5 left-0.5 w-5 h-5 rounded-full bg-white shadow transition-transform ${
+                settings.auto_download ? "translate-x-5" : ""
+              }`}
+            />
+          </button>
+        </div>
+
         {/* 已跳过的版本 */}
         {settings.skipped_version && (
           <div className="flex items-center justify-between p-3 rounded-lg border bg-muted/30">
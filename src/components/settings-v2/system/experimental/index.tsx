@@ -51,6 +51,7 @@ import {
   VoiceInputConfig,
 } from "@/lib/api/asrProvider";
 import { applyCrashReportingSettings } from "@/lib/crashReporting";
+import { exportCrashReport } from "@/lib/api/frontendCrash";
 import {
   buildCrashDiagnosticPayload,
   collectRuntimeSnapshotForDiagnostic,
@@ -556,6 +557,28 @@ export function ExperimentalSettings() {
     }
   }, [buildDiagnosticPayload]);
 
+  const exportLocalCrashReports = useCallback(async () => {
+    setDiagnosticBusy(true);
+    setMessage(null);
+    setShowClipboardGuide(false);
+    try {
+      const result = await exportCrashReport();
+      setMessage({
+        type: "success",
+        text: `已导出 ${result.report_count} 份本地崩溃报告：${result.export_path}`,
+      });
+      setTimeout(() => setMessage(null), 2500);
+    } catch (err) {
+      console.error("导出本地崩溃报告失败:", err);
+      setMessage({
+        type: "error",
+        text: err instanceof Error ? err.message : "导出本地崩溃报告失败",
+      });
+    } finally {
+      setDiagnosticBusy(false);
+    }
+  }, []);
+
   const openCrashDownloadDirectory = useCallback(async () => {
     setDiagnosticBusy(true);
     setMessage(null);
@@ -894,7 +917,7 @@ export function ExperimentalSettings() {
           >
             <div className="space-y-4">
               <div className="rounded-[22px] border border-slate-200/80 bg-slate-50/60 p-4 text-sm leading-6 text-slate-500">
-                DSN 为空时会自动退化为仅本地记录。导出诊断包前建议先完成复现，减少历史噪音。
+                崩溃报告默认只在本地脱敏保存，需手动导出后再发送；只有勾选“同意自动上传”且配置了 DSN 时才会上报到远端。导出诊断包前建议先完成复现，减少历史噪音。
               </div>
 
               <div className="flex items-center justify-between rounded-[22px] border border-slate-200/80 bg-white p-4">
@@ -983,6 +1006,25 @@ export function ExperimentalSettings() {
                 />
               </div>
 
+              <div className="flex items-center justify-between rounded-[22px] border border-slate-200/80 bg-slate-50/60 px-4 py-4">
+                <div>
+                  <p className="text-sm font-semibold text-slate-900">
+                    同意自动上传
+                  </p>
+                  <p className="mt-1 text-sm leading-6 text-slate-500">
+                    关闭时崩溃报告只保存在本地，不会发送到 DSN。
+                  </p>
+                </div>
+                <Switch
+                  checked={Boolean(crashConfig.upload_consent)}
+                  onCheckedChange={(checked) =>
+                    handleCrashFieldChange("upload_consent", checked)
+                  }
+                  disabled={saving}
+                  aria-label="切换同意自动上传崩溃报告"
+                />
+              </div>
+
               <div className="space-y-3">
                 <p className="text-xs leading-5 text-slate-500">
                   复制、导出与打开目录的用途不同。直接发给开发者时优先“复制诊断信息”；需要归档或程序化比对时再选 JSON。
@@ -1006,6 +1048,15 @@ export function ExperimentalSettings() {
                     <Sparkles className="h-4 w-4" />
                     复制纯 JSON
                   </button>
+                  <button
+                    type="button"
+                    onClick={() => void exportLocalCrashReports()}
+                    disabled={saving || diagnosticBusy}
+                    className={SECONDARY_BUTTON_CLASS_NAME}
+                  >
+                    <FolderOpen className="h-4 w-4" />
+                    导出本地崩溃报告
+                  </button>
                   <button
                     type="button"
                     onClick={() => void exportCrashDiagnostic()}
@@ -0,0 +1,31 @@
+import { safeInvoke } from "@/lib/dev-bridge";
+
+/** 待上传的匿名使用统计（预览内容即上传内容） */
+export interface AnonymousStatsReport {
+  schema_version: number;
+  /** 统计周期（YYYY-MM-DD） */
+  period_start: string;
+  period_end: string;
+  /** 应用版本（主次版本号） */
+  app_version: string;
+  platform: string;
+  /** 差分隐私参数 ε */
+  epsilon: number;
+  /** 端点类别 -> 结果类别 -> 加噪后的计数 */
+  counters: Record<string, Record<string, number>>;
+}
+
+/** 查看待上传的统计内容（未启用或暂无数据时为 null） */
+export async function getAnonymousStatsPreview(): Promise<AnonymousStatsReport | null> {
+  return safeInvoke("get_anonymous_stats_preview");
+}
+
+/** 上传已预览的统计内容，返回实际上传的内容 */
+export async function uploadAnonymousStats(): Promise<AnonymousStatsReport> {
+  return safeInvoke("upload_anonymous_stats");
+}
+
+/** 清空已累计的统计数据与预览 */
+export async function clearAnonymousStats(): Promise<void> {
+  return safeInvoke("clear_anonymous_stats");
+}
@@ -22,3 +22,43 @@ export async function checkApiCompatibility(
 ): Promise<ApiCompatibilityResult> {
   return safeInvoke("check_api_compatibility", { provider });
 }
+
+export type ProviderTestCapability =
+  | "chat"
+  | "streaming"
+  | "tool_call"
+  | "long_prompt"
+  | "image";
+
+export interface ProviderCapabilityResult {
+  capability: ProviderTestCapability;
+  status: "passed" | "failed" | "skipped";
+  http_status: number | null;
+  duration_ms: number;
+  message: string | null;
+}
+
+export interface ProviderTestReport {
+  provider: string;
+  model: string;
+  results: ProviderCapabilityResult[];
+  /** 实测能力是否已写回模型注册表 */
+  registry_updated: boolean;
+  tested_at: string;
+}
+
+/**
+ * 对新接入的供应商执行标准接入测试（经本地代理按 X-Provider-Id 路由）。
+ * includeImage 未指定时按模型注册表的视觉能力决定是否测试图片输入。
+ */
+export async function testProviderCapabilities(
+  provider: string,
+  model: string,
+  includeImage?: boolean,
+): Promise<ProviderTestReport> {
+  return safeInvoke("test_provider_capabilities", {
+    provider,
+    model,
+    includeImage,
+  });
+}
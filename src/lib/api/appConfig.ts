@@ -1,5 +1,15 @@
 import { safeInvoke } from "@/lib/dev-bridge";
-import type { Config, EnvironmentPreview } from "./appConfigTypes";
+import type {
+  Config,
+  ConfigAuditEntry,
+  ConfigFieldChange,
+  ConfigLintWarning,
+  ConfigPatchPreview,
+  EnvironmentPreview,
+  PresetConflictStrategy,
+  RoutingPresetImportResult,
+  RoutingPresetValidation,
+} from "./appConfigTypes";
 
 const APP_CONFIG_CHANGE_STAMP_KEY = "lime.app-config.changed-at";
 
@@ -9,6 +19,10 @@ let configCacheStamp: string | null = null;
 
 export type {
   Config,
+  ConfigAuditEntry,
+  ConfigFieldChange,
+  ConfigLintWarning,
+  ConfigPatchPreview,
   CrashReportingConfig,
   ChatAppearanceConfig,
   ContentCreatorConfig,
@@ -20,9 +34,12 @@ export type {
   MultiSearchConfig,
   MultiSearchEngineEntryConfig,
   NavigationConfig,
+  PresetConflictStrategy,
   QuotaExceededConfig,
   RemoteManagementConfig,
   ResponseCacheConfig,
+  RoutingPresetImportResult,
+  RoutingPresetValidation,
   ShellImportPreview,
   TlsConfig,
   ToolCallingConfig,
@@ -114,6 +131,78 @@ export async function saveConfig(config: Config): Promise<void> {
   configCacheStamp = markAppConfigChanged();
 }
 
+/**
+ * 获取当前生效配置的 JSON 树（已合并默认值），默认脱敏密钥。
+ * 脱敏占位符可原样放入补丁，提交时保持原值。
+ */
+export async function getEffectiveConfig(
+  redactSecrets = true,
+): Promise<Record<string, unknown>> {
+  return safeInvoke("get_effective_config", { redactSecrets });
+}
+
+/** 校验配置补丁（JSON Merge Patch，null 表示恢复默认值），不写盘 */
+export async function validateConfigPatch(
+  patch: Record<string, unknown>,
+): Promise<ConfigPatchPreview> {
+  return safeInvoke("validate_config_patch", { patch });
+}
+
+/** 检查配置中的逻辑问题（规则覆盖、未定义的 Provider、无主凭证、回退链） */
+export async function lintConfig(config: Config): Promise<ConfigLintWarning[]> {
+  return safeInvoke("lint_config", { config });
+}
+
+/** 应用配置补丁：校验通过后原子写盘并热重载，返回字段变更 */
+export async function applyConfigPatch(
+  patch: Record<string, unknown>,
+): Promise<ConfigFieldChange[]> {
+  const changes = await safeInvoke<ConfigFieldChange[]>("apply_config_patch", {
+    patch,
+  });
+  invalidateConfigCache();
+  markAppConfigChanged();
+  return changes;
+}
+
+/** 查询配置变更审计记录，path 可按字段路径过滤（如 "routing.rules"） */
+export async function getConfigAuditLog(
+  limit?: number,
+  path?: string,
+): Promise<ConfigAuditEntry[]> {
+  return safeInvoke("get_config_audit_log", { limit, path });
+}
+
+/** 导出路由预设（模型别名、路由规则、参数注入规则） */
+export async function exportRoutingPreset(
+  config: Config,
+  name: string,
+  description?: string,
+): Promise<{ content: string; suggested_filename: string }> {
+  return safeInvoke("export_routing_preset", { config, name, description });
+}
+
+/** 校验路由预设并列出与当前配置冲突的条目 */
+export async function validateRoutingPreset(
+  currentConfig: Config,
+  content: string,
+): Promise<RoutingPresetValidation> {
+  return safeInvoke("validate_routing_preset", { currentConfig, content });
+}
+
+/** 导入路由预设，返回合并后的配置（需再调用 saveConfig 保存） */
+export async function importRoutingPreset(
+  currentConfig: Config,
+  content: string,
+  strategy: PresetConflictStrategy,
+): Promise<RoutingPresetImportResult> {
+  return safeInvoke("import_routing_preset", {
+    currentConfig,
+    content,
+    strategy,
+  });
+}
+
 export async function getEnvironmentPreview(): Promise<EnvironmentPreview> {
   return safeInvoke("get_environment_preview");
 }
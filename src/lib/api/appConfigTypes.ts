@@ -14,6 +14,43 @@ export interface TlsConfig {
   key_path: string | null;
 }
 
+export interface EndpointOverride {
+  base_url?: string | null;
+  api_version?: string | null;
+}
+
+/** 上游企业网关认证 */
+export interface UpstreamAuthConfig {
+  hosts?: string[];
+  headers?: Record<string, string>;
+  hmac?: {
+    secret: string;
+    key_id?: string | null;
+    key_id_header?: string | null;
+    signature_header?: string;
+    timestamp_header?: string;
+    encoding?: "hex" | "base64";
+  } | null;
+  oauth_client_credentials?: {
+    token_url: string;
+    client_id: string;
+    client_secret: string;
+    scope?: string | null;
+    audience?: string | null;
+    header?: string;
+  } | null;
+}
+
+/** 多区域端点延迟选择 */
+export interface EndpointSelectionConfig {
+  enabled: boolean;
+  probe_interval_secs: number;
+  /** 额外候选端点（键为 Provider 名称） */
+  regions?: Record<string, string[]>;
+  /** 固定端点（键为凭证 UUID 或 Provider 名称） */
+  pinned?: Record<string, string>;
+}
+
 export interface ResponseCacheConfig {
   enabled: boolean;
   ttl_secs: number;
@@ -124,6 +161,20 @@ export interface CrashReportingConfig {
   environment?: string;
   sample_rate?: number;
   send_pii?: boolean;
+  /** 同意自动上传到远端；未同意时仅本地保存 */
+  upload_consent?: boolean;
+  local_capture?: boolean;
+  max_local_reports?: number;
+}
+
+/** 匿名使用统计（默认关闭） */
+export interface AnonymousStatsConfig {
+  /** 总开关：关闭时不统计、不上传，并清空已统计的数据 */
+  enabled: boolean;
+  /** 上传地址，未设置时只能在本地查看 */
+  upload_url?: string | null;
+  /** 差分隐私参数 ε，越小噪声越大（默认 1.0） */
+  epsilon?: number;
 }
 
 export interface ShellEnvironmentImportConfig {
@@ -165,6 +216,107 @@ export interface EnvironmentPreview {
   entries: EnvironmentPreviewEntry[];
 }
 
+/** 配置字段变更（密钥类字段以 *** 代替） */
+export interface ConfigFieldChange {
+  path: string;
+  before?: unknown;
+  after?: unknown;
+}
+
+/** 配置逻辑检查警告 */
+export interface ConfigLintWarning {
+  code:
+    | "shadowed_rule"
+    | "undefined_provider"
+    | "orphan_credential"
+    | "fallback_cycle"
+    | "unreachable_fallback";
+  path: string;
+  message: string;
+  suggestion: string;
+}
+
+/** 配置补丁校验结果 */
+export interface ConfigPatchPreview {
+  valid: boolean;
+  error?: string;
+  changes: ConfigFieldChange[];
+  lint: ConfigLintWarning[];
+}
+
+/** 路由预设导入时的冲突处理方式 */
+export type PresetConflictStrategy = "rename" | "overwrite" | "skip";
+
+/** 路由预设校验结果 */
+export interface RoutingPresetValidation {
+  valid: boolean;
+  version?: string;
+  /** 预设是否包含规则 */
+  has_config: boolean;
+  errors: string[];
+  /** 版本提示与冲突条目 */
+  warnings: string[];
+  lint: ConfigLintWarning[];
+}
+
+/** 路由预设导入结果（配置尚未保存） */
+export interface RoutingPresetImportResult {
+  success: boolean;
+  config: Config;
+  warnings: string[];
+  lint: ConfigLintWarning[];
+}
+
+/** 配置变更审计记录 */
+export interface ConfigAuditEntry {
+  id: number;
+  created_at: string;
+  source: "ui" | "command" | "import" | "hot_reload" | "remote_control";
+  /** 系统用户名与应用角色，如 "alice (admin)" */
+  actor: string;
+  summary: string;
+  changes: ConfigFieldChange[];
+}
+
+/** 应用内角色（由低到高） */
+export type AppRole = "viewer" | "operator" | "admin";
+
+/** 应用访问控制配置 */
+export interface AccessControlConfig {
+  /** 启动时的角色 */
+  default_role?: AppRole;
+  /** 升级角色所需 PIN 的加盐摘要（通过 setAppRolePin 设置） */
+  pin_hash?: string | null;
+}
+
+/** 云备份配置 */
+export interface CloudBackupConfig {
+  enabled?: boolean;
+  /** 备份间隔（小时） */
+  interval_hours?: number;
+  /** 远端保留份数 */
+  retention_count?: number;
+  /** 是否包含数据库快照 */
+  include_database?: boolean;
+  /** 备份加密口令 */
+  passphrase?: string;
+  target?: "s3" | "webdav";
+  s3?: {
+    endpoint?: string;
+    region?: string;
+    bucket?: string;
+    prefix?: string;
+    access_key_id?: string;
+    secret_access_key?: string;
+    path_style?: boolean;
+  };
+  webdav?: {
+    url?: string;
+    username?: string;
+    password?: string;
+  };
+}
+
 export interface Config {
   server: {
     host: string;
@@ -178,25 +330,38 @@ export interface Config {
       enabled: boolean;
       credentials_path: string | null;
       region: string | null;
+      base_url?: string | null;
+      api_version?: string | null;
     };
     gemini: {
       enabled: boolean;
       credentials_path: string | null;
+      base_url?: string | null;
+      api_version?: string | null;
     };
     qwen: {
       enabled: boolean;
       credentials_path: string | null;
+      base_url?: string | null;
+      api_version?: string | null;
     };
     openai: {
       enabled: boolean;
       api_key: string | null;
       base_url: string | null;
+      api_version?: string | null;
     };
     claude: {
       enabled: boolean;
       api_key: string | null;
       base_url: string | null;
+      api_version?: string | null;
     };
+    /** 其他 Provider 的上游端点覆盖（如 antigravity、codex、vertex） */
+    endpoints?: Record<string, EndpointOverride>;
+    endpoint_selection?: EndpointSelectionConfig;
+    /** 上游企业网关认证（键为 Provider 名称） */
+    upstream_auth?: Record<string, UpstreamAuthConfig>;
   };
   default_provider: string;
   remote_management: RemoteManagementConfig;
@@ -240,4 +405,7 @@ export interface Config {
   gateway?: GatewayConfig;
   channels?: ChannelsConfig;
   crash_reporting?: CrashReportingConfig;
+  anonymous_stats?: AnonymousStatsConfig;
+  access_control?: AccessControlConfig;
+  cloud_backup?: CloudBackupConfig;
 }
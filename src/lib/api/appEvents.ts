@@ -0,0 +1,182 @@
+/**
+ * 类型化应用事件 API
+ *
+ * 后端通过 `lime://app-event` 推送带版本号的结构化事件，
+ * 类型定义与 Rust `lime_core::app_events` 保持一致。
+ */
+
+import { safeInvoke, safeListen } from "@/lib/dev-bridge";
+
+/** 应用事件通道名称 */
+export const APP_EVENT_CHANNEL = "lime://app-event";
+
+/** 前端支持的事件协议版本 */
+export const SUPPORTED_APP_EVENT_API_VERSION = 1;
+
+/** 前后端协议版本信息 */
+export interface AppApiInfo {
+  command_api_version: number;
+  event_api_version: number;
+  event_channel: string;
+}
+
+/** 凭证池事件 */
+export type PoolEvent =
+  | {
+      type: "credential_health_changed";
+      uuid: string;
+      healthy: boolean;
+      error_count: number;
+      message?: string;
+    }
+  | {
+      type: "credential_used";
+      uuid: string;
+      usage_count: number;
+    }
+  | {
+      type: "provider_paused";
+      provider_type: string;
+      paused: boolean;
+      affected: number;
+    };
+
+/** 配置事件 */
+export type ConfigEvent =
+  | { type: "reloaded"; success: boolean; message?: string }
+  | { type: "profile_applied"; name: string };
+
+/** 服务器事件 */
+export type ServerEvent =
+  | { type: "started"; host: string; port: number }
+  | { type: "stopped" }
+  | { type: "port_fallback"; requested_port: number; port: number }
+  | { type: "took_over"; pid: number; port: number }
+  | { type: "auth_lockout"; ip: string; failures: number; lockout_secs: number }
+  | {
+      type: "degraded_mode";
+      active: boolean;
+      credentials: number;
+      reason?: string;
+    }
+  | {
+      type: "key_rotation";
+      key_id: string;
+      label: string;
+      stage: "successor_prepared" | "rotated" | "retired";
+      successor_id?: string;
+      expires_at?: string;
+    };
+
+/** 用量快照（时间窗口内的增量） */
+export interface UsageTick {
+  window_secs: number;
+  requests: number;
+  failed: number;
+  input_tokens: number;
+  output_tokens: number;
+}
+
+/** 模型请求占比 */
+export interface ModelShare {
+  model: string;
+  requests: number;
+  tokens: number;
+  share: number;
+}
+
+/** 凭证请求占比 */
+export interface CredentialShare {
+  credential_id: string;
+  requests: number;
+  share: number;
+}
+
+/** 本地推理服务资源占用 */
+export interface LocalProviderResources {
+  provider_id: string;
+  name: string;
+  kind: "ollama" | "llama_cpp" | "openai_compatible";
+  reachable: boolean;
+  cpu_percent?: number;
+  memory_bytes?: number;
+  vram_bytes?: number;
+  loaded_models: string[];
+  requests_processing?: number;
+  requests_deferred?: number;
+  kv_cache_usage?: number;
+}
+
+/** GPU 占用 */
+export interface GpuUsage {
+  index: number;
+  name: string;
+  memory_used_bytes: number;
+  memory_total_bytes: number;
+  utilization_percent: number;
+}
+
+/** 预热模型状态 */
+export type WarmModelState = "pending" | "ready" | "failed" | "unavailable";
+
+/** 本地模型预热状态 */
+export interface WarmModelStatus {
+  /** 配置中的 Provider ID 或名称 */
+  provider: string;
+  model: string;
+  state: WarmModelState;
+  /** 最近一次成功预热 / 保活的时间（毫秒时间戳） */
+  last_warmed_at_ms?: number;
+  /** 最近一次请求耗时（毫秒） */
+  latency_ms?: number;
+  error?: string;
+}
+
+/** 滚动统计快照（后端定期预计算） */
+export interface StatsSnapshot {
+  rate_window_secs: number;
+  requests_per_min: number;
+  window_secs: number;
+  requests: number;
+  failed: number;
+  error_rate: number;
+  tokens_per_hour: number;
+  top_models: ModelShare[];
+  credential_share: CredentialShare[];
+  local_providers: LocalProviderResources[];
+  gpus: GpuUsage[];
+  /** 本地模型预热状态（未启用预热时为空） */
+  warm_models: WarmModelStatus[];
+}
+
+/** 应用事件 */
+export type AppEvent =
+  | { category: "pool"; payload: PoolEvent }
+  | { category: "config"; payload: ConfigEvent }
+  | { category: "usage"; payload: UsageTick }
+  | { category: "server"; payload: ServerEvent }
+  | { category: "stats"; payload: StatsSnapshot };
+
+/** 事件信封 */
+export type AppEventEnvelope = AppEvent & {
+  version: number;
+  seq: number;
+  timestamp_ms: number;
+};
+
+/** 获取前后端协议版本信息 */
+export async function getAppApiInfo(): Promise<AppApiInfo> {
+  return safeInvoke("get_app_api_info");
+}
+
+/** 订阅应用事件（忽略不兼容版本的事件） */
+export async function listenAppEvents(
+  handler: (event: AppEventEnvelope) => void,
+): Promise<() => void> {
+  return safeListen<AppEventEnvelope>(APP_EVENT_CHANNEL, (event) => {
+    if (event.payload.version !== SUPPORTED_APP_EVENT_API_VERSION) {
+      return;
+    }
+    handler(event.payload);
+  });
+}
@@ -5,6 +5,7 @@ import {
   closeUpdateWindow,
   dismissUpdateNotification,
   downloadUpdate,
+  getLastUpdateResult,
   getUpdateCheckSettings,
   getUpdateNotificationMetrics,
   recordUpdateNotificationAction,
@@ -90,4 +91,22 @@ describe("appUpdate API", () => {
     ).resolves.toBeUndefined();
     await expect(testUpdateWindow()).resolves.toBeUndefined();
   });
+
+  it("应读取最近一次检查结果", async () => {
+    vi.mocked(safeInvoke).mockResolvedValueOnce({
+      current: "1.0.0",
+      latest: "1.1.0-beta.1",
+      hasUpdate: true,
+      releaseNotes: "- 新增 beta 通道",
+      channel: "beta",
+      rolloutPercent: 20,
+      inRollout: true,
+      readyToInstall: true,
+    });
+
+    await expect(getLastUpdateResult()).resolves.toEqual(
+      expect.objectContaining({ channel: "beta", readyToInstall: true }),
+    );
+    expect(safeInvoke).toHaveBeenCalledWith("get_last_update_result");
+  });
 });
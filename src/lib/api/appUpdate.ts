@@ -1,5 +1,7 @@
 import { safeInvoke } from "@/lib/dev-bridge";
 
+export type UpdateChannel = "stable" | "beta";
+
 export interface VersionInfo {
   current: string;
   latest?: string;
@@ -8,6 +10,13 @@ export interface VersionInfo {
   releaseNotes?: string;
   pubDate?: string;
   error?: string;
+  channel?: UpdateChannel;
+  /** 分阶段推送比例（0-100），缺省为全量 */
+  rolloutPercent?: number | null;
+  /** 本机是否已进入推送范围 */
+  inRollout?: boolean;
+  /** 安装包已在后台下载并通过签名校验 */
+  readyToInstall?: boolean;
 }
 
 export interface DownloadUpdateResult {
@@ -23,6 +32,8 @@ export interface UpdateCheckConfig {
   last_check_timestamp: number;
   skipped_version: string | null;
   remind_later_until: number | null;
+  channel?: UpdateChannel;
+  auto_download?: boolean;
 }
 
 export interface UpdateNotificationMetrics {
@@ -41,6 +52,10 @@ export async function checkForUpdates(): Promise<VersionInfo> {
   return safeInvoke<VersionInfo>("check_for_updates");
 }
 
+export async function getLastUpdateResult(): Promise<VersionInfo | null> {
+  return safeInvoke<VersionInfo | null>("get_last_update_result");
+}
+
 export async function downloadUpdate(): Promise<DownloadUpdateResult> {
   return safeInvoke<DownloadUpdateResult>("download_update");
 }
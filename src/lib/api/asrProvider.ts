@@ -38,6 +38,12 @@ export interface OpenAIAsrConfig {
   api_key: string;
   base_url?: string;
   proxy_url?: string;
+  /** 识别模型，默认 whisper-1；说话人分离需使用支持 diarization 的模型 */
+  model?: string;
+  /** 返回词级时间戳（verbose_json） */
+  word_timestamps?: boolean;
+  /** 说话人分离 */
+  diarization?: boolean;
 }
 
 /** ASR 凭证条目 */
@@ -196,6 +202,35 @@ export async function deleteVoiceInstruction(id: string): Promise<void> {
 export interface TranscribeResult {
   text: string;
   provider: string;
+  /** OpenAI verbose_json 格式的详细结果（含分段、词级时间戳、说话人） */
+  verbose?: TranscribeVerbose;
+}
+
+/** verbose_json 分段 */
+export interface TranscribeVerboseSegment {
+  id: number;
+  start: number;
+  end: number;
+  text: string;
+  speaker?: string;
+}
+
+/** verbose_json 词条 */
+export interface TranscribeVerboseWord {
+  word: string;
+  start: number;
+  end: number;
+  speaker?: string;
+}
+
+/** OpenAI verbose_json 格式识别结果 */
+export interface TranscribeVerbose {
+  task: "transcribe";
+  language: string | null;
+  duration: number;
+  text: string;
+  segments: TranscribeVerboseSegment[];
+  words?: TranscribeVerboseWord[];
 }
 
 /** 润色结果 */
@@ -0,0 +1,54 @@
+import { safeInvoke } from "@/lib/dev-bridge";
+
+/** 压测参数 */
+export interface BenchOptions {
+  model: string;
+  /** 并发数（1-256，默认 4） */
+  concurrency?: number;
+  /** 请求总数（1-10000，默认 20） */
+  total_requests?: number;
+  /** 最长运行时间（秒） */
+  duration_secs?: number;
+  /** 用户消息大小（字节，默认 256） */
+  prompt_bytes?: number;
+  max_tokens?: number;
+  stream?: boolean;
+}
+
+/** 延迟分布（毫秒） */
+export interface LatencyStats {
+  min: number;
+  mean: number;
+  p50: number;
+  p90: number;
+  p99: number;
+  max: number;
+}
+
+export interface BenchReport {
+  options: Required<Omit<BenchOptions, "duration_secs">> &
+    Pick<BenchOptions, "duration_secs">;
+  completed: number;
+  succeeded: number;
+  failed: number;
+  /** 按状态码统计，网络错误记为 "error" */
+  status_counts: Record<string, number>;
+  duration_ms: number;
+  requests_per_sec: number;
+  latency_ms?: LatencyStats;
+  /** 流式请求的首字节时间 */
+  ttfb_ms?: LatencyStats;
+  bytes_received: number;
+  first_error?: string;
+  cancelled: boolean;
+}
+
+/** 对本地服务运行压测（请求会真实转发到上游并计入用量） */
+export async function runBenchmark(options: BenchOptions): Promise<BenchReport> {
+  return safeInvoke("run_benchmark", { options });
+}
+
+/** 取消正在运行的压测 */
+export async function cancelBenchmark(): Promise<boolean> {
+  return safeInvoke("cancel_benchmark");
+}
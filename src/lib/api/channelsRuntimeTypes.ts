@@ -196,6 +196,11 @@ export interface CloudflareTunnelConfig {
   dns_name?: string;
 }
 
+export interface NgrokTunnelConfig {
+  authtoken?: string;
+  domain?: string;
+}
+
 export interface GatewayTunnelConfig {
   enabled?: boolean;
   provider?: string;
@@ -205,6 +210,8 @@ export interface GatewayTunnelConfig {
   local_port?: number;
   public_base_url?: string;
   cloudflare?: CloudflareTunnelConfig;
+  ngrok?: NgrokTunnelConfig;
+  require_strong_api_key?: boolean;
 }
 
 export interface GatewayConfig {
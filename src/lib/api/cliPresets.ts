@@ -0,0 +1,65 @@
+/**
+ * CLI 工具接入预设 API
+ *
+ * 一键生成 Claude Code、Codex CLI 与 Cline 指向本机网关的配置，写入前自动备份，可回滚。
+ */
+
+import { safeInvoke } from "@/lib/dev-bridge";
+
+/** 支持的 CLI 工具 */
+export type CliTool = "claude_code" | "codex_cli" | "cline";
+
+/** 预设使用的模型别名，缺省时使用工具默认值 */
+export interface CliPresetModels {
+  model?: string | null;
+  /** 快速模型（仅 Claude Code） */
+  small_model?: string | null;
+}
+
+/** 将写入的文件 */
+export interface CliPresetFile {
+  path: string;
+  content: string;
+}
+
+/** 需要设置的环境变量 */
+export interface CliPresetEnvVar {
+  name: string;
+  value: string;
+}
+
+/** 预设内容 */
+export interface CliPresetPlan {
+  tool: CliTool;
+  files: CliPresetFile[];
+  env: CliPresetEnvVar[];
+  /** 需要手动完成的步骤 */
+  manual_steps: string[];
+}
+
+/** 应用预设的结果 */
+export interface CliPresetApplyResult extends CliPresetPlan {
+  /** 本次备份 ID，没有写入文件时为空 */
+  backup_id: string | null;
+}
+
+/** 预览接入预设（不写入文件） */
+export async function previewCliPreset(
+  tool: CliTool,
+  models?: CliPresetModels,
+): Promise<CliPresetPlan> {
+  return safeInvoke("preview_cli_preset", { tool, models });
+}
+
+/** 应用接入预设 */
+export async function applyCliPreset(
+  tool: CliTool,
+  models?: CliPresetModels,
+): Promise<CliPresetApplyResult> {
+  return safeInvoke("apply_cli_preset", { tool, models });
+}
+
+/** 回滚最近一次应用的预设，返回还原的文件 */
+export async function rollbackCliPreset(tool: CliTool): Promise<string[]> {
+  return safeInvoke("rollback_cli_preset", { tool });
+}
@@ -0,0 +1,44 @@
+import { safeInvoke } from "@/lib/dev-bridge";
+
+export type DiagnosticStatus = "pass" | "warn" | "fail" | "skipped";
+
+export type DiagnosticCategory =
+  | "dns"
+  | "reachability"
+  | "tls"
+  | "clock_skew"
+  | "token"
+  | "port";
+
+export interface DiagnosticCheck {
+  category: DiagnosticCategory;
+  /** 上游主机名、凭证名称或监听地址 */
+  target: string;
+  status: DiagnosticStatus;
+  message: string;
+  duration_ms: number;
+}
+
+/** 连接诊断报告（不含密钥） */
+export interface DiagnosticReport {
+  generated_at: string;
+  app_version: string;
+  os: string;
+  arch: string;
+  proxy_configured: boolean;
+  summary: { pass: number; warn: number; fail: number; skipped: number };
+  checks: DiagnosticCheck[];
+}
+
+/** 运行连接诊断（DNS、上游可达性、TLS、时钟偏差、Token、端口） */
+export async function runConnectionDoctor(): Promise<DiagnosticReport> {
+  return safeInvoke("run_connection_doctor");
+}
+
+/** 导出诊断报告，路径以 .md 结尾时导出 Markdown，否则导出 JSON */
+export async function exportConnectionDoctorReport(
+  report: DiagnosticReport,
+  path: string,
+): Promise<string> {
+  return safeInvoke("export_connection_doctor_report", { report, path });
+}
@@ -0,0 +1,42 @@
+import { safeInvoke } from "@/lib/dev-bridge";
+
+/** 字段差异 */
+export interface GoldenDiff {
+  path: string;
+  expected: unknown;
+  actual: unknown;
+}
+
+/** 单个用例的运行结果 */
+export interface GoldenCaseResult {
+  name: string;
+  category: string;
+  mapping: string;
+  /** 用例来源文件，内置用例为 `builtin:<文件名>` */
+  source: string;
+  passed: boolean;
+  diffs: GoldenDiff[];
+  /** 输入无法解析或转换失败时的错误 */
+  error: string | null;
+}
+
+/** 协议转换黄金用例报告 */
+export interface GoldenReport {
+  total: number;
+  passed: number;
+  failed: number;
+  results: GoldenCaseResult[];
+}
+
+/**
+ * 运行协议转换黄金用例
+ *
+ * 内置用例始终运行；未指定目录时额外加载数据目录下的 `converter-fixtures/`。
+ */
+export async function runConverterGoldenTests(
+  fixturesDir?: string,
+): Promise<GoldenReport> {
+  return safeInvoke("run_converter_golden_tests", {
+    fixturesDir: fixturesDir ?? null,
+  });
+}
@@ -0,0 +1,82 @@
+import { safeInvoke } from "@/lib/dev-bridge";
+
+/** 数据库维护结果 */
+export interface DbMaintenanceReport {
+  integrity_ok: boolean;
+  issues: string[];
+  checkpointed: boolean;
+  vacuumed: boolean;
+  size_before_bytes: number;
+  size_after_bytes: number;
+  duration_ms: number;
+  ran_at: string;
+}
+
+export interface DbMaintenanceStatus {
+  schema_version: number;
+  last_report: DbMaintenanceReport | null;
+}
+
+/** 立即执行数据库维护（默认包含 VACUUM） */
+export async function runDbMaintenance(
+  vacuum?: boolean,
+): Promise<DbMaintenanceReport> {
+  return safeInvoke("run_db_maintenance", { vacuum });
+}
+
+export async function getDbMaintenanceStatus(): Promise<DbMaintenanceStatus> {
+  return safeInvoke("get_db_maintenance_status");
+}
+
+/** 单个存储的数据保留清理结果 */
+export interface StoreRetentionReport {
+  store:
+    | "audit"
+    | "usage"
+    | "deleted_credentials"
+    | "media"
+    | "debug_captures"
+    | "transcripts";
+  removed: number;
+  freed_bytes: number;
+  error?: string;
+}
+
+export interface RetentionReport {
+  stores: StoreRetentionReport[];
+  duration_ms: number;
+  ran_at: string;
+}
+
+/** 立即按 server.retention 执行一次数据保留清理 */
+export async function runRetentionNow(): Promise<RetentionReport> {
+  return safeInvoke("run_retention_now");
+}
+
+/** 云备份结果 */
+export interface CloudBackupReport {
+  name: string;
+  size_bytes: number;
+  created_at: string;
+  /** 按保留份数删除的旧备份 */
+  removed: string[];
+}
+
+export interface CloudBackupEntry {
+  name: string;
+  created_at: string | null;
+}
+
+/** 立即执行一次云备份 */
+export async function runCloudBackupNow(): Promise<CloudBackupReport> {
+  return safeInvoke("run_cloud_backup_now");
+}
+
+export async function listCloudBackups(): Promise<CloudBackupEntry[]> {
+  return safeInvoke("list_cloud_backups");
+}
+
+/** 从远端备份恢复配置与数据库（保留当前云备份设置） */
+export async function restoreCloudBackup(name: string): Promise<void> {
+  return safeInvoke("restore_cloud_backup", { name });
+}
@@ -0,0 +1,34 @@
+import { safeInvoke } from "@/lib/dev-bridge";
+
+/** 单个端点的探测结果 */
+export interface EndpointProbe {
+  url: string;
+  /** 往返延迟（毫秒），探测失败时为空 */
+  latency_ms: number | null;
+  error: string | null;
+  /** 尚未探测时为空 */
+  probed_at: string | null;
+}
+
+/** 单个 Provider 的端点选择状态 */
+export interface ProviderEndpointReport {
+  provider: string;
+  /** Provider 级固定端点 */
+  pinned: string | null;
+  /** 当前优先使用的端点 */
+  selected: string | null;
+  /** 按优先级排列的候选端点 */
+  endpoints: EndpointProbe[];
+}
+
+/** 获取各 Provider 的端点探测结果 */
+export async function getEndpointLatency(): Promise<ProviderEndpointReport[]> {
+  return safeInvoke("get_endpoint_latency");
+}
+
+/** 立即探测所有区域端点 */
+export async function probeEndpointLatency(): Promise<
+  ProviderEndpointReport[]
+> {
+  return safeInvoke("probe_endpoint_latency");
+}
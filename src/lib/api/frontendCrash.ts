@@ -1,5 +1,34 @@
 import { safeInvoke } from "@/lib/dev-bridge";
 
+export interface CrashReportSummary {
+  id: string;
+  created_at: string;
+  origin: "panic" | "frontend" | string;
+  message: string;
+}
+
+export interface CrashReportExportResult {
+  export_path: string;
+  report_count: number;
+}
+
 export async function reportFrontendCrash(report: unknown): Promise<void> {
   await safeInvoke("report_frontend_crash", { report });
 }
+
+export async function listCrashReports(): Promise<CrashReportSummary[]> {
+  return safeInvoke<CrashReportSummary[]>("list_crash_reports");
+}
+
+/** 导出本地崩溃报告；不传 ids 时导出全部 */
+export async function exportCrashReport(
+  ids?: string[],
+): Promise<CrashReportExportResult> {
+  return safeInvoke<CrashReportExportResult>("export_crash_report", {
+    ids: ids ?? null,
+  });
+}
+
+export async function clearCrashReports(): Promise<number> {
+  return safeInvoke<number>("clear_crash_reports");
+}
@@ -0,0 +1,41 @@
+import { safeInvoke } from "@/lib/dev-bridge";
+
+/** 上游 HTTP 抓包状态 */
+export interface HarCaptureStatus {
+  /** 当前是否在抓包窗口内 */
+  active: boolean;
+  started_at: string | null;
+  /** 抓包窗口结束时间 */
+  ends_at: string | null;
+  entries: number;
+  max_entries: number;
+}
+
+/** 开启抓包窗口（默认 300 秒），会清空上一次的记录 */
+export async function startHarCapture(
+  durationSecs?: number,
+): Promise<HarCaptureStatus> {
+  return safeInvoke("start_har_capture", {
+    durationSecs: durationSecs ?? null,
+  });
+}
+
+/** 提前结束抓包窗口 */
+export async function stopHarCapture(): Promise<HarCaptureStatus> {
+  return safeInvoke("stop_har_capture");
+}
+
+/** 获取抓包状态 */
+export async function getHarCaptureStatus(): Promise<HarCaptureStatus> {
+  return safeInvoke("get_har_capture_status");
+}
+
+/** 清空已记录的内容 */
+export async function clearHarCapture(): Promise<HarCaptureStatus> {
+  return safeInvoke("clear_har_capture");
+}
+
+/** 将已记录的内容导出为 HAR 文件，返回写入路径 */
+export async function exportHarCapture(path: string): Promise<string> {
+  return safeInvoke("export_har_capture", { path });
+}
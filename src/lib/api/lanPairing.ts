@@ -0,0 +1,101 @@
+/**
+ * 局域网扫码配对 API
+ *
+ * 为移动端签发受限 API Key（仅可调用推理端点），并生成二维码。
+ */
+
+import { safeInvoke } from "@/lib/dev-bridge";
+
+/** 配对信息 */
+export interface LanPairingInfo {
+  /** OpenAI 兼容的 Base URL（含 /v1） */
+  base_url: string;
+  /** 受限 API Key 明文（仅返回一次） */
+  api_key: string;
+  key_id: string;
+  expires_at: string | null;
+  /** 二维码内容 */
+  pairing_uri: string;
+  /** 二维码 SVG */
+  qr_svg: string;
+}
+
+/** 响应归属标注方式 */
+export type AttributionMode = "off" | "metadata" | "footer";
+
+/** 请求调度通道 */
+export type PriorityLane = "interactive" | "background";
+
+/** 已签发的受限 API Key */
+export interface ScopedApiKeyRecord {
+  id: string;
+  label: string;
+  key_hash: string;
+  key_prefix: string;
+  created_at: string;
+  expires_at?: string;
+  last_used_at?: string;
+  /** 允许调用的模型（支持 * 通配），缺省表示不限制 */
+  models?: string[];
+  max_requests?: number;
+  request_count: number;
+  /** 响应归属标注方式，缺省时使用全局配置 */
+  attribution?: AttributionMode;
+  /** 最大输出 Token 数，缺省时只受全局上限限制 */
+  max_output_tokens?: number;
+  /** 单次流式响应的最大输出 Token 数 */
+  max_stream_tokens?: number;
+  /** 单次流式响应的最大字节数 */
+  max_stream_bytes?: number;
+  /** 调度通道，缺省时按请求头或全局默认通道 */
+  priority?: PriorityLane;
+}
+
+/** 临时受限 Key 签发选项 */
+export interface ScopedApiKeyOptions {
+  label: string;
+  /** 有效期（分钟），0 表示永不过期 */
+  ttl_minutes?: number;
+  models?: string[];
+  max_requests?: number | null;
+  /** 响应归属标注方式，缺省时使用全局配置 */
+  attribution?: AttributionMode | null;
+  /** 最大输出 Token 数 */
+  max_output_tokens?: number | null;
+  /** 单次流式响应的最大输出 Token 数 */
+  max_stream_tokens?: number | null;
+  /** 单次流式响应的最大字节数 */
+  max_stream_bytes?: number | null;
+  /** 调度通道 */
+  priority?: PriorityLane | null;
+}
+
+/** 新签发的受限 Key（明文仅返回一次） */
+export interface IssuedScopedApiKey {
+  record: ScopedApiKeyRecord;
+  api_key: string;
+}
+
+/** 创建局域网配对 */
+export async function createLanPairing(
+  label?: string,
+): Promise<LanPairingInfo> {
+  return safeInvoke("create_lan_pairing", { label });
+}
+
+/** 签发临时受限 API Key */
+export async function createScopedApiKey(
+  options: ScopedApiKeyOptions,
+): Promise<IssuedScopedApiKey> {
+  return safeInvoke("create_scoped_api_key", { options });
+}
+
+/** 列出已签发的受限 API Key */
+export async function listScopedApiKeys(): Promise<ScopedApiKeyRecord[]> {
+  return safeInvoke("list_scoped_api_keys");
+}
+
+/** 吊销受限 API Key */
+export async function revokeScopedApiKey(id: string): Promise<boolean> {
+  return safeInvoke("revoke_scoped_api_key", { id });
+}
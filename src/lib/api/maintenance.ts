@@ -0,0 +1,28 @@
+import { safeInvoke } from "@/lib/dev-bridge";
+
+/** 维护模式状态 */
+export interface MaintenanceStatus {
+  enabled: boolean;
+  /** 返回给客户端的提示 */
+  message: string;
+  /** 503 响应中的 Retry-After（秒） */
+  retry_after_secs?: number;
+  /** 本次进入维护模式的时间 */
+  since?: string;
+}
+
+/** 获取维护模式状态 */
+export async function getMaintenanceMode(): Promise<MaintenanceStatus> {
+  return safeInvoke("get_maintenance_mode");
+}
+
+/** 开启或关闭维护模式，`message` 为空时沿用当前提示 */
+export async function setMaintenanceMode(
+  enabled: boolean,
+  message?: string,
+): Promise<MaintenanceStatus> {
+  return safeInvoke("set_maintenance_mode", {
+    enabled,
+    message: message ?? null,
+  });
+}
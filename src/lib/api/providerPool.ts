@@ -184,6 +184,8 @@ export interface CredentialDisplay {
   api_key?: string;
   // 凭证级代理 URL（可覆盖全局代理设置）
   proxy_url?: string;
+  // 移入回收站的时间（仅回收站列表有值）
+  deleted_at?: string;
 }
 
 // Pool statistics
@@ -212,6 +214,44 @@ export interface HealthCheckResult {
   duration_ms: number;
 }
 
+// 按需探测（试运行）结果，不更新凭证健康状态
+export interface HealthProbeOutcome {
+  credential_id: string;
+  model: string;
+  success: boolean;
+  latency_ms: number;
+  status_code?: number;
+  error_excerpt?: string;
+  checked_at: string;
+}
+
+// 凭证池洞察
+export type CredentialInsightKind =
+  | "never_selected"
+  | "always_unhealthy"
+  | "idle"
+  | "saturated";
+
+export interface CredentialInsight {
+  uuid: string;
+  name?: string;
+  provider_type: string;
+  kind: CredentialInsightKind;
+  detail: string;
+}
+
+export interface PoolRecommendation {
+  provider_type: string;
+  action: "add_credentials" | "remove_idle" | "review_config";
+  message: string;
+}
+
+export interface PoolInsights {
+  generated_at: string;
+  credentials: CredentialInsight[];
+  recommendations: PoolRecommendation[];
+}
+
 // OAuth status
 export interface OAuthStatus {
   has_access_token: boolean;
@@ -259,6 +299,26 @@ export interface UpdateCredentialRequest {
   new_proxy_url?: string;
 }
 
+// 凭证配对串导出结果
+export interface CredentialPairingExport {
+  pairing_string: string;
+  /// 二维码 SVG，内容超出二维码容量时为空
+  qr_svg?: string | null;
+  credential_count: number;
+}
+
+// 复制凭证请求：沿用原凭证配置，只替换账号信息
+export interface CloneCredentialRequest {
+  /// 新凭证名称，为空时使用「原名称 (副本)」
+  name?: string;
+  /// 新的凭证文件路径（OAuth 凭证必填）
+  creds_file_path?: string;
+  /// 新的 API Key（API Key 凭证必填）
+  api_key?: string;
+  /// 新的 project_id（仅 Gemini / Antigravity OAuth）
+  project_id?: string;
+}
+
 export const providerPoolApi = {
   // Get overview of all provider pools
   async getOverview(
@@ -293,6 +353,34 @@ export const providerPoolApi = {
     );
   },
 
+  // Clone a credential's configuration with a different account
+  async cloneCredential(
+    uuid: string,
+    request: CloneCredentialRequest,
+  ): Promise<ProviderCredential> {
+    return invalidateOverviewAfterMutation(
+      safeInvoke("clone_provider_pool_credential", { uuid, request }),
+    );
+  },
+
+  // Export credentials as an encrypted pairing string / QR code
+  async exportCredentialPairing(
+    uuids: string[],
+    passphrase: string,
+  ): Promise<CredentialPairingExport> {
+    return safeInvoke("export_credential_pairing", { uuids, passphrase });
+  },
+
+  // Import credentials from a pairing string produced by another instance
+  async importCredentialPairing(
+    pairingString: string,
+    passphrase: string,
+  ): Promise<ProviderCredential[]> {
+    return invalidateOverviewAfterMutation(
+      safeInvoke("import_credential_pairing", { pairingString, passphrase }),
+    );
+  },
+
   // Delete a credential
   async deleteCredential(
     uuid: string,
@@ -306,6 +394,23 @@ export const providerPoolApi = {
     );
   },
 
+  // List credentials in the recycle bin
+  async getDeletedCredentials(): Promise<CredentialDisplay[]> {
+    return safeInvoke("get_deleted_provider_pool_credentials");
+  },
+
+  // Restore a credential from the recycle bin
+  async restoreCredential(uuid: string): Promise<boolean> {
+    return invalidateOverviewAfterMutation(
+      safeInvoke("restore_provider_pool_credential", { uuid }),
+    );
+  },
+
+  // Permanently delete a credential from the recycle bin
+  async purgeCredential(uuid: string): Promise<boolean> {
+    return safeInvoke("purge_provider_pool_credential", { uuid });
+  },
+
   // Toggle credential enabled/disabled
   async toggleCredential(
     uuid: string,
@@ -337,6 +442,19 @@ export const providerPoolApi = {
     );
   },
 
+  // Probe one credential against a chosen model without updating its health
+  async probeCredentialHealth(
+    uuid: string,
+    model?: string,
+  ): Promise<HealthProbeOutcome> {
+    return safeInvoke("probe_provider_pool_credential_health", { uuid, model });
+  },
+
+  // Flag idle / unhealthy / saturated credentials and scaling hints
+  async getPoolInsights(): Promise<PoolInsights> {
+    return safeInvoke("pool_insights");
+  },
+
   // Check health of all credentials of a type
   async checkTypeHealth(
     providerType: PoolProviderType,
@@ -1,4 +1,5 @@
 import { safeInvoke } from "@/lib/dev-bridge";
+import type { AppRole } from "./appConfigTypes";
 
 export interface RateLimitConfig {
   enabled: boolean;
@@ -22,6 +23,15 @@ export interface PairingConfig {
   enabled: boolean;
 }
 
+/** 因认证失败被锁定的 IP */
+export interface BannedIp {
+  ip: string;
+  failures: number;
+  strikes: number;
+  locked_until: string;
+  last_failure_at: string;
+}
+
 export async function getRateLimitConfig(): Promise<RateLimitConfig> {
   return await safeInvoke("get_rate_limit_config");
 }
@@ -53,3 +63,27 @@ export async function getPairingConfig(): Promise<PairingConfig> {
 export async function updatePairingConfig(config: PairingConfig): Promise<void> {
   return await safeInvoke("update_pairing_config", { config });
 }
+
+export async function listBannedIps(): Promise<BannedIp[]> {
+  return await safeInvoke("list_banned_ips");
+}
+
+/** 解除锁定，不传 ip 时清除全部 */
+export async function clearBannedIps(ip?: string): Promise<number> {
+  return await safeInvoke("clear_banned_ips", { ip });
+}
+
+/** 当前应用角色 */
+export async function getAppRole(): Promise<AppRole> {
+  return await safeInvoke("get_app_role");
+}
+
+/** 切换应用角色，升级到更高角色时若已设置 PIN 需提供 */
+export async function setAppRole(role: AppRole, pin?: string): Promise<void> {
+  return await safeInvoke("set_app_role", { role, pin });
+}
+
+/** 设置升级角色所需的 PIN，不传时取消 */
+export async function setAppRolePin(pin?: string): Promise<void> {
+  return await safeInvoke("set_app_role_pin", { pin });
+}
@@ -4,12 +4,16 @@ export interface ServerStatus {
   running: boolean;
   host: string;
   port: number;
+  /** 配置的端口（发生端口回退时与 port 不同） */
+  requested_port: number;
   requests: number;
   uptime_secs: number;
   error_rate_1m: number;
   p95_latency_ms_1m: number | null;
   open_circuit_count: number;
   active_requests: number;
+  /** 累计被客户端中断的请求数 */
+  aborted_requests: number;
   capability_routing: CapabilityRoutingMetricsSnapshot;
   response_cache: ResponseCacheStats;
   request_dedup: RequestDedupStats;
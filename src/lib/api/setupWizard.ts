@@ -0,0 +1,85 @@
+import { safeInvoke } from "@/lib/dev-bridge";
+
+/** 设置向导步骤（按执行顺序） */
+export type SetupStep =
+  | "detect_credentials"
+  | "test_credentials"
+  | "generate_api_key"
+  | "choose_port"
+  | "write_config"
+  | "start_server"
+  | "completed";
+
+/** 检测到的本机凭证（密钥已脱敏） */
+export interface DetectedCredential {
+  /** 如 "file:kiro"、"env:OPENAI_API_KEY" */
+  id: string;
+  provider_type: string;
+  /** 凭证文件路径或环境变量名 */
+  source: string;
+  label: string;
+  /** 凭证池中已存在的同一凭证 */
+  pool_uuid?: string;
+}
+
+export interface CredentialTestOutcome {
+  id: string;
+  success: boolean;
+  message?: string;
+  pool_uuid?: string;
+}
+
+export interface SetupWizardState {
+  /** 下一个待执行的步骤 */
+  step: SetupStep;
+  detected: DetectedCredential[];
+  tested: CredentialTestOutcome[];
+  /** 生成的入站 API Key（写入配置后清除） */
+  api_key?: string;
+  port?: number;
+  completed_at?: string;
+}
+
+export interface SetupServerInfo {
+  host: string;
+  port: number;
+  base_url: string;
+}
+
+export async function getSetupWizardState(): Promise<SetupWizardState> {
+  return safeInvoke("get_setup_wizard_state");
+}
+
+export async function resetSetupWizard(): Promise<void> {
+  return safeInvoke("reset_setup_wizard");
+}
+
+export async function setupDetectCredentials(): Promise<SetupWizardState> {
+  return safeInvoke("setup_detect_credentials");
+}
+
+/** 导入并测试检测到的凭证，ids 为空时测试全部 */
+export async function setupTestCredentials(
+  ids?: string[],
+): Promise<SetupWizardState> {
+  return safeInvoke("setup_test_credentials", { ids });
+}
+
+export async function setupGenerateApiKey(): Promise<SetupWizardState> {
+  return safeInvoke("setup_generate_api_key");
+}
+
+/** 从 preferred（默认当前配置端口）开始查找可用端口 */
+export async function setupChoosePort(
+  preferred?: number,
+): Promise<SetupWizardState> {
+  return safeInvoke("setup_choose_port", { preferred });
+}
+
+export async function setupWriteConfig(): Promise<SetupWizardState> {
+  return safeInvoke("setup_write_config");
+}
+
+export async function setupStartServer(): Promise<SetupServerInfo> {
+  return safeInvoke("setup_start_server");
+}
@@ -74,6 +74,10 @@ export interface TokenStatsSummary {
   total_input_tokens: number;
   total_output_tokens: number;
   total_tokens: number;
+  /** 命中缓存的输入 Token 总数 */
+  total_cached_tokens?: number;
+  /** 推理 Token 总数 */
+  total_reasoning_tokens?: number;
   record_count: number;
   actual_count: number;
   estimated_count: number;
@@ -86,6 +90,10 @@ export interface ProviderTokenStats {
   total_input_tokens: number;
   total_output_tokens: number;
   total_tokens: number;
+  /** 命中缓存的输入 Token 总数 */
+  total_cached_tokens?: number;
+  /** 推理 Token 总数 */
+  total_reasoning_tokens?: number;
   record_count: number;
   actual_count: number;
   estimated_count: number;
@@ -98,6 +106,10 @@ export interface ModelTokenStats {
   total_input_tokens: number;
   total_output_tokens: number;
   total_tokens: number;
+  /** 命中缓存的输入 Token 总数 */
+  total_cached_tokens?: number;
+  /** 推理 Token 总数 */
+  total_reasoning_tokens?: number;
   record_count: number;
   actual_count: number;
   estimated_count: number;
@@ -111,6 +123,10 @@ export interface PeriodTokenStats {
   total_input_tokens: number;
   total_output_tokens: number;
   total_tokens: number;
+  /** 命中缓存的输入 Token 总数 */
+  total_cached_tokens?: number;
+  /** 推理 Token 总数 */
+  total_reasoning_tokens?: number;
   record_count: number;
   actual_count: number;
   estimated_count: number;
@@ -124,6 +140,57 @@ export interface TimeRangeParam {
   preset?: "1h" | "24h" | "7d" | "30d";
 }
 
+export type BalanceStrategy =
+  | "health_weighted"
+  | "round_robin"
+  | "least_used"
+  | "random";
+
+export interface ScenarioCredential {
+  id: string;
+  /** 初始健康分，健康分加权策略下即选择权重 */
+  weight?: number;
+  latency_ms?: number;
+  failure_rate?: number;
+  token_quota?: number;
+  request_quota?: number;
+}
+
+export interface PoolScenario {
+  name: string;
+  strategy?: BalanceStrategy;
+  credentials: ScenarioCredential[];
+  traffic_multiplier?: number;
+  seed?: number;
+}
+
+export interface CredentialProjection {
+  id: string;
+  requests: number;
+  share: number;
+  failures: number;
+  tokens: number;
+  mean_latency_ms: number;
+  quota_used?: number;
+  exhausted_after_secs?: number;
+  unhealthy: boolean;
+}
+
+export interface SimulationReport {
+  scenario: string;
+  strategy: BalanceStrategy;
+  total_requests: number;
+  served: number;
+  failed: number;
+  rejected: number;
+  first_rejection_after_secs?: number;
+  total_tokens: number;
+  mean_latency_ms: number;
+  p50_latency_ms: number;
+  p95_latency_ms: number;
+  credentials: CredentialProjection[];
+}
+
 // ========== 请求日志 API ==========
 
 export async function getRequestLogs(params?: {
@@ -190,3 +257,18 @@ export async function getTokenStatsByDay(
 ): Promise<PeriodTokenStats[]> {
   return safeInvoke("get_token_stats_by_day", { days });
 }
+
+// ========== 凭证池模拟 API ==========
+
+export async function simulateCredentialPool(
+  provider: string,
+  scenarios: PoolScenario[],
+  options?: { timeRange?: TimeRangeParam; includeCurrent?: boolean },
+): Promise<SimulationReport[]> {
+  return safeInvoke("simulate_credential_pool", {
+    provider,
+    scenarios,
+    time_range: options?.timeRange,
+    include_current: options?.includeCurrent,
+  });
+}
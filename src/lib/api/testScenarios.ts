@@ -0,0 +1,36 @@
+import { safeInvoke } from "@/lib/dev-bridge";
+
+/** 单步结果 */
+export interface ScenarioStepReport {
+  name: string;
+  passed: boolean;
+  /** 响应状态码，网络错误时为空 */
+  status?: number;
+  latency_ms: number;
+  /** 未满足的期望 */
+  failures?: string[];
+}
+
+/** 场景结果 */
+export interface ScenarioReport {
+  name: string;
+  /** 场景文件路径 */
+  source?: string;
+  passed: boolean;
+  steps: ScenarioStepReport[];
+  /** 因前序步骤失败而未执行的步骤数 */
+  skipped: number;
+  duration_ms: number;
+}
+
+/** 列出场景目录中的场景文件 */
+export async function listTestScenarios(): Promise<string[]> {
+  return safeInvoke("list_test_scenarios");
+}
+
+/** 对本地服务运行测试场景，`paths` 为空时运行场景目录中的全部场景（请求会真实转发到上游） */
+export async function runTestScenarios(
+  paths?: string[],
+): Promise<ScenarioReport[]> {
+  return safeInvoke("run_test_scenarios", { paths });
+}
@@ -39,6 +39,44 @@ export async function syncTrayModelShortcuts(
   });
 }
 
+/** 暂停或恢复整个 Provider，返回切换后是否处于暂停状态 */
+export async function toggleProviderPause(
+  providerType: string,
+): Promise<boolean> {
+  return safeInvoke("toggle_provider_pause", { providerType });
+}
+
+/** 复制网关地址和 API Key 到剪贴板，返回复制的文本 */
+export async function copyApiCredentials(): Promise<string> {
+  return safeInvoke("copy_api_credentials");
+}
+
+/** 列出已保存的配置方案 */
+export async function listConfigProfiles(): Promise<string[]> {
+  return safeInvoke("list_config_profiles");
+}
+
+/** 将当前配置保存为配置方案 */
+export async function saveConfigProfile(name: string): Promise<void> {
+  await safeInvoke("save_config_profile", { name });
+}
+
+/** 删除配置方案 */
+export async function deleteConfigProfile(name: string): Promise<boolean> {
+  return safeInvoke("delete_config_profile", { name });
+}
+
+/** 切换到指定配置方案 */
+export async function switchConfigProfile(name: string): Promise<void> {
+  await safeInvoke("switch_config_profile", { name });
+}
+
 export const trayApi = {
   syncTrayModelShortcuts,
+  toggleProviderPause,
+  copyApiCredentials,
+  listConfigProfiles,
+  saveConfigProfile,
+  deleteConfigProfile,
+  switchConfigProfile,
 };
@@ -203,6 +203,9 @@ export const DEFAULT_CRASH_REPORTING_CONFIG: CrashReportingConfig = {
   environment: "production",
   sample_rate: 1,
   send_pii: false,
+  upload_consent: false,
+  local_capture: true,
+  max_local_reports: 20,
 };
 
 export function normalizeCrashReportingConfig(
@@ -219,6 +222,13 @@ export function normalizeCrashReportingConfig(
         ? Math.min(1, Math.max(0, config.sample_rate))
         : DEFAULT_CRASH_REPORTING_CONFIG.sample_rate,
     send_pii: config?.send_pii ?? DEFAULT_CRASH_REPORTING_CONFIG.send_pii,
+    upload_consent:
+      config?.upload_consent ?? DEFAULT_CRASH_REPORTING_CONFIG.upload_consent,
+    local_capture:
+      config?.local_capture ?? DEFAULT_CRASH_REPORTING_CONFIG.local_capture,
+    max_local_reports:
+      config?.max_local_reports ??
+      DEFAULT_CRASH_REPORTING_CONFIG.max_local_reports,
   };
 }
 
@@ -16,6 +16,7 @@ interface ResolvedCrashReportingConfig {
   environment: string;
   sampleRate: number;
   sendPii: boolean;
+  uploadConsent: boolean;
 }
 
 interface FrontendCrashReportPayload {
@@ -63,6 +64,7 @@ const DEFAULT_CRASH_REPORTING_CONFIG: ResolvedCrashReportingConfig = {
   environment: import.meta.env.DEV ? "development" : "production",
   sampleRate: 1,
   sendPii: false,
+  uploadConsent: false,
 };
 
 let initialized = false;
@@ -147,6 +149,9 @@ function resolveCrashReportingConfig(
     environment,
     sampleRate,
     sendPii: crashConfig?.send_pii ?? DEFAULT_CRASH_REPORTING_CONFIG.sendPii,
+    uploadConsent:
+      crashConfig?.upload_consent ??
+      DEFAULT_CRASH_REPORTING_CONFIG.uploadConsent,
   };
 }
 
@@ -340,7 +345,8 @@ function isSameResolvedConfig(
     left.dsn === right.dsn &&
     left.environment === right.environment &&
     left.sampleRate === right.sampleRate &&
-    left.sendPii === right.sendPii
+    left.sendPii === right.sendPii &&
+    left.uploadConsent === right.uploadConsent
   );
 }
 
@@ -383,7 +389,9 @@ async function applyResolvedConfig(
   const unchanged = isSameResolvedConfig(currentResolvedConfig, nextConfig);
   currentResolvedConfig = { ...nextConfig };
 
-  const shouldEnableRemote = nextConfig.enabled && Boolean(nextConfig.dsn);
+  // 未经用户同意不自动上传
+  const shouldEnableRemote =
+    nextConfig.enabled && nextConfig.uploadConsent && Boolean(nextConfig.dsn);
   if (!shouldEnableRemote) {
     if (sentryEnabled || Sentry.getClient()) {
       await disableSentryClient(reason);
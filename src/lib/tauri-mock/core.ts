@@ -823,6 +823,9 @@ const defaultMocks: Record<string, any> = {
       environment: "development",
       sample_rate: 1.0,
       send_pii: false,
+      upload_consent: false,
+      local_capture: true,
+      max_local_reports: 20,
     },
   }),
 
@@ -830,6 +833,28 @@ const defaultMocks: Record<string, any> = {
     console.log("[Mock] Config saved:", config);
     return { success: true };
   },
+  get_config_audit_log: () => [],
+  get_effective_config: () => ({}),
+  validate_config_patch: () => ({ valid: true, changes: [], lint: [] }),
+  lint_config: () => [],
+  apply_config_patch: () => [],
+  export_routing_preset: () => ({
+    content: "{}",
+    suggested_filename: "lime_preset.json",
+  }),
+  validate_routing_preset: () => ({
+    valid: true,
+    has_config: true,
+    errors: [],
+    warnings: [],
+    lint: [],
+  }),
+  import_routing_preset: (args: any) => ({
+    success: true,
+    config: args?.currentConfig ?? {},
+    warnings: [],
+    lint: [],
+  }),
 
   // Provider 相关
   get_providers: () => [],
@@ -966,17 +991,225 @@ const defaultMocks: Record<string, any> = {
     message: "Provider 配置已同步到浏览器 mock 环境。",
   }),
 
+  // 托盘快捷操作
+  toggle_provider_pause: () => true,
+  copy_api_credentials: () =>
+    "OPENAI_BASE_URL=http://127.0.0.1:8787/v1\nOPENAI_API_KEY=mock-api-key",
+  list_config_profiles: () => [],
+  save_config_profile: () => null,
+  delete_config_profile: () => false,
+  switch_config_profile: () => null,
+
+  // 应用事件协议
+  get_app_api_info: () => ({
+    command_api_version: 1,
+    event_api_version: 1,
+    event_channel: "lime://app-event",
+  }),
+  create_lan_pairing: () => ({
+    base_url: "http://192.168.1.2:8787/v1",
+    api_key: "pc_m_mock",
+    key_id: "mock-key",
+    expires_at: null,
+    pairing_uri: "lime://pair?base_url=http%3A%2F%2F192.168.1.2%3A8787%2Fv1&api_key=pc_m_mock",
+    qr_svg: "<svg xmlns=\"http://www.w3.org/2000/svg\"></svg>",
+  }),
+  preview_cli_preset: () => ({
+    tool: "claude_code",
+    files: [],
+    env: [],
+    manual_steps: [],
+  }),
+  apply_cli_preset: () => ({
+    tool: "claude_code",
+    files: [],
+    env: [],
+    manual_steps: [],
+    backup_id: null,
+  }),
+  rollback_cli_preset: () => [],
+  create_scoped_api_key: () => ({
+    record: {
+      id: "mock-key",
+      label: "demo",
+      key_hash: "",
+      key_prefix: "pc_m_mock",
+      created_at: new Date().toISOString(),
+      request_count: 0,
+    },
+    api_key: "pc_m_mock",
+  }),
+  list_scoped_api_keys: () => [],
+  list_banned_ips: () => [],
+  clear_banned_ips: () => 0,
+  get_app_role: () => "admin",
+  set_app_role: () => null,
+  set_app_role_pin: () => null,
+  run_db_maintenance: () => ({
+    integrity_ok: true,
+    issues: [],
+    checkpointed: true,
+    vacuumed: true,
+    size_before_bytes: 0,
+    size_after_bytes: 0,
+    duration_ms: 0,
+    ran_at: new Date().toISOString(),
+  }),
+  get_db_maintenance_status: () => ({ schema_version: 2, last_report: null }),
+  run_retention_now: () => ({
+    stores: [],
+    duration_ms: 0,
+    ran_at: new Date().toISOString(),
+  }),
+  run_cloud_backup_now: () => ({
+    name: "lime-backup-20260101T000000Z.lbk",
+    size_bytes: 0,
+    created_at: new Date().toISOString(),
+    removed: [],
+  }),
+  list_cloud_backups: () => [],
+  restore_cloud_backup: () => undefined,
+  get_setup_wizard_state: () => ({
+    step: "detect_credentials",
+    detected: [],
+    tested: [],
+  }),
+  reset_setup_wizard: () => undefined,
+  setup_detect_credentials: () => ({
+    step: "test_credentials",
+    detected: [],
+    tested: [],
+  }),
+  setup_test_credentials: () => ({
+    step: "generate_api_key",
+    detected: [],
+    tested: [],
+  }),
+  setup_generate_api_key: () => ({
+    step: "choose_port",
+    detected: [],
+    tested: [],
+    api_key: "pc_mockmockmockmockmockmockmockmock",
+  }),
+  setup_choose_port: () => ({
+    step: "write_config",
+    detected: [],
+    tested: [],
+    api_key: "pc_mockmockmockmockmockmockmockmock",
+    port: 8787,
+  }),
+  setup_write_config: () => ({
+    step: "start_server",
+    detected: [],
+    tested: [],
+    port: 8787,
+  }),
+  setup_start_server: () => ({
+    host: "127.0.0.1",
+    port: 8787,
+    base_url: "http://127.0.0.1:8787",
+  }),
+  run_connection_doctor: () => ({
+    generated_at: new Date().toISOString(),
+    app_version: "0.0.0",
+    os: "macos",
+    arch: "aarch64",
+    proxy_configured: false,
+    summary: { pass: 1, warn: 0, fail: 0, skipped: 0 },
+    checks: [
+      {
+        category: "port",
+        target: "127.0.0.1:8787",
+        status: "pass",
+        message: "端口可绑定",
+        duration_ms: 0,
+      },
+    ],
+  }),
+  export_connection_doctor_report: (args: any) => args?.path ?? "",
+  run_benchmark: (args: any) => ({
+    options: {
+      concurrency: 4,
+      total_requests: 20,
+      prompt_bytes: 256,
+      max_tokens: 64,
+      stream: false,
+      ...args?.options,
+    },
+    completed: 20,
+    succeeded: 20,
+    failed: 0,
+    status_counts: { "200": 20 },
+    duration_ms: 4000,
+    requests_per_sec: 5,
+    latency_ms: { min: 500, mean: 750, p50: 700, p90: 1000, p99: 1200, max: 1200 },
+    bytes_received: 0,
+    cancelled: false,
+  }),
+  cancel_benchmark: () => false,
+  list_test_scenarios: () => [],
+  run_test_scenarios: () => [],
+  get_endpoint_latency: () => [],
+  probe_endpoint_latency: () => [],
+  run_converter_golden_tests: () => ({
+    total: 0,
+    passed: 0,
+    failed: 0,
+    results: [],
+  }),
+  start_har_capture: () => ({
+    active: false,
+    started_at: null,
+    ends_at: null,
+    entries: 0,
+    max_entries: 500,
+  }),
+  stop_har_capture: () => ({
+    active: false,
+    started_at: null,
+    ends_at: null,
+    entries: 0,
+    max_entries: 500,
+  }),
+  get_har_capture_status: () => ({
+    active: false,
+    started_at: null,
+    ends_at: null,
+    entries: 0,
+    max_entries: 500,
+  }),
+  clear_har_capture: () => ({
+    active: false,
+    started_at: null,
+    ends_at: null,
+    entries: 0,
+    max_entries: 500,
+  }),
+  export_har_capture: (args: any) => args?.path ?? "",
+  get_maintenance_mode: () => ({
+    enabled: false,
+    message: "Service is under maintenance, please try again later",
+  }),
+  set_maintenance_mode: (args: any) => ({
+    enabled: Boolean(args?.enabled),
+    message:
+      args?.message || "Service is under maintenance, please try again later",
+  }),
+  revoke_scoped_api_key: () => false,
+
   // 服务器相关
   get_server_status: () => ({
     running: false,
     host: "127.0.0.1",
     port: 8787,
+    requested_port: 8787,
     requests: 0,
     uptime_secs: 0,
     error_rate_1m: 0,
     p95_latency_ms_1m: null,
     open_circuit_count: 0,
     active_requests: 0,
+    aborted_requests: 0,
     capability_routing: {
       filter_eval_total: 0,
       filter_excluded_total: 0,
@@ -1103,12 +1336,14 @@ const defaultMocks: Record<string, any> = {
     running: false,
     host: "127.0.0.1",
     port: 8787,
+    requested_port: 8787,
     requests: 0,
     uptime_secs: 0,
     error_rate_1m: 0,
     p95_latency_ms_1m: null,
     open_circuit_count: 0,
     active_requests: 0,
+    aborted_requests: 0,
     capability_routing: {
       filter_eval_total: 0,
       filter_excluded_total: 0,
@@ -2018,11 +2253,33 @@ const defaultMocks: Record<string, any> = {
   get_provider_pool_credentials: () => [],
   add_provider_pool_credential: () => ({ success: true }),
   update_provider_pool_credential: () => ({ success: true }),
+  clone_provider_pool_credential: () => ({ success: true }),
+  export_credential_pairing: () => ({
+    pairing_string: "lime-pair1:mock",
+    qr_svg: null,
+    credential_count: 0,
+  }),
+  import_credential_pairing: () => [],
   delete_provider_pool_credential: () => ({ success: true }),
+  get_deleted_provider_pool_credentials: () => [],
+  restore_provider_pool_credential: () => true,
+  purge_provider_pool_credential: () => true,
   toggle_provider_pool_credential: () => ({ success: true }),
   reset_provider_pool_credential: () => ({ success: true }),
   reset_provider_pool_health: () => ({ success: true }),
   check_provider_pool_credential_health: () => ({ healthy: false }),
+  pool_insights: () => ({
+    generated_at: new Date().toISOString(),
+    credentials: [],
+    recommendations: [],
+  }),
+  probe_provider_pool_credential_health: () => ({
+    credential_id: "mock",
+    model: "mock-model",
+    success: true,
+    latency_ms: 0,
+    checked_at: new Date().toISOString(),
+  }),
   check_provider_pool_type_health: () => ({ healthy: false }),
 
   // API Key Provider 相关
@@ -2393,6 +2650,17 @@ const defaultMocks: Record<string, any> = {
   get_request_log_detail: () => ({ log: null }),
   clear_request_logs: () => ({ success: true }),
   report_frontend_crash: () => ({ success: true }),
+  list_crash_reports: () => [],
+  export_crash_report: () => ({
+    export_path: "/tmp/lime-crash-report.json",
+    report_count: 1,
+  }),
+  clear_crash_reports: () => 0,
+  get_anonymous_stats_preview: () => null,
+  upload_anonymous_stats: () => {
+    throw new Error("匿名使用统计未启用");
+  },
+  clear_anonymous_stats: () => undefined,
   get_stats_summary: () => ({ summary: {} }),
   get_stats_by_provider: () => ({ stats: [] }),
   get_stats_by_model: () => ({ stats: [] }),
@@ -2400,6 +2668,7 @@ const defaultMocks: Record<string, any> = {
   get_token_stats_by_provider: () => ({ stats: [] }),
   get_token_stats_by_model: () => ({ stats: [] }),
   get_token_stats_by_day: () => ({ stats: [] }),
+  simulate_credential_pool: () => [],
 
   // Routes 相关
   get_available_routes: () => ({ routes: [] }),
@@ -2590,6 +2859,13 @@ const defaultMocks: Record<string, any> = {
     results: [],
     warnings: [],
   }),
+  test_provider_capabilities: (args: any) => ({
+    provider: args?.provider ?? "",
+    model: args?.model ?? "",
+    results: [],
+    registry_updated: false,
+    tested_at: new Date().toISOString(),
+  }),
 
   // Endpoint Providers 相关
   get_endpoint_providers: () => ({}),
@@ -2643,7 +2919,10 @@ const defaultMocks: Record<string, any> = {
     last_check_timestamp: 0,
     skipped_version: null,
     remind_later_until: null,
+    channel: "stable",
+    auto_download: false,
   }),
+  get_last_update_result: () => null,
   get_update_notification_metrics: () => ({
     shown_count: 0,
     update_now_count: 0,
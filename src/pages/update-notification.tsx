@@ -4,7 +4,7 @@
  *
  * 独立于主应用的更新提醒悬浮窗口，采用轻量 toast 形态展示更新操作。
  *
- * input: URL 参数（current, latest, download_url）
+ * input: URL 参数（current, latest, download_url）与最近一次检查结果（发布说明、通道、后台下载状态）
  * output: 更新提醒 UI
  * pos: pages 层，独立 Tauri 窗口
  */
@@ -16,9 +16,11 @@ import {
   closeUpdateWindow,
   dismissUpdateNotification,
   downloadUpdate,
+  getLastUpdateResult,
   recordUpdateNotificationAction,
   remindUpdateLater,
   skipUpdateVersion,
+  type VersionInfo,
 } from "@/lib/api/appUpdate";
 import { Bell, Download, ExternalLink, SkipForward, X } from "lucide-react";
 import "./update-notification.css";
@@ -44,12 +46,16 @@ export function UpdateNotificationPage() {
     latestVersion: "",
     downloadUrl: "",
   });
+  const [lastResult, setLastResult] = useState<VersionInfo | null>(null);
   const [downloading, setDownloading] = useState(false);
   const [visible, setVisible] = useState(false);
   const [closing, setClosing] = useState(false);
 
   useEffect(() => {
     setParams(getUpdateParamsFromUrl());
+    getLastUpdateResult()
+      .then(setLastResult)
+      .catch((error) => console.error("读取更新检查结果失败:", error));
     const timer = window.setTimeout(() => setVisible(true), 10);
     return () => window.clearTimeout(timer);
   }, []);
@@ -180,8 +186,12 @@ export function UpdateNotificationPage() {
         </div>
 
         <div className="update-toast-main">
-          <div className="update-toast-message">
-            发现新版本 {params.latestVersion || ""}
+          <div
+            className="update-toast-message"
+            title={lastResult?.releaseNotes || undefined}
+          >
+            发现{lastResult?.channel === "beta" ? " Beta " : ""}新版本{" "}
+            {params.latestVersion || ""}
             {params.currentVersion ? (
               <span className="update-toast-sub">
                 （当前 {params.currentVersion}）
@@ -234,7 +244,11 @@ export function UpdateNotificationPage() {
                 size={14}
                 className={downloading ? "animate-spin" : ""}
               />
-              {downloading ? "下载中" : "立即更新"}
+              {downloading
+                ? "下载中"
+                : lastResult?.readyToInstall
+                  ? "立即安装"
+                  : "立即更新"}
             </button>
             {params.downloadUrl ? (
               <button